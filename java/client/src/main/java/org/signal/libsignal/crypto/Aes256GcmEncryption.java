@@ -7,6 +7,7 @@ package org.signal.libsignal.crypto;
 
 import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
 
+import java.io.IOException;
 import org.signal.libsignal.internal.Native;
 import org.signal.libsignal.internal.NativeHandleGuard;
 import org.signal.libsignal.protocol.InvalidKeyException;
@@ -22,6 +23,28 @@ public class Aes256GcmEncryption implements NativeHandleGuard.Owner {
             () -> Native.Aes256GcmEncryption_New(key, nonce, associatedData));
   }
 
+  /**
+   * Starts encrypting with the key and next nonce from {@code nonces}.
+   *
+   * <p>The nonce must be sent along with the ciphertext; see {@link #getNonce()}.
+   *
+   * @throws IOException if {@code nonceStore} could not record a new reservation. No nonce is used
+   *     up in this case.
+   * @throws IllegalStateException if {@code nonces} has reached its limit, and the key must be
+   *     replaced.
+   */
+  public Aes256GcmEncryption(NonceSequence nonces, NonceStore nonceStore, byte[] associatedData)
+      throws IOException {
+    try (NativeHandleGuard guard = new NativeHandleGuard(nonces)) {
+      this.unsafeHandle =
+          filterExceptions(
+              IOException.class,
+              () ->
+                  Native.Aes256GcmEncryption_NewWithNonceSequence(
+                      guard.nativeHandle(), nonceStore, associatedData));
+    }
+  }
+
   @Override
   @SuppressWarnings("deprecation")
   protected void finalize() {
@@ -44,6 +67,13 @@ public class Aes256GcmEncryption implements NativeHandleGuard.Owner {
     }
   }
 
+  /** Returns the nonce this encryption uses. */
+  public byte[] getNonce() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return Native.Aes256GcmEncryption_GetNonce(guard.nativeHandle());
+    }
+  }
+
   public byte[] computeTag() {
     try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
       byte[] tag = Native.Aes256GcmEncryption_ComputeTag(guard.nativeHandle());
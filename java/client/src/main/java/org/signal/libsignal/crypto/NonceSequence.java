@@ -0,0 +1,68 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.crypto;
+
+import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
+
+import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.internal.NativeHandleGuard;
+import org.signal.libsignal.protocol.InvalidKeyException;
+
+/**
+ * Hands out unique AES-GCM nonces for a single long-lived key.
+ *
+ * <p>Each nonce is a fixed prefix followed by a 64-bit counter. The sequence keeps its key, and is
+ * only used through {@link Aes256GcmEncryption#Aes256GcmEncryption(NonceSequence, NonceStore,
+ * byte[])}, so its nonces are never used with any other key. Progress is recorded through a {@link
+ * NonceStore} before nonces are handed out; after a restart, create a new sequence with the last
+ * limit that was recorded.
+ *
+ * <p>A sequence may be shared between threads.
+ */
+public class NonceSequence extends NativeHandleGuard.SimpleOwner {
+  /** The most nonces that will ever be produced for one key, and the default limit. */
+  public static final long MAX_LIMIT = 1L << 32;
+
+  /** The length of the fixed prefix of each nonce. */
+  public static final int PREFIX_LENGTH = 4;
+
+  /**
+   * Resumes the sequence for {@code key}.
+   *
+   * @param key a 32-byte AES-256 key.
+   * @param prefix the fixed start of each nonce; keys shared by more than one sender need a
+   *     distinct prefix per sender.
+   * @param persistedLimit the last limit recorded by the {@link NonceStore}, or 0 for a new key.
+   */
+  public NonceSequence(byte[] key, byte[] prefix, long persistedLimit)
+      throws InvalidKeyException {
+    this(key, prefix, persistedLimit, MAX_LIMIT);
+  }
+
+  /**
+   * Like {@link #NonceSequence(byte[], byte[], long)}, but with a lower limit on the number of
+   * nonces used with this key.
+   *
+   * <p>Limits above {@link #MAX_LIMIT} are treated as {@code MAX_LIMIT}.
+   */
+  public NonceSequence(byte[] key, byte[] prefix, long persistedLimit, long limit)
+      throws InvalidKeyException {
+    super(
+        filterExceptions(
+            InvalidKeyException.class,
+            () -> Native.NonceSequence_New(key, prefix, persistedLimit, limit)));
+  }
+
+  /** Returns how many more nonces can be produced before the limit is reached. */
+  public long remaining() {
+    return guardedMap(Native::NonceSequence_Remaining);
+  }
+
+  @Override
+  protected void release(final long nativeHandle) {
+    Native.NonceSequence_Destroy(nativeHandle);
+  }
+}
@@ -0,0 +1,143 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.devicetransfer;
+
+import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
+
+import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.internal.NativeHandleGuard;
+import org.signal.libsignal.protocol.InvalidMessageException;
+
+/**
+ * One side of a device-to-device transfer, independent of how bytes are sent between the devices.
+ *
+ * <p>The transfer goes through the following steps:
+ *
+ * <ul>
+ *   <li>Both sides exchange a hello message and their certificates (see {@link
+ *       DeviceTransferKey#generateCertificate}), each committing to its certificate before either
+ *       is revealed.
+ *   <li>Both sides display the {@link #verificationCode}. Once the user has checked that they
+ *       match, each side calls {@link #confirmVerificationCode}.
+ *   <li>The old device sends the payload with {@link #sendPayloadChunk}, then calls {@link
+ *       #finish}.
+ * </ul>
+ *
+ * Whenever bytes arrive from the other device, pass them to {@link #receive}, then check {@link
+ * #state} to see whether the transfer has progressed. After each operation, send the bytes from
+ * {@link #takeOutgoing} to the other device.
+ */
+public class DeviceTransferSession extends NativeHandleGuard.SimpleOwner {
+  /** The progress of a session through the transfer. */
+  public enum State {
+    AWAITING_HELLO,
+    AWAITING_CERTIFICATE,
+    VERIFYING,
+    TRANSFERRING,
+    COMPLETED,
+    FAILED,
+  }
+
+  private DeviceTransferSession(long nativeHandle) {
+    super(nativeHandle);
+  }
+
+  /** Starts a session on the old device, which will send {@code payloadLength} bytes. */
+  public static DeviceTransferSession forOldDevice(byte[] localCertificate, long payloadLength) {
+    return new DeviceTransferSession(
+        filterExceptions(
+            () -> Native.DeviceTransferSession_ForOldDevice(localCertificate, payloadLength)));
+  }
+
+  /** Starts a session on the new device. */
+  public static DeviceTransferSession forNewDevice(byte[] localCertificate) {
+    return new DeviceTransferSession(
+        filterExceptions(() -> Native.DeviceTransferSession_ForNewDevice(localCertificate)));
+  }
+
+  @Override
+  protected void release(long nativeHandle) {
+    Native.DeviceTransferSession_Destroy(nativeHandle);
+  }
+
+  /**
+   * Processes bytes received from the other device.
+   *
+   * @return any payload bytes that were received
+   * @throws InvalidMessageException if the other device sent something invalid, after which the
+   *     session is in the {@link State#FAILED} state
+   */
+  public byte[] receive(byte[] data) throws InvalidMessageException {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return filterExceptions(
+          InvalidMessageException.class,
+          () -> Native.DeviceTransferSession_Receive(guard.nativeHandle(), data));
+    }
+  }
+
+  /** Takes all bytes waiting to be sent to the other device. */
+  public byte[] takeOutgoing() {
+    return guardedMap(Native::DeviceTransferSession_TakeOutgoing);
+  }
+
+  public State state() {
+    return State.values()[guardedMap(Native::DeviceTransferSession_GetState)];
+  }
+
+  /**
+   * The code the user should compare between the two devices.
+   *
+   * @throws IllegalStateException if the certificates haven't been exchanged yet
+   */
+  public String verificationCode() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return filterExceptions(
+          () -> Native.DeviceTransferSession_GetVerificationCode(guard.nativeHandle()));
+    }
+  }
+
+  /** Records that the user confirmed the verification codes match. */
+  public void confirmVerificationCode() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      filterExceptions(
+          () -> Native.DeviceTransferSession_ConfirmVerificationCode(guard.nativeHandle()));
+    }
+  }
+
+  /**
+   * Sends the next piece of the payload (only on the old device, once both sides have confirmed).
+   */
+  public void sendPayloadChunk(byte[] chunk) {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      filterExceptions(
+          () -> Native.DeviceTransferSession_SendPayloadChunk(guard.nativeHandle(), chunk));
+    }
+  }
+
+  /** Marks the transfer as complete, once the whole payload has been sent (old device only). */
+  public void finish() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      filterExceptions(() -> Native.DeviceTransferSession_Finish(guard.nativeHandle()));
+    }
+  }
+
+  /** The number of payload bytes sent or received so far. */
+  public long bytesTransferred() {
+    return guardedMap(Native::DeviceTransferSession_GetBytesTransferred);
+  }
+
+  /**
+   * The total length of the payload.
+   *
+   * @throws IllegalStateException on the new device, if the old device's hello hasn't arrived yet
+   */
+  public long payloadLength() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return filterExceptions(
+          () -> Native.DeviceTransferSession_GetPayloadLength(guard.nativeHandle()));
+    }
+  }
+}
@@ -96,4 +96,60 @@ public class HsmEnclaveClient implements NativeHandleGuard.Owner {
           () -> Native.HsmEnclaveClient_EstablishedRecv(guard.nativeHandle(), receivedCiphertext));
     }
   }
+
+  /**
+   * Allows up to {@code maxInFlight} requests to be sent before their responses are received.
+   *
+   * <p>Without this, the connection doesn't track requests at all. Once pipelining is enabled,
+   * several requests can be sent with {@link #establishedSend} before any responses arrive.
+   * Responses must be passed to {@link #establishedRecv} in the order they're received, and will
+   * correspond to requests in the order they were sent; use {@link #lastSentRequestId} and {@link
+   * #lastReceivedRequestId} to match them up. Sending more than {@code maxInFlight} requests
+   * without receiving responses throws {@link IllegalStateException}.
+   *
+   * <p>Only valid once the handshake has completed, and should be called before the first request
+   * is sent. Calling it again changes the limit.
+   */
+  public void enablePipelining(int maxInFlight) {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      filterExceptions(
+          () -> Native.HsmEnclaveClient_EnablePipelining(guard.nativeHandle(), maxInFlight));
+    }
+  }
+
+  /**
+   * The id of the request most recently encrypted by {@link #establishedSend}.
+   *
+   * <p>Only valid once pipelining has been enabled and a request has been sent.
+   */
+  public long lastSentRequestId() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return filterExceptions(
+          () -> Native.HsmEnclaveClient_LastSentRequestId(guard.nativeHandle()));
+    }
+  }
+
+  /**
+   * The id of the request answered by the response most recently decrypted by {@link
+   * #establishedRecv}.
+   *
+   * <p>Only valid once pipelining has been enabled and a response has been received.
+   */
+  public long lastReceivedRequestId() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return filterExceptions(
+          () -> Native.HsmEnclaveClient_LastReceivedRequestId(guard.nativeHandle()));
+    }
+  }
+
+  /**
+   * The number of requests sent whose responses haven't been received yet.
+   *
+   * <p>Only valid once pipelining has been enabled.
+   */
+  public int inFlight() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return filterExceptions(() -> Native.HsmEnclaveClient_InFlight(guard.nativeHandle()));
+    }
+  }
 }
@@ -0,0 +1,30 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.media;
+
+/**
+ * The codecs a client is able to play back, beyond H.264 video and AAC audio.
+ *
+ * <p>When set on {@link Mp4SanitizerOptions}, audio and video tracks using any other codec cause
+ * sanitizing to fail with a {@link ParseException}. Tracks of other kinds, such as timed metadata,
+ * are not checked.
+ */
+public class Mp4CodecPolicy {
+  /** Allow HEVC (H.265) video. */
+  public final boolean allowHevc;
+
+  /** Allow AV1 video. */
+  public final boolean allowAv1;
+
+  /** Allow Opus audio. */
+  public final boolean allowOpus;
+
+  public Mp4CodecPolicy(boolean allowHevc, boolean allowAv1, boolean allowOpus) {
+    this.allowHevc = allowHevc;
+    this.allowAv1 = allowAv1;
+    this.allowOpus = allowOpus;
+  }
+}
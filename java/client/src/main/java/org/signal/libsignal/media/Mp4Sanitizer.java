@@ -9,7 +9,10 @@ import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
 
 import java.io.IOException;
 import java.io.InputStream;
+import org.signal.libsignal.internal.AsyncInputStream;
+import org.signal.libsignal.internal.CompletableFuture;
 import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.net.TokioAsyncContext;
 
 /**
  * An MP4 format “sanitizer”.
@@ -64,6 +67,65 @@ public class Mp4Sanitizer {
             IOException.class,
             ParseException.class,
             () -> Native.Mp4Sanitizer_Sanitize(TrustedSkipInputStream.makeTrusted(input), length));
+    return takeSanitizedMetadata(sanitizedMetadataHandle);
+  }
+
+  /**
+   * Sanitize an MP4 input read from a stream that completes asynchronously.
+   *
+   * <p>The returned future completes exceptionally with an {@link IOException} if an IO error on
+   * the input occurs, or a {@link ParseException} if the input could not be parsed.
+   *
+   * @param input An MP4 format input stream.
+   * @param length The exact length of the input stream.
+   * @return The sanitized metadata.
+   * @see #sanitize(InputStream, long)
+   */
+  public static CompletableFuture<SanitizedMetadata> sanitizeAsync(
+      AsyncInputStream input, long length) {
+    return TokioAsyncContext.shared()
+        .guardedMap(asyncRuntime -> Native.Mp4Sanitizer_SanitizeAsync(asyncRuntime, input, length))
+        .thenApply(Mp4Sanitizer::takeSanitizedMetadata);
+  }
+
+  /**
+   * Sanitize an MP4 input, using {@code options} to decide which metadata boxes to keep.
+   *
+   * @param input An MP4 format input stream.
+   * @param length The exact length of the input stream.
+   * @param options Which metadata boxes to preserve and codecs to accept.
+   * @return The sanitized metadata.
+   * @throws IOException If an IO error on the input occurs.
+   * @throws ParseException If the input could not be parsed.
+   * @see #sanitize(InputStream, long)
+   */
+  public static SanitizedMetadata sanitize(
+      InputStream input, long length, Mp4SanitizerOptions options)
+      throws IOException, ParseException {
+    long sanitizedMetadataHandle =
+        filterExceptions(
+            IOException.class,
+            ParseException.class,
+            () ->
+                options.guardedMapChecked(
+                    optionsHandle ->
+                        Native.Mp4Sanitizer_SanitizeWithOptions(
+                            TrustedSkipInputStream.makeTrusted(input), length, optionsHandle)));
+    return takeSanitizedMetadata(sanitizedMetadataHandle);
+  }
+
+  /**
+   * Sets how many bytes of metadata buffers the sanitizer may keep for reuse between inputs.
+   *
+   * <p>Sanitizing a large video can need hundreds of megabytes of buffers. Keeping them around
+   * avoids allocating them again for the next input, at the cost of holding on to that memory while
+   * no sanitizer is running. The default is 0, which keeps nothing.
+   */
+  public static void setMaxPooledBufferBytes(long maxBytes) {
+    Native.Mp4Sanitizer_SetMaxPooledBufferBytes(maxBytes);
+  }
+
+  private static SanitizedMetadata takeSanitizedMetadata(long sanitizedMetadataHandle) {
     try {
       byte[] sanitizedMetadata = Native.SanitizedMetadata_GetMetadata(sanitizedMetadataHandle);
       if (sanitizedMetadata.length == 0) {
@@ -0,0 +1,78 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.media;
+
+import java.nio.charset.StandardCharsets;
+import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.internal.NativeHandleGuard;
+
+/**
+ * Options for {@link Mp4Sanitizer#sanitize(java.io.InputStream, long, Mp4SanitizerOptions)}.
+ *
+ * <p>By default, metadata boxes are left as {@link Mp4Sanitizer#sanitize(java.io.InputStream,
+ * long)} leaves them. Once stripping is turned on with {@link #setMetadataStripped(boolean)} or
+ * {@link #setMetadataBoxPreserved(String, boolean)}, boxes needed for playback are still kept, but
+ * other metadata boxes are replaced with padding of the same size unless they are listed as
+ * preserved. Color information, pixel aspect ratio, HDR, and spherical video metadata start out
+ * preserved.
+ *
+ * <p>By default, codecs are not checked; see {@link #setCodecPolicy(Mp4CodecPolicy)}.
+ */
+public class Mp4SanitizerOptions extends NativeHandleGuard.SimpleOwner {
+  public Mp4SanitizerOptions() {
+    super(Native.Mp4SanitizerOptions_New());
+  }
+
+  /**
+   * Turns stripping of unlisted metadata boxes on or off.
+   *
+   * @param stripped Whether to replace metadata boxes that aren't listed as preserved.
+   * @return this object, for chaining.
+   */
+  public Mp4SanitizerOptions setMetadataStripped(boolean stripped) {
+    guardedRun(
+        nativeHandle -> Native.Mp4SanitizerOptions_SetMetadataStripped(nativeHandle, stripped));
+    return this;
+  }
+
+  /**
+   * Sets whether a metadata box should be kept, turning on stripping of unlisted metadata boxes.
+   *
+   * @param boxType The four-character code of the box, e.g. {@code "colr"}.
+   * @param preserved Whether to keep boxes of this type.
+   * @return this object, for chaining.
+   */
+  public Mp4SanitizerOptions setMetadataBoxPreserved(String boxType, boolean preserved) {
+    byte[] boxTypeBytes = boxType.getBytes(StandardCharsets.ISO_8859_1);
+    if (boxTypeBytes.length != 4) {
+      throw new IllegalArgumentException("box types are four characters long");
+    }
+    guardedRun(
+        nativeHandle ->
+            Native.Mp4SanitizerOptions_SetMetadataBoxPreserved(
+                nativeHandle, boxTypeBytes, preserved));
+    return this;
+  }
+
+  /**
+   * Restricts the codecs accepted in audio and video tracks.
+   *
+   * @param policy The codecs this client can play back.
+   * @return this object, for chaining.
+   */
+  public Mp4SanitizerOptions setCodecPolicy(Mp4CodecPolicy policy) {
+    guardedRun(
+        nativeHandle ->
+            Native.Mp4SanitizerOptions_SetCodecPolicy(
+                nativeHandle, policy.allowHevc, policy.allowAv1, policy.allowOpus));
+    return this;
+  }
+
+  @Override
+  protected void release(final long nativeHandle) {
+    Native.Mp4SanitizerOptions_Destroy(nativeHandle);
+  }
+}
@@ -0,0 +1,60 @@
+//
+// Copyright 2026 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.messagebackup;
+
+import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.internal.NativeHandleGuard;
+
+/**
+ * How compressed backup contents are padded before encryption, to hide the exact size of a backup.
+ *
+ * @see MessageBackup#getPaddedSize
+ */
+public class BackupPaddingPolicy extends NativeHandleGuard.SimpleOwner {
+  /** The policy used for backups produced by Signal clients. */
+  public BackupPaddingPolicy() {
+    super(Native.BackupPaddingPolicy_Default());
+  }
+
+  /**
+   * Pads contents up to the smallest bucket that fits them.
+   *
+   * <p>Bucket sizes are integer powers of {@code growthFactor} (rounded to the nearest hundredth),
+   * but never less than {@code minSize} bytes. A {@code growthFactor} of 1 or less disables
+   * bucketing, leaving only the minimum size.
+   */
+  public BackupPaddingPolicy(double growthFactor, long minSize) {
+    super(Native.BackupPaddingPolicy_New((int) Math.round(growthFactor * 100), minSize));
+  }
+
+  @Override
+  protected void release(long nativeHandle) {
+    Native.BackupPaddingPolicy_Destroy(nativeHandle);
+  }
+
+  /**
+   * Computes the size compressed backup contents will be padded to before encryption.
+   *
+   * @param compressedLength the length of the gzipped backup contents
+   * @return the length after padding with this policy
+   */
+  public long getPaddedSize(long compressedLength) {
+    return guardedMap(
+        nativeHandle -> Native.BackupPaddingPolicy_GetPaddedSize(nativeHandle, compressedLength));
+  }
+
+  /**
+   * Computes the size of the encrypted backup file that will be uploaded.
+   *
+   * @param compressedLength the length of the gzipped backup contents
+   * @return the length of the final encrypted backup file, after padding with this policy
+   */
+  public long getEncryptedSize(long compressedLength) {
+    return guardedMap(
+        nativeHandle ->
+            Native.BackupPaddingPolicy_GetEncryptedSize(nativeHandle, compressedLength));
+  }
+}
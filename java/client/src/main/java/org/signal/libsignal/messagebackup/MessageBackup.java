@@ -9,10 +9,16 @@ import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
 
 import java.io.IOException;
 import java.io.InputStream;
+import java.time.Duration;
+import java.util.Arrays;
+import java.util.concurrent.TimeoutException;
 import java.util.function.Supplier;
+import org.signal.libsignal.internal.AsyncInputStream;
+import org.signal.libsignal.internal.CompletableFuture;
+import org.signal.libsignal.internal.Deadline;
 import org.signal.libsignal.internal.Native;
 import org.signal.libsignal.internal.NativeHandleGuard;
-import org.signal.libsignal.protocol.util.Pair;
+import org.signal.libsignal.net.TokioAsyncContext;
 
 /** Message-backup-related functionality. */
 public class MessageBackup {
@@ -24,11 +30,19 @@ public class MessageBackup {
    */
   public static class ValidationResult {
     public ValidationResult(String[] unknownFieldMessages) {
+      this(unknownFieldMessages, new ValidationFinding[0]);
+    }
+
+    public ValidationResult(String[] unknownFieldMessages, ValidationFinding[] findings) {
       this.unknownFieldMessages = unknownFieldMessages;
+      this.findings = findings;
     }
 
     /** Information about unknown fields encountered while validating. */
     public String[] unknownFieldMessages;
+
+    /** Structured versions of {@link #unknownFieldMessages}. */
+    public ValidationFinding[] findings;
   }
 
   public static enum Purpose {
@@ -47,37 +61,187 @@ public class MessageBackup {
    * @param streamFactory a factory for <code>InputStream</code>s that produce the input
    * @param streamLength the number of bytes each <code>InputStream</code> will produce
    * @return informational result about the successful validation
-   * @throws ValidationError with an error message if the input is invalid
+   * @throws ValidationError with an error message and findings if the input is invalid
    * @throws IOException if the input could not be read
    */
   public static ValidationResult validate(
       MessageBackupKey key, Purpose purpose, Supplier<InputStream> streamFactory, long streamLength)
       throws ValidationError, IOException {
+    try {
+      return validate(key, purpose, streamFactory, streamLength, Duration.ZERO);
+    } catch (TimeoutException e) {
+      throw new AssertionError("no timeout was set", e);
+    }
+  }
+
+  /**
+   * Validates an encrypted message backup bundle, giving up if it takes longer than {@code
+   * timeout}.
+   *
+   * <p>The timeout is checked each time more input is needed, so validation may run slightly past
+   * it before stopping. A zero timeout means validation is never abandoned.
+   *
+   * @param key the key to use to decrypt the backup
+   * @param purpose whether the input was created for device-to-device transfer or remote backup
+   * @param streamFactory a factory for <code>InputStream</code>s that produce the input
+   * @param streamLength the number of bytes each <code>InputStream</code> will produce
+   * @param timeout how long validation may run before it is abandoned
+   * @return informational result about the successful validation
+   * @throws ValidationError with an error message and findings if the input is invalid
+   * @throws IOException if the input could not be read
+   * @throws TimeoutException if the timeout passed before validation completed
+   */
+  public static ValidationResult validate(
+      MessageBackupKey key,
+      Purpose purpose,
+      Supplier<InputStream> streamFactory,
+      long streamLength,
+      Duration timeout)
+      throws ValidationError, IOException, TimeoutException {
+    long timeoutMillis = Deadline.timeoutMillis(timeout);
     InputStream first = streamFactory.get();
     InputStream second = streamFactory.get();
 
-    Pair<String, String[]> result;
+    ValidationFinding[] findings;
     try (NativeHandleGuard keyGuard = new NativeHandleGuard(key)) {
 
       Object output =
           filterExceptions(
               IOException.class,
               ValidationError.class,
+              TimeoutException.class,
               () ->
                   Native.MessageBackupValidator_Validate(
-                      keyGuard.nativeHandle(), first, second, streamLength, purpose.ordinal()));
+                      keyGuard.nativeHandle(),
+                      first,
+                      second,
+                      streamLength,
+                      purpose.ordinal(),
+                      timeoutMillis));
 
       // Rust conversion code is generating an instance of this class.
-      @SuppressWarnings("unchecked")
-      Pair<String, String[]> outputPair = (Pair<String, String[]>) output;
-      result = outputPair;
+      findings = (ValidationFinding[]) output;
     }
 
-    String errorMessage = result.first();
-    if (errorMessage != null) {
-      throw new ValidationError(errorMessage, result.second());
+    return resultFromFindings(findings);
+  }
+
+  /**
+   * Validates an encrypted message backup bundle read from streams that complete asynchronously.
+   *
+   * <p>Behaves like {@link #validate}, but no thread is blocked waiting for the input to be read.
+   * The returned future completes exceptionally with a {@link ValidationError} if the input is
+   * invalid, or an {@link IOException} if the input could not be read.
+   *
+   * @param key the key to use to decrypt the backup
+   * @param purpose whether the input was created for device-to-device transfer or remote backup
+   * @param streamFactory a factory for streams that produce the input
+   * @param streamLength the number of bytes each stream will produce
+   * @return informational result about the successful validation
+   */
+  public static CompletableFuture<ValidationResult> validateAsync(
+      MessageBackupKey key,
+      Purpose purpose,
+      Supplier<AsyncInputStream> streamFactory,
+      long streamLength) {
+    return validateAsync(key, purpose, streamFactory, streamLength, Duration.ZERO);
+  }
+
+  /**
+   * Validates an encrypted message backup bundle read from streams that complete asynchronously,
+   * giving up if it takes longer than {@code timeout}.
+   *
+   * <p>Behaves like {@link #validateAsync(MessageBackupKey, Purpose, Supplier, long)}, except that
+   * the returned future also completes exceptionally with a {@link TimeoutException} once the
+   * timeout passes, even if a read from the input is still outstanding. A zero timeout means
+   * validation is never abandoned.
+   *
+   * @param key the key to use to decrypt the backup
+   * @param purpose whether the input was created for device-to-device transfer or remote backup
+   * @param streamFactory a factory for streams that produce the input
+   * @param streamLength the number of bytes each stream will produce
+   * @param timeout how long validation may run before it is abandoned
+   * @return informational result about the successful validation
+   */
+  public static CompletableFuture<ValidationResult> validateAsync(
+      MessageBackupKey key,
+      Purpose purpose,
+      Supplier<AsyncInputStream> streamFactory,
+      long streamLength,
+      Duration timeout) {
+    long timeoutMillis = Deadline.timeoutMillis(timeout);
+    AsyncInputStream first = streamFactory.get();
+    AsyncInputStream second = streamFactory.get();
+
+    // The key is used until validation finishes, so the guard is only closed then.
+    NativeHandleGuard keyGuard = new NativeHandleGuard(key);
+    CompletableFuture<Object> output =
+        TokioAsyncContext.shared()
+            .guardedMap(
+                asyncRuntime ->
+                    Native.MessageBackupValidator_ValidateAsync(
+                        asyncRuntime,
+                        keyGuard.nativeHandle(),
+                        first,
+                        second,
+                        streamLength,
+                        purpose.ordinal(),
+                        timeoutMillis));
+
+    return output
+        .whenComplete((ignoredResult, ignoredError) -> keyGuard.close())
+        .thenCompose(
+            findings -> {
+              CompletableFuture<ValidationResult> result = new CompletableFuture<>();
+              try {
+                result.complete(resultFromFindings((ValidationFinding[]) findings));
+              } catch (ValidationError e) {
+                result.completeExceptionally(e);
+              }
+              return result;
+            });
+  }
+
+  private static ValidationResult resultFromFindings(ValidationFinding[] findings)
+      throws ValidationError {
+    // Any fatal finding is listed first.
+    ValidationFinding fatal =
+        findings.length > 0 && findings[0].severity == ValidationFinding.Severity.FATAL
+            ? findings[0]
+            : null;
+    String[] unknownFieldMessages =
+        Arrays.stream(findings)
+            .filter(f -> f.kind == ValidationFinding.Kind.UNKNOWN_FIELD)
+            .map(f -> f.message)
+            .toArray(String[]::new);
+
+    if (fatal != null) {
+      throw new ValidationError(fatal.message, unknownFieldMessages, findings);
     }
 
-    return new ValidationResult(result.second());
+    return new ValidationResult(unknownFieldMessages, findings);
+  }
+
+  /**
+   * Computes the size compressed backup contents will be padded to before encryption.
+   *
+   * @param compressedLength the length of the gzipped backup contents
+   * @return the length after padding with the standard bucketing policy
+   */
+  public static long getPaddedSize(long compressedLength) {
+    return Native.MessageBackup_GetPaddedSize(compressedLength);
+  }
+
+  /**
+   * Computes the size of the encrypted backup file that will be uploaded.
+   *
+   * <p>This accounts for padding, encryption, and the trailing HMAC, and so can be used to predict
+   * storage quota usage before producing the backup.
+   *
+   * @param compressedLength the length of the gzipped backup contents
+   * @return the length of the final encrypted backup file
+   */
+  public static long getEncryptedSize(long compressedLength) {
+    return Native.MessageBackup_GetEncryptedSize(compressedLength);
   }
 }
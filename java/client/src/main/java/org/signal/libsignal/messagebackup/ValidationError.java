@@ -14,8 +14,16 @@ public class ValidationError extends Exception {
   /** Contains messages about unknown fields found while parsing. */
   public String[] unknownFieldMessages;
 
+  /** Structured versions of the error and {@link #unknownFieldMessages}, in that order. */
+  public ValidationFinding[] findings;
+
   ValidationError(String message, String[] unknownFields) {
+    this(message, unknownFields, new ValidationFinding[0]);
+  }
+
+  ValidationError(String message, String[] unknownFields, ValidationFinding[] findings) {
     super(message);
     this.unknownFieldMessages = unknownFields;
+    this.findings = findings;
   }
 }
@@ -0,0 +1,59 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.messagebackup;
+
+import org.signal.libsignal.internal.CalledFromNative;
+
+/** A single machine-readable problem found while validating a message backup bundle. */
+public class ValidationFinding {
+  public static enum Kind {
+    // This needs to be kept in sync with the corresponding Rust enum.
+    UNKNOWN_FIELD,
+    INVALID_FRAME,
+    INCOMPLETE_BACKUP,
+    MALFORMED_FRAME,
+    NO_FRAMES,
+    HMAC_MISMATCH,
+    IO,
+    LIMIT_EXCEEDED,
+  }
+
+  public static enum Severity {
+    // This needs to be kept in sync with the corresponding Rust enum.
+    /** The backup can still be imported. */
+    IGNORABLE,
+    /** The backup cannot be imported. */
+    FATAL,
+  }
+
+  /**
+   * The frame the finding applies to, where frame 0 is the backup header, or -1 if the finding
+   * doesn't apply to a single frame.
+   */
+  public final long frameIndex;
+
+  /** Dotted path to the offending field, or the empty string if unknown. */
+  public final String fieldPath;
+
+  public final Kind kind;
+  public final Severity severity;
+
+  /** A developer-facing description of the finding. */
+  public final String message;
+
+  @CalledFromNative
+  ValidationFinding(long frameIndex, String fieldPath, int kind, int severity, String message) {
+    this.frameIndex = frameIndex;
+    this.fieldPath = fieldPath;
+    this.kind = Kind.values()[kind];
+    this.severity = Severity.values()[severity];
+    this.message = message;
+  }
+
+  public String toString() {
+    return kind + " (" + severity + ") in frame " + frameIndex + ": " + message;
+  }
+}
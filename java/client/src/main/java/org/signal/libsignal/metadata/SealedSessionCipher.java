@@ -18,6 +18,7 @@ import org.signal.libsignal.metadata.certificate.CertificateValidator;
 import org.signal.libsignal.metadata.certificate.SenderCertificate;
 import org.signal.libsignal.metadata.protocol.UnidentifiedSenderMessageContent;
 import org.signal.libsignal.protocol.DuplicateMessageException;
+import org.signal.libsignal.protocol.IdentityKey;
 import org.signal.libsignal.protocol.InvalidKeyException;
 import org.signal.libsignal.protocol.InvalidKeyIdException;
 import org.signal.libsignal.protocol.InvalidMessageException;
@@ -29,10 +30,12 @@ import org.signal.libsignal.protocol.ServiceId;
 import org.signal.libsignal.protocol.SessionCipher;
 import org.signal.libsignal.protocol.SignalProtocolAddress;
 import org.signal.libsignal.protocol.UntrustedIdentityException;
+import org.signal.libsignal.protocol.ecc.ECPublicKey;
 import org.signal.libsignal.protocol.groups.GroupCipher;
 import org.signal.libsignal.protocol.message.CiphertextMessage;
 import org.signal.libsignal.protocol.message.PreKeySignalMessage;
 import org.signal.libsignal.protocol.message.SignalMessage;
+import org.signal.libsignal.protocol.state.ReplayCache;
 import org.signal.libsignal.protocol.state.SessionRecord;
 import org.signal.libsignal.protocol.state.SignalProtocolStore;
 
@@ -182,6 +185,34 @@ public class SealedSessionCipher {
     }
   }
 
+  /**
+   * Returns the exact length of the message {@link #multiRecipientEncrypt} would produce.
+   *
+   * <p>This lets a client check a message against the server's size limit before encrypting it.
+   * The result is capped at {@link Integer#MAX_VALUE}.
+   *
+   * @param usmcLength the length of the serialized {@link UnidentifiedSenderMessageContent}
+   * @param recipientCount the number of distinct recipients, assuming each recipient's devices are
+   *     listed together
+   * @param deviceCount the total number of destination devices across all recipients
+   * @param excludedRecipientCount the number of excluded recipients
+   */
+  public static int multiRecipientMessageLength(
+      int usmcLength, int recipientCount, int deviceCount, int excludedRecipientCount) {
+    return Native.SealedSessionCipher_MultiRecipientMessageLength(
+        usmcLength, recipientCount, deviceCount, excludedRecipientCount);
+  }
+
+  /**
+   * Returns the length of each recipient's copy of a multi-recipient message, once the server has
+   * split it up.
+   *
+   * @param usmcLength the length of the serialized {@link UnidentifiedSenderMessageContent}
+   */
+  public static int multiRecipientReceivedMessageLength(int usmcLength) {
+    return Native.SealedSessionCipher_MultiRecipientReceivedMessageLength(usmcLength);
+  }
+
   // For testing only.
   static byte[] multiRecipientMessageForSingleRecipient(byte[] message) {
     return filterExceptions(
@@ -200,6 +231,48 @@ public class SealedSessionCipher {
           ProtocolInvalidKeyIdException,
           ProtocolUntrustedIdentityException,
           SelfSendException {
+    return decrypt(validator, ciphertext, timestamp, Optional.empty());
+  }
+
+  /**
+   * Like {@link #decrypt(CertificateValidator, byte[], long)}, but rejects inner messages already
+   * recorded in {@code replayCache} with {@link ProtocolDuplicateMessageException}.
+   *
+   * <p>See {@link SessionCipher#decrypt(SignalMessage, ReplayCache)} for how the cache is used.
+   * Sender key messages are not checked against the cache.
+   */
+  public DecryptionResult decrypt(
+      CertificateValidator validator, byte[] ciphertext, long timestamp, ReplayCache replayCache)
+      throws InvalidMetadataMessageException,
+          InvalidMetadataVersionException,
+          ProtocolInvalidMessageException,
+          ProtocolInvalidKeyException,
+          ProtocolNoSessionException,
+          ProtocolLegacyMessageException,
+          ProtocolInvalidVersionException,
+          ProtocolDuplicateMessageException,
+          ProtocolInvalidKeyIdException,
+          ProtocolUntrustedIdentityException,
+          SelfSendException {
+    return decrypt(validator, ciphertext, timestamp, Optional.of(replayCache));
+  }
+
+  private DecryptionResult decrypt(
+      CertificateValidator validator,
+      byte[] ciphertext,
+      long timestamp,
+      Optional<ReplayCache> replayCache)
+      throws InvalidMetadataMessageException,
+          InvalidMetadataVersionException,
+          ProtocolInvalidMessageException,
+          ProtocolInvalidKeyException,
+          ProtocolNoSessionException,
+          ProtocolLegacyMessageException,
+          ProtocolInvalidVersionException,
+          ProtocolDuplicateMessageException,
+          ProtocolInvalidKeyIdException,
+          ProtocolUntrustedIdentityException,
+          SelfSendException {
     UnidentifiedSenderMessageContent content;
     try {
       content =
@@ -227,7 +300,7 @@ public class SealedSessionCipher {
           content.getSenderCertificate().getSenderDeviceId(),
           content.getType(),
           content.getGroupId(),
-          decrypt(content));
+          decrypt(content, replayCache));
     } catch (InvalidMessageException e) {
       throw new ProtocolInvalidMessageException(e, content);
     } catch (InvalidKeyException e) {
@@ -247,6 +320,60 @@ public class SealedSessionCipher {
     }
   }
 
+  /**
+   * Like {@link #decrypt(CertificateValidator, byte[], long)}, but decrypts messages from a sender
+   * whose identity key has changed instead of rejecting them.
+   *
+   * <p>The change is reported by {@link IdentityChangeDecryptionResult#getIdentityChange} and is
+   * <em>not</em> saved to the identity store; if the app accepts the new key, it should save it
+   * itself. Senders with no identity on record, or with an unchanged identity, are handled as
+   * usual.
+   *
+   * <p>Only 1:1 messages are supported; sender key and plaintext messages are rejected with {@link
+   * InvalidMessageException}, as are messages whose sender certificate doesn't validate.
+   */
+  public IdentityChangeDecryptionResult decryptCapturingIdentityChange(
+      CertificateValidator validator, byte[] ciphertext, long timestamp)
+      throws InvalidMessageException,
+          InvalidVersionException,
+          LegacyMessageException,
+          InvalidKeyException,
+          InvalidKeyIdException,
+          NoSessionException,
+          DuplicateMessageException,
+          SelfSendException {
+    try (NativeHandleGuard trustRootGuard = new NativeHandleGuard(validator.getTrustRoot())) {
+      long resultHandle =
+          filterExceptions(
+              InvalidMessageException.class,
+              InvalidVersionException.class,
+              LegacyMessageException.class,
+              InvalidKeyException.class,
+              InvalidKeyIdException.class,
+              NoSessionException.class,
+              DuplicateMessageException.class,
+              SelfSendException.class,
+              () ->
+                  Native.SealedSender_DecryptMessageCapturingIdentityChange(
+                      ciphertext,
+                      trustRootGuard.nativeHandle(),
+                      timestamp,
+                      this.localE164Address,
+                      this.localUuidAddress,
+                      this.localDeviceId,
+                      this.signalProtocolStore,
+                      this.signalProtocolStore,
+                      this.signalProtocolStore,
+                      this.signalProtocolStore,
+                      this.signalProtocolStore));
+      try {
+        return new IdentityChangeDecryptionResult(resultHandle);
+      } finally {
+        Native.SealedSenderDecryptionResult_Destroy(resultHandle);
+      }
+    }
+  }
+
   public int getSessionVersion(SignalProtocolAddress remoteAddress) {
     return new SessionCipher(signalProtocolStore, remoteAddress).getSessionVersion();
   }
@@ -255,7 +382,8 @@ public class SealedSessionCipher {
     return new SessionCipher(signalProtocolStore, remoteAddress).getRemoteRegistrationId();
   }
 
-  private byte[] decrypt(UnidentifiedSenderMessageContent message)
+  private byte[] decrypt(
+      UnidentifiedSenderMessageContent message, Optional<ReplayCache> replayCache)
       throws InvalidVersionException,
           InvalidMessageException,
           InvalidKeyException,
@@ -271,11 +399,23 @@ public class SealedSessionCipher {
 
     switch (message.getType()) {
       case CiphertextMessage.WHISPER_TYPE:
-        return new SessionCipher(signalProtocolStore, sender)
-            .decrypt(new SignalMessage(message.getContent()));
+        {
+          SessionCipher cipher = new SessionCipher(signalProtocolStore, sender);
+          SignalMessage signalMessage = new SignalMessage(message.getContent());
+          if (replayCache.isPresent()) {
+            return cipher.decrypt(signalMessage, replayCache.get());
+          }
+          return cipher.decrypt(signalMessage);
+        }
       case CiphertextMessage.PREKEY_TYPE:
-        return new SessionCipher(signalProtocolStore, sender)
-            .decrypt(new PreKeySignalMessage(message.getContent()));
+        {
+          SessionCipher cipher = new SessionCipher(signalProtocolStore, sender);
+          PreKeySignalMessage preKeyMessage = new PreKeySignalMessage(message.getContent());
+          if (replayCache.isPresent()) {
+            return cipher.decrypt(preKeyMessage, replayCache.get());
+          }
+          return cipher.decrypt(preKeyMessage);
+        }
       case CiphertextMessage.SENDERKEY_TYPE:
         return new GroupCipher(signalProtocolStore, sender).decrypt(message.getContent());
       case CiphertextMessage.PLAINTEXT_CONTENT_TYPE:
@@ -348,4 +488,89 @@ public class SealedSessionCipher {
       return groupId;
     }
   }
+
+  /** The result of {@link #decryptCapturingIdentityChange}. */
+  public static class IdentityChangeDecryptionResult {
+    private final String senderUuid;
+    private final Optional<String> senderE164;
+    private final int deviceId;
+    private final byte[] paddedMessage;
+    private final Optional<IdentityChange> identityChange;
+
+    private IdentityChangeDecryptionResult(long nativeHandle) {
+      this.senderUuid =
+          filterExceptions(() -> Native.SealedSenderDecryptionResult_GetSenderUuid(nativeHandle));
+      this.senderE164 =
+          Optional.ofNullable(
+              filterExceptions(
+                  () -> Native.SealedSenderDecryptionResult_GetSenderE164(nativeHandle)));
+      this.deviceId =
+          filterExceptions(() -> Native.SealedSenderDecryptionResult_GetDeviceId(nativeHandle));
+      this.paddedMessage =
+          filterExceptions(() -> Native.SealedSenderDecryptionResult_Message(nativeHandle));
+
+      long previous = Native.SealedSenderDecryptionResult_GetPreviousIdentityKey(nativeHandle);
+      long current = Native.SealedSenderDecryptionResult_GetCurrentIdentityKey(nativeHandle);
+      if (previous != 0 && current != 0) {
+        this.identityChange =
+            Optional.of(
+                new IdentityChange(
+                    new IdentityKey(new ECPublicKey(previous)),
+                    new IdentityKey(new ECPublicKey(current))));
+      } else {
+        this.identityChange = Optional.empty();
+      }
+    }
+
+    public String getSenderUuid() {
+      return senderUuid;
+    }
+
+    /** Returns an ACI if the sender is a valid UUID, {@code null} otherwise. */
+    public ServiceId.Aci getSenderAci() {
+      try {
+        return ServiceId.Aci.parseFromString(getSenderUuid());
+      } catch (ServiceId.InvalidServiceIdException e) {
+        return null;
+      }
+    }
+
+    public Optional<String> getSenderE164() {
+      return senderE164;
+    }
+
+    public int getDeviceId() {
+      return deviceId;
+    }
+
+    public byte[] getPaddedMessage() {
+      return paddedMessage;
+    }
+
+    /** Returns the sender's old and new identity keys, if the sender's identity changed. */
+    public Optional<IdentityChange> getIdentityChange() {
+      return identityChange;
+    }
+  }
+
+  /** The sender of a message used a different identity key than the one on record for them. */
+  public static class IdentityChange {
+    private final IdentityKey previous;
+    private final IdentityKey current;
+
+    private IdentityChange(IdentityKey previous, IdentityKey current) {
+      this.previous = previous;
+      this.current = current;
+    }
+
+    /** The identity key that was in the identity store. */
+    public IdentityKey getPrevious() {
+      return previous;
+    }
+
+    /** The identity key the message was decrypted with. */
+    public IdentityKey getCurrent() {
+      return current;
+    }
+  }
 }
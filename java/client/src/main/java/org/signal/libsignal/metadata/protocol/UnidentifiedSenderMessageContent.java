@@ -103,4 +103,24 @@ public class UnidentifiedSenderMessageContent implements NativeHandleGuard.Owner
               () -> Native.UnidentifiedSenderMessageContent_GetGroupId(guard.nativeHandle())));
     }
   }
+
+  /**
+   * Returns a copy of this message with a different content hint and group ID.
+   *
+   * <p>The sender certificate and the encrypted contents are kept as they are, so a message can be
+   * resent with different handling without rebuilding it from a {@link CiphertextMessage}.
+   *
+   * @param contentHint one of the {@code CONTENT_HINT_*} constants
+   * @param groupId the group the message was sent to, if any
+   */
+  public UnidentifiedSenderMessageContent withContentHintAndGroupId(
+      int contentHint, Optional<byte[]> groupId) {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return new UnidentifiedSenderMessageContent(
+          filterExceptions(
+              () ->
+                  Native.UnidentifiedSenderMessageContent_WithContentHintAndGroupId(
+                      guard.nativeHandle(), contentHint, groupId.orElse(null))));
+    }
+  }
 }
@@ -5,8 +5,19 @@
 
 package org.signal.libsignal.net;
 
+import static org.signal.libsignal.zkgroup.internal.Constants.RANDOM_LENGTH;
+
+import java.security.SecureRandom;
+import java.time.Instant;
 import org.signal.libsignal.internal.CompletableFuture;
 import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.metadata.certificate.SenderCertificate;
+import org.signal.libsignal.protocol.ServiceId;
+import org.signal.libsignal.protocol.ServiceId.Aci;
+import org.signal.libsignal.zkgroup.InvalidInputException;
+import org.signal.libsignal.zkgroup.ServerPublicParams;
+import org.signal.libsignal.zkgroup.profiles.ExpiringProfileKeyCredential;
+import org.signal.libsignal.zkgroup.profiles.ProfileKey;
 
 /**
  * Represents an authenticated communication channel with the ChatService.
@@ -29,6 +40,149 @@ public class AuthenticatedChatService extends ChatService {
                 connectionManagerHandle, username, password, receiveStories));
   }
 
+  /**
+   * Fetches a new sender certificate for sealed-sender sends.
+   *
+   * <p>Use {@link SenderCertificate#needsRefresh} to decide when a cached certificate should be
+   * replaced. The returned certificate has not been validated against a trust root.
+   *
+   * @param includeE164 whether the certificate should include this account's phone number
+   * @param timeoutMillis how long to wait for the server to respond
+   * @return a future that fails with a {@link ChatServiceException} (inside an {@link
+   *     java.util.concurrent.ExecutionException ExecutionException}) if the request fails or the
+   *     server's response can't be parsed.
+   */
+  public CompletableFuture<SenderCertificate> fetchSenderCertificate(
+      final boolean includeE164, final int timeoutMillis) {
+    return tokioAsyncContext.guardedMap(
+        asyncContextHandle ->
+            guardedMap(
+                chatServiceHandle ->
+                    Native.ChatService_auth_fetch_sender_certificate(
+                            asyncContextHandle, chatServiceHandle, includeE164, timeoutMillis)
+                        .thenApply(SenderCertificate::new)));
+  }
+
+  /**
+   * Fetches an expiring profile key credential for {@code aci}'s {@code profileKey}.
+   *
+   * <p>This makes the request and immediately checks and unblinds the server's response, the way
+   * {@code ClientZkProfileOperations.receiveExpiringProfileKeyCredential} would, so the result is
+   * ready to store.
+   *
+   * @param serverPublicParams the public params of the server that issues the credential
+   * @param aci the account whose profile key credential is being requested
+   * @param profileKey the account's current profile key
+   * @param timeoutMillis how long to wait for the server to respond
+   * @return a future that fails with a {@link NotFoundException} if the profile key is out of
+   *     date, a {@link RequestUnauthorizedException} if the server refuses to issue the credential,
+   *     or another {@link ChatServiceException} if the request fails or the server's response
+   *     can't be parsed or verified (all inside an {@link java.util.concurrent.ExecutionException
+   *     ExecutionException}).
+   */
+  public CompletableFuture<ExpiringProfileKeyCredential> fetchProfileKeyCredential(
+      final ServerPublicParams serverPublicParams,
+      final Aci aci,
+      final ProfileKey profileKey,
+      final int timeoutMillis) {
+    byte[] random = new byte[RANDOM_LENGTH];
+    new SecureRandom().nextBytes(random);
+    final long now = Instant.now().getEpochSecond();
+
+    return tokioAsyncContext.guardedMap(
+        asyncContextHandle ->
+            guardedMap(
+                chatServiceHandle ->
+                    serverPublicParams.guardedMap(
+                        serverPublicParamsHandle ->
+                            Native.ChatService_auth_fetch_profile_key_credential(
+                                    asyncContextHandle,
+                                    chatServiceHandle,
+                                    serverPublicParamsHandle,
+                                    random,
+                                    aci.toServiceIdFixedWidthBinary(),
+                                    profileKey.getInternalContentsForJNI(),
+                                    now,
+                                    timeoutMillis)
+                                .thenApply(
+                                    serialized -> {
+                                      try {
+                                        return new ExpiringProfileKeyCredential(serialized);
+                                      } catch (InvalidInputException e) {
+                                        throw new AssertionError(e);
+                                      }
+                                    }))));
+  }
+
+  /**
+   * Searches the key transparency log for {@code searchKey}.
+   *
+   * <p>{@code cache} is checked first; on a miss the server's response is verified and added to
+   * the cache before being returned.
+   *
+   * @param cache verified results to reuse, shared between searches
+   * @param signatureKey the log's signature key
+   * @param vrfKey the log's VRF key
+   * @param auditorKey the key of the log's third-party auditor
+   * @param searchKey the key to look up
+   * @param timeoutMillis how long to wait for the server to respond
+   * @return a future for the value {@code searchKey} maps to, which fails with a {@link
+   *     ChatServiceException} if the request fails or the server's response can't be parsed or
+   *     verified (inside an {@link java.util.concurrent.ExecutionException ExecutionException}).
+   */
+  public CompletableFuture<byte[]> searchKeyTransparency(
+      final KeyTransparencySearchCache cache,
+      final byte[] signatureKey,
+      final byte[] vrfKey,
+      final byte[] auditorKey,
+      final byte[] searchKey,
+      final int timeoutMillis) {
+    return tokioAsyncContext.guardedMap(
+        asyncContextHandle ->
+            guardedMap(
+                chatServiceHandle ->
+                    cache.guardedMap(
+                        cacheHandle ->
+                            Native.ChatService_auth_search_key_transparency(
+                                asyncContextHandle,
+                                chatServiceHandle,
+                                cacheHandle,
+                                signatureKey,
+                                vrfKey,
+                                auditorKey,
+                                searchKey,
+                                timeoutMillis))));
+  }
+
+  /**
+   * Queues a receipt for the message from {@code sender} sent at {@code messageTimestamp}.
+   *
+   * <p>Queued receipts are grouped by sender and kind into as few receipt messages as possible;
+   * take them with {@link #nextReceiptBatch}. The receipt messages themselves may be sent over
+   * either chat connection.
+   */
+  public void queueReceipt(
+      final ServiceId sender, final ReceiptBatch.Kind kind, final long messageTimestamp) {
+    guardedRun(
+        chatServiceHandle ->
+            Native.ChatService_auth_queue_receipt(
+                chatServiceHandle,
+                sender.toServiceIdFixedWidthBinary(),
+                kind.value,
+                messageTimestamp));
+  }
+
+  /**
+   * Takes the next batch of queued receipts, or returns {@code null} if there are none.
+   *
+   * <p>Each batch should be sent as one receipt message. Batches not yet taken stay queued, and can
+   * still absorb new receipts.
+   */
+  public ReceiptBatch nextReceiptBatch() {
+    final long batchHandle = guardedMap(Native::ChatService_auth_next_receipt_batch);
+    return batchHandle != 0 ? new ReceiptBatch(batchHandle) : null;
+  }
+
   // Implementing these abstract methods from ChatService allows UnauthenticatedChatService
   //   to get the implementation of its main functionality (connect, send, etc.)
   //   using the shared implementations of those methods in ChatService.
@@ -64,6 +218,13 @@ public class AuthenticatedChatService extends ChatService {
         nativeAsyncContextHandle, nativeChatServiceHandle, nativeRequestHandle, timeoutMillis);
   }
 
+  @Override
+  protected CompletableFuture<Object> prepareForBackgroundWrapper(
+      long nativeAsyncContextHandle, long nativeChatServiceHandle, int budgetMillis) {
+    return Native.ChatService_prepare_for_background_auth(
+        nativeAsyncContextHandle, nativeChatServiceHandle, budgetMillis);
+  }
+
   @Override
   protected void release(long nativeChatServiceHandle) {
     Native.AuthChat_Destroy(nativeChatServiceHandle);
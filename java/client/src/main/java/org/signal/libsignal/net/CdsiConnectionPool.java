@@ -0,0 +1,47 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.net;
+
+import java.time.Duration;
+import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.internal.NativeHandleGuard;
+
+/**
+ * Keeps attested CDSI connections ready for upcoming lookups.
+ *
+ * <p>Every CDSI lookup needs its own connection, including a fresh attestation handshake. When a
+ * pool is passed to {@link Network#cdsiLookup(String, String, CdsiLookupRequest,
+ * java.util.function.Consumer, CdsiConnectionPool, boolean)}, the connection for the next lookup
+ * can be established while the current one is still running, which speeds up a burst of lookups
+ * such as a contact sync.
+ *
+ * <p>Pooled connections are only reused with the credentials they were established with; using
+ * the pool with new credentials drops any connections made with the old ones. A connection that
+ * has been idle for longer than the pool's idle timeout is discarded rather than reused.
+ */
+public class CdsiConnectionPool extends NativeHandleGuard.SimpleOwner {
+  /** The idle timeout used by {@link #CdsiConnectionPool()}. */
+  public static final Duration DEFAULT_IDLE_TIMEOUT = Duration.ofSeconds(10);
+
+  public CdsiConnectionPool() {
+    this(DEFAULT_IDLE_TIMEOUT);
+  }
+
+  /**
+   * Creates a pool whose connections are discarded after being idle for {@code idleTimeout}.
+   *
+   * <p>The timeout should be short enough that the server won't have closed an idle connection by
+   * then.
+   */
+  public CdsiConnectionPool(Duration idleTimeout) {
+    super(Native.CdsiConnectionPool_new(Math.toIntExact(idleTimeout.toMillis())));
+  }
+
+  @Override
+  protected void release(final long nativeHandle) {
+    Native.CdsiConnectionPool_Destroy(nativeHandle);
+  }
+}
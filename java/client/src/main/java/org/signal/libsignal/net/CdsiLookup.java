@@ -31,6 +31,32 @@ class CdsiLookup implements NativeHandleGuard.Owner {
     }
   }
 
+  public static CompletableFuture<CdsiLookup> startPooled(
+      Network network,
+      String username,
+      String password,
+      CdsiLookupRequest request,
+      CdsiConnectionPool pool,
+      boolean prepareNext) {
+
+    CdsiLookupRequest.NativeRequest nativeRequest = request.makeNative();
+    try (NativeHandleGuard asyncRuntime = new NativeHandleGuard(network.getAsyncContext());
+        NativeHandleGuard connectionManager =
+            new NativeHandleGuard(network.getConnectionManager());
+        NativeHandleGuard poolGuard = new NativeHandleGuard(pool)) {
+
+      return Native.CdsiLookup_newPooled(
+              asyncRuntime.nativeHandle(),
+              connectionManager.nativeHandle(),
+              username,
+              password,
+              nativeRequest.getHandle(),
+              poolGuard.nativeHandle(),
+              prepareNext)
+          .thenApply((Long nativeHandle) -> new CdsiLookup(nativeHandle, network));
+    }
+  }
+
   public CompletableFuture<CdsiLookupResponse> complete() {
     try (NativeHandleGuard asyncRuntime = new NativeHandleGuard(this.network.getAsyncContext());
         NativeHandleGuard self = new NativeHandleGuard(this)) {
@@ -5,9 +5,16 @@
 
 package org.signal.libsignal.net;
 
+import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
+
+import java.nio.ByteBuffer;
+import java.util.ArrayList;
+import java.util.List;
 import java.util.Map;
 import java.util.Objects;
+import java.util.UUID;
 import org.signal.libsignal.internal.CalledFromNative;
+import org.signal.libsignal.internal.Native;
 import org.signal.libsignal.protocol.ServiceId;
 
 public class CdsiLookupResponse {
@@ -44,6 +51,53 @@ public class CdsiLookupResponse {
     public final ServiceId.Pni pni;
   }
 
+  /** A difference between a client's contact mapping and a lookup response. */
+  public static class ContactChange {
+    public enum Kind {
+      /** The number resolved to an ACI the client did not have on record. */
+      NEW_ACI,
+      /** The number's PNI is different from (or newly added to) the one on record. */
+      CHANGED_PNI,
+      /** The number was looked up but is no longer registered. */
+      REMOVED,
+    }
+
+    public ContactChange(Kind kind, String e164, ServiceId.Aci aci, ServiceId.Pni pni) {
+      this.kind = kind;
+      this.e164 = e164;
+      this.aci = aci;
+      this.pni = pni;
+    }
+
+    public String toString() {
+      return "{kind: " + kind + ", e164: " + e164 + ", aci: " + aci + ", pni: " + pni + "}";
+    }
+
+    public boolean equals(Object obj) {
+      if (obj instanceof ContactChange) {
+        ContactChange other = (ContactChange) obj;
+        return this.kind == other.kind
+            && Objects.equals(this.e164, other.e164)
+            && Objects.equals(this.aci, other.aci)
+            && Objects.equals(this.pni, other.pni);
+      }
+      return false;
+    }
+
+    public int hashCode() {
+      return Objects.hash(this.kind, this.e164, this.aci, this.pni);
+    }
+
+    public final Kind kind;
+    public final String e164;
+
+    /** The new ACI, for {@link Kind#NEW_ACI}; otherwise null. */
+    public final ServiceId.Aci aci;
+
+    /** The new PNI, for {@link Kind#CHANGED_PNI}; otherwise null. */
+    public final ServiceId.Pni pni;
+  }
+
   @CalledFromNative
   CdsiLookupResponse(Map<String, Entry> entries, int debugPermitsUsed) {
     this.entries = entries;
@@ -54,6 +108,59 @@ public class CdsiLookupResponse {
     return this.entries;
   }
 
+  /**
+   * Compares the lookup results with the client's existing mapping from phone numbers to service
+   * IDs.
+   *
+   * <p>A number that appears in the response without an ACI does not clear an ACI that's already
+   * on record, since the server only returns ACIs for which the client proved knowledge of an
+   * access key. Every number in {@code existing} is expected to have been part of the request;
+   * any that are missing from the response, or that came back with neither an ACI nor a PNI, are
+   * reported as {@link ContactChange.Kind#REMOVED}.
+   */
+  public List<ContactChange> reconcile(Map<String, Entry> existing) {
+    long changes =
+        filterExceptions(
+            () -> Native.LookupResponse_reconcile(packEntries(existing), packEntries(entries)));
+    try {
+      ContactChange.Kind[] kinds = ContactChange.Kind.values();
+      int count = Native.CdsiContactChanges_Count(changes);
+      List<ContactChange> result = new ArrayList<>(count);
+      for (int i = 0; i < count; i++) {
+        String aci = Native.CdsiContactChanges_GetAci(changes, i);
+        String pni = Native.CdsiContactChanges_GetPni(changes, i);
+        result.add(
+            new ContactChange(
+                kinds[Native.CdsiContactChanges_GetKind(changes, i)],
+                Native.CdsiContactChanges_GetE164(changes, i),
+                aci != null ? ServiceId.Aci.parseFromString(aci) : null,
+                pni != null ? ServiceId.Pni.parseFromString(pni) : null));
+      }
+      return result;
+    } catch (ServiceId.InvalidServiceIdException e) {
+      throw new AssertionError(e);
+    } finally {
+      Native.CdsiContactChanges_Destroy(changes);
+    }
+  }
+
+  /** Packs entries as e164/PNI/ACI triples, the format the CDSI server uses. */
+  private static byte[] packEntries(Map<String, Entry> entries) {
+    ByteBuffer buffer = ByteBuffer.allocate(entries.size() * PACKED_ENTRY_LEN);
+    for (Map.Entry<String, Entry> entry : entries.entrySet()) {
+      buffer.putLong(Long.parseLong(entry.getKey().replaceFirst("^\\+", "")));
+      putUuid(buffer, entry.getValue().pni);
+      putUuid(buffer, entry.getValue().aci);
+    }
+    return buffer.array();
+  }
+
+  private static void putUuid(ByteBuffer buffer, ServiceId serviceId) {
+    UUID uuid = serviceId != null ? serviceId.getRawUUID() : NIL_UUID;
+    buffer.putLong(uuid.getMostSignificantBits());
+    buffer.putLong(uuid.getLeastSignificantBits());
+  }
+
   public String toString() {
     return "{entries: " + entries + ", debugPermitsUsed: " + debugPermitsUsed + "}";
   }
@@ -71,6 +178,9 @@ public class CdsiLookupResponse {
     return Objects.hash(this.entries, this.debugPermitsUsed);
   }
 
+  private static final int PACKED_ENTRY_LEN = 8 + 16 + 16;
+  private static final UUID NIL_UUID = new UUID(0, 0);
+
   private final Map<String, Entry> entries;
   public final int debugPermitsUsed;
 }
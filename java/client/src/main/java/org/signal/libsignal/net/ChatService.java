@@ -16,7 +16,7 @@ import org.signal.libsignal.internal.NativeHandleGuard;
 
 /** Represents an API of communication with the Chat Service. */
 public abstract class ChatService extends NativeHandleGuard.SimpleOwner {
-  private final TokioAsyncContext tokioAsyncContext;
+  final TokioAsyncContext tokioAsyncContext;
 
   ChatService(
       final TokioAsyncContext tokioAsyncContext,
@@ -117,6 +117,25 @@ public abstract class ChatService extends NativeHandleGuard.SimpleOwner {
     }
   }
 
+  /**
+   * Finishes outstanding work and then disconnects, for when the app is about to be suspended.
+   *
+   * <p>Pending acks for received messages and in-flight requests are given as much of {@code
+   * budgetMillis} as possible to complete, leaving a little time to close the connection cleanly.
+   * New requests will fail until {@link #connect()} is called again.
+   *
+   * @param budgetMillis how much background execution time the app has left
+   * @return a future with a report of which work was and wasn't flushed
+   */
+  public CompletableFuture<BackgroundFlushReport> prepareForBackground(final int budgetMillis) {
+    return tokioAsyncContext.guardedMap(
+        asyncContextHandle ->
+            guardedMap(
+                chatServiceHandle ->
+                    prepareForBackgroundWrapper(asyncContextHandle, chatServiceHandle, budgetMillis)
+                        .thenApply(o -> (BackgroundFlushReport) o)));
+  }
+
   // These are meant to be thin wrappers around the correct call to Native.ChatService_* calls
   //   for each of the concrete implementing classes.
   protected abstract CompletableFuture disconnectWrapper(
@@ -137,6 +156,9 @@ public abstract class ChatService extends NativeHandleGuard.SimpleOwner {
       long nativeRequestHandle,
       int timeoutMillis);
 
+  protected abstract CompletableFuture<Object> prepareForBackgroundWrapper(
+      long nativeAsyncContextHandle, long nativeChatServiceHandle, int budgetMillis);
+
   static InternalRequest buildInternalRequest(final Request req) throws MalformedURLException {
     final InternalRequest result =
         new InternalRequest(req.method(), req.pathAndQuery(), req.body());
@@ -180,4 +202,12 @@ public abstract class ChatService extends NativeHandleGuard.SimpleOwner {
   }
 
   public record ResponseAndDebugInfo(Response response, DebugInfo debugInfo) {}
+
+  /**
+   * What happened to outgoing work when preparing to go into the background.
+   *
+   * <p>Unflushed acks mean the server will deliver the corresponding messages again.
+   */
+  public record BackgroundFlushReport(
+      int acksFlushed, int acksUnflushed, int requestsFlushed, int requestsUnflushed) {}
 }
@@ -9,7 +9,19 @@ import java.io.IOException;
 
 /** Error thrown by Chat Service API. */
 public class ChatServiceException extends IOException {
+  /**
+   * What the server said when it ended or refused the connection, or {@code null} if it said
+   * nothing.
+   */
+  public final DisconnectInfo disconnectInfo;
+
   public ChatServiceException(String message) {
     super(message);
+    this.disconnectInfo = null;
+  }
+
+  public ChatServiceException(String message, int closeCode, String reason, String[] alerts) {
+    super(message);
+    this.disconnectInfo = new DisconnectInfo(closeCode, reason, alerts);
   }
 }
@@ -10,4 +10,9 @@ public class ChatServiceInactiveException extends ChatServiceException {
   public ChatServiceInactiveException(String message) {
     super(message);
   }
+
+  public ChatServiceInactiveException(
+      String message, int closeCode, String reason, String[] alerts) {
+    super(message, closeCode, reason, alerts);
+  }
 }
@@ -0,0 +1,36 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.net;
+
+import java.util.Arrays;
+import java.util.Collections;
+import java.util.List;
+
+/**
+ * What the server said when it closed (or refused) a chat connection.
+ *
+ * <p>Lets an app tell "the server asked me to go away" apart from a plain network failure.
+ */
+public class DisconnectInfo {
+  /** The websocket close code, or {@code null} if the server didn't send a close frame. */
+  public final Integer closeCode;
+
+  /**
+   * The reason given in the close frame, if any.
+   *
+   * <p>Suitable for logging, but not for display to users.
+   */
+  public final String reason;
+
+  /** The values of any {@code X-Signal-Alert} headers on the server's response. */
+  public final List<String> alerts;
+
+  DisconnectInfo(int closeCode, String reason, String[] alerts) {
+    this.closeCode = closeCode < 0 ? null : closeCode;
+    this.reason = reason;
+    this.alerts = Collections.unmodifiableList(Arrays.asList(alerts));
+  }
+}
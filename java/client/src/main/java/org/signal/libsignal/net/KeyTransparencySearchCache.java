@@ -0,0 +1,42 @@
+//
+// Copyright 2026 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.net;
+
+import java.time.Duration;
+import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.internal.NativeHandleGuard;
+
+/**
+ * Verified key transparency search results, reused instead of searching the network again.
+ *
+ * <p>Passed to {@link AuthenticatedChatService#searchKeyTransparency}, which checks the cache
+ * before sending anything. A result is only reused while the tree hasn't grown past the latest
+ * tree head verified through the cache, and until it expires.
+ */
+public class KeyTransparencySearchCache extends NativeHandleGuard.SimpleOwner {
+  /** The expiry used by {@link #KeyTransparencySearchCache()}. */
+  public static final Duration DEFAULT_TTL = Duration.ofMinutes(5);
+
+  /** The capacity used by {@link #KeyTransparencySearchCache()}. */
+  public static final int DEFAULT_MAX_ENTRIES = 1000;
+
+  public KeyTransparencySearchCache() {
+    this(DEFAULT_TTL, DEFAULT_MAX_ENTRIES);
+  }
+
+  /**
+   * Creates a cache that keeps results for {@code ttl}, for at most {@code maxEntries} search
+   * keys.
+   */
+  public KeyTransparencySearchCache(Duration ttl, int maxEntries) {
+    super(Native.KeyTransparencySearchCache_New(Math.toIntExact(ttl.getSeconds()), maxEntries));
+  }
+
+  @Override
+  protected void release(final long nativeHandle) {
+    Native.KeyTransparencySearchCache_Destroy(nativeHandle);
+  }
+}
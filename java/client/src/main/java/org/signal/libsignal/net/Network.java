@@ -8,8 +8,13 @@ package org.signal.libsignal.net;
 import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
 
 import java.io.IOException;
+import java.time.Duration;
+import java.time.Instant;
+import java.util.ArrayList;
+import java.util.List;
 import java.util.concurrent.ExecutionException;
 import java.util.function.Consumer;
+import org.jetbrains.annotations.Nullable;
 import org.signal.libsignal.internal.CompletableFuture;
 import org.signal.libsignal.internal.Native;
 import org.signal.libsignal.internal.NativeHandleGuard;
@@ -26,6 +31,39 @@ public class Network {
     }
   }
 
+  /** The services whose connection behavior can be configured separately. */
+  public enum Service {
+    // This needs to be kept in sync with the Rust version of the enum.
+    CHAT(0),
+    CDSI(1),
+    SVR3(2);
+
+    private final int value;
+
+    Service(int value) {
+      this.value = value;
+    }
+  }
+
+  /**
+   * The kinds of route that can be tried when connecting with censorship circumvention enabled.
+   *
+   * <p>A proxy set with {@link #setProxy} isn't listed here, because it is used for whichever route
+   * is being tried.
+   */
+  public enum Route {
+    // This needs to be kept in sync with the Rust ConnectionRoute enum.
+    DIRECT(0),
+    PROXY_F(1),
+    PROXY_G(2);
+
+    private final int value;
+
+    Route(int value) {
+      this.value = value;
+    }
+  }
+
   private final TokioAsyncContext tokioAsyncContext;
 
   private final ConnectionManager connectionManager;
@@ -74,6 +112,16 @@ public class Network {
     this.connectionManager.clearProxy();
   }
 
+  /**
+   * Returns the proxy set by {@link #setProxy} as {@code host:port}, or {@code null} if new
+   * connections are made directly.
+   *
+   * <p>If the last call to {@link #setProxy} had an invalid port, this is just the host.
+   */
+  public @Nullable String getProxy() {
+    return connectionManager.guardedMap(Native::ConnectionManager_get_proxy);
+  }
+
   /**
    * Enables or disables censorship circumvention for all new connections (until changed).
    *
@@ -88,6 +136,126 @@ public class Network {
     this.connectionManager.setCensorshipCircumventionEnabled(enabled);
   }
 
+  /**
+   * Sets size limits for all new chat connections (until changed).
+   *
+   * <p>Messages larger than {@code maxMessageSize} (or frames larger than {@code maxFrameSize})
+   * are rejected. Once {@code maxPendingSendBytes} of outgoing requests are waiting to be written,
+   * further requests wait for the connection to catch up instead of being buffered, so bulk
+   * operations don't use unbounded memory when the connection stalls. Existing connections are
+   * not affected.
+   */
+  public void setChatWebSocketLimits(
+      int maxFrameSize, int maxMessageSize, int maxPendingSendBytes) {
+    this.connectionManager.setChatWebSocketLimits(
+        maxFrameSize, maxMessageSize, maxPendingSendBytes);
+  }
+
+  /**
+   * Sets how new connections to {@code service} back off after failed attempts (until changed).
+   *
+   * <p>After the first failure the delay starts at {@code initialDelay}, and is scaled by {@code
+   * multiplier} after each further failure, up to {@code maxDelay}. {@code jitter} (between 0 and
+   * 1) randomly shortens each delay by up to that fraction. If {@code maxAttempts} is positive,
+   * each route to the service is only tried that many times before moving on to the next one.
+   * Existing connections are not affected.
+   */
+  public void setRetryPolicy(
+      Service service,
+      Duration initialDelay,
+      double multiplier,
+      double jitter,
+      Duration maxDelay,
+      int maxAttempts) {
+    this.connectionManager.setRetryPolicy(
+        service.value,
+        Math.toIntExact(initialDelay.toMillis()),
+        (int) Math.round(multiplier * 100),
+        (int) Math.round(jitter * 100),
+        Math.toIntExact(maxDelay.toMillis()),
+        maxAttempts);
+  }
+
+  /**
+   * Requires new direct connections to {@code service} to present one of {@code pins} (until
+   * changed).
+   *
+   * <p>Each pin is the SHA-256 hash of a DER-encoded SubjectPublicKeyInfo, and matches if any
+   * certificate in the verified chain has that public key. Domain-fronted routes end at the
+   * fronting provider, so they aren't pinned. Passing an empty list stops requiring pins. Existing
+   * connections are not affected.
+   *
+   * @throws IllegalArgumentException if any pin is not 32 bytes long
+   */
+  public void setCertificatePins(Service service, List<byte[]> pins) {
+    byte[] encoded = new byte[pins.size() * 32];
+    for (int i = 0; i < pins.size(); ++i) {
+      byte[] pin = pins.get(i);
+      if (pin.length != 32) {
+        throw new IllegalArgumentException("certificate pins must be SHA-256 hashes");
+      }
+      System.arraycopy(pin, 0, encoded, i * 32, 32);
+    }
+    this.connectionManager.setCertificatePins(service.value, encoded);
+  }
+
+  /**
+   * Sets which routes new connections try, and in what order (until changed).
+   *
+   * <p>Routes not in {@code order} are never tried, so an app can, for example, avoid domain
+   * fronting on a particular network by leaving out {@link Route#PROXY_F} and {@link
+   * Route#PROXY_G}. If none of the listed routes are available for a service, the direct route is
+   * used anyway. The order only matters while censorship circumvention is enabled, but it is kept
+   * when that or any other setting changes. It is also saved by {@link #exportRouteState}, and
+   * {@link #importRouteState} restores it. Existing connections are not affected.
+   */
+  public void setRouteOrder(List<Route> order) {
+    byte[] encoded = new byte[order.size()];
+    for (int i = 0; i < encoded.length; ++i) {
+      encoded[i] = (byte) order.get(i).value;
+    }
+    this.connectionManager.setRouteOrder(encoded);
+  }
+
+  /**
+   * Returns the route order set by {@link #setRouteOrder}, or the default order if it hasn't been
+   * called.
+   */
+  public List<Route> getRouteOrder() {
+    byte[] encoded = connectionManager.guardedMap(Native::ConnectionManager_get_route_order);
+    List<Route> order = new ArrayList<>(encoded.length);
+    for (byte value : encoded) {
+      order.add(Route.values()[value]);
+    }
+    return order;
+  }
+
+  /**
+   * Saves which routes have been working, along with cached DNS results, encrypted with {@code
+   * key}.
+   *
+   * <p>Pass the result to {@link #importRouteState} on a later launch, with the same key, so that
+   * routes that were working get tried first instead of being rediscovered. The key must be 32
+   * bytes. It should be kept in secure storage, since the saved state reveals how the device has
+   * been connecting.
+   */
+  public byte[] exportRouteState(byte[] key) {
+    return connectionManager.guardedMap(h -> Native.ConnectionManager_export_route_state(h, key));
+  }
+
+  /**
+   * Restores state saved by {@link #exportRouteState}.
+   *
+   * <p>Routes that were working through the same proxy (or without one) are tried first by new
+   * connections, and the saved route order replaces the current one. Existing connections are not
+   * affected.
+   *
+   * @throws IOException if the state can't be decrypted with {@code key} or is malformed.
+   */
+  public void importRouteState(byte[] key, byte[] state) throws IOException {
+    this.connectionManager.importRouteState(key, state);
+  }
+
   /**
    * Notifies libsignal that the network has changed.
    *
@@ -97,6 +265,18 @@ public class Network {
     connectionManager.guardedRun(Native::ConnectionManager_on_network_change);
   }
 
+  /**
+   * The current time according to the chat server, estimated from its recent responses.
+   *
+   * <p>Use this instead of the local clock for decisions that have to agree with the server, such
+   * as choosing a redemption time for group credentials or checking whether a credential has
+   * expired. Falls back to the local time until a chat response has been received.
+   */
+  public Instant serverTime() {
+    return Instant.ofEpochMilli(
+        connectionManager.guardedMap(Native::ConnectionManager_server_time));
+  }
+
   public Svr3 svr3() {
     return this.svr3;
   }
@@ -112,6 +292,30 @@ public class Network {
             });
   }
 
+  /**
+   * Like {@link #cdsiLookup(String, String, CdsiLookupRequest, Consumer)}, but takes its connection
+   * from {@code pool} if one is ready.
+   *
+   * <p>Use the same pool for every lookup in a burst, such as a contact sync. If {@code
+   * prepareNext} is set, a connection for the next lookup is established in the pool while this
+   * one runs; leave it unset for the last lookup of the burst, so that no attestation handshake is
+   * wasted.
+   */
+  public CompletableFuture<CdsiLookupResponse> cdsiLookup(
+      String username,
+      String password,
+      CdsiLookupRequest request,
+      Consumer<byte[]> tokenConsumer,
+      CdsiConnectionPool pool,
+      boolean prepareNext) {
+    return CdsiLookup.startPooled(this, username, password, request, pool, prepareNext)
+        .thenCompose(
+            (CdsiLookup lookup) -> {
+              tokenConsumer.accept(lookup.getToken());
+              return lookup.complete();
+            });
+  }
+
   /**
    * Try to load several libsignal classes asynchronously, using the same mechanism as native (Rust)
    * code.
@@ -176,6 +380,54 @@ public class Network {
       guardedRun(h -> Native.ConnectionManager_set_censorship_circumvention_enabled(h, enabled));
     }
 
+    private void setChatWebSocketLimits(
+        int maxFrameSize, int maxMessageSize, int maxPendingSendBytes) {
+      guardedRun(
+          h ->
+              Native.ConnectionManager_set_chat_websocket_limits(
+                  h, maxFrameSize, maxMessageSize, maxPendingSendBytes));
+    }
+
+    private void setRetryPolicy(
+        int service,
+        int initialDelayMillis,
+        int multiplierPercent,
+        int jitterPercent,
+        int maxDelayMillis,
+        int maxAttempts) {
+      guardedRun(
+          h ->
+              Native.ConnectionManager_set_retry_policy(
+                  h,
+                  service,
+                  initialDelayMillis,
+                  multiplierPercent,
+                  jitterPercent,
+                  maxDelayMillis,
+                  maxAttempts));
+    }
+
+    private void setCertificatePins(int service, byte[] pins) {
+      // The pins have already been checked, so they can't be invalid.
+      filterExceptions(
+          () ->
+              guardedRunChecked(
+                  h -> Native.ConnectionManager_set_certificate_pins(h, service, pins)));
+    }
+
+    private void setRouteOrder(byte[] order) {
+      // The order is built from Route values, so it can't be invalid.
+      filterExceptions(
+          () -> guardedRunChecked(h -> Native.ConnectionManager_set_route_order(h, order)));
+    }
+
+    private void importRouteState(byte[] key, byte[] state) throws IOException {
+      filterExceptions(
+          IOException.class,
+          () ->
+              guardedRunChecked(h -> Native.ConnectionManager_import_route_state(h, key, state)));
+    }
+
     @Override
     protected void release(final long nativeHandle) {
       Native.ConnectionManager_Destroy(nativeHandle);
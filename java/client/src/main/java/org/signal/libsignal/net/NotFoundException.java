@@ -0,0 +1,13 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.net;
+
+/** Indicates that the server could not find what a request asked for. */
+public class NotFoundException extends ChatServiceException {
+  public NotFoundException(String message) {
+    super(message);
+  }
+}
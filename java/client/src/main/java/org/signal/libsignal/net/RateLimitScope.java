@@ -0,0 +1,29 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.net;
+
+/**
+ * What a server-imposed rate limit is counted against.
+ *
+ * <p>The order of values in this enum should match {@code RateLimitScope} enum in Rust
+ * (libsignal-net).
+ */
+public enum RateLimitScope {
+  /** The server did not say. */
+  UNSPECIFIED,
+  /** The limit applies to a single phone number, whichever account is asking about it. */
+  PER_NUMBER,
+  /** The limit applies to the requesting account. */
+  PER_ACCOUNT;
+
+  static RateLimitScope fromOrdinal(int ordinal) {
+    RateLimitScope[] values = values();
+    if (ordinal < 0 || ordinal >= values.length) {
+      return UNSPECIFIED;
+    }
+    return values[ordinal];
+  }
+}
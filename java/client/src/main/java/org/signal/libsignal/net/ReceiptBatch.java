@@ -0,0 +1,62 @@
+//
+// Copyright 2026 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.net;
+
+import java.nio.ByteBuffer;
+import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.internal.NativeHandleGuard;
+import org.signal.libsignal.protocol.ServiceId;
+
+/**
+ * Receipts for messages from one sender that should be sent together as a single receipt message.
+ *
+ * <p>Obtained from {@link AuthenticatedChatService#nextReceiptBatch}.
+ */
+public class ReceiptBatch extends NativeHandleGuard.SimpleOwner {
+  /** The kinds of receipt a client can send; each kind is sent as a separate message. */
+  public enum Kind {
+    // This needs to be kept in sync with the Rust ReceiptKind enum.
+    DELIVERY(0),
+    READ(1),
+    VIEWED(2);
+
+    final int value;
+
+    Kind(int value) {
+      this.value = value;
+    }
+  }
+
+  ReceiptBatch(long nativeHandle) {
+    super(nativeHandle);
+  }
+
+  @Override
+  protected void release(long nativeHandle) {
+    Native.ReceiptBatch_Destroy(nativeHandle);
+  }
+
+  /** The author of the acknowledged messages, and the recipient of the receipt message. */
+  public ServiceId getSender() {
+    try {
+      return ServiceId.parseFromFixedWidthBinary(guardedMap(Native::ReceiptBatch_GetSender));
+    } catch (ServiceId.InvalidServiceIdException e) {
+      throw new AssertionError(e);
+    }
+  }
+
+  public Kind getKind() {
+    return Kind.values()[guardedMap(Native::ReceiptBatch_GetKind)];
+  }
+
+  /** The sent timestamps of the acknowledged messages, in ascending order. */
+  public long[] getTimestamps() {
+    ByteBuffer packed = ByteBuffer.wrap(guardedMap(Native::ReceiptBatch_GetTimestamps));
+    long[] timestamps = new long[packed.remaining() / Long.BYTES];
+    packed.asLongBuffer().get(timestamps);
+    return timestamps;
+  }
+}
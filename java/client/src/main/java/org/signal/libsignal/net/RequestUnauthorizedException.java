@@ -0,0 +1,13 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.net;
+
+/** Indicates that the server rejected a request as unauthorized. */
+public class RequestUnauthorizedException extends ChatServiceException {
+  public RequestUnauthorizedException(String message) {
+    super(message);
+  }
+}
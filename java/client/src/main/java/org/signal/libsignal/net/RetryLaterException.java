@@ -11,12 +11,28 @@ public class RetryLaterException extends Exception {
   /** The amount of time to wait before retrying. */
   public final Duration duration;
 
+  /** What the limit is counted against, or {@link RateLimitScope#UNSPECIFIED} if unknown. */
+  public final RateLimitScope scope;
+
+  /**
+   * The server's explanation for the limit, if it gave one.
+   *
+   * <p>Suitable for logging, but not for display to users.
+   */
+  public final String reason;
+
   public RetryLaterException(long retryAfterSeconds) {
-    this(Duration.ofSeconds(retryAfterSeconds));
+    this(Duration.ofSeconds(retryAfterSeconds), RateLimitScope.UNSPECIFIED, null);
+  }
+
+  public RetryLaterException(long retryAfterSeconds, int scope, String reason) {
+    this(Duration.ofSeconds(retryAfterSeconds), RateLimitScope.fromOrdinal(scope), reason);
   }
 
-  private RetryLaterException(Duration duration) {
+  private RetryLaterException(Duration duration, RateLimitScope scope, String reason) {
     super("Retry after " + duration.getSeconds() + " seconds");
     this.duration = duration;
+    this.scope = scope;
+    this.reason = reason;
   }
 }
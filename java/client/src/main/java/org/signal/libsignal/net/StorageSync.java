@@ -0,0 +1,156 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.net;
+
+import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
+
+import java.util.Arrays;
+import java.util.List;
+import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.internal.NativeHandleGuard;
+
+/**
+ * Works out how to bring the local database and the storage service back in sync.
+ *
+ * <p>Manifests are decrypted {@code ManifestRecord} messages, and record ids are serialized {@code
+ * ManifestRecord.Identifier} messages. Records are passed in serialized form, along with a key
+ * identifying "the same" record across devices (such as a contact's ACI); a local record that
+ * isn't on the service yet wins over the service's copy, and otherwise the service's copy wins.
+ *
+ * <p>To sync, add every local record, local deletion, and id already known to be of an unknown
+ * type; fetch and decrypt the records for {@link #idsToFetch}; add those; and then call {@link
+ * #plan}.
+ */
+public class StorageSync extends NativeHandleGuard.SimpleOwner {
+  /**
+   * @param previousManifest the manifest as of the last successful sync, or an empty array if
+   *     there has never been one
+   * @param remoteManifest the manifest currently on the service
+   * @throws IllegalArgumentException if either manifest can't be parsed
+   */
+  public StorageSync(byte[] previousManifest, byte[] remoteManifest) {
+    super(filterExceptions(() -> Native.StorageSync_new(previousManifest, remoteManifest)));
+  }
+
+  @Override
+  protected void release(long nativeHandle) {
+    Native.StorageSync_Destroy(nativeHandle);
+  }
+
+  /** Adds a record from the local database. */
+  public void addLocalRecord(byte[] id, byte[] key, byte[] data) {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      filterExceptions(
+          () -> Native.StorageSync_addLocalRecord(guard.nativeHandle(), id, key, data));
+    }
+  }
+
+  /** Adds the id of a record that was deleted locally since the last sync. */
+  public void addLocalDeletion(byte[] id) {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      filterExceptions(() -> Native.StorageSync_addLocalDeletion(guard.nativeHandle(), id));
+    }
+  }
+
+  /** Adds the id of a record already known to be of a type this client can't parse. */
+  public void addKnownUnknown(byte[] id) {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      filterExceptions(() -> Native.StorageSync_addKnownUnknown(guard.nativeHandle(), id));
+    }
+  }
+
+  /** Returns the ids that need to be fetched from the service, in manifest order. */
+  public List<byte[]> idsToFetch() {
+    return Arrays.asList(guardedMap(Native::StorageSync_idsToFetch));
+  }
+
+  /** Adds a record fetched from the service. */
+  public void addFetchedRecord(byte[] id, byte[] key, byte[] data) {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      filterExceptions(
+          () -> Native.StorageSync_addFetchedRecord(guard.nativeHandle(), id, key, data));
+    }
+  }
+
+  /** Adds the id of a fetched record of a type this client can't parse. */
+  public void addFetchedUnknown(byte[] id) {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      filterExceptions(() -> Native.StorageSync_addFetchedUnknown(guard.nativeHandle(), id));
+    }
+  }
+
+  /**
+   * Plans the sync, consuming the records added so far.
+   *
+   * @throws IllegalStateException if the remote manifest is older than the previous one, or a
+   *     fetched record isn't in the remote manifest
+   */
+  public Plan plan() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return new Plan(filterExceptions(() -> Native.StorageSync_plan(guard.nativeHandle())));
+    }
+  }
+
+  /** The changes to make on each side. */
+  public static class Plan extends NativeHandleGuard.SimpleOwner {
+    private Plan(long nativeHandle) {
+      super(nativeHandle);
+    }
+
+    @Override
+    protected void release(long nativeHandle) {
+      Native.StorageSyncPlan_Destroy(nativeHandle);
+    }
+
+    /** The ids of records to insert or replace locally, parallel to {@link #localUpsertData}. */
+    public List<byte[]> localUpsertIds() {
+      return Arrays.asList(guardedMap(Native::StorageSyncPlan_localUpsertIds));
+    }
+
+    public List<byte[]> localUpsertData() {
+      return Arrays.asList(guardedMap(Native::StorageSyncPlan_localUpsertData));
+    }
+
+    public List<byte[]> localDeletes() {
+      return Arrays.asList(guardedMap(Native::StorageSyncPlan_localDeletes));
+    }
+
+    /** The ids of records of unknown types still in the manifest, to remember for next time. */
+    public List<byte[]> unknown() {
+      return Arrays.asList(guardedMap(Native::StorageSyncPlan_unknown));
+    }
+
+    /**
+     * Whether a new manifest needs to be uploaded.
+     *
+     * <p>If not, the remote-side accessors all return empty lists.
+     */
+    public boolean hasRemoteChanges() {
+      return guardedMap(Native::StorageSyncPlan_hasRemoteChanges);
+    }
+
+    /**
+     * The record ids for the manifest to upload, whose version is one more than the remote
+     * manifest's.
+     */
+    public List<byte[]> remoteManifestIds() {
+      return Arrays.asList(guardedMap(Native::StorageSyncPlan_remoteManifestIds));
+    }
+
+    /** The ids of records to upload, parallel to {@link #remoteInsertData}. */
+    public List<byte[]> remoteInsertIds() {
+      return Arrays.asList(guardedMap(Native::StorageSyncPlan_remoteInsertIds));
+    }
+
+    public List<byte[]> remoteInsertData() {
+      return Arrays.asList(guardedMap(Native::StorageSyncPlan_remoteInsertData));
+    }
+
+    public List<byte[]> remoteDeletes() {
+      return Arrays.asList(guardedMap(Native::StorageSyncPlan_remoteDeletes));
+    }
+  }
+}
@@ -5,11 +5,16 @@
 
 package org.signal.libsignal.net;
 
+import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
+
 import java.nio.ByteBuffer;
 import java.nio.ByteOrder;
+import java.util.ArrayList;
+import java.util.List;
 import org.signal.libsignal.internal.CompletableFuture;
 import org.signal.libsignal.internal.Native;
 import org.signal.libsignal.internal.NativeHandleGuard;
+import org.signal.libsignal.svr.SvrException;
 
 /**
  * This class provides functionality for communicating with SVR3
@@ -96,6 +101,58 @@ public final class Svr3 {
     }
   }
 
+  /**
+   * Backup a secret to only some of the SVR3 backends.
+   *
+   * <p>Works like {@link #backup}, except that the secret is shared only among the backends listed
+   * in {@code distribution}. The resulting share set records the layout, so {@link #restore} needs
+   * no extra arguments.
+   *
+   * @throws {@link org.signal.libsignal.svr.SvrException} when the distribution is not supported:
+   *     an unknown or repeated backend, or a threshold other than the number of selected backends.
+   * @see #backup
+   */
+  public final CompletableFuture<byte[]> backupWithDistribution(
+      byte[] what, String password, int maxTries, ShareDistribution distribution, EnclaveAuth auth) {
+    byte[] serverIds = new byte[distribution.serverIds().size()];
+    for (int i = 0; i < serverIds.length; i++) {
+      serverIds[i] = distribution.serverIds().get(i).byteValue();
+    }
+    try (NativeHandleGuard asyncRuntime = new NativeHandleGuard(this.network.getAsyncContext());
+        NativeHandleGuard connectionManager =
+            new NativeHandleGuard(this.network.getConnectionManager())) {
+
+      return Native.Svr3BackupWithDistribution(
+          asyncRuntime.nativeHandle(),
+          connectionManager.nativeHandle(),
+          what,
+          password,
+          maxTries,
+          serverIds,
+          distribution.threshold(),
+          auth.username,
+          auth.password);
+    }
+  }
+
+  /**
+   * Returns the layout of a share set produced by {@link #backup} or {@link
+   * #backupWithDistribution}.
+   *
+   * @throws SvrException if the share set cannot be parsed.
+   */
+  public static ShareDistribution layoutOf(byte[] shareSet) throws SvrException {
+    byte[] serverIds =
+        filterExceptions(SvrException.class, () -> Native.Svr3GetShareSetServerIds(shareSet));
+    int threshold =
+        filterExceptions(SvrException.class, () -> Native.Svr3GetShareSetThreshold(shareSet));
+    List<Integer> ids = new ArrayList<>(serverIds.length);
+    for (byte id : serverIds) {
+      ids.add(Byte.toUnsignedInt(id));
+    }
+    return new ShareDistribution(ids, threshold);
+  }
+
   /**
    * Migrate a secret to a new SVR3 environment.
    *
@@ -319,6 +376,15 @@ public final class Svr3 {
     }
   }
 
+  /**
+   * Which SVR3 backends hold shares of a secret.
+   *
+   * <p>Backends are identified by their position in the environment, starting from 1; in the
+   * standard environments these are SGX, Nitro, and TPM2-SNP, in that order. {@code threshold} is
+   * the number of backends needed to restore the secret.
+   */
+  public record ShareDistribution(List<Integer> serverIds, int threshold) {}
+
   /** The value containing restored secret returned from {@link #restore}. */
   public record RestoredSecret(int triesRemaining, byte[] value) {
 
@@ -9,11 +9,25 @@ import org.signal.libsignal.internal.CompletableFuture;
 import org.signal.libsignal.internal.Native;
 import org.signal.libsignal.internal.NativeHandleGuard;
 
-class TokioAsyncContext extends NativeHandleGuard.SimpleOwner {
+/**
+ * A Rust async runtime, used to run libsignal operations that complete asynchronously.
+ *
+ * <p>Most code should not need to use this directly.
+ */
+public class TokioAsyncContext extends NativeHandleGuard.SimpleOwner {
+  private static class SharedInstanceHolder {
+    static final TokioAsyncContext INSTANCE = new TokioAsyncContext();
+  }
+
   TokioAsyncContext() {
     super(Native.TokioAsyncContext_new());
   }
 
+  /** A runtime for async operations that aren't associated with a particular {@link Network}. */
+  public static TokioAsyncContext shared() {
+    return SharedInstanceHolder.INSTANCE;
+  }
+
   @SuppressWarnings("unchecked")
   CompletableFuture<Class<Object>> loadClassAsync(String className) {
     return (CompletableFuture<Class<Object>>) Native.AsyncLoadClass(this, className);
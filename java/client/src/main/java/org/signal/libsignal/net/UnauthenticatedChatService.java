@@ -57,6 +57,13 @@ public class UnauthenticatedChatService extends ChatService {
         nativeAsyncContextHandle, nativeChatServiceHandle, nativeRequestHandle, timeoutMillis);
   }
 
+  @Override
+  protected CompletableFuture<Object> prepareForBackgroundWrapper(
+      long nativeAsyncContextHandle, long nativeChatServiceHandle, int budgetMillis) {
+    return Native.ChatService_prepare_for_background_unauth(
+        nativeAsyncContextHandle, nativeChatServiceHandle, budgetMillis);
+  }
+
   @Override
   protected void release(long nativeChatServiceHandle) {
     Native.UnauthChat_Destroy(nativeChatServiceHandle);
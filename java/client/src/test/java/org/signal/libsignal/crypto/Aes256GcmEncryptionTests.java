@@ -6,6 +6,9 @@
 package org.signal.libsignal.crypto;
 
 import java.io.IOException;
+import java.nio.charset.StandardCharsets;
+import java.util.ArrayList;
+import java.util.List;
 import junit.framework.TestCase;
 import org.signal.libsignal.protocol.InvalidKeyException;
 import org.signal.libsignal.protocol.util.Hex;
@@ -22,6 +25,63 @@ public class Aes256GcmEncryptionTests extends TestCase {
         "feedfacedeadbeeffeedfacedeadbeefabaddad2");
   }
 
+  public void testNonceSequence() throws Exception {
+    byte[] key =
+        Hex.fromStringCondensed("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308");
+    byte[] prefix = Hex.fromStringCondensed("01020304");
+    List<Long> reservations = new ArrayList<>();
+    NonceStore store = reservations::add;
+
+    NonceSequence nonces = new NonceSequence(key, prefix, 0, 2);
+    assertEquals(2, nonces.remaining());
+
+    Aes256GcmEncryption gcmEnc = new Aes256GcmEncryption(nonces, store, new byte[0]);
+    byte[] nonce = gcmEnc.getNonce();
+    assertEquals("010203040000000000000000", Hex.toStringCondensed(nonce));
+    byte[] ciphertext = "hello".getBytes(StandardCharsets.UTF_8);
+    gcmEnc.encrypt(ciphertext);
+    byte[] tag = gcmEnc.computeTag();
+
+    Aes256GcmDecryption gcmDec = new Aes256GcmDecryption(key, nonce, new byte[0]);
+    gcmDec.decrypt(ciphertext);
+    assertTrue(gcmDec.verifyTag(tag));
+    assertEquals("hello", new String(ciphertext, StandardCharsets.UTF_8));
+
+    // A failed reservation is reported without using up a nonce.
+    IOException storeFailure = new IOException("disk full");
+    try {
+      new Aes256GcmEncryption(
+          nonces,
+          limit -> {
+            throw storeFailure;
+          },
+          new byte[0]);
+      fail("should have thrown");
+    } catch (IOException e) {
+      assertSame(storeFailure, e);
+    }
+    assertEquals(1, nonces.remaining());
+
+    assertEquals(
+        "010203040000000000000001",
+        Hex.toStringCondensed(new Aes256GcmEncryption(nonces, store, new byte[0]).getNonce()));
+    assertEquals(0, nonces.remaining());
+    assertEquals(List.of(2L), reservations);
+
+    try {
+      new Aes256GcmEncryption(nonces, store, new byte[0]);
+      fail("should have thrown");
+    } catch (IllegalStateException e) {
+      // expected
+    }
+  }
+
+  public void testNonceSequenceLimitIsCapped() throws Exception {
+    byte[] key = new byte[32];
+    NonceSequence nonces = new NonceSequence(key, new byte[4], 0, Long.MAX_VALUE);
+    assertEquals(NonceSequence.MAX_LIMIT, nonces.remaining());
+  }
+
   private static void testAesGcmKat(
       String hex_key,
       String hex_plaintext,
@@ -0,0 +1,67 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.devicetransfer;
+
+import static org.junit.Assert.assertArrayEquals;
+import static org.junit.Assert.assertEquals;
+import static org.junit.Assert.assertThrows;
+
+import java.util.Arrays;
+import org.junit.Test;
+import org.signal.libsignal.protocol.InvalidMessageException;
+
+public class DeviceTransferSessionTest {
+  private static byte[] certificate(String name) {
+    return new DeviceTransferKey().generateCertificate(name, 1);
+  }
+
+  private static byte[] exchange(DeviceTransferSession from, DeviceTransferSession to)
+      throws InvalidMessageException {
+    return to.receive(from.takeOutgoing());
+  }
+
+  @Test
+  public void testTransfer() throws Exception {
+    final byte[] payload = new byte[3000];
+    Arrays.fill(payload, (byte) 0x42);
+
+    final DeviceTransferSession oldDevice =
+        DeviceTransferSession.forOldDevice(certificate("old"), payload.length);
+    final DeviceTransferSession newDevice = DeviceTransferSession.forNewDevice(certificate("new"));
+    assertThrows(IllegalStateException.class, () -> newDevice.verificationCode());
+
+    // Certificates are only revealed after both commitments have been exchanged.
+    exchange(oldDevice, newDevice);
+    exchange(newDevice, oldDevice);
+    exchange(oldDevice, newDevice);
+    assertEquals(DeviceTransferSession.State.VERIFYING, oldDevice.state());
+    assertEquals(DeviceTransferSession.State.VERIFYING, newDevice.state());
+    assertEquals(oldDevice.verificationCode(), newDevice.verificationCode());
+    assertEquals(payload.length, newDevice.payloadLength());
+
+    assertThrows(IllegalStateException.class, () -> oldDevice.sendPayloadChunk(payload));
+    oldDevice.confirmVerificationCode();
+    newDevice.confirmVerificationCode();
+    exchange(oldDevice, newDevice);
+    exchange(newDevice, oldDevice);
+    assertEquals(DeviceTransferSession.State.TRANSFERRING, oldDevice.state());
+    assertEquals(DeviceTransferSession.State.TRANSFERRING, newDevice.state());
+
+    oldDevice.sendPayloadChunk(payload);
+    oldDevice.finish();
+    assertArrayEquals(payload, exchange(oldDevice, newDevice));
+    assertEquals(DeviceTransferSession.State.COMPLETED, newDevice.state());
+    assertEquals(payload.length, newDevice.bytesTransferred());
+  }
+
+  @Test
+  public void testInvalidMessage() {
+    final DeviceTransferSession session = DeviceTransferSession.forNewDevice(certificate("new"));
+    assertThrows(
+        InvalidMessageException.class, () -> session.receive(new byte[] {(byte) 0xff, 0, 0, 0, 0}));
+    assertEquals(DeviceTransferSession.State.FAILED, session.state());
+  }
+}
@@ -5,6 +5,8 @@
 
 package org.signal.libsignal.hsmenclave;
 
+import static org.junit.Assert.assertThrows;
+
 import java.util.ArrayList;
 import java.util.List;
 import junit.framework.TestCase;
@@ -113,4 +115,21 @@ public class HsmEnclaveClientTest extends TestCase {
     }
     fail();
   }
+
+  public void testPipeliningFailsPriorToEstablishment() throws Exception {
+    byte[] validKey = new byte[32];
+    List<byte[]> hashes = new ArrayList<>();
+    hashes.add(
+        new byte[] {
+          0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+          0, 0
+        });
+    HsmEnclaveClient hsmEnclaveClient = new HsmEnclaveClient(validKey, hashes);
+    assertThrows(IllegalStateException.class, () -> hsmEnclaveClient.enablePipelining(4));
+    assertThrows(IllegalStateException.class, () -> hsmEnclaveClient.inFlight());
+    assertThrows(IllegalStateException.class, () -> hsmEnclaveClient.lastSentRequestId());
+    assertThrows(IllegalStateException.class, () -> hsmEnclaveClient.lastReceivedRequestId());
+    // The client is still usable afterwards.
+    hsmEnclaveClient.initialRequest();
+  }
 }
@@ -72,6 +72,24 @@ public class Mp4SanitizerTest {
         sanitized, ftyp().length, mp4Data.length - metadata.length, metadata);
   }
 
+  @Test
+  public void testMinimalMp4WithPooledBuffers() throws Exception {
+    byte[] metadata = ByteUtil.combine(ftyp(), moov());
+    byte[] mp4Data = ByteUtil.combine(ftyp(), mdat(), moov());
+
+    Mp4Sanitizer.setMaxPooledBufferBytes(1 << 20);
+    try {
+      for (int i = 0; i < 3; i++) {
+        SanitizedMetadata sanitized =
+            Mp4Sanitizer.sanitize(new ByteArrayInputStream(mp4Data), mp4Data.length);
+        assertSanitizedMetadataEquals(
+            sanitized, ftyp().length, mp4Data.length - metadata.length, metadata);
+      }
+    } finally {
+      Mp4Sanitizer.setMaxPooledBufferBytes(0);
+    }
+  }
+
   @Test
   public void testBrokenSkipWorkaround() throws Exception {
     // Same setup as testMinimalMp4.
@@ -86,6 +104,31 @@ public class Mp4SanitizerTest {
         sanitized, ftyp().length, mp4Data.length - metadata.length, metadata);
   }
 
+  @Test
+  public void testMinimalMp4WithOptions() throws Exception {
+    byte[] metadata = ByteUtil.combine(ftyp(), moov());
+    byte[] mp4Data = ByteUtil.combine(ftyp(), mdat(), moov());
+
+    Mp4SanitizerOptions options =
+        new Mp4SanitizerOptions()
+            .setMetadataStripped(true)
+            .setMetadataBoxPreserved("colr", false)
+            .setMetadataBoxPreserved("udta", true)
+            .setCodecPolicy(new Mp4CodecPolicy(true, false, true));
+    SanitizedMetadata sanitized =
+        Mp4Sanitizer.sanitize(new ByteArrayInputStream(mp4Data), mp4Data.length, options);
+
+    assertSanitizedMetadataEquals(
+        sanitized, ftyp().length, mp4Data.length - metadata.length, metadata);
+  }
+
+  @Test
+  public void testInvalidBoxTypeInOptions() {
+    assertThrows(
+        IllegalArgumentException.class,
+        () -> new Mp4SanitizerOptions().setMetadataBoxPreserved("toolong", true));
+  }
+
   @Test
   public void testMp4IoError() throws Exception {
     try (InputStream ioErrorStream = new IoErrorInputStream()) {
@@ -12,13 +12,19 @@ import static org.junit.Assert.assertThrows;
 import static org.junit.Assert.assertTrue;
 
 import java.io.ByteArrayInputStream;
+import java.io.FilterInputStream;
 import java.io.IOException;
 import java.io.InputStream;
 import java.nio.charset.StandardCharsets;
+import java.time.Duration;
 import java.util.Arrays;
 import java.util.UUID;
+import java.util.concurrent.ExecutionException;
+import java.util.concurrent.TimeoutException;
 import java.util.function.Supplier;
 import org.junit.Test;
+import org.signal.libsignal.internal.AsyncInputStream;
+import org.signal.libsignal.internal.CompletableFuture;
 import org.signal.libsignal.protocol.ServiceId.Aci;
 import org.signal.libsignal.protocol.kdf.HKDF;
 import org.signal.libsignal.protocol.util.ByteUtil;
@@ -72,6 +78,7 @@ public class MessageBackupValidationTest {
     MessageBackup.ValidationResult result =
         MessageBackup.validate(key, BACKUP_PURPOSE, factory, length);
     assertArrayEquals(result.unknownFieldMessages, new String[0]);
+    assertEquals(result.findings.length, 0);
 
     // Verify that the key can also be created from a backup ID and produce the same result.
     MessageBackupKey keyFromBackupId = makeMessageBackupKeyFromBackupId();
@@ -106,6 +113,10 @@ public class MessageBackupValidationTest {
               MessageBackup.validate(key, BACKUP_PURPOSE, factory, 0);
             });
     assertEquals(error.getMessage(), "not enough bytes for an HMAC");
+    assertEquals(error.findings.length, 1);
+    assertEquals(error.findings[0].kind, ValidationFinding.Kind.MALFORMED_FRAME);
+    assertEquals(error.findings[0].severity, ValidationFinding.Severity.FATAL);
+    assertEquals(error.findings[0].frameIndex, -1);
   }
 
   @Test
@@ -136,6 +147,99 @@ public class MessageBackupValidationTest {
             });
     assertEquals(thrown.getMessage(), ThrowingInputStream.MESSAGE);
   }
+
+  @Test
+  public void slowInputStreamTimesOut() throws IOException {
+    Supplier<InputStream> factory =
+        () -> {
+          return new SlowInputStream(
+              MessageBackupValidationTest.class.getResourceAsStream(VALID_BACKUP_RESOURCE_NAME));
+        };
+    final long length;
+    try (InputStream input = factory.get()) {
+      length = ResourceReader.getLength(input);
+    }
+
+    MessageBackupKey key = makeMessageBackupKey();
+    assertThrows(
+        TimeoutException.class,
+        () -> {
+          MessageBackup.validate(key, BACKUP_PURPOSE, factory, length, Duration.ofMillis(1));
+        });
+  }
+
+  @Test
+  public void validBackupFileAsync() throws Exception {
+    final byte[] contents;
+    try (InputStream input =
+        MessageBackupValidationTest.class.getResourceAsStream(VALID_BACKUP_RESOURCE_NAME)) {
+      contents = ResourceReader.readAll(input);
+    }
+    MessageBackupKey key = makeMessageBackupKey();
+    MessageBackup.ValidationResult result =
+        MessageBackup.validateAsync(
+                key, BACKUP_PURPOSE, () -> new ByteArrayAsyncInputStream(contents), contents.length)
+            .get();
+    assertArrayEquals(result.unknownFieldMessages, new String[0]);
+    assertEquals(result.findings.length, 0);
+  }
+
+  @Test
+  public void emptyBackupFileAsync() {
+    MessageBackupKey key = makeMessageBackupKey();
+    ExecutionException error =
+        assertThrows(
+            ExecutionException.class,
+            () ->
+                MessageBackup.validateAsync(
+                        key, BACKUP_PURPOSE, () -> new ByteArrayAsyncInputStream(new byte[0]), 0)
+                    .get());
+    assertTrue(error.getCause() instanceof ValidationError);
+    assertEquals(error.getCause().getMessage(), "not enough bytes for an HMAC");
+  }
+
+  @Test
+  public void stalledAsyncInputTimesOut() {
+    MessageBackupKey key = makeMessageBackupKey();
+    ExecutionException error =
+        assertThrows(
+            ExecutionException.class,
+            () ->
+                MessageBackup.validateAsync(
+                        key,
+                        BACKUP_PURPOSE,
+                        StalledAsyncInputStream::new,
+                        100,
+                        Duration.ofMillis(1))
+                    .get());
+    assertTrue(error.getCause() instanceof TimeoutException);
+  }
+
+  @Test
+  public void predictsPaddedAndEncryptedSizes() {
+    assertEquals(541, MessageBackup.getPaddedSize(0));
+    assertEquals(568, MessageBackup.getPaddedSize(542));
+    // IV + PKCS#7-padded ciphertext + HMAC
+    assertEquals(16 + 576 + 32, MessageBackup.getEncryptedSize(542));
+  }
+
+  @Test
+  public void customPaddingPolicies() {
+    BackupPaddingPolicy standard = new BackupPaddingPolicy();
+    assertEquals(MessageBackup.getPaddedSize(542), standard.getPaddedSize(542));
+    assertEquals(MessageBackup.getEncryptedSize(542), standard.getEncryptedSize(542));
+
+    BackupPaddingPolicy minimumOnly = new BackupPaddingPolicy(1.0, 1000);
+    assertEquals(1000, minimumOnly.getPaddedSize(0));
+    assertEquals(1001, minimumOnly.getPaddedSize(1001));
+
+    BackupPaddingPolicy unpadded = new BackupPaddingPolicy(1.0, 0);
+    assertEquals(542, unpadded.getPaddedSize(542));
+    // IV + PKCS#7-padded ciphertext + HMAC
+    assertEquals(16 + 544 + 32, unpadded.getEncryptedSize(542));
+
+    assertEquals(1024, new BackupPaddingPolicy(2.0, 0).getPaddedSize(542));
+  }
 }
 
 /** Input stream that throws an exception after producing some number of bytes. */
@@ -177,3 +281,75 @@ class ThrowingInputStream extends InputStream {
   private InputStream inner;
   private long bytesToReadBeforeThrowing;
 }
+
+/** Async input stream over an in-memory array, completing each operation immediately. */
+class ByteArrayAsyncInputStream extends AsyncInputStream {
+  public ByteArrayAsyncInputStream(byte[] contents) {
+    this.contents = contents;
+  }
+
+  @Override
+  public CompletableFuture<byte[]> read(int maxLength) {
+    int end = (int) Math.min(contents.length, position + maxLength);
+    byte[] result = Arrays.copyOfRange(contents, position, end);
+    position = end;
+    CompletableFuture<byte[]> future = new CompletableFuture<>();
+    future.complete(result);
+    return future;
+  }
+
+  @Override
+  public CompletableFuture<Void> skip(long amount) {
+    CompletableFuture<Void> future = new CompletableFuture<>();
+    if (amount > contents.length - position) {
+      future.completeExceptionally(new IOException("skipped past the end of the stream"));
+    } else {
+      position += (int) amount;
+      future.complete(null);
+    }
+    return future;
+  }
+
+  private final byte[] contents;
+  private int position = 0;
+}
+
+/** Input stream that pauses before every read. */
+class SlowInputStream extends FilterInputStream {
+  public SlowInputStream(InputStream inner) {
+    super(inner);
+  }
+
+  @Override
+  public int read() throws IOException {
+    pause();
+    return super.read();
+  }
+
+  @Override
+  public int read(byte[] b, int off, int len) throws IOException {
+    pause();
+    return super.read(b, off, len);
+  }
+
+  private static void pause() {
+    try {
+      Thread.sleep(10);
+    } catch (InterruptedException e) {
+      Thread.currentThread().interrupt();
+    }
+  }
+}
+
+/** Async input stream whose operations never complete. */
+class StalledAsyncInputStream extends AsyncInputStream {
+  @Override
+  public CompletableFuture<byte[]> read(int maxLength) {
+    return new CompletableFuture<>();
+  }
+
+  @Override
+  public CompletableFuture<Void> skip(long amount) {
+    return new CompletableFuture<>();
+  }
+}
@@ -9,15 +9,20 @@ import static org.junit.Assert.assertNotEquals;
 
 import java.util.ArrayList;
 import java.util.Arrays;
+import java.util.HashSet;
 import java.util.Optional;
+import java.util.Set;
 import java.util.UUID;
 import junit.framework.TestCase;
 import org.signal.libsignal.metadata.SealedSessionCipher.DecryptionResult;
+import org.signal.libsignal.metadata.SealedSessionCipher.IdentityChange;
+import org.signal.libsignal.metadata.SealedSessionCipher.IdentityChangeDecryptionResult;
 import org.signal.libsignal.metadata.certificate.CertificateValidator;
 import org.signal.libsignal.metadata.certificate.InvalidCertificateException;
 import org.signal.libsignal.metadata.certificate.SenderCertificate;
 import org.signal.libsignal.metadata.certificate.ServerCertificate;
 import org.signal.libsignal.metadata.protocol.UnidentifiedSenderMessageContent;
+import org.signal.libsignal.protocol.IdentityKey;
 import org.signal.libsignal.protocol.IdentityKeyPair;
 import org.signal.libsignal.protocol.InvalidKeyException;
 import org.signal.libsignal.protocol.InvalidMessageException;
@@ -45,6 +50,7 @@ import org.signal.libsignal.protocol.message.SenderKeyDistributionMessage;
 import org.signal.libsignal.protocol.state.KyberPreKeyRecord;
 import org.signal.libsignal.protocol.state.PreKeyBundle;
 import org.signal.libsignal.protocol.state.PreKeyRecord;
+import org.signal.libsignal.protocol.state.ReplayCache;
 import org.signal.libsignal.protocol.state.SessionRecord;
 import org.signal.libsignal.protocol.state.SignedPreKeyRecord;
 import org.signal.libsignal.protocol.util.Hex;
@@ -123,6 +129,121 @@ public class SealedSessionCipherTest extends TestCase {
     assertEquals(plaintext.getDeviceId(), 1);
   }
 
+  public void testDecryptWithReplayCache() throws Exception {
+    TestInMemorySignalProtocolStore aliceStore = new TestInMemorySignalProtocolStore();
+    TestInMemorySignalProtocolStore bobStore = new TestInMemorySignalProtocolStore();
+    SignalProtocolAddress bobAddress = new SignalProtocolAddress("+14152222222", 1);
+
+    initializeSessions(aliceStore, bobStore, bobAddress);
+
+    ECKeyPair trustRoot = Curve.generateKeyPair();
+    SenderCertificate senderCertificate =
+        createCertificateFor(
+            trustRoot,
+            UUID.fromString("9d0652a3-dcc3-4d11-975f-74d61598733f"),
+            "+14151111111",
+            1,
+            aliceStore.getIdentityKeyPair().getPublicKey().getPublicKey(),
+            31337);
+    SealedSessionCipher aliceCipher =
+        new SealedSessionCipher(
+            aliceStore, UUID.fromString("9d0652a3-dcc3-4d11-975f-74d61598733f"), "+14151111111", 1);
+
+    byte[] ciphertext =
+        aliceCipher.encrypt(bobAddress, senderCertificate, "smert za smert".getBytes());
+
+    SealedSessionCipher bobCipher =
+        new SealedSessionCipher(
+            bobStore, UUID.fromString("e80f7bbe-5b94-471e-bd8c-2173654ea3d1"), "+14152222222", 1);
+
+    Set<Integer> seen = new HashSet<>();
+    ReplayCache replayCache =
+        new ReplayCache() {
+          @Override
+          public boolean contains(
+              SignalProtocolAddress address, ECPublicKey senderRatchetKey, int counter) {
+            return seen.contains(counter);
+          }
+
+          @Override
+          public void insert(
+              SignalProtocolAddress address, ECPublicKey senderRatchetKey, int counter) {
+            seen.add(counter);
+          }
+        };
+
+    CertificateValidator validator = new CertificateValidator(trustRoot.getPublicKey());
+    DecryptionResult plaintext = bobCipher.decrypt(validator, ciphertext, 31335, replayCache);
+    assertEquals(new String(plaintext.getPaddedMessage()), "smert za smert");
+    assertEquals(1, seen.size());
+    assertTrue(seen.contains(0));
+
+    try {
+      bobCipher.decrypt(validator, ciphertext, 31335, replayCache);
+      fail("should have been caught by the replay cache");
+    } catch (ProtocolDuplicateMessageException e) {
+      // expected
+    }
+  }
+
+  public void testDecryptCapturingIdentityChange() throws Exception {
+    TestInMemorySignalProtocolStore aliceStore = new TestInMemorySignalProtocolStore();
+    TestInMemorySignalProtocolStore bobStore = new TestInMemorySignalProtocolStore();
+    SignalProtocolAddress bobAddress = new SignalProtocolAddress("+14152222222", 1);
+    SignalProtocolAddress aliceAddress =
+        new SignalProtocolAddress("9d0652a3-dcc3-4d11-975f-74d61598733f", 1);
+
+    initializeSessions(aliceStore, bobStore, bobAddress);
+
+    // Bob remembers a different identity for Alice.
+    IdentityKey previousIdentity = new IdentityKey(Curve.generateKeyPair().getPublicKey());
+    bobStore.saveIdentity(aliceAddress, previousIdentity);
+
+    ECKeyPair trustRoot = Curve.generateKeyPair();
+    SenderCertificate senderCertificate =
+        createCertificateFor(
+            trustRoot,
+            UUID.fromString("9d0652a3-dcc3-4d11-975f-74d61598733f"),
+            "+14151111111",
+            1,
+            aliceStore.getIdentityKeyPair().getPublicKey().getPublicKey(),
+            31337);
+    SealedSessionCipher aliceCipher =
+        new SealedSessionCipher(
+            aliceStore, UUID.fromString("9d0652a3-dcc3-4d11-975f-74d61598733f"), "+14151111111", 1);
+
+    byte[] ciphertext =
+        aliceCipher.encrypt(bobAddress, senderCertificate, "smert za smert".getBytes());
+
+    SealedSessionCipher bobCipher =
+        new SealedSessionCipher(
+            bobStore, UUID.fromString("e80f7bbe-5b94-471e-bd8c-2173654ea3d1"), "+14152222222", 1);
+    CertificateValidator validator = new CertificateValidator(trustRoot.getPublicKey());
+
+    // The regular entry point refuses the new identity...
+    try {
+      bobCipher.decrypt(validator, ciphertext, 31335);
+      fail("should have rejected the changed identity");
+    } catch (ProtocolUntrustedIdentityException e) {
+      // expected
+    }
+
+    // ...but this one decrypts the message and reports the change.
+    IdentityChangeDecryptionResult plaintext =
+        bobCipher.decryptCapturingIdentityChange(validator, ciphertext, 31335);
+    assertEquals(new String(plaintext.getPaddedMessage()), "smert za smert");
+    assertEquals(plaintext.getSenderUuid(), "9d0652a3-dcc3-4d11-975f-74d61598733f");
+    assertEquals(plaintext.getSenderE164().get(), "+14151111111");
+    assertEquals(plaintext.getDeviceId(), 1);
+
+    IdentityChange change = plaintext.getIdentityChange().get();
+    assertEquals(previousIdentity, change.getPrevious());
+    assertEquals(aliceStore.getIdentityKeyPair().getPublicKey(), change.getCurrent());
+
+    // The identity store is left for the app to update.
+    assertEquals(previousIdentity, bobStore.getIdentity(aliceAddress));
+  }
+
   public void testEncryptDecryptUntrusted() throws Exception {
     TestInMemorySignalProtocolStore aliceStore = new TestInMemorySignalProtocolStore();
     TestInMemorySignalProtocolStore bobStore = new TestInMemorySignalProtocolStore();
@@ -313,6 +434,15 @@ public class SealedSessionCipherTest extends TestCase {
     assertEquals(plaintext.getSenderE164().get(), "+14151111111");
     assertEquals(plaintext.getDeviceId(), 1);
     assertTrue(Arrays.equals(plaintext.getGroupId().get(), new byte[] {42, 43}));
+
+    UnidentifiedSenderMessageContent rebuilt =
+        usmcFromAlice.withContentHintAndGroupId(
+            UnidentifiedSenderMessageContent.CONTENT_HINT_RESENDABLE, Optional.empty());
+    assertEquals(
+        rebuilt.getContentHint(), UnidentifiedSenderMessageContent.CONTENT_HINT_RESENDABLE);
+    assertFalse(rebuilt.getGroupId().isPresent());
+    assertTrue(Arrays.equals(rebuilt.getContent(), usmcFromAlice.getContent()));
+    assertEquals(rebuilt.getType(), usmcFromAlice.getType());
   }
 
   public void testEncryptGroupWithBadRegistrationId()
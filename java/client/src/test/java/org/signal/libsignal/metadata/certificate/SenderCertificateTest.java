@@ -5,6 +5,7 @@
 
 package org.signal.libsignal.metadata.certificate;
 
+import java.time.Duration;
 import java.util.Optional;
 import java.util.UUID;
 import junit.framework.TestCase;
@@ -51,6 +52,24 @@ public class SenderCertificateTest extends TestCase {
     }
   }
 
+  public void testNeedsRefresh() throws InvalidCertificateException, InvalidKeyException {
+    ECKeyPair key = Curve.generateKeyPair();
+
+    SenderCertificate senderCertificate =
+        createCertificateFor(
+            trustRoot,
+            UUID.fromString("9d0652a3-dcc3-4d11-975f-74d61598733f"),
+            "+14151111111",
+            31338,
+            key.getPublicKey(),
+            31337);
+
+    assertFalse(senderCertificate.needsRefresh(31337, Duration.ZERO));
+    assertTrue(senderCertificate.needsRefresh(31338, Duration.ZERO));
+    assertFalse(senderCertificate.needsRefresh(30337, Duration.ofSeconds(1)));
+    assertTrue(senderCertificate.needsRefresh(30338, Duration.ofSeconds(1)));
+  }
+
   public void testBadSignature() throws InvalidCertificateException, InvalidKeyException {
     ECKeyPair key = Curve.generateKeyPair();
 
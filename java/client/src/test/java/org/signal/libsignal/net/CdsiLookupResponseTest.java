@@ -9,6 +9,8 @@ import static org.junit.Assert.*;
 
 import java.io.IOException;
 import java.time.Duration;
+import java.util.LinkedHashMap;
+import java.util.List;
 import java.util.Map;
 import java.util.UUID;
 import java.util.concurrent.ExecutionException;
@@ -52,6 +54,43 @@ public class CdsiLookupResponseTest {
     assertEquals(expected, actual);
   }
 
+  @Test
+  public void reconcileAgainstExistingContacts() {
+    ServiceId.Aci aci = new ServiceId.Aci(UUID.fromString(aciUuid));
+    ServiceId.Pni pni = new ServiceId.Pni(UUID.fromString(pniUuid));
+    ServiceId.Pni oldPni = new ServiceId.Pni(new UUID(1, 1));
+    String e164Unregistered = "+18005551013";
+    String e164Missing = "+18005551014";
+
+    CdsiLookupResponse response =
+        new CdsiLookupResponse(
+            Map.of(
+                this.e164Both, new CdsiLookupResponse.Entry(aci, pni),
+                this.e164Pni, new CdsiLookupResponse.Entry(null, pni),
+                e164Unregistered, new CdsiLookupResponse.Entry(null, null)),
+            this.debugPermitsUsed);
+
+    Map<String, CdsiLookupResponse.Entry> existing = new LinkedHashMap<>();
+    existing.put(this.e164Pni, new CdsiLookupResponse.Entry(aci, oldPni));
+    existing.put(e164Unregistered, new CdsiLookupResponse.Entry(aci, oldPni));
+    existing.put(e164Missing, new CdsiLookupResponse.Entry(null, oldPni));
+
+    List<CdsiLookupResponse.ContactChange> changes = response.reconcile(existing);
+    assertEquals(
+        List.of(
+            new CdsiLookupResponse.ContactChange(
+                CdsiLookupResponse.ContactChange.Kind.CHANGED_PNI, this.e164Pni, null, pni),
+            new CdsiLookupResponse.ContactChange(
+                CdsiLookupResponse.ContactChange.Kind.REMOVED, e164Unregistered, null, null),
+            new CdsiLookupResponse.ContactChange(
+                CdsiLookupResponse.ContactChange.Kind.REMOVED, e164Missing, null, null),
+            new CdsiLookupResponse.ContactChange(
+                CdsiLookupResponse.ContactChange.Kind.NEW_ACI, this.e164Both, aci, null),
+            new CdsiLookupResponse.ContactChange(
+                CdsiLookupResponse.ContactChange.Kind.CHANGED_PNI, this.e164Both, null, pni)),
+        changes);
+  }
+
   @Test
   public void cdsiLookupErrorConvert() {
     assertLookupErrorIs(
@@ -69,6 +108,14 @@ public class CdsiLookupResponseTest {
         assertLookupErrorIs(
             "RetryAfter42Seconds", RetryLaterException.class, "Retry after 42 seconds");
     assertEquals(retryLater.duration, Duration.ofSeconds(42));
+    assertEquals(retryLater.scope, RateLimitScope.UNSPECIFIED);
+    assertNull(retryLater.reason);
+    RetryLaterException perNumber =
+        assertLookupErrorIs(
+            "RetryAfter42SecondsPerNumber", RetryLaterException.class, "Retry after 42 seconds");
+    assertEquals(perNumber.duration, Duration.ofSeconds(42));
+    assertEquals(perNumber.scope, RateLimitScope.PER_NUMBER);
+    assertEquals(perNumber.reason, "fake reason");
 
     assertLookupErrorIs(
         "InvalidToken", CdsiInvalidTokenException.class, "Request token was invalid");
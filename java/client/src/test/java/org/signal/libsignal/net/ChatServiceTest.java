@@ -10,10 +10,13 @@ import static org.junit.Assert.*;
 import java.io.IOException;
 import java.nio.charset.StandardCharsets;
 import java.time.Duration;
+import java.util.List;
 import java.util.Map;
+import java.util.UUID;
 import org.junit.Assume;
 import org.junit.Test;
 import org.signal.libsignal.internal.NativeTesting;
+import org.signal.libsignal.protocol.ServiceId;
 import org.signal.libsignal.util.TestEnvironment;
 
 public class ChatServiceTest {
@@ -79,6 +82,8 @@ public class ChatServiceTest {
     assertChatServiceErrorIs("AppExpired", AppExpiredException.class);
     assertChatServiceErrorIs("DeviceDeregistered", DeviceDeregisteredException.class);
     assertChatServiceErrorIs("ServiceInactive", ChatServiceInactiveException.class);
+    assertChatServiceErrorIs("RequestUnauthorized", RequestUnauthorizedException.class);
+    assertChatServiceErrorIs("NotFound", NotFoundException.class);
 
     assertChatServiceErrorIs("WebSocket", ChatServiceException.class);
     assertChatServiceErrorIs("UnexpectedFrameReceived", ChatServiceException.class);
@@ -89,6 +94,33 @@ public class ChatServiceTest {
     RetryLaterException retryLater =
         assertChatServiceErrorIs("RetryAfter42Seconds", RetryLaterException.class);
     assertEquals(retryLater.duration, Duration.ofSeconds(42));
+    assertEquals(retryLater.scope, RateLimitScope.UNSPECIFIED);
+    assertNull(retryLater.reason);
+    RetryLaterException perAccount =
+        assertChatServiceErrorIs("RetryAfter42SecondsPerAccount", RetryLaterException.class);
+    assertEquals(perAccount.scope, RateLimitScope.PER_ACCOUNT);
+    assertEquals(perAccount.reason, "fake reason");
+
+    ChatServiceException unavailable =
+        assertChatServiceErrorIs("ServiceUnavailable", ChatServiceException.class);
+    assertNull(unavailable.disconnectInfo);
+    ChatServiceException serverClosed =
+        assertChatServiceErrorIs(
+            "ServiceUnavailableWithDisconnectInfo", ChatServiceException.class);
+    assertEquals(Integer.valueOf(4000), serverClosed.disconnectInfo.closeCode);
+    assertEquals("go elsewhere", serverClosed.disconnectInfo.reason);
+    assertEquals(List.of("critical-idle-primary-device"), serverClosed.disconnectInfo.alerts);
+    ChatServiceException channelClosed =
+        assertChatServiceErrorIs("ChannelClosedWithDisconnectInfo", ChatServiceException.class);
+    assertEquals(Integer.valueOf(4000), channelClosed.disconnectInfo.closeCode);
+    assertEquals("go elsewhere", channelClosed.disconnectInfo.reason);
+    assertEquals(List.of("critical-idle-primary-device"), channelClosed.disconnectInfo.alerts);
+    ChatServiceException httpError =
+        assertChatServiceErrorIs("HttpErrorWithAlerts", ChatServiceException.class);
+    assertTrue(httpError.getMessage(), httpError.getMessage().contains("503"));
+    assertNull(httpError.disconnectInfo.closeCode);
+    assertNull(httpError.disconnectInfo.reason);
+    assertEquals(List.of("critical-idle-primary-device"), httpError.disconnectInfo.alerts);
 
     // These two are more of internal errors, but they should never happen anyway.
     assertChatServiceErrorIs("FailedToPassMessageToIncomingChannel", ChatServiceException.class);
@@ -124,6 +156,29 @@ public class ChatServiceTest {
                     h -> NativeTesting.TESTING_ChatRequestGetHeaderValue(h, name))));
   }
 
+  @Test
+  public void testReceiptBatching() throws Exception {
+    final Network net = new Network(Network.Environment.STAGING, USER_AGENT);
+    final AuthenticatedChatService chat = net.createAuthChatService("", "", false);
+    final ServiceId.Aci sender = new ServiceId.Aci(UUID.randomUUID());
+
+    chat.queueReceipt(sender, ReceiptBatch.Kind.READ, 30);
+    chat.queueReceipt(sender, ReceiptBatch.Kind.READ, 10);
+    chat.queueReceipt(sender, ReceiptBatch.Kind.DELIVERY, 20);
+    chat.queueReceipt(sender, ReceiptBatch.Kind.READ, 10);
+
+    final ReceiptBatch delivery = chat.nextReceiptBatch();
+    assertEquals(sender, delivery.getSender());
+    assertEquals(ReceiptBatch.Kind.DELIVERY, delivery.getKind());
+    assertArrayEquals(new long[] {20}, delivery.getTimestamps());
+
+    final ReceiptBatch read = chat.nextReceiptBatch();
+    assertEquals(ReceiptBatch.Kind.READ, read.getKind());
+    assertArrayEquals(new long[] {10, 30}, read.getTimestamps());
+
+    assertNull(chat.nextReceiptBatch());
+  }
+
   @Test
   public void testConnectUnauth() throws Exception {
     // Use the presence of the proxy server environment setting to know whether we should make
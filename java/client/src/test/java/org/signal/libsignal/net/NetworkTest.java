@@ -5,6 +5,16 @@
 
 package org.signal.libsignal.net;
 
+import static org.junit.Assert.assertEquals;
+import static org.junit.Assert.assertNull;
+import static org.junit.Assert.assertThrows;
+import static org.junit.Assert.assertTrue;
+
+import java.io.IOException;
+import java.time.Duration;
+import java.time.Instant;
+import java.util.Arrays;
+import java.util.List;
 import org.junit.Test;
 
 public class NetworkTest {
@@ -16,4 +26,71 @@ public class NetworkTest {
     var net = new Network(Network.Environment.STAGING, USER_AGENT);
     net.onNetworkChange();
   }
+
+  @Test
+  public void serverTimeBeforeAnyResponses() {
+    var net = new Network(Network.Environment.STAGING, USER_AGENT);
+    var before = Instant.now().toEpochMilli();
+    var serverTime = net.serverTime().toEpochMilli();
+    var after = Instant.now().toEpochMilli();
+    assertTrue(before <= serverTime && serverTime <= after);
+  }
+
+  @Test
+  public void routeOrder() throws Exception {
+    var key = new byte[32];
+    var net = new Network(Network.Environment.STAGING, USER_AGENT);
+    assertEquals(
+        List.of(Network.Route.DIRECT, Network.Route.PROXY_F, Network.Route.PROXY_G),
+        net.getRouteOrder());
+
+    var order = List.of(Network.Route.PROXY_G, Network.Route.DIRECT);
+    net.setRouteOrder(order);
+    net.setCensorshipCircumventionEnabled(true);
+    assertEquals(order, net.getRouteOrder());
+
+    var restored = new Network(Network.Environment.STAGING, USER_AGENT);
+    restored.importRouteState(key, net.exportRouteState(key));
+    assertEquals(order, restored.getRouteOrder());
+  }
+
+  @Test
+  public void proxy() throws Exception {
+    var net = new Network(Network.Environment.STAGING, USER_AGENT);
+    assertNull(net.getProxy());
+    net.setProxy("proxy.example", 443);
+    assertEquals("proxy.example:443", net.getProxy());
+    net.clearProxy();
+    assertNull(net.getProxy());
+  }
+
+  @Test
+  public void certificatePins() {
+    var net = new Network(Network.Environment.STAGING, USER_AGENT);
+    net.setCertificatePins(Network.Service.CHAT, List.of(new byte[32], new byte[32]));
+    net.setCertificatePins(Network.Service.CHAT, List.of());
+    assertThrows(
+        IllegalArgumentException.class,
+        () -> net.setCertificatePins(Network.Service.CDSI, List.of(new byte[31])));
+  }
+
+  @Test
+  public void cdsiConnectionPool() {
+    // Lookups need a live server, so just check that pools can be made and that an out-of-range
+    // timeout is rejected rather than truncated.
+    new CdsiConnectionPool();
+    new CdsiConnectionPool(Duration.ofSeconds(2));
+    assertThrows(ArithmeticException.class, () -> new CdsiConnectionPool(Duration.ofDays(30)));
+  }
+
+  @Test
+  public void routeStateRoundTrip() throws Exception {
+    var key = new byte[32];
+    Arrays.fill(key, (byte) 0x42);
+    var exported = new Network(Network.Environment.STAGING, USER_AGENT).exportRouteState(key);
+
+    var net = new Network(Network.Environment.STAGING, USER_AGENT);
+    net.importRouteState(key, exported);
+    assertThrows(IOException.class, () -> net.importRouteState(new byte[32], exported));
+  }
 }
@@ -0,0 +1,78 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.net;
+
+import static org.junit.Assert.*;
+
+import java.util.List;
+import java.util.stream.Collectors;
+import org.junit.Test;
+import org.signal.libsignal.protocol.util.Hex;
+
+public class StorageSyncTest {
+  // ManifestRecord.Identifier { raw: aaaa, type: CONTACT }
+  private static final String ID_A = "0a02aaaa1001";
+  // ManifestRecord.Identifier { raw: bbbb, type: CONTACT }
+  private static final String ID_B = "0a02bbbb1001";
+  // ManifestRecord { version: 1, identifiers: [ID_A] }
+  private static final String MANIFEST_WITH_A = "08011206" + ID_A;
+
+  private static byte[] bytes(String hex) {
+    return Hex.fromStringCondensedAssert(hex);
+  }
+
+  private static List<String> hex(List<byte[]> values) {
+    return values.stream().map(Hex::toStringCondensed).collect(Collectors.toList());
+  }
+
+  @Test
+  public void newRecordsSyncBothWays() {
+    StorageSync sync = new StorageSync(new byte[0], bytes(MANIFEST_WITH_A));
+    sync.addLocalRecord(bytes(ID_B), "b".getBytes(), "local record".getBytes());
+    assertEquals(List.of(ID_A), hex(sync.idsToFetch()));
+    sync.addFetchedRecord(bytes(ID_A), "a".getBytes(), "remote record".getBytes());
+
+    StorageSync.Plan plan = sync.plan();
+    assertEquals(List.of(ID_A), hex(plan.localUpsertIds()));
+    assertEquals("remote record", new String(plan.localUpsertData().get(0)));
+    assertEquals(List.of(), plan.localDeletes());
+    assertEquals(List.of(), plan.unknown());
+
+    assertTrue(plan.hasRemoteChanges());
+    assertEquals(List.of(ID_A, ID_B), hex(plan.remoteManifestIds()));
+    assertEquals(List.of(ID_B), hex(plan.remoteInsertIds()));
+    assertEquals("local record", new String(plan.remoteInsertData().get(0)));
+    assertEquals(List.of(), plan.remoteDeletes());
+  }
+
+  @Test
+  public void localDeletionIsDeletedRemotely() {
+    StorageSync sync = new StorageSync(bytes(MANIFEST_WITH_A), bytes(MANIFEST_WITH_A));
+    sync.addLocalDeletion(bytes(ID_A));
+    assertEquals(List.of(), sync.idsToFetch());
+
+    StorageSync.Plan plan = sync.plan();
+    assertEquals(List.of(), plan.localUpsertIds());
+    assertTrue(plan.hasRemoteChanges());
+    assertEquals(List.of(), plan.remoteManifestIds());
+    assertEquals(List.of(ID_A), hex(plan.remoteDeletes()));
+  }
+
+  @Test
+  public void unknownRecordsAreKept() {
+    StorageSync sync = new StorageSync(new byte[0], bytes(MANIFEST_WITH_A));
+    sync.addFetchedUnknown(bytes(ID_A));
+
+    StorageSync.Plan plan = sync.plan();
+    assertEquals(List.of(ID_A), hex(plan.unknown()));
+    assertFalse(plan.hasRemoteChanges());
+  }
+
+  @Test(expected = IllegalArgumentException.class)
+  public void invalidManifest() {
+    new StorageSync(new byte[0], new byte[] {(byte) 0xff});
+  }
+}
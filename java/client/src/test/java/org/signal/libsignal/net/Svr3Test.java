@@ -8,6 +8,7 @@ package org.signal.libsignal.net;
 import static org.junit.Assert.*;
 
 import java.security.SecureRandom;
+import java.util.List;
 import java.util.concurrent.ExecutionException;
 import org.junit.After;
 import org.junit.Assume;
@@ -80,6 +81,37 @@ public class Svr3Test {
     assertEquals(tries - 1, restored.triesRemaining());
   }
 
+  @Test
+  public void backupWithDistributionRecordsLayout() throws Exception {
+    var distribution = new Svr3.ShareDistribution(List.of(3, 1), 2);
+    byte[] shareSet =
+        state
+            .net()
+            .svr3()
+            .backupWithDistribution(STORED_SECRET, TEST_PASSWORD, 10, distribution, state.auth())
+            .get();
+    assertEquals(distribution, Svr3.layoutOf(shareSet));
+    Svr3.RestoredSecret restored =
+        state.net().svr3().restore(TEST_PASSWORD, shareSet, state.auth()).get();
+    assertEquals(Hex.toStringCondensed(STORED_SECRET), Hex.toStringCondensed(restored.value()));
+  }
+
+  @Test
+  public void backupWithUnsupportedThreshold() throws Exception {
+    var distribution = new Svr3.ShareDistribution(List.of(1, 2, 3), 2);
+    try {
+      state
+          .net()
+          .svr3()
+          .backupWithDistribution(STORED_SECRET, TEST_PASSWORD, 10, distribution, state.auth())
+          .get();
+      fail("Must have thrown");
+    } catch (ExecutionException ex) {
+      Throwable cause = ex.getCause();
+      assertTrue("Unexpected exception: " + cause, cause instanceof SvrException);
+    }
+  }
+
   @Test
   public void noRestoreAfterRemove() throws Exception {
     final int tries = 10;
@@ -0,0 +1,42 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.protocol;
+
+import static org.junit.Assert.assertEquals;
+import static org.junit.Assert.assertFalse;
+import static org.junit.Assert.assertNull;
+import static org.junit.Assert.assertThrows;
+import static org.junit.Assert.assertTrue;
+
+import org.junit.Test;
+
+public class E164Test {
+  @Test
+  public void testNormalize() {
+    assertEquals("+18005551001", E164.normalize(" +1 (800) 555-1001 ", 1));
+    assertEquals("+18005551001", E164.normalize("(800) 555-1001", 1));
+    assertEquals("+49301234567", E164.normalize("030 1234567", 49));
+    assertEquals("+49301234567", E164.normalize("0049 30 1234567", 49));
+
+    assertNull(E164.normalize("+1 800 555 1001 ext 2", 1));
+    assertNull(E164.normalize("+1234", 1));
+    assertNull(E164.normalize("", 1));
+  }
+
+  @Test
+  public void testNormalizeInvalidCallingCode() {
+    assertThrows(IllegalArgumentException.class, () -> E164.normalize("800 555 1001", 0));
+    assertThrows(IllegalArgumentException.class, () -> E164.normalize("800 555 1001", 1000));
+  }
+
+  @Test
+  public void testIsValid() {
+    assertTrue(E164.isValid("+18005551001"));
+    assertFalse(E164.isValid("18005551001"));
+    assertFalse(E164.isValid("+1 800 555 1001"));
+    assertFalse(E164.isValid("+1234"));
+  }
+}
@@ -5,6 +5,7 @@
 
 package org.signal.libsignal.protocol;
 
+import static org.junit.Assert.assertArrayEquals;
 import static org.junit.Assert.assertEquals;
 import static org.junit.Assert.assertThrows;
 
@@ -101,6 +102,26 @@ public class ServiceIdTest {
         });
   }
 
+  @Test
+  public void testBatchConversions() throws Exception {
+    List<ServiceId> serviceIds =
+        List.of(new ServiceId.Aci(TEST_UUID), new ServiceId.Pni(TEST_UUID));
+
+    List<byte[]> binaries = ServiceId.toServiceIdBinaries(serviceIds);
+    assertEquals(2, binaries.size());
+    assertArrayEquals(serviceIds.get(0).toServiceIdBinary(), binaries.get(0));
+    assertArrayEquals(serviceIds.get(1).toServiceIdBinary(), binaries.get(1));
+    assertEquals(
+        List.of(serviceIds.get(0).toServiceIdString(), serviceIds.get(1).toServiceIdString()),
+        ServiceId.toServiceIdStrings(serviceIds));
+    assertEquals(serviceIds, ServiceId.parseFromBinaries(binaries));
+    assertEquals(List.of(), ServiceId.toServiceIdStrings(List.of()));
+
+    assertThrows(
+        InvalidServiceIdException.class,
+        () -> ServiceId.parseFromBinaries(List.of(binaries.get(0), new byte[] {(byte) 0xff})));
+  }
+
   @Test
   public void testOrdering() throws Exception {
     // creates an immutabale list
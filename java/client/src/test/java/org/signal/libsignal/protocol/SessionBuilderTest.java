@@ -25,11 +25,13 @@ import org.junit.runners.Parameterized;
 import org.junit.runners.Parameterized.Parameters;
 import org.signal.libsignal.protocol.ecc.Curve;
 import org.signal.libsignal.protocol.ecc.ECKeyPair;
+import org.signal.libsignal.protocol.ecc.ECPublicKey;
 import org.signal.libsignal.protocol.message.CiphertextMessage;
 import org.signal.libsignal.protocol.message.PreKeySignalMessage;
 import org.signal.libsignal.protocol.message.SignalMessage;
 import org.signal.libsignal.protocol.state.IdentityKeyStore;
 import org.signal.libsignal.protocol.state.PreKeyBundle;
+import org.signal.libsignal.protocol.state.ReplayCache;
 import org.signal.libsignal.protocol.state.SessionRecord;
 import org.signal.libsignal.protocol.state.SignalProtocolStore;
 import org.signal.libsignal.protocol.util.Medium;
@@ -247,6 +249,72 @@ public class SessionBuilderTest {
       assertEquals(originalMessage, new String(plaintext));
     }
 
+    @Test
+    public void testReplayCacheRejectsMessageAfterSessionLoss() throws Exception {
+      SignalProtocolStore aliceStore = new TestInMemorySignalProtocolStore();
+      SessionBuilder aliceSessionBuilder = new SessionBuilder(aliceStore, BOB_ADDRESS);
+
+      SignalProtocolStore bobStore = new TestInMemorySignalProtocolStore();
+      PreKeyBundle bobPreKey = bundleFactory.createBundle(bobStore);
+
+      // Without a one-time pre-key, the same message can set up a fresh session a second time.
+      bobPreKey =
+          new PreKeyBundle(
+              bobPreKey.getRegistrationId(),
+              1,
+              -1,
+              null,
+              bobPreKey.getSignedPreKeyId(),
+              bobPreKey.getSignedPreKey(),
+              bobPreKey.getSignedPreKeySignature(),
+              bobPreKey.getIdentityKey(),
+              bobPreKey.getKyberPreKeyId(),
+              bobPreKey.getKyberPreKey(),
+              bobPreKey.getKyberPreKeySignature());
+
+      aliceSessionBuilder.process(bobPreKey);
+
+      String originalMessage = "Good, fast, cheap: pick two";
+      SessionCipher aliceSessionCipher = new SessionCipher(aliceStore, BOB_ADDRESS);
+      CiphertextMessage outgoingMessage = aliceSessionCipher.encrypt(originalMessage.getBytes());
+      PreKeySignalMessage incomingMessage = new PreKeySignalMessage(outgoingMessage.serialize());
+
+      Set<String> seen = new HashSet<>();
+      ReplayCache replayCache =
+          new ReplayCache() {
+            @Override
+            public boolean contains(
+                SignalProtocolAddress address, ECPublicKey senderRatchetKey, int counter) {
+              return seen.contains(key(address, senderRatchetKey, counter));
+            }
+
+            @Override
+            public void insert(
+                SignalProtocolAddress address, ECPublicKey senderRatchetKey, int counter) {
+              seen.add(key(address, senderRatchetKey, counter));
+            }
+
+            private String key(
+                SignalProtocolAddress address, ECPublicKey senderRatchetKey, int counter) {
+              return address + "/" + Arrays.toString(senderRatchetKey.serialize()) + "/" + counter;
+            }
+          };
+
+      SessionCipher bobSessionCipher = new SessionCipher(bobStore, ALICE_ADDRESS);
+      byte[] plaintext = bobSessionCipher.decrypt(incomingMessage, replayCache);
+      assertEquals(originalMessage, new String(plaintext));
+      assertEquals(1, seen.size());
+
+      bobStore.deleteSession(ALICE_ADDRESS);
+      try {
+        bobSessionCipher.decrypt(incomingMessage, replayCache);
+        fail("should have been caught by the replay cache");
+      } catch (DuplicateMessageException e) {
+        // expected
+      }
+      assertFalse(bobStore.containsSession(ALICE_ADDRESS));
+    }
+
     @Test
     public void testExpiresUnacknowledgedSessions()
         throws InvalidKeyException,
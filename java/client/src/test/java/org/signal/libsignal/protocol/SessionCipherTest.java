@@ -16,6 +16,7 @@ import junit.framework.TestCase;
 import org.signal.libsignal.protocol.ecc.Curve;
 import org.signal.libsignal.protocol.ecc.ECKeyPair;
 import org.signal.libsignal.protocol.message.CiphertextMessage;
+import org.signal.libsignal.protocol.message.RatchetContinuity;
 import org.signal.libsignal.protocol.message.SignalMessage;
 import org.signal.libsignal.protocol.state.SessionRecord;
 import org.signal.libsignal.protocol.state.SignalProtocolStore;
@@ -110,6 +111,36 @@ public class SessionCipherTest extends TestCase {
     assertTrue(Arrays.equals(alicePlaintext, bobPlaintext2));
   }
 
+  public void testRatchetContinuity() throws Exception {
+    PairOfSessions sessions = initializeSessionsV3();
+
+    SignalProtocolStore aliceStore = new TestInMemorySignalProtocolStore();
+    SignalProtocolStore bobStore = new TestInMemorySignalProtocolStore();
+
+    SignalProtocolAddress aliceAddress = new SignalProtocolAddress("+14159999999", 1);
+    SignalProtocolAddress bobAddress = new SignalProtocolAddress("+141588888888", 1);
+
+    aliceStore.storeSession(bobAddress, sessions.aliceSession);
+    bobStore.storeSession(aliceAddress, sessions.bobSession);
+
+    SessionCipher aliceCipher = new SessionCipher(aliceStore, bobAddress);
+    SessionCipher bobCipher = new SessionCipher(bobStore, aliceAddress);
+
+    byte[] plaintext = "This is a plaintext message.".getBytes();
+    SignalMessage first = new SignalMessage(aliceCipher.encrypt(plaintext).serialize());
+    SignalMessage second = new SignalMessage(aliceCipher.encrypt(plaintext).serialize());
+    bobCipher.decrypt(first);
+    bobCipher.decrypt(second);
+    aliceCipher.decrypt(new SignalMessage(bobCipher.encrypt(plaintext).serialize()));
+    SignalMessage third = new SignalMessage(aliceCipher.encrypt(plaintext).serialize());
+
+    assertEquals(RatchetContinuity.SAME_CHAIN, second.getRatchetContinuityAfter(first));
+    assertEquals(RatchetContinuity.REORDERED, first.getRatchetContinuityAfter(second));
+    assertEquals(RatchetContinuity.NEXT_CHAIN, third.getRatchetContinuityAfter(second));
+    assertEquals(second.getCounter(), third.getPreviousCounter());
+    assertFalse(third.getRatchetContinuityAfter(second).isSuspicious());
+  }
+
   public void testDecryptAfterDelete() throws Exception {
     PairOfSessions sessions = initializeSessionsV3();
 
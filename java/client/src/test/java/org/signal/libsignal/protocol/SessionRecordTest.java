@@ -5,6 +5,7 @@
 
 package org.signal.libsignal.protocol;
 
+import static org.junit.Assert.assertArrayEquals;
 import static org.junit.Assert.assertEquals;
 import static org.junit.Assert.assertFalse;
 import static org.junit.Assert.assertThrows;
@@ -69,4 +70,24 @@ public class SessionRecordTest {
       assertThrows(InvalidKeyException.class, () -> record.getKeyPair());
     }
   }
+
+  @Test
+  public void testRecordMigration() throws Exception {
+    // Records written before version headers were introduced are bare protobuf messages.
+    // 1: 42
+    final byte[] legacy = Hex.fromStringCondensedAssert("082a");
+    final byte[] current = Hex.fromStringCondensedAssert("0001082a");
+    assertArrayEquals(current, PreKeyRecord.migrateToCurrent(legacy));
+    assertArrayEquals(current, PreKeyRecord.migrateToCurrent(current));
+    assertEquals(42, new PreKeyRecord(legacy).getId());
+    assertEquals(42, new PreKeyRecord(current).getId());
+    // Writing the header is opt-in; serializing still produces the legacy format.
+    assertArrayEquals(legacy, new PreKeyRecord(current).serialize());
+
+    assertArrayEquals(new byte[] {0x00, 0x01}, SessionRecord.migrateToCurrent(new byte[] {}));
+
+    final byte[] tooNew = Hex.fromStringCondensedAssert("0002082a");
+    assertThrows(InvalidMessageException.class, () -> PreKeyRecord.migrateToCurrent(tooNew));
+    assertThrows(InvalidMessageException.class, () -> new PreKeyRecord(tooNew));
+  }
 }
@@ -314,4 +314,31 @@ public class NumericFingerprintGeneratorTest extends TestCase {
     } catch (FingerprintParsingException e) {
     }
   }
+
+  public void testIdentityDigestMatchesFingerprintHalves() throws Exception {
+    IdentityKey aliceIdentityKey = new IdentityKey(ALICE_IDENTITY, 0);
+    IdentityKey bobIdentityKey = new IdentityKey(BOB_IDENTITY, 0);
+    byte[] aliceStableId = "+14152222222".getBytes();
+    byte[] bobStableId = "+14153333333".getBytes();
+
+    IdentityDigest aliceDigest =
+        IdentityDigest.compute(5200, VERSION_2, aliceStableId, aliceIdentityKey);
+    IdentityDigest bobDigest = IdentityDigest.compute(5200, VERSION_2, bobStableId, bobIdentityKey);
+
+    assertEquals(VERSION_2, aliceDigest.getVersion());
+    assertEquals(DISPLAYABLE_FINGERPRINT_V2.substring(0, 30), aliceDigest.getDisplayText());
+    assertEquals(DISPLAYABLE_FINGERPRINT_V2.substring(30), bobDigest.getDisplayText());
+    assertTrue(
+        Arrays.equals(
+            Arrays.copyOfRange(ALICE_SCANNABLE_FINGERPRINT_V2, 6, 38), aliceDigest.getDigest()));
+
+    IdentityDigest roundTripped = new IdentityDigest(VERSION_2, aliceDigest.getDigest());
+    assertEquals(aliceDigest.getDisplayText(), roundTripped.getDisplayText());
+
+    try {
+      new IdentityDigest(VERSION_2, Arrays.copyOf(aliceDigest.getDigest(), 31));
+      throw new AssertionError("Should have thrown");
+    } catch (IllegalArgumentException e) {
+    }
+  }
 }
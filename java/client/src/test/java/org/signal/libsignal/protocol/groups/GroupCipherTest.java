@@ -357,6 +357,34 @@ public class GroupCipherTest {
     }
   }
 
+  @Test
+  public void testDistributionTracking() throws Exception {
+    InMemorySenderKeyStore aliceStore = new InMemorySenderKeyStore();
+    GroupSessionBuilder aliceSessionBuilder = new GroupSessionBuilder(aliceStore);
+
+    SignalProtocolAddress bob = new SignalProtocolAddress("+14151111111", 1);
+    SignalProtocolAddress carol = new SignalProtocolAddress("+14152222222", 1);
+    List<SignalProtocolAddress> members = Arrays.asList(bob, carol);
+
+    assertEquals(
+        members,
+        aliceSessionBuilder.recipientsNeedingDistribution(
+            SENDER_ADDRESS, DISTRIBUTION_ID, members));
+
+    aliceSessionBuilder.create(SENDER_ADDRESS, DISTRIBUTION_ID);
+    aliceSessionBuilder.markDistributed(SENDER_ADDRESS, DISTRIBUTION_ID, Arrays.asList(bob));
+    assertEquals(
+        Arrays.asList(carol),
+        aliceSessionBuilder.recipientsNeedingDistribution(
+            SENDER_ADDRESS, DISTRIBUTION_ID, members));
+
+    aliceSessionBuilder.invalidateDistribution(SENDER_ADDRESS, DISTRIBUTION_ID, Arrays.asList(bob));
+    assertEquals(
+        members,
+        aliceSessionBuilder.recipientsNeedingDistribution(
+            SENDER_ADDRESS, DISTRIBUTION_ID, members));
+  }
+
   private int randomInt() {
     return new SecureRandom().nextInt(Integer.MAX_VALUE);
   }
@@ -0,0 +1,16 @@
+//
+// Copyright 2026 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.protocol.state;
+
+import org.junit.Test;
+import org.signal.libsignal.protocol.state.impl.InMemorySignalProtocolStore;
+
+public class StoreConformanceTest {
+  @Test
+  public void testInMemoryStoreConforms() {
+    StoreConformance.checkAll(InMemorySignalProtocolStore::new);
+  }
+}
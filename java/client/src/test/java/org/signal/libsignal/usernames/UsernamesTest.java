@@ -16,6 +16,7 @@ import static org.junit.Assert.fail;
 import java.security.SecureRandom;
 import java.util.Arrays;
 import java.util.List;
+import java.util.UUID;
 import java.util.stream.Collectors;
 import java.util.stream.Stream;
 import org.junit.Test;
@@ -208,4 +209,33 @@ public class UsernamesTest {
       // this is fine
     }
   }
+
+  @Test
+  public void testUsernameLinkUrl() throws BaseUsernameException {
+    final String payload = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8AESIzRFVmd4iZqrvM3e7_";
+    final byte[] expectedEntropy = new byte[32];
+    for (int i = 0; i < expectedEntropy.length; i++) {
+      expectedEntropy[i] = (byte) i;
+    }
+    final UUID expectedServerId = UUID.fromString("00112233-4455-6677-8899-aabbccddeeff");
+
+    for (String url :
+        new String[] {"https://signal.me/#eu/" + payload, "sgnl://signal.me/#eu/" + payload}) {
+      final Username.UsernameLinkUrl linkUrl = Username.UsernameLinkUrl.fromUrl(url);
+      assertArrayEquals(expectedEntropy, linkUrl.getEntropy());
+      assertEquals(expectedServerId, linkUrl.getServerId());
+    }
+  }
+
+  @Test
+  public void testUsernameLinkUrlFailures() {
+    assertThrows(
+        UsernameLinkInvalidLinkData.class,
+        () -> Username.UsernameLinkUrl.fromUrl("https://signal.org/#eu/AAECAwQF"));
+    assertThrows(
+        UsernameLinkInvalidEntropyDataLength.class,
+        () ->
+            Username.UsernameLinkUrl.fromUrl(
+                "https://signal.me/#eu/AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8"));
+  }
 }
@@ -5,6 +5,7 @@
 
 package org.signal.libsignal.zkgroup.integrationtests;
 
+import static org.junit.Assert.assertArrayEquals;
 import static org.junit.Assert.assertEquals;
 import static org.junit.Assert.fail;
 
@@ -175,6 +176,15 @@ public final class CallLinksTest extends SecureRandomTest {
     }
   }
 
+  @Test
+  public void testRoomIdKnownAnswer() {
+    byte[] rootKey = Hex.fromStringCondensedAssert("000102030405060708090a0b0c0d0e0f");
+    assertArrayEquals(
+        Hex.fromStringCondensedAssert(
+            "cd415ffc58771108cdbf029b8d86ee857be167bf28dda87e3e592e3f10eb1c9b"),
+        CallLinkSecretParams.deriveRoomId(rootKey));
+  }
+
   @Test
   public void testCallLinkAuthIntegration()
       throws InvalidInputException, VerificationFailedException {
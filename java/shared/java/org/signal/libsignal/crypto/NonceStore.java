@@ -0,0 +1,29 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.crypto;
+
+import java.io.IOException;
+import org.signal.libsignal.internal.CalledFromNative;
+
+/**
+ * Durable storage for a {@link NonceSequence}'s progress.
+ *
+ * <p>Nonces are reserved in batches before they are used, so that a crash can skip nonces but never
+ * repeat one.
+ */
+@CalledFromNative
+public interface NonceStore {
+  /**
+   * Record that every counter below {@code limit} may have been handed out.
+   *
+   * <p>This must not return until {@code limit} would survive a crash. After a restart, pass the
+   * last recorded limit to the {@link NonceSequence} constructor. If this throws, no nonce is
+   * produced and the exception is rethrown to the caller.
+   *
+   * @param limit the new reservation limit.
+   */
+  public void reserveUpTo(long limit) throws IOException;
+}
@@ -0,0 +1,71 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.internal;
+
+/**
+ * An input stream whose reads complete asynchronously.
+ *
+ * <p>Unlike {@link java.io.InputStream}, which libsignal reads synchronously, operations on this
+ * stream return immediately and deliver their results later, so no thread has to wait on slow I/O
+ * while libsignal is processing the input.
+ */
+public abstract class AsyncInputStream {
+  /**
+   * Reads up to {@code maxLength} bytes.
+   *
+   * <p>The returned array may be shorter than requested for any reason; an empty array indicates
+   * that the end of the stream has been reached. It must never be longer than {@code maxLength}.
+   */
+  public abstract CompletableFuture<byte[]> read(int maxLength);
+
+  /**
+   * Skips exactly {@code amount} bytes.
+   *
+   * <p>If fewer bytes could be skipped, including because the end of the stream was reached, the
+   * returned future must complete exceptionally.
+   */
+  public abstract CompletableFuture<Void> skip(long amount);
+
+  @CalledFromNative
+  private void readAsync(int maxLength, long completion) {
+    CompletableFuture<byte[]> result;
+    try {
+      result = read(maxLength);
+    } catch (Throwable t) {
+      result = failed(t);
+    }
+    result.whenComplete((data, error) -> finish(completion, data, error));
+  }
+
+  @CalledFromNative
+  private void skipAsync(long amount, long completion) {
+    CompletableFuture<Void> result;
+    try {
+      result = skip(amount);
+    } catch (Throwable t) {
+      result = failed(t);
+    }
+    result.whenComplete((ignored, error) -> finish(completion, new byte[0], error));
+  }
+
+  private static <T> CompletableFuture<T> failed(Throwable t) {
+    CompletableFuture<T> result = new CompletableFuture<>();
+    result.completeExceptionally(t);
+    return result;
+  }
+
+  private static void finish(long completion, byte[] data, Throwable error) {
+    try {
+      if (error != null) {
+        Native.AsyncInputStreamCompletion_Fail(completion, String.valueOf(error));
+      } else {
+        Native.AsyncInputStreamCompletion_Complete(completion, data);
+      }
+    } finally {
+      Native.AsyncInputStreamCompletion_Destroy(completion);
+    }
+  }
+}
@@ -0,0 +1,42 @@
+//
+// Copyright 2026 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.internal;
+
+/**
+ * Thrown when loading a libsignal native library that was built from different Rust code than the
+ * Java code using it.
+ *
+ * <p>This usually means the jar and the native library come from different libsignal versions. It
+ * is thrown while {@link Native} is being initialized, so later uses of libsignal in the same
+ * process will fail with {@link NoClassDefFoundError}.
+ */
+public class BridgeManifestMismatchError extends LinkageError {
+  private final String expectedSha256;
+  private final String loadedSha256;
+
+  BridgeManifestMismatchError(String expectedSha256, String loadedSha256) {
+    super(
+        "libsignal native library does not match its Java code: expected bridge manifest "
+            + expectedSha256
+            + ", but the loaded library has "
+            + (loadedSha256 != null ? loadedSha256 : "no bridge manifest"));
+    this.expectedSha256 = expectedSha256;
+    this.loadedSha256 = loadedSha256;
+  }
+
+  /** The hash of the bridge manifest the Java code was generated from. */
+  public String getExpectedSha256() {
+    return expectedSha256;
+  }
+
+  /**
+   * The hash of the bridge manifest the loaded library was built from, or {@code null} if the
+   * library is too old to report one.
+   */
+  public String getLoadedSha256() {
+    return loadedSha256;
+  }
+}
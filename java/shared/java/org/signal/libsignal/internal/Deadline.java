@@ -0,0 +1,34 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.internal;
+
+import java.time.Duration;
+
+/** Converts timeouts for bridge functions that take a deadline. */
+public abstract class Deadline {
+  /**
+   * Converts {@code timeout} to whole milliseconds, rounding up.
+   *
+   * <p>A nonzero timeout shorter than a millisecond must not become zero, which the bridge treats
+   * as "no deadline". Timeouts too long to represent saturate instead.
+   *
+   * @throws IllegalArgumentException if {@code timeout} is negative
+   */
+  public static long timeoutMillis(Duration timeout) {
+    if (timeout.isNegative()) {
+      throw new IllegalArgumentException("timeout must not be negative");
+    }
+    try {
+      long millis = timeout.toMillis();
+      if (timeout.getNano() % 1_000_000 != 0) {
+        millis = Math.addExact(millis, 1);
+      }
+      return millis;
+    } catch (ArithmeticException e) {
+      return Long.MAX_VALUE;
+    }
+  }
+}
@@ -7,12 +7,14 @@
 
 package org.signal.libsignal.internal;
 
+import org.signal.libsignal.crypto.NonceStore;
 import org.signal.libsignal.protocol.message.CiphertextMessage;
 import org.signal.libsignal.protocol.state.IdentityKeyStore;
 import org.signal.libsignal.protocol.state.SessionStore;
 import org.signal.libsignal.protocol.state.PreKeyStore;
 import org.signal.libsignal.protocol.state.SignedPreKeyStore;
 import org.signal.libsignal.protocol.state.KyberPreKeyStore;
+import org.signal.libsignal.protocol.state.ReplayCache;
 import org.signal.libsignal.protocol.groups.state.SenderKeyStore;
 import org.signal.libsignal.protocol.logging.Log;
 import org.signal.libsignal.protocol.logging.SignalProtocolLogger;
@@ -28,6 +30,7 @@ import java.nio.file.Path;
 import java.util.concurrent.Future;
 import java.util.UUID;
 import java.util.Map;
+import org.jetbrains.annotations.Nullable;
 
 public final class Native {
   private static Path tempDir;
@@ -93,8 +96,35 @@ public final class Native {
     }
   }
 
+  /**
+   * The hash of the bridge manifest (rust/bridge/shared/bridge_manifest.json) that this class was
+   * generated from.
+   */
+  private static final String BRIDGE_MANIFEST_SHA256 = "e7779331df4cd98645e8751c50d258f9017c203201d21b5fad3a8432a4db3e6a";
+
+  /**
+   * Makes sure the library that was just loaded was built from the same Rust code as this class.
+   *
+   * Otherwise calls could end up with mismatched arguments, or with methods missing entirely.
+   *
+   * @throws BridgeManifestMismatchError if the library doesn't match
+   */
+  private static void checkBridgeManifest() {
+    String loadedSha256;
+    try {
+      loadedSha256 = BridgeManifest_GetSha256();
+    } catch (UnsatisfiedLinkError e) {
+      // The library predates bridge manifests.
+      loadedSha256 = null;
+    }
+    if (!BRIDGE_MANIFEST_SHA256.equals(loadedSha256)) {
+      throw new BridgeManifestMismatchError(BRIDGE_MANIFEST_SHA256, loadedSha256);
+    }
+  }
+
   static {
     loadNativeCode();
+    checkBridgeManifest();
     initializeLibrary();
   }
 
@@ -130,7 +160,9 @@ public final class Native {
 
   public static native byte[] Aes256GcmEncryption_ComputeTag(long gcm);
   public static native void Aes256GcmEncryption_Destroy(long handle);
+  public static native byte[] Aes256GcmEncryption_GetNonce(long gcm);
   public static native long Aes256GcmEncryption_New(byte[] key, byte[] nonce, byte[] associatedData) throws Exception;
+  public static native long Aes256GcmEncryption_NewWithNonceSequence(long nonces, NonceStore nonceStore, byte[] associatedData) throws Exception;
   public static native void Aes256GcmEncryption_Update(long gcm, byte[] data, int offset, int length);
 
   public static native byte[] Aes256GcmSiv_Decrypt(long aesGcmSiv, byte[] ctext, byte[] nonce, byte[] associatedData) throws Exception;
@@ -138,12 +170,16 @@ public final class Native {
   public static native byte[] Aes256GcmSiv_Encrypt(long aesGcmSivObj, byte[] ptext, byte[] nonce, byte[] associatedData) throws Exception;
   public static native long Aes256GcmSiv_New(byte[] key) throws Exception;
 
+  public static native void AsyncInputStreamCompletion_Complete(long completion, byte[] data);
+  public static native void AsyncInputStreamCompletion_Destroy(long handle);
+  public static native void AsyncInputStreamCompletion_Fail(long completion, String message);
+
   public static native Object AsyncLoadClass(Object tokioContext, String className);
 
   public static native void AuthChat_Destroy(long handle);
 
   public static native void AuthCredentialPresentation_CheckValidContents(byte[] presentationBytes) throws Exception;
-  public static native byte[] AuthCredentialPresentation_GetPniCiphertext(byte[] presentationBytes);
+  public static native @Nullable byte[] AuthCredentialPresentation_GetPniCiphertext(byte[] presentationBytes);
   public static native long AuthCredentialPresentation_GetRedemptionTime(byte[] presentationBytes);
   public static native byte[] AuthCredentialPresentation_GetUuidCiphertext(byte[] presentationBytes);
 
@@ -180,6 +216,18 @@ public final class Native {
   public static native byte[] BackupKey_DeriveMediaId(byte[] backupKey, String mediaName);
   public static native byte[] BackupKey_DeriveThumbnailTransitEncryptionKey(byte[] backupKey, byte[] mediaId);
 
+  public static native long BackupPaddingPolicy_Default();
+  public static native void BackupPaddingPolicy_Destroy(long handle);
+  public static native long BackupPaddingPolicy_GetEncryptedSize(long policy, long compressedLen);
+  public static native long BackupPaddingPolicy_GetPaddedSize(long policy, long compressedLen);
+  public static native long BackupPaddingPolicy_New(int growthFactorPercent, long minSize);
+
+  public static native String BridgeManifest_GetSha256();
+
+  public static native void BridgeMetrics_Reset();
+  public static native void BridgeMetrics_SetEnabled(boolean enabled);
+  public static native String BridgeMetrics_Snapshot();
+
   public static native void CallLinkAuthCredentialPresentation_CheckValidContents(byte[] presentationBytes) throws Exception;
   public static native byte[] CallLinkAuthCredentialPresentation_GetUserId(byte[] presentationBytes);
   public static native void CallLinkAuthCredentialPresentation_Verify(byte[] presentationBytes, long now, byte[] serverParamsBytes, byte[] callLinkParamsBytes) throws Exception;
@@ -196,17 +244,34 @@ public final class Native {
   public static native void CallLinkSecretParams_CheckValidContents(byte[] paramsBytes) throws Exception;
   public static native byte[] CallLinkSecretParams_DecryptUserId(byte[] paramsBytes, byte[] userId) throws Exception;
   public static native byte[] CallLinkSecretParams_DeriveFromRootKey(byte[] rootKey);
+  public static native byte[] CallLinkSecretParams_DeriveRoomId(byte[] rootKey);
   public static native byte[] CallLinkSecretParams_GetPublicParams(byte[] paramsBytes);
 
   public static native long Cds2ClientState_New(byte[] mrenclave, byte[] attestationMsg, long currentTimestamp) throws Exception;
 
   public static native Map Cds2Metrics_extract(byte[] attestationMsg) throws Exception;
 
+  public static native void CdsiConnectionPool_Destroy(long handle);
+  public static native long CdsiConnectionPool_new(int idleTimeoutMillis);
+
+  public static native int CdsiContactChanges_Count(long changes);
+  public static native void CdsiContactChanges_Destroy(long handle);
+  public static native @Nullable String CdsiContactChanges_GetAci(long changes, int index);
+  public static native String CdsiContactChanges_GetE164(long changes, int index);
+  public static native int CdsiContactChanges_GetKind(long changes, int index);
+  public static native @Nullable String CdsiContactChanges_GetPni(long changes, int index);
+
   public static native void CdsiLookup_Destroy(long handle);
   public static native CompletableFuture<Object> CdsiLookup_complete(long asyncRuntime, long lookup);
   public static native CompletableFuture<Long> CdsiLookup_new(long asyncRuntime, long connectionManager, String username, String password, long request);
+  public static native CompletableFuture<Long> CdsiLookup_newPooled(long asyncRuntime, long connectionManager, String username, String password, long request, long pool, boolean prepareNext);
   public static native byte[] CdsiLookup_token(long lookup);
 
+  public static native CompletableFuture<byte[]> ChatService_auth_fetch_profile_key_credential(long asyncRuntime, long chat, long serverPublicParams, byte[] randomness, byte[] aci, byte[] profileKey, long currentTimeInSeconds, int timeoutMillis);
+  public static native CompletableFuture<Long> ChatService_auth_fetch_sender_certificate(long asyncRuntime, long chat, boolean includeE164, int timeoutMillis);
+  public static native long ChatService_auth_next_receipt_batch(long chat);
+  public static native void ChatService_auth_queue_receipt(long chat, byte[] sender, int kind, long messageTimestamp);
+  public static native CompletableFuture<byte[]> ChatService_auth_search_key_transparency(long asyncRuntime, long chat, long cache, byte[] signatureKey, byte[] vrfKey, byte[] auditorKey, byte[] searchKey, int timeoutMillis);
   public static native CompletableFuture<Object> ChatService_auth_send(long asyncRuntime, long chat, long httpRequest, int timeoutMillis);
   public static native CompletableFuture<Object> ChatService_auth_send_and_debug(long asyncRuntime, long chat, long httpRequest, int timeoutMillis);
   public static native CompletableFuture<Object> ChatService_connect_auth(long asyncRuntime, long chat);
@@ -215,15 +280,26 @@ public final class Native {
   public static native CompletableFuture ChatService_disconnect_unauth(long asyncRuntime, long chat);
   public static native long ChatService_new_auth(long connectionManager, String username, String password, boolean receiveStories);
   public static native long ChatService_new_unauth(long connectionManager);
+  public static native CompletableFuture<Object> ChatService_prepare_for_background_auth(long asyncRuntime, long chat, int budgetMillis);
+  public static native CompletableFuture<Object> ChatService_prepare_for_background_unauth(long asyncRuntime, long chat, int budgetMillis);
   public static native CompletableFuture<Object> ChatService_unauth_send(long asyncRuntime, long chat, long httpRequest, int timeoutMillis);
   public static native CompletableFuture<Object> ChatService_unauth_send_and_debug(long asyncRuntime, long chat, long httpRequest, int timeoutMillis);
 
   public static native void ConnectionManager_Destroy(long handle);
   public static native void ConnectionManager_clear_proxy(long connectionManager);
+  public static native byte[] ConnectionManager_export_route_state(long connectionManager, byte[] key);
+  public static native @Nullable String ConnectionManager_get_proxy(long connectionManager);
+  public static native byte[] ConnectionManager_get_route_order(long connectionManager);
+  public static native void ConnectionManager_import_route_state(long connectionManager, byte[] key, byte[] state) throws Exception;
   public static native long ConnectionManager_new(int environment, String userAgent);
   public static native void ConnectionManager_on_network_change(long connectionManager);
+  public static native long ConnectionManager_server_time(long connectionManager);
   public static native void ConnectionManager_set_censorship_circumvention_enabled(long connectionManager, boolean enabled);
+  public static native void ConnectionManager_set_certificate_pins(long connectionManager, int service, byte[] pins) throws Exception;
+  public static native void ConnectionManager_set_chat_websocket_limits(long connectionManager, int maxFrameSize, int maxMessageSize, int maxPendingSendBytes);
   public static native void ConnectionManager_set_proxy(long connectionManager, String host, int port) throws Exception;
+  public static native void ConnectionManager_set_retry_policy(long connectionManager, int service, int initialDelayMillis, int multiplierPercent, int jitterPercent, int maxDelayMillis, int maxAttempts);
+  public static native void ConnectionManager_set_route_order(long connectionManager, byte[] order) throws Exception;
 
   public static native void CreateCallLinkCredentialPresentation_CheckValidContents(byte[] presentationBytes) throws Exception;
   public static native void CreateCallLinkCredentialPresentation_Verify(byte[] presentationBytes, byte[] roomId, long now, byte[] serverParamsBytes, byte[] callLinkParamsBytes) throws Exception;
@@ -266,9 +342,23 @@ public final class Native {
   public static native byte[] DecryptionErrorMessage_GetSerialized(long obj) throws Exception;
   public static native long DecryptionErrorMessage_GetTimestamp(long obj) throws Exception;
 
+  public static native void DeviceTransferSession_ConfirmVerificationCode(long session) throws Exception;
+  public static native void DeviceTransferSession_Destroy(long handle);
+  public static native void DeviceTransferSession_Finish(long session) throws Exception;
+  public static native long DeviceTransferSession_ForNewDevice(byte[] localCertificate) throws Exception;
+  public static native long DeviceTransferSession_ForOldDevice(byte[] localCertificate, long payloadLength) throws Exception;
+  public static native long DeviceTransferSession_GetBytesTransferred(long session);
+  public static native long DeviceTransferSession_GetPayloadLength(long session) throws Exception;
+  public static native int DeviceTransferSession_GetState(long session);
+  public static native String DeviceTransferSession_GetVerificationCode(long session) throws Exception;
+  public static native byte[] DeviceTransferSession_Receive(long session, byte[] data) throws Exception;
+  public static native void DeviceTransferSession_SendPayloadChunk(long session, byte[] chunk) throws Exception;
+  public static native byte[] DeviceTransferSession_TakeOutgoing(long session);
   public static native byte[] DeviceTransfer_GenerateCertificate(byte[] privateKey, String name, int daysToExpire) throws Exception;
   public static native byte[] DeviceTransfer_GeneratePrivateKey();
 
+  public static native boolean E164_IsValid(String e164);
+  public static native @Nullable String E164_Normalize(String raw, int regionCallingCode) throws Exception;
   public static native byte[] ECPrivateKey_Agree(long privateKey, long publicKey) throws Exception;
   public static native long ECPrivateKey_Deserialize(byte[] data) throws Exception;
   public static native void ECPrivateKey_Destroy(long handle);
@@ -327,32 +417,43 @@ public final class Native {
   public static native void GroupSendEndorsementsResponse_CheckValidContents(byte[] bytes) throws Exception;
   public static native long GroupSendEndorsementsResponse_GetExpiration(byte[] responseBytes);
   public static native byte[] GroupSendEndorsementsResponse_IssueDeterministic(byte[] concatenatedGroupMemberCiphertexts, byte[] keyPair, byte[] randomness);
-  public static native byte[][] GroupSendEndorsementsResponse_ReceiveAndCombineWithCiphertexts(byte[] responseBytes, byte[] concatenatedGroupMemberCiphertexts, byte[] localUserCiphertext, long now, long serverParams) throws Exception;
-  public static native byte[][] GroupSendEndorsementsResponse_ReceiveAndCombineWithServiceIds(byte[] responseBytes, byte[] groupMembers, byte[] localUser, long now, byte[] groupParams, long serverParams) throws Exception;
+  public static native byte[][] GroupSendEndorsementsResponse_ReceiveAndCombineWithCiphertexts(byte[] responseBytes, byte[] concatenatedGroupMemberCiphertexts, byte[] localUserCiphertext, long now, long serverParams, long deadline) throws Exception;
+  public static native byte[][] GroupSendEndorsementsResponse_ReceiveAndCombineWithServiceIds(byte[] responseBytes, byte[] groupMembers, byte[] localUser, long now, byte[] groupParams, long serverParams, long deadline) throws Exception;
 
   public static native void GroupSendFullToken_CheckValidContents(byte[] bytes) throws Exception;
+  public static native byte[] GroupSendFullToken_FromCompact(byte[] compactToken) throws Exception;
   public static native long GroupSendFullToken_GetExpiration(byte[] token);
+  public static native byte[] GroupSendFullToken_ToCompact(byte[] token) throws Exception;
   public static native void GroupSendFullToken_Verify(byte[] token, byte[] userIds, long now, byte[] keyPair) throws Exception;
 
   public static native void GroupSendToken_CheckValidContents(byte[] bytes) throws Exception;
   public static native byte[] GroupSendToken_ToFullToken(byte[] token, long expiration);
 
   public static native long GroupSessionBuilder_CreateSenderKeyDistributionMessage(long sender, UUID distributionId, SenderKeyStore store) throws Exception;
+  public static native void GroupSessionBuilder_InvalidateDistribution(long sender, UUID distributionId, long[] recipients, SenderKeyStore store) throws Exception;
+  public static native void GroupSessionBuilder_MarkDistributed(long sender, UUID distributionId, long[] recipients, SenderKeyStore store) throws Exception;
   public static native void GroupSessionBuilder_ProcessSenderKeyDistributionMessage(long sender, long senderKeyDistributionMessage, SenderKeyStore store) throws Exception;
+  public static native byte[] GroupSessionBuilder_RecipientsNeedingDistribution(long sender, UUID distributionId, long[] recipients, SenderKeyStore store) throws Exception;
 
-  public static native byte[] HKDF_DeriveSecrets(int outputLength, byte[] ikm, byte[] label, byte[] salt) throws Exception;
+  public static native byte[] HKDF_DeriveSecrets(int outputLength, byte[] ikm, @Nullable byte[] label, @Nullable byte[] salt) throws Exception;
 
   public static native void HsmEnclaveClient_CompleteHandshake(long cli, byte[] handshakeReceived) throws Exception;
   public static native void HsmEnclaveClient_Destroy(long handle);
+  public static native void HsmEnclaveClient_EnablePipelining(long cli, int maxInFlight) throws Exception;
   public static native byte[] HsmEnclaveClient_EstablishedRecv(long cli, byte[] receivedCiphertext) throws Exception;
   public static native byte[] HsmEnclaveClient_EstablishedSend(long cli, byte[] plaintextToSend) throws Exception;
+  public static native int HsmEnclaveClient_InFlight(long cli) throws Exception;
   public static native byte[] HsmEnclaveClient_InitialRequest(long obj) throws Exception;
+  public static native long HsmEnclaveClient_LastReceivedRequestId(long cli) throws Exception;
+  public static native long HsmEnclaveClient_LastSentRequestId(long cli) throws Exception;
   public static native long HsmEnclaveClient_New(byte[] trustedPublicKey, byte[] trustedCodeHashes) throws Exception;
 
   public static native void HttpRequest_Destroy(long handle);
   public static native void HttpRequest_add_header(long request, String name, String value);
-  public static native long HttpRequest_new(String method, String path, byte[] bodyAsSlice) throws Exception;
+  public static native long HttpRequest_new(String method, String path, @Nullable byte[] bodyAsSlice) throws Exception;
 
+  public static native byte[] IdentityDigest_Compute(int iterations, int version, byte[] stableIdentifier, long identityKey) throws Exception;
+  public static native String IdentityDigest_DisplayString(int version, byte[] digest) throws Exception;
   public static native long[] IdentityKeyPair_Deserialize(byte[] data);
   public static native byte[] IdentityKeyPair_Serialize(long publicKey, long privateKey);
   public static native byte[] IdentityKeyPair_SignAlternateIdentity(long publicKey, long privateKey, long otherIdentity) throws Exception;
@@ -365,6 +466,9 @@ public final class Native {
   public static native long IncrementalMac_Initialize(byte[] key, int chunkSize);
   public static native byte[] IncrementalMac_Update(long mac, byte[] bytes, int offset, int length);
 
+  public static native void KeyTransparencySearchCache_Destroy(long handle);
+  public static native long KeyTransparencySearchCache_New(int ttlSeconds, int maxEntries);
+
   public static native void KyberKeyPair_Destroy(long handle);
   public static native long KyberKeyPair_Generate();
   public static native long KyberKeyPair_GetPublicKey(long keyPair);
@@ -379,6 +483,7 @@ public final class Native {
   public static native byte[] KyberPreKeyRecord_GetSerialized(long obj) throws Exception;
   public static native byte[] KyberPreKeyRecord_GetSignature(long obj) throws Exception;
   public static native long KyberPreKeyRecord_GetTimestamp(long obj) throws Exception;
+  public static native byte[] KyberPreKeyRecord_MigrateToCurrent(byte[] data) throws Exception;
   public static native long KyberPreKeyRecord_New(int id, long timestamp, long keyPair, byte[] signature);
 
   public static native long KyberPublicKey_DeserializeWithOffset(byte[] data, int offset) throws Exception;
@@ -397,10 +502,13 @@ public final class Native {
   public static native void LookupRequest_addAciAndAccessKey(long request, byte[] aci, byte[] accessKey) throws Exception;
   public static native void LookupRequest_addE164(long request, String e164);
   public static native void LookupRequest_addPreviousE164(long request, String e164);
+  public static native String[] LookupRequest_addRawE164s(long request, ByteBuffer[] raw, int regionCallingCode) throws Exception;
   public static native long LookupRequest_new();
   public static native void LookupRequest_setReturnAcisWithoutUaks(long request, boolean returnAcisWithoutUaks);
   public static native void LookupRequest_setToken(long request, byte[] token);
 
+  public static native long LookupResponse_reconcile(byte[] existing, byte[] response) throws Exception;
+
   public static native void MessageBackupKey_Destroy(long handle);
   public static native long MessageBackupKey_FromAccountEntropyPool(String accountEntropy, byte[] aci);
   public static native long MessageBackupKey_FromBackupKeyAndBackupId(byte[] backupKey, byte[] backupId);
@@ -408,15 +516,34 @@ public final class Native {
   public static native byte[] MessageBackupKey_GetAesKey(long key);
   public static native byte[] MessageBackupKey_GetHmacKey(long key);
 
-  public static native Object MessageBackupValidator_Validate(long key, InputStream firstStream, InputStream secondStream, long len, int purpose) throws Exception;
+  public static native Object MessageBackupValidator_Validate(long key, InputStream firstStream, InputStream secondStream, long len, int purpose, long deadline) throws Exception;
+  public static native CompletableFuture<Object> MessageBackupValidator_ValidateAsync(long asyncRuntime, long key, AsyncInputStream firstStream, AsyncInputStream secondStream, long len, int purpose, long deadline);
+
+  public static native long MessageBackup_GetEncryptedSize(long compressedLen);
+  public static native long MessageBackup_GetPaddedSize(long compressedLen);
+
+  public static native void Mp4SanitizerOptions_Destroy(long handle);
+  public static native long Mp4SanitizerOptions_New();
+  public static native void Mp4SanitizerOptions_SetCodecPolicy(long options, boolean allowHevc, boolean allowAv1, boolean allowOpus);
+  public static native void Mp4SanitizerOptions_SetMetadataBoxPreserved(long options, byte[] boxType, boolean preserved);
+  public static native void Mp4SanitizerOptions_SetMetadataStripped(long options, boolean stripped);
 
   public static native long Mp4Sanitizer_Sanitize(InputStream input, long len) throws Exception;
+  public static native CompletableFuture<Long> Mp4Sanitizer_SanitizeAsync(long asyncRuntime, AsyncInputStream input, long len);
+  public static native long Mp4Sanitizer_SanitizeWithOptions(InputStream input, long len, long options) throws Exception;
+  public static native void Mp4Sanitizer_SetMaxPooledBufferBytes(long maxBytes);
+
+  public static native void NonceSequence_Destroy(long handle);
+  public static native long NonceSequence_New(byte[] key, byte[] prefix, long persistedLimit, long limit) throws Exception;
+  public static native long NonceSequence_Remaining(long nonces);
 
   public static native void NumericFingerprintGenerator_Destroy(long handle);
   public static native String NumericFingerprintGenerator_GetDisplayString(long obj) throws Exception;
   public static native byte[] NumericFingerprintGenerator_GetScannableEncoding(long obj) throws Exception;
   public static native long NumericFingerprintGenerator_New(int iterations, int version, byte[] localIdentifier, byte[] localKey, byte[] remoteIdentifier, byte[] remoteKey) throws Exception;
 
+  public static native void ParseLimits_Set(long maxMessageSize, long maxCollectionCount, long maxDecompressedSize, long maxMediaMetadataSize, long maxRetainedSize);
+
   public static native byte[] PinHash_AccessKey(long ph);
   public static native void PinHash_Destroy(long handle);
   public static native byte[] PinHash_EncryptionKey(long ph);
@@ -453,6 +580,7 @@ public final class Native {
   public static native long PreKeyRecord_GetPrivateKey(long obj) throws Exception;
   public static native long PreKeyRecord_GetPublicKey(long obj) throws Exception;
   public static native byte[] PreKeyRecord_GetSerialized(long obj) throws Exception;
+  public static native byte[] PreKeyRecord_MigrateToCurrent(byte[] data) throws Exception;
   public static native long PreKeyRecord_New(int id, long pubKey, long privKey);
 
   public static native long PreKeySignalMessage_Deserialize(byte[] data) throws Exception;
@@ -472,9 +600,11 @@ public final class Native {
   public static native void ProfileKeyCommitment_CheckValidContents(byte[] buffer) throws Exception;
 
   public static native void ProfileKeyCredentialPresentation_CheckValidContents(byte[] presentationBytes) throws Exception;
+  public static native byte[] ProfileKeyCredentialPresentation_FromCompact(byte[] compactBytes) throws Exception;
   public static native byte[] ProfileKeyCredentialPresentation_GetProfileKeyCiphertext(byte[] presentationBytes);
   public static native byte[] ProfileKeyCredentialPresentation_GetStructurallyValidV1PresentationBytes(byte[] presentationBytes);
   public static native byte[] ProfileKeyCredentialPresentation_GetUuidCiphertext(byte[] presentationBytes);
+  public static native byte[] ProfileKeyCredentialPresentation_ToCompact(byte[] presentationBytes) throws Exception;
 
   public static native void ProfileKeyCredentialRequestContext_CheckValidContents(byte[] buffer) throws Exception;
   public static native byte[] ProfileKeyCredentialRequestContext_GetRequest(byte[] context);
@@ -491,6 +621,11 @@ public final class Native {
   public static native String ProtocolAddress_Name(long obj);
   public static native long ProtocolAddress_New(String name, int deviceId);
 
+  public static native void ReceiptBatch_Destroy(long handle);
+  public static native int ReceiptBatch_GetKind(long batch);
+  public static native byte[] ReceiptBatch_GetSender(long batch);
+  public static native byte[] ReceiptBatch_GetTimestamps(long batch);
+
   public static native void ReceiptCredentialPresentation_CheckValidContents(byte[] buffer) throws Exception;
   public static native long ReceiptCredentialPresentation_GetReceiptExpirationTime(byte[] presentation);
   public static native long ReceiptCredentialPresentation_GetReceiptLevel(byte[] presentation);
@@ -514,12 +649,23 @@ public final class Native {
 
   public static native boolean ScannableFingerprint_Compare(byte[] fprint1, byte[] fprint2) throws Exception;
 
+  public static native void SealedSenderDecryptionResult_Destroy(long handle);
+  public static native long SealedSenderDecryptionResult_GetCurrentIdentityKey(long obj);
+  public static native int SealedSenderDecryptionResult_GetDeviceId(long obj) throws Exception;
+  public static native long SealedSenderDecryptionResult_GetPreviousIdentityKey(long obj);
+  public static native @Nullable String SealedSenderDecryptionResult_GetSenderE164(long obj) throws Exception;
+  public static native String SealedSenderDecryptionResult_GetSenderUuid(long obj) throws Exception;
+  public static native byte[] SealedSenderDecryptionResult_Message(long obj) throws Exception;
+
+  public static native long SealedSender_DecryptMessageCapturingIdentityChange(byte[] message, long trustRoot, long timestamp, @Nullable String localE164, String localUuid, int localDeviceId, SessionStore sessionStore, IdentityKeyStore identityStore, PreKeyStore prekeyStore, SignedPreKeyStore signedPrekeyStore, KyberPreKeyStore kyberPrekeyStore) throws Exception;
   public static native Object SealedSender_MultiRecipientParseSentMessage(byte[] data);
 
   public static native long SealedSessionCipher_DecryptToUsmc(byte[] ctext, IdentityKeyStore identityStore) throws Exception;
   public static native byte[] SealedSessionCipher_Encrypt(long destination, long content, IdentityKeyStore identityKeyStore) throws Exception;
   public static native byte[] SealedSessionCipher_MultiRecipientEncrypt(long[] recipients, long[] recipientSessions, byte[] excludedRecipients, long content, IdentityKeyStore identityKeyStore) throws Exception;
   public static native byte[] SealedSessionCipher_MultiRecipientMessageForSingleRecipient(byte[] encodedMultiRecipientMessage) throws Exception;
+  public static native int SealedSessionCipher_MultiRecipientMessageLength(int usmcLength, int recipientCount, int deviceCount, int excludedRecipientCount);
+  public static native int SealedSessionCipher_MultiRecipientReceivedMessageLength(int usmcLength);
 
   public static native long SenderCertificate_Deserialize(byte[] data) throws Exception;
   public static native void SenderCertificate_Destroy(long handle);
@@ -527,12 +673,13 @@ public final class Native {
   public static native int SenderCertificate_GetDeviceId(long obj) throws Exception;
   public static native long SenderCertificate_GetExpiration(long obj) throws Exception;
   public static native long SenderCertificate_GetKey(long obj) throws Exception;
-  public static native String SenderCertificate_GetSenderE164(long obj) throws Exception;
+  public static native @Nullable String SenderCertificate_GetSenderE164(long obj) throws Exception;
   public static native String SenderCertificate_GetSenderUuid(long obj) throws Exception;
   public static native byte[] SenderCertificate_GetSerialized(long obj) throws Exception;
   public static native long SenderCertificate_GetServerCertificate(long cert) throws Exception;
   public static native byte[] SenderCertificate_GetSignature(long obj) throws Exception;
-  public static native long SenderCertificate_New(String senderUuid, String senderE164, int senderDeviceId, long senderKey, long expiration, long signerCert, long signerKey) throws Exception;
+  public static native boolean SenderCertificate_NeedsRefresh(long cert, long time, int refreshMarginSeconds);
+  public static native long SenderCertificate_New(String senderUuid, @Nullable String senderE164, int senderDeviceId, long senderKey, long expiration, long signerCert, long signerKey) throws Exception;
   public static native boolean SenderCertificate_Validate(long cert, long key, long time) throws Exception;
 
   public static native long SenderKeyDistributionMessage_Deserialize(byte[] data) throws Exception;
@@ -545,6 +692,10 @@ public final class Native {
   public static native long SenderKeyDistributionMessage_GetSignatureKey(long m) throws Exception;
   public static native long SenderKeyDistributionMessage_New(int messageVersion, UUID distributionId, int chainId, int iteration, byte[] chainkey, long pk) throws Exception;
 
+  public static native long SenderKeyDistributionRecord_Deserialize(byte[] data) throws Exception;
+  public static native void SenderKeyDistributionRecord_Destroy(long handle);
+  public static native byte[] SenderKeyDistributionRecord_Serialize(long obj) throws Exception;
+
   public static native long SenderKeyMessage_Deserialize(byte[] data) throws Exception;
   public static native void SenderKeyMessage_Destroy(long handle);
   public static native int SenderKeyMessage_GetChainId(long obj) throws Exception;
@@ -558,6 +709,7 @@ public final class Native {
   public static native long SenderKeyRecord_Deserialize(byte[] data) throws Exception;
   public static native void SenderKeyRecord_Destroy(long handle);
   public static native byte[] SenderKeyRecord_GetSerialized(long obj) throws Exception;
+  public static native byte[] SenderKeyRecord_MigrateToCurrent(byte[] data) throws Exception;
 
   public static native long ServerCertificate_Deserialize(byte[] data) throws Exception;
   public static native void ServerCertificate_Destroy(long handle);
@@ -598,16 +750,21 @@ public final class Native {
   public static native void ServerSecretParams_VerifyProfileKeyCredentialPresentation(long serverSecretParams, byte[] groupPublicParams, byte[] presentationBytes, long currentTimeInSeconds) throws Exception;
   public static native void ServerSecretParams_VerifyReceiptCredentialPresentation(long serverSecretParams, byte[] presentation) throws Exception;
 
+  public static native byte[] ServiceId_ParseFromServiceIdBinaries(ByteBuffer[] input) throws Exception;
   public static native byte[] ServiceId_ParseFromServiceIdBinary(byte[] input) throws Exception;
   public static native byte[] ServiceId_ParseFromServiceIdString(String input) throws Exception;
+  public static native byte[][] ServiceId_ServiceIdBinaries(byte[] concatenated) throws Exception;
   public static native byte[] ServiceId_ServiceIdBinary(byte[] value);
   public static native String ServiceId_ServiceIdLog(byte[] value);
   public static native String ServiceId_ServiceIdString(byte[] value);
+  public static native String[] ServiceId_ServiceIdStrings(byte[] concatenated) throws Exception;
 
   public static native void SessionBuilder_ProcessPreKeyBundle(long bundle, long protocolAddress, SessionStore sessionStore, IdentityKeyStore identityKeyStore, long now) throws Exception;
 
   public static native byte[] SessionCipher_DecryptPreKeySignalMessage(long message, long protocolAddress, SessionStore sessionStore, IdentityKeyStore identityKeyStore, PreKeyStore prekeyStore, SignedPreKeyStore signedPrekeyStore, KyberPreKeyStore kyberPrekeyStore) throws Exception;
+  public static native byte[] SessionCipher_DecryptPreKeySignalMessageWithReplayCache(long message, long protocolAddress, SessionStore sessionStore, IdentityKeyStore identityKeyStore, PreKeyStore prekeyStore, SignedPreKeyStore signedPrekeyStore, KyberPreKeyStore kyberPrekeyStore, ReplayCache replayCache) throws Exception;
   public static native byte[] SessionCipher_DecryptSignalMessage(long message, long protocolAddress, SessionStore sessionStore, IdentityKeyStore identityKeyStore) throws Exception;
+  public static native byte[] SessionCipher_DecryptSignalMessageWithReplayCache(long message, long protocolAddress, SessionStore sessionStore, IdentityKeyStore identityKeyStore, ReplayCache replayCache) throws Exception;
   public static native CiphertextMessage SessionCipher_EncryptMessage(byte[] ptext, long protocolAddress, SessionStore sessionStore, IdentityKeyStore identityKeyStore, long now) throws Exception;
 
   public static native void SessionRecord_ArchiveCurrentState(long sessionRecord) throws Exception;
@@ -615,16 +772,21 @@ public final class Native {
   public static native long SessionRecord_Deserialize(byte[] data) throws Exception;
   public static native void SessionRecord_Destroy(long handle);
   public static native byte[] SessionRecord_GetAliceBaseKey(long obj) throws Exception;
+  public static native long SessionRecord_GetLastRatchetStepTime(long s) throws Exception;
   public static native byte[] SessionRecord_GetLocalIdentityKeyPublic(long obj) throws Exception;
   public static native int SessionRecord_GetLocalRegistrationId(long obj) throws Exception;
-  public static native byte[] SessionRecord_GetReceiverChainKeyValue(long sessionState, long key) throws Exception;
-  public static native byte[] SessionRecord_GetRemoteIdentityKeyPublic(long obj) throws Exception;
+  public static native int SessionRecord_GetMessagesDecrypted(long s) throws Exception;
+  public static native int SessionRecord_GetMessagesEncrypted(long s) throws Exception;
+  public static native @Nullable byte[] SessionRecord_GetReceiverChainKeyValue(long sessionState, long key) throws Exception;
+  public static native @Nullable byte[] SessionRecord_GetRemoteIdentityKeyPublic(long obj) throws Exception;
   public static native int SessionRecord_GetRemoteRegistrationId(long obj) throws Exception;
   public static native byte[] SessionRecord_GetSenderChainKeyValue(long obj) throws Exception;
   public static native int SessionRecord_GetSessionVersion(long s) throws Exception;
   public static native boolean SessionRecord_HasUsableSenderChain(long s, long now) throws Exception;
   public static native long SessionRecord_InitializeAliceSession(long identityKeyPrivate, long identityKeyPublic, long basePrivate, long basePublic, long theirIdentityKey, long theirSignedPrekey, long theirRatchetKey) throws Exception;
   public static native long SessionRecord_InitializeBobSession(long identityKeyPrivate, long identityKeyPublic, long signedPrekeyPrivate, long signedPrekeyPublic, long ephPrivate, long ephPublic, long theirIdentityKey, long theirBaseKey) throws Exception;
+  public static native boolean SessionRecord_IsPostQuantum(long s) throws Exception;
+  public static native byte[] SessionRecord_MigrateToCurrent(byte[] data) throws Exception;
   public static native long SessionRecord_NewFresh();
   public static native byte[] SessionRecord_Serialize(long obj) throws Exception;
 
@@ -636,11 +798,13 @@ public final class Native {
 
   public static native void SignalMedia_CheckAvailable();
 
+  public static native int SignalMessage_CheckRatchetContinuity(long later, long earlier);
   public static native long SignalMessage_Deserialize(byte[] data) throws Exception;
   public static native void SignalMessage_Destroy(long handle);
   public static native byte[] SignalMessage_GetBody(long obj) throws Exception;
   public static native int SignalMessage_GetCounter(long obj) throws Exception;
   public static native int SignalMessage_GetMessageVersion(long obj) throws Exception;
+  public static native int SignalMessage_GetPreviousCounter(long obj) throws Exception;
   public static native long SignalMessage_GetSenderRatchetKey(long m);
   public static native byte[] SignalMessage_GetSerialized(long obj) throws Exception;
   public static native long SignalMessage_New(int messageVersion, byte[] macKey, long senderRatchetKey, int counter, int previousCounter, byte[] ciphertext, long senderIdentityKey, long receiverIdentityKey) throws Exception;
@@ -654,12 +818,43 @@ public final class Native {
   public static native byte[] SignedPreKeyRecord_GetSerialized(long obj) throws Exception;
   public static native byte[] SignedPreKeyRecord_GetSignature(long obj) throws Exception;
   public static native long SignedPreKeyRecord_GetTimestamp(long obj) throws Exception;
+  public static native byte[] SignedPreKeyRecord_MigrateToCurrent(byte[] data) throws Exception;
   public static native long SignedPreKeyRecord_New(int id, long timestamp, long pubKey, long privKey, byte[] signature);
 
+  public static native void StorageSyncPlan_Destroy(long handle);
+  public static native boolean StorageSyncPlan_hasRemoteChanges(long plan);
+  public static native byte[][] StorageSyncPlan_localDeletes(long plan);
+  public static native byte[][] StorageSyncPlan_localUpsertData(long plan);
+  public static native byte[][] StorageSyncPlan_localUpsertIds(long plan);
+  public static native byte[][] StorageSyncPlan_remoteDeletes(long plan);
+  public static native byte[][] StorageSyncPlan_remoteInsertData(long plan);
+  public static native byte[][] StorageSyncPlan_remoteInsertIds(long plan);
+  public static native byte[][] StorageSyncPlan_remoteManifestIds(long plan);
+  public static native byte[][] StorageSyncPlan_unknown(long plan);
+
+  public static native void StorageSync_Destroy(long handle);
+  public static native void StorageSync_addFetchedRecord(long sync, byte[] id, byte[] key, byte[] data) throws Exception;
+  public static native void StorageSync_addFetchedUnknown(long sync, byte[] id) throws Exception;
+  public static native void StorageSync_addKnownUnknown(long sync, byte[] id) throws Exception;
+  public static native void StorageSync_addLocalDeletion(long sync, byte[] id) throws Exception;
+  public static native void StorageSync_addLocalRecord(long sync, byte[] id, byte[] key, byte[] data) throws Exception;
+  public static native byte[][] StorageSync_idsToFetch(long sync);
+  public static native long StorageSync_new(byte[] previousManifest, byte[] remoteManifest) throws Exception;
+  public static native long StorageSync_plan(long sync) throws Exception;
+
+  public static native void StoreConformance_Check(int check, long identityKey, long privateKey, int registrationId, IdentityKeyStore identityStore, PreKeyStore preKeyStore, SignedPreKeyStore signedPreKeyStore, KyberPreKeyStore kyberPreKeyStore, SessionStore sessionStore, SenderKeyStore senderKeyStore) throws Exception;
+  public static native int StoreConformance_CheckCount();
+
   public static native long Svr2Client_New(byte[] mrenclave, byte[] attestationMsg, long currentTimestamp) throws Exception;
 
   public static native CompletableFuture<byte[]> Svr3Backup(long asyncRuntime, long connectionManager, byte[] secret, String password, int maxTries, String username, String enclavePassword);
 
+  public static native CompletableFuture<byte[]> Svr3BackupWithDistribution(long asyncRuntime, long connectionManager, byte[] secret, String password, int maxTries, byte[] serverIds, int threshold, String username, String enclavePassword);
+
+  public static native byte[] Svr3GetShareSetServerIds(byte[] shareSet) throws Exception;
+
+  public static native int Svr3GetShareSetThreshold(byte[] shareSet) throws Exception;
+
   public static native CompletableFuture<byte[]> Svr3Migrate(long asyncRuntime, long connectionManager, byte[] secret, String password, int maxTries, String username, String enclavePassword);
 
   public static native CompletableFuture<Void> Svr3Remove(long asyncRuntime, long connectionManager, String username, String enclavePassword);
@@ -678,14 +873,16 @@ public final class Native {
   public static native void UnidentifiedSenderMessageContent_Destroy(long handle);
   public static native int UnidentifiedSenderMessageContent_GetContentHint(long m) throws Exception;
   public static native byte[] UnidentifiedSenderMessageContent_GetContents(long obj) throws Exception;
-  public static native byte[] UnidentifiedSenderMessageContent_GetGroupId(long obj) throws Exception;
+  public static native @Nullable byte[] UnidentifiedSenderMessageContent_GetGroupId(long obj) throws Exception;
   public static native int UnidentifiedSenderMessageContent_GetMsgType(long m) throws Exception;
   public static native long UnidentifiedSenderMessageContent_GetSenderCert(long m) throws Exception;
   public static native byte[] UnidentifiedSenderMessageContent_GetSerialized(long obj) throws Exception;
-  public static native long UnidentifiedSenderMessageContent_New(CiphertextMessage message, long sender, int contentHint, byte[] groupId) throws Exception;
+  public static native long UnidentifiedSenderMessageContent_New(CiphertextMessage message, long sender, int contentHint, @Nullable byte[] groupId) throws Exception;
+  public static native long UnidentifiedSenderMessageContent_WithContentHintAndGroupId(long m, int contentHint, @Nullable byte[] groupId) throws Exception;
 
-  public static native byte[] UsernameLink_Create(String username, byte[] entropy) throws Exception;
+  public static native byte[] UsernameLink_Create(String username, @Nullable byte[] entropy) throws Exception;
   public static native String UsernameLink_DecryptUsername(byte[] entropy, byte[] encryptedUsername) throws Exception;
+  public static native byte[] UsernameLink_ParseUrl(String url) throws Exception;
 
   public static native Object[] Username_CandidatesFrom(String nickname, int minLen, int maxLen) throws Exception;
   public static native byte[] Username_Hash(String username) throws Exception;
@@ -0,0 +1,40 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.protocol;
+
+import org.signal.libsignal.internal.Native;
+
+/**
+ * Process-wide call counts and timings for libsignal's native functions.
+ *
+ * <p>Recording is off by default. Durations cover the synchronous part of each call, including
+ * argument conversion; for functions that return a future, that's only the time taken to start the
+ * operation.
+ */
+public final class BridgeMetrics {
+  private BridgeMetrics() {}
+
+  /** Turns recording on or off. Turning it off keeps the statistics gathered so far. */
+  public static void setEnabled(boolean enabled) {
+    Native.BridgeMetrics_SetEnabled(enabled);
+  }
+
+  /**
+   * Returns the statistics recorded so far as a JSON array, sorted by function name.
+   *
+   * <p>Each element has the form {@code {"name": string, "calls": number, "errors": number,
+   * "totalMicros": number, "maxMicros": number}}, where {@code name} is the method's name in
+   * {@code Native} and {@code errors} counts calls that threw.
+   */
+  public static String snapshotJson() {
+    return Native.BridgeMetrics_Snapshot();
+  }
+
+  /** Discards the statistics recorded so far. */
+  public static void reset() {
+    Native.BridgeMetrics_Reset();
+  }
+}
@@ -0,0 +1,40 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.protocol;
+
+import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
+
+import org.jetbrains.annotations.Nullable;
+import org.signal.libsignal.internal.Native;
+
+/**
+ * Parsing and validation for phone numbers in E.164 form ({@code +} followed by digits).
+ *
+ * <p>This only checks the structure of a number, not whether its calling code or the rest of the
+ * number is actually in use.
+ */
+public final class E164 {
+  private E164() {}
+
+  /**
+   * Interprets a phone number as entered by a user or found in a contact list.
+   *
+   * <p>Numbers in international format (starting with {@code +} or {@code 00}) are used as is.
+   * Anything else is treated as a national number in the country with calling code {@code
+   * regionCallingCode} (usually the local user's own). Spaces and common punctuation are ignored.
+   *
+   * @return the number in E.164 form, or {@code null} if it isn't a valid phone number
+   * @throws IllegalArgumentException if {@code regionCallingCode} isn't a plausible calling code
+   */
+  public static @Nullable String normalize(String raw, int regionCallingCode) {
+    return filterExceptions(() -> Native.E164_Normalize(raw, regionCallingCode));
+  }
+
+  /** Checks whether {@code e164} is already a valid number in E.164 form, with no punctuation. */
+  public static boolean isValid(String e164) {
+    return Native.E164_IsValid(e164);
+  }
+}
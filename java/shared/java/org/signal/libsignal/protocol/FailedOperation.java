@@ -0,0 +1,56 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.protocol;
+
+import java.util.Map;
+import java.util.WeakHashMap;
+import org.signal.libsignal.internal.CalledFromNative;
+
+/**
+ * Identifies which native libsignal function produced an exception.
+ *
+ * <p>The exceptions libsignal throws don't share a common base class, so rather than being a field
+ * on the exception, the name is tracked alongside every exception thrown from native code
+ * (including exceptions used to complete a future). It's the name of the underlying Rust function,
+ * which is usually also the name of the method in {@code Native}, and matches the {@code
+ * operation} property on errors in libsignal's TypeScript API.
+ */
+@CalledFromNative
+public final class FailedOperation {
+  private static final Map<Throwable, String> operations = new WeakHashMap<>();
+
+  private FailedOperation() {}
+
+  /**
+   * Returns the name of the native function that threw {@code exception}, or {@code null} if it
+   * didn't come from libsignal.
+   *
+   * <p>Causes are checked as well, so this works for wrapped exceptions, such as the {@link
+   * java.util.concurrent.ExecutionException} thrown from a failed future.
+   */
+  public static String of(Throwable exception) {
+    synchronized (operations) {
+      for (Throwable t = exception; t != null; t = t.getCause()) {
+        String operation = operations.get(t);
+        if (operation != null) {
+          return operation;
+        }
+      }
+    }
+    return null;
+  }
+
+  @CalledFromNative
+  private static void record(Throwable exception, String operation) {
+    synchronized (operations) {
+      // Exceptions rethrown from app callbacks can pass through more than one native call; keep the
+      // innermost one.
+      if (!operations.containsKey(exception)) {
+        operations.put(exception, operation);
+      }
+    }
+  }
+}
@@ -0,0 +1,55 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.protocol;
+
+import org.signal.libsignal.internal.Native;
+
+/**
+ * Process-wide caps on the resources libsignal will use to parse untrusted input.
+ *
+ * <p>By default nothing is restricted beyond what each parser already enforces. Sizes and counts
+ * are treated as unsigned; pass {@link #UNLIMITED} to leave a limit off, or {@link
+ * #DEFAULT_MAX_MEDIA_METADATA_SIZE} to restore the default media metadata limit.
+ */
+public final class ParseLimits {
+  public static final long UNLIMITED = -1;
+  public static final long DEFAULT_MAX_MEDIA_METADATA_SIZE = 300 * 1024 * 1024;
+
+  private ParseLimits() {}
+
+  /**
+   * Replaces the limits used for all subsequent parsing.
+   *
+   * @param maxMessageSize the largest serialized message that will be decoded
+   * @param maxCollectionCount the most elements a single decoded collection may have
+   * @param maxDecompressedSize the most bytes a compressed stream may expand to
+   * @param maxMediaMetadataSize the most metadata a media file may have
+   * @param maxRetainedSize the most decoded data a streaming parser, such as the backup validator,
+   *     may keep in memory
+   */
+  public static void set(
+      long maxMessageSize,
+      long maxCollectionCount,
+      long maxDecompressedSize,
+      long maxMediaMetadataSize,
+      long maxRetainedSize) {
+    Native.ParseLimits_Set(
+        maxMessageSize,
+        maxCollectionCount,
+        maxDecompressedSize,
+        maxMediaMetadataSize,
+        maxRetainedSize);
+  }
+
+  /** Like {@link #set(long, long, long, long, long)}, leaving the retained size unlimited. */
+  public static void set(
+      long maxMessageSize,
+      long maxCollectionCount,
+      long maxDecompressedSize,
+      long maxMediaMetadataSize) {
+    set(maxMessageSize, maxCollectionCount, maxDecompressedSize, maxMediaMetadataSize, UNLIMITED);
+  }
+}
@@ -8,8 +8,10 @@ package org.signal.libsignal.protocol;
 import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
 
 import java.nio.ByteBuffer;
+import java.util.ArrayList;
 import java.util.Arrays;
 import java.util.Collection;
+import java.util.List;
 import java.util.UUID;
 import org.signal.libsignal.internal.CalledFromNative;
 import org.signal.libsignal.internal.Native;
@@ -157,6 +159,77 @@ public abstract class ServiceId implements Comparable<ServiceId> {
     return result;
   }
 
+  /**
+   * Parses the inverse of {@link #toConcatenatedFixedWidthBinary}.
+   *
+   * @throws InvalidServiceIdException if the input isn't a list of valid fixed-width service IDs
+   */
+  public static List<ServiceId> parseFromConcatenatedFixedWidthBinary(byte[] concatenated)
+      throws InvalidServiceIdException {
+    if (concatenated.length % FIXED_WIDTH_BINARY_LENGTH != 0) {
+      throw new InvalidServiceIdException("not a whole number of Service-Id-FixedWidthBinary");
+    }
+    List<ServiceId> result = new ArrayList<>(concatenated.length / FIXED_WIDTH_BINARY_LENGTH);
+    for (int offset = 0; offset < concatenated.length; offset += FIXED_WIDTH_BINARY_LENGTH) {
+      result.add(
+          parseFromFixedWidthBinary(
+              Arrays.copyOfRange(concatenated, offset, offset + FIXED_WIDTH_BINARY_LENGTH)));
+    }
+    return result;
+  }
+
+  /**
+   * Parses many Service-Id-Binary values at once.
+   *
+   * <p>Equivalent to calling {@link #parseFromBinary} on each element, but crosses into native code
+   * only once.
+   *
+   * @throws InvalidServiceIdException if any element is not a valid Service-Id-Binary
+   */
+  public static List<ServiceId> parseFromBinaries(Collection<byte[]> serviceIdBinaries)
+      throws InvalidServiceIdException {
+    ByteBuffer[] buffers = new ByteBuffer[serviceIdBinaries.size()];
+    int nextOffset = 0;
+    for (byte[] next : serviceIdBinaries) {
+      if (next == null) {
+        throw new InvalidServiceIdException("Service-Id-Binary cannot be null");
+      }
+      buffers[nextOffset] = ByteBuffer.allocateDirect(next.length);
+      buffers[nextOffset].put(next);
+      ++nextOffset;
+    }
+    byte[] concatenated;
+    try {
+      concatenated = filterExceptions(() -> Native.ServiceId_ParseFromServiceIdBinaries(buffers));
+    } catch (IllegalArgumentException ex) {
+      throw new InvalidServiceIdException(ex.getMessage());
+    }
+    return parseFromConcatenatedFixedWidthBinary(concatenated);
+  }
+
+  /**
+   * Converts many service IDs to Service-Id-Binary at once.
+   *
+   * <p>Equivalent to calling {@link #toServiceIdBinary} on each element, but crosses into native
+   * code only once.
+   */
+  public static List<byte[]> toServiceIdBinaries(Collection<ServiceId> serviceIds) {
+    byte[] concatenated = toConcatenatedFixedWidthBinary(serviceIds);
+    return Arrays.asList(
+        filterExceptions(() -> Native.ServiceId_ServiceIdBinaries(concatenated)));
+  }
+
+  /**
+   * Converts many service IDs to Service-Id-String at once.
+   *
+   * <p>Equivalent to calling {@link #toServiceIdString} on each element, but crosses into native
+   * code only once.
+   */
+  public static List<String> toServiceIdStrings(Collection<ServiceId> serviceIds) {
+    byte[] concatenated = toConcatenatedFixedWidthBinary(serviceIds);
+    return Arrays.asList(filterExceptions(() -> Native.ServiceId_ServiceIdStrings(concatenated)));
+  }
+
   private static UUID uuidFromBytes(ByteBuffer buffer) {
     long high = buffer.getLong();
     long low = buffer.getLong();
@@ -16,6 +16,7 @@ import org.signal.libsignal.protocol.message.SignalMessage;
 import org.signal.libsignal.protocol.state.IdentityKeyStore;
 import org.signal.libsignal.protocol.state.KyberPreKeyStore;
 import org.signal.libsignal.protocol.state.PreKeyStore;
+import org.signal.libsignal.protocol.state.ReplayCache;
 import org.signal.libsignal.protocol.state.SessionRecord;
 import org.signal.libsignal.protocol.state.SessionStore;
 import org.signal.libsignal.protocol.state.SignalProtocolStore;
@@ -181,6 +182,88 @@ public class SessionCipher {
     }
   }
 
+  /**
+   * Decrypt a message, rejecting it if it was already recorded in {@code replayCache}.
+   *
+   * <p>The message is only added to the cache once it has been decrypted and the session saved.
+   *
+   * @param ciphertext The {@link PreKeySignalMessage} to decrypt.
+   * @param replayCache A record of messages that have already been decrypted.
+   * @return The plaintext.
+   * @throws InvalidMessageException if the input is not valid ciphertext.
+   * @throws DuplicateMessageException if the input is a message that has already been received,
+   *     including one found in {@code replayCache}.
+   * @throws InvalidKeyIdException when there is no local {@link
+   *     org.signal.libsignal.protocol.state.PreKeyRecord} that corresponds to the PreKey ID in the
+   *     message.
+   * @throws InvalidKeyException when the message is formatted incorrectly.
+   * @throws UntrustedIdentityException when the {@link IdentityKey} of the sender is untrusted.
+   */
+  public byte[] decrypt(PreKeySignalMessage ciphertext, ReplayCache replayCache)
+      throws DuplicateMessageException,
+          InvalidMessageException,
+          InvalidKeyIdException,
+          InvalidKeyException,
+          UntrustedIdentityException {
+    try (NativeHandleGuard ciphertextGuard = new NativeHandleGuard(ciphertext);
+        NativeHandleGuard remoteAddressGuard = new NativeHandleGuard(this.remoteAddress); ) {
+      return filterExceptions(
+          DuplicateMessageException.class,
+          InvalidMessageException.class,
+          InvalidKeyIdException.class,
+          InvalidKeyException.class,
+          UntrustedIdentityException.class,
+          () ->
+              Native.SessionCipher_DecryptPreKeySignalMessageWithReplayCache(
+                  ciphertextGuard.nativeHandle(),
+                  remoteAddressGuard.nativeHandle(),
+                  sessionStore,
+                  identityKeyStore,
+                  preKeyStore,
+                  signedPreKeyStore,
+                  kyberPreKeyStore,
+                  replayCache));
+    }
+  }
+
+  /**
+   * Decrypt a message, rejecting it if it was already recorded in {@code replayCache}.
+   *
+   * <p>The message is only added to the cache once it has been decrypted and the session saved.
+   *
+   * @param ciphertext The {@link SignalMessage} to decrypt.
+   * @param replayCache A record of messages that have already been decrypted.
+   * @return The plaintext.
+   * @throws InvalidMessageException if the input is not valid ciphertext.
+   * @throws InvalidVersionException if the message version does not match the session version.
+   * @throws DuplicateMessageException if the input is a message that has already been received,
+   *     including one found in {@code replayCache}.
+   * @throws NoSessionException if there is no established session for this contact.
+   */
+  public byte[] decrypt(SignalMessage ciphertext, ReplayCache replayCache)
+      throws InvalidMessageException,
+          InvalidVersionException,
+          DuplicateMessageException,
+          NoSessionException,
+          UntrustedIdentityException {
+    try (NativeHandleGuard ciphertextGuard = new NativeHandleGuard(ciphertext);
+        NativeHandleGuard remoteAddressGuard = new NativeHandleGuard(this.remoteAddress); ) {
+      return filterExceptions(
+          InvalidMessageException.class,
+          InvalidVersionException.class,
+          DuplicateMessageException.class,
+          NoSessionException.class,
+          UntrustedIdentityException.class,
+          () ->
+              Native.SessionCipher_DecryptSignalMessageWithReplayCache(
+                  ciphertextGuard.nativeHandle(),
+                  remoteAddressGuard.nativeHandle(),
+                  sessionStore,
+                  identityKeyStore,
+                  replayCache));
+    }
+  }
+
   public int getRemoteRegistrationId() {
     if (!sessionStore.containsSession(remoteAddress)) {
       throw new IllegalStateException(String.format("No session for (%s)!", remoteAddress));
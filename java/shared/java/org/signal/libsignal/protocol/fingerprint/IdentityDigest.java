@@ -0,0 +1,68 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.protocol.fingerprint;
+
+import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
+
+import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.internal.NativeHandleGuard;
+import org.signal.libsignal.protocol.IdentityKey;
+
+/**
+ * The digest of a single identity that makes up one half of a safety number.
+ *
+ * <p>Displaying one of these is consistent with the corresponding half of any {@link Fingerprint}
+ * generated with the same version and iteration count.
+ */
+public class IdentityDigest {
+  private final int version;
+  private final byte[] digest;
+
+  /**
+   * Computes the digest for a single identity.
+   *
+   * @param iterations The iteration count, as passed to {@link NumericFingerprintGenerator}.
+   * @param version The fingerprint version, as passed to {@link
+   *     NumericFingerprintGenerator#createFor}.
+   * @param stableIdentifier The identity's "stable" identifier.
+   * @param identityKey The identity key.
+   */
+  public static IdentityDigest compute(
+      int iterations, int version, byte[] stableIdentifier, IdentityKey identityKey) {
+    try (NativeHandleGuard keyGuard = new NativeHandleGuard(identityKey.getPublicKey())) {
+      byte[] digest =
+          filterExceptions(
+              () ->
+                  Native.IdentityDigest_Compute(
+                      iterations, version, stableIdentifier, keyGuard.nativeHandle()));
+      return new IdentityDigest(version, digest);
+    }
+  }
+
+  /**
+   * Reconstructs a digest previously returned by {@link #getDigest}.
+   *
+   * @throws IllegalArgumentException if {@code digest} has the wrong length
+   */
+  public IdentityDigest(int version, byte[] digest) {
+    filterExceptions(() -> Native.IdentityDigest_DisplayString(version, digest));
+    this.version = version;
+    this.digest = digest.clone();
+  }
+
+  public int getVersion() {
+    return this.version;
+  }
+
+  public byte[] getDigest() {
+    return this.digest.clone();
+  }
+
+  /** The 30-digit string this identity contributes to a displayed safety number. */
+  public String getDisplayText() {
+    return filterExceptions(() -> Native.IdentityDigest_DisplayString(this.version, this.digest));
+  }
+}
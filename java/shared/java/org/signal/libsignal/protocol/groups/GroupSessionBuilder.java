@@ -7,6 +7,8 @@ package org.signal.libsignal.protocol.groups;
 
 import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
 
+import java.util.ArrayList;
+import java.util.List;
 import java.util.UUID;
 import org.signal.libsignal.internal.Native;
 import org.signal.libsignal.internal.NativeHandleGuard;
@@ -73,4 +75,102 @@ public class GroupSessionBuilder {
                       senderGuard.nativeHandle(), distributionId, senderKeyStore)));
     }
   }
+
+  /**
+   * Returns the members of {@code recipients} that have not yet been sent the sender key
+   * currently used for {@code distributionId}.
+   *
+   * <p>If there is no sender key yet, the key has changed since distribution was last recorded, or
+   * the store does not track distribution, every recipient is returned. Each of them should be
+   * sent the result of {@link #create} before (or along with) the next group message, and then
+   * passed to {@link #markDistributed}.
+   *
+   * @param sender The address of the current client.
+   * @param distributionId An opaque identifier that uniquely identifies the group (but isn't the
+   *     group ID).
+   * @param recipients The devices the next group message will be sent to.
+   */
+  public List<SignalProtocolAddress> recipientsNeedingDistribution(
+      SignalProtocolAddress sender, UUID distributionId, List<SignalProtocolAddress> recipients) {
+    long[] recipientHandles = unsafeHandles(recipients);
+    try (NativeHandleGuard senderGuard = new NativeHandleGuard(sender)) {
+      byte[] needsDistribution =
+          filterExceptions(
+              () ->
+                  Native.GroupSessionBuilder_RecipientsNeedingDistribution(
+                      senderGuard.nativeHandle(),
+                      distributionId,
+                      recipientHandles,
+                      senderKeyStore));
+      Native.keepAlive(recipients);
+
+      List<SignalProtocolAddress> result = new ArrayList<>();
+      for (int i = 0; i < needsDistribution.length; i++) {
+        if (needsDistribution[i] != 0) {
+          result.add(recipients.get(i));
+        }
+      }
+      return result;
+    }
+  }
+
+  /**
+   * Records that {@code recipients} have been sent the sender key currently used for {@code
+   * distributionId}.
+   *
+   * <p>Call this only once the {@link SenderKeyDistributionMessage} has actually been delivered.
+   * Any record left over from a previous sender key is discarded.
+   *
+   * @param sender The address of the current client.
+   * @param distributionId An opaque identifier that uniquely identifies the group (but isn't the
+   *     group ID).
+   * @param recipients The devices that received the SenderKeyDistributionMessage.
+   */
+  public void markDistributed(
+      SignalProtocolAddress sender, UUID distributionId, List<SignalProtocolAddress> recipients) {
+    long[] recipientHandles = unsafeHandles(recipients);
+    try (NativeHandleGuard senderGuard = new NativeHandleGuard(sender)) {
+      filterExceptions(
+          () ->
+              Native.GroupSessionBuilder_MarkDistributed(
+                  senderGuard.nativeHandle(), distributionId, recipientHandles, senderKeyStore));
+      Native.keepAlive(recipients);
+    }
+  }
+
+  /**
+   * Forgets that {@code recipients} have been sent the sender key for {@code distributionId}, so
+   * that they will be returned by {@link #recipientsNeedingDistribution} again.
+   *
+   * <p>Use this when a recipient may have lost the key, such as after their session is reset or
+   * they send a decryption error for a group message.
+   *
+   * @param sender The address of the current client.
+   * @param distributionId An opaque identifier that uniquely identifies the group (but isn't the
+   *     group ID).
+   * @param recipients The devices that need to be sent the sender key again.
+   */
+  public void invalidateDistribution(
+      SignalProtocolAddress sender, UUID distributionId, List<SignalProtocolAddress> recipients) {
+    long[] recipientHandles = unsafeHandles(recipients);
+    try (NativeHandleGuard senderGuard = new NativeHandleGuard(sender)) {
+      filterExceptions(
+          () ->
+              Native.GroupSessionBuilder_InvalidateDistribution(
+                  senderGuard.nativeHandle(), distributionId, recipientHandles, senderKeyStore));
+      Native.keepAlive(recipients);
+    }
+  }
+
+  // Unsafely access the native handles for the recipients, because try-with-resources syntax
+  // doesn't support a List of resources. Callers must keep the list alive until they're done.
+  private static long[] unsafeHandles(List<SignalProtocolAddress> recipients) {
+    long[] handles = new long[recipients.size()];
+    int i = 0;
+    for (SignalProtocolAddress recipient : recipients) {
+      handles[i] = recipient.unsafeNativeHandleWithoutGuard();
+      i++;
+    }
+    return handles;
+  }
 }
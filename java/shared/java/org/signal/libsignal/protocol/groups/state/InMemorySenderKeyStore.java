@@ -15,6 +15,7 @@ import org.signal.libsignal.protocol.util.Pair;
 public class InMemorySenderKeyStore implements SenderKeyStore {
 
   private final Map<Pair<SignalProtocolAddress, UUID>, SenderKeyRecord> store = new HashMap<>();
+  private final Map<Pair<SignalProtocolAddress, UUID>, byte[]> distributions = new HashMap<>();
 
   @Override
   public void storeSenderKey(
@@ -36,4 +37,26 @@ public class InMemorySenderKeyStore implements SenderKeyStore {
       throw new AssertionError(e);
     }
   }
+
+  @Override
+  public void storeSenderKeyDistribution(
+      SignalProtocolAddress sender, UUID distributionId, SenderKeyDistributionRecord record) {
+    distributions.put(new Pair<>(sender, distributionId), record.serialize());
+  }
+
+  @Override
+  public SenderKeyDistributionRecord loadSenderKeyDistribution(
+      SignalProtocolAddress sender, UUID distributionId) {
+    try {
+      byte[] record = distributions.get(new Pair<>(sender, distributionId));
+
+      if (record == null) {
+        return null;
+      } else {
+        return new SenderKeyDistributionRecord(record);
+      }
+    } catch (InvalidMessageException e) {
+      throw new AssertionError(e);
+    }
+  }
 }
@@ -0,0 +1,52 @@
+//
+// Copyright 2026 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.protocol.groups.state;
+
+import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
+
+import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.internal.NativeHandleGuard;
+import org.signal.libsignal.protocol.InvalidMessageException;
+
+/**
+ * Which recipients have been sent the sender key currently in use for a (sender, distributionId)
+ * pair.
+ *
+ * <p>Stores only need to persist these opaque records; they are maintained by {@link
+ * org.signal.libsignal.protocol.groups.GroupSessionBuilder#markDistributed} and consulted by {@link
+ * org.signal.libsignal.protocol.groups.GroupSessionBuilder#recipientsNeedingDistribution}.
+ */
+public class SenderKeyDistributionRecord implements NativeHandleGuard.Owner {
+  private final long unsafeHandle;
+
+  @Override
+  @SuppressWarnings("deprecation")
+  protected void finalize() {
+    Native.SenderKeyDistributionRecord_Destroy(this.unsafeHandle);
+  }
+
+  public SenderKeyDistributionRecord(long unsafeHandle) {
+    this.unsafeHandle = unsafeHandle;
+  }
+
+  public SenderKeyDistributionRecord(byte[] serialized) throws InvalidMessageException {
+    this.unsafeHandle =
+        filterExceptions(
+            InvalidMessageException.class,
+            () -> Native.SenderKeyDistributionRecord_Deserialize(serialized));
+  }
+
+  public byte[] serialize() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return filterExceptions(
+          () -> Native.SenderKeyDistributionRecord_Serialize(guard.nativeHandle()));
+    }
+  }
+
+  public long unsafeNativeHandleWithoutGuard() {
+    return this.unsafeHandle;
+  }
+}
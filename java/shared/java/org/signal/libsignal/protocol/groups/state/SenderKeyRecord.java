@@ -37,6 +37,16 @@ public class SenderKeyRecord implements NativeHandleGuard.Owner {
             InvalidMessageException.class, () -> Native.SenderKeyRecord_Deserialize(serialized));
   }
 
+  /**
+   * Rewrites a serialized record with a version header, in the newest format libsignal reads.
+   *
+   * @see org.signal.libsignal.protocol.state.SessionRecord#migrateToCurrent
+   */
+  public static byte[] migrateToCurrent(byte[] serialized) throws InvalidMessageException {
+    return filterExceptions(
+        InvalidMessageException.class, () -> Native.SenderKeyRecord_MigrateToCurrent(serialized));
+  }
+
   public byte[] serialize() {
     try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
       return filterExceptions(() -> Native.SenderKeyRecord_GetSerialized(guard.nativeHandle()));
@@ -42,4 +42,35 @@ public interface SenderKeyStore {
    *     or `null` if one does not currently exist.
    */
   public SenderKeyRecord loadSenderKey(SignalProtocolAddress sender, UUID distributionId);
+
+  /**
+   * Commit to storage the {@link SenderKeyDistributionRecord} for a given (distributionId +
+   * senderName + deviceId) tuple.
+   *
+   * <p>Tracking distribution is optional. The default implementation discards the record, in
+   * which case every recipient is always reported as needing the sender key.
+   *
+   * @param sender The address of the current client.
+   * @param distributionId An opaque identifier that uniquely identifies the group (but isn't the
+   *     group ID).
+   * @param record which recipients have been sent the current sender key.
+   */
+  public default void storeSenderKeyDistribution(
+      SignalProtocolAddress sender, UUID distributionId, SenderKeyDistributionRecord record) {}
+
+  /**
+   * Returns the {@link SenderKeyDistributionRecord} last stored for the (distributionId +
+   * senderName + deviceId) tuple, or `null` if there is none.
+   *
+   * <p>Stores that override {@link #storeSenderKeyDistribution} should override this too.
+   *
+   * @param sender The address of the current client.
+   * @param distributionId An opaque identifier that uniquely identifies the group (but isn't the
+   *     group ID).
+   * @return the stored SenderKeyDistributionRecord, or `null` if there is none.
+   */
+  public default SenderKeyDistributionRecord loadSenderKeyDistribution(
+      SignalProtocolAddress sender, UUID distributionId) {
+    return null;
+  }
 }
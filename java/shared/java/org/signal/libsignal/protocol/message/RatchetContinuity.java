@@ -0,0 +1,48 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.protocol.message;
+
+/**
+ * How two consecutive {@link SignalMessage}s from the same peer relate to each other.
+ *
+ * @see SignalMessage#getRatchetContinuityAfter
+ */
+public enum RatchetContinuity {
+  // This must match the Rust version of the enum.
+  /** The later message continues the earlier message's chain. */
+  SAME_CHAIN(0),
+  /** The later message starts a new chain after the earlier message's chain. */
+  NEXT_CHAIN(1),
+  /** The later message is on the same chain but doesn't come after the earlier one. */
+  REORDERED(2),
+  /** The later message starts a new chain that doesn't account for the earlier message. */
+  DISCONTINUOUS(3);
+
+  private final int value;
+
+  RatchetContinuity(int value) {
+    this.value = value;
+  }
+
+  int getValue() {
+    return this.value;
+  }
+
+  /** Whether this is worth flagging for investigation. */
+  public boolean isSuspicious() {
+    return this == DISCONTINUOUS;
+  }
+
+  static RatchetContinuity fromValue(int value) {
+    // A linear scan is simpler than a hash lookup for a set of values this small.
+    for (final var continuity : RatchetContinuity.values()) {
+      if (continuity.getValue() == value) {
+        return continuity;
+      }
+    }
+    throw new IllegalArgumentException("Invalid ratchet continuity: " + value);
+  }
+}
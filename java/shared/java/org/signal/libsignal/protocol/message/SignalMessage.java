@@ -64,6 +64,28 @@ public class SignalMessage implements CiphertextMessage, NativeHandleGuard.Owner
     }
   }
 
+  public int getPreviousCounter() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return filterExceptions(() -> Native.SignalMessage_GetPreviousCounter(guard.nativeHandle()));
+    }
+  }
+
+  /**
+   * Checks whether this message plausibly follows {@code earlier} in the same session.
+   *
+   * <p>{@code earlier} must be the last message received from the same peer before this one. Only
+   * the unauthenticated ratchet metadata is examined, so this is a diagnostic for investigating
+   * suspected session tampering, not a check for whether to accept a message.
+   */
+  public RatchetContinuity getRatchetContinuityAfter(SignalMessage earlier) {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this);
+        NativeHandleGuard earlierGuard = new NativeHandleGuard(earlier)) {
+      return RatchetContinuity.fromValue(
+          Native.SignalMessage_CheckRatchetContinuity(
+              guard.nativeHandle(), earlierGuard.nativeHandle()));
+    }
+  }
+
   public byte[] getBody() {
     try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
       return filterExceptions(() -> Native.SignalMessage_GetBody(guard.nativeHandle()));
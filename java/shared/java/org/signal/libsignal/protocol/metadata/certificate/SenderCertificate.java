@@ -7,6 +7,7 @@ package org.signal.libsignal.metadata.certificate;
 
 import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
 
+import java.time.Duration;
 import java.util.Optional;
 import org.signal.libsignal.internal.Native;
 import org.signal.libsignal.internal.NativeHandleGuard;
@@ -95,6 +96,25 @@ public class SenderCertificate implements NativeHandleGuard.Owner {
     }
   }
 
+  /**
+   * Returns whether this certificate will have expired by {@code timestamp + refreshMargin}.
+   *
+   * <p>Use this to replace a cached certificate before it expires, rather than waiting for sends
+   * to fail. This does not validate the certificate.
+   *
+   * @param timestamp the current time, in milliseconds since the epoch
+   * @param refreshMargin how long before expiration the certificate should be replaced; must be
+   *     non-negative, and is truncated to whole seconds
+   */
+  public boolean needsRefresh(long timestamp, Duration refreshMargin) {
+    final int refreshMarginSeconds =
+        (int) Math.min(Integer.MAX_VALUE, Math.max(0, refreshMargin.getSeconds()));
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return Native.SenderCertificate_NeedsRefresh(
+          guard.nativeHandle(), timestamp, refreshMarginSeconds);
+    }
+  }
+
   public byte[] getSerialized() {
     try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
       return filterExceptions(() -> Native.SenderCertificate_GetSerialized(guard.nativeHandle()));
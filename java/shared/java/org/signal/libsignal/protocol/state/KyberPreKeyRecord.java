@@ -36,6 +36,16 @@ public class KyberPreKeyRecord implements NativeHandleGuard.Owner {
             InvalidMessageException.class, () -> Native.KyberPreKeyRecord_Deserialize(serialized));
   }
 
+  /**
+   * Rewrites a serialized record with a version header, in the newest format libsignal reads.
+   *
+   * @see SessionRecord#migrateToCurrent
+   */
+  public static byte[] migrateToCurrent(byte[] serialized) throws InvalidMessageException {
+    return filterExceptions(
+        InvalidMessageException.class, () -> Native.KyberPreKeyRecord_MigrateToCurrent(serialized));
+  }
+
   public int getId() {
     try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
       return filterExceptions(() -> Native.KyberPreKeyRecord_GetId(guard.nativeHandle()));
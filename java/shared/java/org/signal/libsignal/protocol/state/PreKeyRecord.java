@@ -39,6 +39,16 @@ public class PreKeyRecord implements NativeHandleGuard.Owner {
             InvalidMessageException.class, () -> Native.PreKeyRecord_Deserialize(serialized));
   }
 
+  /**
+   * Rewrites a serialized record with a version header, in the newest format libsignal reads.
+   *
+   * @see SessionRecord#migrateToCurrent
+   */
+  public static byte[] migrateToCurrent(byte[] serialized) throws InvalidMessageException {
+    return filterExceptions(
+        InvalidMessageException.class, () -> Native.PreKeyRecord_MigrateToCurrent(serialized));
+  }
+
   public int getId() {
     try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
       return filterExceptions(() -> Native.PreKeyRecord_GetId(guard.nativeHandle()));
@@ -0,0 +1,48 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.protocol.state;
+
+import org.signal.libsignal.internal.CalledFromNative;
+import org.signal.libsignal.protocol.SignalProtocolAddress;
+import org.signal.libsignal.protocol.ecc.ECPublicKey;
+
+/**
+ * An optional record of messages that have already been decrypted.
+ *
+ * <p>A session normally rejects repeated messages by itself, but that protection is lost if the
+ * session record is deleted or replaced. Clients that need to process each message at most once can
+ * pass a ReplayCache to the {@link org.signal.libsignal.protocol.SessionCipher} or {@code
+ * SealedSessionCipher} decrypt methods to close that gap.
+ *
+ * <p>How long entries are kept is up to the implementation.
+ */
+@CalledFromNative
+public interface ReplayCache {
+
+  /**
+   * Check whether a message has already been recorded (and has not yet expired).
+   *
+   * <p>Messages are identified by the sender's ratchet key as well as the counter, since counters
+   * start over with each new chain.
+   *
+   * @param address the sender of the message.
+   * @param senderRatchetKey the ratchet key the message was sent with.
+   * @param counter the message's position in its chain.
+   * @return true if the message was already recorded.
+   */
+  public boolean contains(SignalProtocolAddress address, ECPublicKey senderRatchetKey, int counter);
+
+  /**
+   * Record that a message has been processed.
+   *
+   * <p>This is only called after the session the message advanced has been saved.
+   *
+   * @param address the sender of the message.
+   * @param senderRatchetKey the ratchet key the message was sent with.
+   * @param counter the message's position in its chain.
+   */
+  public void insert(SignalProtocolAddress address, ECPublicKey senderRatchetKey, int counter);
+}
@@ -47,6 +47,21 @@ public class SessionRecord implements NativeHandleGuard.Owner {
             InvalidMessageException.class, () -> Native.SessionRecord_Deserialize(serialized));
   }
 
+  /**
+   * Rewrites a serialized record with a version header, in the newest format libsignal reads.
+   *
+   * <p>Records with or without a header can always be read directly. {@link #serialize} still
+   * writes records without one, so that older versions of libsignal can read them; this is the
+   * opt-in for apps that no longer need to support those versions.
+   *
+   * @throws InvalidMessageException if the record is malformed or was written by a newer version
+   *     of libsignal
+   */
+  public static byte[] migrateToCurrent(byte[] serialized) throws InvalidMessageException {
+    return filterExceptions(
+        InvalidMessageException.class, () -> Native.SessionRecord_MigrateToCurrent(serialized));
+  }
+
   /**
    * Move the current SessionState into the list of "previous" session states, and replace the
    * current SessionState with a fresh reset instance.
@@ -140,6 +155,58 @@ public class SessionRecord implements NativeHandleGuard.Owner {
     }
   }
 
+  /**
+   * Returns how many messages have been encrypted on the current session.
+   *
+   * <p>Together with {@link #getMessagesDecrypted}, {@link #getLastRatchetStepTime}, and {@link
+   * #isPostQuantum}, this is meant for security screens and diagnostics.
+   *
+   * @throws IllegalStateException if there is no current session
+   */
+  public int getMessagesEncrypted() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return filterExceptions(
+          () -> Native.SessionRecord_GetMessagesEncrypted(guard.nativeHandle()));
+    }
+  }
+
+  /**
+   * Returns how many messages have been successfully decrypted on the current session.
+   *
+   * @throws IllegalStateException if there is no current session
+   */
+  public int getMessagesDecrypted() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return filterExceptions(
+          () -> Native.SessionRecord_GetMessagesDecrypted(guard.nativeHandle()));
+    }
+  }
+
+  /**
+   * Returns when this device first sent a message using its current ratchet key, or {@code null}
+   * if it hasn't sent one yet.
+   *
+   * @throws IllegalStateException if there is no current session
+   */
+  public Instant getLastRatchetStepTime() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      long millis =
+          filterExceptions(() -> Native.SessionRecord_GetLastRatchetStepTime(guard.nativeHandle()));
+      return millis == 0 ? null : Instant.ofEpochMilli(millis);
+    }
+  }
+
+  /**
+   * Returns whether the current session was established with a post-quantum (PQXDH) key agreement.
+   *
+   * @throws IllegalStateException if there is no current session
+   */
+  public boolean isPostQuantum() {
+    try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
+      return filterExceptions(() -> Native.SessionRecord_IsPostQuantum(guard.nativeHandle()));
+    }
+  }
+
   /**
    * @return a serialized version of the current SessionRecord.
    */
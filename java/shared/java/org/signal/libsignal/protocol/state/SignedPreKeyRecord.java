@@ -40,6 +40,17 @@ public class SignedPreKeyRecord implements NativeHandleGuard.Owner {
             InvalidMessageException.class, () -> Native.SignedPreKeyRecord_Deserialize(serialized));
   }
 
+  /**
+   * Rewrites a serialized record with a version header, in the newest format libsignal reads.
+   *
+   * @see SessionRecord#migrateToCurrent
+   */
+  public static byte[] migrateToCurrent(byte[] serialized) throws InvalidMessageException {
+    return filterExceptions(
+        InvalidMessageException.class,
+        () -> Native.SignedPreKeyRecord_MigrateToCurrent(serialized));
+  }
+
   public int getId() {
     try (NativeHandleGuard guard = new NativeHandleGuard(this)) {
       return filterExceptions(() -> Native.SignedPreKeyRecord_GetId(guard.nativeHandle()));
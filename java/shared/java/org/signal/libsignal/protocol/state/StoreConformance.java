@@ -0,0 +1,65 @@
+//
+// Copyright 2026 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.protocol.state;
+
+import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
+
+import org.signal.libsignal.internal.Native;
+import org.signal.libsignal.internal.NativeHandleGuard;
+import org.signal.libsignal.protocol.IdentityKeyPair;
+import org.signal.libsignal.protocol.util.KeyHelper;
+
+/**
+ * Checks that a {@link SignalProtocolStore} implementation behaves the way libsignal expects.
+ *
+ * <p>These are the same checks libsignal runs against its own stores. They are meant to be run
+ * from an app's tests, not in production.
+ */
+public final class StoreConformance {
+  private StoreConformance() {}
+
+  /** Creates an empty store for the given local identity. */
+  @FunctionalInterface
+  public interface StoreFactory {
+    SignalProtocolStore create(IdentityKeyPair identityKeyPair, int registrationId);
+  }
+
+  /**
+   * Runs every check, each against a fresh store from {@code factory}.
+   *
+   * @throws IllegalStateException describing the first check that failed
+   */
+  public static void checkAll(StoreFactory factory) {
+    int count = Native.StoreConformance_CheckCount();
+    for (int check = 0; check < count; check++) {
+      IdentityKeyPair identity = IdentityKeyPair.generate();
+      int registrationId = KeyHelper.generateRegistrationId(false);
+      SignalProtocolStore store = factory.create(identity, registrationId);
+      run(check, identity, registrationId, store);
+    }
+  }
+
+  private static void run(
+      int check, IdentityKeyPair identity, int registrationId, SignalProtocolStore store) {
+    try (NativeHandleGuard publicKey =
+            new NativeHandleGuard(identity.getPublicKey().getPublicKey());
+        NativeHandleGuard privateKey = new NativeHandleGuard(identity.getPrivateKey()); ) {
+      filterExceptions(
+          () ->
+              Native.StoreConformance_Check(
+                  check,
+                  publicKey.nativeHandle(),
+                  privateKey.nativeHandle(),
+                  registrationId,
+                  store,
+                  store,
+                  store,
+                  store,
+                  store,
+                  store));
+    }
+  }
+}
@@ -13,6 +13,7 @@ import org.signal.libsignal.protocol.InvalidKeyIdException;
 import org.signal.libsignal.protocol.NoSessionException;
 import org.signal.libsignal.protocol.SignalProtocolAddress;
 import org.signal.libsignal.protocol.groups.state.InMemorySenderKeyStore;
+import org.signal.libsignal.protocol.groups.state.SenderKeyDistributionRecord;
 import org.signal.libsignal.protocol.groups.state.SenderKeyRecord;
 import org.signal.libsignal.protocol.state.KyberPreKeyRecord;
 import org.signal.libsignal.protocol.state.PreKeyRecord;
@@ -152,6 +153,18 @@ public class InMemorySignalProtocolStore implements SignalProtocolStore {
     return senderKeyStore.loadSenderKey(sender, distributionId);
   }
 
+  @Override
+  public void storeSenderKeyDistribution(
+      SignalProtocolAddress sender, UUID distributionId, SenderKeyDistributionRecord record) {
+    senderKeyStore.storeSenderKeyDistribution(sender, distributionId, record);
+  }
+
+  @Override
+  public SenderKeyDistributionRecord loadSenderKeyDistribution(
+      SignalProtocolAddress sender, UUID distributionId) {
+    return senderKeyStore.loadSenderKeyDistribution(sender, distributionId);
+  }
+
   @Override
   public KyberPreKeyRecord loadKyberPreKey(int kyberPreKeyId) throws InvalidKeyIdException {
     return kyberPreKeyStore.loadKyberPreKey(kyberPreKeyId);
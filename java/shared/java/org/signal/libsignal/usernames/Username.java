@@ -7,11 +7,13 @@ package org.signal.libsignal.usernames;
 
 import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
 
+import java.nio.ByteBuffer;
 import java.security.SecureRandom;
 import java.util.ArrayList;
 import java.util.Arrays;
 import java.util.List;
 import java.util.Objects;
+import java.util.UUID;
 import org.signal.libsignal.internal.Native;
 
 public final class Username {
@@ -52,6 +54,58 @@ public final class Username {
     }
   }
 
+  /**
+   * The contents of a username link URL, as shared via QR code or copied link.
+   *
+   * <p>The server ID identifies the encrypted username stored on the server; once fetched, it can
+   * be decrypted with {@link Username#fromLink} using the entropy.
+   */
+  public static class UsernameLinkUrl {
+    private final byte[] entropy;
+    private final UUID serverId;
+
+    private UsernameLinkUrl(final byte[] entropy, final UUID serverId) {
+      this.entropy = entropy;
+      this.serverId = serverId;
+    }
+
+    /**
+     * Parses a {@code https://signal.me/#eu/...} or {@code sgnl://signal.me/#eu/...} URL without
+     * contacting the server.
+     *
+     * @throws UsernameLinkInvalidLinkData if {@code url} isn't a username link
+     * @throws UsernameLinkInvalidEntropyDataLength if the link's payload is the wrong length
+     */
+    public static UsernameLinkUrl fromUrl(final String url) throws BaseUsernameException {
+      final byte[] bytes =
+          filterExceptions(BaseUsernameException.class, () -> Native.UsernameLink_ParseUrl(url));
+      final ByteBuffer serverId = ByteBuffer.wrap(bytes, 32, 16);
+      return new UsernameLinkUrl(
+          Arrays.copyOfRange(bytes, 0, 32), new UUID(serverId.getLong(), serverId.getLong()));
+    }
+
+    public byte[] getEntropy() {
+      return entropy;
+    }
+
+    public UUID getServerId() {
+      return serverId;
+    }
+
+    @Override
+    public boolean equals(final Object o) {
+      if (this == o) return true;
+      if (o == null || getClass() != o.getClass()) return false;
+      final UsernameLinkUrl that = (UsernameLinkUrl) o;
+      return Arrays.equals(entropy, that.entropy) && serverId.equals(that.serverId);
+    }
+
+    @Override
+    public int hashCode() {
+      return 31 * Arrays.hashCode(entropy) + serverId.hashCode();
+    }
+  }
+
   public Username(String username) throws BaseUsernameException {
     this.username = Objects.requireNonNull(username, "username");
     this.hash = hash(username);
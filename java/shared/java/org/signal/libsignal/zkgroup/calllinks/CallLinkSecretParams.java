@@ -27,6 +27,15 @@ public final class CallLinkSecretParams extends ByteArray {
     }
   }
 
+  /**
+   * Derives the room ID the calling server uses for the call link with {@code rootKey}.
+   *
+   * <p>This matches the derivation RingRTC uses for {@code CallLinkRootKey}.
+   */
+  public static byte[] deriveRoomId(byte[] rootKey) {
+    return Native.CallLinkSecretParams_DeriveRoomId(rootKey);
+  }
+
   public CallLinkSecretParams(byte[] contents) throws InvalidInputException {
     super(contents);
     filterExceptions(
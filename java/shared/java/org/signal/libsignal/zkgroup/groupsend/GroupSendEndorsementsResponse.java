@@ -9,10 +9,13 @@ import static org.signal.libsignal.internal.FilterExceptions.filterExceptions;
 import static org.signal.libsignal.zkgroup.internal.Constants.RANDOM_LENGTH;
 
 import java.security.SecureRandom;
+import java.time.Duration;
 import java.time.Instant;
 import java.util.ArrayList;
 import java.util.Collection;
 import java.util.List;
+import java.util.concurrent.TimeoutException;
+import org.signal.libsignal.internal.Deadline;
 import org.signal.libsignal.internal.Native;
 import org.signal.libsignal.protocol.ServiceId;
 import org.signal.libsignal.zkgroup.InvalidInputException;
@@ -125,9 +128,47 @@ public final class GroupSendEndorsementsResponse extends ByteArray {
       GroupSecretParams groupParams,
       ServerPublicParams serverPublicParams)
       throws VerificationFailedException {
+    try {
+      return receive(groupMembers, localUser, now, groupParams, serverPublicParams, Duration.ZERO);
+    } catch (TimeoutException e) {
+      throw new AssertionError("no timeout was set", e);
+    }
+  }
+
+  /**
+   * Receives, validates, and extracts the endorsements from a response, giving up if it takes
+   * longer than {@code timeout}.
+   *
+   * <p>The timeout is checked before each group member is processed and once more before the
+   * endorsements are verified as a batch, which can't be interrupted, so the operation may run
+   * slightly past it. A zero timeout means the operation is never abandoned.
+   *
+   * @throws VerificationFailedException if the endorsements are not valid for any reason
+   * @throws TimeoutException if the timeout passed before the operation completed
+   * @see #receive(List, ServiceId.Aci, GroupSecretParams, ServerPublicParams)
+   */
+  public ReceivedEndorsements receive(
+      List<ServiceId> groupMembers,
+      ServiceId.Aci localUser,
+      GroupSecretParams groupParams,
+      ServerPublicParams serverParams,
+      Duration timeout)
+      throws VerificationFailedException, TimeoutException {
+    return receive(groupMembers, localUser, Instant.now(), groupParams, serverParams, timeout);
+  }
+
+  private ReceivedEndorsements receive(
+      List<ServiceId> groupMembers,
+      ServiceId.Aci localUser,
+      Instant now,
+      GroupSecretParams groupParams,
+      ServerPublicParams serverPublicParams,
+      Duration timeout)
+      throws VerificationFailedException, TimeoutException {
     byte[][] endorsementContents =
         filterExceptions(
             VerificationFailedException.class,
+            TimeoutException.class,
             () ->
                 serverPublicParams.guardedMapChecked(
                     (publicParams) ->
@@ -137,7 +178,8 @@ public final class GroupSendEndorsementsResponse extends ByteArray {
                             localUser.toServiceIdFixedWidthBinary(),
                             now.getEpochSecond(),
                             groupParams.getInternalContentsForJNI(),
-                            publicParams)));
+                            publicParams,
+                            Deadline.timeoutMillis(timeout))));
 
     List<GroupSendEndorsement> endorsements = new ArrayList<>(endorsementContents.length - 1);
     for (int i = 0; i < endorsementContents.length - 1; ++i) {
@@ -187,9 +229,45 @@ public final class GroupSendEndorsementsResponse extends ByteArray {
       Instant now,
       ServerPublicParams serverPublicParams)
       throws VerificationFailedException {
+    try {
+      return receive(groupMembers, localUser, now, serverPublicParams, Duration.ZERO);
+    } catch (TimeoutException e) {
+      throw new AssertionError("no timeout was set", e);
+    }
+  }
+
+  /**
+   * Receives, validates, and extracts the endorsements from a response, giving up if it takes
+   * longer than {@code timeout}.
+   *
+   * <p>The timeout is checked before each group member is processed and once more before the
+   * endorsements are verified as a batch, which can't be interrupted, so the operation may run
+   * slightly past it. A zero timeout means the operation is never abandoned.
+   *
+   * @throws VerificationFailedException if the endorsements are not valid for any reason
+   * @throws TimeoutException if the timeout passed before the operation completed
+   * @see #receive(List, UuidCiphertext, ServerPublicParams)
+   */
+  public ReceivedEndorsements receive(
+      List<UuidCiphertext> groupMembers,
+      UuidCiphertext localUser,
+      ServerPublicParams serverParams,
+      Duration timeout)
+      throws VerificationFailedException, TimeoutException {
+    return receive(groupMembers, localUser, Instant.now(), serverParams, timeout);
+  }
+
+  private ReceivedEndorsements receive(
+      List<UuidCiphertext> groupMembers,
+      UuidCiphertext localUser,
+      Instant now,
+      ServerPublicParams serverPublicParams,
+      Duration timeout)
+      throws VerificationFailedException, TimeoutException {
     byte[][] endorsementContents =
         filterExceptions(
             VerificationFailedException.class,
+            TimeoutException.class,
             () ->
                 serverPublicParams.guardedMapChecked(
                     (publicParams) ->
@@ -198,7 +276,8 @@ public final class GroupSendEndorsementsResponse extends ByteArray {
                             UuidCiphertext.serializeAndConcatenate(groupMembers),
                             localUser.getInternalContentsForJNI(),
                             now.getEpochSecond(),
-                            publicParams)));
+                            publicParams,
+                            Deadline.timeoutMillis(timeout))));
 
     List<GroupSendEndorsement> endorsements = new ArrayList<>(endorsementContents.length - 1);
     for (int i = 0; i < endorsementContents.length - 1; ++i) {
@@ -60,3 +60,6 @@
 
 # Keep rustls-platform-verifier classes
 -keep, includedescriptorclasses class org.rustls.platformverifier.** { *; }
+
+# Nullability annotations are only needed at compile time.
+-dontwarn org.jetbrains.annotations.**
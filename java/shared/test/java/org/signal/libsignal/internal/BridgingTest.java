@@ -10,6 +10,7 @@ import static org.junit.Assert.*;
 import java.nio.ByteBuffer;
 import java.util.concurrent.ExecutionException;
 import org.junit.Test;
+import org.signal.libsignal.protocol.FailedOperation;
 
 public class BridgingTest {
   @Test
@@ -23,6 +24,22 @@ public class BridgingTest {
         () -> NativeTesting.TESTING_ErrorOnBorrowIo(-1, null).get());
   }
 
+  @Test
+  public void testErrorsRecordOperation() throws Exception {
+    IllegalArgumentException e =
+        assertThrows(
+            IllegalArgumentException.class, () -> NativeTesting.TESTING_ErrorOnBorrowSync(null));
+    assertEquals("TESTING_ErrorOnBorrowSync", FailedOperation.of(e));
+
+    ExecutionException wrapped =
+        assertThrows(
+            ExecutionException.class,
+            () -> NativeTesting.TESTING_PanicOnLoadIo(-1, null, null).get());
+    assertEquals("TESTING_PanicOnLoadIo", FailedOperation.of(wrapped));
+
+    assertNull(FailedOperation.of(new IllegalArgumentException()));
+  }
+
   @Test
   public void testPanicOnBorrow() throws Exception {
     assertThrows(AssertionError.class, () -> NativeTesting.TESTING_PanicOnBorrowSync(null));
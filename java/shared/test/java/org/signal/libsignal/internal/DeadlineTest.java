@@ -0,0 +1,33 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.internal;
+
+import static org.junit.Assert.assertEquals;
+import static org.junit.Assert.assertThrows;
+
+import java.time.Duration;
+import org.junit.Test;
+
+public class DeadlineTest {
+  @Test
+  public void roundsUpToWholeMilliseconds() {
+    assertEquals(0, Deadline.timeoutMillis(Duration.ZERO));
+    assertEquals(1, Deadline.timeoutMillis(Duration.ofNanos(1)));
+    assertEquals(5, Deadline.timeoutMillis(Duration.ofMillis(5)));
+    assertEquals(6, Deadline.timeoutMillis(Duration.ofMillis(5).plusNanos(1)));
+  }
+
+  @Test
+  public void saturatesLongTimeouts() {
+    assertEquals(Long.MAX_VALUE, Deadline.timeoutMillis(Duration.ofSeconds(Long.MAX_VALUE)));
+  }
+
+  @Test
+  public void rejectsNegativeTimeouts() {
+    assertThrows(
+        IllegalArgumentException.class, () -> Deadline.timeoutMillis(Duration.ofMillis(-1)));
+  }
+}
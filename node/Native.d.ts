@@ -9,8 +9,19 @@ type Uuid = Buffer;
 
 /// A Native.Timestamp may be measured in seconds or in milliseconds;
 /// what's important is that it's an integer less than Number.MAX_SAFE_INTEGER.
+/// Unlike the ID types below it isn't branded: one brand can't tell seconds from
+/// milliseconds, which is the mix-up worth catching.
 type Timestamp = number;
 
+/// A Native.Deadline is a timeout in milliseconds, starting from when the call is made;
+/// zero means no deadline.
+type Deadline = number;
+
+/// Branded so that one kind of ID can't be passed where another is expected.
+/// Plain numbers have to be cast explicitly, e.g. `deviceId as Native.DeviceId`.
+type DeviceId = number & { readonly __brand: 'DeviceId' };
+type RegistrationId = number & { readonly __brand: 'RegistrationId' };
+
 interface LookupResponse {
   entries: Map<string, LookupResponseEntry>;
   debugPermitsUsed: number;
@@ -39,6 +50,13 @@ interface ResponseAndDebugInfo {
   debugInfo: ChatServiceDebugInfo;
 }
 
+interface BackgroundFlushReport {
+  acksFlushed: number;
+  acksUnflushed: number;
+  requestsFlushed: number;
+  requestsUnflushed: number;
+}
+
 interface SealedSenderMultiRecipientMessageRecipient {
   deviceIds: number[];
   registrationIds: number[];
@@ -104,6 +122,28 @@ export abstract class SenderKeyStore {
     sender: ProtocolAddress,
     distributionId: Uuid
   ): Promise<SenderKeyRecord | null>;
+  _saveSenderKeyDistribution(
+    sender: ProtocolAddress,
+    distributionId: Uuid,
+    record: SenderKeyDistributionRecord
+  ): Promise<void>;
+  _getSenderKeyDistribution(
+    sender: ProtocolAddress,
+    distributionId: Uuid
+  ): Promise<SenderKeyDistributionRecord | null>;
+}
+
+export abstract class ReplayCache {
+  _contains(
+    address: ProtocolAddress,
+    senderRatchetKey: PublicKey,
+    counter: number
+  ): Promise<boolean>;
+  _insert(
+    address: ProtocolAddress,
+    senderRatchetKey: PublicKey,
+    counter: number
+  ): Promise<void>;
 }
 
 export abstract class InputStream {
@@ -132,9 +172,18 @@ type Wrapper<T> = Readonly<{
   _nativeHandle: T;
 }>;
 
+interface MessageBackupValidationFinding {
+  frameIndex: number | null;
+  fieldPath: string;
+  kind: number;
+  severity: number;
+  message: string;
+}
+
 interface MessageBackupValidationOutcome {
   errorMessage: string | null;
   unknownFieldMessages: Array<string>;
+  findings: Array<MessageBackupValidationFinding>;
 }
 
 // eslint-disable-next-line @typescript-eslint/no-unused-vars
@@ -178,6 +227,14 @@ export function BackupKey_DeriveLocalBackupMetadataKey(backupKey: Buffer): Buffe
 export function BackupKey_DeriveMediaEncryptionKey(backupKey: Buffer, mediaId: Buffer): Buffer;
 export function BackupKey_DeriveMediaId(backupKey: Buffer, mediaName: string): Buffer;
 export function BackupKey_DeriveThumbnailTransitEncryptionKey(backupKey: Buffer, mediaId: Buffer): Buffer;
+export function BackupPaddingPolicy_Default(): BackupPaddingPolicy;
+export function BackupPaddingPolicy_GetEncryptedSize(policy: Wrapper<BackupPaddingPolicy>, compressedLen: bigint): bigint;
+export function BackupPaddingPolicy_GetPaddedSize(policy: Wrapper<BackupPaddingPolicy>, compressedLen: bigint): bigint;
+export function BackupPaddingPolicy_New(growthFactorPercent: number, minSize: bigint): BackupPaddingPolicy;
+export function BridgeManifest_GetSha256(): string;
+export function BridgeMetrics_Reset(): void;
+export function BridgeMetrics_SetEnabled(enabled: boolean): void;
+export function BridgeMetrics_Snapshot(): string;
 export function CallLinkAuthCredentialPresentation_CheckValidContents(presentationBytes: Buffer): void;
 export function CallLinkAuthCredentialPresentation_GetUserId(presentationBytes: Buffer): Serialized<UuidCiphertext>;
 export function CallLinkAuthCredentialPresentation_Verify(presentationBytes: Buffer, now: Timestamp, serverParamsBytes: Buffer, callLinkParamsBytes: Buffer): void;
@@ -190,13 +247,26 @@ export function CallLinkPublicParams_CheckValidContents(paramsBytes: Buffer): vo
 export function CallLinkSecretParams_CheckValidContents(paramsBytes: Buffer): void;
 export function CallLinkSecretParams_DecryptUserId(paramsBytes: Buffer, userId: Serialized<UuidCiphertext>): Buffer;
 export function CallLinkSecretParams_DeriveFromRootKey(rootKey: Buffer): Buffer;
+export function CallLinkSecretParams_DeriveRoomId(rootKey: Buffer): Buffer;
 export function CallLinkSecretParams_GetPublicParams(paramsBytes: Buffer): Buffer;
 export function Cds2ClientState_New(mrenclave: Buffer, attestationMsg: Buffer, currentTimestamp: Timestamp): SgxClientState;
+export function CdsiConnectionPool_new(idleTimeoutMillis: number): CdsiConnectionPool;
+export function CdsiContactChanges_Count(changes: Wrapper<CdsiContactChanges>): number;
+export function CdsiContactChanges_GetAci(changes: Wrapper<CdsiContactChanges>, index: number): string | null;
+export function CdsiContactChanges_GetE164(changes: Wrapper<CdsiContactChanges>, index: number): string;
+export function CdsiContactChanges_GetKind(changes: Wrapper<CdsiContactChanges>, index: number): number;
+export function CdsiContactChanges_GetPni(changes: Wrapper<CdsiContactChanges>, index: number): string | null;
 export function CdsiLookup_complete(asyncRuntime: Wrapper<TokioAsyncContext>, lookup: Wrapper<CdsiLookup>): Promise<LookupResponse>;
 export function CdsiLookup_new(asyncRuntime: Wrapper<TokioAsyncContext>, connectionManager: Wrapper<ConnectionManager>, username: string, password: string, request: Wrapper<LookupRequest>): Promise<CdsiLookup>;
+export function CdsiLookup_newPooled(asyncRuntime: Wrapper<TokioAsyncContext>, connectionManager: Wrapper<ConnectionManager>, username: string, password: string, request: Wrapper<LookupRequest>, pool: Wrapper<CdsiConnectionPool>, prepareNext: boolean): Promise<CdsiLookup>;
 export function CdsiLookup_token(lookup: Wrapper<CdsiLookup>): Buffer;
 export function ChatService_SetListenerAuth(runtime: Wrapper<TokioAsyncContext>, chat: Wrapper<AuthChat>, makeListener: MakeChatListener | null): void;
 export function ChatService_SetListenerUnauth(runtime: Wrapper<TokioAsyncContext>, chat: Wrapper<UnauthChat>, makeListener: MakeChatListener | null): void;
+export function ChatService_auth_fetch_profile_key_credential(asyncRuntime: Wrapper<TokioAsyncContext>, chat: Wrapper<AuthChat>, serverPublicParams: Wrapper<ServerPublicParams>, randomness: Buffer, aci: Buffer, profileKey: Serialized<ProfileKey>, currentTimeInSeconds: Timestamp, timeoutMillis: number): Promise<Buffer>;
+export function ChatService_auth_fetch_sender_certificate(asyncRuntime: Wrapper<TokioAsyncContext>, chat: Wrapper<AuthChat>, includeE164: boolean, timeoutMillis: number): Promise<SenderCertificate>;
+export function ChatService_auth_next_receipt_batch(chat: Wrapper<AuthChat>): ReceiptBatch | null;
+export function ChatService_auth_queue_receipt(chat: Wrapper<AuthChat>, sender: Buffer, kind: number, messageTimestamp: Timestamp): void;
+export function ChatService_auth_search_key_transparency(asyncRuntime: Wrapper<TokioAsyncContext>, chat: Wrapper<AuthChat>, cache: Wrapper<KeyTransparencySearchCache>, signatureKey: Buffer, vrfKey: Buffer, auditorKey: Buffer, searchKey: Buffer, timeoutMillis: number): Promise<Buffer>;
 export function ChatService_auth_send(asyncRuntime: Wrapper<TokioAsyncContext>, chat: Wrapper<AuthChat>, httpRequest: Wrapper<HttpRequest>, timeoutMillis: number): Promise<ChatResponse>;
 export function ChatService_auth_send_and_debug(asyncRuntime: Wrapper<TokioAsyncContext>, chat: Wrapper<AuthChat>, httpRequest: Wrapper<HttpRequest>, timeoutMillis: number): Promise<ResponseAndDebugInfo>;
 export function ChatService_connect_auth(asyncRuntime: Wrapper<TokioAsyncContext>, chat: Wrapper<AuthChat>): Promise<ChatServiceDebugInfo>;
@@ -205,6 +275,8 @@ export function ChatService_disconnect_auth(asyncRuntime: Wrapper<TokioAsyncCont
 export function ChatService_disconnect_unauth(asyncRuntime: Wrapper<TokioAsyncContext>, chat: Wrapper<UnauthChat>): Promise<void>;
 export function ChatService_new_auth(connectionManager: Wrapper<ConnectionManager>, username: string, password: string, receiveStories: boolean): AuthChat;
 export function ChatService_new_unauth(connectionManager: Wrapper<ConnectionManager>): UnauthChat;
+export function ChatService_prepare_for_background_auth(asyncRuntime: Wrapper<TokioAsyncContext>, chat: Wrapper<AuthChat>, budgetMillis: number): Promise<BackgroundFlushReport>;
+export function ChatService_prepare_for_background_unauth(asyncRuntime: Wrapper<TokioAsyncContext>, chat: Wrapper<UnauthChat>, budgetMillis: number): Promise<BackgroundFlushReport>;
 export function ChatService_unauth_send(asyncRuntime: Wrapper<TokioAsyncContext>, chat: Wrapper<UnauthChat>, httpRequest: Wrapper<HttpRequest>, timeoutMillis: number): Promise<ChatResponse>;
 export function ChatService_unauth_send_and_debug(asyncRuntime: Wrapper<TokioAsyncContext>, chat: Wrapper<UnauthChat>, httpRequest: Wrapper<HttpRequest>, timeoutMillis: number): Promise<ResponseAndDebugInfo>;
 export function CiphertextMessage_FromPlaintextContent(m: Wrapper<PlaintextContent>): CiphertextMessage;
@@ -214,11 +286,20 @@ export function ComparableBackup_GetComparableString(backup: Wrapper<ComparableB
 export function ComparableBackup_GetUnknownFields(backup: Wrapper<ComparableBackup>): string[];
 export function ComparableBackup_ReadUnencrypted(stream: InputStream, len: bigint, purpose: number): Promise<ComparableBackup>;
 export function ConnectionManager_clear_proxy(connectionManager: Wrapper<ConnectionManager>): void;
+export function ConnectionManager_export_route_state(connectionManager: Wrapper<ConnectionManager>, key: Buffer): Buffer;
+export function ConnectionManager_get_proxy(connectionManager: Wrapper<ConnectionManager>): string | null;
+export function ConnectionManager_get_route_order(connectionManager: Wrapper<ConnectionManager>): Buffer;
+export function ConnectionManager_import_route_state(connectionManager: Wrapper<ConnectionManager>, key: Buffer, state: Buffer): void;
 export function ConnectionManager_new(environment: number, userAgent: string): ConnectionManager;
 export function ConnectionManager_on_network_change(connectionManager: Wrapper<ConnectionManager>): void;
+export function ConnectionManager_server_time(connectionManager: Wrapper<ConnectionManager>): Timestamp;
 export function ConnectionManager_set_censorship_circumvention_enabled(connectionManager: Wrapper<ConnectionManager>, enabled: boolean): void;
+export function ConnectionManager_set_certificate_pins(connectionManager: Wrapper<ConnectionManager>, service: number, pins: Buffer): void;
+export function ConnectionManager_set_chat_websocket_limits(connectionManager: Wrapper<ConnectionManager>, maxFrameSize: number, maxMessageSize: number, maxPendingSendBytes: number): void;
 export function ConnectionManager_set_ipv6_enabled(connectionManager: Wrapper<ConnectionManager>, ipv6Enabled: boolean): void;
 export function ConnectionManager_set_proxy(connectionManager: Wrapper<ConnectionManager>, host: string, port: number): void;
+export function ConnectionManager_set_retry_policy(connectionManager: Wrapper<ConnectionManager>, service: number, initialDelayMillis: number, multiplierPercent: number, jitterPercent: number, maxDelayMillis: number, maxAttempts: number): void;
+export function ConnectionManager_set_route_order(connectionManager: Wrapper<ConnectionManager>, order: Buffer): void;
 export function CreateCallLinkCredentialPresentation_CheckValidContents(presentationBytes: Buffer): void;
 export function CreateCallLinkCredentialPresentation_Verify(presentationBytes: Buffer, roomId: Buffer, now: Timestamp, serverParamsBytes: Buffer, callLinkParamsBytes: Buffer): void;
 export function CreateCallLinkCredentialRequestContext_CheckValidContents(contextBytes: Buffer): void;
@@ -234,11 +315,13 @@ export function CreateOTP(username: string, secret: Buffer): string;
 export function CreateOTPFromBase64(username: string, secret: string): string;
 export function DecryptionErrorMessage_Deserialize(data: Buffer): DecryptionErrorMessage;
 export function DecryptionErrorMessage_ExtractFromSerializedContent(bytes: Buffer): DecryptionErrorMessage;
-export function DecryptionErrorMessage_ForOriginalMessage(originalBytes: Buffer, originalType: number, originalTimestamp: Timestamp, originalSenderDeviceId: number): DecryptionErrorMessage;
-export function DecryptionErrorMessage_GetDeviceId(obj: Wrapper<DecryptionErrorMessage>): number;
+export function DecryptionErrorMessage_ForOriginalMessage(originalBytes: Buffer, originalType: number, originalTimestamp: Timestamp, originalSenderDeviceId: DeviceId): DecryptionErrorMessage;
+export function DecryptionErrorMessage_GetDeviceId(obj: Wrapper<DecryptionErrorMessage>): DeviceId;
 export function DecryptionErrorMessage_GetRatchetKey(m: Wrapper<DecryptionErrorMessage>): PublicKey | null;
 export function DecryptionErrorMessage_GetTimestamp(obj: Wrapper<DecryptionErrorMessage>): Timestamp;
 export function DecryptionErrorMessage_Serialize(obj: Wrapper<DecryptionErrorMessage>): Buffer;
+export function E164_IsValid(e164: string): boolean;
+export function E164_Normalize(raw: string, regionCallingCode: number): string | null;
 export function ExpiringProfileKeyCredentialResponse_CheckValidContents(buffer: Buffer): void;
 export function ExpiringProfileKeyCredential_CheckValidContents(buffer: Buffer): void;
 export function ExpiringProfileKeyCredential_GetExpirationTime(credential: Serialized<ExpiringProfileKeyCredential>): Timestamp;
@@ -274,21 +357,29 @@ export function GroupSendEndorsement_ToToken(endorsement: Buffer, groupParams: S
 export function GroupSendEndorsementsResponse_CheckValidContents(bytes: Buffer): void;
 export function GroupSendEndorsementsResponse_GetExpiration(responseBytes: Buffer): Timestamp;
 export function GroupSendEndorsementsResponse_IssueDeterministic(concatenatedGroupMemberCiphertexts: Buffer, keyPair: Buffer, randomness: Buffer): Buffer;
-export function GroupSendEndorsementsResponse_ReceiveAndCombineWithCiphertexts(responseBytes: Buffer, concatenatedGroupMemberCiphertexts: Buffer, localUserCiphertext: Buffer, now: Timestamp, serverParams: Wrapper<ServerPublicParams>): Buffer[];
-export function GroupSendEndorsementsResponse_ReceiveAndCombineWithServiceIds(responseBytes: Buffer, groupMembers: Buffer, localUser: Buffer, now: Timestamp, groupParams: Serialized<GroupSecretParams>, serverParams: Wrapper<ServerPublicParams>): Buffer[];
+export function GroupSendEndorsementsResponse_ReceiveAndCombineWithCiphertexts(responseBytes: Buffer, concatenatedGroupMemberCiphertexts: Buffer, localUserCiphertext: Buffer, now: Timestamp, serverParams: Wrapper<ServerPublicParams>, deadline: Deadline): Buffer[];
+export function GroupSendEndorsementsResponse_ReceiveAndCombineWithServiceIds(responseBytes: Buffer, groupMembers: Buffer, localUser: Buffer, now: Timestamp, groupParams: Serialized<GroupSecretParams>, serverParams: Wrapper<ServerPublicParams>, deadline: Deadline): Buffer[];
 export function GroupSendFullToken_CheckValidContents(bytes: Buffer): void;
+export function GroupSendFullToken_FromCompact(compactToken: Buffer): Buffer;
 export function GroupSendFullToken_GetExpiration(token: Buffer): Timestamp;
+export function GroupSendFullToken_ToCompact(token: Buffer): Buffer;
 export function GroupSendFullToken_Verify(token: Buffer, userIds: Buffer, now: Timestamp, keyPair: Buffer): void;
 export function GroupSendToken_CheckValidContents(bytes: Buffer): void;
 export function GroupSendToken_ToFullToken(token: Buffer, expiration: Timestamp): Buffer;
 export function HKDF_DeriveSecrets(outputLength: number, ikm: Buffer, label: Buffer | null, salt: Buffer | null): Buffer;
 export function HsmEnclaveClient_CompleteHandshake(cli: Wrapper<HsmEnclaveClient>, handshakeReceived: Buffer): void;
+export function HsmEnclaveClient_EnablePipelining(cli: Wrapper<HsmEnclaveClient>, maxInFlight: number): void;
 export function HsmEnclaveClient_EstablishedRecv(cli: Wrapper<HsmEnclaveClient>, receivedCiphertext: Buffer): Buffer;
 export function HsmEnclaveClient_EstablishedSend(cli: Wrapper<HsmEnclaveClient>, plaintextToSend: Buffer): Buffer;
+export function HsmEnclaveClient_InFlight(cli: Wrapper<HsmEnclaveClient>): number;
 export function HsmEnclaveClient_InitialRequest(obj: Wrapper<HsmEnclaveClient>): Buffer;
+export function HsmEnclaveClient_LastReceivedRequestId(cli: Wrapper<HsmEnclaveClient>): bigint;
+export function HsmEnclaveClient_LastSentRequestId(cli: Wrapper<HsmEnclaveClient>): bigint;
 export function HsmEnclaveClient_New(trustedPublicKey: Buffer, trustedCodeHashes: Buffer): HsmEnclaveClient;
 export function HttpRequest_add_header(request: Wrapper<HttpRequest>, name: string, value: string): void;
 export function HttpRequest_new(method: string, path: string, bodyAsSlice: Buffer | null): HttpRequest;
+export function IdentityDigest_Compute(iterations: number, version: number, stableIdentifier: Buffer, identityKey: Wrapper<PublicKey>): Buffer;
+export function IdentityDigest_DisplayString(version: number, digest: Buffer): string;
 export function IdentityKeyPair_Deserialize(buffer: Buffer): {publicKey:PublicKey,privateKey:PrivateKey};
 export function IdentityKeyPair_Serialize(publicKey: Wrapper<PublicKey>, privateKey: Wrapper<PrivateKey>): Buffer;
 export function IdentityKeyPair_SignAlternateIdentity(publicKey: Wrapper<PublicKey>, privateKey: Wrapper<PrivateKey>, otherIdentity: Wrapper<PublicKey>): Buffer;
@@ -297,6 +388,7 @@ export function IncrementalMac_CalculateChunkSize(dataSize: number): number;
 export function IncrementalMac_Finalize(mac: Wrapper<IncrementalMac>): Buffer;
 export function IncrementalMac_Initialize(key: Buffer, chunkSize: number): IncrementalMac;
 export function IncrementalMac_Update(mac: Wrapper<IncrementalMac>, bytes: Buffer, offset: number, length: number): Buffer;
+export function KeyTransparencySearchCache_New(ttlSeconds: number, maxEntries: number): KeyTransparencySearchCache;
 export function KyberKeyPair_Generate(): KyberKeyPair;
 export function KyberKeyPair_GetPublicKey(keyPair: Wrapper<KyberKeyPair>): KyberPublicKey;
 export function KyberKeyPair_GetSecretKey(keyPair: Wrapper<KyberKeyPair>): KyberSecretKey;
@@ -307,6 +399,7 @@ export function KyberPreKeyRecord_GetPublicKey(obj: Wrapper<KyberPreKeyRecord>):
 export function KyberPreKeyRecord_GetSecretKey(obj: Wrapper<KyberPreKeyRecord>): KyberSecretKey;
 export function KyberPreKeyRecord_GetSignature(obj: Wrapper<KyberPreKeyRecord>): Buffer;
 export function KyberPreKeyRecord_GetTimestamp(obj: Wrapper<KyberPreKeyRecord>): Timestamp;
+export function KyberPreKeyRecord_MigrateToCurrent(data: Buffer): Buffer;
 export function KyberPreKeyRecord_New(id: number, timestamp: Timestamp, keyPair: Wrapper<KyberKeyPair>, signature: Buffer): KyberPreKeyRecord;
 export function KyberPreKeyRecord_Serialize(obj: Wrapper<KyberPreKeyRecord>): Buffer;
 export function KyberPublicKey_Deserialize(data: Buffer): KyberPublicKey;
@@ -317,45 +410,57 @@ export function KyberSecretKey_Serialize(obj: Wrapper<KyberSecretKey>): Buffer;
 export function LookupRequest_addAciAndAccessKey(request: Wrapper<LookupRequest>, aci: Buffer, accessKey: Buffer): void;
 export function LookupRequest_addE164(request: Wrapper<LookupRequest>, e164: string): void;
 export function LookupRequest_addPreviousE164(request: Wrapper<LookupRequest>, e164: string): void;
+export function LookupRequest_addRawE164s(request: Wrapper<LookupRequest>, raw: Buffer[], regionCallingCode: number): string[];
 export function LookupRequest_new(): LookupRequest;
 export function LookupRequest_setReturnAcisWithoutUaks(request: Wrapper<LookupRequest>, returnAcisWithoutUaks: boolean): void;
 export function LookupRequest_setToken(request: Wrapper<LookupRequest>, token: Buffer): void;
+export function LookupResponse_reconcile(existing: Buffer, response: Buffer): CdsiContactChanges;
 export function MessageBackupKey_FromAccountEntropyPool(accountEntropy: string, aci: Buffer): MessageBackupKey;
 export function MessageBackupKey_FromBackupKeyAndBackupId(backupKey: Buffer, backupId: Buffer): MessageBackupKey;
 export function MessageBackupKey_FromMasterKey(masterKey: Buffer, aci: Buffer): MessageBackupKey;
 export function MessageBackupKey_GetAesKey(key: Wrapper<MessageBackupKey>): Buffer;
 export function MessageBackupKey_GetHmacKey(key: Wrapper<MessageBackupKey>): Buffer;
-export function MessageBackupValidator_Validate(key: Wrapper<MessageBackupKey>, firstStream: InputStream, secondStream: InputStream, len: bigint, purpose: number): Promise<MessageBackupValidationOutcome>;
+export function MessageBackupValidator_Validate(key: Wrapper<MessageBackupKey>, firstStream: InputStream, secondStream: InputStream, len: bigint, purpose: number, deadline: Deadline): Promise<MessageBackupValidationOutcome>;
+export function MessageBackup_GetEncryptedSize(compressedLen: bigint): bigint;
+export function MessageBackup_GetPaddedSize(compressedLen: bigint): bigint;
 export function MinidumpToJSONString(buffer: Buffer): string;
+export function Mp4SanitizerOptions_New(): Mp4SanitizerOptions;
+export function Mp4SanitizerOptions_SetCodecPolicy(options: Wrapper<Mp4SanitizerOptions>, allowHevc: boolean, allowAv1: boolean, allowOpus: boolean): void;
+export function Mp4SanitizerOptions_SetMetadataBoxPreserved(options: Wrapper<Mp4SanitizerOptions>, boxType: Buffer, preserved: boolean): void;
+export function Mp4SanitizerOptions_SetMetadataStripped(options: Wrapper<Mp4SanitizerOptions>, stripped: boolean): void;
 export function Mp4Sanitizer_Sanitize(input: InputStream, len: bigint): Promise<SanitizedMetadata>;
+export function Mp4Sanitizer_SanitizeWithOptions(input: InputStream, len: bigint, options: Wrapper<Mp4SanitizerOptions>): Promise<SanitizedMetadata>;
+export function Mp4Sanitizer_SetMaxPooledBufferBytes(maxBytes: bigint): void;
+export function ParseLimits_Set(maxMessageSize: bigint, maxCollectionCount: bigint, maxDecompressedSize: bigint, maxMediaMetadataSize: bigint, maxRetainedSize: bigint): void;
 export function PlaintextContent_Deserialize(data: Buffer): PlaintextContent;
 export function PlaintextContent_FromDecryptionErrorMessage(m: Wrapper<DecryptionErrorMessage>): PlaintextContent;
 export function PlaintextContent_GetBody(obj: Wrapper<PlaintextContent>): Buffer;
 export function PlaintextContent_Serialize(obj: Wrapper<PlaintextContent>): Buffer;
-export function PreKeyBundle_GetDeviceId(obj: Wrapper<PreKeyBundle>): number;
+export function PreKeyBundle_GetDeviceId(obj: Wrapper<PreKeyBundle>): DeviceId;
 export function PreKeyBundle_GetIdentityKey(p: Wrapper<PreKeyBundle>): PublicKey;
 export function PreKeyBundle_GetKyberPreKeyId(obj: Wrapper<PreKeyBundle>): number | null;
 export function PreKeyBundle_GetKyberPreKeyPublic(bundle: Wrapper<PreKeyBundle>): KyberPublicKey | null;
 export function PreKeyBundle_GetKyberPreKeySignature(bundle: Wrapper<PreKeyBundle>): Buffer;
 export function PreKeyBundle_GetPreKeyId(obj: Wrapper<PreKeyBundle>): number | null;
 export function PreKeyBundle_GetPreKeyPublic(obj: Wrapper<PreKeyBundle>): PublicKey | null;
-export function PreKeyBundle_GetRegistrationId(obj: Wrapper<PreKeyBundle>): number;
+export function PreKeyBundle_GetRegistrationId(obj: Wrapper<PreKeyBundle>): RegistrationId;
 export function PreKeyBundle_GetSignedPreKeyId(obj: Wrapper<PreKeyBundle>): number;
 export function PreKeyBundle_GetSignedPreKeyPublic(obj: Wrapper<PreKeyBundle>): PublicKey;
 export function PreKeyBundle_GetSignedPreKeySignature(obj: Wrapper<PreKeyBundle>): Buffer;
-export function PreKeyBundle_New(registrationId: number, deviceId: number, prekeyId: number | null, prekey: Wrapper<PublicKey> | null, signedPrekeyId: number, signedPrekey: Wrapper<PublicKey>, signedPrekeySignature: Buffer, identityKey: Wrapper<PublicKey>, kyberPrekeyId: number | null, kyberPrekey: Wrapper<KyberPublicKey> | null, kyberPrekeySignature: Buffer): PreKeyBundle;
+export function PreKeyBundle_New(registrationId: RegistrationId, deviceId: DeviceId, prekeyId: number | null, prekey: Wrapper<PublicKey> | null, signedPrekeyId: number, signedPrekey: Wrapper<PublicKey>, signedPrekeySignature: Buffer, identityKey: Wrapper<PublicKey>, kyberPrekeyId: number | null, kyberPrekey: Wrapper<KyberPublicKey> | null, kyberPrekeySignature: Buffer): PreKeyBundle;
 export function PreKeyRecord_Deserialize(data: Buffer): PreKeyRecord;
 export function PreKeyRecord_GetId(obj: Wrapper<PreKeyRecord>): number;
 export function PreKeyRecord_GetPrivateKey(obj: Wrapper<PreKeyRecord>): PrivateKey;
 export function PreKeyRecord_GetPublicKey(obj: Wrapper<PreKeyRecord>): PublicKey;
+export function PreKeyRecord_MigrateToCurrent(data: Buffer): Buffer;
 export function PreKeyRecord_New(id: number, pubKey: Wrapper<PublicKey>, privKey: Wrapper<PrivateKey>): PreKeyRecord;
 export function PreKeyRecord_Serialize(obj: Wrapper<PreKeyRecord>): Buffer;
 export function PreKeySignalMessage_Deserialize(data: Buffer): PreKeySignalMessage;
 export function PreKeySignalMessage_GetPreKeyId(obj: Wrapper<PreKeySignalMessage>): number | null;
-export function PreKeySignalMessage_GetRegistrationId(obj: Wrapper<PreKeySignalMessage>): number;
+export function PreKeySignalMessage_GetRegistrationId(obj: Wrapper<PreKeySignalMessage>): RegistrationId;
 export function PreKeySignalMessage_GetSignedPreKeyId(obj: Wrapper<PreKeySignalMessage>): number;
 export function PreKeySignalMessage_GetVersion(obj: Wrapper<PreKeySignalMessage>): number;
-export function PreKeySignalMessage_New(messageVersion: number, registrationId: number, preKeyId: number | null, signedPreKeyId: number, baseKey: Wrapper<PublicKey>, identityKey: Wrapper<PublicKey>, signalMessage: Wrapper<SignalMessage>): PreKeySignalMessage;
+export function PreKeySignalMessage_New(messageVersion: number, registrationId: RegistrationId, preKeyId: number | null, signedPreKeyId: number, baseKey: Wrapper<PublicKey>, identityKey: Wrapper<PublicKey>, signalMessage: Wrapper<SignalMessage>): PreKeySignalMessage;
 export function PreKeySignalMessage_Serialize(obj: Wrapper<PreKeySignalMessage>): Buffer;
 export function PrivateKey_Agree(privateKey: Wrapper<PrivateKey>, publicKey: Wrapper<PublicKey>): Buffer;
 export function PrivateKey_Deserialize(data: Buffer): PrivateKey;
@@ -366,8 +471,10 @@ export function PrivateKey_Sign(key: Wrapper<PrivateKey>, message: Buffer): Buff
 export function ProfileKeyCiphertext_CheckValidContents(buffer: Buffer): void;
 export function ProfileKeyCommitment_CheckValidContents(buffer: Buffer): void;
 export function ProfileKeyCredentialPresentation_CheckValidContents(presentationBytes: Buffer): void;
+export function ProfileKeyCredentialPresentation_FromCompact(compactBytes: Buffer): Buffer;
 export function ProfileKeyCredentialPresentation_GetProfileKeyCiphertext(presentationBytes: Buffer): Serialized<ProfileKeyCiphertext>;
 export function ProfileKeyCredentialPresentation_GetUuidCiphertext(presentationBytes: Buffer): Serialized<UuidCiphertext>;
+export function ProfileKeyCredentialPresentation_ToCompact(presentationBytes: Buffer): Buffer;
 export function ProfileKeyCredentialRequestContext_CheckValidContents(buffer: Buffer): void;
 export function ProfileKeyCredentialRequestContext_GetRequest(context: Serialized<ProfileKeyCredentialRequestContext>): Serialized<ProfileKeyCredentialRequest>;
 export function ProfileKeyCredentialRequest_CheckValidContents(buffer: Buffer): void;
@@ -375,15 +482,18 @@ export function ProfileKey_CheckValidContents(buffer: Buffer): void;
 export function ProfileKey_DeriveAccessKey(profileKey: Serialized<ProfileKey>): Buffer;
 export function ProfileKey_GetCommitment(profileKey: Serialized<ProfileKey>, userId: Buffer): Serialized<ProfileKeyCommitment>;
 export function ProfileKey_GetProfileKeyVersion(profileKey: Serialized<ProfileKey>, userId: Buffer): Buffer;
-export function ProtocolAddress_DeviceId(obj: Wrapper<ProtocolAddress>): number;
+export function ProtocolAddress_DeviceId(obj: Wrapper<ProtocolAddress>): DeviceId;
 export function ProtocolAddress_Name(obj: Wrapper<ProtocolAddress>): string;
-export function ProtocolAddress_New(name: string, deviceId: number): ProtocolAddress;
+export function ProtocolAddress_New(name: string, deviceId: DeviceId): ProtocolAddress;
 export function PublicKey_Compare(key1: Wrapper<PublicKey>, key2: Wrapper<PublicKey>): number;
 export function PublicKey_Deserialize(data: Buffer): PublicKey;
 export function PublicKey_Equals(lhs: Wrapper<PublicKey>, rhs: Wrapper<PublicKey>): boolean;
 export function PublicKey_GetPublicKeyBytes(obj: Wrapper<PublicKey>): Buffer;
 export function PublicKey_Serialize(obj: Wrapper<PublicKey>): Buffer;
 export function PublicKey_Verify(key: Wrapper<PublicKey>, message: Buffer, signature: Buffer): boolean;
+export function ReceiptBatch_GetKind(batch: Wrapper<ReceiptBatch>): number;
+export function ReceiptBatch_GetSender(batch: Wrapper<ReceiptBatch>): Buffer;
+export function ReceiptBatch_GetTimestamps(batch: Wrapper<ReceiptBatch>): Buffer;
 export function ReceiptCredentialPresentation_CheckValidContents(buffer: Buffer): void;
 export function ReceiptCredentialPresentation_GetReceiptExpirationTime(presentation: Serialized<ReceiptCredentialPresentation>): Timestamp;
 export function ReceiptCredentialPresentation_GetReceiptLevel(presentation: Serialized<ReceiptCredentialPresentation>): bigint;
@@ -399,19 +509,25 @@ export function SanitizedMetadata_GetDataLen(sanitized: Wrapper<SanitizedMetadat
 export function SanitizedMetadata_GetDataOffset(sanitized: Wrapper<SanitizedMetadata>): bigint;
 export function SanitizedMetadata_GetMetadata(sanitized: Wrapper<SanitizedMetadata>): Buffer;
 export function ScannableFingerprint_Compare(fprint1: Buffer, fprint2: Buffer): boolean;
-export function SealedSenderDecryptionResult_GetDeviceId(obj: Wrapper<SealedSenderDecryptionResult>): number;
+export function SealedSenderDecryptionResult_GetCurrentIdentityKey(obj: Wrapper<SealedSenderDecryptionResult>): PublicKey | null;
+export function SealedSenderDecryptionResult_GetDeviceId(obj: Wrapper<SealedSenderDecryptionResult>): DeviceId;
+export function SealedSenderDecryptionResult_GetPreviousIdentityKey(obj: Wrapper<SealedSenderDecryptionResult>): PublicKey | null;
 export function SealedSenderDecryptionResult_GetSenderE164(obj: Wrapper<SealedSenderDecryptionResult>): string | null;
 export function SealedSenderDecryptionResult_GetSenderUuid(obj: Wrapper<SealedSenderDecryptionResult>): string;
 export function SealedSenderDecryptionResult_Message(obj: Wrapper<SealedSenderDecryptionResult>): Buffer;
 export function SealedSenderMultiRecipientMessage_Parse(buffer: Buffer): SealedSenderMultiRecipientMessage;
-export function SealedSender_DecryptMessage(message: Buffer, trustRoot: Wrapper<PublicKey>, timestamp: Timestamp, localE164: string | null, localUuid: string, localDeviceId: number, sessionStore: SessionStore, identityStore: IdentityKeyStore, prekeyStore: PreKeyStore, signedPrekeyStore: SignedPreKeyStore, kyberPrekeyStore: KyberPreKeyStore): Promise<SealedSenderDecryptionResult>;
+export function SealedSender_DecryptMessage(message: Buffer, trustRoot: Wrapper<PublicKey>, timestamp: Timestamp, localE164: string | null, localUuid: string, localDeviceId: DeviceId, sessionStore: SessionStore, identityStore: IdentityKeyStore, prekeyStore: PreKeyStore, signedPrekeyStore: SignedPreKeyStore, kyberPrekeyStore: KyberPreKeyStore): Promise<SealedSenderDecryptionResult>;
+export function SealedSender_DecryptMessageCapturingIdentityChange(message: Buffer, trustRoot: Wrapper<PublicKey>, timestamp: Timestamp, localE164: string | null, localUuid: string, localDeviceId: DeviceId, sessionStore: SessionStore, identityStore: IdentityKeyStore, prekeyStore: PreKeyStore, signedPrekeyStore: SignedPreKeyStore, kyberPrekeyStore: KyberPreKeyStore): Promise<SealedSenderDecryptionResult>;
+export function SealedSender_DecryptMessageWithReplayCache(message: Buffer, trustRoot: Wrapper<PublicKey>, timestamp: Timestamp, localE164: string | null, localUuid: string, localDeviceId: DeviceId, sessionStore: SessionStore, identityStore: IdentityKeyStore, prekeyStore: PreKeyStore, signedPrekeyStore: SignedPreKeyStore, kyberPrekeyStore: KyberPreKeyStore, replayCache: ReplayCache): Promise<SealedSenderDecryptionResult>;
 export function SealedSender_DecryptToUsmc(ctext: Buffer, identityStore: IdentityKeyStore): Promise<UnidentifiedSenderMessageContent>;
 export function SealedSender_Encrypt(destination: Wrapper<ProtocolAddress>, content: Wrapper<UnidentifiedSenderMessageContent>, identityKeyStore: IdentityKeyStore): Promise<Buffer>;
 export function SealedSender_MultiRecipientEncrypt(recipients: Wrapper<ProtocolAddress>[], recipientSessions: Wrapper<SessionRecord>[], excludedRecipients: Buffer, content: Wrapper<UnidentifiedSenderMessageContent>, identityKeyStore: IdentityKeyStore): Promise<Buffer>;
 export function SealedSender_MultiRecipientMessageForSingleRecipient(encodedMultiRecipientMessage: Buffer): Buffer;
+export function SealedSender_MultiRecipientMessageLength(usmcLength: number, recipientCount: number, deviceCount: number, excludedRecipientCount: number): number;
+export function SealedSender_MultiRecipientReceivedMessageLength(usmcLength: number): number;
 export function SenderCertificate_Deserialize(data: Buffer): SenderCertificate;
 export function SenderCertificate_GetCertificate(obj: Wrapper<SenderCertificate>): Buffer;
-export function SenderCertificate_GetDeviceId(obj: Wrapper<SenderCertificate>): number;
+export function SenderCertificate_GetDeviceId(obj: Wrapper<SenderCertificate>): DeviceId;
 export function SenderCertificate_GetExpiration(obj: Wrapper<SenderCertificate>): Timestamp;
 export function SenderCertificate_GetKey(obj: Wrapper<SenderCertificate>): PublicKey;
 export function SenderCertificate_GetSenderE164(obj: Wrapper<SenderCertificate>): string | null;
@@ -419,7 +535,8 @@ export function SenderCertificate_GetSenderUuid(obj: Wrapper<SenderCertificate>)
 export function SenderCertificate_GetSerialized(obj: Wrapper<SenderCertificate>): Buffer;
 export function SenderCertificate_GetServerCertificate(cert: Wrapper<SenderCertificate>): ServerCertificate;
 export function SenderCertificate_GetSignature(obj: Wrapper<SenderCertificate>): Buffer;
-export function SenderCertificate_New(senderUuid: string, senderE164: string | null, senderDeviceId: number, senderKey: Wrapper<PublicKey>, expiration: Timestamp, signerCert: Wrapper<ServerCertificate>, signerKey: Wrapper<PrivateKey>): SenderCertificate;
+export function SenderCertificate_NeedsRefresh(cert: Wrapper<SenderCertificate>, time: Timestamp, refreshMarginSeconds: number): boolean;
+export function SenderCertificate_New(senderUuid: string, senderE164: string | null, senderDeviceId: DeviceId, senderKey: Wrapper<PublicKey>, expiration: Timestamp, signerCert: Wrapper<ServerCertificate>, signerKey: Wrapper<PrivateKey>): SenderCertificate;
 export function SenderCertificate_Validate(cert: Wrapper<SenderCertificate>, key: Wrapper<PublicKey>, time: Timestamp): boolean;
 export function SenderKeyDistributionMessage_Create(sender: Wrapper<ProtocolAddress>, distributionId: Uuid, store: SenderKeyStore): Promise<SenderKeyDistributionMessage>;
 export function SenderKeyDistributionMessage_Deserialize(data: Buffer): SenderKeyDistributionMessage;
@@ -430,6 +547,8 @@ export function SenderKeyDistributionMessage_GetIteration(obj: Wrapper<SenderKey
 export function SenderKeyDistributionMessage_New(messageVersion: number, distributionId: Uuid, chainId: number, iteration: number, chainkey: Buffer, pk: Wrapper<PublicKey>): SenderKeyDistributionMessage;
 export function SenderKeyDistributionMessage_Process(sender: Wrapper<ProtocolAddress>, senderKeyDistributionMessage: Wrapper<SenderKeyDistributionMessage>, store: SenderKeyStore): Promise<void>;
 export function SenderKeyDistributionMessage_Serialize(obj: Wrapper<SenderKeyDistributionMessage>): Buffer;
+export function SenderKeyDistributionRecord_Deserialize(data: Buffer): SenderKeyDistributionRecord;
+export function SenderKeyDistributionRecord_Serialize(obj: Wrapper<SenderKeyDistributionRecord>): Buffer;
 export function SenderKeyMessage_Deserialize(data: Buffer): SenderKeyMessage;
 export function SenderKeyMessage_GetChainId(obj: Wrapper<SenderKeyMessage>): number;
 export function SenderKeyMessage_GetCipherText(obj: Wrapper<SenderKeyMessage>): Buffer;
@@ -439,7 +558,11 @@ export function SenderKeyMessage_New(messageVersion: number, distributionId: Uui
 export function SenderKeyMessage_Serialize(obj: Wrapper<SenderKeyMessage>): Buffer;
 export function SenderKeyMessage_VerifySignature(skm: Wrapper<SenderKeyMessage>, pubkey: Wrapper<PublicKey>): boolean;
 export function SenderKeyRecord_Deserialize(data: Buffer): SenderKeyRecord;
+export function SenderKeyRecord_MigrateToCurrent(data: Buffer): Buffer;
 export function SenderKeyRecord_Serialize(obj: Wrapper<SenderKeyRecord>): Buffer;
+export function SenderKey_InvalidateDistribution(sender: Wrapper<ProtocolAddress>, distributionId: Uuid, recipients: Wrapper<ProtocolAddress>[], store: SenderKeyStore): Promise<void>;
+export function SenderKey_MarkDistributed(sender: Wrapper<ProtocolAddress>, distributionId: Uuid, recipients: Wrapper<ProtocolAddress>[], store: SenderKeyStore): Promise<void>;
+export function SenderKey_RecipientsNeedingDistribution(sender: Wrapper<ProtocolAddress>, distributionId: Uuid, recipients: Wrapper<ProtocolAddress>[], store: SenderKeyStore): Promise<Buffer>;
 export function ServerCertificate_Deserialize(data: Buffer): ServerCertificate;
 export function ServerCertificate_GetCertificate(obj: Wrapper<ServerCertificate>): Buffer;
 export function ServerCertificate_GetKey(obj: Wrapper<ServerCertificate>): PublicKey;
@@ -471,31 +594,43 @@ export function ServerSecretParams_SignDeterministic(params: Wrapper<ServerSecre
 export function ServerSecretParams_VerifyAuthCredentialPresentation(serverSecretParams: Wrapper<ServerSecretParams>, groupPublicParams: Serialized<GroupPublicParams>, presentationBytes: Buffer, currentTimeInSeconds: Timestamp): void;
 export function ServerSecretParams_VerifyProfileKeyCredentialPresentation(serverSecretParams: Wrapper<ServerSecretParams>, groupPublicParams: Serialized<GroupPublicParams>, presentationBytes: Buffer, currentTimeInSeconds: Timestamp): void;
 export function ServerSecretParams_VerifyReceiptCredentialPresentation(serverSecretParams: Wrapper<ServerSecretParams>, presentation: Serialized<ReceiptCredentialPresentation>): void;
+export function ServiceId_ParseFromServiceIdBinaries(input: Buffer[]): Buffer;
 export function ServiceId_ParseFromServiceIdBinary(input: Buffer): Buffer;
 export function ServiceId_ParseFromServiceIdString(input: string): Buffer;
+export function ServiceId_ServiceIdBinaries(concatenated: Buffer): Buffer[];
 export function ServiceId_ServiceIdBinary(value: Buffer): Buffer;
 export function ServiceId_ServiceIdLog(value: Buffer): string;
 export function ServiceId_ServiceIdString(value: Buffer): string;
+export function ServiceId_ServiceIdStrings(concatenated: Buffer): string[];
 export function SessionBuilder_ProcessPreKeyBundle(bundle: Wrapper<PreKeyBundle>, protocolAddress: Wrapper<ProtocolAddress>, sessionStore: SessionStore, identityKeyStore: IdentityKeyStore, now: Timestamp): Promise<void>;
 export function SessionCipher_DecryptPreKeySignalMessage(message: Wrapper<PreKeySignalMessage>, protocolAddress: Wrapper<ProtocolAddress>, sessionStore: SessionStore, identityKeyStore: IdentityKeyStore, prekeyStore: PreKeyStore, signedPrekeyStore: SignedPreKeyStore, kyberPrekeyStore: KyberPreKeyStore): Promise<Buffer>;
+export function SessionCipher_DecryptPreKeySignalMessageWithReplayCache(message: Wrapper<PreKeySignalMessage>, protocolAddress: Wrapper<ProtocolAddress>, sessionStore: SessionStore, identityKeyStore: IdentityKeyStore, prekeyStore: PreKeyStore, signedPrekeyStore: SignedPreKeyStore, kyberPrekeyStore: KyberPreKeyStore, replayCache: ReplayCache): Promise<Buffer>;
 export function SessionCipher_DecryptSignalMessage(message: Wrapper<SignalMessage>, protocolAddress: Wrapper<ProtocolAddress>, sessionStore: SessionStore, identityKeyStore: IdentityKeyStore): Promise<Buffer>;
+export function SessionCipher_DecryptSignalMessageWithReplayCache(message: Wrapper<SignalMessage>, protocolAddress: Wrapper<ProtocolAddress>, sessionStore: SessionStore, identityKeyStore: IdentityKeyStore, replayCache: ReplayCache): Promise<Buffer>;
 export function SessionCipher_EncryptMessage(ptext: Buffer, protocolAddress: Wrapper<ProtocolAddress>, sessionStore: SessionStore, identityKeyStore: IdentityKeyStore, now: Timestamp): Promise<CiphertextMessage>;
 export function SessionRecord_ArchiveCurrentState(sessionRecord: Wrapper<SessionRecord>): void;
 export function SessionRecord_CurrentRatchetKeyMatches(s: Wrapper<SessionRecord>, key: Wrapper<PublicKey>): boolean;
 export function SessionRecord_Deserialize(data: Buffer): SessionRecord;
-export function SessionRecord_GetLocalRegistrationId(obj: Wrapper<SessionRecord>): number;
-export function SessionRecord_GetRemoteRegistrationId(obj: Wrapper<SessionRecord>): number;
+export function SessionRecord_GetLastRatchetStepTime(s: Wrapper<SessionRecord>): Timestamp;
+export function SessionRecord_GetLocalRegistrationId(obj: Wrapper<SessionRecord>): RegistrationId;
+export function SessionRecord_GetMessagesDecrypted(s: Wrapper<SessionRecord>): number;
+export function SessionRecord_GetMessagesEncrypted(s: Wrapper<SessionRecord>): number;
+export function SessionRecord_GetRemoteRegistrationId(obj: Wrapper<SessionRecord>): RegistrationId;
 export function SessionRecord_HasUsableSenderChain(s: Wrapper<SessionRecord>, now: Timestamp): boolean;
+export function SessionRecord_IsPostQuantum(s: Wrapper<SessionRecord>): boolean;
+export function SessionRecord_MigrateToCurrent(data: Buffer): Buffer;
 export function SessionRecord_Serialize(obj: Wrapper<SessionRecord>): Buffer;
 export function SgxClientState_CompleteHandshake(cli: Wrapper<SgxClientState>, handshakeReceived: Buffer): void;
 export function SgxClientState_EstablishedRecv(cli: Wrapper<SgxClientState>, receivedCiphertext: Buffer): Buffer;
 export function SgxClientState_EstablishedSend(cli: Wrapper<SgxClientState>, plaintextToSend: Buffer): Buffer;
 export function SgxClientState_InitialRequest(obj: Wrapper<SgxClientState>): Buffer;
 export function SignalMedia_CheckAvailable(): void;
+export function SignalMessage_CheckRatchetContinuity(later: Wrapper<SignalMessage>, earlier: Wrapper<SignalMessage>): number;
 export function SignalMessage_Deserialize(data: Buffer): SignalMessage;
 export function SignalMessage_GetBody(obj: Wrapper<SignalMessage>): Buffer;
 export function SignalMessage_GetCounter(obj: Wrapper<SignalMessage>): number;
 export function SignalMessage_GetMessageVersion(obj: Wrapper<SignalMessage>): number;
+export function SignalMessage_GetPreviousCounter(obj: Wrapper<SignalMessage>): number;
 export function SignalMessage_GetSerialized(obj: Wrapper<SignalMessage>): Buffer;
 export function SignalMessage_New(messageVersion: number, macKey: Buffer, senderRatchetKey: Wrapper<PublicKey>, counter: number, previousCounter: number, ciphertext: Buffer, senderIdentityKey: Wrapper<PublicKey>, receiverIdentityKey: Wrapper<PublicKey>): SignalMessage;
 export function SignalMessage_VerifyMac(msg: Wrapper<SignalMessage>, senderIdentityKey: Wrapper<PublicKey>, receiverIdentityKey: Wrapper<PublicKey>, macKey: Buffer): boolean;
@@ -505,14 +640,38 @@ export function SignedPreKeyRecord_GetPrivateKey(obj: Wrapper<SignedPreKeyRecord
 export function SignedPreKeyRecord_GetPublicKey(obj: Wrapper<SignedPreKeyRecord>): PublicKey;
 export function SignedPreKeyRecord_GetSignature(obj: Wrapper<SignedPreKeyRecord>): Buffer;
 export function SignedPreKeyRecord_GetTimestamp(obj: Wrapper<SignedPreKeyRecord>): Timestamp;
+export function SignedPreKeyRecord_MigrateToCurrent(data: Buffer): Buffer;
 export function SignedPreKeyRecord_New(id: number, timestamp: Timestamp, pubKey: Wrapper<PublicKey>, privKey: Wrapper<PrivateKey>, signature: Buffer): SignedPreKeyRecord;
 export function SignedPreKeyRecord_Serialize(obj: Wrapper<SignedPreKeyRecord>): Buffer;
+export function StorageSyncPlan_hasRemoteChanges(plan: Wrapper<StorageSyncPlan>): boolean;
+export function StorageSyncPlan_localDeletes(plan: Wrapper<StorageSyncPlan>): Buffer[];
+export function StorageSyncPlan_localUpsertData(plan: Wrapper<StorageSyncPlan>): Buffer[];
+export function StorageSyncPlan_localUpsertIds(plan: Wrapper<StorageSyncPlan>): Buffer[];
+export function StorageSyncPlan_remoteDeletes(plan: Wrapper<StorageSyncPlan>): Buffer[];
+export function StorageSyncPlan_remoteInsertData(plan: Wrapper<StorageSyncPlan>): Buffer[];
+export function StorageSyncPlan_remoteInsertIds(plan: Wrapper<StorageSyncPlan>): Buffer[];
+export function StorageSyncPlan_remoteManifestIds(plan: Wrapper<StorageSyncPlan>): Buffer[];
+export function StorageSyncPlan_unknown(plan: Wrapper<StorageSyncPlan>): Buffer[];
+export function StorageSync_addFetchedRecord(sync: Wrapper<StorageSync>, id: Buffer, key: Buffer, data: Buffer): void;
+export function StorageSync_addFetchedUnknown(sync: Wrapper<StorageSync>, id: Buffer): void;
+export function StorageSync_addKnownUnknown(sync: Wrapper<StorageSync>, id: Buffer): void;
+export function StorageSync_addLocalDeletion(sync: Wrapper<StorageSync>, id: Buffer): void;
+export function StorageSync_addLocalRecord(sync: Wrapper<StorageSync>, id: Buffer, key: Buffer, data: Buffer): void;
+export function StorageSync_idsToFetch(sync: Wrapper<StorageSync>): Buffer[];
+export function StorageSync_new(previousManifest: Buffer, remoteManifest: Buffer): StorageSync;
+export function StorageSync_plan(sync: Wrapper<StorageSync>): StorageSyncPlan;
+export function StoreConformance_Check(check: number, identityKey: Wrapper<PublicKey>, privateKey: Wrapper<PrivateKey>, registrationId: number, identityStore: IdentityKeyStore, preKeyStore: PreKeyStore, signedPreKeyStore: SignedPreKeyStore, kyberPreKeyStore: KyberPreKeyStore, sessionStore: SessionStore, senderKeyStore: SenderKeyStore): Promise<void>;
+export function StoreConformance_CheckCount(): number;
 export function Svr3Backup(asyncRuntime: Wrapper<TokioAsyncContext>, connectionManager: Wrapper<ConnectionManager>, secret: Buffer, password: string, maxTries: number, username: string, enclavePassword: string): Promise<Buffer>;
+export function Svr3BackupWithDistribution(asyncRuntime: Wrapper<TokioAsyncContext>, connectionManager: Wrapper<ConnectionManager>, secret: Buffer, password: string, maxTries: number, serverIds: Buffer, threshold: number, username: string, enclavePassword: string): Promise<Buffer>;
+export function Svr3GetShareSetServerIds(shareSet: Buffer): Buffer;
+export function Svr3GetShareSetThreshold(shareSet: Buffer): number;
 export function Svr3Remove(asyncRuntime: Wrapper<TokioAsyncContext>, connectionManager: Wrapper<ConnectionManager>, username: string, enclavePassword: string): Promise<void>;
 export function Svr3Restore(asyncRuntime: Wrapper<TokioAsyncContext>, connectionManager: Wrapper<ConnectionManager>, password: string, shareSet: Buffer, username: string, enclavePassword: string): Promise<Buffer>;
 export function TESTING_CdsiLookupErrorConvert(errorDescription: string): void;
 export function TESTING_CdsiLookupResponseConvert(asyncRuntime: Wrapper<TokioAsyncContext>): Promise<LookupResponse>;
 export function TESTING_ChatRequestGetBody(request: Wrapper<HttpRequest>): Buffer;
+export function TESTING_ChatRequestGetHeaderNames(request: Wrapper<HttpRequest>): string[];
 export function TESTING_ChatRequestGetHeaderValue(request: Wrapper<HttpRequest>, headerName: string): string;
 export function TESTING_ChatRequestGetMethod(request: Wrapper<HttpRequest>): string;
 export function TESTING_ChatRequestGetPath(request: Wrapper<HttpRequest>): string;
@@ -535,6 +694,13 @@ export function TESTING_FutureProducesOtherPointerType(asyncRuntime: Wrapper<Non
 export function TESTING_FutureProducesPointerType(asyncRuntime: Wrapper<NonSuspendingBackgroundThreadRuntime>, input: number): Promise<TestingHandleType>;
 export function TESTING_FutureSuccess(asyncRuntime: Wrapper<NonSuspendingBackgroundThreadRuntime>, input: number): Promise<number>;
 export function TESTING_InputStreamReadIntoZeroLengthSlice(capsAlphabetInput: InputStream): Promise<Buffer>;
+export function TESTING_MockChatResponse_AddHeader(response: Wrapper<MockChatResponse>, name: string, value: string): void;
+export function TESTING_MockChatResponse_New(status: number, message: string | null, body: Buffer | null): MockChatResponse;
+export function TESTING_MockChatServer_AddResponse(server: Wrapper<MockChatServer>, method: string, path: string, response: Wrapper<MockChatResponse>): void;
+export function TESTING_MockChatServer_New(): MockChatServer;
+export function TESTING_MockChatServer_NewAuthChat(server: Wrapper<MockChatServer>): AuthChat;
+export function TESTING_MockChatServer_NewUnauthChat(server: Wrapper<MockChatServer>): UnauthChat;
+export function TESTING_MockChatServer_TakeSentRequest(server: Wrapper<MockChatServer>): HttpRequest | null;
 export function TESTING_NonSuspendingBackgroundThreadRuntime_New(): NonSuspendingBackgroundThreadRuntime;
 export function TESTING_OnlyCompletesByCancellation(asyncRuntime: Wrapper<TokioAsyncContext>): Promise<void>;
 export function TESTING_OtherTestingHandleType_getValue(handle: Wrapper<OtherTestingHandleType>): string;
@@ -564,8 +730,10 @@ export function UnidentifiedSenderMessageContent_GetMsgType(m: Wrapper<Unidentif
 export function UnidentifiedSenderMessageContent_GetSenderCert(m: Wrapper<UnidentifiedSenderMessageContent>): SenderCertificate;
 export function UnidentifiedSenderMessageContent_New(message: Wrapper<CiphertextMessage>, sender: Wrapper<SenderCertificate>, contentHint: number, groupId: Buffer | null): UnidentifiedSenderMessageContent;
 export function UnidentifiedSenderMessageContent_Serialize(obj: Wrapper<UnidentifiedSenderMessageContent>): Buffer;
+export function UnidentifiedSenderMessageContent_WithContentHintAndGroupId(m: Wrapper<UnidentifiedSenderMessageContent>, contentHint: number, groupId: Buffer | null): UnidentifiedSenderMessageContent;
 export function UsernameLink_Create(username: string, entropy: Buffer | null): Buffer;
 export function UsernameLink_DecryptUsername(entropy: Buffer, encryptedUsername: Buffer): string;
+export function UsernameLink_ParseUrl(url: string): Buffer;
 export function Username_CandidatesFrom(nickname: string, minLen: number, maxLen: number): string[];
 export function Username_Hash(username: string): Buffer;
 export function Username_HashFromParts(nickname: string, discriminator: string, minLen: number, maxLen: number): Buffer;
@@ -580,6 +748,9 @@ export function initLogger(maxLevel: LogLevel, callback: (level: LogLevel, targe
 export function test_only_fn_returns_123(): number;
 interface Aes256GcmSiv { readonly __type: unique symbol; }
 interface AuthChat { readonly __type: unique symbol; }
+interface BackupPaddingPolicy { readonly __type: unique symbol; }
+interface CdsiConnectionPool { readonly __type: unique symbol; }
+interface CdsiContactChanges { readonly __type: unique symbol; }
 interface CdsiLookup { readonly __type: unique symbol; }
 interface CiphertextMessage { readonly __type: unique symbol; }
 interface ComparableBackup { readonly __type: unique symbol; }
@@ -595,12 +766,16 @@ interface GroupSecretParams { readonly __type: unique symbol; }
 interface HsmEnclaveClient { readonly __type: unique symbol; }
 interface HttpRequest { readonly __type: unique symbol; }
 interface IncrementalMac { readonly __type: unique symbol; }
+interface KeyTransparencySearchCache { readonly __type: unique symbol; }
 interface KyberKeyPair { readonly __type: unique symbol; }
 interface KyberPreKeyRecord { readonly __type: unique symbol; }
 interface KyberPublicKey { readonly __type: unique symbol; }
 interface KyberSecretKey { readonly __type: unique symbol; }
 interface LookupRequest { readonly __type: unique symbol; }
 interface MessageBackupKey { readonly __type: unique symbol; }
+interface MockChatResponse { readonly __type: unique symbol; }
+interface MockChatServer { readonly __type: unique symbol; }
+interface Mp4SanitizerOptions { readonly __type: unique symbol; }
 interface NonSuspendingBackgroundThreadRuntime { readonly __type: unique symbol; }
 interface OtherTestingHandleType { readonly __type: unique symbol; }
 interface PlaintextContent { readonly __type: unique symbol; }
@@ -615,6 +790,7 @@ interface ProfileKeyCredentialRequest { readonly __type: unique symbol; }
 interface ProfileKeyCredentialRequestContext { readonly __type: unique symbol; }
 interface ProtocolAddress { readonly __type: unique symbol; }
 interface PublicKey { readonly __type: unique symbol; }
+interface ReceiptBatch { readonly __type: unique symbol; }
 interface ReceiptCredential { readonly __type: unique symbol; }
 interface ReceiptCredentialPresentation { readonly __type: unique symbol; }
 interface ReceiptCredentialRequest { readonly __type: unique symbol; }
@@ -624,6 +800,7 @@ interface SanitizedMetadata { readonly __type: unique symbol; }
 interface SealedSenderDecryptionResult { readonly __type: unique symbol; }
 interface SenderCertificate { readonly __type: unique symbol; }
 interface SenderKeyDistributionMessage { readonly __type: unique symbol; }
+interface SenderKeyDistributionRecord { readonly __type: unique symbol; }
 interface SenderKeyMessage { readonly __type: unique symbol; }
 interface SenderKeyRecord { readonly __type: unique symbol; }
 interface ServerCertificate { readonly __type: unique symbol; }
@@ -634,6 +811,8 @@ interface SessionRecord { readonly __type: unique symbol; }
 interface SgxClientState { readonly __type: unique symbol; }
 interface SignalMessage { readonly __type: unique symbol; }
 interface SignedPreKeyRecord { readonly __type: unique symbol; }
+interface StorageSync { readonly __type: unique symbol; }
+interface StorageSyncPlan { readonly __type: unique symbol; }
 interface TestingHandleType { readonly __type: unique symbol; }
 interface TokioAsyncContext { readonly __type: unique symbol; }
 interface UnauthChat { readonly __type: unique symbol; }
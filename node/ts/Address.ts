@@ -145,6 +145,73 @@ export abstract class ServiceId extends Object {
     }
     return result;
   }
+
+  /**
+   * Parses the inverse of {@link ServiceId.toConcatenatedFixedWidthBinary}.
+   */
+  static parseFromConcatenatedFixedWidthBinary(
+    concatenated: Buffer
+  ): ServiceId[] {
+    if (concatenated.length % SERVICE_ID_FIXED_WIDTH_BINARY_LEN != 0) {
+      throw new TypeError(
+        'not a whole number of Service-Id-FixedWidthBinary values'
+      );
+    }
+    const result = [];
+    for (
+      let offset = 0;
+      offset < concatenated.length;
+      offset += SERVICE_ID_FIXED_WIDTH_BINARY_LEN
+    ) {
+      result.push(
+        ServiceId.parseFromServiceIdFixedWidthBinary(
+          Buffer.from(
+            concatenated.subarray(
+              offset,
+              offset + SERVICE_ID_FIXED_WIDTH_BINARY_LEN
+            )
+          )
+        )
+      );
+    }
+    return result;
+  }
+
+  /**
+   * Parses many Service-Id-Binary values at once.
+   *
+   * Equivalent to calling {@link ServiceId.parseFromServiceIdBinary} on each element, but crosses
+   * into native code only once.
+   */
+  static parseFromServiceIdBinaries(serviceIdBinaries: Buffer[]): ServiceId[] {
+    return ServiceId.parseFromConcatenatedFixedWidthBinary(
+      Native.ServiceId_ParseFromServiceIdBinaries(serviceIdBinaries)
+    );
+  }
+
+  /**
+   * Converts many service IDs to Service-Id-Binary at once.
+   *
+   * Equivalent to calling {@link ServiceId.getServiceIdBinary} on each element, but crosses into
+   * native code only once.
+   */
+  static toServiceIdBinaries(serviceIds: ServiceId[]): Buffer[] {
+    return Native.ServiceId_ServiceIdBinaries(
+      ServiceId.toConcatenatedFixedWidthBinary(serviceIds)
+    );
+  }
+
+  /**
+   * Converts many service IDs to Service-Id-String at once.
+   *
+   * Equivalent to calling {@link ServiceId.getServiceIdString} on each element, but crosses into
+   * native code only once.
+   */
+  static toServiceIdStrings(serviceIds: ServiceId[]): string[] {
+    return Native.ServiceId_ServiceIdStrings(
+      ServiceId.toConcatenatedFixedWidthBinary(serviceIds)
+    );
+  }
 }
 
 export class Aci extends ServiceId {
@@ -186,7 +253,9 @@ export class ProtocolAddress {
     if (typeof name !== 'string') {
       name = name.getServiceIdString();
     }
-    return new ProtocolAddress(Native.ProtocolAddress_New(name, deviceId));
+    return new ProtocolAddress(
+      Native.ProtocolAddress_New(name, deviceId as Native.DeviceId)
+    );
   }
 
   name(): string {
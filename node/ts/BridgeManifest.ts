@@ -0,0 +1,13 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+// WARNING: this file was automatically generated
+
+/**
+ * The hash of the bridge manifest (rust/bridge/shared/bridge_manifest.json) that Native.d.ts was
+ * generated from.
+ */
+export const BRIDGE_MANIFEST_SHA256 =
+  'e7779331df4cd98645e8751c50d258f9017c203201d21b5fad3a8432a4db3e6a';
@@ -0,0 +1,41 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import * as Native from '../Native';
+
+/**
+ * Call statistics for a single native function, as recorded by {@link getBridgeMetrics}.
+ *
+ * Durations cover the synchronous part of each call, including argument conversion. For
+ * functions that return a Promise, that's only the time taken to start the operation.
+ */
+export type BridgeCallStats = {
+  /** The native function's name, as it appears in Native.d.ts. */
+  name: string;
+  calls: number;
+  /** Calls that threw. */
+  errors: number;
+  totalMicros: number;
+  maxMicros: number;
+};
+
+/**
+ * Turns recording of native call statistics on or off for the whole process.
+ *
+ * Recording is off by default. Turning it off keeps the statistics gathered so far.
+ */
+export function setBridgeMetricsEnabled(enabled: boolean): void {
+  Native.BridgeMetrics_SetEnabled(enabled);
+}
+
+/** Returns the statistics recorded so far, sorted by function name. */
+export function getBridgeMetrics(): BridgeCallStats[] {
+  return JSON.parse(Native.BridgeMetrics_Snapshot()) as BridgeCallStats[];
+}
+
+/** Discards the statistics recorded so far. */
+export function resetBridgeMetrics(): void {
+  Native.BridgeMetrics_Reset();
+}
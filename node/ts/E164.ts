@@ -0,0 +1,30 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import * as Native from '../Native';
+
+/**
+ * Interprets a phone number as entered by a user or found in a contact list.
+ *
+ * Numbers in international format (starting with `+` or `00`) are used as is. Anything else is
+ * treated as a national number in the country with calling code `regionCallingCode` (usually the
+ * local user's own). Spaces and common punctuation are ignored.
+ *
+ * Returns the number in E.164 form (`+` followed by digits), or `null` if it isn't a valid phone
+ * number. Throws if `regionCallingCode` isn't a plausible calling code.
+ */
+export function normalize(
+  raw: string,
+  regionCallingCode: number
+): string | null {
+  return Native.E164_Normalize(raw, regionCallingCode);
+}
+
+/**
+ * Checks whether `e164` is already a valid number in E.164 form, with no punctuation.
+ */
+export function isValid(e164: string): boolean {
+  return Native.E164_IsValid(e164);
+}
@@ -50,10 +50,16 @@ export enum ErrorCode {
   ChatServiceInactive,
   AppExpired,
   DeviceDelinked,
+  RequestUnauthorized,
+  NotFound,
 
   BackupValidation,
 
   Cancelled,
+
+  DeadlineExceeded,
+
+  BridgeManifestMismatch,
 }
 
 export class LibSignalErrorBase extends Error {
@@ -215,8 +221,20 @@ export type InvalidUsernameLinkEncryptedData = LibSignalErrorCommon & {
   code: ErrorCode.InvalidUsernameLinkEncryptedData;
 };
 
+/** What the server said when it closed (or refused) a chat connection. */
+export type DisconnectInfo = {
+  /** The websocket close code, if the server sent a close frame. */
+  readonly closeCode?: number;
+  /** The reason given in the close frame, suitable for logs but not for users. */
+  readonly reason?: string;
+  /** The values of any `X-Signal-Alert` headers on the server's response. */
+  readonly alerts: ReadonlyArray<string>;
+};
+
 export type IoError = LibSignalErrorCommon & {
   code: ErrorCode.IoError;
+  /** Present when the server ended or refused the connection and said why. */
+  readonly disconnectInfo?: DisconnectInfo;
 };
 
 export type CdsiInvalidTokenError = LibSignalErrorCommon & {
@@ -238,10 +256,16 @@ export type UnsupportedMediaInputError = LibSignalErrorCommon & {
 export type RateLimitedError = LibSignalErrorBase & {
   code: ErrorCode.RateLimitedError;
   readonly retryAfterSecs: number;
+  /** What the limit is counted against, if the server said. */
+  readonly scope?: 'per-number' | 'per-account';
+  /** The server's explanation for the limit, suitable for logs but not for users. */
+  readonly reason?: string;
 };
 
 export type ChatServiceInactive = LibSignalErrorBase & {
   code: ErrorCode.ChatServiceInactive;
+  /** Present when the server ended the connection and said why. */
+  readonly disconnectInfo?: DisconnectInfo;
 };
 
 export type AppExpiredError = LibSignalErrorBase & {
@@ -252,6 +276,14 @@ export type DeviceDelinkedError = LibSignalErrorBase & {
   code: ErrorCode.DeviceDelinked;
 };
 
+export type RequestUnauthorizedError = LibSignalErrorBase & {
+  code: ErrorCode.RequestUnauthorized;
+};
+
+export type NotFoundError = LibSignalErrorBase & {
+  code: ErrorCode.NotFound;
+};
+
 export type SvrDataMissingError = LibSignalErrorBase & {
   code: ErrorCode.SvrDataMissing;
 };
@@ -274,6 +306,16 @@ export type CancellationError = LibSignalErrorCommon & {
   code: ErrorCode.Cancelled;
 };
 
+export type DeadlineExceededError = LibSignalErrorCommon & {
+  code: ErrorCode.DeadlineExceeded;
+};
+
+export type BridgeManifestMismatchError = LibSignalErrorCommon & {
+  code: ErrorCode.BridgeManifestMismatch;
+  expectedSha256: string;
+  loadedSha256: string | undefined;
+};
+
 export type LibSignalError =
   | GenericError
   | DuplicatedMessageError
@@ -309,6 +351,10 @@ export type LibSignalError =
   | ChatServiceInactive
   | AppExpiredError
   | DeviceDelinkedError
+  | RequestUnauthorizedError
+  | NotFoundError
   | RateLimitedError
   | BackupValidationError
-  | CancellationError;
+  | CancellationError
+  | DeadlineExceededError
+  | BridgeManifestMismatchError;
@@ -16,6 +16,40 @@ import { InputStream } from './io';
 
 export type InputStreamFactory = () => InputStream;
 
+// This must match the Rust version of the enum.
+export enum ValidationFindingKind {
+  UnknownField = 0,
+  InvalidFrame = 1,
+  IncompleteBackup = 2,
+  MalformedFrame = 3,
+  NoFrames = 4,
+  HmacMismatch = 5,
+  Io = 6,
+  LimitExceeded = 7,
+}
+
+// This must match the Rust version of the enum.
+export enum ValidationFindingSeverity {
+  /** The backup can still be imported. */
+  Ignorable = 0,
+  /** The backup cannot be imported. */
+  Fatal = 1,
+}
+
+/**
+ * A single machine-readable problem found during validation.
+ */
+export type ValidationFinding = Readonly<{
+  /** The frame the finding applies to, where frame 0 is the backup header. */
+  frameIndex: number | null;
+  /** Dotted path to the offending field, or the empty string if unknown. */
+  fieldPath: string;
+  kind: ValidationFindingKind;
+  severity: ValidationFindingSeverity;
+  /** A developer-facing description of the finding. */
+  message: string;
+}>;
+
 /**
  * Result of validating a message backup bundle.
  */
@@ -31,6 +65,11 @@ export class ValidationOutcome {
    */
   public unknownFieldMessages: string[];
 
+  /**
+   * Structured versions of the error and unknown fields, in that order.
+   */
+  public findings: ValidationFinding[];
+
   /**
    * `true` if the backup is valid, `false` otherwise.
    *
@@ -41,9 +80,10 @@ export class ValidationOutcome {
   }
 
   constructor(outcome: Native.MessageBackupValidationOutcome) {
-    const { errorMessage, unknownFieldMessages } = outcome;
+    const { errorMessage, unknownFieldMessages, findings } = outcome;
     this.errorMessage = errorMessage;
     this.unknownFieldMessages = unknownFieldMessages;
+    this.findings = findings;
   }
 }
 
@@ -139,14 +179,18 @@ export enum Purpose {
  * @param purpose Whether the backup is intended for device-to-device transfer or remote storage.
  * @param inputFactory A function that returns new input streams that read the backup contents.
  * @param length The exact length of the input stream.
+ * @param options.timeoutMillis If provided, validation is abandoned if it is still running after
+ *   this many milliseconds.
  * @returns The outcome of validation, including any errors and warnings.
  * @throws IoError If an IO error on the input occurs.
+ * @throws DeadlineExceededError If the timeout passes before validation completes.
  */
 export async function validate(
   backupKey: MessageBackupKey,
   purpose: Purpose,
   inputFactory: InputStreamFactory,
-  length: bigint
+  length: bigint,
+  options?: { timeoutMillis?: number }
 ): Promise<ValidationOutcome> {
   const firstStream = inputFactory();
   const secondStream = inputFactory();
@@ -156,11 +200,84 @@ export async function validate(
       firstStream,
       secondStream,
       length,
-      purpose
+      purpose,
+      Math.ceil(options?.timeoutMillis ?? 0)
     )
   );
 }
 
+/**
+ * Computes the size compressed backup contents will be padded to before encryption.
+ *
+ * @param compressedLength The length of the gzipped backup contents.
+ * @returns The length after padding with the standard bucketing policy.
+ */
+export function getPaddedSize(compressedLength: bigint): bigint {
+  return Native.MessageBackup_GetPaddedSize(compressedLength);
+}
+
+/**
+ * Computes the size of the encrypted backup file that will be uploaded.
+ *
+ * This accounts for padding, encryption, and the trailing HMAC, and so can be
+ * used to predict storage quota usage before producing the backup.
+ *
+ * @param compressedLength The length of the gzipped backup contents.
+ * @returns The length of the final encrypted backup file.
+ */
+export function getEncryptedSize(compressedLength: bigint): bigint {
+  return Native.MessageBackup_GetEncryptedSize(compressedLength);
+}
+
+/**
+ * How compressed backup contents are padded before encryption, to hide the
+ * exact size of a backup.
+ */
+export class BackupPaddingPolicy {
+  readonly _nativeHandle: Native.BackupPaddingPolicy;
+
+  /**
+   * Pads contents up to the smallest bucket that fits them.
+   *
+   * Bucket sizes are integer powers of `growthFactor` (rounded to the nearest
+   * hundredth), but never less than `minSize` bytes. A `growthFactor` of 1 or
+   * less disables bucketing, leaving only the minimum size.
+   *
+   * If no arguments are given, uses the policy for backups produced by Signal
+   * clients.
+   */
+  constructor(options?: { growthFactor: number; minSize: bigint }) {
+    this._nativeHandle = options
+      ? Native.BackupPaddingPolicy_New(
+          Math.round(options.growthFactor * 100),
+          options.minSize
+        )
+      : Native.BackupPaddingPolicy_Default();
+  }
+
+  /**
+   * Computes the size compressed backup contents will be padded to before
+   * encryption.
+   *
+   * @param compressedLength The length of the gzipped backup contents.
+   * @returns The length after padding with this policy.
+   */
+  getPaddedSize(compressedLength: bigint): bigint {
+    return Native.BackupPaddingPolicy_GetPaddedSize(this, compressedLength);
+  }
+
+  /**
+   * Computes the size of the encrypted backup file that will be uploaded.
+   *
+   * @param compressedLength The length of the gzipped backup contents.
+   * @returns The length of the final encrypted backup file, after padding with
+   * this policy.
+   */
+  getEncryptedSize(compressedLength: bigint): bigint {
+    return Native.BackupPaddingPolicy_GetEncryptedSize(this, compressedLength);
+  }
+}
+
 /**
  * An in-memory representation of a backup file used to compare contents.
  *
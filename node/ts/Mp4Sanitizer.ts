@@ -81,11 +81,92 @@ export class SanitizedMetadata {
   }
 }
 
+/**
+ * The codecs a client is able to play back, beyond H.264 video and AAC audio.
+ *
+ * Tracks of kinds other than audio and video, such as timed metadata, are not checked.
+ */
+export type Mp4CodecPolicy = {
+  /** Allow HEVC (H.265) video. */
+  allowHevc?: boolean;
+  /** Allow AV1 video. */
+  allowAv1?: boolean;
+  /** Allow Opus audio. */
+  allowOpus?: boolean;
+};
+
+/**
+ * Options for {@link sanitize}.
+ *
+ * By default, metadata boxes are left as they are without options. Once stripping is turned on with
+ * {@link Mp4SanitizerOptions#setMetadataStripped} or {@link Mp4SanitizerOptions#setMetadataBoxPreserved}, boxes needed
+ * for playback are still kept, but other metadata boxes are replaced with padding of the same size unless they are
+ * listed as preserved. Color information, pixel aspect ratio, HDR, and spherical video metadata start out preserved.
+ *
+ * By default, codecs are not checked; see {@link Mp4SanitizerOptions#setCodecPolicy}.
+ */
+export class Mp4SanitizerOptions {
+  readonly _nativeHandle: Native.Mp4SanitizerOptions;
+
+  constructor() {
+    this._nativeHandle = Native.Mp4SanitizerOptions_New();
+  }
+
+  /**
+   * Turns stripping of metadata boxes that aren't listed as preserved on or off.
+   *
+   * @returns this object, for chaining.
+   */
+  setMetadataStripped(stripped: boolean): this {
+    Native.Mp4SanitizerOptions_SetMetadataStripped(this, stripped);
+    return this;
+  }
+
+  /**
+   * Sets whether boxes of type `boxType` should be kept, turning on stripping of unlisted metadata
+   * boxes.
+   *
+   * @param boxType The four-character code of the box, e.g. `'colr'`.
+   * @param preserved Whether to keep boxes of this type.
+   * @returns this object, for chaining.
+   */
+  setMetadataBoxPreserved(boxType: string, preserved: boolean): this {
+    const boxTypeBytes = Buffer.from(boxType, 'latin1');
+    if (boxTypeBytes.length != 4) {
+      throw new TypeError('box types are four characters long');
+    }
+    Native.Mp4SanitizerOptions_SetMetadataBoxPreserved(
+      this,
+      boxTypeBytes,
+      preserved
+    );
+    return this;
+  }
+
+  /**
+   * Restricts the codecs accepted in audio and video tracks.
+   *
+   * Tracks using a codec not allowed by `policy` cause sanitizing to fail with {@link UnsupportedMediaInputError}.
+   *
+   * @returns this object, for chaining.
+   */
+  setCodecPolicy(policy: Mp4CodecPolicy): this {
+    Native.Mp4SanitizerOptions_SetCodecPolicy(
+      this,
+      policy.allowHevc ?? false,
+      policy.allowAv1 ?? false,
+      policy.allowOpus ?? false
+    );
+    return this;
+  }
+}
+
 /**
  * Sanitize an MP4 input.
  *
  * @param input An MP4 format input stream.
  * @param len The exact length of the input stream.
+ * @param options Which metadata boxes to keep and codecs to accept; if omitted, nothing beyond the sanitizing itself.
  * @returns The sanitized metadata.
  * @throws {IoError} If an IO error on the input occurs.
  * @throws {InvalidMediaInputError} If the input could not be parsed because it was invalid.
@@ -93,11 +174,22 @@ export class SanitizedMetadata {
  */
 export async function sanitize(
   input: InputStream,
-  len: bigint
+  len: bigint,
+  options?: Mp4SanitizerOptions
 ): Promise<SanitizedMetadata> {
-  const sanitizedMetadataNativeHandle = await Native.Mp4Sanitizer_Sanitize(
-    input,
-    len
-  );
+  const sanitizedMetadataNativeHandle = options
+    ? await Native.Mp4Sanitizer_SanitizeWithOptions(input, len, options)
+    : await Native.Mp4Sanitizer_Sanitize(input, len);
   return SanitizedMetadata._fromNativeHandle(sanitizedMetadataNativeHandle);
 }
+
+/**
+ * Sets how many bytes of metadata buffers the sanitizer may keep for reuse between inputs.
+ *
+ * Sanitizing a large video can need hundreds of megabytes of buffers. Keeping them around avoids
+ * allocating them again for the next input (or for inputs sanitized concurrently), at the cost of
+ * holding on to that memory while no sanitizer is running. The default is 0, which keeps nothing.
+ */
+export function setMaxPooledBufferBytes(maxBytes: number): void {
+  Native.Mp4Sanitizer_SetMaxPooledBufferBytes(BigInt(maxBytes));
+}
@@ -0,0 +1,46 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import * as Native from '../Native';
+
+/**
+ * Process-wide caps on the resources libsignal will use to parse untrusted input.
+ *
+ * Omitted limits keep their defaults, which don't restrict anything beyond what each parser
+ * already enforces.
+ */
+export type ParseLimits = {
+  /** The largest serialized message that will be decoded. */
+  maxMessageSize?: number;
+  /** The most elements a single decoded collection may have. */
+  maxCollectionCount?: number;
+  /** The most bytes a compressed stream may expand to. */
+  maxDecompressedSize?: number;
+  /** The most metadata a media file may have. */
+  maxMediaMetadataSize?: number;
+  /**
+   * The most decoded data a streaming parser, such as the backup validator, may
+   * keep in memory.
+   */
+  maxRetainedSize?: number;
+};
+
+const UNLIMITED = 0xffff_ffff_ffff_ffffn;
+export const DEFAULT_MAX_MEDIA_METADATA_SIZE = 300 * 1024 * 1024;
+
+function toBigInt(limit: number | undefined): bigint {
+  return limit === undefined ? UNLIMITED : BigInt(limit);
+}
+
+/** Replaces the limits used for all subsequent parsing. */
+export function setParseLimits(limits: ParseLimits): void {
+  Native.ParseLimits_Set(
+    toBigInt(limits.maxMessageSize),
+    toBigInt(limits.maxCollectionCount),
+    toBigInt(limits.maxDecompressedSize),
+    toBigInt(limits.maxMediaMetadataSize ?? DEFAULT_MAX_MEDIA_METADATA_SIZE),
+    toBigInt(limits.maxRetainedSize)
+  );
+}
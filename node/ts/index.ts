@@ -10,9 +10,11 @@ export * from './Errors';
 
 import { Aci, ProtocolAddress, ServiceId } from './Address';
 export * from './Address';
-import { PrivateKey, PublicKey } from './EcKeys';
+import { IdentityKeyPair, PrivateKey, PublicKey } from './EcKeys';
 export * from './EcKeys';
 
+export * as E164 from './E164';
+
 export * as usernames from './usernames';
 
 export * as io from './io';
@@ -22,7 +24,32 @@ export * as Net from './net';
 export * as Mp4Sanitizer from './Mp4Sanitizer';
 export * as WebpSanitizer from './WebpSanitizer';
 
+export * from './ParseLimits';
+export * from './BridgeMetrics';
+
 import * as Native from '../Native';
+import { BRIDGE_MANIFEST_SHA256 } from './BridgeManifest';
+
+// Make sure the native module was built from the same Rust code as Native.d.ts; otherwise calls
+// could end up with mismatched arguments, or with functions missing entirely.
+// A native module that predates bridge manifests won't have the function at all.
+const loadedBridgeManifestSha256 =
+  typeof Native.BridgeManifest_GetSha256 === 'function'
+    ? Native.BridgeManifest_GetSha256()
+    : undefined;
+if (loadedBridgeManifestSha256 !== BRIDGE_MANIFEST_SHA256) {
+  throw new Errors.LibSignalErrorBase(
+    'libsignal native module does not match its TypeScript code: ' +
+      `expected bridge manifest ${BRIDGE_MANIFEST_SHA256}, but the loaded ` +
+      `module has ${loadedBridgeManifestSha256 ?? 'no bridge manifest'}`,
+    'BridgeManifestMismatch',
+    'loading the native module',
+    {
+      expectedSha256: BRIDGE_MANIFEST_SHA256,
+      loadedSha256: loadedBridgeManifestSha256,
+    }
+  );
+}
 
 Native.registerErrors(Errors);
 
@@ -40,6 +67,22 @@ export enum Direction {
   Receiving,
 }
 
+/**
+ * How two consecutive SignalMessages from the same peer relate to each other.
+ *
+ * See {@link SignalMessage#ratchetContinuityAfter}.
+ */
+export enum RatchetContinuity {
+  /** The later message continues the earlier message's chain. */
+  SameChain = 0,
+  /** The later message starts a new chain after the earlier message's chain. */
+  NextChain = 1,
+  /** The later message is on the same chain but doesn't come after the earlier one. */
+  Reordered = 2,
+  /** The later message starts a new chain that doesn't account for the earlier message. */
+  Discontinuous = 3,
+}
+
 // This enum must be kept in sync with sealed_sender.proto.
 export enum ContentHint {
   Default = 0,
@@ -155,6 +198,50 @@ export class Fingerprint {
   }
 }
 
+/**
+ * The digest of a single identity that makes up one half of a safety number.
+ *
+ * Displaying one of these is consistent with the corresponding half of any {@link Fingerprint}
+ * computed with the same version and iteration count.
+ */
+export class IdentityDigest {
+  readonly version: number;
+  readonly digest: Buffer;
+
+  private constructor(version: number, digest: Buffer) {
+    this.version = version;
+    this.digest = digest;
+  }
+
+  static new(
+    iterations: number,
+    version: number,
+    stableIdentifier: Buffer,
+    identityKey: PublicKey
+  ): IdentityDigest {
+    return new IdentityDigest(
+      version,
+      Native.IdentityDigest_Compute(
+        iterations,
+        version,
+        stableIdentifier,
+        identityKey
+      )
+    );
+  }
+
+  static fromBuffer(version: number, digest: Buffer): IdentityDigest {
+    // Validate the length up front.
+    Native.IdentityDigest_DisplayString(version, digest);
+    return new IdentityDigest(version, digest);
+  }
+
+  /** The 30-digit string this identity contributes to a displayed safety number. */
+  displayString(): string {
+    return Native.IdentityDigest_DisplayString(this.version, this.digest);
+  }
+}
+
 export class Aes256GcmSiv {
   readonly _nativeHandle: Native.Aes256GcmSiv;
 
@@ -265,8 +352,8 @@ export class PreKeyBundle {
   ): PreKeyBundle {
     return new PreKeyBundle(
       Native.PreKeyBundle_New(
-        registration_id,
-        device_id,
+        registration_id as Native.RegistrationId,
+        device_id as Native.DeviceId,
         prekey_id,
         prekey != null ? prekey : null,
         //prekey?,
@@ -350,6 +437,16 @@ export class PreKeyRecord {
     return new PreKeyRecord(Native.PreKeyRecord_Deserialize(buffer));
   }
 
+  /**
+   * Rewrites a serialized record with a version header, in the newest format
+   * libsignal reads.
+   *
+   * @see SessionRecord.migrateToCurrent
+   */
+  static migrateToCurrent(buffer: Buffer): Buffer {
+    return Native.PreKeyRecord_MigrateToCurrent(buffer);
+  }
+
   id(): number {
     return Native.PreKeyRecord_GetId(this);
   }
@@ -400,6 +497,16 @@ export class SignedPreKeyRecord {
     );
   }
 
+  /**
+   * Rewrites a serialized record with a version header, in the newest format
+   * libsignal reads.
+   *
+   * @see SessionRecord.migrateToCurrent
+   */
+  static migrateToCurrent(buffer: Buffer): Buffer {
+    return Native.SignedPreKeyRecord_MigrateToCurrent(buffer);
+  }
+
   id(): number {
     return Native.SignedPreKeyRecord_GetId(this);
   }
@@ -461,6 +568,16 @@ export class KyberPreKeyRecord {
     return new KyberPreKeyRecord(Native.KyberPreKeyRecord_Deserialize(buffer));
   }
 
+  /**
+   * Rewrites a serialized record with a version header, in the newest format
+   * libsignal reads.
+   *
+   * @see SessionRecord.migrateToCurrent
+   */
+  static migrateToCurrent(buffer: Buffer): Buffer {
+    return Native.KyberPreKeyRecord_MigrateToCurrent(buffer);
+  }
+
   id(): number {
     return Native.KyberPreKeyRecord_GetId(this);
   }
@@ -535,10 +652,32 @@ export class SignalMessage {
     return Native.SignalMessage_GetCounter(this);
   }
 
+  previousCounter(): number {
+    return Native.SignalMessage_GetPreviousCounter(this);
+  }
+
   messageVersion(): number {
     return Native.SignalMessage_GetMessageVersion(this);
   }
 
+  /**
+   * Checks whether this message plausibly follows `earlier` in the same session.
+   *
+   * `earlier` must be the last message received from the same peer before this one. Only the
+   * unauthenticated ratchet metadata is examined, so this is a diagnostic for investigating
+   * suspected session tampering, not a check for whether to accept a message.
+   */
+  ratchetContinuityAfter(earlier: SignalMessage): RatchetContinuity {
+    const n: number = Native.SignalMessage_CheckRatchetContinuity(
+      this,
+      earlier
+    );
+    if (!(n in RatchetContinuity)) {
+      throw new TypeError(`Invalid RatchetContinuity ${n}`);
+    }
+    return n;
+  }
+
   serialize(): Buffer {
     return Native.SignalMessage_GetSerialized(this);
   }
@@ -576,7 +715,7 @@ export class PreKeySignalMessage {
     return new PreKeySignalMessage(
       Native.PreKeySignalMessage_New(
         messageVersion,
-        registrationId,
+        registrationId as Native.RegistrationId,
         preKeyId,
         signedPreKeyId,
         baseKey,
@@ -628,6 +767,20 @@ export class SessionRecord {
     return new SessionRecord(Native.SessionRecord_Deserialize(buffer));
   }
 
+  /**
+   * Rewrites a serialized record with a version header, in the newest format
+   * libsignal reads.
+   *
+   * Records with or without a header can always be deserialized directly.
+   * `serialize()` still writes records without one, so that older versions of
+   * libsignal can read them; this is the opt-in for apps that no longer need to
+   * support those versions. Throws if the record is malformed or was written by
+   * a newer version of libsignal.
+   */
+  static migrateToCurrent(buffer: Buffer): Buffer {
+    return Native.SessionRecord_MigrateToCurrent(buffer);
+  }
+
   serialize(): Buffer {
     return Native.SessionRecord_Serialize(this);
   }
@@ -656,8 +809,48 @@ export class SessionRecord {
   currentRatchetKeyMatches(key: PublicKey): boolean {
     return Native.SessionRecord_CurrentRatchetKeyMatches(this, key);
   }
+
+  /**
+   * Summarizes the activity on the current session, for security screens and
+   * diagnostics.
+   *
+   * Throws if there is no current session.
+   */
+  stats(): SessionStats {
+    const lastRatchetStepMillis =
+      Native.SessionRecord_GetLastRatchetStepTime(this);
+    return {
+      messagesEncrypted: Native.SessionRecord_GetMessagesEncrypted(this),
+      messagesDecrypted: Native.SessionRecord_GetMessagesDecrypted(this),
+      lastRatchetStep:
+        lastRatchetStepMillis == 0 ? null : new Date(lastRatchetStepMillis),
+      isPostQuantum: Native.SessionRecord_IsPostQuantum(this),
+    };
+  }
 }
 
+/**
+ * A summary of the activity on a session.
+ *
+ * Counts only cover the current session, not any archived ones.
+ */
+export type SessionStats = {
+  /** Messages encrypted on this session. */
+  messagesEncrypted: number;
+  /** Messages successfully decrypted on this session. */
+  messagesDecrypted: number;
+  /**
+   * When this device first sent a message using its current ratchet key, or
+   * null if it hasn't sent one.
+   */
+  lastRatchetStep: Date | null;
+  /**
+   * Whether the session was established with a post-quantum (PQXDH) key
+   * agreement.
+   */
+  isPostQuantum: boolean;
+};
+
 export class ServerCertificate {
   readonly _nativeHandle: Native.ServerCertificate;
 
@@ -723,11 +916,53 @@ export class SenderKeyRecord {
     return new SenderKeyRecord(Native.SenderKeyRecord_Deserialize(buffer));
   }
 
+  /**
+   * Rewrites a serialized record with a version header, in the newest format
+   * libsignal reads.
+   *
+   * @see SessionRecord.migrateToCurrent
+   */
+  static migrateToCurrent(buffer: Buffer): Buffer {
+    return Native.SenderKeyRecord_MigrateToCurrent(buffer);
+  }
+
   serialize(): Buffer {
     return Native.SenderKeyRecord_Serialize(this);
   }
 }
 
+/**
+ * Which recipients have been sent the sender key currently in use for a sender
+ * and distribution ID.
+ *
+ * Stores only need to persist these opaque records; they are maintained by
+ * {@link markSenderKeyDistributed} and consulted by
+ * {@link senderKeyRecipientsNeedingDistribution}.
+ */
+export class SenderKeyDistributionRecord {
+  readonly _nativeHandle: Native.SenderKeyDistributionRecord;
+
+  static _fromNativeHandle(
+    nativeHandle: Native.SenderKeyDistributionRecord
+  ): SenderKeyDistributionRecord {
+    return new SenderKeyDistributionRecord(nativeHandle);
+  }
+
+  private constructor(nativeHandle: Native.SenderKeyDistributionRecord) {
+    this._nativeHandle = nativeHandle;
+  }
+
+  static deserialize(buffer: Buffer): SenderKeyDistributionRecord {
+    return new SenderKeyDistributionRecord(
+      Native.SenderKeyDistributionRecord_Deserialize(buffer)
+    );
+  }
+
+  serialize(): Buffer {
+    return Native.SenderKeyDistributionRecord_Serialize(this);
+  }
+}
+
 export class SenderCertificate {
   readonly _nativeHandle: Native.SenderCertificate;
 
@@ -757,7 +992,7 @@ export class SenderCertificate {
       Native.SenderCertificate_New(
         senderUuid,
         senderE164,
-        senderDeviceId,
+        senderDeviceId as Native.DeviceId,
         senderKey,
         expiration,
         signerCert,
@@ -815,6 +1050,22 @@ export class SenderCertificate {
   validate(trustRoot: PublicKey, time: number): boolean {
     return Native.SenderCertificate_Validate(this, trustRoot, time);
   }
+  /**
+   * Returns whether this certificate will have expired by `time + refreshMarginSeconds`.
+   *
+   * Use this to replace a cached certificate before it expires, rather than waiting for sends to
+   * fail. This does not validate the certificate.
+   *
+   * @param time The current time, in milliseconds since the epoch.
+   * @param refreshMarginSeconds How long before expiration the certificate should be replaced.
+   */
+  needsRefresh(time: number, refreshMarginSeconds: number): boolean {
+    return Native.SenderCertificate_NeedsRefresh(
+      this,
+      time,
+      refreshMarginSeconds
+    );
+  }
 }
 
 export class SenderKeyDistributionMessage {
@@ -1010,6 +1261,26 @@ export class UnidentifiedSenderMessageContent {
   groupId(): Buffer | null {
     return Native.UnidentifiedSenderMessageContent_GetGroupId(this);
   }
+
+  /**
+   * Returns a copy of this message with a different content hint and group ID.
+   *
+   * The sender certificate and the encrypted contents are kept as they are, so
+   * a message can be resent with different handling without rebuilding it from
+   * a {@link CiphertextMessage}.
+   */
+  withContentHintAndGroupId(
+    contentHint: number,
+    groupId: Buffer | null
+  ): UnidentifiedSenderMessageContent {
+    return new UnidentifiedSenderMessageContent(
+      Native.UnidentifiedSenderMessageContent_WithContentHintAndGroupId(
+        this,
+        contentHint,
+        groupId
+      )
+    );
+  }
 }
 
 export abstract class SessionStore implements Native.SessionStore {
@@ -1194,6 +1465,32 @@ export abstract class SenderKeyStore implements Native.SenderKeyStore {
     }
   }
 
+  async _saveSenderKeyDistribution(
+    sender: Native.ProtocolAddress,
+    distributionId: Native.Uuid,
+    record: Native.SenderKeyDistributionRecord
+  ): Promise<void> {
+    return this.saveSenderKeyDistribution(
+      ProtocolAddress._fromNativeHandle(sender),
+      uuid.stringify(distributionId),
+      SenderKeyDistributionRecord._fromNativeHandle(record)
+    );
+  }
+  async _getSenderKeyDistribution(
+    sender: Native.ProtocolAddress,
+    distributionId: Native.Uuid
+  ): Promise<Native.SenderKeyDistributionRecord | null> {
+    const record = await this.getSenderKeyDistribution(
+      ProtocolAddress._fromNativeHandle(sender),
+      uuid.stringify(distributionId)
+    );
+    if (record == null) {
+      return null;
+    } else {
+      return record._nativeHandle;
+    }
+  }
+
   abstract saveSenderKey(
     sender: ProtocolAddress,
     distributionId: Uuid,
@@ -1203,6 +1500,155 @@ export abstract class SenderKeyStore implements Native.SenderKeyStore {
     sender: ProtocolAddress,
     distributionId: Uuid
   ): Promise<SenderKeyRecord | null>;
+
+  /**
+   * Tracking distribution is optional: by default the record is discarded, in
+   * which case every recipient is always reported as needing the sender key.
+   */
+  saveSenderKeyDistribution(
+    _sender: ProtocolAddress,
+    _distributionId: Uuid,
+    _record: SenderKeyDistributionRecord
+  ): Promise<void> {
+    return Promise.resolve();
+  }
+  /**
+   * Stores that override {@link saveSenderKeyDistribution} should override
+   * this too; by default there is never a record.
+   */
+  getSenderKeyDistribution(
+    _sender: ProtocolAddress,
+    _distributionId: Uuid
+  ): Promise<SenderKeyDistributionRecord | null> {
+    return Promise.resolve(null);
+  }
+}
+
+/**
+ * An optional record of messages that have already been decrypted.
+ *
+ * A session normally rejects repeated messages by itself, but that protection is lost if the
+ * session record is deleted or replaced. Clients that need to process each message at most once
+ * can pass a replay cache to {@link signalDecrypt}, {@link signalDecryptPreKey}, or
+ * {@link sealedSenderDecryptMessage}.
+ *
+ * Messages are identified by the sender's ratchet key as well as the counter, since counters
+ * start over with each new chain. How long entries are kept is up to the implementation.
+ */
+export abstract class ReplayCache implements Native.ReplayCache {
+  async _contains(
+    address: Native.ProtocolAddress,
+    senderRatchetKey: Native.PublicKey,
+    counter: number
+  ): Promise<boolean> {
+    return this.contains(
+      ProtocolAddress._fromNativeHandle(address),
+      PublicKey._fromNativeHandle(senderRatchetKey),
+      counter
+    );
+  }
+
+  async _insert(
+    address: Native.ProtocolAddress,
+    senderRatchetKey: Native.PublicKey,
+    counter: number
+  ): Promise<void> {
+    return this.insert(
+      ProtocolAddress._fromNativeHandle(address),
+      PublicKey._fromNativeHandle(senderRatchetKey),
+      counter
+    );
+  }
+
+  /**
+   * Returns true if the message has already been recorded (and has not yet expired).
+   */
+  abstract contains(
+    address: ProtocolAddress,
+    senderRatchetKey: PublicKey,
+    counter: number
+  ): Promise<boolean>;
+
+  /**
+   * Records that a message has been processed.
+   *
+   * This is only called after the session the message advanced has been saved.
+   */
+  abstract insert(
+    address: ProtocolAddress,
+    senderRatchetKey: PublicKey,
+    counter: number
+  ): Promise<void>;
+}
+
+/**
+ * Returns the members of `recipients` that have not yet been sent the sender
+ * key currently used for `distributionId`.
+ *
+ * If there is no sender key yet, the key has changed since distribution was
+ * last recorded, or the store does not track distribution, every recipient is
+ * returned. Each of them should be sent a {@link SenderKeyDistributionMessage}
+ * before (or along with) the next group message, and then passed to
+ * {@link markSenderKeyDistributed}.
+ */
+export async function senderKeyRecipientsNeedingDistribution(
+  sender: ProtocolAddress,
+  distributionId: Uuid,
+  recipients: ProtocolAddress[],
+  store: SenderKeyStore
+): Promise<ProtocolAddress[]> {
+  const needsDistribution =
+    await Native.SenderKey_RecipientsNeedingDistribution(
+      sender,
+      Buffer.from(uuid.parse(distributionId) as Uint8Array),
+      recipients,
+      store
+    );
+  return recipients.filter((_, i) => needsDistribution[i] != 0);
+}
+
+/**
+ * Records that `recipients` have been sent the sender key currently used for
+ * `distributionId`.
+ *
+ * Call this only once the {@link SenderKeyDistributionMessage} has actually
+ * been delivered. Any record left over from a previous sender key is
+ * discarded.
+ */
+export async function markSenderKeyDistributed(
+  sender: ProtocolAddress,
+  distributionId: Uuid,
+  recipients: ProtocolAddress[],
+  store: SenderKeyStore
+): Promise<void> {
+  await Native.SenderKey_MarkDistributed(
+    sender,
+    Buffer.from(uuid.parse(distributionId) as Uint8Array),
+    recipients,
+    store
+  );
+}
+
+/**
+ * Forgets that `recipients` have been sent the sender key for
+ * `distributionId`, so that they will be returned by
+ * {@link senderKeyRecipientsNeedingDistribution} again.
+ *
+ * Use this when a recipient may have lost the key, such as after their session
+ * is reset or they send a decryption error for a group message.
+ */
+export async function invalidateSenderKeyDistribution(
+  sender: ProtocolAddress,
+  distributionId: Uuid,
+  recipients: ProtocolAddress[],
+  store: SenderKeyStore
+): Promise<void> {
+  await Native.SenderKey_InvalidateDistribution(
+    sender,
+    Buffer.from(uuid.parse(distributionId) as Uint8Array),
+    recipients,
+    store
+  );
 }
 
 export async function groupEncrypt(
@@ -1229,6 +1675,56 @@ export async function groupDecrypt(
   return Native.GroupCipher_DecryptMessage(sender, message, store);
 }
 
+/**
+ * The stores checked by {@link checkStoreConformance}.
+ *
+ * The same object may be used for more than one store.
+ */
+export type ConformanceStores = {
+  identityStore: IdentityKeyStore;
+  preKeyStore: PreKeyStore;
+  signedPreKeyStore: SignedPreKeyStore;
+  kyberPreKeyStore: KyberPreKeyStore;
+  sessionStore: SessionStore;
+  senderKeyStore: SenderKeyStore;
+};
+
+/**
+ * Checks that store implementations behave the way libsignal expects.
+ *
+ * These are the same checks libsignal runs against its own stores, each run against fresh, empty
+ * stores from `makeStores`. They are meant to be run from an app's tests, not in production.
+ *
+ * Rejects with an error describing the first check that failed, or with any error thrown by the
+ * stores themselves.
+ */
+export async function checkStoreConformance(
+  makeStores: (
+    identity: IdentityKeyPair,
+    registrationId: number
+  ) => ConformanceStores | Promise<ConformanceStores>
+): Promise<void> {
+  const count = Native.StoreConformance_CheckCount();
+  for (let check = 0; check < count; check++) {
+    const identity = IdentityKeyPair.generate();
+    // Valid registration IDs fit in 14 bits.
+    const registrationId = 1 + Math.floor(Math.random() * 0x3fff);
+    const stores = await makeStores(identity, registrationId);
+    await Native.StoreConformance_Check(
+      check,
+      identity.publicKey,
+      identity.privateKey,
+      registrationId,
+      stores.identityStore,
+      stores.preKeyStore,
+      stores.signedPreKeyStore,
+      stores.kyberPreKeyStore,
+      stores.sessionStore,
+      stores.senderKeyStore
+    );
+  }
+}
+
 export class SealedSenderDecryptionResult {
   readonly _nativeHandle: Native.SealedSenderDecryptionResult;
 
@@ -1270,6 +1766,26 @@ export class SealedSenderDecryptionResult {
   deviceId(): number {
     return Native.SealedSenderDecryptionResult_GetDeviceId(this);
   }
+
+  /**
+   * Returns the sender's previous and current identity keys if the sender's
+   * identity changed, `null` otherwise.
+   *
+   * Only set by {@link sealedSenderDecryptMessageCapturingIdentityChange}.
+   */
+  identityChange(): { previous: PublicKey; current: PublicKey } | null {
+    const previous =
+      Native.SealedSenderDecryptionResult_GetPreviousIdentityKey(this);
+    const current =
+      Native.SealedSenderDecryptionResult_GetCurrentIdentityKey(this);
+    if (previous === null || current === null) {
+      return null;
+    }
+    return {
+      previous: PublicKey._fromNativeHandle(previous),
+      current: PublicKey._fromNativeHandle(current),
+    };
+  }
 }
 
 export interface CiphertextMessageConvertible {
@@ -1358,7 +1874,7 @@ export class DecryptionErrorMessage {
         bytes,
         type,
         timestamp,
-        originalSenderDeviceId
+        originalSenderDeviceId as Native.DeviceId
       )
     );
   }
@@ -1435,8 +1951,18 @@ export function signalDecrypt(
   message: SignalMessage,
   address: ProtocolAddress,
   sessionStore: SessionStore,
-  identityStore: IdentityKeyStore
+  identityStore: IdentityKeyStore,
+  replayCache?: ReplayCache
 ): Promise<Buffer> {
+  if (replayCache) {
+    return Native.SessionCipher_DecryptSignalMessageWithReplayCache(
+      message,
+      address,
+      sessionStore,
+      identityStore,
+      replayCache
+    );
+  }
   return Native.SessionCipher_DecryptSignalMessage(
     message,
     address,
@@ -1452,8 +1978,21 @@ export function signalDecryptPreKey(
   identityStore: IdentityKeyStore,
   prekeyStore: PreKeyStore,
   signedPrekeyStore: SignedPreKeyStore,
-  kyberPrekeyStore: KyberPreKeyStore
+  kyberPrekeyStore: KyberPreKeyStore,
+  replayCache?: ReplayCache
 ): Promise<Buffer> {
+  if (replayCache) {
+    return Native.SessionCipher_DecryptPreKeySignalMessageWithReplayCache(
+      message,
+      address,
+      sessionStore,
+      identityStore,
+      prekeyStore,
+      signedPrekeyStore,
+      kyberPrekeyStore,
+      replayCache
+    );
+  }
   return Native.SessionCipher_DecryptPreKeySignalMessage(
     message,
     address,
@@ -1553,6 +2092,38 @@ export function sealedSenderMultiRecipientMessageForSingleRecipient(
   return Native.SealedSender_MultiRecipientMessageForSingleRecipient(message);
 }
 
+/**
+ * Computes the exact length of the message produced by
+ * {@link sealedSenderMultiRecipientEncrypt}, without encrypting anything.
+ *
+ * `usmcLength` is the length of the serialized
+ * {@link UnidentifiedSenderMessageContent}; `deviceCount` is the total number
+ * of devices across all `recipientCount` recipients.
+ */
+export function sealedSenderMultiRecipientMessageLength(
+  usmcLength: number,
+  recipientCount: number,
+  deviceCount: number,
+  excludedRecipientCount: number
+): number {
+  return Native.SealedSender_MultiRecipientMessageLength(
+    usmcLength,
+    recipientCount,
+    deviceCount,
+    excludedRecipientCount
+  );
+}
+
+/**
+ * Computes the length of the message each recipient device receives after the
+ * server splits up a multi-recipient message.
+ */
+export function sealedSenderMultiRecipientReceivedMessageLength(
+  usmcLength: number
+): number {
+  return Native.SealedSender_MultiRecipientReceivedMessageLength(usmcLength);
+}
+
 export async function sealedSenderDecryptMessage(
   message: Buffer,
   trustRoot: PublicKey,
@@ -1564,15 +2135,70 @@ export async function sealedSenderDecryptMessage(
   identityStore: IdentityKeyStore,
   prekeyStore: PreKeyStore,
   signedPrekeyStore: SignedPreKeyStore,
-  kyberPrekeyStore: KyberPreKeyStore
+  kyberPrekeyStore: KyberPreKeyStore,
+  replayCache?: ReplayCache
 ): Promise<SealedSenderDecryptionResult> {
+  if (replayCache) {
+    const ssdr = await Native.SealedSender_DecryptMessageWithReplayCache(
+      message,
+      trustRoot,
+      timestamp,
+      localE164,
+      localUuid,
+      localDeviceId as Native.DeviceId,
+      sessionStore,
+      identityStore,
+      prekeyStore,
+      signedPrekeyStore,
+      kyberPrekeyStore,
+      replayCache
+    );
+    return SealedSenderDecryptionResult._fromNativeHandle(ssdr);
+  }
   const ssdr = await Native.SealedSender_DecryptMessage(
     message,
     trustRoot,
     timestamp,
     localE164,
     localUuid,
-    localDeviceId,
+    localDeviceId as Native.DeviceId,
+    sessionStore,
+    identityStore,
+    prekeyStore,
+    signedPrekeyStore,
+    kyberPrekeyStore
+  );
+  return SealedSenderDecryptionResult._fromNativeHandle(ssdr);
+}
+
+/**
+ * Like {@link sealedSenderDecryptMessage}, but decrypts messages from a sender
+ * whose identity key has changed instead of consulting `identityStore`.
+ *
+ * The change is reported by {@link SealedSenderDecryptionResult.identityChange}
+ * and is *not* saved to `identityStore`; if the app accepts the new key, it
+ * should save it itself.
+ */
+export async function sealedSenderDecryptMessageCapturingIdentityChange(
+  message: Buffer,
+  trustRoot: PublicKey,
+  timestamp: number,
+  localE164: string | null,
+  localUuid: string,
+  localDeviceId: number,
+  sessionStore: SessionStore,
+  identityStore: IdentityKeyStore,
+  prekeyStore: PreKeyStore,
+  signedPrekeyStore: SignedPreKeyStore,
+  kyberPrekeyStore: KyberPreKeyStore
+): Promise<SealedSenderDecryptionResult> {
+  const ssdr = await Native.SealedSender_DecryptMessageCapturingIdentityChange(
+    message,
+    trustRoot,
+    timestamp,
+    localE164,
+    localUuid,
+    localDeviceId as Native.DeviceId,
     sessionStore,
     identityStore,
     prekeyStore,
@@ -1663,6 +2289,51 @@ export class HsmEnclaveClient {
   establishedRecv(buffer: Buffer): Buffer {
     return Native.HsmEnclaveClient_EstablishedRecv(this, buffer);
   }
+
+  /**
+   * Allows up to `maxInFlight` requests to be sent before their responses are received.
+   *
+   * Without this, the connection doesn't track requests at all. Once pipelining is enabled,
+   * several requests can be sent with {@link #establishedSend} before any responses arrive.
+   * Responses must be passed to {@link #establishedRecv} in the order they're received, and will
+   * correspond to requests in the order they were sent; use {@link #lastSentRequestId} and
+   * {@link #lastReceivedRequestId} to match them up. Sending more than `maxInFlight` requests
+   * without receiving responses throws an error.
+   *
+   * Only valid once the handshake has completed, and should be called before the first request
+   * is sent. Calling it again changes the limit.
+   */
+  enablePipelining(maxInFlight: number): void {
+    Native.HsmEnclaveClient_EnablePipelining(this, maxInFlight);
+  }
+
+  /**
+   * The id of the request most recently encrypted by {@link #establishedSend}.
+   *
+   * Only valid once pipelining has been enabled and a request has been sent.
+   */
+  lastSentRequestId(): bigint {
+    return Native.HsmEnclaveClient_LastSentRequestId(this);
+  }
+
+  /**
+   * The id of the request answered by the response most recently decrypted by
+   * {@link #establishedRecv}.
+   *
+   * Only valid once pipelining has been enabled and a response has been received.
+   */
+  lastReceivedRequestId(): bigint {
+    return Native.HsmEnclaveClient_LastReceivedRequestId(this);
+  }
+
+  /**
+   * The number of requests sent whose responses haven't been received yet.
+   *
+   * Only valid once pipelining has been enabled.
+   */
+  inFlight(): number {
+    return Native.HsmEnclaveClient_InFlight(this);
+  }
 }
 
 export enum LogLevel {
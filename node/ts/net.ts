@@ -5,20 +5,30 @@
 
 import type { ReadonlyDeep } from 'type-fest';
 import * as Native from '../Native';
-import { Aci } from './Address';
+import { Aci, Pni, ServiceId } from './Address';
 import {
   AppExpiredError,
   ChatServiceInactive,
   DeviceDelinkedError,
   IoError,
+  NotFoundError,
   RateLimitedError,
+  RequestUnauthorizedError,
   SvrDataMissingError,
   SvrRestoreFailedError,
   SvrRequestFailedError,
   LibSignalError,
 } from './Errors';
 import { ServerMessageAck, Wrapper } from '../Native';
+import { SenderCertificate } from './index';
+import {
+  ExpiringProfileKeyCredential,
+  ProfileKey,
+  ServerPublicParams,
+} from './zkgroup';
+import { RANDOM_LENGTH } from './zkgroup/internal/Constants';
 import { Buffer } from 'node:buffer';
+import { randomBytes } from 'node:crypto';
 
 const DEFAULT_CHAT_REQUEST_TIMEOUT_MILLIS = 5000;
 
@@ -28,6 +38,62 @@ export enum Environment {
   Production = 1,
 }
 
+// This must match the libsignal-bridge Rust enum of the same name.
+export enum NetService {
+  Chat = 0,
+  Cdsi = 1,
+  Svr3 = 2,
+}
+
+/**
+ * The kinds of route that can be tried when connecting with censorship
+ * circumvention enabled.
+ *
+ * A proxy set with {@link Net#setProxy} isn't listed here, because it is used
+ * for whichever route is being tried.
+ */
+// This must match the libsignal-bridge Rust enum ConnectionRoute.
+export enum ConnectionRoute {
+  Direct = 0,
+  ProxyF = 1,
+  ProxyG = 2,
+}
+
+/** The kinds of receipt a client can send. */
+// This must match the libsignal-net Rust enum ReceiptKind.
+export enum ReceiptKind {
+  Delivery = 0,
+  Read = 1,
+  Viewed = 2,
+}
+
+/**
+ * Receipts for messages from one sender that should be sent together as a
+ * single receipt message.
+ */
+export type ReceiptBatch = Readonly<{
+  sender: ServiceId;
+  kind: ReceiptKind;
+  /** The sent timestamps of the acknowledged messages, in ascending order. */
+  timestamps: number[];
+}>;
+
+/**
+ * How long to wait between repeated attempts to connect to a service.
+ *
+ * After the first failure the delay starts at `initialDelayMillis`, and is scaled by `multiplier`
+ * after each further failure, up to `maxDelayMillis`. `jitter` (between 0 and 1) randomly shortens
+ * each delay by up to that fraction. If `maxAttempts` is set, each route to the service is only
+ * tried that many times before moving on to the next one.
+ */
+export type RetryPolicy = {
+  initialDelayMillis: number;
+  multiplier: number;
+  jitter: number;
+  maxDelayMillis: number;
+  maxAttempts?: number;
+};
+
 export type ServiceAuth = {
   username: string;
   password: string;
@@ -38,6 +104,17 @@ export type CDSRequestOptionsType = {
   acisAndAccessKeys: Array<{ aci: string; accessKey: string }>;
   returnAcisWithoutUaks: boolean;
   abortSignal?: AbortSignal;
+  /**
+   * If provided, the lookup reuses an idle connection from the pool (when
+   * one was made with the same credentials).
+   */
+  connectionPool?: CdsiConnectionPool;
+  /**
+   * If set along with `connectionPool`, a fresh connection is established in
+   * the pool for the next lookup. Only set this when another lookup will
+   * follow, since otherwise the attestation handshake is wasted.
+   */
+  prepareNextConnection?: boolean;
 };
 
 export type CDSResponseEntryType<Aci, Pni> = {
@@ -55,6 +132,90 @@ export interface CDSResponseType<Aci, Pni> {
   debugPermitsUsed: number;
 }
 
+export enum CDSContactChangeKind {
+  /** The number resolved to an ACI the client did not have on record. */
+  NewAci = 0,
+  /** The number's PNI differs from (or was added to) the one on record. */
+  ChangedPni = 1,
+  /** The number was looked up but is no longer registered. */
+  Removed = 2,
+}
+
+/**
+ * A difference between a client's contact mapping and a CDSI lookup response.
+ *
+ * `aci` is only set for {@link CDSContactChangeKind.NewAci}, and `pni` (which
+ * may still be `undefined`) only for {@link CDSContactChangeKind.ChangedPni}.
+ */
+export type CDSContactChange = {
+  kind: CDSContactChangeKind;
+  e164: string;
+  aci: string | undefined;
+  pni: string | undefined;
+};
+
+const CDS_PACKED_ENTRY_LEN = 8 + 16 + 16;
+
+function packCdsEntries(
+  entries: ReadonlyMap<string, CDSResponseEntryType<string, string>>
+): Buffer {
+  const packed = Buffer.alloc(entries.size * CDS_PACKED_ENTRY_LEN);
+  let offset = 0;
+  for (const [e164, { aci, pni }] of entries) {
+    packed.writeBigUInt64BE(BigInt(e164), offset);
+    if (pni !== undefined) {
+      Pni.parseFromServiceIdString(pni)
+        .getRawUuidBytes()
+        .copy(packed, offset + 8);
+    }
+    if (aci !== undefined) {
+      Aci.parseFromServiceIdString(aci)
+        .getRawUuidBytes()
+        .copy(packed, offset + 24);
+    }
+    offset += CDS_PACKED_ENTRY_LEN;
+  }
+  return packed;
+}
+
+/**
+ * Compares CDSI lookup results with the client's existing mapping from phone
+ * numbers to service IDs.
+ *
+ * A number that appears in the response without an ACI does not clear an ACI
+ * that's already on record, since the server only returns ACIs for which the
+ * client proved knowledge of an access key. Every number in `existing` is
+ * expected to have been part of the request; any that are missing from the
+ * response, or that came back with neither an ACI nor a PNI, are reported as
+ * {@link CDSContactChangeKind.Removed}.
+ */
+export function reconcileCdsiResponse(
+  response: CDSResponseType<string, string>,
+  existing: ReadonlyMap<string, CDSResponseEntryType<string, string>>
+): Array<CDSContactChange> {
+  const changes = newNativeHandle(
+    Native.LookupResponse_reconcile(
+      packCdsEntries(existing),
+      packCdsEntries(response.entries)
+    )
+  );
+
+  const result: Array<CDSContactChange> = [];
+  const count = Native.CdsiContactChanges_Count(changes);
+  for (let i = 0; i < count; i++) {
+    result.push({
+      kind: Native.CdsiContactChanges_GetKind(
+        changes,
+        i
+      ) as CDSContactChangeKind,
+      e164: Native.CdsiContactChanges_GetE164(changes, i),
+      aci: Native.CdsiContactChanges_GetAci(changes, i) ?? undefined,
+      pni: Native.CdsiContactChanges_GetPni(changes, i) ?? undefined,
+    });
+  }
+  return result;
+}
+
 export type ChatRequest = Readonly<{
   verb: string;
   path: string;
@@ -103,6 +264,48 @@ export class TokioAsyncContext {
   }
 }
 
+/**
+ * A small pool of pre-established CDSI connections.
+ *
+ * Connections are tied to the credentials they were made with; looking up
+ * with different credentials discards whatever the pool was holding.
+ */
+export class CdsiConnectionPool {
+  readonly _nativeHandle: Native.CdsiConnectionPool;
+
+  /**
+   * @param idleTimeoutMillis How long an unused connection is kept before it
+   * is dropped. Defaults to 10 seconds.
+   */
+  constructor(idleTimeoutMillis = 10000) {
+    this._nativeHandle = Native.CdsiConnectionPool_new(idleTimeoutMillis);
+  }
+}
+
+/**
+ * Verified key transparency search results, reused instead of searching the
+ * network again.
+ *
+ * A result is only reused while the tree hasn't grown past the latest tree
+ * head verified through the cache, and until it expires.
+ */
+export class KeyTransparencySearchCache {
+  readonly _nativeHandle: Native.KeyTransparencySearchCache;
+
+  /**
+   * @param ttlSeconds How long a verified result is reused. Defaults to 5
+   * minutes.
+   * @param maxEntries How many search keys are cached at most. Defaults to
+   * 1000.
+   */
+  constructor(ttlSeconds = 300, maxEntries = 1000) {
+    this._nativeHandle = Native.KeyTransparencySearchCache_New(
+      ttlSeconds,
+      maxEntries
+    );
+  }
+}
+
 export class ChatServerMessageAck {
   private promise: Promise<void> | null = null;
 
@@ -215,6 +418,21 @@ export type ChatService = {
     chatRequest: ChatRequest,
     options?: { abortSignal?: AbortSignal }
   ): Promise<Native.ChatResponse>;
+
+  /**
+   * Finishes outstanding work and then disconnects, for when the app is about
+   * to be suspended.
+   *
+   * Pending acks for received messages and in-flight requests are given as
+   * much of `budgetMillis` as possible to complete, leaving a little time to
+   * close the connection cleanly. New requests will fail until
+   * {@link #connect()} is called again.
+   *
+   * Resolves to a report of which work was and wasn't flushed.
+   */
+  prepareForBackground(
+    budgetMillis: number
+  ): Promise<Native.BackgroundFlushReport>;
 };
 
 /**
@@ -222,23 +440,52 @@ export type ChatService = {
  */
 export class AuthenticatedChatService implements ChatService {
   public readonly chatService: Wrapper<Native.AuthChat>;
+  private readonly asyncContext: TokioAsyncContext;
 
   constructor(
-    private readonly asyncContext: TokioAsyncContext,
+    asyncContext: TokioAsyncContext,
     connectionManager: ConnectionManager,
     username: string,
     password: string,
     receiveStories: boolean,
     listener: ChatServiceListener
+  );
+  /** Wraps an existing native chat service, such as a mock used in tests. */
+  constructor(
+    asyncContext: TokioAsyncContext,
+    chatService: Native.AuthChat,
+    listener: ChatServiceListener
+  );
+  constructor(
+    asyncContext: TokioAsyncContext,
+    ...args:
+      | [ConnectionManager, string, string, boolean, ChatServiceListener]
+      | [Native.AuthChat, ChatServiceListener]
   ) {
-    this.chatService = newNativeHandle(
-      Native.ChatService_new_auth(
+    this.asyncContext = asyncContext;
+    let listener: ChatServiceListener;
+    if (args.length === 2) {
+      const [chatService, chatListener] = args;
+      this.chatService = newNativeHandle(chatService);
+      listener = chatListener;
+    } else {
+      const [
         connectionManager,
         username,
         password,
-        receiveStories
-      )
-    );
+        receiveStories,
+        chatListener,
+      ] = args;
+      this.chatService = newNativeHandle(
+        Native.ChatService_new_auth(
+          connectionManager,
+          username,
+          password,
+          receiveStories
+        )
+      );
+      listener = chatListener;
+    }
     const nativeChatListener = {
       _incoming_message(
         envelope: Buffer,
@@ -310,6 +557,161 @@ export class AuthenticatedChatService implements ChatService {
       )
     );
   }
+
+  /**
+   * Fetches a new sender certificate for sealed-sender sends.
+   *
+   * Use {@link SenderCertificate#needsRefresh} to decide when a cached certificate should be
+   * replaced. The returned certificate has not been validated against a trust root.
+   *
+   * @throws {IoError} if the request fails or the server's response can't be parsed.
+   */
+  async fetchSenderCertificate(options: {
+    includeE164: boolean;
+    timeoutMillis?: number;
+    abortSignal?: AbortSignal;
+  }): Promise<SenderCertificate> {
+    const certificate = await this.asyncContext.makeCancellable(
+      options.abortSignal,
+      Native.ChatService_auth_fetch_sender_certificate(
+        this.asyncContext,
+        this.chatService,
+        options.includeE164,
+        options.timeoutMillis ?? DEFAULT_CHAT_REQUEST_TIMEOUT_MILLIS
+      )
+    );
+    return SenderCertificate._fromNativeHandle(certificate);
+  }
+
+  /**
+   * Fetches an expiring profile key credential for `aci`'s `profileKey`.
+   *
+   * This makes the request and immediately checks and unblinds the server's
+   * response, the way
+   * `ClientZkProfileOperations.receiveExpiringProfileKeyCredential` would, so
+   * the result is ready to store.
+   *
+   * @throws {NotFoundError} if `profileKey` is out of date.
+   * @throws {RequestUnauthorizedError} if the server refuses to issue the
+   * credential.
+   * @throws {IoError} if the request fails, or if the server's response can't be
+   * parsed or verified.
+   */
+  async fetchProfileKeyCredential(options: {
+    serverPublicParams: ServerPublicParams;
+    aci: Aci;
+    profileKey: ProfileKey;
+    timeoutMillis?: number;
+    abortSignal?: AbortSignal;
+  }): Promise<ExpiringProfileKeyCredential> {
+    const credential = await this.asyncContext.makeCancellable(
+      options.abortSignal,
+      Native.ChatService_auth_fetch_profile_key_credential(
+        this.asyncContext,
+        this.chatService,
+        options.serverPublicParams,
+        randomBytes(RANDOM_LENGTH),
+        options.aci.getServiceIdFixedWidthBinary(),
+        options.profileKey.getContents(),
+        Math.floor(Date.now() / 1000),
+        options.timeoutMillis ?? DEFAULT_CHAT_REQUEST_TIMEOUT_MILLIS
+      )
+    );
+    return new ExpiringProfileKeyCredential(credential);
+  }
+
+  /**
+   * Searches the key transparency log for `searchKey`, returning the value it
+   * maps to.
+   *
+   * `cache` is checked first; on a miss the server's response is verified and
+   * added to the cache before being returned.
+   *
+   * @param options.signatureKey The log's signature key.
+   * @param options.vrfKey The log's VRF key.
+   * @param options.auditorKey The key of the log's third-party auditor.
+   * @throws {IoError} if the request fails, or if the server's response can't be
+   * parsed or verified.
+   */
+  async searchKeyTransparency(options: {
+    cache: KeyTransparencySearchCache;
+    signatureKey: Uint8Array;
+    vrfKey: Uint8Array;
+    auditorKey: Uint8Array;
+    searchKey: Uint8Array;
+    timeoutMillis?: number;
+    abortSignal?: AbortSignal;
+  }): Promise<Buffer> {
+    return await this.asyncContext.makeCancellable(
+      options.abortSignal,
+      Native.ChatService_auth_search_key_transparency(
+        this.asyncContext,
+        this.chatService,
+        options.cache,
+        Buffer.from(options.signatureKey),
+        Buffer.from(options.vrfKey),
+        Buffer.from(options.auditorKey),
+        Buffer.from(options.searchKey),
+        options.timeoutMillis ?? DEFAULT_CHAT_REQUEST_TIMEOUT_MILLIS
+      )
+    );
+  }
+
+  /**
+   * Queues a receipt for the message from `sender` sent at `messageTimestamp`.
+   *
+   * Queued receipts are grouped by sender and kind into as few receipt
+   * messages as possible; take them with {@link nextReceiptBatch}. The receipt
+   * messages themselves may be sent over either chat connection.
+   */
+  queueReceipt(
+    sender: ServiceId,
+    kind: ReceiptKind,
+    messageTimestamp: number
+  ): void {
+    Native.ChatService_auth_queue_receipt(
+      this.chatService,
+      sender.getServiceIdFixedWidthBinary(),
+      kind,
+      messageTimestamp
+    );
+  }
+
+  /**
+   * Takes the next batch of queued receipts, or returns null if there are none.
+   *
+   * Each batch should be sent as one receipt message. Batches not yet taken
+   * stay queued, and can still absorb new receipts.
+   */
+  nextReceiptBatch(): ReceiptBatch | null {
+    const batch = Native.ChatService_auth_next_receipt_batch(this.chatService);
+    if (batch == null) {
+      return null;
+    }
+    const wrapped = newNativeHandle(batch);
+    const packed = Native.ReceiptBatch_GetTimestamps(wrapped);
+    const timestamps: number[] = [];
+    for (let offset = 0; offset < packed.length; offset += 8) {
+      timestamps.push(Number(packed.readBigUInt64BE(offset)));
+    }
+    return {
+      sender: ServiceId.parseFromServiceIdFixedWidthBinary(
+        Native.ReceiptBatch_GetSender(wrapped)
+      ),
+      kind: Native.ReceiptBatch_GetKind(wrapped) as ReceiptKind,
+      timestamps,
+    };
+  }
+
+  prepareForBackground(
+    budgetMillis: number
+  ): Promise<Native.BackgroundFlushReport> {
+    return Native.ChatService_prepare_for_background_auth(
+      this.asyncContext,
+      this.chatService,
+      budgetMillis
+    );
+  }
 }
 
 /**
@@ -317,14 +719,29 @@ export class AuthenticatedChatService implements ChatService {
  */
 export class UnauthenticatedChatService implements ChatService {
   public readonly chatService: Wrapper<Native.UnauthChat>;
+  private readonly asyncContext: TokioAsyncContext;
 
   constructor(
-    private readonly asyncContext: TokioAsyncContext,
+    asyncContext: TokioAsyncContext,
     connectionManager: ConnectionManager,
     listener: ConnectionEventsListener
+  );
+  /** Wraps an existing native chat service, such as a mock used in tests. */
+  constructor(
+    asyncContext: TokioAsyncContext,
+    chatService: Native.UnauthChat,
+    listener: ConnectionEventsListener
+  );
+  constructor(
+    asyncContext: TokioAsyncContext,
+    chatServiceOrConnectionManager: Native.UnauthChat | ConnectionManager,
+    listener: ConnectionEventsListener
   ) {
+    this.asyncContext = asyncContext;
     this.chatService = newNativeHandle(
-      Native.ChatService_new_unauth(connectionManager)
+      '_nativeHandle' in chatServiceOrConnectionManager
+        ? Native.ChatService_new_unauth(chatServiceOrConnectionManager)
+        : chatServiceOrConnectionManager
     );
     const nativeChatListener = {
       _incoming_message(
@@ -393,6 +810,16 @@ export class UnauthenticatedChatService implements ChatService {
       )
     );
   }
+
+  prepareForBackground(
+    budgetMillis: number
+  ): Promise<Native.BackgroundFlushReport> {
+    return Native.ChatService_prepare_for_background_unauth(
+      this.asyncContext,
+      this.chatService,
+      budgetMillis
+    );
+  }
 }
 
 export function buildHttpRequest(
@@ -411,6 +838,78 @@ export function buildHttpRequest(
   return httpRequest;
 }
 
+/** A canned response for a {@link MockChatServer}. */
+export type MockChatResponse = Readonly<{
+  status: number;
+  message?: string;
+  headers?: ReadonlyArray<[string, string]>;
+  body?: Uint8Array;
+}>;
+
+/**
+ * A stand-in for the chat server, for tests that want to exercise request building and response
+ * parsing without a network.
+ *
+ * Responses are matched to requests by verb and path (including any query string), first in,
+ * first out. A request with no matching response fails with an {@link IoError}. Every request is
+ * recorded, matched or not, and can be inspected with {@link #takeSentRequest}.
+ *
+ * Create chat services that talk to it with {@link Net#TESTING_newMockAuthenticatedChatService}
+ * and {@link Net#TESTING_newMockUnauthenticatedChatService}.
+ */
+export class MockChatServer {
+  readonly _nativeHandle: Native.MockChatServer;
+
+  constructor() {
+    this._nativeHandle = Native.TESTING_MockChatServer_New();
+  }
+
+  /** Queues `response` as the answer to the next request for `verb` and `path`. */
+  addResponse(
+    request: Readonly<{ verb: string; path: string }>,
+    response: MockChatResponse
+  ): void {
+    const nativeResponse = newNativeHandle(
+      Native.TESTING_MockChatResponse_New(
+        response.status,
+        response.message ?? null,
+        response.body !== undefined ? Buffer.from(response.body) : null
+      )
+    );
+    (response.headers ?? []).forEach(([name, value]) => {
+      Native.TESTING_MockChatResponse_AddHeader(nativeResponse, name, value);
+    });
+    Native.TESTING_MockChatServer_AddResponse(
+      this,
+      request.verb,
+      request.path,
+      nativeResponse
+    );
+  }
+
+  /** Returns the oldest request that hasn't been taken yet, or `null` if there are none. */
+  takeSentRequest(): ChatRequest | null {
+    const handle = Native.TESTING_MockChatServer_TakeSentRequest(this);
+    if (handle === null) {
+      return null;
+    }
+    const request = newNativeHandle(handle);
+    const headers = Native.TESTING_ChatRequestGetHeaderNames(request).map(
+      (name): [string, string] => [
+        name,
+        Native.TESTING_ChatRequestGetHeaderValue(request, name),
+      ]
+    );
+    const body = Native.TESTING_ChatRequestGetBody(request);
+    return {
+      verb: Native.TESTING_ChatRequestGetMethod(request),
+      path: Native.TESTING_ChatRequestGetPath(request),
+      headers,
+      body: body.length > 0 ? body : undefined,
+    };
+  }
+}
+
 export type NetConstructorOptions = Readonly<
   | {
       localTestServer?: false;
@@ -500,6 +999,41 @@ export class Net {
     );
   }
 
+  /**
+   * Creates an {@link AuthenticatedChatService} whose requests are answered by `server` instead
+   * of the network.
+   *
+   * For tests only. The same caveats about `listener` apply as for
+   * {@link #newAuthenticatedChatService}.
+   */
+  public TESTING_newMockAuthenticatedChatService(
+    server: MockChatServer,
+    listener: ChatServiceListener
+  ): AuthenticatedChatService {
+    return new AuthenticatedChatService(
+      this.asyncContext,
+      Native.TESTING_MockChatServer_NewAuthChat(server),
+      listener
+    );
+  }
+
+  /**
+   * Creates an {@link UnauthenticatedChatService} whose requests are answered by `server` instead
+   * of the network.
+   *
+   * For tests only.
+   */
+  public TESTING_newMockUnauthenticatedChatService(
+    server: MockChatServer,
+    listener: ConnectionEventsListener
+  ): UnauthenticatedChatService {
+    return new UnauthenticatedChatService(
+      this.asyncContext,
+      Native.TESTING_MockChatServer_NewUnauthChat(server),
+      listener
+    );
+  }
+
   /**
    * Enables/disables IPv6 for all new connections (until changed).
    *
@@ -529,6 +1063,131 @@ export class Net {
     );
   }
 
+  /**
+   * Sets size limits for all new chat connections (until changed).
+   *
+   * Messages larger than `maxMessageSize` (or frames larger than `maxFrameSize`) are rejected.
+   * Once `maxPendingSendBytes` of outgoing requests are waiting to be written, further requests
+   * wait for the connection to catch up instead of being buffered, so bulk operations don't use
+   * unbounded memory when the connection stalls. Existing connections are not affected.
+   */
+  public setChatWebSocketLimits(limits: {
+    maxFrameSize: number;
+    maxMessageSize: number;
+    maxPendingSendBytes: number;
+  }): void {
+    Native.ConnectionManager_set_chat_websocket_limits(
+      this.connectionManager,
+      limits.maxFrameSize,
+      limits.maxMessageSize,
+      limits.maxPendingSendBytes
+    );
+  }
+
+  /**
+   * Sets how new connections to `service` back off after failed attempts (until changed).
+   *
+   * Existing connections are not affected.
+   */
+  public setRetryPolicy(service: NetService, policy: RetryPolicy): void {
+    Native.ConnectionManager_set_retry_policy(
+      this.connectionManager,
+      service,
+      policy.initialDelayMillis,
+      Math.round(policy.multiplier * 100),
+      Math.round(policy.jitter * 100),
+      policy.maxDelayMillis,
+      policy.maxAttempts ?? 0
+    );
+  }
+
+  /**
+   * Requires new direct connections to `service` to present one of `pins`
+   * (until changed).
+   *
+   * Each pin is the SHA-256 hash of a DER-encoded SubjectPublicKeyInfo, and
+   * matches if any certificate in the verified chain has that public key.
+   * Domain-fronted routes end at the fronting provider, so they aren't pinned.
+   * Passing an empty list stops requiring pins. Existing connections are not
+   * affected.
+   *
+   * @throws if any pin is not 32 bytes long
+   */
+  public setCertificatePins(
+    service: NetService,
+    pins: ReadonlyArray<Uint8Array>
+  ): void {
+    if (pins.some((pin) => pin.length !== 32)) {
+      throw new Error('certificate pins must be SHA-256 hashes');
+    }
+    Native.ConnectionManager_set_certificate_pins(
+      this.connectionManager,
+      service,
+      Buffer.concat(pins)
+    );
+  }
+
+  /**
+   * Sets which routes new connections try, and in what order (until changed).
+   *
+   * Routes not in `order` are never tried, so an app can, for example, avoid
+   * domain fronting on a particular network by leaving out the proxy routes. If
+   * none of the listed routes are available for a service, the direct route is
+   * used anyway. The order only matters while censorship circumvention is
+   * enabled, but it is kept when that or any other setting changes. It is also
+   * saved by {@link #exportRouteState}, and {@link #importRouteState} restores
+   * it. Existing connections are not affected.
+   */
+  public setRouteOrder(order: ReadonlyArray<ConnectionRoute>): void {
+    Native.ConnectionManager_set_route_order(
+      this.connectionManager,
+      Buffer.from(order)
+    );
+  }
+
+  /**
+   * Returns the order set by {@link #setRouteOrder}, or the default order if it
+   * hasn't been called.
+   */
+  public getRouteOrder(): ConnectionRoute[] {
+    return Array.from(
+      Native.ConnectionManager_get_route_order(this.connectionManager)
+    );
+  }
+
+  /**
+   * Saves which routes have been working, along with cached DNS results,
+   * encrypted with `key`.
+   *
+   * Pass the result to {@link #importRouteState} on a later launch, with the
+   * same key, so that routes that were working get tried first instead of being
+   * rediscovered. The key must be 32 bytes. It should be kept in secure
+   * storage, since the saved state reveals how the device has been connecting.
+   */
+  public exportRouteState(key: Buffer): Buffer {
+    return Native.ConnectionManager_export_route_state(
+      this.connectionManager,
+      key
+    );
+  }
+
+  /**
+   * Restores state saved by {@link #exportRouteState}.
+   *
+   * Routes that were working through the same proxy (or without one) are tried
+   * first by new connections, and the saved route order replaces the current
+   * one. Existing connections are not affected.
+   *
+   * Throws if the state can't be decrypted with `key` or is malformed.
+   */
+  public importRouteState(key: Buffer, state: Buffer): void {
+    Native.ConnectionManager_import_route_state(
+      this.connectionManager,
+      key,
+      state
+    );
+  }
+
   /**
    * Sets the proxy host to be used for all new connections (until overridden).
    *
@@ -552,6 +1211,17 @@ export class Net {
     Native.ConnectionManager_clear_proxy(this.connectionManager);
   }
 
+  /**
+   * Returns the proxy set by {@link #setProxy} as `host:port`, or `null` if new
+   * connections are made directly.
+   *
+   * If the last call to {@link #setProxy} had an invalid port, this is just the
+   * host.
+   */
+  getProxy(): string | null {
+    return Native.ConnectionManager_get_proxy(this.connectionManager);
+  }
+
   /**
    * Notifies libsignal that the network has changed.
    *
@@ -561,6 +1231,21 @@ export class Net {
     Native.ConnectionManager_on_network_change(this.connectionManager);
   }
 
+  /**
+   * Returns the current time according to the chat server, estimated from its
+   * recent responses.
+   *
+   * Use this instead of the local clock for decisions that have to agree with
+   * the server, such as choosing a redemption time for group credentials or
+   * checking whether a credential has expired. Falls back to the local time
+   * until a chat response has been received.
+   */
+  serverTime(): Date {
+    return new Date(
+      Native.ConnectionManager_server_time(this.connectionManager)
+    );
+  }
+
   async cdsiLookup(
     { username, password }: Readonly<ServiceAuth>,
     {
@@ -568,6 +1253,8 @@ export class Net {
       acisAndAccessKeys,
       returnAcisWithoutUaks,
       abortSignal,
+      connectionPool,
+      prepareNextConnection,
     }: ReadonlyDeep<CDSRequestOptionsType>
   ): Promise<CDSResponseType<string, string>> {
     const request = newNativeHandle(Native.LookupRequest_new());
@@ -590,13 +1277,23 @@ export class Net {
 
     const lookup = await this.asyncContext.makeCancellable(
       abortSignal,
-      Native.CdsiLookup_new(
-        this.asyncContext,
-        this.connectionManager,
-        username,
-        password,
-        request
-      )
+      connectionPool === undefined
+        ? Native.CdsiLookup_new(
+            this.asyncContext,
+            this.connectionManager,
+            username,
+            password,
+            request
+          )
+        : Native.CdsiLookup_newPooled(
+            this.asyncContext,
+            this.connectionManager,
+            username,
+            password,
+            request,
+            connectionPool,
+            prepareNextConnection ?? false
+          )
     );
     return await this.asyncContext.makeCancellable(
       abortSignal,
@@ -666,6 +1363,25 @@ export interface Svr3Client {
     options?: { abortSignal?: AbortSignal }
   ): Promise<Buffer>;
 
+  /**
+   * Backup a secret to only some of the SVR3 backends.
+   *
+   * Works like {@link Svr3Client#backup}, except that the secret is shared
+   * only among the backends listed in `distribution`. The resulting share set
+   * records the layout, so {@link Svr3Client#restore} needs no extra
+   * arguments. The only supported threshold is the number of selected
+   * backends; anything else, as well as unknown or repeated backends, fails
+   * the returned `Promise`.
+   */
+  backupWithDistribution(
+    what: Buffer,
+    password: string,
+    maxTries: number,
+    distribution: Readonly<Svr3ShareDistribution>,
+    auth: Readonly<ServiceAuth>,
+    options?: { abortSignal?: AbortSignal }
+  ): Promise<Buffer>;
+
   /**
    * Restore a secret from SVR3.
    *
@@ -739,6 +1455,29 @@ export interface Svr3Client {
   ): Promise<void>;
 }
 
+/**
+ * Which SVR3 backends hold shares of a secret.
+ *
+ * Backends are identified by their position in the environment, starting from
+ * 1; in the standard environments these are SGX, Nitro, and TPM2-SNP, in that
+ * order. `threshold` is the number of backends needed to restore the secret.
+ */
+export type Svr3ShareDistribution = {
+  serverIds: number[];
+  threshold: number;
+};
+
+/**
+ * Returns the layout of a share set produced by {@link Svr3Client#backup} or
+ * {@link Svr3Client#backupWithDistribution}.
+ */
+export function svr3ShareSetLayout(shareSet: Buffer): Svr3ShareDistribution {
+  return {
+    serverIds: Array.from(Native.Svr3GetShareSetServerIds(shareSet)),
+    threshold: Native.Svr3GetShareSetThreshold(shareSet),
+  };
+}
+
 /**
  * A simple data class containing the secret restored from SVR3 as well as the
  * number of restore attempts remaining.
@@ -780,6 +1519,30 @@ class Svr3ClientImpl implements Svr3Client {
     );
   }
 
+  async backupWithDistribution(
+    what: Buffer,
+    password: string,
+    maxTries: number,
+    distribution: Readonly<Svr3ShareDistribution>,
+    auth: Readonly<ServiceAuth>,
+    options?: { abortSignal?: AbortSignal }
+  ): Promise<Buffer> {
+    return this.asyncContext.makeCancellable(
+      options?.abortSignal,
+      Native.Svr3BackupWithDistribution(
+        this.asyncContext,
+        this.connectionManager,
+        what,
+        password,
+        maxTries,
+        Buffer.from(distribution.serverIds),
+        distribution.threshold,
+        auth.username,
+        auth.password
+      )
+    );
+  }
+
   async restore(
     password: string,
     shareSet: Buffer,
@@ -814,3 +1577,138 @@ class Svr3ClientImpl implements Svr3Client {
     );
   }
 }
+
+/**
+ * Works out how to bring the local database and the storage service back in
+ * sync.
+ *
+ * Manifests are decrypted `ManifestRecord` messages, and record ids are
+ * serialized `ManifestRecord.Identifier` messages. Records are passed in
+ * serialized form, along with a key identifying "the same" record across
+ * devices (such as a contact's ACI); a local record that isn't on the service
+ * yet wins over the service's copy, and otherwise the service's copy wins.
+ *
+ * To sync, add every local record, local deletion, and id already known to be
+ * of an unknown type; fetch and decrypt the records for
+ * {@link StorageSync#idsToFetch}; add those; and then call
+ * {@link StorageSync#plan}.
+ */
+export class StorageSync {
+  readonly _nativeHandle: Native.StorageSync;
+
+  /**
+   * @param previousManifest The manifest as of the last successful sync, or an
+   * empty buffer if there has never been one.
+   * @param remoteManifest The manifest currently on the service.
+   */
+  constructor(previousManifest: Buffer, remoteManifest: Buffer) {
+    this._nativeHandle = Native.StorageSync_new(
+      previousManifest,
+      remoteManifest
+    );
+  }
+
+  /** Adds a record from the local database. */
+  addLocalRecord(id: Buffer, key: Buffer, data: Buffer): void {
+    Native.StorageSync_addLocalRecord(this, id, key, data);
+  }
+
+  /** Adds the id of a record that was deleted locally since the last sync. */
+  addLocalDeletion(id: Buffer): void {
+    Native.StorageSync_addLocalDeletion(this, id);
+  }
+
+  /**
+   * Adds the id of a record already known to be of a type this client can't
+   * parse.
+   */
+  addKnownUnknown(id: Buffer): void {
+    Native.StorageSync_addKnownUnknown(this, id);
+  }
+
+  /**
+   * Returns the ids that need to be fetched from the service, in manifest
+   * order.
+   */
+  idsToFetch(): Buffer[] {
+    return Native.StorageSync_idsToFetch(this);
+  }
+
+  /** Adds a record fetched from the service. */
+  addFetchedRecord(id: Buffer, key: Buffer, data: Buffer): void {
+    Native.StorageSync_addFetchedRecord(this, id, key, data);
+  }
+
+  /** Adds the id of a fetched record of a type this client can't parse. */
+  addFetchedUnknown(id: Buffer): void {
+    Native.StorageSync_addFetchedUnknown(this, id);
+  }
+
+  /** Plans the sync, consuming the records added so far. */
+  plan(): StorageSyncPlan {
+    return new StorageSyncPlan(Native.StorageSync_plan(this));
+  }
+}
+
+/** The changes to make on each side, as planned by {@link StorageSync}. */
+export class StorageSyncPlan {
+  readonly _nativeHandle: Native.StorageSyncPlan;
+
+  constructor(handle: Native.StorageSyncPlan) {
+    this._nativeHandle = handle;
+  }
+
+  /**
+   * The ids of records to insert or replace locally, parallel to
+   * {@link localUpsertData}.
+   */
+  localUpsertIds(): Buffer[] {
+    return Native.StorageSyncPlan_localUpsertIds(this);
+  }
+
+  localUpsertData(): Buffer[] {
+    return Native.StorageSyncPlan_localUpsertData(this);
+  }
+
+  localDeletes(): Buffer[] {
+    return Native.StorageSyncPlan_localDeletes(this);
+  }
+
+  /**
+   * The ids of records of unknown types still in the manifest, to remember for
+   * next time.
+   */
+  unknown(): Buffer[] {
+    return Native.StorageSyncPlan_unknown(this);
+  }
+
+  /**
+   * Whether a new manifest needs to be uploaded.
+   *
+   * If not, the remote-side accessors all return empty lists.
+   */
+  hasRemoteChanges(): boolean {
+    return Native.StorageSyncPlan_hasRemoteChanges(this);
+  }
+
+  /**
+   * The record ids for the manifest to upload, whose version is one more than
+   * the remote manifest's.
+   */
+  remoteManifestIds(): Buffer[] {
+    return Native.StorageSyncPlan_remoteManifestIds(this);
+  }
+
+  /** The ids of records to upload, parallel to {@link remoteInsertData}. */
+  remoteInsertIds(): Buffer[] {
+    return Native.StorageSyncPlan_remoteInsertIds(this);
+  }
+
+  remoteInsertData(): Buffer[] {
+    return Native.StorageSyncPlan_remoteInsertData(this);
+  }
+
+  remoteDeletes(): Buffer[] {
+    return Native.StorageSyncPlan_remoteDeletes(this);
+  }
+}
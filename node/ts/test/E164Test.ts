@@ -0,0 +1,37 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import { assert } from 'chai';
+import * as E164 from '../E164';
+import * as util from './util';
+
+util.initLogger();
+
+describe('E164', () => {
+  it('normalizes international and national numbers', () => {
+    assert.equal(E164.normalize(' +1 (800) 555-1001 ', 1), '+18005551001');
+    assert.equal(E164.normalize('(800) 555-1001', 1), '+18005551001');
+    assert.equal(E164.normalize('030 1234567', 49), '+49301234567');
+    assert.equal(E164.normalize('0049 30 1234567', 49), '+49301234567');
+  });
+
+  it('rejects invalid numbers', () => {
+    assert.isNull(E164.normalize('+1 800 555 1001 ext 2', 1));
+    assert.isNull(E164.normalize('+1234', 1));
+    assert.isNull(E164.normalize('', 1));
+  });
+
+  it('rejects invalid calling codes', () => {
+    assert.throws(() => E164.normalize('800 555 1001', 0));
+    assert.throws(() => E164.normalize('800 555 1001', 1000));
+  });
+
+  it('validates canonical numbers', () => {
+    assert.isTrue(E164.isValid('+18005551001'));
+    assert.isFalse(E164.isValid('18005551001'));
+    assert.isFalse(E164.isValid('+1 800 555 1001'));
+    assert.isFalse(E164.isValid('+1234'));
+  });
+});
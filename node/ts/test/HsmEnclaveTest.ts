@@ -140,4 +140,29 @@ describe('HsmEnclaveClient', () => {
       assert.equal(err.operation, 'HsmEnclaveClient_EstablishedRecv'); // the Rust entry point
     }
   });
+  it('pipelining settings fail prior to establishment', () => {
+    const hashes: Buffer[] = [];
+    hashes.push(
+      Buffer.from(
+        '0000000000000000000000000000000000000000000000000000000000000000',
+        'hex'
+      )
+    );
+    const hsmEnclaveClient = SignalClient.HsmEnclaveClient.new(
+      validKey,
+      hashes
+    );
+    assert.throws(
+      () => hsmEnclaveClient.enablePipelining(4),
+      SignalClient.LibSignalErrorBase
+    );
+    assert.throws(
+      () => hsmEnclaveClient.inFlight(),
+      SignalClient.LibSignalErrorBase
+    );
+    assert.throws(
+      () => hsmEnclaveClient.lastSentRequestId(),
+      SignalClient.LibSignalErrorBase
+    );
+  });
 });
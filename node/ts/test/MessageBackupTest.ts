@@ -10,8 +10,10 @@ import { Aci } from '../Address';
 import { Uint8ArrayInputStream, ErrorInputStream } from './ioutil';
 import * as fs from 'node:fs';
 import * as path from 'node:path';
+import { setTimeout } from 'timers/promises';
 import { hkdf, LogLevel } from '..';
 import { BackupKey } from '../AccountKeys';
+import { ErrorCode, LibSignalErrorBase } from '../Errors';
 
 util.initLogger(LogLevel.Trace);
 
@@ -34,6 +36,7 @@ describe('MessageBackup', () => {
         BigInt(input.length)
       );
       assert.equal(outcome.errorMessage, null);
+      assert.deepEqual(outcome.findings, []);
 
       // If we manually derive the test key's backup key and ID, we should get the same outcome.
       const backupKey = hkdf(
@@ -80,6 +83,15 @@ describe('MessageBackup', () => {
         0n
       );
       assert.equal(outcome.errorMessage, 'not enough bytes for an HMAC');
+      assert.deepEqual(outcome.findings, [
+        {
+          frameIndex: null,
+          fieldPath: '',
+          kind: MessageBackup.ValidationFindingKind.MalformedFrame,
+          severity: MessageBackup.ValidationFindingSeverity.Fatal,
+          message: 'not enough bytes for an HMAC',
+        },
+      ]);
     });
 
     it('throws a raised IO error', async () => {
@@ -95,6 +107,74 @@ describe('MessageBackup', () => {
         assert.instanceOf(e, ErrorInputStream.Error);
       }
     });
+
+    it('gives up once its timeout passes', async () => {
+      const input = fs.readFileSync(
+        path.join(__dirname, '../../ts/test/new_account.binproto.encrypted')
+      );
+      class SlowInputStream extends Uint8ArrayInputStream {
+        async read(amount: number): Promise<Buffer> {
+          await setTimeout(10);
+          return super.read(amount);
+        }
+      }
+
+      try {
+        await MessageBackup.validate(
+          testKey,
+          purpose,
+          () => new SlowInputStream(input),
+          BigInt(input.length),
+          { timeoutMillis: 1 }
+        );
+        assert.fail('did not throw');
+      } catch (e) {
+        assert.instanceOf(e, LibSignalErrorBase);
+        assert.equal(e.code, ErrorCode.DeadlineExceeded);
+      }
+    });
+  });
+
+  describe('padding', () => {
+    it('predicts padded and encrypted sizes', () => {
+      assert.equal(MessageBackup.getPaddedSize(0n), 541n);
+      assert.equal(MessageBackup.getPaddedSize(542n), 568n);
+      // IV + PKCS#7-padded ciphertext + HMAC
+      assert.equal(MessageBackup.getEncryptedSize(542n), 16n + 576n + 32n);
+    });
+
+    it('supports custom policies', () => {
+      const standard = new MessageBackup.BackupPaddingPolicy();
+      assert.equal(
+        standard.getPaddedSize(542n),
+        MessageBackup.getPaddedSize(542n)
+      );
+      assert.equal(
+        standard.getEncryptedSize(542n),
+        MessageBackup.getEncryptedSize(542n)
+      );
+
+      const minimumOnly = new MessageBackup.BackupPaddingPolicy({
+        growthFactor: 1,
+        minSize: 1000n,
+      });
+      assert.equal(minimumOnly.getPaddedSize(0n), 1000n);
+      assert.equal(minimumOnly.getPaddedSize(1001n), 1001n);
+
+      const unpadded = new MessageBackup.BackupPaddingPolicy({
+        growthFactor: 1,
+        minSize: 0n,
+      });
+      assert.equal(unpadded.getPaddedSize(542n), 542n);
+      // IV + PKCS#7-padded ciphertext + HMAC
+      assert.equal(unpadded.getEncryptedSize(542n), 16n + 544n + 32n);
+
+      const doubling = new MessageBackup.BackupPaddingPolicy({
+        growthFactor: 2,
+        minSize: 0n,
+      });
+      assert.equal(doubling.getPaddedSize(542n), 1024n);
+    });
   });
 });
 
@@ -6,6 +6,12 @@
 import { assert, use } from 'chai';
 import * as chaiAsPromised from 'chai-as-promised';
 import * as Native from '../../Native';
+import { BRIDGE_MANIFEST_SHA256 } from '../BridgeManifest';
+import {
+  getBridgeMetrics,
+  resetBridgeMetrics,
+  setBridgeMetricsEnabled,
+} from '../BridgeMetrics';
 
 use(chaiAsPromised);
 
@@ -14,4 +20,31 @@ describe('Native', () => {
     const value = Native.test_only_fn_returns_123();
     assert.equal(value, 123);
   });
+
+  it('was built from the same bridge manifest as Native.d.ts', () => {
+    assert.equal(Native.BridgeManifest_GetSha256(), BRIDGE_MANIFEST_SHA256);
+  });
+
+  it('records call metrics only while enabled', () => {
+    resetBridgeMetrics();
+    Native.test_only_fn_returns_123();
+    assert.deepEqual(getBridgeMetrics(), []);
+
+    setBridgeMetricsEnabled(true);
+    try {
+      Native.test_only_fn_returns_123();
+      Native.test_only_fn_returns_123();
+    } finally {
+      setBridgeMetricsEnabled(false);
+    }
+
+    const stats = getBridgeMetrics().find(
+      (entry) => entry.name === 'test_only_fn_returns_123'
+    );
+    assert.isDefined(stats);
+    assert.equal(stats?.calls, 2);
+    assert.equal(stats?.errors, 0);
+    assert.isAtMost(stats?.maxMicros ?? Infinity, stats?.totalMicros ?? 0);
+    resetBridgeMetrics();
+  });
 });
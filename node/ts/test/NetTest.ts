@@ -13,13 +13,23 @@ import * as Native from '../../Native';
 import { ErrorCode, LibSignalErrorBase } from '../Errors';
 import {
   buildHttpRequest,
+  CDSContactChangeKind,
   ChatServerMessageAck,
   ChatServiceListener,
+  ConnectionRoute,
   Environment,
+  KeyTransparencySearchCache,
+  MockChatServer,
   Net,
+  NetService,
   newNativeHandle,
+  ReceiptKind,
+  reconcileCdsiResponse,
   ServiceAuth,
+  StorageSync,
+  svr3ShareSetLayout,
 } from '../net';
+import { ProfileKey, ServerSecretParams } from '../zkgroup';
 import { randomBytes } from 'crypto';
 import { ChatResponse } from '../../Native';
 import { CompletablePromise } from './util';
@@ -42,6 +52,86 @@ describe('Net class', () => {
     });
     net.onNetworkChange();
   });
+
+  it('uses the local time as server time before any responses', () => {
+    const net = new Net({
+      env: Environment.Staging,
+      userAgent: userAgent,
+    });
+    const before = Date.now();
+    const serverTime = net.serverTime().getTime();
+    assert.isAtLeast(serverTime, before);
+    assert.isAtMost(serverTime, Date.now());
+  });
+
+  it('keeps the route order across other settings', () => {
+    const net = new Net({
+      env: Environment.Staging,
+      userAgent: userAgent,
+    });
+    assert.deepEqual(net.getRouteOrder(), [
+      ConnectionRoute.Direct,
+      ConnectionRoute.ProxyF,
+      ConnectionRoute.ProxyG,
+    ]);
+
+    const order = [ConnectionRoute.ProxyG, ConnectionRoute.Direct];
+    net.setRouteOrder(order);
+    net.setCensorshipCircumventionEnabled(true);
+    assert.deepEqual(net.getRouteOrder(), order);
+
+    const key = Buffer.alloc(32);
+    const restored = new Net({
+      env: Environment.Staging,
+      userAgent: userAgent,
+    });
+    restored.importRouteState(key, net.exportRouteState(key));
+    assert.deepEqual(restored.getRouteOrder(), order);
+  });
+
+  it('reports the proxy', () => {
+    const net = new Net({
+      env: Environment.Staging,
+      userAgent: userAgent,
+    });
+    assert.isNull(net.getProxy());
+    net.setProxy('proxy.example', 443);
+    assert.equal(net.getProxy(), 'proxy.example:443');
+    net.clearProxy();
+    assert.isNull(net.getProxy());
+  });
+
+  it('can set certificate pins', () => {
+    const net = new Net({
+      env: Environment.Staging,
+      userAgent: userAgent,
+    });
+    net.setCertificatePins(NetService.Chat, [
+      Buffer.alloc(32, 1),
+      Buffer.alloc(32, 2),
+    ]);
+    net.setCertificatePins(NetService.Chat, []);
+    expect(() =>
+      net.setCertificatePins(NetService.Cdsi, [Buffer.alloc(31)])
+    ).throws();
+  });
+
+  it('can save and restore route state', () => {
+    const key = Buffer.alloc(32, 0x42);
+    const exported = new Net({
+      env: Environment.Staging,
+      userAgent: userAgent,
+    }).exportRouteState(key);
+
+    const net = new Net({
+      env: Environment.Staging,
+      userAgent: userAgent,
+    });
+    net.importRouteState(key, exported);
+    expect(() => net.importRouteState(Buffer.alloc(32), exported)).throws(
+      LibSignalErrorBase
+    );
+  });
 });
 
 describe('chat service api', () => {
@@ -50,6 +140,8 @@ describe('chat service api', () => {
       ['AppExpired', ErrorCode.AppExpired],
       ['DeviceDeregistered', ErrorCode.DeviceDelinked],
       ['ServiceInactive', ErrorCode.ChatServiceInactive],
+      ['RequestUnauthorized', ErrorCode.RequestUnauthorized],
+      ['NotFound', ErrorCode.NotFound],
 
       ['WebSocket', ErrorCode.IoError],
       ['UnexpectedFrameReceived', ErrorCode.IoError],
@@ -64,6 +156,15 @@ describe('chat service api', () => {
           retryAfterSecs: 42,
         },
       ],
+      [
+        'RetryAfter42SecondsPerAccount',
+        {
+          code: ErrorCode.RateLimitedError,
+          retryAfterSecs: 42,
+          scope: 'per-account',
+          reason: 'fake reason',
+        },
+      ],
 
       // These two are more of internal errors, but they should never happen anyway.
       ['FailedToPassMessageToIncomingChannel', ErrorCode.IoError],
@@ -77,6 +178,41 @@ describe('chat service api', () => {
           expectation instanceof Object ? expectation : { code: expectation }
         );
     });
+
+    expect(() =>
+      Native.TESTING_ChatServiceErrorConvert(
+        'ServiceUnavailableWithDisconnectInfo'
+      )
+    )
+      .throws(LibSignalErrorBase)
+      .to.deep.include({
+        code: ErrorCode.IoError,
+        disconnectInfo: {
+          closeCode: 4000,
+          reason: 'go elsewhere',
+          alerts: ['critical-idle-primary-device'],
+        },
+      });
+
+    expect(() =>
+      Native.TESTING_ChatServiceErrorConvert('ChannelClosedWithDisconnectInfo')
+    )
+      .throws(LibSignalErrorBase)
+      .to.deep.include({
+        code: ErrorCode.IoError,
+        disconnectInfo: {
+          closeCode: 4000,
+          reason: 'go elsewhere',
+          alerts: ['critical-idle-primary-device'],
+        },
+      });
+
+    expect(() => Native.TESTING_ChatServiceErrorConvert('HttpErrorWithAlerts'))
+      .throws(LibSignalErrorBase, '503')
+      .to.deep.include({
+        code: ErrorCode.IoError,
+        disconnectInfo: { alerts: ['critical-idle-primary-device'] },
+      });
   });
 
   it('converts Response object to native', () => {
@@ -142,6 +278,221 @@ describe('chat service api', () => {
     ).equals(forwarded);
   });
 
+  it('can be backed by a mock server', async () => {
+    const net = new Net({
+      env: Environment.Production,
+      userAgent: userAgent,
+    });
+    const server = new MockChatServer();
+    server.addResponse(
+      { verb: 'GET', path: '/v1/test?q=1' },
+      {
+        status: 200,
+        message: 'OK',
+        headers: [['content-type', 'application/json']],
+        body: Buffer.from('{}'),
+      }
+    );
+    const chat = net.TESTING_newMockUnauthenticatedChatService(server, {
+      onConnectionInterrupted: sinon.stub(),
+    });
+
+    const response = await chat.fetch({
+      verb: 'GET',
+      path: '/v1/test?q=1',
+      headers: [['x-test', 'yes']],
+    });
+    expect(response).deep.equals({
+      status: 200,
+      message: 'OK',
+      headers: [['content-type', 'application/json']],
+      body: Buffer.from('{}'),
+    });
+    expect(server.takeSentRequest()).deep.equals({
+      verb: 'GET',
+      path: '/v1/test?q=1',
+      headers: [['x-test', 'yes']],
+      body: undefined,
+    });
+    expect(server.takeSentRequest()).is.null;
+
+    // Each canned response is only used once.
+    await expect(
+      chat.fetch({ verb: 'GET', path: '/v1/test?q=1', headers: [] })
+    )
+      .to.eventually.be.rejectedWith(LibSignalErrorBase)
+      .and.include({ code: ErrorCode.IoError });
+  });
+
+  it('parses mock responses with the real response handling', async () => {
+    const net = new Net({
+      env: Environment.Production,
+      userAgent: userAgent,
+    });
+    const server = new MockChatServer();
+    server.addResponse(
+      { verb: 'GET', path: '/v1/certificate/delivery?includeE164=false' },
+      { status: 429, headers: [['retry-after', '30']] }
+    );
+    const chat = net.TESTING_newMockAuthenticatedChatService(server, {
+      onIncomingMessage: sinon.stub(),
+      onQueueEmpty: sinon.stub(),
+      onConnectionInterrupted: sinon.stub(),
+    });
+
+    await expect(chat.fetchSenderCertificate({ includeE164: false }))
+      .to.eventually.be.rejectedWith(LibSignalErrorBase)
+      .and.include({ code: ErrorCode.RateLimitedError, retryAfterSecs: 30 });
+    expect(server.takeSentRequest()).to.include({
+      verb: 'GET',
+      path: '/v1/certificate/delivery?includeE164=false',
+    });
+  });
+
+  it('searches key transparency when the cache has no result', async () => {
+    const net = new Net({
+      env: Environment.Production,
+      userAgent: userAgent,
+    });
+    const server = new MockChatServer();
+    const chat = net.TESTING_newMockAuthenticatedChatService(server, {
+      onIncomingMessage: sinon.stub(),
+      onQueueEmpty: sinon.stub(),
+      onConnectionInterrupted: sinon.stub(),
+    });
+    const search = {
+      cache: new KeyTransparencySearchCache(),
+      signatureKey: Buffer.from(
+        '12a21ad60d5a3978e19a3b0baa8c35c55a20e10d45f39e5cb34bf6e1b3cce432',
+        'hex'
+      ),
+      vrfKey: Buffer.from(
+        '1e71563470c1b8a6e0aadf280b6aa96f8ad064674e69b80292ee46d1ab655fcf',
+        'hex'
+      ),
+      auditorKey: Buffer.from(
+        '1123b13ee32479ae6af5739e5d687b51559abf7684120511f68cde7a21a0e755',
+        'hex'
+      ),
+      searchKey: Buffer.from('search key'),
+    };
+
+    // Nothing is cached for a failed search, so both attempts go to the server.
+    for (const status of [429, 500]) {
+      server.addResponse(
+        { verb: 'POST', path: '/v1/key-transparency/search' },
+        { status, headers: [['retry-after', '30']] }
+      );
+      await expect(
+        chat.searchKeyTransparency(search)
+      ).to.eventually.be.rejectedWith(LibSignalErrorBase);
+      const sent = server.takeSentRequest();
+      expect(sent).to.include({
+        verb: 'POST',
+        path: '/v1/key-transparency/search',
+      });
+      expect(sent?.headers).to.deep.include([
+        'content-type',
+        'application/x-protobuf',
+      ]);
+    }
+    expect(server.takeSentRequest()).to.be.null;
+  });
+
+  it('requests profile key credentials', async () => {
+    const net = new Net({
+      env: Environment.Production,
+      userAgent: userAgent,
+    });
+    const server = new MockChatServer();
+    const chat = net.TESTING_newMockAuthenticatedChatService(server, {
+      onIncomingMessage: sinon.stub(),
+      onQueueEmpty: sinon.stub(),
+      onConnectionInterrupted: sinon.stub(),
+    });
+
+    const serverPublicParams = ServerSecretParams.generate().getPublicParams();
+    const aci = Aci.fromUuid('9d0652a3-dcc3-4d11-975f-74d61598733f');
+    const profileKey = new ProfileKey(randomBytes(32));
+
+    // The request is randomized, so the mock server can't match it.
+    await expect(
+      chat.fetchProfileKeyCredential({ serverPublicParams, aci, profileKey })
+    )
+      .to.eventually.be.rejectedWith(LibSignalErrorBase)
+      .and.include({ code: ErrorCode.IoError });
+
+    const version = profileKey.getProfileKeyVersion(aci).toString();
+    const request = server.takeSentRequest();
+    expect(request?.verb).to.equal('GET');
+    expect(request?.path).to.match(
+      new RegExp(
+        `^/v1/profile/${aci.getRawUuid()}/${version}/[0-9a-f]+` +
+          '\\?credentialType=expiringProfileKey$'
+      )
+    );
+  });
+
+  it('batches queued receipts', () => {
+    const net = new Net({
+      env: Environment.Production,
+      userAgent: userAgent,
+    });
+    const chat = net.TESTING_newMockAuthenticatedChatService(
+      new MockChatServer(),
+      {
+        onIncomingMessage: sinon.stub(),
+        onQueueEmpty: sinon.stub(),
+        onConnectionInterrupted: sinon.stub(),
+      }
+    );
+    const sender = Aci.fromUuid('9d0652a3-dcc3-4d11-975f-74d61598733f');
+
+    chat.queueReceipt(sender, ReceiptKind.Read, 30);
+    chat.queueReceipt(sender, ReceiptKind.Read, 10);
+    chat.queueReceipt(sender, ReceiptKind.Delivery, 20);
+    chat.queueReceipt(sender, ReceiptKind.Read, 10);
+
+    const delivery = chat.nextReceiptBatch();
+    assert(delivery?.sender.isEqual(sender));
+    expect(delivery?.kind).equals(ReceiptKind.Delivery);
+    expect(delivery?.timestamps).deep.equals([20]);
+
+    const read = chat.nextReceiptBatch();
+    expect(read?.kind).equals(ReceiptKind.Read);
+    expect(read?.timestamps).deep.equals([10, 30]);
+
+    expect(chat.nextReceiptBatch()).is.null;
+  });
+
+  it('refuses new requests after preparing for the background', async () => {
+    const net = new Net({
+      env: Environment.Production,
+      userAgent: userAgent,
+    });
+    const server = new MockChatServer();
+    server.addResponse(
+      { verb: 'GET', path: '/v1/test' },
+      { status: 200, headers: [] }
+    );
+    const chat = net.TESTING_newMockUnauthenticatedChatService(server, {
+      onConnectionInterrupted: sinon.stub(),
+    });
+
+    const report = await chat.prepareForBackground(5000);
+    expect(report).deep.equals({
+      acksFlushed: 0,
+      acksUnflushed: 0,
+      requestsFlushed: 0,
+      requestsUnflushed: 0,
+    });
+
+    await expect(chat.fetch({ verb: 'GET', path: '/v1/test', headers: [] }))
+      .to.eventually.be.rejectedWith(LibSignalErrorBase)
+      .and.include({ code: ErrorCode.IoError });
+    expect(server.takeSentRequest()).is.null;
+  });
+
   it('handles bad input gracefully', () => {
     const goodRequest = {
       verb: verb,
@@ -472,6 +823,61 @@ describe('cdsi lookup', () => {
       expect(result).deep.equals(expected);
     });
 
+    it('reconciles against existing contacts', () => {
+      const e164Unregistered = '+18005551013';
+      const e164Missing = '+18005551014';
+      const oldPni = Pni.fromUuid(
+        '00000000-0000-0001-0000-000000000001'
+      ).getServiceIdString();
+
+      const response = {
+        entries: new Map([
+          [e164Both, { aci: aci, pni: pni }],
+          [e164Pni, { aci: undefined, pni: pni }],
+          [e164Unregistered, { aci: undefined, pni: undefined }],
+        ]),
+        debugPermitsUsed: debugPermitsUsed,
+      };
+      const existing = new Map([
+        [e164Pni, { aci: aci, pni: oldPni }],
+        [e164Unregistered, { aci: aci, pni: oldPni }],
+        [e164Missing, { aci: undefined, pni: oldPni }],
+      ]);
+
+      expect(reconcileCdsiResponse(response, existing)).deep.equals([
+        {
+          kind: CDSContactChangeKind.ChangedPni,
+          e164: e164Pni,
+          aci: undefined,
+          pni: pni,
+        },
+        {
+          kind: CDSContactChangeKind.Removed,
+          e164: e164Unregistered,
+          aci: undefined,
+          pni: undefined,
+        },
+        {
+          kind: CDSContactChangeKind.Removed,
+          e164: e164Missing,
+          aci: undefined,
+          pni: undefined,
+        },
+        {
+          kind: CDSContactChangeKind.NewAci,
+          e164: e164Both,
+          aci: aci,
+          pni: undefined,
+        },
+        {
+          kind: CDSContactChangeKind.ChangedPni,
+          e164: e164Both,
+          aci: undefined,
+          pni: pni,
+        },
+      ]);
+    });
+
     it('converts errors to native', () => {
       const cases: Array<[string, ErrorCode, string]> = [
         [
@@ -533,6 +939,19 @@ describe('cdsi lookup', () => {
           });
       });
     });
+
+    it('includes rate limit details in errors', () => {
+      expect(() =>
+        Native.TESTING_CdsiLookupErrorConvert('RetryAfter42SecondsPerNumber')
+      )
+        .throws(LibSignalErrorBase)
+        .to.include({
+          code: ErrorCode.RateLimitedError,
+          retryAfterSecs: 42,
+          scope: 'per-number',
+          reason: 'fake reason',
+        });
+    });
   });
 });
 
@@ -580,6 +999,32 @@ describe('SVR3', () => {
       return expect(state!.net.svr3.backup(secret, 'password', 1, state!.auth))
         .to.eventually.be.rejected;
     });
+
+    it('Distribution threshold must cover every backend', () => {
+      const secret = randomBytes(32);
+      return expect(
+        state!.net.svr3.backupWithDistribution(
+          secret,
+          'password',
+          1,
+          { serverIds: [1, 2, 3], threshold: 2 },
+          state!.auth
+        )
+      ).to.eventually.be.rejectedWith(LibSignalErrorBase);
+    });
+
+    it('Distribution backends must be known', () => {
+      const secret = randomBytes(32);
+      return expect(
+        state!.net.svr3.backupWithDistribution(
+          secret,
+          'password',
+          1,
+          { serverIds: [1, 42], threshold: 2 },
+          state!.auth
+        )
+      ).to.eventually.be.rejectedWith(LibSignalErrorBase);
+    });
   });
 
   describe('Restore', () => {
@@ -596,6 +1041,12 @@ describe('SVR3', () => {
         state!.net.svr3.restore('password', shareSet, state!.auth)
       ).to.eventually.be.rejectedWith(LibSignalErrorBase);
     });
+
+    it('Share set layout requires a valid share set', () => {
+      expect(() => svr3ShareSetLayout(Buffer.from([42]))).to.throw(
+        LibSignalErrorBase
+      );
+    });
   });
 
   // Integration tests require access to the staging environment and make real
@@ -631,6 +1082,25 @@ describe('SVR3', () => {
       expect(restoredSecret.triesRemaining).to.eql(tries - 1);
     }).timeout(10000);
 
+    it('Backup with distribution records the layout', async () => {
+      const secret = randomBytes(32);
+      const distribution = { serverIds: [3, 1], threshold: 2 };
+      const shareSet = await state!.net.svr3.backupWithDistribution(
+        secret,
+        'password',
+        10,
+        distribution,
+        state!.auth
+      );
+      expect(svr3ShareSetLayout(shareSet)).to.eql(distribution);
+      const restoredSecret = await state!.net.svr3.restore(
+        'password',
+        shareSet,
+        state!.auth
+      );
+      expect(restoredSecret.value).to.eql(secret);
+    }).timeout(10000);
+
     it('Restore should fail after remove', async () => {
       const secret = randomBytes(32);
       const tries = 10;
@@ -702,3 +1172,60 @@ describe('SVR3', () => {
     }).timeout(10000);
   });
 });
+
+describe('storage sync', () => {
+  // ManifestRecord.Identifier { raw: aaaa, type: CONTACT }
+  const idA = Buffer.from('0a02aaaa1001', 'hex');
+  // ManifestRecord.Identifier { raw: bbbb, type: CONTACT }
+  const idB = Buffer.from('0a02bbbb1001', 'hex');
+  // ManifestRecord { version: 1, identifiers: [idA] }
+  const manifestWithA = Buffer.concat([Buffer.from('08011206', 'hex'), idA]);
+
+  it('syncs new records both ways', () => {
+    const sync = new StorageSync(Buffer.of(), manifestWithA);
+    sync.addLocalRecord(idB, Buffer.from('b'), Buffer.from('local record'));
+    expect(sync.idsToFetch()).to.deep.equal([idA]);
+    sync.addFetchedRecord(idA, Buffer.from('a'), Buffer.from('remote record'));
+
+    const plan = sync.plan();
+    expect(plan.localUpsertIds()).to.deep.equal([idA]);
+    expect(plan.localUpsertData()).to.deep.equal([
+      Buffer.from('remote record'),
+    ]);
+    expect(plan.localDeletes()).to.deep.equal([]);
+    expect(plan.unknown()).to.deep.equal([]);
+
+    assert(plan.hasRemoteChanges());
+    expect(plan.remoteManifestIds()).to.deep.equal([idA, idB]);
+    expect(plan.remoteInsertIds()).to.deep.equal([idB]);
+    expect(plan.remoteInsertData()).to.deep.equal([
+      Buffer.from('local record'),
+    ]);
+    expect(plan.remoteDeletes()).to.deep.equal([]);
+  });
+
+  it('deletes local deletions remotely', () => {
+    const sync = new StorageSync(manifestWithA, manifestWithA);
+    sync.addLocalDeletion(idA);
+    expect(sync.idsToFetch()).to.deep.equal([]);
+
+    const plan = sync.plan();
+    expect(plan.localUpsertIds()).to.deep.equal([]);
+    assert(plan.hasRemoteChanges());
+    expect(plan.remoteManifestIds()).to.deep.equal([]);
+    expect(plan.remoteDeletes()).to.deep.equal([idA]);
+  });
+
+  it('keeps records of unknown types', () => {
+    const sync = new StorageSync(Buffer.of(), manifestWithA);
+    sync.addFetchedUnknown(idA);
+
+    const plan = sync.plan();
+    expect(plan.unknown()).to.deep.equal([idA]);
+    assert.isFalse(plan.hasRemoteChanges());
+  });
+
+  it('rejects invalid manifests', () => {
+    expect(() => new StorageSync(Buffer.of(), Buffer.of(0xff))).to.throw();
+  });
+});
@@ -57,9 +57,12 @@ class InMemoryIdentityKeyStore extends SignalClient.IdentityKeyStore {
   private localRegistrationId: number;
   private identityKey: SignalClient.PrivateKey;
 
-  constructor(localRegistrationId?: number) {
+  constructor(
+    localRegistrationId?: number,
+    identityKey?: SignalClient.PrivateKey
+  ) {
     super();
-    this.identityKey = SignalClient.PrivateKey.generate();
+    this.identityKey = identityKey ?? SignalClient.PrivateKey.generate();
     this.localRegistrationId = localRegistrationId ?? 5;
   }
 
@@ -185,6 +188,25 @@ class InMemorySenderKeyStore extends SignalClient.SenderKeyStore {
     const idx = `${distributionId}::${sender.name()}::${sender.deviceId()}`;
     return this.state.get(idx) ?? null;
   }
+  private distributions = new Map<
+    string,
+    SignalClient.SenderKeyDistributionRecord
+  >();
+  async saveSenderKeyDistribution(
+    sender: SignalClient.ProtocolAddress,
+    distributionId: SignalClient.Uuid,
+    record: SignalClient.SenderKeyDistributionRecord
+  ): Promise<void> {
+    const idx = `${distributionId}::${sender.name()}::${sender.deviceId()}`;
+    this.distributions.set(idx, record);
+  }
+  async getSenderKeyDistribution(
+    sender: SignalClient.ProtocolAddress,
+    distributionId: SignalClient.Uuid
+  ): Promise<SignalClient.SenderKeyDistributionRecord | null> {
+    const idx = `${distributionId}::${sender.name()}::${sender.deviceId()}`;
+    return this.distributions.get(idx) ?? null;
+  }
 }
 
 class TestStores {
@@ -195,12 +217,18 @@ class TestStores {
   identity: InMemoryIdentityKeyStore;
   session: InMemorySessionStore;
 
-  constructor() {
+  constructor(
+    identity?: SignalClient.IdentityKeyPair,
+    registrationId?: number
+  ) {
     this.sender = new InMemorySenderKeyStore();
     this.prekey = new InMemoryPreKeyStore();
     this.signed = new InMemorySignedPreKeyStore();
     this.kyber = new InMemoryKyberPreKeyStore();
-    this.identity = new InMemoryIdentityKeyStore();
+    this.identity = new InMemoryIdentityKeyStore(
+      registrationId,
+      identity?.privateKey
+    );
     this.session = new InMemorySessionStore();
   }
 }
@@ -377,6 +405,36 @@ describe('SignalClient', () => {
         );
       }
     });
+    it('converts lists of ServiceIds in one call', () => {
+      const serviceIds = [
+        SignalClient.Aci.fromUuid(testingUuid),
+        SignalClient.Pni.fromUuid(testingUuid),
+      ];
+      const binaries = SignalClient.ServiceId.toServiceIdBinaries(serviceIds);
+      assert.deepEqual(
+        binaries,
+        serviceIds.map((id) => id.getServiceIdBinary())
+      );
+      assert.deepEqual(
+        SignalClient.ServiceId.toServiceIdStrings(serviceIds),
+        serviceIds.map((id) => id.getServiceIdString())
+      );
+
+      const parsed = SignalClient.ServiceId.parseFromServiceIdBinaries(binaries);
+      assert.equal(parsed.length, 2);
+      assert.instanceOf(parsed[0], SignalClient.Aci);
+      assert.instanceOf(parsed[1], SignalClient.Pni);
+      assert.isTrue(parsed[0].isEqual(serviceIds[0]));
+      assert.isTrue(parsed[1].isEqual(serviceIds[1]));
+
+      assert.deepEqual(SignalClient.ServiceId.toServiceIdStrings([]), []);
+      assert.throws(() =>
+        SignalClient.ServiceId.parseFromServiceIdBinaries([
+          binaries[0],
+          Buffer.of(0xff),
+        ])
+      );
+    });
     it('handles PNIs', () => {
       const pni = SignalClient.Pni.fromUuid(testingUuid);
       assert.instanceOf(pni, SignalClient.Pni);
@@ -524,6 +582,38 @@ describe('SignalClient', () => {
       bFprint1.scannableFingerprint().compare(bFprint1.scannableFingerprint())
     );
   });
+  it('IdentityDigest', () => {
+    const aliceKey = SignalClient.PublicKey.deserialize(
+      Buffer.from(
+        '0506863bc66d02b40d27b8d49ca7c09e9239236f9d7d25d6fcca5ce13c7064d868',
+        'hex'
+      )
+    );
+    const aliceIdentifier = Buffer.from('+14152222222', 'utf8');
+    const digest = SignalClient.IdentityDigest.new(
+      5200,
+      2,
+      aliceIdentifier,
+      aliceKey
+    );
+
+    assert.equal(digest.version, 2);
+    assert.equal(
+      digest.digest.toString('hex'),
+      '1e301a0353dce3dbe7684cb8336e85136cdc0ee96219494ada305d62a7bd61df'
+    );
+    // Alice's half of the safety number in the 'Fingerprint' test.
+    assert.equal(digest.displayString(), '300354477692869396892869876765');
+
+    const roundTripped = SignalClient.IdentityDigest.fromBuffer(
+      2,
+      digest.digest
+    );
+    assert.equal(roundTripped.displayString(), digest.displayString());
+    assert.throws(() =>
+      SignalClient.IdentityDigest.fromBuffer(2, digest.digest.subarray(1))
+    );
+  });
   it('SenderCertificate', () => {
     const trustRoot = SignalClient.PrivateKey.generate();
     const serverKey = SignalClient.PrivateKey.generate();
@@ -573,6 +663,10 @@ describe('SignalClient', () => {
     assert(senderCert.validate(trustRoot.getPublicKey(), expiration - 1000));
     assert(!senderCert.validate(trustRoot.getPublicKey(), expiration + 10)); // expired
 
+    assert(!senderCert.needsRefresh(expiration - 1000, 1));
+    assert(senderCert.needsRefresh(expiration - 999, 1));
+    assert(senderCert.needsRefresh(expiration + 1, 0));
+
     const senderCertWithoutE164 = SignalClient.SenderCertificate.new(
       senderUuid,
       null,
@@ -710,6 +804,78 @@ describe('SignalClient', () => {
       );
       await assert.isRejected(messagePromise2, TypeError);
     });
+
+    it('tracks which recipients have the current sender key', async () => {
+      const sender = SignalClient.ProtocolAddress.new('sender', 1);
+      const distributionId = 'd1d1d1d1-7000-11eb-b32a-33b8a8a487a6';
+      const alice = SignalClient.ProtocolAddress.new('alice', 1);
+      const bob = SignalClient.ProtocolAddress.new('bob', 2);
+      const store = new InMemorySenderKeyStore();
+      await SignalClient.SenderKeyDistributionMessage.create(
+        sender,
+        distributionId,
+        store
+      );
+
+      const initial =
+        await SignalClient.senderKeyRecipientsNeedingDistribution(
+          sender,
+          distributionId,
+          [alice, bob],
+          store
+        );
+      assert.deepEqual(
+        initial.map((address) => address.name()),
+        ['alice', 'bob']
+      );
+
+      await SignalClient.markSenderKeyDistributed(
+        sender,
+        distributionId,
+        [alice],
+        store
+      );
+      const afterMark =
+        await SignalClient.senderKeyRecipientsNeedingDistribution(
+          sender,
+          distributionId,
+          [alice, bob],
+          store
+        );
+      assert.deepEqual(afterMark.map((address) => address.name()), ['bob']);
+
+      await SignalClient.invalidateSenderKeyDistribution(
+        sender,
+        distributionId,
+        [alice],
+        store
+      );
+      const afterInvalidate =
+        await SignalClient.senderKeyRecipientsNeedingDistribution(
+          sender,
+          distributionId,
+          [alice, bob],
+          store
+        );
+      assert.deepEqual(
+        afterInvalidate.map((address) => address.name()),
+        ['alice', 'bob']
+      );
+    });
+  });
+
+  it('test stores conform', async () => {
+    await SignalClient.checkStoreConformance((identity, registrationId) => {
+      const stores = new TestStores(identity, registrationId);
+      return {
+        identityStore: stores.identity,
+        preKeyStore: stores.prekey,
+        signedPreKeyStore: stores.signed,
+        kyberPreKeyStore: stores.kyber,
+        sessionStore: stores.session,
+        senderKeyStore: stores.sender,
+      };
+    });
   });
 
   it('PublicKeyBundle', () => {
@@ -855,6 +1021,29 @@ describe('SignalClient', () => {
     assert.deepEqual(pkr2.publicKey(), pubKey);
     assert.deepEqual(pkr2.privateKey(), privKey);
   });
+  it('migrates records to the current version', () => {
+    // Records written before version headers were introduced are bare protobuf
+    // messages.
+    const legacy = Buffer.from('082a', 'hex');
+    const current = Buffer.from('0001082a', 'hex');
+    assert.deepEqual(SignalClient.PreKeyRecord.migrateToCurrent(legacy), current);
+    assert.deepEqual(
+      SignalClient.PreKeyRecord.migrateToCurrent(current),
+      current
+    );
+    assert.deepEqual(SignalClient.PreKeyRecord.deserialize(legacy).id(), 42);
+    assert.deepEqual(SignalClient.PreKeyRecord.deserialize(current).id(), 42);
+    // Writing the header is opt-in; serializing still produces the legacy
+    // format.
+    assert.deepEqual(
+      SignalClient.PreKeyRecord.deserialize(current).serialize(),
+      legacy
+    );
+
+    const tooNew = Buffer.from('0002082a', 'hex');
+    assert.throws(() => SignalClient.PreKeyRecord.migrateToCurrent(tooNew));
+    assert.throws(() => SignalClient.PreKeyRecord.deserialize(tooNew));
+  });
   it('SignedPreKeyRecord', () => {
     const privKey = SignalClient.PrivateKey.generate();
     const pubKey = privKey.getPublicKey();
@@ -931,6 +1120,7 @@ describe('SignalClient', () => {
     );
 
     assert.deepEqual(sm.counter(), counter);
+    assert.deepEqual(sm.previousCounter(), previousCounter);
     assert.deepEqual(sm.messageVersion(), messageVersion);
 
     const sm_bytes = sm.serialize();
@@ -938,6 +1128,30 @@ describe('SignalClient', () => {
     const sm2 = SignalClient.SignalMessage.deserialize(sm_bytes);
 
     assert.deepEqual(sm.body(), sm2.body());
+    assert.equal(
+      sm2.ratchetContinuityAfter(sm),
+      SignalClient.RatchetContinuity.Reordered
+    );
+
+    const newChainMessage = (previous: number) =>
+      SignalClient.SignalMessage._new(
+        messageVersion,
+        macKey,
+        SignalClient.PrivateKey.generate().getPublicKey(),
+        0,
+        previous,
+        ciphertext,
+        senderIdentityKey,
+        receiverIdentityKey
+      );
+    assert.equal(
+      newChainMessage(counter).ratchetContinuityAfter(sm),
+      SignalClient.RatchetContinuity.NextChain
+    );
+    assert.equal(
+      newChainMessage(0).ratchetContinuityAfter(sm),
+      SignalClient.RatchetContinuity.Discontinuous
+    );
 
     const registrationId = 9;
     const preKeyId = 23;
@@ -1049,6 +1263,13 @@ describe('SignalClient', () => {
         assert.deepEqual(session.localRegistrationId(), 5);
         assert.deepEqual(session.remoteRegistrationId(), 5);
         assert(session.hasCurrentState());
+
+        const stats = session.stats();
+        assert.equal(stats.messagesEncrypted, 1);
+        assert.equal(stats.messagesDecrypted, 1);
+        assert.isNotNull(stats.lastRatchetStep);
+        assert.equal(stats.isPostQuantum, testCase.expectedVersion == 4);
+
         assert(
           !session.currentRatchetKeyMatches(
             SignalClient.PrivateKey.generate().getPublicKey()
@@ -1057,6 +1278,7 @@ describe('SignalClient', () => {
 
         session.archiveCurrentState();
         assert(!session.hasCurrentState());
+        assert.throws(() => session.stats());
         assert(
           !session.currentRatchetKeyMatches(
             SignalClient.PrivateKey.generate().getPublicKey()
@@ -1181,6 +1403,114 @@ describe('SignalClient', () => {
         }
       });
 
+      it('consults the replay cache', async () => {
+        class TestReplayCache extends SignalClient.ReplayCache {
+          seen = new Set<string>();
+          rejectEverything = false;
+
+          static key(
+            address: SignalClient.ProtocolAddress,
+            senderRatchetKey: SignalClient.PublicKey,
+            counter: number
+          ): string {
+            return [
+              address.name(),
+              address.deviceId(),
+              senderRatchetKey.serialize().toString('hex'),
+              counter,
+            ].join('/');
+          }
+
+          contains(
+            address: SignalClient.ProtocolAddress,
+            senderRatchetKey: SignalClient.PublicKey,
+            counter: number
+          ): Promise<boolean> {
+            if (this.rejectEverything) {
+              return Promise.resolve(true);
+            }
+            return Promise.resolve(
+              this.seen.has(
+                TestReplayCache.key(address, senderRatchetKey, counter)
+              )
+            );
+          }
+
+          insert(
+            address: SignalClient.ProtocolAddress,
+            senderRatchetKey: SignalClient.PublicKey,
+            counter: number
+          ): Promise<void> {
+            this.seen.add(
+              TestReplayCache.key(address, senderRatchetKey, counter)
+            );
+            return Promise.resolve();
+          }
+        }
+
+        const aliceStores = new TestStores();
+        const bobStores = new TestStores();
+
+        const aAddress = SignalClient.ProtocolAddress.new('+14151111111', 1);
+        const bAddress = SignalClient.ProtocolAddress.new('+19192222222', 1);
+
+        const bPreKeyBundle = await testCase.makeBundle(bAddress, bobStores);
+
+        await SignalClient.processPreKeyBundle(
+          bPreKeyBundle,
+          bAddress,
+          aliceStores.session,
+          aliceStores.identity
+        );
+        const aMessage = Buffer.from('Greetings hoo-man', 'utf8');
+        const aCiphertext = SignalClient.PreKeySignalMessage.deserialize(
+          (
+            await SignalClient.signalEncrypt(
+              aMessage,
+              bAddress,
+              aliceStores.session,
+              aliceStores.identity
+            )
+          ).serialize()
+        );
+
+        const replayCache = new TestReplayCache();
+        replayCache.rejectEverything = true;
+        try {
+          await SignalClient.signalDecryptPreKey(
+            aCiphertext,
+            aAddress,
+            bobStores.session,
+            bobStores.identity,
+            bobStores.prekey,
+            bobStores.signed,
+            bobStores.kyber,
+            replayCache
+          );
+          assert.fail();
+        } catch (e) {
+          assert.instanceOf(e, SignalClient.LibSignalErrorBase);
+          const err = e as SignalClient.LibSignalError;
+          assert.equal(err.code, SignalClient.ErrorCode.DuplicatedMessage);
+        }
+        // Nothing was saved for the rejected message.
+        assert.isNull(await bobStores.session.getSession(aAddress));
+
+        replayCache.rejectEverything = false;
+        const bDPlaintext = await SignalClient.signalDecryptPreKey(
+          aCiphertext,
+          aAddress,
+          bobStores.session,
+          bobStores.identity,
+          bobStores.prekey,
+          bobStores.signed,
+          bobStores.kyber,
+          replayCache
+        );
+        assert.deepEqual(bDPlaintext, aMessage);
+        assert.equal(replayCache.seen.size, 1);
+      });
+
       it('expires unacknowledged sessions', async () => {
         const aliceStores = new TestStores();
         const bobStores = new TestStores();
@@ -1353,6 +1683,66 @@ describe('SignalClient', () => {
       assert.deepEqual(bPlaintext.senderAci()?.getServiceIdString(), aUuid);
       assert.deepEqual(bPlaintext.deviceId(), aDeviceId);
 
+      class CounterReplayCache extends SignalClient.ReplayCache {
+        seen = new Set<number>();
+        contains(
+          _address: SignalClient.ProtocolAddress,
+          _senderRatchetKey: SignalClient.PublicKey,
+          counter: number
+        ): Promise<boolean> {
+          return Promise.resolve(this.seen.has(counter));
+        }
+        insert(
+          _address: SignalClient.ProtocolAddress,
+          _senderRatchetKey: SignalClient.PublicKey,
+          counter: number
+        ): Promise<void> {
+          this.seen.add(counter);
+          return Promise.resolve();
+        }
+      }
+      const replayCache = new CounterReplayCache();
+      const aSecondCiphertext = await SignalClient.sealedSenderEncryptMessage(
+        aPlaintext,
+        bAddress,
+        senderCert,
+        aSess,
+        aKeys
+      );
+      const bSecondPlaintext = await SignalClient.sealedSenderDecryptMessage(
+        aSecondCiphertext,
+        trustRoot.getPublicKey(),
+        43, // timestamp,
+        bE164,
+        bUuid,
+        bDeviceId,
+        bSess,
+        bKeys,
+        bPreK,
+        bSPreK,
+        kyberStore,
+        replayCache
+      );
+      assert.deepEqual(bSecondPlaintext.message(), aPlaintext);
+      assert.equal(replayCache.seen.size, 1);
+      await assert.isRejected(
+        SignalClient.sealedSenderDecryptMessage(
+          aSecondCiphertext,
+          trustRoot.getPublicKey(),
+          43, // timestamp,
+          bE164,
+          bUuid,
+          bDeviceId,
+          bSess,
+          bKeys,
+          bPreK,
+          bSPreK,
+          kyberStore,
+          replayCache
+        ),
+        SignalClient.LibSignalErrorBase
+      );
+
       const innerMessage = await SignalClient.signalEncrypt(
         aPlaintext,
         bAddress,
@@ -1635,10 +2025,25 @@ describe('SignalClient', () => {
           aSess
         );
 
+      const usmcLength = aUsmc.serialize().length;
+      assert.equal(
+        aSealedSenderMessage.length,
+        SignalClient.sealedSenderMultiRecipientMessageLength(
+          usmcLength,
+          1,
+          1,
+          0
+        )
+      );
+
       const bSealedSenderMessage =
         SignalClient.sealedSenderMultiRecipientMessageForSingleRecipient(
           aSealedSenderMessage
         );
+      assert.equal(
+        bSealedSenderMessage.length,
+        SignalClient.sealedSenderMultiRecipientReceivedMessageLength(usmcLength)
+      );
 
       const bUsmc = await SignalClient.sealedSenderDecryptToUsmc(
         bSealedSenderMessage,
@@ -1651,6 +2056,18 @@ describe('SignalClient', () => {
       assert.deepEqual(bUsmc.contentHint(), SignalClient.ContentHint.Implicit);
       assert.deepEqual(bUsmc.groupId(), Buffer.from([42]));
 
+      const rebuiltUsmc = bUsmc.withContentHintAndGroupId(
+        SignalClient.ContentHint.Resendable,
+        null
+      );
+      assert.deepEqual(
+        rebuiltUsmc.contentHint(),
+        SignalClient.ContentHint.Resendable
+      );
+      assert.isNull(rebuiltUsmc.groupId());
+      assert.deepEqual(rebuiltUsmc.contents(), bUsmc.contents());
+      assert.deepEqual(rebuiltUsmc.msgType(), bUsmc.msgType());
+
       const bPtext = await SignalClient.groupDecrypt(
         aAddress,
         bSenderKeyStore,
@@ -220,4 +220,37 @@ describe('usernames', () => {
         .with.property('code', ErrorCode.InvalidUsernameLinkEncryptedData);
     });
   });
+
+  describe('link URL', () => {
+    const payload =
+      'AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8AESIzRFVmd4iZqrvM3e7_';
+    it('can be parsed', () => {
+      for (const url of [
+        `https://signal.me/#eu/${payload}`,
+        `sgnl://signal.me/#eu/${payload}`,
+      ]) {
+        const linkUrl = usernames.usernameLinkFromUrl(url);
+        assert.deepEqual(
+          linkUrl.entropy,
+          Buffer.from(Array.from({ length: 32 }, (_, i) => i))
+        );
+        assert.equal(linkUrl.serverId, '00112233-4455-6677-8899-aabbccddeeff');
+      }
+    });
+    it('will error on a URL that is not a username link', () => {
+      expect(() =>
+        usernames.usernameLinkFromUrl('https://signal.org/#eu/AAECAwQF')
+      )
+        .throws(LibSignalErrorBase)
+        .with.property('code', ErrorCode.InvalidUsernameLinkEncryptedData);
+    });
+    it('will error on a payload of the wrong length', () => {
+      const entropyOnly = payload.slice(0, 43);
+      expect(() =>
+        usernames.usernameLinkFromUrl(`https://signal.me/#eu/${entropyOnly}`)
+      )
+        .throws(LibSignalErrorBase)
+        .with.property('code', ErrorCode.InvalidEntropyDataLength);
+    });
+  });
 });
@@ -647,6 +647,16 @@ describe('ZKGroup', () => {
     );
   });
 
+  it('testCallLinkRoomIdKnownAnswer', () => {
+    const rootKey = hexToBuffer('000102030405060708090a0b0c0d0e0f');
+    assert.deepEqual(
+      CallLinkSecretParams.deriveRoomId(rootKey),
+      hexToBuffer(
+        'cd415ffc58771108cdbf029b8d86ee857be167bf28dda87e3e592e3f10eb1c9b'
+      )
+    );
+  });
+
   it('testCallLinkAuthCredential', () => {
     const serverSecretParams =
       GenericServerSecretParams.generateWithRandom(TEST_ARRAY_32);
@@ -6,11 +6,21 @@
 /* eslint @typescript-eslint/no-shadow: ["error", { "allow": ["hash"] }] */
 
 import { randomBytes } from 'crypto';
+import * as uuid from 'uuid';
 import { RANDOM_LENGTH } from './zkgroup/internal/Constants';
 import * as Native from '../Native';
 
 export type UsernameLink = { entropy: Buffer; encryptedUsername: Buffer };
 
+/**
+ * The contents of a username link URL, as shared via QR code or copied link.
+ *
+ * The server ID identifies the encrypted username stored on the server; once
+ * fetched, it can be decrypted with {@link decryptUsernameLink} using the
+ * entropy.
+ */
+export type UsernameLinkUrl = { entropy: Buffer; serverId: string };
+
 export function generateCandidates(
   nickname: string,
   minNicknameLength: number,
@@ -76,6 +86,23 @@ export function createUsernameLink(
   return { entropy, encryptedUsername };
 }
 
+/**
+ * Parses a `https://signal.me/#eu/...` or `sgnl://signal.me/#eu/...` URL
+ * without contacting the server.
+ *
+ * Throws a {@link LibSignalError} with code
+ * {@link ErrorCode.InvalidUsernameLinkEncryptedData} if `url` isn't a username
+ * link, or {@link ErrorCode.InvalidEntropyDataLength} if the link's payload is
+ * the wrong length.
+ */
+export function usernameLinkFromUrl(url: string): UsernameLinkUrl {
+  const bytes = Native.UsernameLink_ParseUrl(url);
+  return {
+    entropy: bytes.subarray(0, 32),
+    serverId: uuid.stringify(bytes, 32),
+  };
+}
+
 // Only for testing. Will throw on failure.
 export function verifyProof(proof: Buffer, hash: Buffer): void {
   Native.Username_Verify(proof, hash);
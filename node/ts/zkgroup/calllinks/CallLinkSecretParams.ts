@@ -19,6 +19,16 @@ export default class CallLinkSecretParams extends ByteArray {
     );
   }
 
+  /**
+   * Derives the room ID the calling server uses for the call link with
+   * `callLinkRootKey`.
+   *
+   * This matches the derivation RingRTC uses for `CallLinkRootKey`.
+   */
+  static deriveRoomId(callLinkRootKey: Buffer): Buffer {
+    return Native.CallLinkSecretParams_DeriveRoomId(callLinkRootKey);
+  }
+
   constructor(contents: Buffer) {
     super(contents, Native.CallLinkSecretParams_CheckValidContents);
   }
@@ -97,14 +97,20 @@ export default class GroupSendEndorsementsResponse extends ByteArray {
    *
    * `localUser` should be included in `groupMembers`.
    *
+   * If `options.timeoutMillis` is provided, the operation gives up with a {@link
+   * DeadlineExceededError} if it is still running at the next opportunity after that much time has
+   * passed.
+   *
    * @throws {VerificationFailedError} if the endorsements are not valid for any reason
+   * @throws {DeadlineExceededError} if the timeout passes before the operation completes
    */
   receiveWithServiceIds(
     groupMembers: ServiceId[],
     localUser: Aci,
     groupParams: GroupSecretParams,
     serverParams: ServerPublicParams,
-    now: Date = new Date()
+    now: Date = new Date(),
+    options?: { timeoutMillis?: number }
   ): ReceivedEndorsements {
     const endorsementContents =
       Native.GroupSendEndorsementsResponse_ReceiveAndCombineWithServiceIds(
@@ -113,7 +119,8 @@ export default class GroupSendEndorsementsResponse extends ByteArray {
         localUser.getServiceIdFixedWidthBinary(),
         Math.floor(now.getTime() / 1000),
         groupParams.contents,
-        serverParams
+        serverParams,
+        Math.ceil(options?.timeoutMillis ?? 0)
       );
     const endorsements = endorsementContents.map((next) => {
       // Normally we don't notice the cost of validating just-created zkgroup objects,
@@ -139,13 +146,17 @@ export default class GroupSendEndorsementsResponse extends ByteArray {
    *
    * `localUser` should be included in `groupMembers`.
    *
+   * `options.timeoutMillis` behaves as in {@link #receiveWithServiceIds}.
+   *
    * @throws {VerificationFailedError} if the endorsements are not valid for any reason
+   * @throws {DeadlineExceededError} if the timeout passes before the operation completes
    */
   receiveWithCiphertexts(
     groupMembers: UuidCiphertext[],
     localUser: UuidCiphertext,
     serverParams: ServerPublicParams,
-    now: Date = new Date()
+    now: Date = new Date(),
+    options?: { timeoutMillis?: number }
   ): ReceivedEndorsements {
     const endorsementContents =
       Native.GroupSendEndorsementsResponse_ReceiveAndCombineWithCiphertexts(
@@ -153,7 +164,8 @@ export default class GroupSendEndorsementsResponse extends ByteArray {
         UuidCiphertext.serializeAndConcatenate(groupMembers),
         localUser.contents,
         Math.floor(now.getTime() / 1000),
-        serverParams
+        serverParams,
+        Math.ceil(options?.timeoutMillis ?? 0)
       );
     const endorsements = endorsementContents.map((next) => {
       // Normally we don't notice the cost of validating just-created zkgroup objects,
@@ -93,7 +93,7 @@ pub fn verify_remote_attestation(
     let attestation = attest(evidence_bytes, endorsement_bytes, current_time)?;
 
     // 4. Verify the status of the Intel® SGX TCB described in the chain.
-    if let TcbStanding::SWHardeningNeeded { advisory_ids } = attestation.tcb_standing {
+    if let TcbStanding::SWHardeningNeeded { advisory_ids } = &attestation.tcb_standing {
         if advisory_ids
             .iter()
             .any(|id| !acceptable_sw_advisories.contains(&id.as_str()))
@@ -119,6 +119,26 @@ pub fn verify_remote_attestation(
     Ok(attestation.claims)
 }
 
+/// Verifies a DCAP quote against its collateral, without checking which enclave produced it.
+///
+/// `evidence_bytes` is the quote along with the custom claims it attests to, and
+/// `endorsement_bytes` is the collateral fetched from Intel's provisioning certification service
+/// (TCB info, QE identity, CRLs, and their issuer chains), both in the same serialized form used
+/// during the enclave handshake.
+///
+/// This performs every check [`verify_remote_attestation`] does except for steps 4 and 5: the
+/// caller gets back the enclave's identity and TCB standing and must decide for themselves
+/// whether those are acceptable. It's meant for tooling and tests that need to validate quotes
+/// captured outside of a live connection; clients talking to an enclave should keep using the
+/// handshake, which checks everything.
+pub fn verify_quote(
+    evidence_bytes: &[u8],
+    endorsement_bytes: &[u8],
+    current_time: SystemTime,
+) -> std::result::Result<VerifiedQuote, AttestationError> {
+    Ok(attest(evidence_bytes, endorsement_bytes, current_time)?)
+}
+
 /// Parses evidence/endorsements and builds a map of metrics
 pub fn attestation_metrics(
     evidence_bytes: &[u8],
@@ -200,10 +220,17 @@ pub fn attestation_metrics(
 /// - has a recent enough attestation (via `last_attest_time`)
 /// - has an up to date tcb OR has acceptable SW advisories
 #[derive(Debug)]
-pub(crate) struct Attestation {
-    tcb_standing: TcbStanding,
-    mrenclave: MREnclave,
-    claims: HashMap<String, Vec<u8>>,
+pub struct VerifiedQuote {
+    /// The standing of the platform's TCB, according to the collateral.
+    pub tcb_standing: TcbStanding,
+    /// The measurement of the enclave that produced the quote.
+    pub mrenclave: MREnclave,
+    /// The measurement of the key that signed the enclave.
+    pub mrsigner: [u8; 32],
+    pub isv_prod_id: u16,
+    pub isv_svn: u16,
+    /// The custom claims covered by the quote's report data.
+    pub claims: HashMap<String, Vec<u8>>,
 }
 
 /// Validate that the returned report/claims are generated
@@ -216,7 +243,7 @@ fn attest(
     evidence_bytes: &[u8],
     endorsement_bytes: &[u8],
     current_time: SystemTime,
-) -> Result<Attestation> {
+) -> Result<VerifiedQuote> {
     let evidence = evidence::Evidence::try_from(evidence_bytes).context("evidence")?;
     let endorsements =
         endorsements::SgxEndorsements::try_from(endorsement_bytes).context("endorsements")?;
@@ -228,7 +255,7 @@ fn attest_impl(
     endorsements: SgxEndorsements,
     trusted_root_pkey: &PKeyRef<Public>,
     current_time: SystemTime,
-) -> Result<Attestation> {
+) -> Result<VerifiedQuote> {
     // 1. Verify the integrity of the signature chain from the Quote to the Intel-issued PCK certificate.
     // 2. Verify no keys in the chain have been revoked.
     // verify the time parameter falls within “not before” and “not after” metadata
@@ -256,9 +283,12 @@ fn attest_impl(
         return Err(Error::new("Application enclave in debug mode"));
     }
 
-    Ok(Attestation {
+    Ok(VerifiedQuote {
         tcb_standing,
-        mrenclave: evidence.quote.quote_body.report_body.mrenclave,
+        mrenclave: report.mrenclave,
+        mrsigner: report.mrsigner,
+        isv_prod_id: report.isvprodid.get(),
+        isv_svn: report.isvsvn.get(),
         claims: evidence.claims.map,
     })
 }
@@ -587,8 +617,11 @@ fn verify_claims_hash(evidence: &Evidence) -> Result<()> {
     Ok(())
 }
 
-#[derive(Debug)]
-enum TcbStanding {
+/// Whether a platform's TCB can be trusted, as determined by Intel's TCB info.
+///
+/// Levels that can't be trusted at all (e.g. revoked or out of date) fail verification instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TcbStanding {
     /// The platform is trusted
     UpToDate,
 
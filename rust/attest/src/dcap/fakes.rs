@@ -29,7 +29,7 @@ use crate::dcap::ecdsa::EcdsaSigned;
 use crate::dcap::endorsements::SgxEndorsements;
 use crate::dcap::evidence::Evidence;
 use crate::dcap::revocation_list::RevocationList;
-use crate::dcap::{attest_impl, Attestation};
+use crate::dcap::{attest_impl, VerifiedQuote};
 
 const EVIDENCE_BYTES: &[u8] = include_bytes!("../../tests/data/dcap.evidence");
 const ENDORSEMENT_BYTES: &[u8] = include_bytes!("../../tests/data/dcap.endorsements");
@@ -143,7 +143,7 @@ impl FakeAttestation {
         }
     }
 
-    pub fn attest(self) -> Result<Attestation, super::Error> {
+    pub fn attest(self) -> Result<VerifiedQuote, super::Error> {
         attest_impl(
             self.evidence,
             self.endorsements,
@@ -7,7 +7,9 @@
 
 #![warn(missing_docs)]
 
+use std::collections::VecDeque;
 use std::fmt;
+use std::num::NonZeroUsize;
 
 use log::*;
 
@@ -28,6 +30,10 @@ pub enum Error {
     InvalidCodeHashError,
     /// Invalid state of wrapper (used in bridging)
     InvalidBridgeStateError,
+    /// Too many requests are awaiting responses
+    PipelineFull,
+    /// Received a response with no request awaiting it
+    UnexpectedResponse,
 }
 
 /// Result type for HSM enclave.
@@ -54,6 +60,12 @@ impl fmt::Display for Error {
             Error::InvalidBridgeStateError => {
                 write!(f, "Invalid bridge state")
             }
+            Error::PipelineFull => {
+                write!(f, "Too many requests are awaiting responses")
+            }
+            Error::UnexpectedResponse => {
+                write!(f, "Received a response with no request awaiting it")
+            }
         }
     }
 }
@@ -147,3 +159,194 @@ impl ClientConnectionEstablishment {
         })
     }
 }
+
+/// The default limit on requests awaiting responses in a [`PipelinedConnection`].
+pub const DEFAULT_MAX_IN_FLIGHT: NonZeroUsize = match NonZeroUsize::new(32) {
+    Some(n) => n,
+    None => unreachable!(),
+};
+
+/// Identifies a request sent through a [`PipelinedConnection`], so its response can be matched up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RequestId(u64);
+
+impl From<RequestId> for u64 {
+    fn from(value: RequestId) -> Self {
+        value.0
+    }
+}
+
+/// Wraps an established connection so that several requests can be sent before their responses
+/// arrive.
+///
+/// The HSM processes requests in order, and the Noise transport keeps separate counters for each
+/// direction, so nothing stops a client from sending its next request as soon as the previous one
+/// is on the wire. This type keeps track of which responses are still owed, and refuses to let more
+/// than `max_in_flight` requests go unanswered.
+///
+/// ```pseudocode
+///   let mut conn = PipelinedConnection::new(conn, DEFAULT_MAX_IN_FLIGHT);
+///   for command in commands {
+///       let (id, encrypted) = conn.send(command)?;
+///       websocket.send(&encrypted)?;
+///   }
+///   while conn.in_flight() > 0 {
+///       let (id, plaintext) = conn.recv(&websocket.recv(...)?)?;
+///   }
+/// ```
+#[derive(Debug)]
+pub struct PipelinedConnection {
+    connection: client_connection::ClientConnection,
+    max_in_flight: NonZeroUsize,
+    next_request: u64,
+    in_flight: VecDeque<RequestId>,
+}
+
+impl PipelinedConnection {
+    /// Wraps `connection`, which must not have any requests awaiting responses.
+    pub fn new(
+        connection: client_connection::ClientConnection,
+        max_in_flight: NonZeroUsize,
+    ) -> Self {
+        Self {
+            connection,
+            max_in_flight,
+            next_request: 0,
+            in_flight: VecDeque::new(),
+        }
+    }
+
+    /// Changes the limit on requests awaiting responses.
+    ///
+    /// Lowering the limit below [`Self::in_flight`] doesn't affect requests already sent; it only
+    /// delays the next send until enough responses have arrived.
+    pub fn set_max_in_flight(&mut self, max_in_flight: NonZeroUsize) {
+        self.max_in_flight = max_in_flight;
+    }
+
+    /// The number of requests sent whose responses haven't been received yet.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Encrypts a request to be sent, returning its id along with the ciphertext.
+    ///
+    /// Fails with [`Error::PipelineFull`] if the limit on requests awaiting responses has been
+    /// reached; in that case the connection is unchanged.
+    pub fn send(&mut self, plaintext_to_send: &[u8]) -> Result<(RequestId, Vec<u8>)> {
+        if self.in_flight.len() >= self.max_in_flight.get() {
+            return Err(Error::PipelineFull);
+        }
+        let ciphertext = self.connection.send(plaintext_to_send)?;
+        let id = RequestId(self.next_request);
+        self.next_request += 1;
+        self.in_flight.push_back(id);
+        Ok((id, ciphertext))
+    }
+
+    /// Decrypts a received response, returning the id of the request it answers.
+    ///
+    /// Responses arrive in the order their requests were sent.
+    pub fn recv(&mut self, received_ciphertext: &[u8]) -> Result<(RequestId, Vec<u8>)> {
+        let Some(&id) = self.in_flight.front() else {
+            return Err(Error::UnexpectedResponse);
+        };
+        let plaintext = self.connection.recv(received_ciphertext)?;
+        self.in_flight.pop_front();
+        Ok((id, plaintext))
+    }
+
+    /// Returns the underlying connection, discarding any record of requests in flight.
+    pub fn into_inner(self) -> client_connection::ClientConnection {
+        self.connection
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    const CODE_HASH: [u8; CODE_HASH_SIZE] = [7; CODE_HASH_SIZE];
+
+    fn connect() -> (client_connection::ClientConnection, snow::TransportState) {
+        let builder = || {
+            snow::Builder::with_resolver(
+                client_connection::NOISE_PATTERN.parse().expect("valid"),
+                Box::new(snow_resolver::Resolver),
+            )
+        };
+        let server_keys = builder().generate_keypair().expect("can generate");
+        let mut server = builder()
+            .local_private_key(&server_keys.private)
+            .build_responder()
+            .expect("valid");
+
+        let public_key = server_keys.public.try_into().expect("correct length");
+        let client =
+            ClientConnectionEstablishment::new(public_key, vec![CODE_HASH]).expect("valid");
+
+        let mut payload = [0; CODE_HASH_SIZE];
+        server
+            .read_message(client.initial_request(), &mut payload)
+            .expect("valid handshake");
+        let mut response = vec![0; 1024];
+        let len = server
+            .write_message(&CODE_HASH, &mut response)
+            .expect("can respond");
+        response.truncate(len);
+
+        let client = client.complete(&response).expect("trusted");
+        (
+            client,
+            server.into_transport_mode().expect("handshake done"),
+        )
+    }
+
+    fn server_echo(server: &mut snow::TransportState, ciphertext: &[u8]) -> Vec<u8> {
+        let mut plaintext = vec![0; ciphertext.len()];
+        let len = server
+            .read_message(ciphertext, &mut plaintext)
+            .expect("valid");
+        let mut response = vec![0; len + 16];
+        let len = server
+            .write_message(&plaintext[..len], &mut response)
+            .expect("can respond");
+        response.truncate(len);
+        response
+    }
+
+    #[test]
+    fn pipelined_requests_are_correlated_in_order() {
+        let (client, mut server) = connect();
+        let mut client = PipelinedConnection::new(client, NonZeroUsize::new(2).expect("nonzero"));
+
+        let (first, first_request) = client.send(b"first").expect("can send");
+        let (second, second_request) = client.send(b"second").expect("can send");
+        assert_ne!(first, second);
+        assert_eq!(client.in_flight(), 2);
+        assert_matches!(client.send(b"third"), Err(Error::PipelineFull));
+
+        let first_response = server_echo(&mut server, &first_request);
+        let second_response = server_echo(&mut server, &second_request);
+
+        assert_eq!(
+            client.recv(&first_response).expect("valid"),
+            (first, b"first".to_vec())
+        );
+        let (third, third_request) = client.send(b"third").expect("room again");
+        assert_eq!(
+            client.recv(&second_response).expect("valid"),
+            (second, b"second".to_vec())
+        );
+
+        let third_response = server_echo(&mut server, &third_request);
+        assert_eq!(
+            client.recv(&third_response).expect("valid"),
+            (third, b"third".to_vec())
+        );
+        assert_eq!(client.in_flight(), 0);
+        assert_matches!(client.recv(&third_response), Err(Error::UnexpectedResponse));
+    }
+}
@@ -0,0 +1,46 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::time::{Duration, SystemTime};
+
+use attest::dcap::{self, TcbStanding};
+use hex_literal::hex;
+
+const EVIDENCE: &[u8] = include_bytes!("data/dcap.evidence");
+const ENDORSEMENTS: &[u8] = include_bytes!("data/dcap.endorsements");
+
+fn valid_time() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_millis(1674105089000)
+}
+
+#[test]
+fn verify_captured_quote() {
+    let quote = dcap::verify_quote(EVIDENCE, ENDORSEMENTS, valid_time()).expect("valid quote");
+
+    assert_eq!(
+        quote.mrenclave,
+        hex!("337ac97ce088a132daeb1308ea3159f807de4a827e875b2c90ce21bf4751196f")
+    );
+    assert_eq!(
+        quote.tcb_standing,
+        TcbStanding::SWHardeningNeeded {
+            advisory_ids: vec!["INTEL-SA-00615".to_owned(), "INTEL-SA-00657".to_owned()]
+        }
+    );
+
+    let expected_pubkey = hex::decode(include_bytes!("data/dcap.pubkey")).unwrap();
+    assert_eq!(quote.claims.get("pk"), Some(&expected_pubkey));
+}
+
+#[test]
+fn verify_quote_checks_collateral() {
+    // The same quote is rejected once its collateral has expired...
+    let much_later = valid_time() + Duration::from_secs(365 * 24 * 60 * 60);
+    assert!(dcap::verify_quote(EVIDENCE, ENDORSEMENTS, much_later).is_err());
+
+    // ...or if it's paired with collateral for a different platform.
+    let other_endorsements = include_bytes!("data/dcap_v3.endorsements");
+    assert!(dcap::verify_quote(EVIDENCE, other_endorsements, valid_time()).is_err());
+}
@@ -0,0 +1,306 @@
+#!/usr/bin/env python3
+
+#
+# Copyright 2024 Signal Messenger, LLC.
+# SPDX-License-Identifier: AGPL-3.0-only
+#
+
+"""
+Generates a header-only C++ wrapper around signal_ffi.h.
+
+Every opaque handle type with a `_destroy` function gets a `std::unique_ptr` alias, and every
+function that returns a `SignalFfiError *` gets an inline wrapper that throws `libsignal::Error` and
+returns its output by value. Functions whose output type isn't understood are left out; the C API
+is still available for those.
+"""
+
+import argparse
+import re
+import sys
+
+from typing import Dict, Iterator, List, NamedTuple, Optional, Tuple
+
+OPAQUE_TYPEDEF = re.compile(r'^typedef struct (Signal\w+) \1;$')
+FIXED_ARRAY_TYPEDEF = re.compile(r'^typedef uint8_t (Signal\w+)\[(.+)\];$')
+FALLIBLE_FN = re.compile(r'^SignalFfiError \*(signal_\w+)\((.*)\);$')
+PARAM_NAME = re.compile(r'\(\*(\w+)\)\[.*\]$|(\w+)$')
+
+SCALARS = ['bool', 'int32_t', 'uint8_t', 'uint32_t', 'uint64_t']
+
+PREAMBLE = '''//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+// This file was automatically generated by gen_cpp_wrapper.py from signal_ffi.h.
+
+#ifndef SIGNAL_FFI_HPP_
+#define SIGNAL_FFI_HPP_
+
+#include <algorithm>
+#include <array>
+#include <cstdint>
+#include <memory>
+#include <stdexcept>
+#include <string>
+#include <vector>
+
+#include "signal_ffi.h"
+
+namespace libsignal {
+
+/// An error returned by libsignal, carrying the code and message of the original `SignalFfiError`.
+class Error : public std::runtime_error {
+ public:
+  explicit Error(SignalFfiError *error)
+      : std::runtime_error(describe(error)), code_(signal_error_get_type(error)) {
+    signal_error_free(error);
+  }
+
+  /// One of the values of `SignalErrorCode`.
+  uint32_t code() const noexcept { return code_; }
+
+ private:
+  static std::string describe(const SignalFfiError *error) {
+    const char *message = nullptr;
+    if (SignalFfiError *inner = signal_error_get_message(error, &message)) {
+      signal_error_free(inner);
+      return "unknown libsignal error";
+    }
+    std::string result(message);
+    signal_free_string(message);
+    return result;
+  }
+
+  uint32_t code_;
+};
+
+namespace detail {
+
+inline void check(SignalFfiError *error) {
+  if (error) {
+    throw Error(error);
+  }
+}
+
+template <typename T>
+struct Deleter;
+
+template <size_t N>
+std::array<uint8_t, N> to_array(const uint8_t (&bytes)[N]) {
+  std::array<uint8_t, N> result;
+  std::copy(bytes, bytes + N, result.begin());
+  return result;
+}
+
+inline std::vector<uint8_t> take(SignalOwnedBuffer buffer) {
+  std::vector<uint8_t> result(buffer.base, buffer.base + buffer.length);
+  signal_free_buffer(buffer.base, buffer.length);
+  return result;
+}
+
+inline std::string take(const char *string) {
+  std::string result(string);
+  signal_free_string(string);
+  return result;
+}
+
+inline std::vector<std::string> take(SignalStringArray array) {
+  std::vector<std::string> result(array.base, array.base + array.length);
+  signal_free_list_of_strings(array);
+  return result;
+}
+
+inline std::vector<std::vector<uint8_t>> take(SignalBytestringArray array) {
+  std::vector<std::vector<uint8_t>> result;
+  result.reserve(array.lengths.length);
+  const uint8_t *next = array.bytes.base;
+  for (size_t i = 0; i < array.lengths.length; ++i) {
+    result.emplace_back(next, next + array.lengths.base[i]);
+    next += array.lengths.base[i];
+  }
+  signal_free_bytestring_array(array);
+  return result;
+}
+
+}  // namespace detail
+'''
+
+POSTAMBLE = '''
+}  // namespace libsignal
+
+#endif  /* SIGNAL_FFI_HPP_ */
+'''
+
+
+class Output(NamedTuple):
+    # The C type of the local variable the function writes to.
+    c_type: str
+    # The C++ return type.
+    cpp_type: str
+    # Converts the local variable `out` to the C++ return type.
+    convert: str
+
+
+def handle_alias(c_type: str) -> str:
+    return c_type[len('Signal'):]
+
+
+def parse_args() -> argparse.Namespace:
+    parser = argparse.ArgumentParser(description=__doc__)
+    parser.add_argument('header', help='path to signal_ffi.h')
+    parser.add_argument('-o', '--output', help='where to write the C++ header (default: stdout)')
+    return parser.parse_args()
+
+
+def split_params(params: str) -> List[str]:
+    if params in ('', 'void'):
+        return []
+    return [p.strip() for p in params.split(',')]
+
+
+def param_name(param: str) -> str:
+    match = PARAM_NAME.search(param)
+    assert match, f'unexpected parameter {param!r}'
+    return match.group(1) or match.group(2)
+
+
+def classify_output(param: str, handles: Dict[str, str], fixed_arrays: Dict[str, str]) -> Optional[Output]:
+    """Returns how to translate an output parameter, or None if it isn't supported."""
+    if match := re.fullmatch(r'(Signal\w+) \*\*(out|new_obj)', param):
+        c_type = match.group(1)
+        if c_type not in handles:
+            return None
+        alias = handle_alias(c_type)
+        return Output(f'{c_type} *', alias, f'{alias}(out)')
+    if match := re.fullmatch(r'uint8_t \(\*out\)\[(.+)\]', param):
+        length = match.group(1)
+        return Output(f'uint8_t[{length}]', f'std::array<uint8_t, {length}>', 'detail::to_array(out)')
+    if match := re.fullmatch(r'(\w+) \*out', param):
+        c_type = match.group(1)
+        if c_type in SCALARS:
+            return Output(c_type, c_type, 'out')
+        if c_type in fixed_arrays:
+            length = fixed_arrays[c_type]
+            return Output(c_type, f'std::array<uint8_t, {length}>', 'detail::to_array(out)')
+        if c_type in ('SignalOwnedBuffer', 'SignalStringArray', 'SignalBytestringArray'):
+            cpp_type = {
+                'SignalOwnedBuffer': 'std::vector<uint8_t>',
+                'SignalStringArray': 'std::vector<std::string>',
+                'SignalBytestringArray': 'std::vector<std::vector<uint8_t>>',
+            }[c_type]
+            return Output(c_type, cpp_type, 'detail::take(out)')
+    if param == 'const char **out':
+        return Output('const char *', 'std::string', 'detail::take(out)')
+    return None
+
+
+def declare_local(output: Output) -> str:
+    if output.c_type.startswith('uint8_t['):
+        length = output.c_type[len('uint8_t['):-1]
+        return f'uint8_t out[{length}] = {{}};'
+    separator = '' if output.c_type.endswith('*') else ' '
+    return f'{output.c_type}{separator}out{{}};'
+
+
+def wrap_function(name: str, params: List[str], handles: Dict[str, str], fixed_arrays: Dict[str, str]) -> Optional[str]:
+    if name.startswith('signal_error_') or name.endswith('_destroy'):
+        return None
+
+    output = None
+    if params and re.search(r'\b(out|new_obj)\b', params[0]):
+        output = classify_output(params[0], handles, fixed_arrays)
+        if output is None:
+            return None
+        params = params[1:]
+
+    cpp_name = name[len('signal_'):]
+    args = ', '.join((['&out'] if output else []) + [param_name(p) for p in params])
+    lines = [f'inline {output.cpp_type if output else "void"} {cpp_name}({", ".join(params)}) {{']
+    if output:
+        lines.append(f'  {declare_local(output)}')
+    lines.append(f'  detail::check({name}({args}));')
+    if output:
+        lines.append(f'  return {output.convert};')
+    lines.append('}')
+    return '\n'.join(lines)
+
+
+def guarded(condition: Optional[str], body: str) -> str:
+    if condition is None:
+        return body
+    return f'#if {condition}\n{body}\n#endif'
+
+
+def parse_header(lines: List[str]) -> Iterator[Tuple[Optional[str], str]]:
+    """Yields each line outside comments along with the `#if` condition it's nested in, if any."""
+    condition = None
+    in_comment = False
+    for line in lines:
+        line = line.rstrip('\n')
+        if in_comment:
+            in_comment = '*/' not in line
+            continue
+        if line.startswith('/*'):
+            in_comment = '*/' not in line
+            continue
+        if line.startswith('#if '):
+            condition = line[len('#if '):]
+        elif line.startswith('#endif'):
+            condition = None
+        else:
+            yield (condition, line)
+
+
+def generate(header_lines: List[str]) -> str:
+    declarations = list(parse_header(header_lines))
+
+    fixed_arrays = {}
+    destructors = {}
+    for (_, line) in declarations:
+        if match := FIXED_ARRAY_TYPEDEF.match(line):
+            fixed_arrays[match.group(1)] = match.group(2)
+        elif match := re.match(r'^SignalFfiError \*(signal_\w+_destroy)\((Signal\w+) \*p\);$', line):
+            destructors[match.group(2)] = match.group(1)
+
+    handles: Dict[str, str] = {}
+    handle_decls = []
+    function_decls = []
+    for (condition, line) in declarations:
+        if match := OPAQUE_TYPEDEF.match(line):
+            c_type = match.group(1)
+            if c_type not in destructors:
+                continue
+            handles[c_type] = destructors[c_type]
+            handle_decls.append(guarded(condition, '\n'.join([
+                'template <>',
+                f'struct detail::Deleter<{c_type}> {{',
+                f'  void operator()({c_type} *p) const noexcept {{',
+                f'    if (SignalFfiError *error = {destructors[c_type]}(p)) {{',
+                '      signal_error_free(error);',
+                '    }',
+                '  }',
+                '};',
+                f'using {handle_alias(c_type)} = std::unique_ptr<{c_type}, detail::Deleter<{c_type}>>;',
+            ])))
+        elif match := FALLIBLE_FN.match(line):
+            wrapper = wrap_function(match.group(1), split_params(match.group(2)), handles, fixed_arrays)
+            if wrapper is not None:
+                function_decls.append(guarded(condition, wrapper))
+
+    return '\n\n'.join([PREAMBLE.rstrip('\n')] + handle_decls + function_decls) + '\n' + POSTAMBLE
+
+
+def main() -> None:
+    args = parse_args()
+    with open(args.header) as f:
+        result = generate(f.readlines())
+    if args.output:
+        with open(args.output, 'w') as f:
+            f.write(result)
+    else:
+        sys.stdout.write(result)
+
+
+if __name__ == '__main__':
+    main()
@@ -58,6 +58,18 @@ pub unsafe extern "C" fn signal_free_lookup_response_entry_list(
     drop(buffer.into_box())
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn signal_free_message_backup_validation_finding_list(
+    buffer: OwnedBufferOf<crate::FfiMessageBackupValidationFinding>,
+) {
+    let findings = buffer.into_box();
+    for finding in &*findings {
+        signal_free_string(finding.field_path);
+        signal_free_string(finding.message);
+    }
+    drop(findings);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn signal_free_bytestring_array(array: BytestringArray) {
     drop(array.into_boxed_parts())
@@ -125,13 +137,49 @@ pub unsafe extern "C" fn signal_error_get_retry_after_seconds(
     let err = AssertUnwindSafe(err);
     run_ffi_safe(|| {
         let err = err.as_ref().ok_or(NullPointerError)?;
-        let value = err.provide_retry_after_seconds().map_err(|_| {
+        let value = err.provide_rate_limit().map_err(|_| {
             SignalProtocolError::InvalidArgument(format!(
                 "cannot get retry_after_seconds from error ({})",
                 err
             ))
         })?;
-        write_result_to(out, value)
+        write_result_to(out, value.retry_after_seconds)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn signal_error_get_rate_limit_scope(
+    err: *const SignalFfiError,
+    out: *mut u8,
+) -> *mut SignalFfiError {
+    let err = AssertUnwindSafe(err);
+    run_ffi_safe(|| {
+        let err = err.as_ref().ok_or(NullPointerError)?;
+        let value = err.provide_rate_limit().map_err(|_| {
+            SignalProtocolError::InvalidArgument(format!(
+                "cannot get rate_limit_scope from error ({})",
+                err
+            ))
+        })?;
+        write_result_to(out, value.scope as u8)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn signal_error_get_rate_limit_reason(
+    err: *const SignalFfiError,
+    out: *mut *const c_char,
+) -> *mut SignalFfiError {
+    let err = AssertUnwindSafe(err);
+    run_ffi_safe(|| {
+        let err = err.as_ref().ok_or(NullPointerError)?;
+        let value = err.provide_rate_limit().map_err(|_| {
+            SignalProtocolError::InvalidArgument(format!(
+                "cannot get rate_limit_reason from error ({})",
+                err
+            ))
+        })?;
+        write_result_to(out, value.reason)
     })
 }
 
@@ -174,6 +222,61 @@ pub unsafe extern "C" fn signal_error_get_unknown_fields(
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn signal_error_get_disconnect_close_code(
+    err: *const SignalFfiError,
+    out: *mut u16,
+) -> *mut SignalFfiError {
+    let err = AssertUnwindSafe(err);
+    run_ffi_safe(|| {
+        let err = err.as_ref().ok_or(NullPointerError)?;
+        let value = err.provide_disconnect_info().map_err(|_| {
+            SignalProtocolError::InvalidArgument(format!(
+                "cannot get disconnect_close_code from error ({})",
+                err
+            ))
+        })?;
+        // 0 is never sent on the wire, so it can stand in for "no close frame".
+        write_result_to(out, value.close_code.unwrap_or(0))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn signal_error_get_disconnect_reason(
+    err: *const SignalFfiError,
+    out: *mut *const c_char,
+) -> *mut SignalFfiError {
+    let err = AssertUnwindSafe(err);
+    run_ffi_safe(|| {
+        let err = err.as_ref().ok_or(NullPointerError)?;
+        let value = err.provide_disconnect_info().map_err(|_| {
+            SignalProtocolError::InvalidArgument(format!(
+                "cannot get disconnect_reason from error ({})",
+                err
+            ))
+        })?;
+        write_result_to(out, value.reason)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn signal_error_get_disconnect_alerts(
+    err: *const SignalFfiError,
+    out: *mut StringArray,
+) -> *mut SignalFfiError {
+    let err = AssertUnwindSafe(err);
+    run_ffi_safe(|| {
+        let err = err.as_ref().ok_or(NullPointerError)?;
+        let value = err.provide_disconnect_info().map_err(|_| {
+            SignalProtocolError::InvalidArgument(format!(
+                "cannot get disconnect_alerts from error ({})",
+                err
+            ))
+        })?;
+        write_result_to(out, value.alerts.into_boxed_slice())
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn signal_error_free(err: *mut SignalFfiError) {
     if !err.is_null() {
@@ -248,3 +351,59 @@ pub unsafe extern "C" fn signal_sealed_session_cipher_decrypt(
         Ok(())
     })
 }
+
+#[no_mangle]
+pub unsafe extern "C" fn signal_sealed_session_cipher_decrypt_with_replay_cache(
+    out: *mut OwnedBufferOf<c_uchar>,
+    sender_e164: *mut *const c_char,
+    sender_uuid: *mut *const c_char,
+    sender_device_id: *mut u32,
+    ctext: BorrowedSliceOf<c_uchar>,
+    trust_root: *const PublicKey,
+    timestamp: u64,
+    local_e164: *const c_char,
+    local_uuid: *const c_char,
+    local_device_id: c_uint,
+    session_store: *const FfiSessionStoreStruct,
+    identity_store: *const FfiIdentityKeyStoreStruct,
+    prekey_store: *const FfiPreKeyStoreStruct,
+    signed_prekey_store: *const FfiSignedPreKeyStoreStruct,
+    replay_cache: *const FfiReplayCacheStruct,
+) -> *mut SignalFfiError {
+    run_ffi_safe(|| {
+        let mut kyber_pre_key_store = InMemKyberPreKeyStore::new();
+        let ctext = ctext.as_slice()?;
+        let trust_root = native_handle_cast::<PublicKey>(trust_root)?;
+        let mut identity_store = identity_store.as_ref().ok_or(NullPointerError)?;
+        let mut session_store = session_store.as_ref().ok_or(NullPointerError)?;
+        let mut prekey_store = prekey_store.as_ref().ok_or(NullPointerError)?;
+        let signed_prekey_store = signed_prekey_store.as_ref().ok_or(NullPointerError)?;
+        let mut replay_cache = replay_cache.as_ref().ok_or(NullPointerError)?;
+
+        let local_e164 = Option::convert_from(local_e164)?;
+        let local_uuid = Option::convert_from(local_uuid)?.ok_or(NullPointerError)?;
+
+        let decrypted = sealed_sender_decrypt_with_replay_cache(
+            ctext,
+            trust_root,
+            Timestamp::from_epoch_millis(timestamp),
+            local_e164,
+            local_uuid,
+            local_device_id.into(),
+            &mut identity_store,
+            &mut session_store,
+            &mut prekey_store,
+            &signed_prekey_store,
+            &mut kyber_pre_key_store,
+            &mut replay_cache,
+        )
+        .now_or_never()
+        .expect("synchronous")?;
+
+        write_result_to(sender_e164, decrypted.sender_e164)?;
+        write_result_to(sender_uuid, decrypted.sender_uuid)?;
+        write_result_to(sender_device_id, u32::from(decrypted.device_id))?;
+        write_result_to(out, decrypted.message)?;
+        Ok(())
+    })
+}
@@ -7,6 +7,7 @@
 
 import collections
 import difflib
+import hashlib
 import os
 import subprocess
 import re
@@ -44,7 +45,8 @@ IGNORE_THIS_WARNING = re.compile(
     r"WARN: Skip libsignal-bridge(-testing)?::.+ - \(not `(pub|no_mangle)`\)\.|"
     r"WARN: Couldn't find path for Array\(Path\(GenericPath \{ .+ \}\), Name\(\"LEN\"\)\), skipping associated constants|"
     r"WARN: Cannot find a mangling for generic path GenericPath { path: Path { name: \"JavaCompletableFuture\" }.+|"
-    r"WARN: Cannot find a mangling for generic path GenericPath { path: Path { name: \"Throwing\" }.+"
+    r"WARN: Cannot find a mangling for generic path GenericPath { path: Path { name: \"Throwing\" }.+|"
+    r"WARN: Cannot find a mangling for generic path GenericPath { path: Path { name: \"Nullable\" }.+"
     ")")
 
 
@@ -117,6 +119,14 @@ def translate_to_java(typ: str) -> Tuple[str, bool]:
         assert stripped.endswith('>')
         return (translate_to_java(stripped.removesuffix('>'))[0], True)
 
+    # Kotlin callers pick up nullability from these annotations. ID-like integers stay plain
+    # primitives: the declarations are Java, which has no equivalent of Kotlin's value classes, and
+    # boxing every ID in a wrapper class would cost an allocation per call.
+    if (stripped := typ.removeprefix('Nullable<')) != typ:
+        assert stripped.endswith('>')
+        (inner, is_throwing) = translate_to_java(stripped.removesuffix('>'))
+        return ('@Nullable ' + inner, is_throwing)
+
     if (stripped := typ.removeprefix('JavaCompletableFuture<')) != typ:
         assert stripped.endswith('>')
         inner = translate_to_java(stripped.removesuffix('>'))[0]
@@ -174,10 +184,16 @@ def parse_decls(cbindgen_output: str) -> Iterator[str]:
             " throws Exception" if is_throwing else ""))
 
 
+def bridge_manifest_sha256() -> str:
+    our_abs_dir = os.path.dirname(os.path.realpath(__file__))
+    with open(os.path.join(our_abs_dir, '..', '..', 'shared', 'bridge_manifest.json'), 'rb') as f:
+        return hashlib.sha256(f.read()).hexdigest()
+
+
 def expand_template(template_file: str, decls: Iterable[str]) -> str:
     with open(template_file, "r") as f:
         contents = f.read().replace('\n  // INSERT DECLS HERE', "\n".join(decls))
-    return contents
+    return contents.replace('INSERT BRIDGE MANIFEST SHA256 HERE', bridge_manifest_sha256())
 
 
 def verify_contents(expected_output_file: str, expected_contents: str) -> None:
@@ -25,7 +25,7 @@ pub unsafe extern "C" fn Java_org_signal_libsignal_internal_Native_IdentityKeyPa
     _class: JClass,
     data: JByteArray,
 ) -> JLongArray<'local> {
-    run_ffi_safe(&mut env, |env| {
+    run_ffi_safe(&mut env, "IdentityKeyPair_Deserialize", |env| {
         let data = env
             .convert_byte_array(data)
             .check_exceptions(env, "deserialize")?;
@@ -51,7 +51,7 @@ pub unsafe extern "C" fn Java_org_signal_libsignal_internal_Native_initializeLib
     mut env: JNIEnv<'local>,
     class: JClass<'local>,
 ) {
-    run_ffi_safe(&mut env, |env| {
+    run_ffi_safe(&mut env, "initializeLibrary", |env| {
         #[cfg(target_os = "android")]
         save_class_loader(env, &class)?;
 
@@ -60,7 +60,8 @@ pub unsafe extern "C" fn Java_org_signal_libsignal_internal_Native_initializeLib
 
         // Silence the unused variable warning on non-Android.
         _ = class;
-        _ = env;
+
+        preload_classes(env)?;
 
         Ok(())
     })
@@ -83,7 +84,7 @@ pub unsafe extern "C" fn Java_org_signal_libsignal_internal_Native_AsyncLoadClas
         }
     }
 
-    run_ffi_safe(&mut env, |env| {
+    run_ffi_safe(&mut env, "AsyncLoadClass", |env| {
         let handle = call_method_checked(
             env,
             tokio_context,
@@ -95,7 +96,7 @@ pub unsafe extern "C" fn Java_org_signal_libsignal_internal_Native_AsyncLoadClas
             .get_string(&class_name)
             .check_exceptions(env, "AsyncLoadClass")?
             .into();
-        run_future_on_runtime(env, tokio_context, |_cancel| async {
+        run_future_on_runtime(env, tokio_context, "AsyncLoadClass", |_cancel| async {
             FutureResultReporter::new(Ok(LoadClassFromName(class_name)), ())
         })
     })
@@ -111,7 +112,8 @@ pub unsafe extern "C" fn Java_org_signal_libsignal_internal_Native_SealedSender_
     _class: JClass,
     data: JByteArray<'local>,
 ) -> JObject<'local> {
-    run_ffi_safe(&mut env, |env| {
+    const OPERATION_NAME: &str = "SealedSender_MultiRecipientParseSentMessage";
+    run_ffi_safe(&mut env, OPERATION_NAME, |env| {
         let mut data_stored =
             unsafe { env.get_array_elements(&data, jni::objects::ReleaseMode::NoCopyBack) }
                 .check_exceptions(env, "MultiRecipientParseSentMessage")?;
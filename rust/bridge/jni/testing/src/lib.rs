@@ -26,7 +26,7 @@ pub unsafe extern "C" fn Java_org_signal_libsignal_internal_NativeTesting_initia
     mut env: JNIEnv<'local>,
     class: JClass<'local>,
 ) {
-    run_ffi_safe(&mut env, |env| {
+    run_ffi_safe(&mut env, "initializeLibrary", |env| {
         #[cfg(target_os = "android")]
         libsignal_bridge_types::jni::save_class_loader(env, &class)?;
 
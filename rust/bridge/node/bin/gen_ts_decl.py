@@ -7,6 +7,7 @@
 
 import collections
 import difflib
+import hashlib
 import itertools
 import os
 import subprocess
@@ -155,6 +156,33 @@ DIAGNOSTICS_TO_IGNORE = [
 SHOULD_IGNORE_PATTERN = re.compile("(" + ")|(".join(DIAGNOSTICS_TO_IGNORE) + ")")
 
 
+# Integers that identify something are given branded types so that they can't be mixed up with
+# each other. Arguments are matched by the end of their name; results by the end of the function name.
+# Timestamps stay plain numbers (see Native.d.ts.in).
+BRANDED_INTEGERS = {
+    'device_id': 'DeviceId',
+    'registration_id': 'RegistrationId',
+}
+
+
+def brand_integer_arg(arg_name: str, ts_type: str) -> str:
+    if ts_type != 'number':
+        return ts_type
+    for (suffix, brand) in BRANDED_INTEGERS.items():
+        if arg_name == suffix or arg_name.endswith('_' + suffix):
+            return brand
+    return ts_type
+
+
+def brand_integer_result(fn_prefix: str, ts_type: str) -> str:
+    if ts_type != 'number':
+        return ts_type
+    for brand in BRANDED_INTEGERS.values():
+        if fn_prefix.endswith(brand):
+            return brand
+    return ts_type
+
+
 def camelcase(arg: str) -> str:
     return re.sub(
         # Preserve double-underscores and leading underscores,
@@ -219,14 +247,15 @@ def collect_decls(crate_dir: str, features: Iterable[str] = ()) -> Iterator[str]
 
         (prefix, fn_args, ret_type) = function_match.groups()
 
-        ts_ret_type = translate_to_ts(ret_type)
+        ts_ret_type = brand_integer_result(prefix, translate_to_ts(ret_type))
         ts_args = []
         if '::' in fn_args:
             raise Exception(f'Paths are not supported. Use alias for the type of \'{fn_args}\'')
 
         for (arg_name, arg_type) in split_rust_args(fn_args):
-            ts_arg_type = translate_to_ts(arg_type)
-            ts_args.append('%s: %s' % (camelcase(arg_name.strip()), ts_arg_type))
+            arg_name = arg_name.strip()
+            ts_arg_type = brand_integer_arg(arg_name, translate_to_ts(arg_type))
+            ts_args.append('%s: %s' % (camelcase(arg_name), ts_arg_type))
 
         yield '%s(%s): %s;' % (prefix, ', '.join(ts_args), ts_ret_type)
 
@@ -269,6 +298,34 @@ def convert_to_typescript(rust_crates: Iterable[Crate], ts_in_path: str, ts_out_
         verify_contents(ts_out_path, contents)
 
 
+BRIDGE_MANIFEST_TEMPLATE = """//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+// WARNING: this file was automatically generated
+
+/**
+ * The hash of the bridge manifest (rust/bridge/shared/bridge_manifest.json) that Native.d.ts was
+ * generated from.
+ */
+export const BRIDGE_MANIFEST_SHA256 =
+  '{sha256}';
+"""
+
+
+def write_bridge_manifest_hash(manifest_path: str, ts_out_path: str, verify: bool) -> None:
+    with open(manifest_path, 'rb') as f:
+        sha256 = hashlib.sha256(f.read()).hexdigest()
+    contents = BRIDGE_MANIFEST_TEMPLATE.format(sha256=sha256)
+
+    if not verify:
+        with open(ts_out_path, 'w') as fh:
+            fh.write(contents)
+    else:
+        verify_contents(ts_out_path, contents)
+
+
 def main() -> None:
     args = parse_args()
     our_abs_dir = os.path.dirname(os.path.realpath(__file__))
@@ -279,13 +336,19 @@ def main() -> None:
             Crate(path=os.path.join(our_abs_dir, '..')),
             Crate(path=os.path.join(our_abs_dir, '..', '..', 'shared'), features=('node', 'signal-media')),
             Crate(path=os.path.join(our_abs_dir, '..', '..', 'shared', 'types'), features=('node', 'signal-media')),
-            Crate(path=os.path.join(our_abs_dir, '..', '..', 'shared', 'testing'), features=('node', 'signal-media')),
+            Crate(path=os.path.join(our_abs_dir, '..', '..', 'shared', 'testing'), features=('node', 'signal-media', 'mock-chat')),
         ],
         ts_in_path=os.path.join(our_abs_dir, output_file_name + '.in'),
         ts_out_path=os.path.join(our_abs_dir, '..', '..', '..', '..', 'node', output_file_name),
         verify=args.verify,
     )
 
+    write_bridge_manifest_hash(
+        manifest_path=os.path.join(our_abs_dir, '..', '..', 'shared', 'bridge_manifest.json'),
+        ts_out_path=os.path.join(our_abs_dir, '..', '..', '..', '..', 'node', 'ts', 'BridgeManifest.ts'),
+        verify=args.verify,
+    )
+
 
 if __name__ == '__main__':
     main()
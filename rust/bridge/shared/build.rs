@@ -3,6 +3,15 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use std::path::{Path, PathBuf};
+
+use quote::ToTokens;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use syn::parse::{ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::{Expr, ExprLit, Lit, MetaNameValue, Token};
+
 fn main() {
     // Set environment variables for bridge_fn to produce correctly-named symbols for FFI and JNI.
     println!("cargo:rustc-env=LIBSIGNAL_BRIDGE_FN_PREFIX_FFI=signal_");
@@ -11,4 +20,316 @@ fn main() {
     println!(
         "cargo:rustc-env=LIBSIGNAL_BRIDGE_FN_PREFIX_JNI=Java_org_signal_libsignal_internal_Native_"
     );
+
+    write_manifest();
+}
+
+/// Every crate that defines bridged functions, with its source directory relative to this crate.
+///
+/// All of them are linked into the same native library, so they're covered by one manifest.
+const BRIDGE_CRATES: &[(&str, &str)] = &[
+    ("libsignal-bridge", "src"),
+    ("libsignal-bridge-testing", "testing/src"),
+];
+
+/// Macros from libsignal-bridge-types that expand to `bridge_fn`s.
+///
+/// The manifest can only see what's written in the source of [`BRIDGE_CRATES`], so it records each
+/// invocation of these rather than the functions they produce.
+const BRIDGE_FN_MACROS: &[&str] = &[
+    "bridge_deserialize",
+    "bridge_fixed_length_serializable_fns",
+    "bridge_get",
+    "bridge_handle_fns",
+    "bridge_serializable_handle_fns",
+];
+
+/// A description of every bridged function, independent of which bridges are being built.
+///
+/// See `src/manifest.rs` for how it's used.
+#[derive(Default, Serialize)]
+struct Manifest {
+    functions: Vec<Function>,
+    macros: Vec<MacroInvocation>,
+}
+
+#[derive(Serialize)]
+struct Function {
+    name: String,
+    /// The crate that defines the function.
+    #[serde(rename = "crate")]
+    krate: &'static str,
+    args: Vec<Arg>,
+    result: String,
+    #[serde(rename = "async")]
+    is_async: bool,
+    /// Whether the function is a `bridge_io` function, whose result can be cancelled.
+    cancellable: bool,
+    /// The async runtime a `bridge_io` function runs on.
+    runtime: Option<String>,
+    ffi: Exposure,
+    jni: Exposure,
+    node: Exposure,
+    cfg: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Arg {
+    name: String,
+    #[serde(rename = "type")]
+    ty: String,
+}
+
+#[derive(Serialize)]
+struct MacroInvocation {
+    name: String,
+    #[serde(rename = "crate")]
+    krate: &'static str,
+    input: String,
+    cfg: Vec<String>,
+}
+
+/// Whether a function is exposed to a particular bridge, mirroring the `bridge_fn` attribute:
+/// `true` by default, `false` if disabled, or a custom name.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Exposure {
+    Enabled(bool),
+    Renamed(String),
+}
+
+fn write_manifest() {
+    let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").expect("set by Cargo"));
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").expect("set by Cargo"));
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let mut manifest = Manifest::default();
+    for &(krate, src) in BRIDGE_CRATES {
+        let src_dir = manifest_dir.join(src);
+        println!("cargo:rerun-if-changed={}", src_dir.display());
+        let source = Source { krate, cfg: vec![] };
+        collect_file(&src_dir.join("lib.rs"), &src_dir, &source, &mut manifest);
+    }
+    // Sort so that moving code around doesn't change the manifest.
+    manifest
+        .functions
+        .sort_by(|a, b| (&a.name, &a.cfg).cmp(&(&b.name, &b.cfg)));
+    manifest
+        .macros
+        .sort_by(|a, b| (&a.name, &a.input, &a.cfg).cmp(&(&b.name, &b.input, &b.cfg)));
+
+    let mut json = serde_json::to_string_pretty(&manifest).expect("can serialize");
+    json.push('\n');
+    std::fs::write(out_dir.join("bridge_manifest.json"), &json).expect("can write manifest");
+    println!(
+        "cargo:rustc-env=LIBSIGNAL_BRIDGE_MANIFEST_SHA256={}",
+        hex::encode(Sha256::digest(&json))
+    );
+}
+
+/// Where an item being collected was found: its crate, and the `cfg`s of the modules around it.
+struct Source {
+    krate: &'static str,
+    cfg: Vec<String>,
+}
+
+/// Collects the bridged functions in the module at `path`, whose submodules are in `dir`.
+fn collect_file(path: &Path, dir: &Path, source: &Source, manifest: &mut Manifest) {
+    let contents = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("failed to read {}: {e}", path.display()));
+    let file = syn::parse_file(&contents)
+        .unwrap_or_else(|e| panic!("failed to parse {}: {e}", path.display()));
+    collect_items(&file.items, dir, source, manifest);
+}
+
+fn collect_items(items: &[syn::Item], dir: &Path, source: &Source, manifest: &mut Manifest) {
+    for item in items {
+        match item {
+            syn::Item::Fn(function) => {
+                if let Some(function) = describe_function(function, source) {
+                    manifest.functions.push(function);
+                }
+            }
+            syn::Item::Macro(syn::ItemMacro { mac, attrs, .. }) => {
+                let Some(name) = mac.path.get_ident().map(ToString::to_string) else {
+                    continue;
+                };
+                if BRIDGE_FN_MACROS.contains(&name.as_str()) {
+                    manifest.macros.push(MacroInvocation {
+                        name,
+                        krate: source.krate,
+                        input: render(&mac.tokens),
+                        cfg: with_cfgs(&source.cfg, attrs),
+                    });
+                }
+            }
+            syn::Item::Mod(module) => {
+                if is_cfg_test(&module.attrs) {
+                    continue;
+                }
+                let source = Source {
+                    krate: source.krate,
+                    cfg: with_cfgs(&source.cfg, &module.attrs),
+                };
+                let module_dir = dir.join(module.ident.to_string());
+                match &module.content {
+                    Some((_, items)) => collect_items(items, &module_dir, &source, manifest),
+                    None => {
+                        let flat = dir.join(format!("{}.rs", module.ident));
+                        let path = if flat.exists() {
+                            flat
+                        } else {
+                            module_dir.join("mod.rs")
+                        };
+                        collect_file(&path, &module_dir, &source, manifest);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn describe_function(function: &syn::ItemFn, source: &Source) -> Option<Function> {
+    let (attr, is_io) = function.attrs.iter().find_map(|attr| {
+        let name = attr.path().segments.last()?.ident.to_string();
+        match name.as_str() {
+            "bridge_fn" => Some((attr, false)),
+            "bridge_io" => Some((attr, true)),
+            _ => None,
+        }
+    })?;
+
+    let name = function.sig.ident.to_string();
+    let tokens = match &attr.meta {
+        syn::Meta::List(list) => list.tokens.clone(),
+        _ => Default::default(),
+    };
+    let (runtime, names) = if is_io {
+        let (runtime, names) = (|input: ParseStream| {
+            let runtime: syn::Type = input.parse()?;
+            if input.is_empty() {
+                return Ok((runtime, Punctuated::new()));
+            }
+            input.parse::<Token![,]>()?;
+            Ok((runtime, Punctuated::parse_terminated(input)?))
+        })
+        .parse2(tokens)
+        .unwrap_or_else(|e| panic!("invalid bridge_io attribute on {name}: {e}"));
+        (Some(render(runtime)), names)
+    } else {
+        let names = Punctuated::<MetaNameValue, Token![,]>::parse_terminated
+            .parse2(tokens)
+            .unwrap_or_else(|e| panic!("invalid bridge_fn attribute on {name}: {e}"));
+        (None, names)
+    };
+    let exposure = |bridge: &str| match names.iter().find(|meta| meta.path.is_ident(bridge)) {
+        None => Exposure::Enabled(true),
+        Some(MetaNameValue {
+            value: Expr::Lit(ExprLit { lit, .. }),
+            ..
+        }) => match lit {
+            Lit::Bool(enabled) => Exposure::Enabled(enabled.value),
+            Lit::Str(renamed) => Exposure::Renamed(renamed.value()),
+            _ => panic!("invalid {bridge} name for {name}"),
+        },
+        Some(_) => panic!("invalid {bridge} name for {name}"),
+    };
+
+    let args = function
+        .sig
+        .inputs
+        .iter()
+        .map(|arg| match arg {
+            syn::FnArg::Typed(arg) => Arg {
+                name: render(&arg.pat),
+                ty: render(&arg.ty),
+            },
+            syn::FnArg::Receiver(_) => panic!("bridged function {name} cannot take self"),
+        })
+        .collect();
+    let result = match &function.sig.output {
+        syn::ReturnType::Default => "()".to_owned(),
+        syn::ReturnType::Type(_, ty) => render(ty),
+    };
+
+    Some(Function {
+        args,
+        result,
+        is_async: function.sig.asyncness.is_some(),
+        cancellable: is_io,
+        runtime,
+        ffi: exposure("ffi"),
+        jni: exposure("jni"),
+        node: exposure("node"),
+        cfg: with_cfgs(&source.cfg, &function.attrs),
+        krate: source.krate,
+        name,
+    })
+}
+
+fn with_cfgs(outer: &[String], attrs: &[syn::Attribute]) -> Vec<String> {
+    let own = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .map(|attr| render(&attr.meta));
+    outer.iter().cloned().chain(own).collect()
+}
+
+fn is_cfg_test(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("cfg") && render(&attr.meta) == "cfg(test)")
+}
+
+/// Renders tokens compactly, e.g. `Result<Vec<u8>, Error>` rather than the default
+/// `Result < Vec < u8 > , Error >`, so the manifest is easy to read.
+fn render(tokens: impl ToTokens) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    let spaced = tokens.to_token_stream().to_string();
+    let mut rendered = String::with_capacity(spaced.len());
+    let mut chars = spaced.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            rendered.push(c);
+            match c {
+                '\\' => rendered.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            ' ' => {
+                let keep = match chars.peek() {
+                    Some(&next) if is_word(next) => rendered.ends_with(is_word),
+                    Some('[' | '(') => rendered.ends_with("mut"),
+                    _ => false,
+                };
+                if keep {
+                    rendered.push(' ');
+                }
+            }
+            ',' | ';' => {
+                rendered.push(c);
+                rendered.push(' ');
+            }
+            '=' => rendered.push_str(" = "),
+            '-' if chars.next_if_eq(&'>').is_some() => rendered.push_str(" -> "),
+            ')' | ']' | '}' => {
+                if rendered.ends_with(", ") {
+                    rendered.truncate(rendered.len() - 2);
+                }
+                rendered.push(c);
+            }
+            '"' => {
+                in_string = true;
+                rendered.push(c);
+            }
+            _ => rendered.push(c),
+        }
+    }
+    rendered.trim_end().to_owned()
 }
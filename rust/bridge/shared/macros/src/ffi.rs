@@ -63,7 +63,12 @@ pub(crate) fn bridge_fn(
             #implicit_args
             #(#input_args),*
         ) -> *mut ffi::SignalFfiError {
-            #body
+            let __timer = ffi::CallTimer::start(#name);
+            let __error = { #body };
+            if let Some(__timer) = __timer {
+                __timer.finish(!__error.is_null());
+            }
+            __error
         }
     })
 }
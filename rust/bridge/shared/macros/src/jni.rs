@@ -68,7 +68,12 @@ pub(crate) fn bridge_fn(
             #async_runtime_if_needed
             #(#input_args),*
         ) -> #result_ty {
-            #body
+            let __timer = jni::CallTimer::start(#name);
+            let __result = { #body };
+            if let Some(__timer) = __timer {
+                __timer.finish(env.exception_check().unwrap_or(true));
+            }
+            __result
         }
     })
 }
@@ -101,7 +106,7 @@ fn bridge_fn_body(
     });
 
     quote! {
-        jni::run_ffi_safe(&mut env, |env| {
+        jni::run_ffi_safe(&mut env, stringify!(#orig_name), |env| {
             #(#input_processing)*
             let __result = #orig_name(#(#input_names),*);
             #await_if_needed
@@ -151,10 +156,10 @@ fn bridge_io_body(
     let input_stored_names = input_args.iter().map(|(name, _ty)| storage_ident_for(name));
 
     quote! {
-        jni::run_ffi_safe(&mut env, |env| {
+        jni::run_ffi_safe(&mut env, stringify!(#orig_name), |env| {
             #load_async_runtime
             #(#input_saving)*
-            jni::run_future_on_runtime(env, async_runtime, |__cancel| async move {
+            jni::run_future_on_runtime(env, async_runtime, stringify!(#orig_name), |__cancel| async move {
                 // Wrap the actual work to catch any panics.
                 let __future = jni::catch_unwind(std::panic::AssertUnwindSafe(async {
                     #(#input_loading)*
@@ -131,6 +131,15 @@
 //!    These traits define how to convert between the bridge type and the Rust type used in the
 //!    function as written. See each individual trait for more info on how to add a new type.
 //!
+//! # Deadlines
+//!
+//! If a function takes an argument of type `Deadline`, its body is run inside `Deadline::scope`
+//! (or `Deadline::scope_future`, for `async` functions). The call fails with `DeadlineExceeded`
+//! without running the body if the deadline has already passed, and input streams read during the
+//! call check it before every operation. The function must return a `Result` whose error type can
+//! be created from `DeadlineExceeded`, and it must check the deadline itself in any long-running
+//! loop that doesn't read input.
+//!
 //! # Limitations
 //!
 //! - There is no support for multiple return values, even though some of the FFI entry points
@@ -265,7 +274,7 @@ fn bridge_fn_impl(
     item: TokenStream,
     bridging_kind: BridgingKind<()>,
 ) -> TokenStream {
-    let function = parse_macro_input!(item as ItemFn);
+    let mut function = parse_macro_input!(item as ItemFn);
 
     let (bridging_kind, item_names) = match bridging_kind {
         BridgingKind::Regular => (
@@ -284,6 +293,10 @@ fn bridge_fn_impl(
     };
     let result_kind = ResultKind::from(&function.sig);
 
+    if let Err(error) = scope_deadline_argument(&mut function) {
+        return error.to_compile_error().into();
+    }
+
     let ffi_name = match name_for_meta_key(&item_names, "ffi", || {
         ffi::name_from_ident(&function.sig.ident)
     }) {
@@ -339,6 +352,42 @@ fn bridge_fn_impl(
     .into()
 }
 
+/// If `function` takes a `Deadline` argument, wraps its body in a scope for that deadline.
+///
+/// See the [crate-level documentation](crate#deadlines).
+fn scope_deadline_argument(function: &mut ItemFn) -> Result<()> {
+    let is_deadline = |ty: &Type| match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Deadline"),
+        _ => false,
+    };
+
+    let args = util::extract_arg_names_and_types(&function.sig)?;
+    let mut deadline_args = args.into_iter().filter(|(_, ty)| is_deadline(ty));
+    let Some((name, ty)) = deadline_args.next() else {
+        return Ok(());
+    };
+    if let Some((extra, _)) = deadline_args.next() {
+        return Err(Error::new(
+            extra.span(),
+            "cannot have more than one Deadline parameter",
+        ));
+    }
+
+    let result = util::result_type(&function.sig.output);
+    let stmts = &function.block.stmts;
+    let scoped = if function.sig.asyncness.is_some() {
+        quote!(<#ty>::scope_future::<#result, _>(#name, async move { #stmts }).await)
+    } else {
+        quote!(<#ty>::scope::<#result>(#name, move || { #stmts }))
+    };
+    function.block.stmts = scoped;
+    Ok(())
+}
+
 /// Generates C, Java, and Node entry points for a Rust function that returns a value.
 ///
 /// See the [crate-level documentation](crate) for more information.
@@ -200,9 +200,21 @@ pub(crate) fn bridge_fn(
         #[allow(non_snake_case)]
         #[doc = #ts_signature_comment]
         pub fn #name_with_prefix(
-            mut cx: node::FunctionContext,
+            cx: node::FunctionContext,
         ) -> node::JsResult<node::JsValue> {
-            #body
+            #[inline(always)]
+            fn __bridge_fn_body(
+                mut cx: node::FunctionContext,
+            ) -> node::JsResult<node::JsValue> {
+                #body
+            }
+
+            let __timer = node::CallTimer::start(#name);
+            let __result = __bridge_fn_body(cx);
+            if let Some(__timer) = __timer {
+                __timer.finish(__result.is_err());
+            }
+            __result
         }
 
         #[cfg(feature = "node")]
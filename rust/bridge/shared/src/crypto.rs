@@ -7,8 +7,12 @@ use ::signal_crypto;
 use aes_gcm_siv::aead::generic_array::typenum::Unsigned;
 use aes_gcm_siv::{AeadCore, AeadInPlace, KeyInit};
 use libsignal_bridge_macros::*;
-use libsignal_bridge_types::crypto::{Aes256GcmDecryption, Aes256GcmEncryption, Aes256GcmSiv};
-use signal_crypto::{Aes256Ctr32, CryptographicHash, CryptographicMac, Error, Result};
+use libsignal_bridge_types::crypto::{
+    Aes256GcmDecryption, Aes256GcmEncryption, Aes256GcmSiv, NonceSequence,
+};
+use signal_crypto::{
+    Aes256Ctr32, CryptographicHash, CryptographicMac, Error, NonceStore, Result, NONCE_PREFIX_SIZE,
+};
 
 use crate::support::*;
 use crate::*;
@@ -19,6 +23,7 @@ bridge_handle_fns!(Aes256GcmSiv, clone = false);
 bridge_handle_fns!(Aes256Ctr32, clone = false, node = false);
 bridge_handle_fns!(Aes256GcmEncryption, clone = false, node = false);
 bridge_handle_fns!(Aes256GcmDecryption, clone = false, node = false);
+bridge_handle_fns!(NonceSequence, clone = false, node = false);
 
 #[bridge_fn(node = false)]
 fn Aes256Ctr32_New(key: &[u8], nonce: &[u8], initial_ctr: u32) -> Result<Aes256Ctr32> {
@@ -41,6 +46,20 @@ fn Aes256GcmEncryption_New(
     Aes256GcmEncryption::new(key, nonce, associated_data)
 }
 
+#[bridge_fn(node = false)]
+fn Aes256GcmEncryption_NewWithNonceSequence(
+    nonces: &NonceSequence,
+    nonce_store: &mut dyn NonceStore,
+    associated_data: &[u8],
+) -> Result<Aes256GcmEncryption> {
+    Aes256GcmEncryption::with_nonce_sequence(nonces, nonce_store, associated_data)
+}
+
+#[bridge_fn(node = false)]
+fn Aes256GcmEncryption_GetNonce(gcm: &Aes256GcmEncryption) -> &[u8] {
+    gcm.nonce()
+}
+
 #[bridge_fn(node = false)]
 fn Aes256GcmEncryption_Update(
     gcm: &mut Aes256GcmEncryption,
@@ -58,6 +77,23 @@ fn Aes256GcmEncryption_ComputeTag(gcm: &mut Aes256GcmEncryption) -> Vec<u8> {
     gcm.compute_tag()
 }
 
+#[bridge_fn(node = false)]
+fn NonceSequence_New(
+    key: &[u8],
+    prefix: &[u8],
+    persisted_limit: u64,
+    limit: u64,
+) -> Result<NonceSequence> {
+    let prefix: [u8; NONCE_PREFIX_SIZE] = prefix.try_into().map_err(|_| Error::InvalidNonceSize)?;
+    let nonces = signal_crypto::NonceSequence::new(key, prefix, persisted_limit)?.with_limit(limit);
+    Ok(NonceSequence::new(nonces))
+}
+
+#[bridge_fn(node = false)]
+fn NonceSequence_Remaining(nonces: &NonceSequence) -> u64 {
+    nonces.remaining()
+}
+
 #[bridge_fn(node = false)]
 fn Aes256GcmDecryption_New(
     key: &[u8],
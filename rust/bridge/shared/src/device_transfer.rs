@@ -3,8 +3,10 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use ::device_transfer::session::{Event, Session};
 use ::device_transfer::{self, KeyFormat};
 use libsignal_bridge_macros::*;
+use libsignal_bridge_types::device_transfer::DeviceTransferSession;
 
 // Not used by the Java bridge.
 #[allow(unused_imports)]
@@ -35,3 +37,98 @@ fn DeviceTransfer_GenerateCertificate(
 ) -> Result<Vec<u8>, device_transfer::Error> {
     device_transfer::create_self_signed_cert(private_key, &name, days_to_expire)
 }
+
+bridge_handle_fns!(DeviceTransferSession, clone = false, node = false);
+
+#[bridge_fn(node = false)]
+fn DeviceTransferSession_ForOldDevice(
+    local_certificate: &[u8],
+    payload_length: u64,
+) -> Result<DeviceTransferSession, device_transfer::Error> {
+    Session::for_old_device(local_certificate.to_vec(), payload_length).map(DeviceTransferSession)
+}
+
+#[bridge_fn(node = false)]
+fn DeviceTransferSession_ForNewDevice(
+    local_certificate: &[u8],
+) -> Result<DeviceTransferSession, device_transfer::Error> {
+    Session::for_new_device(local_certificate.to_vec()).map(DeviceTransferSession)
+}
+
+// Returns the payload bytes received, if any. Other events are reflected in the session's state,
+// which the app can check afterwards.
+#[bridge_fn(node = false)]
+fn DeviceTransferSession_Receive(
+    session: &mut DeviceTransferSession,
+    data: &[u8],
+) -> Result<Vec<u8>, device_transfer::Error> {
+    let mut payload = Vec::new();
+    for event in session.0.receive(data)? {
+        match event {
+            Event::PayloadChunk(chunk) => payload.extend(chunk),
+            Event::VerificationCodeReady | Event::PeerConfirmed | Event::Completed => {}
+        }
+    }
+    Ok(payload)
+}
+
+#[bridge_fn(node = false)]
+fn DeviceTransferSession_TakeOutgoing(session: &mut DeviceTransferSession) -> Vec<u8> {
+    session.0.take_outgoing()
+}
+
+#[bridge_fn(node = false)]
+fn DeviceTransferSession_GetState(session: &DeviceTransferSession) -> u8 {
+    session.0.state() as u8
+}
+
+#[bridge_fn(node = false)]
+fn DeviceTransferSession_GetVerificationCode(
+    session: &DeviceTransferSession,
+) -> Result<String, device_transfer::Error> {
+    session.0.verification_code()
+}
+
+#[bridge_fn(node = false)]
+fn DeviceTransferSession_ConfirmVerificationCode(
+    session: &mut DeviceTransferSession,
+) -> Result<(), device_transfer::Error> {
+    session.0.confirm_verification_code()
+}
+
+#[bridge_fn(node = false)]
+fn DeviceTransferSession_SendPayloadChunk(
+    session: &mut DeviceTransferSession,
+    chunk: &[u8],
+) -> Result<(), device_transfer::Error> {
+    session.0.send_payload_chunk(chunk)
+}
+
+#[bridge_fn(node = false)]
+fn DeviceTransferSession_Finish(
+    session: &mut DeviceTransferSession,
+) -> Result<(), device_transfer::Error> {
+    session.0.finish()
+}
+
+#[bridge_fn(node = false)]
+fn DeviceTransferSession_GetBytesTransferred(session: &DeviceTransferSession) -> u64 {
+    session
+        .0
+        .progress()
+        .map_or(0, |progress| progress.transferred)
+}
+
+// Fails if the new device hasn't received the payload length yet.
+#[bridge_fn(node = false)]
+fn DeviceTransferSession_GetPayloadLength(
+    session: &DeviceTransferSession,
+) -> Result<u64, device_transfer::Error> {
+    session
+        .0
+        .progress()
+        .map(|progress| progress.total)
+        .ok_or(device_transfer::Error::InvalidState(
+            "payload length not yet received",
+        ))
+}
@@ -45,6 +45,29 @@ fn HsmEnclaveClient_EstablishedRecv(
     cli.established_recv(received_ciphertext)
 }
 
+#[bridge_fn]
+fn HsmEnclaveClient_EnablePipelining(cli: &mut HsmEnclaveClient, max_in_flight: u32) -> Result<()> {
+    cli.enable_pipelining(max_in_flight.try_into().expect("u32 fits in usize"))
+}
+
+#[bridge_fn]
+fn HsmEnclaveClient_InFlight(cli: &HsmEnclaveClient) -> Result<u32> {
+    Ok(cli
+        .in_flight()?
+        .try_into()
+        .expect("in-flight requests are bounded by a u32 limit"))
+}
+
+#[bridge_fn]
+fn HsmEnclaveClient_LastSentRequestId(cli: &HsmEnclaveClient) -> Result<u64> {
+    Ok(cli.last_sent_request_id()?.into())
+}
+
+#[bridge_fn]
+fn HsmEnclaveClient_LastReceivedRequestId(cli: &HsmEnclaveClient) -> Result<u64> {
+    Ok(cli.last_received_request_id()?.into())
+}
+
 bridge_get!(
     HsmEnclaveClient::initial_request as InitialRequest -> &[u8]
 );
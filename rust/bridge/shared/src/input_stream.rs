@@ -0,0 +1,22 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use libsignal_bridge_macros::*;
+
+use crate::io::AsyncInputStreamCompletion;
+use crate::*;
+
+// Node's InputStream is already asynchronous, so it doesn't need completions.
+bridge_handle_fns!(AsyncInputStreamCompletion, clone = false, node = false);
+
+#[bridge_fn(node = false)]
+fn AsyncInputStreamCompletion_Complete(completion: &AsyncInputStreamCompletion, data: &[u8]) {
+    completion.complete(Ok(data.to_vec()))
+}
+
+#[bridge_fn(node = false)]
+fn AsyncInputStreamCompletion_Fail(completion: &AsyncInputStreamCompletion, message: String) {
+    completion.complete(Err(std::io::Error::new(std::io::ErrorKind::Other, message)))
+}
@@ -39,11 +39,17 @@ pub mod net;
 
 mod account_keys;
 
+mod limits;
+mod manifest;
+mod metrics;
+
 // Desktop does not use SVR
 #[cfg(any(feature = "jni", feature = "ffi"))]
 mod svr2;
 
 pub mod incremental_mac;
+#[cfg(any(feature = "jni", feature = "ffi"))]
+mod input_stream;
 pub mod message_backup;
 pub mod usernames;
 
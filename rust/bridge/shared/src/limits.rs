@@ -0,0 +1,28 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use libsignal_bridge_macros::*;
+use libsignal_core::{set_parse_limits, ParseLimits};
+
+use crate::support::*;
+use crate::*;
+
+#[bridge_fn]
+fn ParseLimits_Set(
+    max_message_size: u64,
+    max_collection_count: u64,
+    max_decompressed_size: u64,
+    max_media_metadata_size: u64,
+    max_retained_size: u64,
+) {
+    let to_usize = |value: u64| usize::try_from(value).unwrap_or(usize::MAX);
+    set_parse_limits(ParseLimits {
+        max_message_size: to_usize(max_message_size),
+        max_collection_count: to_usize(max_collection_count),
+        max_decompressed_size,
+        max_media_metadata_size,
+        max_retained_size,
+    })
+}
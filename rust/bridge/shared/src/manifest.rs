@@ -0,0 +1,53 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! A machine-readable description of every bridged function, generated by build.rs.
+//!
+//! The manifest lists each `bridge_fn` in this crate and in libsignal-bridge-testing with its
+//! argument and result types, whether it's async or cancellable, and which bridges it's exposed
+//! to. It doesn't depend on which bridges are being built, so its hash identifies the bridged API
+//! as a whole. The Java, Swift, and TypeScript
+//! declaration generators record the hash of the checked-in copy, `bridge_manifest.json`, and
+//! each app-language library compares it against [`BridgeManifest_GetSha256`] at startup to catch
+//! a native library built from different sources.
+
+use libsignal_bridge_macros::*;
+
+use crate::*;
+
+/// The generated manifest, as pretty-printed JSON.
+pub const MANIFEST_JSON: &str = include_str!(concat!(env!("OUT_DIR"), "/bridge_manifest.json"));
+
+/// The SHA-256 hash of [`MANIFEST_JSON`], in lowercase hex.
+pub const MANIFEST_SHA256: &str = env!("LIBSIGNAL_BRIDGE_MANIFEST_SHA256");
+
+#[bridge_fn]
+fn BridgeManifest_GetSha256() -> String {
+    MANIFEST_SHA256.to_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Set this environment variable to update `bridge_manifest.json` instead of checking it.
+    const UPDATE_ENV_VAR: &str = "LIBSIGNAL_BRIDGE_UPDATE_MANIFEST";
+
+    #[test]
+    fn checked_in_manifest_is_up_to_date() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("bridge_manifest.json");
+        if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+            std::fs::write(&path, MANIFEST_JSON).expect("can write manifest");
+            return;
+        }
+        let checked_in = std::fs::read_to_string(&path).expect("can read manifest");
+        assert!(
+            checked_in == MANIFEST_JSON,
+            "{} is out of date; re-run this test with {UPDATE_ENV_VAR}=1, then regenerate the \
+             app-language declarations",
+            path.display(),
+        );
+    }
+}
@@ -4,16 +4,21 @@
 //
 
 use libsignal_bridge_macros::*;
-use libsignal_bridge_types::media::SanitizedMetadata;
-use signal_media::sanitize::{mp4, webp};
+use libsignal_bridge_types::media::{Mp4SanitizerOptions, SanitizedMetadata};
+#[cfg(any(feature = "jni", feature = "ffi"))]
+use libsignal_bridge_types::net::TokioAsyncContext;
+use signal_media::sanitize::{mp4, pool, webp};
 
 use crate::io::{AsyncInput, InputStream, SyncInput, SyncInputStream};
+#[cfg(any(feature = "jni", feature = "ffi"))]
+use crate::io::{AsyncInputReader, AsyncInputStream};
 // Not used by the Java bridge.
 #[allow(unused_imports)]
 use crate::support::*;
 use crate::*;
 
 bridge_handle_fns!(SanitizedMetadata);
+bridge_handle_fns!(Mp4SanitizerOptions, clone = false);
 
 /// Exposed so that we have an easy method to invoke from Java to test whether libsignal was
 /// compiled with signal-media.
@@ -30,6 +35,93 @@ async fn Mp4Sanitizer_Sanitize(
     Ok(SanitizedMetadata(metadata))
 }
 
+/// Like `Mp4Sanitizer_Sanitize`, but reads from a stream that completes asynchronously, so no app
+/// thread is blocked while the input is read.
+#[cfg(any(feature = "jni", feature = "ffi"))]
+#[bridge_io(TokioAsyncContext, node = false)]
+async fn Mp4Sanitizer_SanitizeAsync(
+    input: &mut dyn AsyncInputStream,
+    len: u64,
+) -> Result<SanitizedMetadata, mp4::Error> {
+    let input = AsyncInputReader::new(input, len);
+    let metadata = mp4::sanitize(input).await?;
+    Ok(SanitizedMetadata(metadata))
+}
+
+#[bridge_fn]
+async fn Mp4Sanitizer_SanitizeWithOptions(
+    input: &mut dyn InputStream,
+    len: u64,
+    options: &Mp4SanitizerOptions,
+) -> Result<SanitizedMetadata, mp4::Error> {
+    let options = options.lock().clone();
+    let input = AsyncInput::new(input, len);
+    let metadata = mp4::sanitize_with_options(input, &options).await?;
+    Ok(SanitizedMetadata(metadata))
+}
+
+#[bridge_fn]
+fn Mp4SanitizerOptions_New() -> Mp4SanitizerOptions {
+    Mp4SanitizerOptions::default()
+}
+
+/// Turns stripping of metadata boxes on or off.
+///
+/// When turned on, the boxes listed in [`mp4::default_preserved_metadata_boxes`] are kept; adjust
+/// them with `Mp4SanitizerOptions_SetMetadataBoxPreserved`.
+#[bridge_fn]
+fn Mp4SanitizerOptions_SetMetadataStripped(options: &Mp4SanitizerOptions, stripped: bool) {
+    let mut options = options.lock();
+    let preserved_boxes = &mut options.preserved_metadata_boxes;
+    if !stripped {
+        *preserved_boxes = None;
+    } else if preserved_boxes.is_none() {
+        *preserved_boxes = Some(mp4::default_preserved_metadata_boxes());
+    }
+}
+
+/// Sets whether a metadata box is kept. This also turns on stripping of unlisted metadata boxes.
+#[bridge_fn]
+fn Mp4SanitizerOptions_SetMetadataBoxPreserved(
+    options: &Mp4SanitizerOptions,
+    box_type: &[u8; 4],
+    preserved: bool,
+) {
+    let mut options = options.lock();
+    let preserved_boxes = options
+        .preserved_metadata_boxes
+        .get_or_insert_with(mp4::default_preserved_metadata_boxes);
+    if preserved {
+        preserved_boxes.insert(*box_type);
+    } else {
+        preserved_boxes.remove(box_type);
+    }
+}
+
+#[bridge_fn]
+fn Mp4SanitizerOptions_SetCodecPolicy(
+    options: &Mp4SanitizerOptions,
+    allow_hevc: bool,
+    allow_av1: bool,
+    allow_opus: bool,
+) {
+    options.lock().codec_policy = Some(mp4::CodecPolicy {
+        allow_hevc,
+        allow_av1,
+        allow_opus,
+    });
+}
+
+/// Sets how many bytes of metadata buffers to keep around for reuse between sanitizer calls.
+///
+/// Defaults to 0, i.e. no buffers are kept.
+#[bridge_fn]
+fn Mp4Sanitizer_SetMaxPooledBufferBytes(max_bytes: u64) {
+    pool::set_max_retained_bytes(usize::try_from(max_bytes).unwrap_or(usize::MAX))
+}
+
+/// There's no async variant of this: `webpsan` only parses synchronously, and this runs on the
+/// caller's thread rather than blocking the async runtime.
 #[bridge_fn]
 fn WebpSanitizer_Sanitize(input: &mut dyn SyncInputStream) -> Result<(), webp::Error> {
     let input = SyncInput::new(input, None);
@@ -3,18 +3,26 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use futures_util::AsyncRead;
 use libsignal_bridge_macros::*;
 use libsignal_bridge_types::message_backup::*;
+#[cfg(any(feature = "jni", feature = "ffi"))]
+use libsignal_bridge_types::net::TokioAsyncContext;
 use libsignal_message_backup::backup::Purpose;
-use libsignal_message_backup::frame::LimitedReaderFactory;
+use libsignal_message_backup::finding::ValidationFinding;
+use libsignal_message_backup::frame::{LimitedReaderFactory, PaddingPolicy};
 use libsignal_message_backup::{BackupReader, ReadResult};
 use libsignal_protocol::Aci;
+use mediasan_common::AsyncSkip;
 
 use crate::io::{AsyncInput, InputStream};
+#[cfg(any(feature = "jni", feature = "ffi"))]
+use crate::io::{AsyncInputReader, AsyncInputStream};
 use crate::support::*;
 use crate::*;
 
 bridge_handle_fns!(MessageBackupKey, clone = false);
+bridge_handle_fns!(BackupPaddingPolicy, clone = false);
 bridge_handle_fns!(
     MessageBackupValidationOutcome,
     clone = false,
@@ -68,6 +76,17 @@ fn MessageBackupValidationOutcome_getUnknownFields(
         .collect()
 }
 
+#[bridge_fn(jni = false, node = false)]
+fn MessageBackupValidationOutcome_getFindings(
+    outcome: &MessageBackupValidationOutcome,
+) -> Box<[ValidationFinding]> {
+    outcome.findings.clone().into_boxed_slice()
+}
+
+/// Validates a backup read from a pair of streams over the same contents.
+///
+/// `deadline` is checked before every read, so validation gives up within roughly one frame of
+/// the deadline passing.
 #[bridge_fn]
 async fn MessageBackupValidator_Validate(
     key: &MessageBackupKey,
@@ -75,23 +94,68 @@ async fn MessageBackupValidator_Validate(
     second_stream: &mut dyn InputStream,
     len: u64,
     purpose: AsType<Purpose, u8>,
-) -> Result<MessageBackupValidationOutcome, std::io::Error> {
+    deadline: Deadline,
+) -> Result<MessageBackupValidationOutcome, DeadlineOr<std::io::Error>> {
     let streams = [
         AsyncInput::new(first_stream, len),
         AsyncInput::new(second_stream, len),
     ];
-    let factory = LimitedReaderFactory::new(streams);
+    Ok(validate(
+        key,
+        LimitedReaderFactory::new(streams),
+        purpose.into_inner(),
+    )
+    .await?)
+}
+
+/// Like `MessageBackupValidator_Validate`, but reads from streams that complete asynchronously, so
+/// neither an app thread nor a runtime worker is blocked while waiting on the app's I/O.
+///
+/// As well as being checked before every read, `deadline` interrupts a read the app has not yet
+/// completed.
+#[cfg(any(feature = "jni", feature = "ffi"))]
+#[bridge_io(TokioAsyncContext, node = false)]
+async fn MessageBackupValidator_ValidateAsync(
+    key: &MessageBackupKey,
+    first_stream: &mut dyn AsyncInputStream,
+    second_stream: &mut dyn AsyncInputStream,
+    len: u64,
+    purpose: AsType<Purpose, u8>,
+    deadline: Deadline,
+) -> Result<MessageBackupValidationOutcome, DeadlineOr<std::io::Error>> {
+    let streams = [
+        AsyncInputReader::new(first_stream, len),
+        AsyncInputReader::new(second_stream, len),
+    ];
+    Ok(validate(
+        key,
+        LimitedReaderFactory::new(streams),
+        purpose.into_inner(),
+    )
+    .await?)
+}
 
-    let (error, found_unknown_fields) =
-        match BackupReader::new_encrypted_compressed(&key.0, factory, purpose.into_inner()).await {
-            Err(e) => (Some(e.into()), Vec::new()),
+async fn validate<R: AsyncRead + AsyncSkip + Unpin>(
+    key: &MessageBackupKey,
+    factory: LimitedReaderFactory<R, 2>,
+    purpose: Purpose,
+) -> Result<MessageBackupValidationOutcome, std::io::Error> {
+    let (error, found_unknown_fields, findings) =
+        match BackupReader::new_encrypted_compressed(&key.0, factory, purpose).await {
+            Err(e) => {
+                let findings = vec![ValidationFinding::from_frame_error(&e)];
+                (Some(e.into()), Vec::new(), findings)
+            }
             Ok(reader) => {
+                let read_result = reader.validate_all().await;
+                let findings = read_result.findings();
                 let ReadResult {
                     result,
                     found_unknown_fields,
-                } = reader.validate_all().await;
+                    error_frame_index: _,
+                } = read_result;
 
-                (result.err().map(Into::into), found_unknown_fields)
+                (result.err().map(Into::into), found_unknown_fields, findings)
             }
         };
 
@@ -105,5 +169,45 @@ async fn MessageBackupValidator_Validate(
     Ok(MessageBackupValidationOutcome {
         error_message,
         found_unknown_fields,
+        findings,
     })
 }
+
+#[bridge_fn]
+fn MessageBackup_GetPaddedSize(compressed_len: u64) -> u64 {
+    PaddingPolicy::DEFAULT.padded_size(compressed_len)
+}
+
+#[bridge_fn]
+fn MessageBackup_GetEncryptedSize(compressed_len: u64) -> u64 {
+    PaddingPolicy::DEFAULT.encrypted_size(compressed_len)
+}
+
+#[bridge_fn]
+fn BackupPaddingPolicy_Default() -> BackupPaddingPolicy {
+    BackupPaddingPolicy(PaddingPolicy::DEFAULT)
+}
+
+/// Creates a policy that pads to buckets `growth_factor_percent` percent apart, but never below
+/// `min_size` bytes.
+///
+/// A growth factor of 100 percent or less disables bucketing, so `(100, 0)` adds no padding at all.
+#[bridge_fn]
+fn BackupPaddingPolicy_New(growth_factor_percent: u32, min_size: u64) -> BackupPaddingPolicy {
+    // The growth factor is passed as a percentage because not all bridges support floating-point
+    // values.
+    BackupPaddingPolicy(PaddingPolicy::Bucketed {
+        growth_factor: f64::from(growth_factor_percent) / 100.0,
+        min_size,
+    })
+}
+
+#[bridge_fn]
+fn BackupPaddingPolicy_GetPaddedSize(policy: &BackupPaddingPolicy, compressed_len: u64) -> u64 {
+    policy.0.padded_size(compressed_len)
+}
+
+#[bridge_fn]
+fn BackupPaddingPolicy_GetEncryptedSize(policy: &BackupPaddingPolicy, compressed_len: u64) -> u64 {
+    policy.0.encrypted_size(compressed_len)
+}
@@ -0,0 +1,24 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use libsignal_bridge_macros::*;
+
+use crate::support::metrics;
+use crate::*;
+
+#[bridge_fn]
+fn BridgeMetrics_SetEnabled(enabled: bool) {
+    metrics::set_enabled(enabled)
+}
+
+#[bridge_fn]
+fn BridgeMetrics_Snapshot() -> String {
+    metrics::snapshot_json()
+}
+
+#[bridge_fn]
+fn BridgeMetrics_Reset() {
+    metrics::reset()
+}
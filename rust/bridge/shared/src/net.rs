@@ -5,14 +5,24 @@
 
 use std::convert::TryInto as _;
 use std::num::{NonZeroU16, NonZeroU32};
+use std::time::{Duration, UNIX_EPOCH};
 
 use base64::prelude::{Engine, BASE64_STANDARD};
 use libsignal_bridge_macros::{bridge_fn, bridge_io};
 use libsignal_bridge_types::net::Svr3Clients;
-pub use libsignal_bridge_types::net::{ConnectionManager, Environment, TokioAsyncContext};
+pub use libsignal_bridge_types::net::{
+    ConnectionManager, ConnectionRoute, Environment, NetService, TokioAsyncContext,
+};
 use libsignal_net::auth::Auth;
+use libsignal_net::env::Svr3Env;
+use libsignal_net::infra::certs::SpkiPins;
+use libsignal_net::infra::connection_manager::RetryPolicy;
+use libsignal_net::infra::ws::WebSocketLimits;
 use libsignal_net::svr3::traits::*;
-use libsignal_net::svr3::{self, migrate_backup, restore_with_fallback, OpaqueMaskedShareSet};
+use libsignal_net::svr3::{
+    self, migrate_backup, restore_with_fallback, OpaqueMaskedShareSet, ShareDistribution,
+};
+use libsignal_protocol::{SignalProtocolError, Timestamp};
 use rand::rngs::OsRng;
 
 use crate::support::*;
@@ -20,6 +30,9 @@ use crate::*;
 
 pub(crate) mod cdsi;
 pub(crate) mod chat;
+pub(crate) mod key_transparency;
+pub(crate) mod receipts;
+pub(crate) mod storage_service;
 mod tokio;
 
 bridge_handle_fns!(ConnectionManager, clone = false);
@@ -62,11 +75,132 @@ fn ConnectionManager_set_censorship_circumvention_enabled(
     connection_manager.set_censorship_circumvention_enabled(enabled)
 }
 
+#[bridge_fn]
+fn ConnectionManager_set_chat_websocket_limits(
+    connection_manager: &ConnectionManager,
+    max_frame_size: u32,
+    max_message_size: u32,
+    max_pending_send_bytes: u32,
+) {
+    connection_manager.set_chat_websocket_limits(WebSocketLimits {
+        max_frame_size: max_frame_size as usize,
+        max_message_size: max_message_size as usize,
+        max_pending_send_bytes: max_pending_send_bytes as usize,
+    })
+}
+
+#[bridge_fn]
+fn ConnectionManager_set_retry_policy(
+    connection_manager: &ConnectionManager,
+    service: AsType<NetService, u8>,
+    initial_delay_millis: u32,
+    multiplier_percent: u32,
+    jitter_percent: u32,
+    max_delay_millis: u32,
+    max_attempts: u32,
+) {
+    // Fractions are passed as percentages because not all bridges support floating-point values.
+    // A max_attempts of 0 means "no limit".
+    connection_manager.set_retry_policy(
+        service.into_inner(),
+        RetryPolicy {
+            initial_delay: Duration::from_millis(initial_delay_millis.into()),
+            multiplier: f64::from(multiplier_percent) / 100.0,
+            jitter: f64::from(jitter_percent.min(100)) / 100.0,
+            max_delay: Duration::from_millis(max_delay_millis.into()),
+            max_attempts: NonZeroU16::new(u16::try_from(max_attempts).unwrap_or(u16::MAX)),
+        },
+    )
+}
+
+#[bridge_fn]
+fn ConnectionManager_set_certificate_pins(
+    connection_manager: &ConnectionManager,
+    service: AsType<NetService, u8>,
+    pins: &[u8],
+) -> Result<(), std::io::Error> {
+    // The pins are concatenated SHA-256 hashes; not all bridges can pass arrays of arrays. An empty
+    // list stops requiring pins.
+    let chunks = pins.chunks_exact(32);
+    if !chunks.remainder().is_empty() {
+        return Err(std::io::Error::from(std::io::ErrorKind::InvalidInput));
+    }
+    let pins = SpkiPins::new(chunks.map(|pin| pin.try_into().expect("correct length")));
+    connection_manager.set_certificate_pins(service.into_inner(), pins);
+    Ok(())
+}
+
+#[bridge_fn]
+fn ConnectionManager_set_route_order(
+    connection_manager: &ConnectionManager,
+    order: &[u8],
+) -> Result<(), SignalProtocolError> {
+    // Each byte is a ConnectionRoute; not all bridges can pass arrays of enums.
+    let order = order
+        .iter()
+        .map(|&route| {
+            ConnectionRoute::try_from(route).map_err(|_| {
+                SignalProtocolError::InvalidArgument(format!("invalid connection route {route}"))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    connection_manager.set_route_order(&order);
+    Ok(())
+}
+
+#[bridge_fn]
+fn ConnectionManager_get_route_order(connection_manager: &ConnectionManager) -> Vec<u8> {
+    connection_manager
+        .route_order()
+        .iter()
+        .map(|&route| u8::from(route))
+        .collect()
+}
+
+/// Returns the proxy as `host:port`, or null if connections are direct.
+#[bridge_fn]
+fn ConnectionManager_get_proxy(connection_manager: &ConnectionManager) -> Option<String> {
+    connection_manager.proxy()
+}
+
+#[bridge_fn]
+fn ConnectionManager_export_route_state(
+    connection_manager: &ConnectionManager,
+    key: &[u8; 32],
+) -> Vec<u8> {
+    connection_manager.export_route_state(key, &mut rand::rngs::OsRng)
+}
+
+#[bridge_fn]
+fn ConnectionManager_import_route_state(
+    connection_manager: &ConnectionManager,
+    key: &[u8; 32],
+    state: &[u8],
+) -> Result<(), std::io::Error> {
+    connection_manager
+        .import_route_state(key, state)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
 #[bridge_fn]
 fn ConnectionManager_on_network_change(connection_manager: &ConnectionManager) {
     connection_manager.on_network_change()
 }
 
+#[bridge_fn]
+fn ConnectionManager_server_time(connection_manager: &ConnectionManager) -> Timestamp {
+    let since_epoch = connection_manager
+        .server_time()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Timestamp::from_epoch_millis(
+        since_epoch
+            .as_millis()
+            .try_into()
+            .expect("server time fits in a u64"),
+    )
+}
+
 #[bridge_fn]
 fn CreateOTP(username: String, secret: &[u8]) -> String {
     Auth::otp(&username, secret, std::time::SystemTime::now())
@@ -101,6 +235,62 @@ async fn Svr3Backup(
     Ok(share_set.serialize().expect("can serialize the share set"))
 }
 
+#[bridge_io(TokioAsyncContext)]
+async fn Svr3BackupWithDistribution(
+    connection_manager: &ConnectionManager,
+    secret: Box<[u8]>,
+    password: String,
+    max_tries: AsType<NonZeroU32, u32>,
+    server_ids: Box<[u8]>,
+    threshold: u32,
+    username: String,         // hex-encoded uid
+    enclave_password: String, // timestamp:otp(...)
+) -> Result<Vec<u8>, svr3::Error> {
+    let secret = secret
+        .as_ref()
+        .try_into()
+        .expect("can only backup 32 bytes");
+    let distribution = ShareDistribution::new::<Svr3Env<'static>>(
+        server_ids.iter().copied().map(u64::from),
+        threshold.try_into().expect("u32 fits in usize"),
+    )?;
+    let mut rng = OsRng;
+
+    // Like Svr3Backup, this only ever writes to the current set of enclaves.
+    let client = Svr3Clients::new(connection_manager, username, enclave_password).current;
+    let share_set = client
+        .backup_with_distribution(
+            &password,
+            secret,
+            max_tries.into_inner(),
+            &distribution,
+            &mut rng,
+        )
+        .await?;
+    Ok(share_set.serialize().expect("can serialize the share set"))
+}
+
+#[bridge_fn]
+fn Svr3GetShareSetServerIds(share_set: &[u8]) -> Result<Vec<u8>, svr3::Error> {
+    let layout = OpaqueMaskedShareSet::deserialize(share_set)?.layout();
+    layout
+        .server_ids()
+        .iter()
+        .map(|&id| {
+            u8::try_from(id).map_err(|_| svr3::Error::Protocol(format!("server ID {id} too large")))
+        })
+        .collect()
+}
+
+#[bridge_fn]
+fn Svr3GetShareSetThreshold(share_set: &[u8]) -> Result<u32, svr3::Error> {
+    let layout = OpaqueMaskedShareSet::deserialize(share_set)?.layout();
+    Ok(layout
+        .threshold()
+        .try_into()
+        .expect("share sets have few servers"))
+}
+
 #[bridge_io(TokioAsyncContext, node = false)]
 async fn Svr3Migrate(
     connection_manager: &ConnectionManager,
@@ -4,13 +4,18 @@
 //
 
 use std::convert::TryInto as _;
+use std::time::Duration;
 
 use libsignal_bridge_macros::{bridge_fn, bridge_io};
-use libsignal_bridge_types::net::cdsi::{CdsiLookup, LookupRequest};
+use libsignal_bridge_types::net::cdsi::{
+    CdsiConnectionPool, CdsiContactChanges, CdsiLookup, LookupRequest,
+};
 use libsignal_bridge_types::net::{ConnectionManager, TokioAsyncContext};
 use libsignal_core::E164;
 use libsignal_net::auth::Auth;
-use libsignal_net::cdsi::{self, AciAndAccessKey, LookupResponse};
+use libsignal_net::cdsi::{
+    self, AciAndAccessKey, ContactChange, ContactIds, LookupResponse, LookupResponseEntry,
+};
 use libsignal_protocol::{Aci, SignalProtocolError};
 
 use crate::support::*;
@@ -33,6 +38,36 @@ fn LookupRequest_addPreviousE164(request: &LookupRequest, e164: E164) {
     request.lock().prev_e164s.push(e164)
 }
 
+/// Normalizes each UTF-8 phone number in `raw` and adds it as a new E164
+/// unless it's already present.
+///
+/// Returns the normalized numbers in input order, so callers can map lookup
+/// results back to their original entries. An input that isn't a valid phone
+/// number maps to an empty string.
+#[bridge_fn]
+fn LookupRequest_addRawE164s(
+    request: &LookupRequest,
+    raw: Vec<&[u8]>,
+    region_calling_code: u32,
+) -> Result<Box<[String]>, SignalProtocolError> {
+    let region = crate::protocol::region_hint(region_calling_code)?;
+    let raw = raw
+        .into_iter()
+        .enumerate()
+        .map(|(i, raw)| {
+            std::str::from_utf8(raw).map_err(|_| {
+                SignalProtocolError::InvalidArgument(format!("invalid UTF-8 at index {i}"))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mapping = request.lock().add_raw_e164s(raw, region);
+    Ok(mapping
+        .normalized
+        .into_iter()
+        .map(|e164| e164.map(|e164| e164.to_string()).unwrap_or_default())
+        .collect())
+}
+
 #[bridge_fn]
 fn LookupRequest_setToken(request: &LookupRequest, token: &[u8]) {
     request.lock().token = token.into();
@@ -76,6 +111,28 @@ async fn CdsiLookup_new(
     CdsiLookup::new(connection_manager, auth, request).await
 }
 
+bridge_handle_fns!(CdsiConnectionPool, clone = false);
+
+#[bridge_fn]
+fn CdsiConnectionPool_new(idle_timeout_millis: u32) -> CdsiConnectionPool {
+    CdsiConnectionPool::new(Duration::from_millis(idle_timeout_millis.into()))
+}
+
+#[bridge_io(TokioAsyncContext)]
+async fn CdsiLookup_newPooled(
+    connection_manager: &ConnectionManager,
+    username: String,
+    password: String,
+    request: &LookupRequest,
+    pool: &CdsiConnectionPool,
+    prepare_next: bool,
+) -> Result<CdsiLookup, cdsi::LookupError> {
+    let request = std::mem::take(&mut *request.lock());
+    let auth = Auth { username, password };
+
+    CdsiLookup::new_pooled(connection_manager, auth, request, pool, prepare_next).await
+}
+
 #[bridge_fn]
 fn CdsiLookup_token(lookup: &CdsiLookup) -> &[u8] {
     &lookup.token.0
@@ -89,3 +146,92 @@ async fn CdsiLookup_complete(lookup: &CdsiLookup) -> Result<LookupResponse, cdsi
         .collect()
         .await
 }
+
+bridge_handle_fns!(CdsiContactChanges, clone = false);
+
+/// Compares lookup results with the client's existing contact mapping.
+///
+/// `existing` and `response` are both packed e164/PNI/ACI triples, as used by
+/// the CDSI server, with a nil UUID for a missing service ID.
+#[bridge_fn]
+fn LookupResponse_reconcile(
+    existing: &[u8],
+    response: &[u8],
+) -> Result<CdsiContactChanges, SignalProtocolError> {
+    let parse = |name: &str, triples: &[u8]| {
+        LookupResponseEntry::parse_packed(triples).map_err(|_| {
+            SignalProtocolError::InvalidArgument(format!("{name} has wrong number of bytes"))
+        })
+    };
+    let existing = parse("existing", existing)?;
+    let response = LookupResponse {
+        records: parse("response", response)?,
+        debug_permits_used: 0,
+    };
+
+    Ok(CdsiContactChanges(
+        response.reconcile(
+            existing
+                .into_iter()
+                .map(|LookupResponseEntry { e164, aci, pni }| (e164, ContactIds { aci, pni })),
+        ),
+    ))
+}
+
+fn change_at(changes: &CdsiContactChanges, index: u32) -> &ContactChange {
+    changes
+        .0
+        .get(usize::try_from(index).expect("u32 fits in usize"))
+        .expect("index in range")
+}
+
+#[bridge_fn]
+fn CdsiContactChanges_Count(changes: &CdsiContactChanges) -> u32 {
+    changes.0.len().try_into().expect("too many changes")
+}
+
+/// Returns the [`ContactChangeKind`] of the change at `index`.
+#[bridge_fn]
+fn CdsiContactChanges_GetKind(changes: &CdsiContactChanges, index: u32) -> u8 {
+    let kind = match change_at(changes, index) {
+        ContactChange::NewAci { .. } => ContactChangeKind::NewAci,
+        ContactChange::ChangedPni { .. } => ContactChangeKind::ChangedPni,
+        ContactChange::Removed { .. } => ContactChangeKind::Removed,
+    };
+    kind as u8
+}
+
+#[bridge_fn]
+fn CdsiContactChanges_GetE164(changes: &CdsiContactChanges, index: u32) -> String {
+    let (ContactChange::NewAci { e164, .. }
+    | ContactChange::ChangedPni { e164, .. }
+    | ContactChange::Removed { e164, .. }) = change_at(changes, index);
+    e164.to_string()
+}
+
+/// Returns the new ACI of a `NewAci` change, as a service ID string.
+#[bridge_fn]
+fn CdsiContactChanges_GetAci(changes: &CdsiContactChanges, index: u32) -> Option<String> {
+    match change_at(changes, index) {
+        ContactChange::NewAci { aci, .. } => Some(aci.service_id_string()),
+        ContactChange::ChangedPni { .. } | ContactChange::Removed { .. } => None,
+    }
+}
+
+/// Returns the new PNI of a `ChangedPni` change, as a service ID string, or
+/// `None` if the PNI was cleared.
+#[bridge_fn]
+fn CdsiContactChanges_GetPni(changes: &CdsiContactChanges, index: u32) -> Option<String> {
+    match change_at(changes, index) {
+        ContactChange::ChangedPni { pni, .. } => pni.map(|pni| pni.service_id_string()),
+        ContactChange::NewAci { .. } | ContactChange::Removed { .. } => None,
+    }
+}
+
+/// The values returned by `CdsiContactChanges_GetKind`.
+#[repr(u8)]
+enum ContactChangeKind {
+    NewAci = 0,
+    ChangedPni = 1,
+    Removed = 2,
+}
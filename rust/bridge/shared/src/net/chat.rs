@@ -3,8 +3,9 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
+use ::zkgroup;
 use http::uri::InvalidUri;
 use http::{HeaderName, HeaderValue, StatusCode};
 use libsignal_bridge_macros::{bridge_fn, bridge_io};
@@ -12,9 +13,13 @@ use libsignal_bridge_types::net::chat::*;
 use libsignal_bridge_types::net::{ConnectionManager, TokioAsyncContext};
 use libsignal_bridge_types::support::AsType;
 use libsignal_net::auth::Auth;
+use libsignal_net::chat::background::BackgroundFlushReport;
 use libsignal_net::chat::{
     self, ChatServiceError, DebugInfo as ChatServiceDebugInfo, Request, Response as ChatResponse,
 };
+use libsignal_protocol::{Aci, SenderCertificate};
+use zkgroup::profiles::ProfileKey;
+use zkgroup::{ServerPublicParams, Timestamp, RANDOMNESS_LEN};
 
 use crate::support::*;
 use crate::*;
@@ -91,6 +96,7 @@ async fn ChatService_disconnect_auth(chat: &AuthChat) {
 async fn ChatService_connect_unauth(
     chat: &UnauthChat,
 ) -> Result<ChatServiceDebugInfo, ChatServiceError> {
+    chat.resume_from_background();
     chat.service.0.connect_unauthenticated().await
 }
 
@@ -98,6 +104,7 @@ async fn ChatService_connect_unauth(
 async fn ChatService_connect_auth(
     chat: &AuthChat,
 ) -> Result<ChatServiceDebugInfo, ChatServiceError> {
+    chat.resume_from_background();
     chat.service.0.connect_authenticated().await
 }
 
@@ -114,10 +121,16 @@ async fn ChatService_unauth_send(
         headers,
         body: http_request.body.clone(),
     };
-    chat.service
-        .0
-        .send_unauthenticated(request, Duration::from_millis(timeout_millis.into()))
-        .await
+    let request_sent = SystemTime::now();
+    let response = chat
+        .tracked_request(
+            chat.service
+                .0
+                .send_unauthenticated(request, Duration::from_millis(timeout_millis.into())),
+        )
+        .await?;
+    chat.record_server_time(request_sent, &response);
+    Ok(response)
 }
 
 #[bridge_io(TokioAsyncContext)]
@@ -133,16 +146,23 @@ async fn ChatService_unauth_send_and_debug(
         headers,
         body: http_request.body.clone(),
     };
-    let (result, debug_info) = chat
-        .service
-        .0
-        .send_unauthenticated_and_debug(request, Duration::from_millis(timeout_millis.into()))
-        .await;
-
-    result.map(|response| ResponseAndDebugInfo {
-        response,
-        debug_info,
+    let request_sent = SystemTime::now();
+    chat.tracked_request(async {
+        let (result, debug_info) = chat
+            .service
+            .0
+            .send_unauthenticated_and_debug(request, Duration::from_millis(timeout_millis.into()))
+            .await;
+        if let Ok(response) = &result {
+            chat.record_server_time(request_sent, response);
+        }
+
+        result.map(|response| ResponseAndDebugInfo {
+            response,
+            debug_info,
+        })
     })
+    .await
 }
 
 #[bridge_io(TokioAsyncContext)]
@@ -158,10 +178,79 @@ async fn ChatService_auth_send(
         headers,
         body: http_request.body.clone(),
     };
-    chat.service
+    let request_sent = SystemTime::now();
+    let response = chat
+        .tracked_request(
+            chat.service
+                .0
+                .send_authenticated(request, Duration::from_millis(timeout_millis.into())),
+        )
+        .await?;
+    chat.record_server_time(request_sent, &response);
+    Ok(response)
+}
+
+#[bridge_io(TokioAsyncContext)]
+async fn ChatService_auth_fetch_sender_certificate(
+    chat: &AuthChat,
+    include_e164: bool,
+    timeout_millis: u32,
+) -> Result<SenderCertificate, ChatServiceError> {
+    chat.tracked_request(
+        chat.service
+            .0
+            .fetch_sender_certificate(include_e164, Duration::from_millis(timeout_millis.into())),
+    )
+    .await
+}
+
+#[bridge_io(TokioAsyncContext)]
+async fn ChatService_auth_fetch_profile_key_credential(
+    chat: &AuthChat,
+    server_public_params: &ServerPublicParams,
+    randomness: &[u8; RANDOMNESS_LEN],
+    aci: Aci,
+    profile_key: Serialized<ProfileKey>,
+    current_time_in_seconds: Timestamp,
+    timeout_millis: u32,
+) -> Result<Vec<u8>, ChatServiceError> {
+    let credential = chat
+        .service
         .0
-        .send_authenticated(request, Duration::from_millis(timeout_millis.into()))
-        .await
+        .fetch_profile_key_credential(
+            server_public_params,
+            aci,
+            profile_key.into_inner(),
+            *randomness,
+            current_time_in_seconds,
+            Duration::from_millis(timeout_millis.into()),
+        )
+        .await?;
+    Ok(zkgroup::serialize(&credential))
+}
+
+#[bridge_io(TokioAsyncContext)]
+async fn ChatService_prepare_for_background_unauth(
+    chat: &UnauthChat,
+    budget_millis: u32,
+) -> BackgroundFlushReport {
+    let report = chat
+        .flush_for_background(Duration::from_millis(budget_millis.into()))
+        .await;
+    chat.service.0.disconnect().await;
+    report
+}
+
+#[bridge_io(TokioAsyncContext)]
+async fn ChatService_prepare_for_background_auth(
+    chat: &AuthChat,
+    budget_millis: u32,
+) -> BackgroundFlushReport {
+    let report = chat
+        .flush_for_background(Duration::from_millis(budget_millis.into()))
+        .await;
+    chat.service.0.disconnect().await;
+    report
 }
 
 #[bridge_io(TokioAsyncContext)]
@@ -177,16 +266,23 @@ async fn ChatService_auth_send_and_debug(
         headers,
         body: http_request.body.clone(),
     };
-    let (result, debug_info) = chat
-        .service
-        .0
-        .send_authenticated_and_debug(request, Duration::from_millis(timeout_millis.into()))
-        .await;
-
-    result.map(|response| ResponseAndDebugInfo {
-        response,
-        debug_info,
+    let request_sent = SystemTime::now();
+    chat.tracked_request(async {
+        let (result, debug_info) = chat
+            .service
+            .0
+            .send_authenticated_and_debug(request, Duration::from_millis(timeout_millis.into()))
+            .await;
+        if let Ok(response) = &result {
+            chat.record_server_time(request_sent, response);
+        }
+
+        result.map(|response| ResponseAndDebugInfo {
+            response,
+            debug_info,
+        })
     })
+    .await
 }
 
 #[bridge_fn(jni = false)]
@@ -223,6 +319,13 @@ fn ChatService_SetListenerUnauth(
 
 bridge_handle_fns!(ServerMessageAck, clone = false);
 
+bridge_handle_fns!(ChatListenerTask, clone = false, jni = false, node = false);
+
+#[bridge_fn(jni = false, node = false)]
+fn ChatListenerTask_Run(task: &ChatListenerTask) {
+    task.run()
+}
+
 #[bridge_io(TokioAsyncContext, node = false)]
 async fn ServerMessageAck_Send(ack: &ServerMessageAck) -> Result<(), ChatServiceError> {
     let future = ack.take().expect("a message is only acked once");
@@ -0,0 +1,80 @@
+//
+// Copyright 2026 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Key transparency searches on an authenticated chat connection.
+//!
+//! Verified results are kept in a [`KeyTransparencySearchCache`], which is checked before any
+//! request is sent. One cache can be shared between connections.
+
+use std::time::Duration;
+
+use libsignal_bridge_macros::{bridge_fn, bridge_io};
+use libsignal_bridge_types::net::chat::{AuthChat, KeyTransparencySearchCache};
+use libsignal_bridge_types::net::TokioAsyncContext;
+use libsignal_keytrans::{DeploymentMode, PublicConfig, SearchRequest, VerifyingKey, VrfPublicKey};
+use libsignal_net::chat::key_transparency::SearchCache;
+use libsignal_net::chat::ChatServiceError;
+
+use crate::support::*;
+use crate::*;
+
+bridge_handle_fns!(KeyTransparencySearchCache, clone = false);
+
+/// Creates a cache that keeps verified results for `ttl_seconds`, for at most `max_entries` search
+/// keys.
+#[bridge_fn]
+fn KeyTransparencySearchCache_New(
+    ttl_seconds: u32,
+    max_entries: u32,
+) -> KeyTransparencySearchCache {
+    KeyTransparencySearchCache(SearchCache::new(
+        Duration::from_secs(ttl_seconds.into()),
+        max_entries.try_into().expect("u32 fits in usize"),
+    ))
+}
+
+/// Searches the key transparency log for `search_key`, returning the value it maps to.
+///
+/// `cache` is checked first, and a verified response from the server is added to it. The keys are
+/// the log's signature key, VRF key, and third-party auditor key.
+#[bridge_io(TokioAsyncContext)]
+async fn ChatService_auth_search_key_transparency(
+    chat: &AuthChat,
+    cache: &KeyTransparencySearchCache,
+    signature_key: &[u8; 32],
+    vrf_key: &[u8; 32],
+    auditor_key: &[u8; 32],
+    search_key: Box<[u8]>,
+    timeout_millis: u32,
+) -> Result<Vec<u8>, ChatServiceError> {
+    let config = PublicConfig {
+        mode: DeploymentMode::ThirdPartyAuditing(
+            VerifyingKey::from_bytes(auditor_key).expect("valid auditor key"),
+        ),
+        signature_key: VerifyingKey::from_bytes(signature_key).expect("valid signature key"),
+        vrf_key: VrfPublicKey::try_from(*vrf_key).expect("valid VRF key"),
+    };
+    let request = SearchRequest {
+        search_key: search_key.into_vec(),
+        version: None,
+        consistency: None,
+        mapped_value: vec![],
+        unidentified_access_key: None,
+    };
+
+    let result = chat
+        .tracked_request(chat.service.0.search_key_transparency(
+            &config,
+            request,
+            &cache.0,
+            Duration::from_millis(timeout_millis.into()),
+        ))
+        .await?;
+    // Verification fails without a value, so this can only be missing for a malformed response.
+    result.value.ok_or_else(|| {
+        log::warn!("verified key transparency search had no value");
+        ChatServiceError::IncomingDataInvalid
+    })
+}
@@ -0,0 +1,69 @@
+//
+// Copyright 2026 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Batching outgoing receipts on an authenticated chat connection.
+//!
+//! The app still encrypts and sends each receipt message itself; the connection only decides which
+//! message timestamps go together.
+
+use libsignal_bridge_macros::bridge_fn;
+use libsignal_bridge_types::net::chat::{AuthChat, ReceiptBatch};
+use libsignal_bridge_types::support::AsType;
+use libsignal_net::chat::receipts::{ReceiptKind, ReceiptTarget};
+use libsignal_protocol::{ServiceId, Timestamp};
+
+use crate::support::*;
+use crate::*;
+
+bridge_handle_fns!(ReceiptBatch, clone = false);
+
+/// Queues a receipt for the message from `sender` sent at `message_timestamp`.
+///
+/// `kind` is a [`ReceiptKind`]. The queue lives with the authenticated connection, but the
+/// resulting receipt messages may be sent over either connection.
+#[bridge_fn]
+fn ChatService_auth_queue_receipt(
+    chat: &AuthChat,
+    sender: ServiceId,
+    kind: AsType<ReceiptKind, u8>,
+    message_timestamp: Timestamp,
+) {
+    chat.queue_receipt(ReceiptTarget {
+        sender,
+        kind: kind.into_inner(),
+        message_timestamp,
+    })
+}
+
+/// Takes the next batch of queued receipts, or returns null if there are none.
+///
+/// Each batch should be sent as one receipt message. Batches not yet taken stay queued, and can
+/// still absorb new receipts.
+#[bridge_fn]
+fn ChatService_auth_next_receipt_batch(chat: &AuthChat) -> Option<ReceiptBatch> {
+    chat.next_receipt_batch()
+}
+
+#[bridge_fn]
+fn ReceiptBatch_GetSender(batch: &ReceiptBatch) -> ServiceId {
+    batch.0.sender
+}
+
+#[bridge_fn]
+fn ReceiptBatch_GetKind(batch: &ReceiptBatch) -> u8 {
+    batch.0.kind.into()
+}
+
+/// Returns the batch's message timestamps as concatenated big-endian 64-bit integers, in ascending
+/// order.
+#[bridge_fn]
+fn ReceiptBatch_GetTimestamps(batch: &ReceiptBatch) -> Vec<u8> {
+    batch
+        .0
+        .timestamps
+        .iter()
+        .flat_map(|timestamp| timestamp.epoch_millis().to_be_bytes())
+        .collect()
+}
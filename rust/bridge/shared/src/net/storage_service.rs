@@ -0,0 +1,215 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Storage service sync planning, with records passed around in serialized form.
+//!
+//! Record ids are serialized `ManifestRecord.Identifier` messages, so that the app doesn't have to
+//! split the record type out of them.
+
+use libsignal_bridge_macros::bridge_fn;
+use libsignal_bridge_types::net::storage_service::{StorageSync, StorageSyncPlan};
+use libsignal_net::storage_service::{
+    ids_to_fetch, plan_sync, OpaqueStorageRecord, StorageId, StorageManifest, StorageSyncError,
+    StoredRecord,
+};
+use libsignal_protocol::SignalProtocolError;
+use rand::rngs::OsRng;
+
+use crate::support::*;
+use crate::*;
+
+bridge_handle_fns!(StorageSync, clone = false);
+bridge_handle_fns!(StorageSyncPlan, clone = false);
+
+fn invalid_argument(what: &str, e: StorageSyncError) -> SignalProtocolError {
+    SignalProtocolError::InvalidArgument(format!("{what}: {e}"))
+}
+
+fn parse_id(id: &[u8]) -> Result<StorageId, SignalProtocolError> {
+    StorageId::deserialize(id).map_err(|e| invalid_argument("id", e))
+}
+
+fn stored_record(
+    id: &[u8],
+    key: &[u8],
+    data: &[u8],
+    pending_upload: bool,
+) -> Result<StoredRecord<OpaqueStorageRecord>, SignalProtocolError> {
+    let id = parse_id(id)?;
+    Ok(StoredRecord {
+        record: OpaqueStorageRecord {
+            record_type: id.record_type,
+            key: key.to_vec(),
+            data: data.to_vec(),
+            pending_upload,
+        },
+        id,
+    })
+}
+
+fn serialize_ids<'a>(ids: impl IntoIterator<Item = &'a StorageId>) -> Box<[Vec<u8>]> {
+    ids.into_iter().map(StorageId::serialize).collect()
+}
+
+/// Starts a sync from the decrypted manifest of the last successful sync (empty if there has never
+/// been one) and the decrypted manifest currently on the service.
+#[bridge_fn]
+fn StorageSync_new(
+    previous_manifest: &[u8],
+    remote_manifest: &[u8],
+) -> Result<StorageSync, SignalProtocolError> {
+    let previous = if previous_manifest.is_empty() {
+        StorageManifest::default()
+    } else {
+        StorageManifest::deserialize(previous_manifest)
+            .map_err(|e| invalid_argument("previous manifest", e))?
+    };
+    let remote = StorageManifest::deserialize(remote_manifest)
+        .map_err(|e| invalid_argument("remote manifest", e))?;
+    Ok(StorageSync::new(previous, remote))
+}
+
+/// Adds a record from the local database.
+///
+/// A record whose id isn't in the remote manifest is treated as a local change that hasn't been
+/// uploaded yet, and wins over the service's copy.
+#[bridge_fn]
+fn StorageSync_addLocalRecord(
+    sync: &StorageSync,
+    id: &[u8],
+    key: &[u8],
+    data: &[u8],
+) -> Result<(), SignalProtocolError> {
+    let mut sync = sync.lock();
+    let mut record = stored_record(id, key, data, false)?;
+    record.record.pending_upload = !sync.remote.ids.contains(&record.id);
+    sync.local.records.push(record);
+    Ok(())
+}
+
+/// Adds the id of a record that was deleted locally since the last sync.
+#[bridge_fn]
+fn StorageSync_addLocalDeletion(sync: &StorageSync, id: &[u8]) -> Result<(), SignalProtocolError> {
+    let id = parse_id(id)?;
+    sync.lock().local.deleted.push(id);
+    Ok(())
+}
+
+/// Adds the id of a record already known to be of a type this client can't parse.
+#[bridge_fn]
+fn StorageSync_addKnownUnknown(sync: &StorageSync, id: &[u8]) -> Result<(), SignalProtocolError> {
+    let id = parse_id(id)?;
+    sync.lock().local.unknown.push(id);
+    Ok(())
+}
+
+/// Returns the ids that need to be fetched from the service, given the local records added so far.
+#[bridge_fn]
+fn StorageSync_idsToFetch(sync: &StorageSync) -> Box<[Vec<u8>]> {
+    let sync = sync.lock();
+    serialize_ids(&ids_to_fetch(&sync.local, &sync.remote))
+}
+
+#[bridge_fn]
+fn StorageSync_addFetchedRecord(
+    sync: &StorageSync,
+    id: &[u8],
+    key: &[u8],
+    data: &[u8],
+) -> Result<(), SignalProtocolError> {
+    let record = stored_record(id, key, data, false)?;
+    sync.lock().fetched.records.push(record);
+    Ok(())
+}
+
+/// Adds the id of a fetched record of a type this client can't parse.
+#[bridge_fn]
+fn StorageSync_addFetchedUnknown(sync: &StorageSync, id: &[u8]) -> Result<(), SignalProtocolError> {
+    let id = parse_id(id)?;
+    sync.lock().fetched.unknown.push(id);
+    Ok(())
+}
+
+/// Plans the sync, consuming the records added so far.
+#[bridge_fn]
+fn StorageSync_plan(sync: &StorageSync) -> Result<StorageSyncPlan, SignalProtocolError> {
+    let inputs = std::mem::take(&mut *sync.lock());
+    plan_sync(
+        &inputs.previous,
+        &inputs.remote,
+        inputs.fetched,
+        inputs.local,
+        &mut OsRng,
+    )
+    .map(StorageSyncPlan)
+    .map_err(|e| SignalProtocolError::InvalidState("storage sync", e.to_string()))
+}
+
+/// The ids of records to insert or replace locally; see `StorageSyncPlan_localUpsertData`.
+#[bridge_fn]
+fn StorageSyncPlan_localUpsertIds(plan: &StorageSyncPlan) -> Box<[Vec<u8>]> {
+    serialize_ids(plan.0.local.upserts.iter().map(|r| &r.id))
+}
+
+#[bridge_fn]
+fn StorageSyncPlan_localUpsertData(plan: &StorageSyncPlan) -> Box<[Vec<u8>]> {
+    plan.0
+        .local
+        .upserts
+        .iter()
+        .map(|r| r.record.data.clone())
+        .collect()
+}
+
+#[bridge_fn]
+fn StorageSyncPlan_localDeletes(plan: &StorageSyncPlan) -> Box<[Vec<u8>]> {
+    serialize_ids(&plan.0.local.deletes)
+}
+
+/// The ids of records of unknown types that remain in the manifest, to remember for next time.
+#[bridge_fn]
+fn StorageSyncPlan_unknown(plan: &StorageSyncPlan) -> Box<[Vec<u8>]> {
+    serialize_ids(&plan.0.unknown)
+}
+
+/// Whether a new manifest needs to be uploaded.
+///
+/// If not, the remote-side accessors all return empty lists.
+#[bridge_fn]
+fn StorageSyncPlan_hasRemoteChanges(plan: &StorageSyncPlan) -> bool {
+    plan.0.remote.is_some()
+}
+
+/// The record ids for the manifest to upload, whose version is one more than the remote manifest's.
+#[bridge_fn]
+fn StorageSyncPlan_remoteManifestIds(plan: &StorageSyncPlan) -> Box<[Vec<u8>]> {
+    serialize_ids(plan.0.remote.iter().flat_map(|r| &r.manifest.ids))
+}
+
+/// The ids of records to upload; see `StorageSyncPlan_remoteInsertData`.
+#[bridge_fn]
+fn StorageSyncPlan_remoteInsertIds(plan: &StorageSyncPlan) -> Box<[Vec<u8>]> {
+    serialize_ids(
+        plan.0
+            .remote
+            .iter()
+            .flat_map(|r| r.inserts.iter().map(|r| &r.id)),
+    )
+}
+
+#[bridge_fn]
+fn StorageSyncPlan_remoteInsertData(plan: &StorageSyncPlan) -> Box<[Vec<u8>]> {
+    plan.0
+        .remote
+        .iter()
+        .flat_map(|r| &r.inserts)
+        .map(|r| r.record.data.clone())
+        .collect()
+}
+
+#[bridge_fn]
+fn StorageSyncPlan_remoteDeletes(plan: &StorageSyncPlan) -> Box<[Vec<u8>]> {
+    serialize_ids(plan.0.remote.iter().flat_map(|r| &r.deletes))
+}
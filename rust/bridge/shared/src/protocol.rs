@@ -3,12 +3,15 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use std::time::{Duration, SystemTime};
+
 // Will be unused when building for Node only.
 #[allow(unused_imports)]
 use futures_util::FutureExt;
 use libsignal_bridge_macros::*;
 #[cfg(feature = "jni")]
 use libsignal_bridge_types::jni;
+use libsignal_core::{RegionHint, E164};
 use libsignal_protocol::error::Result;
 use libsignal_protocol::*;
 use static_assertions::const_assert_eq;
@@ -38,6 +41,7 @@ bridge_handle_fns!(ProtocolAddress, ffi = address);
 bridge_handle_fns!(PublicKey, ffi = publickey, jni = ECPublicKey);
 bridge_handle_fns!(SenderCertificate);
 bridge_handle_fns!(SenderKeyDistributionMessage);
+bridge_handle_fns!(SenderKeyDistributionRecord);
 bridge_handle_fns!(SenderKeyMessage);
 bridge_handle_fns!(SenderKeyRecord);
 bridge_handle_fns!(ServerCertificate);
@@ -46,7 +50,7 @@ bridge_handle_fns!(SignalMessage, ffi = message);
 bridge_handle_fns!(SignedPreKeyRecord);
 bridge_handle_fns!(KyberPreKeyRecord);
 bridge_handle_fns!(UnidentifiedSenderMessageContent, clone = false);
-bridge_handle_fns!(SealedSenderDecryptionResult, ffi = false, jni = false);
+bridge_handle_fns!(SealedSenderDecryptionResult, clone = false);
 bridge_handle_fns!(KyberKeyPair);
 bridge_handle_fns!(KyberPublicKey);
 bridge_handle_fns!(KyberSecretKey);
@@ -111,6 +115,70 @@ fn ServiceId_ParseFromServiceIdString(input: String) -> Result<ServiceId> {
     })
 }
 
+#[bridge_fn]
+fn ServiceId_ParseFromServiceIdBinaries(input: Vec<&[u8]>) -> Result<Vec<u8>> {
+    let service_ids = input
+        .into_iter()
+        .enumerate()
+        .map(|(i, binary)| {
+            ServiceId::parse_from_service_id_binary(binary).ok_or_else(|| {
+                SignalProtocolError::InvalidArgument(format!(
+                    "invalid Service-Id-Binary at index {i}"
+                ))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ServiceId::concatenated_fixed_width_binary(service_ids))
+}
+
+#[bridge_fn]
+fn ServiceId_ServiceIdBinaries(concatenated: &[u8]) -> Result<Box<[Vec<u8>]>> {
+    Ok(parse_concatenated_service_ids(concatenated)?
+        .iter()
+        .map(ServiceId::service_id_binary)
+        .collect())
+}
+
+#[bridge_fn]
+fn ServiceId_ServiceIdStrings(concatenated: &[u8]) -> Result<Box<[String]>> {
+    Ok(parse_concatenated_service_ids(concatenated)?
+        .iter()
+        .map(ServiceId::service_id_string)
+        .collect())
+}
+
+fn parse_concatenated_service_ids(concatenated: &[u8]) -> Result<Vec<ServiceId>> {
+    ServiceId::parse_from_concatenated_fixed_width_binary(concatenated).ok_or_else(|| {
+        SignalProtocolError::InvalidArgument(
+            "invalid concatenated Service-Id-FixedWidthBinary".to_string(),
+        )
+    })
+}
+
+/// Normalizes a phone number as entered by a user or found in a contact list.
+///
+/// Returns the number in `+<digits>` form, or `None` if `raw` isn't a valid phone number.
+#[bridge_fn]
+fn E164_Normalize(raw: String, region_calling_code: u32) -> Result<Option<String>> {
+    let region = region_hint(region_calling_code)?;
+    Ok(E164::normalize(&raw, region).map(|e164| e164.to_string()))
+}
+
+/// Checks whether `e164` is already a valid number in `+<digits>` form.
+#[bridge_fn]
+fn E164_IsValid(e164: String) -> bool {
+    E164::parse_strict(&e164).is_some()
+}
+
+pub(crate) fn region_hint(calling_code: u32) -> Result<RegionHint> {
+    u16::try_from(calling_code)
+        .ok()
+        .and_then(RegionHint::from_calling_code)
+        .ok_or_else(|| {
+            SignalProtocolError::InvalidArgument(format!("invalid calling code {calling_code}"))
+        })
+}
+
 #[bridge_fn(ffi = "address_new")]
 fn ProtocolAddress_New(name: String, device_id: u32) -> ProtocolAddress {
     ProtocolAddress::new(name, device_id.into())
@@ -336,6 +404,27 @@ fn ScannableFingerprint_Compare(fprint1: &[u8], fprint2: &[u8]) -> Result<bool>
     ScannableFingerprint::deserialize(fprint1)?.compare(fprint2)
 }
 
+#[bridge_fn]
+fn IdentityDigest_Compute(
+    iterations: u32,
+    version: u32,
+    stable_identifier: &[u8],
+    identity_key: &PublicKey,
+) -> Result<Vec<u8>> {
+    let digest = IdentityDigest::new(
+        version,
+        iterations,
+        stable_identifier,
+        &IdentityKey::new(*identity_key),
+    )?;
+    Ok(digest.as_bytes().to_vec())
+}
+
+#[bridge_fn]
+fn IdentityDigest_DisplayString(version: u32, digest: &[u8]) -> Result<String> {
+    Ok(IdentityDigest::from_bytes(version, digest)?.display_string())
+}
+
 #[bridge_fn(ffi = "message_deserialize")]
 fn SignalMessage_Deserialize(data: &[u8]) -> Result<SignalMessage> {
     SignalMessage::try_from(data)
@@ -345,6 +434,7 @@ bridge_get!(SignalMessage::body -> &[u8], ffi = "message_get_body");
 bridge_get!(SignalMessage::serialized -> &[u8], ffi = "message_get_serialized");
 bridge_get!(SignalMessage::counter -> u32, ffi = "message_get_counter");
 bridge_get!(SignalMessage::message_version -> u32, ffi = "message_get_message_version");
+bridge_get!(SignalMessage::previous_counter -> u32, ffi = "message_get_previous_counter");
 
 #[bridge_fn(ffi = "message_new")]
 fn SignalMessage_New(
@@ -388,6 +478,11 @@ fn SignalMessage_GetSenderRatchetKey(m: &SignalMessage) -> PublicKey {
     *m.sender_ratchet_key()
 }
 
+#[bridge_fn(ffi = "message_check_ratchet_continuity")]
+fn SignalMessage_CheckRatchetContinuity(later: &SignalMessage, earlier: &SignalMessage) -> u8 {
+    later.ratchet_continuity_after(earlier) as u8
+}
+
 #[bridge_fn]
 fn PreKeySignalMessage_New(
     message_version: u8,
@@ -654,6 +749,11 @@ bridge_get!(SignedPreKeyRecord::timestamp -> Timestamp);
 bridge_get!(SignedPreKeyRecord::public_key -> PublicKey);
 bridge_get!(SignedPreKeyRecord::private_key -> PrivateKey);
 
+#[bridge_fn]
+fn SignedPreKeyRecord_MigrateToCurrent(data: &[u8]) -> Result<Vec<u8>> {
+    SignedPreKeyRecord::migrate_to_current(data)
+}
+
 bridge_deserialize!(KyberPreKeyRecord::deserialize);
 bridge_get!(KyberPreKeyRecord::signature -> Vec<u8>);
 bridge_get!(
@@ -666,6 +766,11 @@ bridge_get!(KyberPreKeyRecord::public_key -> KyberPublicKey);
 bridge_get!(KyberPreKeyRecord::secret_key -> KyberSecretKey);
 bridge_get!(KyberPreKeyRecord::key_pair -> KyberKeyPair);
 
+#[bridge_fn]
+fn KyberPreKeyRecord_MigrateToCurrent(data: &[u8]) -> Result<Vec<u8>> {
+    KyberPreKeyRecord::migrate_to_current(data)
+}
+
 #[bridge_fn]
 fn SignedPreKeyRecord_New(
     id: u32,
@@ -697,6 +802,11 @@ bridge_get!(PreKeyRecord::id -> u32);
 bridge_get!(PreKeyRecord::public_key -> PublicKey);
 bridge_get!(PreKeyRecord::private_key -> PrivateKey);
 
+#[bridge_fn]
+fn PreKeyRecord_MigrateToCurrent(data: &[u8]) -> Result<Vec<u8>> {
+    PreKeyRecord::migrate_to_current(data)
+}
+
 #[bridge_fn]
 fn PreKeyRecord_New(id: u32, pub_key: &PublicKey, priv_key: &PrivateKey) -> PreKeyRecord {
     let keypair = KeyPair::new(*pub_key, *priv_key);
@@ -709,6 +819,14 @@ bridge_get!(
     jni = "SenderKeyRecord_1GetSerialized"
 );
 
+#[bridge_fn]
+fn SenderKeyRecord_MigrateToCurrent(data: &[u8]) -> Result<Vec<u8>> {
+    SenderKeyRecord::migrate_to_current(data)
+}
+
+bridge_deserialize!(SenderKeyDistributionRecord::deserialize);
+bridge_get!(SenderKeyDistributionRecord::serialize as Serialize -> Vec<u8>);
+
 bridge_deserialize!(ServerCertificate::deserialize);
 bridge_get!(ServerCertificate::serialized -> &[u8]);
 bridge_get!(ServerCertificate::certificate -> &[u8]);
@@ -745,6 +863,15 @@ fn SenderCertificate_Validate(
     cert.validate(key, time)
 }
 
+#[bridge_fn]
+fn SenderCertificate_NeedsRefresh(
+    cert: &SenderCertificate,
+    time: Timestamp,
+    refresh_margin_seconds: u32,
+) -> bool {
+    cert.needs_refresh(time, Duration::from_secs(refresh_margin_seconds.into()))
+}
+
 #[bridge_fn]
 fn SenderCertificate_GetServerCertificate(cert: &SenderCertificate) -> Result<ServerCertificate> {
     Ok(cert.signer()?.clone())
@@ -866,6 +993,35 @@ fn UnidentifiedSenderMessageContentNew(
     )
 }
 
+#[bridge_fn(ffi = false)]
+fn UnidentifiedSenderMessageContent_WithContentHintAndGroupId(
+    m: &UnidentifiedSenderMessageContent,
+    content_hint: u32,
+    group_id: Option<&[u8]>,
+) -> Result<UnidentifiedSenderMessageContent> {
+    m.with_content_hint_and_group_id(
+        ContentHint::from(content_hint),
+        group_id.map(|g| g.to_owned()),
+    )
+}
+
+// Alternate version for FFI because FFI can't support optional slices.
+#[bridge_fn(jni = false, node = false)]
+fn UnidentifiedSenderMessageContentWithContentHintAndGroupId(
+    m: &UnidentifiedSenderMessageContent,
+    content_hint: u32,
+    group_id: &[u8],
+) -> Result<UnidentifiedSenderMessageContent> {
+    m.with_content_hint_and_group_id(
+        ContentHint::from(content_hint),
+        if group_id.is_empty() {
+            None
+        } else {
+            Some(group_id.to_owned())
+        },
+    )
+}
+
 // Alternate version for Java since CiphertextMessage isn't opaque in Java.
 #[bridge_fn(
     ffi = false,
@@ -955,6 +1111,34 @@ fn SessionRecord_CurrentRatchetKeyMatches(s: &SessionRecord, key: &PublicKey) ->
     s.current_ratchet_key_matches(key)
 }
 
+#[bridge_fn]
+fn SessionRecord_GetMessagesEncrypted(s: &SessionRecord) -> Result<u32> {
+    Ok(s.stats()?.messages_encrypted)
+}
+
+#[bridge_fn]
+fn SessionRecord_GetMessagesDecrypted(s: &SessionRecord) -> Result<u32> {
+    Ok(s.stats()?.messages_decrypted)
+}
+
+/// Returns 0 if this device hasn't sent anything with its current ratchet key.
+#[bridge_fn]
+fn SessionRecord_GetLastRatchetStepTime(s: &SessionRecord) -> Result<Timestamp> {
+    let millis = s.stats()?.last_ratchet_step.map_or(0, |time| {
+        time.duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+            .try_into()
+            .unwrap_or(u64::MAX)
+    });
+    Ok(Timestamp::from_epoch_millis(millis))
+}
+
+#[bridge_fn]
+fn SessionRecord_IsPostQuantum(s: &SessionRecord) -> Result<bool> {
+    Ok(s.stats()?.post_quantum)
+}
+
 bridge_deserialize!(SessionRecord::deserialize);
 bridge_get!(SessionRecord::serialize as Serialize -> Vec<u8>);
 bridge_get!(SessionRecord::alice_base_key -> &[u8], ffi = false, node = false);
@@ -971,14 +1155,31 @@ bridge_get!(
 bridge_get!(SessionRecord::local_registration_id -> u32);
 bridge_get!(SessionRecord::remote_registration_id -> u32);
 
-bridge_get!(SealedSenderDecryptionResult::sender_uuid -> String, ffi = false, jni = false);
-bridge_get!(SealedSenderDecryptionResult::sender_e164 -> Option<String>, ffi = false, jni = false);
-bridge_get!(SealedSenderDecryptionResult::device_id -> u32, ffi = false, jni = false);
-bridge_get!(
-    SealedSenderDecryptionResult::message as Message -> &[u8],
-    ffi = false,
-    jni = false
-);
+#[bridge_fn]
+fn SessionRecord_MigrateToCurrent(data: &[u8]) -> Result<Vec<u8>> {
+    SessionRecord::migrate_to_current(data)
+}
+
+bridge_get!(SealedSenderDecryptionResult::sender_uuid -> &str);
+bridge_get!(SealedSenderDecryptionResult::sender_e164 -> Option<&str>);
+bridge_get!(SealedSenderDecryptionResult::device_id -> u32);
+bridge_get!(SealedSenderDecryptionResult::message as Message -> &[u8]);
+
+#[bridge_fn]
+fn SealedSenderDecryptionResult_GetPreviousIdentityKey(
+    obj: &SealedSenderDecryptionResult,
+) -> Option<PublicKey> {
+    obj.identity_change()
+        .map(|change| *change.previous.public_key())
+}
+
+#[bridge_fn]
+fn SealedSenderDecryptionResult_GetCurrentIdentityKey(
+    obj: &SealedSenderDecryptionResult,
+) -> Option<PublicKey> {
+    obj.identity_change()
+        .map(|change| *change.current.public_key())
+}
 
 // The following SessionRecord APIs are just exposed to make it possible to retain some of the Java tests:
 
@@ -1147,6 +1348,53 @@ async fn SessionCipher_DecryptPreKeySignalMessage(
     .await
 }
 
+#[bridge_fn(ffi = "decrypt_message_with_replay_cache")]
+async fn SessionCipher_DecryptSignalMessageWithReplayCache(
+    message: &SignalMessage,
+    protocol_address: &ProtocolAddress,
+    session_store: &mut dyn SessionStore,
+    identity_key_store: &mut dyn IdentityKeyStore,
+    replay_cache: &mut dyn ReplayCache,
+) -> Result<Vec<u8>> {
+    let mut csprng = rand::rngs::OsRng;
+    message_decrypt_signal_with_replay_cache(
+        message,
+        protocol_address,
+        session_store,
+        identity_key_store,
+        replay_cache,
+        &mut csprng,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+#[bridge_fn(ffi = "decrypt_pre_key_message_with_replay_cache")]
+async fn SessionCipher_DecryptPreKeySignalMessageWithReplayCache(
+    message: &PreKeySignalMessage,
+    protocol_address: &ProtocolAddress,
+    session_store: &mut dyn SessionStore,
+    identity_key_store: &mut dyn IdentityKeyStore,
+    prekey_store: &mut dyn PreKeyStore,
+    signed_prekey_store: &mut dyn SignedPreKeyStore,
+    kyber_prekey_store: &mut dyn KyberPreKeyStore,
+    replay_cache: &mut dyn ReplayCache,
+) -> Result<Vec<u8>> {
+    let mut csprng = rand::rngs::OsRng;
+    message_decrypt_prekey_with_replay_cache(
+        message,
+        protocol_address,
+        session_store,
+        identity_key_store,
+        prekey_store,
+        signed_prekey_store,
+        kyber_prekey_store,
+        replay_cache,
+        &mut csprng,
+    )
+    .await
+}
+
 #[bridge_fn(node = "SealedSender_Encrypt")]
 async fn SealedSessionCipher_Encrypt(
     destination: &ProtocolAddress,
@@ -1215,6 +1463,30 @@ fn SealedSender_MultiRecipientMessageForSingleRecipient(
     Ok(result)
 }
 
+// Lengths are capped at i32::MAX so that every bridge can represent them; that's still far beyond
+// anything the server would accept.
+#[bridge_fn(jni = "SealedSessionCipher_1MultiRecipientMessageLength")]
+fn SealedSender_MultiRecipientMessageLength(
+    usmc_length: u32,
+    recipient_count: u32,
+    device_count: u32,
+    excluded_recipient_count: u32,
+) -> u32 {
+    let length = sealed_sender_multi_recipient_message_len(
+        usmc_length as usize,
+        recipient_count as usize,
+        device_count as usize,
+        excluded_recipient_count as usize,
+    );
+    length.min(i32::MAX as usize) as u32
+}
+
+#[bridge_fn(jni = "SealedSessionCipher_1MultiRecipientReceivedMessageLength")]
+fn SealedSender_MultiRecipientReceivedMessageLength(usmc_length: u32) -> u32 {
+    let length = sealed_sender_multi_recipient_received_message_len(usmc_length as usize);
+    length.min(i32::MAX as usize) as u32
+}
+
 #[bridge_fn(node = "SealedSender_DecryptToUsmc")]
 async fn SealedSessionCipher_DecryptToUsmc(
     ctext: &[u8],
@@ -1254,6 +1526,70 @@ async fn SealedSender_DecryptMessage(
     .await
 }
 
+#[allow(clippy::too_many_arguments)]
+#[bridge_fn]
+async fn SealedSender_DecryptMessageCapturingIdentityChange(
+    message: &[u8],
+    trust_root: &PublicKey,
+    timestamp: Timestamp,
+    local_e164: Option<String>,
+    local_uuid: String,
+    local_device_id: u32,
+    session_store: &mut dyn SessionStore,
+    identity_store: &mut dyn IdentityKeyStore,
+    prekey_store: &mut dyn PreKeyStore,
+    signed_prekey_store: &mut dyn SignedPreKeyStore,
+    kyber_prekey_store: &mut dyn KyberPreKeyStore,
+) -> Result<SealedSenderDecryptionResult> {
+    sealed_sender_decrypt_capturing_identity_change(
+        message,
+        trust_root,
+        timestamp,
+        local_e164,
+        local_uuid,
+        local_device_id.into(),
+        identity_store,
+        session_store,
+        prekey_store,
+        signed_prekey_store,
+        kyber_prekey_store,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+#[bridge_fn(ffi = false, jni = false)]
+async fn SealedSender_DecryptMessageWithReplayCache(
+    message: &[u8],
+    trust_root: &PublicKey,
+    timestamp: Timestamp,
+    local_e164: Option<String>,
+    local_uuid: String,
+    local_device_id: u32,
+    session_store: &mut dyn SessionStore,
+    identity_store: &mut dyn IdentityKeyStore,
+    prekey_store: &mut dyn PreKeyStore,
+    signed_prekey_store: &mut dyn SignedPreKeyStore,
+    kyber_prekey_store: &mut dyn KyberPreKeyStore,
+    replay_cache: &mut dyn ReplayCache,
+) -> Result<SealedSenderDecryptionResult> {
+    sealed_sender_decrypt_with_replay_cache(
+        message,
+        trust_root,
+        timestamp,
+        local_e164,
+        local_uuid,
+        local_device_id.into(),
+        identity_store,
+        session_store,
+        prekey_store,
+        signed_prekey_store,
+        kyber_prekey_store,
+        replay_cache,
+    )
+    .await
+}
+
 #[bridge_fn(jni = "GroupSessionBuilder_1CreateSenderKeyDistributionMessage")]
 async fn SenderKeyDistributionMessage_Create(
     sender: &ProtocolAddress,
@@ -1276,6 +1612,48 @@ async fn SenderKeyDistributionMessage_Process(
     process_sender_key_distribution_message(sender, sender_key_distribution_message, store).await
 }
 
+/// Returns one byte per recipient, nonzero if that recipient still needs the current sender key.
+///
+/// (Flags rather than the addresses themselves because the bridge can't return a list of handles.)
+#[bridge_fn(jni = "GroupSessionBuilder_1RecipientsNeedingDistribution")]
+async fn SenderKey_RecipientsNeedingDistribution(
+    sender: &ProtocolAddress,
+    distribution_id: Uuid,
+    recipients: &[&ProtocolAddress],
+    store: &mut dyn SenderKeyStore,
+) -> Result<Vec<u8>> {
+    let recipients = recipients.iter().map(|&r| r.clone()).collect::<Vec<_>>();
+    let needing_distribution =
+        sender_key_recipients_needing_distribution(sender, distribution_id, &recipients, store)
+            .await?;
+    Ok(recipients
+        .iter()
+        .map(|recipient| needing_distribution.contains(recipient).into())
+        .collect())
+}
+
+#[bridge_fn(jni = "GroupSessionBuilder_1MarkDistributed")]
+async fn SenderKey_MarkDistributed(
+    sender: &ProtocolAddress,
+    distribution_id: Uuid,
+    recipients: &[&ProtocolAddress],
+    store: &mut dyn SenderKeyStore,
+) -> Result<()> {
+    let recipients = recipients.iter().map(|&r| r.clone()).collect::<Vec<_>>();
+    mark_sender_key_distributed(sender, distribution_id, &recipients, store).await
+}
+
+#[bridge_fn(jni = "GroupSessionBuilder_1InvalidateDistribution")]
+async fn SenderKey_InvalidateDistribution(
+    sender: &ProtocolAddress,
+    distribution_id: Uuid,
+    recipients: &[&ProtocolAddress],
+    store: &mut dyn SenderKeyStore,
+) -> Result<()> {
+    let recipients = recipients.iter().map(|&r| r.clone()).collect::<Vec<_>>();
+    invalidate_sender_key_distribution(sender, distribution_id, &recipients, store).await
+}
+
 #[bridge_fn(ffi = "group_encrypt_message")]
 async fn GroupCipher_EncryptMessage(
     sender: &ProtocolAddress,
@@ -1296,3 +1674,54 @@ async fn GroupCipher_DecryptMessage(
 ) -> Result<Vec<u8>> {
     group_decrypt(message, store, sender).await
 }
+
+#[bridge_fn]
+fn StoreConformance_CheckCount() -> u32 {
+    conformance::Check::ALL
+        .len()
+        .try_into()
+        .expect("only a few checks")
+}
+
+/// Runs check number `check` against an app's stores.
+///
+/// The stores must be empty and belong to the identity and registration ID given. A failed check
+/// is reported as an `InvalidState` error describing the first mismatch found.
+#[allow(clippy::too_many_arguments)]
+#[bridge_fn]
+async fn StoreConformance_Check(
+    check: u32,
+    identity_key: &PublicKey,
+    private_key: &PrivateKey,
+    registration_id: u32,
+    identity_store: &mut dyn IdentityKeyStore,
+    pre_key_store: &mut dyn PreKeyStore,
+    signed_pre_key_store: &mut dyn SignedPreKeyStore,
+    kyber_pre_key_store: &mut dyn KyberPreKeyStore,
+    session_store: &mut dyn SessionStore,
+    sender_key_store: &mut dyn SenderKeyStore,
+) -> Result<()> {
+    let check = usize::try_from(check)
+        .ok()
+        .and_then(|index| conformance::Check::ALL.get(index).copied())
+        .ok_or_else(|| SignalProtocolError::InvalidArgument(format!("no check #{check}")))?;
+    let identity = IdentityKeyPair::new(IdentityKey::new(*identity_key), *private_key);
+    let stores = conformance::SeparateStores {
+        identity_store,
+        pre_key_store,
+        signed_pre_key_store,
+        kyber_pre_key_store,
+        session_store,
+        sender_key_store,
+    };
+    // The checks report mismatches by panicking, which is what a Rust test wants but not an app.
+    std::panic::AssertUnwindSafe(check.run(stores, &identity, registration_id))
+        .catch_unwind()
+        .await
+        .map_err(|panic| {
+            SignalProtocolError::InvalidState(
+                "store conformance",
+                format!("{check:?} check failed: {}", describe_panic(&panic)),
+            )
+        })
+}
@@ -6,7 +6,7 @@
 #[allow(unused_imports)]
 use ::usernames::{
     create_for_username, decrypt_username, NicknameLimits, Username, UsernameError,
-    UsernameLinkError,
+    UsernameLinkError, UsernameLinkUrl,
 };
 use libsignal_bridge_macros::*;
 
@@ -106,3 +106,14 @@ pub fn UsernameLink_DecryptUsername(
         .map_err(|_| UsernameLinkError::InvalidEntropyDataLength)?;
     decrypt_username(entropy, encrypted_username)
 }
+
+/// Parses a username link URL, returning its entropy followed by the server ID's UUID bytes.
+#[bridge_fn]
+pub fn UsernameLink_ParseUrl(url: String) -> Result<[u8; 48], UsernameLinkError> {
+    let UsernameLinkUrl { entropy, server_id } = UsernameLinkUrl::parse(&url)?;
+    let mut result = [0; 48];
+    let (entropy_out, server_id_out) = result.split_at_mut(entropy.len());
+    entropy_out.copy_from_slice(&entropy);
+    server_id_out.copy_from_slice(server_id.as_bytes());
+    Ok(result)
+}
@@ -15,6 +15,7 @@ use zkgroup::backups::{
     BackupAuthCredentialRequestContext, BackupAuthCredentialResponse, BackupLevel,
 };
 use zkgroup::call_links::*;
+use zkgroup::common::serialization::{compact_to_standard, standard_to_compact};
 use zkgroup::generic_server_params::*;
 use zkgroup::groups::*;
 use zkgroup::profiles::*;
@@ -523,6 +524,22 @@ fn ProfileKeyCredentialPresentation_GetProfileKeyCiphertext(
     presentation.get_profile_key_ciphertext().into()
 }
 
+#[bridge_fn]
+fn ProfileKeyCredentialPresentation_ToCompact(
+    presentation_bytes: &[u8],
+) -> Result<Vec<u8>, ZkGroupDeserializationFailure> {
+    Ok(AnyProfileKeyCredentialPresentation::new(presentation_bytes)?.to_compact())
+}
+
+#[bridge_fn]
+fn ProfileKeyCredentialPresentation_FromCompact(
+    compact_bytes: &[u8],
+) -> Result<Vec<u8>, ZkGroupDeserializationFailure> {
+    Ok(zkgroup::serialize(
+        &AnyProfileKeyCredentialPresentation::from_compact(compact_bytes)?,
+    ))
+}
+
 // Only used by the server.
 #[bridge_fn(ffi = false, node = false)]
 fn ProfileKeyCredentialPresentation_GetStructurallyValidV1PresentationBytes(
@@ -621,6 +638,11 @@ fn CallLinkSecretParams_DeriveFromRootKey(root_key: &[u8]) -> Vec<u8> {
     zkgroup::serialize(&params)
 }
 
+#[bridge_fn]
+fn CallLinkSecretParams_DeriveRoomId(root_key: &[u8]) -> [u8; 32] {
+    CallLinkSecretParams::derive_room_id(root_key)
+}
+
 #[bridge_fn]
 fn CallLinkSecretParams_GetPublicParams(params_bytes: &[u8]) -> Vec<u8> {
     let params = zkgroup::deserialize::<CallLinkSecretParams>(params_bytes)
@@ -1113,6 +1135,8 @@ fn GroupSendEndorsementsResponse_GetExpiration(response_bytes: &[u8]) -> Timesta
     response.expiration()
 }
 
+/// `deadline` is checked before processing each member and once more before the endorsements are
+/// verified as a batch, which can't be interrupted.
 #[bridge_fn]
 fn GroupSendEndorsementsResponse_ReceiveAndCombineWithServiceIds(
     response_bytes: &[u8],
@@ -1121,7 +1145,8 @@ fn GroupSendEndorsementsResponse_ReceiveAndCombineWithServiceIds(
     now: Timestamp,
     group_params: Serialized<GroupSecretParams>,
     server_params: &ServerPublicParams,
-) -> Result<Box<[Vec<u8>]>, ZkGroupVerificationFailure> {
+    deadline: Deadline,
+) -> Result<Box<[Vec<u8>]>, DeadlineOr<ZkGroupVerificationFailure>> {
     let response = zkgroup::deserialize::<GroupSendEndorsementsResponse>(response_bytes)
         .expect("should have been parsed previously");
 
@@ -1130,8 +1155,15 @@ fn GroupSendEndorsementsResponse_ReceiveAndCombineWithServiceIds(
         .position(|next| next == local_user)
         .expect("local user not included in member list");
 
-    let endorsements =
-        response.receive_with_service_ids(group_members, now, &group_params, server_params)?;
+    let check_deadline =
+        || -> Result<(), DeadlineOr<ZkGroupVerificationFailure>> { Ok(deadline.check()?) };
+    let endorsements = response.receive_with_service_ids_interruptible(
+        group_members,
+        now,
+        &group_params,
+        server_params,
+        check_deadline,
+    )?;
     let combined_endorsement = GroupSendEndorsement::combine(
         endorsements[..local_user_index]
             .iter()
@@ -1146,6 +1178,7 @@ fn GroupSendEndorsementsResponse_ReceiveAndCombineWithServiceIds(
         .collect())
 }
 
+/// See `GroupSendEndorsementsResponse_ReceiveAndCombineWithServiceIds` for how `deadline` is used.
 #[bridge_fn]
 fn GroupSendEndorsementsResponse_ReceiveAndCombineWithCiphertexts(
     response_bytes: &[u8],
@@ -1153,7 +1186,8 @@ fn GroupSendEndorsementsResponse_ReceiveAndCombineWithCiphertexts(
     local_user_ciphertext: &[u8],
     now: Timestamp,
     server_params: &ServerPublicParams,
-) -> Result<Box<[Vec<u8>]>, ZkGroupVerificationFailure> {
+    deadline: Deadline,
+) -> Result<Box<[Vec<u8>]>, DeadlineOr<ZkGroupVerificationFailure>> {
     let response = zkgroup::deserialize::<GroupSendEndorsementsResponse>(response_bytes)
         .expect("should have been parsed previously");
 
@@ -1170,8 +1204,14 @@ fn GroupSendEndorsementsResponse_ReceiveAndCombineWithCiphertexts(
                 .expect("should have been parsed previously")
         });
 
-    let endorsements =
-        response.receive_with_ciphertexts(user_id_ciphertexts, now, server_params)?;
+    let check_deadline =
+        || -> Result<(), DeadlineOr<ZkGroupVerificationFailure>> { Ok(deadline.check()?) };
+    let endorsements = response.receive_with_ciphertexts_interruptible(
+        user_id_ciphertexts,
+        now,
+        server_params,
+        check_deadline,
+    )?;
     let combined_endorsement = GroupSendEndorsement::combine(
         endorsements[..local_user_index]
             .iter()
@@ -1249,6 +1289,18 @@ fn GroupSendFullToken_GetExpiration(token: &[u8]) -> Timestamp {
     token.expiration()
 }
 
+#[bridge_fn]
+fn GroupSendFullToken_ToCompact(token: &[u8]) -> Result<Vec<u8>, ZkGroupDeserializationFailure> {
+    standard_to_compact::<GroupSendFullToken>(token)
+}
+
+#[bridge_fn]
+fn GroupSendFullToken_FromCompact(
+    compact_token: &[u8],
+) -> Result<Vec<u8>, ZkGroupDeserializationFailure> {
+    compact_to_standard::<GroupSendFullToken>(compact_token)
+}
+
 #[bridge_fn]
 fn GroupSendFullToken_Verify(
     token: &[u8],
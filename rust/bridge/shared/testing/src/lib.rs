@@ -19,6 +19,8 @@ pub fn test_only_fn_returns_123() -> u32 {
 
 pub mod convert;
 pub mod message_backup;
+#[cfg(feature = "mock-chat")]
+pub mod mock_chat;
 pub mod net;
 #[cfg(feature = "node")]
 pub mod net_env;
@@ -0,0 +1,125 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! A mock chat server for exercising chat requests from app-level tests.
+//!
+//! This pulls in `libsignal-net`'s `test-util` code, so it's only built with the `mock-chat`
+//! feature.
+
+use http::uri::InvalidUri;
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use libsignal_bridge_macros::*;
+use libsignal_bridge_types::net::chat::{
+    AuthChat, AuthChatService, HttpMethod, HttpRequest, HttpStatus, UnauthChat, UnauthChatService,
+};
+use libsignal_net::chat::test_support::{mock_chat, MockChatService};
+use libsignal_net::chat::{self, Response as ChatResponse};
+
+use crate::*;
+
+/// A stand-in for the chat server, shared by every chat service created from it.
+///
+/// See [`MockChatService`] for how requests are matched to responses.
+pub struct MockChatServer(MockChatService);
+
+bridge_as_handle!(MockChatServer, ffi = false, jni = false);
+
+/// A canned response waiting to be registered with a [`MockChatServer`].
+pub struct MockChatResponse {
+    status: StatusCode,
+    message: Option<String>,
+    body: Option<Box<[u8]>>,
+    headers: std::sync::Mutex<HeaderMap>,
+}
+
+bridge_as_handle!(MockChatResponse, ffi = false, jni = false);
+
+#[bridge_fn(jni = false, ffi = false)]
+fn TESTING_MockChatServer_New() -> MockChatServer {
+    MockChatServer(MockChatService::default())
+}
+
+#[bridge_fn(jni = false, ffi = false)]
+fn TESTING_MockChatServer_NewAuthChat(server: &MockChatServer) -> AuthChat {
+    // The unauthenticated half of an AuthChat is never used.
+    let service = mock_chat(server.0.clone(), MockChatService::default());
+    AuthChat::from_service(AuthChatService(service))
+}
+
+#[bridge_fn(jni = false, ffi = false)]
+fn TESTING_MockChatServer_NewUnauthChat(server: &MockChatServer) -> UnauthChat {
+    // The authenticated half of an UnauthChat is never used.
+    let service = mock_chat(MockChatService::default(), server.0.clone());
+    UnauthChat::from_service(UnauthChatService(service))
+}
+
+#[bridge_fn(jni = false, ffi = false)]
+fn TESTING_MockChatServer_AddResponse(
+    server: &MockChatServer,
+    method: AsType<HttpMethod, String>,
+    path: String,
+    response: &MockChatResponse,
+) -> Result<(), InvalidUri> {
+    let MockChatResponse {
+        status,
+        message,
+        body,
+        headers,
+    } = response;
+    server.0.add_response(
+        method.into_inner().into(),
+        path.try_into()?,
+        ChatResponse {
+            status: *status,
+            message: message.clone(),
+            body: body.clone(),
+            headers: headers.lock().expect("not poisoned").clone(),
+        },
+    );
+    Ok(())
+}
+
+#[bridge_fn(jni = false, ffi = false)]
+fn TESTING_MockChatServer_TakeSentRequest(server: &MockChatServer) -> Option<HttpRequest> {
+    let chat::Request {
+        method,
+        body,
+        headers,
+        path,
+    } = server.0.take_sent_request()?;
+    Some(HttpRequest {
+        method,
+        path,
+        body,
+        headers: headers.into(),
+    })
+}
+
+#[bridge_fn(jni = false, ffi = false)]
+fn TESTING_MockChatResponse_New(
+    status: AsType<HttpStatus, u16>,
+    message: Option<String>,
+    body: Option<&[u8]>,
+) -> MockChatResponse {
+    MockChatResponse {
+        status: status.into_inner().into(),
+        message,
+        body: body.map(Box::from),
+        headers: Default::default(),
+    }
+}
+
+#[bridge_fn(jni = false, ffi = false)]
+fn TESTING_MockChatResponse_AddHeader(
+    response: &MockChatResponse,
+    name: AsType<HeaderName, String>,
+    value: AsType<HeaderValue, String>,
+) {
+    response
+        .headers
+        .lock()
+        .expect("not poisoned")
+        .append(name.into_inner(), value.into_inner());
+}
@@ -16,11 +16,13 @@ use libsignal_bridge_types::net::{ConnectionManager, TokioAsyncContext};
 use libsignal_core::E164;
 use libsignal_net::cdsi::{CdsiProtocolError, LookupError, LookupResponse, LookupResponseEntry};
 use libsignal_net::chat::{
-    self, ChatServiceError, DebugInfo as ChatServiceDebugInfo, Response as ChatResponse,
+    self, ChatServiceError, DebugInfo as ChatServiceDebugInfo, DisconnectInfo,
+    Response as ChatResponse,
 };
 use libsignal_net::infra::ws::WebSocketServiceError;
 use libsignal_net::infra::ws2::attested::AttestedProtocolError;
 use libsignal_net::infra::IpType;
+use libsignal_net::rate_limit::{RateLimit, RateLimitScope};
 use libsignal_protocol::{Aci, Pni};
 use nonzero_ext::nonzero;
 use uuid::Uuid;
@@ -104,6 +106,8 @@ make_error_testing_enum! {
         WebSocket => WebSocketIdleTooLong,
         ConnectionTimedOut => ConnectionTimedOut,
         Server => ServerCrashed,
+        ;
+        RetryAfter42SecondsPerNumber,
     }
 }
 
@@ -126,9 +130,16 @@ fn TESTING_CdsiLookupErrorConvert(
             })
         }
         TestingCdsiLookupError::InvalidResponse => LookupError::InvalidResponse,
-        TestingCdsiLookupError::RetryAfter42Seconds => LookupError::RateLimited {
-            retry_after_seconds: 42,
-        },
+        TestingCdsiLookupError::RetryAfter42Seconds => {
+            LookupError::RateLimited(RateLimit::retry_after(42))
+        }
+        TestingCdsiLookupError::RetryAfter42SecondsPerNumber => {
+            LookupError::RateLimited(RateLimit {
+                retry_after_seconds: 42,
+                scope: RateLimitScope::PerNumber,
+                reason: Some("fake reason".into()),
+            })
+        }
         TestingCdsiLookupError::InvalidToken => LookupError::InvalidToken,
         TestingCdsiLookupError::InvalidArgument => LookupError::InvalidArgument {
             server_reason: "fake reason".into(),
@@ -162,6 +173,13 @@ make_error_testing_enum! {
         ServiceUnavailable => ServiceUnavailable,
         ServiceIntentionallyDisconnected => ServiceIntentionallyDisconnected,
         RetryLater => RetryAfter42Seconds,
+        RequestUnauthorized => RequestUnauthorized,
+        NotFound => NotFound,
+        ChannelClosed => ChannelClosedWithDisconnectInfo,
+        ;
+        RetryAfter42SecondsPerAccount,
+        ServiceUnavailableWithDisconnectInfo,
+        HttpErrorWithAlerts,
     }
 }
 
@@ -194,14 +212,46 @@ fn TESTING_ChatServiceErrorConvert(
         TestingChatServiceError::AllConnectionRoutesFailed => {
             ChatServiceError::AllConnectionRoutesFailed { attempts: 42 }
         }
-        TestingChatServiceError::ServiceInactive => ChatServiceError::ServiceInactive,
-        TestingChatServiceError::ServiceUnavailable => ChatServiceError::ServiceUnavailable,
+        TestingChatServiceError::ServiceInactive => ChatServiceError::ServiceInactive(None),
+        TestingChatServiceError::ServiceUnavailable => ChatServiceError::ServiceUnavailable(None),
         TestingChatServiceError::ServiceIntentionallyDisconnected => {
             ChatServiceError::ServiceIntentionallyDisconnected
         }
-        TestingChatServiceError::RetryAfter42Seconds => ChatServiceError::RetryLater {
-            retry_after_seconds: 42,
-        },
+        TestingChatServiceError::RetryAfter42Seconds => {
+            ChatServiceError::RetryLater(RateLimit::retry_after(42))
+        }
+        TestingChatServiceError::RequestUnauthorized => ChatServiceError::RequestUnauthorized,
+        TestingChatServiceError::NotFound => ChatServiceError::NotFound,
+        TestingChatServiceError::RetryAfter42SecondsPerAccount => {
+            ChatServiceError::RetryLater(RateLimit {
+                retry_after_seconds: 42,
+                scope: RateLimitScope::PerAccount,
+                reason: Some("fake reason".into()),
+            })
+        }
+        TestingChatServiceError::ServiceUnavailableWithDisconnectInfo => {
+            ChatServiceError::ServiceUnavailable(Some(DisconnectInfo {
+                close_code: Some(4000),
+                reason: Some("go elsewhere".into()),
+                alerts: vec!["critical-idle-primary-device".into()],
+            }))
+        }
+        TestingChatServiceError::ChannelClosedWithDisconnectInfo => {
+            ChatServiceError::ChannelClosed(DisconnectInfo {
+                close_code: Some(4000),
+                reason: Some("go elsewhere".into()),
+                alerts: vec!["critical-idle-primary-device".into()],
+            })
+        }
+        TestingChatServiceError::HttpErrorWithAlerts => {
+            ChatServiceError::WebSocket(WebSocketServiceError::Http(
+                http::Response::builder()
+                    .status(StatusCode::SERVICE_UNAVAILABLE)
+                    .header("x-signal-alert", "critical-idle-primary-device")
+                    .body(None)
+                    .expect("valid response"),
+            ))
+        }
     })
 }
 
@@ -304,6 +354,17 @@ fn TESTING_ChatService_InjectIntentionalDisconnect(chat: &AuthChat) {
         .expect("not closed");
 }
 
+#[bridge_fn(jni = false, ffi = false)]
+fn TESTING_ChatRequestGetHeaderNames(request: &HttpRequest) -> Box<[String]> {
+    request
+        .headers
+        .lock()
+        .expect("not poisoned")
+        .keys()
+        .map(|name| name.to_string())
+        .collect()
+}
+
 #[bridge_fn(jni = false, ffi = false)]
 fn TESTING_ServerMessageAck_Create() -> ServerMessageAck {
     ServerMessageAck::new(Box::new(|_| Box::pin(std::future::ready(Ok(())))))
@@ -10,12 +10,39 @@ use crate::*;
 
 pub struct Aes256GcmEncryption {
     gcm: Option<signal_crypto::Aes256GcmEncryption>,
+    nonce: [u8; signal_crypto::Aes256GcmEncryption::NONCE_SIZE],
 }
 
 impl Aes256GcmEncryption {
     pub fn new(key: &[u8], nonce: &[u8], associated_data: &[u8]) -> Result<Self> {
         let gcm = signal_crypto::Aes256GcmEncryption::new(key, nonce, associated_data)?;
-        Ok(Self { gcm: Some(gcm) })
+        Ok(Self {
+            gcm: Some(gcm),
+            nonce: nonce
+                .try_into()
+                .expect("checked by Aes256GcmEncryption::new"),
+        })
+    }
+
+    pub fn with_nonce_sequence(
+        nonces: &NonceSequence,
+        store: &mut dyn NonceStore,
+        associated_data: &[u8],
+    ) -> Result<Self> {
+        let mut nonces = nonces.0.lock().expect("not poisoned");
+        let (gcm, nonce) = signal_crypto::Aes256GcmEncryption::with_nonce_sequence(
+            &mut nonces,
+            store,
+            associated_data,
+        )?;
+        Ok(Self {
+            gcm: Some(gcm),
+            nonce,
+        })
+    }
+
+    pub fn nonce(&self) -> &[u8] {
+        &self.nonce
     }
 
     pub fn encrypt(&mut self, buf: &mut [u8]) {
@@ -52,6 +79,22 @@ impl Aes256GcmDecryption {
     }
 }
 
+/// A [`signal_crypto::NonceSequence`] that can be shared between threads.
+///
+/// The lock is held while the app's [`NonceStore`] runs, so the store must not use the same
+/// sequence.
+pub struct NonceSequence(std::sync::Mutex<signal_crypto::NonceSequence>);
+
+impl NonceSequence {
+    pub fn new(nonces: signal_crypto::NonceSequence) -> Self {
+        Self(nonces.into())
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.0.lock().expect("not poisoned").remaining()
+    }
+}
+
 // Explicit wrapper for cbindgen purposes.
 pub struct Aes256GcmSiv(pub aes_gcm_siv::Aes256GcmSiv);
 
@@ -61,3 +104,4 @@ bridge_as_handle!(Aes256GcmSiv);
 bridge_as_handle!(Aes256Ctr32, mut = true, node = false);
 bridge_as_handle!(Aes256GcmEncryption, mut = true, node = false);
 bridge_as_handle!(Aes256GcmDecryption, mut = true, node = false);
+bridge_as_handle!(NonceSequence, node = false);
@@ -8,7 +8,9 @@ use std::ffi::{c_uchar, c_void};
 use libsignal_net::chat::ChatServiceError;
 
 use super::*;
-use crate::net::chat::{ChatListener, MakeChatListener, ServerMessageAck};
+use crate::net::chat::{
+    ChatListener, ChatListenerExecutor, ChatListenerTask, MakeChatListener, ServerMessageAck,
+};
 
 type ReceivedIncomingMessage = extern "C" fn(
     ctx: *mut c_void,
@@ -19,12 +21,18 @@ type ReceivedIncomingMessage = extern "C" fn(
 type ReceivedQueueEmpty = extern "C" fn(ctx: *mut c_void);
 type ConnectionInterrupted = extern "C" fn(ctx: *mut c_void, error: *mut SignalFfiError);
 type DestroyChatListener = extern "C" fn(ctx: *mut c_void);
+type ExecuteChatListenerTask = extern "C" fn(ctx: *mut c_void, task: *mut ChatListenerTask);
 
 /// Callbacks for [`ChatListener`].
 ///
 /// Callbacks will be serialized (i.e. two calls will not come in at the same time), but may not
 /// always happen on the same thread. Calls should be responded to promptly to avoid blocking later
 /// messages.
+///
+/// If `execute` is provided, each callback is instead wrapped in a task and passed to `execute`,
+/// which should arrange for it to be run (e.g. on a serial queue) and then destroyed. The next task
+/// won't be submitted until the previous one has run. Destroying a task without running it stops
+/// delivery to this listener.
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct FfiChatListenerStruct {
@@ -33,6 +41,7 @@ pub struct FfiChatListenerStruct {
     received_queue_empty: ReceivedQueueEmpty,
     connection_interrupted: ConnectionInterrupted,
     destroy: DestroyChatListener,
+    execute: Option<ExecuteChatListenerTask>,
 }
 
 pub type FfiMakeChatListenerStruct = FfiChatListenerStruct;
@@ -55,6 +64,29 @@ impl Drop for ChatListenerStruct {
     }
 }
 
+/// Submits tasks using [`FfiChatListenerStruct::execute`].
+///
+/// Shares its `ctx` with the listener, so it must not be used after the listener is destroyed.
+struct ChatListenerExecutorStruct {
+    ctx: *mut c_void,
+    execute: ExecuteChatListenerTask,
+}
+
+// SAFETY: See FfiChatListenerStruct; the executor is only used from the task that owns the
+// listener, one call at a time.
+unsafe impl Send for ChatListenerExecutorStruct {}
+unsafe impl Sync for ChatListenerExecutorStruct {}
+
+impl ChatListenerExecutor for ChatListenerExecutorStruct {
+    fn execute(&self, task: ChatListenerTask) {
+        (self.execute)(
+            self.ctx,
+            task.convert_into()
+                .expect("bridge_as_handle conversion is infallible"),
+        )
+    }
+}
+
 impl ChatListener for ChatListenerStruct {
     fn received_incoming_message(
         &mut self,
@@ -87,4 +119,12 @@ impl ChatListener for ChatListenerStruct {
             error.map_or(std::ptr::null_mut(), Box::into_raw),
         )
     }
+
+    fn take_executor(&mut self) -> Option<Box<dyn ChatListenerExecutor>> {
+        let execute = self.0.execute?;
+        Some(Box::new(ChatListenerExecutorStruct {
+            ctx: self.0.ctx,
+            execute,
+        }))
+    }
 }
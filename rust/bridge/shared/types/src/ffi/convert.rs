@@ -10,12 +10,15 @@ use std::ops::Deref;
 
 use libsignal_protocol::*;
 use paste::paste;
+use signal_crypto::NonceStore;
 use uuid::Uuid;
 
 use super::*;
-use crate::io::{InputStream, SyncInputStream};
+use crate::io::{AsyncInputStream, InputStream, SyncInputStream};
 use crate::net::chat::MakeChatListener;
-use crate::support::{extend_lifetime, AsType, FixedLengthBincodeSerializable, Serialized};
+use crate::support::{
+    extend_lifetime, AsType, Deadline, FixedLengthBincodeSerializable, Serialized,
+};
 
 /// Converts arguments from their FFI form to their Rust form.
 ///
@@ -383,10 +386,29 @@ bridge_trait!(SenderKeyStore);
 bridge_trait!(SessionStore);
 bridge_trait!(SignedPreKeyStore);
 bridge_trait!(KyberPreKeyStore);
+bridge_trait!(ReplayCache);
 bridge_trait!(InputStream);
 bridge_trait!(SyncInputStream);
+bridge_trait!(NonceStore);
 bridge_trait!(MakeChatListener);
 
+/// Unlike the other bridged traits, async input streams are taken by value, because they're used
+/// after the bridge call that receives them returns.
+impl<'a> ArgTypeInfo<'a> for &'a mut dyn AsyncInputStream {
+    type ArgType = *const FfiAsyncInputStreamStruct;
+    type StoredType = OwnedFfiAsyncInputStream;
+    #[allow(clippy::not_unsafe_ptr_arg_deref)]
+    fn borrow(foreign: Self::ArgType) -> SignalFfiResult<Self::StoredType> {
+        match unsafe { foreign.as_ref() } {
+            None => Err(NullPointerError.into()),
+            Some(stream) => Ok(OwnedFfiAsyncInputStream::new(*stream)),
+        }
+    }
+    fn load_from(stored: &'a mut Self::StoredType) -> Self {
+        stored
+    }
+}
+
 impl<T: ResultTypeInfo, E> ResultTypeInfo for Result<T, E>
 where
     E: FfiError,
@@ -437,7 +459,11 @@ impl ResultTypeInfo for Option<&str> {
 impl ResultTypeInfo for Box<[String]> {
     type ResultType = StringArray;
     fn convert_into(self) -> SignalFfiResult<Self::ResultType> {
-        Ok(StringArray::from_iter(&*self))
+        let strings = self
+            .iter()
+            .map(|s| s.as_str().convert_into())
+            .collect::<SignalFfiResult<Box<[CStringPtr]>>>()?;
+        Ok(strings.into())
     }
 }
 
@@ -478,6 +504,14 @@ impl SimpleArgTypeInfo for crate::protocol::Timestamp {
     }
 }
 
+/// Interprets the value as a timeout in milliseconds, where zero means "no deadline".
+impl SimpleArgTypeInfo for Deadline {
+    type ArgType = u64;
+    fn convert_from(foreign: Self::ArgType) -> SignalFfiResult<Self> {
+        Ok(Self::from_timeout_millis(foreign))
+    }
+}
+
 impl ResultTypeInfo for crate::protocol::Timestamp {
     type ResultType = u64;
     fn convert_into(self) -> SignalFfiResult<Self::ResultType> {
@@ -541,6 +575,10 @@ impl<'a, T: BridgeHandle> ArgTypeInfo<'a> for &'a [&'a T] {
         if slice_of_pointers.contains(&std::ptr::null()) {
             return Err(NullPointerError.into());
         }
+        #[cfg(feature = "handle-poisoning")]
+        for &handle in slice_of_pointers {
+            crate::support::handle_poisoning::check(handle)?;
+        }
 
         Ok(foreign)
     }
@@ -559,13 +597,18 @@ impl<'a, T: BridgeHandle> ArgTypeInfo<'a> for &'a [&'a T] {
 
 impl<T: BridgeHandle> ResultTypeInfo for T {
     type ResultType = *mut T;
+    #[track_caller]
     fn convert_into(self) -> SignalFfiResult<Self::ResultType> {
-        Ok(Box::into_raw(Box::new(self)))
+        let handle = Box::into_raw(Box::new(self));
+        #[cfg(feature = "handle-poisoning")]
+        crate::support::handle_poisoning::register(handle);
+        Ok(handle)
     }
 }
 
 impl<T: BridgeHandle> ResultTypeInfo for Option<T> {
     type ResultType = *mut T;
+    #[track_caller]
     fn convert_into(self) -> SignalFfiResult<Self::ResultType> {
         match self {
             Some(obj) => obj.convert_into(),
@@ -663,6 +706,36 @@ impl ResultTypeInfo for libsignal_net::cdsi::LookupResponse {
     }
 }
 
+impl ResultTypeInfo for Box<[libsignal_message_backup::finding::ValidationFinding]> {
+    type ResultType = OwnedBufferOf<FfiMessageBackupValidationFinding>;
+
+    fn convert_into(self) -> SignalFfiResult<Self::ResultType> {
+        let findings = self
+            .into_vec()
+            .into_iter()
+            .map(|finding| {
+                let libsignal_message_backup::finding::ValidationFinding {
+                    frame_index,
+                    field_path,
+                    kind,
+                    severity,
+                    message,
+                } = finding;
+                Ok(FfiMessageBackupValidationFinding {
+                    frame_index: frame_index.map_or(-1, |index| {
+                        index.try_into().expect("frame count fits in an i64")
+                    }),
+                    field_path: field_path.convert_into()?,
+                    kind: kind.into(),
+                    severity: severity.into(),
+                    message: message.convert_into()?,
+                })
+            })
+            .collect::<SignalFfiResult<Vec<_>>>()?;
+        Ok(findings.into_boxed_slice().into())
+    }
+}
+
 impl ResultTypeInfo for libsignal_net::chat::Response {
     type ResultType = FfiChatResponse;
 
@@ -733,6 +806,26 @@ impl ResultTypeInfo for crate::net::chat::ResponseAndDebugInfo {
     }
 }
 
+impl ResultTypeInfo for libsignal_net::chat::background::BackgroundFlushReport {
+    type ResultType = FfiBackgroundFlushReport;
+
+    fn convert_into(self) -> SignalFfiResult<Self::ResultType> {
+        let Self {
+            acks_flushed,
+            acks_unflushed,
+            requests_flushed,
+            requests_unflushed,
+        } = self;
+
+        Ok(FfiBackgroundFlushReport {
+            acks_flushed,
+            acks_unflushed,
+            requests_flushed,
+            requests_unflushed,
+        })
+    }
+}
+
 /// Defines an `extern "C"` function for cloning the given type.
 #[macro_export]
 macro_rules! ffi_bridge_handle_clone {
@@ -838,6 +931,7 @@ macro_rules! ffi_arg_type {
     (Option<String>) => (*const std::ffi::c_char);
     (Option<&str>) => (*const std::ffi::c_char);
     (Timestamp) => (u64);
+    (Deadline) => (u64);
     (Uuid) => (*const [u8; 16]);
     (ServiceId) => (*const libsignal_protocol::ServiceIdFixedWidthBinaryBytes);
     (Aci) => (*const libsignal_protocol::ServiceIdFixedWidthBinaryBytes);
@@ -900,11 +994,13 @@ macro_rules! ffi_result_type {
     (Vec<u8>) => (ffi::OwnedBufferOf<std::ffi::c_uchar>);
     (Box<[String]>) => (ffi::StringArray);
     (Box<[Vec<u8>]>) => (ffi::BytestringArray);
+    (Box<[ValidationFinding]>) => (ffi::OwnedBufferOf<ffi::FfiMessageBackupValidationFinding>);
 
     (LookupResponse) => (ffi::FfiCdsiLookupResponse);
     (ChatResponse) => (ffi::FfiChatResponse);
     (ChatServiceDebugInfo) => (ffi::FfiChatServiceDebugInfo);
     (ResponseAndDebugInfo) => (ffi::FfiResponseAndDebugInfo);
+    (BackgroundFlushReport) => (ffi::FfiBackgroundFlushReport);
 
     // In order to provide a fixed-sized array of the correct length,
     // a serialized type FooBar must have a constant FOO_BAR_LEN that's in scope (and exposed to C).
@@ -10,8 +10,9 @@ use attest::enclave::Error as EnclaveError;
 use attest::hsm_enclave::Error as HsmEnclaveError;
 use device_transfer::Error as DeviceTransferError;
 use libsignal_account_keys::Error as PinError;
-use libsignal_net::chat::ChatServiceError;
-use libsignal_net::infra::ws::WebSocketConnectError;
+use libsignal_net::chat::{ChatServiceError, DisconnectInfo};
+use libsignal_net::infra::ws::{WebSocketConnectError, WebSocketServiceError};
+use libsignal_net::rate_limit::RateLimit;
 use libsignal_net::svr3::Error as Svr3Error;
 use libsignal_net::ws::WebSocketServiceConnectError;
 use libsignal_protocol::*;
@@ -20,7 +21,7 @@ use usernames::{UsernameError, UsernameLinkError};
 use zkgroup::{ZkGroupDeserializationFailure, ZkGroupVerificationFailure};
 
 use super::{FutureCancelled, NullPointerError, UnexpectedPanic};
-use crate::support::describe_panic;
+use crate::support::{describe_panic, DeadlineExceeded, DeadlineOr};
 
 #[derive(Debug)]
 #[repr(C)]
@@ -34,6 +35,7 @@ pub enum SignalErrorCode {
     InvalidType = 6,
     InvalidUtf8String = 7,
     Cancelled = 8,
+    DeadlineExceeded = 9,
 
     ProtobufError = 10,
 
@@ -96,6 +98,8 @@ pub enum SignalErrorCode {
     ConnectionFailed = 148,
     ChatServiceInactive = 149,
     ChatServiceIntentionallyDisconnected = 150,
+    RequestUnauthorized = 151,
+    NotFound = 152,
 
     SvrDataMissing = 160,
     SvrRestoreFailed = 161,
@@ -129,7 +133,7 @@ pub trait FfiError: UpcastAsAny + fmt::Debug + Send + 'static {
     fn provide_uuid(&self) -> Result<uuid::Uuid, WrongErrorKind> {
         Err(WrongErrorKind)
     }
-    fn provide_retry_after_seconds(&self) -> Result<u32, WrongErrorKind> {
+    fn provide_rate_limit(&self) -> Result<RateLimit, WrongErrorKind> {
         Err(WrongErrorKind)
     }
     fn provide_tries_remaining(&self) -> Result<u32, WrongErrorKind> {
@@ -138,6 +142,9 @@ pub trait FfiError: UpcastAsAny + fmt::Debug + Send + 'static {
     fn provide_unknown_fields(&self) -> Result<Vec<String>, WrongErrorKind> {
         Err(WrongErrorKind)
     }
+    fn provide_disconnect_info(&self) -> Result<DisconnectInfo, WrongErrorKind> {
+        Err(WrongErrorKind)
+    }
 }
 
 /// The top-level error type (opaquely) returned to C clients when something goes wrong.
@@ -218,9 +225,9 @@ impl FfiError for SignalProtocolError {
             | Self::BadKEMCiphertextLength(_, _) => SignalErrorCode::InvalidMessage,
             Self::LegacyCiphertextVersion(_) => SignalErrorCode::LegacyCiphertextVersion,
             Self::UnrecognizedCiphertextVersion(_) => SignalErrorCode::UnknownCiphertextVersion,
-            Self::UnrecognizedMessageVersion(_) | Self::UnknownSealedSenderVersion(_) => {
-                SignalErrorCode::UnrecognizedMessageVersion
-            }
+            Self::UnrecognizedMessageVersion(_)
+            | Self::UnknownSealedSenderVersion(_)
+            | Self::UnrecognizedRecordVersion(_) => SignalErrorCode::UnrecognizedMessageVersion,
             Self::FingerprintVersionMismatch(_, _) => SignalErrorCode::FingerprintVersionMismatch,
             Self::FingerprintParsingError => SignalErrorCode::FingerprintParsingError,
             Self::NoKeyTypeIdentifier
@@ -241,7 +248,9 @@ impl FfiError for SignalProtocolError {
             Self::InvalidSessionStructure(_) => SignalErrorCode::InvalidSession,
             Self::InvalidSenderKeySession { .. } => SignalErrorCode::InvalidSenderKeySession,
             Self::InvalidRegistrationId(_, _) => SignalErrorCode::InvalidRegistrationId,
-            Self::DuplicatedMessage(_, _) => SignalErrorCode::DuplicatedMessage,
+            Self::DuplicatedMessage(_, _) | Self::ReplayedMessage(_) => {
+                SignalErrorCode::DuplicatedMessage
+            }
             Self::FfiBindingError(_) => SignalErrorCode::InternalError,
             Self::ApplicationCallbackError(_, _) => SignalErrorCode::CallbackError,
             Self::SealedSenderSelfSend => SignalErrorCode::SealedSenderSelfSend,
@@ -272,6 +281,8 @@ impl FfiError for DeviceTransferError {
         match self {
             Self::KeyDecodingFailed => SignalErrorCode::InvalidKey,
             Self::InternalError(_) => SignalErrorCode::InternalError,
+            Self::InvalidState(_) => SignalErrorCode::InvalidState,
+            Self::UnexpectedMessage(_) => SignalErrorCode::InvalidMessage,
         }
     }
 }
@@ -289,7 +300,8 @@ impl FfiError for HsmEnclaveError {
             Self::TrustedCodeError => SignalErrorCode::UntrustedIdentity,
             Self::InvalidPublicKeyError => SignalErrorCode::InvalidKey,
             Self::InvalidCodeHashError => SignalErrorCode::InvalidArgument,
-            Self::InvalidBridgeStateError => SignalErrorCode::InvalidState,
+            Self::InvalidBridgeStateError | Self::PipelineFull => SignalErrorCode::InvalidState,
+            Self::UnexpectedResponse => SignalErrorCode::InvalidMessage,
         }
     }
 }
@@ -336,6 +348,8 @@ impl FfiError for SignalCryptoError {
             | Self::InvalidNonceSize
             | Self::InvalidInputSize => SignalErrorCode::InvalidArgument,
             Self::InvalidTag => SignalErrorCode::InvalidMessage,
+            Self::NonceLimitReached => SignalErrorCode::InvalidState,
+            Self::NonceStoreFailed(e) => e.code(),
         }
     }
 }
@@ -409,7 +423,8 @@ impl FfiError for UsernameLinkError {
             Self::UsernameLinkDataTooShort
             | Self::HmacMismatch
             | Self::BadCiphertext
-            | Self::InvalidDecryptedDataStructure => SignalErrorCode::UsernameLinkInvalid,
+            | Self::InvalidDecryptedDataStructure
+            | Self::InvalidLinkUrl => SignalErrorCode::UsernameLinkInvalid,
         }
     }
 }
@@ -445,9 +460,7 @@ impl FfiError for libsignal_net::cdsi::LookupError {
                 format!("Protocol error: {self}")
             }
             Self::AttestationError(e) => e.describe(),
-            Self::RateLimited {
-                retry_after_seconds,
-            } => format!("Rate limited; try again after {retry_after_seconds}s"),
+            Self::RateLimited(rate_limit) => format!("Rate limited; try again after {rate_limit}"),
             Self::InvalidToken => "CDSI request token was invalid".to_owned(),
             Self::ConnectTransport(e) => format!("IO error: {e}"),
             Self::WebSocket(e) => format!("WebSocket error: {e}"),
@@ -473,11 +486,9 @@ impl FfiError for libsignal_net::cdsi::LookupError {
         }
     }
 
-    fn provide_retry_after_seconds(&self) -> Result<u32, WrongErrorKind> {
+    fn provide_rate_limit(&self) -> Result<RateLimit, WrongErrorKind> {
         match self {
-            Self::RateLimited {
-                retry_after_seconds,
-            } => Ok(*retry_after_seconds),
+            Self::RateLimited(rate_limit) => Ok(rate_limit.clone()),
             _ => Err(WrongErrorKind),
         }
     }
@@ -509,6 +520,7 @@ impl FfiError for Svr3Error {
             Self::Service(e) => format!("WebSocket error: {e}"),
             Self::Protocol(e) => format!("Protocol error: {e}"),
             Self::AttestationError(inner) => inner.describe(),
+            Self::InvalidShareDistribution(_) => format!("Invalid argument: {self}"),
             Self::RequestFailed(_)
             | Self::RestoreFailed(_)
             | Self::DataMissing
@@ -536,6 +548,7 @@ impl FfiError for Svr3Error {
             Self::RestoreFailed(_) => SignalErrorCode::SvrRestoreFailed,
             Self::DataMissing => SignalErrorCode::SvrDataMissing,
             Self::RotationMachineTooManySteps => SignalErrorCode::SvrRotationMachineTooManySteps,
+            Self::InvalidShareDistribution(_) => SignalErrorCode::InvalidArgument,
         }
     }
 
@@ -551,7 +564,10 @@ impl FfiError for ChatServiceError {
     fn describe(&self) -> String {
         match self {
             Self::WebSocket(e) => format!("WebSocket error: {e}"),
-            Self::AllConnectionRoutesFailed { .. } | Self::ServiceUnavailable => {
+            Self::ChannelClosed(_) => {
+                format!("WebSocket error: {}", WebSocketServiceError::ChannelClosed)
+            }
+            Self::AllConnectionRoutesFailed { .. } | Self::ServiceUnavailable(_) => {
                 "Connection failed".to_owned()
             }
             Self::UnexpectedFrameReceived
@@ -563,22 +579,22 @@ impl FfiError for ChatServiceError {
             Self::Timeout | Self::TimeoutEstablishingConnection { .. } => {
                 "Connect timed out".to_owned()
             }
-            Self::ServiceInactive => "Chat service inactive".to_owned(),
+            Self::ServiceInactive(_) => "Chat service inactive".to_owned(),
             Self::AppExpired => "App expired".to_owned(),
             Self::DeviceDeregistered => "Device deregistered or delinked".to_owned(),
             Self::ServiceIntentionallyDisconnected => {
                 "Chat service explicitly disconnected".to_owned()
             }
-            Self::RetryLater {
-                retry_after_seconds,
-            } => format!("Rate limited; try again after {retry_after_seconds}s"),
+            Self::RetryLater(rate_limit) => format!("Rate limited; try again after {rate_limit}"),
+            Self::RequestUnauthorized => "Request unauthorized".to_owned(),
+            Self::NotFound => "Not found".to_owned(),
         }
     }
 
     fn code(&self) -> SignalErrorCode {
         match self {
-            Self::WebSocket(_) => SignalErrorCode::WebSocket,
-            Self::AllConnectionRoutesFailed { .. } | Self::ServiceUnavailable => {
+            Self::WebSocket(_) | Self::ChannelClosed(_) => SignalErrorCode::WebSocket,
+            Self::AllConnectionRoutesFailed { .. } | Self::ServiceUnavailable(_) => {
                 SignalErrorCode::ConnectionFailed
             }
             Self::UnexpectedFrameReceived
@@ -590,23 +606,26 @@ impl FfiError for ChatServiceError {
             Self::Timeout | Self::TimeoutEstablishingConnection { .. } => {
                 SignalErrorCode::ConnectionTimedOut
             }
-            Self::ServiceInactive => SignalErrorCode::ChatServiceInactive,
+            Self::ServiceInactive(_) => SignalErrorCode::ChatServiceInactive,
             Self::AppExpired => SignalErrorCode::AppExpired,
             Self::DeviceDeregistered => SignalErrorCode::DeviceDeregistered,
             Self::ServiceIntentionallyDisconnected => {
                 SignalErrorCode::ChatServiceIntentionallyDisconnected
             }
             Self::RetryLater { .. } => SignalErrorCode::RateLimited,
+            Self::RequestUnauthorized => SignalErrorCode::RequestUnauthorized,
+            Self::NotFound => SignalErrorCode::NotFound,
         }
     }
-    fn provide_retry_after_seconds(&self) -> Result<u32, WrongErrorKind> {
+    fn provide_rate_limit(&self) -> Result<RateLimit, WrongErrorKind> {
         match self {
-            ChatServiceError::RetryLater {
-                retry_after_seconds,
-            } => Ok(*retry_after_seconds),
+            ChatServiceError::RetryLater(rate_limit) => Ok(rate_limit.clone()),
             _ => Err(WrongErrorKind),
         }
     }
+    fn provide_disconnect_info(&self) -> Result<DisconnectInfo, WrongErrorKind> {
+        self.disconnect_info().ok_or(WrongErrorKind)
+    }
 }
 
 impl FfiError for http::uri::InvalidUri {
@@ -732,6 +751,85 @@ impl FfiError for FutureCancelled {
     }
 }
 
+impl FfiError for DeadlineExceeded {
+    fn describe(&self) -> String {
+        self.to_string()
+    }
+
+    fn code(&self) -> SignalErrorCode {
+        SignalErrorCode::DeadlineExceeded
+    }
+}
+
+impl<E: FfiError> FfiError for DeadlineOr<E> {
+    fn describe(&self) -> String {
+        match self {
+            Self::DeadlineExceeded(e) => e.describe(),
+            Self::Other(e) => e.describe(),
+        }
+    }
+
+    fn code(&self) -> SignalErrorCode {
+        match self {
+            Self::DeadlineExceeded(e) => e.code(),
+            Self::Other(e) => e.code(),
+        }
+    }
+
+    fn provide_address(&self) -> Result<ProtocolAddress, WrongErrorKind> {
+        match self {
+            Self::DeadlineExceeded(_) => Err(WrongErrorKind),
+            Self::Other(e) => e.provide_address(),
+        }
+    }
+
+    fn provide_uuid(&self) -> Result<uuid::Uuid, WrongErrorKind> {
+        match self {
+            Self::DeadlineExceeded(_) => Err(WrongErrorKind),
+            Self::Other(e) => e.provide_uuid(),
+        }
+    }
+
+    fn provide_rate_limit(&self) -> Result<RateLimit, WrongErrorKind> {
+        match self {
+            Self::DeadlineExceeded(_) => Err(WrongErrorKind),
+            Self::Other(e) => e.provide_rate_limit(),
+        }
+    }
+
+    fn provide_tries_remaining(&self) -> Result<u32, WrongErrorKind> {
+        match self {
+            Self::DeadlineExceeded(_) => Err(WrongErrorKind),
+            Self::Other(e) => e.provide_tries_remaining(),
+        }
+    }
+
+    fn provide_unknown_fields(&self) -> Result<Vec<String>, WrongErrorKind> {
+        match self {
+            Self::DeadlineExceeded(_) => Err(WrongErrorKind),
+            Self::Other(e) => e.provide_unknown_fields(),
+        }
+    }
+
+    fn provide_disconnect_info(&self) -> Result<DisconnectInfo, WrongErrorKind> {
+        match self {
+            Self::DeadlineExceeded(_) => Err(WrongErrorKind),
+            Self::Other(e) => e.provide_disconnect_info(),
+        }
+    }
+}
+
+#[cfg(feature = "handle-poisoning")]
+impl FfiError for crate::support::handle_poisoning::UseAfterFree {
+    fn describe(&self) -> String {
+        self.to_string()
+    }
+
+    fn code(&self) -> SignalErrorCode {
+        SignalErrorCode::InvalidState
+    }
+}
+
 pub type SignalFfiResult<T> = Result<T, SignalFfiError>;
 
 /// Represents an error returned by a callback, following the C conventions that 0 means "success".
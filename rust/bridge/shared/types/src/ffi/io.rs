@@ -8,9 +8,12 @@ use std::io;
 
 use async_trait::async_trait;
 use libsignal_protocol::SignalProtocolError;
+use signal_crypto::NonceStore;
 
-use super::CallbackError;
-use crate::io::{InputStream, InputStreamRead, SyncInputStream};
+use super::{CallbackError, ResultTypeInfo as _};
+use crate::io::{
+    AsyncInputStream, AsyncInputStreamCompletion, InputStream, InputStreamRead, SyncInputStream,
+};
 
 type Read =
     extern "C" fn(ctx: *mut c_void, buf: *mut u8, buf_len: usize, amount_read: *mut usize) -> c_int;
@@ -67,3 +70,90 @@ impl SyncInputStream for &FfiInputStreamStruct {
         self.do_skip(amount)
     }
 }
+
+type ReserveNonces = extern "C" fn(ctx: *mut c_void, limit: u64) -> c_int;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct FfiNonceStoreStruct {
+    ctx: *mut c_void,
+    reserve_up_to: ReserveNonces,
+}
+
+impl NonceStore for &FfiNonceStoreStruct {
+    fn reserve_up_to(&mut self, limit: u64) -> io::Result<()> {
+        let result = (self.reserve_up_to)(self.ctx, limit);
+        CallbackError::check(result).map_err(|e| {
+            let err = SignalProtocolError::for_application_callback("reserve_up_to")(e);
+            io::Error::new(io::ErrorKind::Other, err)
+        })
+    }
+}
+
+type AsyncRead =
+    extern "C" fn(ctx: *mut c_void, amount: usize, completion: *mut AsyncInputStreamCompletion);
+type AsyncSkip =
+    extern "C" fn(ctx: *mut c_void, amount: u64, completion: *mut AsyncInputStreamCompletion);
+type DestroyAsyncInputStream = extern "C" fn(ctx: *mut c_void);
+
+/// Callbacks for [`AsyncInputStream`].
+///
+/// `read` and `skip` must return promptly, and report their results later by completing the
+/// given completion (with `signal_async_input_stream_completion_complete` or
+/// `signal_async_input_stream_completion_fail`) and then destroying it. Rust calls `destroy` once
+/// it no longer needs the stream.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct FfiAsyncInputStreamStruct {
+    ctx: *mut c_void,
+    read: AsyncRead,
+    skip: AsyncSkip,
+    destroy: DestroyAsyncInputStream,
+}
+
+/// Owns an [`FfiAsyncInputStreamStruct`] for the duration of an async bridge call.
+pub struct OwnedFfiAsyncInputStream(FfiAsyncInputStreamStruct);
+
+// SAFETY: Async input streams are used from the async runtime's threads. It's up to the creator of
+// the C struct to make sure `ctx` is appropriate for this.
+unsafe impl Send for OwnedFfiAsyncInputStream {}
+unsafe impl Sync for OwnedFfiAsyncInputStream {}
+
+impl OwnedFfiAsyncInputStream {
+    pub fn new(stream: FfiAsyncInputStreamStruct) -> Self {
+        Self(stream)
+    }
+
+    fn start(
+        &self,
+        callback: impl FnOnce(*mut AsyncInputStreamCompletion),
+    ) -> impl std::future::Future<Output = io::Result<Vec<u8>>> + Send {
+        let (completion, result) = AsyncInputStreamCompletion::new();
+        callback(
+            completion
+                .convert_into()
+                .expect("bridge_as_handle conversion is infallible"),
+        );
+        result
+    }
+}
+
+impl Drop for OwnedFfiAsyncInputStream {
+    fn drop(&mut self) {
+        (self.0.destroy)(self.0.ctx);
+    }
+}
+
+#[async_trait]
+impl AsyncInputStream for OwnedFfiAsyncInputStream {
+    async fn read(&self, amount: usize) -> io::Result<Vec<u8>> {
+        self.start(|completion| (self.0.read)(self.0.ctx, amount, completion))
+            .await
+    }
+
+    async fn skip(&self, amount: u64) -> io::Result<()> {
+        self.start(|completion| (self.0.skip)(self.0.ctx, amount, completion))
+            .await?;
+        Ok(())
+    }
+}
@@ -28,6 +28,7 @@ mod storage;
 pub use storage::*;
 
 use crate::support::describe_panic;
+pub use crate::support::metrics::CallTimer;
 
 #[derive(Debug)]
 pub struct NullPointerError;
@@ -113,7 +114,6 @@ pub struct BytestringArray {
     bytes: OwnedBufferOf<std::ffi::c_uchar>,
     lengths: OwnedBufferOf<usize>,
 }
-pub type StringArray = BytestringArray;
 
 impl BytestringArray {
     /// Converts `self` into owned buffers of contents and string lengths.
@@ -163,10 +163,24 @@ pub struct FfiCdsiLookupResponse {
     debug_permits_used: i32,
 }
 
+#[repr(C)]
+#[derive(Debug)]
+pub struct FfiMessageBackupValidationFinding {
+    /// The frame the finding applies to, or -1 if it doesn't apply to a single frame.
+    pub frame_index: i64,
+    pub field_path: *const std::ffi::c_char,
+    pub kind: u8,
+    pub severity: u8,
+    pub message: *const std::ffi::c_char,
+}
+
 /// A type alias to be used with [`OwnedBufferOf`], so that `OwnedBufferOf<c_char>` and
 /// `OwnedBufferOf<*const c_char>` get distinct names.
 pub type CStringPtr = *const std::ffi::c_char;
 
+/// An array of Rust-owned C strings, to be freed with `signal_free_list_of_strings`.
+pub type StringArray = OwnedBufferOf<CStringPtr>;
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct FfiChatResponse {
@@ -191,6 +205,15 @@ pub struct FfiResponseAndDebugInfo {
     debug_info: FfiChatServiceDebugInfo,
 }
 
+#[repr(C)]
+#[derive(Debug)]
+pub struct FfiBackgroundFlushReport {
+    acks_flushed: u32,
+    acks_unflushed: u32,
+    requests_flushed: u32,
+    requests_unflushed: u32,
+}
+
 struct UnexpectedPanic(Box<dyn std::any::Any + Send>);
 
 impl std::fmt::Debug for UnexpectedPanic {
@@ -223,6 +246,8 @@ pub unsafe fn native_handle_cast<T>(handle: *const T) -> Result<&'static T, Sign
     if handle.is_null() {
         return Err(NullPointerError.into());
     }
+    #[cfg(feature = "handle-poisoning")]
+    crate::support::handle_poisoning::check(handle)?;
 
     Ok(&*(handle))
 }
@@ -231,10 +256,13 @@ pub unsafe fn native_handle_cast_mut<T>(handle: *mut T) -> Result<&'static mut T
     if handle.is_null() {
         return Err(NullPointerError.into());
     }
+    #[cfg(feature = "handle-poisoning")]
+    crate::support::handle_poisoning::check(handle)?;
 
     Ok(&mut *handle)
 }
 
+#[track_caller]
 pub unsafe fn write_result_to<T: ResultTypeInfo>(
     ptr: *mut T::ResultType,
     value: T,
@@ -271,6 +299,9 @@ macro_rules! ffi_bridge_handle_destroy {
                 let p = std::panic::AssertUnwindSafe(p);
                 ffi::run_ffi_safe(|| {
                     if !p.is_null() {
+                        #[cfg(feature = "handle-poisoning")]
+                        $crate::support::handle_poisoning::destroy(*p)?;
+                        #[cfg(not(feature = "handle-poisoning"))]
                         drop(Box::from_raw(*p));
                     }
                     Ok(())
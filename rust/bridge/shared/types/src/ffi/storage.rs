@@ -376,6 +376,18 @@ type StoreSenderKey = extern "C" fn(
     distribution_id: *const [u8; 16],
     *const SenderKeyRecord,
 ) -> c_int;
+type LoadSenderKeyDistribution = extern "C" fn(
+    store_ctx: *mut c_void,
+    *mut *mut SenderKeyDistributionRecord,
+    *const ProtocolAddress,
+    distribution_id: *const [u8; 16],
+) -> c_int;
+type StoreSenderKeyDistribution = extern "C" fn(
+    store_ctx: *mut c_void,
+    *const ProtocolAddress,
+    distribution_id: *const [u8; 16],
+    *const SenderKeyDistributionRecord,
+) -> c_int;
 
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -383,6 +395,8 @@ pub struct FfiSenderKeyStoreStruct {
     ctx: *mut c_void,
     load_sender_key: LoadSenderKey,
     store_sender_key: StoreSenderKey,
+    load_sender_key_distribution: LoadSenderKeyDistribution,
+    store_sender_key_distribution: StoreSenderKeyDistribution,
 }
 
 #[async_trait(?Send)]
@@ -421,4 +435,101 @@ impl SenderKeyStore for &FfiSenderKeyStoreStruct {
 
         Ok(Some(*record))
     }
+
+    async fn store_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+        record: &SenderKeyDistributionRecord,
+    ) -> Result<(), SignalProtocolError> {
+        let result = (self.store_sender_key_distribution)(
+            self.ctx,
+            sender,
+            distribution_id.as_bytes(),
+            record,
+        );
+
+        CallbackError::check(result).map_err(SignalProtocolError::for_application_callback(
+            "store_sender_key_distribution",
+        ))
+    }
+
+    async fn load_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+    ) -> Result<Option<SenderKeyDistributionRecord>, SignalProtocolError> {
+        let mut record = std::ptr::null_mut();
+        let result = (self.load_sender_key_distribution)(
+            self.ctx,
+            &mut record,
+            sender,
+            distribution_id.as_bytes(),
+        );
+
+        CallbackError::check(result).map_err(SignalProtocolError::for_application_callback(
+            "load_sender_key_distribution",
+        ))?;
+
+        if record.is_null() {
+            return Ok(None);
+        }
+
+        let record = unsafe { Box::from_raw(record) };
+
+        Ok(Some(*record))
+    }
+}
+
+type ContainsReplay = extern "C" fn(
+    store_ctx: *mut c_void,
+    address: *const ProtocolAddress,
+    sender_ratchet_key: *const PublicKey,
+    counter: u32,
+) -> c_int;
+type InsertReplay = extern "C" fn(
+    store_ctx: *mut c_void,
+    address: *const ProtocolAddress,
+    sender_ratchet_key: *const PublicKey,
+    counter: u32,
+) -> c_int;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct FfiReplayCacheStruct {
+    ctx: *mut c_void,
+    contains: ContainsReplay,
+    insert: InsertReplay,
+}
+
+#[async_trait(?Send)]
+impl ReplayCache for &FfiReplayCacheStruct {
+    async fn contains(
+        &self,
+        address: &ProtocolAddress,
+        sender_ratchet_key: &PublicKey,
+        counter: u32,
+    ) -> Result<bool, SignalProtocolError> {
+        let result = (self.contains)(self.ctx, address, sender_ratchet_key, counter);
+
+        match result {
+            0 => Ok(false),
+            1 => Ok(true),
+            r => Err(SignalProtocolError::for_application_callback("contains")(
+                CallbackError::check(r).expect_err("verified non-zero"),
+            )),
+        }
+    }
+
+    async fn insert(
+        &mut self,
+        address: &ProtocolAddress,
+        sender_ratchet_key: &PublicKey,
+        counter: u32,
+    ) -> Result<(), SignalProtocolError> {
+        let result = (self.insert)(self.ctx, address, sender_ratchet_key, counter);
+
+        CallbackError::check(result)
+            .map_err(SignalProtocolError::for_application_callback("insert"))
+    }
 }
@@ -3,9 +3,10 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use std::num::NonZeroUsize;
 use std::panic::RefUnwindSafe;
 
-use ::attest::hsm_enclave::Result;
+use ::attest::hsm_enclave::{RequestId, Result};
 use ::attest::{client_connection, hsm_enclave};
 
 use crate::*;
@@ -16,9 +17,18 @@ use crate::*;
 pub enum HsmEnclaveClient {
     ConnectionEstablishment(hsm_enclave::ClientConnectionEstablishment),
     Connection(client_connection::ClientConnection),
+    PipelinedConnection(PipelinedClient),
     InvalidConnectionState,
 }
 
+/// A connection that has opted into pipelining, along with the ids of the most recent request and
+/// response so they can be fetched separately over the bridge.
+pub struct PipelinedClient {
+    connection: hsm_enclave::PipelinedConnection,
+    last_sent: Option<RequestId>,
+    last_received: Option<RequestId>,
+}
+
 impl RefUnwindSafe for HsmEnclaveClient {}
 
 impl HsmEnclaveClient {
@@ -67,6 +77,11 @@ impl HsmEnclaveClient {
                 Ok(v) => Ok(v),
                 Err(e) => Err(hsm_enclave::Error::HSMCommunicationError(e)),
             },
+            HsmEnclaveClient::PipelinedConnection(c) => {
+                let (id, ciphertext) = c.connection.send(plaintext_to_send)?;
+                c.last_sent = Some(id);
+                Ok(ciphertext)
+            }
             _ => Err(hsm_enclave::Error::InvalidBridgeStateError),
         }
     }
@@ -77,9 +92,69 @@ impl HsmEnclaveClient {
                 Ok(v) => Ok(v),
                 Err(e) => Err(hsm_enclave::Error::HSMCommunicationError(e)),
             },
+            HsmEnclaveClient::PipelinedConnection(c) => {
+                let (id, plaintext) = c.connection.recv(received_ciphertext)?;
+                c.last_received = Some(id);
+                Ok(plaintext)
+            }
+            _ => Err(hsm_enclave::Error::InvalidBridgeStateError),
+        }
+    }
+
+    /// Allows up to `max_in_flight` requests to await responses; zero is treated as one.
+    ///
+    /// Until this is called, an established connection behaves as it always has, with no limit
+    /// and no tracking of requests. Enabling pipelining on a connection with requests already in
+    /// flight would mismatch their responses, so it should be done before the first send. Calling
+    /// it again only changes the limit.
+    pub fn enable_pipelining(&mut self, max_in_flight: usize) -> Result<()> {
+        let max_in_flight = NonZeroUsize::new(max_in_flight).unwrap_or(NonZeroUsize::MIN);
+        match std::mem::replace(self, HsmEnclaveClient::InvalidConnectionState) {
+            HsmEnclaveClient::Connection(c) => {
+                *self = HsmEnclaveClient::PipelinedConnection(PipelinedClient {
+                    connection: hsm_enclave::PipelinedConnection::new(c, max_in_flight),
+                    last_sent: None,
+                    last_received: None,
+                });
+                Ok(())
+            }
+            HsmEnclaveClient::PipelinedConnection(mut c) => {
+                c.connection.set_max_in_flight(max_in_flight);
+                *self = HsmEnclaveClient::PipelinedConnection(c);
+                Ok(())
+            }
+            other => {
+                *self = other;
+                Err(hsm_enclave::Error::InvalidBridgeStateError)
+            }
+        }
+    }
+
+    fn pipelined(&self) -> Result<&PipelinedClient> {
+        match self {
+            HsmEnclaveClient::PipelinedConnection(c) => Ok(c),
             _ => Err(hsm_enclave::Error::InvalidBridgeStateError),
         }
     }
+
+    pub fn in_flight(&self) -> Result<usize> {
+        Ok(self.pipelined()?.connection.in_flight())
+    }
+
+    /// The id of the request most recently encrypted by [`Self::established_send`].
+    pub fn last_sent_request_id(&self) -> Result<RequestId> {
+        self.pipelined()?
+            .last_sent
+            .ok_or(hsm_enclave::Error::InvalidBridgeStateError)
+    }
+
+    /// The id of the request answered by the response most recently decrypted by
+    /// [`Self::established_recv`].
+    pub fn last_received_request_id(&self) -> Result<RequestId> {
+        self.pipelined()?
+            .last_received
+            .ok_or(hsm_enclave::Error::InvalidBridgeStateError)
+    }
 }
 
 bridge_as_handle!(HsmEnclaveClient, mut = true);
@@ -3,14 +3,20 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use std::future::Future;
 use std::io;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use async_trait::async_trait;
-use futures_util::future::LocalBoxFuture;
+use atomic_take::AtomicTake;
+use futures_util::future::{BoxFuture, LocalBoxFuture};
 use futures_util::{AsyncRead, FutureExt as _};
 use mediasan_common::{AsyncSkip, Skip};
+use tokio::sync::oneshot;
+
+use crate::support::{Deadline, DeadlineExceeded};
+use crate::*;
 
 /// The result of a [`InputStream::read`].
 ///
@@ -83,6 +89,82 @@ pub trait SyncInputStream {
     fn skip(&self, amount: u64) -> io::Result<()>;
 }
 
+/// An input stream of bytes whose operations complete asynchronously.
+///
+/// Unlike [`InputStream`], which app-side implementations satisfy by reading synchronously, every
+/// operation here is handed off to the app and completed later through an
+/// [`AsyncInputStreamCompletion`], so no thread is blocked waiting on app I/O. Implementations and
+/// their futures are `Send`, so bridge functions taking one can run on a multi-threaded runtime.
+///
+/// This is only bridged to Java and Swift. Node's [`InputStream`] is already backed by JavaScript
+/// promises, so it never blocks a thread in the first place. The WebP sanitizer doesn't take one
+/// either: `webpsan` can only parse synchronously, and it runs on the caller's thread rather than
+/// the tokio pool. libsignal has no upload client to adopt it in.
+#[async_trait]
+pub trait AsyncInputStream: Send + Sync {
+    /// Read up to `amount` bytes from the input stream.
+    ///
+    /// Returning zero bytes indicates that the end of the stream has been reached. Returning more
+    /// than `amount` bytes is an error.
+    async fn read(&self, amount: usize) -> io::Result<Vec<u8>>;
+
+    /// Skip an amount of bytes in the input stream.
+    ///
+    /// # Errors
+    ///
+    /// If the requested number of bytes could not be skipped for any reason, including if the end of stream was
+    /// reached, an error must be returned.
+    async fn skip(&self, amount: u64) -> io::Result<()>;
+}
+
+/// Delivers the result of one [`AsyncInputStream`] operation from the app back to Rust.
+///
+/// Each read or skip passes a new completion to the app, which must complete it exactly once (and
+/// then destroy it). Dropping a completion without completing it fails the operation.
+pub struct AsyncInputStreamCompletion {
+    sender: AtomicTake<oneshot::Sender<io::Result<Vec<u8>>>>,
+}
+
+impl AsyncInputStreamCompletion {
+    /// Creates a completion along with a future that resolves when it is completed.
+    pub fn new() -> (Self, impl Future<Output = io::Result<Vec<u8>>> + Send) {
+        let (sender, receiver) = oneshot::channel();
+        let completion = Self {
+            sender: AtomicTake::new(sender),
+        };
+        let result = receiver.map(|result| {
+            result.unwrap_or_else(|_| {
+                Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "input stream operation was abandoned without completing",
+                ))
+            })
+        });
+        (completion, result)
+    }
+
+    /// Reports the outcome of the operation. Only the first call has any effect.
+    pub fn complete(&self, result: io::Result<Vec<u8>>) {
+        if let Some(sender) = self.sender.take() {
+            // The receiver is gone if the bridge call was cancelled; that's fine.
+            _ = sender.send(result);
+        }
+    }
+}
+
+bridge_as_handle!(AsyncInputStreamCompletion, node = false);
+
+// See the equivalent comment on ServerMessageAck: the `AtomicTake` is only manipulated atomically.
+impl std::panic::RefUnwindSafe for AsyncInputStreamCompletion {}
+
+/// Fails once the [current deadline](Deadline::check_current) has passed.
+///
+/// The adapters below call this before starting each read or skip, so that bridge functions taking
+/// a [`Deadline`] stop consuming input once it passes.
+fn check_deadline() -> io::Result<()> {
+    Deadline::check_current().map_err(DeadlineExceeded::into_io_error)
+}
+
 pub struct SyncInput<'a> {
     stream: &'a dyn SyncInputStream,
     pos: u64,
@@ -117,6 +199,34 @@ impl<'a> AsyncInput<'a> {
     }
 }
 
+/// Adapts an [`AsyncInputStream`] to [`AsyncRead`] and [`AsyncSkip`], like [`AsyncInput`] does for
+/// [`InputStream`].
+pub struct AsyncInputReader<'a> {
+    stream: &'a dyn AsyncInputStream,
+    state: AsyncInputReaderState<'a>,
+    pos: u64,
+    len: u64,
+}
+
+impl<'a> AsyncInputReader<'a> {
+    pub fn new(stream: &'a dyn AsyncInputStream, len: u64) -> Self {
+        Self {
+            stream,
+            state: AsyncInputReaderState::default(),
+            pos: 0,
+            len,
+        }
+    }
+}
+
+#[derive(Default)]
+enum AsyncInputReaderState<'a> {
+    #[default]
+    Idle,
+    Reading(BoxFuture<'a, io::Result<Vec<u8>>>),
+    Skipping(BoxFuture<'a, io::Result<()>>),
+}
+
 #[derive(Default)]
 enum AsyncInputState<'a> {
     #[default]
@@ -127,6 +237,7 @@ enum AsyncInputState<'a> {
 
 impl std::io::Read for SyncInput<'_> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        check_deadline()?;
         let amount_read = self.stream.read(buf)?;
         let new_pos = self.pos.checked_add(amount_read as u64);
         self.pos = new_pos
@@ -137,6 +248,7 @@ impl std::io::Read for SyncInput<'_> {
 
 impl Skip for SyncInput<'_> {
     fn skip(&mut self, amount: u64) -> io::Result<()> {
+        check_deadline()?;
         self.stream.skip(amount)?;
         self.pos = self
             .pos
@@ -161,7 +273,10 @@ impl AsyncRead for AsyncInput<'_> {
         buf: &mut [u8],
     ) -> Poll<io::Result<usize>> {
         let input_stream_read = match std::mem::take(&mut self.state) {
-            AsyncInputState::Idle => self.stream.read(buf)?,
+            AsyncInputState::Idle => {
+                check_deadline()?;
+                self.stream.read(buf)?
+            }
             AsyncInputState::Reading(read_future) => InputStreamRead::Pending(read_future),
             AsyncInputState::Skipping { .. } => {
                 return Poll::Ready(Err(io::Error::new(
@@ -201,7 +316,10 @@ impl AsyncSkip for AsyncInput<'_> {
         amount: u64,
     ) -> Poll<io::Result<()>> {
         let mut skip_future = match std::mem::take(&mut self.state) {
-            AsyncInputState::Idle => self.stream.skip(amount),
+            AsyncInputState::Idle => {
+                check_deadline()?;
+                self.stream.skip(amount)
+            }
             AsyncInputState::Skipping(skip_future) => skip_future,
             AsyncInputState::Reading { .. } => {
                 return Poll::Ready(Err(io::Error::new(
@@ -234,3 +352,90 @@ impl AsyncSkip for AsyncInput<'_> {
         Poll::Ready(Ok(self.len))
     }
 }
+
+impl AsyncRead for AsyncInputReader<'_> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut read_future = match std::mem::take(&mut self.state) {
+            AsyncInputReaderState::Idle => {
+                check_deadline()?;
+                self.stream.read(buf.len())
+            }
+            AsyncInputReaderState::Reading(read_future) => read_future,
+            AsyncInputReaderState::Skipping(_) => {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "cannot read while skipping",
+                )))
+            }
+        };
+
+        let data = match read_future.poll_unpin(cx) {
+            Poll::Ready(result) => result?,
+            Poll::Pending => {
+                self.state = AsyncInputReaderState::Reading(read_future);
+                return Poll::Pending;
+            }
+        };
+        let Some(buf) = buf.get_mut(..data.len()) else {
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "input stream returned more bytes than requested",
+            )));
+        };
+        buf.copy_from_slice(&data);
+
+        let new_pos = self.pos.checked_add(data.len() as u64);
+        self.pos = new_pos
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "input length overflow"))?;
+
+        Poll::Ready(Ok(data.len()))
+    }
+}
+
+impl AsyncSkip for AsyncInputReader<'_> {
+    fn poll_skip(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        amount: u64,
+    ) -> Poll<io::Result<()>> {
+        let mut skip_future = match std::mem::take(&mut self.state) {
+            AsyncInputReaderState::Idle => {
+                check_deadline()?;
+                self.stream.skip(amount)
+            }
+            AsyncInputReaderState::Skipping(skip_future) => skip_future,
+            AsyncInputReaderState::Reading(_) => {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "cannot skip while reading",
+                )))
+            }
+        };
+        match skip_future.poll_unpin(cx) {
+            Poll::Ready(Ok(())) => {
+                self.pos = self.pos.checked_add(amount).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::UnexpectedEof, "input length overflow")
+                })?;
+
+                Poll::Ready(Ok(()))
+            }
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => {
+                self.state = AsyncInputReaderState::Skipping(skip_future);
+                Poll::Pending
+            }
+        }
+    }
+
+    fn poll_stream_position(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.pos))
+    }
+
+    fn poll_stream_len(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.len))
+    }
+}
@@ -2,14 +2,47 @@
 // Copyright 2024 Signal Messenger, LLC.
 // SPDX-License-Identifier: AGPL-3.0-only
 //
-use jni::objects::{GlobalRef, JClass, JObject, JValue};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use jni::objects::{GlobalRef, JClass, JMethodID, JObject, JValue, WeakRef};
+use jni::signature::{ReturnType, TypeSignature};
 use jni::JNIEnv;
-use once_cell::sync::OnceCell;
+use once_cell::sync::{Lazy, OnceCell};
 
 use crate::jni::{BridgeLayerError, HandleJniError};
 
 static CACHED_CLASS_LOADER: OnceCell<GlobalRef> = OnceCell::new();
 
+/// Classes used on every store callback, resolved once by [`preload_classes`].
+const PRELOADED_CLASSES: &[&str] = &[
+    "org.signal.libsignal.protocol.IdentityKey",
+    "org.signal.libsignal.protocol.SignalProtocolAddress",
+    "org.signal.libsignal.protocol.ecc.ECPublicKey",
+    "org.signal.libsignal.protocol.groups.state.SenderKeyDistributionRecord",
+    "org.signal.libsignal.protocol.groups.state.SenderKeyRecord",
+    "org.signal.libsignal.protocol.groups.state.SenderKeyStore",
+    "org.signal.libsignal.protocol.state.IdentityKeyStore",
+    "org.signal.libsignal.protocol.state.IdentityKeyStore$Direction",
+    "org.signal.libsignal.protocol.state.KyberPreKeyRecord",
+    "org.signal.libsignal.protocol.state.KyberPreKeyStore",
+    "org.signal.libsignal.protocol.state.PreKeyRecord",
+    "org.signal.libsignal.protocol.state.PreKeyStore",
+    "org.signal.libsignal.protocol.state.ReplayCache",
+    "org.signal.libsignal.protocol.state.SessionRecord",
+    "org.signal.libsignal.protocol.state.SessionStore",
+    "org.signal.libsignal.protocol.state.SignedPreKeyRecord",
+    "org.signal.libsignal.protocol.state.SignedPreKeyStore",
+];
+
+/// Weak references, so that caching a class doesn't keep it from being unloaded.
+static PRELOADED_CLASS_REFS: OnceCell<HashMap<&'static str, WeakRef>> = OnceCell::new();
+
+type MethodKey = (&'static str, &'static str, &'static str);
+
+static CACHED_METHOD_IDS: Lazy<RwLock<HashMap<MethodKey, (JMethodID, ReturnType)>>> =
+    Lazy::new(Default::default);
+
 /// Saves the class loader from the provided `java.lang.Class` instance.
 ///
 /// This is useful on Android where natively-spawned threads use a
@@ -33,6 +66,28 @@ pub fn save_class_loader(
     Ok(())
 }
 
+/// Resolves the classes that are looked up on hot paths, so that [`find_class`] can skip the lookup.
+///
+/// Should be called once, when the library is loaded (and after [`save_class_loader`], if that's
+/// going to be called at all).
+pub fn preload_classes(env: &mut JNIEnv<'_>) -> Result<(), BridgeLayerError> {
+    let _saved = PRELOADED_CLASS_REFS.get_or_try_init(|| {
+        PRELOADED_CLASSES
+            .iter()
+            .map(|&name| {
+                let class = load_class(env, ClassName(name))?;
+                let class_ref = env
+                    .new_weak_ref(&class)
+                    .check_exceptions(env, "NewWeakGlobalRef")?
+                    .expect("class is not null");
+                env.delete_local_ref(class).expect_no_exceptions()?;
+                Ok((name, class_ref))
+            })
+            .collect::<Result<_, BridgeLayerError>>()
+    })?;
+    Ok(())
+}
+
 /// Wrapper type for a Java class name in [binary name] format.
 ///
 /// [binary name]: https://docs.oracle.com/javase/8/docs/api/java/lang/ClassLoader.html#name
@@ -47,11 +102,30 @@ impl std::fmt::Display for ClassName<'_> {
 
 /// Looks up a class by name.
 ///
-/// Uses the cached class loader, if there is one, or the provided `JNIEnv`. Use
+/// Uses the classes resolved by [`preload_classes`] when possible. Otherwise,
+/// uses the cached class loader, if there is one, or the provided `JNIEnv`. Use
 /// this instead of [`JNIEnv::find_class`].
 pub fn find_class<'output>(
     env: &mut JNIEnv<'output>,
     class_name: ClassName<'_>,
+) -> Result<JClass<'output>, BridgeLayerError> {
+    if let Some(class_ref) = PRELOADED_CLASS_REFS
+        .get()
+        .and_then(|classes| classes.get(class_name.0))
+    {
+        if let Some(class) = class_ref
+            .upgrade_local(env)
+            .check_exceptions(env, "NewLocalRef")?
+        {
+            return Ok(class.into());
+        }
+    }
+    load_class(env, class_name)
+}
+
+fn load_class<'output>(
+    env: &mut JNIEnv<'output>,
+    class_name: ClassName<'_>,
 ) -> Result<JClass<'output>, BridgeLayerError> {
     let Some(class_loader) = CACHED_CLASS_LOADER.get() else {
         let jni_name = jni_name_from_binary_name(class_name);
@@ -70,6 +144,37 @@ pub fn find_class<'output>(
     Ok(class.into())
 }
 
+/// Looks up the ID and return type of a method declared by `class_name`, caching the result.
+///
+/// The ID stays valid as long as the class is loaded, which is guaranteed while any instance of
+/// it is around to call the method on.
+pub fn get_cached_method_id(
+    env: &mut JNIEnv<'_>,
+    class_name: ClassName<'static>,
+    fn_name: &'static str,
+    sig: &'static str,
+) -> Result<(JMethodID, ReturnType), BridgeLayerError> {
+    let key = (class_name.0, fn_name, sig);
+    if let Some(cached) = CACHED_METHOD_IDS.read().expect("not poisoned").get(&key) {
+        return Ok(cached.clone());
+    }
+
+    let class = find_class(env, class_name)?;
+    let method_id = env
+        .get_method_id(&class, fn_name, sig)
+        .check_exceptions(env, fn_name)?;
+    env.delete_local_ref(class).expect_no_exceptions()?;
+    let return_type = TypeSignature::from_str(sig)
+        .expect("signatures are produced by jni_args!")
+        .ret;
+
+    CACHED_METHOD_IDS
+        .write()
+        .expect("not poisoned")
+        .insert(key, (method_id, return_type.clone()));
+    Ok((method_id, return_type))
+}
+
 /// Equivalent to [`JNIEnv::find_class`].
 ///
 /// That function is marked as disallowed because its behavior is different on
@@ -12,12 +12,13 @@ use jni::JNIEnv;
 use libsignal_net::cdsi::LookupResponseEntry;
 use libsignal_protocol::*;
 use paste::paste;
+use signal_crypto::NonceStore;
 
 use super::*;
-use crate::io::{InputStream, SyncInputStream};
+use crate::io::{AsyncInputStream, InputStream, SyncInputStream};
 use crate::message_backup::MessageBackupValidationOutcome;
 use crate::net::chat::ResponseAndDebugInfo;
-use crate::support::{Array, AsType, FixedLengthBincodeSerializable, Serialized};
+use crate::support::{Array, AsType, Deadline, FixedLengthBincodeSerializable, Serialized};
 
 /// Converts arguments from their JNI form to their Rust form.
 ///
@@ -215,6 +216,22 @@ impl SimpleArgTypeInfo<'_> for crate::zkgroup::Timestamp {
     }
 }
 
+/// Interprets the value as a timeout in milliseconds, where zero means "no deadline".
+///
+/// Negative `long` values are rejected.
+impl SimpleArgTypeInfo<'_> for Deadline {
+    type ArgType = jlong;
+    fn convert_from(_env: &mut JNIEnv, foreign: &jlong) -> Result<Self, BridgeLayerError> {
+        if *foreign < 0 {
+            return Err(BridgeLayerError::IntegerOverflow(format!(
+                "{} to Deadline (u64)",
+                foreign
+            )));
+        }
+        Ok(Self::from_timeout_millis(*foreign as u64))
+    }
+}
+
 /// Supports all valid byte values `0..=255`.
 impl SimpleArgTypeInfo<'_> for u8 {
     type ArgType = jint;
@@ -453,8 +470,28 @@ bridge_trait!(SenderKeyStore);
 bridge_trait!(SessionStore);
 bridge_trait!(SignedPreKeyStore);
 bridge_trait!(KyberPreKeyStore);
+bridge_trait!(ReplayCache);
 bridge_trait!(InputStream);
 bridge_trait!(SyncInputStream);
+bridge_trait!(NonceStore);
+
+/// Unlike the other bridged traits, async input streams hold a global reference to the Java
+/// object, because they're used after the bridge call that receives them returns.
+impl<'storage, 'param: 'storage, 'context: 'param> ArgTypeInfo<'storage, 'param, 'context>
+    for &'storage mut dyn AsyncInputStream
+{
+    type ArgType = JavaAsyncInputStream<'context>;
+    type StoredType = JniAsyncInputStream;
+    fn borrow(
+        env: &mut JNIEnv<'context>,
+        store: &'param Self::ArgType,
+    ) -> Result<Self::StoredType, BridgeLayerError> {
+        JniAsyncInputStream::new(env, store)
+    }
+    fn load_from(stored: &'storage mut Self::StoredType) -> Self {
+        stored
+    }
+}
 
 /// A translation from a Java interface where the implementing class wraps the Rust handle.
 impl<'a> SimpleArgTypeInfo<'a> for CiphertextMessageRef<'a> {
@@ -822,6 +859,9 @@ impl<'storage, 'param: 'storage, 'context: 'param, T: BridgeHandle>
         array
             .iter()
             .map(|&raw_handle| unsafe {
+                #[cfg(feature = "handle-poisoning")]
+                crate::support::handle_poisoning::check(raw_handle as *const T)
+                    .map_err(BridgeLayerError::UseAfterFree)?;
                 (raw_handle as *const T)
                     .as_ref()
                     .ok_or(BridgeLayerError::NullPointer(None))
@@ -835,13 +875,18 @@ impl<'storage, 'param: 'storage, 'context: 'param, T: BridgeHandle>
 
 impl<T: BridgeHandle> ResultTypeInfo<'_> for T {
     type ResultType = ObjectHandle;
+    #[track_caller]
     fn convert_into(self, _env: &mut JNIEnv) -> Result<Self::ResultType, BridgeLayerError> {
-        Ok(Box::into_raw(Box::new(self)) as ObjectHandle)
+        let handle = Box::into_raw(Box::new(self));
+        #[cfg(feature = "handle-poisoning")]
+        crate::support::handle_poisoning::register(handle);
+        Ok(handle as ObjectHandle)
     }
 }
 
 impl<T: BridgeHandle> ResultTypeInfo<'_> for Option<T> {
     type ResultType = ObjectHandle;
+    #[track_caller]
     fn convert_into(self, env: &mut JNIEnv) -> Result<Self::ResultType, BridgeLayerError> {
         match self {
             Some(obj) => obj.convert_into(env),
@@ -1155,6 +1200,32 @@ impl<'a> ResultTypeInfo<'a> for ResponseAndDebugInfo {
     }
 }
 
+impl<'a> ResultTypeInfo<'a> for libsignal_net::chat::background::BackgroundFlushReport {
+    type ResultType = JObject<'a>;
+
+    fn convert_into(self, env: &mut JNIEnv<'a>) -> Result<Self::ResultType, BridgeLayerError> {
+        let Self {
+            acks_flushed,
+            acks_unflushed,
+            requests_flushed,
+            requests_unflushed,
+        } = self;
+
+        let as_int = |count: u32| -> i32 { count.try_into().unwrap_or(i32::MAX) };
+
+        new_instance(
+            env,
+            ClassName("org.signal.libsignal.net.ChatService$BackgroundFlushReport"),
+            jni_args!((
+                as_int(acks_flushed) => int,
+                as_int(acks_unflushed) => int,
+                as_int(requests_flushed) => int,
+                as_int(requests_unflushed) => int,
+            ) -> void),
+        )
+    }
+}
+
 /// Converts each element of `it` to a Java object, storing the result in an array.
 ///
 /// `element_type_signature` should use [`jni_class_name`] if it's a plain class and
@@ -1214,22 +1285,53 @@ impl<'a> ResultTypeInfo<'a> for MessageBackupValidationOutcome {
     type ResultType = JObject<'a>;
 
     fn convert_into(self, env: &mut JNIEnv<'a>) -> Result<Self::ResultType, BridgeLayerError> {
+        // The error message and unknown field messages are also present in the findings, so
+        // there's no need to pass them separately.
         let Self {
-            error_message,
-            found_unknown_fields,
+            error_message: _,
+            found_unknown_fields: _,
+            findings,
         } = self;
 
-        let unknown_fields = make_object_array(
+        Ok(make_object_array(
             env,
-            jni_class_name!(java.lang.String),
-            found_unknown_fields.into_iter().map(|f| f.to_string()),
-        )?;
-        let error_message = error_message.convert_into(env)?;
+            jni_class_name!(org.signal.libsignal.messagebackup.ValidationFinding),
+            findings,
+        )?
+        .into())
+    }
+}
+
+impl<'a> ResultTypeInfo<'a> for libsignal_message_backup::finding::ValidationFinding {
+    type ResultType = JObject<'a>;
+
+    fn convert_into(self, env: &mut JNIEnv<'a>) -> Result<Self::ResultType, BridgeLayerError> {
+        let Self {
+            frame_index,
+            field_path,
+            kind,
+            severity,
+            message,
+        } = self;
+
+        let frame_index: jlong = frame_index.map_or(-1, |index| {
+            index.try_into().expect("frame count fits in a jlong")
+        });
+        let field_path = field_path.convert_into(env)?;
+        let kind: jint = u8::from(kind).into();
+        let severity: jint = u8::from(severity).into();
+        let message = message.convert_into(env)?;
 
         new_instance(
             env,
-            ClassName("org.signal.libsignal.protocol.util.Pair"),
-            jni_args!((error_message => java.lang.Object, unknown_fields => java.lang.Object) -> void),
+            ClassName("org.signal.libsignal.messagebackup.ValidationFinding"),
+            jni_args!((
+                frame_index => long,
+                field_path => java.lang.String,
+                kind => int,
+                severity => int,
+                message => java.lang.String,
+            ) -> void),
         )
     }
 }
@@ -1324,13 +1426,13 @@ macro_rules! jni_arg_type {
         ::jni::objects::JString<'local>
     };
     (Option<String>) => {
-        ::jni::objects::JString<'local>
+        $crate::jni::Nullable<::jni::objects::JString<'local>>
     };
     (&[u8]) => {
         ::jni::objects::JByteArray<'local>
     };
     (Option<&[u8]>) => {
-        ::jni::objects::JByteArray<'local>
+        $crate::jni::Nullable<::jni::objects::JByteArray<'local>>
     };
     (&mut [u8]) => {
         ::jni::objects::JByteArray<'local>
@@ -1359,6 +1461,9 @@ macro_rules! jni_arg_type {
     (Timestamp) => {
         ::jni::sys::jlong
     };
+    (Deadline) => {
+        ::jni::sys::jlong
+    };
     (Uuid) => {
         $crate::jni::JavaUUID<'local>
     };
@@ -1416,14 +1521,26 @@ macro_rules! jni_result_type {
         $crate::jni::Throwing<jni_result_type!(&$typ)>
     };
     (Result<Option<&$typ:tt> $(, $_:ty)?>) => {
-        $crate::jni::Throwing<jni_result_type!(&$typ)>
+        $crate::jni::Throwing<jni_result_type!(Option<&$typ>)>
     };
     (Result<Option<$typ:tt<$($args:tt),+> > $(, $_:ty)?>) => {
-        $crate::jni::Throwing<jni_result_type!($typ<$($args),+>)>
+        $crate::jni::Throwing<jni_result_type!(Option<$typ<$($args),+> >)>
     };
     (Result<$typ:tt<$($args:tt),+> $(, $_:ty)?>) => {
         $crate::jni::Throwing<jni_result_type!($typ<$($args),+>)>
     };
+    (Option<String>) => {
+        $crate::jni::Nullable<::jni::objects::JString<'local>>
+    };
+    (Option<&str>) => {
+        $crate::jni::Nullable<::jni::objects::JString<'local>>
+    };
+    (Option<&[u8]>) => {
+        $crate::jni::Nullable<::jni::objects::JByteArray<'local>>
+    };
+    (Option<Vec<u8> >) => {
+        $crate::jni::Nullable<::jni::objects::JByteArray<'local>>
+    };
     (Option<$typ:tt>) => {
         $crate::jni_result_type!($typ)
     };
@@ -1515,6 +1632,9 @@ macro_rules! jni_result_type {
     (ResponseAndDebugInfo) => {
         ::jni::objects::JObject<'local>
     };
+    (BackgroundFlushReport) => {
+        ::jni::objects::JObject<'local>
+    };
     (CiphertextMessage) => {
         jni::JavaCiphertextMessage<'local>
     };
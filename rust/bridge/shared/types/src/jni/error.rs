@@ -4,7 +4,6 @@
 //
 use std::fmt;
 use std::io::{Error as IoError, ErrorKind as IoErrorKind};
-use std::time::Duration;
 
 use attest::hsm_enclave::Error as HsmEnclaveError;
 use device_transfer::Error as DeviceTransferError;
@@ -23,7 +22,7 @@ use zkgroup::{ZkGroupDeserializationFailure, ZkGroupVerificationFailure};
 
 use super::*;
 use crate::net::cdsi::CdsiError;
-use crate::support::describe_panic;
+use crate::support::{describe_panic, DeadlineExceeded, DeadlineOr};
 
 /// The top-level error type for when something goes wrong.
 #[derive(Debug, thiserror::Error)]
@@ -50,6 +49,7 @@ pub enum SignalJniError {
     ChatService(ChatServiceError),
     InvalidUri(InvalidUri),
     ConnectTimedOut,
+    DeadlineExceeded(#[from] DeadlineExceeded),
     BackupValidation(#[from] libsignal_message_backup::ReadError),
     Bridge(BridgeLayerError),
     TestingError {
@@ -68,8 +68,13 @@ pub enum BridgeLayerError {
     BadJniParameter(&'static str),
     UnexpectedJniResultType(&'static str, &'static str),
     NullPointer(Option<&'static str>),
+    #[cfg(feature = "handle-poisoning")]
+    UseAfterFree(crate::support::handle_poisoning::UseAfterFree),
     IntegerOverflow(String),
-    IncorrectArrayLength { expected: usize, actual: usize },
+    IncorrectArrayLength {
+        expected: usize,
+        actual: usize,
+    },
     CallbackException(&'static str, ThrownException),
     UnexpectedPanic(std::boxed::Box<dyn std::any::Any + std::marker::Send>),
 }
@@ -98,6 +103,7 @@ impl fmt::Display for SignalJniError {
             SignalJniError::InvalidUri(e) => write!(f, "{}", e),
             SignalJniError::WebSocket(e) => write!(f, "{e}"),
             SignalJniError::ConnectTimedOut => write!(f, "connect timed out"),
+            SignalJniError::DeadlineExceeded(e) => write!(f, "{}", e),
             SignalJniError::BackupValidation(e) => write!(f, "{}", e),
             SignalJniError::Svr3(e) => write!(f, "{}", e),
             SignalJniError::Bridge(e) => write!(f, "{}", e),
@@ -116,6 +122,8 @@ impl fmt::Display for BridgeLayerError {
             Self::NullPointer(Some(expected_type)) => {
                 write!(f, "got null where {expected_type} instance is expected")
             }
+            #[cfg(feature = "handle-poisoning")]
+            Self::UseAfterFree(e) => write!(f, "{e}"),
             Self::BadArgument(m) => write!(f, "{}", m),
             Self::BadJniParameter(m) => write!(f, "bad parameter type {}", m),
             Self::UnexpectedJniResultType(m, t) => {
@@ -219,6 +227,15 @@ impl From<ChatServiceError> for SignalJniError {
     }
 }
 
+impl<E: Into<SignalJniError>> From<DeadlineOr<E>> for SignalJniError {
+    fn from(e: DeadlineOr<E>) -> Self {
+        match e {
+            DeadlineOr::DeadlineExceeded(e) => e.into(),
+            DeadlineOr::Other(e) => e.into(),
+        }
+    }
+}
+
 impl From<IoError> for SignalJniError {
     fn from(e: IoError) -> SignalJniError {
         Self::Io(e)
@@ -265,11 +282,7 @@ impl From<libsignal_net::cdsi::LookupError> for SignalJniError {
             LookupError::CdsiProtocol(CdsiProtocolError::NoTokenInResponse) => {
                 CdsiError::NoTokenInResponse
             }
-            LookupError::RateLimited {
-                retry_after_seconds,
-            } => CdsiError::RateLimited {
-                retry_after: Duration::from_secs(retry_after_seconds.into()),
-            },
+            LookupError::RateLimited(rate_limit) => CdsiError::RateLimited(rate_limit),
             LookupError::ParseError => CdsiError::ParseError,
             LookupError::InvalidToken => CdsiError::InvalidToken,
             LookupError::Server { reason } => CdsiError::Server { reason },
@@ -306,7 +319,8 @@ impl From<Svr3Error> for SignalJniError {
             | Svr3Error::RequestFailed(_)
             | Svr3Error::RestoreFailed(_)
             | Svr3Error::DataMissing
-            | Svr3Error::RotationMachineTooManySteps => SignalJniError::Svr3(err),
+            | Svr3Error::RotationMachineTooManySteps
+            | Svr3Error::InvalidShareDistribution(_) => SignalJniError::Svr3(err),
         }
     }
 }
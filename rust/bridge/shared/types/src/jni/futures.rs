@@ -14,6 +14,7 @@ use crate::support::{AsyncRuntime, ResultReporter};
 pub struct FutureCompleter<T> {
     jvm: JavaVM,
     future: GlobalRef,
+    operation_name: &'static str,
     complete_signature: PhantomData<fn(T)>,
 }
 
@@ -34,10 +35,16 @@ impl<T: for<'a> ResultTypeInfo<'a> + std::panic::UnwindSafe> FutureCompleter<T>
     ///
     /// `future` is expected to refer to a CompletableFuture instance, and will
     /// have methods called on it that match the signatures on CompletableFuture.
-    pub fn new(env: &mut JNIEnv, future: &JObject) -> Result<Self, BridgeLayerError> {
+    /// `operation_name` is attached to the exception if the future fails.
+    pub fn new(
+        env: &mut JNIEnv,
+        future: &JObject,
+        operation_name: &'static str,
+    ) -> Result<Self, BridgeLayerError> {
         Ok(Self {
             jvm: env.get_java_vm().expect_no_exceptions()?,
             future: env.new_global_ref(future).expect_no_exceptions()?,
+            operation_name,
             complete_signature: PhantomData,
         })
     }
@@ -56,6 +63,7 @@ impl<T: for<'a> ResultTypeInfo<'a> + std::panic::UnwindSafe, U> ResultReporter
         let FutureCompleter {
             jvm,
             future,
+            operation_name: operation,
             complete_signature: _,
         } = receiver;
 
@@ -104,7 +112,7 @@ impl<T: for<'a> ResultTypeInfo<'a> + std::panic::UnwindSafe, U> ResultReporter
         let future_for_convert = &future;
         let env_mut = &mut *env;
         maybe_error.unwrap_or_else(move |error| {
-            convert_to_exception(env_mut, error, move |env, throwable, error| {
+            convert_to_exception(env_mut, error, operation, move |env, throwable, error| {
                 throwable
                     .and_then(move |throwable| {
                         _ = call_method_checked(
@@ -144,7 +152,7 @@ impl<T: for<'a> ResultTypeInfo<'a> + std::panic::UnwindSafe, U> ResultReporter
 /// # use libsignal_bridge_types::jni::*;
 /// # use libsignal_bridge_types::support::NoOpAsyncRuntime;
 /// # fn test(env: &mut JNIEnv, async_runtime: &NoOpAsyncRuntime) -> SignalJniResult<()> {
-/// let java_future = run_future_on_runtime(env, async_runtime, |_cancel| async {
+/// let java_future = run_future_on_runtime(env, async_runtime, "example", |_cancel| async {
 ///     let result: i32 = 1 + 2;
 ///     // Do some complicated awaiting here.
 ///     FutureResultReporter::new(Ok(result), ())
@@ -154,6 +162,7 @@ impl<T: for<'a> ResultTypeInfo<'a> + std::panic::UnwindSafe, U> ResultReporter
 pub fn run_future_on_runtime<'local, R, F, O>(
     env: &mut JNIEnv<'local>,
     runtime: &R,
+    operation_name: &'static str,
     future: impl FnOnce(R::Cancellation) -> F,
 ) -> SignalJniResult<JavaCompletableFuture<'local, <O as ResultTypeInfo<'local>>::ResultType>>
 where
@@ -167,7 +176,7 @@ where
         ClassName("org.signal.libsignal.internal.CompletableFuture"),
         jni_args!(() -> void),
     )?;
-    let completer = FutureCompleter::new(env, &java_future)?;
+    let completer = FutureCompleter::new(env, &java_future, operation_name)?;
     runtime.run_future(future, completer);
     Ok(java_future.into())
 }
@@ -7,12 +7,17 @@ use std::cell::RefCell;
 use std::io;
 
 use async_trait::async_trait;
+use signal_crypto::NonceStore;
 
 use super::*;
-use crate::io::{InputStream, InputStreamRead, SyncInputStream};
+use crate::io::{
+    AsyncInputStream, AsyncInputStreamCompletion, InputStream, InputStreamRead, SyncInputStream,
+};
 
 pub type JavaInputStream<'a> = JObject<'a>;
 pub type JavaSyncInputStream<'a> = JObject<'a>;
+pub type JavaAsyncInputStream<'a> = JObject<'a>;
+pub type JavaNonceStore<'a> = JObject<'a>;
 
 /// Implementation of [`InputStream`] for an argument to a bridge function.
 pub struct JniInputStream<'a> {
@@ -112,3 +117,126 @@ impl SyncInputStream for JniInputStream<'_> {
         Ok(self.do_skip(amount)?)
     }
 }
+
+/// Implementation of [`NonceStore`] for an argument to a bridge function.
+pub struct JniNonceStore<'a> {
+    env: RefCell<EnvHandle<'a>>,
+    store: &'a JObject<'a>,
+}
+
+impl<'a> JniNonceStore<'a> {
+    pub fn new<'context: 'a>(
+        env: &mut JNIEnv<'context>,
+        store: &'a JObject<'a>,
+    ) -> Result<Self, BridgeLayerError> {
+        check_jobject_type(
+            env,
+            store,
+            ClassName("org.signal.libsignal.crypto.NonceStore"),
+        )?;
+        Ok(Self {
+            env: EnvHandle::new(env).into(),
+            store,
+        })
+    }
+
+    fn do_reserve_up_to(&mut self, limit: u64) -> SignalJniResult<()> {
+        self.env
+            .borrow_mut()
+            .with_local_frame(8, "reserveUpTo", |env| {
+                let java_limit: jlong = limit.convert_into(env)?;
+                call_method_checked(
+                    env,
+                    self.store,
+                    "reserveUpTo",
+                    jni_args!((java_limit => long) -> void),
+                )?;
+                Ok(())
+            })
+    }
+}
+
+impl NonceStore for JniNonceStore<'_> {
+    fn reserve_up_to(&mut self, limit: u64) -> io::Result<()> {
+        Ok(self.do_reserve_up_to(limit)?)
+    }
+}
+
+/// Implementation of [`AsyncInputStream`] for an argument to an async bridge function.
+///
+/// Holds a global reference to the Java stream, so it can outlive the JNI call that created it.
+pub struct JniAsyncInputStream {
+    jvm: JavaVM,
+    stream: GlobalRef,
+}
+
+impl JniAsyncInputStream {
+    pub fn new(env: &mut JNIEnv, stream: &JObject) -> Result<Self, BridgeLayerError> {
+        check_jobject_type(
+            env,
+            stream,
+            ClassName("org.signal.libsignal.internal.AsyncInputStream"),
+        )?;
+        Ok(Self {
+            jvm: env.get_java_vm().expect_no_exceptions()?,
+            stream: env.new_global_ref(stream).expect_no_exceptions()?,
+        })
+    }
+
+    /// Creates a completion, hands it to Java via `call`, and returns the eventual result.
+    fn start(
+        &self,
+        operation: &'static str,
+        call: impl FnOnce(&mut JNIEnv, &JObject, ObjectHandle) -> SignalJniResult<()>,
+    ) -> SignalJniResult<impl std::future::Future<Output = io::Result<Vec<u8>>> + Send> {
+        let (completion, result) = AsyncInputStreamCompletion::new();
+        let mut env = self
+            .jvm
+            .attach_current_thread()
+            .map_err(|e| SignalJniError::Io(io::Error::new(io::ErrorKind::Other, e)))?;
+        with_local_frame(&mut env, 8, operation, |env| {
+            let completion = completion.convert_into(env)?;
+            call(env, self.stream.as_obj(), completion).inspect_err(|_| {
+                // Java never took ownership of the completion, so clean it up here.
+                // SAFETY: the handle was just created by convert_into above.
+                drop(unsafe { Box::from_raw(completion as *mut AsyncInputStreamCompletion) });
+            })
+        })?;
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl AsyncInputStream for JniAsyncInputStream {
+    async fn read(&self, amount: usize) -> io::Result<Vec<u8>> {
+        let java_amount: jint = amount.try_into().unwrap_or(jint::MAX);
+        self.start("readAsync", |env, stream, completion| {
+            call_method_checked(
+                env,
+                stream,
+                "readAsync",
+                jni_args!((java_amount => int, completion => long) -> void),
+            )
+        })?
+        .await
+    }
+
+    async fn skip(&self, amount: u64) -> io::Result<()> {
+        let java_amount: jlong = amount.try_into().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "InputStream::skip more than i64::MAX not supported",
+            )
+        })?;
+        self.start("skipAsync", |env, stream, completion| {
+            call_method_checked(
+                env,
+                stream,
+                "skipAsync",
+                jni_args!((java_amount => long, completion => long) -> void),
+            )
+        })?
+        .await?;
+        Ok(())
+    }
+}
@@ -18,6 +18,7 @@ pub use jni::JNIEnv;
 use jni::JavaVM;
 use libsignal_account_keys::Error as PinError;
 use libsignal_net::infra::ws::WebSocketServiceError;
+use libsignal_net::rate_limit::RateLimit;
 use libsignal_net::svr3::Error as Svr3Error;
 use libsignal_protocol::*;
 use signal_crypto::Error as SignalCryptoError;
@@ -44,11 +45,13 @@ pub use futures::*;
 
 mod io;
 pub use io::*;
-use libsignal_net::chat::ChatServiceError;
+use libsignal_net::chat::{ChatServiceError, DisconnectInfo};
 
 mod storage;
 pub use storage::*;
 
+pub use crate::support::metrics::CallTimer;
+
 /// The type of boxed Rust values, as surfaced in JavaScript.
 pub type ObjectHandle = jlong;
 
@@ -64,6 +67,10 @@ pub type JavaMap<'a> = JObject<'a>;
 /// when generating Native.java.
 pub type Throwing<T> = T;
 
+/// Marker for `Option` arguments and results that are passed as possibly-null Java objects, which
+/// gen_java_decl.py will annotate as `@Nullable` when generating Native.java.
+pub type Nullable<T> = T;
+
 /// A Java wrapper for a `CompletableFuture` type.
 #[derive(Default)]
 #[repr(transparent)] // Ensures that the representation is the same as JObject.
@@ -87,8 +94,12 @@ impl<'a, T> From<JavaCompletableFuture<'a, T>> for JObject<'a> {
     }
 }
 
-fn convert_to_exception<'a, 'env, F>(env: &'a mut JNIEnv<'env>, error: SignalJniError, consume: F)
-where
+fn convert_to_exception<'a, 'env, F>(
+    env: &'a mut JNIEnv<'env>,
+    error: SignalJniError,
+    operation_name: &str,
+    consume: F,
+) where
     F: 'a
         + FnOnce(
             &'a mut JNIEnv<'env>,
@@ -100,9 +111,37 @@ where
     // `F`. That's expensive in terms of code size, so we break out the
     // invariant part into a separate function.
     let ConsumableException { throwable, error } = ConsumableException::new(env, error);
+    if let Ok(throwable) = &throwable {
+        // Failing to record the operation shouldn't keep the exception from being thrown.
+        if let Err(failure) = record_failed_operation(env, throwable, operation_name) {
+            log::warn!("failed to record operation for {}: {}", error, failure);
+        }
+    }
     consume(env, throwable, error)
 }
 
+/// Associates `throwable` with the bridged function that produced it, so that apps can retrieve
+/// the name with `FailedOperation.of`.
+fn record_failed_operation(
+    env: &mut JNIEnv,
+    throwable: &JThrowable,
+    operation_name: &str,
+) -> Result<(), BridgeLayerError> {
+    let operation_name = env
+        .new_string(operation_name)
+        .check_exceptions(env, "record_failed_operation")?;
+    let class = find_class(
+        env,
+        ClassName("org.signal.libsignal.protocol.FailedOperation"),
+    )?;
+    call_static_method_checked(
+        env,
+        &class,
+        "record",
+        jni_args!((throwable => java.lang.Throwable, operation_name => java.lang.String) -> void),
+    )
+}
+
 struct ConsumableException<'a> {
     throwable: Result<JThrowable<'a>, BridgeLayerError>,
     error: ConsumableExceptionError,
@@ -153,6 +192,11 @@ impl<'env> ConsumableException<'env> {
                 (ClassName("java.io.IOException"), SignalJniError::Io(error))
             }
 
+            SignalJniError::SignalCrypto(SignalCryptoError::NonceStoreFailed(error)) => {
+                // Rethrow whatever the NonceStore threw, if anything.
+                return Self::new(env, SignalJniError::Io(error));
+            }
+
             SignalJniError::Protocol(SignalProtocolError::ApplicationCallbackError(
                 callback,
                 exception,
@@ -292,12 +336,8 @@ impl<'env> ConsumableException<'env> {
                 };
             }
 
-            SignalJniError::Cdsi(CdsiError::RateLimited { retry_after }) => {
-                let retry_after_seconds = retry_after
-                    .as_secs()
-                    .try_into()
-                    .expect("duration < lifetime of the universe");
-                let throwable = retry_later_exception(env, retry_after_seconds);
+            SignalJniError::Cdsi(CdsiError::RateLimited(ref rate_limit)) => {
+                let throwable = retry_later_exception(env, rate_limit);
 
                 return ConsumableException {
                     throwable: throwable.map(Into::into),
@@ -354,7 +394,14 @@ impl<'env> ConsumableException<'env> {
                 (ClassName("java.lang.NullPointerException"), error)
             }
 
-            SignalJniError::Protocol(SignalProtocolError::InvalidState(_, _)) => {
+            #[cfg(feature = "handle-poisoning")]
+            SignalJniError::Bridge(BridgeLayerError::UseAfterFree(_)) => {
+                (ClassName("java.lang.IllegalStateException"), error)
+            }
+
+            SignalJniError::Protocol(SignalProtocolError::InvalidState(_, _))
+            | SignalJniError::SignalCrypto(SignalCryptoError::NonceLimitReached)
+            | SignalJniError::DeviceTransfer(DeviceTransferError::InvalidState(_)) => {
                 (ClassName("java.lang.IllegalStateException"), error)
             }
 
@@ -376,7 +423,8 @@ impl<'env> ConsumableException<'env> {
                 (ClassName("java.lang.RuntimeException"), error)
             }
 
-            SignalJniError::Protocol(SignalProtocolError::DuplicatedMessage(_, _)) => (
+            SignalJniError::Protocol(SignalProtocolError::DuplicatedMessage(_, _))
+            | SignalJniError::Protocol(SignalProtocolError::ReplayedMessage(_)) => (
                 ClassName("org.signal.libsignal.protocol.DuplicateMessageException"),
                 error,
             ),
@@ -416,7 +464,9 @@ impl<'env> ConsumableException<'env> {
             | SignalJniError::Protocol(SignalProtocolError::InvalidProtobufEncoding)
             | SignalJniError::Protocol(SignalProtocolError::InvalidSealedSenderMessage(_))
             | SignalJniError::Protocol(SignalProtocolError::BadKEMCiphertextLength(_, _))
-            | SignalJniError::SignalCrypto(SignalCryptoError::InvalidTag) => (
+            | SignalJniError::Protocol(SignalProtocolError::UnrecognizedRecordVersion(_))
+            | SignalJniError::SignalCrypto(SignalCryptoError::InvalidTag)
+            | SignalJniError::DeviceTransfer(DeviceTransferError::UnexpectedMessage(_)) => (
                 ClassName("org.signal.libsignal.protocol.InvalidMessageException"),
                 error,
             ),
@@ -439,7 +489,8 @@ impl<'env> ConsumableException<'env> {
             ),
 
             SignalJniError::HsmEnclave(HsmEnclaveError::HSMHandshakeError(_))
-            | SignalJniError::HsmEnclave(HsmEnclaveError::HSMCommunicationError(_)) => (
+            | SignalJniError::HsmEnclave(HsmEnclaveError::HSMCommunicationError(_))
+            | SignalJniError::HsmEnclave(HsmEnclaveError::UnexpectedResponse) => (
                 ClassName("org.signal.libsignal.hsmenclave.EnclaveCommunicationFailureException"),
                 error,
             ),
@@ -451,7 +502,8 @@ impl<'env> ConsumableException<'env> {
             | SignalJniError::HsmEnclave(HsmEnclaveError::InvalidCodeHashError) => {
                 (ClassName("java.lang.IllegalArgumentException"), error)
             }
-            SignalJniError::HsmEnclave(HsmEnclaveError::InvalidBridgeStateError) => {
+            SignalJniError::HsmEnclave(HsmEnclaveError::InvalidBridgeStateError)
+            | SignalJniError::HsmEnclave(HsmEnclaveError::PipelineFull) => {
                 (ClassName("java.lang.IllegalStateException"), error)
             }
 
@@ -637,17 +689,19 @@ impl<'env> ConsumableException<'env> {
 
             SignalJniError::InvalidUri(_) => (ClassName("java.net.MalformedURLException"), error),
 
+            SignalJniError::DeadlineExceeded(_) => {
+                (ClassName("java.util.concurrent.TimeoutException"), error)
+            }
+
             SignalJniError::ChatService(ref chat) => {
                 let class = match chat {
-                    ChatServiceError::RetryLater {
-                        retry_after_seconds,
-                    } => {
+                    ChatServiceError::RetryLater(rate_limit) => {
                         return ConsumableException {
-                            throwable: retry_later_exception(env, *retry_after_seconds),
+                            throwable: retry_later_exception(env, rate_limit),
                             error: error.into(),
                         }
                     }
-                    ChatServiceError::ServiceInactive => {
+                    ChatServiceError::ServiceInactive(_) => {
                         ClassName("org.signal.libsignal.net.ChatServiceInactiveException")
                     }
                     ChatServiceError::AppExpired => {
@@ -656,7 +710,14 @@ impl<'env> ConsumableException<'env> {
                     ChatServiceError::DeviceDeregistered => {
                         ClassName("org.signal.libsignal.net.DeviceDeregisteredException")
                     }
+                    ChatServiceError::RequestUnauthorized => {
+                        ClassName("org.signal.libsignal.net.RequestUnauthorizedException")
+                    }
+                    ChatServiceError::NotFound => {
+                        ClassName("org.signal.libsignal.net.NotFoundException")
+                    }
                     ChatServiceError::WebSocket(_)
+                    | ChatServiceError::ChannelClosed(_)
                     | ChatServiceError::UnexpectedFrameReceived
                     | ChatServiceError::ServerRequestMissingId
                     | ChatServiceError::FailedToPassMessageToIncomingChannel
@@ -665,11 +726,17 @@ impl<'env> ConsumableException<'env> {
                     | ChatServiceError::Timeout
                     | ChatServiceError::TimeoutEstablishingConnection { attempts: _ }
                     | ChatServiceError::AllConnectionRoutesFailed { attempts: _ }
-                    | ChatServiceError::ServiceUnavailable
+                    | ChatServiceError::ServiceUnavailable(_)
                     | ChatServiceError::ServiceIntentionallyDisconnected => {
                         ClassName("org.signal.libsignal.net.ChatServiceException")
                     }
                 };
+                if let Some(info) = chat.disconnect_info() {
+                    return ConsumableException {
+                        throwable: disconnected_exception(env, class, &error.to_string(), &info),
+                        error: error.into(),
+                    };
+                }
                 (class, error)
             }
 
@@ -692,12 +759,50 @@ impl<'env> ConsumableException<'env> {
 
 fn retry_later_exception<'env>(
     env: &mut JNIEnv<'env>,
-    retry_after_seconds: u32,
+    rate_limit: &RateLimit,
 ) -> Result<JThrowable<'env>, BridgeLayerError> {
+    let RateLimit {
+        retry_after_seconds,
+        scope,
+        reason,
+    } = rate_limit;
+    let reason = reason.as_deref().convert_into(env)?;
     new_instance(
         env,
         ClassName("org.signal.libsignal.net.RetryLaterException"),
-        jni_args!((retry_after_seconds.into() => long) -> void),
+        jni_args!((
+            i64::from(*retry_after_seconds) => long,
+            i32::from(*scope as u8) => int,
+            reason => java.lang.String,
+        ) -> void),
+    )
+    .map(Into::into)
+}
+
+fn disconnected_exception<'env>(
+    env: &mut JNIEnv<'env>,
+    exception_type: ClassName<'static>,
+    message: &str,
+    info: &DisconnectInfo,
+) -> Result<JThrowable<'env>, BridgeLayerError> {
+    let DisconnectInfo {
+        close_code,
+        reason,
+        alerts,
+    } = info;
+    let message = message.convert_into(env)?;
+    let close_code = close_code.map_or(-1, i32::from);
+    let reason = reason.as_deref().convert_into(env)?;
+    let alerts = alerts.clone().into_boxed_slice().convert_into(env)?;
+    new_instance(
+        env,
+        exception_type,
+        jni_args!((
+            message => java.lang.String,
+            close_code => int,
+            reason => java.lang.String,
+            alerts => [java.lang.String],
+        ) -> void),
     )
     .map(Into::into)
 }
@@ -733,21 +838,31 @@ impl Display for ConsumableExceptionError {
 /// Translates errors into Java exceptions.
 ///
 /// Exceptions thrown in callbacks will be rethrown; all other errors will be mapped to an
-/// appropriate Java exception class and thrown.
-fn throw_error(env: &mut JNIEnv, error: SignalJniError) {
-    convert_to_exception(env, error, |env, throwable, error| match throwable {
-        Err(failure) => log::error!("failed to create exception for {}: {}", error, failure),
-        Ok(throwable) => {
-            let result = env.throw(throwable);
-            if let Err(failure) = result {
-                log::error!("failed to throw exception for {}: {}", error, failure);
+/// appropriate Java exception class and thrown. Either way, the exception is tagged with
+/// `operation_name`.
+fn throw_error(env: &mut JNIEnv, error: SignalJniError, operation_name: &str) {
+    convert_to_exception(
+        env,
+        error,
+        operation_name,
+        |env, throwable, error| match throwable {
+            Err(failure) => log::error!("failed to create exception for {}: {}", error, failure),
+            Ok(throwable) => {
+                let result = env.throw(throwable);
+                if let Err(failure) = result {
+                    log::error!("failed to throw exception for {}: {}", error, failure);
+                }
             }
-        }
-    });
+        },
+    );
 }
 
+/// Runs `f`, translating any error or panic into a thrown Java exception.
+///
+/// `operation_name` identifies the bridged function being run; it's attached to any exception
+/// thrown.
 #[inline(always)]
-pub fn run_ffi_safe<'local, F, R>(env: &mut JNIEnv<'local>, f: F) -> R
+pub fn run_ffi_safe<'local, F, R>(env: &mut JNIEnv<'local>, operation_name: &str, f: F) -> R
 where
     F: for<'a> FnOnce(&'a mut JNIEnv<'local>) -> Result<R, SignalJniError> + std::panic::UnwindSafe,
     R: Default,
@@ -759,11 +874,15 @@ where
     match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(env))) {
         Ok(Ok(r)) => r,
         Ok(Err(e)) => {
-            throw_error(env, e);
+            throw_error(env, e, operation_name);
             R::default()
         }
         Err(r) => {
-            throw_error(env, BridgeLayerError::UnexpectedPanic(r).into());
+            throw_error(
+                env,
+                BridgeLayerError::UnexpectedPanic(r).into(),
+                operation_name,
+            );
             R::default()
         }
     }
@@ -780,6 +899,9 @@ pub unsafe fn native_handle_cast<T>(
     if handle == 0 {
         return Err(BridgeLayerError::NullPointer(None));
     }
+    #[cfg(feature = "handle-poisoning")]
+    crate::support::handle_poisoning::check(handle as *const T)
+        .map_err(BridgeLayerError::UseAfterFree)?;
 
     Ok(&mut *(handle as *mut T))
 }
@@ -808,6 +930,31 @@ pub fn call_method_checked<
     check_exceptions_and_convert_result(env, fn_name, result)
 }
 
+/// Like [`call_method_checked`], but looks up the method on `class_name` only once.
+///
+/// Use this for callbacks made frequently, such as store methods. `obj` must be an instance of
+/// `class_name` (checked with [`check_jobject_type`], for example).
+pub fn call_cached_method_checked<
+    'input,
+    'output,
+    O: AsRef<JObject<'input>>,
+    R: TryFrom<JValueOwned<'output>>,
+    const LEN: usize,
+>(
+    env: &mut JNIEnv<'output>,
+    class_name: ClassName<'static>,
+    obj: O,
+    fn_name: &'static str,
+    args: JniArgs<R, LEN>,
+) -> Result<R, BridgeLayerError> {
+    let (method_id, return_type) = get_cached_method_id(env, class_name, fn_name, args.sig)?;
+    let raw_args = args.args.map(|arg| arg.as_jni());
+    // SAFETY: the method was looked up with the signature `args` was built against, and the
+    // caller has promised that `obj` is an instance of the class that declares it.
+    let result = unsafe { env.call_method_unchecked(obj, method_id, return_type, &raw_args) };
+    check_exceptions_and_convert_result(env, fn_name, result)
+}
+
 /// Calls a method and translates any thrown exceptions to
 /// [`BridgeLayerError::CallbackException`].
 ///
@@ -957,19 +1104,22 @@ pub fn with_local_frame_returning_local<'env>(
     Ok(result)
 }
 
-/// Calls a method, then clones the Rust value from the result.
+/// Calls a method on a store, then clones the Rust value from the result.
 ///
-/// The method is assumed to return a type with a `long unsafeHandle` field,
-/// which in turn must hold a raw pointer produced from a boxed Rust value.
+/// `store_obj` must be an instance of `store_class`. The method is assumed to return a type with
+/// a `long unsafeHandle` field, which in turn must hold a raw pointer produced from a boxed Rust
+/// value.
 pub fn get_object_with_native_handle<T: 'static + Clone, const LEN: usize>(
     env: &mut JNIEnv,
+    store_class: ClassName<'static>,
     store_obj: &JObject,
     callback_args: JniArgs<JObject<'_>, LEN>,
     callback_fn: &'static str,
 ) -> Result<Option<T>, SignalJniError> {
     with_local_frame(env, 64, callback_fn, |env| -> SignalJniResult<Option<T>> {
-        let obj = call_method_checked(
+        let obj = call_cached_method_checked(
             env,
+            store_class,
             store_obj,
             callback_fn,
             callback_args.for_nested_frame(),
@@ -992,11 +1142,13 @@ pub fn get_object_with_native_handle<T: 'static + Clone, const LEN: usize>(
     })
 }
 
-/// Calls a method, then serializes the result.
+/// Calls a method on a store, then serializes the result.
 ///
-/// The method is assumed to return a type with a `byte[] serialize()` method.
+/// `store_obj` must be an instance of `store_class`. The method is assumed to return a type with a
+/// `byte[] serialize()` method.
 pub fn get_object_with_serialization<const LEN: usize>(
     env: &mut JNIEnv,
+    store_class: ClassName<'static>,
     store_obj: &JObject,
     callback_args: JniArgs<JObject<'_>, LEN>,
     callback_fn: &'static str,
@@ -1006,8 +1158,9 @@ pub fn get_object_with_serialization<const LEN: usize>(
         64,
         callback_fn,
         |env| -> SignalJniResult<Option<Vec<u8>>> {
-            let obj = call_method_checked(
+            let obj = call_cached_method_checked(
                 env,
+                store_class,
                 store_obj,
                 callback_fn,
                 callback_args.for_nested_frame(),
@@ -1079,6 +1232,12 @@ macro_rules! jni_bridge_handle_destroy {
                 handle: $crate::jni::ObjectHandle,
             ) {
                 if handle != 0 {
+                    #[cfg(feature = "handle-poisoning")]
+                    if let Err(e) = $crate::support::handle_poisoning::destroy(handle as *mut $typ) {
+                        // There's no good way to report this from a finalizer or cleaner.
+                        log::error!("{e}");
+                    }
+                    #[cfg(not(feature = "handle-poisoning"))]
                     let _boxed_value = Box::from_raw(handle as *mut $typ);
                 }
             }
@@ -16,6 +16,7 @@ pub type JavaSignedPreKeyStore<'a> = JObject<'a>;
 pub type JavaKyberPreKeyStore<'a> = JObject<'a>;
 pub type JavaSessionStore<'a> = JObject<'a>;
 pub type JavaSenderKeyStore<'a> = JObject<'a>;
+pub type JavaReplayCache<'a> = JObject<'a>;
 
 pub struct JniIdentityKeyStore<'a> {
     env: RefCell<EnvHandle<'a>>,
@@ -23,15 +24,14 @@ pub struct JniIdentityKeyStore<'a> {
 }
 
 impl<'a> JniIdentityKeyStore<'a> {
+    const CLASS: ClassName<'static> =
+        ClassName("org.signal.libsignal.protocol.state.IdentityKeyStore");
+
     pub fn new<'context: 'a>(
         env: &mut JNIEnv<'context>,
         store: &'a JObject<'a>,
     ) -> Result<Self, BridgeLayerError> {
-        check_jobject_type(
-            env,
-            store,
-            ClassName("org.signal.libsignal.protocol.state.IdentityKeyStore"),
-        )?;
+        check_jobject_type(env, store, Self::CLASS)?;
         Ok(Self {
             env: EnvHandle::new(env).into(),
             store,
@@ -47,6 +47,7 @@ impl<'a> JniIdentityKeyStore<'a> {
                 let callback_args = jni_args!(() -> org.signal.libsignal.protocol.IdentityKeyPair);
                 let bits = get_object_with_serialization(
                     env,
+                    Self::CLASS,
                     self.store,
                     callback_args,
                     "getIdentityKeyPair",
@@ -67,8 +68,9 @@ impl<'a> JniIdentityKeyStore<'a> {
         self.env
             .borrow_mut()
             .with_local_frame(8, "getLocalRegistrationId", |env| {
-                let i: jint = call_method_checked(
+                let i: jint = call_cached_method_checked(
                     env,
+                    Self::CLASS,
                     self.store,
                     "getLocalRegistrationId",
                     jni_args!(() -> int),
@@ -96,8 +98,13 @@ impl<'a> JniIdentityKeyStore<'a> {
                     address_jobject => org.signal.libsignal.protocol.SignalProtocolAddress,
                     key_jobject => org.signal.libsignal.protocol.IdentityKey
                 ) -> boolean);
-                let result: jboolean =
-                    call_method_checked(env, self.store, "saveIdentity", callback_args)?;
+                let result: jboolean = call_cached_method_checked(
+                    env,
+                    Self::CLASS,
+                    self.store,
+                    "saveIdentity",
+                    callback_args,
+                )?;
                 Ok(result != 0)
             })
     }
@@ -144,7 +151,7 @@ impl<'a> JniIdentityKeyStore<'a> {
                     field_value => org.signal.libsignal.protocol.state.IdentityKeyStore::Direction,
                 ) -> boolean);
                 let result: jboolean =
-                    call_method_checked(env, self.store, "isTrustedIdentity", callback_args)?;
+                    call_cached_method_checked(env, Self::CLASS, self.store, "isTrustedIdentity", callback_args)?;
 
                 Ok(result != 0)
             })
@@ -163,8 +170,13 @@ impl<'a> JniIdentityKeyStore<'a> {
                     address_jobject => org.signal.libsignal.protocol.SignalProtocolAddress,
                 ) -> org.signal.libsignal.protocol.IdentityKey);
 
-                let bits =
-                    get_object_with_serialization(env, self.store, callback_args, "getIdentity")?;
+                let bits = get_object_with_serialization(
+                    env,
+                    Self::CLASS,
+                    self.store,
+                    callback_args,
+                    "getIdentity",
+                )?;
 
                 match bits {
                     None => Ok(None),
@@ -216,15 +228,13 @@ pub struct JniPreKeyStore<'a> {
 }
 
 impl<'a> JniPreKeyStore<'a> {
+    const CLASS: ClassName<'static> = ClassName("org.signal.libsignal.protocol.state.PreKeyStore");
+
     pub fn new<'context: 'a>(
         env: &mut JNIEnv<'context>,
         store: &'a JObject<'a>,
     ) -> Result<Self, BridgeLayerError> {
-        check_jobject_type(
-            env,
-            store,
-            ClassName("org.signal.libsignal.protocol.state.PreKeyStore"),
-        )?;
+        check_jobject_type(env, store, Self::CLASS)?;
         Ok(Self {
             env: EnvHandle::new(env).into(),
             store,
@@ -240,8 +250,13 @@ impl<'a> JniPreKeyStore<'a> {
                 let callback_args = jni_args!((
             prekey_id.convert_into(env)? => int
         ) -> org.signal.libsignal.protocol.state.PreKeyRecord);
-                let pk: Option<PreKeyRecord> =
-                    get_object_with_native_handle(env, self.store, callback_args, "loadPreKey")?;
+                let pk: Option<PreKeyRecord> = get_object_with_native_handle(
+                    env,
+                    Self::CLASS,
+                    self.store,
+                    callback_args,
+                    "loadPreKey",
+                )?;
                 match pk {
                     Some(pk) => Ok(pk),
                     None => Err(SignalJniError::Protocol(
@@ -269,7 +284,13 @@ impl<'a> JniPreKeyStore<'a> {
                     prekey_id.convert_into(env)? => int,
                     jobject_record => org.signal.libsignal.protocol.state.PreKeyRecord
                 ) -> void);
-                call_method_checked(env, self.store, "storePreKey", callback_args)?;
+                call_cached_method_checked(
+                    env,
+                    Self::CLASS,
+                    self.store,
+                    "storePreKey",
+                    callback_args,
+                )?;
                 Ok(())
             })
     }
@@ -279,8 +300,9 @@ impl<'a> JniPreKeyStore<'a> {
             .borrow_mut()
             .with_local_frame(8, "removePreKey", |env| {
                 let java_id = prekey_id.convert_into(env)?;
-                call_method_checked(
+                call_cached_method_checked(
                     env,
+                    Self::CLASS,
                     self.store,
                     "removePreKey",
                     jni_args!((java_id => int) -> void),
@@ -315,15 +337,14 @@ pub struct JniSignedPreKeyStore<'a> {
 }
 
 impl<'a> JniSignedPreKeyStore<'a> {
+    const CLASS: ClassName<'static> =
+        ClassName("org.signal.libsignal.protocol.state.SignedPreKeyStore");
+
     pub fn new<'context: 'a>(
         env: &mut JNIEnv<'context>,
         store: &'a JObject<'a>,
     ) -> Result<Self, BridgeLayerError> {
-        check_jobject_type(
-            env,
-            store,
-            ClassName("org.signal.libsignal.protocol.state.SignedPreKeyStore"),
-        )?;
+        check_jobject_type(env, store, Self::CLASS)?;
         Ok(Self {
             env: EnvHandle::new(env).into(),
             store,
@@ -341,6 +362,7 @@ impl<'a> JniSignedPreKeyStore<'a> {
         ) -> org.signal.libsignal.protocol.state.SignedPreKeyRecord);
                 let spk: Option<SignedPreKeyRecord> = get_object_with_native_handle(
                     env,
+                    Self::CLASS,
                     self.store,
                     callback_args,
                     "loadSignedPreKey",
@@ -372,7 +394,13 @@ impl<'a> JniSignedPreKeyStore<'a> {
                     prekey_id.convert_into(env)? => int,
                     jobject_record => org.signal.libsignal.protocol.state.SignedPreKeyRecord
                 ) -> void);
-                call_method_checked(env, self.store, "storeSignedPreKey", callback_args)?;
+                call_cached_method_checked(
+                    env,
+                    Self::CLASS,
+                    self.store,
+                    "storeSignedPreKey",
+                    callback_args,
+                )?;
                 Ok(())
             })
     }
@@ -402,15 +430,14 @@ pub struct JniKyberPreKeyStore<'a> {
 }
 
 impl<'a> JniKyberPreKeyStore<'a> {
+    const CLASS: ClassName<'static> =
+        ClassName("org.signal.libsignal.protocol.state.KyberPreKeyStore");
+
     pub fn new<'context: 'a>(
         env: &mut JNIEnv<'context>,
         store: &'a JObject<'a>,
     ) -> Result<Self, BridgeLayerError> {
-        check_jobject_type(
-            env,
-            store,
-            ClassName("org.signal.libsignal.protocol.state.KyberPreKeyStore"),
-        )?;
+        check_jobject_type(env, store, Self::CLASS)?;
         Ok(Self {
             env: EnvHandle::new(env).into(),
             store,
@@ -428,6 +455,7 @@ impl<'a> JniKyberPreKeyStore<'a> {
         ) -> org.signal.libsignal.protocol.state.KyberPreKeyRecord);
                 let kpk: Option<KyberPreKeyRecord> = get_object_with_native_handle(
                     env,
+                    Self::CLASS,
                     self.store,
                     callback_args,
                     "loadKyberPreKey",
@@ -459,7 +487,13 @@ impl<'a> JniKyberPreKeyStore<'a> {
                     prekey_id.convert_into(env)? => int,
                     jobject_record => org.signal.libsignal.protocol.state.KyberPreKeyRecord
                 ) -> void);
-                call_method_checked(env, self.store, "storeKyberPreKey", callback_args)?;
+                call_cached_method_checked(
+                    env,
+                    Self::CLASS,
+                    self.store,
+                    "storeKyberPreKey",
+                    callback_args,
+                )?;
                 Ok(())
             })
     }
@@ -469,8 +503,9 @@ impl<'a> JniKyberPreKeyStore<'a> {
             .borrow_mut()
             .with_local_frame(8, "markKyberPreKeyUsed", |env| {
                 let java_id = prekey_id.convert_into(env)?;
-                call_method_checked(
+                call_cached_method_checked(
                     env,
+                    Self::CLASS,
                     self.store,
                     "markKyberPreKeyUsed",
                     jni_args!((java_id => int) -> void),
@@ -511,15 +546,13 @@ pub struct JniSessionStore<'a> {
 }
 
 impl<'a> JniSessionStore<'a> {
+    const CLASS: ClassName<'static> = ClassName("org.signal.libsignal.protocol.state.SessionStore");
+
     pub fn new<'context: 'a>(
         env: &mut JNIEnv<'context>,
         store: &'a JObject<'a>,
     ) -> Result<Self, BridgeLayerError> {
-        check_jobject_type(
-            env,
-            store,
-            ClassName("org.signal.libsignal.protocol.state.SessionStore"),
-        )?;
+        check_jobject_type(env, store, Self::CLASS)?;
         Ok(Self {
             env: EnvHandle::new(env).into(),
             store,
@@ -540,7 +573,13 @@ impl<'a> JniSessionStore<'a> {
                 let callback_args = jni_args!((
                     address_jobject => org.signal.libsignal.protocol.SignalProtocolAddress
                 ) -> org.signal.libsignal.protocol.state.SessionRecord);
-                get_object_with_native_handle(env, self.store, callback_args, "loadSession")
+                get_object_with_native_handle(
+                    env,
+                    Self::CLASS,
+                    self.store,
+                    callback_args,
+                    "loadSession",
+                )
             })
     }
 
@@ -564,7 +603,13 @@ impl<'a> JniSessionStore<'a> {
                     address_jobject => org.signal.libsignal.protocol.SignalProtocolAddress,
                     session_jobject => org.signal.libsignal.protocol.state.SessionRecord,
                 ) -> void);
-                call_method_checked(env, self.store, "storeSession", callback_args)?;
+                call_cached_method_checked(
+                    env,
+                    Self::CLASS,
+                    self.store,
+                    "storeSession",
+                    callback_args,
+                )?;
                 Ok(())
             })
     }
@@ -594,15 +639,14 @@ pub struct JniSenderKeyStore<'a> {
 }
 
 impl<'a> JniSenderKeyStore<'a> {
+    const CLASS: ClassName<'static> =
+        ClassName("org.signal.libsignal.protocol.groups.state.SenderKeyStore");
+
     pub fn new<'context: 'a>(
         env: &mut JNIEnv<'context>,
         store: &'a JObject<'a>,
     ) -> Result<Self, BridgeLayerError> {
-        check_jobject_type(
-            env,
-            store,
-            ClassName("org.signal.libsignal.protocol.groups.state.SenderKeyStore"),
-        )?;
+        check_jobject_type(env, store, Self::CLASS)?;
         Ok(Self {
             env: EnvHandle::new(env).into(),
             store,
@@ -634,7 +678,7 @@ impl<'a> JniSenderKeyStore<'a> {
                     distribution_id_jobject => java.util.UUID,
                     sender_key_record_jobject => org.signal.libsignal.protocol.groups.state.SenderKeyRecord,
                 ) -> void);
-                call_method_checked(env, self.store, "storeSenderKey", callback_args)?;
+                call_cached_method_checked(env, Self::CLASS, self.store, "storeSenderKey", callback_args)?;
 
                 Ok(())
             })
@@ -654,7 +698,74 @@ impl<'a> JniSenderKeyStore<'a> {
                     sender_jobject => org.signal.libsignal.protocol.SignalProtocolAddress,
                     distribution_id_jobject => java.util.UUID,
                 ) -> org.signal.libsignal.protocol.groups.state.SenderKeyRecord);
-                get_object_with_native_handle(env, self.store, callback_args, "loadSenderKey")
+                get_object_with_native_handle(
+                    env,
+                    Self::CLASS,
+                    self.store,
+                    callback_args,
+                    "loadSenderKey",
+                )
+            })
+    }
+
+    fn do_store_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+        record: &SenderKeyDistributionRecord,
+    ) -> Result<(), SignalJniError> {
+        self.env
+            .borrow_mut()
+            .with_local_frame(8, "storeSenderKeyDistribution", |env| {
+                let sender_jobject = protocol_address_to_jobject(env, sender)?;
+                let distribution_id_jobject = distribution_id.convert_into(env)?;
+                let record_handle = record.clone().convert_into(env)?;
+                let record_jobject = jobject_from_native_handle(
+                    env,
+                    ClassName(
+                        "org.signal.libsignal.protocol.groups.state.SenderKeyDistributionRecord",
+                    ),
+                    record_handle,
+                )?;
+
+                let callback_args = jni_args!((
+                    sender_jobject => org.signal.libsignal.protocol.SignalProtocolAddress,
+                    distribution_id_jobject => java.util.UUID,
+                    record_jobject => org.signal.libsignal.protocol.groups.state.SenderKeyDistributionRecord,
+                ) -> void);
+                call_cached_method_checked(
+                    env,
+                    Self::CLASS,
+                    self.store,
+                    "storeSenderKeyDistribution",
+                    callback_args,
+                )?;
+
+                Ok(())
+            })
+    }
+
+    fn do_load_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+    ) -> Result<Option<SenderKeyDistributionRecord>, SignalJniError> {
+        self.env
+            .borrow_mut()
+            .with_local_frame(8, "loadSenderKeyDistribution", |env| {
+                let sender_jobject = protocol_address_to_jobject(env, sender)?;
+                let distribution_id_jobject = distribution_id.convert_into(env)?;
+                let callback_args = jni_args!((
+                    sender_jobject => org.signal.libsignal.protocol.SignalProtocolAddress,
+                    distribution_id_jobject => java.util.UUID,
+                ) -> org.signal.libsignal.protocol.groups.state.SenderKeyDistributionRecord);
+                get_object_with_native_handle(
+                    env,
+                    Self::CLASS,
+                    self.store,
+                    callback_args,
+                    "loadSenderKeyDistribution",
+                )
             })
     }
 }
@@ -677,4 +788,121 @@ impl<'a> SenderKeyStore for JniSenderKeyStore<'a> {
     ) -> Result<Option<SenderKeyRecord>, SignalProtocolError> {
         Ok(self.do_load_sender_key(sender, distribution_id)?)
     }
+
+    async fn store_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+        record: &SenderKeyDistributionRecord,
+    ) -> Result<(), SignalProtocolError> {
+        Ok(self.do_store_sender_key_distribution(sender, distribution_id, record)?)
+    }
+
+    async fn load_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+    ) -> Result<Option<SenderKeyDistributionRecord>, SignalProtocolError> {
+        Ok(self.do_load_sender_key_distribution(sender, distribution_id)?)
+    }
+}
+
+pub struct JniReplayCache<'a> {
+    env: RefCell<EnvHandle<'a>>,
+    store: &'a JObject<'a>,
+}
+
+impl<'a> JniReplayCache<'a> {
+    const CLASS: ClassName<'static> = ClassName("org.signal.libsignal.protocol.state.ReplayCache");
+
+    pub fn new<'context: 'a>(
+        env: &mut JNIEnv<'context>,
+        store: &'a JObject<'a>,
+    ) -> Result<Self, BridgeLayerError> {
+        check_jobject_type(env, store, Self::CLASS)?;
+        Ok(Self {
+            env: EnvHandle::new(env).into(),
+            store,
+        })
+    }
+}
+
+impl<'a> JniReplayCache<'a> {
+    fn do_contains(
+        &self,
+        address: &ProtocolAddress,
+        sender_ratchet_key: &PublicKey,
+        counter: u32,
+    ) -> Result<bool, SignalJniError> {
+        self.env
+            .borrow_mut()
+            .with_local_frame(8, "contains", |env| {
+                let address_jobject = protocol_address_to_jobject(env, address)?;
+                let key_handle = sender_ratchet_key.convert_into(env)?;
+                let key_jobject = jobject_from_native_handle(
+                    env,
+                    ClassName("org.signal.libsignal.protocol.ecc.ECPublicKey"),
+                    key_handle,
+                )?;
+                let callback_args = jni_args!((
+                    address_jobject => org.signal.libsignal.protocol.SignalProtocolAddress,
+                    key_jobject => org.signal.libsignal.protocol.ecc.ECPublicKey,
+                    counter.convert_into(env)? => int,
+                ) -> boolean);
+                let result: jboolean = call_cached_method_checked(
+                    env,
+                    Self::CLASS,
+                    self.store,
+                    "contains",
+                    callback_args,
+                )?;
+
+                Ok(result != 0)
+            })
+    }
+
+    fn do_insert(
+        &mut self,
+        address: &ProtocolAddress,
+        sender_ratchet_key: &PublicKey,
+        counter: u32,
+    ) -> Result<(), SignalJniError> {
+        self.env.borrow_mut().with_local_frame(8, "insert", |env| {
+            let address_jobject = protocol_address_to_jobject(env, address)?;
+            let key_handle = sender_ratchet_key.convert_into(env)?;
+            let key_jobject = jobject_from_native_handle(
+                env,
+                ClassName("org.signal.libsignal.protocol.ecc.ECPublicKey"),
+                key_handle,
+            )?;
+            let callback_args = jni_args!((
+                address_jobject => org.signal.libsignal.protocol.SignalProtocolAddress,
+                key_jobject => org.signal.libsignal.protocol.ecc.ECPublicKey,
+                counter.convert_into(env)? => int,
+            ) -> void);
+            call_cached_method_checked(env, Self::CLASS, self.store, "insert", callback_args)?;
+            Ok(())
+        })
+    }
+}
+
+#[async_trait(? Send)]
+impl<'a> ReplayCache for JniReplayCache<'a> {
+    async fn contains(
+        &self,
+        address: &ProtocolAddress,
+        sender_ratchet_key: &PublicKey,
+        counter: u32,
+    ) -> Result<bool, SignalProtocolError> {
+        Ok(self.do_contains(address, sender_ratchet_key, counter)?)
+    }
+
+    async fn insert(
+        &mut self,
+        address: &ProtocolAddress,
+        sender_ratchet_key: &PublicKey,
+        counter: u32,
+    ) -> Result<(), SignalProtocolError> {
+        Ok(self.do_insert(address, sender_ratchet_key, counter)?)
+    }
 }
@@ -41,6 +41,18 @@ mod pin {
     bridge_as_handle!(PinHash, node = false);
 }
 
+// Desktop does not make use of device transfer
+#[cfg(any(feature = "jni", feature = "ffi"))]
+pub mod device_transfer {
+    // Wrapper struct for cbindgen
+    #[derive(Debug)]
+    pub struct DeviceTransferSession(pub ::device_transfer::session::Session);
+
+    use crate::*;
+
+    bridge_as_handle!(DeviceTransferSession, mut = true, node = false);
+}
+
 pub mod incremental_mac;
 pub mod message_backup;
 
@@ -52,7 +64,28 @@ pub mod media {
     #[derive(Clone, Debug)]
     pub struct SanitizedMetadata(pub signal_media::sanitize::mp4::SanitizedMetadata);
 
+    impl Drop for SanitizedMetadata {
+        fn drop(&mut self) {
+            if let Some(metadata) = self.0.metadata.take() {
+                signal_media::sanitize::pool::recycle(metadata);
+            }
+        }
+    }
+
+    #[derive(Debug, Default)]
+    pub struct Mp4SanitizerOptions(std::sync::Mutex<signal_media::sanitize::mp4::SanitizeOptions>);
+
+    impl Mp4SanitizerOptions {
+        pub fn lock(
+            &self,
+        ) -> impl std::ops::DerefMut<Target = signal_media::sanitize::mp4::SanitizeOptions> + '_
+        {
+            self.0.lock().expect("not poisoned")
+        }
+    }
+
     use crate::*;
 
     bridge_as_handle!(SanitizedMetadata);
+    bridge_as_handle!(Mp4SanitizerOptions);
 }
@@ -6,7 +6,8 @@
 use std::str::FromStr as _;
 
 use libsignal_account_keys::{AccountEntropyPool, BackupId, BackupKey, BACKUP_KEY_LEN};
-use libsignal_message_backup::frame::ValidationError as FrameValidationError;
+use libsignal_message_backup::finding::ValidationFinding;
+use libsignal_message_backup::frame::{PaddingPolicy, ValidationError as FrameValidationError};
 use libsignal_message_backup::key::MessageBackupKey as MessageBackupKeyInner;
 use libsignal_message_backup::parse::ParseError;
 use libsignal_message_backup::{Error, FoundUnknownField};
@@ -50,6 +51,10 @@ impl MessageBackupKey {
 
 bridge_as_handle!(MessageBackupKey);
 
+pub struct BackupPaddingPolicy(pub PaddingPolicy);
+
+bridge_as_handle!(BackupPaddingPolicy);
+
 #[derive(Debug)]
 pub enum MessageBackupValidationError {
     Io(std::io::Error),
@@ -65,6 +70,7 @@ impl From<Error> for MessageBackupValidationError {
             e @ Error::NoFrames
             | e @ Error::InvalidProtobuf(_)
             | e @ Error::HmacMismatch(_)
+            | e @ Error::LimitExceeded(_)
             | e @ Error::Parse(ParseError::Decode(_)) => Self::String(e.to_string()),
         }
     }
@@ -84,6 +90,8 @@ impl From<FrameValidationError> for MessageBackupValidationError {
 pub struct MessageBackupValidationOutcome {
     pub error_message: Option<String>,
     pub found_unknown_fields: Vec<FoundUnknownField>,
+    /// Structured versions of `error_message` and `found_unknown_fields`, in that order.
+    pub findings: Vec<ValidationFinding>,
 }
 bridge_as_handle!(MessageBackupValidationOutcome, jni = false, node = false);
 
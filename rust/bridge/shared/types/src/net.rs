@@ -7,23 +7,29 @@ use std::marker::PhantomData;
 use std::num::{NonZeroU16, NonZeroU32};
 use std::panic::RefUnwindSafe;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use aes_gcm_siv::aead::rand_core::CryptoRngCore;
 use async_trait::async_trait;
 use futures_util::future::join3;
 use libsignal_net::auth::Auth;
+use libsignal_net::chat::server_time::ClockSkewTracker;
 use libsignal_net::enclave::{
     Cdsi, EnclaveEndpoint, EnclaveEndpointConnection, EnclaveKind, Nitro, PpssSetup, Sgx, Tpm2Snp,
 };
-use libsignal_net::env::{add_user_agent_header, Env, Svr3Env};
-use libsignal_net::infra::connection_manager::MultiRouteConnectionManager;
+use libsignal_net::env::{add_user_agent_header, ConnectionConfig, Env, Svr3Env};
+use libsignal_net::infra::certs::SpkiPins;
+use libsignal_net::infra::connection_manager::{MultiRouteConnectionManager, RetryPolicy};
+use libsignal_net::infra::dns::lookup_result::LookupResult;
 use libsignal_net::infra::dns::DnsResolver;
 use libsignal_net::infra::host::Host;
 use libsignal_net::infra::tcp_ssl::proxy::tls::TlsProxyConnector as TcpSslProxyConnector;
 use libsignal_net::infra::tcp_ssl::{DirectConnector as TcpSslDirectConnector, TcpSslConnector};
 use libsignal_net::infra::timeouts::ONE_ROUTE_CONNECTION_TIMEOUT;
 use libsignal_net::infra::utils::ObservableEvent;
-use libsignal_net::infra::EndpointConnection;
+use libsignal_net::infra::ws::WebSocketLimits;
+use libsignal_net::infra::{ConnectionParams, DnsSource, EndpointConnection, RouteType};
+use libsignal_net::route_state::{DnsRecord, RouteKey, RouteRecord, RouteState, RouteStateError};
 use libsignal_net::svr::SvrConnection;
 use libsignal_net::svr3::traits::*;
 use libsignal_net::svr3::{Error, OpaqueMaskedShareSet};
@@ -33,6 +39,7 @@ use crate::*;
 
 pub mod cdsi;
 pub mod chat;
+pub mod storage_service;
 pub mod tokio;
 
 pub use tokio::TokioAsyncContext;
@@ -54,25 +61,138 @@ impl Environment {
     }
 }
 
+/// The services whose reconnect behavior can be configured separately.
+#[derive(num_enum::TryFromPrimitive)]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, strum::Display)]
+pub enum NetService {
+    Chat = 0,
+    Cdsi = 1,
+    Svr3 = 2,
+}
+
+/// The kinds of route that can be put in order with [`ConnectionManager::set_route_order`].
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionRoute {
+    Direct = 0,
+    ProxyF = 1,
+    ProxyG = 2,
+}
+
+impl ConnectionRoute {
+    /// The same as [`libsignal_net::env::DEFAULT_ROUTE_ORDER`].
+    pub const DEFAULT_ORDER: [Self; 3] = [Self::Direct, Self::ProxyF, Self::ProxyG];
+}
+
+impl From<ConnectionRoute> for RouteType {
+    fn from(value: ConnectionRoute) -> Self {
+        match value {
+            ConnectionRoute::Direct => RouteType::Direct,
+            ConnectionRoute::ProxyF => RouteType::ProxyF,
+            ConnectionRoute::ProxyG => RouteType::ProxyG,
+        }
+    }
+}
+
+impl TryFrom<RouteType> for ConnectionRoute {
+    type Error = RouteType;
+
+    fn try_from(value: RouteType) -> Result<Self, Self::Error> {
+        match value {
+            RouteType::Direct => Ok(Self::Direct),
+            RouteType::ProxyF => Ok(Self::ProxyF),
+            RouteType::ProxyG => Ok(Self::ProxyG),
+            other => Err(other),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct RetryPolicies {
+    chat: RetryPolicy,
+    cdsi: RetryPolicy,
+    svr3: RetryPolicy,
+}
+
+/// Pins for the direct route to each service; see [`ConnectionManager::set_certificate_pins`].
+#[derive(Clone, Debug, Default)]
+struct CertificatePins {
+    chat: Option<SpkiPins>,
+    cdsi: Option<SpkiPins>,
+    svr3: Option<SpkiPins>,
+}
+
+impl CertificatePins {
+    fn apply(pins: &Option<SpkiPins>, connect: &ConnectionConfig) -> ConnectionConfig {
+        match pins {
+            Some(pins) => connect.clone().with_pins(pins.clone()),
+            None => connect.clone(),
+        }
+    }
+}
+
 type Svr3EndpointConnections = (
     EnclaveEndpointConnection<Sgx, MultiRouteConnectionManager>,
     EnclaveEndpointConnection<Nitro, MultiRouteConnectionManager>,
     EnclaveEndpointConnection<Tpm2Snp, MultiRouteConnectionManager>,
 );
 
+/// The app-controlled settings that [`EndpointConnections`] are built from.
+///
+/// These are kept when the endpoints are rebuilt to change any one of them.
+#[derive(Clone, Debug)]
+struct EndpointSettings {
+    use_fallbacks: bool,
+    route_order: Arc<[ConnectionRoute]>,
+    chat_limits: WebSocketLimits,
+    retry_policies: RetryPolicies,
+    certificate_pins: CertificatePins,
+    /// The proxy connections go through, as `host:port`, so that what's learned about each route
+    /// can be attributed to the right path.
+    proxy: Option<String>,
+}
+
+impl Default for EndpointSettings {
+    fn default() -> Self {
+        Self {
+            use_fallbacks: false,
+            route_order: ConnectionRoute::DEFAULT_ORDER.into(),
+            chat_limits: WebSocketLimits::DEFAULT,
+            retry_policies: RetryPolicies::default(),
+            certificate_pins: CertificatePins::default(),
+            proxy: None,
+        }
+    }
+}
+
 struct EndpointConnections {
     chat: EndpointConnection<MultiRouteConnectionManager>,
     cdsi: EnclaveEndpointConnection<Cdsi, MultiRouteConnectionManager>,
     svr3: Svr3EndpointConnections,
+    settings: EndpointSettings,
 }
 
 impl EndpointConnections {
+    /// Creates endpoints that try `preferred_routes` before any others (otherwise following the
+    /// configured route order).
     fn new(
         env: &Env<'static, Svr3Env<'static>>,
         user_agent: &str,
-        use_fallbacks: bool,
+        settings: EndpointSettings,
+        preferred_routes: &[RouteKey],
         network_change_event: &ObservableEvent,
     ) -> Self {
+        let EndpointSettings {
+            use_fallbacks,
+            route_order,
+            chat_limits,
+            retry_policies,
+            certificate_pins,
+            proxy: _,
+        } = &settings;
+        let use_fallbacks = *use_fallbacks;
         log::info!(
             "Creating endpoint connections (fallbacks {}) for {} and others",
             if use_fallbacks { "enabled" } else { "disabled" },
@@ -81,59 +201,121 @@ impl EndpointConnections {
             // testing. (Or the person running this isn't Signal.)
             env.chat_domain_config.connect.hostname
         );
-        let chat = libsignal_net::chat::endpoint_connection(
-            &env.chat_domain_config.connect,
+        // Without censorship circumvention, the direct route is the only one there is.
+        let effective_route_order = if use_fallbacks {
+            route_order.iter().copied().map(RouteType::from).collect()
+        } else {
+            vec![RouteType::Direct]
+        };
+        let is_preferred = |params: &ConnectionParams| {
+            preferred_routes
+                .iter()
+                .any(|preferred| preferred.matches(params))
+        };
+        let mut chat = libsignal_net::chat::endpoint_connection(
+            &CertificatePins::apply(&certificate_pins.chat, &env.chat_domain_config.connect),
+            user_agent,
+            &effective_route_order,
+            *chat_limits,
+            retry_policies.chat,
+            network_change_event,
+        );
+        chat.manager = chat.manager.with_preferred_routes(&is_preferred);
+        let cdsi = Self::endpoint_connection(
+            &env.cdsi,
             user_agent,
-            use_fallbacks,
+            &effective_route_order,
+            &is_preferred,
+            retry_policies.cdsi,
+            &certificate_pins.cdsi,
             network_change_event,
         );
-        let cdsi =
-            Self::endpoint_connection(&env.cdsi, user_agent, use_fallbacks, network_change_event);
         let svr3 = (
             Self::endpoint_connection(
                 env.svr3.sgx(),
                 user_agent,
-                use_fallbacks,
+                &effective_route_order,
+                &is_preferred,
+                retry_policies.svr3,
+                &certificate_pins.svr3,
                 network_change_event,
             ),
             Self::endpoint_connection(
                 env.svr3.nitro(),
                 user_agent,
-                use_fallbacks,
+                &effective_route_order,
+                &is_preferred,
+                retry_policies.svr3,
+                &certificate_pins.svr3,
                 network_change_event,
             ),
             Self::endpoint_connection(
                 env.svr3.tpm2snp(),
                 user_agent,
-                use_fallbacks,
+                &effective_route_order,
+                &is_preferred,
+                retry_policies.svr3,
+                &certificate_pins.svr3,
                 network_change_event,
             ),
         );
-        Self { chat, cdsi, svr3 }
+        Self {
+            chat,
+            cdsi,
+            svr3,
+            settings,
+        }
     }
 
     fn endpoint_connection<E: EnclaveKind>(
         endpoint: &EnclaveEndpoint<'static, E>,
         user_agent: &str,
-        include_fallback: bool,
+        route_order: &[RouteType],
+        is_preferred: &dyn Fn(&ConnectionParams) -> bool,
+        retry_policy: RetryPolicy,
+        pins: &Option<SpkiPins>,
         network_change_event: &ObservableEvent,
     ) -> EnclaveEndpointConnection<E, MultiRouteConnectionManager> {
-        let params = if include_fallback {
-            endpoint
-                .domain_config
-                .connect
-                .connection_params_with_fallback()
-        } else {
-            vec![endpoint.domain_config.connect.direct_connection_params()]
-        };
+        let mut params = CertificatePins::apply(pins, &endpoint.domain_config.connect)
+            .connection_params_in_order(route_order);
+        // Stable, so the configured order is kept otherwise.
+        params.sort_by_key(|params| !is_preferred(params));
         let params = add_user_agent_header(params, user_agent);
         EnclaveEndpointConnection::new_multi(
             endpoint,
             params,
             ONE_ROUTE_CONNECTION_TIMEOUT,
+            retry_policy,
             network_change_event,
         )
     }
+
+    /// Returns how the most recent attempt on each route went, for the routes that have been tried.
+    fn learned_routes(&self) -> Vec<RouteRecord> {
+        let Self {
+            chat,
+            cdsi,
+            svr3: (sgx, nitro, tpm2snp),
+            settings,
+        } = self;
+        [
+            &chat.manager,
+            cdsi.manager(),
+            sgx.manager(),
+            nitro.manager(),
+            tpm2snp.manager(),
+        ]
+        .into_iter()
+        .flat_map(MultiRouteConnectionManager::route_managers)
+        .filter_map(|route_manager| {
+            Some(RouteRecord {
+                route: RouteKey::for_params(route_manager.connection_params()),
+                proxy: settings.proxy.clone(),
+                outcome: route_manager.latest_outcome()?,
+            })
+        })
+        .collect()
+    }
 }
 
 pub struct ConnectionManager {
@@ -143,7 +325,13 @@ pub struct ConnectionManager {
     // but we don't hold it for very long anyway (just enough to clone the Arc).
     endpoints: std::sync::Mutex<Arc<EndpointConnections>>,
     transport_connector: std::sync::Mutex<TcpSslConnector>,
+    /// Route outcomes from imported state and from endpoints that have since been replaced.
+    ///
+    /// Always locked after `endpoints` when both are needed.
+    known_routes: std::sync::Mutex<RouteState>,
     network_change_event: ObservableEvent,
+    /// Shared with each chat connection, which updates it from the server's responses.
+    clock_skew: Arc<ClockSkewTracker>,
 }
 
 impl RefUnwindSafe for ConnectionManager {}
@@ -164,18 +352,70 @@ impl ConnectionManager {
         let transport_connector =
             std::sync::Mutex::new(TcpSslDirectConnector::new(dns_resolver).into());
         let endpoints = std::sync::Mutex::new(
-            EndpointConnections::new(&env, user_agent, false, &network_change_event).into(),
+            EndpointConnections::new(
+                &env,
+                user_agent,
+                EndpointSettings::default(),
+                &[],
+                &network_change_event,
+            )
+            .into(),
         );
         Self {
             env,
             user_agent: user_agent.to_owned(),
             endpoints,
             transport_connector,
+            known_routes: Default::default(),
             network_change_event,
+            clock_skew: Default::default(),
         }
     }
 
+    /// Rebuilds the endpoints with updated settings, carrying over what the current ones learned.
+    fn update_endpoints(&self, update: impl FnOnce(&mut EndpointSettings)) {
+        let mut guard = self.endpoints.lock().expect("not poisoned");
+        let mut known_routes = self.known_routes.lock().expect("not poisoned");
+        let learned = RouteState {
+            routes: guard.learned_routes(),
+            dns_lookups: vec![],
+            route_order: None,
+        };
+        *known_routes = std::mem::take(&mut *known_routes).merge(learned, SystemTime::now());
+
+        let mut settings = guard.settings.clone();
+        update(&mut settings);
+        let preferred_routes = known_routes.working_routes(settings.proxy.as_deref());
+        *guard = Arc::new(EndpointConnections::new(
+            &self.env,
+            &self.user_agent,
+            settings,
+            &preferred_routes,
+            &self.network_change_event,
+        ));
+    }
+
+    /// Sets the proxy to use for all new connections.
+    ///
+    /// This also rebuilds the endpoints, resetting any cooldowns, since connecting through a
+    /// different proxy may change which routes work.
     pub fn set_proxy(&self, host: &str, port: Option<NonZeroU16>) -> Result<(), std::io::Error> {
+        let result = self.set_proxy_connector(host, port);
+        // Without a valid port nothing can connect, but recording failures separately keeps them
+        // from counting against the previous path.
+        let proxy = match port {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_owned(),
+        };
+        self.update_endpoints(|settings| settings.proxy = Some(proxy));
+        result
+    }
+
+    fn set_proxy_connector(
+        &self,
+        host: &str,
+        port: Option<NonZeroU16>,
+    ) -> Result<(), std::io::Error> {
         let host = Host::parse_as_ip_or_domain(host);
 
         let mut guard = self.transport_connector.lock().expect("not poisoned");
@@ -207,14 +447,28 @@ impl ConnectionManager {
     }
 
     pub fn clear_proxy(&self) {
-        let mut guard = self.transport_connector.lock().expect("not poisoned");
-        match &*guard {
-            TcpSslConnector::Direct(_direct) => (),
-            TcpSslConnector::Proxied(TcpSslProxyConnector { dns_resolver, .. })
-            | TcpSslConnector::Invalid(dns_resolver) => {
-                *guard = TcpSslDirectConnector::new(dns_resolver.clone()).into()
-            }
-        };
+        {
+            let mut guard = self.transport_connector.lock().expect("not poisoned");
+            match &*guard {
+                TcpSslConnector::Direct(_direct) => return,
+                TcpSslConnector::Proxied(TcpSslProxyConnector { dns_resolver, .. })
+                | TcpSslConnector::Invalid(dns_resolver) => {
+                    *guard = TcpSslDirectConnector::new(dns_resolver.clone()).into()
+                }
+            };
+        }
+        self.update_endpoints(|settings| settings.proxy = None);
+    }
+
+    /// The proxy passed to [`Self::set_proxy`] as `host:port` (or just `host` if the port was
+    /// invalid), or `None` if connections are direct.
+    pub fn proxy(&self) -> Option<String> {
+        self.endpoints
+            .lock()
+            .expect("not poisoned")
+            .settings
+            .proxy
+            .clone()
     }
 
     pub fn set_ipv6_enabled(&self, ipv6_enabled: bool) {
@@ -227,18 +481,176 @@ impl ConnectionManager {
     /// This is not itself a network change event; existing working connections are expected to
     /// continue to work, and existing failing connections will continue to fail.
     pub fn set_censorship_circumvention_enabled(&self, enabled: bool) {
-        let new_endpoints = EndpointConnections::new(
-            &self.env,
-            &self.user_agent,
-            enabled,
-            &self.network_change_event,
-        );
-        *self.endpoints.lock().expect("not poisoned") = Arc::new(new_endpoints);
+        self.update_endpoints(|settings| settings.use_fallbacks = enabled)
+    }
+
+    /// Resets the chat endpoint to use the given frame, message, and send buffer sizes.
+    ///
+    /// Like [`Self::set_censorship_circumvention_enabled`], this only affects new connections.
+    pub fn set_chat_websocket_limits(&self, limits: WebSocketLimits) {
+        self.update_endpoints(|settings| settings.chat_limits = limits)
+    }
+
+    /// Resets the endpoints for `service` to use the given backoff between connection attempts.
+    ///
+    /// Like [`Self::set_censorship_circumvention_enabled`], this only affects new connections.
+    pub fn set_retry_policy(&self, service: NetService, policy: RetryPolicy) {
+        self.update_endpoints(|settings| {
+            let retry_policies = &mut settings.retry_policies;
+            *match service {
+                NetService::Chat => &mut retry_policies.chat,
+                NetService::Cdsi => &mut retry_policies.cdsi,
+                NetService::Svr3 => &mut retry_policies.svr3,
+            } = policy;
+        })
+    }
+
+    /// Resets the endpoints for `service` to require one of `pins` on the direct route, or to stop
+    /// requiring pins if `pins` is `None`.
+    ///
+    /// Domain-fronted routes end at the fronting provider, so they aren't pinned. Like
+    /// [`Self::set_censorship_circumvention_enabled`], this only affects new connections.
+    pub fn set_certificate_pins(&self, service: NetService, pins: Option<SpkiPins>) {
+        self.update_endpoints(|settings| {
+            let certificate_pins = &mut settings.certificate_pins;
+            *match service {
+                NetService::Chat => &mut certificate_pins.chat,
+                NetService::Cdsi => &mut certificate_pins.cdsi,
+                NetService::Svr3 => &mut certificate_pins.svr3,
+            } = pins;
+        })
+    }
+
+    /// Resets the endpoints to try routes in the given order, leaving out any that aren't listed.
+    ///
+    /// This only matters while censorship circumvention is enabled; otherwise only the direct route
+    /// is used. The order is kept when other settings change, and is saved by
+    /// [`Self::export_route_state`] so it can be restored on a later launch. Like
+    /// [`Self::set_censorship_circumvention_enabled`], this only affects new connections.
+    pub fn set_route_order(&self, order: &[ConnectionRoute]) {
+        self.update_endpoints(|settings| settings.route_order = order.into())
+    }
+
+    pub fn route_order(&self) -> Arc<[ConnectionRoute]> {
+        self.endpoints
+            .lock()
+            .expect("not poisoned")
+            .settings
+            .route_order
+            .clone()
+    }
+
+    /// Saves which routes have been working, along with cached DNS results, encrypted with `key`.
+    ///
+    /// The result can be passed to [`Self::import_route_state`] on a later launch (with the same
+    /// key) so that routes that were working get tried first. It includes whatever was imported,
+    /// minus anything that has since been superseded or is too old to be useful.
+    pub fn export_route_state(&self, key: &[u8; 32], rng: &mut impl CryptoRngCore) -> Vec<u8> {
+        let now = SystemTime::now();
+        let routes = self
+            .endpoints
+            .lock()
+            .expect("not poisoned")
+            .learned_routes();
+        let dns_lookups = {
+            let transport_connector = self.transport_connector.lock().expect("not poisoned");
+            let instant_now = ::tokio::time::Instant::now();
+            transport_connector
+                .dns_resolver()
+                .cached_lookups()
+                .into_iter()
+                .map(|(hostname, lookup_result, expiration)| DnsRecord {
+                    hostname,
+                    ipv4: lookup_result.ipv4().to_vec(),
+                    ipv6: lookup_result.ipv6().to_vec(),
+                    expiration: now + expiration.saturating_duration_since(instant_now),
+                })
+                .collect()
+        };
+        let known_routes = self.known_routes.lock().expect("not poisoned").clone();
+        known_routes
+            .merge(
+                RouteState {
+                    routes,
+                    dns_lookups,
+                    route_order: Some(
+                        self.route_order()
+                            .iter()
+                            .copied()
+                            .map(RouteType::from)
+                            .collect(),
+                    ),
+                },
+                now,
+            )
+            .encrypt(key, rng)
+    }
+
+    /// Restores state saved by [`Self::export_route_state`].
+    ///
+    /// Routes that were working (through the same proxy, if any) are tried before others by new
+    /// connections, and unexpired DNS results are added to the cache. A saved route order replaces
+    /// the current one. Existing connections are not affected.
+    pub fn import_route_state(
+        &self,
+        key: &[u8; 32],
+        encrypted: &[u8],
+    ) -> Result<(), RouteStateError> {
+        let imported = RouteState::decrypt(key, encrypted)?;
+        let now = SystemTime::now();
+
+        {
+            let transport_connector = self.transport_connector.lock().expect("not poisoned");
+            let instant_now = ::tokio::time::Instant::now();
+            transport_connector
+                .dns_resolver()
+                .seed_cache(imported.dns_lookups.iter().filter_map(|record| {
+                    let remaining = record.expiration.duration_since(now).ok()?;
+                    let lookup_result = LookupResult::new(
+                        DnsSource::Cache,
+                        record.ipv4.clone(),
+                        record.ipv6.clone(),
+                    );
+                    Some((
+                        record.hostname.clone(),
+                        lookup_result,
+                        instant_now + remaining,
+                    ))
+                }));
+        }
+
+        {
+            let mut known_routes = self.known_routes.lock().expect("not poisoned");
+            let routes = RouteState {
+                routes: imported.routes,
+                dns_lookups: vec![],
+                route_order: None,
+            };
+            *known_routes = std::mem::take(&mut *known_routes).merge(routes, now);
+        }
+        // Rebuild the endpoints so the imported routes take effect.
+        self.update_endpoints(|settings| {
+            if let Some(order) = imported.route_order {
+                // Only the routes an app can order are ever saved.
+                settings.route_order = order
+                    .into_iter()
+                    .filter_map(|route_type| ConnectionRoute::try_from(route_type).ok())
+                    .collect();
+            }
+        });
+        Ok(())
     }
 
     pub fn on_network_change(&self) {
         self.network_change_event.fire()
     }
+
+    /// The current time according to the chat server, based on its recent responses.
+    ///
+    /// Falls back to the local time if no chat responses have been received yet.
+    pub fn server_time(&self) -> SystemTime {
+        self.clock_skew.server_now()
+    }
 }
 
 bridge_as_handle!(ConnectionManager);
@@ -396,7 +808,11 @@ mod empty_env {
 
 #[cfg(test)]
 mod test {
+    use aes_gcm_siv::aead::OsRng;
     use assert_matches::assert_matches;
+    use libsignal_net::env::DEFAULT_ROUTE_ORDER;
+    use libsignal_net::infra::certs::RootCertificates;
+    use libsignal_net::infra::connection_manager::RouteOutcome;
     use test_case::test_case;
 
     use super::*;
@@ -415,4 +831,157 @@ mod test {
         let transport_connector = manager.transport_connector.lock().expect("not poisoned");
         assert_matches!(&*transport_connector, TcpSslConnector::Invalid(_))
     }
+
+    #[test]
+    fn default_route_order_matches() {
+        assert_eq!(
+            ConnectionRoute::DEFAULT_ORDER.map(RouteType::from),
+            DEFAULT_ROUTE_ORDER
+        );
+    }
+
+    #[test]
+    fn route_order_survives_other_settings() {
+        let manager = ConnectionManager::new(Environment::Staging, "test-user-agent");
+        assert_eq!(&*manager.route_order(), ConnectionRoute::DEFAULT_ORDER);
+
+        let order = [ConnectionRoute::ProxyG, ConnectionRoute::Direct];
+        manager.set_route_order(&order);
+        manager.set_censorship_circumvention_enabled(true);
+        manager.set_chat_websocket_limits(WebSocketLimits::DEFAULT);
+        assert_eq!(&*manager.route_order(), order);
+    }
+
+    #[test]
+    fn route_order_is_restored_by_import() {
+        const KEY: [u8; 32] = [0x42; 32];
+        let order = [ConnectionRoute::ProxyF, ConnectionRoute::Direct];
+        let exported = {
+            let manager = ConnectionManager::new(Environment::Staging, "test-user-agent");
+            manager.set_route_order(&order);
+            manager.export_route_state(&KEY, &mut OsRng)
+        };
+
+        let manager = ConnectionManager::new(Environment::Staging, "test-user-agent");
+        manager.import_route_state(&KEY, &exported).expect("valid");
+        assert_eq!(&*manager.route_order(), order);
+    }
+
+    #[test]
+    fn proxy_is_reported() {
+        let manager = ConnectionManager::new(Environment::Staging, "test-user-agent");
+        assert_eq!(manager.proxy(), None);
+        manager
+            .set_proxy("proxy.example", NonZeroU16::new(443))
+            .expect("valid");
+        assert_eq!(manager.proxy().as_deref(), Some("proxy.example:443"));
+        manager.clear_proxy();
+        assert_eq!(manager.proxy(), None);
+    }
+
+    #[test]
+    fn certificate_pins_apply_to_direct_route() {
+        let manager = ConnectionManager::new(Environment::Staging, "test-user-agent");
+        manager.set_censorship_circumvention_enabled(true);
+        let pins = SpkiPins::new([[0x42; 32]]).expect("non-empty");
+        manager.set_certificate_pins(NetService::Chat, Some(pins.clone()));
+
+        let endpoints = manager.endpoints.lock().expect("not poisoned").clone();
+        for route_manager in endpoints.chat.manager.route_managers().iter() {
+            let params = route_manager.connection_params();
+            let is_pinned = matches!(
+                &params.transport.certs,
+                RootCertificates::Pinned { pins: route_pins, .. } if *route_pins == pins
+            );
+            assert_eq!(
+                is_pinned,
+                params.route_type == RouteType::Direct,
+                "{:?}",
+                params.route_type
+            );
+        }
+        assert_matches!(
+            endpoints.cdsi.manager().route_managers()[0]
+                .connection_params()
+                .transport
+                .certs,
+            RootCertificates::FromStaticDers(_)
+        );
+
+        manager.set_certificate_pins(NetService::Chat, None);
+        let endpoints = manager.endpoints.lock().expect("not poisoned").clone();
+        assert_matches!(
+            endpoints.chat.manager.route_managers()[0]
+                .connection_params()
+                .transport
+                .certs,
+            RootCertificates::FromStaticDers(_)
+        );
+    }
+
+    #[test]
+    fn imported_working_routes_are_tried_first() {
+        const KEY: [u8; 32] = [0x42; 32];
+        let env = Environment::Staging.env();
+        let proxy_g_params = env
+            .chat_domain_config
+            .connect
+            .connection_params_in_order(&[RouteType::ProxyG]);
+        let working_route = RouteKey::for_params(proxy_g_params.last().expect("has fronts"));
+        let state = RouteState {
+            routes: vec![RouteRecord {
+                route: working_route.clone(),
+                proxy: None,
+                outcome: RouteOutcome {
+                    succeeded: true,
+                    at: SystemTime::now(),
+                },
+            }],
+            dns_lookups: vec![],
+            route_order: None,
+        };
+
+        let manager = ConnectionManager::new(Environment::Staging, "test-user-agent");
+        manager
+            .import_route_state(&KEY, &state.encrypt(&KEY, &mut OsRng))
+            .expect("valid");
+        manager.set_censorship_circumvention_enabled(true);
+        let first_chat_route = RouteKey::for_params(
+            manager
+                .endpoints
+                .lock()
+                .expect("not poisoned")
+                .chat
+                .manager
+                .route_managers()[0]
+                .connection_params(),
+        );
+        assert_eq!(first_chat_route, working_route);
+
+        // The imported route is exported again.
+        let exported = manager.export_route_state(&KEY, &mut OsRng);
+        let reimported = RouteState::decrypt(&KEY, &exported).expect("valid");
+        assert_eq!(reimported.working_routes(None), [working_route.clone()]);
+
+        // ...but doesn't apply through a proxy.
+        manager
+            .set_proxy("proxy.example", NonZeroU16::new(443))
+            .expect("valid");
+        let first_chat_route = RouteKey::for_params(
+            manager
+                .endpoints
+                .lock()
+                .expect("not poisoned")
+                .chat
+                .manager
+                .route_managers()[0]
+                .connection_params(),
+        );
+        assert_ne!(first_chat_route, working_route);
+
+        assert_matches!(
+            manager.import_route_state(&[0; 32], &exported),
+            Err(RouteStateError::DecryptionFailed)
+        );
+    }
 }
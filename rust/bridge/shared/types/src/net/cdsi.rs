@@ -3,6 +3,9 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use std::sync::Arc;
+use std::time::Duration;
+
 use libsignal_net::auth::Auth;
 use libsignal_net::cdsi::{self, CdsiConnection, ClientResponseCollector, Token};
 
@@ -20,7 +23,7 @@ pub enum CdsiError {
     /// Invalid response received from the server
     InvalidResponse,
     /// Retry later
-    RateLimited { retry_after: std::time::Duration },
+    RateLimited(libsignal_net::rate_limit::RateLimit),
     /// Failed to parse the response from the server
     ParseError,
     /// Request token was invalid
@@ -72,9 +75,72 @@ impl CdsiLookup {
         })
     }
 
+    /// Like [`CdsiLookup::new`], but takes its connection from `pool` if possible.
+    ///
+    /// If `prepare_next` is set, then once the request has been sent, a
+    /// connection for the next lookup is established in the background and put
+    /// in the pool, unless the pool already has enough for these credentials.
+    /// Callers should only set it when another lookup will follow soon, since
+    /// otherwise the attestation handshake is wasted.
+    pub async fn new_pooled(
+        connection_manager: &ConnectionManager,
+        auth: Auth,
+        request: cdsi::LookupRequest,
+        pool: &CdsiConnectionPool,
+        prepare_next: bool,
+    ) -> Result<Self, cdsi::LookupError> {
+        let transport_connector = connection_manager
+            .transport_connector
+            .lock()
+            .expect("not poisoned")
+            .clone();
+        let endpoints = connection_manager
+            .endpoints
+            .lock()
+            .expect("not poisoned")
+            .clone();
+        let connected = pool
+            .0
+            .take_or_connect(
+                &auth,
+                CdsiConnection::connect(&endpoints.cdsi, transport_connector.clone(), auth.clone()),
+            )
+            .await?;
+        let (token, remaining_response) = connected.send_request(request).await?;
+
+        if prepare_next {
+            pool.0.refill(&auth, {
+                let auth = auth.clone();
+                async move {
+                    CdsiConnection::connect(&endpoints.cdsi, transport_connector, auth).await
+                }
+            });
+        }
+
+        Ok(CdsiLookup {
+            token,
+            remaining: std::sync::Mutex::new(Some(remaining_response)),
+        })
+    }
+
     pub fn take_remaining(&self) -> Option<ClientResponseCollector> {
         self.remaining.lock().expect("not poisoned").take()
     }
 }
 
 bridge_as_handle!(CdsiLookup);
+
+pub struct CdsiConnectionPool(Arc<cdsi::CdsiConnectionPool>);
+
+impl CdsiConnectionPool {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self(Arc::new(cdsi::CdsiConnectionPool::new(idle_timeout)))
+    }
+}
+
+bridge_as_handle!(CdsiConnectionPool);
+
+/// The changes found by comparing a lookup response with a client's existing contacts.
+pub struct CdsiContactChanges(pub Vec<cdsi::ContactChange>);
+
+bridge_as_handle!(CdsiContactChanges);
@@ -7,6 +7,7 @@ use std::future::Future;
 use std::panic::{self, RefUnwindSafe};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 use atomic_take::AtomicTake;
 use futures_util::stream::BoxStream;
@@ -15,6 +16,9 @@ use http::status::InvalidStatusCode;
 use http::uri::{InvalidUri, PathAndQuery};
 use http::{HeaderMap, HeaderName, HeaderValue};
 use libsignal_net::auth::Auth;
+use libsignal_net::chat::background::{BackgroundFlushReport, BackgroundFlushTracker};
+use libsignal_net::chat::receipts::{ReceiptCoalescer, ReceiptTarget};
+use libsignal_net::chat::server_time::ClockSkewTracker;
 use libsignal_net::chat::{
     self, ChatServiceError, DebugInfo as ChatServiceDebugInfo, Response as ChatResponse,
 };
@@ -59,8 +63,14 @@ pub struct Chat<T> {
     listener: std::sync::Mutex<ChatListenerState>,
     pub synthetic_request_tx:
         mpsc::Sender<chat::ws::ServerEvent<libsignal_net::infra::tcp_ssl::TcpSslConnectorStream>>,
+    background: BackgroundFlushTracker,
+    clock_skew: Arc<ClockSkewTracker>,
+    receipts: std::sync::Mutex<ReceiptCoalescer>,
 }
 
+/// How much of an app's background time is held back for closing the connection.
+const BACKGROUND_DISCONNECT_RESERVE: Duration = Duration::from_millis(500);
+
 type MpscPair<T> = (mpsc::Sender<T>, mpsc::Receiver<T>);
 type ServerEventStreamPair =
     MpscPair<chat::ws::ServerEvent<libsignal_net::infra::tcp_ssl::TcpSslConnectorStream>>;
@@ -83,16 +93,30 @@ impl RefUnwindSafe for AuthChatService {}
 impl RefUnwindSafe for UnauthChatService {}
 
 impl<T> Chat<T> {
-    fn new(service: T, (incoming_tx, incoming_rx): ServerEventStreamPair) -> Self {
+    fn new(
+        service: T,
+        (incoming_tx, incoming_rx): ServerEventStreamPair,
+        clock_skew: Arc<ClockSkewTracker>,
+    ) -> Self {
         let incoming_stream = chat::server_requests::stream_incoming_messages(incoming_rx);
 
         Self {
             service,
             listener: std::sync::Mutex::new(ChatListenerState::Inactive(Box::pin(incoming_stream))),
             synthetic_request_tx: incoming_tx,
+            background: BackgroundFlushTracker::default(),
+            clock_skew,
+            receipts: Default::default(),
         }
     }
 
+    /// Wraps an already-constructed service, such as a mock used in tests.
+    ///
+    /// Server events can still be delivered through [`Self::synthetic_request_tx`].
+    pub fn from_service(service: T) -> Self {
+        Self::new(service, mpsc::channel(1), Default::default())
+    }
+
     pub fn set_listener(&self, listener: Box<dyn ChatListener>, runtime: &TokioAsyncContext) {
         use futures_util::future::Either;
 
@@ -119,9 +143,11 @@ impl<T> Chat<T> {
         // We're not using run_future here because we aren't trying to run a single task; we're
         // starting a run-loop. We *do* want that run-loop to be async so it goes to sleep when
         // there are no messages.
-        let handle = runtime
-            .rt
-            .spawn(listener.start_listening(request_stream_future, cancel_rx));
+        let handle = runtime.rt.spawn(listener.start_listening(
+            request_stream_future,
+            cancel_rx,
+            self.background.clone(),
+        ));
 
         *guard = ChatListenerState::Active {
             handle,
@@ -133,6 +159,56 @@ impl<T> Chat<T> {
     pub fn clear_listener(&self) {
         self.listener.lock().expect("unpoisoned").cancel();
     }
+
+    /// Runs `request`, keeping track of it in case the app is about to go into the background.
+    ///
+    /// Fails without running `request` if the app has already asked to go into the background.
+    pub async fn tracked_request<R>(
+        &self,
+        request: impl Future<Output = Result<R, ChatServiceError>>,
+    ) -> Result<R, ChatServiceError> {
+        let work = self.background.start_request()?;
+        let result = request.await;
+        work.finish(result.is_ok());
+        result
+    }
+
+    /// Waits for outstanding acks and requests to finish, leaving time to disconnect cleanly
+    /// afterwards.
+    ///
+    /// `budget` is how much background execution time the app has left. New requests are refused
+    /// until [`Self::resume_from_background`] is called.
+    pub async fn flush_for_background(&self, budget: Duration) -> BackgroundFlushReport {
+        let deadline =
+            tokio::time::Instant::now() + budget.saturating_sub(BACKGROUND_DISCONNECT_RESERVE);
+        self.background.flush(deadline).await
+    }
+
+    /// Accepts new requests again after [`Self::flush_for_background`].
+    pub fn resume_from_background(&self) {
+        self.background.resume()
+    }
+
+    /// Holds a receipt to be sent later, batched with others for the same sender.
+    pub fn queue_receipt(&self, target: ReceiptTarget) {
+        self.receipts.lock().expect("unpoisoned").add(target)
+    }
+
+    /// Takes the next batch of queued receipts, which should be sent as a single receipt message.
+    pub fn next_receipt_batch(&self) -> Option<ReceiptBatch> {
+        self.receipts
+            .lock()
+            .expect("unpoisoned")
+            .next_batch()
+            .map(ReceiptBatch)
+    }
+
+    /// Updates the connection manager's estimate of the server's clock from a response to a
+    /// request sent at `request_sent`.
+    pub fn record_server_time(&self, request_sent: SystemTime, response: &ChatResponse) {
+        self.clock_skew
+            .record_response(&response.headers, request_sent, SystemTime::now())
+    }
 }
 
 impl Chat<AuthChatService> {
@@ -169,6 +245,7 @@ impl Chat<AuthChatService> {
         Self::new(
             AuthChatService(service),
             (synthetic_request_tx, incoming_auth_rx),
+            connection_manager.clock_skew.clone(),
         )
     }
 }
@@ -206,6 +283,7 @@ impl Chat<UnauthChatService> {
         Self::new(
             UnauthChatService(service),
             (synthetic_request_tx, incoming_unauth_rx),
+            connection_manager.clock_skew.clone(),
         )
     }
 }
@@ -229,6 +307,14 @@ bridge_as_handle!(UnauthChat);
 bridge_as_handle!(AuthChat);
 bridge_as_handle!(HttpRequest);
 
+pub struct ReceiptBatch(pub chat::receipts::ReceiptBatch);
+
+bridge_as_handle!(ReceiptBatch);
+
+pub struct KeyTransparencySearchCache(pub chat::key_transparency::SearchCache);
+
+bridge_as_handle!(KeyTransparencySearchCache);
+
 /// Newtype wrapper for implementing [`TryFrom`]`
 pub struct HttpMethod(http::Method);
 
@@ -248,6 +334,12 @@ impl TryFrom<u16> for HttpStatus {
     }
 }
 
+impl From<HttpMethod> for http::Method {
+    fn from(value: HttpMethod) -> Self {
+        value.0
+    }
+}
+
 impl From<HttpStatus> for http::StatusCode {
     fn from(value: HttpStatus) -> Self {
         value.0
@@ -289,12 +381,67 @@ pub trait ChatListener: Send {
     );
     fn received_queue_empty(&mut self);
     fn connection_interrupted(&mut self, disconnect_cause: ChatServiceError);
+
+    /// Takes the executor this listener's callbacks should run on, if it has one.
+    ///
+    /// Called once, when the listener is installed. Without an executor, callbacks run on tokio's
+    /// blocking thread pool.
+    ///
+    /// Only the FFI listener offers one. The Node listener doesn't need one, because it already
+    /// hands every callback to the JavaScript event loop through its Neon `Channel`, in order. Chat
+    /// listeners aren't bridged to Java at all yet; when they are, they should accept an `Executor`
+    /// the same way.
+    fn take_executor(&mut self) -> Option<Box<dyn ChatListenerExecutor>> {
+        None
+    }
+}
+
+/// Runs [`ChatListener`] callbacks in an app-chosen context, such as a serial dispatch queue.
+///
+/// Tasks are submitted one at a time: the next one isn't submitted until the previous one has run.
+/// Executors therefore don't need to provide any ordering of their own.
+pub trait ChatListenerExecutor: Send + Sync {
+    /// Arranges for `task` to be run, exactly once, and then destroyed.
+    ///
+    /// Dropping `task` without running it stops delivery to the listener.
+    fn execute(&self, task: ChatListenerTask);
+}
+
+/// A single delivery to a [`ChatListener`], handed to a [`ChatListenerExecutor`].
+pub struct ChatListenerTask {
+    inner: AtomicTake<Box<dyn FnOnce() + Send>>,
+}
+
+impl ChatListenerTask {
+    fn new(task: impl FnOnce() + Send + 'static) -> Self {
+        Self {
+            inner: AtomicTake::new(Box::new(task)),
+        }
+    }
+
+    /// Runs the task. Only the first call has any effect.
+    pub fn run(&self) {
+        if let Some(task) = self.inner.take() {
+            task()
+        }
+    }
 }
 
+bridge_as_handle!(ChatListenerTask, jni = false, node = false);
+
+// See the equivalent comment on ServerMessageAck: the `AtomicTake` is only manipulated atomically.
+impl RefUnwindSafe for ChatListenerTask {}
+
 impl dyn ChatListener {
     /// A helper to translate from the libsignal-net enum to the separate callback methods in this
     /// trait.
-    fn received_server_request(&mut self, request: chat::server_requests::ServerEvent) {
+    ///
+    /// Acks for incoming messages are tracked by `background` until they're sent.
+    fn received_server_request(
+        &mut self,
+        request: chat::server_requests::ServerEvent,
+        background: &BackgroundFlushTracker,
+    ) {
         match request {
             chat::server_requests::ServerEvent::IncomingMessage {
                 request_id: _,
@@ -304,7 +451,7 @@ impl dyn ChatListener {
             } => self.received_incoming_message(
                 envelope,
                 server_delivery_timestamp,
-                ServerMessageAck::new(send_ack),
+                ServerMessageAck::tracked(send_ack, background),
             ),
             chat::server_requests::ServerEvent::QueueEmpty => self.received_queue_empty(),
             chat::server_requests::ServerEvent::Stopped(error) => {
@@ -329,6 +476,7 @@ impl dyn ChatListener {
             >,
         >,
         mut cancel_rx: oneshot::Receiver<()>,
+        background: BackgroundFlushTracker,
     ) -> BoxStream<'static, chat::server_requests::ServerEvent> {
         // This is normally done implicitly inside tokio::task::spawn[_blocking], but we do it
         // explicitly here to get a panic right away rather than only when the first request comes
@@ -342,7 +490,9 @@ impl dyn ChatListener {
             .await
             .unwrap_or_else(|e| panic::resume_unwind(e.into_panic()));
 
-        let mut listener = Some(self);
+        let mut listener = self;
+        let executor = listener.take_executor();
+        let mut listener = Some(listener);
         loop {
             let next = ::tokio::select! {
                 biased; // Always checking cancellation first makes it easier to test changing listeners.
@@ -354,24 +504,47 @@ impl dyn ChatListener {
             };
 
             // We won't read the next item until the first one is delivered, because order is
-            // important. But we still want to jump over to a blocking thread, because we don't
-            // *really* know what the app is going to do, and tokio should be able to work on other
-            // properly async tasks in the mean time. (And because of this, we have to move
-            // `listener` out and back into this task.)
-            let mut listener_for_blocking_task = listener.take().expect("have listener");
-            let blocking_task = runtime.spawn_blocking(move || {
-                listener_for_blocking_task.received_server_request(next);
-                listener_for_blocking_task
-            });
-            listener = match blocking_task.await {
+            // important. But we still want to jump over to a blocking thread (or the app's
+            // executor), because we don't *really* know what the app is going to do, and tokio
+            // should be able to work on other properly async tasks in the mean time. (And because
+            // of this, we have to move `listener` out and back into this task.)
+            let mut listener_for_task = listener.take().expect("have listener");
+            let background = background.clone();
+            let deliver = move || {
+                listener_for_task.received_server_request(next, &background);
+                listener_for_task
+            };
+            let delivered = match &executor {
+                None => runtime
+                    .spawn_blocking(deliver)
+                    .await
+                    .map_err(|e| Some(e.into_panic())),
+                Some(executor) => {
+                    let (done_tx, done_rx) = oneshot::channel();
+                    executor.execute(ChatListenerTask::new(move || {
+                        _ = done_tx.send(panic::catch_unwind(panic::AssertUnwindSafe(deliver)));
+                    }));
+                    match done_rx.await {
+                        Ok(result) => result.map_err(Some),
+                        Err(_) => Err(None),
+                    }
+                }
+            };
+            listener = match delivered {
                 Ok(listener) => Some(listener),
-                Err(e) => {
+                Err(Some(panic)) => {
                     log::error!(
                             "chat listener panicked; no further messages will be read until a new listener is set: {}",
-                            describe_panic(&e.into_panic())
+                            describe_panic(&panic)
                         );
                     break;
                 }
+                Err(None) => {
+                    log::error!(
+                        "chat listener executor dropped a task; no further messages will be read until a new listener is set"
+                    );
+                    break;
+                }
             };
         }
 
@@ -399,6 +572,25 @@ impl ServerMessageAck {
         }
     }
 
+    /// Like [`Self::new`], but counts the ack as outstanding work in `background` until it has been
+    /// sent.
+    ///
+    /// An ack that's never sent counts as unflushed.
+    pub fn tracked(
+        send_ack: chat::server_requests::ResponseEnvelopeSender,
+        background: &BackgroundFlushTracker,
+    ) -> Self {
+        let work = background.start_ack();
+        Self::new(Box::new(move |status| {
+            let send = send_ack(status);
+            Box::pin(async move {
+                let result = send.await;
+                work.finish(result.is_ok());
+                result
+            })
+        }))
+    }
+
     pub fn take(&self) -> Option<chat::server_requests::ResponseEnvelopeSender> {
         self.inner.take()
     }
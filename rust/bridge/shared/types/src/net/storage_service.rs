@@ -0,0 +1,41 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use libsignal_net::storage_service::{
+    FetchedRecords, LocalState, OpaqueStorageRecord, StorageManifest, SyncPlan,
+};
+
+use crate::*;
+
+/// The inputs to a storage service sync, collected one record at a time.
+pub struct StorageSync(std::sync::Mutex<StorageSyncInputs>);
+
+#[derive(Default)]
+pub struct StorageSyncInputs {
+    pub previous: StorageManifest,
+    pub remote: StorageManifest,
+    pub local: LocalState<OpaqueStorageRecord>,
+    pub fetched: FetchedRecords<OpaqueStorageRecord>,
+}
+
+impl StorageSync {
+    pub fn new(previous: StorageManifest, remote: StorageManifest) -> Self {
+        Self(std::sync::Mutex::new(StorageSyncInputs {
+            previous,
+            remote,
+            ..Default::default()
+        }))
+    }
+
+    pub fn lock(&self) -> impl std::ops::DerefMut<Target = StorageSyncInputs> + '_ {
+        self.0.lock().expect("not poisoned")
+    }
+}
+
+bridge_as_handle!(StorageSync);
+
+pub struct StorageSyncPlan(pub SyncPlan<OpaqueStorageRecord>);
+
+bridge_as_handle!(StorageSyncPlan);
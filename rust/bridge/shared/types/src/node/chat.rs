@@ -17,6 +17,11 @@ use signal_neon_futures::call_method;
 use crate::net::chat::{ChatListener, MakeChatListener, ServerMessageAck};
 use crate::node::{ResultTypeInfo, SignalNodeError as _};
 
+/// Delivers [`ChatListener`] callbacks to a JavaScript object.
+///
+/// Every callback is queued on `js_channel`, so callbacks already run on the JavaScript event loop,
+/// one at a time and in order. That is why this listener doesn't provide a
+/// [`ChatListenerExecutor`](crate::net::chat::ChatListenerExecutor).
 #[derive(Clone)]
 pub struct NodeChatListener {
     js_channel: Channel,
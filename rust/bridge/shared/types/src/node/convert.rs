@@ -20,7 +20,9 @@ use crate::io::{InputStream, SyncInputStream};
 use crate::message_backup::MessageBackupValidationOutcome;
 use crate::net::chat::{MakeChatListener, ResponseAndDebugInfo};
 use crate::node::chat::NodeMakeChatListener;
-use crate::support::{extend_lifetime, Array, AsType, FixedLengthBincodeSerializable, Serialized};
+use crate::support::{
+    extend_lifetime, Array, AsType, Deadline, FixedLengthBincodeSerializable, Serialized,
+};
 
 /// Converts arguments from their JavaScript form to their Rust form.
 ///
@@ -283,6 +285,22 @@ impl SimpleArgTypeInfo for crate::zkgroup::Timestamp {
     }
 }
 
+/// Converts a timeout in milliseconds, up to [`Number.MAX_SAFE_INTEGER`][]; zero means "no
+/// deadline".
+///
+/// [`Number.MAX_SAFE_INTEGER`]: https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Global_Objects/Number/MAX_SAFE_INTEGER
+impl SimpleArgTypeInfo for Deadline {
+    type ArgType = JsNumber;
+    #[allow(clippy::cast_possible_truncation)]
+    fn convert_from(cx: &mut FunctionContext, foreign: Handle<Self::ArgType>) -> NeonResult<Self> {
+        let value = foreign.value(cx);
+        if !can_convert_js_number_to_int(value, 0.0..=MAX_SAFE_JS_INTEGER) {
+            return cx.throw_range_error(format!("cannot convert {} to Deadline (u64)", value));
+        }
+        Ok(Self::from_timeout_millis(value as u64))
+    }
+}
+
 impl SimpleArgTypeInfo for u64 {
     type ArgType = JsBigInt;
     fn convert_from(cx: &mut FunctionContext, foreign: Handle<Self::ArgType>) -> NeonResult<Self> {
@@ -650,6 +668,7 @@ bridge_trait!(SenderKeyStore);
 bridge_trait!(SessionStore);
 bridge_trait!(SignedPreKeyStore);
 bridge_trait!(KyberPreKeyStore);
+bridge_trait!(ReplayCache);
 bridge_trait!(InputStream);
 
 impl<'storage, 'context: 'storage> ArgTypeInfo<'storage, 'context>
@@ -898,13 +917,47 @@ impl<'a> ResultTypeInfo<'a> for MessageBackupValidationOutcome {
         let Self {
             error_message,
             found_unknown_fields,
+            findings,
         } = self;
         let error_message = error_message.convert_into(cx)?;
         let unknown_field_messages = found_unknown_fields.as_slice().convert_into(cx)?;
+        let findings = make_array(cx, findings.iter())?;
 
         let obj = JsObject::new(cx);
         obj.set(cx, "errorMessage", error_message)?;
         obj.set(cx, "unknownFieldMessages", unknown_field_messages)?;
+        obj.set(cx, "findings", findings)?;
+
+        Ok(obj)
+    }
+}
+
+impl<'a> ResultTypeInfo<'a> for &libsignal_message_backup::finding::ValidationFinding {
+    type ResultType = JsObject;
+
+    fn convert_into(self, cx: &mut impl Context<'a>) -> JsResult<'a, Self::ResultType> {
+        let libsignal_message_backup::finding::ValidationFinding {
+            frame_index,
+            field_path,
+            kind,
+            severity,
+            message,
+        } = self;
+        let frame_index: Handle<JsValue> = match frame_index {
+            Some(index) => cx.number(*index as f64).upcast(),
+            None => cx.null().upcast(),
+        };
+        let field_path = cx.string(field_path);
+        let kind = cx.number(u8::from(*kind));
+        let severity = cx.number(u8::from(*severity));
+        let message = cx.string(message);
+
+        let obj = JsObject::new(cx);
+        obj.set(cx, "frameIndex", frame_index)?;
+        obj.set(cx, "fieldPath", field_path)?;
+        obj.set(cx, "kind", kind)?;
+        obj.set(cx, "severity", severity)?;
+        obj.set(cx, "message", message)?;
 
         Ok(obj)
     }
@@ -1017,6 +1070,31 @@ impl<'a> ResultTypeInfo<'a> for ResponseAndDebugInfo {
     }
 }
 
+impl<'a> ResultTypeInfo<'a> for libsignal_net::chat::background::BackgroundFlushReport {
+    type ResultType = JsObject;
+    fn convert_into(self, cx: &mut impl Context<'a>) -> JsResult<'a, Self::ResultType> {
+        let Self {
+            acks_flushed,
+            acks_unflushed,
+            requests_flushed,
+            requests_unflushed,
+        } = self;
+        let obj = JsObject::new(cx);
+
+        let acks_flushed = cx.number(acks_flushed);
+        let acks_unflushed = cx.number(acks_unflushed);
+        let requests_flushed = cx.number(requests_flushed);
+        let requests_unflushed = cx.number(requests_unflushed);
+
+        obj.set(cx, "acksFlushed", acks_flushed)?;
+        obj.set(cx, "acksUnflushed", acks_unflushed)?;
+        obj.set(cx, "requestsFlushed", requests_flushed)?;
+        obj.set(cx, "requestsUnflushed", requests_unflushed)?;
+
+        Ok(obj)
+    }
+}
+
 impl<'a> ResultTypeInfo<'a> for libsignal_net::cdsi::LookupResponse {
     type ResultType = JsObject;
     fn convert_into(self, cx: &mut impl Context<'a>) -> JsResult<'a, Self::ResultType> {
@@ -5,7 +5,8 @@
 
 use std::fmt;
 
-use libsignal_net::chat::ChatServiceError;
+use libsignal_net::chat::{ChatServiceError, DisconnectInfo};
+use libsignal_net::rate_limit::{RateLimit, RateLimitScope};
 use libsignal_net::svr3::Error as Svr3Error;
 use signal_media::sanitize::mp4::{Error as Mp4Error, ParseError as Mp4ParseError};
 use signal_media::sanitize::webp::{Error as WebpError, ParseError as WebpParseError};
@@ -158,7 +159,8 @@ impl SignalNodeError for SignalProtocolError {
     ) -> Handle<'a, JsError> {
         let message = self.to_string();
         match self {
-            SignalProtocolError::DuplicatedMessage(..) => new_js_error(
+            SignalProtocolError::DuplicatedMessage(..)
+            | SignalProtocolError::ReplayedMessage(_) => new_js_error(
                 cx,
                 module,
                 Some("DuplicatedMessage"),
@@ -303,7 +305,8 @@ impl SignalNodeError for usernames::UsernameLinkError {
             Self::UsernameLinkDataTooShort
             | Self::HmacMismatch
             | Self::BadCiphertext
-            | Self::InvalidDecryptedDataStructure => Some("InvalidUsernameLinkEncryptedData"),
+            | Self::InvalidDecryptedDataStructure
+            | Self::InvalidLinkUrl => Some("InvalidUsernameLinkEncryptedData"),
         };
         let message = self.to_string();
         new_js_error(
@@ -411,14 +414,51 @@ impl SignalNodeError for libsignal_net::chat::ChatServiceError {
         module: Handle<'a, JsObject>,
         operation_name: &str,
     ) -> Handle<'a, JsError> {
+        if let Some(info) = self.disconnect_info() {
+            let name = match &self {
+                ChatServiceError::ServiceInactive(_) => "ChatServiceInactive",
+                _ => IO_ERROR,
+            };
+            let message = self.to_string();
+            return new_js_error(
+                cx,
+                module,
+                Some(name),
+                &message,
+                operation_name,
+                move |cx: &mut C| {
+                    let DisconnectInfo {
+                        close_code,
+                        reason,
+                        alerts,
+                    } = info;
+                    let props = cx.empty_object();
+                    let disconnect_info = cx.empty_object();
+                    if let Some(close_code) = close_code {
+                        let close_code = cx.number(close_code);
+                        disconnect_info.set(cx, "closeCode", close_code)?;
+                    }
+                    if let Some(reason) = reason {
+                        let reason = cx.string(reason);
+                        disconnect_info.set(cx, "reason", reason)?;
+                    }
+                    let alerts = alerts.into_boxed_slice().convert_into(cx)?;
+                    disconnect_info.set(cx, "alerts", alerts)?;
+                    props.set(cx, "disconnectInfo", disconnect_info)?;
+                    Ok(props.upcast())
+                },
+            );
+        }
+
         let (name, properties) = match self {
-            ChatServiceError::ServiceInactive => (Some("ChatServiceInactive"), None),
+            ChatServiceError::ServiceInactive(_) => (Some("ChatServiceInactive"), None),
             ChatServiceError::AppExpired => (Some("AppExpired"), None),
             ChatServiceError::DeviceDeregistered => (Some("DeviceDelinked"), None),
-            ChatServiceError::RetryLater {
-                retry_after_seconds,
-            } => rate_limited_error(retry_after_seconds),
+            ChatServiceError::RetryLater(ref rate_limit) => rate_limited_error(rate_limit.clone()),
+            ChatServiceError::RequestUnauthorized => (Some("RequestUnauthorized"), None),
+            ChatServiceError::NotFound => (Some("NotFound"), None),
             ChatServiceError::WebSocket(_)
+            | ChatServiceError::ChannelClosed(_)
             | ChatServiceError::UnexpectedFrameReceived
             | ChatServiceError::ServerRequestMissingId
             | ChatServiceError::FailedToPassMessageToIncomingChannel
@@ -427,7 +467,7 @@ impl SignalNodeError for libsignal_net::chat::ChatServiceError {
             | ChatServiceError::Timeout
             | ChatServiceError::TimeoutEstablishingConnection { attempts: _ }
             | ChatServiceError::AllConnectionRoutesFailed { attempts: _ }
-            | ChatServiceError::ServiceUnavailable
+            | ChatServiceError::ServiceUnavailable(_)
             | ChatServiceError::ServiceIntentionallyDisconnected =>
             // TODO: Distinguish retryable errors from proper failures?
             {
@@ -474,9 +514,7 @@ impl SignalNodeError for libsignal_net::cdsi::LookupError {
         operation_name: &str,
     ) -> Handle<'a, JsError> {
         let (name, make_extra_props) = match self {
-            Self::RateLimited {
-                retry_after_seconds,
-            } => rate_limited_error(retry_after_seconds),
+            Self::RateLimited(ref rate_limit) => rate_limited_error(rate_limit.clone()),
             Self::AttestationError(e) => return e.into_throwable(cx, module, operation_name),
             Self::InvalidArgument { server_reason: _ } => (None, None),
             Self::InvalidToken => (Some("CdsiInvalidToken"), None),
@@ -502,7 +540,7 @@ impl SignalNodeError for libsignal_net::cdsi::LookupError {
 }
 
 fn rate_limited_error<'a, C: Context<'a>>(
-    retry_after_seconds: u32,
+    rate_limit: RateLimit,
 ) -> (
     Option<&'a str>,
     Option<impl Fn(&mut C) -> Result<Handle<'a, JsValue>, neon::result::Throw>>,
@@ -510,9 +548,27 @@ fn rate_limited_error<'a, C: Context<'a>>(
     (
         Some(RATE_LIMITED_ERROR),
         Some(move |cx: &mut C| {
+            let RateLimit {
+                retry_after_seconds,
+                scope,
+                reason,
+            } = &rate_limit;
             let props = cx.empty_object();
             let retry_after = retry_after_seconds.convert_into(cx)?;
             props.set(cx, "retryAfterSecs", retry_after)?;
+            let scope = match scope {
+                RateLimitScope::Unspecified => None,
+                RateLimitScope::PerNumber => Some("per-number"),
+                RateLimitScope::PerAccount => Some("per-account"),
+            };
+            if let Some(scope) = scope {
+                let scope = cx.string(scope);
+                props.set(cx, "scope", scope)?;
+            }
+            if let Some(reason) = reason {
+                let reason = cx.string(reason);
+                props.set(cx, "reason", reason)?;
+            }
             Ok(props.upcast())
         }),
     )
@@ -543,7 +599,7 @@ impl SignalNodeError for libsignal_net::svr3::Error {
                 }),
             ),
             Svr3Error::DataMissing => (Some(SVR3_DATA_MISSING), None),
-            Svr3Error::Protocol(_) => (None, None),
+            Svr3Error::Protocol(_) | Svr3Error::InvalidShareDistribution(_) => (None, None),
             Svr3Error::RotationMachineTooManySteps => (Some(SVR3_ROTATION_MACHINE_STEPS), None),
         };
 
@@ -578,6 +634,39 @@ impl SignalNodeError for CancellationError {
     }
 }
 
+impl SignalNodeError for crate::support::DeadlineExceeded {
+    fn into_throwable<'a, C: Context<'a>>(
+        self,
+        cx: &mut C,
+        module: Handle<'a, JsObject>,
+        operation_name: &str,
+    ) -> Handle<'a, JsError> {
+        let message = self.to_string();
+        new_js_error(
+            cx,
+            module,
+            Some("DeadlineExceeded"),
+            &message,
+            operation_name,
+            no_extra_properties,
+        )
+    }
+}
+
+impl<E: SignalNodeError> SignalNodeError for crate::support::DeadlineOr<E> {
+    fn into_throwable<'a, C: Context<'a>>(
+        self,
+        cx: &mut C,
+        module: Handle<'a, JsObject>,
+        operation_name: &str,
+    ) -> Handle<'a, JsError> {
+        match self {
+            Self::DeadlineExceeded(e) => e.into_throwable(cx, module, operation_name),
+            Self::Other(e) => e.into_throwable(cx, module, operation_name),
+        }
+    }
+}
+
 impl SignalNodeError for libsignal_message_backup::ReadError {
     fn into_throwable<'a, C: Context<'a>>(
         self,
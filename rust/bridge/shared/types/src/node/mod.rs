@@ -46,6 +46,8 @@ mod storage;
 
 pub use storage::*;
 
+pub use crate::support::metrics::CallTimer;
+
 /// A function pointer referring to a Neon-based Node entry point.
 #[doc(hidden)]
 pub type JsFn = for<'a> fn(FunctionContext<'a>) -> JsResult<'a, JsValue>;
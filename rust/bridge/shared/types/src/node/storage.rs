@@ -738,6 +738,80 @@ impl NodeSenderKeyStore {
         })
         .await
     }
+
+    async fn do_get_sender_key_distribution(
+        &self,
+        sender: ProtocolAddress,
+        distribution_id: Uuid,
+    ) -> Result<Option<SenderKeyDistributionRecord>, String> {
+        let store_object_shared = self.store_object.clone();
+        JsFuture::get_promise(&self.js_channel, move |cx| {
+            let store_object = store_object_shared.to_inner(cx);
+            let sender: Handle<JsValue> = sender.convert_into(cx)?;
+            let distribution_id: Handle<JsValue> = distribution_id.convert_into(cx)?.upcast();
+            let result = call_method(
+                cx,
+                store_object,
+                "_getSenderKeyDistribution",
+                [sender, distribution_id],
+            )?;
+            let result = result.downcast_or_throw(cx)?;
+            store_object_shared.finalize(cx);
+            Ok(result)
+        })
+        .then(|cx, result| match result {
+            Ok(value) => match value.downcast::<DefaultJsBox<SenderKeyDistributionRecord>, _>(cx) {
+                Ok(obj) => Ok(Some((***obj).clone())),
+                Err(_) => {
+                    if value.is_a::<JsNull, _>(cx) {
+                        Ok(None)
+                    } else {
+                        Err("result must be an object".to_owned())
+                    }
+                }
+            },
+            Err(error) => Err(error
+                .to_string(cx)
+                .expect("can convert to string")
+                .value(cx)),
+        })
+        .await
+    }
+
+    async fn do_save_sender_key_distribution(
+        &self,
+        sender: ProtocolAddress,
+        distribution_id: Uuid,
+        record: SenderKeyDistributionRecord,
+    ) -> Result<(), String> {
+        let store_object_shared = self.store_object.clone();
+        JsFuture::get_promise(&self.js_channel, move |cx| {
+            let store_object = store_object_shared.to_inner(cx);
+            let sender: Handle<JsValue> = sender.convert_into(cx)?;
+            let distribution_id: Handle<JsValue> = distribution_id.convert_into(cx)?.upcast();
+            let record: Handle<JsValue> = record.convert_into(cx)?;
+            let result = call_method(
+                cx,
+                store_object,
+                "_saveSenderKeyDistribution",
+                [sender, distribution_id, record],
+            )?
+            .downcast_or_throw(cx)?;
+            store_object_shared.finalize(cx);
+            Ok(result)
+        })
+        .then(|cx, result| match result {
+            Ok(value) => match value.downcast::<JsUndefined, _>(cx) {
+                Ok(_) => Ok(()),
+                Err(_) => Err("unexpected result from _saveSenderKeyDistribution".into()),
+            },
+            Err(error) => Err(error
+                .to_string(cx)
+                .expect("can convert to string")
+                .value(cx)),
+        })
+        .await
+    }
 }
 
 impl Finalize for NodeSenderKeyStore {
@@ -768,4 +842,140 @@ impl SenderKeyStore for NodeSenderKeyStore {
             .await
             .map_err(|s| js_error_to_rust("saveSenderKey", s))
     }
+
+    async fn load_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+    ) -> Result<Option<SenderKeyDistributionRecord>, SignalProtocolError> {
+        self.do_get_sender_key_distribution(sender.clone(), distribution_id)
+            .await
+            .map_err(|s| js_error_to_rust("getSenderKeyDistribution", s))
+    }
+
+    async fn store_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+        record: &SenderKeyDistributionRecord,
+    ) -> Result<(), SignalProtocolError> {
+        self.do_save_sender_key_distribution(sender.clone(), distribution_id, record.clone())
+            .await
+            .map_err(|s| js_error_to_rust("saveSenderKeyDistribution", s))
+    }
+}
+
+pub struct NodeReplayCache {
+    js_channel: Channel,
+    store_object: Arc<Root<JsObject>>,
+}
+
+impl NodeReplayCache {
+    pub(crate) fn new(cx: &mut FunctionContext, store: Handle<JsObject>) -> Self {
+        Self {
+            js_channel: cx.channel(),
+            store_object: Arc::new(store.root(cx)),
+        }
+    }
+
+    async fn do_contains(
+        &self,
+        address: ProtocolAddress,
+        sender_ratchet_key: PublicKey,
+        counter: u32,
+    ) -> Result<bool, String> {
+        let store_object_shared = self.store_object.clone();
+        JsFuture::get_promise(&self.js_channel, move |cx| {
+            let store_object = store_object_shared.to_inner(cx);
+            let address: Handle<JsValue> = address.convert_into(cx)?;
+            let sender_ratchet_key: Handle<JsValue> = sender_ratchet_key.convert_into(cx)?;
+            let counter: Handle<JsNumber> = counter.convert_into(cx)?;
+            let result = call_method(
+                cx,
+                store_object,
+                "_contains",
+                [address, sender_ratchet_key, counter.upcast()],
+            )?
+            .downcast_or_throw(cx)?;
+            store_object_shared.finalize(cx);
+            Ok(result)
+        })
+        .then(|cx, result| match result {
+            Ok(value) => match value.downcast::<JsBoolean, _>(cx) {
+                Ok(b) => Ok(b.value(cx)),
+                Err(_) => Err("unexpected result from _contains".into()),
+            },
+            Err(error) => Err(error
+                .to_string(cx)
+                .expect("can convert to string")
+                .value(cx)),
+        })
+        .await
+    }
+
+    async fn do_insert(
+        &self,
+        address: ProtocolAddress,
+        sender_ratchet_key: PublicKey,
+        counter: u32,
+    ) -> Result<(), String> {
+        let store_object_shared = self.store_object.clone();
+        JsFuture::get_promise(&self.js_channel, move |cx| {
+            let store_object = store_object_shared.to_inner(cx);
+            let address: Handle<JsValue> = address.convert_into(cx)?;
+            let sender_ratchet_key: Handle<JsValue> = sender_ratchet_key.convert_into(cx)?;
+            let counter: Handle<JsNumber> = counter.convert_into(cx)?;
+            let result = call_method(
+                cx,
+                store_object,
+                "_insert",
+                [address, sender_ratchet_key, counter.upcast()],
+            )?
+            .downcast_or_throw(cx)?;
+            store_object_shared.finalize(cx);
+            Ok(result)
+        })
+        .then(|cx, result| match result {
+            Ok(value) => match value.downcast::<JsUndefined, _>(cx) {
+                Ok(_) => Ok(()),
+                Err(_) => Err("unexpected result from _insert".into()),
+            },
+            Err(error) => Err(error
+                .to_string(cx)
+                .expect("can convert to string")
+                .value(cx)),
+        })
+        .await
+    }
+}
+
+impl Finalize for NodeReplayCache {
+    fn finalize<'a, C: Context<'a>>(self, cx: &mut C) {
+        self.store_object.finalize(cx)
+    }
+}
+
+#[async_trait(?Send)]
+impl ReplayCache for NodeReplayCache {
+    async fn contains(
+        &self,
+        address: &ProtocolAddress,
+        sender_ratchet_key: &PublicKey,
+        counter: u32,
+    ) -> Result<bool, SignalProtocolError> {
+        self.do_contains(address.clone(), *sender_ratchet_key, counter)
+            .await
+            .map_err(|s| js_error_to_rust("contains", s))
+    }
+
+    async fn insert(
+        &mut self,
+        address: &ProtocolAddress,
+        sender_ratchet_key: &PublicKey,
+        counter: u32,
+    ) -> Result<(), SignalProtocolError> {
+        self.do_insert(address.clone(), *sender_ratchet_key, counter)
+            .await
+            .map_err(|s| js_error_to_rust("insert", s))
+    }
 }
@@ -30,6 +30,7 @@ bridge_as_handle!(ProtocolAddress, ffi = address);
 bridge_as_handle!(PublicKey, ffi = publickey, jni = ECPublicKey);
 bridge_as_handle!(SenderCertificate);
 bridge_as_handle!(SenderKeyDistributionMessage);
+bridge_as_handle!(SenderKeyDistributionRecord);
 bridge_as_handle!(SenderKeyMessage);
 bridge_as_handle!(SenderKeyRecord);
 bridge_as_handle!(ServerCertificate);
@@ -38,7 +39,7 @@ bridge_as_handle!(SignalMessage, ffi = message);
 bridge_as_handle!(SignedPreKeyRecord);
 bridge_as_handle!(KyberPreKeyRecord);
 bridge_as_handle!(UnidentifiedSenderMessageContent);
-bridge_as_handle!(SealedSenderDecryptionResult, ffi = false, jni = false);
+bridge_as_handle!(SealedSenderDecryptionResult);
 bridge_as_handle!(KyberKeyPair);
 bridge_as_handle!(KyberPublicKey);
 bridge_as_handle!(KyberSecretKey);
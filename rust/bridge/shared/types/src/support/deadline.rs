@@ -0,0 +1,230 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::pin;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+use futures_util::FutureExt as _;
+
+/// An optional limit on how long a bridged operation may keep working.
+///
+/// Crosses the bridge as a timeout in milliseconds, measured from the start of the call; zero means
+/// "no limit". Deadlines are cooperative: an operation calls [`Deadline::check`] at points where it
+/// can stop cleanly, so it may run slightly past the deadline before giving up.
+///
+/// Bridge functions don't usually handle their `Deadline` argument themselves. The bridge macros
+/// run the body of any function that takes one inside [`Deadline::scope`] or
+/// [`Deadline::scope_future`], which fail the call up front if the deadline has already passed and
+/// make it the [current deadline](Deadline::check_current) for the stream adapters in
+/// [`crate::io`]. Only work that neither reads input nor yields to the executor, like a long loop
+/// over group members, needs to check the argument explicitly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Deadline(Option<Instant>);
+
+thread_local! {
+    static CURRENT: Cell<Deadline> = const { Cell::new(Deadline::NONE) };
+}
+
+/// Restores the previous current deadline when dropped.
+struct CurrentDeadlineGuard(Deadline);
+
+impl CurrentDeadlineGuard {
+    fn enter(deadline: Deadline) -> Self {
+        Self(CURRENT.replace(deadline))
+    }
+}
+
+impl Drop for CurrentDeadlineGuard {
+    fn drop(&mut self) {
+        CURRENT.set(self.0);
+    }
+}
+
+/// A result that can report that its operation's [`Deadline`] passed.
+pub trait DeadlineResult {
+    fn deadline_exceeded() -> Self;
+}
+
+impl<T, E: From<DeadlineExceeded>> DeadlineResult for Result<T, E> {
+    fn deadline_exceeded() -> Self {
+        Err(DeadlineExceeded.into())
+    }
+}
+
+impl Deadline {
+    pub const NONE: Self = Self(None);
+
+    pub fn from_timeout_millis(millis: u64) -> Self {
+        if millis == 0 {
+            return Self::NONE;
+        }
+        // A timeout too far in the future to represent is as good as no timeout at all.
+        Self(Instant::now().checked_add(Duration::from_millis(millis)))
+    }
+
+    pub fn check(&self) -> Result<(), DeadlineExceeded> {
+        match self.0 {
+            Some(deadline) if Instant::now() >= deadline => Err(DeadlineExceeded),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the deadline of the innermost enclosing [`Deadline::scope`] or
+    /// [`Deadline::scope_future`] on this thread, if any.
+    pub fn check_current() -> Result<(), DeadlineExceeded> {
+        CURRENT.get().check()
+    }
+
+    /// Runs `f` with `self` as the current deadline, failing without running it if the deadline
+    /// has already passed.
+    pub fn scope<R: DeadlineResult>(self, f: impl FnOnce() -> R) -> R {
+        if self.check().is_err() {
+            return R::deadline_exceeded();
+        }
+        let _guard = CurrentDeadlineGuard::enter(self);
+        f()
+    }
+
+    /// Runs `future` with `self` as the current deadline whenever it is polled, failing as soon as
+    /// the deadline passes.
+    ///
+    /// The deadline is checked before every poll. When running on a tokio runtime, a timer also
+    /// wakes the task at the deadline, so an operation stuck waiting on the app still fails on
+    /// time. (The runtime must have its time driver enabled.) Without a runtime, an operation only
+    /// notices the deadline the next time it is polled.
+    pub async fn scope_future<R, F>(self, future: F) -> R
+    where
+        R: DeadlineResult,
+        F: Future<Output = R>,
+    {
+        let mut future = pin!(future);
+        let mut timer = match (self.0, tokio::runtime::Handle::try_current()) {
+            (Some(deadline), Ok(_)) => Some(Box::pin(tokio::time::sleep_until(deadline.into()))),
+            (None, _) | (_, Err(_)) => None,
+        };
+        std::future::poll_fn(|cx| {
+            if self.check().is_err() {
+                return Poll::Ready(R::deadline_exceeded());
+            }
+            let poll = {
+                let _guard = CurrentDeadlineGuard::enter(self);
+                future.as_mut().poll(cx)
+            };
+            match (poll, &mut timer) {
+                (Poll::Ready(output), _) => Poll::Ready(output),
+                (Poll::Pending, None) => Poll::Pending,
+                (Poll::Pending, Some(timer)) => {
+                    timer.poll_unpin(cx).map(|()| R::deadline_exceeded())
+                }
+            }
+        })
+        .await
+    }
+}
+
+/// The operation was abandoned because its [`Deadline`] passed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("operation did not complete before its deadline")]
+pub struct DeadlineExceeded;
+
+impl DeadlineExceeded {
+    /// Wraps `self` in an [`std::io::Error`], for operations that check their deadline while
+    /// reading.
+    ///
+    /// Recover it with [`DeadlineOr::from_io`].
+    pub fn into_io_error(self) -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::TimedOut, self)
+    }
+}
+
+/// The error type for bridged operations that take a [`Deadline`].
+#[derive(Debug, thiserror::Error)]
+pub enum DeadlineOr<E> {
+    #[error(transparent)]
+    DeadlineExceeded(#[from] DeadlineExceeded),
+    #[error(transparent)]
+    Other(E),
+}
+
+impl DeadlineOr<std::io::Error> {
+    /// Separates deadline failures produced by [`DeadlineExceeded::into_io_error`] from other I/O
+    /// errors.
+    pub fn from_io(error: std::io::Error) -> Self {
+        if error
+            .get_ref()
+            .is_some_and(|inner| inner.is::<DeadlineExceeded>())
+        {
+            return Self::DeadlineExceeded(DeadlineExceeded);
+        }
+        Self::Other(error)
+    }
+}
+
+impl From<std::io::Error> for DeadlineOr<std::io::Error> {
+    fn from(error: std::io::Error) -> Self {
+        Self::from_io(error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn zero_means_no_deadline() {
+        assert_eq!(Deadline::from_timeout_millis(0), Deadline::NONE);
+        assert_eq!(Deadline::NONE.check(), Ok(()));
+    }
+
+    #[test]
+    fn check_fails_once_deadline_passes() {
+        let deadline = Deadline::from_timeout_millis(1);
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(deadline.check(), Err(DeadlineExceeded));
+
+        assert_eq!(Deadline::from_timeout_millis(60_000).check(), Ok(()));
+    }
+
+    #[test]
+    fn io_round_trip() {
+        assert!(matches!(
+            DeadlineOr::from_io(DeadlineExceeded.into_io_error()),
+            DeadlineOr::DeadlineExceeded(DeadlineExceeded)
+        ));
+        assert!(matches!(
+            DeadlineOr::from_io(std::io::ErrorKind::TimedOut.into()),
+            DeadlineOr::Other(_)
+        ));
+    }
+
+    #[test]
+    fn scope_sets_current_deadline() {
+        let deadline = Deadline::from_timeout_millis(1);
+        let result: Result<(), DeadlineExceeded> = deadline.scope(|| {
+            assert_eq!(Deadline::check_current(), Ok(()));
+            std::thread::sleep(Duration::from_millis(5));
+            Deadline::check_current()
+        });
+        assert_eq!(result, Err(DeadlineExceeded));
+        assert_eq!(Deadline::check_current(), Ok(()));
+
+        let result: Result<(), DeadlineExceeded> = deadline.scope(|| panic!("should not run"));
+        assert_eq!(result, Err(DeadlineExceeded));
+    }
+
+    #[test]
+    fn scope_future_fails_while_pending() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .expect("valid runtime");
+        let result: Result<(), DeadlineExceeded> =
+            runtime.block_on(Deadline::from_timeout_millis(5).scope_future(std::future::pending()));
+        assert_eq!(result, Err(DeadlineExceeded));
+    }
+}
@@ -0,0 +1,141 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Debug tracking of boxed handles, enabled by the `handle-poisoning` feature.
+//!
+//! Every handle given to the app is recorded along with its type and the bridge function that
+//! created it. Destroying a handle drops its value but never frees its allocation, so the address
+//! can't be handed out again. Any later use of the handle through the bridge then produces a
+//! [`UseAfterFree`] error instead of undefined behavior.
+//!
+//! This leaks memory for every destroyed handle, so it's only meant for debugging app lifecycle
+//! bugs.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::Location;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use once_cell::sync::Lazy;
+
+struct HandleRecord {
+    type_name: &'static str,
+    created_at: &'static Location<'static>,
+    destroyed: bool,
+}
+
+static HANDLES: Lazy<Mutex<HashMap<usize, HandleRecord>>> = Lazy::new(Default::default);
+
+fn handles() -> MutexGuard<'static, HashMap<usize, HandleRecord>> {
+    // The map is never left in an inconsistent state, so a panic elsewhere doesn't matter.
+    HANDLES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A handle was used (or destroyed) after it had already been destroyed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UseAfterFree {
+    pub type_name: &'static str,
+    pub created_at: &'static Location<'static>,
+}
+
+impl From<&HandleRecord> for UseAfterFree {
+    fn from(record: &HandleRecord) -> Self {
+        Self {
+            type_name: record.type_name,
+            created_at: record.created_at,
+        }
+    }
+}
+
+impl fmt::Display for UseAfterFree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "use-after-free of handle type {} created at {}",
+            self.type_name, self.created_at
+        )
+    }
+}
+
+impl std::error::Error for UseAfterFree {}
+
+/// Records a handle that is about to be given to the app.
+#[track_caller]
+pub fn register<T>(handle: *const T) {
+    handles().insert(
+        handle as usize,
+        HandleRecord {
+            type_name: std::any::type_name::<T>(),
+            created_at: Location::caller(),
+            destroyed: false,
+        },
+    );
+}
+
+/// Checks that `handle` has not been destroyed.
+///
+/// Handles that were never registered are assumed to be valid.
+pub fn check<T>(handle: *const T) -> Result<(), UseAfterFree> {
+    match handles().get(&(handle as usize)) {
+        Some(record) if record.destroyed => Err(record.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Drops the value behind `handle`, keeping its allocation if the handle was registered.
+///
+/// Handles that were never registered are freed normally.
+///
+/// # Safety
+///
+/// `handle` must be non-null and have come from [`Box::into_raw`]. It must not be dereferenced
+/// afterwards unless [`check`] succeeds.
+pub unsafe fn destroy<T>(handle: *mut T) -> Result<(), UseAfterFree> {
+    let mut handles = handles();
+    match handles.get_mut(&(handle as usize)) {
+        Some(record) if record.destroyed => Err(UseAfterFree::from(&*record)),
+        Some(record) => {
+            record.destroyed = true;
+            drop(handles);
+            std::ptr::drop_in_place(handle);
+            Ok(())
+        }
+        None => {
+            drop(handles);
+            drop(Box::from_raw(handle));
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn destroyed_handles_are_reported() {
+        let handle = Box::into_raw(Box::new(String::from("payload")));
+        register(handle);
+        check(handle).expect("live");
+
+        unsafe { destroy(handle) }.expect("first destroy");
+
+        let error = check(handle).expect_err("destroyed");
+        assert_eq!(error.type_name, "alloc::string::String");
+        assert_eq!(error.created_at.file(), file!());
+        assert!(error
+            .to_string()
+            .starts_with("use-after-free of handle type alloc::string::String created at "));
+
+        assert_eq!(unsafe { destroy(handle) }, Err(error));
+    }
+
+    #[test]
+    fn unregistered_handles_are_freed() {
+        let handle = Box::into_raw(Box::new(5u64));
+        check(handle).expect("unknown handles are allowed");
+        unsafe { destroy(handle) }.expect("freed normally");
+    }
+}
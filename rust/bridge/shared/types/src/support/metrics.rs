@@ -0,0 +1,171 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Opt-in call counts and timings for bridged functions.
+//!
+//! Every `bridge_fn` wrapper starts a [`CallTimer`] on entry. While recording is disabled (the
+//! default) that costs a single atomic load; once [`set_enabled`] turns it on, each call's
+//! duration and outcome are added to a process-wide registry that [`snapshot`] reads back.
+//!
+//! Durations cover the whole synchronous native call, including argument and result conversion.
+//! For async and `bridge_io` functions that is only the time taken to start the operation, not
+//! the time until it completes.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static REGISTRY: Mutex<BTreeMap<&'static str, CallStats>> = Mutex::new(BTreeMap::new());
+
+/// Accumulated statistics for one bridged function.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CallStats {
+    pub calls: u64,
+    /// Calls that reported an error (or panicked) back to the app.
+    pub errors: u64,
+    pub total_duration: Duration,
+    pub max_duration: Duration,
+}
+
+impl CallStats {
+    fn record(&mut self, duration: Duration, failed: bool) {
+        self.calls += 1;
+        self.errors += u64::from(failed);
+        self.total_duration = self.total_duration.saturating_add(duration);
+        self.max_duration = self.max_duration.max(duration);
+    }
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Returns the statistics gathered so far, sorted by function name.
+///
+/// Functions that haven't been called while recording was enabled are omitted.
+pub fn snapshot() -> Vec<(&'static str, CallStats)> {
+    registry()
+        .iter()
+        .map(|(name, stats)| (*name, *stats))
+        .collect()
+}
+
+/// Like [`snapshot`], but encoded as a JSON array for handing to app code.
+///
+/// Each element has the form
+/// `{"name": string, "calls": number, "errors": number, "totalMicros": number, "maxMicros": number}`.
+pub fn snapshot_json() -> String {
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Entry {
+        name: &'static str,
+        calls: u64,
+        errors: u64,
+        total_micros: u128,
+        max_micros: u128,
+    }
+
+    let entries = snapshot()
+        .into_iter()
+        .map(|(name, stats)| Entry {
+            name,
+            calls: stats.calls,
+            errors: stats.errors,
+            total_micros: stats.total_duration.as_micros(),
+            max_micros: stats.max_duration.as_micros(),
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_string(&entries).expect("can serialize")
+}
+
+/// Discards all statistics gathered so far, without changing whether recording is enabled.
+pub fn reset() {
+    registry().clear();
+}
+
+fn registry() -> std::sync::MutexGuard<'static, BTreeMap<&'static str, CallStats>> {
+    // The registry is only ever updated with simple arithmetic, so a panic elsewhere while the
+    // lock was held can't have left it inconsistent.
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Measures one call to a bridged function.
+///
+/// The call is recorded when the timer is dropped. It counts as failed unless
+/// [`CallTimer::finish`] says otherwise, so that panics are counted as errors.
+#[must_use]
+pub struct CallTimer {
+    name: &'static str,
+    start: Instant,
+    failed: bool,
+}
+
+impl CallTimer {
+    /// Starts timing a call to `name`, or returns `None` if recording is disabled.
+    #[inline]
+    pub fn start(name: &'static str) -> Option<Self> {
+        is_enabled().then(|| Self {
+            name,
+            start: Instant::now(),
+            failed: true,
+        })
+    }
+
+    pub fn finish(mut self, failed: bool) {
+        self.failed = failed;
+    }
+}
+
+impl Drop for CallTimer {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        registry()
+            .entry(self.name)
+            .or_default()
+            .record(duration, self.failed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // The registry is global, so everything is checked in one test to avoid interference.
+    #[test]
+    fn records_only_while_enabled() {
+        const NAME: &str = "metrics_test_fn";
+        let stats_for_test = || {
+            snapshot()
+                .into_iter()
+                .find(|(name, _)| *name == NAME)
+                .map(|(_, stats)| stats)
+        };
+
+        set_enabled(false);
+        assert!(CallTimer::start(NAME).is_none());
+        assert_eq!(stats_for_test(), None);
+
+        set_enabled(true);
+        CallTimer::start(NAME).expect("enabled").finish(false);
+        CallTimer::start(NAME).expect("enabled").finish(true);
+        drop(CallTimer::start(NAME).expect("enabled"));
+        set_enabled(false);
+
+        let stats = stats_for_test().expect("recorded");
+        assert_eq!(stats.calls, 3);
+        assert_eq!(stats.errors, 2);
+        assert!(stats.max_duration <= stats.total_duration);
+        assert!(snapshot_json().contains(r#"{"name":"metrics_test_fn","calls":3,"errors":2,"#));
+
+        reset();
+        assert_eq!(stats_for_test(), None);
+    }
+}
@@ -7,15 +7,21 @@ use std::future::Future;
 use std::num::NonZeroU64;
 
 mod as_type;
+mod deadline;
+pub mod metrics;
 mod sequences;
 mod serialized;
 pub use as_type::*;
+pub use deadline::*;
 pub use sequences::*;
 pub use serialized::*;
 
 mod transform_helper;
 pub use transform_helper::*;
 
+#[cfg(feature = "handle-poisoning")]
+pub mod handle_poisoning;
+
 // See https://github.com/rust-lang/rfcs/issues/1389
 pub fn describe_panic(any: &Box<dyn std::any::Any + Send>) -> String {
     if let Some(msg) = any.downcast_ref::<&str>() {
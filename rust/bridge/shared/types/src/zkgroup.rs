@@ -22,6 +22,14 @@ pub fn validate_serialization<'a, T: Deserialize<'a> + PartialDefault>(
     zkgroup::deserialize::<T>(bytes).map(|_| ())
 }
 
+/// Lets the interruptible zkgroup operations report verification failures and
+/// [`DeadlineExceeded`] through one error type.
+impl From<ZkGroupVerificationFailure> for DeadlineOr<ZkGroupVerificationFailure> {
+    fn from(error: ZkGroupVerificationFailure) -> Self {
+        Self::Other(error)
+    }
+}
+
 /// Implements [`FixedLengthBincodeSerializable`] for a ZKGroup serializable type.
 ///
 /// `bridge_as_fixed_length_serializable!(FooBar)` generates
@@ -263,6 +263,33 @@ impl ServiceId {
         }
     }
 
+    /// Concatenates the fixed-width binary representations of `service_ids`.
+    ///
+    /// This is the packed form used to pass many service IDs at once; see
+    /// [`parse_from_concatenated_fixed_width_binary`](Self::parse_from_concatenated_fixed_width_binary).
+    pub fn concatenated_fixed_width_binary(service_ids: impl IntoIterator<Item = Self>) -> Vec<u8> {
+        service_ids
+            .into_iter()
+            .flat_map(|service_id| service_id.service_id_fixed_width_binary())
+            .collect()
+    }
+
+    /// Parses a concatenation of fixed-width binary representations, returning `None` if any entry
+    /// is invalid or the input isn't a whole number of entries.
+    pub fn parse_from_concatenated_fixed_width_binary(bytes: &[u8]) -> Option<Vec<Self>> {
+        let chunks = bytes.chunks_exact(std::mem::size_of::<ServiceIdFixedWidthBinaryBytes>());
+        if !chunks.remainder().is_empty() {
+            return None;
+        }
+        chunks
+            .map(|chunk| {
+                Self::parse_from_service_id_fixed_width_binary(
+                    chunk.try_into().expect("correct size"),
+                )
+            })
+            .collect()
+    }
+
     /// Returns the UUID inside this service ID, discarding the type.
     #[inline]
     pub fn raw_uuid(self) -> Uuid {
@@ -550,6 +577,42 @@ mod service_id_tests {
         assert_eq!(ServiceIdKind::Aci, service_id.kind());
     }
 
+    #[test]
+    fn concatenated_fixed_width_binary_round_trip() {
+        let uuid = uuid::uuid!("8c78cd2a-16ff-427d-83dc-1a5e36ce713d");
+        let service_ids = [
+            ServiceId::from(Aci::from(uuid)),
+            ServiceId::from(Pni::from(uuid)),
+            ServiceId::from(Aci::from(Uuid::nil())),
+        ];
+
+        let concatenated = ServiceId::concatenated_fixed_width_binary(service_ids);
+        assert_eq!(concatenated.len(), 3 * 17);
+        assert_eq!(
+            &concatenated[17..34],
+            &service_ids[1].service_id_fixed_width_binary()
+        );
+        assert_eq!(
+            ServiceId::parse_from_concatenated_fixed_width_binary(&concatenated),
+            Some(service_ids.to_vec())
+        );
+
+        assert_eq!(
+            ServiceId::parse_from_concatenated_fixed_width_binary(&[]),
+            Some(vec![])
+        );
+        assert_eq!(
+            ServiceId::parse_from_concatenated_fixed_width_binary(&concatenated[1..]),
+            None
+        );
+        let mut bad_kind = concatenated;
+        bad_kind[17] = 0xFF;
+        assert_eq!(
+            ServiceId::parse_from_concatenated_fixed_width_binary(&bad_kind),
+            None
+        );
+    }
+
     #[test]
     fn rejects_invalid_binary_lengths() {
         let uuid = uuid::uuid!("8c78cd2a-16ff-427d-83dc-1a5e36ce713d");
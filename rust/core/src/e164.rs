@@ -7,14 +7,134 @@ use std::fmt::Display;
 use std::num::{NonZeroU64, ParseIntError};
 use std::str::FromStr;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct E164(NonZeroU64);
 
+/// The maximum number of digits in an E.164 number, including the country
+/// calling code.
+const MAX_E164_DIGITS: usize = 15;
+/// The minimum number of digits in an assigned E.164 number, including the
+/// country calling code.
+const MIN_E164_DIGITS: usize = 7;
+
+/// Country calling code used to interpret numbers written in national format.
+///
+/// This is usually the calling code of the local user's own number.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RegionHint(u16);
+
+impl RegionHint {
+    /// North American Numbering Plan, which uses `1` as its trunk prefix.
+    const NANP: u16 = 1;
+
+    /// Calling codes whose national numbers keep their leading zero in E.164
+    /// form.
+    const KEEPS_LEADING_ZERO: &'static [u16] = &[
+        39,  // Italy
+        225, // Côte d'Ivoire
+        378, // San Marino
+        379, // Vatican City
+    ];
+
+    /// Returns `None` if `calling_code` is not a plausible country calling
+    /// code (1 to 3 digits, not starting with zero).
+    pub fn from_calling_code(calling_code: u16) -> Option<Self> {
+        (1..=999)
+            .contains(&calling_code)
+            .then_some(Self(calling_code))
+    }
+
+    pub fn calling_code(&self) -> u16 {
+        self.0
+    }
+}
+
 impl E164 {
     pub const fn new(number: NonZeroU64) -> Self {
         Self(number)
     }
 
+    /// Whether this number has a plausible length for an assigned E.164
+    /// number (7 to 15 digits).
+    ///
+    /// This doesn't check whether the country calling code or the rest of the
+    /// number is actually in use.
+    pub fn is_valid(&self) -> bool {
+        let digits = self.0.ilog10() as usize + 1;
+        (MIN_E164_DIGITS..=MAX_E164_DIGITS).contains(&digits)
+    }
+
+    /// Parses a number that is already in E.164 form: a `+` followed by 7 to
+    /// 15 digits, the first of which is not zero.
+    ///
+    /// Unlike [`FromStr`], this rejects numbers without a `+`, punctuation,
+    /// and numbers of implausible length.
+    pub fn parse_strict(s: &str) -> Option<Self> {
+        let digits = s.strip_prefix('+')?;
+        if digits.starts_with('0') || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let e164 = Self(digits.parse().ok()?);
+        e164.is_valid().then_some(e164)
+    }
+
+    /// Interprets a phone number as entered by a user or stored in a contact
+    /// list.
+    ///
+    /// Accepted forms are:
+    /// - international format with a leading `+`,
+    /// - international format with a leading `00` (or `011` in the North
+    ///   American Numbering Plan), and
+    /// - national format, which is prefixed with the calling code from
+    ///   `region` after dropping a trunk prefix (`0`, or `1` in the North
+    ///   American Numbering Plan).
+    ///
+    /// Spaces, dashes, dots, slashes, and parentheses are ignored. Any other
+    /// non-digit character, or a result that isn't between 7 and 15 digits
+    /// long, makes the input invalid.
+    pub fn normalize(raw: &str, region: RegionHint) -> Option<Self> {
+        let raw = raw.trim();
+        let (has_plus, raw) = match raw.strip_prefix('+') {
+            Some(rest) => (true, rest),
+            None => (false, raw),
+        };
+
+        let mut digits = String::with_capacity(raw.len());
+        for c in raw.chars() {
+            match c {
+                '0'..='9' => digits.push(c),
+                ' ' | '\u{a0}' | '-' | '.' | '/' | '(' | ')' => {}
+                _ => return None,
+            }
+        }
+
+        let calling_code = region.calling_code();
+        let international = if has_plus {
+            digits
+        } else if let Some(rest) = digits.strip_prefix("00") {
+            rest.to_owned()
+        } else if calling_code == RegionHint::NANP && digits.starts_with("011") {
+            digits["011".len()..].to_owned()
+        } else if calling_code == RegionHint::NANP && digits.len() == 11 && digits.starts_with('1')
+        {
+            // Already includes the country code, which doubles as the trunk prefix.
+            digits
+        } else {
+            let national = if RegionHint::KEEPS_LEADING_ZERO.contains(&calling_code) {
+                &digits[..]
+            } else {
+                digits.strip_prefix('0').unwrap_or(&digits)
+            };
+            format!("{calling_code}{national}")
+        };
+
+        if international.starts_with('0') {
+            return None;
+        }
+        let e164 = Self(international.parse().ok()?);
+        e164.is_valid().then_some(e164)
+    }
+
     pub fn to_be_bytes(&self) -> [u8; std::mem::size_of::<u64>()] {
         self.0.get().to_be_bytes()
     }
@@ -50,8 +170,9 @@ mod test {
 
     use assert_matches::assert_matches;
     use proptest::{prop_compose, proptest};
+    use test_case::test_case;
 
-    use super::E164;
+    use super::{RegionHint, E164};
 
     prop_compose! {
         fn gen_e164()(num in 18005550101_u64..=18995550199) -> E164 {
@@ -76,4 +197,47 @@ mod test {
             assert_matches!(E164::from_str(&repr), Ok(actual) => assert_eq!(actual, e164));
         });
     }
+
+    const US: RegionHint = RegionHint(1);
+    const DE: RegionHint = RegionHint(49);
+    const IT: RegionHint = RegionHint(39);
+
+    #[test_case("+18005551001", US => Some(18005551001); "international")]
+    #[test_case(" +1 (800) 555-1001 ", US => Some(18005551001); "international with punctuation")]
+    #[test_case("(800) 555-1001", US => Some(18005551001); "nanp national")]
+    #[test_case("1-800-555-1001", US => Some(18005551001); "nanp with trunk prefix")]
+    #[test_case("011 49 30 1234567", US => Some(49301234567); "nanp international prefix")]
+    #[test_case("030 1234567", DE => Some(49301234567); "trunk prefix removed")]
+    #[test_case("0049 30 1234567", DE => Some(49301234567); "double zero prefix")]
+    #[test_case("06 1234 5678", IT => Some(390612345678); "leading zero kept")]
+    #[test_case("+1 800 555 1001 ext 2", US => None; "letters")]
+    #[test_case("+1234", US => None; "too short")]
+    #[test_case("+1234567890123456", US => None; "too long")]
+    #[test_case("+0123456789", US => None; "leading zero country code")]
+    #[test_case("", US => None; "empty")]
+    fn normalize(raw: &str, region: RegionHint) -> Option<u64> {
+        E164::normalize(raw, region).map(|e164| e164.0.get())
+    }
+
+    #[test_case("+18005551001" => Some(18005551001); "valid")]
+    #[test_case("18005551001" => None; "missing plus")]
+    #[test_case("+1 800 555 1001" => None; "punctuation")]
+    #[test_case("+1234" => None; "too short")]
+    #[test_case("+1234567890123456" => None; "too long")]
+    #[test_case("+0123456789" => None; "leading zero")]
+    #[test_case("++18005551001" => None; "double plus")]
+    fn parse_strict(s: &str) -> Option<u64> {
+        E164::parse_strict(s).map(|e164| e164.0.get())
+    }
+
+    #[test]
+    fn region_hint_range() {
+        assert_eq!(RegionHint::from_calling_code(0), None);
+        assert_eq!(RegionHint::from_calling_code(1), Some(US));
+        assert_eq!(
+            RegionHint::from_calling_code(999).map(|r| r.calling_code()),
+            Some(999)
+        );
+        assert_eq!(RegionHint::from_calling_code(1000), None);
+    }
 }
@@ -5,11 +5,13 @@
 
 mod address;
 mod e164;
+mod limits;
 mod version;
 
 pub use address::{
     Aci, DeviceId, Pni, ProtocolAddress, ServiceId, ServiceIdFixedWidthBinaryBytes, ServiceIdKind,
     WrongKindOfServiceIdError,
 };
-pub use e164::E164;
+pub use e164::{RegionHint, E164};
+pub use limits::{parse_limits, set_parse_limits, LimitExceeded, ParseLimits};
 pub use version::VERSION;
@@ -0,0 +1,136 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::fmt;
+use std::sync::{PoisonError, RwLock};
+
+/// Caps on the resources used to parse untrusted input.
+///
+/// By default these apply process-wide, to every libsignal crate that parses untrusted input. The
+/// defaults don't restrict anything beyond what each parser already enforces; embedders on
+/// memory-constrained devices can lower them with [`set_parse_limits`]. Parsers with a
+/// `*_with_limits` variant (or a `with_limits` option) can also be given limits directly, which
+/// take the place of the process-wide ones for that call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// The largest serialized message (protobuf or otherwise) that will be decoded.
+    pub max_message_size: usize,
+    /// The most elements a single decoded collection may have, such as the sessions in a session
+    /// record, the recipients of a multi-recipient message, or the frames in a backup.
+    ///
+    /// Collections are counted before their elements are decoded, so this also caps the memory
+    /// they take. The exception is group send endorsements, which are counted only after being
+    /// deserialized, so there the limit just skips verifying them.
+    pub max_collection_count: usize,
+    /// The most bytes a compressed stream may expand to.
+    pub max_decompressed_size: u64,
+    /// The most metadata a media file may have, all of which is held in memory while sanitizing.
+    pub max_media_metadata_size: u64,
+    /// The most decoded data a streaming parser, such as the backup validator, may keep in memory
+    /// until it finishes.
+    pub max_retained_size: u64,
+}
+
+impl ParseLimits {
+    pub const DEFAULT: Self = Self {
+        max_message_size: usize::MAX,
+        max_collection_count: usize::MAX,
+        max_decompressed_size: u64::MAX,
+        max_media_metadata_size: 300 * 1024 * 1024,
+        max_retained_size: u64::MAX,
+    };
+
+    pub fn check_message_size(&self, size: usize) -> Result<(), LimitExceeded> {
+        LimitExceeded::check("message size", size as u64, self.max_message_size as u64)
+    }
+
+    pub fn check_collection_count(&self, count: usize) -> Result<(), LimitExceeded> {
+        LimitExceeded::check(
+            "collection count",
+            count as u64,
+            self.max_collection_count as u64,
+        )
+    }
+
+    pub fn check_decompressed_size(&self, size: u64) -> Result<(), LimitExceeded> {
+        LimitExceeded::check("decompressed size", size, self.max_decompressed_size)
+    }
+
+    pub fn check_retained_size(&self, size: u64) -> Result<(), LimitExceeded> {
+        LimitExceeded::check("retained size", size, self.max_retained_size)
+    }
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+static PARSE_LIMITS: RwLock<ParseLimits> = RwLock::new(ParseLimits::DEFAULT);
+
+/// Returns the limits currently in effect.
+pub fn parse_limits() -> ParseLimits {
+    *PARSE_LIMITS.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Replaces the limits used for all subsequent parsing.
+pub fn set_parse_limits(limits: ParseLimits) {
+    *PARSE_LIMITS.write().unwrap_or_else(PoisonError::into_inner) = limits;
+}
+
+/// Input was rejected for exceeding one of the [`ParseLimits`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LimitExceeded {
+    pub limit: &'static str,
+    pub max: u64,
+    pub actual: u64,
+}
+
+impl LimitExceeded {
+    fn check(limit: &'static str, actual: u64, max: u64) -> Result<(), Self> {
+        if actual > max {
+            return Err(Self { limit, max, actual });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} exceeds limit of {}",
+            self.limit, self.actual, self.max
+        )
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_limits_allow_everything() {
+        let limits = ParseLimits::default();
+        assert_eq!(limits.check_message_size(usize::MAX), Ok(()));
+        assert_eq!(limits.check_collection_count(usize::MAX), Ok(()));
+        assert_eq!(limits.check_decompressed_size(u64::MAX), Ok(()));
+        assert_eq!(limits.check_retained_size(u64::MAX), Ok(()));
+    }
+
+    #[test]
+    fn limits_are_inclusive() {
+        let limits = ParseLimits {
+            max_message_size: 10,
+            ..ParseLimits::DEFAULT
+        };
+        assert_eq!(limits.check_message_size(10), Ok(()));
+        let error = limits.check_message_size(11).expect_err("too large");
+        assert_eq!(error.to_string(), "message size 11 exceeds limit of 10");
+    }
+}
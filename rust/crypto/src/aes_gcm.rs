@@ -10,7 +10,7 @@ use ghash::universal_hash::UniversalHash;
 use ghash::GHash;
 use subtle::ConstantTimeEq;
 
-use crate::{Aes256Ctr32, Error, Result};
+use crate::{Aes256Ctr32, Error, NonceSequence, NonceStore, Result};
 
 pub const TAG_SIZE: usize = 16;
 pub const NONCE_SIZE: usize = 12;
@@ -118,6 +118,14 @@ fn setup_gcm(key: &[u8], nonce: &[u8], associated_data: &[u8]) -> Result<(Aes256
     }
 
     let aes256 = Aes256::new_from_slice(key).map_err(|_| Error::InvalidKeySize)?;
+    setup_gcm_with_cipher(aes256, nonce, associated_data)
+}
+
+fn setup_gcm_with_cipher(
+    aes256: Aes256,
+    nonce: &[u8],
+    associated_data: &[u8],
+) -> Result<(Aes256Ctr32, GcmGhash)> {
     let mut h = [0u8; TAG_SIZE];
     aes256.encrypt_block(GenericArray::from_mut_slice(&mut h));
 
@@ -144,6 +152,19 @@ impl Aes256GcmEncryption {
         Ok(Self { ctr, ghash })
     }
 
+    /// Like [`Self::new`], but uses the key and next nonce from `nonces`.
+    ///
+    /// The nonce is returned alongside the encryptor; it must be sent with the ciphertext.
+    pub fn with_nonce_sequence(
+        nonces: &mut NonceSequence,
+        store: &mut dyn NonceStore,
+        associated_data: &[u8],
+    ) -> Result<(Self, [u8; NONCE_SIZE])> {
+        let nonce = nonces.next_nonce(store)?;
+        let (ctr, ghash) = setup_gcm_with_cipher(nonces.cipher().clone(), &nonce, associated_data)?;
+        Ok((Self { ctr, ghash }, nonce))
+    }
+
     pub fn encrypt(&mut self, buf: &mut [u8]) {
         self.ctr.process(buf);
         self.ghash.update(buf);
@@ -15,6 +15,10 @@ pub enum Error {
     InvalidInputSize,
     /// invalid authentication tag
     InvalidTag,
+    /// no more nonces can be used with this key
+    NonceLimitReached,
+    /// failed to persist nonce state: {0}
+    NonceStoreFailed(std::io::Error),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -11,9 +11,11 @@ mod hash;
 mod aes_cbc;
 mod aes_ctr;
 mod aes_gcm;
+mod nonce_sequence;
 
 pub use aes_cbc::{aes_256_cbc_decrypt, aes_256_cbc_encrypt, DecryptionError, EncryptionError};
 pub use aes_ctr::Aes256Ctr32;
 pub use aes_gcm::{Aes256GcmDecryption, Aes256GcmEncryption};
 pub use error::{Error, Result};
 pub use hash::{CryptographicHash, CryptographicMac};
+pub use nonce_sequence::{NonceSequence, NonceStore, NONCE_PREFIX_SIZE};
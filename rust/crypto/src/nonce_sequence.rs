@@ -0,0 +1,128 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::num::NonZeroU64;
+
+use aes::cipher::KeyInit;
+use aes::Aes256;
+
+use crate::aes_gcm::NONCE_SIZE;
+use crate::{Error, Result};
+
+/// The number of bytes at the start of each nonce that are fixed for the life of a
+/// [`NonceSequence`].
+pub const NONCE_PREFIX_SIZE: usize = NONCE_SIZE - std::mem::size_of::<u64>();
+
+/// Durable storage for a [`NonceSequence`]'s progress.
+pub trait NonceStore {
+    /// Records that every counter below `limit` may have been handed out.
+    ///
+    /// This must not return successfully until `limit` would survive a crash. After a restart,
+    /// pass the last recorded limit to [`NonceSequence::new`].
+    fn reserve_up_to(&mut self, limit: u64) -> std::io::Result<()>;
+}
+
+/// Hands out unique AES-GCM nonces for a single long-lived key.
+///
+/// Each nonce is a fixed prefix followed by a big-endian 64-bit counter (the "deterministic
+/// construction" from NIST SP 800-38D). Counters are reserved in batches through a [`NonceStore`]
+/// *before* they are used, so a crash can skip nonces but never repeat one. Once the limit is
+/// reached, encryption fails with [`Error::NonceLimitReached`] and the key must be replaced.
+///
+/// The sequence holds on to its key, and its nonces are only handed out through
+/// [`Aes256GcmEncryption::with_nonce_sequence`](crate::Aes256GcmEncryption::with_nonce_sequence),
+/// so they can't end up being used with some other key.
+pub struct NonceSequence {
+    cipher: Aes256,
+    prefix: [u8; NONCE_PREFIX_SIZE],
+    next: u64,
+    reserved: u64,
+    limit: u64,
+    reservation_size: NonZeroU64,
+}
+
+impl NonceSequence {
+    /// The maximum number of nonces used with one key, which is also the default limit.
+    ///
+    /// This is far below what the counter can represent, in keeping with NIST's advice to limit
+    /// the total number of invocations of GCM with a single key.
+    pub const MAX_LIMIT: u64 = 1 << 32;
+
+    /// The default number of counters reserved with each call to the store.
+    pub const DEFAULT_RESERVATION_SIZE: NonZeroU64 = match NonZeroU64::new(1024) {
+        Some(size) => size,
+        None => unreachable!(),
+    };
+
+    /// Resumes the sequence for `key`, given the last limit recorded by its [`NonceStore`].
+    ///
+    /// Use 0 for `persisted_limit` when the key is new. Keys that are shared by more than one
+    /// sender need a distinct `prefix` per sender.
+    pub fn new(key: &[u8], prefix: [u8; NONCE_PREFIX_SIZE], persisted_limit: u64) -> Result<Self> {
+        let cipher = Aes256::new_from_slice(key).map_err(|_| Error::InvalidKeySize)?;
+        Ok(Self {
+            cipher,
+            prefix,
+            next: persisted_limit,
+            reserved: persisted_limit,
+            limit: Self::MAX_LIMIT,
+            reservation_size: Self::DEFAULT_RESERVATION_SIZE,
+        })
+    }
+
+    /// Lowers the maximum number of nonces used with this key.
+    ///
+    /// Limits above [`Self::MAX_LIMIT`] are clamped to it.
+    pub fn with_limit(mut self, limit: u64) -> Self {
+        self.limit = limit.min(Self::MAX_LIMIT);
+        self
+    }
+
+    /// Changes how many counters are reserved with each call to the store.
+    ///
+    /// Larger reservations mean fewer writes, but more nonces are skipped after a crash.
+    pub fn with_reservation_size(mut self, reservation_size: NonZeroU64) -> Self {
+        self.reservation_size = reservation_size;
+        self
+    }
+
+    /// Returns how many more nonces can be produced before the limit is reached.
+    pub fn remaining(&self) -> u64 {
+        self.limit.saturating_sub(self.next)
+    }
+
+    pub(crate) fn cipher(&self) -> &Aes256 {
+        &self.cipher
+    }
+
+    /// Returns a nonce that has never been returned before for this key.
+    ///
+    /// If `store` fails to record a new reservation, the error is returned and no nonce is
+    /// produced; calling again will retry the reservation.
+    pub(crate) fn next_nonce(&mut self, store: &mut dyn NonceStore) -> Result<[u8; NONCE_SIZE]> {
+        if self.next >= self.limit {
+            return Err(Error::NonceLimitReached);
+        }
+        if self.next >= self.reserved {
+            let new_reserved = self
+                .next
+                .saturating_add(self.reservation_size.get())
+                .min(self.limit);
+            store
+                .reserve_up_to(new_reserved)
+                .map_err(Error::NonceStoreFailed)?;
+            self.reserved = new_reserved;
+        }
+
+        let counter = self.next;
+        self.next += 1;
+
+        let mut nonce = [0; NONCE_SIZE];
+        let (prefix, counter_bytes) = nonce.split_at_mut(NONCE_PREFIX_SIZE);
+        prefix.copy_from_slice(&self.prefix);
+        counter_bytes.copy_from_slice(&counter.to_be_bytes());
+        Ok(nonce)
+    }
+}
@@ -0,0 +1,155 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::collections::HashSet;
+use std::num::NonZeroU64;
+
+use hex_literal::hex;
+use signal_crypto::{Aes256GcmDecryption, Aes256GcmEncryption, Error, NonceSequence, NonceStore};
+
+const KEY: [u8; 32] = hex!("603DEB1015CA71BE2B73AEF0857D77811F352C073B6108D72D9810A30914DFF4");
+
+/// Records every reservation, optionally failing the next one.
+#[derive(Default)]
+struct TestStore {
+    reservations: Vec<u64>,
+    fail_next: bool,
+}
+
+impl NonceStore for TestStore {
+    fn reserve_up_to(&mut self, limit: u64) -> std::io::Result<()> {
+        if std::mem::take(&mut self.fail_next) {
+            return Err(std::io::ErrorKind::Other.into());
+        }
+        self.reservations.push(limit);
+        Ok(())
+    }
+}
+
+fn next_nonce(nonces: &mut NonceSequence, store: &mut TestStore) -> Result<[u8; 12], Error> {
+    let (_, nonce) = Aes256GcmEncryption::with_nonce_sequence(nonces, store, b"")?;
+    Ok(nonce)
+}
+
+#[test]
+fn nonces_are_prefix_then_counter() -> Result<(), Error> {
+    let mut store = TestStore::default();
+    let mut nonces = NonceSequence::new(&KEY, hex!("01020304"), 0)?;
+    assert_eq!(
+        next_nonce(&mut nonces, &mut store)?,
+        hex!("01020304 0000000000000000")
+    );
+    assert_eq!(
+        next_nonce(&mut nonces, &mut store)?,
+        hex!("01020304 0000000000000001")
+    );
+    Ok(())
+}
+
+#[test]
+fn counters_are_reserved_before_use() -> Result<(), Error> {
+    let mut store = TestStore::default();
+    let mut nonces = NonceSequence::new(&KEY, [0; 4], 0)?
+        .with_reservation_size(NonZeroU64::new(3).expect("non-zero"));
+    let issued: HashSet<_> = (0..7)
+        .map(|_| next_nonce(&mut nonces, &mut store))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(issued.len(), 7);
+    assert_eq!(store.reservations, [3, 6, 9]);
+
+    // After a restart, nothing below the last reservation is reused.
+    let mut resumed = NonceSequence::new(&KEY, [0; 4], 9)?;
+    assert_eq!(
+        next_nonce(&mut resumed, &mut store)?,
+        hex!("00000000 0000000000000009")
+    );
+    Ok(())
+}
+
+#[test]
+fn failed_reservations_produce_no_nonce() -> Result<(), Error> {
+    let mut store = TestStore {
+        fail_next: true,
+        ..Default::default()
+    };
+    let mut nonces = NonceSequence::new(&KEY, [0; 4], 0)?;
+    assert!(matches!(
+        next_nonce(&mut nonces, &mut store),
+        Err(Error::NonceStoreFailed(_))
+    ));
+    assert_eq!(
+        next_nonce(&mut nonces, &mut store)?,
+        hex!("00000000 0000000000000000")
+    );
+    Ok(())
+}
+
+#[test]
+fn limit_is_enforced() -> Result<(), Error> {
+    let mut store = TestStore::default();
+    let mut nonces = NonceSequence::new(&KEY, [0; 4], 0)?.with_limit(2);
+    assert_eq!(nonces.remaining(), 2);
+    next_nonce(&mut nonces, &mut store)?;
+    next_nonce(&mut nonces, &mut store)?;
+    assert_eq!(nonces.remaining(), 0);
+    assert!(matches!(
+        next_nonce(&mut nonces, &mut store),
+        Err(Error::NonceLimitReached)
+    ));
+    assert_eq!(store.reservations, [2]);
+    Ok(())
+}
+
+#[test]
+fn limit_is_capped() -> Result<(), Error> {
+    let nonces = NonceSequence::new(&KEY, [0; 4], 0)?.with_limit(u64::MAX);
+    assert_eq!(nonces.remaining(), NonceSequence::MAX_LIMIT);
+
+    let resumed = NonceSequence::new(&KEY, [0; 4], NonceSequence::MAX_LIMIT)?;
+    assert_eq!(resumed.remaining(), 0);
+    Ok(())
+}
+
+#[test]
+fn sequence_requires_valid_key() {
+    assert!(matches!(
+        NonceSequence::new(&KEY[1..], [0; 4], 0),
+        Err(Error::InvalidKeySize)
+    ));
+}
+
+#[test]
+fn streaming_encryption_uses_sequence() -> Result<(), Error> {
+    let mut store = TestStore::default();
+    let mut nonces = NonceSequence::new(&KEY, [0; 4], 0)?;
+
+    let (mut first, first_nonce) =
+        Aes256GcmEncryption::with_nonce_sequence(&mut nonces, &mut store, b"ad")?;
+    let (_, second_nonce) =
+        Aes256GcmEncryption::with_nonce_sequence(&mut nonces, &mut store, b"ad")?;
+    assert_ne!(first_nonce, second_nonce);
+
+    let mut buf = *b"hello";
+    first.encrypt(&mut buf);
+    let tag = first.compute_tag();
+
+    // The sequence's own key is used, so decrypting with it succeeds...
+    let mut decryption = Aes256GcmDecryption::new(&KEY, &first_nonce, b"ad")?;
+    let mut decrypted = buf;
+    decryption.decrypt(&mut decrypted);
+    decryption.verify_tag(&tag)?;
+    assert_eq!(&decrypted, b"hello");
+
+    // ...and any other key fails.
+    let mut other_key = KEY;
+    other_key[0] ^= 1;
+    let mut decryption = Aes256GcmDecryption::new(&other_key, &first_nonce, b"ad")?;
+    decryption.decrypt(&mut buf);
+    assert!(matches!(
+        decryption.verify_tag(&tag),
+        Err(Error::InvalidTag)
+    ));
+    Ok(())
+}
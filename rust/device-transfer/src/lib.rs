@@ -18,6 +18,8 @@ use boring::pkey::{PKey, Private};
 use boring::rsa::Rsa;
 use boring::x509::{X509Builder, X509Name, X509NameBuilder, X509};
 
+pub mod session;
+
 /// Error types for device transfer.
 #[derive(Copy, Clone, Debug)]
 pub enum Error {
@@ -25,6 +27,10 @@ pub enum Error {
     KeyDecodingFailed,
     /// Internal error in device transfer.
     InternalError(&'static str),
+    /// An operation was attempted at the wrong point in a transfer session.
+    InvalidState(&'static str),
+    /// The peer sent a malformed or out-of-order message.
+    UnexpectedMessage(&'static str),
 }
 
 impl fmt::Display for Error {
@@ -32,6 +38,10 @@ impl fmt::Display for Error {
         match self {
             Error::KeyDecodingFailed => write!(f, "Decoding provided RSA private key failed"),
             Error::InternalError(s) => write!(f, "Internal error in device transfer ({})", s),
+            Error::InvalidState(s) => write!(f, "Invalid device transfer state ({})", s),
+            Error::UnexpectedMessage(s) => {
+                write!(f, "Unexpected message from the other device ({})", s)
+            }
         }
     }
 }
@@ -0,0 +1,458 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! The device transfer session protocol, independent of any particular transport.
+//!
+//! A transfer is a conversation between the *old* device, which holds the data being transferred,
+//! and the *new* device, which receives it. Both sides go through the same sequence of steps:
+//!
+//! 1. Each side sends a hello message announcing the protocol version and its role. The old
+//!    device's hello also includes the total length of the payload it's about to send.
+//! 2. Each side sends a SHA-256 hash of its certificate (as produced by
+//!    [`create_self_signed_cert`]), committing to it.
+//! 3. Once it has the peer's commitment, each side sends its certificate, which must match the
+//!    commitment it sent earlier. Neither side can choose its certificate after seeing the other's,
+//!    so someone in the middle can't search for certificates that produce matching codes.
+//! 4. Once both certificates are known, both sides can display a [verification
+//!    code](Session::verification_code). The user checks that the codes match and confirms on each
+//!    device, which sends a confirmation to the other side.
+//! 5. Once both sides have confirmed, the old device sends the payload in chunks, followed by a
+//!    final message marking the transfer as complete.
+//!
+//! [`Session`] doesn't do any I/O itself. Bytes received from the peer are passed to
+//! [`Session::receive`], which reports what happened as a list of [`Event`]s, and bytes to be sent
+//! to the peer are collected with [`Session::take_outgoing`]. Messages may be split or combined
+//! arbitrarily by the transport; the session buffers partial messages until they're complete.
+//!
+//! [`create_self_signed_cert`]: crate::create_self_signed_cert
+
+use boring::sha::sha256;
+use boring::x509::X509;
+
+use crate::Error;
+
+/// The version of the session protocol implemented here.
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// The largest payload chunk that may be sent in a single message.
+pub const MAX_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// The largest certificate that will be accepted from the peer.
+const MAX_CERTIFICATE_SIZE: usize = 64 * 1024;
+
+/// A SHA-256 hash of a certificate.
+const COMMITMENT_SIZE: usize = 32;
+
+/// Message type (1 byte) followed by the payload length (4 bytes, big-endian).
+const FRAME_HEADER_SIZE: usize = 5;
+
+const NUM_VERIFICATION_CODE_DIGITS: u32 = 6;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+enum MessageType {
+    Hello = 1,
+    Certificate = 2,
+    VerificationConfirmed = 3,
+    PayloadChunk = 4,
+    Done = 5,
+    CertificateCommitment = 6,
+}
+
+impl TryFrom<u8> for MessageType {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Hello),
+            2 => Ok(Self::Certificate),
+            3 => Ok(Self::VerificationConfirmed),
+            4 => Ok(Self::PayloadChunk),
+            5 => Ok(Self::Done),
+            6 => Ok(Self::CertificateCommitment),
+            _ => Err(Error::UnexpectedMessage("unknown message type")),
+        }
+    }
+}
+
+impl MessageType {
+    fn max_payload_size(self) -> usize {
+        match self {
+            // version, role, and (for the old device) the payload length
+            MessageType::Hello => 2 + 8,
+            MessageType::CertificateCommitment => COMMITMENT_SIZE,
+            MessageType::Certificate => MAX_CERTIFICATE_SIZE,
+            MessageType::VerificationConfirmed | MessageType::Done => 0,
+            MessageType::PayloadChunk => MAX_CHUNK_SIZE,
+        }
+    }
+}
+
+/// Which side of the transfer a [`Session`] is running on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Role {
+    /// The device sending its data.
+    OldDevice = 0,
+    /// The device receiving the data.
+    NewDevice = 1,
+}
+
+/// The progress of a [`Session`] through the transfer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum State {
+    /// Waiting for the peer's hello.
+    AwaitingHello = 0,
+    /// Waiting for the peer's certificate commitment, and then for the certificate itself.
+    AwaitingCertificate = 1,
+    /// Both certificates are known; waiting for the verification code to be confirmed on one or
+    /// both sides.
+    Verifying = 2,
+    /// Both sides have confirmed the verification code, and the payload is being transferred.
+    Transferring = 3,
+    /// The whole payload has been transferred.
+    Completed = 4,
+    /// The session encountered an error and can't be used any further.
+    Failed = 5,
+}
+
+/// Something that happened as a result of [`Session::receive`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// Both certificates have been exchanged, so the verification code can now be displayed.
+    VerificationCodeReady,
+    /// The peer confirmed the verification code.
+    PeerConfirmed,
+    /// A piece of the payload arrived (only on the new device).
+    PayloadChunk(Vec<u8>),
+    /// The transfer is complete (only on the new device).
+    Completed,
+}
+
+/// How much of the payload has been transferred.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of payload bytes sent or received so far.
+    pub transferred: u64,
+    /// The total length of the payload.
+    pub total: u64,
+}
+
+/// One side of a device transfer; see the [module-level documentation](self).
+#[derive(Debug)]
+pub struct Session {
+    role: Role,
+    state: State,
+    local_certificate: Vec<u8>,
+    peer_commitment: Option<[u8; COMMITMENT_SIZE]>,
+    peer_certificate: Option<Vec<u8>>,
+    payload_length: Option<u64>,
+    transferred: u64,
+    local_confirmed: bool,
+    peer_confirmed: bool,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+}
+
+impl Session {
+    /// Starts a session on the old device, which will send `payload_length` bytes.
+    pub fn for_old_device(local_certificate: Vec<u8>, payload_length: u64) -> Result<Self, Error> {
+        Self::new(Role::OldDevice, local_certificate, Some(payload_length))
+    }
+
+    /// Starts a session on the new device.
+    pub fn for_new_device(local_certificate: Vec<u8>) -> Result<Self, Error> {
+        Self::new(Role::NewDevice, local_certificate, None)
+    }
+
+    fn new(
+        role: Role,
+        local_certificate: Vec<u8>,
+        payload_length: Option<u64>,
+    ) -> Result<Self, Error> {
+        if local_certificate.len() > MAX_CERTIFICATE_SIZE
+            || X509::from_der(&local_certificate).is_err()
+        {
+            return Err(Error::InternalError("invalid local certificate"));
+        }
+
+        let mut session = Self {
+            role,
+            state: State::AwaitingHello,
+            local_certificate,
+            peer_commitment: None,
+            peer_certificate: None,
+            payload_length,
+            transferred: 0,
+            local_confirmed: false,
+            peer_confirmed: false,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+        };
+
+        let mut hello = vec![PROTOCOL_VERSION, role as u8];
+        if let Some(payload_length) = payload_length {
+            hello.extend_from_slice(&payload_length.to_be_bytes());
+        }
+        session.queue(MessageType::Hello, &hello);
+        // The certificate itself isn't sent until the peer's commitment arrives.
+        session.queue(
+            MessageType::CertificateCommitment,
+            &sha256(&session.local_certificate),
+        );
+        Ok(session)
+    }
+
+    /// Which side of the transfer this session is running on.
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// The current state of the transfer.
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// How much of the payload has been transferred, or `None` if the new device hasn't yet
+    /// learned the payload length.
+    pub fn progress(&self) -> Option<Progress> {
+        self.payload_length.map(|total| Progress {
+            transferred: self.transferred,
+            total,
+        })
+    }
+
+    /// Takes all bytes that are waiting to be sent to the peer.
+    pub fn take_outgoing(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.outgoing)
+    }
+
+    /// Computes the code the user should compare between the two devices.
+    ///
+    /// The code is derived from both devices' certificates, so a mismatch means the devices aren't
+    /// talking directly to each other. Because each certificate was committed to before either was
+    /// revealed, an attacker gets a single one-in-a-million guess at a matching code rather than
+    /// being able to search for one. Only available once both certificates are known.
+    pub fn verification_code(&self) -> Result<String, Error> {
+        let peer_certificate = self
+            .peer_certificate
+            .as_ref()
+            .ok_or(Error::InvalidState("peer certificate not yet received"))?;
+        let (old_certificate, new_certificate) = match self.role {
+            Role::OldDevice => (&self.local_certificate, peer_certificate),
+            Role::NewDevice => (peer_certificate, &self.local_certificate),
+        };
+
+        let mut input = Vec::with_capacity(8 + old_certificate.len() + new_certificate.len());
+        for certificate in [old_certificate, new_certificate] {
+            let len = u32::try_from(certificate.len()).expect("certificate size is bounded");
+            input.extend_from_slice(&len.to_be_bytes());
+            input.extend_from_slice(certificate);
+        }
+        let digest = sha256(&input);
+        let value = u64::from_be_bytes(digest[..8].try_into().expect("correct length"));
+        let modulus = 10u64.pow(NUM_VERIFICATION_CODE_DIGITS);
+        Ok(format!(
+            "{:0width$}",
+            value % modulus,
+            width = NUM_VERIFICATION_CODE_DIGITS as usize
+        ))
+    }
+
+    /// Records that the user confirmed the verification codes match, and tells the peer.
+    pub fn confirm_verification_code(&mut self) -> Result<(), Error> {
+        if self.state != State::Verifying {
+            return Err(Error::InvalidState("not awaiting verification"));
+        }
+        if self.local_confirmed {
+            return Err(Error::InvalidState("verification code already confirmed"));
+        }
+        self.local_confirmed = true;
+        self.queue(MessageType::VerificationConfirmed, &[]);
+        self.update_verification_state();
+        Ok(())
+    }
+
+    /// Queues the next piece of the payload to be sent (only on the old device).
+    ///
+    /// Chunks can be sent once both sides have confirmed the verification code, and must not be
+    /// larger than [`MAX_CHUNK_SIZE`] or exceed the payload length given when the session was
+    /// created.
+    pub fn send_payload_chunk(&mut self, chunk: &[u8]) -> Result<(), Error> {
+        if self.role != Role::OldDevice {
+            return Err(Error::InvalidState("only the old device sends the payload"));
+        }
+        if self.state != State::Transferring {
+            return Err(Error::InvalidState("transfer not yet started"));
+        }
+        if chunk.len() > MAX_CHUNK_SIZE {
+            return Err(Error::InvalidState("payload chunk too large"));
+        }
+        self.add_transferred(chunk.len())
+            .map_err(|_| Error::InvalidState("payload exceeds announced length"))?;
+        self.queue(MessageType::PayloadChunk, chunk);
+        Ok(())
+    }
+
+    /// Marks the transfer as complete (only on the old device).
+    ///
+    /// The whole payload must have been sent with [`send_payload_chunk`](Self::send_payload_chunk).
+    pub fn finish(&mut self) -> Result<(), Error> {
+        if self.role != Role::OldDevice {
+            return Err(Error::InvalidState("only the old device finishes"));
+        }
+        if self.state != State::Transferring {
+            return Err(Error::InvalidState("transfer not yet started"));
+        }
+        if Some(self.transferred) != self.payload_length {
+            return Err(Error::InvalidState("payload not completely sent"));
+        }
+        self.queue(MessageType::Done, &[]);
+        self.state = State::Completed;
+        Ok(())
+    }
+
+    /// Processes bytes received from the peer.
+    ///
+    /// Any errors are fatal: afterwards the session is in the [`State::Failed`] state and all
+    /// further operations fail.
+    pub fn receive(&mut self, data: &[u8]) -> Result<Vec<Event>, Error> {
+        if self.state == State::Failed {
+            return Err(Error::InvalidState("session has failed"));
+        }
+        self.incoming.extend_from_slice(data);
+        let result = self.process_incoming();
+        if result.is_err() {
+            self.state = State::Failed;
+            self.incoming.clear();
+            self.outgoing.clear();
+        }
+        result
+    }
+
+    fn process_incoming(&mut self) -> Result<Vec<Event>, Error> {
+        let mut events = Vec::new();
+        let mut offset = 0;
+        while let Some(header) = self.incoming.get(offset..offset + FRAME_HEADER_SIZE) {
+            let message_type = MessageType::try_from(header[0])?;
+            let len = u32::from_be_bytes(header[1..].try_into().expect("correct length")) as usize;
+            if len > message_type.max_payload_size() {
+                return Err(Error::UnexpectedMessage("message too large"));
+            }
+            let start = offset + FRAME_HEADER_SIZE;
+            let Some(payload) = self.incoming.get(start..start + len) else {
+                break;
+            };
+            let payload = payload.to_vec();
+            offset = start + len;
+            self.handle_message(message_type, payload, &mut events)?;
+        }
+        self.incoming.drain(..offset);
+        Ok(events)
+    }
+
+    fn handle_message(
+        &mut self,
+        message_type: MessageType,
+        payload: Vec<u8>,
+        events: &mut Vec<Event>,
+    ) -> Result<(), Error> {
+        match (self.state, message_type) {
+            (State::AwaitingHello, MessageType::Hello) => {
+                let (version, peer_role, rest) = match payload.as_slice() {
+                    [version, role, rest @ ..] => (*version, *role, rest),
+                    _ => return Err(Error::UnexpectedMessage("truncated hello")),
+                };
+                if version != PROTOCOL_VERSION {
+                    return Err(Error::UnexpectedMessage("unsupported protocol version"));
+                }
+                match (self.role, peer_role, rest) {
+                    (Role::OldDevice, 1, []) => {}
+                    (Role::NewDevice, 0, payload_length) => {
+                        let payload_length = payload_length
+                            .try_into()
+                            .map_err(|_| Error::UnexpectedMessage("invalid hello"))?;
+                        self.payload_length = Some(u64::from_be_bytes(payload_length));
+                    }
+                    _ => return Err(Error::UnexpectedMessage("invalid hello")),
+                }
+                self.state = State::AwaitingCertificate;
+            }
+            (State::AwaitingCertificate, MessageType::CertificateCommitment)
+                if self.peer_commitment.is_none() =>
+            {
+                let commitment = payload
+                    .try_into()
+                    .map_err(|_| Error::UnexpectedMessage("invalid certificate commitment"))?;
+                self.peer_commitment = Some(commitment);
+                write_message(
+                    &mut self.outgoing,
+                    MessageType::Certificate,
+                    &self.local_certificate,
+                );
+            }
+            (State::AwaitingCertificate, MessageType::Certificate)
+                if self.peer_commitment.is_some() =>
+            {
+                if Some(sha256(&payload)) != self.peer_commitment {
+                    return Err(Error::UnexpectedMessage(
+                        "peer certificate doesn't match its commitment",
+                    ));
+                }
+                X509::from_der(&payload)
+                    .map_err(|_| Error::UnexpectedMessage("invalid peer certificate"))?;
+                self.peer_certificate = Some(payload);
+                self.state = State::Verifying;
+                events.push(Event::VerificationCodeReady);
+            }
+            (State::Verifying, MessageType::VerificationConfirmed) if !self.peer_confirmed => {
+                self.peer_confirmed = true;
+                self.update_verification_state();
+                events.push(Event::PeerConfirmed);
+            }
+            (State::Transferring, MessageType::PayloadChunk) if self.role == Role::NewDevice => {
+                self.add_transferred(payload.len())?;
+                events.push(Event::PayloadChunk(payload));
+            }
+            (State::Transferring, MessageType::Done) if self.role == Role::NewDevice => {
+                if Some(self.transferred) != self.payload_length {
+                    return Err(Error::UnexpectedMessage("payload ended early"));
+                }
+                self.state = State::Completed;
+                events.push(Event::Completed);
+            }
+            _ => return Err(Error::UnexpectedMessage("message out of order")),
+        }
+        Ok(())
+    }
+
+    fn update_verification_state(&mut self) {
+        if self.local_confirmed && self.peer_confirmed {
+            self.state = State::Transferring;
+        }
+    }
+
+    fn add_transferred(&mut self, len: usize) -> Result<(), Error> {
+        let transferred = self.transferred + len as u64;
+        if Some(transferred) > self.payload_length {
+            return Err(Error::UnexpectedMessage("payload exceeds announced length"));
+        }
+        self.transferred = transferred;
+        Ok(())
+    }
+
+    fn queue(&mut self, message_type: MessageType, payload: &[u8]) {
+        write_message(&mut self.outgoing, message_type, payload)
+    }
+}
+
+fn write_message(out: &mut Vec<u8>, message_type: MessageType, payload: &[u8]) {
+    debug_assert!(payload.len() <= message_type.max_payload_size());
+    out.push(message_type as u8);
+    let len = u32::try_from(payload.len()).expect("message size is bounded");
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(payload);
+}
@@ -0,0 +1,206 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use device_transfer::session::*;
+use device_transfer::{create_rsa_private_key, create_self_signed_cert, Error, KeyFormat};
+
+fn certificate(name: &str) -> Vec<u8> {
+    let key = create_rsa_private_key(2048, KeyFormat::Pkcs8).expect("can generate key");
+    create_self_signed_cert(&key, name, 1).expect("can generate certificate")
+}
+
+fn exchange(from: &mut Session, to: &mut Session) -> Vec<Event> {
+    to.receive(&from.take_outgoing()).expect("valid messages")
+}
+
+fn verified_sessions(payload_length: u64) -> (Session, Session) {
+    let mut old_device =
+        Session::for_old_device(certificate("old"), payload_length).expect("valid");
+    let mut new_device = Session::for_new_device(certificate("new")).expect("valid");
+
+    // Hello and commitment, after which the new device reveals its certificate.
+    assert!(exchange(&mut old_device, &mut new_device).is_empty());
+    assert_eq!(new_device.state(), State::AwaitingCertificate);
+    assert_eq!(
+        exchange(&mut new_device, &mut old_device),
+        [Event::VerificationCodeReady]
+    );
+    assert_eq!(
+        exchange(&mut old_device, &mut new_device),
+        [Event::VerificationCodeReady]
+    );
+    assert_eq!(old_device.state(), State::Verifying);
+    assert_eq!(new_device.state(), State::Verifying);
+
+    let code = old_device.verification_code().expect("ready");
+    assert_eq!(code.len(), 6);
+    assert_eq!(code, new_device.verification_code().expect("ready"));
+
+    old_device.confirm_verification_code().expect("verifying");
+    assert_eq!(old_device.state(), State::Verifying);
+    assert_eq!(
+        exchange(&mut old_device, &mut new_device),
+        [Event::PeerConfirmed]
+    );
+    assert_eq!(new_device.state(), State::Verifying);
+    new_device.confirm_verification_code().expect("verifying");
+    assert_eq!(new_device.state(), State::Transferring);
+    assert_eq!(
+        exchange(&mut new_device, &mut old_device),
+        [Event::PeerConfirmed]
+    );
+    assert_eq!(old_device.state(), State::Transferring);
+
+    (old_device, new_device)
+}
+
+#[test]
+fn test_full_transfer() {
+    let payload: Vec<u8> = (0..=255).cycle().take(3000).collect();
+    let (mut old_device, mut new_device) = verified_sessions(payload.len() as u64);
+    assert_eq!(
+        new_device.progress(),
+        Some(Progress {
+            transferred: 0,
+            total: 3000
+        })
+    );
+
+    for chunk in payload.chunks(1000) {
+        old_device.send_payload_chunk(chunk).expect("transferring");
+    }
+    old_device.finish().expect("all sent");
+    assert_eq!(old_device.state(), State::Completed);
+
+    // Deliver the messages a few bytes at a time to make sure partial messages are buffered.
+    let mut received = Vec::new();
+    let mut completed = false;
+    for piece in old_device.take_outgoing().chunks(7) {
+        for event in new_device.receive(piece).expect("valid") {
+            match event {
+                Event::PayloadChunk(chunk) => received.extend(chunk),
+                Event::Completed => completed = true,
+                e => panic!("unexpected event {e:?}"),
+            }
+        }
+    }
+    assert!(completed);
+    assert_eq!(received, payload);
+    assert_eq!(new_device.state(), State::Completed);
+    assert_eq!(
+        new_device.progress(),
+        Some(Progress {
+            transferred: 3000,
+            total: 3000
+        })
+    );
+}
+
+#[test]
+fn test_operations_out_of_order() {
+    let mut old_device = Session::for_old_device(certificate("old"), 10).expect("valid");
+    assert!(matches!(
+        old_device.verification_code(),
+        Err(Error::InvalidState(_))
+    ));
+    assert!(matches!(
+        old_device.confirm_verification_code(),
+        Err(Error::InvalidState(_))
+    ));
+    assert!(matches!(
+        old_device.send_payload_chunk(b"hello"),
+        Err(Error::InvalidState(_))
+    ));
+
+    let (mut old_device, mut new_device) = verified_sessions(10);
+    assert!(matches!(
+        new_device.send_payload_chunk(b"hello"),
+        Err(Error::InvalidState(_))
+    ));
+    assert!(matches!(
+        old_device.send_payload_chunk(&[0; 11]),
+        Err(Error::InvalidState(_))
+    ));
+    old_device
+        .send_payload_chunk(&[0; 5])
+        .expect("transferring");
+    assert!(matches!(old_device.finish(), Err(Error::InvalidState(_))));
+}
+
+fn certificate_message(certificate: &[u8]) -> Vec<u8> {
+    let mut message = vec![2];
+    message.extend_from_slice(
+        &u32::try_from(certificate.len())
+            .expect("small")
+            .to_be_bytes(),
+    );
+    message.extend_from_slice(certificate);
+    message
+}
+
+#[test]
+fn test_certificate_must_match_commitment() {
+    let old_certificate = certificate("old");
+    let mut old_device = Session::for_old_device(old_certificate.clone(), 10).expect("valid");
+    let mut new_device = Session::for_new_device(certificate("new")).expect("valid");
+
+    // The certificate isn't revealed until the peer has committed to its own.
+    let outgoing = old_device.take_outgoing();
+    assert!(!outgoing
+        .windows(old_certificate.len())
+        .any(|window| window == old_certificate));
+    assert!(new_device.receive(&outgoing).expect("valid").is_empty());
+
+    // Revealing a different certificate from the one committed to is rejected.
+    assert!(matches!(
+        new_device.receive(&certificate_message(&certificate("other"))),
+        Err(Error::UnexpectedMessage(_))
+    ));
+    assert_eq!(new_device.state(), State::Failed);
+
+    // So is revealing a certificate without committing to it first.
+    let mut new_device = Session::for_new_device(certificate("new")).expect("valid");
+    let mut messages = vec![0x01, 0, 0, 0, 10, PROTOCOL_VERSION, Role::OldDevice as u8];
+    messages.extend_from_slice(&10u64.to_be_bytes());
+    messages.extend(certificate_message(&old_certificate));
+    assert!(matches!(
+        new_device.receive(&messages),
+        Err(Error::UnexpectedMessage(_))
+    ));
+}
+
+#[test]
+fn test_unexpected_messages() {
+    let mut old_device = Session::for_old_device(certificate("old"), 10).expect("valid");
+    let mut other_old_device = Session::for_old_device(certificate("other"), 10).expect("valid");
+
+    // Two old devices can't talk to each other.
+    assert!(matches!(
+        old_device.receive(&other_old_device.take_outgoing()),
+        Err(Error::UnexpectedMessage(_))
+    ));
+    assert_eq!(old_device.state(), State::Failed);
+    assert!(matches!(
+        old_device.receive(&[]),
+        Err(Error::InvalidState(_))
+    ));
+
+    // A chunk can't arrive before verification.
+    let mut new_device = Session::for_new_device(certificate("new")).expect("valid");
+    let mut old_device = Session::for_old_device(certificate("old"), 10).expect("valid");
+    let mut messages = old_device.take_outgoing();
+    messages.extend([4, 0, 0, 0, 1, 0xff]);
+    assert!(matches!(
+        new_device.receive(&messages),
+        Err(Error::UnexpectedMessage(_))
+    ));
+
+    // Garbage is rejected.
+    let mut new_device = Session::for_new_device(certificate("new")).expect("valid");
+    assert!(matches!(
+        new_device.receive(&[0xff, 0, 0, 0, 0]),
+        Err(Error::UnexpectedMessage(_))
+    ));
+}
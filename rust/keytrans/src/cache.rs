@@ -0,0 +1,227 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Caching of verified search results.
+//!
+//! Looking up the same keys repeatedly (for example, while scrolling through a contact list)
+//! doesn't need a network request each time as long as the tree hasn't grown. Only results that
+//! passed [`verify_search`](crate::KeyTransparency::verify_search) are ever stored, so a cache hit
+//! is as trustworthy as a fresh search, and a miss falls back to a fully-verified search.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use crate::{TreeHead, TreeRoot};
+
+/// A search result that has already been verified.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CachedSearchResult {
+    /// The value the search key mapped to, if the response included one.
+    pub value: Option<Vec<u8>>,
+    pub tree_head: TreeHead,
+    pub tree_root: TreeRoot,
+    /// When the result was verified, used to expire old entries.
+    pub verified_at: SystemTime,
+}
+
+/// Storage for verified search results, keyed by search key and tree size.
+///
+/// Implementations decide how long results stay valid; a result must never be returned for a
+/// different tree size than it was stored with.
+pub trait SearchResultCache {
+    /// Looks up the result for `search_key` verified against a tree of `tree_size` entries.
+    fn get(&self, search_key: &[u8], tree_size: u64, now: SystemTime)
+        -> Option<CachedSearchResult>;
+
+    /// Records a result that was just verified.
+    fn put(&mut self, search_key: &[u8], result: CachedSearchResult);
+}
+
+/// The number of search keys an [`InMemorySearchResultCache`] holds by default.
+pub const DEFAULT_MAX_CACHED_SEARCH_KEYS: usize = 1000;
+
+/// A [`SearchResultCache`] that keeps results in memory for a fixed time.
+///
+/// Only the result for the largest tree size is kept for each search key, since a client always
+/// searches against its latest tree head. Once `max_entries` search keys are cached, adding another
+/// first drops expired results and then, if there is still no room, the least recently verified
+/// one.
+#[derive(Debug)]
+pub struct InMemorySearchResultCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: HashMap<Vec<u8>, CachedSearchResult>,
+}
+
+impl InMemorySearchResultCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_max_entries(ttl, DEFAULT_MAX_CACHED_SEARCH_KEYS)
+    }
+
+    /// Creates a cache that holds results for at most `max_entries` search keys.
+    ///
+    /// A `max_entries` of zero disables caching.
+    pub fn with_max_entries(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// The number of search keys with a cached result, including expired ones not yet dropped.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drops every entry that has outlived the TTL as of `now`.
+    pub fn remove_expired(&mut self, now: SystemTime) {
+        let ttl = self.ttl;
+        self.entries
+            .retain(|_, result| !is_expired(result, ttl, now));
+    }
+}
+
+impl SearchResultCache for InMemorySearchResultCache {
+    fn get(
+        &self,
+        search_key: &[u8],
+        tree_size: u64,
+        now: SystemTime,
+    ) -> Option<CachedSearchResult> {
+        let result = self.entries.get(search_key)?;
+        (result.tree_head.tree_size == tree_size && !is_expired(result, self.ttl, now))
+            .then(|| result.clone())
+    }
+
+    fn put(&mut self, search_key: &[u8], result: CachedSearchResult) {
+        if let Some(existing) = self.entries.get_mut(search_key) {
+            if existing.tree_head.tree_size <= result.tree_head.tree_size {
+                *existing = result;
+            }
+            return;
+        }
+
+        if self.entries.len() >= self.max_entries {
+            self.remove_expired(result.verified_at);
+        }
+        if self.entries.len() >= self.max_entries {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, cached)| cached.verified_at)
+                .map(|(key, _)| key.clone())
+            else {
+                // There's no room at all.
+                return;
+            };
+            self.entries.remove(&oldest);
+        }
+        self.entries.insert(search_key.to_vec(), result);
+    }
+}
+
+fn is_expired(result: &CachedSearchResult, ttl: Duration, now: SystemTime) -> bool {
+    // Results from the future (because the clock moved backwards) are treated as expired too.
+    match now.duration_since(result.verified_at) {
+        Ok(age) => age > ttl,
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn result(tree_size: u64, verified_at: SystemTime) -> CachedSearchResult {
+        CachedSearchResult {
+            value: Some(b"value".to_vec()),
+            tree_head: TreeHead {
+                tree_size,
+                timestamp: 0,
+                signature: vec![],
+            },
+            tree_root: [0; 32],
+            verified_at,
+        }
+    }
+
+    #[test]
+    fn hit_requires_matching_tree_size() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let mut cache = InMemorySearchResultCache::new(TTL);
+        cache.put(b"key", result(10, now));
+
+        assert_eq!(cache.get(b"key", 10, now), Some(result(10, now)));
+        assert_eq!(cache.get(b"key", 11, now), None);
+        assert_eq!(cache.get(b"other", 10, now), None);
+    }
+
+    #[test]
+    fn entries_expire() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let mut cache = InMemorySearchResultCache::new(TTL);
+        cache.put(b"key", result(10, now));
+
+        assert!(cache.get(b"key", 10, now + TTL).is_some());
+        assert_eq!(
+            cache.get(b"key", 10, now + TTL + Duration::from_secs(1)),
+            None
+        );
+        assert_eq!(cache.get(b"key", 10, now - Duration::from_secs(1)), None);
+
+        cache.remove_expired(now + TTL + Duration::from_secs(1));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn keeps_only_the_largest_tree_size() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let mut cache = InMemorySearchResultCache::new(TTL);
+        cache.put(b"key", result(10, now));
+        cache.put(b"key", result(12, now));
+        cache.put(b"key", result(11, now));
+
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(b"key", 10, now), None);
+        assert_eq!(cache.get(b"key", 11, now), None);
+        assert_eq!(cache.get(b"key", 12, now), Some(result(12, now)));
+    }
+
+    #[test]
+    fn evicts_when_full() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let second = Duration::from_secs(1);
+        let mut cache = InMemorySearchResultCache::with_max_entries(TTL, 2);
+        cache.put(b"a", result(10, start));
+        cache.put(b"b", result(10, start + second));
+
+        // Nothing has expired, so the least recently verified entry goes.
+        cache.put(b"c", result(10, start + 2 * second));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(b"a", 10, start + 2 * second), None);
+        assert!(cache.get(b"b", 10, start + 2 * second).is_some());
+
+        // Expired entries all go before anything live is evicted.
+        let later = start + 2 * second + TTL + second;
+        cache.put(b"d", result(10, later));
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(b"d", 10, later).is_some());
+    }
+
+    #[test]
+    fn zero_capacity_caches_nothing() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let mut cache = InMemorySearchResultCache::with_max_entries(TTL, 0);
+        cache.put(b"key", result(10, now));
+        assert!(cache.is_empty());
+    }
+}
@@ -5,6 +5,7 @@
 
 #![cfg_attr(not(test), warn(clippy::unwrap_used))]
 
+mod cache;
 mod commitments;
 mod guide;
 mod implicit;
@@ -18,6 +19,10 @@ mod wire;
 use std::collections::HashMap;
 use std::time::SystemTime;
 
+pub use cache::{
+    CachedSearchResult, InMemorySearchResultCache, SearchResultCache,
+    DEFAULT_MAX_CACHED_SEARCH_KEYS,
+};
 pub use ed25519_dalek::VerifyingKey;
 use verify::{
     truncate_search_response, verify_distinguished, verify_monitor, verify_search, verify_update,
@@ -119,6 +124,53 @@ impl KeyTransparency {
         verify_search(&self.config, request, response, context, force_monitor, now)
     }
 
+    /// Looks up a result for `request` that was already verified against a tree of `tree_size`
+    /// entries, so that the network request can be skipped.
+    ///
+    /// Only searches for the latest version are cached.
+    pub fn cached_search(
+        &self,
+        cache: &dyn SearchResultCache,
+        request: &SearchRequest,
+        tree_size: u64,
+        now: SystemTime,
+    ) -> Option<CachedSearchResult> {
+        if request.version.is_some() {
+            return None;
+        }
+        cache.get(&request.search_key, tree_size, now)
+    }
+
+    /// Like [`Self::verify_search`], but also stores the verified result in `cache` for
+    /// [`Self::cached_search`].
+    pub fn verify_search_and_cache(
+        &mut self,
+        request: SearchRequest,
+        response: SearchResponse,
+        context: SearchContext,
+        force_monitor: bool,
+        now: SystemTime,
+        cache: &mut dyn SearchResultCache,
+    ) -> Result<SearchUpdate, verify::Error> {
+        let search_key = request.search_key.clone();
+        let cacheable = request.version.is_none();
+        let value = response.value.as_ref().map(|value| value.value.clone());
+
+        let update = self.verify_search(request, response, context, force_monitor, now)?;
+        if cacheable {
+            cache.put(
+                &search_key,
+                CachedSearchResult {
+                    value,
+                    tree_head: update.tree_head.clone(),
+                    tree_root: update.tree_root,
+                    verified_at: now,
+                },
+            );
+        }
+        Ok(update)
+    }
+
     /// Checks that the provided FullTreeHead has a valid consistency proof relative
     /// to the provided distinguished head.
     pub fn verify_distinguished(
@@ -200,3 +252,149 @@ impl MonitoringData {
         out
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use hex_literal::hex;
+    use prost::Message as _;
+
+    use super::*;
+
+    const TREE_SIZE: u64 = 7154;
+
+    fn test_key_transparency() -> KeyTransparency {
+        let auditor_key = VerifyingKey::from_bytes(&hex!(
+            "1123b13ee32479ae6af5739e5d687b51559abf7684120511f68cde7a21a0e755"
+        ))
+        .expect("valid key");
+        KeyTransparency {
+            config: PublicConfig {
+                mode: DeploymentMode::ThirdPartyAuditing(auditor_key),
+                signature_key: VerifyingKey::from_bytes(&hex!(
+                    "12a21ad60d5a3978e19a3b0baa8c35c55a20e10d45f39e5cb34bf6e1b3cce432"
+                ))
+                .expect("valid key"),
+                vrf_key: vrf::PublicKey::try_from(hex!(
+                    "1e71563470c1b8a6e0aadf280b6aa96f8ad064674e69b80292ee46d1ab655fcf"
+                ))
+                .expect("valid key"),
+            },
+        }
+    }
+
+    fn test_request() -> SearchRequest {
+        let aci = uuid::uuid!("84fd7196-b3fa-4d4d-bbf8-8f1cdf2b7cea");
+        SearchRequest {
+            search_key: [b"a", aci.as_bytes().as_slice()].concat(),
+            version: None,
+            consistency: None,
+            mapped_value: vec![],
+            unidentified_access_key: None,
+        }
+    }
+
+    fn test_response() -> SearchResponse {
+        SearchResponse::decode(include_bytes!("../res/kt-search-response.dat").as_slice())
+            .expect("valid response")
+    }
+
+    fn valid_at() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1724279958)
+    }
+
+    #[test]
+    fn verified_search_is_cached() {
+        let mut kt = test_key_transparency();
+        let mut cache = InMemorySearchResultCache::new(Duration::from_secs(60));
+        let request = test_request();
+        let response = test_response();
+        let expected_value = response.value.as_ref().map(|value| value.value.clone());
+        assert!(expected_value.is_some());
+
+        assert_eq!(
+            kt.cached_search(&cache, &request, TREE_SIZE, valid_at()),
+            None
+        );
+        let update = kt
+            .verify_search_and_cache(
+                request.clone(),
+                response,
+                SearchContext::default(),
+                false,
+                valid_at(),
+                &mut cache,
+            )
+            .expect("valid search");
+        assert_eq!(update.tree_head.tree_size, TREE_SIZE);
+
+        let cached = kt
+            .cached_search(&cache, &request, TREE_SIZE, valid_at())
+            .expect("cache hit");
+        assert_eq!(cached.value, expected_value);
+        assert_eq!(cached.tree_head, update.tree_head);
+        assert_eq!(cached.tree_root, update.tree_root);
+
+        // A different tree size or a request for a specific version doesn't use the cached result.
+        assert_eq!(
+            kt.cached_search(&cache, &request, TREE_SIZE + 1, valid_at()),
+            None
+        );
+        let versioned = SearchRequest {
+            version: Some(0),
+            ..request
+        };
+        assert_eq!(
+            kt.cached_search(&cache, &versioned, TREE_SIZE, valid_at()),
+            None
+        );
+    }
+
+    #[test]
+    fn failed_verification_is_not_cached() {
+        let mut kt = test_key_transparency();
+        let mut cache = InMemorySearchResultCache::new(Duration::from_secs(60));
+        let mut response = test_response();
+        if let Some(value) = response.value.as_mut() {
+            value.value.push(0);
+        }
+
+        assert!(kt
+            .verify_search_and_cache(
+                test_request(),
+                response,
+                SearchContext::default(),
+                false,
+                valid_at(),
+                &mut cache,
+            )
+            .is_err());
+        assert!(cache.is_empty());
+        assert_eq!(
+            kt.cached_search(&cache, &test_request(), TREE_SIZE, valid_at()),
+            None
+        );
+    }
+
+    #[test]
+    fn versioned_search_is_not_cached() {
+        let mut kt = test_key_transparency();
+        let mut cache = InMemorySearchResultCache::new(Duration::from_secs(60));
+        let request = SearchRequest {
+            version: Some(0),
+            ..test_request()
+        };
+
+        // Whether or not this verifies, a search for a specific version must not be stored.
+        let _ = kt.verify_search_and_cache(
+            request,
+            test_response(),
+            SearchContext::default(),
+            false,
+            valid_at(),
+            &mut cache,
+        );
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,17 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+#![no_main]
+
+use futures_util::io::Cursor;
+use futures_util::FutureExt;
+use libfuzzer_sys::fuzz_target;
+use signal_media::sanitize::mp4;
+
+fuzz_target!(|data: &[u8]| {
+    let _: Result<_, _> = mp4::sanitize(Cursor::new(data))
+        .now_or_never()
+        .expect("reading from memory never blocks");
+});
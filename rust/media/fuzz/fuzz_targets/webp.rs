@@ -0,0 +1,15 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+#![no_main]
+
+use std::io::Cursor;
+
+use libfuzzer_sys::fuzz_target;
+use signal_media::sanitize::webp;
+
+fuzz_target!(|data: &[u8]| {
+    let _: Result<_, _> = webp::sanitize(Cursor::new(data));
+});
@@ -7,6 +7,7 @@ mod error;
 
 #[cfg(feature = "mp4san")]
 pub mod mp4;
+pub mod pool;
 #[cfg(feature = "webpsan")]
 pub mod webp;
 
@@ -3,33 +3,352 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use std::collections::BTreeSet;
+
 use futures_util::AsyncRead;
 use mediasan_common::AsyncSkip;
+use mp4san::parse::FourCC;
 pub use mp4san::parse::ParseError;
 use mp4san::{sanitize_async_with_config, Config};
 pub use mp4san::{InputSpan, SanitizedMetadata};
 
+pub use self::boxes::BoxType;
+use self::recording::{Recorded, RecordingInput};
+use super::pool;
+
+mod boxes;
+mod recording;
+
 /// Error type returned by [`sanitize_mp4`].
 pub type Error = super::error::SanitizerError<ParseError>;
 
 /// A decomposed and stringified [`error_stack::Report<ParseError>`](mediasan_common::Error::Parse).
 pub type ParseErrorReport = super::error::ParseErrorReport<ParseError>;
 
+/// Options controlling what [`sanitize_with_options`] keeps from its input.
+///
+/// The default options leave the output exactly as [`sanitize`] produces it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SanitizeOptions {
+    /// Metadata boxes to keep in the output, if metadata should be stripped at all.
+    ///
+    /// If set, boxes needed for playback are always kept. Any other box not in this set is
+    /// replaced by a `free` box of the same size, which leaves the positions of the media data
+    /// unchanged. [`default_preserved_metadata_boxes`] is a reasonable starting point.
+    ///
+    /// If `None`, metadata boxes are left as they are.
+    pub preserved_metadata_boxes: Option<BTreeSet<BoxType>>,
+
+    /// Which codecs to accept in audio and video tracks.
+    ///
+    /// If `None`, sample entries are not checked.
+    pub codec_policy: Option<CodecPolicy>,
+}
+
+/// The codecs a client is able to play back, beyond H.264 video and AAC audio.
+///
+/// Audio and video tracks using any other codec are rejected with
+/// [`ParseError::UnsupportedFormat`]. Tracks of other kinds, such as timed metadata, are not
+/// checked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CodecPolicy {
+    /// Allow HEVC (H.265) video, in `hvc1` or `hev1` sample entries.
+    pub allow_hevc: bool,
+    /// Allow AV1 video.
+    pub allow_av1: bool,
+    /// Allow Opus audio.
+    pub allow_opus: bool,
+}
+
+impl CodecPolicy {
+    /// Whether a sample entry of type `sample_entry` is acceptable in a `handler_type` track.
+    fn allows(&self, handler_type: &BoxType, sample_entry: &BoxType) -> bool {
+        match (handler_type, sample_entry) {
+            (b"vide", b"avc1" | b"avc3") => true,
+            (b"vide", b"hvc1" | b"hev1") => self.allow_hevc,
+            (b"vide", b"av01") => self.allow_av1,
+            (b"soun", b"mp4a") => true,
+            (b"soun", b"Opus") => self.allow_opus,
+            (b"vide" | b"soun", _) => false,
+            _ => true,
+        }
+    }
+
+    fn check(&self, metadata: &[u8]) -> Result<(), Error> {
+        for track in boxes::track_codecs(metadata) {
+            let Some(handler_type) = track.handler_type else {
+                continue;
+            };
+            if let Some(sample_entry) = track
+                .sample_entries
+                .iter()
+                .find(|entry| !self.allows(&handler_type, entry))
+            {
+                return Err(Error::Parse(ParseErrorReport {
+                    kind: ParseError::UnsupportedFormat(FourCC {
+                        value: *sample_entry,
+                    }),
+                    report: format!(
+                        "{} track uses codec {} not allowed by {self:?}",
+                        String::from_utf8_lossy(&handler_type),
+                        String::from_utf8_lossy(sample_entry),
+                    ),
+                }));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SanitizeOptions {
+    /// Whether these options need anything beyond what mp4san does on its own.
+    fn is_plain(&self) -> bool {
+        self.preserved_metadata_boxes.is_none() && self.codec_policy.is_none()
+    }
+}
+
+/// Color, aspect ratio, HDR, and spherical video metadata boxes.
+pub fn default_preserved_metadata_boxes() -> BTreeSet<BoxType> {
+    boxes::DEFAULT_PRESERVED_BOXES
+        .iter()
+        .copied()
+        .copied()
+        .collect()
+}
+
+/// The most metadata [`sanitize_with_options`] will copy aside to apply its options when mp4san
+/// leaves the input's metadata in place.
+///
+/// This copy is held in addition to what mp4san itself keeps, so it's bounded much more tightly
+/// than [`ParseLimits::max_media_metadata_size`](libsignal_core::ParseLimits). Inputs with more
+/// metadata than this are left as they are, or rejected if a [`CodecPolicy`] was requested.
+const MAX_RECORDED_METADATA_SIZE: u64 = 16 * 1024 * 1024;
+
 /// Sanitize an MP4 input.
 ///
 /// The input must implement [`AsyncRead`] + [`AsyncSkip`], where `AsyncSkip` represents the
 /// ability to skip forward, but not necessarily seek to arbitrary positions.
 ///
+/// The returned metadata may use a buffer from the shared [`pool`]; pass it to [`pool::recycle`]
+/// once it's no longer needed to make it available for the next input.
+///
 /// # Errors
 ///
 /// If the input cannot be parsed, or an IO error occurs, an `Error` is returned.
 pub async fn sanitize<R: AsyncRead + AsyncSkip>(input: R) -> Result<SanitizedMetadata, Error> {
+    sanitize_with_options(input, &SanitizeOptions::default()).await
+}
+
+/// Sanitize an MP4 input, using `options` to decide which metadata to keep.
+///
+/// See [`sanitize`].
+pub async fn sanitize_with_options<R: AsyncRead + AsyncSkip>(
+    input: R,
+    options: &SanitizeOptions,
+) -> Result<SanitizedMetadata, Error> {
+    // This sets an upper bound on memory consumption in the parser.
+    let max_metadata_size = libsignal_core::parse_limits().max_media_metadata_size;
     let config = Config::builder()
-        .max_metadata_size(MAX_METADATA_SIZE)
+        .max_metadata_size(max_metadata_size)
         .build();
-    let metadata = sanitize_async_with_config(input, config).await?;
-    Ok(metadata)
+    if options.is_plain() {
+        return Ok(sanitize_async_with_config(input, config).await?);
+    }
+
+    let (input, recorded) =
+        RecordingInput::new(input, max_metadata_size.min(MAX_RECORDED_METADATA_SIZE));
+    let mut sanitized = sanitize_async_with_config(input, config).await?;
+
+    match &mut sanitized.metadata {
+        Some(metadata) => {
+            if let Some(policy) = &options.codec_policy {
+                policy.check(metadata)?;
+            }
+            if let Some(preserved) = &options.preserved_metadata_boxes {
+                boxes::blank_unlisted_boxes(metadata, preserved);
+            }
+        }
+        None => {
+            // mp4san was happy with the input as it was; we have to start from the original.
+            let recorded = recorded.lock().expect("not poisoned");
+            sanitized.metadata =
+                rewrite_original_metadata(&recorded, sanitized.data.offset, options)?;
+        }
+    }
+    Ok(sanitized)
+}
+
+/// Applies `options` to the metadata of an input mp4san accepted as it was.
+///
+/// Returns the metadata to use in place of the original, if any boxes had to be blanked out.
+fn rewrite_original_metadata(
+    recorded: &Recorded,
+    len: u64,
+    options: &SanitizeOptions,
+) -> Result<Option<Vec<u8>>, Error> {
+    let preserved = options.preserved_metadata_boxes.as_ref();
+    let Some(Reconstructed {
+        mut metadata,
+        complete,
+    }) = reconstruct_metadata(recorded, len, preserved)
+    else {
+        // Without the metadata there's no way to tell which codecs the input uses, so it can't be
+        // let through when a policy was requested.
+        if let Some(policy) = &options.codec_policy {
+            return Err(Error::Parse(ParseErrorReport {
+                kind: ParseError::UnsupportedBoxLayout,
+                report: format!("could not recover metadata to check against {policy:?}"),
+            }));
+        }
+        return Ok(None);
+    };
+    // mp4san always reads the whole `moov`, so the codecs can be checked either way.
+    if let Some(policy) = &options.codec_policy {
+        if let Err(e) = policy.check(&metadata) {
+            pool::recycle(metadata);
+            return Err(e);
+        }
+    }
+    let blanked = preserved
+        .is_some_and(|preserved| complete && boxes::blank_unlisted_boxes(&mut metadata, preserved));
+    if blanked {
+        Ok(Some(metadata))
+    } else {
+        pool::recycle(metadata);
+        Ok(None)
+    }
+}
+
+struct Reconstructed {
+    metadata: Vec<u8>,
+    /// Whether `metadata` is fit to replace the original, i.e. nothing was lost by skipping it.
+    complete: bool,
+}
+
+/// Rebuilds the first `len` bytes of the input from what mp4san read.
+///
+/// mp4san may have skipped over the contents of boxes it ignores. Those are filled with zeros. If
+/// any of them were going to be kept, or nothing is to be blanked, the result is marked incomplete.
+fn reconstruct_metadata(
+    recorded: &Recorded,
+    len: u64,
+    preserved: Option<&BTreeSet<BoxType>>,
+) -> Option<Reconstructed> {
+    const MAX_HEADER_LEN: u64 = 16;
+
+    if len > recorded.limit() {
+        return None;
+    }
+    let mut metadata = pool::take(len.try_into().ok()?);
+    let mut complete = true;
+    let mut offset = 0;
+    while offset < len {
+        let header = recorded
+            .get(offset, MAX_HEADER_LEN.min(len - offset))
+            .or_else(|| recorded.get(offset, 8))?;
+        let (box_type, box_len, header_len) = boxes::peek_box(header)?;
+        let box_len = match box_len {
+            0 => len - offset,
+            box_len if box_len < header_len as u64 => return None,
+            box_len => box_len.min(len - offset),
+        };
+        match recorded.get(offset, box_len) {
+            Some(contents) => metadata.extend_from_slice(contents),
+            None => {
+                complete &= preserved
+                    .is_some_and(|preserved| boxes::may_discard_contents(&box_type, preserved));
+                metadata.extend_from_slice(recorded.get(offset, header_len as u64)?);
+                let contents_len = usize::try_from(box_len).ok()? - header_len;
+                metadata.resize(metadata.len() + contents_len, 0);
+            }
+        }
+        offset += box_len;
+    }
+    Some(Reconstructed { metadata, complete })
 }
 
-/// The maximum size of metadata to support, setting an upper bound on memory consumption in the parser.
-const MAX_METADATA_SIZE: u64 = 300 * 1024 * 1024;
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_codec_policy() {
+        let policy = CodecPolicy::default();
+        assert!(policy.allows(b"vide", b"avc1"));
+        assert!(policy.allows(b"soun", b"mp4a"));
+        assert!(!policy.allows(b"vide", b"hvc1"));
+        assert!(!policy.allows(b"vide", b"av01"));
+        assert!(!policy.allows(b"soun", b"Opus"));
+        assert!(!policy.allows(b"vide", b"mp4a"));
+        assert!(policy.allows(b"meta", b"mebx"));
+    }
+
+    #[test]
+    fn metadata_is_only_stripped_when_requested() {
+        use futures_util::{AsyncReadExt as _, FutureExt as _};
+
+        let udta = [0, 0, 0, 12, b'u', b'd', b't', b'a', 1, 2, 3, 4];
+        let (mut input, recorded) = RecordingInput::new(futures_util::io::Cursor::new(udta), 100);
+        input
+            .read_to_end(&mut vec![])
+            .now_or_never()
+            .expect("ready")
+            .expect("can read");
+        let recorded = recorded.lock().expect("not poisoned");
+
+        let options = SanitizeOptions::default();
+        assert!(options.is_plain());
+        assert!(matches!(
+            rewrite_original_metadata(&recorded, 12, &options),
+            Ok(None)
+        ));
+
+        let options = SanitizeOptions {
+            preserved_metadata_boxes: Some(default_preserved_metadata_boxes()),
+            ..SanitizeOptions::default()
+        };
+        let metadata = rewrite_original_metadata(&recorded, 12, &options)
+            .expect("valid")
+            .expect("blanked");
+        assert_eq!(metadata, [0, 0, 0, 12, b'f', b'r', b'e', b'e', 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn unrecoverable_metadata_is_rejected_with_codec_policy() {
+        // Nothing was recorded, so the metadata can't be rebuilt.
+        let (_input, recorded) = RecordingInput::new(futures_util::io::empty(), 0);
+        let recorded = recorded.lock().expect("not poisoned");
+
+        let options = SanitizeOptions::default();
+        assert!(matches!(
+            rewrite_original_metadata(&recorded, 32, &options),
+            Ok(None)
+        ));
+
+        let options = SanitizeOptions {
+            codec_policy: Some(CodecPolicy::default()),
+            ..SanitizeOptions::default()
+        };
+        assert!(matches!(
+            rewrite_original_metadata(&recorded, 32, &options),
+            Err(Error::Parse(ParseErrorReport {
+                kind: ParseError::UnsupportedBoxLayout,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn permissive_codec_policy() {
+        let policy = CodecPolicy {
+            allow_hevc: true,
+            allow_av1: true,
+            allow_opus: true,
+        };
+        assert!(policy.allows(b"vide", b"hvc1"));
+        assert!(policy.allows(b"vide", b"hev1"));
+        assert!(policy.allows(b"vide", b"av01"));
+        assert!(policy.allows(b"soun", b"Opus"));
+        assert!(!policy.allows(b"vide", b"vp09"));
+    }
+}
@@ -0,0 +1,317 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Minimal walking of MP4 metadata that has already been checked by mp4san.
+//!
+//! This code only needs to be careful enough not to panic or misbehave on layouts it doesn't
+//! expect; anything it doesn't understand is left alone.
+
+use std::collections::BTreeSet;
+
+/// The four-character code identifying an MP4 box, e.g. `*b"colr"`.
+pub type BoxType = [u8; 4];
+
+const FREE: BoxType = *b"free";
+
+/// Boxes that are needed to play back the media, and so are never removed.
+///
+/// Note that the track and movie transformation matrices (used for rotation) live in `tkhd` and
+/// `mvhd`, which are always kept.
+const REQUIRED_BOXES: &[&BoxType] = &[
+    b"ftyp", b"moov", b"mdat", b"free", b"skip", b"wide", // top level
+    b"mvhd", b"trak", b"tkhd", b"tref", b"edts", b"elst", b"mdia", b"mdhd", b"hdlr", b"minf",
+    b"vmhd", b"smhd", b"nmhd", b"sthd", b"dinf", b"dref", b"url ", b"urn ", b"stbl", b"stsd",
+    b"stts", b"ctts", b"cslg", b"stss", b"stsh", b"sdtp", b"stsc", b"stsz", b"stz2", b"stco",
+    b"co64", b"sgpd", b"sbgp", b"subs", b"saiz", b"saio", b"mvex", b"mehd", b"trex",
+    // codec configuration
+    b"avcC", b"hvcC", b"av1C", b"vpcC", b"dvcC", b"dvvC", b"esds", b"dOps", b"dfLa", b"dac3",
+    b"dec3", b"btrt", b"sinf",
+];
+
+/// Boxes that contain only other boxes, along with how many bytes precede the first child.
+const CONTAINER_BOXES: &[(&BoxType, usize)] = &[
+    (b"moov", 0),
+    (b"trak", 0),
+    (b"edts", 0),
+    (b"mdia", 0),
+    (b"minf", 0),
+    (b"dinf", 0),
+    (b"stbl", 0),
+    (b"mvex", 0),
+    // FullBox header plus entry count.
+    (b"stsd", 8),
+    (b"dref", 8),
+];
+
+const VISUAL_SAMPLE_ENTRIES: &[&BoxType] = &[
+    b"avc1", b"avc3", b"hvc1", b"hev1", b"dvh1", b"dvhe", b"av01", b"vp08", b"vp09", b"mp4v",
+    b"encv",
+];
+
+const AUDIO_SAMPLE_ENTRIES: &[&BoxType] = &[
+    b"mp4a", b"Opus", b"fLaC", b"ac-3", b"ec-3", b"alac", b"enca",
+];
+
+/// Benign metadata kept by default: color information, pixel aspect ratio and cropping, HDR
+/// mastering and light levels, and spherical video layout.
+pub(super) const DEFAULT_PRESERVED_BOXES: &[&BoxType] = &[
+    b"colr", b"pasp", b"clap", b"mdcv", b"clli", b"cclv", b"amve", b"st3d", b"sv3d",
+];
+
+struct BoxHeader {
+    box_type: BoxType,
+    /// The length of the size and type fields (8 or 16 bytes).
+    base_header_len: usize,
+    /// The offset of the box's payload, including any `uuid` extended type.
+    header_len: usize,
+    total_len: usize,
+}
+
+fn parse_header(buf: &[u8]) -> Option<BoxHeader> {
+    let size = u32::from_be_bytes(buf.get(0..4)?.try_into().expect("correct length"));
+    let box_type: BoxType = buf.get(4..8)?.try_into().expect("correct length");
+    let (base_header_len, total_len) = match size {
+        0 => (8, buf.len()),
+        1 => {
+            let large_size = buf.get(8..16)?.try_into().expect("correct length");
+            (16, usize::try_from(u64::from_be_bytes(large_size)).ok()?)
+        }
+        size => (8, usize::try_from(size).ok()?),
+    };
+    let header_len = if &box_type == b"uuid" {
+        base_header_len + 16
+    } else {
+        base_header_len
+    };
+    if total_len < header_len || total_len > buf.len() {
+        return None;
+    }
+    Some(BoxHeader {
+        box_type,
+        base_header_len,
+        header_len,
+        total_len,
+    })
+}
+
+/// Returns the offset of the first child box within a payload of `box_type`, or `None` if the box
+/// isn't known to have children.
+fn children_offset(box_type: &BoxType, parent: Option<&BoxType>, payload: &[u8]) -> Option<usize> {
+    if parent == Some(b"stsd") {
+        return sample_entry_children_offset(box_type, payload);
+    }
+    CONTAINER_BOXES
+        .iter()
+        .find(|(container, _)| *container == box_type)
+        .map(|(_, offset)| *offset)
+}
+
+fn sample_entry_children_offset(box_type: &BoxType, payload: &[u8]) -> Option<usize> {
+    // SampleEntry: 6 reserved bytes and a data reference index.
+    const SAMPLE_ENTRY_LEN: usize = 8;
+    if VISUAL_SAMPLE_ENTRIES.contains(&box_type) {
+        return Some(SAMPLE_ENTRY_LEN + 70);
+    }
+    if AUDIO_SAMPLE_ENTRIES.contains(&box_type) {
+        // QuickTime sound sample descriptions grow with their version.
+        let version = u16::from_be_bytes(payload.get(8..10)?.try_into().expect("correct length"));
+        return match version {
+            0 => Some(SAMPLE_ENTRY_LEN + 20),
+            1 => Some(SAMPLE_ENTRY_LEN + 36),
+            2 => Some(SAMPLE_ENTRY_LEN + 56),
+            _ => None,
+        };
+    }
+    None
+}
+
+fn is_required(box_type: &BoxType, parent: Option<&BoxType>) -> bool {
+    // Sample entries are the codecs themselves; whether they're allowed is a separate decision.
+    parent == Some(b"stsd") || REQUIRED_BOXES.contains(&box_type)
+}
+
+/// Replaces every box in `buf` that is neither required for playback nor listed in `preserved`
+/// with a zero-filled `free` box of the same size.
+///
+/// `buf` holds a sequence of sibling boxes. Since sizes are unchanged, any sample offsets in the
+/// metadata remain valid. Returns whether anything was replaced.
+pub(super) fn blank_unlisted_boxes(buf: &mut [u8], preserved: &BTreeSet<BoxType>) -> bool {
+    blank_unlisted_boxes_in(buf, None, preserved)
+}
+
+fn blank_unlisted_boxes_in(
+    buf: &mut [u8],
+    parent: Option<&BoxType>,
+    preserved: &BTreeSet<BoxType>,
+) -> bool {
+    let mut changed = false;
+    let mut offset = 0;
+    while let Some(header) = parse_header(&buf[offset..]) {
+        let this_box = &mut buf[offset..][..header.total_len];
+        if is_required(&header.box_type, parent) {
+            let payload = &mut this_box[header.header_len..];
+            if let Some(children) = children_offset(&header.box_type, parent, payload) {
+                if let Some(children) = payload.get_mut(children..) {
+                    changed |= blank_unlisted_boxes_in(children, Some(&header.box_type), preserved);
+                }
+            }
+        } else if !preserved.contains(&header.box_type) {
+            this_box[4..8].copy_from_slice(&FREE);
+            this_box[header.base_header_len..].fill(0);
+            changed = true;
+        }
+
+        offset += header.total_len;
+    }
+    changed
+}
+
+/// The handler type of a track (such as `vide` or `soun`) and the types of its sample entries.
+pub(super) struct TrackCodecs {
+    pub handler_type: Option<BoxType>,
+    pub sample_entries: Vec<BoxType>,
+}
+
+/// Lists the codecs used by each track in the `moov` boxes among the boxes in `buf`.
+pub(super) fn track_codecs(buf: &[u8]) -> Vec<TrackCodecs> {
+    let mut tracks = vec![];
+    for moov in children_of_type(buf, b"moov") {
+        for trak in children_of_type(moov, b"trak") {
+            let Some(mdia) = children_of_type(trak, b"mdia").next() else {
+                continue;
+            };
+            // FullBox header and pre_defined, followed by the handler type.
+            let handler_type = children_of_type(mdia, b"hdlr")
+                .next()
+                .and_then(|hdlr| hdlr.get(8..12))
+                .map(|handler_type| handler_type.try_into().expect("correct length"));
+            let sample_entries = children_of_type(mdia, b"minf")
+                .flat_map(|minf| children_of_type(minf, b"stbl"))
+                .flat_map(|stbl| children_of_type(stbl, b"stsd"))
+                .filter_map(|stsd| stsd.get(8..))
+                .flat_map(|entries| children(entries).map(|(box_type, _)| box_type))
+                .collect();
+            tracks.push(TrackCodecs {
+                handler_type,
+                sample_entries,
+            });
+        }
+    }
+    tracks
+}
+
+/// Iterates over the sibling boxes in `buf`, yielding each one's type and payload.
+fn children(buf: &[u8]) -> impl Iterator<Item = (BoxType, &[u8])> {
+    let mut rest = buf;
+    std::iter::from_fn(move || {
+        let header = parse_header(rest)?;
+        let (this_box, next) = rest.split_at(header.total_len);
+        rest = next;
+        Some((header.box_type, &this_box[header.header_len..]))
+    })
+}
+
+fn children_of_type<'a>(
+    buf: &'a [u8],
+    box_type: &'a BoxType,
+) -> impl Iterator<Item = &'a [u8]> + 'a {
+    children(buf)
+        .filter_map(move |(child_type, payload)| (&child_type == box_type).then_some(payload))
+}
+
+/// Whether the contents of a top-level box can be thrown away, either because they're meaningless
+/// or because the box will be blanked.
+pub(super) fn may_discard_contents(box_type: &BoxType, preserved: &BTreeSet<BoxType>) -> bool {
+    [b"free", b"skip"].contains(&box_type)
+        || !(is_required(box_type, None) || preserved.contains(box_type))
+}
+
+/// Returns the type and total length of the box starting at the beginning of `buf`, which only
+/// needs to contain the box's header.
+pub(super) fn peek_box(buf: &[u8]) -> Option<(BoxType, u64, usize)> {
+    let size = u32::from_be_bytes(buf.get(0..4)?.try_into().expect("correct length"));
+    let box_type: BoxType = buf.get(4..8)?.try_into().expect("correct length");
+    match size {
+        1 => {
+            let large_size = buf.get(8..16)?.try_into().expect("correct length");
+            Some((box_type, u64::from_be_bytes(large_size), 16))
+        }
+        size => Some((box_type, size.into(), 8)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_box(box_type: &BoxType, payload: &[u8]) -> Vec<u8> {
+        let len = u32::try_from(8 + payload.len()).expect("small");
+        [&len.to_be_bytes()[..], box_type, payload].concat()
+    }
+
+    fn make_movie(sample_entry: &[u8], extra_moov_children: &[u8]) -> Vec<u8> {
+        let stsd = make_box(b"stsd", &[&[0; 8][..], sample_entry].concat());
+        let stbl = make_box(b"stbl", &stsd);
+        let minf = make_box(b"minf", &stbl);
+        let hdlr = make_box(b"hdlr", &[&[0; 8][..], b"vide", &[0; 13]].concat());
+        let mdia = make_box(b"mdia", &[hdlr, minf].concat());
+        let tkhd = make_box(b"tkhd", &[1; 84]);
+        let trak = make_box(b"trak", &[tkhd, mdia].concat());
+        let moov = make_box(b"moov", &[&trak, extra_moov_children].concat());
+        [make_box(b"ftyp", b"isom"), moov].concat()
+    }
+
+    fn make_avc1(children: &[u8]) -> Vec<u8> {
+        let avcc = make_box(b"avcC", &[1; 10]);
+        make_box(b"avc1", &[&[0; 78][..], &avcc, children].concat())
+    }
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    }
+
+    #[test]
+    fn blanks_only_unlisted_boxes() {
+        let colr = make_box(b"colr", b"nclx\0\x01\0\x01\0\x01\0");
+        let udta = make_box(b"udta", &make_box(b"\xa9xyz", b"+12.3456-065.4321/"));
+        let original = make_movie(&make_avc1(&colr), &udta);
+
+        let mut sanitized = original.clone();
+        let preserved = DEFAULT_PRESERVED_BOXES.iter().copied().copied().collect();
+        assert!(blank_unlisted_boxes(&mut sanitized, &preserved));
+
+        assert_eq!(sanitized.len(), original.len());
+        assert_eq!(find(&sanitized, b"udta"), None);
+        assert_eq!(find(&sanitized, b"+12.3456"), None);
+        assert_eq!(find(&sanitized, b"free"), find(&original, b"udta"));
+        assert_eq!(find(&sanitized, b"colr"), find(&original, b"colr"));
+        assert_eq!(find(&sanitized, b"avcC"), find(&original, b"avcC"));
+    }
+
+    #[test]
+    fn blanks_metadata_that_is_not_preserved() {
+        let colr = make_box(b"colr", b"nclx\0\x01\0\x01\0\x01\0");
+        let mut movie = make_movie(&make_avc1(&colr), &[]);
+        assert!(blank_unlisted_boxes(&mut movie, &BTreeSet::new()));
+        assert_eq!(find(&movie, b"colr"), None);
+    }
+
+    #[test]
+    fn lists_track_codecs() {
+        let movie = make_movie(&make_avc1(&[]), &[]);
+        let tracks = track_codecs(&movie);
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].handler_type, Some(*b"vide"));
+        assert_eq!(tracks[0].sample_entries, vec![*b"avc1"]);
+    }
+
+    #[test]
+    fn nothing_to_blank() {
+        let mut movie = make_movie(&make_avc1(&[]), &[]);
+        assert!(!blank_unlisted_boxes(&mut movie, &BTreeSet::new()));
+    }
+}
@@ -0,0 +1,155 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+
+use futures_util::AsyncRead;
+use mediasan_common::AsyncSkip;
+
+use crate::sanitize::pool;
+
+/// Wraps an input to keep a copy of everything read from it (but not anything skipped).
+///
+/// mp4san skips over media data, so this holds roughly as much memory as the metadata it parses.
+/// The copy is shared so that it outlives the input, which is consumed by the sanitizer. Its
+/// buffers come from, and go back to, the shared [`pool`].
+#[pin_project::pin_project]
+pub(super) struct RecordingInput<R> {
+    #[pin]
+    inner: R,
+    position: u64,
+    recorded: Arc<Mutex<Recorded>>,
+}
+
+/// Regions of an input that were read, in order of position.
+#[derive(Default)]
+pub(super) struct Recorded {
+    chunks: Vec<(u64, Vec<u8>)>,
+    /// Bytes beyond this limit are not recorded.
+    limit: u64,
+}
+
+impl<R> RecordingInput<R> {
+    pub(super) fn new(inner: R, limit: u64) -> (Self, Arc<Mutex<Recorded>>) {
+        let recorded = Arc::new(Mutex::new(Recorded {
+            chunks: vec![],
+            limit,
+        }));
+        let input = Self {
+            inner,
+            position: 0,
+            recorded: recorded.clone(),
+        };
+        (input, recorded)
+    }
+}
+
+impl Recorded {
+    fn record(&mut self, position: u64, bytes: &[u8]) {
+        let Some(room) = self.limit.checked_sub(position) else {
+            return;
+        };
+        let bytes = &bytes[..bytes.len().min(room.try_into().unwrap_or(usize::MAX))];
+        if bytes.is_empty() {
+            return;
+        }
+        match self.chunks.last_mut() {
+            Some((start, chunk)) if *start + chunk.len() as u64 == position => {
+                chunk.extend_from_slice(bytes)
+            }
+            _ => {
+                let mut chunk = pool::take(bytes.len());
+                chunk.extend_from_slice(bytes);
+                self.chunks.push((position, chunk))
+            }
+        }
+    }
+
+    /// The position beyond which nothing is recorded.
+    pub(super) fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Returns the bytes in `start..start + len`, if they were all read.
+    pub(super) fn get(&self, start: u64, len: u64) -> Option<&[u8]> {
+        let index = self
+            .chunks
+            .partition_point(|(chunk_start, _)| *chunk_start <= start)
+            .checked_sub(1)?;
+        let (chunk_start, chunk) = &self.chunks[index];
+        let offset = usize::try_from(start - chunk_start).ok()?;
+        let len = usize::try_from(len).ok()?;
+        chunk.get(offset..offset.checked_add(len)?)
+    }
+}
+
+impl Drop for Recorded {
+    fn drop(&mut self) {
+        for (_, chunk) in self.chunks.drain(..) {
+            pool::recycle(chunk);
+        }
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for RecordingInput<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let read = ready!(this.inner.poll_read(cx, buf))?;
+        this.recorded
+            .lock()
+            .expect("not poisoned")
+            .record(*this.position, &buf[..read]);
+        *this.position += read as u64;
+        Poll::Ready(Ok(read))
+    }
+}
+
+impl<R: AsyncSkip> AsyncSkip for RecordingInput<R> {
+    fn poll_skip(self: Pin<&mut Self>, cx: &mut Context<'_>, amount: u64) -> Poll<io::Result<()>> {
+        let this = self.project();
+        ready!(this.inner.poll_skip(cx, amount))?;
+        *this.position += amount;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_stream_position(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        self.project().inner.poll_stream_position(cx)
+    }
+
+    fn poll_stream_len(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        self.project().inner.poll_stream_len(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn get_recorded_ranges() {
+        let mut recorded = Recorded {
+            chunks: vec![],
+            limit: 100,
+        };
+        recorded.record(0, b"abc");
+        recorded.record(3, b"def");
+        recorded.record(10, b"xyz");
+        recorded.record(98, b"1234");
+
+        assert_eq!(recorded.get(0, 6), Some(&b"abcdef"[..]));
+        assert_eq!(recorded.get(2, 2), Some(&b"cd"[..]));
+        assert_eq!(recorded.get(4, 8), None);
+        assert_eq!(recorded.get(11, 2), Some(&b"yz"[..]));
+        assert_eq!(recorded.get(98, 2), Some(&b"12"[..]));
+        assert_eq!(recorded.get(98, 3), None);
+    }
+}
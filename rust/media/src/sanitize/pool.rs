@@ -0,0 +1,160 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! A process-wide pool of byte buffers for sanitizer metadata.
+//!
+//! Sanitizing an MP4 keeps a copy of everything the parser read, which for a large video can be
+//! hundreds of megabytes, and then builds the sanitized metadata from it. When several videos are
+//! sanitized at once, or one after another, each of those buffers is a fresh allocation. The pool
+//! holds on to buffers once they're no longer needed so the next sanitizer can reuse them.
+//!
+//! The pool keeps at most [`set_max_retained_bytes`] bytes of idle buffers, and nothing by default,
+//! so apps that don't opt in see no change in memory use. The WebP sanitizer validates its input
+//! as it streams past without keeping any metadata buffers, so it doesn't use the pool.
+
+use std::sync::Mutex;
+
+/// The default for [`set_max_retained_bytes`]: don't keep any buffers.
+pub const DEFAULT_MAX_RETAINED_BYTES: usize = 0;
+
+static SHARED_POOL: BufferPool = BufferPool::new(DEFAULT_MAX_RETAINED_BYTES);
+
+/// Changes how many bytes of idle buffers the shared pool may hold.
+///
+/// Buffers beyond the new limit are freed immediately.
+pub fn set_max_retained_bytes(max_retained_bytes: usize) {
+    SHARED_POOL.set_max_retained_bytes(max_retained_bytes)
+}
+
+/// The number of bytes of idle buffers the shared pool currently holds.
+pub fn retained_bytes() -> usize {
+    SHARED_POOL.retained_bytes()
+}
+
+/// Returns a buffer to the shared pool, for example once the metadata from
+/// [`mp4::sanitize`](super::mp4::sanitize) has been written out.
+///
+/// The buffer is freed instead if keeping it would exceed the pool's limit.
+pub fn recycle(buffer: Vec<u8>) {
+    SHARED_POOL.recycle(buffer)
+}
+
+/// Takes an empty buffer with room for at least `min_capacity` bytes from the shared pool.
+pub(crate) fn take(min_capacity: usize) -> Vec<u8> {
+    SHARED_POOL.take(min_capacity)
+}
+
+/// A pool of idle byte buffers, with a cap on their total capacity.
+#[derive(Debug)]
+struct BufferPool {
+    state: Mutex<PoolState>,
+}
+
+#[derive(Debug)]
+struct PoolState {
+    /// Idle buffers, all empty, in increasing order of capacity.
+    buffers: Vec<Vec<u8>>,
+    retained_bytes: usize,
+    max_retained_bytes: usize,
+}
+
+impl BufferPool {
+    const fn new(max_retained_bytes: usize) -> Self {
+        Self {
+            state: Mutex::new(PoolState {
+                buffers: Vec::new(),
+                retained_bytes: 0,
+                max_retained_bytes,
+            }),
+        }
+    }
+
+    fn set_max_retained_bytes(&self, max_retained_bytes: usize) {
+        let mut state = self.state.lock().expect("not poisoned");
+        state.max_retained_bytes = max_retained_bytes;
+        // Free the largest buffers first; they're the most expensive to keep around.
+        while state.retained_bytes > max_retained_bytes {
+            let buffer = state.buffers.pop().expect("retained bytes are in buffers");
+            state.retained_bytes -= buffer.capacity();
+        }
+    }
+
+    fn retained_bytes(&self) -> usize {
+        self.state.lock().expect("not poisoned").retained_bytes
+    }
+
+    fn take(&self, min_capacity: usize) -> Vec<u8> {
+        let mut state = self.state.lock().expect("not poisoned");
+        if state.buffers.is_empty() {
+            return Vec::with_capacity(min_capacity);
+        }
+        // Prefer the smallest buffer that's big enough, leaving larger ones for larger inputs.
+        // Failing that, growing the largest buffer copies nothing, since it's empty.
+        let index = state
+            .buffers
+            .partition_point(|buffer| buffer.capacity() < min_capacity)
+            .min(state.buffers.len() - 1);
+        let mut buffer = state.buffers.remove(index);
+        state.retained_bytes -= buffer.capacity();
+        drop(state);
+
+        buffer.reserve(min_capacity);
+        buffer
+    }
+
+    fn recycle(&self, mut buffer: Vec<u8>) {
+        buffer.clear();
+        let mut state = self.state.lock().expect("not poisoned");
+        let capacity = buffer.capacity();
+        if capacity == 0 || state.retained_bytes + capacity > state.max_retained_bytes {
+            return;
+        }
+        state.retained_bytes += capacity;
+        let index = state
+            .buffers
+            .partition_point(|existing| existing.capacity() < capacity);
+        state.buffers.insert(index, buffer);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn retains_up_to_limit() {
+        let pool = BufferPool::new(0);
+        pool.recycle(Vec::with_capacity(100));
+        assert_eq!(pool.retained_bytes(), 0);
+
+        pool.set_max_retained_bytes(250);
+        pool.recycle(Vec::with_capacity(100));
+        pool.recycle(Vec::with_capacity(100));
+        pool.recycle(Vec::with_capacity(100));
+        assert_eq!(pool.retained_bytes(), 200);
+
+        pool.set_max_retained_bytes(150);
+        assert_eq!(pool.retained_bytes(), 100);
+    }
+
+    #[test]
+    fn takes_best_fit() {
+        let pool = BufferPool::new(1000);
+        pool.recycle(Vec::with_capacity(300));
+        pool.recycle(b"not empty".repeat(10));
+        assert!(pool.retained_bytes() >= 390);
+
+        let buffer = pool.take(50);
+        assert!(buffer.is_empty());
+        assert!(buffer.capacity() >= 90 && buffer.capacity() < 300);
+
+        let buffer = pool.take(500);
+        assert!(buffer.capacity() >= 500);
+        assert_eq!(pool.retained_bytes(), 0);
+
+        let buffer = pool.take(10);
+        assert!(buffer.capacity() >= 10);
+    }
+}
@@ -21,6 +21,7 @@ use hmac::Mac;
 use libsignal_account_keys::{AccountEntropyPool, BackupKey};
 use libsignal_core::Aci;
 use libsignal_message_backup::args::{parse_aci, parse_hex_bytes};
+use libsignal_message_backup::frame::PaddingPolicy;
 use libsignal_message_backup::key::MessageBackupKey;
 use sha2::Sha256;
 
@@ -101,7 +102,7 @@ fn main() {
     eprintln!("compressed to {} bytes", compressed_contents.len());
 
     if pad_bucketed {
-        pad_gzipped_bucketed(&mut compressed_contents);
+        PaddingPolicy::DEFAULT.pad(&mut compressed_contents);
         eprintln!("padded to {} bytes", compressed_contents.len());
     }
 
@@ -151,19 +152,6 @@ fn gzip_compress(contents: Vec<u8>) -> Vec<u8> {
     compressed_contents
 }
 
-fn pad_gzipped_bucketed(out: &mut Vec<u8>) {
-    const BASE: f64 = 1.05;
-    let len = u32::try_from(out.len()).expect("backup < 4GB");
-
-    #[allow(clippy::cast_possible_truncation)]
-    let padded_len = {
-        let exp = f64::log(len.into(), BASE).ceil();
-        u32::max(541, BASE.powf(exp).floor() as u32)
-    };
-
-    out.resize(padded_len.try_into().unwrap(), 0);
-}
-
 fn write_bytes(label: &'static str, bytes: impl AsRef<[u8]>) {
     let bytes = bytes.as_ref();
     stdout().write_all(bytes).expect("failed to write");
@@ -0,0 +1,24 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+#![no_main]
+
+use futures::io::Cursor;
+use libfuzzer_sys::fuzz_target;
+use libsignal_message_backup::backup::Purpose;
+use libsignal_message_backup::BackupReader;
+
+fuzz_target!(|input: (bool, &[u8])| {
+    let (for_transfer, frames) = input;
+    let purpose = if for_transfer {
+        Purpose::DeviceTransfer
+    } else {
+        Purpose::RemoteBackup
+    };
+    // Decryption and decompression are tested elsewhere; this target starts from the plaintext,
+    // varint-delimited frames that the validator checks.
+    let reader = BackupReader::new_unencrypted(Cursor::new(frames), purpose);
+    let _ = futures::executor::block_on(reader.validate_all());
+});
@@ -386,6 +386,21 @@ impl<M: Method + ReferencedTypes> PartialBackup<M> {
         })
     }
 
+    /// Whether the contents of `frame` stay in memory after being added, until the backup is
+    /// complete.
+    pub(crate) fn retains(frame: &proto::Frame) -> bool {
+        match &frame.item {
+            None => false,
+            Some(FrameItem::ChatItem(_) | FrameItem::AdHocCall(_)) => M::KEEPS_LIST_ITEMS,
+            Some(
+                FrameItem::Account(_)
+                | FrameItem::Recipient(_)
+                | FrameItem::Chat(_)
+                | FrameItem::StickerPack(_),
+            ) => true,
+        }
+    }
+
     pub fn add_frame(&mut self, frame: proto::Frame) -> Result<(), ValidationError> {
         self.add_frame_item(frame.item.ok_or(ValidationError::EmptyFrame)?)
     }
@@ -37,6 +37,9 @@ pub trait Method: serde::Serialize {
     type BoxedValue<T: Debug + serde::Serialize>: Debug + serde::Serialize;
     type List<T: Debug>: Extend<T> + Default + Debug;
 
+    /// Whether [`Self::List`] holds on to the items added to it.
+    const KEEPS_LIST_ITEMS: bool;
+
     fn value<T: Debug + serde::Serialize>(value: T) -> Self::Value<T>;
     fn boxed_value<T: Debug + serde::Serialize>(value: T) -> Self::BoxedValue<T>;
 }
@@ -58,6 +61,8 @@ impl Method for ValidateOnly {
     type BoxedValue<T: Debug + serde::Serialize> = ();
     type List<T: Debug> = ValidateOnlyList;
 
+    const KEEPS_LIST_ITEMS: bool = false;
+
     fn value<T: Debug + serde::Serialize>(_value: T) -> Self::Value<T> {}
     fn boxed_value<T: Debug + serde::Serialize>(_value: T) -> Self::BoxedValue<T> {}
 }
@@ -70,6 +75,8 @@ impl Method for Store {
     type BoxedValue<T: Debug + serde::Serialize> = Box<T>;
     type List<T: Debug> = Vec<T>;
 
+    const KEEPS_LIST_ITEMS: bool = true;
+
     fn value<T: Debug + serde::Serialize>(value: T) -> Self::Value<T> {
         value
     }
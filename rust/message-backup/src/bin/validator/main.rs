@@ -246,6 +246,7 @@ impl<R: AsyncRead + Unpin> MaybeEncryptedBackupReader<R> {
             let ReadResult {
                 found_unknown_fields,
                 result,
+                error_frame_index: _,
             } = backup_reader.read_all().await;
 
             print_unknown_fields(found_unknown_fields);
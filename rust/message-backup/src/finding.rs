@@ -0,0 +1,232 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Machine-readable results of validating a backup.
+//!
+//! A [`ReadResult`] carries at most one error and any number of unknown
+//! fields. [`ReadResult::findings`] flattens those into a uniform list so that
+//! callers can tell fatal problems apart from ignorable ones without parsing
+//! error messages.
+
+use crate::backup::ValidationError;
+use crate::frame::ValidationError as FrameValidationError;
+use crate::parse::ParseError;
+use crate::unknown::FormatPath;
+use crate::{Error, FoundUnknownField, ReadResult};
+
+/// How much a [`ValidationFinding`] affects the usability of the backup.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, num_enum::IntoPrimitive)]
+#[repr(u8)]
+pub enum FindingSeverity {
+    /// The backup can still be imported.
+    Ignorable = 0,
+    /// The backup cannot be imported.
+    Fatal = 1,
+}
+
+/// The category of a [`ValidationFinding`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, num_enum::IntoPrimitive)]
+#[repr(u8)]
+pub enum FindingKind {
+    /// A field or enum value not known to this version of the validator.
+    UnknownField = 0,
+    /// A frame was well-formed but its contents were invalid.
+    InvalidFrame = 1,
+    /// All frames were valid but required data was missing.
+    IncompleteBackup = 2,
+    /// A frame could not be decoded.
+    MalformedFrame = 3,
+    /// The backup contained no frames at all.
+    NoFrames = 4,
+    /// The backup's HMAC did not match its contents.
+    HmacMismatch = 5,
+    /// The backup could not be read.
+    Io = 6,
+    /// Reading stopped because the backup exceeded one of the configured
+    /// [`ParseLimits`](libsignal_core::ParseLimits).
+    LimitExceeded = 7,
+}
+
+/// A single problem found while validating a backup.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValidationFinding {
+    /// The index of the frame the finding applies to, where the
+    /// `BackupInfo` header is frame 0.
+    pub frame_index: Option<usize>,
+    /// Dotted path to the offending field within the frame, or the frame item
+    /// type when the finding applies to the whole item. Empty if unknown.
+    pub field_path: String,
+    pub kind: FindingKind,
+    pub severity: FindingSeverity,
+    /// Human-readable description, suitable for logging.
+    pub message: String,
+}
+
+impl<B> ReadResult<B> {
+    /// Collects the error (if any) and unknown fields into a list of findings.
+    ///
+    /// The error, if present, is listed first.
+    pub fn findings(&self) -> Vec<ValidationFinding> {
+        let error_finding = self
+            .result
+            .as_ref()
+            .err()
+            .map(|e| ValidationFinding::from_error(e, self.error_frame_index));
+
+        error_finding
+            .into_iter()
+            .chain(
+                self.found_unknown_fields
+                    .iter()
+                    .map(ValidationFinding::from_unknown_field),
+            )
+            .collect()
+    }
+}
+
+impl ValidationFinding {
+    fn from_error(error: &Error, frame_index: Option<usize>) -> Self {
+        let (kind, field_path) = match error {
+            Error::BackupValidation(e) => (FindingKind::InvalidFrame, frame_item_name(e)),
+            Error::BackupCompletion(_) => (FindingKind::IncompleteBackup, ""),
+            Error::Parse(ParseError::Decode(_)) | Error::InvalidProtobuf(_) => {
+                (FindingKind::MalformedFrame, "")
+            }
+            Error::Parse(ParseError::Io(_)) => (FindingKind::Io, ""),
+            Error::NoFrames => (FindingKind::NoFrames, ""),
+            Error::HmacMismatch(_) => (FindingKind::HmacMismatch, ""),
+            Error::LimitExceeded(_) => (FindingKind::LimitExceeded, ""),
+        };
+        Self {
+            frame_index,
+            field_path: field_path.to_owned(),
+            kind,
+            severity: FindingSeverity::Fatal,
+            message: error.to_string(),
+        }
+    }
+
+    /// Describes a failure to open the encrypted backup, before any frames
+    /// could be read.
+    pub fn from_frame_error(error: &FrameValidationError) -> Self {
+        let kind = match error {
+            FrameValidationError::Io(_) => FindingKind::Io,
+            FrameValidationError::TooShort => FindingKind::MalformedFrame,
+            FrameValidationError::InvalidHmac(_) => FindingKind::HmacMismatch,
+        };
+        Self {
+            frame_index: None,
+            field_path: String::new(),
+            kind,
+            severity: FindingSeverity::Fatal,
+            message: error.to_string(),
+        }
+    }
+
+    fn from_unknown_field(found: &FoundUnknownField) -> Self {
+        let FoundUnknownField {
+            frame_index,
+            path,
+            value: _,
+        } = found;
+        Self {
+            frame_index: Some(*frame_index),
+            field_path: FormatPath(path.as_slice()).to_string(),
+            kind: FindingKind::UnknownField,
+            severity: FindingSeverity::Ignorable,
+            message: found.to_string(),
+        }
+    }
+}
+
+/// The name of the `Frame.item` field that produced `error`, as spelled in
+/// Backup.proto.
+fn frame_item_name(error: &ValidationError) -> &'static str {
+    match error {
+        ValidationError::EmptyFrame => "item",
+        ValidationError::BackupInfoError(_) => "backupInfo",
+        ValidationError::MultipleAccountData | ValidationError::AccountData(_) => "account",
+        ValidationError::RecipientError(_) => "recipient",
+        ValidationError::ChatError(_) => "chat",
+        ValidationError::CallError(_) => "adHocCall",
+        ValidationError::StickerError(_) => "stickerPack",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+
+    use super::*;
+    use crate::backup::CompletionError;
+    use crate::unknown::{PathPart, UnknownValue};
+
+    #[test]
+    fn error_is_listed_first_and_fatal() {
+        let result = ReadResult::<()> {
+            result: Err(Error::BackupValidation(
+                ValidationError::MultipleAccountData,
+            )),
+            found_unknown_fields: vec![FoundUnknownField {
+                frame_index: 1,
+                path: vec![PathPart::Field {
+                    field_name: "account".to_owned(),
+                }],
+                value: UnknownValue::EnumValue { number: 5 },
+            }],
+            error_frame_index: Some(3),
+        };
+
+        let findings = result.findings();
+        assert_matches!(
+            findings.as_slice(),
+            [
+                ValidationFinding {
+                    frame_index: Some(3),
+                    field_path: error_path,
+                    kind: FindingKind::InvalidFrame,
+                    severity: FindingSeverity::Fatal,
+                    message: _,
+                },
+                ValidationFinding {
+                    frame_index: Some(1),
+                    field_path: unknown_path,
+                    kind: FindingKind::UnknownField,
+                    severity: FindingSeverity::Ignorable,
+                    message: _,
+                },
+            ] if error_path == "account" && unknown_path == "account"
+        );
+    }
+
+    #[test]
+    fn completion_error_has_no_frame() {
+        let result = ReadResult::<()> {
+            result: Err(Error::BackupCompletion(CompletionError::MissingAccountData)),
+            found_unknown_fields: vec![],
+            error_frame_index: None,
+        };
+
+        assert_matches!(
+            result.findings().as_slice(),
+            [ValidationFinding {
+                frame_index: None,
+                kind: FindingKind::IncompleteBackup,
+                severity: FindingSeverity::Fatal,
+                ..
+            }]
+        );
+    }
+
+    #[test]
+    fn valid_backup_has_no_findings() {
+        let result = ReadResult {
+            result: Ok(()),
+            found_unknown_fields: vec![],
+            error_frame_index: None,
+        };
+        assert_eq!(result.findings(), vec![]);
+    }
+}
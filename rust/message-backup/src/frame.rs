@@ -4,10 +4,10 @@
 //
 
 use std::borrow::BorrowMut;
+use std::future::Future;
 
 use aes::cipher::Unsigned;
 use async_compression::futures::bufread::GzipDecoder;
-use async_trait::async_trait;
 use futures::io::{BufReader, Take};
 use futures::{AsyncRead, AsyncReadExt};
 use hmac::digest::OutputSizeUser;
@@ -17,23 +17,29 @@ use sha2::Sha256;
 use subtle::ConstantTimeEq as _;
 
 use crate::frame::aes_read::{Aes256CbcReader, AES_IV_SIZE};
+use crate::frame::limit_read::DecompressionLimitReader;
 use crate::frame::mac_read::MacReader;
 use crate::key::MessageBackupKey;
 
 mod aes_read;
 mod block_stream;
 mod cbc;
+mod limit_read;
 mod mac_read;
+mod padding;
 mod reader_factory;
 mod unpad;
 
+pub use padding::{encrypted_size, PaddingPolicy};
 pub use reader_factory::{CursorFactory, FileReaderFactory, LimitedReaderFactory, ReaderFactory};
 
 const HMAC_LEN: usize = <<Hmac<Sha256> as OutputSizeUser>::OutputSize as Unsigned>::USIZE;
 
 #[derive(Debug)]
 pub struct FramesReader<R: AsyncRead + Unpin> {
-    reader: GzipDecoder<BufReader<Aes256CbcReader<HmacSha256Reader<Take<R>>>>>,
+    reader: DecompressionLimitReader<
+        GzipDecoder<BufReader<Aes256CbcReader<HmacSha256Reader<Take<R>>>>>,
+    >,
     expected_hmac: [u8; HMAC_LEN],
 }
 
@@ -70,10 +76,12 @@ pub enum VerifyHmacError {
     HmacMismatch(#[from] HmacMismatchError),
 }
 
-#[async_trait(?Send)]
 pub trait VerifyHmac: Sized {
     /// Checks that the input that was received has a valid HMAC.
-    async fn verify_hmac(self) -> Result<(), VerifyHmacError>;
+    ///
+    /// Implementations should make the returned future `Send` whenever the reader is, so that
+    /// backups can be read on a multi-threaded runtime.
+    fn verify_hmac(self) -> impl Future<Output = Result<(), VerifyHmacError>>;
 }
 
 impl<R: AsyncRead + AsyncSkip + Unpin> FramesReader<R> {
@@ -118,7 +126,10 @@ impl<R: AsyncRead + AsyncSkip + Unpin> FramesReader<R> {
         content.read_exact(&mut iv).await?;
 
         let decrypted = Aes256CbcReader::new(&key.aes_key, &iv, content);
-        let decompressed = GzipDecoder::new(BufReader::new(decrypted));
+        let decompressed = DecompressionLimitReader::new(
+            GzipDecoder::new(BufReader::new(decrypted)),
+            libsignal_core::parse_limits(),
+        );
 
         Ok(Self {
             reader: decompressed,
@@ -163,7 +174,6 @@ impl<R: AsyncRead + Unpin> AsyncRead for UnvalidatedHmacReader<R> {
     }
 }
 
-#[async_trait(?Send)]
 impl<R> VerifyHmac for UnvalidatedHmacReader<R> {
     async fn verify_hmac(self) -> Result<(), VerifyHmacError> {
         Ok(())
@@ -184,7 +194,6 @@ impl std::fmt::Display for HmacMismatchError {
     }
 }
 
-#[async_trait(?Send)]
 impl<R: AsyncRead + Unpin> VerifyHmac for FramesReader<R> {
     async fn verify_hmac(self) -> Result<(), VerifyHmacError> {
         let Self {
@@ -196,7 +205,8 @@ impl<R: AsyncRead + Unpin> VerifyHmac for FramesReader<R> {
         // the compressed contents. Make sure all the bytes from the inner
         // stream get read through the MacReader input bytes before doing the
         // comparison.
-        let mut reader: MacReader<_, _> = reader.into_inner().into_inner().into_inner();
+        let mut reader: MacReader<_, _> =
+            reader.into_inner().into_inner().into_inner().into_inner();
         futures::io::copy(&mut reader, &mut futures::io::sink()).await?;
 
         let found: [u8; HMAC_LEN] = reader.finalize().into();
@@ -3,9 +3,8 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
-use std::cell::RefCell;
 use std::pin::Pin;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 use std::task::Poll;
 
 use aes::Aes256;
@@ -18,7 +17,7 @@ use crate::frame::block_stream::ExactReadBlockStream;
 use crate::frame::cbc::CbcStreamDecryptor;
 use crate::frame::unpad::UnpadLast;
 
-const AES_BLOCK_SIZE: usize = <<Aes256 as BlockSizeUser>::BlockSize as Unsigned>::USIZE;
+pub(super) const AES_BLOCK_SIZE: usize = <<Aes256 as BlockSizeUser>::BlockSize as Unsigned>::USIZE;
 const AES_KEY_SIZE: usize = <<Aes256 as KeySizeUser>::KeySize as Unsigned>::USIZE;
 pub(super) const AES_IV_SIZE: usize =
     <<cbc::Decryptor<Aes256> as IvSizeUser>::IvSize as Unsigned>::USIZE;
@@ -37,24 +36,24 @@ pub(crate) struct Aes256CbcReader<R: AsyncRead + Unpin> {
     /// are fed into an [`Aes256`] decryptor as a stream, then un-padded and
     /// un-chunked in response to an [`AsyncRead::poll_read`] call.
     reader: futures::stream::IntoAsyncRead<
-        Aes256CbcUnpadLast<Aes256CbcDecryptStream<BlockStream<RcReader<R>>>>,
+        Aes256CbcUnpadLast<Aes256CbcDecryptStream<BlockStream<SharedReader<R>>>>,
     >,
     /// Separate reference to the wrapped reader for production via
     /// [`Aes256CbcReader::into_inner`].
-    inner: Rc<RefCell<R>>,
+    inner: Arc<Mutex<R>>,
 }
 
 impl<R: AsyncRead + Unpin> Aes256CbcReader<R> {
     pub(crate) fn new(key: &[u8; AES_KEY_SIZE], iv: &[u8; AES_IV_SIZE], reader: R) -> Self {
-        let rc_reader = Rc::new(RefCell::new(reader));
-        let reader = RcReader(rc_reader.clone());
+        let shared_reader = Arc::new(Mutex::new(reader));
+        let reader = SharedReader(shared_reader.clone());
         let stream: BlockStream<_> = ExactReadBlockStream::from_reader(reader).map_ok(Into::into);
         let decrypt: Aes256CbcDecryptStream<BlockStream<_>> =
             CbcStreamDecryptor::new(cbc::Decryptor::<Aes256>::new(key.into(), iv.into()), stream);
         let unpad = UnpadLast::<_, Pkcs7, _, 16>::new(decrypt);
         Self {
             reader: TryStreamExt::into_async_read(unpad),
-            inner: rc_reader,
+            inner: shared_reader,
         }
     }
 
@@ -67,7 +66,10 @@ impl<R: AsyncRead + Unpin> Aes256CbcReader<R> {
         let Self { reader, inner } = self;
         drop(reader);
 
-        RefCell::into_inner(Rc::into_inner(inner).expect("only other reference was just dropped"))
+        Arc::into_inner(inner)
+            .expect("only other reference was just dropped")
+            .into_inner()
+            .expect("not poisoned")
     }
 }
 
@@ -98,17 +100,18 @@ type Aes256CbcDecryptStream<S> = CbcStreamDecryptor<Aes256, S>;
 
 /// A [`AsyncRead`]er that reads from shared mutable state.
 ///
-/// Trivial implementer of `AsyncRead` around a `Rc<RefCell<R>>`.
+/// Trivial implementer of `AsyncRead` around an `Arc<Mutex<R>>`. The lock is never contended; it
+/// only exists so that the reader can be sent between threads along with its owner.
 #[derive(Debug)]
-struct RcReader<R>(Rc<RefCell<R>>);
+struct SharedReader<R>(Arc<Mutex<R>>);
 
-impl<R: AsyncRead + Unpin> AsyncRead for RcReader<R> {
+impl<R: AsyncRead + Unpin> AsyncRead for SharedReader<R> {
     fn poll_read(
         self: Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
         buf: &mut [u8],
     ) -> Poll<std::io::Result<usize>> {
-        Pin::new(&mut *self.get_mut().0.borrow_mut()).poll_read(cx, buf)
+        Pin::new(&mut *self.get_mut().0.lock().expect("not poisoned")).poll_read(cx, buf)
     }
 }
 
@@ -0,0 +1,88 @@
+//
+// Copyright (C) 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use std::pin::Pin;
+
+use futures::{ready, AsyncRead};
+use libsignal_core::ParseLimits;
+
+/// [`AsyncRead`]er that fails once its input has produced more than
+/// [`ParseLimits::max_decompressed_size`] bytes.
+///
+/// Unlike [`futures::io::Take`], this doesn't silently truncate the input,
+/// which could otherwise be mistaken for a shorter but valid backup.
+#[derive(Debug)]
+pub(crate) struct DecompressionLimitReader<R> {
+    reader: R,
+    limits: ParseLimits,
+    total_read: u64,
+}
+
+impl<R> DecompressionLimitReader<R> {
+    pub(crate) fn new(reader: R, limits: ParseLimits) -> Self {
+        Self {
+            reader,
+            limits,
+            total_read: 0,
+        }
+    }
+
+    pub(crate) fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for DecompressionLimitReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> std::task::Poll<futures::io::Result<usize>> {
+        let Self {
+            reader,
+            limits,
+            total_read,
+        } = self.get_mut();
+        let num_read = ready!(Pin::new(reader).poll_read(cx, buf))?;
+
+        *total_read = total_read.saturating_add(num_read as u64);
+        limits
+            .check_decompressed_size(*total_read)
+            .map_err(|e| futures::io::Error::new(futures::io::ErrorKind::InvalidData, e))?;
+
+        std::task::Poll::Ready(Ok(num_read))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures::io::Cursor;
+    use futures::{AsyncReadExt as _, FutureExt as _};
+
+    use super::*;
+
+    #[test]
+    fn limit_read() {
+        let limits = ParseLimits {
+            max_decompressed_size: 10,
+            ..ParseLimits::DEFAULT
+        };
+
+        let mut buf = Vec::new();
+        DecompressionLimitReader::new(Cursor::new([1; 10]), limits)
+            .read_to_end(&mut buf)
+            .now_or_never()
+            .expect("sync")
+            .expect("within limit");
+        assert_eq!(buf, [1; 10]);
+
+        let error = DecompressionLimitReader::new(Cursor::new([1; 11]), limits)
+            .read_to_end(&mut buf)
+            .now_or_never()
+            .expect("sync")
+            .expect_err("over limit");
+        assert_eq!(error.kind(), futures::io::ErrorKind::InvalidData);
+    }
+}
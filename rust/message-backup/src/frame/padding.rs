@@ -0,0 +1,142 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Padding applied to compressed backup contents before they are encrypted.
+//!
+//! Readers ignore anything after the end of the gzip stream (see
+//! [`FramesReader`](super::FramesReader)), so writers can append zeros to hide the exact size of a
+//! backup. The same policy also tells clients how large an upload will be before producing it.
+
+use super::aes_read::{AES_BLOCK_SIZE, AES_IV_SIZE};
+use super::HMAC_LEN;
+
+/// How compressed backup contents are padded before encryption.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PaddingPolicy {
+    /// No padding is added.
+    None,
+    /// The contents are padded up to the smallest bucket that fits them.
+    ///
+    /// Bucket sizes are `floor(growth_factor^n)` for integer `n`, but never less than `min_size`.
+    /// A `growth_factor` of 1 or less disables bucketing, leaving only the minimum size.
+    Bucketed { growth_factor: f64, min_size: u64 },
+}
+
+impl PaddingPolicy {
+    /// The policy used for backups produced by Signal clients.
+    pub const DEFAULT: Self = Self::Bucketed {
+        growth_factor: 1.05,
+        min_size: 541,
+    };
+
+    /// The size that `compressed_len` bytes of compressed contents become after padding.
+    pub fn padded_size(&self, compressed_len: u64) -> u64 {
+        match *self {
+            Self::None => compressed_len,
+            Self::Bucketed {
+                growth_factor,
+                min_size,
+            } => {
+                let bucket = if growth_factor > 1.0 && compressed_len > 0 {
+                    let exp = (compressed_len as f64).log(growth_factor).ceil();
+                    #[allow(clippy::cast_possible_truncation)]
+                    let bucket = growth_factor.powf(exp).floor() as u64;
+                    // Guard against rounding error putting the bucket just below the input.
+                    bucket.max(compressed_len)
+                } else {
+                    compressed_len
+                };
+                bucket.max(min_size)
+            }
+        }
+    }
+
+    /// Appends zeros to `compressed` to reach its [padded size](Self::padded_size).
+    pub fn pad(&self, compressed: &mut Vec<u8>) {
+        let len = u64::try_from(compressed.len()).expect("usize fits in u64");
+        let padded_len =
+            usize::try_from(self.padded_size(len)).expect("padded contents fit in memory");
+        compressed.resize(padded_len, 0);
+    }
+
+    /// The total size of an encrypted backup file whose compressed contents are `compressed_len`
+    /// bytes, after padding with this policy.
+    ///
+    /// This is the number of bytes that will be uploaded, and so counts against storage quota.
+    pub fn encrypted_size(&self, compressed_len: u64) -> u64 {
+        encrypted_size(self.padded_size(compressed_len))
+    }
+}
+
+impl Default for PaddingPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The size of an encrypted backup file whose padded, compressed contents are `padded_len` bytes.
+///
+/// The file consists of an IV, the AES-256-CBC ciphertext (including PKCS#7 padding, which
+/// always adds at least one byte), and an HMAC.
+pub fn encrypted_size(padded_len: u64) -> u64 {
+    const BLOCK_SIZE: u64 = AES_BLOCK_SIZE as u64;
+    let ciphertext_len = (padded_len / BLOCK_SIZE + 1) * BLOCK_SIZE;
+    AES_IV_SIZE as u64 + ciphertext_len + HMAC_LEN as u64
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(0 => 541)]
+    #[test_case(1 => 541)]
+    #[test_case(541 => 541)]
+    #[test_case(542 => 568)]
+    #[test_case(568 => 568)]
+    #[test_case(569 => 596)]
+    #[test_case(1_000_000 => 1_041_743)]
+    fn default_buckets(compressed_len: u64) -> u64 {
+        PaddingPolicy::DEFAULT.padded_size(compressed_len)
+    }
+
+    #[test]
+    fn padded_size_is_monotonic_and_sufficient() {
+        let mut previous = 0;
+        for len in (0..100_000).step_by(7) {
+            let padded = PaddingPolicy::DEFAULT.padded_size(len);
+            assert!(padded >= len);
+            assert!(padded >= previous);
+            previous = padded;
+        }
+    }
+
+    #[test]
+    fn no_padding() {
+        assert_eq!(PaddingPolicy::None.padded_size(1234), 1234);
+        let mut contents = vec![1; 10];
+        PaddingPolicy::None.pad(&mut contents);
+        assert_eq!(contents, [1; 10]);
+    }
+
+    #[test]
+    fn pad_appends_zeros() {
+        let policy = PaddingPolicy::Bucketed {
+            growth_factor: 2.0,
+            min_size: 4,
+        };
+        let mut contents = vec![1; 5];
+        policy.pad(&mut contents);
+        assert_eq!(contents, [1, 1, 1, 1, 1, 0, 0, 0]);
+    }
+
+    #[test_case(0 => 64)]
+    #[test_case(15 => 64)]
+    #[test_case(16 => 80)]
+    fn encrypted_sizes(padded_len: u64) -> u64 {
+        encrypted_size(padded_len)
+    }
+}
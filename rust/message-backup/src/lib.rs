@@ -8,6 +8,7 @@
 //! Contains code to read and validate message backup files.
 
 use futures::AsyncRead;
+use libsignal_core::{LimitExceeded, ParseLimits};
 use mediasan_common::AsyncSkip;
 use protobuf::Message as _;
 
@@ -17,11 +18,12 @@ use crate::frame::{
     HmacMismatchError, ReaderFactory, UnvalidatedHmacReader, VerifyHmac, VerifyHmacError,
 };
 use crate::key::MessageBackupKey;
-use crate::parse::VarintDelimitedReader;
+use crate::parse::{ParseError, VarintDelimitedReader};
 use crate::unknown::{FormatPath, PathPart, UnknownValue, VisitUnknownFieldsExt as _};
 
 pub mod args;
 pub mod backup;
+pub mod finding;
 pub mod frame;
 pub mod key;
 pub mod parse;
@@ -32,6 +34,7 @@ pub(crate) mod proto;
 pub struct BackupReader<R> {
     purpose: Purpose,
     reader: VarintDelimitedReader<R>,
+    limits: ParseLimits,
     pub visitor: fn(&dyn std::fmt::Debug),
 }
 
@@ -42,7 +45,9 @@ pub enum Error {
     /// {0}
     BackupCompletion(#[from] backup::CompletionError),
     /// {0}
-    Parse(#[from] parse::ParseError),
+    Parse(parse::ParseError),
+    /// {0}
+    LimitExceeded(#[from] LimitExceeded),
     /// no frames found
     NoFrames,
     /// invalid protobuf: {0}
@@ -55,6 +60,9 @@ pub enum Error {
 pub struct ReadResult<B> {
     pub result: Result<B, Error>,
     pub found_unknown_fields: Vec<FoundUnknownField>,
+    /// The index of the frame that was being processed when `result` became
+    /// an error, if the error can be attributed to a single frame.
+    pub error_frame_index: Option<usize>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -109,10 +117,12 @@ impl<R> ReadResult<R> {
         let Self {
             result,
             found_unknown_fields,
+            error_frame_index,
         } = self;
         ReadResult {
             found_unknown_fields,
             result: result.and_then(f),
+            error_frame_index,
         }
     }
 }
@@ -136,25 +146,64 @@ impl<R: AsyncRead + Unpin + VerifyHmac> BackupReader<R> {
     ) -> ReadResult<backup::PartialBackup<M>> {
         let Self {
             reader,
+            limits,
             visitor,
             purpose,
         } = self;
 
         let mut found_unknown_fields = Vec::new();
-        let result = read_all_frames(purpose, reader, visitor, &mut found_unknown_fields).await;
+        let mut frame_index = 0;
+        let result = read_all_frames(
+            purpose,
+            reader,
+            limits,
+            visitor,
+            &mut found_unknown_fields,
+            &mut frame_index,
+        )
+        .await;
+        let error_frame_index = match &result {
+            Err(
+                Error::BackupValidation(_)
+                | Error::Parse(_)
+                | Error::InvalidProtobuf(_)
+                | Error::LimitExceeded(_),
+            ) => Some(frame_index),
+            Ok(_) | Err(Error::BackupCompletion(_) | Error::NoFrames | Error::HmacMismatch(_)) => {
+                None
+            }
+        };
         ReadResult {
             found_unknown_fields,
             result,
+            error_frame_index,
         }
     }
 }
 
+impl<R: AsyncRead + Unpin> BackupReader<R> {
+    /// Replaces the limits applied while reading, which default to the process-wide
+    /// [`ParseLimits`] in effect when the reader was created.
+    ///
+    /// The message size limit applies to each frame and the decompressed size limit to all frames
+    /// together, both after decompression. The collection count limit applies to the number of
+    /// frames, including the backup info. The retained size limit applies to an estimate of the
+    /// memory used to hold the backup's contents until reading finishes. Exceeding any of them
+    /// stops reading with [`Error::LimitExceeded`].
+    pub fn with_limits(mut self, limits: ParseLimits) -> Self {
+        self.reader.set_limits(limits);
+        self.limits = limits;
+        self
+    }
+}
+
 impl<R: AsyncRead + Unpin> BackupReader<UnvalidatedHmacReader<R>> {
     pub fn new_unencrypted(reader: R, purpose: Purpose) -> Self {
         let reader = VarintDelimitedReader::new(UnvalidatedHmacReader::new(reader));
         Self {
             reader,
             purpose,
+            limits: libsignal_core::parse_limits(),
             visitor: |_| (),
         }
     }
@@ -170,6 +219,7 @@ impl<R: AsyncRead + AsyncSkip + Unpin> BackupReader<frame::FramesReader<R>> {
         Ok(Self {
             reader: VarintDelimitedReader::new(reader),
             purpose,
+            limits: libsignal_core::parse_limits(),
             visitor: |_| (),
         })
     }
@@ -178,8 +228,10 @@ impl<R: AsyncRead + AsyncSkip + Unpin> BackupReader<frame::FramesReader<R>> {
 async fn read_all_frames<M: backup::method::Method + backup::ReferencedTypes>(
     purpose: Purpose,
     mut reader: VarintDelimitedReader<impl AsyncRead + Unpin + VerifyHmac>,
+    limits: ParseLimits,
     mut visitor: impl FnMut(&dyn std::fmt::Debug),
     unknown_fields: &mut impl Extend<FoundUnknownField>,
+    frame_index: &mut usize,
 ) -> Result<backup::PartialBackup<M>, Error> {
     let mut add_found_unknown = |found_unknown: Vec<_>, index| {
         let iter = found_unknown
@@ -199,15 +251,23 @@ async fn read_all_frames<M: backup::method::Method + backup::ReferencedTypes>(
     add_found_unknown(backup_info.collect_unknown_fields(), 0);
 
     let mut backup = backup::PartialBackup::new(backup_info, purpose)?;
-    let mut frame_index = 1;
+    *frame_index = 1;
+    let mut retained_size = 0u64;
 
     while let Some(frame) = reader.read_next().await? {
+        limits.check_collection_count(*frame_index + 1)?;
         let frame_proto = proto::backup::Frame::parse_from_bytes(&frame)?;
         visitor(&frame_proto);
-        add_found_unknown(frame_proto.collect_unknown_fields(), frame_index);
-        frame_index += 1;
+        add_found_unknown(frame_proto.collect_unknown_fields(), *frame_index);
+
+        // The encoded size is a rough but cheap stand-in for the size of the parsed contents.
+        if backup::PartialBackup::<M>::retains(&frame_proto) {
+            retained_size = retained_size.saturating_add(frame.len() as u64);
+            limits.check_retained_size(retained_size)?;
+        }
 
-        backup.add_frame(frame_proto)?
+        backup.add_frame(frame_proto)?;
+        *frame_index += 1;
     }
 
     // Before reporting success, check that the HMAC still matches. This
@@ -221,7 +281,19 @@ impl From<VerifyHmacError> for Error {
     fn from(value: VerifyHmacError) -> Self {
         match value {
             VerifyHmacError::HmacMismatch(e) => e.into(),
-            VerifyHmacError::Io(e) => Self::Parse(e.into()),
+            VerifyHmacError::Io(e) => ParseError::from(e).into(),
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(value: ParseError) -> Self {
+        // Limits are enforced by readers, which can only report them as I/O errors.
+        if let ParseError::Io(e) = &value {
+            if let Some(limit) = e.get_ref().and_then(|e| e.downcast_ref::<LimitExceeded>()) {
+                return Self::LimitExceeded(*limit);
+            }
         }
+        Self::Parse(value)
     }
 }
@@ -7,6 +7,7 @@ use std::fmt::Debug;
 
 use arrayvec::ArrayVec;
 use futures::io::{AsyncRead, AsyncReadExt as _};
+use libsignal_core::ParseLimits;
 
 #[derive(Debug, displaydoc::Display, thiserror::Error)]
 pub enum ParseError {
@@ -21,6 +22,8 @@ const VARINT_MAX_LENGTH: usize = 10;
 pub(crate) struct VarintDelimitedReader<R> {
     reader: R,
     buffer: ArrayVec<u8, VARINT_MAX_LENGTH>,
+    limits: ParseLimits,
+    total_message_size: u64,
 }
 
 impl<R: AsyncRead + Unpin> VarintDelimitedReader<R> {
@@ -28,16 +31,34 @@ impl<R: AsyncRead + Unpin> VarintDelimitedReader<R> {
         Self {
             reader,
             buffer: ArrayVec::new(),
+            limits: libsignal_core::parse_limits(),
+            total_message_size: 0,
         }
     }
 
+    /// Replaces the limits on the size of each message and on all messages together.
+    pub(crate) fn set_limits(&mut self, limits: ParseLimits) {
+        self.limits = limits;
+    }
+
     pub(crate) async fn read_next(&mut self) -> Result<Option<Box<[u8]>>, ParseError> {
         let length = match self.read_next_varint().await? {
             None => return Ok(None),
             Some(length) => length,
         };
-
-        let Self { reader, buffer } = self;
+        let Self {
+            reader,
+            buffer,
+            limits,
+            total_message_size,
+        } = self;
+
+        // Check before allocating, so that a hostile length can't be used to exhaust memory.
+        *total_message_size = total_message_size.saturating_add(length as u64);
+        limits
+            .check_message_size(length)
+            .and_then(|()| limits.check_decompressed_size(*total_message_size))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
 
         // Read `length` bytes, first from the buffer, then from the reader.
         let mut buf = Vec::with_capacity(length);
@@ -60,7 +81,7 @@ impl<R: AsyncRead + Unpin> VarintDelimitedReader<R> {
     }
 
     async fn read_next_varint(&mut self) -> Result<Option<usize>, ParseError> {
-        let Self { buffer, reader } = self;
+        let Self { buffer, reader, .. } = self;
 
         fill_buffer_from_reader(reader, buffer).await?;
 
@@ -158,6 +179,38 @@ mod test {
             Err(ParseError::Io(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof
         );
     }
+
+    #[test]
+    fn read_length_delimited_over_limits() {
+        // Three messages, of lengths 3, 2, and 3.
+        let frames = [3, 1, 2, 3, 2, 4, 5, 3, 6, 7, 8];
+        let read_with_limits = |limits: ParseLimits| {
+            let mut reader = VarintDelimitedReader::new(frames.as_slice());
+            reader.set_limits(limits);
+            block_on(async move {
+                while reader.read_next().await?.is_some() {}
+                Ok::<_, ParseError>(())
+            })
+        };
+
+        assert_matches!(read_with_limits(ParseLimits::DEFAULT), Ok(()));
+        for limits in [
+            ParseLimits {
+                max_message_size: 2,
+                ..ParseLimits::DEFAULT
+            },
+            ParseLimits {
+                max_decompressed_size: 7,
+                ..ParseLimits::DEFAULT
+            },
+        ] {
+            assert_matches!(
+                read_with_limits(limits),
+                Err(ParseError::Io(e)) if e.kind() == std::io::ErrorKind::InvalidData,
+                "{limits:?}"
+            );
+        }
+    }
     struct MessageAndLen<const L: usize, const M: usize> {
         varint: [u8; L],
         message: [u8; M],
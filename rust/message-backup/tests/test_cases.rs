@@ -11,11 +11,12 @@ use dir_test::{dir_test, Fixture};
 use futures::io::Cursor;
 use futures::AsyncRead;
 use libsignal_account_keys::BackupKey;
-use libsignal_core::Aci;
+use libsignal_core::{Aci, ParseLimits};
 use libsignal_message_backup::backup::Purpose;
+use libsignal_message_backup::finding::{FindingKind, ValidationFinding};
 use libsignal_message_backup::frame::{FileReaderFactory, VerifyHmac};
 use libsignal_message_backup::key::MessageBackupKey;
-use libsignal_message_backup::{BackupReader, ReadResult};
+use libsignal_message_backup::{BackupReader, Error, ReadResult};
 
 const BACKUP_PURPOSE: Purpose = Purpose::RemoteBackup;
 
@@ -77,6 +78,50 @@ fn serialized_account_settings_is_valid() {
     pretty_assertions::assert_str_eq!(canonical_repr, expected_canonical_str)
 }
 
+#[test]
+fn exceeding_limits_stops_reading() {
+    let binproto = include_bytes!("res/canonical-backup.binproto");
+
+    for limits in [
+        ParseLimits {
+            max_message_size: 16,
+            ..ParseLimits::DEFAULT
+        },
+        ParseLimits {
+            max_decompressed_size: 64,
+            ..ParseLimits::DEFAULT
+        },
+        ParseLimits {
+            max_retained_size: 64,
+            ..ParseLimits::DEFAULT
+        },
+        ParseLimits {
+            max_collection_count: 2,
+            ..ParseLimits::DEFAULT
+        },
+    ] {
+        let reader = BackupReader::new_unencrypted(Cursor::new(binproto), BACKUP_PURPOSE)
+            .with_limits(limits);
+        let read_result = futures::executor::block_on(reader.validate_all());
+        assert_matches!(
+            read_result.result,
+            Err(Error::LimitExceeded(_)),
+            "{limits:?}"
+        );
+        assert_matches!(
+            read_result.findings().as_slice(),
+            [
+                ValidationFinding {
+                    kind: FindingKind::LimitExceeded,
+                    frame_index: Some(_),
+                    ..
+                },
+                ..
+            ]
+        );
+    }
+}
+
 const ENCRYPTED_SOURCE_SUFFIX: &str = ".source.jsonproto";
 #[dir_test(
         dir: "$CARGO_MANIFEST_DIR/tests/res/test-cases",
@@ -195,6 +240,7 @@ fn invalid_jsonproto(input: Fixture<PathBuf>) {
     let ReadResult {
         result,
         found_unknown_fields: _,
+        error_frame_index: _,
     } = futures::executor::block_on(reader.read_all());
 
     let text = result.expect_err("unexpectedly valid").to_string();
@@ -236,6 +282,7 @@ fn validate(mut reader: BackupReader<impl AsyncRead + Unpin + VerifyHmac>) {
     let ReadResult {
         result,
         found_unknown_fields,
+        error_frame_index: _,
     } = futures::executor::block_on(reader.read_all());
     assert_eq!(found_unknown_fields, Vec::new());
 
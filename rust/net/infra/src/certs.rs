@@ -4,11 +4,12 @@
 //
 
 use std::borrow::Cow;
+use std::sync::{Arc, OnceLock};
 
 use boring_signal::error::ErrorStack;
 use boring_signal::ssl::{SslAlert, SslConnectorBuilder, SslVerifyError, SslVerifyMode};
 use boring_signal::x509::store::X509StoreBuilder;
-use boring_signal::x509::X509;
+use boring_signal::x509::{X509Ref, X509};
 use rustls::client::danger::ServerCertVerifier;
 
 use crate::host::Host;
@@ -27,23 +28,151 @@ impl From<ErrorStack> for Error {
     }
 }
 
+/// The server's verified certificate chain didn't include any of the pinned public keys.
+#[derive(thiserror::Error, Debug, displaydoc::Display)]
+pub struct PinningFailure;
+
+/// SHA-256 digest of a certificate's DER-encoded SubjectPublicKeyInfo.
+pub type SpkiHash = [u8; 32];
+
+/// A set of public keys, at least one of which must appear in a server's verified certificate
+/// chain.
+///
+/// Pins are checked against the path that was built from the server's certificate to a trusted
+/// root, not against the certificates the server sent, since a server can send any extra
+/// certificates it likes. Any certificate on that path may match, so pins can be for the leaf or
+/// for an intermediate. The exception is [`RootCertificates::Native`]: the platform verifier
+/// doesn't report the path it built, so there only the leaf can be pinned.
+///
+/// To rotate keys without breaking existing connections, pin both the old and new keys until the
+/// old one is no longer in use.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpkiPins(Arc<[SpkiHash]>);
+
+impl SpkiPins {
+    /// Returns `None` if `pins` is empty, since that would reject every server.
+    pub fn new(pins: impl IntoIterator<Item = SpkiHash>) -> Option<Self> {
+        let pins: Arc<[SpkiHash]> = pins.into_iter().collect();
+        (!pins.is_empty()).then_some(Self(pins))
+    }
+
+    /// Computes the pin for the public key in a DER-encoded certificate.
+    pub fn hash_of_certificate(der: &[u8]) -> Result<SpkiHash, Error> {
+        Ok(spki_hash(&X509::from_der(der)?)?)
+    }
+
+    fn matches<'a>(&self, verified_chain: impl IntoIterator<Item = &'a X509Ref>) -> bool {
+        verified_chain.into_iter().any(|cert| {
+            spki_hash(cert)
+                .map(|hash| self.0.contains(&hash))
+                .unwrap_or(false)
+        })
+    }
+}
+
+/// Checks one connection's verified certificate chain against [`SpkiPins`].
+///
+/// The check happens while the chain is verified, and rejecting it fails the handshake. The
+/// outcome is recorded so that it can be reported as a [`PinningFailure`] rather than a generic
+/// handshake error.
+#[derive(Debug, Clone)]
+pub struct PinCheck {
+    pins: SpkiPins,
+    matched: Arc<OnceLock<bool>>,
+}
+
+impl PinCheck {
+    fn new(pins: SpkiPins) -> Self {
+        Self {
+            pins,
+            matched: Default::default(),
+        }
+    }
+
+    /// Records whether `verified_chain` contains a pinned key, and returns whether it did.
+    fn record<'a>(&self, verified_chain: impl IntoIterator<Item = &'a X509Ref>) -> bool {
+        let matched = self.pins.matches(verified_chain);
+        // A handshake only verifies its chain once; if that somehow changes, the first result wins.
+        *self.matched.get_or_init(|| matched)
+    }
+
+    /// Whether the chain was verified and rejected because it contained none of the pinned keys.
+    pub fn rejected(&self) -> bool {
+        self.matched.get() == Some(&false)
+    }
+
+    /// Succeeds only if the chain was verified and contained one of the pinned keys.
+    pub fn result(&self) -> Result<(), PinningFailure> {
+        match self.matched.get() {
+            Some(true) => Ok(()),
+            Some(false) | None => Err(PinningFailure),
+        }
+    }
+}
+
+fn spki_hash(cert: &X509Ref) -> Result<SpkiHash, ErrorStack> {
+    let spki = cert.public_key()?.public_key_to_der()?;
+    Ok(boring_signal::sha::sha256(&spki))
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum RootCertificates {
     Native,
     FromStaticDers(&'static [&'static [u8]]),
     FromDer(Cow<'static, [u8]>),
+    /// Trusts the same certificates as `roots`, but additionally requires the server's chain to
+    /// contain one of `pins`.
+    Pinned {
+        roots: Box<RootCertificates>,
+        pins: SpkiPins,
+    },
 }
 
 impl RootCertificates {
+    /// Restricts `self` to servers presenting one of `pins`, replacing any existing pins.
+    pub fn with_pins(self, pins: SpkiPins) -> Self {
+        let roots = match self {
+            RootCertificates::Pinned { roots, pins: _ } => roots,
+            other => Box::new(other),
+        };
+        RootCertificates::Pinned { roots, pins }
+    }
+
+    /// Sets up `connector` to verify servers against these certificates.
+    ///
+    /// If there are pins, the returned [`PinCheck`] reports whether the server's chain matched
+    /// them once the handshake is done.
     pub fn apply_to_connector(
         &self,
         connector: &mut SslConnectorBuilder,
         host: Host<&str>,
+    ) -> Result<Option<PinCheck>, Error> {
+        let (roots, pin_check) = match self {
+            RootCertificates::Pinned { roots, pins } => {
+                (&**roots, Some(PinCheck::new(pins.clone())))
+            }
+            RootCertificates::Native
+            | RootCertificates::FromStaticDers(_)
+            | RootCertificates::FromDer(_) => (self, None),
+        };
+        roots.apply_roots_to_connector(connector, host, pin_check.clone())?;
+        Ok(pin_check)
+    }
+
+    fn apply_roots_to_connector(
+        &self,
+        connector: &mut SslConnectorBuilder,
+        host: Host<&str>,
+        pin_check: Option<PinCheck>,
     ) -> Result<(), Error> {
         // See below.
         let lifetime_extended_single_der: &[u8];
 
         let ders: &[&[u8]] = match self {
+            // `with_pins` never nests pins, so the outer ones are all there is to check.
+            RootCertificates::Pinned { roots, pins: _ } => {
+                return roots.apply_roots_to_connector(connector, host, pin_check);
+            }
             RootCertificates::Native => {
                 let mut verifier = rustls_platform_verifier::Verifier::new();
                 if cfg!(target_os = "linux")
@@ -55,7 +184,7 @@ impl RootCertificates {
                     // dependency on ring.
                     verifier.set_provider(rustls::crypto::ring::default_provider().into())
                 }
-                return set_up_platform_verifier(connector, host, verifier);
+                return set_up_platform_verifier(connector, host, verifier, pin_check);
             }
             RootCertificates::FromStaticDers(ders) => ders,
             RootCertificates::FromDer(der) => {
@@ -70,16 +199,32 @@ impl RootCertificates {
             store_builder.add_cert(X509::from_der(der)?)?;
         }
         connector.set_verify_cert_store(store_builder.build())?;
+        if let Some(pin_check) = pin_check {
+            connector.set_verify_callback(SslVerifyMode::PEER, move |preverified, context| {
+                // The callback runs once for each certificate on the verified path, from the root
+                // down to the leaf at depth 0. By then the context's chain is that whole path.
+                if !preverified || context.error_depth() != 0 {
+                    return preverified;
+                }
+                context
+                    .chain()
+                    .is_some_and(|verified_chain| pin_check.record(verified_chain))
+            });
+        }
         Ok(())
     }
 }
 
 /// Configures [rustls_platform_verifier] as a BoringSSL [custom verify
 /// callback](boring::ssl::SslContextBuilder::set_custom_verify_callback).
+///
+/// If there's a `pin_check`, it's applied to the leaf certificate once the platform has accepted
+/// the chain.
 fn set_up_platform_verifier(
     connector: &mut SslConnectorBuilder,
     host: Host<&str>,
     verifier: impl ServerCertVerifier + 'static,
+    pin_check: Option<PinCheck>,
 ) -> Result<(), Error> {
     let host_as_server_name = match host {
         Host::Domain(host_name) => rustls::pki_types::ServerName::try_from(host_name)
@@ -170,6 +315,19 @@ fn set_up_platform_verifier(
                 })
             })?;
 
+        if let Some(pin_check) = &pin_check {
+            // The platform doesn't say which path it built, but the leaf it just accepted for this
+            // host is always on it.
+            let leaf = ssl.peer_cert_chain().and_then(|chain| chain.get(0));
+            if !pin_check.record(leaf) {
+                log::debug!(
+                    "TLS certificate for {} did not match any pinned key",
+                    host_as_server_name.to_str()
+                );
+                return Err(SslVerifyError::Invalid(SslAlert::BAD_CERTIFICATE));
+            }
+        }
+
         Ok(())
     });
 
@@ -209,6 +367,7 @@ mod test {
             &mut ssl,
             Host::Domain(SERVER_HOSTNAME),
             Arc::into_inner(verifier).expect("only one referent"),
+            None,
         )
         .expect("valid");
 
@@ -243,6 +402,7 @@ mod test {
             &mut ssl,
             Host::Domain(SERVER_HOSTNAME),
             Arc::into_inner(verifier).expect("only one referent"),
+            None,
         )
         .expect("valid");
 
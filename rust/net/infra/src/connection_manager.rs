@@ -3,13 +3,14 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
-use std::cmp::{max, min};
+use std::cmp::max;
 use std::fmt::Debug;
 use std::future::Future;
+use std::num::NonZeroU16;
 use std::ops::Add;
 use std::panic::RefUnwindSafe;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use async_trait::async_trait;
 use itertools::Itertools;
@@ -17,7 +18,7 @@ use tokio::sync::Mutex;
 use tokio::time::{timeout_at, Instant};
 
 use crate::errors::LogSafeDisplay;
-use crate::timeouts::{CONNECTION_ROUTE_COOLDOWN_INTERVALS, CONNECTION_ROUTE_MAX_COOLDOWN};
+use crate::timeouts::{CONNECTION_ROUTE_INITIAL_COOLDOWN, CONNECTION_ROUTE_MAX_COOLDOWN};
 use crate::utils::{EventSubscription, ObservableEvent};
 use crate::ConnectionParams;
 
@@ -78,21 +79,74 @@ where
     }
 }
 
+/// How long to wait before retrying a route that keeps failing to connect.
+///
+/// The first failure on a route is retried immediately. After that, each consecutive failure waits
+/// `initial_delay * multiplier^(n - 1)`, capped at `max_delay`. If `jitter` is nonzero, each delay
+/// is then reduced by a random fraction of up to `jitter`, so that many clients that failed at the
+/// same time don't all come back at once.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryPolicy {
+    pub initial_delay: Duration,
+    pub multiplier: f64,
+    /// A fraction between 0 and 1.
+    pub jitter: f64,
+    pub max_delay: Duration,
+    /// How many times a single route may be attempted within one connect operation before moving
+    /// on to the next route. `None` means to keep going until the route enters a cooldown.
+    pub max_attempts: Option<NonZeroU16>,
+}
+
+impl RetryPolicy {
+    pub const DEFAULT: Self = Self {
+        initial_delay: CONNECTION_ROUTE_INITIAL_COOLDOWN,
+        multiplier: 2.0,
+        jitter: 0.0,
+        max_delay: CONNECTION_ROUTE_MAX_COOLDOWN,
+        max_attempts: None,
+    };
+
+    /// The cooldown to apply after a failure that follows `previous_fails` consecutive failures.
+    fn cooldown(&self, previous_fails: u16, rng: &mut impl rand::Rng) -> Duration {
+        let Some(exponent) = previous_fails.checked_sub(1) else {
+            return Duration::ZERO;
+        };
+        let unjittered = Duration::try_from_secs_f64(
+            self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent.into()),
+        )
+        .unwrap_or(self.max_delay)
+        .min(self.max_delay);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return unjittered;
+        }
+        unjittered.mul_f64(1.0 - rng.gen_range(0.0..=jitter))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
 #[derive(Clone, Debug)]
 struct ThrottlingConnectionManagerState {
     consecutive_fails: u16,
     next_attempt: Instant,
     latest_attempt: Instant,
+    retry_policy: RetryPolicy,
     #[cfg(test)]
     reset_counter: u8,
 }
 
 impl ThrottlingConnectionManagerState {
-    fn new(now: Instant) -> Self {
+    fn new(now: Instant, retry_policy: RetryPolicy) -> Self {
         Self {
             consecutive_fails: 0,
             next_attempt: now,
             latest_attempt: now - Duration::from_nanos(1),
+            retry_policy,
             #[cfg(test)]
             reset_counter: 0,
         }
@@ -117,17 +171,11 @@ impl ThrottlingConnectionManagerState {
             }
         } else if attempt_start_time > s.latest_attempt || s.consecutive_fails > 0 {
             s.latest_attempt = max(attempt_start_time, s.latest_attempt);
-            let idx: usize = s.consecutive_fails.into();
-            let cooldown_interval = CONNECTION_ROUTE_COOLDOWN_INTERVALS
-                .get(idx)
-                .unwrap_or(&CONNECTION_ROUTE_MAX_COOLDOWN);
-            s.next_attempt = Instant::now() + *cooldown_interval;
-            s.consecutive_fails = min(
-                s.consecutive_fails.saturating_add(1),
-                (CONNECTION_ROUTE_COOLDOWN_INTERVALS.len() - 1)
-                    .try_into()
-                    .unwrap(),
-            );
+            let cooldown_interval = s
+                .retry_policy
+                .cooldown(s.consecutive_fails, &mut rand::thread_rng());
+            s.next_attempt = Instant::now() + cooldown_interval;
+            s.consecutive_fails = s.consecutive_fails.saturating_add(1);
         }
         s
     }
@@ -145,12 +193,13 @@ impl ThrottlingConnectionManagerState {
             return;
         }
 
+        let retry_policy = self.retry_policy;
         let Self {
             latest_attempt,
             #[cfg(test)]
             reset_counter,
             ..
-        } = std::mem::replace(self, Self::new(network_change_time));
+        } = std::mem::replace(self, Self::new(network_change_time, retry_policy));
 
         #[cfg(test)]
         {
@@ -171,13 +220,25 @@ impl ThrottlingConnectionManagerState {
     }
 }
 
+/// How the most recent connection attempt on a route turned out.
+///
+/// Unlike the throttling state, this is wall-clock based and isn't reset by network changes, so it
+/// can be remembered across app launches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RouteOutcome {
+    pub succeeded: bool,
+    pub at: SystemTime,
+}
+
 /// A connection manager that only attempts one route (i.e. one [ConnectionParams]).
 ///
 /// It keeps track of consecutive failed attempts and after each failure waits for a duration
-/// chosen according to [CONNECTION_ROUTE_COOLDOWN_INTERVALS] list.
+/// chosen according to its [RetryPolicy].
 #[derive(Clone, Debug)]
 pub struct SingleRouteThrottlingConnectionManager<C = ConnectionParams> {
     state: Arc<Mutex<ThrottlingConnectionManagerState>>,
+    // Kept separately from `state` so it can be read without going through the async mutex.
+    latest_outcome: Arc<std::sync::Mutex<Option<RouteOutcome>>>,
     connection_params: C,
     connection_timeout: Duration,
     _network_changed_subscription: Arc<EventSubscription>,
@@ -191,11 +252,45 @@ pub struct SingleRouteThrottlingConnectionManager<C = ConnectionParams> {
 #[derive(Clone)]
 pub struct MultiRouteConnectionManager<M = SingleRouteThrottlingConnectionManager> {
     route_managers: Vec<M>,
+    max_attempts_per_route: Option<NonZeroU16>,
 }
 
 impl<M> MultiRouteConnectionManager<M> {
     pub fn new(route_managers: Vec<M>) -> Self {
-        Self { route_managers }
+        Self {
+            route_managers,
+            max_attempts_per_route: None,
+        }
+    }
+
+    /// Limits how many times each route is attempted before moving on to the next one.
+    ///
+    /// See [`RetryPolicy::max_attempts`].
+    pub fn with_max_attempts_per_route(self, max_attempts: Option<NonZeroU16>) -> Self {
+        Self {
+            max_attempts_per_route: max_attempts,
+            ..self
+        }
+    }
+
+    pub fn route_managers(&self) -> &[M] {
+        &self.route_managers
+    }
+}
+
+impl MultiRouteConnectionManager {
+    /// Moves the routes for which `is_preferred` returns `true` to the front, keeping the relative
+    /// order of the routes otherwise.
+    pub fn with_preferred_routes(self, is_preferred: impl Fn(&ConnectionParams) -> bool) -> Self {
+        let Self {
+            mut route_managers,
+            max_attempts_per_route,
+        } = self;
+        route_managers.sort_by_key(|manager| !is_preferred(manager.connection_params()));
+        Self {
+            route_managers,
+            max_attempts_per_route,
+        }
     }
 }
 
@@ -224,13 +319,20 @@ where
     {
         let mut wait_until = None;
         for route_manager in self.route_managers.iter() {
-            match retry_connect_until_cooldown(route_manager, &connection_fn).await {
+            match retry_connect_until_cooldown(
+                route_manager,
+                &connection_fn,
+                self.max_attempts_per_route,
+            )
+            .await
+            {
                 Ok(t) => return ConnectionAttemptOutcome::Attempted(Ok(t)),
                 Err(RetryError::WaitUntil(i)) => {
                     wait_until = Some(
                         wait_until.map_or(i, |earliest_retry| Instant::min(i, earliest_retry)),
                     );
                 }
+                Err(RetryError::AttemptsExhausted) => {}
                 Err(RetryError::Fatal(e)) => return ConnectionAttemptOutcome::Attempted(Err(e)),
             }
         }
@@ -254,6 +356,8 @@ where
 pub enum RetryError<E> {
     /// Connection can be attempted again at a given Instant
     WaitUntil(Instant),
+    /// The route was attempted as many times as allowed without success
+    AttemptsExhausted,
     /// Connection failed due to an issue that retries will not solve
     Fatal(E),
 }
@@ -280,6 +384,7 @@ pub trait ErrorClassifier {
 async fn retry_connect_until_cooldown<'a, T, E, Fun, Fut>(
     route_manager: &'a impl ConnectionManager,
     connection_fn: &Fun,
+    max_attempts: Option<NonZeroU16>,
 ) -> Result<T, RetryError<E>>
 where
     T: Send,
@@ -287,8 +392,19 @@ where
     Fun: Fn(&'a ConnectionParams) -> Fut + Send + Sync,
     Fut: Future<Output = Result<T, E>> + Send,
 {
+    let mut attempts: u16 = 0;
     loop {
+        if max_attempts.is_some_and(|max| attempts >= max.get()) {
+            log::info!(
+                "Giving up on route after {attempts} attempts ({})",
+                route_manager.describe_for_logging()
+            );
+            return Err(RetryError::AttemptsExhausted);
+        }
         let result = route_manager.connect_or_wait(connection_fn).await;
+        if !matches!(result, ConnectionAttemptOutcome::WaitUntil(_)) {
+            attempts = attempts.saturating_add(1);
+        }
         match result {
             ConnectionAttemptOutcome::Attempted(Ok(r)) => {
                 return Ok(r);
@@ -337,9 +453,26 @@ impl<C> SingleRouteThrottlingConnectionManager<C> {
         connection_params: C,
         connection_timeout: Duration,
         network_changed_event: &ObservableEvent,
+    ) -> Self {
+        Self::new_with_retry_policy(
+            connection_params,
+            connection_timeout,
+            RetryPolicy::DEFAULT,
+            network_changed_event,
+        )
+    }
+
+    pub fn new_with_retry_policy(
+        connection_params: C,
+        connection_timeout: Duration,
+        retry_policy: RetryPolicy,
+        network_changed_event: &ObservableEvent,
     ) -> Self {
         let now = Instant::now();
-        let state = Arc::new(Mutex::new(ThrottlingConnectionManagerState::new(now)));
+        let state = Arc::new(Mutex::new(ThrottlingConnectionManagerState::new(
+            now,
+            retry_policy,
+        )));
 
         // Make sure that we don't have a reference cycle subscribing to the network change event:
         // - the connection manager's subscription to the event will live as long as it does...
@@ -369,10 +502,20 @@ impl<C> SingleRouteThrottlingConnectionManager<C> {
             connection_params,
             connection_timeout,
             state,
+            latest_outcome: Default::default(),
             _network_changed_subscription: Arc::new(network_changed_subscription),
         }
     }
 
+    pub fn connection_params(&self) -> &C {
+        &self.connection_params
+    }
+
+    /// Returns how the most recent connection attempt on this route went, if there has been one.
+    pub fn latest_outcome(&self) -> Option<RouteOutcome> {
+        *self.latest_outcome.lock().expect("not poisoned")
+    }
+
     pub(crate) async fn connect_or_wait<'a, T, E, Fun, Fut>(
         &'a self,
         connection_fn: Fun,
@@ -403,6 +546,10 @@ impl<C> SingleRouteThrottlingConnectionManager<C> {
             .map_or(false, |r| r.is_ok());
         let new_state = s.clone().after_attempt(was_successful, attempt_start_time);
         *s = new_state;
+        *self.latest_outcome.lock().expect("not poisoned") = Some(RouteOutcome {
+            succeeded: was_successful,
+            at: SystemTime::now(),
+        });
 
         connection_result_or_timeout.map_or(ConnectionAttemptOutcome::TimedOut, |result| {
             ConnectionAttemptOutcome::Attempted(result)
@@ -653,13 +800,74 @@ mod test {
             if later
                 .checked_duration_since(Instant::now())
                 .expect("future")
-                <= CONNECTION_ROUTE_COOLDOWN_INTERVALS[1]
+                <= RetryPolicy::DEFAULT.initial_delay
+        );
+    }
+
+    #[test]
+    fn default_retry_policy_cooldowns() {
+        let mut rng = rand::thread_rng();
+        let cooldowns = (0..10)
+            .map(|previous_fails| RetryPolicy::DEFAULT.cooldown(previous_fails, &mut rng))
+            .collect_vec();
+        assert_eq!(
+            cooldowns,
+            [0, 1, 2, 4, 8, 16, 32, 64, 64, 64].map(Duration::from_secs)
+        );
+        assert_eq!(
+            RetryPolicy::DEFAULT.cooldown(u16::MAX, &mut rng),
+            CONNECTION_ROUTE_MAX_COOLDOWN
+        );
+    }
+
+    #[test]
+    fn retry_policy_jitter_stays_within_bounds() {
+        let policy = RetryPolicy {
+            initial_delay: Duration::from_secs(10),
+            multiplier: 3.0,
+            jitter: 0.5,
+            max_delay: Duration::from_secs(60),
+            max_attempts: None,
+        };
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let cooldown = policy.cooldown(2, &mut rng);
+            assert!(cooldown <= Duration::from_secs(30), "{cooldown:?}");
+            assert!(cooldown >= Duration::from_secs(15), "{cooldown:?}");
+            let capped = policy.cooldown(10, &mut rng);
+            assert!(capped <= policy.max_delay, "{capped:?}");
+            assert!(capped >= policy.max_delay / 2, "{capped:?}");
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn single_route_manager_uses_retry_policy() {
+        let policy = RetryPolicy {
+            initial_delay: Duration::from_secs(5),
+            ..RetryPolicy::DEFAULT
+        };
+        let manager = SingleRouteThrottlingConnectionManager::new_with_retry_policy(
+            example_connection_params("chat.staging.signal.org"),
+            TIMEOUT_DURATION,
+            policy,
+            &ObservableEvent::default(),
+        );
+        for _ in 0..2 {
+            let _attempt_outcome: ConnectionAttemptOutcome<(), TestError> = manager
+                .connect_or_wait(|_| future::ready(Err(TestError::Expected)))
+                .await;
+        }
+        let attempt_outcome: ConnectionAttemptOutcome<(), TestError> =
+            manager.connect_or_wait(|_| future::ready(Ok(()))).await;
+        assert_matches!(
+            attempt_outcome,
+            ConnectionAttemptOutcome::WaitUntil(later) if later == Instant::now() + policy.initial_delay
         );
     }
 
     #[tokio::test(flavor = "current_thread", start_paused = true)]
     async fn network_resets_consider_latest_attempt_time() {
-        let mut state = ThrottlingConnectionManagerState::new(Instant::now());
+        let mut state = ThrottlingConnectionManagerState::new(Instant::now(), RetryPolicy::DEFAULT);
         state = state.clone().after_attempt(false, Instant::now());
         assert_eq!(state.consecutive_fails, 1);
         assert_eq!(state.reset_counter, 0);
@@ -691,12 +899,60 @@ mod test {
         // (If we are more precise in the future, please update this test accordingly.)
         assert_eq!(state.consecutive_fails, 1);
         assert_eq!(state.latest_attempt, latest_attempt);
-        assert_eq!(
-            state.next_attempt,
-            Instant::now() + CONNECTION_ROUTE_COOLDOWN_INTERVALS[0]
+        assert_eq!(state.next_attempt, Instant::now());
+    }
+
+    #[tokio::test]
+    async fn single_route_manager_records_latest_outcome() {
+        let manager = SingleRouteThrottlingConnectionManager::new(
+            example_connection_params("chat.staging.signal.org"),
+            TIMEOUT_DURATION,
+            &ObservableEvent::default(),
+        );
+        assert_eq!(manager.latest_outcome(), None);
+
+        let _: ConnectionAttemptOutcome<(), TestError> =
+            manager.connect_or_wait(|_| future::ready(Ok(()))).await;
+        assert_matches!(
+            manager.latest_outcome(),
+            Some(RouteOutcome {
+                succeeded: true,
+                ..
+            })
+        );
+
+        let _: ConnectionAttemptOutcome<(), TestError> = manager
+            .connect_or_wait(|_| future::ready(Err(TestError::Expected)))
+            .await;
+        assert_matches!(
+            manager.latest_outcome(),
+            Some(RouteOutcome {
+                succeeded: false,
+                ..
+            })
         );
     }
 
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn multi_route_manager_tries_preferred_routes_first() {
+        let managers = [ROUTE_1, ROUTE_2, ROUTE_THAT_TIMES_OUT].map(|route| {
+            SingleRouteThrottlingConnectionManager::new(
+                example_connection_params(route),
+                TIMEOUT_DURATION,
+                &ObservableEvent::default(),
+            )
+        });
+        let multi_route_manager = MultiRouteConnectionManager::new(managers.into())
+            .with_preferred_routes(|params| &*params.http_host != ROUTE_1);
+
+        let hosts = multi_route_manager
+            .route_managers()
+            .iter()
+            .map(|manager| &*manager.connection_params().http_host)
+            .collect_vec();
+        assert_eq!(hosts, [ROUTE_2, ROUTE_THAT_TIMES_OUT, ROUTE_1]);
+    }
+
     #[tokio::test(flavor = "current_thread", start_paused = true)]
     async fn multi_route_manager_picks_working_route() {
         let manager_1 = SingleRouteThrottlingConnectionManager::new(
@@ -889,6 +1145,24 @@ mod test {
         );
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn multi_route_manager_respects_max_attempts_per_route() {
+        let connection_params = example_connection_params(ROUTE_1);
+        let first_manager = CooldownAfterSomeAttempts::new(5, connection_params.clone());
+        let second_manager = CooldownAfterSomeAttempts::new(3, connection_params);
+        let multi_route_manager =
+            MultiRouteConnectionManager::new(vec![first_manager.clone(), second_manager.clone()])
+                .with_max_attempts_per_route(Some(nonzero!(2u16)));
+        let res = multi_route_manager
+            .connect_or_wait(|connection_params| {
+                simulate_connect(connection_params, Some(TestError::Expected))
+            })
+            .await;
+        assert_matches!(res, ConnectionAttemptOutcome::TimedOut);
+        assert_eq!(2, first_manager.attempts_made.load(Ordering::Relaxed));
+        assert_eq!(2, second_manager.attempts_made.load(Ordering::Relaxed));
+    }
+
     #[tokio::test(start_paused = true)]
     async fn multi_route_manager_propagates_post_connection_failure() {
         let connection_params = example_connection_params(ROUTE_1);
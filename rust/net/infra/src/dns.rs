@@ -68,6 +68,8 @@ impl Default for DnsResolverState {
 #[derive(Clone, Debug)]
 pub struct DnsResolver {
     lookup_options: Arc<[LookupOption]>,
+    /// The resolver in `lookup_options` that caches its results, if there is one.
+    caching_resolver: Option<CustomDnsResolver<DohTransport>>,
     state: Arc<Mutex<DnsResolverState>>,
 }
 
@@ -92,6 +94,7 @@ impl DnsResolver {
 
         DnsResolver {
             lookup_options,
+            caching_resolver: None,
             state: Default::default(),
         }
     }
@@ -108,6 +111,7 @@ impl DnsResolver {
                 lookup: Box::new(StaticDnsMap(static_map)),
                 timeout_after: Duration::from_millis(1),
             }]),
+            caching_resolver: None,
             state: Default::default(),
         }
     }
@@ -131,15 +135,13 @@ impl DnsResolver {
             http_request_decorator: HttpRequestDecoratorSeq::default(),
             connection_confirmation_header: None,
         };
-        let custom_resolver = Box::new(CustomDnsResolver::<DohTransport>::new(
-            connection_params,
-            network_change_event,
-        ));
+        let custom_resolver =
+            CustomDnsResolver::<DohTransport>::new(connection_params, network_change_event);
         let fallback_lookups = DNS_FALLBACK_LOOKUP_TIMEOUTS
             .iter()
             .copied()
             .map(|timeout_after| LookupOption {
-                lookup: custom_resolver.clone(),
+                lookup: Box::new(custom_resolver.clone()),
                 timeout_after,
             });
 
@@ -156,6 +158,7 @@ impl DnsResolver {
         .collect();
         DnsResolver {
             lookup_options,
+            caching_resolver: Some(custom_resolver),
             state: Default::default(),
         }
     }
@@ -168,6 +171,26 @@ impl DnsResolver {
         }
     }
 
+    /// Returns the unexpired cached lookup results, along with when they expire.
+    ///
+    /// Only the fallback (DNS-over-HTTPS) lookups are cached; the system resolver does its own
+    /// caching.
+    pub fn cached_lookups(&self) -> Vec<(String, LookupResult, Instant)> {
+        self.caching_resolver
+            .as_ref()
+            .map(CustomDnsResolver::cached_entries)
+            .unwrap_or_default()
+    }
+
+    /// Adds previously cached lookup results back to the cache.
+    ///
+    /// See [`CustomDnsResolver::seed_cache`].
+    pub fn seed_cache(&self, entries: impl IntoIterator<Item = (String, LookupResult, Instant)>) {
+        if let Some(resolver) = &self.caching_resolver {
+            resolver.seed_cache(entries)
+        }
+    }
+
     pub async fn lookup_ip(&self, hostname: &str) -> Result<LookupResult> {
         let parse_as_ip_addr = hostname.parse().ok().or_else(|| {
             let hostname = hostname.strip_prefix('[')?;
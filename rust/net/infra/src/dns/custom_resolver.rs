@@ -145,6 +145,45 @@ impl<T: DnsTransport + Sync + 'static> CustomDnsResolver<T> {
         }
     }
 
+    /// Returns the unexpired entries in the cache, along with when they expire.
+    pub fn cached_entries(&self) -> Vec<(String, LookupResult, Instant)> {
+        let now = Instant::now();
+        let guard = self.cache.lock().expect("not poisoned");
+        guard
+            .map
+            .iter()
+            .filter(|(_, expiring)| expiring.expiration >= now)
+            .map(|(hostname, expiring)| {
+                (hostname.clone(), expiring.data.clone(), expiring.expiration)
+            })
+            .collect()
+    }
+
+    /// Adds entries to the cache as if they had been looked up, e.g. to restore entries saved by
+    /// [`Self::cached_entries`].
+    ///
+    /// Expired entries are skipped, and existing entries are only replaced by ones that expire
+    /// later. Expiration times are clamped the same way as for live lookups.
+    pub fn seed_cache(&self, entries: impl IntoIterator<Item = (String, LookupResult, Instant)>) {
+        let now = Instant::now();
+        let mut guard = self.cache.lock().expect("not poisoned");
+        for (hostname, lookup_result, expiration) in entries {
+            let expiration = min(expiration, now + MAX_CACHE_TTL);
+            if expiration < now || lookup_result.is_empty() {
+                continue;
+            }
+            if guard
+                .map
+                .get(&hostname)
+                .is_some_and(|existing| existing.expiration >= expiration)
+            {
+                continue;
+            }
+            let data = LookupResult::new(DnsSource::Cache, lookup_result.ipv4, lookup_result.ipv6);
+            guard.map.insert(hostname, Expiring { data, expiration });
+        }
+    }
+
     fn cache_get(&self, hostname: &str) -> Option<LookupResult> {
         let mut guard = self.cache.lock().expect("not poisoned");
         match guard.map.get(hostname) {
@@ -820,4 +859,39 @@ pub(crate) mod test {
         lookup.await.expect("success");
         assert_matches!(resolver.cache_get(&test_request().hostname), None);
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn cache_entries_can_be_saved_and_restored() {
+        let (transport, resolver) =
+            TestDnsTransportWithTwoResponses::transport_and_custom_dns_resolver(|_, _, txs| {
+                let [tx_1, tx_2] = txs;
+                tx_1.send(ok_query_result_ipv4(NORMAL_TTL, IP_V4_LIST_1))
+                    .unwrap();
+                tx_2.send(ok_query_result_ipv6(NORMAL_TTL, IP_V6_LIST_1))
+                    .unwrap();
+            });
+        resolver.resolve(test_request()).await.expect("success");
+        let saved = resolver.cached_entries();
+        assert_eq!(saved.len(), 1);
+
+        let (restored_transport, restored) =
+            TestDnsTransportWithTwoResponses::transport_and_custom_dns_resolver(|_, _, _| {
+                // Never answer; the restored entry should be used instead.
+            });
+        restored.seed_cache(saved);
+        let result = restored.resolve(test_request()).await.expect("cached");
+        assert_lookup_result_content_equal(&result, IP_V4_LIST_1, IP_V6_LIST_1);
+        assert_eq!(0, restored_transport.queries_count());
+
+        // Expired entries aren't restored.
+        tokio::time::sleep(NORMAL_TTL).await;
+        restored.seed_cache(resolver.cached_entries());
+        restored.seed_cache([(
+            test_request().hostname.to_string(),
+            LookupResult::localhost(),
+            Instant::now() - Duration::from_secs(1),
+        )]);
+        assert!(restored.cached_entries().is_empty());
+        assert_eq!(1, transport.queries_count());
+    }
 }
@@ -39,6 +39,14 @@ impl LookupResult {
         Self { source, ipv4, ipv6 }
     }
 
+    pub fn ipv4(&self) -> &[Ipv4Addr] {
+        &self.ipv4
+    }
+
+    pub fn ipv6(&self) -> &[Ipv6Addr] {
+        &self.ipv6
+    }
+
     pub(crate) fn source(&self) -> DnsSource {
         self.source
     }
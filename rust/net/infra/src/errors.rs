@@ -26,6 +26,8 @@ pub enum TransportConnectError {
     CertError,
     /// Failed to establish SSL connection: {0}
     SslFailedHandshake(FailedHandshakeReason),
+    /// Server certificate chain did not match any pinned key
+    PinningFailure,
     /// Proxy handshake failed
     ProxyProtocol,
     /// Abort due to local error
@@ -95,6 +97,12 @@ impl From<certs::Error> for TransportConnectError {
     }
 }
 
+impl From<certs::PinningFailure> for TransportConnectError {
+    fn from(_value: certs::PinningFailure) -> Self {
+        Self::PinningFailure
+    }
+}
+
 impl<S> From<HandshakeError<S>> for TransportConnectError {
     fn from(error: HandshakeError<S>) -> Self {
         Self::SslFailedHandshake(FailedHandshakeReason::from(error))
@@ -110,6 +118,7 @@ impl From<TransportConnectError> for std::io::Error {
             TransportConnectError::SslFailedHandshake(_)
             | TransportConnectError::SslError(_)
             | TransportConnectError::CertError
+            | TransportConnectError::PinningFailure
             | TransportConnectError::ProxyProtocol => ErrorKind::InvalidData,
             TransportConnectError::DnsError => ErrorKind::NotFound,
             TransportConnectError::ClientAbort => ErrorKind::ConnectionAborted,
@@ -18,7 +18,7 @@ use tokio::io::{AsyncRead, AsyncWrite};
 
 use crate::certs::RootCertificates;
 use crate::connection_manager::{
-    MultiRouteConnectionManager, SingleRouteThrottlingConnectionManager,
+    MultiRouteConnectionManager, RetryPolicy, SingleRouteThrottlingConnectionManager,
 };
 use crate::errors::TransportConnectError;
 use crate::host::Host;
@@ -312,6 +312,7 @@ impl EndpointConnection<MultiRouteConnectionManager> {
         connection_params: impl IntoIterator<Item = ConnectionParams>,
         one_route_connect_timeout: Duration,
         config: WebSocketConfig,
+        retry_policy: RetryPolicy,
         network_changed_event: &ObservableEvent,
     ) -> Self {
         Self {
@@ -319,14 +320,16 @@ impl EndpointConnection<MultiRouteConnectionManager> {
                 connection_params
                     .into_iter()
                     .map(|params| {
-                        SingleRouteThrottlingConnectionManager::new(
+                        SingleRouteThrottlingConnectionManager::new_with_retry_policy(
                             params,
                             one_route_connect_timeout,
+                            retry_policy,
                             network_changed_event,
                         )
                     })
                     .collect(),
-            ),
+            )
+            .with_max_attempts_per_route(retry_policy.max_attempts),
             config,
         }
     }
@@ -342,6 +345,7 @@ pub fn make_ws_config(
         max_connection_time: connect_timeout,
         keep_alive_interval: WS_KEEP_ALIVE_INTERVAL,
         max_idle_time: WS_MAX_IDLE_INTERVAL,
+        max_pending_send_bytes: ws::WebSocketLimits::DEFAULT.max_pending_send_bytes,
     }
 }
 
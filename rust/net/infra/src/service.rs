@@ -48,9 +48,18 @@ pub enum CancellationReason {
     ExplicitDisconnect,
     ServiceError,
     RemoteClose,
+    /// The server closed the connection with a close frame.
+    ServerClose(ServerClose),
     ProtocolError,
 }
 
+/// The contents of a close frame sent by the server.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ServerClose {
+    pub code: u16,
+    pub reason: String,
+}
+
 pub type CancellationToken = cancel_token::CancellationToken<CancellationReason>;
 
 pub trait RemoteAddressInfo {
@@ -255,7 +264,7 @@ pub enum StateError {
     /// Service is in the inactive state
     Inactive,
     /// Service is unavailable due to the lost connection
-    ServiceUnavailable,
+    ServiceUnavailable(Option<ServerClose>),
 }
 
 impl<C, M> Service<C, M>
@@ -267,10 +276,16 @@ where
         match &*guard {
             ServiceState::Active(service, status) if !status.is_cancelled() => Ok(mapper(service)),
             ServiceState::Inactive => Err(StateError::Inactive),
+            ServiceState::Active(_, status) => {
+                let server_close = match status.reason() {
+                    Some(CancellationReason::ServerClose(close)) => Some(close),
+                    _ => None,
+                };
+                Err(StateError::ServiceUnavailable(server_close))
+            }
             ServiceState::Cooldown(_)
             | ServiceState::ConnectionTimedOut
-            | ServiceState::Error(_)
-            | ServiceState::Active(_, _) => Err(StateError::ServiceUnavailable),
+            | ServiceState::Error(_) => Err(StateError::ServiceUnavailable(None)),
         }
     }
 }
@@ -27,6 +27,14 @@ impl<T> CancellationToken<T> {
         self.receiver.borrow().is_some()
     }
 
+    /// Returns the reason the token was cancelled with, if it has been cancelled.
+    pub fn reason(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.receiver.borrow().clone()
+    }
+
     /// Cancels the token with the provided reason.
     ///
     /// If the token was already cancelled this has no effect. Otherwise any
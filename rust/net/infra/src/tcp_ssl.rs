@@ -11,10 +11,10 @@ use boring_signal::ssl::{ConnectConfiguration, SslConnector, SslMethod};
 use futures_util::TryFutureExt;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpStream;
-use tokio_boring_signal::SslStream;
+use tokio_boring_signal::{HandshakeError, SslStream};
 use tokio_util::either::Either;
 
-use crate::certs::RootCertificates;
+use crate::certs::{PinCheck, PinningFailure, RootCertificates};
 use crate::dns::DnsResolver;
 use crate::errors::TransportConnectError;
 use crate::host::Host;
@@ -49,6 +49,14 @@ impl TcpSslConnector {
         };
         dns_resolver.set_ipv6_enabled(ipv6_enabled);
     }
+
+    pub fn dns_resolver(&self) -> &DnsResolver {
+        match self {
+            TcpSslConnector::Direct(c) => &c.dns_resolver,
+            TcpSslConnector::Proxied(c) => &c.dns_resolver,
+            TcpSslConnector::Invalid(resolver) => resolver,
+        }
+    }
 }
 
 pub struct InvalidProxyConfig;
@@ -117,9 +125,9 @@ fn ssl_config(
     certs: &RootCertificates,
     host: Host<&str>,
     alpn: Option<Alpn>,
-) -> Result<ConnectConfiguration, TransportConnectError> {
+) -> Result<(ConnectConfiguration, Option<PinCheck>), TransportConnectError> {
     let mut ssl = SslConnector::builder(SslMethod::tls_client())?;
-    certs.apply_to_connector(&mut ssl, host)?;
+    let pin_check = certs.apply_to_connector(&mut ssl, host)?;
     if let Some(alpn) = alpn {
         ssl.set_alpn_protos(alpn.as_ref())?;
     }
@@ -131,7 +139,7 @@ fn ssl_config(
     // #[cfg(feature = "dev-util")]
     // development_only_enable_nss_standard_debug_interop(&mut ssl)?;
 
-    Ok(ssl.build().configure()?)
+    Ok((ssl.build().configure()?, pin_check))
 }
 
 async fn connect_tls<S: AsyncRead + AsyncWrite + Unpin>(
@@ -139,13 +147,37 @@ async fn connect_tls<S: AsyncRead + AsyncWrite + Unpin>(
     connection_params: &TransportConnectionParams,
     alpn: Alpn,
 ) -> Result<SslStream<S>, TransportConnectError> {
-    let ssl_config = ssl_config(
+    let (ssl_config, pin_check) = ssl_config(
         &connection_params.certs,
         Host::Domain(&connection_params.sni),
         Some(alpn),
     )?;
 
-    Ok(tokio_boring_signal::connect(ssl_config, &connection_params.sni, transport).await?)
+    let result = tokio_boring_signal::connect(ssl_config, &connection_params.sni, transport).await;
+    finish_handshake(result, pin_check.as_ref())
+}
+
+/// Reports a [`PinningFailure`] if `pin_check` rejected the server's certificates, rather than the
+/// generic handshake error that produces.
+fn finish_handshake<S>(
+    result: Result<SslStream<S>, HandshakeError<S>>,
+    pin_check: Option<&PinCheck>,
+) -> Result<SslStream<S>, TransportConnectError> {
+    let Some(pin_check) = pin_check else {
+        return Ok(result?);
+    };
+    let pinning_failure = |_: PinningFailure| {
+        log::warn!("TLS certificate chain did not match any pinned key");
+        TransportConnectError::PinningFailure
+    };
+    match result {
+        Ok(stream) => {
+            pin_check.result().map_err(pinning_failure)?;
+            Ok(stream)
+        }
+        Err(_) if pin_check.rejected() => Err(pinning_failure(PinningFailure)),
+        Err(e) => Err(e.into()),
+    }
 }
 
 async fn connect_tcp(
@@ -310,10 +342,22 @@ pub(crate) mod testutil {
     ///
     /// Returns the address of the server and a [`Future`] that runs it.
     pub(crate) fn localhost_http_server() -> (SocketAddr, impl Future<Output = ()>) {
+        localhost_http_server_with_extra_certs(&[])
+    }
+
+    /// Like [`localhost_http_server`], but the server sends `extra_certs` after its own
+    /// certificate, as if they were intermediates.
+    pub(crate) fn localhost_http_server_with_extra_certs(
+        extra_certs: &[&CertifiedKey],
+    ) -> (SocketAddr, impl Future<Output = ()>) {
+        let cert_chain = std::iter::once(&*SERVER_CERTIFICATE)
+            .chain(extra_certs.iter().copied())
+            .map(|certified_key| certified_key.cert.pem())
+            .collect::<String>();
         let filter = warp::any().map(|| FAKE_RESPONSE);
         let server = warp::serve(filter)
             .tls()
-            .cert(SERVER_CERTIFICATE.cert.pem())
+            .cert(cert_chain)
             .key(SERVER_CERTIFICATE.key_pair.serialize_pem());
 
         server.bind_ephemeral((Ipv6Addr::LOCALHOST, 0))
@@ -352,12 +396,15 @@ mod test {
     use std::net::Ipv6Addr;
 
     use assert_matches::assert_matches;
+    use rcgen::CertifiedKey;
     use test_case::test_case;
 
     use super::testutil::*;
     use super::*;
+    use crate::certs::SpkiPins;
     use crate::dns::lookup_result::LookupResult;
     use crate::host::Host;
+    use crate::tcp_ssl::proxy::testutil::PROXY_CERTIFICATE;
 
     #[test_case(true; "resolved hostname")]
     #[test_case(false; "by IP")]
@@ -397,6 +444,71 @@ mod test {
         make_http_request_response_over(stream).await
     }
 
+    #[test_case(true; "matching pin")]
+    #[test_case(false; "mismatched pin")]
+    #[tokio::test]
+    async fn connect_with_pinned_key(pin_matches: bool) {
+        let (addr, server) = localhost_http_server();
+        let _server_handle = tokio::spawn(server);
+
+        let pinned_cert: &CertifiedKey = if pin_matches {
+            &SERVER_CERTIFICATE
+        } else {
+            &PROXY_CERTIFICATE
+        };
+        let pins = SpkiPins::new([
+            [0; 32], // an unrelated pin, as during a key rotation
+            SpkiPins::hash_of_certificate(pinned_cert.cert.der()).expect("valid certificate"),
+        ])
+        .expect("non-empty");
+
+        let connector = DirectConnector::new(DnsResolver::new_from_static_map(HashMap::new()));
+        let connection_params = TransportConnectionParams {
+            sni: SERVER_HOSTNAME.into(),
+            tcp_host: Host::Ip(addr.ip()),
+            port: addr.port().try_into().expect("bound port"),
+            certs: RootCertificates::FromDer(Cow::Borrowed(SERVER_CERTIFICATE.cert.der()))
+                .with_pins(pins),
+        };
+
+        let result = connector.connect(&connection_params, Alpn::Http1_1).await;
+        if pin_matches {
+            let StreamAndInfo(stream, _info) = result.expect("can connect");
+            make_http_request_response_over(stream).await
+        } else {
+            assert_matches!(
+                result.map(|_| ()),
+                Err(TransportConnectError::PinningFailure)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn pins_only_match_verified_chain() {
+        // The server also sends a certificate that isn't on the path to the trusted root.
+        let (addr, server) = localhost_http_server_with_extra_certs(&[&PROXY_CERTIFICATE]);
+        let _server_handle = tokio::spawn(server);
+
+        let appended_pin =
+            SpkiPins::hash_of_certificate(PROXY_CERTIFICATE.cert.der()).expect("valid certificate");
+        let pins = SpkiPins::new([appended_pin]).expect("non-empty");
+
+        let connector = DirectConnector::new(DnsResolver::new_from_static_map(HashMap::new()));
+        let connection_params = TransportConnectionParams {
+            sni: SERVER_HOSTNAME.into(),
+            tcp_host: Host::Ip(addr.ip()),
+            port: addr.port().try_into().expect("bound port"),
+            certs: RootCertificates::FromDer(Cow::Borrowed(SERVER_CERTIFICATE.cert.der()))
+                .with_pins(pins),
+        };
+
+        let result = connector.connect(&connection_params, Alpn::Http1_1).await;
+        assert_matches!(
+            result.map(|_| ()),
+            Err(TransportConnectError::PinningFailure)
+        );
+    }
+
     #[tokio::test]
     async fn connect_through_invalid() {
         let (addr, server) = localhost_http_server();
@@ -16,7 +16,7 @@ use crate::dns::DnsResolver;
 use crate::errors::TransportConnectError;
 use crate::host::Host;
 use crate::route::ConnectionProxyConfig;
-use crate::tcp_ssl::{connect_tcp, connect_tls, ssl_config};
+use crate::tcp_ssl::{connect_tcp, connect_tls, finish_handshake, ssl_config};
 use crate::{
     Alpn, ConnectionInfo, RouteType, StreamAndInfo, TransportConnectionParams, TransportConnector,
 };
@@ -71,15 +71,15 @@ impl TransportConnector for TlsProxyConnector {
                 );
                 // This won't always work, but it's enough to connect to proxies
                 // by hostnames.
-                let ssl_config = ssl_config(&self.proxy_certs, self.proxy_host.as_deref(), None)?;
-                Either::Left(
-                    tokio_boring_signal::connect(
-                        ssl_config,
-                        &self.proxy_host.to_string(),
-                        tcp_stream,
-                    )
-                    .await?,
+                let (ssl_config, pin_check) =
+                    ssl_config(&self.proxy_certs, self.proxy_host.as_deref(), None)?;
+                let result = tokio_boring_signal::connect(
+                    ssl_config,
+                    &self.proxy_host.to_string(),
+                    tcp_stream,
                 )
+                .await;
+                Either::Left(finish_handshake(result, pin_check.as_ref())?)
             }
             ShouldUseTls::No => {
                 log::debug!(
@@ -37,18 +37,11 @@ pub const MULTI_ROUTE_CONNECTION_TIMEOUT: Duration = Duration::from_secs(180);
 /// before it starts.
 pub const TCP_CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(200);
 
-/// A sequence of timeout values to be used as cooldown intervals between attempts
-/// when a connection to a given route is consecutively failing to establish
-pub const CONNECTION_ROUTE_COOLDOWN_INTERVALS: [Duration; 8] = [
-    Duration::from_secs(0),
-    Duration::from_secs(1),
-    Duration::from_secs(2),
-    Duration::from_secs(4),
-    Duration::from_secs(8),
-    Duration::from_secs(16),
-    Duration::from_secs(32),
-    CONNECTION_ROUTE_MAX_COOLDOWN,
-];
+/// Default cooldown interval after the second consecutive failure to connect to a given route
+///
+/// The first failure is retried immediately; see
+/// [`RetryPolicy`](crate::connection_manager::RetryPolicy).
+pub const CONNECTION_ROUTE_INITIAL_COOLDOWN: Duration = Duration::from_secs(1);
 
-/// Maximum value of a coolduwn interval between connection attempts
+/// Default maximum value of a cooldown interval between connection attempts
 pub const CONNECTION_ROUTE_MAX_COOLDOWN: Duration = Duration::from_secs(64);
@@ -14,7 +14,7 @@ use derive_where::derive_where;
 use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt as _, StreamExt, TryFutureExt};
 use http::uri::PathAndQuery;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Semaphore};
 use tokio::time::Instant;
 use tokio_tungstenite::WebSocketStream;
 use tungstenite::handshake::client::generate_key;
@@ -23,7 +23,7 @@ use tungstenite::{http, Message};
 
 use crate::errors::LogSafeDisplay;
 use crate::route::WebSocketRouteFragment;
-use crate::service::{CancellationReason, CancellationToken, ServiceConnector};
+use crate::service::{CancellationReason, CancellationToken, ServerClose, ServiceConnector};
 use crate::utils::timeout;
 use crate::ws::error::{HttpFormatError, ProtocolError, SpaceError};
 use crate::{
@@ -60,6 +60,37 @@ pub struct WebSocketConfig {
     /// How long to allow the connection to be idle before the server is assumed
     /// to have become unavailable.
     pub max_idle_time: Duration,
+    /// How many bytes of outgoing messages can be waiting to be written before
+    /// senders have to wait.
+    ///
+    /// See [`WebSocketClientWriter::ready`].
+    pub max_pending_send_bytes: usize,
+}
+
+/// Size limits for a websocket connection.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WebSocketLimits {
+    /// The largest frame that will be accepted.
+    pub max_frame_size: usize,
+    /// The largest message that will be sent or accepted.
+    pub max_message_size: usize,
+    /// See [`WebSocketConfig::max_pending_send_bytes`].
+    pub max_pending_send_bytes: usize,
+}
+
+impl WebSocketLimits {
+    /// Matches the defaults used by [`tungstenite`].
+    pub const DEFAULT: Self = Self {
+        max_frame_size: 16 << 20,
+        max_message_size: 64 << 20,
+        max_pending_send_bytes: 64 << 20,
+    };
+}
+
+impl Default for WebSocketLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
 }
 
 /// [`ServiceConnector`] for services that wrap a websocket connection.
@@ -68,6 +99,7 @@ pub struct WebSocketClientConnector<T, E> {
     service_connector: WebSocketStreamConnector<T>,
     keep_alive_interval: Duration,
     max_idle_time: Duration,
+    max_pending_send_bytes: usize,
     service_error_type: PhantomData<E>,
 }
 
@@ -79,6 +111,7 @@ impl<T: TransportConnector, E> WebSocketClientConnector<T, E> {
             max_connection_time,
             keep_alive_interval,
             max_idle_time,
+            max_pending_send_bytes,
         } = cfg;
         Self {
             service_connector: WebSocketStreamConnector::new(
@@ -92,6 +125,7 @@ impl<T: TransportConnector, E> WebSocketClientConnector<T, E> {
             ),
             keep_alive_interval,
             max_idle_time,
+            max_pending_send_bytes,
             service_error_type: PhantomData,
         }
     }
@@ -120,6 +154,20 @@ impl<T: TransportConnector> WebSocketStreamConnector<T> {
 }
 
 impl WebSocketConfig {
+    /// Applies `limits` to both the protocol-level configuration and the send
+    /// buffer.
+    pub fn with_limits(mut self, limits: WebSocketLimits) -> Self {
+        let WebSocketLimits {
+            max_frame_size,
+            max_message_size,
+            max_pending_send_bytes,
+        } = limits;
+        self.ws_config.max_frame_size = Some(max_frame_size);
+        self.ws_config.max_message_size = Some(max_message_size);
+        self.max_pending_send_bytes = max_pending_send_bytes;
+        self
+    }
+
     pub fn ws2_config(&self) -> crate::ws2::Config {
         crate::ws2::Config {
             local_idle_timeout: self.keep_alive_interval,
@@ -190,8 +238,12 @@ where
     E: Send + Sync,
     WebSocketServiceError: Into<E>,
 {
-    type Service = (WebSocketClient<T::Stream, E>, ConnectionInfo);
-    type Channel = (WebSocketStream<T::Stream>, ConnectionInfo);
+    type Service = (
+        WebSocketClient<T::Stream, E>,
+        ConnectionInfo,
+        http::HeaderMap,
+    );
+    type Channel = (WebSocketStream<T::Stream>, ConnectionInfo, http::HeaderMap);
     type ConnectError = WebSocketConnectError;
 
     async fn connect_channel(
@@ -204,9 +256,14 @@ where
     }
 
     fn start_service(&self, channel: Self::Channel) -> (Self::Service, CancellationToken) {
-        let (service, token) =
-            start_ws_service(channel.0, self.keep_alive_interval, self.max_idle_time);
-        ((service, channel.1), token)
+        let (ws_stream, connection_info, response_headers) = channel;
+        let (service, token) = start_ws_service(
+            ws_stream,
+            self.keep_alive_interval,
+            self.max_idle_time,
+            self.max_pending_send_bytes,
+        );
+        ((service, connection_info, response_headers), token)
     }
 }
 
@@ -215,8 +272,8 @@ impl<T> ServiceConnector for WebSocketStreamConnector<T>
 where
     T: TransportConnector,
 {
-    type Service = (WebSocketStream<T::Stream>, ConnectionInfo);
-    type Channel = (WebSocketStream<T::Stream>, ConnectionInfo);
+    type Service = (WebSocketStream<T::Stream>, ConnectionInfo, http::HeaderMap);
+    type Channel = (WebSocketStream<T::Stream>, ConnectionInfo, http::HeaderMap);
     type ConnectError = WebSocketConnectError;
 
     async fn connect_channel(
@@ -255,11 +312,15 @@ fn start_ws_service<S: AsyncDuplexStream, E>(
     channel: WebSocketStream<S>,
     keep_alive_interval: Duration,
     max_idle_time: Duration,
+    max_pending_send_bytes: usize,
 ) -> (WebSocketClient<S, E>, CancellationToken) {
     let service_cancellation = CancellationToken::new();
     let (ws_sink, ws_stream) = channel.split();
+    let max_pending_send_bytes = max_pending_send_bytes.min(Semaphore::MAX_PERMITS);
     let ws_client_writer = WebSocketClientWriter {
         ws_sink: Arc::new(Mutex::new(ws_sink)),
+        send_budget: Arc::new(Semaphore::new(max_pending_send_bytes)),
+        max_pending_send_bytes,
         service_cancellation: service_cancellation.clone(),
         error_type: Default::default(),
     };
@@ -285,19 +346,51 @@ fn start_ws_service<S: AsyncDuplexStream, E>(
 #[derive(Debug)]
 pub struct WebSocketClientWriter<S, E> {
     ws_sink: Arc<Mutex<SplitSink<WebSocketStream<S>, Message>>>,
+    /// One permit per byte of outgoing messages that may be waiting to be
+    /// written.
+    send_budget: Arc<Semaphore>,
+    max_pending_send_bytes: usize,
     service_cancellation: CancellationToken,
     error_type: PhantomData<E>,
 }
 
+impl<S, E> WebSocketClientWriter<S, E> {
+    /// How many more bytes can be sent without waiting.
+    pub fn available_send_capacity(&self) -> usize {
+        self.send_budget.available_permits()
+    }
+
+    /// Waits until a message of `len` bytes could be sent without waiting.
+    ///
+    /// Bulk senders can use this to hold off on producing more data while the
+    /// connection is stalled. There's no guarantee another sender won't use up
+    /// the capacity before the message is actually sent.
+    pub async fn ready(&self, len: usize) {
+        let _permit = self.acquire_send_budget(len).await;
+    }
+
+    async fn acquire_send_budget(&self, len: usize) -> Option<tokio::sync::SemaphorePermit<'_>> {
+        // A message larger than the whole budget still gets sent, just on its own.
+        let permits = len.min(self.max_pending_send_bytes);
+        let permits = u32::try_from(permits).unwrap_or(u32::MAX);
+        // The semaphore is never closed.
+        self.send_budget.acquire_many(permits).await.ok()
+    }
+}
+
 impl<S: AsyncDuplexStream, E> WebSocketClientWriter<S, E>
 where
     WebSocketServiceError: Into<E>,
 {
+    /// Sends `message`, waiting first if too many bytes are already waiting to
+    /// be written.
     pub async fn send(&self, message: impl Into<Message>) -> Result<(), E> {
+        let message = message.into();
+        let _permit = self.acquire_send_budget(message.len()).await;
         run_and_update_status(&self.service_cancellation, || {
             async {
                 let mut guard = self.ws_sink.lock().await;
-                guard.send(message.into()).await?;
+                guard.send(message).await?;
                 guard.flush().await?;
                 Ok(())
             }
@@ -374,8 +467,13 @@ where
                     Message::Binary(b) => return Ok(NextOrClose::Next(b.into())),
                     Message::Ping(_) | Message::Pong(_) => continue,
                     Message::Close(close_frame) => {
-                        self.service_cancellation
-                            .cancel(CancellationReason::RemoteClose);
+                        self.service_cancellation.cancel(match &close_frame {
+                            Some(frame) => CancellationReason::ServerClose(ServerClose {
+                                code: frame.code.into(),
+                                reason: frame.reason.to_string(),
+                            }),
+                            None => CancellationReason::RemoteClose,
+                        });
                         return Ok(NextOrClose::Close(close_frame));
                     }
                     Message::Frame(_) => unreachable!("only for sending"),
@@ -405,12 +503,13 @@ where
     result.map_err(Into::into)
 }
 
+/// Connects and upgrades to a websocket, also returning the headers of the server's `101` response.
 async fn connect_websocket<T: TransportConnector>(
     connection_params: &ConnectionParams,
     endpoint: PathAndQuery,
     ws_config: tungstenite::protocol::WebSocketConfig,
     transport_connector: &T,
-) -> Result<(WebSocketStream<T::Stream>, ConnectionInfo), WebSocketConnectError> {
+) -> Result<(WebSocketStream<T::Stream>, ConnectionInfo, http::HeaderMap), WebSocketConnectError> {
     let StreamAndInfo(ssl_stream, remote_address) = transport_connector
         .connect(&connection_params.transport, Alpn::Http1_1)
         .await?;
@@ -441,14 +540,14 @@ async fn connect_websocket<T: TransportConnector>(
         .http_request_decorator
         .decorate_request(request_builder);
 
-    let (ws_stream, _response) = tokio_tungstenite::client_async_with_config(
+    let (ws_stream, response) = tokio_tungstenite::client_async_with_config(
         request_builder.body(()).expect("can get request body"),
         ssl_stream,
         Some(ws_config),
     )
     .await?;
 
-    Ok((ws_stream, remote_address))
+    Ok((ws_stream, remote_address, response.into_parts().0.headers))
 }
 
 #[derive(Debug)]
@@ -564,14 +663,24 @@ pub mod testutil {
     pub fn websocket_test_client<S: AsyncDuplexStream>(
         channel: WebSocketStream<S>,
     ) -> WebSocketClient<S, WebSocketServiceError> {
-        start_ws_service(channel, WS_KEEP_ALIVE_INTERVAL, WS_MAX_IDLE_INTERVAL).0
+        start_ws_service(
+            channel,
+            WS_KEEP_ALIVE_INTERVAL,
+            WS_MAX_IDLE_INTERVAL,
+            WebSocketLimits::DEFAULT.max_pending_send_bytes,
+        )
+        .0
     }
 
     impl<S: AsyncDuplexStream, E> WebSocketClient<S, E> {
         pub fn new_fake(channel: WebSocketStream<S>) -> Self {
             const VERY_LARGE_TIMEOUT: Duration = Duration::from_secs(u32::MAX as u64);
-            let (client, _service_status) =
-                start_ws_service(channel, VERY_LARGE_TIMEOUT, VERY_LARGE_TIMEOUT);
+            let (client, _service_status) = start_ws_service(
+                channel,
+                VERY_LARGE_TIMEOUT,
+                VERY_LARGE_TIMEOUT,
+                WebSocketLimits::DEFAULT.max_pending_send_bytes,
+            );
             client
         }
     }
@@ -658,4 +767,47 @@ mod test {
         drop(server);
         assert_matches!(handle.await.expect("joined"), Ok(()));
     }
+
+    #[tokio::test]
+    async fn websocket_send_waits_for_stalled_sends() {
+        const MAX_PENDING_SEND_BYTES: usize = 100;
+        const VERY_LARGE_TIMEOUT: Duration = Duration::from_secs(u32::MAX as u64);
+
+        let (mut server, client) = fake_websocket().await;
+        let (ws, _service_status) = start_ws_service::<_, WebSocketServiceError>(
+            client,
+            VERY_LARGE_TIMEOUT,
+            VERY_LARGE_TIMEOUT,
+            MAX_PENDING_SEND_BYTES,
+        );
+        let writer = ws.ws_client_writer;
+        assert_eq!(writer.available_send_capacity(), MAX_PENDING_SEND_BYTES);
+
+        // The fake transport only buffers 1024 bytes, so this can't be written
+        // until the server reads it.
+        let large_message = vec![0; 4096];
+        let stalled_send = tokio::spawn({
+            let writer = writer.clone();
+            let large_message = large_message.clone();
+            async move { writer.send(large_message).await }
+        });
+        while writer.available_send_capacity() != 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let ready = writer.ready(1);
+        pin_mut!(ready);
+        assert_matches!(poll!(&mut ready), std::task::Poll::Pending);
+
+        assert_eq!(
+            server.next().await.unwrap().unwrap(),
+            Message::Binary(large_message)
+        );
+        stalled_send
+            .await
+            .expect("joined")
+            .expect("sent once the server read it");
+        ready.await;
+        assert_eq!(writer.available_send_capacity(), MAX_PENDING_SEND_BYTES);
+    }
 }
@@ -12,7 +12,7 @@ use sha2::Sha256;
 /// username and password as returned by the chat server's /auth endpoints.
 /// - username is a "hex(uid)"
 /// - password is a "timestamp:hex(otp(uid, timestamp, secret))"
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "test-util", derive(Default))]
 pub struct Auth {
     pub username: String,
@@ -3,20 +3,25 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use std::collections::{HashMap, HashSet};
 use std::default::Default;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
 
 use futures_util::TryFutureExt as _;
 use http::StatusCode;
-use libsignal_core::{Aci, Pni, E164};
+use libsignal_core::{Aci, Pni, RegionHint, E164};
 use libsignal_net_infra::connection_manager::ConnectionManager;
 use libsignal_net_infra::errors::{LogSafeDisplay, TransportConnectError};
 use libsignal_net_infra::ws::{NextOrClose, WebSocketConnectError, WebSocketServiceError};
 use libsignal_net_infra::ws2::attested::{
     AttestedConnection, AttestedConnectionError, AttestedProtocolError,
 };
-use libsignal_net_infra::{extract_retry_after_seconds, TransportConnector};
+use libsignal_net_infra::TransportConnector;
 use prost::Message as _;
 use thiserror::Error;
+use tokio::time::Instant;
 use tungstenite::protocol::frame::coding::CloseCode;
 use tungstenite::protocol::CloseFrame;
 use uuid::Uuid;
@@ -24,6 +29,7 @@ use uuid::Uuid;
 use crate::auth::Auth;
 use crate::enclave::{Cdsi, EnclaveEndpointConnection};
 use crate::proto::cds2::{ClientRequest, ClientResponse};
+use crate::rate_limit::{RateLimit, RateLimitDetails};
 use crate::ws::WebSocketServiceConnectError;
 
 trait FixedLengthSerializable {
@@ -114,6 +120,77 @@ impl LookupRequest {
             discard_e164s: Vec::new(),
         }
     }
+
+    /// Normalizes raw phone number strings and adds them as new E164s.
+    ///
+    /// Each entry is interpreted according to [`E164::normalize`] using
+    /// `region` for numbers written without an international prefix. Numbers
+    /// that normalize to an E164 already present in the request (as a new or
+    /// previous E164) are not added a second time.
+    ///
+    /// The returned mapping has one entry per input, in order, so that lookup
+    /// results can be matched back to the caller's original entries.
+    pub fn add_raw_e164s<S: AsRef<str>>(
+        &mut self,
+        raw_numbers: impl IntoIterator<Item = S>,
+        region: RegionHint,
+    ) -> RawE164Mapping {
+        let mut seen: HashSet<E164> = self
+            .new_e164s
+            .iter()
+            .chain(&self.prev_e164s)
+            .copied()
+            .collect();
+
+        let normalized = raw_numbers
+            .into_iter()
+            .map(|raw| {
+                let e164 = E164::normalize(raw.as_ref(), region)?;
+                if seen.insert(e164) {
+                    self.new_e164s.push(e164);
+                }
+                Some(e164)
+            })
+            .collect();
+
+        RawE164Mapping { normalized }
+    }
+}
+
+/// The result of [`LookupRequest::add_raw_e164s`].
+#[derive(Debug, Default, PartialEq)]
+pub struct RawE164Mapping {
+    /// For each raw input, in order, the number it was normalized to, or
+    /// `None` if it could not be interpreted as a valid E164.
+    pub normalized: Vec<Option<E164>>,
+}
+
+impl RawE164Mapping {
+    /// Indices of the raw inputs that were rejected during normalization.
+    pub fn rejected(&self) -> impl Iterator<Item = usize> + '_ {
+        self.normalized
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e164)| e164.is_none().then_some(i))
+    }
+
+    /// Matches each raw input with its entry in `response`, if any.
+    ///
+    /// Inputs that normalized to the same number share the same entry.
+    pub fn resolve<'r>(
+        &self,
+        response: &'r LookupResponse,
+    ) -> Vec<Option<&'r LookupResponseEntry>> {
+        let by_e164: HashMap<E164, &LookupResponseEntry> = response
+            .records
+            .iter()
+            .map(|entry| (entry.e164, entry))
+            .collect();
+        self.normalized
+            .iter()
+            .map(|e164| e164.and_then(|e164| by_e164.get(&e164).copied()))
+            .collect()
+    }
 }
 
 #[derive(Debug)]
@@ -135,6 +212,106 @@ pub struct LookupResponseEntry {
     pub pni: Option<Pni>,
 }
 
+/// The service IDs a client currently has on record for a phone number.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ContactIds {
+    pub aci: Option<Aci>,
+    pub pni: Option<Pni>,
+}
+
+/// A difference between a client's contact mapping and a [`LookupResponse`].
+///
+/// Produced by [`LookupResponse::reconcile`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContactChange {
+    /// The number resolved to an ACI the client did not have on record.
+    NewAci {
+        e164: E164,
+        previous: Option<Aci>,
+        aci: Aci,
+    },
+    /// The number's PNI is different from (or newly added to) the one on
+    /// record.
+    ChangedPni {
+        e164: E164,
+        previous: Option<Pni>,
+        pni: Option<Pni>,
+    },
+    /// The number was looked up but is no longer registered.
+    Removed { e164: E164, previous: ContactIds },
+}
+
+impl LookupResponse {
+    /// Compares the lookup results with the client's existing mapping from
+    /// phone numbers to service IDs.
+    ///
+    /// Changes for numbers in `existing` are reported in the order they were
+    /// provided, followed by changes for numbers that only appear in the
+    /// response, in response order. A number that appears in the response
+    /// without an ACI does not clear an ACI that's already on record, since
+    /// the server only returns ACIs for which the client proved knowledge of
+    /// an access key (unless `return_acis_without_uaks` was set).
+    ///
+    /// Every number in `existing` is expected to have been part of the
+    /// request; any that are missing from the response, or that come back
+    /// with neither an ACI nor a PNI, are reported as
+    /// [`ContactChange::Removed`].
+    pub fn reconcile(
+        &self,
+        existing: impl IntoIterator<Item = (E164, ContactIds)>,
+    ) -> Vec<ContactChange> {
+        fn diff(
+            e164: E164,
+            previous: ContactIds,
+            entry: &LookupResponseEntry,
+            changes: &mut Vec<ContactChange>,
+        ) {
+            if let Some(aci) = entry.aci {
+                if previous.aci != Some(aci) {
+                    changes.push(ContactChange::NewAci {
+                        e164,
+                        previous: previous.aci,
+                        aci,
+                    });
+                }
+            }
+            if previous.pni != entry.pni {
+                changes.push(ContactChange::ChangedPni {
+                    e164,
+                    previous: previous.pni,
+                    pni: entry.pni,
+                });
+            }
+        }
+
+        let by_e164: HashMap<E164, &LookupResponseEntry> = self
+            .records
+            .iter()
+            .map(|entry| (entry.e164, entry))
+            .collect();
+        let mut seen = HashSet::new();
+        let mut changes = Vec::new();
+
+        for (e164, previous) in existing {
+            if !seen.insert(e164) {
+                continue;
+            }
+            match by_e164.get(&e164) {
+                Some(entry) if entry.is_registered() => diff(e164, previous, entry, &mut changes),
+                Some(_) | None => changes.push(ContactChange::Removed { e164, previous }),
+            }
+        }
+
+        for entry in &self.records {
+            if seen.insert(entry.e164) && entry.is_registered() {
+                diff(entry.e164, ContactIds::default(), entry, &mut changes);
+            }
+        }
+
+        changes
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum LookupResponseParseError {
     InvalidNumberOfBytes { actual_length: usize },
@@ -158,29 +335,48 @@ impl TryFrom<ClientResponse> for LookupResponse {
             debug_permits_used,
         } = response;
 
-        if e164_pni_aci_triples.len() % LookupResponseEntry::SERIALIZED_LEN != 0 {
+        Ok(Self {
+            records: LookupResponseEntry::parse_packed(&e164_pni_aci_triples)?,
+            debug_permits_used,
+        })
+    }
+}
+
+impl LookupResponseEntry {
+    /// The length of one entry in the packed format used by the server.
+    pub const PACKED_LEN: usize = <Self as FixedLengthSerializable>::SERIALIZED_LEN;
+
+    /// Parses entries packed as consecutive e164/PNI/ACI triples, with a nil
+    /// UUID standing in for a missing service ID.
+    ///
+    /// This is the format the server uses for lookup results.
+    pub fn parse_packed(triples: &[u8]) -> Result<Vec<Self>, LookupResponseParseError> {
+        if triples.len() % Self::PACKED_LEN != 0 {
             return Err(LookupResponseParseError::InvalidNumberOfBytes {
-                actual_length: e164_pni_aci_triples.len(),
+                actual_length: triples.len(),
             });
         }
 
-        let records = e164_pni_aci_triples
-            .chunks(LookupResponseEntry::SERIALIZED_LEN)
+        Ok(triples
+            .chunks(Self::PACKED_LEN)
             .flat_map(|record| {
-                LookupResponseEntry::try_parse_from(
-                    record.try_into().expect("chunk size is correct"),
-                )
+                Self::try_parse_from(record.try_into().expect("chunk size is correct"))
             })
-            .collect();
+            .collect())
+    }
 
-        Ok(Self {
-            records,
-            debug_permits_used,
-        })
+    /// Packs the entry in the format read by [`Self::parse_packed`].
+    pub fn to_packed(&self) -> [u8; Self::PACKED_LEN] {
+        let mut packed = [0; Self::PACKED_LEN];
+        self.serialize_into(&mut packed);
+        packed
+    }
+
+    /// Whether the server has any service ID on record for the number.
+    fn is_registered(&self) -> bool {
+        self.aci.is_some() || self.pni.is_some()
     }
-}
 
-impl LookupResponseEntry {
     fn try_parse_from(record: &[u8; Self::SERIALIZED_LEN]) -> Option<Self> {
         fn non_nil_uuid<T: From<Uuid>>(bytes: &uuid::Bytes) -> Option<T> {
             let uuid = Uuid::from_bytes(*bytes);
@@ -238,7 +434,7 @@ pub enum LookupError {
     /// invalid response received from the server
     InvalidResponse,
     /// retry later
-    RateLimited { retry_after_seconds: u32 },
+    RateLimited(RateLimit),
     /// request token was invalid
     InvalidToken,
     /// failed to parse the response from the server
@@ -285,12 +481,11 @@ impl From<crate::enclave::Error> for LookupError {
                     received_at: _,
                 } => {
                     if response.status() == StatusCode::TOO_MANY_REQUESTS {
-                        if let Some(retry_after_seconds) =
-                            extract_retry_after_seconds(response.headers())
-                        {
-                            return Self::RateLimited {
-                                retry_after_seconds,
-                            };
+                        if let Some(rate_limit) = RateLimit::from_http_response(
+                            response.headers(),
+                            response.body().as_deref(),
+                        ) {
+                            return Self::RateLimited(rate_limit);
                         }
                     }
                     Self::WebSocket(WebSocketServiceError::Http(response))
@@ -319,11 +514,174 @@ impl From<prost::DecodeError> for LookupError {
 #[cfg_attr(test, derive(serde::Serialize))]
 struct RateLimitExceededResponse {
     retry_after_seconds: u32,
+    #[serde(flatten)]
+    details: RateLimitDetails,
 }
 
 #[cfg_attr(test, derive(Debug))]
 pub struct ClientResponseCollector(CdsiConnection);
 
+/// Holds attested [`CdsiConnection`]s that haven't been used yet.
+///
+/// The server closes a CDSI connection once its lookup completes, so every
+/// lookup needs its own connection. During a contact sync, though, the next
+/// connection (and its attestation handshake) can be established while the
+/// current lookup is still in progress, then handed to the following request.
+///
+/// Connections are only handed out for the credentials they were established
+/// with. Since credentials from the chat server expire, the pool only keeps
+/// connections for one set of credentials at a time: using it with different
+/// credentials drops everything pooled for the old ones, including connections
+/// that are still being established.
+///
+/// A pooled connection is only handed out if it was put in the pool less than
+/// `idle_timeout` ago; the timeout should be short enough that the server won't
+/// have given up on the idle connection by then.
+pub struct CdsiConnectionPool {
+    idle_timeout: Duration,
+    state: std::sync::Mutex<PoolState>,
+}
+
+#[derive(Default)]
+struct PoolState {
+    /// The credentials for everything in `idle` and `connecting`.
+    auth: Option<Auth>,
+    idle: Vec<(CdsiConnection, Instant)>,
+    /// The number of connections being established by [`CdsiConnectionPool::refill`].
+    connecting: usize,
+    /// Incremented whenever the pool switches credentials.
+    ///
+    /// A refill started before the switch doesn't count towards `connecting`
+    /// any more, even if the pool has since switched back to its credentials.
+    generation: u64,
+}
+
+impl PoolState {
+    /// Drops everything in the pool if it belongs to credentials other than `auth`.
+    fn switch_to(&mut self, auth: &Auth) {
+        if self.auth.as_ref() == Some(auth) {
+            return;
+        }
+        if !self.idle.is_empty() || self.connecting != 0 {
+            log::info!("CDSI credentials changed; dropping pooled connections");
+        }
+        self.idle.clear();
+        self.connecting = 0;
+        self.generation += 1;
+        self.auth = Some(auth.clone());
+    }
+
+    fn put(&mut self, connection: CdsiConnection, idle_timeout: Duration) {
+        self.discard_expired(idle_timeout);
+        if self.idle.len() >= CdsiConnectionPool::MAX_CONNECTIONS {
+            log::info!("CDSI connection pool is full; dropping connection");
+            return;
+        }
+        self.idle.push((connection, Instant::now()));
+    }
+
+    fn discard_expired(&mut self, idle_timeout: Duration) {
+        let count = self.idle.len();
+        self.idle
+            .retain(|(_connection, pooled_at)| pooled_at.elapsed() < idle_timeout);
+        if self.idle.len() != count {
+            log::info!("discarding expired pooled CDSI connection");
+        }
+    }
+}
+
+impl CdsiConnectionPool {
+    pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// The most connections the pool will hold or establish at once.
+    pub const MAX_CONNECTIONS: usize = 2;
+
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            idle_timeout,
+            state: Default::default(),
+        }
+    }
+
+    /// Takes a pooled connection for `auth`, if there is one that hasn't expired.
+    pub fn take(&self, auth: &Auth) -> Option<CdsiConnection> {
+        let mut state = self.state.lock().expect("not poisoned");
+        state.switch_to(auth);
+        state.discard_expired(self.idle_timeout);
+        let (connection, _pooled_at) = state.idle.pop()?;
+        log::info!("reusing pooled CDSI connection");
+        Some(connection)
+    }
+
+    /// Adds a connection established with `auth` to the pool.
+    ///
+    /// The connection is dropped instead if the pool has moved on to other
+    /// credentials, or if it is already full.
+    pub fn put(&self, auth: &Auth, connection: CdsiConnection) {
+        let mut state = self.state.lock().expect("not poisoned");
+        if state.auth.as_ref() != Some(auth) {
+            log::info!("dropping CDSI connection made with old credentials");
+            return;
+        }
+        state.put(connection, self.idle_timeout);
+    }
+
+    /// Takes a pooled connection for `auth`, or establishes a new one using `connect`.
+    pub async fn take_or_connect(
+        &self,
+        auth: &Auth,
+        connect: impl Future<Output = Result<CdsiConnection, LookupError>>,
+    ) -> Result<CdsiConnection, LookupError> {
+        match self.take(auth) {
+            Some(connection) => Ok(connection),
+            None => connect.await,
+        }
+    }
+
+    /// Establishes a connection for a later lookup in the background, using `connect`.
+    ///
+    /// Nothing is done if the pool already holds, or is establishing, as many
+    /// connections for `auth` as it will keep, so a completed handshake is never
+    /// thrown away just to make room for another.
+    pub fn refill(
+        self: &Arc<Self>,
+        auth: &Auth,
+        connect: impl Future<Output = Result<CdsiConnection, LookupError>> + Send + 'static,
+    ) {
+        let generation = {
+            let mut state = self.state.lock().expect("not poisoned");
+            state.switch_to(auth);
+            state.discard_expired(self.idle_timeout);
+            if state.idle.len() + state.connecting >= Self::MAX_CONNECTIONS {
+                return;
+            }
+            state.connecting += 1;
+            state.generation
+        };
+
+        let pool = Arc::clone(self);
+        tokio::spawn(async move {
+            let result = connect.await;
+            let mut state = pool.state.lock().expect("not poisoned");
+            if state.generation != generation {
+                log::info!("dropping CDSI connection made with old credentials");
+                return;
+            }
+            state.connecting -= 1;
+            match result {
+                Ok(connection) => state.put(connection, pool.idle_timeout),
+                Err(e) => log::info!("failed to establish CDSI connection for pool: {e}"),
+            }
+        });
+    }
+}
+
+impl Default for CdsiConnectionPool {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_IDLE_TIMEOUT)
+    }
+}
+
 impl CdsiConnection {
     /// Connect to remote host and verify remote attestation.
     pub async fn connect<C, T>(
@@ -489,14 +847,13 @@ fn err_for_close(close: Option<CloseFrame<'_>>) -> LookupError {
         CdsiCloseCode::RateLimitExceeded => {
             let Some(RateLimitExceededResponse {
                 retry_after_seconds,
+                details,
             }) = serde_json::from_str(reason).ok()
             else {
                 log::warn!("failed to parse rate limit from reason");
                 return unexpected_close(close);
             };
-            LookupError::RateLimited {
-                retry_after_seconds,
-            }
+            LookupError::RateLimited(RateLimit::with_details(retry_after_seconds, details))
         }
         CdsiCloseCode::ServerInternalError | CdsiCloseCode::ServerUnavailable => {
             LookupError::Server {
@@ -527,6 +884,7 @@ mod test {
 
     use super::*;
     use crate::auth::Auth;
+    use crate::rate_limit::RateLimitScope;
 
     #[test]
     fn parse_lookup_response_entries() {
@@ -609,6 +967,193 @@ mod test {
         );
     }
 
+    #[test]
+    fn add_raw_e164s_deduplicates_and_maps_back() {
+        let existing: E164 = "+18005551001".parse().unwrap();
+        let mut request = LookupRequest {
+            prev_e164s: vec![existing],
+            ..Default::default()
+        };
+
+        let mapping = request.add_raw_e164s(
+            [
+                "800-555-1001",
+                "+1 800 555 1002",
+                "not a number",
+                "(800) 555-1002",
+            ],
+            RegionHint::from_calling_code(1).expect("valid"),
+        );
+
+        let second: E164 = "+18005551002".parse().unwrap();
+        assert_eq!(
+            mapping.normalized,
+            [Some(existing), Some(second), None, Some(second)]
+        );
+        assert_eq!(mapping.rejected().collect::<Vec<_>>(), [2]);
+        assert_eq!(request.new_e164s, [second]);
+
+        let found = LookupResponseEntry {
+            e164: second,
+            aci: Some(Aci::from_uuid_bytes([1; 16])),
+            pni: None,
+        };
+        let response = LookupResponse {
+            records: vec![found.clone()],
+            debug_permits_used: 0,
+        };
+        assert_eq!(
+            mapping.resolve(&response),
+            [None, Some(&found), None, Some(&found)]
+        );
+    }
+
+    #[test]
+    fn reconcile_reports_changes_against_existing_contacts() {
+        let e164 = |n: u64| E164::new(NonZeroU64::new(18005550000 + n).unwrap());
+        let aci = |b: u8| Aci::from_uuid_bytes([b; 16]);
+        let pni = |b: u8| Pni::from_uuid_bytes([b; 16]);
+
+        let response = LookupResponse {
+            records: vec![
+                // Unchanged.
+                LookupResponseEntry {
+                    e164: e164(1),
+                    aci: Some(aci(1)),
+                    pni: Some(pni(1)),
+                },
+                // ACI omitted by the server, PNI changed.
+                LookupResponseEntry {
+                    e164: e164(2),
+                    aci: None,
+                    pni: Some(pni(22)),
+                },
+                // Previously unknown ACI.
+                LookupResponseEntry {
+                    e164: e164(3),
+                    aci: Some(aci(3)),
+                    pni: Some(pni(3)),
+                },
+                // Not in the existing mapping at all.
+                LookupResponseEntry {
+                    e164: e164(5),
+                    aci: None,
+                    pni: Some(pni(5)),
+                },
+            ],
+            debug_permits_used: 0,
+        };
+
+        let existing = [
+            (
+                e164(1),
+                ContactIds {
+                    aci: Some(aci(1)),
+                    pni: Some(pni(1)),
+                },
+            ),
+            (
+                e164(2),
+                ContactIds {
+                    aci: Some(aci(2)),
+                    pni: Some(pni(2)),
+                },
+            ),
+            (
+                e164(3),
+                ContactIds {
+                    aci: None,
+                    pni: Some(pni(3)),
+                },
+            ),
+            (
+                e164(4),
+                ContactIds {
+                    aci: Some(aci(4)),
+                    pni: None,
+                },
+            ),
+        ];
+
+        assert_eq!(
+            response.reconcile(existing),
+            [
+                ContactChange::ChangedPni {
+                    e164: e164(2),
+                    previous: Some(pni(2)),
+                    pni: Some(pni(22)),
+                },
+                ContactChange::NewAci {
+                    e164: e164(3),
+                    previous: None,
+                    aci: aci(3),
+                },
+                ContactChange::Removed {
+                    e164: e164(4),
+                    previous: ContactIds {
+                        aci: Some(aci(4)),
+                        pni: None,
+                    },
+                },
+                ContactChange::ChangedPni {
+                    e164: e164(5),
+                    previous: None,
+                    pni: Some(pni(5)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reconcile_treats_entries_without_service_ids_as_removed() {
+        let e164 = |n: u64| E164::new(NonZeroU64::new(18005550000 + n).unwrap());
+        let unregistered = |n: u64| LookupResponseEntry {
+            e164: e164(n),
+            aci: None,
+            pni: None,
+        };
+
+        let response = LookupResponse {
+            records: vec![unregistered(1), unregistered(2)],
+            debug_permits_used: 0,
+        };
+        let previous = ContactIds {
+            aci: Some(Aci::from_uuid_bytes([1; 16])),
+            pni: Some(Pni::from_uuid_bytes([1; 16])),
+        };
+
+        assert_eq!(
+            response.reconcile([(e164(1), previous)]),
+            [ContactChange::Removed {
+                e164: e164(1),
+                previous
+            }]
+        );
+    }
+
+    #[test]
+    fn packed_entries_round_trip() {
+        let entries = [
+            LookupResponseEntry {
+                e164: "+18005551001".parse().unwrap(),
+                aci: Some(Aci::from_uuid_bytes([1; 16])),
+                pni: None,
+            },
+            LookupResponseEntry {
+                e164: "+18005551002".parse().unwrap(),
+                aci: None,
+                pni: Some(Pni::from_uuid_bytes([2; 16])),
+            },
+        ];
+        let packed: Vec<u8> = entries.iter().flat_map(|e| e.to_packed()).collect();
+
+        assert_eq!(LookupResponseEntry::parse_packed(&packed).unwrap(), entries);
+        assert_matches!(
+            LookupResponseEntry::parse_packed(&packed[1..]),
+            Err(LookupResponseParseError::InvalidNumberOfBytes { actual_length: 79 })
+        );
+    }
+
     /// Server-side state relative to a remote request.
     #[derive(Debug, Default, PartialEq)]
     enum FakeServerState {
@@ -747,6 +1292,137 @@ mod test {
         );
     }
 
+    async fn fake_cdsi_connection() -> CdsiConnection {
+        let (server, client) = fake_websocket().await;
+        tokio::spawn(run_attested_server(
+            server,
+            attest::sgx_session::testutil::private_key(),
+            FakeServerState::default().into_handler(),
+        ));
+
+        CdsiConnection(
+            AttestedConnection::connect(client, FAKE_WS_CONFIG, |_fake_attestation| {
+                attest::sgx_session::testutil::handshake_from_tests_data()
+            })
+            .await
+            .expect("handshake failed"),
+        )
+    }
+
+    fn fake_auth(username: &str) -> Auth {
+        Auth {
+            username: username.to_owned(),
+            password: "password".to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn pooled_connection_expires() {
+        let cdsi_connection = fake_cdsi_connection().await;
+        let auth = fake_auth("username");
+
+        tokio::time::pause();
+        const IDLE_TIMEOUT: Duration = Duration::from_secs(3);
+        let pool = CdsiConnectionPool::new(IDLE_TIMEOUT);
+        assert_matches!(pool.take(&auth), None);
+
+        pool.put(&auth, cdsi_connection);
+        tokio::time::advance(IDLE_TIMEOUT / 2).await;
+        let cdsi_connection = pool.take(&auth).expect("not expired");
+        assert_matches!(pool.take(&auth), None, "already taken");
+
+        pool.put(&auth, cdsi_connection);
+        tokio::time::advance(IDLE_TIMEOUT).await;
+        assert_matches!(pool.take(&auth), None, "expired");
+    }
+
+    #[tokio::test]
+    async fn pooled_connections_are_keyed_by_auth() {
+        let pool = CdsiConnectionPool::default();
+        let auth = fake_auth("username");
+        let other_auth = fake_auth("other");
+
+        assert_matches!(pool.take(&auth), None);
+        pool.put(&auth, fake_cdsi_connection().await);
+        pool.put(&other_auth, fake_cdsi_connection().await);
+
+        // The connection for other credentials was dropped...
+        assert_matches!(pool.take(&auth), Some(_));
+        assert_matches!(pool.take(&auth), None);
+
+        // ...and switching credentials drops what was pooled for the old ones.
+        pool.put(&auth, fake_cdsi_connection().await);
+        assert_matches!(pool.take(&other_auth), None);
+        assert_matches!(pool.take(&auth), None);
+    }
+
+    #[tokio::test]
+    async fn pool_refill_stops_when_full() {
+        let pool = Arc::new(CdsiConnectionPool::default());
+        let auth = fake_auth("username");
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let rx = Arc::new(tokio::sync::Mutex::new(rx));
+        let refill = |pool: &Arc<CdsiConnectionPool>| {
+            let rx = Arc::clone(&rx);
+            pool.refill(&auth, async move {
+                let connection: CdsiConnection = rx.lock().await.recv().await.expect("sent");
+                Ok(connection)
+            })
+        };
+
+        for _ in 0..CdsiConnectionPool::MAX_CONNECTIONS + 1 {
+            refill(&pool);
+        }
+        for _ in 0..CdsiConnectionPool::MAX_CONNECTIONS {
+            tx.send(fake_cdsi_connection().await)
+                .expect("receiver alive");
+        }
+        // Let the refills finish.
+        while pool.state.lock().expect("not poisoned").connecting != 0 {
+            tokio::task::yield_now().await;
+        }
+
+        // The extra refill never started, so it isn't waiting for a connection.
+        assert_eq!(
+            pool.state.lock().expect("not poisoned").idle.len(),
+            CdsiConnectionPool::MAX_CONNECTIONS
+        );
+        refill(&pool);
+        assert_eq!(pool.state.lock().expect("not poisoned").connecting, 0);
+    }
+
+    #[tokio::test]
+    async fn pool_refill_from_before_a_credential_switch_is_dropped() {
+        let pool = Arc::new(CdsiConnectionPool::default());
+        let auth = fake_auth("username");
+        let other_auth = fake_auth("other");
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        pool.refill(&auth, async move { Ok(rx.await.expect("sent")) });
+
+        // Switch away and back while the refill is still connecting.
+        assert_matches!(pool.take(&other_auth), None);
+        assert_matches!(pool.take(&auth), None);
+
+        tx.send(fake_cdsi_connection().await)
+            .unwrap_or_else(|_| panic!("receiver alive"));
+        // The refill task holds the only other reference to the pool.
+        while Arc::strong_count(&pool) > 1 {
+            tokio::task::yield_now().await;
+        }
+
+        {
+            let state = pool.state.lock().expect("not poisoned");
+            assert_eq!(state.connecting, 0);
+            assert!(state.idle.is_empty(), "stale connection was pooled");
+        }
+
+        // New refills for the same credentials still start.
+        pool.refill(&auth, std::future::pending());
+        assert_eq!(pool.state.lock().expect("not poisoned").connecting, 1);
+    }
+
     const RETRY_AFTER_SECS: u32 = 12345;
 
     #[tokio::test]
@@ -759,6 +1435,10 @@ mod test {
                 code: CloseCode::Bad(4008),
                 reason: serde_json::to_string_pretty(&RateLimitExceededResponse {
                     retry_after_seconds: RETRY_AFTER_SECS,
+                    details: RateLimitDetails {
+                        scope: RateLimitScope::PerAccount,
+                        reason: Some("lookup quota exhausted".to_owned()),
+                    },
                 })
                 .expect("can JSON-encode")
                 .into(),
@@ -789,8 +1469,10 @@ mod test {
 
         assert_matches!(
             response,
-            Err(LookupError::RateLimited {
-                retry_after_seconds: RETRY_AFTER_SECS
+            Err(LookupError::RateLimited(rate_limit)) => assert_eq!(rate_limit, RateLimit {
+                retry_after_seconds: RETRY_AFTER_SECS,
+                scope: RateLimitScope::PerAccount,
+                reason: Some("lookup quota exhausted".to_owned()),
             })
         );
     }
@@ -805,6 +1487,7 @@ mod test {
                 code: CloseCode::Bad(4008),
                 reason: serde_json::to_string_pretty(&RateLimitExceededResponse {
                     retry_after_seconds: RETRY_AFTER_SECS,
+                    details: RateLimitDetails::default(),
                 })
                 .expect("can JSON-encode")
                 .into(),
@@ -838,9 +1521,9 @@ mod test {
 
         assert_matches!(
             response,
-            Err(LookupError::RateLimited {
-                retry_after_seconds: RETRY_AFTER_SECS
-            })
+            Err(LookupError::RateLimited(rate_limit)) => {
+                assert_eq!(rate_limit, RateLimit::retry_after(RETRY_AFTER_SECS))
+            }
         )
     }
 
@@ -868,9 +1551,9 @@ mod test {
         let result = CdsiConnection::connect(&endpoint_connection, connector, auth).await;
         assert_matches!(
             result,
-            Err(LookupError::RateLimited {
-                retry_after_seconds: 100
-            })
+            Err(LookupError::RateLimited(rate_limit)) => {
+                assert_eq!(rate_limit, RateLimit::retry_after(100))
+            }
         )
     }
 
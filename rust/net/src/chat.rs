@@ -9,13 +9,13 @@ use ::http::uri::PathAndQuery;
 use ::http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
 use async_trait::async_trait;
 use futures_util::future::BoxFuture;
-use libsignal_net_infra::connection_manager::MultiRouteConnectionManager;
+use libsignal_net_infra::connection_manager::{MultiRouteConnectionManager, RetryPolicy};
 use libsignal_net_infra::service::{Service, ServiceConnectorWithDecorator};
 use libsignal_net_infra::timeouts::{MULTI_ROUTE_CONNECTION_TIMEOUT, ONE_ROUTE_CONNECTION_TIMEOUT};
 use libsignal_net_infra::utils::ObservableEvent;
-use libsignal_net_infra::ws::WebSocketClientConnector;
+use libsignal_net_infra::ws::{WebSocketClientConnector, WebSocketLimits};
 use libsignal_net_infra::{
-    make_ws_config, AsHttpHeader, EndpointConnection, HttpRequestDecorator, IpType,
+    make_ws_config, AsHttpHeader, EndpointConnection, HttpRequestDecorator, IpType, RouteType,
     TransportConnector,
 };
 
@@ -24,12 +24,20 @@ use crate::chat::ws::{ChatOverWebSocketServiceConnector, ServerEvent};
 use crate::env::{add_user_agent_header, ConnectionConfig};
 use crate::proto;
 
+pub mod background;
 mod error;
-pub use error::ChatServiceError;
+pub use error::{ChatServiceError, DisconnectInfo};
 
+pub mod key_transparency;
 pub mod noise;
+pub mod pre_keys;
+pub mod profile_key_credential;
+pub mod receipts;
+pub mod sender_certificate;
 pub mod server_requests;
+pub mod server_time;
 pub mod service;
+pub mod unidentified_access;
 pub mod ws;
 pub mod ws2;
 
@@ -185,6 +193,73 @@ where
         self.unauth_service.send_and_debug(msg, timeout).await
     }
 
+    /// Fetches a new sender certificate for this account.
+    ///
+    /// See [`sender_certificate::parse_response`] for how the server's response is checked.
+    pub async fn fetch_sender_certificate(
+        &self,
+        include_e164: bool,
+        timeout: Duration,
+    ) -> Result<libsignal_protocol::SenderCertificate, ChatServiceError> {
+        let response = self
+            .send_authenticated(sender_certificate::request(include_e164), timeout)
+            .await?;
+        sender_certificate::parse_response(response)
+    }
+
+    /// Fetches an expiring profile key credential for `aci`'s `profile_key`.
+    ///
+    /// This makes the request and immediately verifies and unblinds the server's response, so the
+    /// result is ready to store. See [`profile_key_credential::parse_response`] for how the
+    /// response is checked.
+    pub async fn fetch_profile_key_credential(
+        &self,
+        server_params: &zkgroup::ServerPublicParams,
+        aci: libsignal_core::Aci,
+        profile_key: zkgroup::profiles::ProfileKey,
+        randomness: zkgroup::RandomnessBytes,
+        current_time: zkgroup::Timestamp,
+        timeout: Duration,
+    ) -> Result<zkgroup::profiles::ExpiringProfileKeyCredential, ChatServiceError> {
+        let (request, context) =
+            profile_key_credential::request(server_params, aci, profile_key, randomness);
+        let response = self.send_authenticated(request, timeout).await?;
+        profile_key_credential::parse_response(response, server_params, &context, current_time)
+    }
+
+    /// Searches the key transparency log for `request.search_key`.
+    ///
+    /// `cache` is consulted first; only on a miss is a request sent, and its response is verified
+    /// and added to `cache` before being returned.
+    pub async fn search_key_transparency(
+        &self,
+        config: &libsignal_keytrans::PublicConfig,
+        request: libsignal_keytrans::SearchRequest,
+        cache: &key_transparency::SearchCache,
+        timeout: Duration,
+    ) -> Result<key_transparency::SearchResult, ChatServiceError> {
+        if let Some(cached) = cache.lookup(config, &request, std::time::SystemTime::now()) {
+            return Ok(cached);
+        }
+        let response = self
+            .send_authenticated(key_transparency::search_request(&request), timeout)
+            .await?;
+        let response = key_transparency::parse_search_response(response)?;
+        cache.verify_and_store(config, request, response, std::time::SystemTime::now())
+    }
+
+    /// Checks how many one-time pre-keys the server has left for one of this account's identities.
+    pub async fn pre_key_counts(
+        &self,
+        identity: libsignal_core::ServiceIdKind,
+        timeout: Duration,
+    ) -> Result<pre_keys::PreKeyCounts, ChatServiceError> {
+        let response = self
+            .send_authenticated(pre_keys::count_request(identity), timeout)
+            .await?;
+        pre_keys::parse_count_response(response)
+    }
+
     pub async fn connect_authenticated(&self) -> Result<DebugInfo, ChatServiceError> {
         self.auth_service.connect_and_debug().await
     }
@@ -486,33 +561,36 @@ pub fn chat_service<T: TransportConnector + 'static>(
 pub fn endpoint_connection(
     connection_config: &ConnectionConfig,
     user_agent: &str,
-    include_fallback: bool,
+    route_order: &[RouteType],
+    limits: WebSocketLimits,
+    retry_policy: RetryPolicy,
     network_change_event: &ObservableEvent,
 ) -> EndpointConnection<MultiRouteConnectionManager> {
     let chat_endpoint = PathAndQuery::from_static(crate::env::constants::WEB_SOCKET_PATH);
-    let chat_connection_params = if include_fallback {
-        connection_config.connection_params_with_fallback()
-    } else {
-        vec![connection_config.direct_connection_params()]
-    };
+    let chat_connection_params = connection_config.connection_params_in_order(route_order);
     let chat_connection_params = add_user_agent_header(chat_connection_params, user_agent);
-    let chat_ws_config = make_ws_config(chat_endpoint, ONE_ROUTE_CONNECTION_TIMEOUT);
+    let chat_ws_config =
+        make_ws_config(chat_endpoint, ONE_ROUTE_CONNECTION_TIMEOUT).with_limits(limits);
     EndpointConnection::new_multi(
         chat_connection_params,
         ONE_ROUTE_CONNECTION_TIMEOUT,
         chat_ws_config,
+        retry_policy,
         network_change_event,
     )
 }
 
 #[cfg(feature = "test-util")]
 pub mod test_support {
-    use std::sync::Arc;
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::{Arc, Mutex};
     use std::time::Duration;
 
     use http::uri::PathAndQuery;
+    use http::Method;
     use libsignal_net_infra::dns::DnsResolver;
     use libsignal_net_infra::tcp_ssl::DirectConnector;
+    use libsignal_net_infra::ws::WebSocketServiceError;
     use libsignal_net_infra::{make_ws_config, ConnectionParams, EndpointConnection};
     use tokio::sync::mpsc;
 
@@ -543,6 +621,7 @@ pub mod test_support {
             connection_params,
             one_route_connect_timeout,
             chat_ws_config,
+            RetryPolicy::DEFAULT,
             &network_change_event,
         );
 
@@ -558,6 +637,100 @@ pub mod test_support {
         )
         .into_dyn()
     }
+
+    /// A chat service that answers from canned responses instead of the network.
+    ///
+    /// Responses are registered with [`add_response`](Self::add_response) and matched by method
+    /// and path (including the query), first-in first-out. Every request is recorded, whether or
+    /// not it matched, and can be inspected with [`take_sent_request`](Self::take_sent_request). A
+    /// request with no registered response fails with [`ChatServiceError::WebSocket`].
+    ///
+    /// Clones share the same responses and request log.
+    #[derive(Clone, Default)]
+    pub struct MockChatService {
+        state: Arc<Mutex<MockChatState>>,
+    }
+
+    #[derive(Default)]
+    struct MockChatState {
+        responses: HashMap<(Method, PathAndQuery), VecDeque<Response>>,
+        sent: VecDeque<Request>,
+    }
+
+    impl MockChatService {
+        pub fn add_response(&self, method: Method, path: PathAndQuery, response: Response) {
+            self.state
+                .lock()
+                .expect("not poisoned")
+                .responses
+                .entry((method, path))
+                .or_default()
+                .push_back(response);
+        }
+
+        /// Returns the oldest request that hasn't been taken yet.
+        pub fn take_sent_request(&self) -> Option<Request> {
+            self.state.lock().expect("not poisoned").sent.pop_front()
+        }
+
+        fn debug_info() -> DebugInfo {
+            DebugInfo {
+                ip_type: IpType::Unknown,
+                duration: Duration::ZERO,
+                connection_info: "mock".to_owned(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ChatService for MockChatService {
+        async fn send(
+            &self,
+            msg: Request,
+            _timeout: Duration,
+        ) -> Result<Response, ChatServiceError> {
+            let mut state = self.state.lock().expect("not poisoned");
+            let response = state
+                .responses
+                .get_mut(&(msg.method.clone(), msg.path.clone()))
+                .and_then(VecDeque::pop_front);
+            state.sent.push_back(msg);
+            response.ok_or(ChatServiceError::WebSocket(WebSocketServiceError::Other(
+                "no mock response for request",
+            )))
+        }
+
+        async fn connect(&self) -> Result<(), ChatServiceError> {
+            Ok(())
+        }
+
+        async fn disconnect(&self) {}
+    }
+
+    #[async_trait]
+    impl ChatServiceWithDebugInfo for MockChatService {
+        async fn send_and_debug(
+            &self,
+            msg: Request,
+            timeout: Duration,
+        ) -> (Result<Response, ChatServiceError>, DebugInfo) {
+            (self.send(msg, timeout).await, Self::debug_info())
+        }
+
+        async fn connect_and_debug(&self) -> Result<DebugInfo, ChatServiceError> {
+            Ok(Self::debug_info())
+        }
+    }
+
+    /// Builds a [`Chat`] whose authenticated and unauthenticated halves are backed by the given
+    /// mocks.
+    pub fn mock_chat(auth: MockChatService, unauth: MockChatService) -> AnyChat {
+        Chat {
+            auth_service: AuthorizedChatService { inner: auth },
+            unauth_service: AnonymousChatService { inner: unauth },
+        }
+        .into_dyn()
+    }
 }
 
 #[cfg(test)]
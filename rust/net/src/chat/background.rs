@@ -0,0 +1,286 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Support for wrapping up chat activity when the app is about to be suspended.
+//!
+//! Platforms like iOS give an app a limited amount of background execution time before suspending
+//! it. The best use of that time is to finish the work that's already in flight—in particular,
+//! acknowledging messages that have already been delivered, so the server doesn't send them
+//! again—and then close the connection cleanly, rather than letting it get cut off mid-request.
+//!
+//! [`BackgroundFlushTracker`] keeps count of that outstanding work so it can be waited on.
+
+use std::sync::Arc;
+
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+use crate::chat::ChatServiceError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PendingWorkKind {
+    Ack,
+    Request,
+}
+
+/// What happened to outgoing work while preparing to go into the background.
+///
+/// Covers everything that was outstanding when [`BackgroundFlushTracker::flush`] was called, plus
+/// any acks started while it was running.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BackgroundFlushReport {
+    /// Acks that were successfully sent.
+    pub acks_flushed: u32,
+    /// Acks that failed, or that were still outstanding when time ran out.
+    ///
+    /// The server will deliver the corresponding messages again.
+    pub acks_unflushed: u32,
+    /// Requests that completed successfully.
+    pub requests_flushed: u32,
+    /// Requests that failed, or that were still outstanding when time ran out.
+    pub requests_unflushed: u32,
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    outstanding: u32,
+    flushed: u32,
+    failed: u32,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    quiescing: bool,
+    acks: Counts,
+    requests: Counts,
+}
+
+impl State {
+    fn counts(&mut self, kind: PendingWorkKind) -> &mut Counts {
+        match kind {
+            PendingWorkKind::Ack => &mut self.acks,
+            PendingWorkKind::Request => &mut self.requests,
+        }
+    }
+
+    fn has_outstanding_work(&self) -> bool {
+        self.acks.outstanding != 0 || self.requests.outstanding != 0
+    }
+}
+
+/// Tracks outgoing acks and requests so they can be flushed before the app is suspended.
+///
+/// Cheap to clone; all clones share the same counts.
+#[derive(Clone, Debug)]
+pub struct BackgroundFlushTracker {
+    state: Arc<watch::Sender<State>>,
+}
+
+impl Default for BackgroundFlushTracker {
+    fn default() -> Self {
+        let (state, _receiver) = watch::channel(State::default());
+        Self {
+            state: Arc::new(state),
+        }
+    }
+}
+
+impl BackgroundFlushTracker {
+    /// Records an ack that's about to be sent.
+    ///
+    /// Acks are accepted even while flushing, since the messages they acknowledge have already been
+    /// received.
+    pub fn start_ack(&self) -> PendingWork {
+        self.state
+            .send_modify(|state| state.counts(PendingWorkKind::Ack).outstanding += 1);
+        PendingWork {
+            tracker: self.clone(),
+            kind: PendingWorkKind::Ack,
+            finished: false,
+        }
+    }
+
+    /// Records a request that's about to be sent.
+    ///
+    /// Once [`flush`](Self::flush) has been called, new requests are refused with
+    /// [`ChatServiceError::ServiceIntentionallyDisconnected`] so that the work already in flight
+    /// gets the remaining time.
+    pub fn start_request(&self) -> Result<PendingWork, ChatServiceError> {
+        let accepted = self.state.send_if_modified(|state| {
+            if state.quiescing {
+                return false;
+            }
+            state.counts(PendingWorkKind::Request).outstanding += 1;
+            true
+        });
+        if !accepted {
+            return Err(ChatServiceError::ServiceIntentionallyDisconnected);
+        }
+        Ok(PendingWork {
+            tracker: self.clone(),
+            kind: PendingWorkKind::Request,
+            finished: false,
+        })
+    }
+
+    /// Stops accepting new requests and waits for outstanding work to finish, or for `deadline` to
+    /// pass, whichever comes first.
+    ///
+    /// The tracker stays quiescent until [`resume`](Self::resume) is called.
+    pub async fn flush(&self, deadline: Instant) -> BackgroundFlushReport {
+        self.state.send_modify(|state| {
+            state.quiescing = true;
+            state.acks.flushed = 0;
+            state.acks.failed = 0;
+            state.requests.flushed = 0;
+            state.requests.failed = 0;
+        });
+
+        let mut receiver = self.state.subscribe();
+        // The sender is owned by `self`, so waiting can't fail.
+        let _ = tokio::time::timeout_at(
+            deadline,
+            receiver.wait_for(|state| !state.has_outstanding_work()),
+        )
+        .await;
+
+        let state = self.state.borrow();
+        BackgroundFlushReport {
+            acks_flushed: state.acks.flushed,
+            acks_unflushed: state.acks.failed + state.acks.outstanding,
+            requests_flushed: state.requests.flushed,
+            requests_unflushed: state.requests.failed + state.requests.outstanding,
+        }
+    }
+
+    /// Accepts new requests again, e.g. because the app has returned to the foreground.
+    pub fn resume(&self) {
+        self.state.send_if_modified(|state| {
+            let was_quiescing = state.quiescing;
+            state.quiescing = false;
+            was_quiescing
+        });
+    }
+
+    pub fn is_quiescing(&self) -> bool {
+        self.state.borrow().quiescing
+    }
+
+    fn finish(&self, kind: PendingWorkKind, succeeded: bool) {
+        self.state.send_modify(|state| {
+            let counts = state.counts(kind);
+            counts.outstanding -= 1;
+            if succeeded {
+                counts.flushed += 1;
+            } else {
+                counts.failed += 1;
+            }
+        });
+    }
+}
+
+/// A single piece of outgoing work registered with a [`BackgroundFlushTracker`].
+///
+/// Dropping this without calling [`finish`](Self::finish) counts as a failure.
+#[derive(Debug)]
+#[must_use]
+pub struct PendingWork {
+    tracker: BackgroundFlushTracker,
+    kind: PendingWorkKind,
+    finished: bool,
+}
+
+impl PendingWork {
+    pub fn finish(mut self, succeeded: bool) {
+        self.finished = true;
+        self.tracker.finish(self.kind, succeeded);
+    }
+}
+
+impl Drop for PendingWork {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.tracker.finish(self.kind, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn flush_with_nothing_outstanding() {
+        let tracker = BackgroundFlushTracker::default();
+        let report = tracker.flush(Instant::now() + Duration::from_secs(5)).await;
+        assert_eq!(report, BackgroundFlushReport::default());
+        assert!(tracker.is_quiescing());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn flush_waits_for_outstanding_work() {
+        let tracker = BackgroundFlushTracker::default();
+        let ack = tracker.start_ack();
+        let request = tracker.start_request().expect("not quiescing");
+
+        let flush = tokio::spawn({
+            let tracker = tracker.clone();
+            async move { tracker.flush(Instant::now() + Duration::from_secs(5)).await }
+        });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        // New requests are refused, but acks can still go out.
+        assert_matches!(
+            tracker.start_request(),
+            Err(ChatServiceError::ServiceIntentionallyDisconnected)
+        );
+        let late_ack = tracker.start_ack();
+
+        ack.finish(true);
+        request.finish(false);
+        late_ack.finish(true);
+
+        let start = Instant::now();
+        let report = flush.await.expect("no panic");
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(
+            report,
+            BackgroundFlushReport {
+                acks_flushed: 2,
+                acks_unflushed: 0,
+                requests_flushed: 0,
+                requests_unflushed: 1,
+            }
+        );
+
+        tracker.resume();
+        tracker
+            .start_request()
+            .expect("accepted after resuming")
+            .finish(true);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn flush_reports_work_outstanding_at_deadline() {
+        let tracker = BackgroundFlushTracker::default();
+        let _ack = tracker.start_ack();
+        tracker.start_request().expect("not quiescing").finish(true);
+
+        let report = tracker.flush(Instant::now() + Duration::from_secs(5)).await;
+        assert_eq!(
+            report,
+            BackgroundFlushReport {
+                acks_flushed: 0,
+                acks_unflushed: 1,
+                requests_flushed: 0,
+                requests_unflushed: 0,
+            }
+        );
+    }
+}
@@ -4,9 +4,10 @@
 //
 
 use libsignal_net_infra::errors::{LogSafeDisplay, TransportConnectError};
+use libsignal_net_infra::service;
 use libsignal_net_infra::ws::{WebSocketConnectError, WebSocketServiceError};
-use libsignal_net_infra::{extract_retry_after_seconds, service};
 
+use crate::rate_limit::RateLimit;
 use crate::ws::WebSocketServiceConnectError;
 
 #[derive(Debug, thiserror::Error, displaydoc::Display)]
@@ -34,17 +35,110 @@ pub enum ChatServiceError {
     /// All connection routes failed or timed out, {attempts} attempts made
     AllConnectionRoutesFailed { attempts: u16 },
     /// Service is inactive
-    ServiceInactive,
+    ServiceInactive(Option<DisconnectInfo>),
     /// Service is unavailable due to the lost connection
-    ServiceUnavailable,
+    ServiceUnavailable(Option<DisconnectInfo>),
     /// Service was disconnected by an intentional local call
     ServiceIntentionallyDisconnected,
-    /// Service is unavailable now, try again after {retry_after_seconds}s
-    RetryLater { retry_after_seconds: u32 },
+    /// Service is unavailable now, try again after {0}
+    RetryLater(RateLimit),
+    /// Server rejected the request as unauthorized
+    RequestUnauthorized,
+    /// Server could not find what the request asked for
+    NotFound,
+    // Displayed the same as `WebSocket(WebSocketServiceError::ChannelClosed)`; this is that error
+    // with what the server told us about why.
+    /// websocket error: channel already closed
+    ChannelClosed(DisconnectInfo),
 }
 
 impl LogSafeDisplay for ChatServiceError {}
 
+impl ChatServiceError {
+    /// What the server said about why the connection was closed or refused, if anything.
+    pub fn disconnect_info(&self) -> Option<DisconnectInfo> {
+        match self {
+            Self::ServiceInactive(info) | Self::ServiceUnavailable(info) => info.clone(),
+            Self::ChannelClosed(info) => Some(info.clone()),
+            Self::WebSocket(WebSocketServiceError::Http(response)) => {
+                DisconnectInfo::from_alert_headers(response.headers())
+            }
+            _ => None,
+        }
+    }
+
+    /// Builds the error for the server closing an established connection.
+    ///
+    /// `alerts` are the ones sent with the `101 Switching Protocols` response that opened the
+    /// connection. Without a close frame or any alerts, there's nothing to report beyond the
+    /// channel being closed.
+    pub(crate) fn closed_by_server(close_frame: Option<(u16, &str)>, alerts: Vec<String>) -> Self {
+        let info = match close_frame {
+            Some((code, reason)) => DisconnectInfo::from_close_frame(code, reason),
+            None if alerts.is_empty() => {
+                return WebSocketServiceError::ChannelClosed.into();
+            }
+            None => DisconnectInfo::default(),
+        };
+        Self::ChannelClosed(DisconnectInfo { alerts, ..info })
+    }
+}
+
+/// What the server said when it closed (or refused) a chat connection.
+///
+/// Lets an app tell "the server asked me to go away" apart from a plain network failure.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DisconnectInfo {
+    /// The websocket close code, if the server sent a close frame.
+    pub close_code: Option<u16>,
+    /// The reason given in the close frame, if it was not empty.
+    pub reason: Option<String>,
+    /// The values of any `X-Signal-Alert` headers on the server's response.
+    pub alerts: Vec<String>,
+}
+
+const ALERT_HEADER_NAME: &str = "x-signal-alert";
+
+impl DisconnectInfo {
+    pub(crate) fn from_close_frame(code: u16, reason: &str) -> Self {
+        Self {
+            close_code: Some(code),
+            reason: (!reason.is_empty()).then(|| reason.to_owned()),
+            alerts: vec![],
+        }
+    }
+
+    /// Collects the `X-Signal-Alert` headers from an HTTP response.
+    ///
+    /// Returns `None` if there weren't any, since there's nothing else to report in that case.
+    pub(crate) fn from_alert_headers(headers: &http::HeaderMap) -> Option<Self> {
+        let alerts = alerts_from_headers(headers);
+        (!alerts.is_empty()).then_some(Self {
+            alerts,
+            ..Default::default()
+        })
+    }
+}
+
+/// Returns the values of any `X-Signal-Alert` headers, split on commas.
+pub(crate) fn alerts_from_headers(headers: &http::HeaderMap) -> Vec<String> {
+    headers
+        .get_all(ALERT_HEADER_NAME)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|alert| alert.trim().to_owned())
+        .filter(|alert| !alert.is_empty())
+        .collect()
+}
+
+impl From<&service::ServerClose> for DisconnectInfo {
+    fn from(value: &service::ServerClose) -> Self {
+        let service::ServerClose { code, reason } = value;
+        Self::from_close_frame(*code, reason)
+    }
+}
+
 impl From<WebSocketServiceError> for ChatServiceError {
     fn from(e: WebSocketServiceError) -> Self {
         Self::WebSocket(e)
@@ -70,6 +164,9 @@ impl From<WebSocketServiceConnectError> for ChatServiceError {
                     TransportConnectError::CertError => {
                         WebSocketServiceError::Other("failed to load certificates")
                     }
+                    TransportConnectError::PinningFailure => {
+                        WebSocketServiceError::Other("certificate pinning failure")
+                    }
                     TransportConnectError::ProxyProtocol => {
                         WebSocketServiceError::Other("proxy protocol error")
                     }
@@ -86,10 +183,10 @@ impl From<WebSocketServiceConnectError> for ChatServiceError {
                 received_at: _,
             } => {
                 // Retry-After takes precedence over everything else.
-                if let Some(retry_after_seconds) = extract_retry_after_seconds(response.headers()) {
-                    return Self::RetryLater {
-                        retry_after_seconds,
-                    };
+                if let Some(rate_limit) =
+                    RateLimit::from_http_response(response.headers(), response.body().as_deref())
+                {
+                    return Self::RetryLater(rate_limit);
                 }
                 match response.status().as_u16() {
                     499 => Self::AppExpired,
@@ -98,6 +195,7 @@ impl From<WebSocketServiceConnectError> for ChatServiceError {
                         // but unidentified sockets should never produce a 403 anyway.
                         Self::DeviceDeregistered
                     }
+                    // Any alerts stay with the response; see `disconnect_info`.
                     _ => Self::WebSocket(WebSocketServiceError::Http(response)),
                 }
             }
@@ -124,8 +222,106 @@ impl<E: LogSafeDisplay + Into<ChatServiceError>> From<service::ConnectError<E>>
 impl From<service::StateError> for ChatServiceError {
     fn from(e: service::StateError) -> Self {
         match e {
-            service::StateError::Inactive => Self::ServiceInactive,
-            service::StateError::ServiceUnavailable => Self::ServiceUnavailable,
+            service::StateError::Inactive => Self::ServiceInactive(None),
+            service::StateError::ServiceUnavailable(server_close) => {
+                Self::ServiceUnavailable(server_close.as_ref().map(DisconnectInfo::from))
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[test]
+    fn alert_headers_are_collected() {
+        let mut headers = http::HeaderMap::new();
+        assert_eq!(DisconnectInfo::from_alert_headers(&headers), None);
+
+        headers.append(
+            ALERT_HEADER_NAME,
+            "critical-idle-primary-device".parse().unwrap(),
+        );
+        headers.append(
+            ALERT_HEADER_NAME,
+            "idle-primary-device, ,other".parse().unwrap(),
+        );
+        assert_eq!(
+            DisconnectInfo::from_alert_headers(&headers),
+            Some(DisconnectInfo {
+                close_code: None,
+                reason: None,
+                alerts: vec![
+                    "critical-idle-primary-device".to_owned(),
+                    "idle-primary-device".to_owned(),
+                    "other".to_owned(),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn server_close_without_reason() {
+        let info = DisconnectInfo::from(&service::ServerClose {
+            code: 4001,
+            reason: "".to_owned(),
+        });
+        assert_eq!(info.close_code, Some(4001));
+        assert_eq!(info.reason, None);
+    }
+
+    #[test]
+    fn closed_by_server_keeps_upgrade_alerts() {
+        assert_matches!(
+            ChatServiceError::closed_by_server(None, vec![]),
+            ChatServiceError::WebSocket(WebSocketServiceError::ChannelClosed)
+        );
+
+        let alerts = vec!["critical-idle-primary-device".to_owned()];
+        let error = ChatServiceError::closed_by_server(None, alerts.clone());
+        assert_eq!(error.to_string(), "websocket error: channel already closed");
+        assert_eq!(
+            error.disconnect_info(),
+            Some(DisconnectInfo {
+                close_code: None,
+                reason: None,
+                alerts: alerts.clone(),
+            })
+        );
+
+        assert_eq!(
+            ChatServiceError::closed_by_server(Some((4000, "go elsewhere")), alerts.clone())
+                .disconnect_info(),
+            Some(DisconnectInfo {
+                close_code: Some(4000),
+                reason: Some("go elsewhere".to_owned()),
+                alerts,
+            })
+        );
+    }
+
+    #[test]
+    fn rejected_upgrade_keeps_status_and_alerts() {
+        let response = http::Response::builder()
+            .status(http::StatusCode::SERVICE_UNAVAILABLE)
+            .header(ALERT_HEADER_NAME, "critical-idle-primary-device")
+            .body(None)
+            .unwrap();
+        let error = ChatServiceError::from(WebSocketServiceConnectError::RejectedByServer {
+            response,
+            received_at: tokio::time::Instant::now(),
+        });
+        let response = assert_matches!(
+            &error,
+            ChatServiceError::WebSocket(WebSocketServiceError::Http(response)) => response
+        );
+        assert_eq!(response.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            error.disconnect_info().map(|info| info.alerts),
+            Some(vec!["critical-idle-primary-device".to_owned()])
+        );
+    }
+}
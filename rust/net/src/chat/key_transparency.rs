@@ -0,0 +1,314 @@
+//
+// Copyright 2026 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Key transparency searches over an authenticated chat connection.
+//!
+//! Searching for the same keys repeatedly doesn't need a network request each time: a
+//! [`SearchCache`] remembers verified results and is consulted before anything is sent. See
+//! [`libsignal_keytrans::SearchResultCache`] for what makes a cached result safe to reuse.
+
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use http::header::CONTENT_TYPE;
+use http::uri::PathAndQuery;
+use http::{HeaderMap, HeaderValue, Method, StatusCode};
+use libsignal_keytrans::{
+    InMemorySearchResultCache, KeyTransparency, PublicConfig, SearchContext, SearchRequest,
+    SearchResponse, TreeHead, TreeRoot,
+};
+use prost::Message as _;
+
+use crate::chat::{ChatServiceError, Request, Response};
+use crate::rate_limit::RateLimit;
+
+const SEARCH_PATH: &str = "/v1/key-transparency/search";
+
+/// A verified search result, either fresh from the server or from a [`SearchCache`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchResult {
+    /// The value the search key maps to, if there is one.
+    pub value: Option<Vec<u8>>,
+    pub tree_head: TreeHead,
+    pub tree_root: TreeRoot,
+    /// Whether the result was answered from the cache without a network request.
+    pub from_cache: bool,
+}
+
+/// Verified search results shared between searches.
+///
+/// Results are looked up against the size of the latest tree head verified through this cache, so
+/// a key is searched on the network again once its result expires or any search observes a larger
+/// tree.
+#[derive(Debug)]
+pub struct SearchCache {
+    state: Mutex<SearchCacheState>,
+}
+
+#[derive(Debug)]
+struct SearchCacheState {
+    results: InMemorySearchResultCache,
+    latest_tree_size: Option<u64>,
+}
+
+impl SearchCache {
+    /// Creates a cache that keeps results for `ttl` and holds at most `max_entries` search keys.
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            state: Mutex::new(SearchCacheState {
+                results: InMemorySearchResultCache::with_max_entries(ttl, max_entries),
+                latest_tree_size: None,
+            }),
+        }
+    }
+
+    /// Returns the cached result for `request`, if one was verified against the latest tree head.
+    pub fn lookup(
+        &self,
+        config: &PublicConfig,
+        request: &SearchRequest,
+        now: SystemTime,
+    ) -> Option<SearchResult> {
+        let state = self.state.lock().expect("not poisoned");
+        let tree_size = state.latest_tree_size?;
+        let kt = KeyTransparency {
+            config: config.clone(),
+        };
+        let cached = kt.cached_search(&state.results, request, tree_size, now)?;
+        Some(SearchResult {
+            value: cached.value,
+            tree_head: cached.tree_head,
+            tree_root: cached.tree_root,
+            from_cache: true,
+        })
+    }
+
+    /// Verifies the server's `response` to `request` and stores the result for later lookups.
+    ///
+    /// A response that fails verification is logged and reported as
+    /// [`ChatServiceError::IncomingDataInvalid`]; nothing is cached for it.
+    pub fn verify_and_store(
+        &self,
+        config: &PublicConfig,
+        request: SearchRequest,
+        response: SearchResponse,
+        now: SystemTime,
+    ) -> Result<SearchResult, ChatServiceError> {
+        let value = response.value.as_ref().map(|value| value.value.clone());
+        let mut kt = KeyTransparency {
+            config: config.clone(),
+        };
+
+        let mut state = self.state.lock().expect("not poisoned");
+        let update = kt
+            .verify_search_and_cache(
+                request,
+                response,
+                SearchContext::default(),
+                false,
+                now,
+                &mut state.results,
+            )
+            .map_err(|e| {
+                log::warn!("key transparency search response failed verification: {e}");
+                ChatServiceError::IncomingDataInvalid
+            })?;
+        let tree_size = update.tree_head.tree_size;
+        state.latest_tree_size = Some(
+            state
+                .latest_tree_size
+                .map_or(tree_size, |latest| latest.max(tree_size)),
+        );
+
+        Ok(SearchResult {
+            value,
+            tree_head: update.tree_head,
+            tree_root: update.tree_root,
+            from_cache: false,
+        })
+    }
+}
+
+/// Builds the request for a key transparency search.
+pub fn search_request(request: &SearchRequest) -> Request {
+    Request {
+        method: Method::POST,
+        body: Some(request.encode_to_vec().into_boxed_slice()),
+        headers: HeaderMap::from_iter([(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-protobuf"),
+        )]),
+        path: PathAndQuery::from_static(SEARCH_PATH),
+    }
+}
+
+/// Extracts the search response from the server's response to [`search_request`].
+///
+/// The response is parsed but not verified; see [`SearchCache::verify_and_store`].
+pub fn parse_search_response(response: Response) -> Result<SearchResponse, ChatServiceError> {
+    let Response {
+        status,
+        body,
+        headers,
+        message: _,
+    } = response;
+
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        if let Some(rate_limit) = RateLimit::from_http_response(&headers, body.as_deref()) {
+            return Err(ChatServiceError::RetryLater(rate_limit));
+        }
+    }
+    if !status.is_success() {
+        log::warn!("unexpected status searching key transparency log: {status}");
+        return Err(ChatServiceError::IncomingDataInvalid);
+    }
+
+    SearchResponse::decode(body.as_deref().unwrap_or_default()).map_err(|e| {
+        log::warn!("invalid key transparency search response: {e}");
+        ChatServiceError::IncomingDataInvalid
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+    use hex_literal::hex;
+    use libsignal_keytrans::{CachedSearchResult, DeploymentMode, VerifyingKey, VrfPublicKey};
+
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn config() -> PublicConfig {
+        let key = VerifyingKey::from_bytes(&hex!(
+            "12a21ad60d5a3978e19a3b0baa8c35c55a20e10d45f39e5cb34bf6e1b3cce432"
+        ))
+        .expect("valid key");
+        PublicConfig {
+            mode: DeploymentMode::ThirdPartyAuditing(key),
+            signature_key: key,
+            vrf_key: VrfPublicKey::try_from(hex!(
+                "1e71563470c1b8a6e0aadf280b6aa96f8ad064674e69b80292ee46d1ab655fcf"
+            ))
+            .expect("valid key"),
+        }
+    }
+
+    fn search(search_key: &[u8]) -> SearchRequest {
+        SearchRequest {
+            search_key: search_key.to_vec(),
+            version: None,
+            consistency: None,
+            mapped_value: vec![],
+            unidentified_access_key: None,
+        }
+    }
+
+    fn cached(tree_size: u64, now: SystemTime) -> CachedSearchResult {
+        CachedSearchResult {
+            value: Some(b"value".to_vec()),
+            tree_head: TreeHead {
+                tree_size,
+                timestamp: 0,
+                signature: vec![],
+            },
+            tree_root: [0; 32],
+            verified_at: now,
+        }
+    }
+
+    fn response(status: StatusCode, body: Option<&[u8]>) -> Response {
+        Response {
+            status,
+            message: None,
+            body: body.map(Box::from),
+            headers: HeaderMap::new(),
+        }
+    }
+
+    #[test]
+    fn request_encodes_search() {
+        let request = search_request(&search(b"key"));
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.path.as_str(), SEARCH_PATH);
+        assert_eq!(request.headers[CONTENT_TYPE], "application/x-protobuf");
+        assert_eq!(
+            SearchRequest::decode(request.body.as_deref().expect("has body")).expect("valid"),
+            search(b"key")
+        );
+    }
+
+    #[test]
+    fn parses_search_response() {
+        let expected = SearchResponse {
+            vrf_proof: vec![1; 80],
+            opening: vec![2; 16],
+            ..Default::default()
+        };
+        let body = expected.encode_to_vec();
+        assert_eq!(
+            parse_search_response(response(StatusCode::OK, Some(&body))).expect("valid"),
+            expected
+        );
+    }
+
+    #[test]
+    fn rejects_bad_responses() {
+        for (status, body) in [
+            (StatusCode::UNAUTHORIZED, None),
+            (StatusCode::OK, Some(&b"\xff\xff"[..])),
+        ] {
+            assert_matches!(
+                parse_search_response(response(status, body)),
+                Err(ChatServiceError::IncomingDataInvalid),
+                "{status} {body:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn lookup_uses_the_latest_tree_size() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let cache = SearchCache::new(TTL, 10);
+        {
+            let mut state = cache.state.lock().expect("not poisoned");
+            state.results.put(b"old", cached(10, now));
+            state.results.put(b"new", cached(12, now));
+        }
+
+        // Nothing has been verified through this cache yet, so there's no tree head to match.
+        assert_eq!(cache.lookup(&config(), &search(b"new"), now), None);
+
+        cache.state.lock().expect("not poisoned").latest_tree_size = Some(12);
+        assert_eq!(
+            cache.lookup(&config(), &search(b"new"), now),
+            Some(SearchResult {
+                value: Some(b"value".to_vec()),
+                tree_head: cached(12, now).tree_head,
+                tree_root: [0; 32],
+                from_cache: true,
+            })
+        );
+        assert_eq!(cache.lookup(&config(), &search(b"old"), now), None);
+        assert_eq!(
+            cache.lookup(&config(), &search(b"new"), now + TTL + TTL),
+            None
+        );
+    }
+
+    #[test]
+    fn unverifiable_response_is_not_stored() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let cache = SearchCache::new(TTL, 10);
+        assert_matches!(
+            cache.verify_and_store(&config(), search(b"key"), SearchResponse::default(), now),
+            Err(ChatServiceError::IncomingDataInvalid)
+        );
+
+        let state = cache.state.lock().expect("not poisoned");
+        assert!(state.results.is_empty());
+        assert_eq!(state.latest_tree_size, None);
+    }
+}
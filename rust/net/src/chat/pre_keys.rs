@@ -0,0 +1,361 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Uploading pre-keys to the chat server, and checking how many it has left.
+//!
+//! Clients check [`count_request`] periodically and upload a fresh batch of one-time pre-keys when
+//! the server is running low, rotating their signed and last-resort pre-keys along the way. Both
+//! requests are made separately for the account's ACI and PNI identities.
+
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use http::header::CONTENT_TYPE;
+use http::uri::PathAndQuery;
+use http::{HeaderMap, HeaderValue, Method, StatusCode};
+use libsignal_core::ServiceIdKind;
+use libsignal_protocol::{
+    GenericSignedPreKey, KyberPreKeyRecord, PreKeyRecord, SignalProtocolError, SignedPreKeyRecord,
+};
+
+use crate::chat::{ChatServiceError, Request, Response};
+use crate::rate_limit::RateLimit;
+
+const KEYS_PATH: &str = "/v2/keys";
+
+/// Pre-keys to upload for one identity.
+///
+/// Anything left empty is left unchanged on the server.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PreKeyUpload<'a> {
+    /// One-time EC pre-keys; these replace any the server still has.
+    pub pre_keys: &'a [PreKeyRecord],
+    pub signed_pre_key: Option<&'a SignedPreKeyRecord>,
+    /// One-time Kyber pre-keys; these replace any the server still has.
+    pub pq_pre_keys: &'a [KyberPreKeyRecord],
+    pub pq_last_resort_pre_key: Option<&'a KyberPreKeyRecord>,
+}
+
+/// How many one-time pre-keys the server has left for one identity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PreKeyCounts {
+    pub pre_keys: u32,
+    pub pq_pre_keys: u32,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SetKeysRequest {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pre_keys: Vec<PreKeyEntity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signed_pre_key: Option<SignedPreKeyEntity>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pq_pre_keys: Vec<SignedPreKeyEntity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pq_last_resort_pre_key: Option<SignedPreKeyEntity>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PreKeyEntity {
+    key_id: u32,
+    public_key: String,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SignedPreKeyEntity {
+    key_id: u32,
+    public_key: String,
+    signature: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PreKeyCountResponse {
+    count: u32,
+    pq_count: u32,
+}
+
+impl PreKeyEntity {
+    fn new(record: &PreKeyRecord) -> Result<Self, SignalProtocolError> {
+        Ok(Self {
+            key_id: record.id()?.into(),
+            public_key: BASE64_STANDARD.encode(record.public_key()?.serialize()),
+        })
+    }
+}
+
+impl SignedPreKeyEntity {
+    fn from_ec(record: &SignedPreKeyRecord) -> Result<Self, SignalProtocolError> {
+        Ok(Self {
+            key_id: record.id()?.into(),
+            public_key: BASE64_STANDARD.encode(record.public_key()?.serialize()),
+            signature: BASE64_STANDARD.encode(record.signature()?),
+        })
+    }
+
+    fn from_kyber(record: &KyberPreKeyRecord) -> Result<Self, SignalProtocolError> {
+        Ok(Self {
+            key_id: record.id()?.into(),
+            public_key: BASE64_STANDARD.encode(record.public_key()?.serialize()),
+            signature: BASE64_STANDARD.encode(record.signature()?),
+        })
+    }
+}
+
+fn identity_query(identity: ServiceIdKind) -> &'static str {
+    match identity {
+        ServiceIdKind::Aci => "aci",
+        ServiceIdKind::Pni => "pni",
+    }
+}
+
+/// Builds the request to upload `keys` for the account's `identity`.
+///
+/// Fails only if one of the records can't be parsed.
+pub fn upload_request(
+    identity: ServiceIdKind,
+    keys: &PreKeyUpload<'_>,
+) -> Result<Request, SignalProtocolError> {
+    let PreKeyUpload {
+        pre_keys,
+        signed_pre_key,
+        pq_pre_keys,
+        pq_last_resort_pre_key,
+    } = *keys;
+
+    let body = SetKeysRequest {
+        pre_keys: pre_keys
+            .iter()
+            .map(PreKeyEntity::new)
+            .collect::<Result<_, _>>()?,
+        signed_pre_key: signed_pre_key
+            .map(SignedPreKeyEntity::from_ec)
+            .transpose()?,
+        pq_pre_keys: pq_pre_keys
+            .iter()
+            .map(SignedPreKeyEntity::from_kyber)
+            .collect::<Result<_, _>>()?,
+        pq_last_resort_pre_key: pq_last_resort_pre_key
+            .map(SignedPreKeyEntity::from_kyber)
+            .transpose()?,
+    };
+    let body = serde_json::to_vec(&body).expect("can serialize");
+
+    let path = format!("{KEYS_PATH}?identity={}", identity_query(identity));
+    Ok(Request {
+        method: Method::PUT,
+        body: Some(body.into_boxed_slice()),
+        headers: HeaderMap::from_iter([(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        )]),
+        path: PathAndQuery::try_from(path).expect("valid path"),
+    })
+}
+
+/// Checks the server's response to [`upload_request`].
+pub fn parse_upload_response(response: Response) -> Result<(), ChatServiceError> {
+    check_status(&response, "uploading pre-keys")
+}
+
+/// Builds the request for how many one-time pre-keys the server has left for `identity`.
+pub fn count_request(identity: ServiceIdKind) -> Request {
+    let path = format!("{KEYS_PATH}?identity={}", identity_query(identity));
+    Request {
+        method: Method::GET,
+        body: None,
+        headers: HeaderMap::new(),
+        path: PathAndQuery::try_from(path).expect("valid path"),
+    }
+}
+
+/// Extracts the counts from the server's response to [`count_request`].
+pub fn parse_count_response(response: Response) -> Result<PreKeyCounts, ChatServiceError> {
+    check_status(&response, "fetching pre-key counts")?;
+
+    let PreKeyCountResponse { count, pq_count } =
+        serde_json::from_slice(response.body.as_deref().unwrap_or_default()).map_err(|e| {
+            log::warn!("invalid pre-key count response: {e}");
+            ChatServiceError::IncomingDataInvalid
+        })?;
+    Ok(PreKeyCounts {
+        pre_keys: count,
+        pq_pre_keys: pq_count,
+    })
+}
+
+fn check_status(response: &Response, operation: &str) -> Result<(), ChatServiceError> {
+    let Response {
+        status,
+        body,
+        headers,
+        message: _,
+    } = response;
+
+    if *status == StatusCode::TOO_MANY_REQUESTS {
+        if let Some(rate_limit) = RateLimit::from_http_response(headers, body.as_deref()) {
+            return Err(ChatServiceError::RetryLater(rate_limit));
+        }
+    }
+    match *status {
+        StatusCode::NOT_FOUND => Err(ChatServiceError::NotFound),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            Err(ChatServiceError::RequestUnauthorized)
+        }
+        status if !status.is_success() => {
+            log::warn!("unexpected status {operation}: {status}");
+            Err(ChatServiceError::IncomingDataInvalid)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+    use libsignal_protocol::{kem, KeyPair, KyberPreKeyId, PreKeyId, SignedPreKeyId, Timestamp};
+    use rand::rngs::OsRng;
+
+    use super::*;
+
+    fn response(status: StatusCode, body: Option<&[u8]>) -> Response {
+        Response {
+            status,
+            message: None,
+            body: body.map(Box::from),
+            headers: HeaderMap::new(),
+        }
+    }
+
+    #[test]
+    fn upload_body() {
+        let mut rng = OsRng;
+        let identity_key = KeyPair::generate(&mut rng);
+
+        let pre_key = PreKeyRecord::new(PreKeyId::from(7), &KeyPair::generate(&mut rng));
+        let signed_key_pair = KeyPair::generate(&mut rng);
+        let signed_pre_key = SignedPreKeyRecord::new(
+            SignedPreKeyId::from(8),
+            Timestamp::from_epoch_millis(1_700_000_000_000),
+            &signed_key_pair,
+            &identity_key
+                .private_key
+                .calculate_signature(&signed_key_pair.public_key.serialize(), &mut rng)
+                .expect("can sign"),
+        );
+        let last_resort = KyberPreKeyRecord::generate(
+            kem::KeyType::Kyber1024,
+            KyberPreKeyId::from(9),
+            &identity_key.private_key,
+        )
+        .expect("can generate");
+
+        let request = upload_request(
+            ServiceIdKind::Pni,
+            &PreKeyUpload {
+                pre_keys: std::slice::from_ref(&pre_key),
+                signed_pre_key: Some(&signed_pre_key),
+                pq_last_resort_pre_key: Some(&last_resort),
+                ..Default::default()
+            },
+        )
+        .expect("valid records");
+
+        assert_eq!(request.method, Method::PUT);
+        assert_eq!(request.path.as_str(), "/v2/keys?identity=pni");
+        assert_eq!(request.headers[CONTENT_TYPE], "application/json");
+
+        let body: serde_json::Value =
+            serde_json::from_slice(&request.body.expect("has body")).expect("valid JSON");
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "preKeys": [{
+                    "keyId": 7,
+                    "publicKey": BASE64_STANDARD.encode(
+                        pre_key.public_key().expect("valid").serialize()
+                    ),
+                }],
+                "signedPreKey": {
+                    "keyId": 8,
+                    "publicKey": BASE64_STANDARD.encode(signed_key_pair.public_key.serialize()),
+                    "signature": BASE64_STANDARD.encode(
+                        signed_pre_key.signature().expect("valid")
+                    ),
+                },
+                "pqLastResortPreKey": {
+                    "keyId": 9,
+                    "publicKey": BASE64_STANDARD.encode(
+                        last_resort.public_key().expect("valid").serialize()
+                    ),
+                    "signature": BASE64_STANDARD.encode(last_resort.signature().expect("valid")),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn count_request_path() {
+        let request = count_request(ServiceIdKind::Aci);
+        assert_eq!(request.method, Method::GET);
+        assert_eq!(request.path.as_str(), "/v2/keys?identity=aci");
+    }
+
+    #[test]
+    fn parses_counts() {
+        assert_eq!(
+            parse_count_response(response(
+                StatusCode::OK,
+                Some(br#"{"count":12,"pqCount":34}"#)
+            ))
+            .expect("valid"),
+            PreKeyCounts {
+                pre_keys: 12,
+                pq_pre_keys: 34,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_bad_responses() {
+        for (status, body) in [
+            (StatusCode::INTERNAL_SERVER_ERROR, None),
+            (StatusCode::OK, None),
+            (StatusCode::OK, Some(&b"{}"[..])),
+            (StatusCode::OK, Some(&br#"{"count":-1,"pqCount":0}"#[..])),
+        ] {
+            assert_matches!(
+                parse_count_response(response(status, body)),
+                Err(ChatServiceError::IncomingDataInvalid),
+                "{status} {body:?}"
+            );
+        }
+        assert_matches!(
+            parse_upload_response(response(StatusCode::UNPROCESSABLE_ENTITY, None)),
+            Err(ChatServiceError::IncomingDataInvalid)
+        );
+        assert_matches!(
+            parse_upload_response(response(StatusCode::OK, None)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn distinguishes_rejected_requests() {
+        assert_matches!(
+            parse_count_response(response(StatusCode::UNAUTHORIZED, None)),
+            Err(ChatServiceError::RequestUnauthorized)
+        );
+        assert_matches!(
+            parse_upload_response(response(StatusCode::FORBIDDEN, None)),
+            Err(ChatServiceError::RequestUnauthorized)
+        );
+        assert_matches!(
+            parse_upload_response(response(StatusCode::NOT_FOUND, None)),
+            Err(ChatServiceError::NotFound)
+        );
+    }
+}
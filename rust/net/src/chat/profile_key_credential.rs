@@ -0,0 +1,271 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Fetching an [`ExpiringProfileKeyCredential`] over an authenticated chat connection.
+//!
+//! Getting a credential takes two steps that have to agree with each other: the client sends a
+//! blinded request for a particular profile key, then checks the server's proof and unblinds the
+//! response using the context the request was made from. [`request`] returns that context so it
+//! can be passed straight to [`parse_response`].
+
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use http::uri::PathAndQuery;
+use http::{HeaderMap, Method, StatusCode};
+use libsignal_core::Aci;
+use zkgroup::profiles::{
+    ExpiringProfileKeyCredential, ExpiringProfileKeyCredentialResponse, ProfileKey,
+    ProfileKeyCredentialRequestContext,
+};
+use zkgroup::{RandomnessBytes, ServerPublicParams, Timestamp};
+
+use crate::chat::{ChatServiceError, Request, Response};
+use crate::rate_limit::RateLimit;
+
+const PROFILE_PATH: &str = "/v1/profile";
+
+#[derive(serde::Deserialize)]
+struct ProfileKeyCredentialResponse {
+    credential: Option<String>,
+}
+
+/// Builds the request for a credential for `aci`'s `profile_key`.
+///
+/// The returned context must be kept to pass to [`parse_response`].
+pub fn request(
+    server_params: &ServerPublicParams,
+    aci: Aci,
+    profile_key: ProfileKey,
+    randomness: RandomnessBytes,
+) -> (Request, ProfileKeyCredentialRequestContext) {
+    let context =
+        server_params.create_profile_key_credential_request_context(randomness, aci, profile_key);
+    let version = zkgroup::serialize(&profile_key.get_profile_key_version(aci));
+    let version = std::str::from_utf8(&version).expect("version is hex-encoded");
+    let credential_request = hex::encode(zkgroup::serialize(&context.get_request()));
+
+    let path = format!(
+        "{PROFILE_PATH}/{}/{version}/{credential_request}?credentialType=expiringProfileKey",
+        aci.service_id_string()
+    );
+    let request = Request {
+        method: Method::GET,
+        body: None,
+        headers: HeaderMap::new(),
+        path: PathAndQuery::try_from(path).expect("valid path"),
+    };
+    (request, context)
+}
+
+/// Checks the server's response to [`request`] and unblinds the credential it contains.
+///
+/// `current_time` is used to reject credentials that have already expired or that expire too far
+/// in the future.
+///
+/// A 404 from the server, reported as [`ChatServiceError::NotFound`], usually means `profile_key`
+/// is no longer the account's current profile key. A 401 or 403 is reported as
+/// [`ChatServiceError::RequestUnauthorized`]. Any other failure is reported as
+/// [`ChatServiceError::IncomingDataInvalid`].
+pub fn parse_response(
+    response: Response,
+    server_params: &ServerPublicParams,
+    context: &ProfileKeyCredentialRequestContext,
+    current_time: Timestamp,
+) -> Result<ExpiringProfileKeyCredential, ChatServiceError> {
+    let Response {
+        status,
+        body,
+        headers,
+        message: _,
+    } = response;
+
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        if let Some(rate_limit) = RateLimit::from_http_response(&headers, body.as_deref()) {
+            return Err(ChatServiceError::RetryLater(rate_limit));
+        }
+    }
+    match status {
+        StatusCode::NOT_FOUND => return Err(ChatServiceError::NotFound),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            return Err(ChatServiceError::RequestUnauthorized)
+        }
+        status if !status.is_success() => {
+            log::warn!("unexpected status fetching profile key credential: {status}");
+            return Err(ChatServiceError::IncomingDataInvalid);
+        }
+        _ => {}
+    }
+
+    let ProfileKeyCredentialResponse { credential } =
+        serde_json::from_slice(body.as_deref().unwrap_or_default()).map_err(|e| {
+            log::warn!("invalid profile key credential response: {e}");
+            ChatServiceError::IncomingDataInvalid
+        })?;
+    let credential = credential.ok_or_else(|| {
+        log::warn!("profile response did not include a credential");
+        ChatServiceError::IncomingDataInvalid
+    })?;
+    let credential = BASE64_STANDARD.decode(credential).map_err(|_| {
+        log::warn!("profile key credential was not valid base64");
+        ChatServiceError::IncomingDataInvalid
+    })?;
+    let credential_response: ExpiringProfileKeyCredentialResponse =
+        zkgroup::deserialize(&credential).map_err(|_| {
+            log::warn!("profile key credential response could not be parsed");
+            ChatServiceError::IncomingDataInvalid
+        })?;
+    server_params
+        .receive_expiring_profile_key_credential(context, &credential_response, current_time)
+        .map_err(|_| {
+            log::warn!("profile key credential response failed verification");
+            ChatServiceError::IncomingDataInvalid
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+    use zkgroup::{ServerSecretParams, SECONDS_PER_DAY};
+
+    use super::*;
+
+    const ACI: Aci = Aci::from_uuid_bytes([0x11; 16]);
+    const NOW: Timestamp =
+        Timestamp::from_epoch_seconds(1_700_000_000 / SECONDS_PER_DAY * SECONDS_PER_DAY);
+
+    fn response(status: StatusCode, body: Option<&[u8]>) -> Response {
+        Response {
+            status,
+            message: None,
+            body: body.map(Box::from),
+            headers: HeaderMap::new(),
+        }
+    }
+
+    fn issue(
+        server_secret_params: &ServerSecretParams,
+        context: &ProfileKeyCredentialRequestContext,
+        profile_key: ProfileKey,
+    ) -> Vec<u8> {
+        let credential_response = server_secret_params
+            .issue_expiring_profile_key_credential(
+                [0x33; 32],
+                &context.get_request(),
+                ACI,
+                profile_key.get_commitment(ACI),
+                NOW.add_seconds(SECONDS_PER_DAY),
+            )
+            .expect("valid request");
+        serde_json::json!({
+            "credential": BASE64_STANDARD.encode(zkgroup::serialize(&credential_response)),
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    #[test]
+    fn request_path() {
+        let server_params = ServerSecretParams::generate([0x01; 32]).get_public_params();
+        let profile_key = ProfileKey::create([0x22; 32]);
+        let (request, context) = request(&server_params, ACI, profile_key, [0x02; 32]);
+
+        let version = zkgroup::serialize(&profile_key.get_profile_key_version(ACI));
+        let expected = format!(
+            "/v1/profile/11111111-1111-1111-1111-111111111111/{}/{}?credentialType=expiringProfileKey",
+            std::str::from_utf8(&version).expect("hex"),
+            hex::encode(zkgroup::serialize(&context.get_request())),
+        );
+        assert_eq!(request.path.as_str(), expected);
+        assert_eq!(request.method, Method::GET);
+    }
+
+    #[test]
+    fn receives_credential() {
+        let server_secret_params = ServerSecretParams::generate([0x01; 32]);
+        let server_params = server_secret_params.get_public_params();
+        let profile_key = ProfileKey::create([0x22; 32]);
+        let (_, context) = request(&server_params, ACI, profile_key, [0x02; 32]);
+
+        let body = issue(&server_secret_params, &context, profile_key);
+        let credential = parse_response(
+            response(StatusCode::OK, Some(&body)),
+            &server_params,
+            &context,
+            NOW,
+        )
+        .expect("valid");
+        assert_eq!(
+            credential.get_expiration_time(),
+            NOW.add_seconds(SECONDS_PER_DAY)
+        );
+    }
+
+    #[test]
+    fn rejects_bad_responses() {
+        let server_secret_params = ServerSecretParams::generate([0x01; 32]);
+        let server_params = server_secret_params.get_public_params();
+        let profile_key = ProfileKey::create([0x22; 32]);
+        let (_, context) = request(&server_params, ACI, profile_key, [0x02; 32]);
+        let (_, other_context) = request(&server_params, ACI, profile_key, [0x03; 32]);
+        let wrong_context_body = issue(&server_secret_params, &other_context, profile_key);
+
+        for (status, body) in [
+            (StatusCode::INTERNAL_SERVER_ERROR, None),
+            (StatusCode::OK, None),
+            (StatusCode::OK, Some(&b"{}"[..])),
+            (
+                StatusCode::OK,
+                Some(&br#"{"credential":"not base64!"}"#[..]),
+            ),
+            (StatusCode::OK, Some(&br#"{"credential":"AAAA"}"#[..])),
+            (StatusCode::OK, Some(&wrong_context_body[..])),
+        ] {
+            assert_matches!(
+                parse_response(response(status, body), &server_params, &context, NOW),
+                Err(ChatServiceError::IncomingDataInvalid),
+                "{status} {body:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn distinguishes_rejected_requests() {
+        let server_params = ServerSecretParams::generate([0x01; 32]).get_public_params();
+        let profile_key = ProfileKey::create([0x22; 32]);
+        let (_, context) = request(&server_params, ACI, profile_key, [0x02; 32]);
+        let parse = |status| parse_response(response(status, None), &server_params, &context, NOW);
+
+        assert_matches!(
+            parse(StatusCode::NOT_FOUND),
+            Err(ChatServiceError::NotFound)
+        );
+        assert_matches!(
+            parse(StatusCode::UNAUTHORIZED),
+            Err(ChatServiceError::RequestUnauthorized)
+        );
+        assert_matches!(
+            parse(StatusCode::FORBIDDEN),
+            Err(ChatServiceError::RequestUnauthorized)
+        );
+    }
+
+    #[test]
+    fn rejects_expired_credentials() {
+        let server_secret_params = ServerSecretParams::generate([0x01; 32]);
+        let server_params = server_secret_params.get_public_params();
+        let profile_key = ProfileKey::create([0x22; 32]);
+        let (_, context) = request(&server_params, ACI, profile_key, [0x02; 32]);
+
+        let body = issue(&server_secret_params, &context, profile_key);
+        assert_matches!(
+            parse_response(
+                response(StatusCode::OK, Some(&body)),
+                &server_params,
+                &context,
+                NOW.add_seconds(SECONDS_PER_DAY),
+            ),
+            Err(ChatServiceError::IncomingDataInvalid)
+        );
+    }
+}
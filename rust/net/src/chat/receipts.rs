@@ -0,0 +1,289 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Coalescing delivery, read, and viewed receipts into as few outgoing messages as possible.
+//!
+//! A single receipt message can acknowledge any number of messages from the same sender, so
+//! sending one message per acknowledged message wastes requests (and battery) in busy
+//! conversations. [`ReceiptCoalescer`] collects individual [`ReceiptTarget`]s and hands back
+//! [`ReceiptBatch`]es, each of which should be sent as one receipt message.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+use libsignal_core::ServiceId;
+use libsignal_protocol::Timestamp;
+
+/// The largest number of timestamps put in a single batch by default.
+pub const DEFAULT_MAX_TIMESTAMPS_PER_BATCH: usize = 100;
+
+/// The default span of message timestamps allowed in a single batch.
+pub const DEFAULT_BATCH_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The kinds of receipt a client can send; each kind is sent as a separate message.
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ReceiptKind {
+    Delivery = 0,
+    Read = 1,
+    Viewed = 2,
+}
+
+/// One message to be acknowledged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReceiptTarget {
+    /// The author of the message being acknowledged.
+    pub sender: ServiceId,
+    pub kind: ReceiptKind,
+    /// The sent timestamp of the message being acknowledged.
+    pub message_timestamp: Timestamp,
+}
+
+/// A set of receipts for a single sender that can be sent as one message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceiptBatch {
+    pub sender: ServiceId,
+    pub kind: ReceiptKind,
+    /// The acknowledged message timestamps, in ascending order and without duplicates.
+    pub timestamps: Vec<Timestamp>,
+}
+
+/// Collects receipts and groups them by sender, kind, and timestamp window.
+///
+/// Adding the same target more than once only acknowledges it once.
+#[derive(Clone, Debug)]
+pub struct ReceiptCoalescer {
+    window: Duration,
+    max_timestamps_per_batch: usize,
+    pending: BTreeMap<(ServiceId, ReceiptKind), BTreeSet<Timestamp>>,
+}
+
+impl Default for ReceiptCoalescer {
+    fn default() -> Self {
+        Self::new(DEFAULT_BATCH_WINDOW, DEFAULT_MAX_TIMESTAMPS_PER_BATCH)
+    }
+}
+
+impl ReceiptCoalescer {
+    /// Creates an empty coalescer.
+    ///
+    /// No batch will cover message timestamps more than `window` apart, or contain more than
+    /// `max_timestamps_per_batch` timestamps.
+    ///
+    /// # Panics
+    ///
+    /// If `max_timestamps_per_batch` is zero.
+    pub fn new(window: Duration, max_timestamps_per_batch: usize) -> Self {
+        assert!(
+            max_timestamps_per_batch > 0,
+            "batches must be allowed at least one timestamp"
+        );
+        Self {
+            window,
+            max_timestamps_per_batch,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, target: ReceiptTarget) {
+        let ReceiptTarget {
+            sender,
+            kind,
+            message_timestamp,
+        } = target;
+        self.pending
+            .entry((sender, kind))
+            .or_default()
+            .insert(message_timestamp);
+    }
+
+    /// Returns `true` if no receipts are waiting to be sent.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Removes all pending receipts, grouped into the fewest batches that respect the window and
+    /// size limits.
+    ///
+    /// Batches are ordered by sender, then kind, then timestamp.
+    pub fn drain(&mut self) -> Vec<ReceiptBatch> {
+        std::iter::from_fn(|| self.next_batch()).collect()
+    }
+
+    /// Removes and returns the first batch [`Self::drain`] would produce, if any.
+    ///
+    /// This lets a caller send batches one at a time, leaving the rest pending (and still open to
+    /// new receipts) if it is interrupted.
+    pub fn next_batch(&mut self) -> Option<ReceiptBatch> {
+        let window_millis = u64::try_from(self.window.as_millis()).unwrap_or(u64::MAX);
+        let mut entry = self.pending.first_entry()?;
+        let (sender, kind) = *entry.key();
+        let pending = entry.get_mut();
+
+        let first = *pending.first().expect("empty sets are never left pending");
+        let timestamps: Vec<Timestamp> = pending
+            .iter()
+            .copied()
+            .take_while(|timestamp| {
+                timestamp.epoch_millis() - first.epoch_millis() <= window_millis
+            })
+            .take(self.max_timestamps_per_batch)
+            .collect();
+        if timestamps.len() == pending.len() {
+            entry.remove();
+        } else {
+            for timestamp in &timestamps {
+                pending.remove(timestamp);
+            }
+        }
+
+        Some(ReceiptBatch {
+            sender,
+            kind,
+            timestamps,
+        })
+    }
+}
+
+impl Extend<ReceiptTarget> for ReceiptCoalescer {
+    fn extend<T: IntoIterator<Item = ReceiptTarget>>(&mut self, iter: T) {
+        for target in iter {
+            self.add(target);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use libsignal_core::{Aci, Pni};
+
+    use super::*;
+
+    const ALICE: Aci = Aci::from_uuid_bytes([0xaa; 16]);
+    const BOB: Aci = Aci::from_uuid_bytes([0xbb; 16]);
+
+    fn target(sender: impl Into<ServiceId>, kind: ReceiptKind, millis: u64) -> ReceiptTarget {
+        ReceiptTarget {
+            sender: sender.into(),
+            kind,
+            message_timestamp: Timestamp::from_epoch_millis(millis),
+        }
+    }
+
+    fn timestamps(millis: &[u64]) -> Vec<Timestamp> {
+        millis
+            .iter()
+            .copied()
+            .map(Timestamp::from_epoch_millis)
+            .collect()
+    }
+
+    #[test]
+    fn groups_by_sender_and_kind() {
+        let mut coalescer = ReceiptCoalescer::default();
+        coalescer.extend([
+            target(BOB, ReceiptKind::Read, 30),
+            target(ALICE, ReceiptKind::Delivery, 20),
+            target(BOB, ReceiptKind::Read, 10),
+            target(ALICE, ReceiptKind::Read, 20),
+            target(ALICE, ReceiptKind::Delivery, 10),
+            target(ALICE, ReceiptKind::Delivery, 20),
+        ]);
+
+        assert_eq!(
+            coalescer.drain(),
+            [
+                ReceiptBatch {
+                    sender: ALICE.into(),
+                    kind: ReceiptKind::Delivery,
+                    timestamps: timestamps(&[10, 20]),
+                },
+                ReceiptBatch {
+                    sender: ALICE.into(),
+                    kind: ReceiptKind::Read,
+                    timestamps: timestamps(&[20]),
+                },
+                ReceiptBatch {
+                    sender: BOB.into(),
+                    kind: ReceiptKind::Read,
+                    timestamps: timestamps(&[10, 30]),
+                },
+            ]
+        );
+        assert!(coalescer.is_empty());
+        assert_eq!(coalescer.drain(), []);
+    }
+
+    #[test]
+    fn next_batch_leaves_the_rest_pending() {
+        let mut coalescer = ReceiptCoalescer::new(DEFAULT_BATCH_WINDOW, 2);
+        coalescer.extend((1..=3).map(|millis| target(ALICE, ReceiptKind::Read, millis)));
+
+        assert_eq!(
+            coalescer.next_batch().map(|batch| batch.timestamps),
+            Some(timestamps(&[1, 2]))
+        );
+        coalescer.add(target(ALICE, ReceiptKind::Read, 4));
+        assert_eq!(
+            coalescer.next_batch().map(|batch| batch.timestamps),
+            Some(timestamps(&[3, 4]))
+        );
+        assert_eq!(coalescer.next_batch(), None);
+        assert!(coalescer.is_empty());
+    }
+
+    #[test]
+    fn aci_and_pni_are_separate_senders() {
+        let pni = Pni::from_uuid_bytes([0xaa; 16]);
+        let mut coalescer = ReceiptCoalescer::default();
+        coalescer.extend([
+            target(ALICE, ReceiptKind::Delivery, 10),
+            target(pni, ReceiptKind::Delivery, 10),
+        ]);
+        assert_eq!(coalescer.drain().len(), 2);
+    }
+
+    #[test]
+    fn splits_on_window() {
+        let mut coalescer = ReceiptCoalescer::new(Duration::from_millis(100), 10);
+        coalescer.extend(
+            [0, 50, 100, 101, 250, 500]
+                .into_iter()
+                .map(|millis| target(ALICE, ReceiptKind::Read, millis)),
+        );
+
+        let batches: Vec<_> = coalescer
+            .drain()
+            .into_iter()
+            .map(|batch| batch.timestamps)
+            .collect();
+        assert_eq!(
+            batches,
+            [
+                timestamps(&[0, 50, 100]),
+                timestamps(&[101]),
+                timestamps(&[250]),
+                timestamps(&[500]),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_on_size() {
+        let mut coalescer = ReceiptCoalescer::new(DEFAULT_BATCH_WINDOW, 2);
+        coalescer.extend((1..=5).map(|millis| target(ALICE, ReceiptKind::Viewed, millis)));
+
+        let batches: Vec<_> = coalescer
+            .drain()
+            .into_iter()
+            .map(|batch| batch.timestamps)
+            .collect();
+        assert_eq!(
+            batches,
+            [timestamps(&[1, 2]), timestamps(&[3, 4]), timestamps(&[5])]
+        );
+    }
+}
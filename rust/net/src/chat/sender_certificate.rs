@@ -0,0 +1,180 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Fetching a fresh [`SenderCertificate`] over an authenticated chat connection.
+//!
+//! Sealed-sender sends need a sender certificate that hasn't expired yet. Clients usually cache
+//! one and use [`SenderCertificate::needs_refresh`] to decide when to fetch a replacement.
+
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use http::uri::PathAndQuery;
+use http::{HeaderMap, Method, StatusCode};
+use libsignal_protocol::SenderCertificate;
+
+use crate::chat::{ChatServiceError, Request, Response};
+use crate::rate_limit::RateLimit;
+
+const SENDER_CERTIFICATE_PATH: &str = "/v1/certificate/delivery";
+
+#[derive(serde::Deserialize)]
+struct SenderCertificateResponse {
+    certificate: String,
+}
+
+/// Builds the request for a new sender certificate.
+///
+/// If `include_e164` is set, the certificate will carry the account's phone number in addition to
+/// its ACI.
+pub fn request(include_e164: bool) -> Request {
+    let path = format!("{SENDER_CERTIFICATE_PATH}?includeE164={include_e164}");
+    Request {
+        method: Method::GET,
+        body: None,
+        headers: HeaderMap::new(),
+        path: PathAndQuery::try_from(path).expect("valid path"),
+    }
+}
+
+/// Extracts the certificate from the server's response to [`request`].
+///
+/// The certificate is parsed but not validated; callers should still check it against their trust
+/// root before using it.
+pub fn parse_response(response: Response) -> Result<SenderCertificate, ChatServiceError> {
+    let Response {
+        status,
+        body,
+        headers,
+        message: _,
+    } = response;
+
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        if let Some(rate_limit) = RateLimit::from_http_response(&headers, body.as_deref()) {
+            return Err(ChatServiceError::RetryLater(rate_limit));
+        }
+    }
+    if !status.is_success() {
+        log::warn!("unexpected status fetching sender certificate: {status}");
+        return Err(ChatServiceError::IncomingDataInvalid);
+    }
+
+    let SenderCertificateResponse { certificate } =
+        serde_json::from_slice(body.as_deref().unwrap_or_default()).map_err(|e| {
+            log::warn!("invalid sender certificate response: {e}");
+            ChatServiceError::IncomingDataInvalid
+        })?;
+    let certificate = BASE64_STANDARD.decode(certificate).map_err(|_| {
+        log::warn!("sender certificate response was not valid base64");
+        ChatServiceError::IncomingDataInvalid
+    })?;
+    SenderCertificate::deserialize(&certificate).map_err(|e| {
+        log::warn!("sender certificate response could not be parsed: {e}");
+        ChatServiceError::IncomingDataInvalid
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+    use http::HeaderValue;
+    use libsignal_protocol::{DeviceId, KeyPair, ServerCertificate, Timestamp};
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::rate_limit::RateLimitScope;
+
+    fn make_certificate() -> SenderCertificate {
+        let mut rng = OsRng;
+        let trust_root = KeyPair::generate(&mut rng);
+        let server_key = KeyPair::generate(&mut rng);
+        let server_cert =
+            ServerCertificate::new(1, server_key.public_key, &trust_root.private_key, &mut rng)
+                .expect("can create server certificate");
+        SenderCertificate::new(
+            "9d0652a3-dcc3-4d11-975f-74d61598733f".to_owned(),
+            None,
+            KeyPair::generate(&mut rng).public_key,
+            DeviceId::from(1),
+            Timestamp::from_epoch_millis(1_700_000_000_000),
+            server_cert,
+            &server_key.private_key,
+            &mut rng,
+        )
+        .expect("can create sender certificate")
+    }
+
+    fn response(status: StatusCode, body: Option<&[u8]>) -> Response {
+        Response {
+            status,
+            message: None,
+            body: body.map(Box::from),
+            headers: HeaderMap::new(),
+        }
+    }
+
+    #[test]
+    fn request_path() {
+        assert_eq!(
+            request(false).path.as_str(),
+            "/v1/certificate/delivery?includeE164=false"
+        );
+        assert_eq!(
+            request(true).path.as_str(),
+            "/v1/certificate/delivery?includeE164=true"
+        );
+        assert_eq!(request(true).method, Method::GET);
+    }
+
+    #[test]
+    fn parses_certificate() {
+        let certificate = make_certificate();
+        let body = serde_json::json!({
+            "certificate": BASE64_STANDARD.encode(certificate.serialized().expect("serialized")),
+        })
+        .to_string();
+
+        let parsed =
+            parse_response(response(StatusCode::OK, Some(body.as_bytes()))).expect("valid");
+        assert_eq!(
+            parsed.serialized().expect("serialized"),
+            certificate.serialized().expect("serialized")
+        );
+    }
+
+    #[test]
+    fn rejects_bad_responses() {
+        for (status, body) in [
+            (StatusCode::UNAUTHORIZED, None),
+            (StatusCode::OK, None),
+            (StatusCode::OK, Some(&b"{}"[..])),
+            (
+                StatusCode::OK,
+                Some(&br#"{"certificate":"not base64!"}"#[..]),
+            ),
+            (StatusCode::OK, Some(&br#"{"certificate":"AAAA"}"#[..])),
+        ] {
+            assert_matches!(
+                parse_response(response(status, body)),
+                Err(ChatServiceError::IncomingDataInvalid),
+                "{status} {body:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rate_limited() {
+        let mut response = response(StatusCode::TOO_MANY_REQUESTS, None);
+        response
+            .headers
+            .insert("retry-after", HeaderValue::from_static("30"));
+        assert_matches!(
+            parse_response(response),
+            Err(ChatServiceError::RetryLater(RateLimit {
+                retry_after_seconds: 30,
+                scope: RateLimitScope::Unspecified,
+                reason: None,
+            }))
+        );
+    }
+}
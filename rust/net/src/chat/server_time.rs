@@ -0,0 +1,230 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Estimating how far the local clock is from the server's.
+//!
+//! Some operations need to agree with the server about the current time: picking a redemption time
+//! for zkgroup credentials, or checking whether a credential has expired. Device clocks are
+//! sometimes wrong by minutes or even days, though. Every chat response carries the server's clock,
+//! in the `x-signal-timestamp` header (milliseconds since the epoch) or at least the standard HTTP
+//! `Date` header (whole seconds), so each response is an opportunity to measure the difference.
+//!
+//! [`ClockSkewTracker`] combines those measurements into a smoothed estimate that can be applied to
+//! the local time.
+
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use http::header::DATE;
+use http::HeaderMap;
+
+use crate::env::TIMESTAMP_HEADER_NAME;
+
+/// How much weight each new sample gets, as a fraction `1/SMOOTHING_FACTOR`.
+///
+/// Small enough that one slow response doesn't throw off the estimate, large enough that a change
+/// to the device's clock is picked up after a handful of requests.
+const SMOOTHING_FACTOR: i64 = 4;
+
+/// The `Date` header only has whole seconds; assume the server's clock was halfway through the
+/// second it reported.
+const DATE_HEADER_ROUNDING: Duration = Duration::from_millis(500);
+
+/// Extracts the server's clock from the headers of a chat response.
+///
+/// Prefers the millisecond-precision `x-signal-timestamp` header, falling back to `Date`. Returns
+/// `None` if neither is present and valid, including if the time can't be represented as a
+/// [`SystemTime`] on this platform.
+pub fn server_time_from_headers(headers: &HeaderMap) -> Option<SystemTime> {
+    let timestamp = headers
+        .get(TIMESTAMP_HEADER_NAME)
+        .and_then(|value| value.to_str().ok()?.parse::<u64>().ok())
+        .and_then(|millis| UNIX_EPOCH.checked_add(Duration::from_millis(millis)));
+    timestamp.or_else(|| {
+        let date = headers.get(DATE)?.to_str().ok()?;
+        let date = chrono::DateTime::parse_from_rfc2822(date).ok()?;
+        SystemTime::from(date).checked_add(DATE_HEADER_ROUNDING)
+    })
+}
+
+/// A smoothed estimate of the difference between the server's clock and the local clock.
+#[derive(Debug, Default)]
+pub struct ClockSkewTracker {
+    /// Milliseconds to add to the local time to get the server's, once there's been a sample.
+    skew_millis: Mutex<Option<i64>>,
+}
+
+impl ClockSkewTracker {
+    /// Records a response received at `response_received` to a request sent at `request_sent`.
+    ///
+    /// Responses without a usable timestamp are ignored.
+    pub fn record_response(
+        &self,
+        headers: &HeaderMap,
+        request_sent: SystemTime,
+        response_received: SystemTime,
+    ) {
+        if let Some(server_time) = server_time_from_headers(headers) {
+            self.record(server_time, request_sent, response_received)
+        }
+    }
+
+    /// Records that the server's clock read `server_time` while a request was in flight between
+    /// `request_sent` and `response_received` (both local times).
+    ///
+    /// The server's clock is assumed to have been read halfway through the round trip.
+    pub fn record(
+        &self,
+        server_time: SystemTime,
+        request_sent: SystemTime,
+        response_received: SystemTime,
+    ) {
+        let Ok(round_trip) = response_received.duration_since(request_sent) else {
+            // The local clock went backwards during the request, so it can't be trusted.
+            return;
+        };
+        let local_time = request_sent + round_trip / 2;
+        let sample = millis_since_epoch(server_time).saturating_sub(millis_since_epoch(local_time));
+
+        let mut skew_millis = self.skew_millis.lock().expect("not poisoned");
+        *skew_millis = Some(match *skew_millis {
+            None => sample,
+            Some(previous) => {
+                previous.saturating_add(sample.saturating_sub(previous) / SMOOTHING_FACTOR)
+            }
+        });
+    }
+
+    /// Milliseconds to add to the local time to get the server's, or `None` if no responses have
+    /// been recorded.
+    ///
+    /// Positive if the server's clock is ahead of the local clock.
+    pub fn skew_millis(&self) -> Option<i64> {
+        *self.skew_millis.lock().expect("not poisoned")
+    }
+
+    /// Converts a local time to the server's time, using the current estimate.
+    ///
+    /// Returns `local_time` unchanged if no responses have been recorded.
+    pub fn to_server_time(&self, local_time: SystemTime) -> SystemTime {
+        let Some(skew) = self.skew_millis() else {
+            return local_time;
+        };
+        let offset = Duration::from_millis(skew.unsigned_abs());
+        let adjusted = if skew >= 0 {
+            local_time.checked_add(offset)
+        } else {
+            local_time.checked_sub(offset)
+        };
+        adjusted.unwrap_or(local_time)
+    }
+
+    /// The current time according to the server, as best as can be estimated.
+    pub fn server_now(&self) -> SystemTime {
+        self.to_server_time(SystemTime::now())
+    }
+}
+
+fn millis_since_epoch(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => i64::try_from(after.as_millis()).unwrap_or(i64::MAX),
+        Err(before) => i64::try_from(before.duration().as_millis()).map_or(i64::MIN, |m| -m),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use http::HeaderValue;
+
+    use super::*;
+
+    fn at_millis(millis: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn prefers_signal_timestamp_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(server_time_from_headers(&headers), None);
+
+        headers.insert(
+            DATE,
+            HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT"),
+        );
+        assert_eq!(
+            server_time_from_headers(&headers),
+            Some(at_millis(784_111_777_500))
+        );
+
+        headers.insert(
+            TIMESTAMP_HEADER_NAME,
+            HeaderValue::from_static("784111777123"),
+        );
+        assert_eq!(
+            server_time_from_headers(&headers),
+            Some(at_millis(784_111_777_123))
+        );
+
+        headers.insert(TIMESTAMP_HEADER_NAME, HeaderValue::from_static("garbage"));
+        assert_eq!(
+            server_time_from_headers(&headers),
+            Some(at_millis(784_111_777_500))
+        );
+    }
+
+    #[test]
+    fn ignores_unrepresentable_timestamp() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            TIMESTAMP_HEADER_NAME,
+            HeaderValue::from_str(&u64::MAX.to_string()).expect("valid header"),
+        );
+        // Whether or not this platform can represent the timestamp, parsing it must not panic.
+        assert_eq!(
+            server_time_from_headers(&headers),
+            UNIX_EPOCH.checked_add(Duration::from_millis(u64::MAX))
+        );
+
+        let tracker = ClockSkewTracker::default();
+        tracker.record_response(&headers, at_millis(1000), at_millis(2000));
+        assert_eq!(
+            tracker.skew_millis().is_some(),
+            server_time_from_headers(&headers).is_some()
+        );
+    }
+
+    #[test]
+    fn smooths_samples() {
+        let tracker = ClockSkewTracker::default();
+        assert_eq!(tracker.skew_millis(), None);
+        assert_eq!(tracker.to_server_time(at_millis(1000)), at_millis(1000));
+
+        // The server read its clock at local time 1500, halfway through the request.
+        tracker.record(at_millis(11_500), at_millis(1000), at_millis(2000));
+        assert_eq!(tracker.skew_millis(), Some(10_000));
+        assert_eq!(tracker.to_server_time(at_millis(5000)), at_millis(15_000));
+
+        tracker.record(at_millis(2000), at_millis(6000), at_millis(6000));
+        assert_eq!(
+            tracker.skew_millis(),
+            Some(10_000 - 14_000 / SMOOTHING_FACTOR)
+        );
+
+        // Requests that appear to end before they start are ignored.
+        tracker.record(at_millis(0), at_millis(6000), at_millis(5000));
+        assert_eq!(
+            tracker.skew_millis(),
+            Some(10_000 - 14_000 / SMOOTHING_FACTOR)
+        );
+    }
+
+    #[test]
+    fn server_behind_local_clock() {
+        let tracker = ClockSkewTracker::default();
+        tracker.record(at_millis(1000), at_millis(5000), at_millis(5000));
+        assert_eq!(tracker.skew_millis(), Some(-4000));
+        assert_eq!(tracker.to_server_time(at_millis(5000)), at_millis(1000));
+    }
+}
@@ -0,0 +1,315 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Authorization headers for sealed-sender requests sent over an unauthenticated chat connection.
+//!
+//! The chat server accepts either an [`UnidentifiedAccessKey`] (derived from each recipient's
+//! profile key) or a [`GroupSendAuthorization`] (a group send endorsement token). Both are carried
+//! as base64 HTTP headers; [`UnidentifiedAuth`] lets send paths treat them uniformly.
+
+use std::time::SystemTime;
+
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use http::{HeaderMap, HeaderName, HeaderValue};
+use libsignal_net_infra::AsHttpHeader;
+use zkgroup::groups::GroupSendFullToken;
+
+pub const UNIDENTIFIED_ACCESS_KEY_HEADER_NAME: &str = "unidentified-access-key";
+pub const GROUP_SEND_TOKEN_HEADER_NAME: &str = "group-send-token";
+
+const ACCESS_KEY_LEN: usize = zkgroup::ACCESS_KEY_LEN;
+
+#[derive(Debug, thiserror::Error, displaydoc::Display, PartialEq, Eq)]
+pub enum InvalidUnidentifiedAccess {
+    /// header is not valid base64
+    InvalidBase64,
+    /// access key must be {ACCESS_KEY_LEN} bytes, got {0}
+    InvalidAccessKeyLength(usize),
+    /// group send token could not be parsed
+    InvalidGroupSendToken,
+    /// group send token expired at {0:?}
+    Expired(SystemTime),
+    /// both an access key and a group send token were provided
+    Ambiguous,
+}
+
+/// An unidentified access key, as derived from a profile key.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct UnidentifiedAccessKey(pub [u8; ACCESS_KEY_LEN]);
+
+impl std::fmt::Debug for UnidentifiedAccessKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("UnidentifiedAccessKey").field(&"_").finish()
+    }
+}
+
+impl UnidentifiedAccessKey {
+    /// Combines the access keys of several recipients, as required for multi-recipient sends.
+    pub fn combined<'a>(keys: impl IntoIterator<Item = &'a Self>) -> Self {
+        Self(keys.into_iter().fold([0; ACCESS_KEY_LEN], |mut acc, key| {
+            acc.iter_mut().zip(key.0).for_each(|(a, b)| *a ^= b);
+            acc
+        }))
+    }
+
+    pub fn from_header_value(value: &HeaderValue) -> Result<Self, InvalidUnidentifiedAccess> {
+        let bytes = decode_base64(value)?;
+        let key = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| InvalidUnidentifiedAccess::InvalidAccessKeyLength(bytes.len()))?;
+        Ok(Self(key))
+    }
+}
+
+impl AsHttpHeader for UnidentifiedAccessKey {
+    const HEADER_NAME: HeaderName = HeaderName::from_static(UNIDENTIFIED_ACCESS_KEY_HEADER_NAME);
+
+    fn header_value(&self) -> HeaderValue {
+        encode_base64(&self.0)
+    }
+}
+
+/// A group send endorsement token that has been checked for expiry.
+#[derive(Debug)]
+pub struct GroupSendAuthorization {
+    token: GroupSendFullToken,
+    serialized: Vec<u8>,
+}
+
+impl GroupSendAuthorization {
+    /// Wraps `token`, failing if it has already expired at `now`.
+    pub fn new(
+        token: GroupSendFullToken,
+        now: SystemTime,
+    ) -> Result<Self, InvalidUnidentifiedAccess> {
+        let serialized = zkgroup::serialize(&token);
+        let auth = Self { token, serialized };
+        auth.check_expiration(now)?;
+        Ok(auth)
+    }
+
+    pub fn from_header_value(
+        value: &HeaderValue,
+        now: SystemTime,
+    ) -> Result<Self, InvalidUnidentifiedAccess> {
+        let serialized = decode_base64(value)?;
+        let token = zkgroup::deserialize(&serialized)
+            .map_err(|_| InvalidUnidentifiedAccess::InvalidGroupSendToken)?;
+        let auth = Self { token, serialized };
+        auth.check_expiration(now)?;
+        Ok(auth)
+    }
+
+    pub fn token(&self) -> &GroupSendFullToken {
+        &self.token
+    }
+
+    pub fn expiration(&self) -> SystemTime {
+        self.token.expiration().into()
+    }
+
+    /// Fails if the token is no longer valid at `now`.
+    ///
+    /// Tokens can be cached across several sends, so this should be checked again before each
+    /// one.
+    pub fn check_expiration(&self, now: SystemTime) -> Result<(), InvalidUnidentifiedAccess> {
+        let expiration = self.expiration();
+        if now > expiration {
+            return Err(InvalidUnidentifiedAccess::Expired(expiration));
+        }
+        Ok(())
+    }
+}
+
+impl AsHttpHeader for GroupSendAuthorization {
+    const HEADER_NAME: HeaderName = HeaderName::from_static(GROUP_SEND_TOKEN_HEADER_NAME);
+
+    fn header_value(&self) -> HeaderValue {
+        encode_base64(&self.serialized)
+    }
+}
+
+/// Either form of sealed-sender authorization accepted by the chat server.
+#[derive(Debug)]
+pub enum UnidentifiedAuth {
+    AccessKey(UnidentifiedAccessKey),
+    GroupSend(GroupSendAuthorization),
+}
+
+impl UnidentifiedAuth {
+    /// Finds the sealed-sender authorization in `headers`, if there is one.
+    pub fn from_headers(
+        headers: &HeaderMap,
+        now: SystemTime,
+    ) -> Result<Option<Self>, InvalidUnidentifiedAccess> {
+        let access_key = headers.get(UnidentifiedAccessKey::HEADER_NAME);
+        let group_send = headers.get(GroupSendAuthorization::HEADER_NAME);
+        match (access_key, group_send) {
+            (None, None) => Ok(None),
+            (Some(value), None) => UnidentifiedAccessKey::from_header_value(value)
+                .map(Self::AccessKey)
+                .map(Some),
+            (None, Some(value)) => GroupSendAuthorization::from_header_value(value, now)
+                .map(Self::GroupSend)
+                .map(Some),
+            (Some(_), Some(_)) => Err(InvalidUnidentifiedAccess::Ambiguous),
+        }
+    }
+
+    /// Fails if this authorization can no longer be used at `now`.
+    pub fn check_expiration(&self, now: SystemTime) -> Result<(), InvalidUnidentifiedAccess> {
+        match self {
+            Self::AccessKey(_) => Ok(()),
+            Self::GroupSend(auth) => auth.check_expiration(now),
+        }
+    }
+
+    pub fn as_header(&self) -> (HeaderName, HeaderValue) {
+        match self {
+            Self::AccessKey(key) => key.as_header(),
+            Self::GroupSend(auth) => auth.as_header(),
+        }
+    }
+}
+
+impl From<UnidentifiedAccessKey> for UnidentifiedAuth {
+    fn from(value: UnidentifiedAccessKey) -> Self {
+        Self::AccessKey(value)
+    }
+}
+
+impl From<GroupSendAuthorization> for UnidentifiedAuth {
+    fn from(value: GroupSendAuthorization) -> Self {
+        Self::GroupSend(value)
+    }
+}
+
+fn decode_base64(value: &HeaderValue) -> Result<Vec<u8>, InvalidUnidentifiedAccess> {
+    BASE64_STANDARD
+        .decode(value.as_bytes())
+        .map_err(|_| InvalidUnidentifiedAccess::InvalidBase64)
+}
+
+fn encode_base64(bytes: &[u8]) -> HeaderValue {
+    HeaderValue::try_from(BASE64_STANDARD.encode(bytes)).expect("base64 is a valid header value")
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use zkgroup::groups::{
+        GroupSecretParams, GroupSendDerivedKeyPair, GroupSendEndorsementsResponse,
+    };
+    use zkgroup::{
+        RandomnessBytes, ServerSecretParams, Timestamp, RANDOMNESS_LEN, SECONDS_PER_DAY, UUID_LEN,
+    };
+
+    use super::*;
+
+    #[test]
+    fn access_key_round_trip() {
+        let key = UnidentifiedAccessKey([7; ACCESS_KEY_LEN]);
+        let (name, value) = key.as_header();
+        assert_eq!(name, UNIDENTIFIED_ACCESS_KEY_HEADER_NAME);
+
+        let headers = HeaderMap::from_iter([(name, value)]);
+        let parsed = UnidentifiedAuth::from_headers(&headers, SystemTime::now())
+            .expect("valid")
+            .expect("present");
+        assert!(matches!(parsed, UnidentifiedAuth::AccessKey(parsed) if parsed == key));
+    }
+
+    #[test]
+    fn access_key_wrong_length() {
+        let value = HeaderValue::from_static("AAAA");
+        assert_eq!(
+            UnidentifiedAccessKey::from_header_value(&value),
+            Err(InvalidUnidentifiedAccess::InvalidAccessKeyLength(3))
+        );
+    }
+
+    #[test]
+    fn combined_access_keys() {
+        let a = UnidentifiedAccessKey([0b0101; ACCESS_KEY_LEN]);
+        let b = UnidentifiedAccessKey([0b0011; ACCESS_KEY_LEN]);
+        assert_eq!(
+            UnidentifiedAccessKey::combined([&a, &b]),
+            UnidentifiedAccessKey([0b0110; ACCESS_KEY_LEN])
+        );
+    }
+
+    const DAY_ALIGNED_TIMESTAMP: Timestamp = Timestamp::from_epoch_seconds(1681344000);
+
+    fn group_send_token() -> GroupSendFullToken {
+        let randomness: RandomnessBytes = [0x42; RANDOMNESS_LEN];
+        let member: libsignal_core::ServiceId =
+            libsignal_core::Aci::from_uuid_bytes([0xaa; UUID_LEN]).into();
+
+        let group_params = GroupSecretParams::generate(randomness);
+        let server_params = ServerSecretParams::generate(randomness);
+        let key_pair = GroupSendDerivedKeyPair::for_expiration(
+            DAY_ALIGNED_TIMESTAMP.add_seconds(SECONDS_PER_DAY),
+            &server_params,
+        );
+        let response = GroupSendEndorsementsResponse::issue(
+            [group_params.encrypt_service_id(member)],
+            &key_pair,
+            randomness,
+        );
+        let expiration = response.expiration();
+        let endorsements = response
+            .receive_with_service_ids(
+                [member],
+                DAY_ALIGNED_TIMESTAMP,
+                &group_params,
+                &server_params.get_public_params(),
+            )
+            .expect("valid response");
+        endorsements[0]
+            .decompressed
+            .to_token(&group_params)
+            .into_full_token(expiration)
+    }
+
+    #[test]
+    fn group_send_token_round_trip_and_expiry() {
+        let token = group_send_token();
+        let expiration = token.expiration();
+        let valid_at = SystemTime::from(expiration) - Duration::from_secs(1);
+        let expired_at = SystemTime::from(expiration) + Duration::from_secs(1);
+
+        let auth = GroupSendAuthorization::new(token, valid_at).expect("not yet expired");
+        let headers = HeaderMap::from_iter([auth.as_header()]);
+
+        let parsed = UnidentifiedAuth::from_headers(&headers, valid_at)
+            .expect("valid")
+            .expect("present");
+        assert_eq!(parsed.as_header(), auth.as_header());
+        assert_eq!(
+            parsed.check_expiration(expired_at),
+            Err(InvalidUnidentifiedAccess::Expired(expiration.into()))
+        );
+        assert_eq!(
+            UnidentifiedAuth::from_headers(&headers, expired_at).map(|_| ()),
+            Err(InvalidUnidentifiedAccess::Expired(expiration.into()))
+        );
+    }
+
+    #[test]
+    fn both_headers_is_ambiguous() {
+        let now = SystemTime::from(DAY_ALIGNED_TIMESTAMP);
+        let auth = GroupSendAuthorization::new(group_send_token(), now).expect("valid");
+        let headers = HeaderMap::from_iter([
+            auth.as_header(),
+            UnidentifiedAccessKey([0; ACCESS_KEY_LEN]).as_header(),
+        ]);
+        assert_eq!(
+            UnidentifiedAuth::from_headers(&headers, now).map(|_| ()),
+            Err(InvalidUnidentifiedAccess::Ambiguous)
+        );
+    }
+}
@@ -27,6 +27,7 @@ use tokio::sync::{mpsc, oneshot, Mutex};
 use tokio::time::Instant;
 use tokio_tungstenite::WebSocketStream;
 
+use crate::chat::error::alerts_from_headers;
 use crate::chat::{
     ChatMessageType, ChatService, ChatServiceError, MessageProto, Request, RequestProto, Response,
     ResponseProto,
@@ -166,7 +167,7 @@ impl<T: TransportConnector> ChatOverWebSocketServiceConnector<T> {
 #[async_trait]
 impl<T: TransportConnector> ServiceConnector for ChatOverWebSocketServiceConnector<T> {
     type Service = ChatOverWebSocket<T::Stream>;
-    type Channel = (WebSocketStream<T::Stream>, ConnectionInfo);
+    type Channel = (WebSocketStream<T::Stream>, ConnectionInfo, http::HeaderMap);
     type ConnectError = WebSocketServiceConnectError;
 
     async fn connect_channel(
@@ -186,6 +187,7 @@ impl<T: TransportConnector> ServiceConnector for ChatOverWebSocketServiceConnect
                 ws_client_reader,
             },
             connection_info,
+            response_headers,
         ) = ws_client;
         let pending_messages: Arc<Mutex<PendingMessagesMap>> = Default::default();
         tokio::spawn(reader_task(
@@ -194,6 +196,7 @@ impl<T: TransportConnector> ServiceConnector for ChatOverWebSocketServiceConnect
             self.incoming_tx.clone(),
             pending_messages.clone(),
             service_status.clone(),
+            alerts_from_headers(&response_headers),
         ));
         (
             ChatOverWebSocket {
@@ -213,6 +216,7 @@ async fn reader_task<S: AsyncDuplexStream + 'static>(
     incoming_tx: Arc<Mutex<mpsc::Sender<ServerEvent<S>>>>,
     pending_messages: Arc<Mutex<PendingMessagesMap>>,
     service_cancellation: CancellationToken,
+    upgrade_alerts: Vec<String>,
 ) {
     const LONG_REQUEST_PROCESSING_THRESHOLD: Duration = Duration::from_millis(500);
 
@@ -232,14 +236,19 @@ async fn reader_task<S: AsyncDuplexStream + 'static>(
                 service_cancellation.cancel(CancellationReason::ProtocolError);
                 break ChatServiceError::UnexpectedFrameReceived;
             }
-            Ok(NextOrClose::Close(_)) => {
+            Ok(NextOrClose::Close(close_frame)) => {
                 if service_cancellation.cancelled().now_or_never()
                     == Some(CancellationReason::ExplicitDisconnect)
                 {
                     break ChatServiceError::ServiceIntentionallyDisconnected;
                 }
                 service_cancellation.cancel(CancellationReason::RemoteClose);
-                break WebSocketServiceError::ChannelClosed.into();
+                break ChatServiceError::closed_by_server(
+                    close_frame
+                        .as_ref()
+                        .map(|frame| (frame.code.into(), &*frame.reason)),
+                    upgrade_alerts,
+                );
             }
             Err(e) => {
                 if service_cancellation.cancelled().now_or_never()
@@ -469,7 +478,7 @@ mod test {
         decode_and_validate, request_to_websocket_proto, ChatMessage,
         ChatOverWebSocketServiceConnector, ChatServiceError, RequestId, ServerEvent,
     };
-    use crate::chat::{ChatMessageType, ChatService, MessageProto, ResponseProto};
+    use crate::chat::{ChatMessageType, ChatService, DisconnectInfo, MessageProto, ResponseProto};
     use crate::proto::chat_websocket::WebSocketMessage;
 
     fn test_ws_config() -> WebSocketConfig {
@@ -479,6 +488,7 @@ mod test {
             max_connection_time: Duration::from_secs(1),
             keep_alive_interval: Duration::from_secs(5),
             max_idle_time: Duration::from_secs(15),
+            max_pending_send_bytes: usize::MAX,
         }
     }
 
@@ -550,6 +560,63 @@ mod test {
         validate_server_stopped_successfully(server_res_rx).await;
     }
 
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn ws_service_reports_close_frame_from_server() {
+        let (ws_server, server_res_rx) = ws_warp_filter(move |websocket| async move {
+            let (mut tx, mut rx) = websocket.split();
+            tokio::spawn(async move { while (rx.next().await).is_some() {} });
+            tx.send(warp::filters::ws::Message::close_with(
+                4000u16,
+                "go elsewhere",
+            ))
+            .await
+            .expect("can send")
+        });
+
+        let ws_config = test_ws_config();
+        let (ws_chat, mut incoming_rx) = create_ws_chat_service(ws_config, ws_server).await;
+
+        let event = incoming_rx.recv().await;
+        assert_matches!(
+            event,
+            Some(ServerEvent::Stopped(ChatServiceError::ChannelClosed(info)))
+            if info == DisconnectInfo {
+                close_code: Some(4000),
+                reason: Some("go elsewhere".to_owned()),
+                alerts: vec![],
+            }
+        );
+        assert!(ws_chat.service_status().unwrap().is_cancelled());
+        validate_server_stopped_successfully(server_res_rx).await;
+    }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn ws_service_reports_alerts_from_upgrade_response() {
+        let (ws_server, server_res_rx) = ws_warp_filter(move |websocket| async move {
+            let (mut tx, mut rx) = websocket.split();
+            tokio::spawn(async move { while (rx.next().await).is_some() {} });
+            tx.send(warp::filters::ws::Message::close())
+                .await
+                .expect("can send")
+        });
+        let ws_server = ws_server.with(warp::reply::with::header(
+            "x-signal-alert",
+            "critical-idle-primary-device",
+        ));
+
+        let ws_config = test_ws_config();
+        let (ws_chat, mut incoming_rx) = create_ws_chat_service(ws_config, ws_server).await;
+
+        let event = incoming_rx.recv().await;
+        assert_matches!(
+            event,
+            Some(ServerEvent::Stopped(ChatServiceError::ChannelClosed(info)))
+            if info.alerts == ["critical-idle-primary-device"]
+        );
+        assert!(ws_chat.service_status().unwrap().is_cancelled());
+        validate_server_stopped_successfully(server_res_rx).await;
+    }
+
     #[tokio::test(flavor = "current_thread", start_paused = true)]
     async fn ws_service_stops_on_unexpected_frame_from_server() {
         let ws_config = test_ws_config();
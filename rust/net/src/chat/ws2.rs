@@ -27,7 +27,8 @@ use tokio_stream::wrappers::{ReceiverStream, UnboundedReceiverStream};
 use tungstenite::Message;
 
 use crate::chat::{
-    ChatMessageType, ChatServiceError, MessageProto, Request, RequestProto, Response, ResponseProto,
+    ChatMessageType, ChatServiceError, DisconnectInfo, MessageProto, Request, RequestProto,
+    Response, ResponseProto,
 };
 use crate::infra::ws::TextOrBinary;
 use crate::infra::ws2::{MessageEvent, NextEventError, TungsteniteSendError};
@@ -96,6 +97,8 @@ pub enum ListenerEvent {
 pub enum SendError {
     /// the chat service is no longer connected
     Disconnected { reason: &'static str },
+    /// the server closed the connection
+    ClosedByServer(DisconnectInfo),
     /// an OS-level I/O error occurred
     Io(IoErrorKind),
     /// the message is larger than the configured limit
@@ -410,9 +413,7 @@ enum TaskErrorState {
     Panic(#[allow(unused)] Box<dyn Any + Send>),
     SendFailed,
     AbnormalServerClose {
-        #[allow(unused)]
         code: tungstenite::protocol::frame::coding::CloseCode,
-        #[allow(unused)]
         reason: String,
     },
     ReceiveFailed,
@@ -1094,9 +1095,9 @@ impl From<&TaskErrorState> for SendError {
             TaskErrorState::Panic(_) => SendError::Disconnected {
                 reason: "chat task panicked",
             },
-            TaskErrorState::AbnormalServerClose { .. } => SendError::Disconnected {
-                reason: "server closed abnormally",
-            },
+            TaskErrorState::AbnormalServerClose { code, reason } => {
+                SendError::ClosedByServer(DisconnectInfo::from_close_frame((*code).into(), reason))
+            }
             TaskErrorState::ReceiveFailed => SendError::Disconnected {
                 reason: "receive failed",
             },
@@ -1170,10 +1171,13 @@ impl From<TaskExitError> for ChatServiceError {
                 NextEventError::PingFailed(tungstenite_error)
                 | NextEventError::CloseFailed(tungstenite_error) => tungstenite_error.into(),
                 NextEventError::ReceiveError(tungstenite_error) => tungstenite_error.into(),
-                NextEventError::UnexpectedConnectionClose
-                | NextEventError::AbnormalServerClose { .. } => {
-                    WebSocketServiceError::ChannelClosed
+                NextEventError::AbnormalServerClose { code, reason } => {
+                    return ChatServiceError::ChannelClosed(DisconnectInfo::from_close_frame(
+                        code.into(),
+                        &reason,
+                    ));
                 }
+                NextEventError::UnexpectedConnectionClose => WebSocketServiceError::ChannelClosed,
                 NextEventError::ServerIdleTimeout(_duration) => {
                     WebSocketServiceError::ChannelIdleTooLong
                 }
@@ -1196,7 +1200,8 @@ impl From<TaskExitError> for ChatServiceError {
 impl From<SendError> for ChatServiceError {
     fn from(value: SendError) -> Self {
         match value {
-            SendError::Disconnected { reason: _ } => ChatServiceError::ServiceInactive,
+            SendError::Disconnected { reason: _ } => ChatServiceError::ServiceInactive(None),
+            SendError::ClosedByServer(info) => ChatServiceError::ServiceInactive(Some(info)),
             SendError::Io(error_kind) => {
                 ChatServiceError::WebSocket(WebSocketServiceError::Io(error_kind.into()))
             }
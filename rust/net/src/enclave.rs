@@ -13,7 +13,8 @@ use derive_where::derive_where;
 use http::uri::PathAndQuery;
 use http::HeaderMap;
 use libsignal_net_infra::connection_manager::{
-    ConnectionManager, MultiRouteConnectionManager, SingleRouteThrottlingConnectionManager,
+    ConnectionManager, MultiRouteConnectionManager, RetryPolicy,
+    SingleRouteThrottlingConnectionManager,
 };
 use libsignal_net_infra::errors::LogSafeDisplay;
 use libsignal_net_infra::host::Host;
@@ -270,6 +271,12 @@ impl From<AttestedConnectionError> for Error {
     }
 }
 
+impl<E: EnclaveKind, C> EnclaveEndpointConnection<E, C> {
+    pub fn manager(&self) -> &C {
+        &self.endpoint_connection.manager
+    }
+}
+
 impl<E: EnclaveKind + NewHandshake, C: ConnectionManager> EnclaveEndpointConnection<E, C> {
     pub(crate) async fn connect<S: AsyncDuplexStream, T: TransportConnector<Stream = S>>(
         &self,
@@ -337,7 +344,7 @@ async fn connect_attested<C: ConnectionManager, T: TransportConnector>(
     let connector = WebSocketServiceConnector::new(connector);
     let service_initializer = ServiceInitializer::new(connector, &endpoint_connection.manager);
     let connection_attempt_result = service_initializer.connect().await;
-    let (websocket, connection_info) = match connection_attempt_result {
+    let (websocket, connection_info, _response_headers) = match connection_attempt_result {
         ServiceState::Active(websocket, _) => Ok(websocket),
         ServiceState::Error(e) => Err(Error::WebSocketConnect(e)),
         ServiceState::Cooldown(_) | ServiceState::ConnectionTimedOut => {
@@ -384,6 +391,7 @@ impl<E: EnclaveKind> EnclaveEndpointConnection<E, MultiRouteConnectionManager> {
         endpoint: &EnclaveEndpoint<'static, E>,
         connection_params: impl IntoIterator<Item = ConnectionParams>,
         one_route_connect_timeout: Duration,
+        retry_policy: RetryPolicy,
         network_change_event: &ObservableEvent,
     ) -> Self {
         Self {
@@ -394,6 +402,7 @@ impl<E: EnclaveKind> EnclaveEndpointConnection<E, MultiRouteConnectionManager> {
                     E::url_path(endpoint.params.mr_enclave.as_ref()),
                     one_route_connect_timeout,
                 ),
+                retry_policy,
                 network_change_event,
             ),
             params: endpoint.params.clone(),
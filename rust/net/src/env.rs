@@ -4,14 +4,14 @@
 //
 
 use std::collections::HashMap;
-use std::iter;
 use std::net::{Ipv4Addr, Ipv6Addr};
 use std::num::NonZeroU16;
 use std::sync::Arc;
 
 use const_str::ip_addr;
 use http::HeaderValue;
-use libsignal_net_infra::certs::RootCertificates;
+use itertools::Itertools as _;
+use libsignal_net_infra::certs::{RootCertificates, SpkiPins};
 use libsignal_net_infra::dns::lookup_result::LookupResult;
 use libsignal_net_infra::host::Host;
 use libsignal_net_infra::route::{
@@ -320,6 +320,14 @@ pub struct DomainConfig {
     pub ip_v6: &'static [Ipv6Addr],
 }
 
+impl DomainConfig {
+    /// See [`ConnectionConfig::with_pins`].
+    pub fn with_pins(mut self, pins: SpkiPins) -> Self {
+        self.connect = self.connect.with_pins(pins);
+        self
+    }
+}
+
 #[derive(Clone)]
 pub struct ConnectionConfig {
     /// The domain name of the resource.
@@ -357,7 +365,39 @@ impl DomainConfig {
     }
 }
 
+/// The order routes are tried in when censorship circumvention is enabled.
+///
+/// The app-configured proxy, if any, isn't listed because it's applied to whichever route is in use.
+pub const DEFAULT_ROUTE_ORDER: [RouteType; 3] =
+    [RouteType::Direct, RouteType::ProxyF, RouteType::ProxyG];
+
+/// Takes one item from each list in turn, until they've all run out.
+fn round_robin<T>(lists: Vec<Vec<T>>) -> Vec<T> {
+    let mut iters = lists.into_iter().map(Vec::into_iter).collect_vec();
+    let mut result = Vec::new();
+    while !iters.is_empty() {
+        iters.retain_mut(|iter| match iter.next() {
+            Some(item) => {
+                result.push(item);
+                true
+            }
+            None => false,
+        });
+    }
+    result
+}
+
 impl ConnectionConfig {
+    /// Requires direct connections to this resource to present one of `pins`, on top of the usual
+    /// certificate checks.
+    ///
+    /// Domain-fronted routes end at the fronting provider, so they keep that provider's
+    /// certificates and aren't pinned.
+    pub fn with_pins(mut self, pins: SpkiPins) -> Self {
+        self.cert = self.cert.with_pins(pins);
+        self
+    }
+
     pub fn direct_connection_params(&self) -> ConnectionParams {
         let result = {
             let hostname = self.hostname.into();
@@ -381,24 +421,51 @@ impl ConnectionConfig {
     }
 
     pub fn connection_params_with_fallback(&self) -> Vec<ConnectionParams> {
-        let direct = self.direct_connection_params();
-        if let Some(proxy) = &self.proxy {
-            let mut rng = thread_rng();
-            // TODO use array::each_ref() once MSRV >= 1.77
-            let [params_a, params_b] = &proxy.configs;
-            let [params_a, params_b] = [params_a, params_b].map(|config| {
-                config.shuffled_connection_params(
-                    proxy.path_prefix,
-                    self.confirmation_header_name,
-                    &mut rng,
-                )
-            });
-
-            let proxy_params = itertools::interleave(params_a, params_b);
-            iter::once(direct).chain(proxy_params).collect()
-        } else {
-            iter::once(direct).collect()
+        self.connection_params_in_order(&DEFAULT_ROUTE_ORDER)
+    }
+
+    /// Returns the connection parameters for each kind of route in `order`, in that order.
+    ///
+    /// Route types this config doesn't have (such as proxies for a service that isn't reachable
+    /// through them) are skipped, as are repeats. Adjacent proxy routes are interleaved, so that a
+    /// failure of one proxy provider doesn't hold up trying the other. If that leaves nothing at all,
+    /// the direct route is used anyway, since there has to be some way to connect.
+    pub fn connection_params_in_order(&self, order: &[RouteType]) -> Vec<ConnectionParams> {
+        let mut rng = thread_rng();
+        let mut params = Vec::new();
+        let mut pending_proxy_params = Vec::new();
+        for route_type in order.iter().copied().unique() {
+            if route_type == RouteType::Direct {
+                params.extend(round_robin(std::mem::take(&mut pending_proxy_params)));
+                params.push(self.direct_connection_params());
+                continue;
+            }
+            let Some(proxy) = &self.proxy else {
+                continue;
+            };
+            let Some(config) = proxy
+                .configs
+                .iter()
+                .find(|config| config.route_type == route_type)
+            else {
+                continue;
+            };
+            pending_proxy_params.push(
+                config
+                    .shuffled_connection_params(
+                        proxy.path_prefix,
+                        self.confirmation_header_name,
+                        &mut rng,
+                    )
+                    .collect_vec(),
+            );
+        }
+        params.extend(round_robin(pending_proxy_params));
+        if params.is_empty() {
+            log::warn!("no usable routes in {order:?}; falling back to a direct connection");
+            params.push(self.direct_connection_params());
         }
+        params
     }
 
     pub fn route_provider(
@@ -677,6 +744,33 @@ mod test {
         }
     }
 
+    #[test]
+    fn connection_params_follow_route_order() {
+        let config = &DOMAIN_CONFIG_CHAT.connect;
+        let route_types = |order: &[RouteType]| {
+            config
+                .connection_params_in_order(order)
+                .into_iter()
+                .map(|params| params.route_type)
+                .collect_vec()
+        };
+
+        let default_order = route_types(&DEFAULT_ROUTE_ORDER);
+        assert_eq!(default_order[0], RouteType::Direct);
+        assert!(default_order[1..].contains(&RouteType::ProxyF));
+        assert!(default_order[1..].contains(&RouteType::ProxyG));
+        assert!(!default_order[1..].contains(&RouteType::Direct));
+
+        assert_eq!(route_types(&[RouteType::Direct]), [RouteType::Direct]);
+        assert_eq!(route_types(&[]), [RouteType::Direct]);
+
+        let fronting_last = route_types(&[RouteType::ProxyG, RouteType::Direct, RouteType::ProxyG]);
+        assert_eq!(fronting_last.last(), Some(&RouteType::Direct));
+        assert!(fronting_last[..fronting_last.len() - 1]
+            .iter()
+            .all(|route_type| *route_type == RouteType::ProxyG));
+    }
+
     #[test_matrix([&DOMAIN_CONFIG_CDSI, &DOMAIN_CONFIG_CDSI_STAGING])]
     fn cdsi_has_no_confirmation_header(config: &DomainConfig) {
         assert_eq!(
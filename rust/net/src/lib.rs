@@ -10,6 +10,9 @@ pub mod chat;
 pub mod enclave;
 pub mod env;
 pub mod proto;
+pub mod rate_limit;
+pub mod route_state;
+pub mod storage_service;
 pub mod svr;
 pub mod svr3;
 pub mod ws;
@@ -5,3 +5,5 @@
 
 pub(crate) mod cds2;
 pub mod chat_websocket;
+pub(crate) mod route_state;
+pub(crate) mod storage_service;
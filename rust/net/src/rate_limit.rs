@@ -0,0 +1,123 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Structured details for requests the server has turned away for exceeding a rate limit.
+
+use http::HeaderMap;
+use libsignal_net_infra::extract_retry_after_seconds;
+
+/// What a server-imposed rate limit is counted against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[repr(u8)]
+pub enum RateLimitScope {
+    /// The server did not say, or used a scope this version doesn't recognize.
+    #[default]
+    #[serde(other)]
+    Unspecified = 0,
+    /// The limit applies to a single phone number, whichever account is asking about it.
+    PerNumber = 1,
+    /// The limit applies to the requesting account.
+    PerAccount = 2,
+}
+
+/// A rate limit reported by the server, along with whatever context it gave.
+///
+/// Only `retry_after_seconds` is guaranteed; the scope and reason are filled in when the server
+/// provides them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    /// How long to wait before trying again.
+    pub retry_after_seconds: u32,
+    pub scope: RateLimitScope,
+    /// A server-provided explanation, suitable for logs but not for display to users.
+    pub reason: Option<String>,
+}
+
+/// The optional fields a server may send alongside a rate limit, as JSON in a response body or a
+/// close frame reason.
+#[derive(Debug, Default, serde::Deserialize)]
+#[cfg_attr(test, derive(serde::Serialize))]
+pub(crate) struct RateLimitDetails {
+    #[serde(default)]
+    pub(crate) scope: RateLimitScope,
+    #[serde(default)]
+    pub(crate) reason: Option<String>,
+}
+
+impl RateLimit {
+    /// A rate limit with no information beyond when to retry.
+    pub fn retry_after(retry_after_seconds: u32) -> Self {
+        Self {
+            retry_after_seconds,
+            scope: RateLimitScope::Unspecified,
+            reason: None,
+        }
+    }
+
+    pub(crate) fn with_details(retry_after_seconds: u32, details: RateLimitDetails) -> Self {
+        let RateLimitDetails { scope, reason } = details;
+        Self {
+            retry_after_seconds,
+            scope,
+            reason,
+        }
+    }
+
+    /// Extracts a rate limit from an HTTP response's `Retry-After` header and (optionally) its
+    /// body.
+    ///
+    /// Returns `None` if there is no usable `Retry-After` header. A body that isn't JSON, or that
+    /// doesn't have the expected fields, is ignored.
+    pub fn from_http_response(headers: &HeaderMap, body: Option<&[u8]>) -> Option<Self> {
+        let retry_after_seconds = extract_retry_after_seconds(headers)?;
+        let details = body
+            .and_then(|body| serde_json::from_slice(body).ok())
+            .unwrap_or_default();
+        Some(Self::with_details(retry_after_seconds, details))
+    }
+}
+
+/// Only the retry interval is displayed; the server's reason is left out of logs by default.
+impl std::fmt::Display for RateLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}s", self.retry_after_seconds)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use http::HeaderValue;
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(None => RateLimit::retry_after(30); "no body")]
+    #[test_case(Some(b"not json") => RateLimit::retry_after(30); "non-JSON body")]
+    #[test_case(Some(br#"{"scope":"per-number"}"#) => RateLimit {
+        retry_after_seconds: 30,
+        scope: RateLimitScope::PerNumber,
+        reason: None,
+    }; "scope only")]
+    #[test_case(Some(br#"{"scope":"per-account","reason":"too many lookups"}"#) => RateLimit {
+        retry_after_seconds: 30,
+        scope: RateLimitScope::PerAccount,
+        reason: Some("too many lookups".to_owned()),
+    }; "scope and reason")]
+    #[test_case(Some(br#"{"scope":"per-galaxy"}"#) => RateLimit::retry_after(30); "unknown scope")]
+    fn from_http_response(body: Option<&[u8]>) -> RateLimit {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("30"));
+        RateLimit::from_http_response(&headers, body).expect("has Retry-After")
+    }
+
+    #[test]
+    fn from_http_response_requires_retry_after() {
+        assert_eq!(
+            RateLimit::from_http_response(&HeaderMap::new(), Some(br#"{"scope":"per-number"}"#)),
+            None
+        );
+    }
+}
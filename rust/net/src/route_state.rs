@@ -0,0 +1,535 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! What a client has learned about how to reach the servers, in a form that can be saved across
+//! app launches.
+//!
+//! On a censored network, finding a route that works can take several slow attempts. Saving which
+//! routes most recently worked (and the DNS results used to reach them) lets the next launch try
+//! those first instead of starting over.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use aes_gcm_siv::aead::{Aead, Payload};
+use aes_gcm_siv::{Aes256GcmSiv, KeyInit, Nonce};
+use libsignal_net_infra::connection_manager::RouteOutcome;
+use libsignal_net_infra::{ConnectionParams, RouteType};
+use prost::Message as _;
+use rand::{CryptoRng, RngCore};
+
+use crate::proto::route_state as proto;
+
+/// Route outcomes older than this are dropped rather than saved.
+pub const MAX_ROUTE_OUTCOME_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+const FORMAT_VERSION: u8 = 1;
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, displaydoc::Display, thiserror::Error)]
+pub enum RouteStateError {
+    /// unsupported route state version {0}
+    UnsupportedVersion(u8),
+    /// route state could not be decrypted
+    DecryptionFailed,
+    /// route state is malformed
+    InvalidData,
+}
+
+/// Identifies a single route (direct, or through a particular front) to a particular service.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RouteKey {
+    pub route_type: RouteType,
+    pub sni: Arc<str>,
+    pub http_host: Arc<str>,
+}
+
+impl RouteKey {
+    pub fn for_params(params: &ConnectionParams) -> Self {
+        Self {
+            route_type: params.route_type,
+            sni: params.transport.sni.clone(),
+            http_host: params.http_host.clone(),
+        }
+    }
+
+    pub fn matches(&self, params: &ConnectionParams) -> bool {
+        self.route_type == params.route_type
+            && self.sni == params.transport.sni
+            && self.http_host == params.http_host
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteRecord {
+    pub route: RouteKey,
+    /// The app-provided proxy the route was used through, as `host:port`.
+    pub proxy: Option<String>,
+    pub outcome: RouteOutcome,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DnsRecord {
+    pub hostname: String,
+    pub ipv4: Vec<Ipv4Addr>,
+    pub ipv6: Vec<Ipv6Addr>,
+    pub expiration: SystemTime,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RouteState {
+    pub routes: Vec<RouteRecord>,
+    pub dns_lookups: Vec<DnsRecord>,
+    /// The order the app asked for routes to be tried in, if it set one.
+    pub route_order: Option<Vec<RouteType>>,
+}
+
+impl RouteState {
+    /// Combines two states, keeping the most recent record for each route and hostname.
+    ///
+    /// Route outcomes older than [`MAX_ROUTE_OUTCOME_AGE`] and DNS results that have expired as of
+    /// `now` are dropped. `other`'s route order is used if it has one.
+    pub fn merge(self, other: Self, now: SystemTime) -> Self {
+        let oldest_relevant = now
+            .checked_sub(MAX_ROUTE_OUTCOME_AGE)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        let mut routes = HashMap::new();
+        for record in self.routes.into_iter().chain(other.routes) {
+            if record.outcome.at < oldest_relevant {
+                continue;
+            }
+            match routes.entry((record.route.clone(), record.proxy.clone())) {
+                Entry::Vacant(entry) => {
+                    entry.insert(record);
+                }
+                Entry::Occupied(mut entry) => {
+                    if entry.get().outcome.at < record.outcome.at {
+                        entry.insert(record);
+                    }
+                }
+            }
+        }
+        let mut routes: Vec<RouteRecord> = routes.into_values().collect();
+        routes.sort_by_key(|record| std::cmp::Reverse(record.outcome.at));
+
+        let mut dns_lookups = HashMap::new();
+        for record in self.dns_lookups.into_iter().chain(other.dns_lookups) {
+            if record.expiration < now {
+                continue;
+            }
+            match dns_lookups.entry(record.hostname.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(record);
+                }
+                Entry::Occupied(mut entry) => {
+                    if entry.get().expiration < record.expiration {
+                        entry.insert(record);
+                    }
+                }
+            }
+        }
+        let mut dns_lookups: Vec<DnsRecord> = dns_lookups.into_values().collect();
+        dns_lookups.sort_by(|a, b| a.hostname.cmp(&b.hostname));
+
+        Self {
+            routes,
+            dns_lookups,
+            route_order: other.route_order.or(self.route_order),
+        }
+    }
+
+    /// Returns the routes whose most recent attempt through `proxy` succeeded, most recent first.
+    pub fn working_routes(&self, proxy: Option<&str>) -> Vec<RouteKey> {
+        let mut working = self
+            .routes
+            .iter()
+            .filter(|record| record.outcome.succeeded && record.proxy.as_deref() == proxy)
+            .collect::<Vec<_>>();
+        working.sort_by_key(|record| std::cmp::Reverse(record.outcome.at));
+        working
+            .into_iter()
+            .map(|record| record.route.clone())
+            .collect()
+    }
+
+    /// Serializes and encrypts the state with a key provided by the app.
+    pub fn encrypt<R: RngCore + CryptoRng>(&self, key: &[u8; 32], rng: &mut R) -> Vec<u8> {
+        let plaintext = proto::RouteState::from(self).encode_to_vec();
+        let mut nonce = [0; NONCE_LEN];
+        rng.fill_bytes(&mut nonce);
+        let ciphertext = Aes256GcmSiv::new(key.into())
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: &[FORMAT_VERSION],
+                },
+            )
+            .expect("can encrypt arbitrary data");
+
+        let mut result = Vec::with_capacity(1 + NONCE_LEN + ciphertext.len());
+        result.push(FORMAT_VERSION);
+        result.extend_from_slice(&nonce);
+        result.extend_from_slice(&ciphertext);
+        result
+    }
+
+    /// Reverses [`Self::encrypt`].
+    pub fn decrypt(key: &[u8; 32], encrypted: &[u8]) -> Result<Self, RouteStateError> {
+        let (&version, rest) = encrypted
+            .split_first()
+            .ok_or(RouteStateError::InvalidData)?;
+        if version != FORMAT_VERSION {
+            return Err(RouteStateError::UnsupportedVersion(version));
+        }
+        if rest.len() < NONCE_LEN {
+            return Err(RouteStateError::InvalidData);
+        }
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        let plaintext = Aes256GcmSiv::new(key.into())
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &[FORMAT_VERSION],
+                },
+            )
+            .map_err(|_| RouteStateError::DecryptionFailed)?;
+        let state = proto::RouteState::decode(plaintext.as_slice())
+            .map_err(|_| RouteStateError::InvalidData)?;
+        state.try_into()
+    }
+}
+
+fn to_epoch_millis(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+        .try_into()
+        .unwrap_or(u64::MAX)
+}
+
+fn from_epoch_millis(millis: u64) -> Result<SystemTime, RouteStateError> {
+    SystemTime::UNIX_EPOCH
+        .checked_add(Duration::from_millis(millis))
+        .ok_or(RouteStateError::InvalidData)
+}
+
+fn to_proto_route_type(route_type: RouteType) -> Option<proto::route::Type> {
+    Some(match route_type {
+        RouteType::Direct => proto::route::Type::Direct,
+        RouteType::ProxyF => proto::route::Type::ProxyF,
+        RouteType::ProxyG => proto::route::Type::ProxyG,
+        RouteType::TlsProxy => proto::route::Type::TlsProxy,
+        RouteType::SocksProxy => proto::route::Type::SocksProxy,
+        // Test-only routes aren't worth saving.
+        #[allow(unreachable_patterns)]
+        _ => return None,
+    })
+}
+
+fn from_proto_route_type(route_type: i32) -> Result<RouteType, RouteStateError> {
+    Ok(
+        match proto::route::Type::try_from(route_type).map_err(|_| RouteStateError::InvalidData)? {
+            proto::route::Type::Direct => RouteType::Direct,
+            proto::route::Type::ProxyF => RouteType::ProxyF,
+            proto::route::Type::ProxyG => RouteType::ProxyG,
+            proto::route::Type::TlsProxy => RouteType::TlsProxy,
+            proto::route::Type::SocksProxy => RouteType::SocksProxy,
+        },
+    )
+}
+
+impl From<&RouteState> for proto::RouteState {
+    fn from(value: &RouteState) -> Self {
+        let RouteState {
+            routes,
+            dns_lookups,
+            route_order,
+        } = value;
+        Self {
+            routes: routes
+                .iter()
+                .filter_map(|record| {
+                    let RouteRecord {
+                        route:
+                            RouteKey {
+                                route_type,
+                                sni,
+                                http_host,
+                            },
+                        proxy,
+                        outcome: RouteOutcome { succeeded, at },
+                    } = record;
+                    let route_type = to_proto_route_type(*route_type)?;
+                    Some(proto::Route {
+                        r#type: route_type.into(),
+                        sni: sni.to_string(),
+                        http_host: http_host.to_string(),
+                        proxy: proxy.clone().unwrap_or_default(),
+                        succeeded: *succeeded,
+                        at_epoch_millis: to_epoch_millis(*at),
+                    })
+                })
+                .collect(),
+            dns_lookups: dns_lookups
+                .iter()
+                .map(|record| {
+                    let DnsRecord {
+                        hostname,
+                        ipv4,
+                        ipv6,
+                        expiration,
+                    } = record;
+                    proto::DnsLookup {
+                        hostname: hostname.clone(),
+                        ipv4: ipv4.iter().copied().map(u32::from).collect(),
+                        ipv6: ipv6.iter().map(|ip| ip.octets().to_vec()).collect(),
+                        expiration_epoch_millis: to_epoch_millis(*expiration),
+                    }
+                })
+                .collect(),
+            route_order: route_order.as_ref().map(|order| proto::RouteOrder {
+                routes: order
+                    .iter()
+                    .filter_map(|route_type| to_proto_route_type(*route_type))
+                    .map(Into::into)
+                    .collect(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<proto::RouteState> for RouteState {
+    type Error = RouteStateError;
+
+    fn try_from(value: proto::RouteState) -> Result<Self, Self::Error> {
+        let proto::RouteState {
+            routes,
+            dns_lookups,
+            route_order,
+        } = value;
+        let routes = routes
+            .into_iter()
+            .map(|route| {
+                let proto::Route {
+                    r#type,
+                    sni,
+                    http_host,
+                    proxy,
+                    succeeded,
+                    at_epoch_millis,
+                } = route;
+                Ok(RouteRecord {
+                    route: RouteKey {
+                        route_type: from_proto_route_type(r#type)?,
+                        sni: sni.into(),
+                        http_host: http_host.into(),
+                    },
+                    proxy: Some(proxy).filter(|proxy| !proxy.is_empty()),
+                    outcome: RouteOutcome {
+                        succeeded,
+                        at: from_epoch_millis(at_epoch_millis)?,
+                    },
+                })
+            })
+            .collect::<Result<_, RouteStateError>>()?;
+        let dns_lookups = dns_lookups
+            .into_iter()
+            .map(|lookup| {
+                let proto::DnsLookup {
+                    hostname,
+                    ipv4,
+                    ipv6,
+                    expiration_epoch_millis,
+                } = lookup;
+                let ipv6 = ipv6
+                    .into_iter()
+                    .map(|ip| {
+                        <[u8; 16]>::try_from(ip)
+                            .map(Ipv6Addr::from)
+                            .map_err(|_| RouteStateError::InvalidData)
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(DnsRecord {
+                    hostname,
+                    ipv4: ipv4.into_iter().map(Ipv4Addr::from).collect(),
+                    ipv6,
+                    expiration: from_epoch_millis(expiration_epoch_millis)?,
+                })
+            })
+            .collect::<Result<_, RouteStateError>>()?;
+        let route_order = route_order
+            .map(|order| {
+                order
+                    .routes
+                    .into_iter()
+                    .map(from_proto_route_type)
+                    .collect::<Result<_, _>>()
+            })
+            .transpose()?;
+        Ok(Self {
+            routes,
+            dns_lookups,
+            route_order,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+    use rand::rngs::OsRng;
+
+    use super::*;
+
+    const KEY: [u8; 32] = [0x42; 32];
+
+    fn route(route_type: RouteType, sni: &str) -> RouteKey {
+        RouteKey {
+            route_type,
+            sni: sni.into(),
+            http_host: "chat.signal.org".into(),
+        }
+    }
+
+    fn record(route: RouteKey, succeeded: bool, at: SystemTime) -> RouteRecord {
+        RouteRecord {
+            route,
+            proxy: None,
+            outcome: RouteOutcome { succeeded, at },
+        }
+    }
+
+    fn example_state(now: SystemTime) -> RouteState {
+        RouteState {
+            routes: vec![
+                record(
+                    route(RouteType::ProxyG, "front.example"),
+                    true,
+                    now - Duration::from_secs(60),
+                ),
+                RouteRecord {
+                    proxy: Some("proxy.example:443".to_owned()),
+                    ..record(route(RouteType::Direct, "chat.signal.org"), false, now)
+                },
+            ],
+            dns_lookups: vec![DnsRecord {
+                hostname: "chat.signal.org".to_owned(),
+                ipv4: vec![Ipv4Addr::new(192, 0, 2, 1)],
+                ipv6: vec![Ipv6Addr::LOCALHOST],
+                expiration: now + Duration::from_secs(60),
+            }],
+            route_order: Some(vec![RouteType::ProxyG, RouteType::Direct]),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        // Use a whole number of milliseconds, since that's what's saved.
+        let now = SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+        let state = example_state(now);
+        let encrypted = state.encrypt(&KEY, &mut OsRng);
+        assert_eq!(RouteState::decrypt(&KEY, &encrypted).expect("valid"), state);
+    }
+
+    #[test]
+    fn wrong_key_or_tampering_is_rejected() {
+        let encrypted = example_state(SystemTime::now()).encrypt(&KEY, &mut OsRng);
+        assert_matches!(
+            RouteState::decrypt(&[0; 32], &encrypted),
+            Err(RouteStateError::DecryptionFailed)
+        );
+
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_matches!(
+            RouteState::decrypt(&KEY, &tampered),
+            Err(RouteStateError::DecryptionFailed)
+        );
+
+        let mut wrong_version = encrypted;
+        wrong_version[0] = 0;
+        assert_matches!(
+            RouteState::decrypt(&KEY, &wrong_version),
+            Err(RouteStateError::UnsupportedVersion(0))
+        );
+
+        assert_matches!(
+            RouteState::decrypt(&KEY, &[FORMAT_VERSION]),
+            Err(RouteStateError::InvalidData)
+        );
+    }
+
+    #[test]
+    fn merge_keeps_newest_and_drops_stale() {
+        let now = SystemTime::now();
+        let front = route(RouteType::ProxyF, "front.example");
+        let older = RouteState {
+            routes: vec![
+                record(front.clone(), true, now - Duration::from_secs(120)),
+                record(
+                    route(RouteType::Direct, "chat.signal.org"),
+                    true,
+                    now - MAX_ROUTE_OUTCOME_AGE - Duration::from_secs(1),
+                ),
+            ],
+            dns_lookups: vec![DnsRecord {
+                hostname: "chat.signal.org".to_owned(),
+                ipv4: vec![Ipv4Addr::new(192, 0, 2, 1)],
+                ipv6: vec![],
+                expiration: now - Duration::from_secs(1),
+            }],
+            route_order: Some(vec![RouteType::Direct]),
+        };
+        let newer = RouteState {
+            routes: vec![record(front.clone(), false, now - Duration::from_secs(60))],
+            dns_lookups: vec![],
+            route_order: None,
+        };
+
+        let merged = older.merge(newer, now);
+        assert_eq!(
+            merged,
+            RouteState {
+                routes: vec![record(front, false, now - Duration::from_secs(60))],
+                dns_lookups: vec![],
+                route_order: Some(vec![RouteType::Direct]),
+            }
+        );
+    }
+
+    #[test]
+    fn unset_route_order_round_trips() {
+        let state = RouteState {
+            route_order: None,
+            ..example_state(SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_000))
+        };
+        let encrypted = state.encrypt(&KEY, &mut OsRng);
+        assert_eq!(RouteState::decrypt(&KEY, &encrypted).expect("valid"), state);
+
+        let empty = RouteState {
+            route_order: Some(vec![]),
+            ..state
+        };
+        let encrypted = empty.encrypt(&KEY, &mut OsRng);
+        assert_eq!(RouteState::decrypt(&KEY, &encrypted).expect("valid"), empty);
+    }
+
+    #[test]
+    fn working_routes_are_filtered_by_proxy() {
+        let now = SystemTime::now();
+        let state = example_state(now);
+        assert_eq!(
+            state.working_routes(None),
+            [route(RouteType::ProxyG, "front.example")]
+        );
+        assert_eq!(state.working_routes(Some("proxy.example:443")), vec![]);
+    }
+}
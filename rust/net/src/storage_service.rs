@@ -0,0 +1,797 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Comparing storage service manifests and planning the record operations needed to sync them.
+//!
+//! The storage service holds an encrypted manifest listing the [`StorageId`]s of every record an
+//! account has synced. Records are immutable: changing one means uploading it under a fresh id and
+//! deleting the old one. A sync therefore goes:
+//!
+//! 1. Fetch the remote manifest, and use [`ids_to_fetch`] to find which records are new.
+//! 2. Fetch and decrypt those records, then call [`plan_sync`] with them and the local state.
+//! 3. Apply [`SyncPlan::local`] to the local database, remember [`SyncPlan::unknown`], and, if
+//!    [`SyncPlan::remote`] is present, write it back to the service (retrying from step 1 if the
+//!    write loses a version race).
+
+use std::collections::{BTreeMap, HashSet};
+
+use prost::Message as _;
+use rand::{CryptoRng, Rng};
+
+use crate::proto::storage_service::manifest_record::Identifier;
+use crate::proto::storage_service::ManifestRecord;
+
+/// The length of the random part of a freshly generated [`StorageId`].
+pub const STORAGE_ID_LEN: usize = 16;
+
+/// The identifier of a single record in the storage service.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StorageId {
+    /// The kind of record, as defined by the storage service protos.
+    ///
+    /// Types this client doesn't understand are carried through unchanged.
+    pub record_type: u32,
+    pub raw: Vec<u8>,
+}
+
+impl StorageId {
+    /// Generates a new random id for a record of type `record_type`.
+    pub fn random<R: Rng + CryptoRng>(record_type: u32, rng: &mut R) -> Self {
+        Self {
+            record_type,
+            raw: rng.gen::<[u8; STORAGE_ID_LEN]>().to_vec(),
+        }
+    }
+
+    /// Parses a serialized `ManifestRecord.Identifier`.
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, StorageSyncError> {
+        Identifier::decode(bytes)
+            .map(Self::from)
+            .map_err(|_| StorageSyncError::InvalidProtobuf)
+    }
+
+    /// Serializes the id as a `ManifestRecord.Identifier`.
+    pub fn serialize(&self) -> Vec<u8> {
+        Identifier::from(self).encode_to_vec()
+    }
+}
+
+impl From<Identifier> for StorageId {
+    fn from(Identifier { raw, r#type }: Identifier) -> Self {
+        Self {
+            record_type: r#type,
+            raw,
+        }
+    }
+}
+
+impl From<&StorageId> for Identifier {
+    fn from(id: &StorageId) -> Self {
+        Self {
+            raw: id.raw.clone(),
+            r#type: id.record_type,
+        }
+    }
+}
+
+/// The decrypted contents of a storage service manifest.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageManifest {
+    pub version: u64,
+    pub ids: Vec<StorageId>,
+}
+
+impl StorageManifest {
+    /// Parses a decrypted `ManifestRecord`.
+    ///
+    /// Fields other than the version and the record ids are ignored. There's deliberately no way
+    /// to serialize a manifest, since that would drop them; callers build the manifest to upload
+    /// from [`RemoteChanges::manifest`] themselves.
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, StorageSyncError> {
+        let ManifestRecord {
+            version,
+            identifiers,
+        } = ManifestRecord::decode(bytes).map_err(|_| StorageSyncError::InvalidProtobuf)?;
+        Ok(Self {
+            version,
+            ids: identifiers.into_iter().map(StorageId::from).collect(),
+        })
+    }
+}
+
+/// A decrypted record that can take part in a sync.
+pub trait StorageRecord: Clone + PartialEq {
+    /// What identifies "the same" record across devices, such as a contact's ACI or a group's
+    /// master key.
+    type Key: Ord;
+
+    /// The value used to fill in [`StorageId::record_type`] when uploading this record.
+    fn record_type(&self) -> u32;
+
+    fn key(&self) -> Self::Key;
+
+    /// Combines two records with the same key, one fetched from the service and one local.
+    ///
+    /// Should return a value equal to `remote` if the local record adds nothing, and a value equal
+    /// to `local` if the remote record adds nothing; the plan uses this to avoid needless writes.
+    fn merge(remote: &Self, local: &Self) -> Self;
+}
+
+/// A record tracked by its serialized form rather than by its fields.
+///
+/// This is for callers that can't (or don't want to) implement a field-by-field
+/// [`merge`](StorageRecord::merge). A local record that isn't on the service yet is a local change
+/// that hasn't been uploaded, and wins over the service's copy; otherwise the service's copy wins.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpaqueStorageRecord {
+    pub record_type: u32,
+    /// What identifies "the same" record across devices, as extracted by the caller.
+    pub key: Vec<u8>,
+    /// The serialized record.
+    pub data: Vec<u8>,
+    /// Whether this is a local record that hasn't been uploaded yet.
+    pub pending_upload: bool,
+}
+
+impl StorageRecord for OpaqueStorageRecord {
+    type Key = (u32, Vec<u8>);
+
+    fn record_type(&self) -> u32 {
+        self.record_type
+    }
+
+    fn key(&self) -> Self::Key {
+        (self.record_type, self.key.clone())
+    }
+
+    fn merge(remote: &Self, local: &Self) -> Self {
+        if local.pending_upload {
+            local.clone()
+        } else {
+            remote.clone()
+        }
+    }
+}
+
+/// A record paired with the id it is (or will be) stored under.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredRecord<R> {
+    pub id: StorageId,
+    pub record: R,
+}
+
+/// What this client knows as of the last sync, plus anything changed since.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalState<R> {
+    /// Every syncable local record, each tagged with the id it was last stored under.
+    ///
+    /// A record changed since the last sync should be tagged with a fresh [`StorageId::random`],
+    /// with its old id listed in [`Self::deleted`].
+    pub records: Vec<StoredRecord<R>>,
+    /// Ids of records deleted locally since the last sync.
+    pub deleted: Vec<StorageId>,
+    /// Ids this client has already fetched but couldn't parse, as returned in
+    /// [`SyncPlan::unknown`] by the last sync.
+    pub unknown: Vec<StorageId>,
+}
+
+impl<R> Default for LocalState<R> {
+    fn default() -> Self {
+        Self {
+            records: vec![],
+            deleted: vec![],
+            unknown: vec![],
+        }
+    }
+}
+
+/// The records fetched for [`ids_to_fetch`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FetchedRecords<R> {
+    /// The records this client could decrypt and parse.
+    pub records: Vec<StoredRecord<R>>,
+    /// Ids of records of types this client doesn't understand.
+    pub unknown: Vec<StorageId>,
+}
+
+impl<R> Default for FetchedRecords<R> {
+    fn default() -> Self {
+        Self {
+            records: vec![],
+            unknown: vec![],
+        }
+    }
+}
+
+/// Changes to make to the local database.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalChanges<R> {
+    /// Records to store, replacing any local record with the same key.
+    pub upserts: Vec<StoredRecord<R>>,
+    /// Ids of local records to delete because another device deleted them.
+    pub deletes: Vec<StorageId>,
+}
+
+/// A write to send to the storage service.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteChanges<R> {
+    /// The manifest to upload; its version is one past the remote manifest's.
+    pub manifest: StorageManifest,
+    pub inserts: Vec<StoredRecord<R>>,
+    pub deletes: Vec<StorageId>,
+}
+
+/// The result of [`plan_sync`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyncPlan<R> {
+    pub local: LocalChanges<R>,
+    /// `None` if the service is already up to date.
+    pub remote: Option<RemoteChanges<R>>,
+    /// Ids of records this client can't parse that are still on the service.
+    ///
+    /// These should be saved and passed back as [`LocalState::unknown`] next time, so they aren't
+    /// fetched again.
+    pub unknown: Vec<StorageId>,
+}
+
+#[derive(Debug, displaydoc::Display, thiserror::Error, Clone, PartialEq, Eq)]
+pub enum StorageSyncError {
+    /// remote manifest version {remote} is older than last synced version {previous}
+    RemoteRolledBack { previous: u64, remote: u64 },
+    /// fetched record {0:?} is not in the remote manifest
+    UnexpectedRecord(StorageId),
+    /// invalid storage service protobuf
+    InvalidProtobuf,
+}
+
+/// Returns the ids in `remote` that this client doesn't know about yet, in manifest order.
+///
+/// These are the records that need to be fetched before calling [`plan_sync`]. Records that were
+/// deleted locally, or that are already known to be of a type this client can't parse, aren't
+/// fetched again.
+pub fn ids_to_fetch<R>(local: &LocalState<R>, remote: &StorageManifest) -> Vec<StorageId> {
+    let known_ids: HashSet<&StorageId> = local
+        .records
+        .iter()
+        .map(|r| &r.id)
+        .chain(&local.deleted)
+        .chain(&local.unknown)
+        .collect();
+    remote
+        .ids
+        .iter()
+        .filter(|id| !known_ids.contains(id))
+        .cloned()
+        .collect()
+}
+
+/// Works out how to bring the local database and the storage service back in sync.
+///
+/// - `previous` is the manifest as of the last successful sync (or an empty manifest if there has
+///   never been one).
+/// - `remote` is the manifest currently on the service.
+/// - `fetched` holds the records for [`ids_to_fetch`]. Records of unknown types are listed by id
+///   only; they stay in the manifest, and are reported in [`SyncPlan::unknown`].
+/// - `local` is the local database's view of the account.
+///
+/// Conflicts are resolved as follows:
+///
+/// - A fetched record with no local counterpart is inserted locally.
+/// - A fetched record whose key matches a local record is [merged](StorageRecord::merge) with it.
+///   If the merged record differs from what the service has, it is re-uploaded under a new id; if
+///   it differs from what is stored locally, it is stored locally.
+/// - A local record whose id was in `previous` but not in `remote` was deleted by another device,
+///   and is deleted locally.
+/// - A local record whose id was in neither manifest hasn't been uploaded yet, and is uploaded.
+/// - A record deleted locally is deleted from the service, if it's still there. If another device
+///   changed it in the meantime, the changed copy is fetched and restored locally instead.
+/// - Duplicate remote records for the same key are collapsed into one.
+pub fn plan_sync<R: StorageRecord, C: Rng + CryptoRng>(
+    previous: &StorageManifest,
+    remote: &StorageManifest,
+    fetched: FetchedRecords<R>,
+    local: LocalState<R>,
+    rng: &mut C,
+) -> Result<SyncPlan<R>, StorageSyncError> {
+    if remote.version < previous.version {
+        return Err(StorageSyncError::RemoteRolledBack {
+            previous: previous.version,
+            remote: remote.version,
+        });
+    }
+
+    let remote_ids: HashSet<&StorageId> = remote.ids.iter().collect();
+    let previous_ids: HashSet<&StorageId> = previous.ids.iter().collect();
+    if let Some(unexpected) = fetched
+        .records
+        .iter()
+        .map(|r| &r.id)
+        .chain(&fetched.unknown)
+        .find(|id| !remote_ids.contains(id))
+    {
+        return Err(StorageSyncError::UnexpectedRecord(unexpected.clone()));
+    }
+
+    let unknown_ids: HashSet<&StorageId> = local.unknown.iter().chain(&fetched.unknown).collect();
+    let unknown = remote
+        .ids
+        .iter()
+        .filter(|id| unknown_ids.contains(id))
+        .cloned()
+        .collect();
+
+    let mut local_changes = LocalChanges {
+        upserts: vec![],
+        deletes: vec![],
+    };
+    let mut remote_inserts = vec![];
+    let mut remote_deletes: Vec<StorageId> = local
+        .deleted
+        .into_iter()
+        .filter(|id| remote_ids.contains(id))
+        .collect();
+
+    let mut local_by_key: BTreeMap<R::Key, StoredRecord<R>> = BTreeMap::new();
+    for stored in local.records {
+        if remote_ids.contains(&stored.id) {
+            local_by_key.insert(stored.record.key(), stored);
+        } else if previous_ids.contains(&stored.id) {
+            local_changes.deletes.push(stored.id);
+        } else {
+            remote_inserts.push(stored.clone());
+            local_by_key.insert(stored.record.key(), stored);
+        }
+    }
+
+    for fetched in fetched.records {
+        let key = fetched.record.key();
+        let Some(current) = local_by_key.remove(&key) else {
+            local_changes.upserts.push(fetched.clone());
+            local_by_key.insert(key, fetched);
+            continue;
+        };
+
+        let merged = R::merge(&fetched.record, &current.record);
+        let resolved = if merged == fetched.record {
+            // The service's copy wins; drop whatever we had for this key from both sides.
+            if current.id != fetched.id {
+                retract(
+                    &current.id,
+                    &remote_ids,
+                    &mut remote_inserts,
+                    &mut remote_deletes,
+                );
+            }
+            fetched
+        } else if merged == current.record {
+            // The local copy wins, and is either already on the service or about to be uploaded.
+            remote_deletes.push(fetched.id);
+            local_by_key.insert(key, current);
+            continue;
+        } else {
+            // Neither copy is complete, so both are replaced by the merged record.
+            remote_deletes.push(fetched.id);
+            retract(
+                &current.id,
+                &remote_ids,
+                &mut remote_inserts,
+                &mut remote_deletes,
+            );
+            let replacement = StoredRecord {
+                id: StorageId::random(merged.record_type(), rng),
+                record: merged,
+            };
+            remote_inserts.push(replacement.clone());
+            replacement
+        };
+        local_changes.upserts.push(resolved.clone());
+        local_by_key.insert(key, resolved);
+    }
+
+    let remote_changes = (!remote_inserts.is_empty() || !remote_deletes.is_empty()).then(|| {
+        let deleted: HashSet<&StorageId> = remote_deletes.iter().collect();
+        let ids = remote
+            .ids
+            .iter()
+            .filter(|id| !deleted.contains(id))
+            .chain(remote_inserts.iter().map(|r| &r.id))
+            .cloned()
+            .collect();
+        RemoteChanges {
+            manifest: StorageManifest {
+                version: remote.version + 1,
+                ids,
+            },
+            inserts: remote_inserts,
+            deletes: remote_deletes,
+        }
+    });
+
+    Ok(SyncPlan {
+        local: local_changes,
+        remote: remote_changes,
+        unknown,
+    })
+}
+
+/// Removes `id` from the pending remote changes, deleting it remotely if it was already uploaded.
+fn retract<R>(
+    id: &StorageId,
+    remote_ids: &HashSet<&StorageId>,
+    remote_inserts: &mut Vec<StoredRecord<R>>,
+    remote_deletes: &mut Vec<StorageId>,
+) {
+    if remote_ids.contains(id) {
+        remote_deletes.push(id.clone());
+    } else {
+        remote_inserts.retain(|r| &r.id != id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use prost::Message as _;
+    use rand::rngs::OsRng;
+
+    use super::*;
+
+    /// A contact-like record: a name plus a set of flags that merge by union.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct TestRecord {
+        name: &'static str,
+        flags: u8,
+    }
+
+    impl StorageRecord for TestRecord {
+        type Key = &'static str;
+
+        fn record_type(&self) -> u32 {
+            1
+        }
+
+        fn key(&self) -> Self::Key {
+            self.name
+        }
+
+        fn merge(remote: &Self, local: &Self) -> Self {
+            Self {
+                name: remote.name,
+                flags: remote.flags | local.flags,
+            }
+        }
+    }
+
+    fn id(n: u8) -> StorageId {
+        StorageId {
+            record_type: 1,
+            raw: vec![n],
+        }
+    }
+
+    fn stored(n: u8, name: &'static str, flags: u8) -> StoredRecord<TestRecord> {
+        StoredRecord {
+            id: id(n),
+            record: TestRecord { name, flags },
+        }
+    }
+
+    fn fetched(records: Vec<StoredRecord<TestRecord>>) -> FetchedRecords<TestRecord> {
+        FetchedRecords {
+            records,
+            ..Default::default()
+        }
+    }
+
+    fn local(records: Vec<StoredRecord<TestRecord>>) -> LocalState<TestRecord> {
+        LocalState {
+            records,
+            ..Default::default()
+        }
+    }
+
+    fn manifest(version: u64, ids: &[u8]) -> StorageManifest {
+        StorageManifest {
+            version,
+            ids: ids.iter().copied().map(id).collect(),
+        }
+    }
+
+    #[test]
+    fn fetches_only_new_ids() {
+        let state = LocalState {
+            records: vec![stored(1, "alice", 0), stored(2, "bob", 0)],
+            deleted: vec![id(5)],
+            unknown: vec![id(6)],
+        };
+        assert_eq!(
+            ids_to_fetch(&state, &manifest(3, &[3, 1, 4, 5, 6])),
+            [id(3), id(4)]
+        );
+    }
+
+    #[test]
+    fn up_to_date() {
+        let plan = plan_sync(
+            &manifest(1, &[1]),
+            &manifest(1, &[1]),
+            fetched(vec![]),
+            local(vec![stored(1, "alice", 0)]),
+            &mut OsRng,
+        )
+        .expect("valid");
+        assert_eq!(plan.local.upserts, []);
+        assert_eq!(plan.local.deletes, []);
+        assert_eq!(plan.remote, None);
+    }
+
+    #[test]
+    fn remote_insert_and_delete_apply_locally() {
+        let plan = plan_sync(
+            &manifest(1, &[1, 2]),
+            &manifest(2, &[1, 3]),
+            fetched(vec![stored(3, "carol", 0)]),
+            local(vec![stored(1, "alice", 0), stored(2, "bob", 0)]),
+            &mut OsRng,
+        )
+        .expect("valid");
+        assert_eq!(plan.local.upserts, [stored(3, "carol", 0)]);
+        assert_eq!(plan.local.deletes, [id(2)]);
+        assert_eq!(plan.remote, None);
+    }
+
+    #[test]
+    fn new_local_record_is_uploaded() {
+        let plan = plan_sync(
+            &manifest(1, &[1]),
+            &manifest(1, &[1]),
+            fetched(vec![]),
+            local(vec![stored(1, "alice", 0), stored(5, "dave", 0)]),
+            &mut OsRng,
+        )
+        .expect("valid");
+        assert_eq!(plan.local.upserts, []);
+        let remote = plan.remote.expect("has changes");
+        assert_eq!(remote.manifest, manifest(2, &[1, 5]));
+        assert_eq!(remote.inserts, [stored(5, "dave", 0)]);
+        assert_eq!(remote.deletes, []);
+    }
+
+    #[test]
+    fn remote_update_wins_when_it_subsumes_local() {
+        let plan = plan_sync(
+            &manifest(1, &[1]),
+            &manifest(2, &[2]),
+            fetched(vec![stored(2, "alice", 0b11)]),
+            local(vec![stored(1, "alice", 0b01)]),
+            &mut OsRng,
+        )
+        .expect("valid");
+        assert_eq!(plan.local.upserts, [stored(2, "alice", 0b11)]);
+        assert_eq!(plan.local.deletes, [id(1)]);
+        assert_eq!(plan.remote, None);
+    }
+
+    #[test]
+    fn conflicting_changes_are_merged_and_reuploaded() {
+        // The local change (flag 0b10) was never uploaded; another device uploaded 0b01.
+        let plan = plan_sync(
+            &manifest(1, &[1]),
+            &manifest(2, &[2]),
+            fetched(vec![stored(2, "alice", 0b01)]),
+            local(vec![stored(5, "alice", 0b10)]),
+            &mut OsRng,
+        )
+        .expect("valid");
+
+        let [merged] = &plan.local.upserts[..] else {
+            panic!("expected one upsert: {:?}", plan.local.upserts);
+        };
+        assert_eq!(merged.record.flags, 0b11);
+        assert_ne!(merged.id, id(2));
+        assert_ne!(merged.id, id(5));
+
+        let remote = plan.remote.expect("has changes");
+        assert_eq!(remote.inserts, [merged.clone()]);
+        assert_eq!(remote.deletes, [id(2)]);
+        assert_eq!(remote.manifest.version, 3);
+        assert_eq!(remote.manifest.ids, [merged.id.clone()]);
+    }
+
+    #[test]
+    fn stale_remote_duplicate_is_replaced_by_local() {
+        let plan = plan_sync(
+            &manifest(1, &[1]),
+            &manifest(2, &[1, 2]),
+            fetched(vec![stored(2, "alice", 0b01)]),
+            local(vec![stored(1, "alice", 0b11)]),
+            &mut OsRng,
+        )
+        .expect("valid");
+        assert_eq!(plan.local.upserts, []);
+        let remote = plan.remote.expect("has changes");
+        assert_eq!(remote.inserts, []);
+        assert_eq!(remote.deletes, [id(2)]);
+        assert_eq!(remote.manifest, manifest(3, &[1]));
+    }
+
+    #[test]
+    fn unknown_records_are_preserved() {
+        let unknown = |n: u8| StorageId {
+            record_type: 99,
+            raw: vec![n],
+        };
+        let remote = StorageManifest {
+            version: 2,
+            ids: vec![id(1), unknown(8), unknown(9)],
+        };
+        let plan = plan_sync(
+            &manifest(1, &[1]),
+            &remote,
+            FetchedRecords {
+                records: vec![],
+                unknown: vec![unknown(9)],
+            },
+            LocalState {
+                records: vec![stored(1, "alice", 0), stored(5, "dave", 0)],
+                deleted: vec![],
+                // Known from an earlier sync; 7 has since been removed by another device.
+                unknown: vec![unknown(7), unknown(8)],
+            },
+            &mut OsRng,
+        )
+        .expect("valid");
+        assert_eq!(plan.unknown, [unknown(8), unknown(9)]);
+        let remote = plan.remote.expect("has changes");
+        assert_eq!(remote.manifest.ids, [id(1), unknown(8), unknown(9), id(5)]);
+    }
+
+    #[test]
+    fn local_deletion_is_deleted_remotely() {
+        let plan = plan_sync(
+            &manifest(1, &[1, 2]),
+            &manifest(1, &[1, 2]),
+            fetched(vec![]),
+            LocalState {
+                records: vec![stored(1, "alice", 0)],
+                deleted: vec![id(2)],
+                unknown: vec![],
+            },
+            &mut OsRng,
+        )
+        .expect("valid");
+        assert_eq!(plan.local.upserts, []);
+        assert_eq!(plan.local.deletes, []);
+        let remote = plan.remote.expect("has changes");
+        assert_eq!(remote.inserts, []);
+        assert_eq!(remote.deletes, [id(2)]);
+        assert_eq!(remote.manifest, manifest(2, &[1]));
+    }
+
+    #[test]
+    fn local_deletion_of_record_already_gone_is_dropped() {
+        let plan = plan_sync(
+            &manifest(1, &[1, 2]),
+            &manifest(2, &[1]),
+            fetched(vec![]),
+            LocalState {
+                records: vec![stored(1, "alice", 0)],
+                deleted: vec![id(2)],
+                unknown: vec![],
+            },
+            &mut OsRng,
+        )
+        .expect("valid");
+        assert_eq!(plan.remote, None);
+    }
+
+    #[test]
+    fn opaque_records_prefer_pending_local_changes() {
+        let opaque = |n: u8, data: &[u8], pending_upload| StoredRecord {
+            id: id(n),
+            record: OpaqueStorageRecord {
+                record_type: 1,
+                key: b"alice".to_vec(),
+                data: data.to_vec(),
+                pending_upload,
+            },
+        };
+        // Another device uploaded a second record for the same key without removing the first.
+        let sync = |local| {
+            plan_sync(
+                &manifest(1, &[1]),
+                &manifest(2, &[1, 2]),
+                FetchedRecords {
+                    records: vec![opaque(2, b"remote", false)],
+                    unknown: vec![],
+                },
+                local,
+                &mut OsRng,
+            )
+            .expect("valid")
+        };
+
+        // An unchanged local record gives way to the other device's copy.
+        let plan = sync(LocalState {
+            records: vec![opaque(1, b"local", false)],
+            ..Default::default()
+        });
+        assert_eq!(plan.local.upserts, [opaque(2, b"remote", false)]);
+        let remote = plan.remote.expect("has changes");
+        assert_eq!(remote.inserts, []);
+        assert_eq!(remote.deletes, [id(1)]);
+        assert_eq!(remote.manifest, manifest(3, &[2]));
+
+        // A local change that hasn't been uploaded yet replaces the other device's copy.
+        let plan = sync(LocalState {
+            records: vec![opaque(5, b"local", true)],
+            deleted: vec![id(1)],
+            unknown: vec![],
+        });
+        assert_eq!(plan.local.upserts, []);
+        let remote = plan.remote.expect("has changes");
+        assert_eq!(remote.inserts, [opaque(5, b"local", true)]);
+        assert_eq!(remote.deletes, [id(1), id(2)]);
+        assert_eq!(remote.manifest, manifest(3, &[5]));
+    }
+
+    #[test]
+    fn ids_and_manifests_parse_from_protobufs() {
+        let id = StorageId {
+            record_type: 42,
+            raw: vec![1, 2, 3],
+        };
+        assert_eq!(StorageId::deserialize(&id.serialize()), Ok(id.clone()));
+
+        let manifest = ManifestRecord {
+            version: 7,
+            identifiers: vec![Identifier::from(&id)],
+        }
+        .encode_to_vec();
+        assert_eq!(
+            StorageManifest::deserialize(&manifest),
+            Ok(StorageManifest {
+                version: 7,
+                ids: vec![id],
+            })
+        );
+
+        assert_eq!(
+            StorageManifest::deserialize(&[0xff]),
+            Err(StorageSyncError::InvalidProtobuf)
+        );
+    }
+
+    #[test]
+    fn rejects_rollback() {
+        assert_matches!(
+            plan_sync::<TestRecord, _>(
+                &manifest(5, &[]),
+                &manifest(4, &[]),
+                fetched(vec![]),
+                local(vec![]),
+                &mut OsRng
+            ),
+            Err(StorageSyncError::RemoteRolledBack {
+                previous: 5,
+                remote: 4
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_unlisted_fetched_record() {
+        assert_matches!(
+            plan_sync(
+                &manifest(1, &[]),
+                &manifest(2, &[1]),
+                fetched(vec![stored(2, "bob", 0)]),
+                local(vec![]),
+                &mut OsRng
+            ),
+            Err(StorageSyncError::UnexpectedRecord(unexpected)) if unexpected == id(2)
+        );
+    }
+}
@@ -21,6 +21,7 @@ pub mod direct;
 pub mod traits;
 use traits::*;
 
+use crate::enclave::PpssSetup;
 use crate::ws::WebSocketServiceConnectError;
 
 // Versions:
@@ -73,6 +74,77 @@ pub enum DeserializeError {
 
 impl LogSafeDisplay for DeserializeError {}
 
+/// Which backends of an SVR3 environment receive shares of a backup.
+///
+/// The secret is split so that every selected backend is needed to restore it; thresholds below
+/// the number of selected backends are not supported by the underlying PPSS scheme.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShareDistribution {
+    server_ids: Vec<u64>,
+}
+
+#[derive(Debug, Eq, PartialEq, displaydoc::Display, Error)]
+pub enum ShareDistributionError {
+    /// No backends were selected
+    NoServers,
+    /// Backend {0} was selected more than once
+    DuplicateServer(u64),
+    /// Backend {0} is not part of this environment
+    UnknownServer(u64),
+    /// A threshold of {threshold} out of {servers} backends is not supported
+    UnsupportedThreshold { threshold: usize, servers: usize },
+}
+
+impl LogSafeDisplay for ShareDistributionError {}
+
+impl ShareDistribution {
+    /// Selects the backends of `Env` identified by `server_ids`, in that order.
+    ///
+    /// `threshold` is the number of backends needed to restore; currently it must be equal to the
+    /// number of selected backends.
+    pub fn new<Env: PpssSetup>(
+        server_ids: impl IntoIterator<Item = u64>,
+        threshold: usize,
+    ) -> Result<Self, ShareDistributionError> {
+        let server_ids: Vec<u64> = server_ids.into_iter().collect();
+        if server_ids.is_empty() {
+            return Err(ShareDistributionError::NoServers);
+        }
+        let known_ids = Env::server_ids();
+        for (i, id) in server_ids.iter().enumerate() {
+            if !known_ids.as_ref().contains(id) {
+                return Err(ShareDistributionError::UnknownServer(*id));
+            }
+            if server_ids[..i].contains(id) {
+                return Err(ShareDistributionError::DuplicateServer(*id));
+            }
+        }
+        if threshold != server_ids.len() {
+            return Err(ShareDistributionError::UnsupportedThreshold {
+                threshold,
+                servers: server_ids.len(),
+            });
+        }
+        Ok(Self { server_ids })
+    }
+
+    /// Selects every backend of `Env`, which is what [`Backup::backup`] does.
+    pub fn all<Env: PpssSetup>() -> Self {
+        Self {
+            server_ids: Env::server_ids().as_ref().to_vec(),
+        }
+    }
+
+    pub fn server_ids(&self) -> &[u64] {
+        &self.server_ids
+    }
+
+    /// The number of backends needed to restore.
+    pub fn threshold(&self) -> usize {
+        self.server_ids.len()
+    }
+}
+
 impl OpaqueMaskedShareSet {
     fn new(inner: MaskedSecret) -> Self {
         Self {
@@ -83,6 +155,13 @@ impl OpaqueMaskedShareSet {
         self.inner.into()
     }
 
+    /// The backends holding shares of this backup.
+    pub fn layout(&self) -> ShareDistribution {
+        ShareDistribution {
+            server_ids: self.inner.server_ids.clone(),
+        }
+    }
+
     // OpaqueMaskedShareSet should be presented to the clients as an opaque blob,
     // therefore serialize/deserialize should be the only public APIs for it.
     pub fn serialize(&self) -> Result<Vec<u8>, SerializeError> {
@@ -153,6 +232,8 @@ pub enum Error {
     ConnectionTimedOut,
     /// Rotation machine took too many steps
     RotationMachineTooManySteps,
+    /// Invalid share distribution: {0}
+    InvalidShareDistribution(#[from] ShareDistributionError),
 }
 
 impl From<DeserializeError> for Error {
@@ -341,6 +422,48 @@ mod test {
         ));
     }
 
+    #[test]
+    fn share_distribution_validation() {
+        type Env = crate::env::Svr3Env<'static>;
+        assert_eq!(
+            ShareDistribution::new::<Env>([3, 1], 2)
+                .expect("valid")
+                .server_ids(),
+            &[3, 1]
+        );
+        assert_eq!(ShareDistribution::all::<Env>().threshold(), 3);
+        assert_eq!(
+            ShareDistribution::new::<Env>([], 0),
+            Err(ShareDistributionError::NoServers)
+        );
+        assert_eq!(
+            ShareDistribution::new::<Env>([1, 4], 2),
+            Err(ShareDistributionError::UnknownServer(4))
+        );
+        assert_eq!(
+            ShareDistribution::new::<Env>([2, 2], 2),
+            Err(ShareDistributionError::DuplicateServer(2))
+        );
+        assert_eq!(
+            ShareDistribution::new::<Env>([1, 2, 3], 2),
+            Err(ShareDistributionError::UnsupportedThreshold {
+                threshold: 2,
+                servers: 3
+            })
+        );
+    }
+
+    #[test]
+    fn share_set_layout() {
+        let share_set = OpaqueMaskedShareSet::new(MaskedSecret {
+            server_ids: vec![2, 3],
+            masked_secret: vec![],
+        });
+        let layout = share_set.layout();
+        assert_eq!(layout.server_ids(), &[2, 3]);
+        assert_eq!(layout.threshold(), 2);
+    }
+
     struct TestSvr3Client {
         backup_fn: fn() -> Result<OpaqueMaskedShareSet, Error>,
         restore_fn: fn() -> Result<EvaluationResult, Error>,
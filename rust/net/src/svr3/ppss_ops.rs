@@ -21,11 +21,12 @@ use libsignal_svr3::{
 };
 use rand_core::CryptoRngCore;
 
-use super::{Error, OpaqueMaskedShareSet};
+use super::{Error, OpaqueMaskedShareSet, ShareDistribution, ShareDistributionError};
 use crate::enclave::{ArrayIsh, IntoConnectionResults, PpssSetup};
 
 pub async fn do_backup<Env: PpssSetup>(
     connect_results: Env::ConnectionResults,
+    distribution: &ShareDistribution,
     password: &str,
     secret: [u8; 32],
     max_tries: NonZeroU32,
@@ -35,14 +36,17 @@ pub async fn do_backup<Env: PpssSetup>(
         mut connections,
         addresses,
         errors,
-    } = ConnectionContext::new(connect_results);
+    } = ConnectionContext::for_servers(
+        connect_results,
+        Env::server_ids().as_ref(),
+        distribution.server_ids(),
+    )?;
     if let Some(err) = errors.into_iter().next() {
         return Err(err);
     }
 
-    let server_ids = Env::server_ids();
     let backup = Backup4::new(
-        server_ids.as_ref(),
+        distribution.server_ids(),
         password.as_bytes(),
         &secret,
         max_tries,
@@ -60,23 +64,27 @@ pub async fn do_backup<Env: PpssSetup>(
     Ok(OpaqueMaskedShareSet::new(backup.masked_secret))
 }
 
-pub async fn do_restore(
+pub async fn do_restore<Env: PpssSetup>(
     connect_results: impl IntoConnectionResults,
     password: &str,
     share_set: OpaqueMaskedShareSet,
     rng: &mut (impl CryptoRngCore + Send),
 ) -> Result<EvaluationResult, Error> {
+    let masked_secret: MaskedSecret = share_set.into_inner();
+
     let ConnectionContext {
         mut connections,
         addresses,
         errors,
-    } = ConnectionContext::new(connect_results);
+    } = ConnectionContext::for_servers(
+        connect_results,
+        Env::server_ids().as_ref(),
+        &masked_secret.server_ids,
+    )?;
     if let Some(err) = errors.into_iter().next() {
         return Err(err);
     }
 
-    let masked_secret: MaskedSecret = share_set.into_inner();
-
     let restore1 = Restore1::new(masked_secret.server_ids.as_ref(), password.as_bytes(), rng);
     let responses1 = {
         let futures = connections
@@ -139,12 +147,19 @@ pub async fn do_remove(connect_results: impl IntoConnectionResults) -> Result<()
     Ok(())
 }
 
-pub async fn do_query(connect_results: impl IntoConnectionResults) -> Result<u32, Error> {
+pub async fn do_query<Env: PpssSetup>(
+    connect_results: impl IntoConnectionResults,
+    distribution: &ShareDistribution,
+) -> Result<u32, Error> {
     let ConnectionContext {
         mut connections,
         addresses,
         errors,
-    } = ConnectionContext::new(connect_results);
+    } = ConnectionContext::for_servers(
+        connect_results,
+        Env::server_ids().as_ref(),
+        distribution.server_ids(),
+    )?;
     if let Some(err) = errors.into_iter().next() {
         return Err(err);
     }
@@ -161,20 +176,24 @@ pub async fn do_query(connect_results: impl IntoConnectionResults) -> Result<u32
     Ok(Query4::finalize(&responses)?)
 }
 
-pub async fn do_rotate(
+pub async fn do_rotate<Env: PpssSetup>(
     connect_results: impl IntoConnectionResults,
     share_set: OpaqueMaskedShareSet,
     rng: &mut (impl CryptoRngCore + Send),
 ) -> Result<(), Error> {
+    let masked_secret: MaskedSecret = share_set.into_inner();
     let ConnectionContext {
         mut connections,
         addresses,
         errors,
-    } = ConnectionContext::new(connect_results);
+    } = ConnectionContext::for_servers(
+        connect_results,
+        Env::server_ids().as_ref(),
+        &masked_secret.server_ids,
+    )?;
     if let Some(err) = errors.into_iter().next() {
         return Err(err);
     }
-    let masked_secret: MaskedSecret = share_set.into_inner();
 
     let mut rotation_machine = RotationMachine::new(masked_secret.server_ids.as_ref(), rng);
 
@@ -227,6 +246,47 @@ impl ConnectionContext {
             errors,
         }
     }
+
+    /// Like [`ConnectionContext::new`], but keeps only the results for `server_ids`, in that
+    /// order.
+    ///
+    /// `all_server_ids` identifies each of the results in `connect_results`, as returned by
+    /// [`PpssSetup::server_ids`].
+    fn for_servers<Arr: IntoConnectionResults>(
+        connect_results: Arr,
+        all_server_ids: &[u64],
+        server_ids: &[u64],
+    ) -> Result<Self, Error> {
+        let mut results = connect_results
+            .into_connection_results()
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        let mut connections = Vec::with_capacity(server_ids.len());
+        let mut addresses = Vec::with_capacity(server_ids.len());
+        let mut errors = VecDeque::new();
+        for &id in server_ids {
+            let result = all_server_ids
+                .iter()
+                .position(|known| *known == id)
+                .and_then(|index| results.get_mut(index))
+                .ok_or(ShareDistributionError::UnknownServer(id))?
+                .take()
+                .ok_or(ShareDistributionError::DuplicateServer(id))?;
+            match result {
+                Ok((connection, remote_address)) => {
+                    addresses.push(remote_address);
+                    connections.push(connection);
+                }
+                Err(err) => errors.push_back(err.into()),
+            }
+        }
+        Ok(Self {
+            connections,
+            addresses,
+            errors,
+        })
+    }
 }
 
 fn collect_responses<'a>(
@@ -279,30 +339,87 @@ mod test {
     #[tokio::test]
     async fn do_backup_fails_with_the_first_error() {
         let mut rng = OsRng;
-        let result =
-            do_backup::<TestEnv>(NotConnectedResults, "", [0; 32], nonzero!(1u32), &mut rng).await;
+        let result = do_backup::<TestEnv>(
+            NotConnectedResults,
+            &ShareDistribution::all::<TestEnv>(),
+            "",
+            [0; 32],
+            nonzero!(1u32),
+            &mut rng,
+        )
+        .await;
         assert_matches!(result, Err(crate::svr3::Error::ConnectionTimedOut));
     }
 
     #[tokio::test]
-    async fn do_restore_fails_with_the_first_error() {
+    async fn do_backup_only_uses_selected_servers() {
         let mut rng = OsRng;
-        let result = do_restore(
+        let distribution = ShareDistribution::new::<TestEnv>([2], 1).expect("valid");
+        let result = do_backup::<TestEnv>(
             NotConnectedResults,
+            &distribution,
             "",
-            OpaqueMaskedShareSet::default(),
+            [0; 32],
+            nonzero!(1u32),
             &mut rng,
         )
         .await;
+        assert_matches!(result, Err(crate::svr3::Error::AttestationError(_)));
+    }
+
+    fn share_set_for(server_ids: Vec<u64>) -> OpaqueMaskedShareSet {
+        OpaqueMaskedShareSet::new(MaskedSecret {
+            server_ids,
+            masked_secret: vec![],
+        })
+    }
+
+    #[tokio::test]
+    async fn do_restore_fails_with_the_first_error() {
+        let mut rng = OsRng;
+        let result =
+            do_restore::<TestEnv>(NotConnectedResults, "", share_set_for(vec![1, 2]), &mut rng)
+                .await;
         assert_matches!(result, Err(crate::svr3::Error::ConnectionTimedOut));
     }
 
+    #[tokio::test]
+    async fn do_restore_follows_share_set_layout() {
+        let mut rng = OsRng;
+        let result =
+            do_restore::<TestEnv>(NotConnectedResults, "", share_set_for(vec![2, 1]), &mut rng)
+                .await;
+        assert_matches!(result, Err(crate::svr3::Error::AttestationError(_)));
+    }
+
+    #[tokio::test]
+    async fn do_restore_rejects_unknown_servers() {
+        let mut rng = OsRng;
+        let result =
+            do_restore::<TestEnv>(NotConnectedResults, "", share_set_for(vec![1, 3]), &mut rng)
+                .await;
+        assert_matches!(
+            result,
+            Err(crate::svr3::Error::InvalidShareDistribution(
+                ShareDistributionError::UnknownServer(3)
+            ))
+        );
+    }
+
     #[tokio::test]
     async fn do_query_fails_with_the_first_error() {
-        let result = do_query(NotConnectedResults).await;
+        let result =
+            do_query::<TestEnv>(NotConnectedResults, &ShareDistribution::all::<TestEnv>()).await;
         assert_matches!(result, Err(crate::svr3::Error::ConnectionTimedOut));
     }
 
+    #[tokio::test]
+    async fn do_query_only_uses_selected_servers() {
+        let distribution = ShareDistribution::new::<TestEnv>([2, 1], 2).expect("valid");
+        let result = do_query::<TestEnv>(NotConnectedResults, &distribution).await;
+        assert_matches!(result, Err(crate::svr3::Error::AttestationError(_)));
+    }
+
     #[tokio::test]
     async fn do_remove_does_not_fail_on_bad_connections() {
         do_remove(NotConnectedResults)
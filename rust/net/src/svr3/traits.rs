@@ -13,7 +13,7 @@ use async_trait::async_trait;
 use libsignal_svr3::EvaluationResult;
 use rand_core::CryptoRngCore;
 
-use super::{ppss_ops, Error, OpaqueMaskedShareSet};
+use super::{ppss_ops, Error, OpaqueMaskedShareSet, ShareDistribution};
 use crate::enclave::PpssSetup;
 
 #[async_trait]
@@ -27,6 +27,19 @@ pub trait Backup {
     ) -> Result<OpaqueMaskedShareSet, Error>;
 }
 
+/// Like [`Backup`], but only sharing the secret among some of the environment's backends.
+#[async_trait]
+pub trait BackupWithDistribution {
+    async fn backup_with_distribution(
+        &self,
+        password: &str,
+        secret: [u8; 32],
+        max_tries: NonZeroU32,
+        distribution: &ShareDistribution,
+        rng: &mut (impl CryptoRngCore + Send),
+    ) -> Result<OpaqueMaskedShareSet, Error>;
+}
+
 #[async_trait]
 pub trait Restore {
     async fn restore(
@@ -42,6 +55,14 @@ pub trait Query {
     async fn query(&self) -> Result<u32, Error>;
 }
 
+/// Like [`Query`], but only asking the backends a secret was shared among, as given by
+/// [`OpaqueMaskedShareSet::layout`].
+#[async_trait]
+pub trait QueryWithDistribution {
+    async fn query_with_distribution(&self, distribution: &ShareDistribution)
+        -> Result<u32, Error>;
+}
+
 #[async_trait]
 pub trait Remove {
     async fn remove(&self) -> Result<(), Error>;
@@ -74,7 +95,41 @@ where
         max_tries: NonZeroU32,
         rng: &mut (impl CryptoRngCore + Send),
     ) -> Result<OpaqueMaskedShareSet, Error> {
-        ppss_ops::do_backup::<T::Env>(self.connect().await, password, secret, max_tries, rng).await
+        let distribution = ShareDistribution::all::<T::Env>();
+        ppss_ops::do_backup::<T::Env>(
+            self.connect().await,
+            &distribution,
+            password,
+            secret,
+            max_tries,
+            rng,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<T> BackupWithDistribution for T
+where
+    T: Svr3Connect + Sync,
+{
+    async fn backup_with_distribution(
+        &self,
+        password: &str,
+        secret: [u8; 32],
+        max_tries: NonZeroU32,
+        distribution: &ShareDistribution,
+        rng: &mut (impl CryptoRngCore + Send),
+    ) -> Result<OpaqueMaskedShareSet, Error> {
+        ppss_ops::do_backup::<T::Env>(
+            self.connect().await,
+            distribution,
+            password,
+            secret,
+            max_tries,
+            rng,
+        )
+        .await
     }
 }
 
@@ -89,7 +144,7 @@ where
         share_set: OpaqueMaskedShareSet,
         rng: &mut (impl CryptoRngCore + Send),
     ) -> Result<EvaluationResult, Error> {
-        ppss_ops::do_restore(self.connect().await, password, share_set, rng).await
+        ppss_ops::do_restore::<T::Env>(self.connect().await, password, share_set, rng).await
     }
 }
 
@@ -109,7 +164,21 @@ where
     T: Svr3Connect + Sync,
 {
     async fn query(&self) -> Result<u32, Error> {
-        ppss_ops::do_query(self.connect().await).await
+        let distribution = ShareDistribution::all::<T::Env>();
+        ppss_ops::do_query::<T::Env>(self.connect().await, &distribution).await
+    }
+}
+
+#[async_trait]
+impl<T> QueryWithDistribution for T
+where
+    T: Svr3Connect + Sync,
+{
+    async fn query_with_distribution(
+        &self,
+        distribution: &ShareDistribution,
+    ) -> Result<u32, Error> {
+        ppss_ops::do_query::<T::Env>(self.connect().await, distribution).await
     }
 }
 
@@ -123,6 +192,6 @@ where
         share_set: OpaqueMaskedShareSet,
         rng: &mut (impl CryptoRngCore + Send),
     ) -> Result<(), Error> {
-        ppss_ops::do_rotate(self.connect().await, share_set, rng).await
+        ppss_ops::do_rotate::<T::Env>(self.connect().await, share_set, rng).await
     }
 }
@@ -141,7 +141,9 @@ impl FakeDeps {
         let endpoint_connection = libsignal_net::chat::endpoint_connection(
             &chat_domain_config.connect,
             "libsignal test",
-            true,
+            &libsignal_net::env::DEFAULT_ROUTE_ORDER,
+            Default::default(),
+            Default::default(),
             &ObservableEvent::new(),
         );
 
@@ -16,7 +16,7 @@ use libsignal_net::auth::Auth;
 use libsignal_net::enclave::PpssSetup;
 use libsignal_net::env::Svr3Env;
 use libsignal_net::svr3::traits::*;
-use libsignal_net::svr3::{Error, OpaqueMaskedShareSet};
+use libsignal_net::svr3::{Error, OpaqueMaskedShareSet, ShareDistribution};
 use nonzero_ext::nonzero;
 use rand_core::{CryptoRngCore, OsRng, RngCore};
 
@@ -131,6 +131,75 @@ async fn svr3_integration() {
     println!("{}.", "Done".green());
 }
 
+#[tokio::test]
+async fn svr3_subset_integration() {
+    init_logger();
+
+    let Some(enclave_secret) = get_enclave_secret() else {
+        println!(
+            "LIBSIGNAL_TESTING_ENCLAVE_SECRET environment variable is not set. The test will be ignored."
+        );
+        return;
+    };
+
+    let mut rng = OsRng;
+
+    for server_ids in [vec![3, 1], vec![2, 3]] {
+        let uid = {
+            let mut bytes = [0u8; 16];
+            rng.fill_bytes(&mut bytes[..]);
+            bytes
+        };
+
+        let client = {
+            let env = libsignal_net::env::PROD.svr3;
+            let auth = Auth::from_uid_and_secret(uid, enclave_secret);
+            Svr3Client { env, auth }
+        };
+        let threshold = server_ids.len();
+        let distribution =
+            ShareDistribution::new::<Svr3Env>(server_ids, threshold).expect("valid distribution");
+        println!("{}: {:?}", "Distribution".cyan(), distribution);
+
+        let secret = make_secret(&mut rng);
+        let tries = nonzero!(10u32);
+        let share_set_bytes = client
+            .backup_with_distribution(PASSWORD, secret, tries, &distribution, &mut rng)
+            .await
+            .expect("can backup")
+            .serialize()
+            .expect("can serialize");
+        let share_set =
+            OpaqueMaskedShareSet::deserialize(&share_set_bytes).expect("can deserialize");
+        assert_eq!(distribution, share_set.layout());
+
+        let query_result = client
+            .query_with_distribution(&share_set.layout())
+            .await
+            .expect("can query");
+        assert_eq!(tries.get(), query_result);
+
+        let restored = client
+            .restore(PASSWORD, share_set.clone(), &mut rng)
+            .await
+            .expect("can restore");
+        assert_eq!(secret, restored.value);
+
+        client
+            .rotate(share_set.clone(), &mut rng)
+            .await
+            .expect("can rotate");
+        let restored = client
+            .restore(PASSWORD, share_set, &mut rng)
+            .await
+            .expect("can restore after rotation");
+        assert_eq!(secret, restored.value);
+
+        client.remove().await.expect("can remove");
+    }
+    println!("{}.", "Done".green());
+}
+
 fn make_secret(rng: &mut impl CryptoRngCore) -> [u8; 32] {
     let mut bytes = [0u8; 32];
     rng.fill_bytes(&mut bytes[..]);
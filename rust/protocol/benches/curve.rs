@@ -4,7 +4,7 @@
 //
 
 use criterion::{criterion_group, criterion_main, Criterion};
-use libsignal_protocol::KeyPair;
+use libsignal_protocol::{KeyPair, PrecomputedPublicKey};
 use rand::{thread_rng, Rng};
 
 pub fn generation(c: &mut Criterion) {
@@ -20,6 +20,19 @@ pub fn key_agreement(c: &mut Criterion) {
     c.bench_function("key agreement", |b| {
         b.iter(|| alice_key.calculate_agreement(&bob_key.public_key).unwrap())
     });
+
+    let bob_precomputed = PrecomputedPublicKey::new(bob_key.public_key);
+    c.bench_function("key agreement (precomputed)", |b| {
+        b.iter(|| {
+            alice_key
+                .calculate_agreement_precomputed(&bob_precomputed)
+                .unwrap()
+        })
+    });
+
+    c.bench_function("precomputation", |b| {
+        b.iter(|| PrecomputedPublicKey::new(bob_key.public_key))
+    });
 }
 
 pub fn signatures(c: &mut Criterion) {
@@ -0,0 +1,27 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use libsignal_protocol::*;
+
+fuzz_target!(|input: (SenderKeyDistributionMessage, &[u8])| {
+    let (message, data) = input;
+
+    let _: Result<_, _> = SenderKeyDistributionMessage::try_from(data);
+    let _: Result<_, _> = SenderKeyMessage::try_from(data);
+
+    let parsed =
+        SenderKeyDistributionMessage::try_from(message.serialized()).expect("well-formed message");
+    assert_eq!(
+        parsed.distribution_id().ok(),
+        message.distribution_id().ok()
+    );
+    assert_eq!(parsed.chain_id().ok(), message.chain_id().ok());
+    assert_eq!(parsed.iteration().ok(), message.iteration().ok());
+    assert_eq!(parsed.chain_key().ok(), message.chain_key().ok());
+    assert_eq!(parsed.signing_key().ok(), message.signing_key().ok());
+});
@@ -0,0 +1,23 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use libsignal_protocol::*;
+
+fuzz_target!(|input: (SignalMessage, &[u8])| {
+    let (message, data) = input;
+
+    let _: Result<_, _> = SignalMessage::try_from(data);
+    let _: Result<_, _> = PreKeySignalMessage::try_from(data);
+
+    let parsed = SignalMessage::try_from(message.serialized()).expect("well-formed message");
+    assert_eq!(parsed.message_version(), message.message_version());
+    assert_eq!(parsed.sender_ratchet_key(), message.sender_ratchet_key());
+    assert_eq!(parsed.counter(), message.counter());
+    assert_eq!(parsed.previous_counter(), message.previous_counter());
+    assert_eq!(parsed.body(), message.body());
+});
@@ -0,0 +1,1254 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Checks that a store implementation behaves the way the rest of this crate expects.
+//!
+//! Each `check_*` function exercises a fresh store produced by `new_store`, playing the other side
+//! of any conversation with the [in-memory stores](crate::InMemSignalProtocolStore). They panic
+//! with a description of the first mismatch they find, so they can be called directly from an
+//! app's own tests:
+//!
+//! ```ignore
+//! #[test]
+//! fn sqlite_store_conforms() {
+//!     futures::executor::block_on(libsignal_protocol::conformance::check_all(
+//!         |identity, registration_id| SqliteStore::open_in_memory(identity, registration_id),
+//!     ));
+//! }
+//! ```
+//!
+//! Stores that already exist, including ones implemented by an app on the other side of the bridge,
+//! can be checked one [`Check`] at a time instead, combined with [`SeparateStores`] if necessary.
+//!
+//! Only the behavior the library relies on is checked. In particular, a store is free to apply
+//! its own trust policy in [`IdentityKeyStore::is_trusted_identity`], and the checks only verify
+//! that the library's reaction to that decision is persisted correctly.
+
+use std::cell::RefCell;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use rand::rngs::OsRng;
+use rand::{CryptoRng, Rng};
+use uuid::Uuid;
+
+use crate::{
+    create_sender_key_distribution_message, group_decrypt, group_encrypt, kem, message_decrypt,
+    message_encrypt, process_prekey_bundle, process_sender_key_distribution_message,
+    CiphertextMessage, DeviceId, Direction, GenericSignedPreKey, IdentityKey, IdentityKeyPair,
+    IdentityKeyStore, InMemSignalProtocolStore, KeyPair, KyberPreKeyId, KyberPreKeyRecord,
+    KyberPreKeyStore, PreKeyBundle, PreKeyId, PreKeyRecord, PreKeyStore, ProtocolAddress,
+    ProtocolStore, SenderKeyDistributionRecord, SenderKeyRecord, SenderKeyStore, SessionRecord,
+    SessionStore, SignalProtocolError, SignedPreKeyId, SignedPreKeyRecord, SignedPreKeyStore,
+    Timestamp,
+};
+
+/// All the stores a client needs, as implemented by the store under test.
+pub trait ConformanceStore: ProtocolStore + SenderKeyStore {}
+
+impl<S: ProtocolStore + SenderKeyStore> ConformanceStore for S {}
+
+/// Runs every check in this module, each against its own fresh store.
+pub async fn check_all<S: ConformanceStore>(mut new_store: impl FnMut(IdentityKeyPair, u32) -> S) {
+    for check in Check::ALL {
+        check.run_fresh(&mut new_store).await;
+    }
+}
+
+/// Checks the local identity and the bookkeeping of remote identities.
+pub async fn check_identity_store<S: ConformanceStore>(
+    new_store: impl FnMut(IdentityKeyPair, u32) -> S,
+) {
+    Check::IdentityStore.run_fresh(new_store).await
+}
+
+/// Checks saving, loading, and removing one-time pre-keys.
+pub async fn check_pre_key_store<S: ConformanceStore>(
+    new_store: impl FnMut(IdentityKeyPair, u32) -> S,
+) {
+    Check::PreKeyStore.run_fresh(new_store).await
+}
+
+/// Checks saving, loading, and replacing signed pre-keys.
+pub async fn check_signed_pre_key_store<S: ConformanceStore>(
+    new_store: impl FnMut(IdentityKeyPair, u32) -> S,
+) {
+    Check::SignedPreKeyStore.run_fresh(new_store).await
+}
+
+/// Checks saving and loading Kyber pre-keys, and marking them used.
+pub async fn check_kyber_pre_key_store<S: ConformanceStore>(
+    new_store: impl FnMut(IdentityKeyPair, u32) -> S,
+) {
+    Check::KyberPreKeyStore.run_fresh(new_store).await
+}
+
+/// Checks storing and loading sessions, individually and per device.
+pub async fn check_session_store<S: ConformanceStore>(
+    new_store: impl FnMut(IdentityKeyPair, u32) -> S,
+) {
+    Check::SessionStore.run_fresh(new_store).await
+}
+
+/// Checks that sender keys are stored per sender and per distribution ID.
+pub async fn check_sender_key_store<S: ConformanceStore>(
+    new_store: impl FnMut(IdentityKeyPair, u32) -> S,
+) {
+    Check::SenderKeyStore.run_fresh(new_store).await
+}
+
+/// Checks that a one-time pre-key can only be used to start one session.
+pub async fn check_pre_key_reuse<S: ConformanceStore>(
+    new_store: impl FnMut(IdentityKeyPair, u32) -> S,
+) {
+    Check::PreKeyReuse.run_fresh(new_store).await
+}
+
+/// Checks what's persisted when a peer's identity changes between two sessions.
+///
+/// If the store doesn't trust the new identity, decryption must fail without changing the stored
+/// session or identity. If it does, the new identity must be saved along with the new session.
+pub async fn check_identity_change_mid_decrypt<S: ConformanceStore>(
+    new_store: impl FnMut(IdentityKeyPair, u32) -> S,
+) {
+    Check::IdentityChangeMidDecrypt.run_fresh(new_store).await
+}
+
+/// Checks interleaved and out-of-order group messages from several senders and distributions.
+pub async fn check_concurrent_sender_keys<S: ConformanceStore>(
+    new_store: impl FnMut(IdentityKeyPair, u32) -> S,
+) {
+    Check::ConcurrentSenderKeys.run_fresh(new_store).await
+}
+
+/// One of the checks in this module.
+///
+/// This is for running the checks against stores that were already created, for example by an app
+/// on the other side of the bridge. Each check still expects a store of its own with nothing in it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Check {
+    /// See [`check_identity_store`].
+    IdentityStore,
+    /// See [`check_pre_key_store`].
+    PreKeyStore,
+    /// See [`check_signed_pre_key_store`].
+    SignedPreKeyStore,
+    /// See [`check_kyber_pre_key_store`].
+    KyberPreKeyStore,
+    /// See [`check_session_store`].
+    SessionStore,
+    /// See [`check_sender_key_store`].
+    SenderKeyStore,
+    /// See [`check_pre_key_reuse`].
+    PreKeyReuse,
+    /// See [`check_identity_change_mid_decrypt`].
+    IdentityChangeMidDecrypt,
+    /// See [`check_concurrent_sender_keys`].
+    ConcurrentSenderKeys,
+}
+
+impl Check {
+    pub const ALL: [Self; 9] = [
+        Self::IdentityStore,
+        Self::PreKeyStore,
+        Self::SignedPreKeyStore,
+        Self::KyberPreKeyStore,
+        Self::SessionStore,
+        Self::SenderKeyStore,
+        Self::PreKeyReuse,
+        Self::IdentityChangeMidDecrypt,
+        Self::ConcurrentSenderKeys,
+    ];
+
+    /// Runs this check against `store`, which must be empty and have been created with `identity`
+    /// and `registration_id`.
+    ///
+    /// Panics with a description of the first mismatch found.
+    pub async fn run<S: ConformanceStore>(
+        self,
+        store: S,
+        identity: &IdentityKeyPair,
+        registration_id: u32,
+    ) {
+        match self {
+            Self::IdentityStore => identity_store(store, identity, registration_id).await,
+            Self::PreKeyStore => pre_key_store(store, identity, registration_id).await,
+            Self::SignedPreKeyStore => signed_pre_key_store(store, identity, registration_id).await,
+            Self::KyberPreKeyStore => kyber_pre_key_store(store, identity, registration_id).await,
+            Self::SessionStore => session_store(store, identity, registration_id).await,
+            Self::SenderKeyStore => sender_key_store(store, identity, registration_id).await,
+            Self::PreKeyReuse => pre_key_reuse(store, identity, registration_id).await,
+            Self::IdentityChangeMidDecrypt => {
+                identity_change_mid_decrypt(store, identity, registration_id).await
+            }
+            Self::ConcurrentSenderKeys => {
+                concurrent_sender_keys(store, identity, registration_id).await
+            }
+        }
+    }
+
+    async fn run_fresh<S: ConformanceStore>(
+        self,
+        mut new_store: impl FnMut(IdentityKeyPair, u32) -> S,
+    ) {
+        let (store, identity, registration_id) = fresh_store(&mut new_store, &mut OsRng);
+        self.run(store, &identity, registration_id).await
+    }
+}
+
+async fn identity_store<S: ConformanceStore>(
+    mut store: S,
+    identity: &IdentityKeyPair,
+    registration_id: u32,
+) {
+    let mut rng = OsRng;
+
+    let stored_identity = store
+        .get_identity_key_pair()
+        .await
+        .expect("can load local identity");
+    assert_eq!(
+        stored_identity.serialize(),
+        identity.serialize(),
+        "local identity must match the one the store was created with"
+    );
+    assert_eq!(
+        store
+            .get_local_registration_id()
+            .await
+            .expect("can load registration ID"),
+        registration_id,
+        "local registration ID must match the one the store was created with"
+    );
+
+    let address = remote_address("identity", 1);
+    let other_address = remote_address("other identity", 1);
+    assert_eq!(
+        store.get_identity(&address).await.expect("can look up"),
+        None,
+        "unknown addresses must not have an identity"
+    );
+
+    let first = random_identity(&mut rng);
+    assert!(
+        !store
+            .save_identity(&address, &first)
+            .await
+            .expect("can save"),
+        "saving a new identity must not report a replacement"
+    );
+    assert_eq!(
+        store.get_identity(&address).await.expect("can look up"),
+        Some(first),
+        "a saved identity must be returned"
+    );
+    assert!(
+        !store
+            .save_identity(&address, &first)
+            .await
+            .expect("can save"),
+        "saving an unchanged identity must not report a replacement"
+    );
+    let second = random_identity(&mut rng);
+    assert!(
+        store
+            .save_identity(&address, &second)
+            .await
+            .expect("can save"),
+        "saving a different identity must report a replacement"
+    );
+    assert_eq!(
+        store.get_identity(&address).await.expect("can look up"),
+        Some(second),
+        "a replaced identity must be returned"
+    );
+    assert_eq!(
+        store
+            .get_identity(&other_address)
+            .await
+            .expect("can look up"),
+        None,
+        "identities must be stored per address"
+    );
+}
+
+async fn pre_key_store<S: ConformanceStore>(
+    mut store: S,
+    _identity: &IdentityKeyPair,
+    _registration_id: u32,
+) {
+    let mut rng = OsRng;
+
+    let id_a = PreKeyId::from(rng.gen::<u32>());
+    let id_b = PreKeyId::from(u32::from(id_a).wrapping_add(1));
+    assert!(
+        store.get_pre_key(id_a).await.is_err(),
+        "loading a missing pre-key must fail"
+    );
+
+    let record_a = PreKeyRecord::new(id_a, &KeyPair::generate(&mut rng));
+    let record_b = PreKeyRecord::new(id_b, &KeyPair::generate(&mut rng));
+    store.save_pre_key(id_a, &record_a).await.expect("can save");
+    store.save_pre_key(id_b, &record_b).await.expect("can save");
+    assert_eq!(
+        serialized(store.get_pre_key(id_a).await.expect("can load").serialize()),
+        serialized(record_a.serialize()),
+        "a saved pre-key must round-trip"
+    );
+
+    store.remove_pre_key(id_a).await.expect("can remove");
+    assert!(
+        store.get_pre_key(id_a).await.is_err(),
+        "loading a removed pre-key must fail"
+    );
+    assert_eq!(
+        serialized(store.get_pre_key(id_b).await.expect("can load").serialize()),
+        serialized(record_b.serialize()),
+        "removing one pre-key must not affect others"
+    );
+}
+
+async fn signed_pre_key_store<S: ConformanceStore>(
+    mut store: S,
+    identity: &IdentityKeyPair,
+    _registration_id: u32,
+) {
+    let mut rng = OsRng;
+
+    let id = SignedPreKeyId::from(rng.gen::<u32>());
+    assert!(
+        store.get_signed_pre_key(id).await.is_err(),
+        "loading a missing signed pre-key must fail"
+    );
+
+    for _ in 0..2 {
+        let record = signed_pre_key_record(id, identity, &mut rng);
+        store
+            .save_signed_pre_key(id, &record)
+            .await
+            .expect("can save");
+        assert_eq!(
+            serialized(
+                store
+                    .get_signed_pre_key(id)
+                    .await
+                    .expect("can load")
+                    .serialize()
+            ),
+            serialized(record.serialize()),
+            "the most recently saved signed pre-key must be returned"
+        );
+    }
+}
+
+async fn kyber_pre_key_store<S: ConformanceStore>(
+    mut store: S,
+    identity: &IdentityKeyPair,
+    _registration_id: u32,
+) {
+    let mut rng = OsRng;
+
+    let id = KyberPreKeyId::from(rng.gen::<u32>());
+    assert!(
+        store.get_kyber_pre_key(id).await.is_err(),
+        "loading a missing Kyber pre-key must fail"
+    );
+
+    let record = KyberPreKeyRecord::generate(kem::KeyType::Kyber1024, id, identity.private_key())
+        .expect("can generate");
+    store
+        .save_kyber_pre_key(id, &record)
+        .await
+        .expect("can save");
+    assert_eq!(
+        serialized(
+            store
+                .get_kyber_pre_key(id)
+                .await
+                .expect("can load")
+                .serialize()
+        ),
+        serialized(record.serialize()),
+        "a saved Kyber pre-key must round-trip"
+    );
+    // Whether a used key stays loadable depends on whether it's a last-resort key, which only the
+    // app knows, so only check that marking succeeds.
+    store
+        .mark_kyber_pre_key_used(id)
+        .await
+        .expect("can mark Kyber pre-key used");
+}
+
+async fn session_store<S: ConformanceStore>(
+    mut store: S,
+    _identity: &IdentityKeyPair,
+    _registration_id: u32,
+) {
+    let mut rng = OsRng;
+
+    let name = "sessions";
+    let device_ids = [DeviceId::from(1), DeviceId::from(2), DeviceId::from(3)];
+    assert!(
+        store
+            .load_session(&ProtocolAddress::new(name.to_owned(), device_ids[0]))
+            .await
+            .expect("can load")
+            .is_none(),
+        "unknown addresses must not have a session"
+    );
+
+    let mut expected = Vec::new();
+    for device_id in [device_ids[0], device_ids[2]] {
+        let address = ProtocolAddress::new(name.to_owned(), device_id);
+        let mut remote = in_memory_store(&mut rng);
+        let bundle = create_pre_key_bundle(&mut remote, device_id, &mut rng).await;
+
+        let mut local = in_memory_store(&mut rng);
+        process_prekey_bundle(
+            &address,
+            &mut local.session_store,
+            &mut local.identity_store,
+            &bundle,
+            SystemTime::now(),
+            &mut rng,
+        )
+        .await
+        .expect("can process bundle");
+        let record = local
+            .load_session(&address)
+            .await
+            .expect("can load")
+            .expect("session was created");
+        store
+            .store_session(&address, &record)
+            .await
+            .expect("can store");
+        assert_eq!(
+            serialized(
+                store
+                    .load_session(&address)
+                    .await
+                    .expect("can load")
+                    .expect("session was stored")
+                    .serialize()
+            ),
+            serialized(record.serialize()),
+            "a stored session must round-trip"
+        );
+        expected.push((device_id, serialized(record.serialize())));
+    }
+
+    let loaded: Vec<_> = store
+        .load_sessions_for_devices(name, &device_ids)
+        .await
+        .expect("can load")
+        .into_iter()
+        .map(|(device_id, record)| (device_id, serialized(record.serialize())))
+        .collect();
+    assert_eq!(
+        loaded, expected,
+        "loading sessions for several devices must skip devices without one, in order"
+    );
+}
+
+async fn sender_key_store<S: ConformanceStore>(
+    mut store: S,
+    _identity: &IdentityKeyPair,
+    _registration_id: u32,
+) {
+    let mut rng = OsRng;
+
+    let sender = remote_address("sender keys", 1);
+    let other_sender = remote_address("other sender keys", 1);
+    let distribution_id = Uuid::from_bytes(rng.gen());
+    let other_distribution_id = Uuid::from_bytes(rng.gen());
+
+    assert!(
+        store
+            .load_sender_key(&sender, distribution_id)
+            .await
+            .expect("can load")
+            .is_none(),
+        "unknown sender keys must not be found"
+    );
+    create_sender_key_distribution_message(&sender, distribution_id, &mut store, &mut rng)
+        .await
+        .expect("can create sender key");
+    let record = store
+        .load_sender_key(&sender, distribution_id)
+        .await
+        .expect("can load")
+        .expect("sender key was stored");
+    assert!(
+        store
+            .load_sender_key(&sender, other_distribution_id)
+            .await
+            .expect("can load")
+            .is_none(),
+        "sender keys must be stored per distribution ID"
+    );
+    assert!(
+        store
+            .load_sender_key(&other_sender, distribution_id)
+            .await
+            .expect("can load")
+            .is_none(),
+        "sender keys must be stored per sender"
+    );
+
+    store
+        .store_sender_key(&other_sender, distribution_id, &record)
+        .await
+        .expect("can store");
+    assert_eq!(
+        serialized(
+            store
+                .load_sender_key(&other_sender, distribution_id)
+                .await
+                .expect("can load")
+                .expect("sender key was stored")
+                .serialize()
+        ),
+        serialized(record.serialize()),
+        "a stored sender key must round-trip"
+    );
+}
+
+async fn pre_key_reuse<S: ConformanceStore>(
+    mut store: S,
+    _identity: &IdentityKeyPair,
+    _registration_id: u32,
+) {
+    let mut rng = OsRng;
+    let local_address = remote_address("local", 1);
+
+    let bundle = create_pre_key_bundle(&mut store, DeviceId::from(1), &mut rng).await;
+    let pre_key_id = bundle
+        .pre_key_id()
+        .expect("valid")
+        .expect("has one-time pre-key");
+
+    let first_address = remote_address("first sender", 1);
+    let mut first_sender = in_memory_store(&mut rng);
+    let first_message = start_session(
+        &mut first_sender,
+        &local_address,
+        &bundle,
+        &mut rng,
+        b"first",
+    )
+    .await;
+    assert_eq!(
+        decrypt(&mut store, &first_address, &first_message, &mut rng)
+            .await
+            .expect("can decrypt first message"),
+        b"first",
+    );
+    assert!(
+        store.get_pre_key(pre_key_id).await.is_err(),
+        "a one-time pre-key must be removed once used"
+    );
+
+    decrypt(&mut store, &first_address, &first_message, &mut rng)
+        .await
+        .expect_err("a replayed pre-key message must be rejected");
+
+    let second_address = remote_address("second sender", 1);
+    let mut second_sender = in_memory_store(&mut rng);
+    let second_message = start_session(
+        &mut second_sender,
+        &local_address,
+        &bundle,
+        &mut rng,
+        b"second",
+    )
+    .await;
+    decrypt(&mut store, &second_address, &second_message, &mut rng)
+        .await
+        .expect_err("a used one-time pre-key must not start a second session");
+
+    // The first session must still work in both directions.
+    let reply = encrypt(&mut store, &first_address, b"reply").await;
+    assert_eq!(
+        decrypt(&mut first_sender, &local_address, &reply, &mut rng)
+            .await
+            .expect("can decrypt reply"),
+        b"reply",
+    );
+}
+
+async fn identity_change_mid_decrypt<S: ConformanceStore>(
+    mut store: S,
+    _identity: &IdentityKeyPair,
+    _registration_id: u32,
+) {
+    let mut rng = OsRng;
+    let local_address = remote_address("local", 1);
+    let peer_address = remote_address("changing peer", 1);
+
+    let bundle = create_pre_key_bundle(&mut store, DeviceId::from(1), &mut rng).await;
+    let mut peer = in_memory_store(&mut rng);
+    let message = start_session(&mut peer, &local_address, &bundle, &mut rng, b"before").await;
+    decrypt(&mut store, &peer_address, &message, &mut rng)
+        .await
+        .expect("can decrypt before identity change");
+    let old_identity = *peer
+        .get_identity_key_pair()
+        .await
+        .expect("has identity")
+        .identity_key();
+
+    // The peer reinstalls, getting a new identity, and starts over with a fresh bundle.
+    let bundle = create_pre_key_bundle(&mut store, DeviceId::from(1), &mut rng).await;
+    let mut reinstalled_peer = in_memory_store(&mut rng);
+    let new_identity = *reinstalled_peer
+        .get_identity_key_pair()
+        .await
+        .expect("has identity")
+        .identity_key();
+    let message = start_session(
+        &mut reinstalled_peer,
+        &local_address,
+        &bundle,
+        &mut rng,
+        b"after",
+    )
+    .await;
+
+    let trusted = store
+        .is_trusted_identity(&peer_address, &new_identity, Direction::Receiving)
+        .await
+        .expect("can check trust");
+    let session_before = serialized(
+        store
+            .load_session(&peer_address)
+            .await
+            .expect("can load")
+            .expect("has session")
+            .serialize(),
+    );
+
+    let result = decrypt(&mut store, &peer_address, &message, &mut rng).await;
+    let stored_identity = store
+        .get_identity(&peer_address)
+        .await
+        .expect("can look up");
+    let session_after = serialized(
+        store
+            .load_session(&peer_address)
+            .await
+            .expect("can load")
+            .expect("has session")
+            .serialize(),
+    );
+    if trusted {
+        assert_eq!(
+            result.expect("trusted identity change must decrypt"),
+            b"after"
+        );
+        assert_eq!(
+            stored_identity,
+            Some(new_identity),
+            "a trusted new identity must be saved"
+        );
+        assert_ne!(
+            session_after, session_before,
+            "the new session must be saved"
+        );
+    } else {
+        assert!(
+            matches!(result, Err(SignalProtocolError::UntrustedIdentity(_))),
+            "an untrusted identity change must be rejected, got {result:?}"
+        );
+        assert_eq!(
+            stored_identity,
+            Some(old_identity),
+            "rejecting a new identity must keep the old one"
+        );
+        assert_eq!(
+            session_after, session_before,
+            "rejecting a new identity must not modify the session"
+        );
+    }
+}
+
+async fn concurrent_sender_keys<S: ConformanceStore>(
+    mut store: S,
+    _identity: &IdentityKeyPair,
+    _registration_id: u32,
+) {
+    let mut rng = OsRng;
+
+    let mut chains = Vec::new();
+    for name in ["group sender a", "group sender b"] {
+        let sender = remote_address(name, 1);
+        let mut sender_store = in_memory_store(&mut rng);
+        for _ in 0..2 {
+            let distribution_id = Uuid::from_bytes(rng.gen());
+            let skdm = create_sender_key_distribution_message(
+                &sender,
+                distribution_id,
+                &mut sender_store,
+                &mut rng,
+            )
+            .await
+            .expect("can create distribution message");
+            process_sender_key_distribution_message(&sender, &skdm, &mut store)
+                .await
+                .expect("can process distribution message");
+            chains.push((sender.clone(), distribution_id));
+        }
+        // Alternate between the sender's distributions so their messages end up interleaved.
+        let mut messages = Vec::new();
+        for i in 0..3 {
+            for (chain_sender, distribution_id) in chains.iter().filter(|(s, _)| *s == sender) {
+                let plaintext = format!("{chain_sender} {distribution_id} {i}");
+                let message = group_encrypt(
+                    &mut sender_store,
+                    chain_sender,
+                    *distribution_id,
+                    plaintext.as_bytes(),
+                    &mut rng,
+                )
+                .await
+                .expect("can encrypt");
+                messages.push((chain_sender.clone(), plaintext, message));
+            }
+        }
+
+        // Deliver the messages interleaved across distributions and in reverse order within
+        // each, so the store has to keep several chains and skipped message keys at once.
+        messages.reverse();
+        for (chain_sender, plaintext, message) in messages {
+            assert_eq!(
+                group_decrypt(message.serialized(), &mut store, &chain_sender)
+                    .await
+                    .expect("can decrypt group message"),
+                plaintext.as_bytes(),
+            );
+        }
+    }
+
+    // The store's own sender keys must coexist with everyone else's.
+    let local = remote_address("local", 1);
+    let distribution_id = Uuid::from_bytes(rng.gen());
+    let skdm =
+        create_sender_key_distribution_message(&local, distribution_id, &mut store, &mut rng)
+            .await
+            .expect("can create distribution message");
+    let mut recipient = in_memory_store(&mut rng);
+    process_sender_key_distribution_message(&local, &skdm, &mut recipient)
+        .await
+        .expect("can process distribution message");
+    let message = group_encrypt(&mut store, &local, distribution_id, b"outgoing", &mut rng)
+        .await
+        .expect("can encrypt");
+    assert_eq!(
+        group_decrypt(message.serialized(), &mut recipient, &local)
+            .await
+            .expect("can decrypt own group message"),
+        b"outgoing",
+    );
+    for (sender, distribution_id) in chains {
+        assert!(
+            store
+                .load_sender_key(&sender, distribution_id)
+                .await
+                .expect("can load")
+                .is_some(),
+            "sending must not disturb received sender keys"
+        );
+    }
+}
+
+/// Stores implemented separately, combined into one [`ConformanceStore`].
+///
+/// This lets the checks run against stores that aren't all one type, such as the ones an app
+/// passes over the bridge.
+pub struct SeparateStores<'a> {
+    pub identity_store: &'a mut dyn IdentityKeyStore,
+    pub pre_key_store: &'a mut dyn PreKeyStore,
+    pub signed_pre_key_store: &'a mut dyn SignedPreKeyStore,
+    pub kyber_pre_key_store: &'a mut dyn KyberPreKeyStore,
+    pub session_store: &'a mut dyn SessionStore,
+    pub sender_key_store: &'a mut dyn SenderKeyStore,
+}
+
+impl ProtocolStore for SeparateStores<'_> {}
+
+fn fresh_store<S: ConformanceStore, R: Rng + CryptoRng>(
+    new_store: &mut impl FnMut(IdentityKeyPair, u32) -> S,
+    rng: &mut R,
+) -> (S, IdentityKeyPair, u32) {
+    let identity = IdentityKeyPair::generate(rng);
+    // Valid registration IDs fit in 14 bits.
+    let registration_id = rng.gen_range(1..0x4000);
+    (
+        new_store(identity, registration_id),
+        identity,
+        registration_id,
+    )
+}
+
+fn in_memory_store<R: Rng + CryptoRng>(rng: &mut R) -> InMemSignalProtocolStore {
+    InMemSignalProtocolStore::new(IdentityKeyPair::generate(rng), rng.gen_range(1..0x4000))
+        .expect("can create in-memory store")
+}
+
+fn random_identity<R: Rng + CryptoRng>(rng: &mut R) -> IdentityKey {
+    *IdentityKeyPair::generate(rng).identity_key()
+}
+
+fn remote_address(name: &str, device_id: u32) -> ProtocolAddress {
+    ProtocolAddress::new(name.to_owned(), device_id.into())
+}
+
+fn serialized(result: Result<Vec<u8>, SignalProtocolError>) -> Vec<u8> {
+    result.expect("can serialize")
+}
+
+fn signed_pre_key_record<R: Rng + CryptoRng>(
+    id: SignedPreKeyId,
+    identity: &IdentityKeyPair,
+    rng: &mut R,
+) -> SignedPreKeyRecord {
+    let key_pair = KeyPair::generate(rng);
+    let signature = identity
+        .private_key()
+        .calculate_signature(&key_pair.public_key.serialize(), rng)
+        .expect("can sign");
+    SignedPreKeyRecord::new(
+        id,
+        Timestamp::from_epoch_millis(rng.gen()),
+        &key_pair,
+        &signature,
+    )
+}
+
+/// Generates a full set of pre-keys, saves them in `store`, and returns the matching bundle.
+async fn create_pre_key_bundle<R: Rng + CryptoRng>(
+    store: &mut dyn ProtocolStore,
+    device_id: DeviceId,
+    rng: &mut R,
+) -> PreKeyBundle {
+    let identity = store.get_identity_key_pair().await.expect("has identity");
+    let registration_id = store
+        .get_local_registration_id()
+        .await
+        .expect("has registration ID");
+
+    let pre_key_id = PreKeyId::from(rng.gen::<u32>());
+    let pre_key = PreKeyRecord::new(pre_key_id, &KeyPair::generate(rng));
+    let signed_pre_key_id = SignedPreKeyId::from(rng.gen::<u32>());
+    let signed_pre_key = signed_pre_key_record(signed_pre_key_id, &identity, rng);
+    let kyber_pre_key_id = KyberPreKeyId::from(rng.gen::<u32>());
+    let kyber_pre_key = KyberPreKeyRecord::generate(
+        kem::KeyType::Kyber1024,
+        kyber_pre_key_id,
+        identity.private_key(),
+    )
+    .expect("can generate");
+
+    store
+        .save_pre_key(pre_key_id, &pre_key)
+        .await
+        .expect("can save pre-key");
+    store
+        .save_signed_pre_key(signed_pre_key_id, &signed_pre_key)
+        .await
+        .expect("can save signed pre-key");
+    store
+        .save_kyber_pre_key(kyber_pre_key_id, &kyber_pre_key)
+        .await
+        .expect("can save Kyber pre-key");
+
+    PreKeyBundle::new(
+        registration_id,
+        device_id,
+        Some((pre_key_id, pre_key.public_key().expect("valid"))),
+        signed_pre_key_id,
+        signed_pre_key.public_key().expect("valid"),
+        signed_pre_key.signature().expect("valid"),
+        *identity.identity_key(),
+    )
+    .expect("valid bundle")
+    .with_kyber_pre_key(
+        kyber_pre_key_id,
+        kyber_pre_key.public_key().expect("valid"),
+        kyber_pre_key.signature().expect("valid"),
+    )
+}
+
+/// Processes `bundle` in `sender` and returns the first message of the new session.
+async fn start_session<R: Rng + CryptoRng>(
+    sender: &mut InMemSignalProtocolStore,
+    recipient: &ProtocolAddress,
+    bundle: &PreKeyBundle,
+    rng: &mut R,
+    plaintext: &[u8],
+) -> CiphertextMessage {
+    process_prekey_bundle(
+        recipient,
+        &mut sender.session_store,
+        &mut sender.identity_store,
+        bundle,
+        SystemTime::now(),
+        rng,
+    )
+    .await
+    .expect("can process bundle");
+    encrypt(sender, recipient, plaintext).await
+}
+
+async fn encrypt<S: ProtocolStore>(
+    store: &mut S,
+    remote_address: &ProtocolAddress,
+    plaintext: &[u8],
+) -> CiphertextMessage {
+    let store = RefCell::new(store);
+    message_encrypt(
+        plaintext,
+        remote_address,
+        &mut Shared(&store),
+        &mut Shared(&store),
+        SystemTime::now(),
+    )
+    .await
+    .expect("can encrypt")
+}
+
+async fn decrypt<S: ProtocolStore, R: Rng + CryptoRng>(
+    store: &mut S,
+    remote_address: &ProtocolAddress,
+    message: &CiphertextMessage,
+    rng: &mut R,
+) -> Result<Vec<u8>, SignalProtocolError> {
+    let store = RefCell::new(store);
+    message_decrypt(
+        message,
+        remote_address,
+        &mut Shared(&store),
+        &mut Shared(&store),
+        &mut Shared(&store),
+        &Shared(&store),
+        &mut Shared(&store),
+        rng,
+    )
+    .await
+}
+
+/// Lets a single [`ProtocolStore`] be passed as each of the separate stores the session APIs take.
+///
+/// The session APIs never call into two stores at once, so the borrows never overlap.
+struct Shared<'a, 'b, S>(&'a RefCell<&'b mut S>);
+
+#[async_trait(?Send)]
+impl<S: ProtocolStore> IdentityKeyStore for Shared<'_, '_, S> {
+    async fn get_identity_key_pair(&self) -> Result<IdentityKeyPair, SignalProtocolError> {
+        self.0.borrow().get_identity_key_pair().await
+    }
+
+    async fn get_local_registration_id(&self) -> Result<u32, SignalProtocolError> {
+        self.0.borrow().get_local_registration_id().await
+    }
+
+    async fn save_identity(
+        &mut self,
+        address: &ProtocolAddress,
+        identity: &IdentityKey,
+    ) -> Result<bool, SignalProtocolError> {
+        self.0.borrow_mut().save_identity(address, identity).await
+    }
+
+    async fn is_trusted_identity(
+        &self,
+        address: &ProtocolAddress,
+        identity: &IdentityKey,
+        direction: Direction,
+    ) -> Result<bool, SignalProtocolError> {
+        self.0
+            .borrow()
+            .is_trusted_identity(address, identity, direction)
+            .await
+    }
+
+    async fn get_identity(
+        &self,
+        address: &ProtocolAddress,
+    ) -> Result<Option<IdentityKey>, SignalProtocolError> {
+        self.0.borrow().get_identity(address).await
+    }
+}
+
+#[async_trait(?Send)]
+impl<S: ProtocolStore> SessionStore for Shared<'_, '_, S> {
+    async fn load_session(
+        &self,
+        address: &ProtocolAddress,
+    ) -> Result<Option<SessionRecord>, SignalProtocolError> {
+        self.0.borrow().load_session(address).await
+    }
+
+    async fn store_session(
+        &mut self,
+        address: &ProtocolAddress,
+        record: &SessionRecord,
+    ) -> Result<(), SignalProtocolError> {
+        self.0.borrow_mut().store_session(address, record).await
+    }
+}
+
+#[async_trait(?Send)]
+impl<S: ProtocolStore> PreKeyStore for Shared<'_, '_, S> {
+    async fn get_pre_key(&self, prekey_id: PreKeyId) -> Result<PreKeyRecord, SignalProtocolError> {
+        self.0.borrow().get_pre_key(prekey_id).await
+    }
+
+    async fn save_pre_key(
+        &mut self,
+        prekey_id: PreKeyId,
+        record: &PreKeyRecord,
+    ) -> Result<(), SignalProtocolError> {
+        self.0.borrow_mut().save_pre_key(prekey_id, record).await
+    }
+
+    async fn remove_pre_key(&mut self, prekey_id: PreKeyId) -> Result<(), SignalProtocolError> {
+        self.0.borrow_mut().remove_pre_key(prekey_id).await
+    }
+}
+
+#[async_trait(?Send)]
+impl<S: ProtocolStore> SignedPreKeyStore for Shared<'_, '_, S> {
+    async fn get_signed_pre_key(
+        &self,
+        signed_prekey_id: SignedPreKeyId,
+    ) -> Result<SignedPreKeyRecord, SignalProtocolError> {
+        self.0.borrow().get_signed_pre_key(signed_prekey_id).await
+    }
+
+    async fn save_signed_pre_key(
+        &mut self,
+        signed_prekey_id: SignedPreKeyId,
+        record: &SignedPreKeyRecord,
+    ) -> Result<(), SignalProtocolError> {
+        self.0
+            .borrow_mut()
+            .save_signed_pre_key(signed_prekey_id, record)
+            .await
+    }
+}
+
+#[async_trait(?Send)]
+impl<S: ProtocolStore> KyberPreKeyStore for Shared<'_, '_, S> {
+    async fn get_kyber_pre_key(
+        &self,
+        kyber_prekey_id: KyberPreKeyId,
+    ) -> Result<KyberPreKeyRecord, SignalProtocolError> {
+        self.0.borrow().get_kyber_pre_key(kyber_prekey_id).await
+    }
+
+    async fn save_kyber_pre_key(
+        &mut self,
+        kyber_prekey_id: KyberPreKeyId,
+        record: &KyberPreKeyRecord,
+    ) -> Result<(), SignalProtocolError> {
+        self.0
+            .borrow_mut()
+            .save_kyber_pre_key(kyber_prekey_id, record)
+            .await
+    }
+
+    async fn mark_kyber_pre_key_used(
+        &mut self,
+        kyber_prekey_id: KyberPreKeyId,
+    ) -> Result<(), SignalProtocolError> {
+        self.0
+            .borrow_mut()
+            .mark_kyber_pre_key_used(kyber_prekey_id)
+            .await
+    }
+}
+
+#[async_trait(?Send)]
+impl IdentityKeyStore for SeparateStores<'_> {
+    async fn get_identity_key_pair(&self) -> Result<IdentityKeyPair, SignalProtocolError> {
+        self.identity_store.get_identity_key_pair().await
+    }
+
+    async fn get_local_registration_id(&self) -> Result<u32, SignalProtocolError> {
+        self.identity_store.get_local_registration_id().await
+    }
+
+    async fn save_identity(
+        &mut self,
+        address: &ProtocolAddress,
+        identity: &IdentityKey,
+    ) -> Result<bool, SignalProtocolError> {
+        self.identity_store.save_identity(address, identity).await
+    }
+
+    async fn is_trusted_identity(
+        &self,
+        address: &ProtocolAddress,
+        identity: &IdentityKey,
+        direction: Direction,
+    ) -> Result<bool, SignalProtocolError> {
+        self.identity_store
+            .is_trusted_identity(address, identity, direction)
+            .await
+    }
+
+    async fn get_identity(
+        &self,
+        address: &ProtocolAddress,
+    ) -> Result<Option<IdentityKey>, SignalProtocolError> {
+        self.identity_store.get_identity(address).await
+    }
+}
+
+#[async_trait(?Send)]
+impl SessionStore for SeparateStores<'_> {
+    async fn load_session(
+        &self,
+        address: &ProtocolAddress,
+    ) -> Result<Option<SessionRecord>, SignalProtocolError> {
+        self.session_store.load_session(address).await
+    }
+
+    async fn store_session(
+        &mut self,
+        address: &ProtocolAddress,
+        record: &SessionRecord,
+    ) -> Result<(), SignalProtocolError> {
+        self.session_store.store_session(address, record).await
+    }
+
+    async fn load_sessions_for_devices(
+        &self,
+        name: &str,
+        device_ids: &[DeviceId],
+    ) -> Result<Vec<(DeviceId, SessionRecord)>, SignalProtocolError> {
+        self.session_store
+            .load_sessions_for_devices(name, device_ids)
+            .await
+    }
+}
+
+#[async_trait(?Send)]
+impl PreKeyStore for SeparateStores<'_> {
+    async fn get_pre_key(&self, prekey_id: PreKeyId) -> Result<PreKeyRecord, SignalProtocolError> {
+        self.pre_key_store.get_pre_key(prekey_id).await
+    }
+
+    async fn save_pre_key(
+        &mut self,
+        prekey_id: PreKeyId,
+        record: &PreKeyRecord,
+    ) -> Result<(), SignalProtocolError> {
+        self.pre_key_store.save_pre_key(prekey_id, record).await
+    }
+
+    async fn remove_pre_key(&mut self, prekey_id: PreKeyId) -> Result<(), SignalProtocolError> {
+        self.pre_key_store.remove_pre_key(prekey_id).await
+    }
+}
+
+#[async_trait(?Send)]
+impl SignedPreKeyStore for SeparateStores<'_> {
+    async fn get_signed_pre_key(
+        &self,
+        signed_prekey_id: SignedPreKeyId,
+    ) -> Result<SignedPreKeyRecord, SignalProtocolError> {
+        self.signed_pre_key_store
+            .get_signed_pre_key(signed_prekey_id)
+            .await
+    }
+
+    async fn save_signed_pre_key(
+        &mut self,
+        signed_prekey_id: SignedPreKeyId,
+        record: &SignedPreKeyRecord,
+    ) -> Result<(), SignalProtocolError> {
+        self.signed_pre_key_store
+            .save_signed_pre_key(signed_prekey_id, record)
+            .await
+    }
+}
+
+#[async_trait(?Send)]
+impl KyberPreKeyStore for SeparateStores<'_> {
+    async fn get_kyber_pre_key(
+        &self,
+        kyber_prekey_id: KyberPreKeyId,
+    ) -> Result<KyberPreKeyRecord, SignalProtocolError> {
+        self.kyber_pre_key_store
+            .get_kyber_pre_key(kyber_prekey_id)
+            .await
+    }
+
+    async fn save_kyber_pre_key(
+        &mut self,
+        kyber_prekey_id: KyberPreKeyId,
+        record: &KyberPreKeyRecord,
+    ) -> Result<(), SignalProtocolError> {
+        self.kyber_pre_key_store
+            .save_kyber_pre_key(kyber_prekey_id, record)
+            .await
+    }
+
+    async fn mark_kyber_pre_key_used(
+        &mut self,
+        kyber_prekey_id: KyberPreKeyId,
+    ) -> Result<(), SignalProtocolError> {
+        self.kyber_pre_key_store
+            .mark_kyber_pre_key_used(kyber_prekey_id)
+            .await
+    }
+}
+
+#[async_trait(?Send)]
+impl SenderKeyStore for SeparateStores<'_> {
+    async fn store_sender_key(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+        record: &SenderKeyRecord,
+    ) -> Result<(), SignalProtocolError> {
+        self.sender_key_store
+            .store_sender_key(sender, distribution_id, record)
+            .await
+    }
+
+    async fn load_sender_key(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+    ) -> Result<Option<SenderKeyRecord>, SignalProtocolError> {
+        self.sender_key_store
+            .load_sender_key(sender, distribution_id)
+            .await
+    }
+
+    async fn store_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+        record: &SenderKeyDistributionRecord,
+    ) -> Result<(), SignalProtocolError> {
+        self.sender_key_store
+            .store_sender_key_distribution(sender, distribution_id, record)
+            .await
+    }
+
+    async fn load_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+    ) -> Result<Option<SenderKeyDistributionRecord>, SignalProtocolError> {
+        self.sender_key_store
+            .load_sender_key_distribution(sender, distribution_id)
+            .await
+    }
+}
@@ -4,7 +4,7 @@
 //
 
 use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
-use curve25519_dalek::edwards::EdwardsPoint;
+use curve25519_dalek::edwards::{EdwardsBasepointTable, EdwardsPoint};
 use curve25519_dalek::montgomery::MontgomeryPoint;
 use curve25519_dalek::scalar;
 use curve25519_dalek::scalar::Scalar;
@@ -47,6 +47,29 @@ impl PrivateKey {
             .as_bytes()
     }
 
+    /// Same as [`calculate_agreement`](Self::calculate_agreement), but using the tables in
+    /// `their_public_key` instead of a Montgomery ladder.
+    pub fn calculate_agreement_precomputed(
+        &self,
+        their_public_key: &PrecomputedPublicKey,
+    ) -> [u8; AGREEMENT_LENGTH] {
+        let Some(table) = &their_public_key.table else {
+            return self.calculate_agreement(&their_public_key.public_key);
+        };
+
+        // X25519 multiplies by the clamped scalar k, which is always a multiple of 8. The table
+        // holds multiples of 8P, which has prime order, so k * P = (k / 8) * 8P, where k / 8 can
+        // safely be reduced mod l. This matches the ladder even when P has a torsion component.
+        let clamped = scalar::clamp_integer(self.secret.to_bytes());
+        let mut k_over_8 = [0u8; 32];
+        for (i, byte) in k_over_8.iter_mut().enumerate() {
+            *byte = (clamped[i] >> 3) | clamped.get(i + 1).map_or(0, |next| next << 5);
+        }
+        let k_over_8 = Scalar::from_bytes_mod_order(k_over_8);
+
+        (&k_over_8 * table.as_ref()).to_montgomery().to_bytes()
+    }
+
     /// Calculates an XEdDSA signature using the X25519 private key directly.
     ///
     /// Refer to <https://signal.org/docs/specifications/xeddsa/#curve25519> for more details.
@@ -157,6 +180,29 @@ impl PrivateKey {
     }
 }
 
+/// A public key along with a table of precomputed multiples, for keys used in many agreements.
+#[derive(Clone)]
+pub struct PrecomputedPublicKey {
+    public_key: [u8; PUBLIC_KEY_LENGTH],
+    /// Multiples of 8 times the key's Edwards point, or `None` if the key isn't on the curve
+    /// (e.g. it's on the twist), in which case agreements fall back to the Montgomery ladder.
+    table: Option<Box<EdwardsBasepointTable>>,
+}
+
+impl PrecomputedPublicKey {
+    pub fn new(public_key: &[u8; PUBLIC_KEY_LENGTH]) -> Self {
+        // X25519 only uses the u-coordinate, and P and -P share a u-coordinate, so either sign
+        // works here.
+        let table = MontgomeryPoint(*public_key)
+            .to_edwards(0)
+            .map(|point| Box::new(EdwardsBasepointTable::create(&point.mul_by_cofactor())));
+        Self {
+            public_key: *public_key,
+            table,
+        }
+    }
+}
+
 impl From<[u8; PRIVATE_KEY_LENGTH]> for PrivateKey {
     fn from(private_key: [u8; 32]) -> Self {
         let secret = StaticSecret::from(scalar::clamp_integer(private_key));
@@ -228,6 +274,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_precomputed_agreements() {
+        let mut csprng = OsRng;
+        let bob_key = PrivateKey::new(&mut csprng);
+        let bob_precomputed = PrecomputedPublicKey::new(&bob_key.derive_public_key_bytes());
+        for _ in 0..50 {
+            let alice_key = PrivateKey::new(&mut csprng);
+            assert_eq!(
+                alice_key.calculate_agreement_precomputed(&bob_precomputed),
+                alice_key.calculate_agreement(&bob_key.derive_public_key_bytes())
+            );
+        }
+    }
+
+    #[test]
+    fn test_precomputed_agreement_with_unusual_points() {
+        let mut csprng = OsRng;
+        let alice_key = PrivateKey::new(&mut csprng);
+
+        // A point of small order, a point with a torsion component, and a point on the twist.
+        let small_order = curve25519_dalek::constants::EIGHT_TORSION[1];
+        let with_torsion = EdwardsPoint::mul_base(&Scalar::from(1234u64)) + small_order;
+        let mut twist = [0u8; PUBLIC_KEY_LENGTH];
+        twist[0] = 2;
+        for point in [
+            small_order.to_montgomery().to_bytes(),
+            with_torsion.to_montgomery().to_bytes(),
+            twist,
+        ] {
+            assert_eq!(
+                alice_key.calculate_agreement_precomputed(&PrecomputedPublicKey::new(&point)),
+                alice_key.calculate_agreement(&point),
+                "{}",
+                hex::encode(point)
+            );
+        }
+    }
+
     #[test]
     fn test_signature() {
         let alice_identity_private: [u8; PRIVATE_KEY_LENGTH] = [
@@ -32,6 +32,8 @@ pub enum SignalProtocolError {
     UnrecognizedCiphertextVersion(u8),
     /// unrecognized message version <{0}>
     UnrecognizedMessageVersion(u32),
+    /// record version was unrecognized <{0}>
+    UnrecognizedRecordVersion(u8),
 
     /// fingerprint version number mismatch them {0} us {1}
     FingerprintVersionMismatch(u32, u32),
@@ -75,6 +77,8 @@ pub enum SignalProtocolError {
 
     /// message with old counter {0} / {1}
     DuplicatedMessage(u32, u32),
+    /// message with counter {0} was already processed
+    ReplayedMessage(u32),
     /// invalid {0:?} message: {1}
     InvalidMessage(crate::CiphertextMessageType, &'static str),
 
@@ -11,6 +11,7 @@ use sha2::digest::Digest;
 use sha2::Sha512;
 use subtle::ConstantTimeEq;
 
+use crate::proto::DecodeLimited;
 use crate::{proto, IdentityKey, Result, SignalProtocolError};
 
 #[derive(Debug, Clone)]
@@ -79,7 +80,7 @@ impl ScannableFingerprint {
     }
 
     pub fn deserialize(protobuf: &[u8]) -> Result<Self> {
-        let fingerprint = proto::fingerprint::CombinedFingerprints::decode(protobuf)
+        let fingerprint = proto::fingerprint::CombinedFingerprints::decode_limited(protobuf)
             .map_err(|_| SignalProtocolError::FingerprintParsingError)?;
 
         Ok(Self {
@@ -114,7 +115,7 @@ impl ScannableFingerprint {
     }
 
     pub fn compare(&self, combined: &[u8]) -> Result<bool> {
-        let combined = proto::fingerprint::CombinedFingerprints::decode(combined)
+        let combined = proto::fingerprint::CombinedFingerprints::decode_limited(combined)
             .map_err(|_| SignalProtocolError::FingerprintParsingError)?;
 
         let their_version = combined.version.unwrap_or(0);
@@ -147,18 +148,27 @@ impl ScannableFingerprint {
     }
 }
 
+/// The length of an [`IdentityDigest`], in bytes.
+pub const IDENTITY_DIGEST_LEN: usize = 32;
+
+/// The digest of a single identity that makes up one half of a [`Fingerprint`].
+///
+/// Two users' digests combine to form their safety number, so displaying a single digest (e.g. on
+/// a device management screen) will always be consistent with the corresponding half of any safety
+/// number computed with the same version and iteration count.
 #[derive(Debug, Clone)]
-pub struct Fingerprint {
-    pub display: DisplayableFingerprint,
-    pub scannable: ScannableFingerprint,
+pub struct IdentityDigest {
+    version: u32,
+    digest: [u8; IDENTITY_DIGEST_LEN],
 }
 
-impl Fingerprint {
-    fn get_fingerprint(
+impl IdentityDigest {
+    pub fn new(
+        version: u32,
         iterations: u32,
-        local_id: &[u8],
-        local_key: &IdentityKey,
-    ) -> Result<Vec<u8>> {
+        stable_id: &[u8],
+        identity_key: &IdentityKey,
+    ) -> Result<Self> {
         if iterations <= 1 || iterations > 1000000 {
             return Err(SignalProtocolError::InvalidArgument(format!(
                 "Invalid fingerprint iterations {}",
@@ -167,7 +177,7 @@ impl Fingerprint {
         }
 
         let fingerprint_version = [0u8, 0u8]; // 0x0000
-        let key_bytes = local_key.serialize();
+        let key_bytes = identity_key.serialize();
 
         let mut sha512 = Sha512::new();
 
@@ -175,7 +185,7 @@ impl Fingerprint {
         // Explicitly pass a slice to avoid generating multiple versions of update().
         sha512.update(&fingerprint_version[..]);
         sha512.update(&key_bytes);
-        sha512.update(local_id);
+        sha512.update(stable_id);
         sha512.update(&key_bytes);
         let mut buf = sha512.finalize();
 
@@ -187,9 +197,47 @@ impl Fingerprint {
             buf = sha512.finalize();
         }
 
-        Ok(buf.to_vec())
+        Ok(Self {
+            version,
+            digest: buf[..IDENTITY_DIGEST_LEN]
+                .try_into()
+                .expect("SHA-512 output is longer than a digest"),
+        })
+    }
+
+    /// Reconstructs a digest previously produced by [`IdentityDigest::as_bytes`].
+    pub fn from_bytes(version: u32, bytes: &[u8]) -> Result<Self> {
+        let digest = bytes.try_into().map_err(|_| {
+            SignalProtocolError::InvalidArgument(format!(
+                "identity digest must be {} bytes, got {}",
+                IDENTITY_DIGEST_LEN,
+                bytes.len()
+            ))
+        })?;
+        Ok(Self { version, digest })
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
     }
 
+    pub fn as_bytes(&self) -> &[u8; IDENTITY_DIGEST_LEN] {
+        &self.digest
+    }
+
+    /// The 30-digit string this identity contributes to a displayed safety number.
+    pub fn display_string(&self) -> String {
+        get_encoded_string(&self.digest).expect("digest is long enough to encode")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Fingerprint {
+    pub display: DisplayableFingerprint,
+    pub scannable: ScannableFingerprint,
+}
+
+impl Fingerprint {
     pub fn new(
         version: u32,
         iterations: u32,
@@ -198,12 +246,12 @@ impl Fingerprint {
         remote_id: &[u8],
         remote_key: &IdentityKey,
     ) -> Result<Fingerprint> {
-        let local_fingerprint = Fingerprint::get_fingerprint(iterations, local_id, local_key)?;
-        let remote_fingerprint = Fingerprint::get_fingerprint(iterations, remote_id, remote_key)?;
+        let local = IdentityDigest::new(version, iterations, local_id, local_key)?;
+        let remote = IdentityDigest::new(version, iterations, remote_id, remote_key)?;
 
         Ok(Fingerprint {
-            display: DisplayableFingerprint::new(&local_fingerprint, &remote_fingerprint)?,
-            scannable: ScannableFingerprint::new(version, &local_fingerprint, &remote_fingerprint),
+            display: DisplayableFingerprint::new(local.as_bytes(), remote.as_bytes())?,
+            scannable: ScannableFingerprint::new(version, local.as_bytes(), remote.as_bytes()),
         })
     }
 
@@ -354,6 +402,34 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn identity_digest_matches_fingerprint_halves() -> Result<()> {
+        let a_key = IdentityKey::decode(ALICE_IDENTITY)?;
+        let b_key = IdentityKey::decode(BOB_IDENTITY)?;
+
+        let a_digest = IdentityDigest::new(2, 5200, ALICE_STABLE_ID.as_bytes(), &a_key)?;
+        let b_digest = IdentityDigest::new(2, 5200, BOB_STABLE_ID.as_bytes(), &b_key)?;
+
+        assert_eq!(a_digest.version(), 2);
+        assert_eq!(
+            hex::encode(a_digest.as_bytes()),
+            "1e301a0353dce3dbe7684cb8336e85136cdc0ee96219494ada305d62a7bd61df"
+        );
+        assert_eq!(
+            hex::encode(b_digest.as_bytes()),
+            "d62cbf73a11592015b6b9f1682ac306fea3aaf3885b84d12bca631e9d4fb3a4d"
+        );
+
+        assert_eq!(a_digest.display_string(), DISPLAYABLE_FINGERPRINT_V1[..30]);
+        assert_eq!(b_digest.display_string(), DISPLAYABLE_FINGERPRINT_V1[30..]);
+
+        let round_tripped = IdentityDigest::from_bytes(2, a_digest.as_bytes())?;
+        assert_eq!(round_tripped.display_string(), a_digest.display_string());
+        assert!(IdentityDigest::from_bytes(2, &a_digest.as_bytes()[1..]).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn fingerprint_matching_identifiers() -> Result<()> {
         // testMatchingFingerprints
@@ -10,7 +10,8 @@ use crate::protocol::SENDERKEY_MESSAGE_CURRENT_VERSION;
 use crate::sender_keys::{SenderKeyState, SenderMessageKey};
 use crate::{
     consts, CiphertextMessageType, KeyPair, ProtocolAddress, Result, SenderKeyDistributionMessage,
-    SenderKeyMessage, SenderKeyRecord, SenderKeyStore, SignalProtocolError,
+    SenderKeyDistributionRecord, SenderKeyMessage, SenderKeyRecord, SenderKeyStore,
+    SignalProtocolError,
 };
 
 pub async fn group_encrypt<R: Rng + CryptoRng>(
@@ -293,3 +294,112 @@ pub async fn create_sender_key_distribution_message<R: Rng + CryptoRng>(
             .map_err(|_| SignalProtocolError::InvalidSenderKeySession { distribution_id })?,
     )
 }
+
+/// Returns the chain id of the sender key currently used to send to `distribution_id`.
+async fn current_sender_key_chain_id(
+    sender: &ProtocolAddress,
+    distribution_id: Uuid,
+    sender_key_store: &mut dyn SenderKeyStore,
+) -> Result<Option<u32>> {
+    let Some(record) = sender_key_store
+        .load_sender_key(sender, distribution_id)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let state = record
+        .sender_key_state()
+        .map_err(|_| SignalProtocolError::InvalidSenderKeySession { distribution_id })?;
+    Ok(Some(state.chain_id()))
+}
+
+/// Returns the members of `recipients` that have not yet been sent the sender key currently used
+/// for `distribution_id`.
+///
+/// If there is no sender key yet, the key has changed since distribution was last recorded, or
+/// the store does not track distribution, every recipient is returned. Each of them should be
+/// sent the result of [`create_sender_key_distribution_message`] before (or along with) the next
+/// group message, and then passed to [`mark_sender_key_distributed`].
+pub async fn sender_key_recipients_needing_distribution(
+    sender: &ProtocolAddress,
+    distribution_id: Uuid,
+    recipients: &[ProtocolAddress],
+    sender_key_store: &mut dyn SenderKeyStore,
+) -> Result<Vec<ProtocolAddress>> {
+    let Some(chain_id) =
+        current_sender_key_chain_id(sender, distribution_id, sender_key_store).await?
+    else {
+        return Ok(recipients.to_vec());
+    };
+    let distribution = sender_key_store
+        .load_sender_key_distribution(sender, distribution_id)
+        .await?
+        .filter(|distribution| distribution.chain_id() == chain_id);
+
+    Ok(match distribution {
+        None => recipients.to_vec(),
+        Some(distribution) => recipients
+            .iter()
+            .filter(|recipient| !distribution.contains_recipient(recipient))
+            .cloned()
+            .collect(),
+    })
+}
+
+/// Records that `recipients` have been sent the sender key currently used for
+/// `distribution_id`.
+///
+/// Call this only once the [`SenderKeyDistributionMessage`] has actually been delivered. Any
+/// record left over from a previous sender key is discarded.
+pub async fn mark_sender_key_distributed(
+    sender: &ProtocolAddress,
+    distribution_id: Uuid,
+    recipients: &[ProtocolAddress],
+    sender_key_store: &mut dyn SenderKeyStore,
+) -> Result<()> {
+    let chain_id = current_sender_key_chain_id(sender, distribution_id, sender_key_store)
+        .await?
+        .ok_or(SignalProtocolError::NoSenderKeyState { distribution_id })?;
+    let mut distribution = sender_key_store
+        .load_sender_key_distribution(sender, distribution_id)
+        .await?
+        .filter(|distribution| distribution.chain_id() == chain_id)
+        .unwrap_or_else(|| SenderKeyDistributionRecord::new(chain_id));
+
+    for recipient in recipients {
+        distribution.add_recipient(recipient.clone());
+    }
+    sender_key_store
+        .store_sender_key_distribution(sender, distribution_id, &distribution)
+        .await
+}
+
+/// Forgets that `recipients` have been sent the sender key for `distribution_id`, so that they
+/// will be reported by [`sender_key_recipients_needing_distribution`] again.
+///
+/// Use this when a recipient may have lost the key, such as after their session is reset or they
+/// send a decryption error for a group message.
+pub async fn invalidate_sender_key_distribution(
+    sender: &ProtocolAddress,
+    distribution_id: Uuid,
+    recipients: &[ProtocolAddress],
+    sender_key_store: &mut dyn SenderKeyStore,
+) -> Result<()> {
+    let Some(mut distribution) = sender_key_store
+        .load_sender_key_distribution(sender, distribution_id)
+        .await?
+    else {
+        return Ok(());
+    };
+
+    let mut changed = false;
+    for recipient in recipients {
+        changed |= distribution.remove_recipient(recipient);
+    }
+    if changed {
+        sender_key_store
+            .store_sender_key_distribution(sender, distribution_id, &distribution)
+            .await?;
+    }
+    Ok(())
+}
@@ -10,6 +10,7 @@
 use prost::Message;
 use rand::{CryptoRng, Rng};
 
+use crate::proto::DecodeLimited;
 use crate::{proto, KeyPair, PrivateKey, PublicKey, Result, SignalProtocolError};
 
 // Used for domain separation between alternate-identity signatures and other key-to-key signatures.
@@ -78,6 +79,13 @@ impl From<PublicKey> for IdentityKey {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for IdentityKey {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::new(u.arbitrary()?))
+    }
+}
+
 impl From<IdentityKey> for PublicKey {
     fn from(value: IdentityKey) -> Self {
         value.public_key
@@ -162,7 +170,7 @@ impl TryFrom<&[u8]> for IdentityKeyPair {
     type Error = SignalProtocolError;
 
     fn try_from(value: &[u8]) -> Result<Self> {
-        let structure = proto::storage::IdentityKeyPairStructure::decode(value)
+        let structure = proto::storage::IdentityKeyPairStructure::decode_limited(value)
             .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
         Ok(Self {
             identity_key: IdentityKey::try_from(&structure.public_key[..])?,
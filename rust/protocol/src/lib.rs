@@ -22,6 +22,8 @@
 // https://doc.rust-lang.org/rustdoc/what-to-include.html for background.
 // #![warn(missing_docs)]
 
+#[cfg(feature = "conformance")]
+pub mod conformance;
 mod consts;
 mod crypto;
 mod curve;
@@ -43,45 +45,59 @@ mod storage;
 mod timestamp;
 mod utils;
 
-pub use curve::{KeyPair, PrivateKey, PublicKey};
+pub use curve::{KeyPair, PrecomputedPublicKey, PrivateKey, PublicKey};
 use error::Result;
 pub use error::SignalProtocolError;
-pub use fingerprint::{DisplayableFingerprint, Fingerprint, ScannableFingerprint};
+pub use fingerprint::{
+    DisplayableFingerprint, Fingerprint, IdentityDigest, ScannableFingerprint, IDENTITY_DIGEST_LEN,
+};
 pub use group_cipher::{
     create_sender_key_distribution_message, group_decrypt, group_encrypt,
-    process_sender_key_distribution_message,
+    invalidate_sender_key_distribution, mark_sender_key_distributed,
+    process_sender_key_distribution_message, sender_key_recipients_needing_distribution,
 };
 pub use identity_key::{IdentityKey, IdentityKeyPair};
 pub use libsignal_core::{
-    Aci, DeviceId, Pni, ProtocolAddress, ServiceId, ServiceIdFixedWidthBinaryBytes, ServiceIdKind,
+    Aci, DeviceId, ParseLimits, Pni, ProtocolAddress, ServiceId, ServiceIdFixedWidthBinaryBytes,
+    ServiceIdKind,
 };
 pub use protocol::{
     extract_decryption_error_message_from_serialized_content, CiphertextMessage,
     CiphertextMessageType, DecryptionErrorMessage, KyberPayload, PlaintextContent,
-    PreKeySignalMessage, SenderKeyDistributionMessage, SenderKeyMessage, SignalMessage,
+    PreKeySignalMessage, RatchetContinuity, SenderKeyDistributionMessage, SenderKeyMessage,
+    SignalMessage,
 };
 pub use ratchet::{
     initialize_alice_session_record, initialize_bob_session_record, AliceSignalProtocolParameters,
     BobSignalProtocolParameters,
 };
 pub use sealed_sender::{
-    sealed_sender_decrypt, sealed_sender_decrypt_to_usmc, sealed_sender_encrypt,
-    sealed_sender_encrypt_from_usmc, sealed_sender_multi_recipient_encrypt, ContentHint,
-    SealedSenderDecryptionResult, SealedSenderV2SentMessage, SealedSenderV2SentMessageRecipient,
-    SenderCertificate, ServerCertificate, UnidentifiedSenderMessageContent,
+    load_multi_recipient_sessions, sealed_sender_decrypt,
+    sealed_sender_decrypt_capturing_identity_change, sealed_sender_decrypt_to_usmc,
+    sealed_sender_decrypt_with_replay_cache, sealed_sender_encrypt,
+    sealed_sender_encrypt_from_usmc, sealed_sender_multi_recipient_encrypt,
+    sealed_sender_multi_recipient_message_len, sealed_sender_multi_recipient_received_message_len,
+    ContentHint, IdentityChange, SealedSenderDecryptionResult, SealedSenderV2SentMessage,
+    SealedSenderV2SentMessageRecipient, SenderCertificate, ServerCertificate,
+    UnidentifiedSenderMessageContent,
+};
+pub use sender_keys::{SenderKeyDistributionRecord, SenderKeyRecord};
+pub use session::{
+    archive_sessions_for_devices, process_prekey, process_prekey_bundle, session_stats,
 };
-pub use sender_keys::SenderKeyRecord;
-pub use session::{process_prekey, process_prekey_bundle};
 pub use session_cipher::{
-    message_decrypt, message_decrypt_prekey, message_decrypt_signal, message_encrypt,
+    message_decrypt, message_decrypt_prekey, message_decrypt_prekey_with_replay_cache,
+    message_decrypt_signal, message_decrypt_signal_with_replay_cache, message_encrypt,
 };
 pub use state::{
-    GenericSignedPreKey, KyberPreKeyId, KyberPreKeyRecord, PreKeyBundle, PreKeyBundleContent,
-    PreKeyId, PreKeyRecord, SessionRecord, SignedPreKeyId, SignedPreKeyRecord,
+    record_version, GenericSignedPreKey, KyberPreKeyId, KyberPreKeyRecord, PreKeyBundle,
+    PreKeyBundleContent, PreKeyId, PreKeyRecord, SessionRecord, SessionStats, SignedPreKeyId,
+    SignedPreKeyRecord, CURRENT_RECORD_VERSION,
 };
 pub use storage::{
     Direction, IdentityKeyStore, InMemIdentityKeyStore, InMemKyberPreKeyStore, InMemPreKeyStore,
-    InMemSenderKeyStore, InMemSessionStore, InMemSignalProtocolStore, InMemSignedPreKeyStore,
-    KyberPreKeyStore, PreKeyStore, ProtocolStore, SenderKeyStore, SessionStore, SignedPreKeyStore,
+    InMemReplayCache, InMemSenderKeyStore, InMemSessionStore, InMemSignalProtocolStore,
+    InMemSignedPreKeyStore, KyberPreKeyStore, PreKeyStore, ProtocolStore, ReplayCache,
+    SenderKeyStore, SessionStore, SignedPreKeyStore,
 };
 pub use timestamp::Timestamp;
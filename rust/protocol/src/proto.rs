@@ -3,8 +3,140 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use std::fmt;
+
+use libsignal_core::{parse_limits, LimitExceeded, ParseLimits};
+
 pub mod fingerprint;
 pub mod sealed_sender;
 pub mod service;
 pub mod storage;
 pub mod wire;
+
+#[derive(Debug)]
+pub(crate) enum LimitedDecodeError {
+    Limit(LimitExceeded),
+    Decode(prost::DecodeError),
+}
+
+impl fmt::Display for LimitedDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Limit(e) => write!(f, "{e}"),
+            Self::Decode(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+/// Decodes untrusted protobuf input, subject to the process-wide [`ParseLimits`].
+pub(crate) trait DecodeLimited: prost::Message + Default {
+    fn decode_limited(buf: &[u8]) -> Result<Self, LimitedDecodeError> {
+        Self::decode_with_limits(buf, &parse_limits())
+    }
+
+    fn decode_with_limits(buf: &[u8], limits: &ParseLimits) -> Result<Self, LimitedDecodeError> {
+        if let Err(e) = limits.check_message_size(buf.len()) {
+            log::warn!("rejecting {}: {e}", std::any::type_name::<Self>());
+            return Err(LimitedDecodeError::Limit(e));
+        }
+        Self::decode(buf).map_err(LimitedDecodeError::Decode)
+    }
+}
+
+impl<M: prost::Message + Default> DecodeLimited for M {}
+
+/// Returns the contents of each length-delimited field numbered `tag` at the top level of the
+/// encoded message `buf`.
+///
+/// Nothing is decoded or allocated, so repeated fields can be counted before decoding them.
+/// Iteration stops at the first malformed field, which is left for the decoder to reject.
+pub(crate) fn embedded_fields(mut buf: &[u8], tag: u32) -> impl Iterator<Item = &[u8]> {
+    std::iter::from_fn(move || {
+        while !buf.is_empty() {
+            let (field, contents) = skip_field(&mut buf)?;
+            if field == tag && contents.is_some() {
+                return contents;
+            }
+        }
+        None
+    })
+}
+
+/// Advances past the next field in `buf`, returning its number and, if it's length-delimited, its
+/// contents.
+fn skip_field<'a>(buf: &mut &'a [u8]) -> Option<(u32, Option<&'a [u8]>)> {
+    let key = prost::encoding::decode_varint(buf).ok()?;
+    let field = u32::try_from(key >> 3).ok()?;
+    let len = match key & 0b111 {
+        0 => {
+            prost::encoding::decode_varint(buf).ok()?;
+            return Some((field, None));
+        }
+        1 => 8,
+        2 => {
+            let len = usize::try_from(prost::encoding::decode_varint(buf).ok()?).ok()?;
+            let contents = buf.get(..len)?;
+            *buf = &buf[len..];
+            return Some((field, Some(contents)));
+        }
+        5 => 4,
+        // Groups aren't used by any of our messages.
+        _ => return None,
+    };
+    *buf = buf.get(len..)?;
+    Some((field, None))
+}
+
+/// Checks the length of a repeated field, logging which one if it's too long.
+pub(crate) fn check_collection_count(
+    limits: &ParseLimits,
+    what: &str,
+    count: usize,
+) -> Result<(), LimitExceeded> {
+    limits
+        .check_collection_count(count)
+        .inspect_err(|e| log::warn!("rejecting {what}: {e}"))
+}
+
+#[cfg(test)]
+mod test {
+    use prost::Message as _;
+
+    use super::*;
+
+    #[test]
+    fn embedded_fields_match_decoded_fields() {
+        let session = storage::SessionStructure {
+            session_version: 4,
+            receiver_chains: vec![
+                storage::session_structure::Chain {
+                    sender_ratchet_key: vec![1; 33],
+                    message_keys: vec![Default::default(); 3],
+                    ..Default::default()
+                },
+                storage::session_structure::Chain::default(),
+            ],
+            ..Default::default()
+        };
+        let encoded = session.encode_to_vec();
+
+        let chains: Vec<&[u8]> = embedded_fields(&encoded, 7).collect();
+        assert_eq!(chains.len(), 2);
+        assert_eq!(embedded_fields(chains[0], 4).count(), 3);
+        assert_eq!(embedded_fields(chains[1], 4).count(), 0);
+    }
+
+    #[test]
+    fn embedded_fields_stop_at_malformed_input() {
+        let chain = storage::session_structure::Chain {
+            message_keys: vec![Default::default(); 3],
+            ..Default::default()
+        };
+        let mut encoded = chain.encode_to_vec();
+        let truncated = &encoded[..encoded.len() - 1];
+        assert_eq!(embedded_fields(truncated, 4).count(), 2);
+
+        encoded.insert(0, 0b011); // a group, which we don't support
+        assert_eq!(embedded_fields(&encoded, 4).count(), 0);
+    }
+}
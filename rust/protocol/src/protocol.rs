@@ -10,6 +10,7 @@ use sha2::Sha256;
 use subtle::ConstantTimeEq;
 use uuid::Uuid;
 
+use crate::proto::DecodeLimited;
 use crate::state::{KyberPreKeyId, PreKeyId, SignedPreKeyId};
 use crate::{
     kem, proto, IdentityKey, PrivateKey, PublicKey, Result, SignalProtocolError, Timestamp,
@@ -57,12 +58,39 @@ impl CiphertextMessage {
     }
 }
 
+/// How two consecutive [`SignalMessage`]s from the same peer relate to each other.
+///
+/// See [`SignalMessage::ratchet_continuity_after`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, num_enum::TryFromPrimitive)]
+#[repr(u8)]
+pub enum RatchetContinuity {
+    /// The later message continues the earlier message's chain.
+    SameChain = 0,
+    /// The later message starts a new chain after the earlier message's chain.
+    NextChain = 1,
+    /// The later message is on the same chain but doesn't come after the earlier one, as happens
+    /// with delayed or duplicated delivery.
+    Reordered = 2,
+    /// The later message starts a new chain that doesn't account for the earlier message, as
+    /// happens when the sender's session was reset or replaced.
+    Discontinuous = 3,
+}
+
+impl RatchetContinuity {
+    /// Whether this is worth flagging for investigation.
+    pub fn is_suspicious(self) -> bool {
+        match self {
+            Self::SameChain | Self::NextChain | Self::Reordered => false,
+            Self::Discontinuous => true,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SignalMessage {
     message_version: u8,
     sender_ratchet_key: PublicKey,
     counter: u32,
-    #[allow(dead_code)]
     previous_counter: u32,
     ciphertext: Box<[u8]>,
     serialized: Box<[u8]>,
@@ -125,6 +153,12 @@ impl SignalMessage {
         self.counter
     }
 
+    /// The index of the last message the sender sent on its previous ratchet chain.
+    #[inline]
+    pub fn previous_counter(&self) -> u32 {
+        self.previous_counter
+    }
+
     #[inline]
     pub fn serialized(&self) -> &[u8] {
         &self.serialized
@@ -135,6 +169,26 @@ impl SignalMessage {
         &self.ciphertext
     }
 
+    /// Checks whether this message plausibly follows `earlier` in the same session.
+    ///
+    /// Both messages must come from the same peer, and `earlier` must be the last message
+    /// received from them before this one. This only looks at the unauthenticated ratchet
+    /// metadata, so it's meant for diagnosing reports of session tampering, not for deciding
+    /// whether to accept a message.
+    pub fn ratchet_continuity_after(&self, earlier: &SignalMessage) -> RatchetContinuity {
+        if self.sender_ratchet_key == earlier.sender_ratchet_key {
+            if self.counter > earlier.counter {
+                RatchetContinuity::SameChain
+            } else {
+                RatchetContinuity::Reordered
+            }
+        } else if self.previous_counter >= earlier.counter {
+            RatchetContinuity::NextChain
+        } else {
+            RatchetContinuity::Discontinuous
+        }
+    }
+
     pub fn verify_mac(
         &self,
         sender_identity_key: &IdentityKey,
@@ -206,9 +260,10 @@ impl TryFrom<&[u8]> for SignalMessage {
             ));
         }
 
-        let proto_structure =
-            proto::wire::SignalMessage::decode(&value[1..value.len() - SignalMessage::MAC_LENGTH])
-                .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
+        let proto_structure = proto::wire::SignalMessage::decode_limited(
+            &value[1..value.len() - SignalMessage::MAC_LENGTH],
+        )
+        .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
 
         let sender_ratchet_key = proto_structure
             .ratchet_key
@@ -234,6 +289,30 @@ impl TryFrom<&[u8]> for SignalMessage {
     }
 }
 
+/// Produces messages with well-formed structure and a MAC computed for arbitrary identities, so
+/// that fuzzing can get past parsing into the code that processes messages.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SignalMessage {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let message_version = u.int_in_range(
+            CIPHERTEXT_MESSAGE_PRE_KYBER_VERSION..=CIPHERTEXT_MESSAGE_CURRENT_VERSION,
+        )?;
+        let mac_key: [u8; 32] = u.arbitrary()?;
+        let ciphertext: &[u8] = u.arbitrary()?;
+        Self::new(
+            message_version,
+            &mac_key,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            ciphertext,
+            &u.arbitrary()?,
+            &u.arbitrary()?,
+        )
+        .map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct KyberPayload {
     pre_key_id: KyberPreKeyId,
@@ -381,7 +460,7 @@ impl TryFrom<&[u8]> for PreKeySignalMessage {
             ));
         }
 
-        let proto_structure = proto::wire::PreKeySignalMessage::decode(&value[1..])
+        let proto_structure = proto::wire::PreKeySignalMessage::decode_limited(&value[1..])
             .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
 
         let base_key = proto_structure
@@ -543,9 +622,10 @@ impl TryFrom<&[u8]> for SenderKeyMessage {
                 message_version,
             ));
         }
-        let proto_structure =
-            proto::wire::SenderKeyMessage::decode(&value[1..value.len() - Self::SIGNATURE_LEN])
-                .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
+        let proto_structure = proto::wire::SenderKeyMessage::decode_limited(
+            &value[1..value.len() - Self::SIGNATURE_LEN],
+        )
+        .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
 
         let distribution_id = proto_structure
             .distribution_uuid
@@ -681,8 +761,9 @@ impl TryFrom<&[u8]> for SenderKeyDistributionMessage {
             ));
         }
 
-        let proto_structure = proto::wire::SenderKeyDistributionMessage::decode(&value[1..])
-            .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
+        let proto_structure =
+            proto::wire::SenderKeyDistributionMessage::decode_limited(&value[1..])
+                .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
 
         let distribution_id = proto_structure
             .distribution_uuid
@@ -719,6 +800,22 @@ impl TryFrom<&[u8]> for SenderKeyDistributionMessage {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SenderKeyDistributionMessage {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let chain_key: [u8; 32] = u.arbitrary()?;
+        Self::new(
+            SENDERKEY_MESSAGE_CURRENT_VERSION,
+            Uuid::from_bytes(u.arbitrary()?),
+            u.arbitrary()?,
+            u.arbitrary()?,
+            chain_key.to_vec(),
+            u.arbitrary()?,
+        )
+        .map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PlaintextContent {
     serialized: Box<[u8]>,
@@ -855,7 +952,7 @@ impl TryFrom<&[u8]> for DecryptionErrorMessage {
     type Error = SignalProtocolError;
 
     fn try_from(value: &[u8]) -> Result<Self> {
-        let proto_structure = proto::service::DecryptionErrorMessage::decode(value)
+        let proto_structure = proto::service::DecryptionErrorMessage::decode_limited(value)
             .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
         let timestamp = proto_structure
             .timestamp
@@ -882,8 +979,9 @@ pub fn extract_decryption_error_message_from_serialized_content(
     if bytes.last() != Some(&PlaintextContent::PADDING_BOUNDARY_BYTE) {
         return Err(SignalProtocolError::InvalidProtobufEncoding);
     }
-    let content = proto::service::Content::decode(bytes.split_last().expect("checked above").1)
-        .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
+    let content =
+        proto::service::Content::decode_limited(bytes.split_last().expect("checked above").1)
+            .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
     content
         .decryption_error_message
         .as_deref()
@@ -950,6 +1048,57 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_signal_message_ratchet_continuity() -> Result<()> {
+        let mut csprng = OsRng;
+        let identity_key: IdentityKey = KeyPair::generate(&mut csprng).public_key.into();
+        let first_chain = KeyPair::generate(&mut csprng).public_key;
+        let second_chain = KeyPair::generate(&mut csprng).public_key;
+        let message = |ratchet_key, counter, previous_counter| {
+            SignalMessage::new(
+                4,
+                &[0; 32],
+                ratchet_key,
+                counter,
+                previous_counter,
+                b"",
+                &identity_key,
+                &identity_key,
+            )
+        };
+
+        let earlier = message(first_chain, 5, 0)?;
+        for (later, expected) in [
+            (message(first_chain, 6, 0)?, RatchetContinuity::SameChain),
+            (message(first_chain, 5, 0)?, RatchetContinuity::Reordered),
+            (message(first_chain, 2, 0)?, RatchetContinuity::Reordered),
+            (message(second_chain, 0, 5)?, RatchetContinuity::NextChain),
+            (message(second_chain, 3, 9)?, RatchetContinuity::NextChain),
+            (
+                message(second_chain, 0, 4)?,
+                RatchetContinuity::Discontinuous,
+            ),
+            (
+                message(second_chain, 0, 0)?,
+                RatchetContinuity::Discontinuous,
+            ),
+        ] {
+            let continuity = later.ratchet_continuity_after(&earlier);
+            assert_eq!(
+                continuity,
+                expected,
+                "counter {} previous {}",
+                later.counter(),
+                later.previous_counter()
+            );
+            assert_eq!(
+                continuity.is_suspicious(),
+                expected == RatchetContinuity::Discontinuous
+            );
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_pre_key_signal_message_serialize_deserialize() -> Result<()> {
         let mut csprng = OsRng;
@@ -3,12 +3,14 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use std::cell::RefCell;
 use std::ops::Range;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 use aes_gcm_siv::aead::generic_array::typenum::Unsigned;
 use aes_gcm_siv::{AeadInPlace, Aes256GcmSiv, KeyInit};
 use arrayref::array_ref;
+use async_trait::async_trait;
 use indexmap::IndexMap;
 use itertools::Itertools;
 use prost::Message;
@@ -16,11 +18,12 @@ use proto::sealed_sender::unidentified_sender_message::message::Type as ProtoMes
 use rand::{CryptoRng, Rng};
 use subtle::ConstantTimeEq;
 
+use crate::proto::{check_collection_count, DecodeLimited};
 use crate::{
     crypto, curve, message_encrypt, proto, session_cipher, Aci, CiphertextMessageType, DeviceId,
     Direction, IdentityKey, IdentityKeyPair, IdentityKeyStore, KeyPair, KyberPreKeyStore,
-    PreKeySignalMessage, PreKeyStore, PrivateKey, ProtocolAddress, PublicKey, Result, ServiceId,
-    ServiceIdFixedWidthBinaryBytes, SessionRecord, SessionStore, SignalMessage,
+    ParseLimits, PreKeySignalMessage, PreKeyStore, PrivateKey, ProtocolAddress, PublicKey, Result,
+    ServiceId, ServiceIdFixedWidthBinaryBytes, SessionRecord, SessionStore, SignalMessage,
     SignalProtocolError, SignedPreKeyStore, Timestamp,
 };
 
@@ -52,7 +55,7 @@ const MAX_VALID_DEVICE_ID: u32 = 127;
 
 impl ServerCertificate {
     pub fn deserialize(data: &[u8]) -> Result<Self> {
-        let pb = proto::sealed_sender::ServerCertificate::decode(data)
+        let pb = proto::sealed_sender::ServerCertificate::decode_limited(data)
             .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
 
         if pb.certificate.is_none() || pb.signature.is_none() {
@@ -66,8 +69,10 @@ impl ServerCertificate {
             .signature
             .ok_or(SignalProtocolError::InvalidProtobufEncoding)?;
         let certificate_data =
-            proto::sealed_sender::server_certificate::Certificate::decode(certificate.as_ref())
-                .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
+            proto::sealed_sender::server_certificate::Certificate::decode_limited(
+                certificate.as_ref(),
+            )
+            .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
         let key = PublicKey::try_from(
             &certificate_data
                 .key
@@ -170,7 +175,7 @@ pub struct SenderCertificate {
 
 impl SenderCertificate {
     pub fn deserialize(data: &[u8]) -> Result<Self> {
-        let pb = proto::sealed_sender::SenderCertificate::decode(data)
+        let pb = proto::sealed_sender::SenderCertificate::decode_limited(data)
             .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
         let certificate = pb
             .certificate
@@ -179,8 +184,10 @@ impl SenderCertificate {
             .signature
             .ok_or(SignalProtocolError::InvalidProtobufEncoding)?;
         let certificate_data =
-            proto::sealed_sender::sender_certificate::Certificate::decode(certificate.as_ref())
-                .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
+            proto::sealed_sender::sender_certificate::Certificate::decode_limited(
+                certificate.as_ref(),
+            )
+            .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
 
         let sender_device_id: DeviceId = certificate_data
             .sender_device
@@ -291,6 +298,16 @@ impl SenderCertificate {
         Ok(true)
     }
 
+    /// Returns `true` if this certificate will be expired at `validation_time + refresh_margin`.
+    ///
+    /// This does not check signatures; it is meant for deciding when to fetch a replacement for a
+    /// cached certificate, so that sends don't start failing right at the expiration boundary.
+    pub fn needs_refresh(&self, validation_time: Timestamp, refresh_margin: Duration) -> bool {
+        let margin_millis = refresh_margin.as_millis().try_into().unwrap_or(u64::MAX);
+        let deadline = validation_time.epoch_millis().saturating_add(margin_millis);
+        deadline > self.expiration.epoch_millis()
+    }
+
     pub fn signer(&self) -> Result<&ServerCertificate> {
         Ok(&self.signer)
     }
@@ -414,7 +431,7 @@ pub struct UnidentifiedSenderMessageContent {
 
 impl UnidentifiedSenderMessageContent {
     pub fn deserialize(data: &[u8]) -> Result<Self> {
-        let pb = proto::sealed_sender::unidentified_sender_message::Message::decode(data)
+        let pb = proto::sealed_sender::unidentified_sender_message::Message::decode_limited(data)
             .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
 
         let msg_type = pb
@@ -512,6 +529,25 @@ impl UnidentifiedSenderMessageContent {
     pub fn serialized(&self) -> Result<&[u8]> {
         Ok(&self.serialized)
     }
+
+    /// Returns a copy of this message with a different content hint and group ID.
+    ///
+    /// The sender certificate and the encrypted contents are kept as they are. This lets a client
+    /// that is resending a message (for example, after a decryption error) change how it should be
+    /// treated without rebuilding the underlying [`crate::CiphertextMessage`].
+    pub fn with_content_hint_and_group_id(
+        &self,
+        content_hint: ContentHint,
+        group_id: Option<Vec<u8>>,
+    ) -> Result<Self> {
+        Self::new(
+            self.msg_type,
+            self.sender.clone(),
+            self.contents.clone(),
+            content_hint,
+            group_id,
+        )
+    }
 }
 
 enum UnidentifiedSenderMessage {
@@ -550,8 +586,9 @@ impl UnidentifiedSenderMessage {
         match version {
             0 | SEALED_SENDER_V1_MAJOR_VERSION => {
                 // XXX should we really be accepted version == 0 here?
-                let pb = proto::sealed_sender::UnidentifiedSenderMessage::decode(&data[1..])
-                    .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
+                let pb =
+                    proto::sealed_sender::UnidentifiedSenderMessage::decode_limited(&data[1..])
+                        .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
 
                 let ephemeral_public = pb
                     .ephemeral_public
@@ -1263,6 +1300,98 @@ where
     .await
 }
 
+/// The length of the AES-GCM-SIV tag on the shared ciphertext in a Sealed Sender v2 message.
+const SEALED_SENDER_V2_SYMMETRIC_TAG_LEN: usize =
+    <Aes256GcmSiv as aes_gcm_siv::aead::AeadCore>::TagSize::USIZE;
+
+/// Returns the exact length of the message [`sealed_sender_multi_recipient_encrypt`] would produce.
+///
+/// `usmc_len` is the length of the serialized [`UnidentifiedSenderMessageContent`].
+/// `recipient_count` is the number of distinct recipients, and `device_count` is the total number
+/// of destination devices across all of them; this assumes each recipient's devices are listed
+/// together, as recommended. `excluded_recipient_count` is the number of excluded recipients.
+///
+/// This lets a client check a message against the server's size limit before encrypting it.
+pub fn sealed_sender_multi_recipient_message_len(
+    usmc_len: usize,
+    recipient_count: usize,
+    device_count: usize,
+    excluded_recipient_count: usize,
+) -> usize {
+    const SERVICE_ID_LEN: usize = std::mem::size_of::<ServiceIdFixedWidthBinaryBytes>();
+    // A device ID byte followed by a 16-bit registration ID.
+    const DEVICE_LEN: usize = 3;
+    const RECIPIENT_LEN: usize =
+        SERVICE_ID_LEN + sealed_sender_v2::MESSAGE_KEY_LEN + sealed_sender_v2::AUTH_TAG_LEN;
+    // The service ID followed by a zero "no devices" marker.
+    const EXCLUDED_RECIPIENT_LEN: usize = SERVICE_ID_LEN + 1;
+
+    // Saturate rather than overflow, so absurd inputs just produce an absurdly large length.
+    let entry_count = recipient_count.saturating_add(excluded_recipient_count);
+    [
+        1,
+        prost::length_delimiter_len(entry_count),
+        recipient_count.saturating_mul(RECIPIENT_LEN),
+        device_count.saturating_mul(DEVICE_LEN),
+        excluded_recipient_count.saturating_mul(EXCLUDED_RECIPIENT_LEN),
+        curve::curve25519::PUBLIC_KEY_LENGTH,
+        usmc_len,
+        SEALED_SENDER_V2_SYMMETRIC_TAG_LEN,
+    ]
+    .into_iter()
+    .fold(0, usize::saturating_add)
+}
+
+/// Returns the length of each recipient's copy of a multi-recipient message, once the server has
+/// split it up.
+///
+/// This is `usmc_len` plus a fixed overhead; see [`sealed_sender_multi_recipient_message_len`].
+pub fn sealed_sender_multi_recipient_received_message_len(usmc_len: usize) -> usize {
+    const OVERHEAD: usize = 1
+        + sealed_sender_v2::MESSAGE_KEY_LEN
+        + sealed_sender_v2::AUTH_TAG_LEN
+        + curve::curve25519::PUBLIC_KEY_LENGTH
+        + SEALED_SENDER_V2_SYMMETRIC_TAG_LEN;
+    usmc_len.saturating_add(OVERHEAD)
+}
+
+/// Loads the sessions [`sealed_sender_multi_recipient_encrypt`] needs for `destinations`.
+///
+/// Each recipient's sessions are fetched with a single call to
+/// [`SessionStore::load_sessions_for_devices`]. Fails with
+/// [`SignalProtocolError::SessionNotFound`] if any destination has no session.
+pub async fn load_multi_recipient_sessions(
+    destinations: &[&ProtocolAddress],
+    session_store: &dyn SessionStore,
+) -> Result<Vec<SessionRecord>> {
+    let mut devices_by_name: IndexMap<&str, Vec<DeviceId>> = IndexMap::new();
+    for destination in destinations {
+        devices_by_name
+            .entry(destination.name())
+            .or_default()
+            .push(destination.device_id());
+    }
+
+    let mut sessions_by_name = IndexMap::with_capacity(devices_by_name.len());
+    for (name, device_ids) in devices_by_name {
+        let sessions = session_store
+            .load_sessions_for_devices(name, &device_ids)
+            .await?;
+        sessions_by_name.insert(name, sessions);
+    }
+
+    destinations
+        .iter()
+        .map(|&destination| {
+            sessions_by_name[destination.name()]
+                .iter()
+                .find(|(device_id, _)| *device_id == destination.device_id())
+                .map(|(_, session)| session.clone())
+                .ok_or_else(|| SignalProtocolError::SessionNotFound(destination.clone()))
+        })
+        .collect()
+}
+
 async fn sealed_sender_multi_recipient_encrypt_impl<
     R: Rng + CryptoRng,
     X: IntoIterator<Item = ServiceId>,
@@ -1526,7 +1655,14 @@ pub struct SealedSenderV2SentMessage<'a> {
 
 impl<'a> SealedSenderV2SentMessage<'a> {
     /// Parses the message, or produces an error if the message is invalid.
+    ///
+    /// The number of recipients is subject to the process-wide [`ParseLimits`].
     pub fn parse(data: &'a [u8]) -> Result<Self> {
+        Self::parse_with_limits(data, &libsignal_core::parse_limits())
+    }
+
+    /// Like [`Self::parse`], but subject to `limits` rather than the process-wide ones.
+    pub fn parse_with_limits(data: &'a [u8], limits: &ParseLimits) -> Result<Self> {
         if data.is_empty() {
             return Err(SignalProtocolError::InvalidSealedSenderMessage(
                 "Message was empty".to_owned(),
@@ -1563,6 +1699,8 @@ impl<'a> SealedSenderV2SentMessage<'a> {
         let recipient_count = decode_varint(&mut remaining)?
             .try_into()
             .unwrap_or(usize::MAX);
+        check_collection_count(limits, "SSv2 recipients", recipient_count)
+            .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
 
         // Cap our preallocated capacity; anything higher than this is *probably* a mistake, but
         // could just be a very large message.
@@ -1879,6 +2017,17 @@ pub struct SealedSenderDecryptionResult {
     pub sender_e164: Option<String>,
     pub device_id: DeviceId,
     pub message: Vec<u8>,
+    /// Set only by [`sealed_sender_decrypt_capturing_identity_change`].
+    pub identity_change: Option<IdentityChange>,
+}
+
+/// The sender of a message used a different identity key than the one on record for them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdentityChange {
+    /// The identity key that was in the identity store.
+    pub previous: IdentityKey,
+    /// The identity key the message was decrypted with.
+    pub current: IdentityKey,
 }
 
 impl SealedSenderDecryptionResult {
@@ -1897,6 +2046,10 @@ impl SealedSenderDecryptionResult {
     pub fn message(&self) -> Result<&[u8]> {
         Ok(self.message.as_ref())
     }
+
+    pub fn identity_change(&self) -> Option<&IdentityChange> {
+        self.identity_change.as_ref()
+    }
 }
 
 /// Decrypt a Sealed Sender message `ciphertext` in either the v1 or v2 format, validate its sender
@@ -1919,6 +2072,75 @@ pub async fn sealed_sender_decrypt(
     pre_key_store: &mut dyn PreKeyStore,
     signed_pre_key_store: &dyn SignedPreKeyStore,
     kyber_pre_key_store: &mut dyn KyberPreKeyStore,
+) -> Result<SealedSenderDecryptionResult> {
+    sealed_sender_decrypt_impl(
+        ciphertext,
+        trust_root,
+        timestamp,
+        local_e164,
+        local_uuid,
+        local_device_id,
+        identity_store,
+        session_store,
+        pre_key_store,
+        signed_pre_key_store,
+        kyber_pre_key_store,
+        None,
+    )
+    .await
+}
+
+/// Like [`sealed_sender_decrypt`], but rejects inner messages already recorded in `replay_cache`.
+///
+/// See [`session_cipher::message_decrypt_signal_with_replay_cache`] and
+/// [`session_cipher::message_decrypt_prekey_with_replay_cache`] for how the cache is used. A
+/// replayed message fails with [`SignalProtocolError::ReplayedMessage`].
+#[allow(clippy::too_many_arguments)]
+pub async fn sealed_sender_decrypt_with_replay_cache(
+    ciphertext: &[u8],
+    trust_root: &PublicKey,
+    timestamp: Timestamp,
+    local_e164: Option<String>,
+    local_uuid: String,
+    local_device_id: DeviceId,
+    identity_store: &mut dyn IdentityKeyStore,
+    session_store: &mut dyn SessionStore,
+    pre_key_store: &mut dyn PreKeyStore,
+    signed_pre_key_store: &dyn SignedPreKeyStore,
+    kyber_pre_key_store: &mut dyn KyberPreKeyStore,
+    replay_cache: &mut dyn ReplayCache,
+) -> Result<SealedSenderDecryptionResult> {
+    sealed_sender_decrypt_impl(
+        ciphertext,
+        trust_root,
+        timestamp,
+        local_e164,
+        local_uuid,
+        local_device_id,
+        identity_store,
+        session_store,
+        pre_key_store,
+        signed_pre_key_store,
+        kyber_pre_key_store,
+        Some(replay_cache),
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn sealed_sender_decrypt_impl(
+    ciphertext: &[u8],
+    trust_root: &PublicKey,
+    timestamp: Timestamp,
+    local_e164: Option<String>,
+    local_uuid: String,
+    local_device_id: DeviceId,
+    identity_store: &mut dyn IdentityKeyStore,
+    session_store: &mut dyn SessionStore,
+    pre_key_store: &mut dyn PreKeyStore,
+    signed_pre_key_store: &dyn SignedPreKeyStore,
+    kyber_pre_key_store: &mut dyn KyberPreKeyStore,
+    replay_cache: Option<&mut dyn ReplayCache>,
 ) -> Result<SealedSenderDecryptionResult> {
     let usmc = sealed_sender_decrypt_to_usmc(ciphertext, identity_store).await?;
 
@@ -1949,18 +2171,19 @@ pub async fn sealed_sender_decrypt(
     let message = match usmc.msg_type()? {
         CiphertextMessageType::Whisper => {
             let ctext = SignalMessage::try_from(usmc.contents()?)?;
-            session_cipher::message_decrypt_signal(
+            session_cipher::decrypt_signal_impl(
                 &ctext,
                 &remote_address,
                 session_store,
                 identity_store,
+                replay_cache,
                 &mut rng,
             )
             .await?
         }
         CiphertextMessageType::PreKey => {
             let ctext = PreKeySignalMessage::try_from(usmc.contents()?)?;
-            session_cipher::message_decrypt_prekey(
+            session_cipher::decrypt_prekey_impl(
                 &ctext,
                 &remote_address,
                 session_store,
@@ -1968,6 +2191,7 @@ pub async fn sealed_sender_decrypt(
                 pre_key_store,
                 signed_pre_key_store,
                 kyber_pre_key_store,
+                replay_cache,
                 &mut rng,
             )
             .await?
@@ -1985,9 +2209,117 @@ pub async fn sealed_sender_decrypt(
         sender_e164: usmc.sender()?.sender_e164()?.map(|s| s.to_string()),
         device_id: usmc.sender()?.sender_device_id()?,
         message,
+        identity_change: None,
     })
 }
 
+/// Like [`sealed_sender_decrypt`], but lets the caller decide what to do if the sender's identity
+/// key has changed.
+///
+/// Normally, the identity store is asked whether a changed key is trusted; if it is, the new key is
+/// saved, and if not, decryption fails with [`SignalProtocolError::UntrustedIdentity`]. Here,
+/// decryption instead goes ahead with the new key and reports both keys in
+/// [`SealedSenderDecryptionResult::identity_change`], leaving the identity store untouched. (Any
+/// session established by the message is still saved.) An app that accepts the change should save
+/// the new identity itself; one that doesn't can archive the sender's session.
+///
+/// Senders with no identity on record, or with an unchanged identity, are handled exactly as in
+/// [`sealed_sender_decrypt`].
+#[allow(clippy::too_many_arguments)]
+pub async fn sealed_sender_decrypt_capturing_identity_change(
+    ciphertext: &[u8],
+    trust_root: &PublicKey,
+    timestamp: Timestamp,
+    local_e164: Option<String>,
+    local_uuid: String,
+    local_device_id: DeviceId,
+    identity_store: &mut dyn IdentityKeyStore,
+    session_store: &mut dyn SessionStore,
+    pre_key_store: &mut dyn PreKeyStore,
+    signed_pre_key_store: &dyn SignedPreKeyStore,
+    kyber_pre_key_store: &mut dyn KyberPreKeyStore,
+) -> Result<SealedSenderDecryptionResult> {
+    let mut capturing_store = IdentityChangeCapturingStore {
+        inner: identity_store,
+        change: RefCell::new(None),
+    };
+    let mut result = sealed_sender_decrypt(
+        ciphertext,
+        trust_root,
+        timestamp,
+        local_e164,
+        local_uuid,
+        local_device_id,
+        &mut capturing_store,
+        session_store,
+        pre_key_store,
+        signed_pre_key_store,
+        kyber_pre_key_store,
+    )
+    .await?;
+    result.identity_change = capturing_store.change.into_inner();
+    Ok(result)
+}
+
+/// Treats a changed identity as trusted, but remembers the change instead of saving it.
+struct IdentityChangeCapturingStore<'a> {
+    inner: &'a mut dyn IdentityKeyStore,
+    change: RefCell<Option<IdentityChange>>,
+}
+
+#[async_trait(?Send)]
+impl IdentityKeyStore for IdentityChangeCapturingStore<'_> {
+    async fn get_identity_key_pair(&self) -> Result<IdentityKeyPair> {
+        self.inner.get_identity_key_pair().await
+    }
+
+    async fn get_local_registration_id(&self) -> Result<u32> {
+        self.inner.get_local_registration_id().await
+    }
+
+    async fn save_identity(
+        &mut self,
+        address: &ProtocolAddress,
+        identity: &IdentityKey,
+    ) -> Result<bool> {
+        if self
+            .change
+            .get_mut()
+            .is_some_and(|change| change.current == *identity)
+        {
+            // Leave it to the caller to decide whether to save the new identity.
+            return Ok(false);
+        }
+        self.inner.save_identity(address, identity).await
+    }
+
+    async fn is_trusted_identity(
+        &self,
+        address: &ProtocolAddress,
+        identity: &IdentityKey,
+        direction: Direction,
+    ) -> Result<bool> {
+        match self.inner.get_identity(address).await? {
+            Some(previous) if previous != *identity => {
+                *self.change.borrow_mut() = Some(IdentityChange {
+                    previous,
+                    current: *identity,
+                });
+                Ok(true)
+            }
+            _ => {
+                self.inner
+                    .is_trusted_identity(address, identity, direction)
+                    .await
+            }
+        }
+    }
+
+    async fn get_identity(&self, address: &ProtocolAddress) -> Result<Option<IdentityKey>> {
+        self.inner.get_identity(address).await
+    }
+}
+
 #[test]
 fn test_lossless_round_trip() -> Result<()> {
     let trust_root = PrivateKey::deserialize(&[0u8; 32])?;
@@ -3,14 +3,22 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 
 use itertools::Itertools;
 use prost::Message;
 
 use crate::crypto::hmac_sha256;
-use crate::proto::storage as storage_proto;
-use crate::{consts, PrivateKey, PublicKey, SignalProtocolError};
+use crate::proto::{
+    check_collection_count, embedded_fields, storage as storage_proto, DecodeLimited,
+};
+use crate::state::{add_record_header, strip_record_header};
+use crate::{consts, ParseLimits, PrivateKey, ProtocolAddress, PublicKey, SignalProtocolError};
+
+// Field numbers from storage.proto, for counting repeated fields before decoding them.
+const RECORD_SENDER_KEY_STATES: u32 = 1;
+const STATE_SENDER_MESSAGE_KEYS: u32 = 4;
+const DISTRIBUTION_RECORD_RECIPIENTS: u32 = 2;
 
 /// A distinct error type to keep from accidentally propagating deserialization errors.
 #[derive(Debug)]
@@ -248,8 +256,31 @@ impl SenderKeyRecord {
         }
     }
 
+    /// Deserializes a record, subject to the process-wide [`ParseLimits`].
     pub fn deserialize(buf: &[u8]) -> Result<SenderKeyRecord, SignalProtocolError> {
-        let skr = storage_proto::SenderKeyRecordStructure::decode(buf)
+        Self::deserialize_with_limits(buf, &libsignal_core::parse_limits())
+    }
+
+    /// Deserializes a record, subject to `limits` rather than the process-wide ones.
+    pub fn deserialize_with_limits(
+        buf: &[u8],
+        limits: &ParseLimits,
+    ) -> Result<SenderKeyRecord, SignalProtocolError> {
+        let buf = strip_record_header(buf)?;
+        // Count the repeated fields before decoding allocates them.
+        let mut states = 0;
+        for state in embedded_fields(&buf, RECORD_SENDER_KEY_STATES) {
+            states += 1;
+            check_collection_count(
+                limits,
+                "sender message keys",
+                embedded_fields(state, STATE_SENDER_MESSAGE_KEYS).count(),
+            )
+            .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
+        }
+        check_collection_count(limits, "sender key states", states)
+            .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
+        let skr = storage_proto::SenderKeyRecordStructure::decode_with_limits(&buf, limits)
             .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
 
         let mut states = VecDeque::with_capacity(skr.sender_key_states.len());
@@ -362,6 +393,13 @@ impl SenderKeyRecord {
     pub fn serialize(&self) -> Result<Vec<u8>, SignalProtocolError> {
         Ok(self.as_protobuf().encode_to_vec())
     }
+
+    /// Rewrites a serialized sender key record in the current format, with a version header.
+    ///
+    /// See [`crate::record_version`].
+    pub fn migrate_to_current(buf: &[u8]) -> Result<Vec<u8>, SignalProtocolError> {
+        Ok(add_record_header(Self::deserialize(buf)?.serialize()?))
+    }
 }
 
 #[cfg(test)]
@@ -636,3 +674,123 @@ mod sender_chain_key_iteration_tests {
         );
     }
 }
+
+/// Which recipients have been sent the sender key chain currently in use for a distribution id.
+///
+/// Maintained by [`mark_sender_key_distributed`](crate::mark_sender_key_distributed) and
+/// consulted by
+/// [`sender_key_recipients_needing_distribution`](crate::sender_key_recipients_needing_distribution).
+/// The record is tied to a single chain; once the sender's key is replaced, every recipient
+/// needs a new [`SenderKeyDistributionMessage`](crate::SenderKeyDistributionMessage) again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SenderKeyDistributionRecord {
+    chain_id: u32,
+    recipients: HashSet<ProtocolAddress>,
+}
+
+impl SenderKeyDistributionRecord {
+    /// Creates a record for `chain_id` that nobody has received yet.
+    ///
+    /// Stores that persist records themselves can rebuild one from [`Self::chain_id`] and
+    /// [`Self::recipients`] by following this with [`Self::add_recipient`].
+    pub fn new(chain_id: u32) -> Self {
+        Self {
+            chain_id,
+            recipients: HashSet::new(),
+        }
+    }
+
+    /// The sender key chain this record describes.
+    pub fn chain_id(&self) -> u32 {
+        self.chain_id
+    }
+
+    /// The recipients known to have been sent this chain, in no particular order.
+    pub fn recipients(&self) -> impl Iterator<Item = &ProtocolAddress> {
+        self.recipients.iter()
+    }
+
+    pub fn contains_recipient(&self, recipient: &ProtocolAddress) -> bool {
+        self.recipients.contains(recipient)
+    }
+
+    pub fn add_recipient(&mut self, recipient: ProtocolAddress) {
+        self.recipients.insert(recipient);
+    }
+
+    /// Returns `true` if `recipient` was present.
+    pub fn remove_recipient(&mut self, recipient: &ProtocolAddress) -> bool {
+        self.recipients.remove(recipient)
+    }
+
+    pub fn deserialize(buf: &[u8]) -> Result<Self, SignalProtocolError> {
+        let buf = strip_record_header(buf)?;
+        check_collection_count(
+            &libsignal_core::parse_limits(),
+            "sender key recipients",
+            embedded_fields(&buf, DISTRIBUTION_RECORD_RECIPIENTS).count(),
+        )
+        .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
+        let record = storage_proto::SenderKeyDistributionRecordStructure::decode_limited(&buf)
+            .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?;
+
+        Ok(Self {
+            chain_id: record.chain_id,
+            recipients: record
+                .recipients
+                .into_iter()
+                .map(|recipient| ProtocolAddress::new(recipient.name, recipient.device_id.into()))
+                .collect(),
+        })
+    }
+
+    /// Serializes the record so that a store can persist it.
+    ///
+    /// Recipients are written in a fixed order, so equal records serialize identically.
+    pub fn serialize(&self) -> Result<Vec<u8>, SignalProtocolError> {
+        let recipients = self
+            .recipients
+            .iter()
+            .sorted_by_key(|recipient| (recipient.name(), recipient.device_id()))
+            .map(
+                |recipient| storage_proto::sender_key_distribution_record_structure::Recipient {
+                    name: recipient.name().to_owned(),
+                    device_id: recipient.device_id().into(),
+                },
+            )
+            .collect();
+        Ok(storage_proto::SenderKeyDistributionRecordStructure {
+            chain_id: self.chain_id,
+            recipients,
+        }
+        .encode_to_vec())
+    }
+}
+
+#[cfg(test)]
+mod sender_key_distribution_record_tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_serialization() {
+        let mut record = SenderKeyDistributionRecord::new(42);
+        record.add_recipient(ProtocolAddress::new("+14151111111".to_owned(), 1.into()));
+        record.add_recipient(ProtocolAddress::new("+14151111111".to_owned(), 2.into()));
+        record.add_recipient(ProtocolAddress::new("+14152222222".to_owned(), 1.into()));
+
+        let serialized = record.serialize().expect("can serialize");
+        let deserialized =
+            SenderKeyDistributionRecord::deserialize(&serialized).expect("can deserialize");
+        assert_eq!(deserialized, record);
+        assert_eq!(
+            deserialized.serialize().expect("can serialize"),
+            serialized,
+            "serialization should not depend on insertion order"
+        );
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(SenderKeyDistributionRecord::deserialize(&[0xff; 4]).is_err());
+    }
+}
@@ -10,9 +10,9 @@ use rand::{CryptoRng, Rng};
 use crate::ratchet::{AliceSignalProtocolParameters, BobSignalProtocolParameters};
 use crate::state::GenericSignedPreKey;
 use crate::{
-    kem, ratchet, Direction, IdentityKeyStore, KeyPair, KyberPreKeyId, KyberPreKeyStore,
+    kem, ratchet, DeviceId, Direction, IdentityKeyStore, KeyPair, KyberPreKeyId, KyberPreKeyStore,
     PreKeyBundle, PreKeyId, PreKeySignalMessage, PreKeyStore, ProtocolAddress, Result,
-    SessionRecord, SessionStore, SignalProtocolError, SignedPreKeyStore,
+    SessionRecord, SessionStats, SessionStore, SignalProtocolError, SignedPreKeyStore,
 };
 
 #[derive(Default)]
@@ -237,3 +237,41 @@ pub async fn process_prekey_bundle<R: Rng + CryptoRng>(
 
     Ok(())
 }
+
+/// Archives the current session for `name` on each of `device_ids`.
+///
+/// Devices without a session are skipped. This is typically used when a recipient's identity key
+/// changes, so that the next message to any of their devices starts a new session.
+pub async fn archive_sessions_for_devices(
+    name: &str,
+    device_ids: &[DeviceId],
+    session_store: &mut dyn SessionStore,
+) -> Result<()> {
+    let sessions = session_store
+        .load_sessions_for_devices(name, device_ids)
+        .await?;
+    for (device_id, mut session) in sessions {
+        session.archive_current_state()?;
+        session_store
+            .store_session(&ProtocolAddress::new(name.to_owned(), device_id), &session)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Summarizes the current session with `remote_address`.
+///
+/// Returns `None` if there's no session with that address, or if its current session has been
+/// archived.
+pub async fn session_stats(
+    remote_address: &ProtocolAddress,
+    session_store: &dyn SessionStore,
+) -> Result<Option<SessionStats>> {
+    let Some(session) = session_store.load_session(remote_address).await? else {
+        return Ok(None);
+    };
+    if session.session_state().is_none() {
+        return Ok(None);
+    }
+    session.stats().map(Some)
+}
@@ -13,7 +13,8 @@ use crate::state::{InvalidSessionError, SessionState};
 use crate::{
     session, CiphertextMessage, CiphertextMessageType, Direction, IdentityKeyStore, KeyPair,
     KyberPayload, KyberPreKeyStore, PreKeySignalMessage, PreKeyStore, ProtocolAddress, PublicKey,
-    Result, SessionRecord, SessionStore, SignalMessage, SignalProtocolError, SignedPreKeyStore,
+    ReplayCache, Result, SessionRecord, SessionStore, SignalMessage, SignalProtocolError,
+    SignedPreKeyStore,
 };
 
 pub async fn message_encrypt(
@@ -123,6 +124,7 @@ pub async fn message_encrypt(
     };
 
     session_state.set_sender_chain_key(&chain_key.next_chain_key());
+    session_state.record_message_encrypted(chain_key.index(), now);
 
     // XXX why is this check after everything else?!!
     if !identity_store
@@ -199,6 +201,71 @@ pub async fn message_decrypt_prekey<R: Rng + CryptoRng>(
     kyber_pre_key_store: &mut dyn KyberPreKeyStore,
     csprng: &mut R,
 ) -> Result<Vec<u8>> {
+    decrypt_prekey_impl(
+        ciphertext,
+        remote_address,
+        session_store,
+        identity_store,
+        pre_key_store,
+        signed_pre_key_store,
+        kyber_pre_key_store,
+        None,
+        csprng,
+    )
+    .await
+}
+
+/// Like [`message_decrypt_prekey`], but rejects messages already recorded in `replay_cache`.
+///
+/// A replayed message fails with [`SignalProtocolError::ReplayedMessage`], and none of the stores
+/// are touched. The message is only added to the cache once it has been decrypted and the session
+/// saved, so forged messages can't be used to fill it up, and a message whose processing fails
+/// partway through can still be retried.
+#[allow(clippy::too_many_arguments)]
+pub async fn message_decrypt_prekey_with_replay_cache<R: Rng + CryptoRng>(
+    ciphertext: &PreKeySignalMessage,
+    remote_address: &ProtocolAddress,
+    session_store: &mut dyn SessionStore,
+    identity_store: &mut dyn IdentityKeyStore,
+    pre_key_store: &mut dyn PreKeyStore,
+    signed_pre_key_store: &dyn SignedPreKeyStore,
+    kyber_pre_key_store: &mut dyn KyberPreKeyStore,
+    replay_cache: &mut dyn ReplayCache,
+    csprng: &mut R,
+) -> Result<Vec<u8>> {
+    decrypt_prekey_impl(
+        ciphertext,
+        remote_address,
+        session_store,
+        identity_store,
+        pre_key_store,
+        signed_pre_key_store,
+        kyber_pre_key_store,
+        Some(replay_cache),
+        csprng,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn decrypt_prekey_impl<R: Rng + CryptoRng>(
+    ciphertext: &PreKeySignalMessage,
+    remote_address: &ProtocolAddress,
+    session_store: &mut dyn SessionStore,
+    identity_store: &mut dyn IdentityKeyStore,
+    pre_key_store: &mut dyn PreKeyStore,
+    signed_pre_key_store: &dyn SignedPreKeyStore,
+    kyber_pre_key_store: &mut dyn KyberPreKeyStore,
+    replay_cache: Option<&mut dyn ReplayCache>,
+    csprng: &mut R,
+) -> Result<Vec<u8>> {
+    check_replay_cache(
+        replay_cache.as_deref(),
+        remote_address,
+        ciphertext.message(),
+    )
+    .await?;
+
     let mut session_record = session_store
         .load_session(remote_address)
         .await?
@@ -246,6 +313,8 @@ pub async fn message_decrypt_prekey<R: Rng + CryptoRng>(
         .store_session(remote_address, &session_record)
         .await?;
 
+    record_in_replay_cache(replay_cache, remote_address, ciphertext.message()).await;
+
     if let Some(pre_key_id) = pre_key_used.pre_key_id {
         pre_key_store.remove_pre_key(pre_key_id).await?;
     }
@@ -266,6 +335,52 @@ pub async fn message_decrypt_signal<R: Rng + CryptoRng>(
     identity_store: &mut dyn IdentityKeyStore,
     csprng: &mut R,
 ) -> Result<Vec<u8>> {
+    decrypt_signal_impl(
+        ciphertext,
+        remote_address,
+        session_store,
+        identity_store,
+        None,
+        csprng,
+    )
+    .await
+}
+
+/// Like [`message_decrypt_signal`], but rejects messages already recorded in `replay_cache`.
+///
+/// A replayed message fails with [`SignalProtocolError::ReplayedMessage`], and none of the stores
+/// are touched. The message is only added to the cache once it has been decrypted, the sender's
+/// identity checked, and the session saved, so forged messages can't be used to fill it up, and a
+/// message whose processing fails partway through can still be retried.
+pub async fn message_decrypt_signal_with_replay_cache<R: Rng + CryptoRng>(
+    ciphertext: &SignalMessage,
+    remote_address: &ProtocolAddress,
+    session_store: &mut dyn SessionStore,
+    identity_store: &mut dyn IdentityKeyStore,
+    replay_cache: &mut dyn ReplayCache,
+    csprng: &mut R,
+) -> Result<Vec<u8>> {
+    decrypt_signal_impl(
+        ciphertext,
+        remote_address,
+        session_store,
+        identity_store,
+        Some(replay_cache),
+        csprng,
+    )
+    .await
+}
+
+pub(crate) async fn decrypt_signal_impl<R: Rng + CryptoRng>(
+    ciphertext: &SignalMessage,
+    remote_address: &ProtocolAddress,
+    session_store: &mut dyn SessionStore,
+    identity_store: &mut dyn IdentityKeyStore,
+    replay_cache: Option<&mut dyn ReplayCache>,
+    csprng: &mut R,
+) -> Result<Vec<u8>> {
+    check_replay_cache(replay_cache.as_deref(), remote_address, ciphertext).await?;
+
     let mut session_record = session_store
         .load_session(remote_address)
         .await?
@@ -312,9 +427,70 @@ pub async fn message_decrypt_signal<R: Rng + CryptoRng>(
         .store_session(remote_address, &session_record)
         .await?;
 
+    record_in_replay_cache(replay_cache, remote_address, ciphertext).await;
+
     Ok(ptext)
 }
 
+/// Fails with [`SignalProtocolError::ReplayedMessage`] if `ciphertext` is already in the cache.
+///
+/// This happens before any of the stores are touched, so a replay leaves them as they were.
+async fn check_replay_cache(
+    replay_cache: Option<&dyn ReplayCache>,
+    remote_address: &ProtocolAddress,
+    ciphertext: &SignalMessage,
+) -> Result<()> {
+    let Some(replay_cache) = replay_cache else {
+        return Ok(());
+    };
+    let counter = ciphertext.counter();
+    if replay_cache
+        .contains(remote_address, ciphertext.sender_ratchet_key(), counter)
+        .await?
+    {
+        log::info!(
+            "{} Replayed message for counter: {}",
+            remote_address,
+            counter
+        );
+        return Err(SignalProtocolError::ReplayedMessage(counter));
+    }
+    Ok(())
+}
+
+/// Adds `ciphertext` to the cache, once the session it advanced has been saved.
+///
+/// A message is only recorded once it has been fully processed, so a failure partway through
+/// doesn't keep the sender from retrying it.
+///
+/// Failing to record the message is only logged. By this point the advanced session has been
+/// saved, so failing the decryption would lose the plaintext for good: the sender's retry would no
+/// longer decrypt.
+async fn record_in_replay_cache(
+    replay_cache: Option<&mut dyn ReplayCache>,
+    remote_address: &ProtocolAddress,
+    ciphertext: &SignalMessage,
+) {
+    let Some(replay_cache) = replay_cache else {
+        return;
+    };
+    if let Err(e) = replay_cache
+        .insert(
+            remote_address,
+            ciphertext.sender_ratchet_key(),
+            ciphertext.counter(),
+        )
+        .await
+    {
+        log::warn!(
+            "{} failed to record message with counter {} in replay cache: {}",
+            remote_address,
+            ciphertext.counter(),
+            e
+        );
+    }
+}
+
 fn create_decryption_failure_log(
     remote_address: &ProtocolAddress,
     mut errs: &[SignalProtocolError],
@@ -641,6 +817,7 @@ fn decrypt_message_with_state<R: Rng + CryptoRng>(
     };
 
     state.clear_unacknowledged_pre_key_message();
+    state.record_message_decrypted();
 
     Ok(ptext)
 }
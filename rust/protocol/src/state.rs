@@ -6,12 +6,15 @@
 mod bundle;
 mod kyber_prekey;
 mod prekey;
+mod record_version;
 mod session;
 mod signed_prekey;
 
 pub use bundle::{PreKeyBundle, PreKeyBundleContent};
 pub use kyber_prekey::{KyberPreKeyId, KyberPreKeyRecord};
 pub use prekey::{PreKeyId, PreKeyRecord};
-pub use session::SessionRecord;
+pub(crate) use record_version::{add_record_header, strip_record_header};
+pub use record_version::{record_version, CURRENT_RECORD_VERSION};
 pub(crate) use session::{InvalidSessionError, SessionState};
+pub use session::{SessionRecord, SessionStats};
 pub use signed_prekey::{GenericSignedPreKey, SignedPreKeyId, SignedPreKeyRecord};
@@ -8,6 +8,8 @@ use std::fmt;
 use prost::Message;
 
 use crate::proto::storage::PreKeyRecordStructure;
+use crate::proto::DecodeLimited;
+use crate::state::{add_record_header, strip_record_header};
 use crate::{KeyPair, PrivateKey, PublicKey, Result, SignalProtocolError};
 
 /// A unique identifier selecting among this client's known pre-keys.
@@ -52,7 +54,7 @@ impl PreKeyRecord {
 
     pub fn deserialize(data: &[u8]) -> Result<Self> {
         Ok(Self {
-            pre_key: PreKeyRecordStructure::decode(data)
+            pre_key: PreKeyRecordStructure::decode_limited(&strip_record_header(data)?)
                 .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?,
         })
     }
@@ -76,4 +78,11 @@ impl PreKeyRecord {
     pub fn serialize(&self) -> Result<Vec<u8>> {
         Ok(self.pre_key.encode_to_vec())
     }
+
+    /// Rewrites a serialized pre-key record in the current format, with a version header.
+    ///
+    /// See [`crate::record_version`].
+    pub fn migrate_to_current(data: &[u8]) -> Result<Vec<u8>> {
+        Ok(add_record_header(Self::deserialize(data)?.serialize()?))
+    }
 }
@@ -0,0 +1,125 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Version headers for serialized records.
+//!
+//! Session, pre-key, signed pre-key, Kyber pre-key, and sender key records were originally stored
+//! as bare protobuf messages, which left protobuf's field compatibility rules as the only way to
+//! evolve them. Versioned records start with a two-byte header: a zero byte followed by the
+//! record's format version. A protobuf message can never start with a zero byte (field number 0 is
+//! reserved), so records without a header are still recognized, and older versions of this library
+//! reject headered records as malformed rather than misreading them.
+//!
+//! The versions so far:
+//!
+//! - **0**: no header; the record is a bare protobuf message.
+//! - **1**: the same protobuf message, preceded by the header.
+//!
+//! Every version up to [`CURRENT_RECORD_VERSION`] can be read, but records' `serialize` methods
+//! still write version 0, so that an app can roll back to a version of this library that predates
+//! the header without losing its stored records. Writing the header is opt-in, through each
+//! record's `migrate_to_current`; once all supported versions of the library can read it, it can
+//! become the default.
+//!
+//! When the format changes, bump [`CURRENT_RECORD_VERSION`] and add a step to
+//! [`migrate_record_body`] that converts the previous version's body into the new one.
+
+use std::borrow::Cow;
+
+use crate::{Result, SignalProtocolError};
+
+/// The newest record format version understood by this version of the library.
+pub const CURRENT_RECORD_VERSION: u8 = 1;
+
+const RECORD_HEADER_MARKER: u8 = 0;
+
+/// Returns the format version of a serialized record, without otherwise parsing it.
+///
+/// Records written before version headers were introduced report version 0. A version greater than
+/// [`CURRENT_RECORD_VERSION`] means the record was written by a newer version of the library and
+/// can't be read by this one.
+pub fn record_version(data: &[u8]) -> Result<u8> {
+    match data {
+        [RECORD_HEADER_MARKER, 0, ..] | [RECORD_HEADER_MARKER] => {
+            Err(SignalProtocolError::InvalidProtobufEncoding)
+        }
+        [RECORD_HEADER_MARKER, version, ..] => Ok(*version),
+        _ => Ok(0),
+    }
+}
+
+/// Prepends the current version header to a record's protobuf encoding.
+pub(crate) fn add_record_header(body: Vec<u8>) -> Vec<u8> {
+    let mut result = Vec::with_capacity(body.len() + 2);
+    result.extend([RECORD_HEADER_MARKER, CURRENT_RECORD_VERSION]);
+    result.extend(body);
+    result
+}
+
+/// Strips the version header from a serialized record, migrating its body to the current version's
+/// protobuf encoding if necessary.
+pub(crate) fn strip_record_header(data: &[u8]) -> Result<Cow<'_, [u8]>> {
+    let version = record_version(data)?;
+    if version > CURRENT_RECORD_VERSION {
+        return Err(SignalProtocolError::UnrecognizedRecordVersion(version));
+    }
+    let body = if version == 0 { data } else { &data[2..] };
+    migrate_record_body(version, Cow::Borrowed(body))
+}
+
+/// Converts the body of a record of the given `version` into the current version's encoding.
+///
+/// Each step converts from one version to the next, so that old records go through every migration
+/// in order.
+fn migrate_record_body(version: u8, body: Cow<'_, [u8]>) -> Result<Cow<'_, [u8]>> {
+    match version {
+        // Version 1 only added the header; the protobuf message is unchanged.
+        0 => migrate_record_body(1, body),
+        CURRENT_RECORD_VERSION => Ok(body),
+        _ => Err(SignalProtocolError::UnrecognizedRecordVersion(version)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[test]
+    fn legacy_records_have_no_header() {
+        for legacy in [&[][..], &[0x0a, 0x00], &[0x12, 0x01, 0xff]] {
+            assert_eq!(record_version(legacy).expect("valid"), 0);
+            assert_eq!(&*strip_record_header(legacy).expect("valid"), legacy);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let record = add_record_header(vec![0x0a, 0x00]);
+        assert_eq!(record, [0x00, CURRENT_RECORD_VERSION, 0x0a, 0x00]);
+        assert_eq!(
+            record_version(&record).expect("valid"),
+            CURRENT_RECORD_VERSION
+        );
+        assert_eq!(&*strip_record_header(&record).expect("valid"), [0x0a, 0x00]);
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        assert_matches!(
+            strip_record_header(&[0x00]),
+            Err(SignalProtocolError::InvalidProtobufEncoding)
+        );
+        assert_matches!(
+            strip_record_header(&[0x00, 0x00, 0x0a, 0x00]),
+            Err(SignalProtocolError::InvalidProtobufEncoding)
+        );
+        assert_matches!(
+            strip_record_header(&[0x00, CURRENT_RECORD_VERSION + 1, 0x0a, 0x00]),
+            Err(SignalProtocolError::UnrecognizedRecordVersion(v)) if v == CURRENT_RECORD_VERSION + 1
+        );
+    }
+}
@@ -10,9 +10,15 @@ use prost::Message;
 use subtle::ConstantTimeEq;
 
 use crate::proto::storage::{session_structure, RecordStructure, SessionStructure};
+use crate::proto::{check_collection_count, embedded_fields, DecodeLimited};
+use crate::protocol::CIPHERTEXT_MESSAGE_CURRENT_VERSION;
 use crate::ratchet::{ChainKey, MessageKeys, RootKey};
-use crate::state::{KyberPreKeyId, PreKeyId, SignedPreKeyId};
-use crate::{consts, kem, IdentityKey, KeyPair, PrivateKey, PublicKey, SignalProtocolError};
+use crate::state::{
+    add_record_header, strip_record_header, KyberPreKeyId, PreKeyId, SignedPreKeyId,
+};
+use crate::{
+    consts, kem, IdentityKey, KeyPair, ParseLimits, PrivateKey, PublicKey, SignalProtocolError,
+};
 
 /// A distinct error type to keep from accidentally propagating deserialization errors.
 #[derive(Debug)]
@@ -117,6 +123,9 @@ impl SessionState {
                 remote_registration_id: 0,
                 local_registration_id: 0,
                 alice_base_key: alice_base_key.serialize().into_vec(),
+                messages_encrypted: 0,
+                messages_decrypted: 0,
+                last_ratchet_step_time: 0,
             },
         }
     }
@@ -529,6 +538,9 @@ impl SessionState {
             remote_registration_id: _remote_registration_id,
             local_registration_id: _local_registration_id,
             alice_base_key: _alice_base_key,
+            messages_encrypted: _messages_encrypted,
+            messages_decrypted: _messages_decrypted,
+            last_ratchet_step_time: _last_ratchet_step_time,
         } = &self.session;
         // ####### IMPORTANT #######
         // Don't forget to clean up new pending fields.
@@ -553,6 +565,39 @@ impl SessionState {
         self.session.local_registration_id
     }
 
+    /// Records that a message was encrypted with the sender chain key at `chain_index`.
+    ///
+    /// The first message on a sender chain is the first use of a new ratchet key, so that's when
+    /// the ratchet step is considered to have happened.
+    pub(crate) fn record_message_encrypted(&mut self, chain_index: u32, now: SystemTime) {
+        self.session.messages_encrypted = self.session.messages_encrypted.saturating_add(1);
+        if chain_index == 0 {
+            self.session.last_ratchet_step_time = now
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis()
+                .try_into()
+                .unwrap_or(u64::MAX);
+        }
+    }
+
+    pub(crate) fn record_message_decrypted(&mut self) {
+        self.session.messages_decrypted = self.session.messages_decrypted.saturating_add(1);
+    }
+
+    pub(crate) fn stats(&self) -> Result<SessionStats, InvalidSessionError> {
+        let last_ratchet_step = match self.session.last_ratchet_step_time {
+            0 => None,
+            millis => Some(SystemTime::UNIX_EPOCH + Duration::from_millis(millis)),
+        };
+        Ok(SessionStats {
+            messages_encrypted: self.session.messages_encrypted,
+            messages_decrypted: self.session.messages_decrypted,
+            last_ratchet_step,
+            post_quantum: self.session_version()? >= CIPHERTEXT_MESSAGE_CURRENT_VERSION.into(),
+        })
+    }
+
     pub(crate) fn get_kyber_ciphertext(&self) -> Option<&Vec<u8>> {
         self.session
             .pending_kyber_pre_key
@@ -579,10 +624,55 @@ impl From<&SessionState> for SessionStructure {
     }
 }
 
+/// A summary of the activity on a session, for "chat details" security screens and diagnostics.
+///
+/// Counts only cover the current session, not any archived ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionStats {
+    /// Messages encrypted on this session.
+    pub messages_encrypted: u32,
+    /// Messages successfully decrypted on this session.
+    pub messages_decrypted: u32,
+    /// When this device first sent a message using its current ratchet key, if it has sent one.
+    pub last_ratchet_step: Option<SystemTime>,
+    /// Whether the session was established with a post-quantum (PQXDH) key agreement.
+    pub post_quantum: bool,
+}
+
+// Field numbers from storage.proto, for counting repeated fields before decoding them.
+const RECORD_CURRENT_SESSION: u32 = 1;
+const RECORD_PREVIOUS_SESSIONS: u32 = 2;
+const SESSION_RECEIVER_CHAINS: u32 = 7;
+const CHAIN_MESSAGE_KEYS: u32 = 4;
+
+/// Checks the repeated fields of an encoded session against `limits`, before decoding allocates
+/// them.
+///
+/// A session may be encoded in several parts, which the decoder merges.
+fn check_session_limits<'a>(
+    parts: impl Iterator<Item = &'a [u8]>,
+    limits: &ParseLimits,
+) -> Result<(), InvalidSessionError> {
+    let mut receiver_chains = 0;
+    for chain in parts.flat_map(|part| embedded_fields(part, SESSION_RECEIVER_CHAINS)) {
+        receiver_chains += 1;
+        check_collection_count(
+            limits,
+            "message keys",
+            embedded_fields(chain, CHAIN_MESSAGE_KEYS).count(),
+        )
+        .map_err(|_| InvalidSessionError("too many message keys"))?;
+    }
+    check_collection_count(limits, "receiver chains", receiver_chains)
+        .map_err(|_| InvalidSessionError("too many receiver chains"))
+}
+
 #[derive(Clone)]
 pub struct SessionRecord {
     current_session: Option<SessionState>,
     previous_sessions: Vec<Vec<u8>>,
+    /// Applied to the previous sessions, which are only decoded when needed.
+    limits: ParseLimits,
 }
 
 impl SessionRecord {
@@ -590,6 +680,7 @@ impl SessionRecord {
         Self {
             current_session: None,
             previous_sessions: Vec::new(),
+            limits: libsignal_core::parse_limits(),
         }
     }
 
@@ -597,16 +688,37 @@ impl SessionRecord {
         Self {
             current_session: Some(state),
             previous_sessions: Vec::new(),
+            limits: libsignal_core::parse_limits(),
         }
     }
 
+    /// Deserializes a record, subject to the process-wide [`ParseLimits`].
     pub fn deserialize(bytes: &[u8]) -> Result<Self, SignalProtocolError> {
-        let record = RecordStructure::decode(bytes)
+        Self::deserialize_with_limits(bytes, &libsignal_core::parse_limits())
+    }
+
+    /// Deserializes a record, subject to `limits` rather than the process-wide ones.
+    ///
+    /// The limits also apply when the record's previous sessions are decoded later.
+    pub fn deserialize_with_limits(
+        bytes: &[u8],
+        limits: &ParseLimits,
+    ) -> Result<Self, SignalProtocolError> {
+        let bytes = strip_record_header(bytes)?;
+        check_collection_count(
+            limits,
+            "previous sessions",
+            embedded_fields(&bytes, RECORD_PREVIOUS_SESSIONS).count(),
+        )
+        .map_err(|_| InvalidSessionError("too many previous sessions"))?;
+        check_session_limits(embedded_fields(&bytes, RECORD_CURRENT_SESSION), limits)?;
+        let record = RecordStructure::decode_with_limits(&bytes, limits)
             .map_err(|_| InvalidSessionError("failed to decode session record protobuf"))?;
 
         Ok(Self {
             current_session: record.current_session.map(|s| s.into()),
             previous_sessions: record.previous_sessions,
+            limits: *limits,
         })
     }
 
@@ -653,9 +765,10 @@ impl SessionRecord {
         &self,
     ) -> impl ExactSizeIterator<Item = Result<SessionState, InvalidSessionError>> + '_ {
         self.previous_sessions.iter().map(|bytes| {
-            Ok(SessionStructure::decode(&bytes[..])
-                .map_err(|_| InvalidSessionError("failed to decode previous session protobuf"))?
-                .into())
+            check_session_limits(std::iter::once(&bytes[..]), &self.limits)?;
+            let session = SessionStructure::decode_with_limits(&bytes[..], &self.limits)
+                .map_err(|_| InvalidSessionError("failed to decode previous session protobuf"))?;
+            Ok(session.into())
         })
     }
 
@@ -705,6 +818,13 @@ impl SessionRecord {
         Ok(record.encode_to_vec())
     }
 
+    /// Rewrites a serialized session record in the current format, with a version header.
+    ///
+    /// See [`crate::record_version`].
+    pub fn migrate_to_current(bytes: &[u8]) -> Result<Vec<u8>, SignalProtocolError> {
+        Ok(add_record_header(Self::deserialize(bytes)?.serialize()?))
+    }
+
     pub fn remote_registration_id(&self) -> Result<u32, SignalProtocolError> {
         Ok(self
             .session_state()
@@ -816,6 +936,13 @@ impl SessionRecord {
         }
     }
 
+    pub fn stats(&self) -> Result<SessionStats, SignalProtocolError> {
+        Ok(self
+            .session_state()
+            .ok_or_else(|| SignalProtocolError::InvalidState("stats", "No current session".into()))?
+            .stats()?)
+    }
+
     pub fn get_kyber_ciphertext(&self) -> Result<Option<&Vec<u8>>, SignalProtocolError> {
         Ok(self
             .session_state()
@@ -9,6 +9,8 @@ use std::fmt;
 use prost::Message;
 
 use crate::proto::storage::SignedPreKeyRecordStructure;
+use crate::proto::DecodeLimited;
+use crate::state::{add_record_header, strip_record_header};
 use crate::{kem, KeyPair, PrivateKey, PublicKey, Result, SignalProtocolError, Timestamp};
 
 /// A unique identifier selecting among this client's known signed pre-keys.
@@ -92,11 +94,21 @@ pub trait GenericSignedPreKey {
         Self: Sized,
     {
         Ok(Self::from_storage(
-            SignedPreKeyRecordStructure::decode(data)
+            SignedPreKeyRecordStructure::decode_limited(&strip_record_header(data)?)
                 .map_err(|_| SignalProtocolError::InvalidProtobufEncoding)?,
         ))
     }
 
+    /// Rewrites a serialized record in the current format, with a version header.
+    ///
+    /// See [`crate::record_version`].
+    fn migrate_to_current(data: &[u8]) -> Result<Vec<u8>>
+    where
+        Self: Sized,
+    {
+        Ok(add_record_header(Self::deserialize(data)?.serialize()?))
+    }
+
     fn id(&self) -> Result<Self::Id> {
         Ok(self.get_storage().id.into())
     }
@@ -8,16 +8,17 @@
 //! These implementations are purely in-memory, and therefore most likely useful for testing.
 
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use uuid::Uuid;
 
 use crate::storage::traits;
 use crate::{
-    IdentityKey, IdentityKeyPair, KyberPreKeyId, KyberPreKeyRecord, PreKeyId, PreKeyRecord,
-    ProtocolAddress, Result, SenderKeyRecord, SessionRecord, SignalProtocolError, SignedPreKeyId,
-    SignedPreKeyRecord,
+    DeviceId, IdentityKey, IdentityKeyPair, KyberPreKeyId, KyberPreKeyRecord, PreKeyId,
+    PreKeyRecord, ProtocolAddress, PublicKey, Result, SenderKeyDistributionRecord, SenderKeyRecord,
+    SessionRecord, SignalProtocolError, SignedPreKeyId, SignedPreKeyRecord,
 };
 
 /// Reference implementation of [traits::IdentityKeyStore].
@@ -295,6 +296,22 @@ impl traits::SessionStore for InMemSessionStore {
         }
     }
 
+    async fn load_sessions_for_devices(
+        &self,
+        name: &str,
+        device_ids: &[DeviceId],
+    ) -> Result<Vec<(DeviceId, SessionRecord)>> {
+        Ok(device_ids
+            .iter()
+            .filter_map(|&device_id| {
+                let session = self
+                    .sessions
+                    .get(&ProtocolAddress::new(name.to_owned(), device_id))?;
+                Some((device_id, session.clone()))
+            })
+            .collect())
+    }
+
     async fn store_session(
         &mut self,
         address: &ProtocolAddress,
@@ -311,6 +328,7 @@ pub struct InMemSenderKeyStore {
     // We use Cow keys in order to store owned values but compare to referenced ones.
     // See https://users.rust-lang.org/t/hashmap-with-tuple-keys/12711/6.
     keys: HashMap<(Cow<'static, ProtocolAddress>, Uuid), SenderKeyRecord>,
+    distributions: HashMap<(Cow<'static, ProtocolAddress>, Uuid), SenderKeyDistributionRecord>,
 }
 
 impl InMemSenderKeyStore {
@@ -318,6 +336,7 @@ impl InMemSenderKeyStore {
     pub fn new() -> Self {
         Self {
             keys: HashMap::new(),
+            distributions: HashMap::new(),
         }
     }
 }
@@ -353,6 +372,101 @@ impl traits::SenderKeyStore for InMemSenderKeyStore {
             .get(&(Cow::Borrowed(sender), distribution_id))
             .cloned())
     }
+
+    async fn store_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+        record: &SenderKeyDistributionRecord,
+    ) -> Result<()> {
+        self.distributions.insert(
+            (Cow::Owned(sender.clone()), distribution_id),
+            record.clone(),
+        );
+        Ok(())
+    }
+
+    async fn load_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+    ) -> Result<Option<SenderKeyDistributionRecord>> {
+        Ok(self
+            .distributions
+            .get(&(Cow::Borrowed(sender), distribution_id))
+            .cloned())
+    }
+}
+
+type ReplayCacheKey = (ProtocolAddress, Box<[u8]>, u32);
+
+/// Reference implementation of [traits::ReplayCache].
+///
+/// Entries are forgotten once they are older than the `ttl` passed to [`Self::new`].
+#[derive(Clone)]
+pub struct InMemReplayCache {
+    ttl: Duration,
+    seen: HashMap<ReplayCacheKey, Instant>,
+    /// The keys in `seen`, oldest first, so expired entries can be dropped without a full scan.
+    insertion_order: VecDeque<(Instant, ReplayCacheKey)>,
+}
+
+impl InMemReplayCache {
+    /// Create an empty replay cache whose entries expire after `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            seen: HashMap::new(),
+            insertion_order: VecDeque::new(),
+        }
+    }
+
+    fn is_live(&self, inserted_at: Instant, now: Instant) -> bool {
+        now.saturating_duration_since(inserted_at) < self.ttl
+    }
+
+    fn remove_expired(&mut self, now: Instant) {
+        while let Some((inserted_at, _)) = self.insertion_order.front() {
+            if self.is_live(*inserted_at, now) {
+                break;
+            }
+            let (inserted_at, key) = self.insertion_order.pop_front().expect("just checked");
+            // The key may have been inserted again since, in which case it's still live.
+            if self.seen.get(&key) == Some(&inserted_at) {
+                self.seen.remove(&key);
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl traits::ReplayCache for InMemReplayCache {
+    async fn contains(
+        &self,
+        address: &ProtocolAddress,
+        sender_ratchet_key: &PublicKey,
+        counter: u32,
+    ) -> Result<bool> {
+        let key = (address.clone(), sender_ratchet_key.serialize(), counter);
+        Ok(self
+            .seen
+            .get(&key)
+            .is_some_and(|inserted_at| self.is_live(*inserted_at, Instant::now())))
+    }
+
+    async fn insert(
+        &mut self,
+        address: &ProtocolAddress,
+        sender_ratchet_key: &PublicKey,
+        counter: u32,
+    ) -> Result<()> {
+        let now = Instant::now();
+        self.remove_expired(now);
+        let key = (address.clone(), sender_ratchet_key.serialize(), counter);
+        self.seen.insert(key.clone(), now);
+        self.insertion_order.push_back((now, key));
+        Ok(())
+    }
 }
 
 /// Reference implementation of [traits::ProtocolStore].
@@ -494,6 +608,16 @@ impl traits::SessionStore for InMemSignalProtocolStore {
         self.session_store.load_session(address).await
     }
 
+    async fn load_sessions_for_devices(
+        &self,
+        name: &str,
+        device_ids: &[DeviceId],
+    ) -> Result<Vec<(DeviceId, SessionRecord)>> {
+        self.session_store
+            .load_sessions_for_devices(name, device_ids)
+            .await
+    }
+
     async fn store_session(
         &mut self,
         address: &ProtocolAddress,
@@ -525,6 +649,27 @@ impl traits::SenderKeyStore for InMemSignalProtocolStore {
             .load_sender_key(sender, distribution_id)
             .await
     }
+
+    async fn store_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+        record: &SenderKeyDistributionRecord,
+    ) -> Result<()> {
+        self.sender_key_store
+            .store_sender_key_distribution(sender, distribution_id, record)
+            .await
+    }
+
+    async fn load_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+    ) -> Result<Option<SenderKeyDistributionRecord>> {
+        self.sender_key_store
+            .load_sender_key_distribution(sender, distribution_id)
+            .await
+    }
 }
 
 impl traits::ProtocolStore for InMemSignalProtocolStore {}
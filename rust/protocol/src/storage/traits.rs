@@ -9,12 +9,12 @@ use async_trait::async_trait;
 use uuid::Uuid;
 
 use crate::error::Result;
-use crate::sender_keys::SenderKeyRecord;
+use crate::sender_keys::{SenderKeyDistributionRecord, SenderKeyRecord};
 use crate::state::{
     KyberPreKeyId, KyberPreKeyRecord, PreKeyId, PreKeyRecord, SessionRecord, SignedPreKeyId,
     SignedPreKeyRecord,
 };
-use crate::{IdentityKey, IdentityKeyPair, ProtocolAddress};
+use crate::{DeviceId, IdentityKey, IdentityKeyPair, ProtocolAddress, PublicKey};
 
 // TODO: consider moving this enum into utils.rs?
 /// Each Signal message can be considered to have exactly two participants, a sender and receiver.
@@ -140,6 +140,26 @@ pub trait SessionStore {
         address: &ProtocolAddress,
         record: &SessionRecord,
     ) -> Result<()>;
+
+    /// Look up the sessions for `name` on each of `device_ids`, in order.
+    ///
+    /// Devices without a session are omitted from the result. The default implementation calls
+    /// [`load_session`](Self::load_session) once per device; stores that can fetch all of an
+    /// account's sessions in one query should override it.
+    async fn load_sessions_for_devices(
+        &self,
+        name: &str,
+        device_ids: &[DeviceId],
+    ) -> Result<Vec<(DeviceId, SessionRecord)>> {
+        let mut sessions = Vec::with_capacity(device_ids.len());
+        for &device_id in device_ids {
+            let address = ProtocolAddress::new(name.to_owned(), device_id);
+            if let Some(session) = self.load_session(&address).await? {
+                sessions.push((device_id, session));
+            }
+        }
+        Ok(sessions)
+    }
 }
 
 /// Interface for storing sender key records, allowing multiple keys per user.
@@ -160,6 +180,75 @@ pub trait SenderKeyStore {
         sender: &ProtocolAddress,
         distribution_id: Uuid,
     ) -> Result<Option<SenderKeyRecord>>;
+
+    /// Assign `record` to the distribution-tracking entry for `(sender, distribution_id)`.
+    ///
+    /// Tracking is optional: the default implementation discards the record, in which case
+    /// [`sender_key_recipients_needing_distribution`](crate::sender_key_recipients_needing_distribution)
+    /// will report every recipient as needing the sender key.
+    async fn store_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+        record: &SenderKeyDistributionRecord,
+    ) -> Result<()> {
+        let _ = (sender, distribution_id, record);
+        Ok(())
+    }
+
+    /// Look up the distribution-tracking entry for `(sender, distribution_id)`.
+    ///
+    /// The default implementation never has one; stores that override
+    /// [`store_sender_key_distribution`](Self::store_sender_key_distribution) should override
+    /// this too.
+    async fn load_sender_key_distribution(
+        &mut self,
+        sender: &ProtocolAddress,
+        distribution_id: Uuid,
+    ) -> Result<Option<SenderKeyDistributionRecord>> {
+        let _ = (sender, distribution_id);
+        Ok(None)
+    }
+}
+
+/// Interface for an optional record of messages that have already been decrypted.
+///
+/// A session normally rejects a repeated message by itself, because the message keys for each
+/// counter are discarded once they're used. That protection lives in the session record, though,
+/// so it is lost if the record is deleted or replaced; for example, a replayed
+/// [`PreKeySignalMessage`](crate::PreKeySignalMessage) that didn't consume a one-time pre-key can
+/// set up a fresh session and decrypt a second time. Clients that need at-most-once processing can
+/// pass a replay cache to
+/// [`message_decrypt_signal_with_replay_cache`](crate::message_decrypt_signal_with_replay_cache),
+/// [`message_decrypt_prekey_with_replay_cache`](crate::message_decrypt_prekey_with_replay_cache),
+/// or [`sealed_sender_decrypt_with_replay_cache`](crate::sealed_sender_decrypt_with_replay_cache)
+/// to close that gap.
+///
+/// Entries are keyed by the sender's ratchet key as well as the counter, since counters start over
+/// with each new chain. How long entries are kept is up to the implementation; anything older than
+/// the longest period a client would accept a delayed message is safe to forget.
+#[async_trait(?Send)]
+pub trait ReplayCache {
+    /// Return whether the message at `counter` in the chain for `sender_ratchet_key` has already
+    /// been recorded (and has not yet expired).
+    async fn contains(
+        &self,
+        address: &ProtocolAddress,
+        sender_ratchet_key: &PublicKey,
+        counter: u32,
+    ) -> Result<bool>;
+
+    /// Record that the message at `counter` in the chain for `sender_ratchet_key` has been
+    /// processed.
+    ///
+    /// This is only called after the session the message advanced has been saved. Errors are
+    /// logged but don't fail the decryption, since the plaintext couldn't be recovered afterwards.
+    async fn insert(
+        &mut self,
+        address: &ProtocolAddress,
+        sender_ratchet_key: &PublicKey,
+        counter: u32,
+    ) -> Result<()>;
 }
 
 /// Mixes in all the store interfaces defined in this module.
@@ -0,0 +1,38 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use futures_util::FutureExt;
+use libsignal_protocol::*;
+
+#[test]
+fn in_memory_store_conforms() {
+    conformance::check_all(|identity, registration_id| {
+        InMemSignalProtocolStore::new(identity, registration_id).expect("valid")
+    })
+    .now_or_never()
+    .expect("sync");
+}
+
+#[test]
+fn separate_in_memory_stores_conform() {
+    let mut rng = rand::rngs::OsRng;
+    for check in conformance::Check::ALL {
+        let identity = IdentityKeyPair::generate(&mut rng);
+        let registration_id = 0x1234;
+        let mut store = InMemSignalProtocolStore::new(identity, registration_id).expect("valid");
+        let stores = conformance::SeparateStores {
+            identity_store: &mut store.identity_store,
+            pre_key_store: &mut store.pre_key_store,
+            signed_pre_key_store: &mut store.signed_pre_key_store,
+            kyber_pre_key_store: &mut store.kyber_pre_key_store,
+            session_store: &mut store.session_store,
+            sender_key_store: &mut store.sender_key_store,
+        };
+        check
+            .run(stores, &identity, registration_id)
+            .now_or_never()
+            .expect("sync");
+    }
+}
@@ -1280,3 +1280,192 @@ fn group_message_key_limit() -> Result<(), SignalProtocolError> {
     .now_or_never()
     .expect("sync")
 }
+
+#[test]
+fn group_record_collection_limit() -> Result<(), SignalProtocolError> {
+    async {
+        let mut csprng = OsRng;
+
+        let sender_address = ProtocolAddress::new("+14159999111".to_owned(), 1.into());
+        let distribution_id = Uuid::from_u128(0xd1d1d1d1_7000_11eb_b32a_33b8a8a487a6);
+
+        let mut alice_store = test_in_memory_protocol_store()?;
+        let mut bob_store = test_in_memory_protocol_store()?;
+
+        let sent_distribution_message = create_sender_key_distribution_message(
+            &sender_address,
+            distribution_id,
+            &mut alice_store,
+            &mut csprng,
+        )
+        .await?;
+        process_sender_key_distribution_message(
+            &sender_address,
+            &SenderKeyDistributionMessage::try_from(sent_distribution_message.serialized())?,
+            &mut bob_store,
+        )
+        .await?;
+
+        // Skipping two messages leaves Bob with two saved message keys.
+        let mut last_ciphertext = None;
+        for _ in 0..3 {
+            last_ciphertext = Some(
+                group_encrypt(
+                    &mut alice_store,
+                    &sender_address,
+                    distribution_id,
+                    "skip".as_bytes(),
+                    &mut csprng,
+                )
+                .await?,
+            );
+        }
+        group_decrypt(
+            last_ciphertext.expect("encrypted").serialized(),
+            &mut bob_store,
+            &sender_address,
+        )
+        .await?;
+
+        let record = bob_store
+            .load_sender_key(&sender_address, distribution_id)
+            .await?
+            .expect("present")
+            .serialize()?;
+        let limits = |max_collection_count| ParseLimits {
+            max_collection_count,
+            ..ParseLimits::DEFAULT
+        };
+        SenderKeyRecord::deserialize_with_limits(&record, &limits(2))?;
+        assert!(SenderKeyRecord::deserialize_with_limits(&record, &limits(1)).is_err());
+
+        Ok(())
+    }
+    .now_or_never()
+    .expect("sync")
+}
+
+#[test]
+fn group_sender_key_distribution_tracking() -> Result<(), SignalProtocolError> {
+    async {
+        let mut csprng = OsRng;
+
+        let sender_address = ProtocolAddress::new("+14159999111".to_owned(), 1.into());
+        let distribution_id = Uuid::from_u128(0xd1d1d1d1_7000_11eb_b32a_33b8a8a487a6);
+        let bob = ProtocolAddress::new("+14159999222".to_owned(), 1.into());
+        let bob_linked = ProtocolAddress::new("+14159999222".to_owned(), 2.into());
+        let carol = ProtocolAddress::new("+14159999333".to_owned(), 1.into());
+        let recipients = [bob.clone(), bob_linked.clone(), carol.clone()];
+
+        let mut alice_store = test_in_memory_protocol_store()?;
+
+        // Before there's a sender key, everyone needs one, and there's nothing to mark.
+        assert_eq!(
+            sender_key_recipients_needing_distribution(
+                &sender_address,
+                distribution_id,
+                &recipients,
+                &mut alice_store,
+            )
+            .await?,
+            recipients
+        );
+        assert!(matches!(
+            mark_sender_key_distributed(
+                &sender_address,
+                distribution_id,
+                &recipients,
+                &mut alice_store,
+            )
+            .await,
+            Err(SignalProtocolError::NoSenderKeyState { .. })
+        ));
+
+        create_sender_key_distribution_message(
+            &sender_address,
+            distribution_id,
+            &mut alice_store,
+            &mut csprng,
+        )
+        .await?;
+        mark_sender_key_distributed(
+            &sender_address,
+            distribution_id,
+            &[bob.clone(), carol.clone()],
+            &mut alice_store,
+        )
+        .await?;
+        assert_eq!(
+            sender_key_recipients_needing_distribution(
+                &sender_address,
+                distribution_id,
+                &recipients,
+                &mut alice_store,
+            )
+            .await?,
+            [bob_linked.clone()]
+        );
+
+        invalidate_sender_key_distribution(
+            &sender_address,
+            distribution_id,
+            &[carol.clone()],
+            &mut alice_store,
+        )
+        .await?;
+        assert_eq!(
+            sender_key_recipients_needing_distribution(
+                &sender_address,
+                distribution_id,
+                &recipients,
+                &mut alice_store,
+            )
+            .await?,
+            [bob_linked.clone(), carol.clone()]
+        );
+
+        // Replacing the sender key makes the old record irrelevant.
+        let mut other_store = test_in_memory_protocol_store()?;
+        create_sender_key_distribution_message(
+            &sender_address,
+            distribution_id,
+            &mut other_store,
+            &mut csprng,
+        )
+        .await?;
+        let new_record = other_store
+            .load_sender_key(&sender_address, distribution_id)
+            .await?
+            .expect("just created");
+        alice_store
+            .store_sender_key(&sender_address, distribution_id, &new_record)
+            .await?;
+        assert_eq!(
+            sender_key_recipients_needing_distribution(
+                &sender_address,
+                distribution_id,
+                &recipients,
+                &mut alice_store,
+            )
+            .await?,
+            recipients
+        );
+
+        mark_sender_key_distributed(
+            &sender_address,
+            distribution_id,
+            &[bob_linked.clone()],
+            &mut alice_store,
+        )
+        .await?;
+        let distribution = alice_store
+            .load_sender_key_distribution(&sender_address, distribution_id)
+            .await?
+            .expect("just stored");
+        assert_eq!(distribution.recipients().collect::<Vec<_>>(), [&bob_linked]);
+
+        Ok(())
+    }
+    .now_or_never()
+    .expect("sync")
+}
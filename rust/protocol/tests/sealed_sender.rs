@@ -4,7 +4,7 @@
 //
 
 mod support;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 use futures_util::FutureExt;
 use libsignal_protocol::*;
@@ -105,6 +105,13 @@ fn test_sender_cert() -> Result<(), SignalProtocolError> {
     assert!(sender_cert.validate(&trust_root.public_key, expires)?);
     assert!(!sender_cert.validate(&trust_root.public_key, expires.add_millis(1))?); // expired
 
+    let margin = Duration::from_secs(60);
+    assert!(!sender_cert.needs_refresh(expires.sub_millis(60_000), margin));
+    assert!(sender_cert.needs_refresh(expires.sub_millis(59_999), margin));
+    assert!(!sender_cert.needs_refresh(expires, Duration::ZERO));
+    assert!(sender_cert.needs_refresh(expires.add_millis(1), Duration::ZERO));
+    assert!(sender_cert.needs_refresh(expires, Duration::MAX));
+
     let mut sender_cert_data = sender_cert.serialized()?.to_vec();
     let sender_cert_bits = sender_cert_data.len() * 8;
 
@@ -132,6 +139,53 @@ fn test_sender_cert() -> Result<(), SignalProtocolError> {
     Ok(())
 }
 
+#[test]
+fn test_usmc_with_content_hint_and_group_id() -> Result<(), SignalProtocolError> {
+    let mut rng = OsRng;
+    let trust_root = KeyPair::generate(&mut rng);
+    let server_key = KeyPair::generate(&mut rng);
+    let server_cert =
+        ServerCertificate::new(1, server_key.public_key, &trust_root.private_key, &mut rng)?;
+    let sender_cert = SenderCertificate::new(
+        "9d0652a3-dcc3-4d11-975f-74d61598733f".to_string(),
+        None,
+        KeyPair::generate(&mut rng).public_key,
+        1.into(),
+        Timestamp::from_epoch_millis(1605722925),
+        server_cert,
+        &server_key.private_key,
+        &mut rng,
+    )?;
+
+    let original = UnidentifiedSenderMessageContent::new(
+        CiphertextMessageType::Whisper,
+        sender_cert,
+        b"contents".to_vec(),
+        ContentHint::Default,
+        None,
+    )?;
+
+    let rebuilt =
+        original.with_content_hint_and_group_id(ContentHint::Resendable, Some(vec![42]))?;
+    assert_eq!(rebuilt.content_hint()?, ContentHint::Resendable);
+    assert_eq!(rebuilt.group_id()?, Some(&[42][..]));
+    assert_eq!(rebuilt.msg_type()?, original.msg_type()?);
+    assert_eq!(rebuilt.contents()?, original.contents()?);
+    assert_eq!(
+        rebuilt.sender()?.serialized()?,
+        original.sender()?.serialized()?
+    );
+
+    // The change survives a round trip, and can be undone.
+    let reparsed = UnidentifiedSenderMessageContent::deserialize(rebuilt.serialized()?)?;
+    assert_eq!(reparsed.content_hint()?, ContentHint::Resendable);
+    assert_eq!(reparsed.group_id()?, Some(&[42][..]));
+    let restored = reparsed.with_content_hint_and_group_id(ContentHint::Default, None)?;
+    assert_eq!(restored.serialized()?, original.serialized()?);
+
+    Ok(())
+}
+
 #[test]
 fn test_sealed_sender() -> Result<(), SignalProtocolError> {
     async {
@@ -300,6 +354,240 @@ fn test_sealed_sender() -> Result<(), SignalProtocolError> {
     .expect("sync")
 }
 
+#[test]
+fn test_sealed_sender_identity_change() -> Result<(), SignalProtocolError> {
+    async {
+        let mut rng = OsRng;
+
+        let alice_device_id: DeviceId = 23.into();
+        let bob_device_id: DeviceId = 42.into();
+
+        let alice_uuid = "9d0652a3-dcc3-4d11-975f-74d61598733f".to_string();
+        let bob_uuid = "796abedb-ca4e-4f18-8803-1fde5b921f9f".to_string();
+
+        let alice_uuid_address = ProtocolAddress::new(alice_uuid.clone(), alice_device_id);
+        let bob_uuid_address = ProtocolAddress::new(bob_uuid.clone(), bob_device_id);
+
+        let mut alice_store = support::test_in_memory_protocol_store()?;
+        let mut bob_store = support::test_in_memory_protocol_store()?;
+
+        let alice_pubkey = *alice_store.get_identity_key_pair().await?.public_key();
+
+        // Bob remembers a different identity for Alice.
+        let previous_identity = IdentityKey::new(KeyPair::generate(&mut rng).public_key);
+        bob_store
+            .save_identity(&alice_uuid_address, &previous_identity)
+            .await?;
+
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut rng).await?;
+
+        process_prekey_bundle(
+            &bob_uuid_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_pre_key_bundle,
+            SystemTime::now(),
+            &mut rng,
+        )
+        .await?;
+
+        let trust_root = KeyPair::generate(&mut rng);
+        let server_key = KeyPair::generate(&mut rng);
+
+        let server_cert =
+            ServerCertificate::new(1, server_key.public_key, &trust_root.private_key, &mut rng)?;
+
+        let expires = Timestamp::from_epoch_millis(1605722925);
+
+        let sender_cert = SenderCertificate::new(
+            alice_uuid.clone(),
+            None,
+            alice_pubkey,
+            alice_device_id,
+            expires,
+            server_cert,
+            &server_key.private_key,
+            &mut rng,
+        )?;
+
+        let alice_ptext = vec![1, 2, 3, 23, 99];
+        let alice_ctext = sealed_sender_encrypt(
+            &bob_uuid_address,
+            &sender_cert,
+            &alice_ptext,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            SystemTime::now(),
+            &mut rng,
+        )
+        .await?;
+
+        // The regular entry point refuses the new identity...
+        let result = sealed_sender_decrypt(
+            &alice_ctext,
+            &trust_root.public_key,
+            expires.sub_millis(1),
+            None,
+            bob_uuid.clone(),
+            bob_device_id,
+            &mut bob_store.identity_store,
+            &mut bob_store.session_store,
+            &mut bob_store.pre_key_store,
+            &bob_store.signed_pre_key_store,
+            &mut bob_store.kyber_pre_key_store,
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(SignalProtocolError::UntrustedIdentity(_))
+        ));
+
+        // ...but this one decrypts the message and reports the change.
+        let bob_ptext = sealed_sender_decrypt_capturing_identity_change(
+            &alice_ctext,
+            &trust_root.public_key,
+            expires.sub_millis(1),
+            None,
+            bob_uuid.clone(),
+            bob_device_id,
+            &mut bob_store.identity_store,
+            &mut bob_store.session_store,
+            &mut bob_store.pre_key_store,
+            &bob_store.signed_pre_key_store,
+            &mut bob_store.kyber_pre_key_store,
+        )
+        .await?;
+
+        assert_eq!(bob_ptext.message, alice_ptext);
+        assert_eq!(
+            bob_ptext.identity_change,
+            Some(IdentityChange {
+                previous: previous_identity,
+                current: IdentityKey::new(alice_pubkey),
+            })
+        );
+
+        // The identity store is left for the app to update.
+        assert_eq!(
+            bob_store.get_identity(&alice_uuid_address).await?,
+            Some(previous_identity)
+        );
+
+        Ok(())
+    }
+    .now_or_never()
+    .expect("sync")
+}
+
+#[test]
+fn test_sealed_sender_replay_cache() -> Result<(), SignalProtocolError> {
+    async {
+        let mut rng = OsRng;
+
+        let alice_device_id: DeviceId = 23.into();
+        let bob_device_id: DeviceId = 42.into();
+
+        let alice_uuid = "9d0652a3-dcc3-4d11-975f-74d61598733f".to_string();
+        let bob_uuid = "796abedb-ca4e-4f18-8803-1fde5b921f9f".to_string();
+
+        let bob_uuid_address = ProtocolAddress::new(bob_uuid.clone(), bob_device_id);
+
+        let mut alice_store = support::test_in_memory_protocol_store()?;
+        let mut bob_store = support::test_in_memory_protocol_store()?;
+
+        let alice_pubkey = *alice_store.get_identity_key_pair().await?.public_key();
+
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut rng).await?;
+
+        process_prekey_bundle(
+            &bob_uuid_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_pre_key_bundle,
+            SystemTime::now(),
+            &mut rng,
+        )
+        .await?;
+
+        let trust_root = KeyPair::generate(&mut rng);
+        let server_key = KeyPair::generate(&mut rng);
+
+        let server_cert =
+            ServerCertificate::new(1, server_key.public_key, &trust_root.private_key, &mut rng)?;
+
+        let expires = Timestamp::from_epoch_millis(1605722925);
+
+        let sender_cert = SenderCertificate::new(
+            alice_uuid.clone(),
+            None,
+            alice_pubkey,
+            alice_device_id,
+            expires,
+            server_cert,
+            &server_key.private_key,
+            &mut rng,
+        )?;
+
+        let alice_ptext = vec![1, 2, 3, 23, 99];
+        let alice_ctext = sealed_sender_encrypt(
+            &bob_uuid_address,
+            &sender_cert,
+            &alice_ptext,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            SystemTime::now(),
+            &mut rng,
+        )
+        .await?;
+
+        // Keep a copy of Bob's state from before the message arrived, as if it were restored from
+        // a backup later.
+        let mut restored_bob_store = bob_store.clone();
+        let mut replay_cache = InMemReplayCache::new(Duration::from_secs(60 * 60));
+
+        let bob_ptext = sealed_sender_decrypt_with_replay_cache(
+            &alice_ctext,
+            &trust_root.public_key,
+            expires.sub_millis(1),
+            None,
+            bob_uuid.clone(),
+            bob_device_id,
+            &mut bob_store.identity_store,
+            &mut bob_store.session_store,
+            &mut bob_store.pre_key_store,
+            &bob_store.signed_pre_key_store,
+            &mut bob_store.kyber_pre_key_store,
+            &mut replay_cache,
+        )
+        .await?;
+        assert_eq!(bob_ptext.message, alice_ptext);
+
+        let result = sealed_sender_decrypt_with_replay_cache(
+            &alice_ctext,
+            &trust_root.public_key,
+            expires.sub_millis(1),
+            None,
+            bob_uuid.clone(),
+            bob_device_id,
+            &mut restored_bob_store.identity_store,
+            &mut restored_bob_store.session_store,
+            &mut restored_bob_store.pre_key_store,
+            &restored_bob_store.signed_pre_key_store,
+            &mut restored_bob_store.kyber_pre_key_store,
+            &mut replay_cache,
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(SignalProtocolError::ReplayedMessage(0))
+        ));
+
+        Ok(())
+    }
+    .now_or_never()
+    .expect("sync")
+}
+
 #[test]
 fn test_sender_key_in_sealed_sender() -> Result<(), SignalProtocolError> {
     async {
@@ -503,6 +791,16 @@ fn test_sealed_sender_multi_recipient() -> Result<(), SignalProtocolError> {
         assert_eq!(recipient_addr.service_id_string(), bob_uuid);
         assert_eq!(bob_ctext[0], 0x22); // Use the original SSv2 version byte.
 
+        let usmc_len = alice_usmc.serialized()?.len();
+        assert_eq!(
+            alice_ctext.len(),
+            sealed_sender_multi_recipient_message_len(usmc_len, 1, 1, 0)
+        );
+        assert_eq!(
+            bob_ctext.len(),
+            sealed_sender_multi_recipient_received_message_len(usmc_len)
+        );
+
         let bob_ptext = sealed_sender_decrypt(
             &bob_ctext,
             &trust_root.public_key,
@@ -1096,6 +1394,74 @@ fn test_sealed_sender_multi_recipient_redundant_empty_devices() -> Result<(), Si
         )
         .await?;
         assert!(SealedSenderV2SentMessage::parse(&recipient_excluded_twice).is_err());
+        assert_eq!(
+            recipient_excluded_twice.len(),
+            sealed_sender_multi_recipient_message_len(alice_usmc.serialized()?.len(), 0, 0, 2)
+        );
+
+        Ok(())
+    }
+    .now_or_never()
+    .expect("sync")
+}
+
+#[test]
+fn test_load_and_archive_multi_recipient_sessions() -> Result<(), SignalProtocolError> {
+    async {
+        let mut rng = OsRng;
+
+        let bob_uuid = "796abedb-ca4e-4f18-8803-1fde5b921f9f".to_string();
+        let bob_first_address = ProtocolAddress::new(bob_uuid.clone(), 1.into());
+        let bob_second_address = ProtocolAddress::new(bob_uuid.clone(), 2.into());
+        let bob_missing_address = ProtocolAddress::new(bob_uuid.clone(), 3.into());
+
+        let mut alice_store = support::test_in_memory_protocol_store()?;
+        let mut bob_store = support::test_in_memory_protocol_store()?;
+
+        for address in [&bob_first_address, &bob_second_address] {
+            let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut rng).await?;
+            process_prekey_bundle(
+                address,
+                &mut alice_store.session_store,
+                &mut alice_store.identity_store,
+                &bob_pre_key_bundle,
+                SystemTime::now(),
+                &mut rng,
+            )
+            .await?;
+        }
+
+        let sessions =
+            load_multi_recipient_sessions(&[&bob_second_address, &bob_first_address], &alice_store)
+                .await?;
+        assert_eq!(
+            sessions
+                .iter()
+                .map(SessionRecord::serialize)
+                .collect::<Result<Vec<_>, _>>()?,
+            alice_store
+                .session_store
+                .load_existing_sessions(&[&bob_second_address, &bob_first_address])?
+                .into_iter()
+                .map(SessionRecord::serialize)
+                .collect::<Result<Vec<_>, _>>()?,
+        );
+
+        assert!(matches!(
+            load_multi_recipient_sessions(&[&bob_first_address, &bob_missing_address], &alice_store)
+                .await,
+            Err(SignalProtocolError::SessionNotFound(address)) if address == bob_missing_address
+        ));
+
+        archive_sessions_for_devices(&bob_uuid, &[1.into(), 2.into(), 3.into()], &mut alice_store)
+            .await?;
+        let archived = alice_store
+            .load_sessions_for_devices(&bob_uuid, &[1.into(), 2.into(), 3.into()])
+            .await?;
+        assert_eq!(archived.len(), 2);
+        for (_, session) in archived {
+            assert!(!session.has_usable_sender_chain(SystemTime::now())?);
+        }
 
         Ok(())
     }
@@ -743,6 +743,250 @@ fn test_optional_one_time_prekey() -> TestResult {
     Ok(())
 }
 
+#[test]
+fn test_replay_cache_rejects_message_after_session_loss() -> TestResult {
+    async {
+        let mut csprng = OsRng;
+        let alice_address = ProtocolAddress::new("+14151111111".to_owned(), 1.into());
+        let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1.into());
+
+        let mut alice_store_builder = TestStoreBuilder::new();
+        let bob_store_builder = TestStoreBuilder::new().with_signed_pre_key(22.into());
+        let alice_store = &mut alice_store_builder.store;
+
+        process_prekey_bundle(
+            &bob_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_store_builder.make_bundle_with_latest_keys(1.into()),
+            SystemTime::now(),
+            &mut csprng,
+        )
+        .await?;
+
+        let outgoing_message = encrypt(alice_store, &bob_address, "only once").await?;
+        let incoming_message = PreKeySignalMessage::try_from(outgoing_message.serialize())?;
+
+        let mut replay_cache = InMemReplayCache::new(Duration::from_secs(60 * 60));
+
+        // A message that fails to decrypt isn't recorded, so it can be retried.
+        let mut wrong_store = TestStoreBuilder::new().store;
+        assert!(decrypt_with_cache(
+            &incoming_message,
+            &alice_address,
+            &mut wrong_store,
+            &mut replay_cache,
+        )
+        .await
+        .is_err());
+
+        // Without a one-time pre-key, losing the session means the same message can be decrypted
+        // again from scratch; the replay cache is what catches that.
+        let ptext = decrypt_with_cache(
+            &incoming_message,
+            &alice_address,
+            &mut bob_store_builder.store.clone(),
+            &mut replay_cache,
+        )
+        .await?;
+        assert_eq!(ptext, b"only once");
+
+        let mut replay_store = bob_store_builder.store.clone();
+        assert!(matches!(
+            decrypt_with_cache(
+                &incoming_message,
+                &alice_address,
+                &mut replay_store,
+                &mut replay_cache,
+            )
+            .await,
+            Err(SignalProtocolError::ReplayedMessage(0))
+        ));
+        // The replay was rejected before any of the stores were touched.
+        assert!(replay_store.load_session(&alice_address).await?.is_none());
+        assert!(replay_store.get_identity(&alice_address).await?.is_none());
+
+        // A fresh cache has no record of it.
+        let mut fresh_cache = InMemReplayCache::new(Duration::from_secs(60 * 60));
+        let ptext = decrypt_with_cache(
+            &incoming_message,
+            &alice_address,
+            &mut bob_store_builder.store.clone(),
+            &mut fresh_cache,
+        )
+        .await?;
+        assert_eq!(ptext, b"only once");
+
+        Ok(())
+    }
+    .now_or_never()
+    .expect("sync")
+}
+
+#[test]
+fn test_replay_cache_insert_failure_keeps_plaintext() -> TestResult {
+    struct FailingReplayCache;
+
+    #[async_trait::async_trait(?Send)]
+    impl ReplayCache for FailingReplayCache {
+        async fn contains(
+            &self,
+            _address: &ProtocolAddress,
+            _sender_ratchet_key: &PublicKey,
+            _counter: u32,
+        ) -> Result<bool, SignalProtocolError> {
+            Ok(false)
+        }
+
+        async fn insert(
+            &mut self,
+            _address: &ProtocolAddress,
+            _sender_ratchet_key: &PublicKey,
+            _counter: u32,
+        ) -> Result<(), SignalProtocolError> {
+            Err(SignalProtocolError::InvalidState(
+                "insert",
+                "cache full".to_owned(),
+            ))
+        }
+    }
+
+    async {
+        let mut csprng = OsRng;
+        let alice_address = ProtocolAddress::new("+14151111111".to_owned(), 1.into());
+        let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1.into());
+
+        let mut alice_store_builder = TestStoreBuilder::new();
+        let mut bob_store_builder = TestStoreBuilder::new().with_signed_pre_key(22.into());
+        let alice_store = &mut alice_store_builder.store;
+
+        process_prekey_bundle(
+            &bob_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_store_builder.make_bundle_with_latest_keys(1.into()),
+            SystemTime::now(),
+            &mut csprng,
+        )
+        .await?;
+
+        let outgoing_message = encrypt(alice_store, &bob_address, "kept").await?;
+        let incoming_message = PreKeySignalMessage::try_from(outgoing_message.serialize())?;
+
+        // The session is saved before the cache is updated, so the plaintext must still be
+        // returned when the cache can't record the message.
+        let bob_store = &mut bob_store_builder.store;
+        let ptext = decrypt_with_cache(
+            &incoming_message,
+            &alice_address,
+            bob_store,
+            &mut FailingReplayCache,
+        )
+        .await?;
+        assert_eq!(ptext, b"kept");
+        assert!(bob_store.load_session(&alice_address).await?.is_some());
+
+        Ok(())
+    }
+    .now_or_never()
+    .expect("sync")
+}
+
+#[test]
+fn test_session_stats() -> TestResult {
+    async {
+        let mut csprng = OsRng;
+        let alice_address = ProtocolAddress::new("+14151111111".to_owned(), 1.into());
+        let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1.into());
+
+        let mut alice_store_builder = TestStoreBuilder::new();
+        let mut bob_store_builder = TestStoreBuilder::new()
+            .with_signed_pre_key(22.into())
+            .with_kyber_pre_key(33.into());
+
+        assert_eq!(
+            session_stats(&bob_address, &alice_store_builder.store.session_store).await?,
+            None
+        );
+
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        process_prekey_bundle(
+            &bob_address,
+            &mut alice_store_builder.store.session_store,
+            &mut alice_store_builder.store.identity_store,
+            &bob_store_builder.make_bundle_with_latest_keys(1.into()),
+            now,
+            &mut csprng,
+        )
+        .await?;
+
+        let alice_store = &mut alice_store_builder.store;
+        let bob_store = &mut bob_store_builder.store;
+        for _ in 0..3 {
+            let message = message_encrypt(
+                b"hi bob",
+                &bob_address,
+                &mut alice_store.session_store,
+                &mut alice_store.identity_store,
+                now,
+            )
+            .await?;
+            decrypt(bob_store, &alice_address, &message).await?;
+        }
+
+        let alice_stats = session_stats(&bob_address, &alice_store.session_store)
+            .await?
+            .expect("session exists");
+        assert_eq!(
+            alice_stats,
+            SessionStats {
+                messages_encrypted: 3,
+                messages_decrypted: 0,
+                last_ratchet_step: Some(now),
+                post_quantum: true,
+            }
+        );
+
+        // Bob hasn't sent anything yet, so he hasn't used his new ratchet key.
+        let bob_stats = session_stats(&alice_address, &bob_store.session_store)
+            .await?
+            .expect("session exists");
+        assert_eq!(
+            bob_stats,
+            SessionStats {
+                messages_encrypted: 0,
+                messages_decrypted: 3,
+                last_ratchet_step: None,
+                post_quantum: true,
+            }
+        );
+
+        Ok(())
+    }
+    .now_or_never()
+    .expect("sync")
+}
+
+async fn decrypt_with_cache(
+    message: &PreKeySignalMessage,
+    remote_address: &ProtocolAddress,
+    store: &mut InMemSignalProtocolStore,
+    replay_cache: &mut dyn ReplayCache,
+) -> Result<Vec<u8>, SignalProtocolError> {
+    message_decrypt_prekey_with_replay_cache(
+        message,
+        remote_address,
+        &mut store.session_store,
+        &mut store.identity_store,
+        &mut store.pre_key_store,
+        &store.signed_pre_key_store,
+        &mut store.kyber_pre_key_store,
+        replay_cache,
+        &mut OsRng,
+    )
+    .await
+}
+
 #[test]
 fn test_basic_session() -> TestResult {
     let (alice_session, bob_session) = initialize_sessions_v3()?;
@@ -2058,6 +2302,130 @@ fn test_unacknowledged_sessions_eventually_expire() -> TestResult {
     .expect("sync")
 }
 
+#[test]
+fn test_records_are_versioned() -> TestResult {
+    async {
+        let mut csprng = OsRng;
+        let identity_key = KeyPair::generate(&mut csprng);
+
+        let (alice_session, _) = initialize_sessions_v4()?;
+        let pre_key = PreKeyRecord::new(1.into(), &KeyPair::generate(&mut csprng));
+        let signed_pre_key_pair = KeyPair::generate(&mut csprng);
+        let signed_pre_key = SignedPreKeyRecord::new(
+            2.into(),
+            Timestamp::from_epoch_millis(42),
+            &signed_pre_key_pair,
+            &identity_key
+                .private_key
+                .calculate_signature(&signed_pre_key_pair.public_key.serialize(), &mut csprng)?,
+        );
+        let kyber_pre_key = KyberPreKeyRecord::generate(
+            kem::KeyType::Kyber1024,
+            3.into(),
+            &identity_key.private_key,
+        )?;
+
+        let sender_address = ProtocolAddress::new("+14159999111".to_owned(), 1.into());
+        let distribution_id = uuid::Uuid::from_u128(0xd1d1d1d1_7000_11eb_b32a_33b8a8a487a6);
+        let mut store = TestStoreBuilder::new().store;
+        create_sender_key_distribution_message(
+            &sender_address,
+            distribution_id,
+            &mut store,
+            &mut csprng,
+        )
+        .await?;
+        let sender_key = store
+            .load_sender_key(&sender_address, distribution_id)
+            .await?
+            .expect("just created");
+
+        // Records are still written without a header by default, so that older versions of
+        // libsignal can read them.
+        let legacy_records = [
+            alice_session.serialize()?,
+            pre_key.serialize()?,
+            signed_pre_key.serialize()?,
+            kyber_pre_key.serialize()?,
+            sender_key.serialize()?,
+        ];
+        for bytes in &legacy_records {
+            assert_eq!(record_version(bytes)?, 0);
+        }
+        let [session_bytes, pre_key_bytes, signed_pre_key_bytes, kyber_pre_key_bytes, sender_key_bytes] =
+            &legacy_records;
+
+        // Migrating adds the header in front of the same protobuf message...
+        let migrated = [
+            SessionRecord::migrate_to_current(session_bytes)?,
+            PreKeyRecord::migrate_to_current(pre_key_bytes)?,
+            SignedPreKeyRecord::migrate_to_current(signed_pre_key_bytes)?,
+            KyberPreKeyRecord::migrate_to_current(kyber_pre_key_bytes)?,
+            SenderKeyRecord::migrate_to_current(sender_key_bytes)?,
+        ];
+        for (legacy, current) in std::iter::zip(&legacy_records, &migrated) {
+            assert_eq!(record_version(current)?, CURRENT_RECORD_VERSION);
+            assert_eq!(&current[2..], &legacy[..]);
+        }
+        let [session_current, pre_key_current, signed_pre_key_current, kyber_pre_key_current, sender_key_current] =
+            &migrated;
+
+        // ...which is stable under another migration...
+        assert_eq!(
+            &SenderKeyRecord::migrate_to_current(sender_key_current)?,
+            sender_key_current
+        );
+        assert_eq!(
+            &SignedPreKeyRecord::migrate_to_current(signed_pre_key_current)?,
+            signed_pre_key_current
+        );
+
+        // ...and reads back as the same record.
+        assert_eq!(
+            SessionRecord::deserialize(session_current)?.serialize()?,
+            *session_bytes
+        );
+        assert_eq!(
+            PreKeyRecord::deserialize(pre_key_current)?.key_pair()?.public_key,
+            pre_key.key_pair()?.public_key
+        );
+        let signed_pre_key_read = SignedPreKeyRecord::deserialize(signed_pre_key_current)?;
+        assert_eq!(signed_pre_key_read.id()?, signed_pre_key.id()?);
+        assert_eq!(
+            signed_pre_key_read.public_key()?,
+            signed_pre_key_pair.public_key
+        );
+        assert_eq!(
+            signed_pre_key_read.signature()?,
+            signed_pre_key.signature()?
+        );
+        assert_eq!(
+            KyberPreKeyRecord::deserialize(kyber_pre_key_current)?.serialize()?,
+            *kyber_pre_key_bytes
+        );
+        assert_eq!(
+            SenderKeyRecord::deserialize(sender_key_current)?.serialize()?,
+            *sender_key_bytes
+        );
+
+        // Records from a newer version of the library are rejected outright.
+        let mut too_new = pre_key_current.clone();
+        too_new[1] = CURRENT_RECORD_VERSION + 1;
+        assert!(matches!(
+            PreKeyRecord::deserialize(&too_new),
+            Err(SignalProtocolError::UnrecognizedRecordVersion(v)) if v == CURRENT_RECORD_VERSION + 1
+        ));
+        assert!(matches!(
+            SenderKeyRecord::migrate_to_current(&too_new),
+            Err(SignalProtocolError::UnrecognizedRecordVersion(_))
+        ));
+
+        Ok(())
+    }
+    .now_or_never()
+    .expect("sync")
+}
+
 #[allow(clippy::needless_range_loop)]
 fn run_session_interaction(alice_session: SessionRecord, bob_session: SessionRecord) -> TestResult {
     async {
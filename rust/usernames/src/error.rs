@@ -49,4 +49,6 @@ pub enum UsernameLinkError {
     BadCiphertext,
     /// Data decrypted from the username link is of invalid structure
     InvalidDecryptedDataStructure,
+    /// Username link URL is not of the expected form
+    InvalidLinkUrl,
 }
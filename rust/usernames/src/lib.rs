@@ -13,4 +13,4 @@ mod username;
 mod username_links;
 
 pub use error::UsernameLinkError;
-pub use username_links::{create_for_username, decrypt_username};
+pub use username_links::{create_for_username, decrypt_username, UsernameLinkUrl};
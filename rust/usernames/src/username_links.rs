@@ -3,11 +3,13 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use base64::prelude::{Engine as _, BASE64_URL_SAFE_NO_PAD};
 use hmac::Mac;
 use prost::Message;
 use rand::{CryptoRng, Rng};
 use signal_crypto::{aes_256_cbc_decrypt, aes_256_cbc_encrypt};
 use subtle::ConstantTimeEq;
+use uuid::Uuid;
 
 use crate::constants::{
     USERNAME_LINK_ENTROPY_SIZE, USERNAME_LINK_HMAC_LEN, USERNAME_LINK_IV_SIZE,
@@ -81,6 +83,74 @@ pub fn decrypt_username(
     Ok(username_data.username)
 }
 
+/// The contents of a username link URL, as shared via QR code or copied link.
+///
+/// The server ID identifies the encrypted username stored on the server, which can then be
+/// decrypted with [`decrypt_username`] using the entropy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UsernameLinkUrl {
+    pub entropy: [u8; USERNAME_LINK_ENTROPY_SIZE],
+    pub server_id: Uuid,
+}
+
+impl UsernameLinkUrl {
+    const SCHEMES: [&'static str; 2] = ["https://", "sgnl://"];
+    const HOST: &'static str = "signal.me";
+    const FRAGMENT_PREFIX: &'static str = "#eu/";
+    const PAYLOAD_LEN: usize = USERNAME_LINK_ENTROPY_SIZE + std::mem::size_of::<uuid::Bytes>();
+
+    /// Parses a username link URL without contacting the server.
+    ///
+    /// Both `https://signal.me/#eu/...` and `sgnl://signal.me/#eu/...` forms are accepted. The
+    /// payload must be unpadded URL-safe base64 encoding the link entropy followed by the server ID.
+    pub fn parse(url: &str) -> Result<Self, UsernameLinkError> {
+        let url = url.trim();
+        let rest = Self::SCHEMES
+            .iter()
+            .find_map(|scheme| strip_prefix_ignoring_ascii_case(url, scheme))
+            .and_then(|rest| strip_prefix_ignoring_ascii_case(rest, Self::HOST))
+            .ok_or(UsernameLinkError::InvalidLinkUrl)?;
+        let rest = rest.strip_prefix('/').unwrap_or(rest);
+        let encoded = rest
+            .strip_prefix(Self::FRAGMENT_PREFIX)
+            .ok_or(UsernameLinkError::InvalidLinkUrl)?;
+
+        let payload = BASE64_URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|_| UsernameLinkError::InvalidLinkUrl)?;
+        let payload: &[u8; Self::PAYLOAD_LEN] = payload
+            .as_slice()
+            .try_into()
+            .map_err(|_| UsernameLinkError::InvalidEntropyDataLength)?;
+
+        let (entropy, server_id) = payload.split_at(USERNAME_LINK_ENTROPY_SIZE);
+        Ok(Self {
+            entropy: entropy.try_into().expect("correct length"),
+            server_id: Uuid::from_slice(server_id).expect("correct length"),
+        })
+    }
+
+    /// Formats the link in its canonical `https://signal.me/#eu/...` form.
+    pub fn to_url(&self) -> String {
+        let mut payload = Vec::with_capacity(Self::PAYLOAD_LEN);
+        payload.extend_from_slice(&self.entropy);
+        payload.extend_from_slice(self.server_id.as_bytes());
+        format!(
+            "{}{}/{}{}",
+            Self::SCHEMES[0],
+            Self::HOST,
+            Self::FRAGMENT_PREFIX,
+            BASE64_URL_SAFE_NO_PAD.encode(payload)
+        )
+    }
+}
+
+fn strip_prefix_ignoring_ascii_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = s.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &s[prefix.len()..])
+}
+
 fn hkdf(entropy: &[u8], label: &[u8]) -> [u8; USERNAME_LINK_KEY_SIZE] {
     let mut result = [0u8; USERNAME_LINK_KEY_SIZE];
     hkdf::Hkdf::<sha2::Sha256>::new(None, entropy)
@@ -238,6 +308,57 @@ mod test {
         assert_eq!(expected_username, actual_username);
     }
 
+    #[test]
+    fn link_url_round_trip() {
+        let link = UsernameLinkUrl {
+            entropy: random_bytes(&mut OsRng),
+            server_id: Uuid::from_bytes(random_bytes(&mut OsRng)),
+        };
+        let url = link.to_url();
+        assert!(url.starts_with("https://signal.me/#eu/"), "{url}");
+        assert_eq!(link, UsernameLinkUrl::parse(&url).expect("valid"));
+
+        let app_url = url.replacen("https://signal.me/", "SGNL://Signal.me", 1);
+        assert_eq!(link, UsernameLinkUrl::parse(&app_url).expect("valid"));
+    }
+
+    #[test]
+    fn link_url_bad_structure() {
+        let payload = BASE64_URL_SAFE_NO_PAD.encode([1; 48]);
+        for url in [
+            format!("http://signal.me/#eu/{payload}"),
+            format!("https://signal.org/#eu/{payload}"),
+            format!("https://signal.me/#p/{payload}"),
+            format!("https://signal.me/#eu/{payload}=="),
+            "https://signal.me/#eu/not*base64".to_owned(),
+        ] {
+            assert!(
+                matches!(
+                    UsernameLinkUrl::parse(&url),
+                    Err(UsernameLinkError::InvalidLinkUrl)
+                ),
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn link_url_wrong_payload_length() {
+        for len in [0, 32, 47, 49] {
+            let url = format!(
+                "https://signal.me/#eu/{}",
+                BASE64_URL_SAFE_NO_PAD.encode(vec![1; len])
+            );
+            assert!(
+                matches!(
+                    UsernameLinkUrl::parse(&url),
+                    Err(UsernameLinkError::InvalidEntropyDataLength)
+                ),
+                "{len}"
+            );
+        }
+    }
+
     #[test]
     fn prost_ignores_unknown_fields_and_handles_missing_ones() {
         // Field # 0b1111111_1111 (way higher than anything we'd use) with a type of VARINT (0) and a value of 0
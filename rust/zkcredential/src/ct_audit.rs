@@ -0,0 +1,60 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Counters for checking that secret-dependent branches go the same way for every secret.
+//!
+//! Where issuance, presentation, endorsement, and decryption code in this crate and in zkgroup
+//! branches on a value derived from secrets (such as whether a proof verified or a ciphertext
+//! decrypted to a valid candidate), each arm of the branch calls [`record`]. With the `ct-audit`
+//! feature enabled, each call is counted per thread, and a test can run the same operation with two
+//! different secrets and check that the resulting `Counts` match, i.e. that the same arms were
+//! taken. Without the feature, [`record`] does nothing and is compiled out entirely.
+//!
+//! Straight-line code and loops over public lengths aren't instrumented, since they can't diverge.
+//! This doesn't prove anything about the generated machine code, or about branches inside
+//! dependencies; it catches regressions like a new early return that depends on a secret.
+
+/// Notes that the instrumented point `site` was reached.
+#[inline(always)]
+pub fn record(site: &'static str) {
+    #[cfg(feature = "ct-audit")]
+    imp::COUNTS.with_borrow_mut(|counts| *counts.0.entry(site).or_default() += 1);
+    #[cfg(not(feature = "ct-audit"))]
+    let _ = site;
+}
+
+#[cfg(feature = "ct-audit")]
+pub use imp::{take, Counts};
+
+#[cfg(feature = "ct-audit")]
+mod imp {
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+
+    /// How many times each instrumented point was reached, as returned by [`take`].
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    pub struct Counts(pub(super) BTreeMap<&'static str, u64>);
+
+    impl Counts {
+        /// Returns how many times `site` was reached (possibly zero).
+        pub fn get(&self, site: &str) -> u64 {
+            self.0.get(site).copied().unwrap_or_default()
+        }
+
+        /// Returns `true` if no instrumented points were reached.
+        pub fn is_empty(&self) -> bool {
+            self.0.is_empty()
+        }
+    }
+
+    thread_local! {
+        pub(super) static COUNTS: RefCell<Counts> = RefCell::default();
+    }
+
+    /// Returns the counts recorded on the current thread so far, and resets them.
+    pub fn take() -> Counts {
+        COUNTS.take()
+    }
+}
@@ -447,6 +447,12 @@ impl EndorsementResponse {
             .collect()
     }
 
+    /// The number of endorsements in the response, which [`Self::receive`] requires to match the
+    /// number of points.
+    pub fn endorsement_count(&self) -> usize {
+        self.R.len()
+    }
+
     /// Validates and retrieves the endorsements stored in `self`.
     ///
     /// `hidden_attribute_points` should be the same points seen by the issuing server, i.e. blinded
@@ -629,8 +635,10 @@ impl ServerDerivedKeyPair {
         let P = self.sk_prime * point;
         let expected = Endorsement::to_token_raw(P);
         if token.ct_eq(&expected).into() {
+            crate::ct_audit::record("endorsements::verify::accepted");
             Ok(())
         } else {
+            crate::ct_audit::record("endorsements::verify::rejected");
             Err(VerificationFailure)
         }
     }
@@ -274,8 +274,14 @@ impl<'a> IssuanceProofBuilder<'a> {
             &point_args,
             self.authenticated_message,
         ) {
-            Err(_) => Err(VerificationFailure),
-            Ok(_) => Ok(proof.credential),
+            Err(_) => {
+                crate::ct_audit::record("issuance::verify::rejected");
+                Err(VerificationFailure)
+            }
+            Ok(_) => {
+                crate::ct_audit::record("issuance::verify::accepted");
+                Ok(proof.credential)
+            }
         }
     }
 }
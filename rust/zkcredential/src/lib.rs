@@ -51,6 +51,7 @@ pub const RANDOMNESS_LEN: usize = 32;
 
 pub mod attributes;
 pub mod credentials;
+pub mod ct_audit;
 pub mod endorsements;
 pub mod issuance;
 pub mod presentation;
@@ -703,8 +703,14 @@ impl<'a> PresentationProofVerifier<'a> {
             &point_args,
             self.core.authenticated_message,
         ) {
-            Err(_) => Err(VerificationFailure),
-            Ok(_) => Ok(()),
+            Err(_) => {
+                crate::ct_audit::record("presentation::verify::rejected");
+                Err(VerificationFailure)
+            }
+            Ok(_) => {
+                crate::ct_audit::record("presentation::verify::accepted");
+                Ok(())
+            }
         }
     }
 }
@@ -0,0 +1,62 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkgroup::auth::*;
+use zkgroup::backups::*;
+use zkgroup::call_links::*;
+use zkgroup::groups::*;
+use zkgroup::profiles::*;
+use zkgroup::receipts::*;
+use zkgroup::ServerPublicParams;
+
+/// Picks one of the listed parsers based on `selector`, so that a single target covers each
+/// serialized type that clients and servers accept from each other.
+macro_rules! deserialize_one_of {
+    ($selector:expr, $data:expr; $($ty:ty),+ $(,)?; $($parse:expr),* $(,)?) => {{
+        let parsers: &[fn(&[u8])] = &[
+            $(|data| {
+                let _: Result<$ty, _> = zkgroup::deserialize(data);
+            },)+
+            $(|data| {
+                let _: Result<_, _> = $parse(data);
+            },)*
+        ];
+        parsers[usize::from($selector) % parsers.len()]($data)
+    }};
+}
+
+fuzz_target!(|input: (u8, &[u8])| {
+    let (selector, data) = input;
+    deserialize_one_of!(
+        selector, data;
+        ServerPublicParams,
+        GroupPublicParams,
+        UuidCiphertext,
+        ProfileKeyCiphertext,
+        ProfileKeyCredentialRequest,
+        ExpiringProfileKeyCredentialResponse,
+        AuthCredentialWithPniZkcResponse,
+        ReceiptCredentialRequest,
+        ReceiptCredentialResponse,
+        ReceiptCredentialPresentation,
+        CreateCallLinkCredentialRequest,
+        CreateCallLinkCredentialResponse,
+        CreateCallLinkCredentialPresentation,
+        CallLinkAuthCredentialResponse,
+        CallLinkAuthCredentialPresentation,
+        BackupAuthCredentialRequest,
+        BackupAuthCredentialResponse,
+        BackupAuthCredentialPresentation,
+        GroupSendEndorsementsResponse,
+        GroupSendEndorsement,
+        GroupSendFullToken;
+        AnyProfileKeyCredentialPresentation::new,
+        AnyAuthCredentialPresentation::new,
+        AuthCredentialWithPniResponse::new,
+    );
+});
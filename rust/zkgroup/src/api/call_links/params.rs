@@ -39,6 +39,18 @@ impl CallLinkSecretParams {
         }
     }
 
+    /// Derives the room ID the calling server uses for the call link with `root_key`.
+    ///
+    /// This matches RingRTC's `CallLinkRootKey::derive_room_id`: HKDF-SHA256 with no salt, the
+    /// root key as input key material, and a fixed info string.
+    pub fn derive_room_id(root_key: &[u8]) -> [u8; 32] {
+        let mut room_id = [0; 32];
+        hkdf::Hkdf::<sha2::Sha256>::new(None, root_key)
+            .expand(b"20230501-Signal-CallLinkRootKey-RoomId", &mut room_id)
+            .expect("valid output length");
+        room_id
+    }
+
     pub fn get_public_params(&self) -> CallLinkPublicParams {
         CallLinkPublicParams {
             reserved: Default::default(),
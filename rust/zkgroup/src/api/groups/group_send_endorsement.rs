@@ -16,6 +16,7 @@ use std::fmt::Debug;
 use derive_where::derive_where;
 use partial_default::PartialDefault;
 use poksho::ShoApi;
+#[cfg(feature = "rayon")]
 use rayon::iter::{IndexedParallelIterator as _, ParallelIterator as _};
 use serde::{Deserialize, Serialize};
 use zkcredential::attributes::Attribute as _;
@@ -145,6 +146,9 @@ impl GroupSendEndorsementsResponse {
     /// Validates `self.expiration` against `now` and derives the appropriate signing key (using
     /// [`GroupSendDerivedKeyPair::tag_info`]).
     ///
+    /// Also rejects responses with more endorsements than the process-wide
+    /// [`ParseLimits`](libsignal_core::ParseLimits) allow, before any work is done per member.
+    ///
     /// Note that if a client expects to receive endorsements from many different groups in one day
     /// it *could* be worth caching this, but the operation is pretty cheap compared to the rest of
     /// verifying responses, so we don't think it would make that much of a difference.
@@ -154,6 +158,9 @@ impl GroupSendEndorsementsResponse {
         server_params: &ServerPublicParams,
     ) -> Result<zkcredential::endorsements::ServerDerivedPublicKey, ZkGroupVerificationFailure>
     {
+        libsignal_core::parse_limits()
+            .check_collection_count(self.endorsements.endorsement_count())
+            .map_err(|_| ZkGroupVerificationFailure)?;
         if !self.expiration.is_day_aligned() {
             // Reject credentials that don't expire on a day boundary,
             // because the server might be trying to fingerprint us.
@@ -177,36 +184,22 @@ impl GroupSendEndorsementsResponse {
             .derive_key(GroupSendDerivedKeyPair::tag_info(self.expiration)))
     }
 
-    /// Same as [`receive_with_service_ids`], but without parallelizing the zkgroup-specific parts
-    /// of the operation.
+    /// Verifies the endorsements against `member_points`, each tagged with its index in the
+    /// caller's list of members, and returns them in that original order.
     ///
-    /// Only interesting for benchmarking. The zkcredential part of the operation may still be
-    /// parallelized.
-    pub fn receive_with_service_ids_single_threaded(
+    /// `check` is called once more before the verification itself, which can't be interrupted.
+    fn receive_member_points<E: From<ZkGroupVerificationFailure>>(
         self,
-        user_ids: impl IntoIterator<Item = libsignal_core::ServiceId>,
-        now: Timestamp,
-        group_params: &GroupSecretParams,
-        server_params: &ServerPublicParams,
-    ) -> Result<Vec<ReceivedEndorsement>, ZkGroupVerificationFailure> {
-        let derived_key = self.derive_public_signing_key_from_expiration(now, server_params)?;
-
-        // The endorsements are sorted by the serialized *ciphertext* representations.
-        // We have to compute the ciphertexts (expensive), but we can skip the second point (which
-        // would be much more expensive).
-        // We zip the results together with a set of indexes so we can un-sort the results later.
-        let mut member_points: Vec<(usize, curve25519_dalek_signal::RistrettoPoint)> = user_ids
-            .into_iter()
-            .map(|user_id| {
-                group_params.uid_enc_key_pair.a1 * crypto::uid_struct::UidStruct::calc_M1(user_id)
-            })
-            .enumerate()
-            .collect();
+        mut member_points: Vec<(usize, curve25519_dalek_signal::RistrettoPoint)>,
+        derived_key: &zkcredential::endorsements::ServerDerivedPublicKey,
+        check: impl Fn() -> Result<(), E>,
+    ) -> Result<Vec<ReceivedEndorsement>, E> {
         Self::sort_points(&mut member_points);
 
+        check()?;
         let endorsements = self
             .endorsements
-            .receive(member_points.iter().map(|(_i, point)| *point), &derived_key)
+            .receive(member_points.iter().map(|(_i, point)| *point), derived_key)
             .map_err(|_| ZkGroupVerificationFailure)?;
 
         Ok(array_utils::collect_permutation(
@@ -228,6 +221,103 @@ impl GroupSendEndorsementsResponse {
         ))
     }
 
+    /// Same as [`receive_with_service_ids`], but without parallelizing the zkgroup-specific parts
+    /// of the operation.
+    ///
+    /// Only interesting for benchmarking. The zkcredential part of the operation may still be
+    /// parallelized if the `rayon` feature is enabled.
+    pub fn receive_with_service_ids_single_threaded(
+        self,
+        user_ids: impl IntoIterator<Item = libsignal_core::ServiceId>,
+        now: Timestamp,
+        group_params: &GroupSecretParams,
+        server_params: &ServerPublicParams,
+    ) -> Result<Vec<ReceivedEndorsement>, ZkGroupVerificationFailure> {
+        let derived_key = self.derive_public_signing_key_from_expiration(now, server_params)?;
+
+        // The endorsements are sorted by the serialized *ciphertext* representations.
+        // We have to compute the ciphertexts (expensive), but we can skip the second point (which
+        // would be much more expensive).
+        // We zip the results together with a set of indexes so we can un-sort the results later.
+        let member_points = user_ids
+            .into_iter()
+            .map(|user_id| {
+                group_params.uid_enc_key_pair.a1 * crypto::uid_struct::UidStruct::calc_M1(user_id)
+            })
+            .enumerate()
+            .collect();
+        self.receive_member_points(member_points, &derived_key, || Ok(()))
+    }
+
+    /// Validates and returns the endorsements issued by the server.
+    ///
+    /// The result will be in the same order as `user_ids`. `user_ids` should contain the current
+    /// user as well.
+    ///
+    /// If you already have the member ciphertexts for the group available,
+    /// [`receive_with_ciphertexts`] will be faster than this method.
+    ///
+    /// Without the `rayon` feature, `user_ids` is processed on the current thread. With it,
+    /// `user_ids` must be a parallel iterator instead; code that should build either way can pass
+    /// a `Vec` or an array.
+    #[cfg(not(feature = "rayon"))]
+    pub fn receive_with_service_ids<T>(
+        self,
+        user_ids: T,
+        now: Timestamp,
+        group_params: &GroupSecretParams,
+        server_params: &ServerPublicParams,
+    ) -> Result<Vec<ReceivedEndorsement>, ZkGroupVerificationFailure>
+    where
+        T: IntoIterator<Item = libsignal_core::ServiceId>,
+        T::IntoIter: ExactSizeIterator,
+    {
+        self.receive_with_service_ids_interruptible(
+            user_ids,
+            now,
+            group_params,
+            server_params,
+            || Ok(()),
+        )
+    }
+
+    /// Like [`receive_with_service_ids`], but gives up with `check`'s error if it fails.
+    ///
+    /// `check` is called before processing each member and once more before verifying the
+    /// endorsements. Verification is a single batched operation, and can't be interrupted.
+    ///
+    /// The bounds match the `rayon` version of this method, so that enabling the feature doesn't
+    /// break callers.
+    #[cfg(not(feature = "rayon"))]
+    pub fn receive_with_service_ids_interruptible<T, E>(
+        self,
+        user_ids: T,
+        now: Timestamp,
+        group_params: &GroupSecretParams,
+        server_params: &ServerPublicParams,
+        check: impl Fn() -> Result<(), E> + Sync,
+    ) -> Result<Vec<ReceivedEndorsement>, E>
+    where
+        T: IntoIterator<Item = libsignal_core::ServiceId>,
+        T::IntoIter: ExactSizeIterator,
+        E: From<ZkGroupVerificationFailure> + Send,
+    {
+        let derived_key = self.derive_public_signing_key_from_expiration(now, server_params)?;
+
+        // See receive_with_service_ids_single_threaded.
+        let member_points = user_ids
+            .into_iter()
+            .enumerate()
+            .map(|(i, user_id)| {
+                check()?;
+                let point = group_params.uid_enc_key_pair.a1
+                    * crypto::uid_struct::UidStruct::calc_M1(user_id);
+                Ok((i, point))
+            })
+            .collect::<Result<_, E>>()?;
+        self.receive_member_points(member_points, &derived_key, check)
+    }
+
     /// Validates and returns the endorsements issued by the server.
     ///
     /// The result will be in the same order as `user_ids`. `user_ids` should contain the current
@@ -235,6 +325,7 @@ impl GroupSendEndorsementsResponse {
     ///
     /// If you already have the member ciphertexts for the group available,
     /// [`receive_with_ciphertexts`] will be faster than this method.
+    #[cfg(feature = "rayon")]
     pub fn receive_with_service_ids<T>(
         self,
         user_ids: T,
@@ -245,6 +336,33 @@ impl GroupSendEndorsementsResponse {
     where
         T: rayon::iter::IntoParallelIterator<Item = libsignal_core::ServiceId>,
         T::Iter: rayon::iter::IndexedParallelIterator,
+    {
+        self.receive_with_service_ids_interruptible(
+            user_ids,
+            now,
+            group_params,
+            server_params,
+            || Ok(()),
+        )
+    }
+
+    /// Like [`receive_with_service_ids`], but gives up with `check`'s error if it fails.
+    ///
+    /// `check` is called before processing each member and once more before verifying the
+    /// endorsements. Verification is a single batched operation, and can't be interrupted.
+    #[cfg(feature = "rayon")]
+    pub fn receive_with_service_ids_interruptible<T, E>(
+        self,
+        user_ids: T,
+        now: Timestamp,
+        group_params: &GroupSecretParams,
+        server_params: &ServerPublicParams,
+        check: impl Fn() -> Result<(), E> + Sync,
+    ) -> Result<Vec<ReceivedEndorsement>, E>
+    where
+        T: rayon::iter::IntoParallelIterator<Item = libsignal_core::ServiceId>,
+        T::Iter: rayon::iter::IndexedParallelIterator,
+        E: From<ZkGroupVerificationFailure> + Send,
     {
         let derived_key = self.derive_public_signing_key_from_expiration(now, server_params)?;
 
@@ -252,37 +370,17 @@ impl GroupSendEndorsementsResponse {
         // We have to compute the ciphertexts (expensive), but we can skip the second point (which
         // would be much more expensive).
         // We zip the results together with a set of indexes so we can un-sort the results later.
-        let mut member_points: Vec<(usize, curve25519_dalek_signal::RistrettoPoint)> = user_ids
+        let member_points = user_ids
             .into_par_iter()
-            .map(|user_id| {
-                group_params.uid_enc_key_pair.a1 * crypto::uid_struct::UidStruct::calc_M1(user_id)
-            })
             .enumerate()
-            .collect();
-        Self::sort_points(&mut member_points);
-
-        let endorsements = self
-            .endorsements
-            .receive(member_points.iter().map(|(_i, point)| *point), &derived_key)
-            .map_err(|_| ZkGroupVerificationFailure)?;
-
-        Ok(array_utils::collect_permutation(
-            endorsements
-                .compressed
-                .into_iter()
-                .zip(endorsements.decompressed)
-                .map(|(compressed, decompressed)| ReceivedEndorsement {
-                    compressed: GroupSendEndorsement {
-                        reserved: ReservedByte::default(),
-                        endorsement: compressed,
-                    },
-                    decompressed: GroupSendEndorsement {
-                        reserved: ReservedByte::default(),
-                        endorsement: decompressed,
-                    },
-                })
-                .zip(member_points.iter().map(|(i, _)| *i)),
-        ))
+            .map(|(i, user_id)| {
+                check()?;
+                let point = group_params.uid_enc_key_pair.a1
+                    * crypto::uid_struct::UidStruct::calc_M1(user_id);
+                Ok((i, point))
+            })
+            .collect::<Result<_, E>>()?;
+        self.receive_member_points(member_points, &derived_key, check)
     }
 
     /// Validates and returns the endorsements issued by the server.
@@ -299,43 +397,37 @@ impl GroupSendEndorsementsResponse {
         now: Timestamp,
         server_params: &ServerPublicParams,
     ) -> Result<Vec<ReceivedEndorsement>, ZkGroupVerificationFailure> {
+        self.receive_with_ciphertexts_interruptible(member_ciphertexts, now, server_params, || {
+            Ok(())
+        })
+    }
+
+    /// Like [`receive_with_ciphertexts`], but gives up with `check`'s error if it fails.
+    ///
+    /// `check` is called before taking each member's ciphertext from `member_ciphertexts` and once
+    /// more before verifying the endorsements. Verification is a single batched operation, and
+    /// can't be interrupted.
+    pub fn receive_with_ciphertexts_interruptible<E: From<ZkGroupVerificationFailure>>(
+        self,
+        member_ciphertexts: impl IntoIterator<Item = UuidCiphertext>,
+        now: Timestamp,
+        server_params: &ServerPublicParams,
+        check: impl Fn() -> Result<(), E>,
+    ) -> Result<Vec<ReceivedEndorsement>, E> {
         let derived_key = self.derive_public_signing_key_from_expiration(now, server_params)?;
 
         // Note: we could save some work here by pulling the single point we need out of the
         // serialized form of UuidCiphertext, and operating directly on that. However, we'd have to
         // remember to update that if the serialization format ever changes.
-        let mut points_to_check: Vec<_> = member_ciphertexts
+        let points_to_check = member_ciphertexts
             .into_iter()
-            .map(|ciphertext| ciphertext.ciphertext.as_points()[0])
             .enumerate()
-            .collect();
-        Self::sort_points(&mut points_to_check);
-
-        let endorsements = self
-            .endorsements
-            .receive(
-                points_to_check.iter().map(|(_i, point)| *point),
-                &derived_key,
-            )
-            .map_err(|_| ZkGroupVerificationFailure)?;
-
-        Ok(array_utils::collect_permutation(
-            endorsements
-                .compressed
-                .into_iter()
-                .zip(endorsements.decompressed)
-                .map(|(compressed, decompressed)| ReceivedEndorsement {
-                    compressed: GroupSendEndorsement {
-                        reserved: ReservedByte::default(),
-                        endorsement: compressed,
-                    },
-                    decompressed: GroupSendEndorsement {
-                        reserved: ReservedByte::default(),
-                        endorsement: decompressed,
-                    },
-                })
-                .zip(points_to_check.iter().map(|(i, _)| *i)),
-        ))
+            .map(|(i, ciphertext)| {
+                check()?;
+                Ok((i, ciphertext.ciphertext.as_points()[0]))
+            })
+            .collect::<Result<_, E>>()?;
+        self.receive_member_points(points_to_check, &derived_key, check)
     }
 }
 
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize, Serializer};
 
 use crate::common::constants::*;
 use crate::common::errors::*;
-use crate::common::serialization::VersionByte;
+use crate::common::serialization::{deserialize_compact, serialize_compact, VersionByte};
 use crate::common::simple_types::*;
 use crate::{api, crypto};
 
@@ -115,6 +115,31 @@ impl AnyProfileKeyCredentialPresentation {
         }
     }
 
+    /// Parses a presentation in the [compact encoding](serialize_compact).
+    pub fn from_compact(compact_bytes: &[u8]) -> Result<Self, ZkGroupDeserializationFailure> {
+        // The first byte identifies the compact encoding; the second is the presentation version.
+        match compact_bytes.get(1) {
+            Some(&PRESENTATION_VERSION_1) => {
+                deserialize_compact::<ProfileKeyCredentialPresentationV1>(compact_bytes)
+                    .map(AnyProfileKeyCredentialPresentation::V1)
+            }
+            Some(&PRESENTATION_VERSION_2) => {
+                deserialize_compact::<ProfileKeyCredentialPresentationV2>(compact_bytes)
+                    .map(AnyProfileKeyCredentialPresentation::V2)
+            }
+            Some(&PRESENTATION_VERSION_3) => {
+                deserialize_compact::<ExpiringProfileKeyCredentialPresentation>(compact_bytes)
+                    .map(AnyProfileKeyCredentialPresentation::V3)
+            }
+            _ => Err(ZkGroupDeserializationFailure::new::<Self>()),
+        }
+    }
+
+    /// Serializes the presentation using the [compact encoding](serialize_compact).
+    pub fn to_compact(&self) -> Vec<u8> {
+        serialize_compact(self)
+    }
+
     pub fn get_uuid_ciphertext(&self) -> api::groups::UuidCiphertext {
         match self {
             AnyProfileKeyCredentialPresentation::V1(presentation) => {
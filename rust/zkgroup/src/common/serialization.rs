@@ -4,6 +4,7 @@
 //
 
 use bincode::Options;
+use libsignal_core::ParseLimits;
 use partial_default::PartialDefault;
 use serde::{Deserialize, Serialize};
 
@@ -16,12 +17,32 @@ fn zkgroup_bincode_options() -> impl bincode::Options {
         .reject_trailing_bytes()
 }
 
+/// Rejects input larger than `limits` allow.
+fn check_size_limit<T>(
+    bytes: &[u8],
+    limits: &ParseLimits,
+) -> Result<(), ZkGroupDeserializationFailure> {
+    limits
+        .check_message_size(bytes.len())
+        .map_err(|_| ZkGroupDeserializationFailure::new::<T>())
+}
+
 /// Deserializes a type using the standard zkgroup encoding (based on bincode).
 ///
-/// The type must support [`PartialDefault`] to save on code size.
+/// The type must support [`PartialDefault`] to save on code size. Input larger than the
+/// process-wide [`ParseLimits`] allow is rejected.
 pub fn deserialize<'a, T: Deserialize<'a> + PartialDefault>(
     bytes: &'a [u8],
 ) -> Result<T, ZkGroupDeserializationFailure> {
+    deserialize_with_limits(bytes, &libsignal_core::parse_limits())
+}
+
+/// Like [`deserialize`], but subject to `limits` rather than the process-wide ones.
+pub fn deserialize_with_limits<'a, T: Deserialize<'a> + PartialDefault>(
+    bytes: &'a [u8],
+    limits: &ParseLimits,
+) -> Result<T, ZkGroupDeserializationFailure> {
+    check_size_limit::<T>(bytes, limits)?;
     let mut result = T::partial_default();
     // Use the same encoding options as plain bincode::deserialize, which we used historically,
     // but also reject trailing bytes.
@@ -41,6 +62,65 @@ pub fn serialize<T: Serialize>(value: &T) -> Vec<u8> {
         .expect("cannot fail")
 }
 
+/// Leading byte of the compact encoding produced by [`serialize_compact`].
+///
+/// Chosen to be distinct from the version and reserved bytes used by the standard encoding, so the
+/// two encodings can't be confused for one another.
+pub const COMPACT_ENCODING_VERSION_1: u8 = 0xC1;
+
+fn compact_bincode_options() -> impl bincode::Options {
+    bincode::DefaultOptions::new()
+        .with_varint_encoding()
+        .with_little_endian()
+        .reject_trailing_bytes()
+}
+
+/// Serializes a type using the compact zkgroup encoding.
+///
+/// This is the standard encoding with variable-length integers (including length prefixes) instead
+/// of fixed-width ones, preceded by [`COMPACT_ENCODING_VERSION_1`]. Group elements and scalars are
+/// already as small as they can be, so the savings come from integer fields like expirations and
+/// from the length prefixes of proofs and tokens. That matters most for small values sent with
+/// every message, like group send tokens.
+pub fn serialize_compact<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut result = vec![COMPACT_ENCODING_VERSION_1];
+    compact_bincode_options()
+        .serialize_into(&mut result, value)
+        .expect("cannot fail");
+    result
+}
+
+/// Deserializes a type from the compact zkgroup encoding produced by [`serialize_compact`].
+pub fn deserialize_compact<'a, T: Deserialize<'a> + PartialDefault>(
+    bytes: &'a [u8],
+) -> Result<T, ZkGroupDeserializationFailure> {
+    check_size_limit::<T>(bytes, &libsignal_core::parse_limits())?;
+    let Some((&COMPACT_ENCODING_VERSION_1, bytes)) = bytes.split_first() else {
+        return Err(ZkGroupDeserializationFailure::new::<T>());
+    };
+    let mut result = T::partial_default();
+    T::deserialize_in_place(
+        &mut bincode::Deserializer::from_slice(bytes, compact_bincode_options()),
+        &mut result,
+    )
+    .map_err(|_| ZkGroupDeserializationFailure::new::<T>())?;
+    Ok(result)
+}
+
+/// Converts a value from the standard encoding to the compact encoding.
+pub fn standard_to_compact<T: Serialize + for<'a> Deserialize<'a> + PartialDefault>(
+    standard: &[u8],
+) -> Result<Vec<u8>, ZkGroupDeserializationFailure> {
+    Ok(serialize_compact(&deserialize::<T>(standard)?))
+}
+
+/// Converts a value from the compact encoding back to the standard encoding.
+pub fn compact_to_standard<T: Serialize + for<'a> Deserialize<'a> + PartialDefault>(
+    compact: &[u8],
+) -> Result<Vec<u8>, ZkGroupDeserializationFailure> {
+    Ok(serialize(&deserialize_compact::<T>(compact)?))
+}
+
 /// Constant version number `C` as a type.
 ///
 /// Zero-sized type that converts to and from for the value `C` via `Into`,
@@ -150,6 +230,42 @@ mod test {
         crate::deserialize::<T>(&serialized).expect_err("invalid version");
     }
 
+    #[test_case(WithReservedByte::test_value())]
+    #[test_case(WithVersionByte::test_value())]
+    fn compact_round_trip<
+        T: Serialize + for<'a> Deserialize<'a> + PartialEq + PartialDefault + Debug,
+    >(
+        test_value: T,
+    ) {
+        let standard = crate::serialize(&test_value);
+        let compact = serialize_compact(&test_value);
+
+        assert_eq!(compact[0], COMPACT_ENCODING_VERSION_1);
+        assert!(compact.len() < standard.len());
+        assert_eq!(
+            deserialize_compact::<T>(&compact).expect("can deserialize"),
+            test_value
+        );
+        assert_eq!(standard_to_compact::<T>(&standard).expect("valid"), compact);
+        assert_eq!(compact_to_standard::<T>(&compact).expect("valid"), standard);
+
+        deserialize_compact::<T>(&standard).expect_err("not compact");
+        crate::deserialize::<T>(&compact).expect_err("not standard");
+    }
+
+    #[test]
+    fn explicit_limits() {
+        let serialized = crate::serialize(&WithReservedByte::test_value());
+        let limits = |max_message_size| ParseLimits {
+            max_message_size,
+            ..ParseLimits::DEFAULT
+        };
+        deserialize_with_limits::<WithReservedByte>(&serialized, &limits(serialized.len()))
+            .expect("within limit");
+        deserialize_with_limits::<WithReservedByte>(&serialized, &limits(serialized.len() - 1))
+            .expect_err("too large");
+    }
+
     #[test]
     fn version_byte_error_message() {
         let mut bincode_serialized =
@@ -106,8 +106,10 @@ impl ProfileKeyEncryptionDomain {
             }
         }
         if n_found == 1 {
+            zkcredential::ct_audit::record("profile_key_encryption::decrypt::matched");
             Ok(retval)
         } else {
+            zkcredential::ct_audit::record("profile_key_encryption::decrypt::unmatched");
             Err(ZkGroupVerificationFailure)
         }
     }
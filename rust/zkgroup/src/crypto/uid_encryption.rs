@@ -76,7 +76,10 @@ impl UidEncryptionDomain {
             .decrypt_to_second_point(ciphertext)
             .map_err(|_| ZkGroupVerificationFailure)?;
         match M2.lizard_decode::<sha2::Sha256>() {
-            None => Err(ZkGroupVerificationFailure),
+            None => {
+                zkcredential::ct_audit::record("uid_encryption::decrypt::undecodable");
+                Err(ZkGroupVerificationFailure)
+            }
             Some(bytes) => {
                 // We want to do a constant-time choice between the ACI and the PNI possibilities.
                 // Only at the end do we do a normal branch to see if decryption succeeded,
@@ -98,10 +101,16 @@ impl UidEncryptionDomain {
                 let mut index = u8::MAX;
                 index.conditional_assign(&0, decrypted_M1.ct_eq(&aci_M1));
                 index.conditional_assign(&1, decrypted_M1.ct_eq(&pni_M1));
-                decoded_service_ids
-                    .get(index as usize)
-                    .copied()
-                    .ok_or(ZkGroupVerificationFailure)
+                match decoded_service_ids.get(index as usize) {
+                    Some(service_id) => {
+                        zkcredential::ct_audit::record("uid_encryption::decrypt::matched");
+                        Ok(*service_id)
+                    }
+                    None => {
+                        zkcredential::ct_audit::record("uid_encryption::decrypt::unmatched");
+                        Err(ZkGroupVerificationFailure)
+                    }
+                }
             }
         }
     }
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use hex_literal::hex;
 use zkgroup::call_links::CallLinkAuthCredentialResponse;
 use zkgroup::{RandomnessBytes, Timestamp, RANDOMNESS_LEN, SECONDS_PER_DAY, UUID_LEN};
 
@@ -250,3 +251,14 @@ fn test_auth_credential_enforces_timestamp_granularity() {
         "client should reject timestamp"
     );
 }
+
+#[test]
+fn test_room_id_known_answer() {
+    use zkgroup::call_links::CallLinkSecretParams;
+
+    let root_key = hex!("000102030405060708090a0b0c0d0e0f");
+    assert_eq!(
+        CallLinkSecretParams::derive_room_id(&root_key),
+        hex!("cd415ffc58771108cdbf029b8d86ee857be167bf28dda87e3e592e3f10eb1c9b")
+    );
+}
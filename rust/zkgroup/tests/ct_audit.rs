@@ -0,0 +1,124 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+//! Checks that secret-dependent code paths do the same amount of work for different secrets.
+//!
+//! Only built with `--features ct-audit`.
+
+#![cfg(feature = "ct-audit")]
+
+use zkcredential::ct_audit;
+use zkgroup::{RandomnessBytes, Timestamp, RANDOMNESS_LEN, UUID_LEN};
+
+const DAY_ALIGNED_TIMESTAMP: Timestamp = Timestamp::from_epoch_seconds(1681344000); // 2023-04-13 00:00:00 UTC
+
+fn call_link_flow(user_byte: u8, room_id: &[u8], seed: u8) -> ct_audit::Counts {
+    let randomness = |n: u8| -> RandomnessBytes { [seed.wrapping_add(n); RANDOMNESS_LEN] };
+    let user_id = libsignal_core::Aci::from_uuid_bytes([user_byte; UUID_LEN]);
+
+    let server_secret_params =
+        zkgroup::generic_server_params::GenericServerSecretParams::generate(randomness(0));
+    let server_public_params = server_secret_params.get_public_params();
+    let client_secret_params =
+        zkgroup::call_links::CallLinkSecretParams::derive_from_root_key(&randomness(1));
+
+    _ = ct_audit::take();
+
+    let request_context =
+        zkgroup::call_links::CreateCallLinkCredentialRequestContext::new(room_id, randomness(2));
+    let response = request_context.get_request().issue(
+        user_id,
+        DAY_ALIGNED_TIMESTAMP,
+        &server_secret_params,
+        randomness(3),
+    );
+    let credential = request_context
+        .receive(response, user_id, &server_public_params)
+        .expect("credential should be valid");
+    let presentation = credential.present(
+        room_id,
+        user_id,
+        &server_public_params,
+        &client_secret_params,
+        randomness(4),
+    );
+    presentation
+        .verify(
+            room_id,
+            DAY_ALIGNED_TIMESTAMP,
+            &server_secret_params,
+            &client_secret_params.get_public_params(),
+        )
+        .expect("presentation should be valid");
+
+    ct_audit::take()
+}
+
+fn group_decryption_flow(
+    master_key_byte: u8,
+    uuid_byte: u8,
+    profile_key_byte: u8,
+) -> ct_audit::Counts {
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new([master_key_byte; 32]),
+    );
+    let aci = libsignal_core::Aci::from_uuid_bytes([uuid_byte; UUID_LEN]);
+    let profile_key = zkgroup::profiles::ProfileKey::create([profile_key_byte; 32]);
+    let uid_ciphertext = group_secret_params.encrypt_service_id(aci.into());
+    let profile_key_ciphertext = group_secret_params.encrypt_profile_key(profile_key, aci);
+
+    _ = ct_audit::take();
+
+    group_secret_params
+        .decrypt_service_id(uid_ciphertext)
+        .expect("valid ciphertext");
+    group_secret_params
+        .decrypt_profile_key(profile_key_ciphertext, aci)
+        .expect("valid ciphertext");
+
+    ct_audit::take()
+}
+
+#[test]
+fn call_link_issuance_and_presentation_do_not_depend_on_secrets() {
+    let first = call_link_flow(0x04, b"a very special room", 0x42);
+    let second = call_link_flow(0xA7, b"a different room", 0x99);
+    assert_eq!(first.get("issuance::verify::accepted"), 1);
+    assert_eq!(first.get("presentation::verify::accepted"), 1);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn group_decryption_does_not_depend_on_secrets() {
+    let first = group_decryption_flow(0x01, 0x02, 0x03);
+    let second = group_decryption_flow(0xF0, 0xE1, 0xD2);
+    assert_eq!(first.get("uid_encryption::decrypt::matched"), 1);
+    assert_eq!(first.get("profile_key_encryption::decrypt::matched"), 1);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn failed_decryption_is_counted() {
+    // Checks that the counters actually see secret-dependent branches: decrypting with the wrong
+    // key takes the other arm.
+    let group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new([0x01; 32]),
+    );
+    let other_group_secret_params = zkgroup::groups::GroupSecretParams::derive_from_master_key(
+        zkgroup::groups::GroupMasterKey::new([0x02; 32]),
+    );
+    let aci = libsignal_core::Aci::from_uuid_bytes([0x03; UUID_LEN]);
+    let profile_key = zkgroup::profiles::ProfileKey::create([0x04; 32]);
+    let profile_key_ciphertext = group_secret_params.encrypt_profile_key(profile_key, aci);
+
+    _ = ct_audit::take();
+    assert!(other_group_secret_params
+        .decrypt_profile_key(profile_key_ciphertext, aci)
+        .is_err());
+    let counts = ct_audit::take();
+
+    assert_eq!(counts.get("profile_key_encryption::decrypt::matched"), 0);
+    assert_eq!(counts.get("profile_key_encryption::decrypt::unmatched"), 1);
+}
@@ -81,6 +81,21 @@ fn test_endorsement() {
                 &todays_key,
             )
             .expect("credential should be valid for the timestamp given");
+
+        // the compact encoding is smaller and converts back losslessly
+        let token_bytes = zkgroup::serialize(&token);
+        let compact_token_bytes = zkgroup::common::serialization::standard_to_compact::<
+            zkgroup::groups::GroupSendFullToken,
+        >(&token_bytes)
+        .expect("valid token");
+        assert!(compact_token_bytes.len() < token_bytes.len());
+        assert_eq!(
+            token_bytes,
+            zkgroup::common::serialization::compact_to_standard::<
+                zkgroup::groups::GroupSendFullToken,
+            >(&compact_token_bytes)
+            .expect("valid compact token")
+        );
     }
 
     // Try again for receive_with_ciphertexts:
@@ -359,6 +359,18 @@ fn test_integration_expiring_profile() {
         presentation_any_bytes[..]
     );
 
+    let presentation_compact_bytes = presentation_any.to_compact();
+    assert!(presentation_compact_bytes.len() < presentation_any_bytes.len());
+    let presentation_from_compact =
+        zkgroup::profiles::AnyProfileKeyCredentialPresentation::from_compact(
+            &presentation_compact_bytes,
+        )
+        .unwrap();
+    assert_hex_eq!(
+        PROFILE_KEY_CREDENTIAL_PRESENTATION_V3_RESULT[..],
+        bincode::serialize(&presentation_from_compact).unwrap()[..]
+    );
+
     server_secret_params
         .verify_profile_key_credential_presentation(
             group_public_params,
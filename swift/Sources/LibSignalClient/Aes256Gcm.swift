@@ -105,10 +105,48 @@ public class Aes256GcmEncryption: NativeHandleOwner {
         self.init(owned: handle!)
     }
 
+    /// Starts encrypting with the key and next nonce from `nonces`.
+    ///
+    /// The nonce must be sent along with the ciphertext; see ``nonce``. Throws
+    /// ``SignalError/invalidState(_:)`` once `nonces` has reached its limit, in which case the key
+    /// must be replaced. Errors thrown by `store` are rethrown, and no nonce is used up.
+    public convenience init(
+        nonces: NonceSequence,
+        store: NonceStore,
+        associatedData: some ContiguousBytes
+    ) throws {
+        let handle: OpaquePointer? = try nonces.withNativeHandle { noncesHandle in
+            try withNonceStore(store) { ffiStore in
+                try associatedData.withUnsafeBorrowedBuffer { adBuffer in
+                    var result: OpaquePointer?
+                    try checkError(signal_aes256_gcm_encryption_new_with_nonce_sequence(
+                        &result,
+                        noncesHandle,
+                        ffiStore,
+                        adBuffer
+                    ))
+                    return result
+                }
+            }
+        }
+        self.init(owned: handle!)
+    }
+
     override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
         return signal_aes256_gcm_encryption_destroy(handle)
     }
 
+    /// The nonce this encryption uses.
+    public var nonce: Data {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningData {
+                    signal_aes256_gcm_encryption_get_nonce($0, nativeHandle)
+                }
+            }
+        }
+    }
+
     public func encrypt(_ message: inout Data) throws {
         try withNativeHandle { nativeHandle in
             try message.withUnsafeMutableBytes { messageBytes in
@@ -56,10 +56,26 @@ extension SignalCPromiseFfiResponseAndDebugInfo: PromiseStruct {
     typealias Result = SignalFfiResponseAndDebugInfo
 }
 
+extension SignalCPromiseFfiBackgroundFlushReport: PromiseStruct {
+    typealias Result = SignalFfiBackgroundFlushReport
+}
+
+extension SignalCPromiseSenderCertificate: PromiseStruct {
+    typealias Result = OpaquePointer
+}
+
 extension SignalCPromiseOwnedBufferOfc_uchar: PromiseStruct {
     typealias Result = SignalOwnedBuffer
 }
 
+extension SignalCPromiseMessageBackupValidationOutcome: PromiseStruct {
+    typealias Result = OpaquePointer
+}
+
+extension SignalCPromiseSanitizedMetadata: PromiseStruct {
+    typealias Result = OpaquePointer
+}
+
 /// A type-erased version of ``Completer``.
 ///
 /// Not for direct use, see Completer instead.
@@ -0,0 +1,28 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+import SignalFfi
+
+/// Identifies the Rust code that LibSignalClient's native functions were generated from.
+public enum BridgeManifest {
+    /// Checks that the linked libsignal_ffi was built from the same Rust code as signal_ffi.h.
+    ///
+    /// A mismatch can only happen if libsignal_ffi is built separately from LibSignalClient, for
+    /// example when using a prebuilt library. Apps that do so should call this at startup, before
+    /// using any other API.
+    ///
+    /// - Throws: `SignalError.invalidState` if the library doesn't match.
+    public static func verify() throws {
+        let loadedSha256 = failOnError {
+            try invokeFnReturningString {
+                signal_bridge_manifest_get_sha256($0)
+            }
+        }
+        guard loadedSha256 == self.expectedSha256 else {
+            throw SignalError.invalidState("libsignal_ffi does not match LibSignalClient (bridge manifest \(loadedSha256), expected \(self.expectedSha256))")
+        }
+    }
+}
@@ -0,0 +1,12 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+// WARNING: this file was automatically generated
+
+extension BridgeManifest {
+    /// The hash of the bridge manifest (rust/bridge/shared/bridge_manifest.json) that signal_ffi.h
+    /// was generated from.
+    internal static let expectedSha256 = "e7779331df4cd98645e8751c50d258f9017c203201d21b5fad3a8432a4db3e6a"
+}
@@ -0,0 +1,46 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+import SignalFfi
+
+/// Process-wide call counts and timings for libsignal's native functions.
+///
+/// Recording is off by default. Durations cover the synchronous part of each call, including
+/// argument conversion; for async APIs, that's only the time taken to start the operation.
+public enum BridgeMetrics {
+    /// Call statistics for a single native function.
+    public struct CallStats: Codable, Equatable, Sendable {
+        /// The function's name in the C API, without the `signal_` prefix.
+        public var name: String
+        public var calls: UInt64
+        /// Calls that returned an error.
+        public var errors: UInt64
+        public var totalMicros: UInt64
+        public var maxMicros: UInt64
+    }
+
+    /// Turns recording on or off. Turning it off keeps the statistics gathered so far.
+    public static func setEnabled(_ enabled: Bool) {
+        failOnError(signal_bridge_metrics_set_enabled(enabled))
+    }
+
+    /// Returns the statistics recorded so far, sorted by function name.
+    public static func snapshot() -> [CallStats] {
+        let json = failOnError {
+            try invokeFnReturningString {
+                signal_bridge_metrics_snapshot($0)
+            }
+        }
+        return failOnError {
+            try JSONDecoder().decode([CallStats].self, from: Data(json.utf8))
+        }
+    }
+
+    /// Discards the statistics recorded so far.
+    public static func reset() {
+        failOnError(signal_bridge_metrics_reset())
+    }
+}
@@ -79,6 +79,34 @@ public class CdsiLookupRequest: NativeHandleOwner {
 /// CDSI lookup in progress.
 ///
 /// Returned by ``Net/cdsiLookup(auth:request:)`` when a request is successfully initiated.
+/// Keeps attested CDSI connections ready for upcoming lookups.
+///
+/// Every CDSI lookup needs its own connection, including a fresh attestation
+/// handshake. When a pool is passed to ``Net/cdsiLookup(auth:request:pool:prepareNext:)``,
+/// the connection for the next lookup can be established while the current one
+/// is still running, which speeds up a burst of lookups such as a contact sync.
+///
+/// Pooled connections are only reused with the credentials they were
+/// established with; using the pool with new credentials drops any connections
+/// made with the old ones. A connection that has been idle for longer than the
+/// pool's idle timeout is discarded rather than reused.
+public class CdsiConnectionPool: NativeHandleOwner, @unchecked Sendable {
+    /// Creates a pool whose connections are discarded after being idle for
+    /// `idleTimeout` seconds.
+    ///
+    /// The timeout should be short enough that the server won't have closed an
+    /// idle connection by then.
+    public convenience init(idleTimeout: TimeInterval = 10) {
+        var handle: OpaquePointer?
+        failOnError(signal_cdsi_connection_pool_new(&handle, UInt32(idleTimeout * 1000)))
+        self.init(owned: handle!)
+    }
+
+    override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        signal_cdsi_connection_pool_destroy(handle)
+    }
+}
+
 public class CdsiLookup {
     class NativeCdsiLookup: NativeHandleOwner {
         override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
@@ -140,6 +168,90 @@ public struct CdsiLookupResponse {
     public let debugPermitsUsed: Int32
 }
 
+/// A difference between a client's contact mapping and a ``CdsiLookupResponse``.
+///
+/// Produced by ``CdsiLookupResponse/reconcile(existing:)``.
+public enum CdsiContactChange: Equatable, Sendable {
+    /// The number resolved to an ACI the client did not have on record.
+    case newAci(e164: UInt64, aci: Aci)
+    /// The number's PNI is different from (or newly added to) the one on record.
+    case changedPni(e164: UInt64, pni: Pni?)
+    /// The number was looked up but is no longer registered.
+    case removed(e164: UInt64)
+}
+
+private func packCdsiEntries<Entries: Sequence>(_ entries: Entries) -> [UInt8] where Entries.Element == CdsiLookupResponseEntry {
+    var packed: [UInt8] = []
+    for entry in entries {
+        withUnsafeBytes(of: entry.e164.bigEndian) { packed.append(contentsOf: $0) }
+        withUnsafeBytes(of: entry.rawPniUuid) { packed.append(contentsOf: $0) }
+        withUnsafeBytes(of: entry.rawAciUuid) { packed.append(contentsOf: $0) }
+    }
+    return packed
+}
+
+extension CdsiLookupResponse {
+    /// Compares the lookup results with the client's existing mapping from phone numbers to
+    /// service IDs.
+    ///
+    /// A number that appears in the response without an ACI does not clear an ACI that's already
+    /// on record, since the server only returns ACIs for which the client proved knowledge of an
+    /// access key. Every number in `existing` is expected to have been part of the request; any
+    /// that are missing from the response, or that came back with neither an ACI nor a PNI, are
+    /// reported as ``CdsiContactChange/removed(e164:)``.
+    public func reconcile<Existing: Sequence>(existing: Existing) -> [CdsiContactChange] where Existing.Element == CdsiLookupResponseEntry {
+        let changes: CdsiContactChanges = failOnError {
+            try packCdsiEntries(existing).withUnsafeBorrowedBuffer { existing in
+                try packCdsiEntries(self.entries).withUnsafeBorrowedBuffer { response in
+                    try invokeFnReturningNativeHandle {
+                        signal_lookup_response_reconcile($0, existing, response)
+                    }
+                }
+            }
+        }
+        return changes.withNativeHandle { changes in
+            failOnError { () throws -> [CdsiContactChange] in
+                let count = try invokeFnReturningInteger {
+                    signal_cdsi_contact_changes_count($0, changes)
+                }
+                return try (0..<count).map { index -> CdsiContactChange in
+                    let e164String = try invokeFnReturningString {
+                        signal_cdsi_contact_changes_get_e164($0, changes, index)
+                    }
+                    let e164 = UInt64(e164String.dropFirst())!
+                    let kind = try invokeFnReturningInteger {
+                        signal_cdsi_contact_changes_get_kind($0, changes, index)
+                    }
+                    switch kind {
+                    case 0:
+                        let aci = try invokeFnReturningString {
+                            signal_cdsi_contact_changes_get_aci($0, changes, index)
+                        }
+                        return .newAci(e164: e164, aci: try Aci.parseFrom(serviceIdString: aci))
+                    case 1:
+                        let pni = try invokeFnReturningOptionalString {
+                            signal_cdsi_contact_changes_get_pni($0, changes, index)
+                        }
+                        return .changedPni(e164: e164, pni: try pni.map { try Pni.parseFrom(serviceIdString: $0) })
+                    case 2:
+                        return .removed(e164: e164)
+                    default:
+                        fatalError("unexpected contact change kind \(kind)")
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The changes produced by ``CdsiLookupResponse/reconcile(existing:)``, before they are converted
+/// to ``CdsiContactChange`` values.
+private class CdsiContactChanges: NativeHandleOwner {
+    override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        return signal_cdsi_contact_changes_destroy(handle)
+    }
+}
+
 /// Entries received from the CDSI server in response to a lookup request.
 ///
 /// Contains a sequence of ``CdsiLookupResponseEntry`` values. Conforms
@@ -201,7 +313,7 @@ extension CdsiLookupResponseEntry: CdsiLookupResponseEntryProtocol {
         return pniUuid != UUID(uuid: nilUuid) ? Pni(fromUUID: pniUuid) : nil
     }
 
-    init(e164: UInt64, _ aci: Aci?, _ pni: Pni?) {
+    public init(e164: UInt64, _ aci: Aci?, _ pni: Pni?) {
         self.init(
             e164: e164,
             rawAciUuid: aci?.rawUUID.uuid ?? nilUuid,
@@ -37,6 +37,22 @@ extension ChatListener {
     public func chatServiceDidReceiveQueueEmpty(_: AuthenticatedChatService) {}
 }
 
+private class ChatListenerTaskOwner: NativeHandleOwner {
+    override class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        signal_chat_listener_task_destroy(handle)
+    }
+}
+
+/// Runs a task from a listener's `execute` callback on `queue`, then destroys it.
+private func runChatListenerTask(_ task: OpaquePointer, on queue: DispatchQueue) {
+    let taskOwner = ChatListenerTaskOwner(owned: task)
+    queue.async {
+        taskOwner.withNativeHandle { task in
+            failOnError(signal_chat_listener_task_run(task))
+        }
+    }
+}
+
 internal class ChatListenerBridge {
     private class AckHandleOwner: NativeHandleOwner {
         override class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
@@ -46,10 +62,12 @@ internal class ChatListenerBridge {
 
     weak var chatService: AuthenticatedChatService?
     let chatListener: any ChatListener
+    let queue: DispatchQueue?
 
-    init(chatService: AuthenticatedChatService, chatListener: any ChatListener) {
+    init(chatService: AuthenticatedChatService, chatListener: any ChatListener, queue: DispatchQueue?) {
         self.chatService = chatService
         self.chatListener = chatListener
+        self.queue = queue
     }
 
     /// Creates an **owned** callback struct from this object.
@@ -93,6 +111,10 @@ internal class ChatListenerBridge {
 
             bridge.chatListener.connectionWasInterrupted(chatService, error: error)
         }
+        let execute: SignalExecuteChatListenerTask = { rawCtx, task in
+            let bridge = Unmanaged<ChatListenerBridge>.fromOpaque(rawCtx!).takeUnretainedValue()
+            runChatListenerTask(task!, on: bridge.queue!)
+        }
 
         return .init(
             ctx: Unmanaged.passRetained(self).toOpaque(),
@@ -101,7 +123,8 @@ internal class ChatListenerBridge {
             connection_interrupted: connectionInterrupted,
             destroy: { rawCtx in
                 _ = Unmanaged<AnyObject>.fromOpaque(rawCtx!).takeRetainedValue()
-            }
+            },
+            execute: self.queue == nil ? nil : execute
         )
     }
 }
@@ -109,10 +132,12 @@ internal class ChatListenerBridge {
 internal final class UnauthConnectionEventsListenerBridge {
     weak var chatService: UnauthenticatedChatService?
     let listener: any ConnectionEventsListener<UnauthenticatedChatService>
+    let queue: DispatchQueue?
 
-    init(chatService: UnauthenticatedChatService, listener: any ConnectionEventsListener<UnauthenticatedChatService>) {
+    init(chatService: UnauthenticatedChatService, listener: any ConnectionEventsListener<UnauthenticatedChatService>, queue: DispatchQueue?) {
         self.chatService = chatService
         self.listener = listener
+        self.queue = queue
     }
 
     /// Creates an **owned** callback struct from this object.
@@ -136,6 +161,10 @@ internal final class UnauthConnectionEventsListenerBridge {
 
             bridge.listener.connectionWasInterrupted(chatService, error: error)
         }
+        let execute: SignalExecuteChatListenerTask = { rawCtx, task in
+            let bridge = Unmanaged<UnauthConnectionEventsListenerBridge>.fromOpaque(rawCtx!).takeUnretainedValue()
+            runChatListenerTask(task!, on: bridge.queue!)
+        }
 
         return .init(
             ctx: Unmanaged.passRetained(self).toOpaque(),
@@ -144,7 +173,8 @@ internal final class UnauthConnectionEventsListenerBridge {
             connection_interrupted: connectionInterrupted,
             destroy: { rawCtx in
                 _ = Unmanaged<AnyObject>.fromOpaque(rawCtx!).takeRetainedValue()
-            }
+            },
+            execute: self.queue == nil ? nil : execute
         )
     }
 }
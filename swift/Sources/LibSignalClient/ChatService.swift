@@ -28,7 +28,7 @@ public protocol ChatService: AnyObject {
 
     /// Sends a request to the Chat Service.
     ///
-    /// - Throws: ``SignalError/chatServiceInactive(_:)`` if you haven't called ``connect()``
+    /// - Throws: ``SignalError/chatServiceInactive(_:disconnectInfo:)`` if you haven't called ``connect()``
     /// - Throws: Other ``SignalError``s for other kinds of failures.
     /// - SeeAlso: ``send(_:)``
     func send(_ request: Request) async throws -> Response
@@ -38,16 +38,27 @@ public protocol ChatService: AnyObject {
     /// In addition to the response, an object containing debug information about the request flow
     /// is returned.
     ///
-    /// - Throws: ``SignalError/chatServiceInactive(_:)`` if you haven't called ``connect()``
+    /// - Throws: ``SignalError/chatServiceInactive(_:disconnectInfo:)`` if you haven't called ``connect()``
     /// - Throws: Other ``SignalError``s for other kinds of failures.
     /// - SeeAlso: ``send(_:)``
     func sendAndDebug(_ request: Request) async throws -> (Response, DebugInfo)
+
+    /// Finishes outstanding work and then disconnects, for when the app is about to be suspended.
+    ///
+    /// Pending acks for received messages and in-flight requests are given as much of
+    /// `timeRemaining` as possible to complete, leaving a little time to close the connection
+    /// cleanly. New requests fail with ``SignalError/chatServiceIntentionallyDisconnected(_:)``
+    /// until ``connect()`` is called again.
+    ///
+    /// Returns a report of which work was and wasn't flushed.
+    func prepareForBackground(timeRemaining: TimeInterval) async throws -> BackgroundFlushReport
 }
 
 extension ChatService {
     public typealias Request = ChatRequest
     public typealias Response = ChatResponse
     public typealias DebugInfo = ChatServiceDebugInfo
+    public typealias BackgroundFlushReport = ChatServiceBackgroundFlushReport
 }
 
 /// Represents an API of authenticated communication with the Chat Service.
@@ -76,11 +87,15 @@ public class AuthenticatedChatService: NativeHandleOwner, ChatService {
     /// Sets (or clears) the listener for server push messages.
     ///
     /// Takes ownership of the listener; be careful this doesn't lead to a reference cycle (unless the owner lives forever anyway).
-    public func setListener(_ listener: (any ChatListener)?) {
+    ///
+    /// If `queue` is provided, the listener's methods will be called on it, in order, with each
+    /// call finishing before the next message is delivered. Otherwise they will be called on an
+    /// arbitrary background thread (but still one at a time).
+    public func setListener(_ listener: (any ChatListener)?, queue: DispatchQueue? = nil) {
         self.tokioAsyncContext.withNativeHandle { tokioAsyncContext in
             withNativeHandle { chatService in
                 if let listener {
-                    var listenerStruct = ChatListenerBridge(chatService: self, chatListener: listener).makeListenerStruct()
+                    var listenerStruct = ChatListenerBridge(chatService: self, chatListener: listener, queue: queue).makeListenerStruct()
                     failOnError(signal_chat_service_set_listener_auth(tokioAsyncContext, chatService, &listenerStruct))
                 } else {
                     failOnError(signal_chat_service_set_listener_auth(tokioAsyncContext, chatService, nil))
@@ -101,7 +116,7 @@ public class AuthenticatedChatService: NativeHandleOwner, ChatService {
     ///   the server).
     /// - Throws: ``SignalError/deviceDeregistered(_:)`` if the current device has been deregistered
     ///   or delinked.
-    /// - Throws: ``SignalError/rateLimitedError(_:, _:)`` if the server
+    /// - Throws: ``SignalError/rateLimitedError(retryAfter:scope:reason:message:)`` if the server
     ///   response indicates the request should be tried again after some time.
     /// - Throws: Other ``SignalError``s for other kinds of failures.
     @discardableResult
@@ -131,7 +146,7 @@ public class AuthenticatedChatService: NativeHandleOwner, ChatService {
 
     /// Sends request to the Chat Service over an authenticated channel.
     ///
-    /// - Throws: ``SignalError/chatServiceInactive(_:)`` if you haven't called ``connect()``
+    /// - Throws: ``SignalError/chatServiceInactive(_:disconnectInfo:)`` if you haven't called ``connect()``
     /// - Throws: Other ``SignalError``s for other kinds of failures.
     /// - SeeAlso: ``sendAndDebug(_:)``
     public func send(_ request: Request) async throws -> Response {
@@ -152,7 +167,7 @@ public class AuthenticatedChatService: NativeHandleOwner, ChatService {
     /// In addition to the response, an object containing debug information about the request flow
     /// is returned.
     ///
-    /// - Throws: ``SignalError/chatServiceInactive(_:)`` if you haven't called ``connect()``
+    /// - Throws: ``SignalError/chatServiceInactive(_:disconnectInfo:)`` if you haven't called ``connect()``
     /// - Throws: Other ``SignalError``s for other kinds of failures.
     /// - SeeAlso: ``send(_:)``
     public func sendAndDebug(_ request: Request) async throws -> (Response, DebugInfo) {
@@ -167,6 +182,153 @@ public class AuthenticatedChatService: NativeHandleOwner, ChatService {
         }
         return (try Response(consuming: rawResponse.response), DebugInfo(consuming: rawResponse.debug_info))
     }
+
+    /// Fetches a new sender certificate for sealed-sender sends.
+    ///
+    /// Use ``SenderCertificate/needsRefresh(time:refreshMargin:)`` to decide when a cached
+    /// certificate should be replaced. The returned certificate has not been validated against a
+    /// trust root.
+    ///
+    /// - Parameter includeE164: Whether the certificate should include this account's phone number.
+    /// - Throws: ``SignalError/chatServiceInactive(_:disconnectInfo:)`` if you haven't called ``connect()``
+    /// - Throws: ``SignalError/networkProtocolError(_:)`` if the server's response can't be parsed.
+    /// - Throws: Other ``SignalError``s for other kinds of failures.
+    public func fetchSenderCertificate(includeE164: Bool, timeout: TimeInterval) async throws -> SenderCertificate {
+        let timeoutMillis = ChatRequest.timeoutMillis(timeout)
+        let handle: OpaquePointer = try await self.tokioAsyncContext.invokeAsyncFunction { promise, tokioAsyncContext in
+            withNativeHandle { chatService in
+                signal_chat_service_auth_fetch_sender_certificate(promise, tokioAsyncContext, chatService, includeE164, timeoutMillis)
+            }
+        }
+        return SenderCertificate(owned: handle)
+    }
+
+    /// Fetches an expiring profile key credential for `aci`'s `profileKey`.
+    ///
+    /// This makes the request and immediately checks and unblinds the server's response, the way
+    /// ``ClientZkProfileOperations/receiveExpiringProfileKeyCredential(profileKeyCredentialRequestContext:profileKeyCredentialResponse:now:)``
+    /// would, so the result is ready to store.
+    ///
+    /// - Throws: ``SignalError/chatServiceInactive(_:disconnectInfo:)`` if you haven't called ``connect()``
+    /// - Throws: ``SignalError/notFound(_:)`` if `profileKey` is out of date
+    /// - Throws: ``SignalError/requestUnauthorized(_:)`` if the server refuses to issue the credential
+    /// - Throws: ``SignalError/networkProtocolError(_:)`` if the server's response can't be parsed
+    ///   or verified.
+    /// - Throws: Other ``SignalError``s for other kinds of failures.
+    public func fetchProfileKeyCredential(
+        serverPublicParams: ServerPublicParams,
+        aci: Aci,
+        profileKey: ProfileKey,
+        timeout: TimeInterval
+    ) async throws -> ExpiringProfileKeyCredential {
+        let timeoutMillis = ChatRequest.timeoutMillis(timeout)
+        let randomness = try Randomness.generate()
+        let now = UInt64(Date().timeIntervalSince1970)
+        let output: SignalOwnedBuffer = try await self.tokioAsyncContext.invokeAsyncFunction { promise, tokioAsyncContext in
+            withNativeHandle { chatService in
+                serverPublicParams.withNativeHandle { serverPublicParams in
+                    randomness.withUnsafePointerToBytes { randomness in
+                        aci.withPointerToFixedWidthBinary { aci in
+                            failOnError {
+                                try profileKey.withUnsafePointerToSerialized { profileKey in
+                                    signal_chat_service_auth_fetch_profile_key_credential(promise, tokioAsyncContext, chatService, serverPublicParams, randomness, aci, profileKey, now, timeoutMillis)
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        defer {
+            signal_free_buffer(output.base, output.length)
+        }
+        return try ExpiringProfileKeyCredential(contents: Array(UnsafeBufferPointer(start: output.base, count: output.length)))
+    }
+
+    /// Searches the key transparency log for `searchKey`, returning the value it maps to.
+    ///
+    /// `cache` is checked first; on a miss the server's response is verified and added to the
+    /// cache before being returned. `signatureKey`, `vrfKey`, and `auditorKey` are the log's
+    /// signature key, VRF key, and third-party auditor key, each 32 bytes long.
+    ///
+    /// - Throws: ``SignalError/chatServiceInactive(_:disconnectInfo:)`` if you haven't called ``connect()``
+    /// - Throws: ``SignalError/networkProtocolError(_:)`` if the server's response can't be parsed
+    ///   or verified.
+    /// - Throws: Other ``SignalError``s for other kinds of failures.
+    public func searchKeyTransparency(
+        cache: KeyTransparencySearchCache,
+        signatureKey: [UInt8],
+        vrfKey: [UInt8],
+        auditorKey: [UInt8],
+        searchKey: [UInt8],
+        timeout: TimeInterval
+    ) async throws -> [UInt8] {
+        let timeoutMillis = ChatRequest.timeoutMillis(timeout)
+        let signatureKey = try ByteArray(newContents: signatureKey, expectedLength: 32)
+        let vrfKey = try ByteArray(newContents: vrfKey, expectedLength: 32)
+        let auditorKey = try ByteArray(newContents: auditorKey, expectedLength: 32)
+        let output: SignalOwnedBuffer = try await self.tokioAsyncContext.invokeAsyncFunction { promise, tokioAsyncContext in
+            withNativeHandle { chatService in
+                cache.withNativeHandle { cache in
+                    failOnError {
+                        try signatureKey.withUnsafePointerToSerialized { signatureKey in
+                            try vrfKey.withUnsafePointerToSerialized { vrfKey in
+                                try auditorKey.withUnsafePointerToSerialized { auditorKey in
+                                    try searchKey.withUnsafeBorrowedBuffer { searchKey in
+                                        signal_chat_service_auth_search_key_transparency(promise, tokioAsyncContext, chatService, cache, signatureKey, vrfKey, auditorKey, searchKey, timeoutMillis)
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        defer {
+            signal_free_buffer(output.base, output.length)
+        }
+        return Array(UnsafeBufferPointer(start: output.base, count: output.length))
+    }
+
+    /// Queues a receipt for the message from `sender` sent at `messageTimestamp`.
+    ///
+    /// Queued receipts are grouped by sender and kind into as few receipt messages as possible; take
+    /// them with ``nextReceiptBatch()``. The receipt messages themselves may be sent over either chat
+    /// connection.
+    public func queueReceipt(sender: ServiceId, kind: ReceiptBatch.Kind, messageTimestamp: UInt64) {
+        withNativeHandle { chatService in
+            sender.withPointerToFixedWidthBinary { sender in
+                failOnError(signal_chat_service_auth_queue_receipt(chatService, sender, kind.rawValue, messageTimestamp))
+            }
+        }
+    }
+
+    /// Takes the next batch of queued receipts, or returns `nil` if there are none.
+    ///
+    /// Each batch should be sent as one receipt message. Batches not yet taken stay queued, and can
+    /// still absorb new receipts.
+    public func nextReceiptBatch() -> ReceiptBatch? {
+        return withNativeHandle { chatService in
+            failOnError {
+                try invokeFnReturningOptionalNativeHandle {
+                    signal_chat_service_auth_next_receipt_batch($0, chatService)
+                }
+            }
+        }
+    }
+
+    /// Finishes outstanding work and then disconnects, for when the app is about to be suspended.
+    ///
+    /// - SeeAlso: ``ChatService/prepareForBackground(timeRemaining:)``
+    public func prepareForBackground(timeRemaining: TimeInterval) async throws -> BackgroundFlushReport {
+        let budgetMillis = ChatRequest.timeoutMillis(timeRemaining)
+        let rawReport: SignalFfiBackgroundFlushReport = try await self.tokioAsyncContext.invokeAsyncFunction { promise, tokioAsyncContext in
+            withNativeHandle { chatService in
+                signal_chat_service_prepare_for_background_auth(promise, tokioAsyncContext, chatService, budgetMillis)
+            }
+        }
+        return BackgroundFlushReport(rawReport)
+    }
 }
 
 /// Represents an API of unauthenticated communication with the Chat Service.
@@ -195,11 +357,15 @@ public class UnauthenticatedChatService: NativeHandleOwner, ChatService {
     /// Sets (or clears) the listener for connection events.
     ///
     /// Takes ownership of the listener; be careful this doesn't lead to a reference cycle (unless the owner lives forever anyway).
-    public func setListener(_ listener: (any ConnectionEventsListener<UnauthenticatedChatService>)?) {
+    ///
+    /// If `queue` is provided, the listener's methods will be called on it, in order, with each
+    /// call finishing before the next event is delivered. Otherwise they will be called on an
+    /// arbitrary background thread (but still one at a time).
+    public func setListener(_ listener: (any ConnectionEventsListener<UnauthenticatedChatService>)?, queue: DispatchQueue? = nil) {
         self.tokioAsyncContext.withNativeHandle { tokioAsyncContext in
             withNativeHandle { chatService in
                 if let listener {
-                    var listenerStruct = UnauthConnectionEventsListenerBridge(chatService: self, listener: listener).makeListenerStruct()
+                    var listenerStruct = UnauthConnectionEventsListenerBridge(chatService: self, listener: listener, queue: queue).makeListenerStruct()
                     failOnError(signal_chat_service_set_listener_unauth(tokioAsyncContext, chatService, &listenerStruct))
                 } else {
                     failOnError(signal_chat_service_set_listener_unauth(tokioAsyncContext, chatService, nil))
@@ -215,7 +381,7 @@ public class UnauthenticatedChatService: NativeHandleOwner, ChatService {
     ///
     /// - Throws: ``SignalError/appExpired(_:)`` if the current app version is too old (as judged by
     ///   the server).
-    /// - Throws: ``SignalError/rateLimitedError(_:, _:)`` if the server
+    /// - Throws: ``SignalError/rateLimitedError(retryAfter:scope:reason:message:)`` if the server
     ///   response indicates the request should be tried again after some time.
     /// - Throws: Other ``SignalError``s for other kinds of failures.
     @discardableResult
@@ -245,7 +411,7 @@ public class UnauthenticatedChatService: NativeHandleOwner, ChatService {
 
     /// Sends request to the Chat Service over an unauthenticated channel.
     ///
-    /// - Throws: ``SignalError/chatServiceInactive(_:)`` if you haven't called ``connect()``
+    /// - Throws: ``SignalError/chatServiceInactive(_:disconnectInfo:)`` if you haven't called ``connect()``
     /// - Throws: Other ``SignalError``s for other kinds of failures.
     /// - SeeAlso: ``sendAndDebug(_:)``
     public func send(_ request: Request) async throws -> Response {
@@ -266,7 +432,7 @@ public class UnauthenticatedChatService: NativeHandleOwner, ChatService {
     /// In addition to the response, an object containing debug information about the request flow
     /// is returned.
     ///
-    /// - Throws: ``SignalError/chatServiceInactive(_:)`` if you haven't called ``connect()``
+    /// - Throws: ``SignalError/chatServiceInactive(_:disconnectInfo:)`` if you haven't called ``connect()``
     /// - Throws: Other ``SignalError``s for other kinds of failures.
     /// - SeeAlso: ``send(_:)``
     public func sendAndDebug(_ request: Request) async throws -> (Response, DebugInfo) {
@@ -281,4 +447,17 @@ public class UnauthenticatedChatService: NativeHandleOwner, ChatService {
         }
         return (try Response(consuming: rawResponse.response), DebugInfo(consuming: rawResponse.debug_info))
     }
+
+    /// Finishes outstanding work and then disconnects, for when the app is about to be suspended.
+    ///
+    /// - SeeAlso: ``ChatService/prepareForBackground(timeRemaining:)``
+    public func prepareForBackground(timeRemaining: TimeInterval) async throws -> BackgroundFlushReport {
+        let budgetMillis = ChatRequest.timeoutMillis(timeRemaining)
+        let rawReport: SignalFfiBackgroundFlushReport = try await self.tokioAsyncContext.invokeAsyncFunction { promise, tokioAsyncContext in
+            withNativeHandle { chatService in
+                signal_chat_service_prepare_for_background_unauth(promise, tokioAsyncContext, chatService, budgetMillis)
+            }
+        }
+        return BackgroundFlushReport(rawReport)
+    }
 }
@@ -27,7 +27,11 @@ public struct ChatRequest: Equatable, Sendable {
     }
 
     internal var timeoutMillis: UInt32 {
-        let timeoutMillisFloat: Double = 1000 * self.timeout
+        return Self.timeoutMillis(self.timeout)
+    }
+
+    internal static func timeoutMillis(_ timeout: TimeInterval) -> UInt32 {
+        let timeoutMillisFloat: Double = 1000 * timeout
         if timeoutMillisFloat > Double(UInt32.max) {
             return .max
         } else if timeoutMillisFloat < 0 {
@@ -131,6 +135,36 @@ public struct ChatServiceDebugInfo: Equatable, Sendable {
     }
 }
 
+/// What happened to outgoing work when a ``ChatService`` prepared to go into the background.
+///
+/// - SeeAlso: ``ChatService/prepareForBackground(timeRemaining:)``
+public struct ChatServiceBackgroundFlushReport: Equatable, Sendable {
+    /// Acks for received messages that were successfully sent.
+    public var acksFlushed: UInt32
+    /// Acks that failed or were still outstanding when time ran out.
+    ///
+    /// The server will deliver the corresponding messages again.
+    public var acksUnflushed: UInt32
+    /// Requests that completed successfully.
+    public var requestsFlushed: UInt32
+    /// Requests that failed or were still outstanding when time ran out.
+    public var requestsUnflushed: UInt32
+
+    public init(acksFlushed: UInt32, acksUnflushed: UInt32, requestsFlushed: UInt32, requestsUnflushed: UInt32) {
+        self.acksFlushed = acksFlushed
+        self.acksUnflushed = acksUnflushed
+        self.requestsFlushed = requestsFlushed
+        self.requestsUnflushed = requestsUnflushed
+    }
+
+    internal init(_ rawReport: SignalFfiBackgroundFlushReport) {
+        self.acksFlushed = rawReport.acks_flushed
+        self.acksUnflushed = rawReport.acks_unflushed
+        self.requestsFlushed = rawReport.requests_flushed
+        self.requestsUnflushed = rawReport.requests_unflushed
+    }
+}
+
 extension SignalFfiChatResponse {
     fileprivate var rawHeadersAsBuffer: UnsafeBufferPointer<UnsafePointer<CChar>?> {
         .init(start: self.headers.base, count: self.headers.length)
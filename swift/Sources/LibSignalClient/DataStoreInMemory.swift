@@ -25,6 +25,7 @@ open class InMemorySignalProtocolStore: IdentityKeyStore, PreKeyStore, SignedPre
     private var kyberPrekeysUsed: Set<UInt32> = []
     private var sessionMap: [ProtocolAddress: SessionRecord] = [:]
     private var senderKeyMap: [SenderKeyName: SenderKeyRecord] = [:]
+    private var senderKeyDistributionMap: [SenderKeyName: SenderKeyDistributionRecord] = [:]
 
     public init() {
         self.privateKey = IdentityKeyPair.generate()
@@ -132,4 +133,12 @@ open class InMemorySignalProtocolStore: IdentityKeyStore, PreKeyStore, SignedPre
     open func loadSenderKey(from sender: ProtocolAddress, distributionId: UUID, context: StoreContext) throws -> SenderKeyRecord? {
         return self.senderKeyMap[SenderKeyName(sender: sender, distributionId: distributionId)]
     }
+
+    open func storeSenderKeyDistribution(from sender: ProtocolAddress, distributionId: UUID, record: SenderKeyDistributionRecord, context: StoreContext) throws {
+        self.senderKeyDistributionMap[SenderKeyName(sender: sender, distributionId: distributionId)] = record
+    }
+
+    open func loadSenderKeyDistribution(from sender: ProtocolAddress, distributionId: UUID, context: StoreContext) throws -> SenderKeyDistributionRecord? {
+        return self.senderKeyDistributionMap[SenderKeyName(sender: sender, distributionId: distributionId)]
+    }
 }
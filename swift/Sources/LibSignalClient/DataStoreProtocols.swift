@@ -50,4 +50,38 @@ public protocol SessionStore: AnyObject {
 public protocol SenderKeyStore: AnyObject {
     func storeSenderKey(from sender: ProtocolAddress, distributionId: UUID, record: SenderKeyRecord, context: StoreContext) throws
     func loadSenderKey(from sender: ProtocolAddress, distributionId: UUID, context: StoreContext) throws -> SenderKeyRecord?
+
+    /// Tracking distribution is optional: the default implementation discards the record, in which case every recipient
+    /// is always reported as needing the sender key.
+    func storeSenderKeyDistribution(from sender: ProtocolAddress, distributionId: UUID, record: SenderKeyDistributionRecord, context: StoreContext) throws
+    /// Stores that implement ``storeSenderKeyDistribution(from:distributionId:record:context:)`` should implement this
+    /// too; the default implementation always returns `nil`.
+    func loadSenderKeyDistribution(from sender: ProtocolAddress, distributionId: UUID, context: StoreContext) throws -> SenderKeyDistributionRecord?
+}
+
+extension SenderKeyStore {
+    public func storeSenderKeyDistribution(from sender: ProtocolAddress, distributionId: UUID, record: SenderKeyDistributionRecord, context: StoreContext) throws {}
+
+    public func loadSenderKeyDistribution(from sender: ProtocolAddress, distributionId: UUID, context: StoreContext) throws -> SenderKeyDistributionRecord? {
+        return nil
+    }
+}
+
+/// An optional record of messages that have already been decrypted.
+///
+/// A session normally rejects repeated messages by itself, but that protection is lost if the
+/// session record is deleted or replaced. Clients that need to process each message at most once
+/// can pass a replay cache to ``signalDecrypt(message:from:sessionStore:identityStore:replayCache:context:)``,
+/// ``signalDecryptPreKey(message:from:sessionStore:identityStore:preKeyStore:signedPreKeyStore:kyberPreKeyStore:replayCache:context:)``,
+/// or ``sealedSenderDecrypt(message:from:trustRoot:timestamp:sessionStore:identityStore:preKeyStore:signedPreKeyStore:replayCache:context:)``.
+///
+/// Messages are identified by the sender's ratchet key as well as the counter, since counters start
+/// over with each new chain. How long entries are kept is up to the implementation.
+public protocol ReplayCache: AnyObject {
+    /// Returns `true` if the message has already been recorded (and has not yet expired).
+    func contains(from address: ProtocolAddress, senderRatchetKey: PublicKey, counter: UInt32, context: StoreContext) throws -> Bool
+    /// Records that a message has been processed.
+    ///
+    /// This is only called after the session the message advanced has been saved.
+    func insert(from address: ProtocolAddress, senderRatchetKey: PublicKey, counter: UInt32, context: StoreContext) throws
 }
@@ -334,11 +334,100 @@ internal func withSenderKeyStore<Result>(_ store: SenderKeyStore, _ context: Sto
         }
     }
 
+    func ffiShimStoreSenderKeyDistribution(
+        storeCtx: UnsafeMutableRawPointer?,
+        sender: OpaquePointer?,
+        distributionId: UnsafePointer<uuid_t>?,
+        record: OpaquePointer?
+    ) -> Int32 {
+        let storeContext = storeCtx!.assumingMemoryBound(to: ErrorHandlingContext<(SenderKeyStore, StoreContext)>.self)
+        return storeContext.pointee.catchCallbackErrors { store, context in
+            var sender = ProtocolAddress(borrowing: sender)
+            let distributionId = UUID(uuid: distributionId!.pointee)
+            defer { cloneOrForgetAsNeeded(&sender) }
+            var record = SenderKeyDistributionRecord(borrowing: record)
+            defer { cloneOrForgetAsNeeded(&record) }
+            try store.storeSenderKeyDistribution(from: sender, distributionId: distributionId, record: record, context: context)
+            return 0
+        }
+    }
+
+    func ffiShimLoadSenderKeyDistribution(
+        storeCtx: UnsafeMutableRawPointer?,
+        recordp: UnsafeMutablePointer<OpaquePointer?>?,
+        sender: OpaquePointer?,
+        distributionId: UnsafePointer<uuid_t>?
+    ) -> Int32 {
+        let storeContext = storeCtx!.assumingMemoryBound(to: ErrorHandlingContext<(SenderKeyStore, StoreContext)>.self)
+        return storeContext.pointee.catchCallbackErrors { store, context in
+            var sender = ProtocolAddress(borrowing: sender)
+            let distributionId = UUID(uuid: distributionId!.pointee)
+            defer { cloneOrForgetAsNeeded(&sender) }
+            if var record = try store.loadSenderKeyDistribution(from: sender, distributionId: distributionId, context: context) {
+                recordp!.pointee = try cloneOrTakeHandle(from: &record)
+            } else {
+                recordp!.pointee = nil
+            }
+            return 0
+        }
+    }
+
     return try rethrowCallbackErrors((store, context)) {
         var ffiStore = SignalSenderKeyStore(
             ctx: $0,
             load_sender_key: ffiShimLoadSenderKey,
-            store_sender_key: ffiShimStoreSenderKey
+            store_sender_key: ffiShimStoreSenderKey,
+            load_sender_key_distribution: ffiShimLoadSenderKeyDistribution,
+            store_sender_key_distribution: ffiShimStoreSenderKeyDistribution
+        )
+        return try body(&ffiStore)
+    }
+}
+
+internal func withReplayCache<Result>(_ store: ReplayCache, _ context: StoreContext, _ body: (UnsafePointer<SignalReplayCache>) throws -> Result) throws -> Result {
+    func ffiShimContains(
+        storeCtx: UnsafeMutableRawPointer?,
+        address: OpaquePointer?,
+        senderRatchetKey: OpaquePointer?,
+        counter: UInt32
+    ) -> Int32 {
+        let storeContext = storeCtx!.assumingMemoryBound(to: ErrorHandlingContext<(ReplayCache, StoreContext)>.self)
+        return storeContext.pointee.catchCallbackErrors { store, context in
+            var address = ProtocolAddress(borrowing: address)
+            defer { cloneOrForgetAsNeeded(&address) }
+            var senderRatchetKey = PublicKey(borrowing: senderRatchetKey)
+            defer { cloneOrForgetAsNeeded(&senderRatchetKey) }
+            let seen = try store.contains(from: address, senderRatchetKey: senderRatchetKey, counter: counter, context: context)
+            if seen {
+                return 1
+            } else {
+                return 0
+            }
+        }
+    }
+
+    func ffiShimInsert(
+        storeCtx: UnsafeMutableRawPointer?,
+        address: OpaquePointer?,
+        senderRatchetKey: OpaquePointer?,
+        counter: UInt32
+    ) -> Int32 {
+        let storeContext = storeCtx!.assumingMemoryBound(to: ErrorHandlingContext<(ReplayCache, StoreContext)>.self)
+        return storeContext.pointee.catchCallbackErrors { store, context in
+            var address = ProtocolAddress(borrowing: address)
+            defer { cloneOrForgetAsNeeded(&address) }
+            var senderRatchetKey = PublicKey(borrowing: senderRatchetKey)
+            defer { cloneOrForgetAsNeeded(&senderRatchetKey) }
+            try store.insert(from: address, senderRatchetKey: senderRatchetKey, counter: counter, context: context)
+            return 0
+        }
+    }
+
+    return try rethrowCallbackErrors((store, context)) {
+        var ffiStore = SignalReplayCache(
+            ctx: $0,
+            contains: ffiShimContains,
+            insert: ffiShimInsert
         )
         return try body(&ffiStore)
     }
@@ -44,3 +44,144 @@ public struct DeviceTransferKey: Sendable {
         }
     }
 }
+
+/// One side of a device-to-device transfer, independent of how bytes are sent between the devices.
+///
+/// Both sides first exchange a hello message and their certificates (see
+/// ``DeviceTransferKey/generateCertificate(_:_:)``), each committing to its certificate before
+/// either is revealed. Then both sides display the
+/// ``verificationCode()``; once the user has checked that the codes match, each side calls
+/// ``confirmVerificationCode()``. Finally, the old device sends the payload with
+/// ``sendPayloadChunk(_:)`` and calls ``finish()``.
+///
+/// Whenever bytes arrive from the other device, pass them to ``receive(_:)``, then check ``state``
+/// to see whether the transfer has progressed. After each operation, send the bytes from
+/// ``takeOutgoing()`` to the other device.
+public class DeviceTransferSession: NativeHandleOwner {
+    /// The progress of a session through the transfer.
+    public enum State: UInt8, Sendable {
+        case awaitingHello = 0
+        case awaitingCertificate = 1
+        case verifying = 2
+        case transferring = 3
+        case completed = 4
+        case failed = 5
+    }
+
+    /// Starts a session on the old device, which will send `payloadLength` bytes.
+    public static func forOldDevice<Bytes: ContiguousBytes>(localCertificate: Bytes, payloadLength: UInt64) throws -> DeviceTransferSession {
+        let handle: OpaquePointer? = try localCertificate.withUnsafeBorrowedBuffer { certificateBuffer in
+            var result: OpaquePointer?
+            try checkError(signal_device_transfer_session_for_old_device(&result, certificateBuffer, payloadLength))
+            return result
+        }
+        return DeviceTransferSession(owned: handle!)
+    }
+
+    /// Starts a session on the new device.
+    public static func forNewDevice<Bytes: ContiguousBytes>(localCertificate: Bytes) throws -> DeviceTransferSession {
+        let handle: OpaquePointer? = try localCertificate.withUnsafeBorrowedBuffer { certificateBuffer in
+            var result: OpaquePointer?
+            try checkError(signal_device_transfer_session_for_new_device(&result, certificateBuffer))
+            return result
+        }
+        return DeviceTransferSession(owned: handle!)
+    }
+
+    override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        return signal_device_transfer_session_destroy(handle)
+    }
+
+    /// Processes bytes received from the other device, returning any payload bytes that arrived.
+    ///
+    /// Throws ``SignalError/invalidMessage(_:)`` if the other device sent something invalid, after
+    /// which the session is in the ``State/failed`` state.
+    public func receive<Bytes: ContiguousBytes>(_ data: Bytes) throws -> [UInt8] {
+        return try withNativeHandle { nativeHandle in
+            try data.withUnsafeBorrowedBuffer { buffer in
+                try invokeFnReturningArray {
+                    signal_device_transfer_session_receive($0, nativeHandle, buffer)
+                }
+            }
+        }
+    }
+
+    /// Takes all bytes waiting to be sent to the other device.
+    public func takeOutgoing() -> [UInt8] {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningArray {
+                    signal_device_transfer_session_take_outgoing($0, nativeHandle)
+                }
+            }
+        }
+    }
+
+    public var state: State {
+        let rawValue = withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningInteger {
+                    signal_device_transfer_session_get_state($0, nativeHandle)
+                }
+            }
+        }
+        return State(rawValue: rawValue)!
+    }
+
+    /// The code the user should compare between the two devices.
+    ///
+    /// Throws ``SignalError/invalidState(_:)`` if the certificates haven't been exchanged yet.
+    public func verificationCode() throws -> String {
+        return try withNativeHandle { nativeHandle in
+            try invokeFnReturningString {
+                signal_device_transfer_session_get_verification_code($0, nativeHandle)
+            }
+        }
+    }
+
+    /// Records that the user confirmed the verification codes match.
+    public func confirmVerificationCode() throws {
+        try withNativeHandle { nativeHandle in
+            try checkError(signal_device_transfer_session_confirm_verification_code(nativeHandle))
+        }
+    }
+
+    /// Sends the next piece of the payload (only on the old device, once both sides have confirmed).
+    public func sendPayloadChunk<Bytes: ContiguousBytes>(_ chunk: Bytes) throws {
+        try withNativeHandle { nativeHandle in
+            try chunk.withUnsafeBorrowedBuffer { buffer in
+                try checkError(signal_device_transfer_session_send_payload_chunk(nativeHandle, buffer))
+            }
+        }
+    }
+
+    /// Marks the transfer as complete, once the whole payload has been sent (old device only).
+    public func finish() throws {
+        try withNativeHandle { nativeHandle in
+            try checkError(signal_device_transfer_session_finish(nativeHandle))
+        }
+    }
+
+    /// The number of payload bytes sent or received so far.
+    public var bytesTransferred: UInt64 {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningInteger {
+                    signal_device_transfer_session_get_bytes_transferred($0, nativeHandle)
+                }
+            }
+        }
+    }
+
+    /// The total length of the payload.
+    ///
+    /// Throws ``SignalError/invalidState(_:)`` on the new device if the old device's hello hasn't
+    /// arrived yet.
+    public func payloadLength() throws -> UInt64 {
+        return try withNativeHandle { nativeHandle in
+            try invokeFnReturningInteger {
+                signal_device_transfer_session_get_payload_length($0, nativeHandle)
+            }
+        }
+    }
+}
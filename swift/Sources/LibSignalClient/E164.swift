@@ -0,0 +1,37 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+import SignalFfi
+
+/// Parsing and validation for phone numbers in E.164 form (`+` followed by digits).
+///
+/// This only checks the structure of a number, not whether its calling code or the rest of the
+/// number is actually in use.
+public enum E164 {
+    /// Interprets a phone number as entered by a user or found in a contact list.
+    ///
+    /// Numbers in international format (starting with `+` or `00`) are used as is. Anything else is
+    /// treated as a national number in the country with calling code `regionCallingCode` (usually
+    /// the local user's own). Spaces and common punctuation are ignored.
+    ///
+    /// - Returns: the number in E.164 form, or `nil` if it isn't a valid phone number.
+    /// - Throws: ``SignalError/invalidArgument(_:)`` if `regionCallingCode` isn't a plausible
+    ///   calling code.
+    public static func normalize(_ raw: String, regionCallingCode: UInt32) throws -> String? {
+        try invokeFnReturningOptionalString {
+            signal_e164_normalize($0, raw, regionCallingCode)
+        }
+    }
+
+    /// Checks whether `e164` is already a valid number in E.164 form, with no punctuation.
+    public static func isValid(_ e164: String) -> Bool {
+        failOnError {
+            try invokeFnReturningBool {
+                signal_e164_is_valid($0, e164)
+            }
+        }
+    }
+}
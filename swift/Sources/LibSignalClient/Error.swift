@@ -13,6 +13,7 @@ public enum SignalError: Error {
     case invalidArgument(String)
     case invalidType(String)
     case invalidUtf8String(String)
+    case deadlineExceeded(String)
     case protobufError(String)
     case legacyCiphertextVersion(String)
     case unknownCiphertextVersion(String)
@@ -50,17 +51,19 @@ public enum SignalError: Error {
     case invalidMediaInput(String)
     case unsupportedMediaInput(String)
     case callbackError(String)
-    case webSocketError(String)
+    case webSocketError(String, disconnectInfo: DisconnectInfo? = nil)
     case connectionTimeoutError(String)
-    case connectionFailed(String)
+    case connectionFailed(String, disconnectInfo: DisconnectInfo? = nil)
     case networkProtocolError(String)
     case cdsiInvalidToken(String)
-    case rateLimitedError(retryAfter: TimeInterval, message: String)
+    case rateLimitedError(retryAfter: TimeInterval, scope: RateLimitScope, reason: String?, message: String)
     case svrDataMissing(String)
     case svrRestoreFailed(triesRemaining: UInt32, message: String)
     case svrRotationMachineTooManySteps(String)
-    case chatServiceInactive(String)
+    case chatServiceInactive(String, disconnectInfo: DisconnectInfo? = nil)
     case chatServiceIntentionallyDisconnected(String)
+    case requestUnauthorized(String)
+    case notFound(String)
     case appExpired(String)
     case deviceDeregistered(String)
     case backupValidation(unknownFields: [String], message: String)
@@ -68,6 +71,38 @@ public enum SignalError: Error {
     case unknown(UInt32, String)
 }
 
+/// What a server-imposed rate limit is counted against.
+///
+/// The raw values should match `RateLimitScope` in Rust (libsignal-net).
+public enum RateLimitScope: UInt8 {
+    /// The server did not say.
+    case unspecified = 0
+    /// The limit applies to a single phone number, whichever account is asking about it.
+    case perNumber = 1
+    /// The limit applies to the requesting account.
+    case perAccount = 2
+}
+
+/// What the server said when it closed (or refused) a chat connection.
+///
+/// Lets an app tell "the server asked me to go away" apart from a plain network failure.
+public struct DisconnectInfo: Equatable, Sendable {
+    /// The websocket close code, or `nil` if the server didn't send a close frame.
+    public var closeCode: UInt16?
+    /// The reason given in the close frame, if any.
+    ///
+    /// Suitable for logging, but not for display to users.
+    public var reason: String?
+    /// The values of any `X-Signal-Alert` headers on the server's response.
+    public var alerts: [String]
+
+    public init(closeCode: UInt16?, reason: String?, alerts: [String]) {
+        self.closeCode = closeCode
+        self.reason = reason
+        self.alerts = alerts
+    }
+}
+
 internal typealias SignalFfiErrorRef = OpaquePointer
 
 internal func convertError(_ error: SignalFfiErrorRef?) -> Error? {
@@ -109,6 +144,8 @@ internal func checkError(_ error: SignalFfiErrorRef?) throws {
         throw SignalError.invalidType(errStr)
     case SignalErrorCodeInvalidUtf8String:
         throw SignalError.invalidUtf8String(errStr)
+    case SignalErrorCodeDeadlineExceeded:
+        throw SignalError.deadlineExceeded(errStr)
     case SignalErrorCodeProtobufError:
         throw SignalError.protobufError(errStr)
     case SignalErrorCodeLegacyCiphertextVersion:
@@ -190,11 +227,11 @@ internal func checkError(_ error: SignalFfiErrorRef?) throws {
     case SignalErrorCodeCallbackError:
         throw SignalError.callbackError(errStr)
     case SignalErrorCodeWebSocket:
-        throw SignalError.webSocketError(errStr)
+        throw SignalError.webSocketError(errStr, disconnectInfo: disconnectInfo(from: error))
     case SignalErrorCodeConnectionTimedOut:
         throw SignalError.connectionTimeoutError(errStr)
     case SignalErrorCodeConnectionFailed:
-        throw SignalError.connectionFailed(errStr)
+        throw SignalError.connectionFailed(errStr, disconnectInfo: disconnectInfo(from: error))
     case SignalErrorCodeNetworkProtocol:
         throw SignalError.networkProtocolError(errStr)
     case SignalErrorCodeCdsiInvalidToken:
@@ -203,7 +240,18 @@ internal func checkError(_ error: SignalFfiErrorRef?) throws {
         let retryAfterSeconds = try invokeFnReturningInteger {
             signal_error_get_retry_after_seconds(error, $0)
         }
-        throw SignalError.rateLimitedError(retryAfter: TimeInterval(retryAfterSeconds), message: errStr)
+        let scope = try invokeFnReturningInteger {
+            signal_error_get_rate_limit_scope(error, $0)
+        }
+        let reason = try invokeFnReturningOptionalString {
+            signal_error_get_rate_limit_reason(error, $0)
+        }
+        throw SignalError.rateLimitedError(
+            retryAfter: TimeInterval(retryAfterSeconds),
+            scope: RateLimitScope(rawValue: scope) ?? .unspecified,
+            reason: reason,
+            message: errStr
+        )
     case SignalErrorCodeSvrDataMissing:
         throw SignalError.svrDataMissing(errStr)
     case SignalErrorCodeSvrRestoreFailed:
@@ -214,9 +262,13 @@ internal func checkError(_ error: SignalFfiErrorRef?) throws {
     case SignalErrorCodeSvrRotationMachineTooManySteps:
         throw SignalError.svrRotationMachineTooManySteps(errStr)
     case SignalErrorCodeChatServiceInactive:
-        throw SignalError.chatServiceInactive(errStr)
+        throw SignalError.chatServiceInactive(errStr, disconnectInfo: disconnectInfo(from: error))
     case SignalErrorCodeChatServiceIntentionallyDisconnected:
         throw SignalError.chatServiceIntentionallyDisconnected(errStr)
+    case SignalErrorCodeRequestUnauthorized:
+        throw SignalError.requestUnauthorized(errStr)
+    case SignalErrorCodeNotFound:
+        throw SignalError.notFound(errStr)
     case SignalErrorCodeAppExpired:
         throw SignalError.appExpired(errStr)
     case SignalErrorCodeDeviceDeregistered:
@@ -247,3 +299,23 @@ internal func failOnError<Result>(_ fn: () throws -> Result, file: StaticString
         }
     }
 }
+
+private func disconnectInfo(from error: SignalFfiErrorRef) -> DisconnectInfo? {
+    // Only some errors carry disconnect info; the others fail to provide it.
+    guard let closeCode = try? invokeFnReturningInteger(fn: {
+        signal_error_get_disconnect_close_code(error, $0)
+    }) else {
+        return nil
+    }
+    let reason = try? invokeFnReturningOptionalString {
+        signal_error_get_disconnect_reason(error, $0)
+    }
+    let alerts = try? invokeFnReturningStringArray {
+        signal_error_get_disconnect_alerts(error, $0)
+    }
+    return DisconnectInfo(
+        closeCode: closeCode == 0 ? nil : closeCode,
+        reason: reason ?? nil,
+        alerts: alerts ?? []
+    )
+}
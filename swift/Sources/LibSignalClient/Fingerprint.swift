@@ -83,3 +83,46 @@ public struct NumericFingerprintGenerator: Sendable {
         return Fingerprint(displayable: displayable, scannable: scannable)
     }
 }
+
+/// The digest of a single identity that makes up one half of a safety number.
+///
+/// Displaying one of these is consistent with the corresponding half of any ``Fingerprint``
+/// created with the same version and iteration count.
+public struct IdentityDigest: Sendable {
+    public let version: Int
+    public let digest: [UInt8]
+
+    public init(
+        iterations: Int,
+        version: Int,
+        stableIdentifier: some ContiguousBytes,
+        identityKey: PublicKey
+    ) throws {
+        self.version = version
+        self.digest = try withNativeHandle(identityKey) { keyHandle in
+            try stableIdentifier.withUnsafeBorrowedBuffer { identifierBuffer in
+                try invokeFnReturningArray {
+                    signal_identity_digest_compute($0, UInt32(iterations), UInt32(version), identifierBuffer, keyHandle)
+                }
+            }
+        }
+    }
+
+    /// Reconstructs a digest previously read from ``digest``.
+    ///
+    /// Throws ``SignalError/invalidArgument(_:)`` if `digest` has the wrong length.
+    public init(version: Int, digest: [UInt8]) throws {
+        self.version = version
+        self.digest = digest
+        _ = try self.displayString()
+    }
+
+    /// The 30-digit string this identity contributes to a displayed safety number.
+    public func displayString() throws -> String {
+        try self.digest.withUnsafeBorrowedBuffer { digestBuffer in
+            try invokeFnReturningString {
+                signal_identity_digest_display_string($0, UInt32(self.version), digestBuffer)
+            }
+        }
+    }
+}
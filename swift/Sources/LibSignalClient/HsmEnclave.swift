@@ -111,4 +111,55 @@ public class HsmEnclaveClient: NativeHandleOwner {
             }
         }
     }
+
+    /// Allows up to `maxInFlight` requests to be sent before their responses are received.
+    ///
+    /// Without this, the connection doesn't track requests at all. Once pipelining is enabled,
+    /// several requests can be sent with ``establishedSend(_:)`` before any responses arrive.
+    /// Responses must be passed to ``establishedRecv(_:)`` in the order they're received, and will
+    /// correspond to requests in the order they were sent; use ``lastSentRequestId()`` and
+    /// ``lastReceivedRequestId()`` to match them up. Sending more than `maxInFlight` requests
+    /// without receiving responses throws ``SignalError/invalidState(_:)``.
+    ///
+    /// Only valid once the handshake has completed, and should be called before the first request
+    /// is sent. Calling it again changes the limit.
+    public func enablePipelining(maxInFlight: UInt32) throws {
+        try withNativeHandle { nativeHandle in
+            try checkError(signal_hsm_enclave_client_enable_pipelining(nativeHandle, maxInFlight))
+        }
+    }
+
+    /// The id of the request most recently encrypted by ``establishedSend(_:)``.
+    ///
+    /// Only valid once pipelining has been enabled and a request has been sent.
+    public func lastSentRequestId() throws -> UInt64 {
+        return try withNativeHandle { nativeHandle in
+            try invokeFnReturningInteger {
+                signal_hsm_enclave_client_last_sent_request_id($0, nativeHandle)
+            }
+        }
+    }
+
+    /// The id of the request answered by the response most recently decrypted by
+    /// ``establishedRecv(_:)``.
+    ///
+    /// Only valid once pipelining has been enabled and a response has been received.
+    public func lastReceivedRequestId() throws -> UInt64 {
+        return try withNativeHandle { nativeHandle in
+            try invokeFnReturningInteger {
+                signal_hsm_enclave_client_last_received_request_id($0, nativeHandle)
+            }
+        }
+    }
+
+    /// The number of requests sent whose responses haven't been received yet.
+    ///
+    /// Only valid once pipelining has been enabled.
+    public func inFlight() throws -> UInt32 {
+        return try withNativeHandle { nativeHandle in
+            try invokeFnReturningInteger {
+                signal_hsm_enclave_client_in_flight($0, nativeHandle)
+            }
+        }
+    }
 }
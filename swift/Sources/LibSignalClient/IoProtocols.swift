@@ -30,6 +30,28 @@ public protocol SignalInputStream: AnyObject {
     func skip(by amount: UInt64) throws
 }
 
+/// An input stream of bytes whose reads complete asynchronously.
+///
+/// Unlike ``SignalInputStream``, which is read synchronously, operations on this stream are
+/// awaited, so no thread is blocked waiting on slow I/O while libsignal processes the input.
+public protocol SignalAsyncInputStream: AnyObject, Sendable {
+    /// Read up to `maxLength` bytes from the input stream.
+    ///
+    /// The returned data may be shorter than requested for any reason; however, returning no bytes always
+    /// indicates that the end of the stream has been reached. It must never be longer than `maxLength`.
+    ///
+    /// - Throws: If an I/O error occurred while reading from the input.
+    func read(maxLength: Int) async throws -> Data
+
+    /// Skip an amount of bytes in the input stream.
+    ///
+    /// If the requested number of bytes could not be skipped for any reason, including if the end of stream was
+    /// reached, an error must be raised.
+    ///
+    /// - Throws: If an I/O error occurred while skipping the bytes in the input.
+    func skip(by amount: UInt64) async throws
+}
+
 /// An error thrown by `SignalInputStreamAdapter`.
 public enum SignalInputStreamError: Error {
     /// The end of the input stream was reached while attempting to `skip()`.
@@ -39,3 +39,58 @@ internal func withInputStream<Result>(_ stream: SignalInputStream, _ body: (Unsa
         return try body(&ffiStream)
     }
 }
+
+/// A runtime for async operations that aren't associated with a particular ``Net`` instance.
+internal let sharedIoAsyncContext = TokioAsyncContext()
+
+/// Wraps `stream` for an async libsignal\_ffi call, which takes ownership of the result.
+///
+/// Rust calls the `destroy` callback once it's done with the stream, releasing the reference
+/// retained here.
+internal func makeAsyncInputStream(_ stream: SignalAsyncInputStream) -> SignalFfi.SignalAsyncInputStream {
+    func ffiShimRead(streamCtx: UnsafeMutableRawPointer?, amount: Int, completion: OpaquePointer?) {
+        let stream = asyncInputStream(fromContext: streamCtx)
+        Task {
+            await completeAsyncInputStreamOperation(completion) { try await stream.read(maxLength: amount) }
+        }
+    }
+
+    func ffiShimSkip(streamCtx: UnsafeMutableRawPointer?, amount: UInt64, completion: OpaquePointer?) {
+        let stream = asyncInputStream(fromContext: streamCtx)
+        Task {
+            await completeAsyncInputStreamOperation(completion) {
+                try await stream.skip(by: amount)
+                return Data()
+            }
+        }
+    }
+
+    func ffiShimDestroy(streamCtx: UnsafeMutableRawPointer?) {
+        Unmanaged<AnyObject>.fromOpaque(streamCtx!).release()
+    }
+
+    return SignalFfi.SignalAsyncInputStream(
+        ctx: Unmanaged.passRetained(stream as AnyObject).toOpaque(),
+        read: ffiShimRead as SignalAsyncRead,
+        skip: ffiShimSkip as SignalAsyncSkip,
+        destroy: ffiShimDestroy as SignalDestroyAsyncInputStream
+    )
+}
+
+private func asyncInputStream(fromContext streamCtx: UnsafeMutableRawPointer?) -> SignalAsyncInputStream {
+    // swiftlint:disable:next force_cast
+    Unmanaged<AnyObject>.fromOpaque(streamCtx!).takeUnretainedValue() as! SignalAsyncInputStream
+}
+
+/// Reports the outcome of `operation` to Rust, then destroys `completion`.
+private func completeAsyncInputStreamOperation(_ completion: OpaquePointer?, _ operation: () async throws -> Data) async {
+    do {
+        let data = try await operation()
+        data.withUnsafeBorrowedBuffer {
+            failOnError(signal_async_input_stream_completion_complete(completion, $0))
+        }
+    } catch {
+        failOnError(signal_async_input_stream_completion_fail(completion, "\(error)"))
+    }
+    failOnError(signal_async_input_stream_completion_destroy(completion))
+}
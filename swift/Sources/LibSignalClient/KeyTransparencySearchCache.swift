@@ -0,0 +1,25 @@
+//
+// Copyright 2026 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+import SignalFfi
+
+/// Verified key transparency search results, reused instead of searching the network again.
+///
+/// Passed to ``AuthenticatedChatService/searchKeyTransparency(cache:signatureKey:vrfKey:auditorKey:searchKey:timeout:)``,
+/// which checks the cache before sending anything. A result is only reused while the tree hasn't
+/// grown past the latest tree head verified through the cache, and until it expires.
+public class KeyTransparencySearchCache: NativeHandleOwner, @unchecked Sendable {
+    /// Creates a cache that keeps results for `ttl` seconds, for at most `maxEntries` search keys.
+    public convenience init(ttl: TimeInterval = 300, maxEntries: UInt32 = 1000) {
+        var handle: OpaquePointer?
+        failOnError(signal_key_transparency_search_cache_new(&handle, UInt32(ttl), maxEntries))
+        self.init(owned: handle!)
+    }
+
+    override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        signal_key_transparency_search_cache_destroy(handle)
+    }
+}
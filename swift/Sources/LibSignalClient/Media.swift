@@ -50,6 +50,106 @@ public func sanitizeMp4(input: SignalInputStream, len: UInt64) throws -> Sanitiz
     }
 }
 
+/// "Sanitize" an MP4 input read from a stream that completes asynchronously.
+///
+/// See ``sanitizeMp4(input:len:)``.
+public func sanitizeMp4(input: SignalAsyncInputStream, len: UInt64) async throws -> SanitizedMetadata {
+    var ffiInput = makeAsyncInputStream(input)
+    let handle = try await sharedIoAsyncContext.invokeAsyncFunction { promise, asyncContext in
+        signal_mp4_sanitizer_sanitize_async(promise, asyncContext, &ffiInput, len)
+    }
+    return SanitizedMetadata(owned: handle)
+}
+
+/// "Sanitize" an MP4 input, using `options` to decide which metadata boxes to keep.
+///
+/// See ``sanitizeMp4(input:len:)``.
+public func sanitizeMp4(input: SignalInputStream, len: UInt64, options: Mp4SanitizerOptions) throws -> SanitizedMetadata {
+    return try withInputStream(input) { ffiInput in
+        try options.withNativeHandle { options in
+            try invokeFnReturningNativeHandle {
+                signal_mp4_sanitizer_sanitize_with_options($0, ffiInput, len, options)
+            }
+        }
+    }
+}
+
+/// Sets how many bytes of metadata buffers ``sanitizeMp4(input:len:)`` may keep for reuse between inputs.
+///
+/// Sanitizing a large video can need hundreds of megabytes of buffers. Keeping them around avoids allocating them again
+/// for the next input, at the cost of holding on to that memory while no sanitizer is running. The default is 0, which
+/// keeps nothing.
+public func setMp4SanitizerMaxPooledBufferBytes(_ maxBytes: UInt64) {
+    failOnError(signal_mp4_sanitizer_set_max_pooled_buffer_bytes(maxBytes))
+}
+
+/// The codecs a client is able to play back, beyond H.264 video and AAC audio.
+///
+/// Tracks of kinds other than audio and video, such as timed metadata, are not checked.
+public struct Mp4CodecPolicy: Sendable {
+    /// Allow HEVC (H.265) video.
+    public var allowHevc: Bool
+    /// Allow AV1 video.
+    public var allowAv1: Bool
+    /// Allow Opus audio.
+    public var allowOpus: Bool
+
+    public init(allowHevc: Bool = false, allowAv1: Bool = false, allowOpus: Bool = false) {
+        self.allowHevc = allowHevc
+        self.allowAv1 = allowAv1
+        self.allowOpus = allowOpus
+    }
+}
+
+/// Options for ``sanitizeMp4(input:len:options:)``.
+///
+/// By default, metadata boxes are left as ``sanitizeMp4(input:len:)`` leaves them. Once stripping is turned on with
+/// ``setMetadataStripped(_:)`` or ``setMetadataBoxPreserved(_:_:)``, boxes needed for playback are still kept, but
+/// other metadata boxes are replaced with padding of the same size unless they are listed as preserved. Color
+/// information, pixel aspect ratio, HDR, and spherical video metadata start out preserved.
+///
+/// By default, codecs are not checked; see ``setCodecPolicy(_:)``.
+public class Mp4SanitizerOptions: NativeHandleOwner {
+    public convenience init() {
+        var handle: OpaquePointer?
+        failOnError(signal_mp4_sanitizer_options_new(&handle))
+        self.init(owned: handle!)
+    }
+
+    /// Turns stripping of metadata boxes that aren't listed as preserved on or off.
+    public func setMetadataStripped(_ stripped: Bool) {
+        self.withNativeHandle { nativeHandle in
+            failOnError(signal_mp4_sanitizer_options_set_metadata_stripped(nativeHandle, stripped))
+        }
+    }
+
+    /// Sets whether boxes of type `boxType` (a four-character code such as `"colr"`) should be kept.
+    ///
+    /// This turns on stripping of metadata boxes that aren't listed as preserved.
+    public func setMetadataBoxPreserved(_ boxType: String, _ preserved: Bool) {
+        let bytes = Array(boxType.utf8)
+        precondition(bytes.count == 4, "box types are four characters long")
+        self.withNativeHandle { nativeHandle in
+            withUnsafePointer(to: (bytes[0], bytes[1], bytes[2], bytes[3])) { boxType in
+                failOnError(signal_mp4_sanitizer_options_set_metadata_box_preserved(nativeHandle, boxType, preserved))
+            }
+        }
+    }
+
+    /// Restricts the codecs accepted in audio and video tracks.
+    ///
+    /// Tracks using a codec not allowed by `policy` cause sanitizing to fail with `SignalError.unsupportedMediaInput`.
+    public func setCodecPolicy(_ policy: Mp4CodecPolicy) {
+        self.withNativeHandle { nativeHandle in
+            failOnError(signal_mp4_sanitizer_options_set_codec_policy(nativeHandle, policy.allowHevc, policy.allowAv1, policy.allowOpus))
+        }
+    }
+
+    override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        return signal_mp4_sanitizer_options_destroy(handle)
+    }
+}
+
 /// "Sanitize" a WebP input.
 ///
 /// The sanitizer currently simply checks the validity of a WebP file input, so that passing a malformed file to an
@@ -102,43 +102,194 @@ public enum MessageBackupPurpose: UInt8, Sendable {
 ///  - key: The key used to decrypt the backup file.
 ///  - purpose: Whether the backup is intended for transfer or remote storage.
 ///  - length: The exact length of the backup file, in bytes.
+///  - timeout: If provided, validation is abandoned if it is still running after this long.
 ///  - makeStream: A callback that produces InputStreams needed for backups.
 ///
 /// - Returns: an object describing the validation outcome.
 ///
 /// - Throws:
 ///  - `SignalError.ioError`: If an IO error on the input occurs.
+///  - `SignalError.deadlineExceeded`: If the timeout passes before validation completes.
 ///  - `MessageBackupValidationError`: If validation fails
 public func validateMessageBackup(
-    key: MessageBackupKey, purpose: MessageBackupPurpose, length: UInt64, makeStream: () throws -> SignalInputStream
+    key: MessageBackupKey, purpose: MessageBackupPurpose, length: UInt64, timeout: TimeInterval? = nil, makeStream: () throws -> SignalInputStream
 ) throws -> MessageBackupUnknownFields {
     let outcome: ValidationOutcome = try withInputStream(try makeStream()) { firstInput in
         try withInputStream(try makeStream()) { secondInput in
             try key.withNativeHandle { key in
                 try invokeFnReturningNativeHandle {
-                    signal_message_backup_validator_validate($0, key, firstInput, secondInput, length, purpose.rawValue)
+                    signal_message_backup_validator_validate($0, key, firstInput, secondInput, length, purpose.rawValue, deadlineMillis(timeout))
                 }
             }
         }
     }
 
     if let errorMessage = outcome.errorMessage {
-        throw MessageBackupValidationError(errorMessage: errorMessage, unknownFields: outcome.unknownFields)
+        throw MessageBackupValidationError(errorMessage: errorMessage, unknownFields: outcome.unknownFields, findings: outcome.findings)
     }
     return outcome.unknownFields
 }
 
+/// Validates a passed-in message backup bundle read from streams that complete asynchronously.
+///
+/// Behaves like the synchronous overload, but no thread is blocked waiting for the input to be read.
+///
+/// - Parameters:
+///  - key: The key used to decrypt the backup file.
+///  - purpose: Whether the backup is intended for transfer or remote storage.
+///  - length: The exact length of the backup file, in bytes.
+///  - timeout: If provided, validation is abandoned if it is still running after this long, even
+///    if a read from the input is still outstanding.
+///  - makeStream: A callback that produces the streams needed for backups.
+///
+/// - Returns: an object describing the validation outcome.
+///
+/// - Throws:
+///  - `SignalError.ioError`: If an IO error on the input occurs.
+///  - `SignalError.deadlineExceeded`: If the timeout passes before validation completes.
+///  - `MessageBackupValidationError`: If validation fails
+public func validateMessageBackup(
+    key: MessageBackupKey, purpose: MessageBackupPurpose, length: UInt64, timeout: TimeInterval? = nil, makeStream: () throws -> SignalAsyncInputStream
+) async throws -> MessageBackupUnknownFields {
+    let firstStream = try makeStream()
+    let secondStream = try makeStream()
+    var firstInput = makeAsyncInputStream(firstStream)
+    var secondInput = makeAsyncInputStream(secondStream)
+    // The key is used until validation finishes.
+    defer { withExtendedLifetime(key) {} }
+    let handle = try await sharedIoAsyncContext.invokeAsyncFunction { promise, asyncContext in
+        key.withNativeHandle { key in
+            signal_message_backup_validator_validate_async(promise, asyncContext, key, &firstInput, &secondInput, length, purpose.rawValue, deadlineMillis(timeout))
+        }
+    }
+    let outcome = ValidationOutcome(owned: handle)
+
+    if let errorMessage = outcome.errorMessage {
+        throw MessageBackupValidationError(errorMessage: errorMessage, unknownFields: outcome.unknownFields, findings: outcome.findings)
+    }
+    return outcome.unknownFields
+}
+
+/// Computes the size compressed backup contents will be padded to before encryption.
+///
+/// - Parameter compressedLength: The length of the gzipped backup contents.
+/// - Returns: The length after padding with the standard bucketing policy.
+public func messageBackupPaddedSize(compressedLength: UInt64) -> UInt64 {
+    return failOnError {
+        try invokeFnReturningInteger {
+            signal_message_backup_get_padded_size($0, compressedLength)
+        }
+    }
+}
+
+/// Computes the size of the encrypted backup file that will be uploaded.
+///
+/// This accounts for padding, encryption, and the trailing HMAC, and so can be used to predict
+/// storage quota usage before producing the backup.
+///
+/// - Parameter compressedLength: The length of the gzipped backup contents.
+/// - Returns: The length of the final encrypted backup file.
+public func messageBackupEncryptedSize(compressedLength: UInt64) -> UInt64 {
+    return failOnError {
+        try invokeFnReturningInteger {
+            signal_message_backup_get_encrypted_size($0, compressedLength)
+        }
+    }
+}
+
+/// How compressed backup contents are padded before encryption, to hide the exact size of a backup.
+public class BackupPaddingPolicy: NativeHandleOwner {
+    /// The policy used for backups produced by Signal clients.
+    public convenience init() {
+        var handle: OpaquePointer?
+        failOnError(signal_backup_padding_policy_default(&handle))
+        self.init(owned: handle!)
+    }
+
+    /// Pads contents up to the smallest bucket that fits them.
+    ///
+    /// Bucket sizes are integer powers of `growthFactor` (rounded to the nearest hundredth), but never
+    /// less than `minSize` bytes. A `growthFactor` of 1 or less disables bucketing, leaving only the
+    /// minimum size.
+    public convenience init(growthFactor: Double, minSize: UInt64) {
+        var handle: OpaquePointer?
+        failOnError(signal_backup_padding_policy_new(&handle, UInt32(clamping: Int64((growthFactor * 100).rounded())), minSize))
+        self.init(owned: handle!)
+    }
+
+    override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        return signal_backup_padding_policy_destroy(handle)
+    }
+
+    /// Computes the size compressed backup contents will be padded to before encryption.
+    ///
+    /// - Parameter compressedLength: The length of the gzipped backup contents.
+    /// - Returns: The length after padding with this policy.
+    public func paddedSize(compressedLength: UInt64) -> UInt64 {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningInteger {
+                    signal_backup_padding_policy_get_padded_size($0, nativeHandle, compressedLength)
+                }
+            }
+        }
+    }
+
+    /// Computes the size of the encrypted backup file that will be uploaded.
+    ///
+    /// - Parameter compressedLength: The length of the gzipped backup contents.
+    /// - Returns: The length of the final encrypted backup file, after padding with this policy.
+    public func encryptedSize(compressedLength: UInt64) -> UInt64 {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningInteger {
+                    signal_backup_padding_policy_get_encrypted_size($0, nativeHandle, compressedLength)
+                }
+            }
+        }
+    }
+}
+
 /// The outcome of a failed validation attempt.
 public struct MessageBackupValidationError: Error {
     /// The human-readable error that caused validation to fail.
     public var errorMessage: String
     /// Unknown fields encountered while validating.
     public var unknownFields: MessageBackupUnknownFields
+    /// Structured versions of ``errorMessage`` and ``unknownFields``, in that order.
+    public var findings: [MessageBackupValidationFinding] = []
 }
 
 /// Unknown fields encountered while validating.
 public struct MessageBackupUnknownFields: Sendable {
     public let fields: [String]
+    /// Structured versions of ``fields``.
+    public var findings: [MessageBackupValidationFinding] = []
+}
+
+/// A single machine-readable problem found while validating a backup.
+public struct MessageBackupValidationFinding: Sendable {
+    public enum Kind: UInt8, Sendable {
+        // This needs to be kept in sync with the Rust version of the enum.
+        case unknownField = 0, invalidFrame, incompleteBackup, malformedFrame, noFrames, hmacMismatch, io, limitExceeded
+    }
+
+    public enum Severity: UInt8, Sendable {
+        // This needs to be kept in sync with the Rust version of the enum.
+        /// The backup can still be imported.
+        case ignorable = 0
+        /// The backup cannot be imported.
+        case fatal = 1
+    }
+
+    /// The frame the finding applies to, where frame 0 is the backup header.
+    public let frameIndex: Int?
+    /// Dotted path to the offending field, or the empty string if unknown.
+    public let fieldPath: String
+    public let kind: Kind
+    public let severity: Severity
+    /// A developer-facing description of the finding.
+    public let message: String
 }
 
 private class ValidationOutcome: NativeHandleOwner {
@@ -150,7 +301,27 @@ private class ValidationOutcome: NativeHandleOwner {
                 }
             }
         }
-        return MessageBackupUnknownFields(fields: fields)
+        return MessageBackupUnknownFields(fields: fields, findings: self.findings.filter { $0.kind == .unknownField })
+    }
+
+    public var findings: [MessageBackupValidationFinding] {
+        failOnError {
+            try self.withNativeHandle { result in
+                var buffer = SignalOwnedBufferOfFfiMessageBackupValidationFinding()
+                try checkError(signal_message_backup_validation_outcome_get_findings(&buffer, result))
+                defer { signal_free_message_backup_validation_finding_list(buffer) }
+
+                return UnsafeBufferPointer(start: buffer.base, count: buffer.length).map { finding in
+                    MessageBackupValidationFinding(
+                        frameIndex: finding.frame_index < 0 ? nil : Int(finding.frame_index),
+                        fieldPath: String(cString: finding.field_path),
+                        kind: MessageBackupValidationFinding.Kind(rawValue: finding.kind)!,
+                        severity: MessageBackupValidationFinding.Severity(rawValue: finding.severity)!,
+                        message: String(cString: finding.message)
+                    )
+                }
+            }
+        }
     }
 
     public var errorMessage: String? {
@@ -22,6 +22,25 @@ public class Net {
         case production = 1
     }
 
+    /// The services whose connection behavior can be configured separately.
+    public enum Service: UInt8, Sendable {
+        // This needs to be kept in sync with the Rust version of the enum.
+        case chat = 0
+        case cdsi = 1
+        case svr3 = 2
+    }
+
+    /// The kinds of route that can be tried when connecting with censorship circumvention enabled.
+    ///
+    /// A proxy set with ``Net/setProxy(host:port:)`` isn't listed here, because it is used for
+    /// whichever route is being tried.
+    public enum Route: UInt8, Sendable {
+        // This needs to be kept in sync with the Rust ConnectionRoute enum.
+        case direct = 0
+        case proxyF = 1
+        case proxyG = 2
+    }
+
     /// An SVR3 client providing backup and restore functionality.
     public let svr3: Svr3Client
 
@@ -56,6 +75,14 @@ public class Net {
         self.connectionManager.clearProxy()
     }
 
+    /// The proxy set with ``Net/setProxy(host:port:)`` as `host:port`, or `nil` if new connections
+    /// are made directly.
+    ///
+    /// If the last call to ``Net/setProxy(host:port:)`` had an invalid port, this is just the host.
+    public var proxy: String? {
+        self.connectionManager.proxy()
+    }
+
     /// Enables or disables censorship circumvention for all new connections (until changed).
     ///
     /// If CC is enabled, *new* connections and services may try additional routes to the Signal servers.
@@ -67,6 +94,101 @@ public class Net {
         self.connectionManager.setCensorshipCircumventionEnabled(enabled)
     }
 
+    /// Sets size limits for all new chat connections (until changed).
+    ///
+    /// Messages larger than `maxMessageSize` (or frames larger than `maxFrameSize`) are rejected.
+    /// Once `maxPendingSendBytes` of outgoing requests are waiting to be written, further requests
+    /// wait for the connection to catch up instead of being buffered, so bulk operations don't use
+    /// unbounded memory when the connection stalls. Existing connections are not affected.
+    public func setChatWebSocketLimits(maxFrameSize: UInt32, maxMessageSize: UInt32, maxPendingSendBytes: UInt32) {
+        self.connectionManager.setChatWebSocketLimits(
+            maxFrameSize: maxFrameSize,
+            maxMessageSize: maxMessageSize,
+            maxPendingSendBytes: maxPendingSendBytes
+        )
+    }
+
+    /// Sets how new connections to `service` back off after failed attempts (until changed).
+    ///
+    /// After the first failure the delay starts at `initialDelay`, and is scaled by `multiplier`
+    /// after each further failure, up to `maxDelay`. `jitter` (between 0 and 1) randomly shortens
+    /// each delay by up to that fraction. If `maxAttempts` is set, each route to the service is
+    /// only tried that many times before moving on to the next one. Existing connections are not
+    /// affected.
+    public func setRetryPolicy(
+        for service: Service,
+        initialDelay: TimeInterval,
+        multiplier: Double,
+        jitter: Double,
+        maxDelay: TimeInterval,
+        maxAttempts: UInt16? = nil
+    ) {
+        self.connectionManager.setRetryPolicy(
+            service: service,
+            initialDelayMillis: UInt32(clamping: Int64(initialDelay * 1000)),
+            multiplierPercent: UInt32(clamping: Int64((multiplier * 100).rounded())),
+            jitterPercent: UInt32(clamping: Int64((jitter * 100).rounded())),
+            maxDelayMillis: UInt32(clamping: Int64(maxDelay * 1000)),
+            maxAttempts: UInt32(maxAttempts ?? 0)
+        )
+    }
+
+    /// Requires new direct connections to `service` to present one of `pins` (until changed).
+    ///
+    /// Each pin is the SHA-256 hash of a DER-encoded SubjectPublicKeyInfo, and matches if any
+    /// certificate in the verified chain has that public key. Domain-fronted routes end at the
+    /// fronting provider, so they aren't pinned. Passing an empty list stops requiring pins.
+    /// Existing connections are not affected.
+    ///
+    /// - Throws: ``SignalError/invalidArgument(_:)`` if any pin is not 32 bytes long.
+    public func setCertificatePins(for service: Service, _ pins: [Data]) throws {
+        guard pins.allSatisfy({ $0.count == 32 }) else {
+            throw SignalError.invalidArgument("certificate pins must be SHA-256 hashes")
+        }
+        self.connectionManager.setCertificatePins(service: service, pins.reduce(Data(), +))
+    }
+
+    /// Which routes new connections try, and in what order.
+    ///
+    /// Routes not listed are never tried, so an app can, for example, avoid domain fronting on a
+    /// particular network by leaving out ``Route/proxyF`` and ``Route/proxyG``. If none of the
+    /// listed routes are available for a service, the direct route is used anyway. The order only
+    /// matters while censorship circumvention is enabled, but it is kept when that or any other
+    /// setting changes. It is also saved by ``exportRouteState(key:)``, and
+    /// ``importRouteState(_:key:)`` restores it. Setting this does not affect existing connections.
+    public var routeOrder: [Route] {
+        get {
+            self.connectionManager.routeOrder()
+        }
+        set {
+            self.connectionManager.setRouteOrder(newValue)
+        }
+    }
+
+    /// Saves which routes have been working, along with cached DNS results, encrypted with `key`.
+    ///
+    /// Pass the result to ``importRouteState(_:key:)`` on a later launch, with the same key, so that
+    /// routes that were working get tried first instead of being rediscovered. The key should be
+    /// kept in secure storage, since the saved state reveals how the device has been connecting.
+    ///
+    /// - parameter key: A 32-byte key.
+    /// - throws: ``SignalError/invalidArgument(_:)`` if `key` is the wrong length.
+    public func exportRouteState<KeyBytes: ContiguousBytes>(key: KeyBytes) throws -> Data {
+        try self.connectionManager.exportRouteState(key: key)
+    }
+
+    /// Restores state saved by ``exportRouteState(key:)``.
+    ///
+    /// Routes that were working through the same proxy (or without one) are tried first by new
+    /// connections, and the saved ``routeOrder`` replaces the current one. Existing connections are
+    /// not affected.
+    ///
+    /// - throws: ``SignalError/ioError(_:)`` if the state can't be decrypted with `key` or is
+    ///   malformed.
+    public func importRouteState<StateBytes: ContiguousBytes, KeyBytes: ContiguousBytes>(_ state: StateBytes, key: KeyBytes) throws {
+        try self.connectionManager.importRouteState(state, key: key)
+    }
+
     /// Notifies libsignal that the network has changed.
     ///
     /// This will lead to, e.g. caches being cleared and cooldowns being reset.
@@ -79,6 +201,22 @@ public class Net {
         }
     }
 
+    /// The current time according to the chat server, estimated from its recent responses.
+    ///
+    /// Use this instead of the local clock for decisions that have to agree with the server, such
+    /// as choosing a redemption time for group credentials or checking whether a credential has
+    /// expired. Falls back to the local time until a chat response has been received.
+    public var serverTime: Date {
+        let millis = self.connectionManager.withNativeHandle { connectionManager in
+            failOnError {
+                try invokeFnReturningInteger {
+                    signal_connection_manager_server_time($0, connectionManager)
+                }
+            }
+        }
+        return Date(timeIntervalSince1970: TimeInterval(millis) / 1000)
+    }
+
     /// Like ``cdsiLookup(auth:request:)`` but with the parameters to ``CdsiLookupRequest`` broken out.
     public func cdsiLookup(
         auth: Auth,
@@ -147,6 +285,27 @@ public class Net {
         return CdsiLookup(native: handle, asyncContext: self.asyncContext)
     }
 
+    /// Like ``cdsiLookup(auth:request:)``, but takes its connection from `pool`
+    /// if one is ready.
+    ///
+    /// Use the same pool for every lookup in a burst, such as a contact sync.
+    /// If `prepareNext` is set, a connection for the next lookup is established
+    /// in the pool while this one runs; leave it unset for the last lookup of the
+    /// burst, so that no attestation handshake is wasted.
+    public func cdsiLookup(
+        auth: Auth,
+        request: CdsiLookupRequest,
+        pool: CdsiConnectionPool,
+        prepareNext: Bool
+    ) async throws -> CdsiLookup {
+        let handle: OpaquePointer = try await self.asyncContext.invokeAsyncFunction { promise, asyncContext in
+            withNativeHandles(self.connectionManager, request, pool) { connectionManager, request, pool in
+                signal_cdsi_lookup_new_pooled(promise, asyncContext, connectionManager, auth.username, auth.password, request, pool, prepareNext)
+            }
+        }
+        return CdsiLookup(native: handle, asyncContext: self.asyncContext)
+    }
+
     public func createAuthenticatedChatService(username: String, password: String, receiveStories: Bool) -> AuthenticatedChatService {
         return AuthenticatedChatService(tokioAsyncContext: self.asyncContext, connectionManager: self.connectionManager, username: username, password: password, receiveStories: receiveStories)
     }
@@ -201,12 +360,86 @@ internal class ConnectionManager: NativeHandleOwner {
         }
     }
 
+    internal func proxy() -> String? {
+        self.withNativeHandle { connectionManager in
+            failOnError {
+                try invokeFnReturningOptionalString {
+                    signal_connection_manager_get_proxy($0, connectionManager)
+                }
+            }
+        }
+    }
+
     internal func setCensorshipCircumventionEnabled(_ enabled: Bool) {
         self.withNativeHandle {
             failOnError(signal_connection_manager_set_censorship_circumvention_enabled($0, enabled))
         }
     }
 
+    internal func setChatWebSocketLimits(maxFrameSize: UInt32, maxMessageSize: UInt32, maxPendingSendBytes: UInt32) {
+        self.withNativeHandle {
+            failOnError(signal_connection_manager_set_chat_websocket_limits($0, maxFrameSize, maxMessageSize, maxPendingSendBytes))
+        }
+    }
+
+    internal func setRetryPolicy(service: Net.Service, initialDelayMillis: UInt32, multiplierPercent: UInt32, jitterPercent: UInt32, maxDelayMillis: UInt32, maxAttempts: UInt32) {
+        self.withNativeHandle {
+            failOnError(signal_connection_manager_set_retry_policy($0, service.rawValue, initialDelayMillis, multiplierPercent, jitterPercent, maxDelayMillis, maxAttempts))
+        }
+    }
+
+    internal func setCertificatePins(service: Net.Service, _ pins: Data) {
+        self.withNativeHandle { connectionManager in
+            pins.withUnsafeBorrowedBuffer {
+                // The pins have already been checked, so they can't be invalid.
+                failOnError(signal_connection_manager_set_certificate_pins(connectionManager, service.rawValue, $0))
+            }
+        }
+    }
+
+    internal func setRouteOrder(_ order: [Net.Route]) {
+        self.withNativeHandle { connectionManager in
+            order.map(\.rawValue).withUnsafeBorrowedBuffer {
+                failOnError(signal_connection_manager_set_route_order(connectionManager, $0))
+            }
+        }
+    }
+
+    internal func routeOrder() -> [Net.Route] {
+        let encoded = self.withNativeHandle { connectionManager in
+            failOnError {
+                try invokeFnReturningArray {
+                    signal_connection_manager_get_route_order($0, connectionManager)
+                }
+            }
+        }
+        return encoded.map { Net.Route(rawValue: $0)! }
+    }
+
+    internal func exportRouteState<KeyBytes: ContiguousBytes>(key: KeyBytes) throws -> Data {
+        try self.withNativeHandle { connectionManager in
+            try key.withUnsafeBytes { keyBytes in
+                try ByteArray(newContents: Array(keyBytes), expectedLength: 32).withUnsafePointerToSerialized { keyTuple in
+                    try invokeFnReturningData {
+                        signal_connection_manager_export_route_state($0, connectionManager, keyTuple)
+                    }
+                }
+            }
+        }
+    }
+
+    internal func importRouteState<StateBytes: ContiguousBytes, KeyBytes: ContiguousBytes>(_ state: StateBytes, key: KeyBytes) throws {
+        try self.withNativeHandle { connectionManager in
+            try state.withUnsafeBorrowedBuffer { stateBuffer in
+                try key.withUnsafeBytes { keyBytes in
+                    try ByteArray(newContents: Array(keyBytes), expectedLength: 32).withUnsafePointerToSerialized { keyTuple in
+                        try checkError(signal_connection_manager_import_route_state(connectionManager, keyTuple, stateBuffer))
+                    }
+                }
+            }
+        }
+    }
+
     override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
         signal_connection_manager_destroy(handle)
     }
@@ -0,0 +1,97 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+import SignalFfi
+
+/// Durable storage for a ``NonceSequence``'s progress.
+public protocol NonceStore: AnyObject {
+    /// Records that every counter below `limit` may have been handed out.
+    ///
+    /// This must not return until `limit` would survive a crash. After a restart, pass the last
+    /// recorded limit to ``NonceSequence/init(key:prefix:persistedLimit:limit:)``. If this throws,
+    /// no nonce is produced and the error is rethrown to the caller.
+    func reserve(upTo limit: UInt64) throws
+}
+
+/// Hands out unique AES-GCM nonces for a single long-lived key.
+///
+/// Each nonce is a fixed prefix followed by a 64-bit counter. The sequence keeps its key, and is
+/// only used through ``Aes256GcmEncryption/init(nonces:store:associatedData:)``, so its nonces are
+/// never used with any other key. Progress is recorded through a ``NonceStore`` before nonces are
+/// handed out, so a crash can skip nonces but never repeat one.
+///
+/// A sequence may be shared between threads.
+public class NonceSequence: NativeHandleOwner, @unchecked Sendable {
+    /// The most nonces that will ever be produced for one key, and the default limit.
+    public static let maxLimit: UInt64 = 1 << 32
+
+    /// The length of the fixed prefix of each nonce.
+    public static let prefixLength: Int = 4
+
+    /// Resumes the sequence for `key`.
+    ///
+    /// - Parameters:
+    ///   - key: A 32-byte AES-256 key.
+    ///   - prefix: The fixed start of each nonce. Keys shared by more than one sender need a
+    ///     distinct prefix per sender.
+    ///   - persistedLimit: The last limit recorded by the ``NonceStore``, or 0 for a new key.
+    ///   - limit: The maximum number of nonces to use with this key. Values above ``maxLimit`` are
+    ///     treated as `maxLimit`.
+    public convenience init(
+        key: some ContiguousBytes,
+        prefix: some ContiguousBytes,
+        persistedLimit: UInt64,
+        limit: UInt64 = NonceSequence.maxLimit
+    ) throws {
+        let handle: OpaquePointer? = try key.withUnsafeBorrowedBuffer { keyBuffer in
+            try prefix.withUnsafeBorrowedBuffer { prefixBuffer in
+                var result: OpaquePointer?
+                try checkError(signal_nonce_sequence_new(
+                    &result,
+                    keyBuffer,
+                    prefixBuffer,
+                    persistedLimit,
+                    limit
+                ))
+                return result
+            }
+        }
+        self.init(owned: handle!)
+    }
+
+    override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        return signal_nonce_sequence_destroy(handle)
+    }
+
+    /// How many more nonces can be produced before the limit is reached.
+    public var remaining: UInt64 {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningInteger {
+                    signal_nonce_sequence_remaining($0, nativeHandle)
+                }
+            }
+        }
+    }
+}
+
+internal func withNonceStore<Result>(_ store: NonceStore, _ body: (UnsafePointer<SignalNonceStore>) throws -> Result) throws -> Result {
+    func ffiShimReserveUpTo(ctx: UnsafeMutableRawPointer?, limit: UInt64) -> Int32 {
+        let storeContext = ctx!.assumingMemoryBound(to: ErrorHandlingContext<NonceStore>.self)
+        return storeContext.pointee.catchCallbackErrors { store in
+            try store.reserve(upTo: limit)
+            return 0
+        }
+    }
+
+    return try rethrowCallbackErrors(store) {
+        var ffiStore = SignalNonceStore(
+            ctx: $0,
+            reserve_up_to: ffiShimReserveUpTo
+        )
+        return try body(&ffiStore)
+    }
+}
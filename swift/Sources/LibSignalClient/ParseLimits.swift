@@ -0,0 +1,36 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+import SignalFfi
+
+/// Process-wide caps on the resources libsignal will use to parse untrusted input.
+///
+/// The defaults don't restrict anything beyond what each parser already enforces.
+public struct ParseLimits: Equatable, Sendable {
+    /// The largest serialized message that will be decoded.
+    public var maxMessageSize: UInt64 = .max
+    /// The most elements a single decoded collection may have.
+    public var maxCollectionCount: UInt64 = .max
+    /// The most bytes a compressed stream may expand to.
+    public var maxDecompressedSize: UInt64 = .max
+    /// The most metadata a media file may have.
+    public var maxMediaMetadataSize: UInt64 = 300 * 1024 * 1024
+    /// The most decoded data a streaming parser, such as the backup validator, may keep in memory.
+    public var maxRetainedSize: UInt64 = .max
+
+    public init() {}
+
+    /// Replaces the limits used for all subsequent parsing.
+    public func apply() {
+        failOnError(signal_parse_limits_set(
+            self.maxMessageSize,
+            self.maxCollectionCount,
+            self.maxDecompressedSize,
+            self.maxMediaMetadataSize,
+            self.maxRetainedSize
+        ))
+    }
+}
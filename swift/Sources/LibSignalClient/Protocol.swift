@@ -72,6 +72,61 @@ public func signalDecryptPreKey(
     }
 }
 
+/// Like ``signalDecrypt(message:from:sessionStore:identityStore:context:)``, but rejects messages
+/// already recorded in `replayCache` with ``SignalError/duplicatedMessage(_:)``.
+public func signalDecrypt(
+    message: SignalMessage,
+    from address: ProtocolAddress,
+    sessionStore: SessionStore,
+    identityStore: IdentityKeyStore,
+    replayCache: ReplayCache,
+    context: StoreContext
+) throws -> [UInt8] {
+    return try withNativeHandles(message, address) { messageHandle, addressHandle in
+        try withSessionStore(sessionStore, context) { ffiSessionStore in
+            try withIdentityKeyStore(identityStore, context) { ffiIdentityStore in
+                try withReplayCache(replayCache, context) { ffiReplayCache in
+                    try invokeFnReturningArray {
+                        signal_decrypt_message_with_replay_cache($0, messageHandle, addressHandle, ffiSessionStore, ffiIdentityStore, ffiReplayCache)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Like ``signalDecryptPreKey(message:from:sessionStore:identityStore:preKeyStore:signedPreKeyStore:kyberPreKeyStore:context:)``,
+/// but rejects messages already recorded in `replayCache` with ``SignalError/duplicatedMessage(_:)``.
+public func signalDecryptPreKey(
+    message: PreKeySignalMessage,
+    from address: ProtocolAddress,
+    sessionStore: SessionStore,
+    identityStore: IdentityKeyStore,
+    preKeyStore: PreKeyStore,
+    signedPreKeyStore: SignedPreKeyStore,
+    kyberPreKeyStore: KyberPreKeyStore,
+    replayCache: ReplayCache,
+    context: StoreContext
+) throws -> [UInt8] {
+    return try withNativeHandles(message, address) { messageHandle, addressHandle in
+        try withSessionStore(sessionStore, context) { ffiSessionStore in
+            try withIdentityKeyStore(identityStore, context) { ffiIdentityStore in
+                try withPreKeyStore(preKeyStore, context) { ffiPreKeyStore in
+                    try withSignedPreKeyStore(signedPreKeyStore, context) { ffiSignedPreKeyStore in
+                        try withKyberPreKeyStore(kyberPreKeyStore, context) { ffiKyberPreKeyStore in
+                            try withReplayCache(replayCache, context) { ffiReplayCache in
+                                try invokeFnReturningArray {
+                                    signal_decrypt_pre_key_message_with_replay_cache($0, messageHandle, addressHandle, ffiSessionStore, ffiIdentityStore, ffiPreKeyStore, ffiSignedPreKeyStore, ffiKyberPreKeyStore, ffiReplayCache)
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
 public func processPreKeyBundle(
     _ bundle: PreKeyBundle,
     for address: ProtocolAddress,
@@ -89,6 +144,85 @@ public func processPreKeyBundle(
     }
 }
 
+/// Returns the members of `recipients` that have not yet been sent the sender key currently used for `distributionId`.
+///
+/// If there is no sender key yet, the key has changed since distribution was last recorded, or the store does not
+/// track distribution, every recipient is returned. Each of them should be sent a ``SenderKeyDistributionMessage``
+/// before (or along with) the next group message, and then passed to
+/// ``markSenderKeyDistributed(from:distributionId:to:store:context:)``.
+public func senderKeyRecipientsNeedingDistribution(
+    from sender: ProtocolAddress,
+    distributionId: UUID,
+    among recipients: [ProtocolAddress],
+    store: SenderKeyStore,
+    context: StoreContext
+) throws -> [ProtocolAddress] {
+    let needsDistribution = try withSenderKeyDistributionArguments(sender, distributionId, recipients, store, context) { sender, distributionId, recipients, ffiStore in
+        try invokeFnReturningArray {
+            signal_sender_key_recipients_needing_distribution($0, sender, distributionId, recipients, ffiStore)
+        }
+    }
+    return zip(recipients, needsDistribution).filter { $0.1 != 0 }.map { $0.0 }
+}
+
+/// Records that `recipients` have been sent the sender key currently used for `distributionId`.
+///
+/// Call this only once the ``SenderKeyDistributionMessage`` has actually been delivered. Any record left over from a
+/// previous sender key is discarded.
+public func markSenderKeyDistributed(
+    from sender: ProtocolAddress,
+    distributionId: UUID,
+    to recipients: [ProtocolAddress],
+    store: SenderKeyStore,
+    context: StoreContext
+) throws {
+    try withSenderKeyDistributionArguments(sender, distributionId, recipients, store, context) { sender, distributionId, recipients, ffiStore in
+        try checkError(signal_sender_key_mark_distributed(sender, distributionId, recipients, ffiStore))
+    }
+}
+
+/// Forgets that `recipients` have been sent the sender key for `distributionId`, so that they will be returned by
+/// ``senderKeyRecipientsNeedingDistribution(from:distributionId:among:store:context:)`` again.
+///
+/// Use this when a recipient may have lost the key, such as after their session is reset or they send a decryption
+/// error for a group message.
+public func invalidateSenderKeyDistribution(
+    from sender: ProtocolAddress,
+    distributionId: UUID,
+    for recipients: [ProtocolAddress],
+    store: SenderKeyStore,
+    context: StoreContext
+) throws {
+    try withSenderKeyDistributionArguments(sender, distributionId, recipients, store, context) { sender, distributionId, recipients, ffiStore in
+        try checkError(signal_sender_key_invalidate_distribution(sender, distributionId, recipients, ffiStore))
+    }
+}
+
+private func withSenderKeyDistributionArguments<Result>(
+    _ sender: ProtocolAddress,
+    _ distributionId: UUID,
+    _ recipients: [ProtocolAddress],
+    _ store: SenderKeyStore,
+    _ context: StoreContext,
+    _ body: (OpaquePointer?, UnsafePointer<uuid_t>, SignalBorrowedSliceOfProtocolAddress, UnsafePointer<SignalSenderKeyStore>) throws -> Result
+) throws -> Result {
+    // Use withExtendedLifetime instead of withNativeHandle for the array of wrapper objects,
+    // which isn't compatible with withNativeHandle's simple lexical scoping.
+    return try withExtendedLifetime(recipients) {
+        let recipientHandles = recipients.map { $0.unsafeNativeHandle }
+        return try sender.withNativeHandle { senderHandle in
+            try recipientHandles.withUnsafeBufferPointer { recipientHandles in
+                let recipientHandlesBuffer = SignalBorrowedSliceOfProtocolAddress(base: recipientHandles.baseAddress, length: recipientHandles.count)
+                return try withUnsafePointer(to: distributionId.uuid) { distributionId in
+                    try withSenderKeyStore(store, context) { ffiStore in
+                        try body(senderHandle, distributionId, recipientHandlesBuffer, ffiStore)
+                    }
+                }
+            }
+        }
+    }
+}
+
 public func groupEncrypt<Bytes: ContiguousBytes>(
     _ message: Bytes,
     from sender: ProtocolAddress,
@@ -0,0 +1,60 @@
+//
+// Copyright 2026 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+import SignalFfi
+
+/// Receipts for messages from one sender that should be sent together as a single receipt message.
+///
+/// Obtained from ``AuthenticatedChatService/nextReceiptBatch()``.
+public class ReceiptBatch: NativeHandleOwner {
+    /// The kinds of receipt a client can send; each kind is sent as a separate message.
+    public enum Kind: UInt8, Sendable {
+        // This needs to be kept in sync with the Rust ReceiptKind enum.
+        case delivery = 0
+        case read = 1
+        case viewed = 2
+    }
+
+    override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        return signal_receipt_batch_destroy(handle)
+    }
+
+    /// The author of the acknowledged messages, and the recipient of the receipt message.
+    public var sender: ServiceId {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningServiceId {
+                    signal_receipt_batch_get_sender($0, nativeHandle)
+                }
+            }
+        }
+    }
+
+    public var kind: Kind {
+        let rawValue = withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningInteger {
+                    signal_receipt_batch_get_kind($0, nativeHandle)
+                }
+            }
+        }
+        return Kind(rawValue: rawValue)!
+    }
+
+    /// The sent timestamps of the acknowledged messages, in ascending order.
+    public var timestamps: [UInt64] {
+        let packed = withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningArray {
+                    signal_receipt_batch_get_timestamps($0, nativeHandle)
+                }
+            }
+        }
+        return stride(from: 0, to: packed.count, by: 8).map { start in
+            packed[start..<start + 8].reduce(0) { $0 << 8 | UInt64($1) }
+        }
+    }
+}
@@ -98,10 +98,54 @@ public class UnidentifiedSenderMessageContent: NativeHandleOwner {
         self.init(owned: result!)
     }
 
+    public convenience init<Bytes: ContiguousBytes>(bytes: Bytes) throws {
+        let handle: OpaquePointer? = try bytes.withUnsafeBorrowedBuffer {
+            var result: OpaquePointer?
+            try checkError(signal_unidentified_sender_message_content_deserialize(&result, $0))
+            return result
+        }
+        self.init(owned: handle!)
+    }
+
     override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
         return signal_unidentified_sender_message_content_destroy(handle)
     }
 
+    public func serialize() -> [UInt8] {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningArray {
+                    signal_unidentified_sender_message_content_serialize($0, nativeHandle)
+                }
+            }
+        }
+    }
+
+    /// Returns a copy of this message with a different content hint and group ID.
+    ///
+    /// The sender certificate and the encrypted contents are kept as they are, so a message can be
+    /// resent with different handling without rebuilding it from a ``CiphertextMessage``. Pass an
+    /// empty `groupId` for a message that wasn't sent to a group.
+    public func withContentHint<GroupIdBytes: ContiguousBytes>(
+        _ contentHint: ContentHint,
+        groupId: GroupIdBytes
+    ) -> UnidentifiedSenderMessageContent {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try groupId.withUnsafeBorrowedBuffer { groupIdBuffer in
+                    try invokeFnReturningNativeHandle {
+                        signal_unidentified_sender_message_content_with_content_hint_and_group_id(
+                            $0,
+                            nativeHandle,
+                            contentHint.rawValue,
+                            groupIdBuffer
+                        )
+                    }
+                }
+            }
+        }
+    }
+
     public var senderCertificate: SenderCertificate {
         return withNativeHandle { nativeHandle in
             failOnError {
@@ -218,6 +262,42 @@ public func sealedSenderMultiRecipientEncrypt(
     }
 }
 
+/// Computes the exact length of the message produced by ``sealedSenderMultiRecipientEncrypt(_:for:excludedRecipients:identityStore:sessionStore:context:)``,
+/// without encrypting anything.
+///
+/// `usmcLength` is the length of the serialized ``UnidentifiedSenderMessageContent``;
+/// `deviceCount` is the total number of devices across all `recipientCount` recipients.
+public func sealedSenderMultiRecipientMessageLength(
+    usmcLength: Int,
+    recipientCount: Int,
+    deviceCount: Int,
+    excludedRecipientCount: Int = 0
+) -> Int {
+    let result = failOnError {
+        try invokeFnReturningInteger {
+            signal_sealed_sender_multi_recipient_message_length(
+                $0,
+                UInt32(usmcLength),
+                UInt32(recipientCount),
+                UInt32(deviceCount),
+                UInt32(excludedRecipientCount)
+            )
+        }
+    }
+    return Int(result)
+}
+
+/// Computes the length of the message each recipient device receives after the server splits up
+/// a multi-recipient message.
+public func sealedSenderMultiRecipientReceivedMessageLength(usmcLength: Int) -> Int {
+    let result = failOnError {
+        try invokeFnReturningInteger {
+            signal_sealed_sender_multi_recipient_received_message_length($0, UInt32(usmcLength))
+        }
+    }
+    return Int(result)
+}
+
 // For testing only.
 internal func sealedSenderMultiRecipientMessageForSingleRecipient(_ message: [UInt8]) throws -> [UInt8] {
     return try message.withUnsafeBorrowedBuffer { message in
@@ -255,6 +335,21 @@ public struct SealedSenderAddress: Hashable, Sendable {
 public struct SealedSenderResult: Sendable {
     public var message: [UInt8]
     public var sender: SealedSenderAddress
+    /// Only set by ``sealedSenderDecryptCapturingIdentityChange(message:from:trustRoot:timestamp:sessionStore:identityStore:preKeyStore:signedPreKeyStore:kyberPreKeyStore:context:)``.
+    public var identityChange: SealedSenderIdentityChange? = nil
+}
+
+/// The sender of a message used a different identity key than the one on record for them.
+public struct SealedSenderIdentityChange: Equatable, Sendable {
+    /// The identity key that was in the identity store.
+    public var previous: IdentityKey
+    /// The identity key the message was decrypted with.
+    public var current: IdentityKey
+
+    public init(previous: IdentityKey, current: IdentityKey) {
+        self.previous = previous
+        self.current = current
+    }
 }
 
 public func sealedSenderDecrypt<Bytes: ContiguousBytes>(
@@ -317,3 +412,155 @@ public func sealedSenderDecrypt<Bytes: ContiguousBytes>(
         )
     )
 }
+
+/// Like ``sealedSenderDecrypt(message:from:trustRoot:timestamp:sessionStore:identityStore:preKeyStore:signedPreKeyStore:context:)``,
+/// but rejects inner messages already recorded in `replayCache` with ``SignalError/duplicatedMessage(_:)``.
+public func sealedSenderDecrypt<Bytes: ContiguousBytes>(
+    message: Bytes,
+    from localAddress: SealedSenderAddress,
+    trustRoot: PublicKey,
+    timestamp: UInt64,
+    sessionStore: SessionStore,
+    identityStore: IdentityKeyStore,
+    preKeyStore: PreKeyStore,
+    signedPreKeyStore: SignedPreKeyStore,
+    replayCache: ReplayCache,
+    context: StoreContext
+) throws -> SealedSenderResult {
+    var senderE164: UnsafePointer<CChar>?
+    var senderUUID: UnsafePointer<CChar>?
+    var senderDeviceId: UInt32 = 0
+
+    let plaintext = try trustRoot.withNativeHandle { trustRootHandle in
+        try message.withUnsafeBorrowedBuffer { messageBuffer in
+            try withSessionStore(sessionStore, context) { ffiSessionStore in
+                try withIdentityKeyStore(identityStore, context) { ffiIdentityStore in
+                    try withPreKeyStore(preKeyStore, context) { ffiPreKeyStore in
+                        try withSignedPreKeyStore(signedPreKeyStore, context) { ffiSignedPreKeyStore in
+                            try withReplayCache(replayCache, context) { ffiReplayCache in
+                                try invokeFnReturningArray {
+                                    signal_sealed_session_cipher_decrypt_with_replay_cache(
+                                        $0,
+                                        &senderE164,
+                                        &senderUUID,
+                                        &senderDeviceId,
+                                        messageBuffer,
+                                        trustRootHandle,
+                                        timestamp,
+                                        localAddress.e164,
+                                        localAddress.uuidString,
+                                        localAddress.deviceId,
+                                        ffiSessionStore,
+                                        ffiIdentityStore,
+                                        ffiPreKeyStore,
+                                        ffiSignedPreKeyStore,
+                                        ffiReplayCache
+                                    )
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    defer {
+        signal_free_string(senderE164)
+        signal_free_string(senderUUID)
+    }
+
+    return SealedSenderResult(
+        message: plaintext,
+        sender: try SealedSenderAddress(
+            e164: senderE164.map(String.init(cString:)),
+            uuidString: String(cString: senderUUID!),
+            deviceId: senderDeviceId
+        )
+    )
+}
+
+/// Like ``sealedSenderDecrypt(message:from:trustRoot:timestamp:sessionStore:identityStore:preKeyStore:signedPreKeyStore:context:)``,
+/// but decrypts messages from a sender whose identity key has changed instead of rejecting them.
+///
+/// The change is reported in ``SealedSenderResult/identityChange`` and is *not* saved to
+/// `identityStore`; if the app accepts the new key, it should save it itself. Senders with no
+/// identity on record, or with an unchanged identity, are handled as usual.
+public func sealedSenderDecryptCapturingIdentityChange<Bytes: ContiguousBytes>(
+    message: Bytes,
+    from localAddress: SealedSenderAddress,
+    trustRoot: PublicKey,
+    timestamp: UInt64,
+    sessionStore: SessionStore,
+    identityStore: IdentityKeyStore,
+    preKeyStore: PreKeyStore,
+    signedPreKeyStore: SignedPreKeyStore,
+    kyberPreKeyStore: KyberPreKeyStore,
+    context: StoreContext
+) throws -> SealedSenderResult {
+    var result: OpaquePointer?
+    try trustRoot.withNativeHandle { trustRootHandle in
+        try message.withUnsafeBorrowedBuffer { messageBuffer in
+            try withSessionStore(sessionStore, context) { ffiSessionStore in
+                try withIdentityKeyStore(identityStore, context) { ffiIdentityStore in
+                    try withPreKeyStore(preKeyStore, context) { ffiPreKeyStore in
+                        try withSignedPreKeyStore(signedPreKeyStore, context) { ffiSignedPreKeyStore in
+                            try withKyberPreKeyStore(kyberPreKeyStore, context) { ffiKyberPreKeyStore in
+                                try checkError(
+                                    signal_sealed_sender_decrypt_message_capturing_identity_change(
+                                        &result,
+                                        messageBuffer,
+                                        trustRootHandle,
+                                        timestamp,
+                                        localAddress.e164,
+                                        localAddress.uuidString,
+                                        localAddress.deviceId,
+                                        ffiSessionStore,
+                                        ffiIdentityStore,
+                                        ffiPreKeyStore,
+                                        ffiSignedPreKeyStore,
+                                        ffiKyberPreKeyStore
+                                    ))
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    defer {
+        failOnError(signal_sealed_sender_decryption_result_destroy(result))
+    }
+
+    let message = try invokeFnReturningArray {
+        signal_sealed_sender_decryption_result_message($0, result)
+    }
+    let sender = try SealedSenderAddress(
+        e164: invokeFnReturningOptionalString {
+            signal_sealed_sender_decryption_result_get_sender_e164($0, result)
+        },
+        uuidString: invokeFnReturningString {
+            signal_sealed_sender_decryption_result_get_sender_uuid($0, result)
+        },
+        deviceId: invokeFnReturningInteger {
+            signal_sealed_sender_decryption_result_get_device_id($0, result)
+        }
+    )
+    let previous: PublicKey? = try invokeFnReturningOptionalNativeHandle {
+        signal_sealed_sender_decryption_result_get_previous_identity_key($0, result)
+    }
+    let current: PublicKey? = try invokeFnReturningOptionalNativeHandle {
+        signal_sealed_sender_decryption_result_get_current_identity_key($0, result)
+    }
+    let identityChange = previous.flatMap { previous in
+        current.map { current in
+            SealedSenderIdentityChange(
+                previous: IdentityKey(publicKey: previous),
+                current: IdentityKey(publicKey: current)
+            )
+        }
+    }
+
+    return SealedSenderResult(message: message, sender: sender, identityChange: identityChange)
+}
@@ -222,4 +222,23 @@ public class SenderCertificate: NativeHandleOwner, @unchecked Sendable {
         }
         return result
     }
+
+    /// Returns whether this certificate will have expired by `time + refreshMargin`.
+    ///
+    /// Use this to replace a cached certificate before it expires, rather than waiting for sends to
+    /// fail. This does not validate the certificate.
+    ///
+    /// - Parameter time: The current time, in milliseconds since the epoch.
+    /// - Parameter refreshMargin: How long before expiration the certificate should be replaced.
+    ///   Truncated to whole seconds.
+    public func needsRefresh(time: UInt64, refreshMargin: TimeInterval) -> Bool {
+        let refreshMarginSeconds = UInt32(clamping: Int64(max(0, min(refreshMargin, Double(UInt32.max)))))
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningBool {
+                    signal_sender_certificate_needs_refresh($0, nativeHandle, time, refreshMarginSeconds)
+                }
+            }
+        }
+    }
 }
@@ -152,6 +152,76 @@ public class ServiceId: @unchecked Sendable {
         return try result.downcast(to: Self.self)
     }
 
+    /// Parses many Service-Id-Binary values at once.
+    ///
+    /// Equivalent to calling ``parseFrom(serviceIdBinary:)`` on each element, but crosses into
+    /// native code only once.
+    public static func parseFrom(serviceIdBinaries: some Collection<some ContiguousBytes>) throws -> [ServiceId] {
+        // Swift doesn't let us access an arbitrary number of arrays as pointers, so instead we
+        // concatenate all the inputs into one big buffer and then chop that up into borrowed
+        // slices.
+        var concatenated: [UInt8] = []
+        var lengths: [Int] = []
+        lengths.reserveCapacity(serviceIdBinaries.count)
+        for next in serviceIdBinaries {
+            next.withUnsafeBytes { concatenated.append(contentsOf: $0) }
+            lengths.append(next.withUnsafeBytes { $0.count })
+        }
+        let fixedWidthBinaries = try concatenated.withUnsafeBytes { concatenated in
+            var slices: [SignalBorrowedBuffer] = []
+            slices.reserveCapacity(lengths.count)
+            var offset = 0
+            for length in lengths {
+                let slice = UnsafeRawBufferPointer(rebasing: concatenated[offset...].prefix(length))
+                slices.append(SignalBorrowedBuffer(slice))
+                offset += length
+            }
+
+            return try slices.withUnsafeBufferPointer { slices in
+                try invokeFnReturningArray {
+                    signal_service_id_parse_from_service_id_binaries($0, SignalBorrowedSliceOfBuffers(base: slices.baseAddress, length: slices.count))
+                }
+            }
+        }
+
+        let entrySize = MemoryLayout<ServiceIdStorage>.size
+        return try stride(from: 0, to: fixedWidthBinaries.count, by: entrySize).map { offset in
+            var storage: ServiceIdStorage = (0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0)
+            withUnsafeMutableBytes(of: &storage) {
+                $0.copyBytes(from: fixedWidthBinaries[offset..<(offset + entrySize)])
+            }
+            return try ServiceId.parseFrom(fixedWidthBinary: storage)
+        }
+    }
+
+    /// Converts many service IDs to Service-Id-Binary at once.
+    ///
+    /// Equivalent to calling ``serviceIdBinary`` on each element, but crosses into native code only
+    /// once.
+    public static func serviceIdBinaries(of serviceIds: some Collection<ServiceId>) -> [[UInt8]] {
+        return failOnError {
+            try concatenatedFixedWidthBinary(serviceIds).withUnsafeBorrowedBuffer { concatenated in
+                try invokeFnReturningBytestringArray {
+                    signal_service_id_service_id_binaries($0, concatenated)
+                }
+            }
+        }
+    }
+
+    /// Converts many service IDs to Service-Id-String at once.
+    ///
+    /// Equivalent to calling ``serviceIdString`` on each element, but crosses into native code only
+    /// once.
+    public static func serviceIdStrings(of serviceIds: some Collection<ServiceId>) -> [String] {
+        return failOnError {
+            try concatenatedFixedWidthBinary(serviceIds).withUnsafeBorrowedBuffer { concatenated in
+                try invokeFnReturningStringArray {
+                    signal_service_id_service_id_strings($0, concatenated)
+                }
+            }
+        }
+    }
+
     internal func withPointerToFixedWidthBinary<R>(_ callback: (UnsafePointer<ServiceIdStorage>) throws -> R) rethrows -> R {
         return try callback(&self.storage)
     }
@@ -0,0 +1,177 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+import SignalFfi
+
+/// Works out how to bring the local database and the storage service back in sync.
+///
+/// Manifests are decrypted `ManifestRecord` messages, and record ids are serialized
+/// `ManifestRecord.Identifier` messages. Records are passed in serialized form, along with a key
+/// identifying "the same" record across devices (such as a contact's ACI); a local record that isn't
+/// on the service yet wins over the service's copy, and otherwise the service's copy wins.
+///
+/// To sync, add every local record, local deletion, and id already known to be of an unknown type;
+/// fetch and decrypt the records for ``idsToFetch()``; add those; and then call ``plan()``.
+public class StorageSync: NativeHandleOwner {
+    /// - Parameters:
+    ///   - previousManifest: The manifest as of the last successful sync, or empty if there has never
+    ///     been one.
+    ///   - remoteManifest: The manifest currently on the service.
+    public convenience init<Previous: ContiguousBytes, Remote: ContiguousBytes>(previousManifest: Previous, remoteManifest: Remote) throws {
+        let handle: OpaquePointer? = try previousManifest.withUnsafeBorrowedBuffer { previousBuffer in
+            try remoteManifest.withUnsafeBorrowedBuffer { remoteBuffer in
+                var result: OpaquePointer?
+                try checkError(signal_storage_sync_new(&result, previousBuffer, remoteBuffer))
+                return result
+            }
+        }
+        self.init(owned: handle!)
+    }
+
+    override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        return signal_storage_sync_destroy(handle)
+    }
+
+    /// Adds a record from the local database.
+    public func addLocalRecord(id: [UInt8], key: [UInt8], data: [UInt8]) throws {
+        try self.addRecord(id: id, key: key, data: data, signal_storage_sync_add_local_record)
+    }
+
+    /// Adds the id of a record that was deleted locally since the last sync.
+    public func addLocalDeletion(id: [UInt8]) throws {
+        try self.addId(id, signal_storage_sync_add_local_deletion)
+    }
+
+    /// Adds the id of a record already known to be of a type this client can't parse.
+    public func addKnownUnknown(id: [UInt8]) throws {
+        try self.addId(id, signal_storage_sync_add_known_unknown)
+    }
+
+    /// Returns the ids that need to be fetched from the service, in manifest order.
+    public func idsToFetch() -> [[UInt8]] {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningBytestringArray {
+                    signal_storage_sync_ids_to_fetch($0, nativeHandle)
+                }
+            }
+        }
+    }
+
+    /// Adds a record fetched from the service.
+    public func addFetchedRecord(id: [UInt8], key: [UInt8], data: [UInt8]) throws {
+        try self.addRecord(id: id, key: key, data: data, signal_storage_sync_add_fetched_record)
+    }
+
+    /// Adds the id of a fetched record of a type this client can't parse.
+    public func addFetchedUnknown(id: [UInt8]) throws {
+        try self.addId(id, signal_storage_sync_add_fetched_unknown)
+    }
+
+    /// Plans the sync, consuming the records added so far.
+    ///
+    /// Fails if the remote manifest is older than the previous one, or if a fetched record isn't in
+    /// the remote manifest.
+    public func plan() throws -> StorageSyncPlan {
+        return try withNativeHandle { nativeHandle in
+            try invokeFnReturningNativeHandle {
+                signal_storage_sync_plan($0, nativeHandle)
+            }
+        }
+    }
+
+    private func addRecord(
+        id: [UInt8],
+        key: [UInt8],
+        data: [UInt8],
+        _ fn: (OpaquePointer?, SignalBorrowedBuffer, SignalBorrowedBuffer, SignalBorrowedBuffer) -> SignalFfiErrorRef?
+    ) throws {
+        try withNativeHandle { nativeHandle in
+            try id.withUnsafeBorrowedBuffer { idBuffer in
+                try key.withUnsafeBorrowedBuffer { keyBuffer in
+                    try data.withUnsafeBorrowedBuffer { dataBuffer in
+                        try checkError(fn(nativeHandle, idBuffer, keyBuffer, dataBuffer))
+                    }
+                }
+            }
+        }
+    }
+
+    private func addId(_ id: [UInt8], _ fn: (OpaquePointer?, SignalBorrowedBuffer) -> SignalFfiErrorRef?) throws {
+        try withNativeHandle { nativeHandle in
+            try id.withUnsafeBorrowedBuffer { idBuffer in
+                try checkError(fn(nativeHandle, idBuffer))
+            }
+        }
+    }
+}
+
+/// The changes to make on each side, as planned by ``StorageSync``.
+public class StorageSyncPlan: NativeHandleOwner {
+    override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        return signal_storage_sync_plan_destroy(handle)
+    }
+
+    /// The ids of records to insert or replace locally, parallel to ``localUpsertData``.
+    public var localUpsertIds: [[UInt8]] {
+        return self.getList(signal_storage_sync_plan_local_upsert_ids)
+    }
+
+    public var localUpsertData: [[UInt8]] {
+        return self.getList(signal_storage_sync_plan_local_upsert_data)
+    }
+
+    public var localDeletes: [[UInt8]] {
+        return self.getList(signal_storage_sync_plan_local_deletes)
+    }
+
+    /// The ids of records of unknown types still in the manifest, to remember for next time.
+    public var unknown: [[UInt8]] {
+        return self.getList(signal_storage_sync_plan_unknown)
+    }
+
+    /// Whether a new manifest needs to be uploaded.
+    ///
+    /// If not, the remote-side accessors all return empty lists.
+    public var hasRemoteChanges: Bool {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningBool {
+                    signal_storage_sync_plan_has_remote_changes($0, nativeHandle)
+                }
+            }
+        }
+    }
+
+    /// The record ids for the manifest to upload, whose version is one more than the remote
+    /// manifest's.
+    public var remoteManifestIds: [[UInt8]] {
+        return self.getList(signal_storage_sync_plan_remote_manifest_ids)
+    }
+
+    /// The ids of records to upload, parallel to ``remoteInsertData``.
+    public var remoteInsertIds: [[UInt8]] {
+        return self.getList(signal_storage_sync_plan_remote_insert_ids)
+    }
+
+    public var remoteInsertData: [[UInt8]] {
+        return self.getList(signal_storage_sync_plan_remote_insert_data)
+    }
+
+    public var remoteDeletes: [[UInt8]] {
+        return self.getList(signal_storage_sync_plan_remote_deletes)
+    }
+
+    private func getList(_ fn: (UnsafeMutablePointer<SignalBytestringArray>?, OpaquePointer?) -> SignalFfiErrorRef?) -> [[UInt8]] {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningBytestringArray {
+                    fn($0, nativeHandle)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//
+// Copyright 2026 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+import SignalFfi
+
+/// Checks that a store implementation behaves the way libsignal expects.
+///
+/// These are the same checks libsignal runs against its own stores, each run against a fresh store
+/// from `makeStore`. They are meant to be run from an app's tests, not in production.
+///
+/// - Throws: ``SignalError/invalidState(_:)`` describing the first check that failed, or any error
+///   thrown by the store itself.
+public func checkStoreConformance<Store>(
+    context: StoreContext,
+    makeStore: (IdentityKeyPair, UInt32) throws -> Store
+) throws where Store: IdentityKeyStore & PreKeyStore & SignedPreKeyStore & KyberPreKeyStore & SessionStore & SenderKeyStore {
+    let count: UInt32 = failOnError {
+        try invokeFnReturningInteger {
+            signal_store_conformance_check_count($0)
+        }
+    }
+    for check in 0..<count {
+        let identity = IdentityKeyPair.generate()
+        let registrationId = UInt32.random(in: 1...0x3FFF)
+        let store = try makeStore(identity, registrationId)
+        try withNativeHandles(identity.publicKey, identity.privateKey) { publicKey, privateKey in
+            try withIdentityKeyStore(store, context) { ffiIdentityStore in
+                try withPreKeyStore(store, context) { ffiPreKeyStore in
+                    try withSignedPreKeyStore(store, context) { ffiSignedPreKeyStore in
+                        try withKyberPreKeyStore(store, context) { ffiKyberPreKeyStore in
+                            try withSessionStore(store, context) { ffiSessionStore in
+                                try withSenderKeyStore(store, context) { ffiSenderKeyStore in
+                                    try checkError(
+                                        signal_store_conformance_check(
+                                            check,
+                                            publicKey,
+                                            privateKey,
+                                            registrationId,
+                                            ffiIdentityStore,
+                                            ffiPreKeyStore,
+                                            ffiSignedPreKeyStore,
+                                            ffiKyberPreKeyStore,
+                                            ffiSessionStore,
+                                            ffiSenderKeyStore
+                                        ))
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
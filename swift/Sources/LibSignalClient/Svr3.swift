@@ -114,6 +114,67 @@ public class Svr3Client {
         return Array(UnsafeBufferPointer(start: output.base, count: output.length))
     }
 
+    /// Backup a secret to only some of the SVR3 backends.
+    ///
+    /// Works like ``backup(_:password:maxTries:auth:)``, except that the
+    /// secret is shared only among the backends listed in `distribution`. The
+    /// resulting share set records the layout, so ``restore(password:shareSet:auth:)``
+    /// needs no extra arguments.
+    ///
+    /// - Throws:
+    ///   In addition to the errors thrown by ``backup(_:password:maxTries:auth:)``,
+    ///   `SignalError.invalidArgument` if the distribution is not supported:
+    ///   an unknown or repeated backend, or a threshold other than the number
+    ///   of selected backends.
+    public func backup(
+        _ secret: some ContiguousBytes,
+        password: String,
+        maxTries: UInt32,
+        distribution: Svr3ShareDistribution,
+        auth: Auth
+    ) async throws -> [UInt8] {
+        let output = try await self.asyncContext.invokeAsyncFunction { promise, asyncContext in
+            self.connectionManager.withNativeHandle { connectionManager in
+                secret.withUnsafeBorrowedBuffer { secretBuffer in
+                    distribution.serverIds.withUnsafeBorrowedBuffer { serverIdsBuffer in
+                        signal_svr3_backup_with_distribution(
+                            promise,
+                            asyncContext,
+                            connectionManager,
+                            secretBuffer,
+                            password,
+                            maxTries,
+                            serverIdsBuffer,
+                            distribution.threshold,
+                            auth.username,
+                            auth.password
+                        )
+                    }
+                }
+            }
+        }
+        defer {
+            signal_free_buffer(output.base, output.length)
+        }
+        return Array(UnsafeBufferPointer(start: output.base, count: output.length))
+    }
+
+    /// Returns the layout of a share set produced by one of the `backup`
+    /// methods.
+    ///
+    /// - Throws: `SignalError` if the share set cannot be parsed.
+    public static func layout(ofShareSet shareSet: some ContiguousBytes) throws -> Svr3ShareDistribution {
+        try shareSet.withUnsafeBorrowedBuffer { shareSetBuffer in
+            let serverIds = try invokeFnReturningArray {
+                signal_svr3_get_share_set_server_ids($0, shareSetBuffer)
+            }
+            let threshold = try invokeFnReturningInteger {
+                signal_svr3_get_share_set_threshold($0, shareSetBuffer)
+            }
+            return Svr3ShareDistribution(serverIds: serverIds, threshold: threshold)
+        }
+    }
+
     /// Migrate a secret to a new SVR3 environment.
     ///
     /// Enclaves need to be updated from time to time and when they do, the
@@ -350,6 +411,22 @@ public class Svr3Client {
     }
 }
 
+/// Which SVR3 backends hold shares of a secret.
+///
+/// Backends are identified by their position in the environment, starting
+/// from 1; in the standard environments these are SGX, Nitro, and TPM2-SNP, in
+/// that order. `threshold` is the number of backends needed to restore the
+/// secret.
+public struct Svr3ShareDistribution: Equatable, Sendable {
+    public var serverIds: [UInt8]
+    public var threshold: UInt32
+
+    public init(serverIds: [UInt8], threshold: UInt32) {
+        self.serverIds = serverIds
+        self.threshold = threshold
+    }
+}
+
 public struct RestoredSecret: Sendable {
     public let value: [UInt8]
     public let triesRemaining: UInt32
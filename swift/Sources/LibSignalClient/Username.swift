@@ -96,6 +96,31 @@ public struct Username: Sendable {
     }
 }
 
+/// The contents of a username link URL, as shared via QR code or copied link.
+///
+/// The server ID identifies the encrypted username stored on the server; once fetched, it can be
+/// decrypted with ``Username/init(fromLink:withRandomness:)`` using the entropy.
+public struct UsernameLinkUrl: Equatable, Sendable {
+    public let entropy: [UInt8]
+    public let serverId: UUID
+
+    /// Parses a `https://signal.me/#eu/...` or `sgnl://signal.me/#eu/...` URL without contacting
+    /// the server.
+    ///
+    /// - Throws: ``SignalError/usernameLinkInvalid(_:)`` if `url` isn't a username link, or
+    ///   ``SignalError/usernameLinkInvalidEntropyDataLength(_:)`` if the link's payload is the
+    ///   wrong length.
+    public init(fromUrl url: String) throws {
+        let bytes = try url.withCString { urlPtr in
+            try invokeFnReturningFixedLengthArray {
+                signal_username_link_parse_url($0, urlPtr)
+            }
+        }
+        self.entropy = Array(bytes[..<32])
+        self.serverId = bytes[32...].withUnsafeBytes { UUID(uuid: $0.load(as: uuid_t.self)) }
+    }
+}
+
 extension Username: CustomStringConvertible {
     public var description: String {
         return self.value
@@ -25,7 +25,15 @@ internal func invokeFnReturningOptionalString(fn: (UnsafeMutablePointer<UnsafePo
     return result
 }
 
-private func invokeFnReturningSomeBytestringArray<Element>(fn: (UnsafeMutablePointer<SignalBytestringArray>?) -> SignalFfiErrorRef?, transform: (UnsafeBufferPointer<UInt8>) -> Element) throws -> [Element] {
+internal func invokeFnReturningStringArray(fn: (UnsafeMutablePointer<SignalStringArray>?) -> SignalFfiErrorRef?) throws -> [String] {
+    var array = SignalStringArray()
+    try checkError(fn(&array))
+    defer { signal_free_list_of_strings(array) }
+
+    return UnsafeBufferPointer(start: array.base, count: array.length).map { String(cString: $0!) }
+}
+
+internal func invokeFnReturningBytestringArray(fn: (UnsafeMutablePointer<SignalBytestringArray>?) -> SignalFfiErrorRef?) throws -> [[UInt8]] {
     var array = SignalFfi.SignalBytestringArray()
     try checkError(fn(&array))
 
@@ -33,27 +41,15 @@ private func invokeFnReturningSomeBytestringArray<Element>(fn: (UnsafeMutablePoi
     let lengths = UnsafeBufferPointer(start: array.lengths.base, count: array.lengths.length)
 
     let result = lengths.map { length in
-        let view = UnsafeBufferPointer(rebasing: bytes.prefix(length))
+        let view = bytes.prefix(length)
         bytes = bytes.dropFirst(length)
-        return transform(view)
+        return Array(view)
     }
 
     signal_free_bytestring_array(array)
     return result
 }
 
-internal func invokeFnReturningStringArray(fn: (UnsafeMutablePointer<SignalStringArray>?) -> SignalFfiErrorRef?) throws -> [String] {
-    return try invokeFnReturningSomeBytestringArray(fn: fn) {
-        String(decoding: $0, as: Unicode.UTF8.self)
-    }
-}
-
-internal func invokeFnReturningBytestringArray(fn: (UnsafeMutablePointer<SignalBytestringArray>?) -> SignalFfiErrorRef?) throws -> [[UInt8]] {
-    return try invokeFnReturningSomeBytestringArray(fn: fn) {
-        Array($0)
-    }
-}
-
 internal func invokeFnReturningArray(fn: (UnsafeMutablePointer<SignalOwnedBuffer>?) -> SignalFfiErrorRef?) throws -> [UInt8] {
     var output = SignalOwnedBuffer()
     try checkError(fn(&output))
@@ -212,6 +208,22 @@ internal func rethrowCallbackErrors<Store, Result>(_ store: Store, _ body: (Unsa
     }
 }
 
+/// Converts an optional timeout to the millisecond deadline expected by operations that support
+/// one, where `0` means "no deadline".
+///
+/// Positive timeouts are rounded up, so that a very short timeout doesn't turn into no timeout.
+/// Timeouts too long to represent saturate instead of trapping.
+internal func deadlineMillis(_ timeout: TimeInterval?) -> UInt64 {
+    guard let timeout, timeout > 0 else {
+        return 0
+    }
+    let millis = (timeout * 1000).rounded(.up)
+    guard millis < Double(UInt64.max) else {
+        return UInt64.max
+    }
+    return UInt64(millis)
+}
+
 extension Collection {
     public func split(at index: Self.Index) -> (Self.SubSequence, Self.SubSequence) {
         (self.prefix(upTo: index), self.suffix(from: index))
@@ -6,6 +6,26 @@
 import Foundation
 import SignalFfi
 
+/// How two consecutive ``SignalMessage``s from the same peer relate to each other.
+///
+/// See ``SignalMessage/ratchetContinuity(after:)``.
+public enum RatchetContinuity: UInt8 {
+    // This must match the Rust version of the enum.
+    /// The later message continues the earlier message's chain.
+    case sameChain = 0
+    /// The later message starts a new chain after the earlier message's chain.
+    case nextChain = 1
+    /// The later message is on the same chain but doesn't come after the earlier one.
+    case reordered = 2
+    /// The later message starts a new chain that doesn't account for the earlier message.
+    case discontinuous = 3
+
+    /// Whether this is worth flagging for investigation.
+    public var isSuspicious: Bool {
+        return self == .discontinuous
+    }
+}
+
 public class SignalMessage: NativeHandleOwner {
     override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
         return signal_message_destroy(handle)
@@ -69,6 +89,35 @@ public class SignalMessage: NativeHandleOwner {
         }
     }
 
+    public var previousCounter: UInt32 {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningInteger {
+                    signal_message_get_previous_counter($0, nativeHandle)
+                }
+            }
+        }
+    }
+
+    /// Checks whether this message plausibly follows `earlier` in the same session.
+    ///
+    /// `earlier` must be the last message received from the same peer before this one. Only the
+    /// unauthenticated ratchet metadata is examined, so this is a diagnostic for investigating
+    /// suspected session tampering, not a check for whether to accept a message.
+    public func ratchetContinuity(after earlier: SignalMessage) -> RatchetContinuity {
+        return withNativeHandles(self, earlier) { laterHandle, earlierHandle in
+            failOnError {
+                let rawValue = try invokeFnReturningInteger {
+                    signal_message_check_ratchet_continuity($0, laterHandle, earlierHandle)
+                }
+                guard let continuity = RatchetContinuity(rawValue: rawValue) else {
+                    throw SignalError.internalError("Invalid RatchetContinuity \(rawValue)")
+                }
+                return continuity
+            }
+        }
+    }
+
     public func verifyMac<Bytes: ContiguousBytes>(
         sender: PublicKey,
         receiver: PublicKey,
@@ -0,0 +1,41 @@
+//
+// Copyright 2026 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+import SignalFfi
+
+/// Which recipients have been sent the sender key currently in use for a sender and distribution ID.
+///
+/// Stores only need to persist these opaque records; they are maintained by
+/// ``markSenderKeyDistributed(from:distributionId:to:store:context:)`` and consulted by
+/// ``senderKeyRecipientsNeedingDistribution(from:distributionId:among:store:context:)``.
+public class SenderKeyDistributionRecord: ClonableHandleOwner {
+    override internal class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        return signal_sender_key_distribution_record_destroy(handle)
+    }
+
+    override internal class func cloneNativeHandle(_ newHandle: inout OpaquePointer?, currentHandle: OpaquePointer?) -> SignalFfiErrorRef? {
+        return signal_sender_key_distribution_record_clone(&newHandle, currentHandle)
+    }
+
+    public convenience init<Bytes: ContiguousBytes>(bytes: Bytes) throws {
+        let handle: OpaquePointer? = try bytes.withUnsafeBorrowedBuffer {
+            var result: OpaquePointer?
+            try checkError(signal_sender_key_distribution_record_deserialize(&result, $0))
+            return result
+        }
+        self.init(owned: handle!)
+    }
+
+    public func serialize() -> [UInt8] {
+        return withNativeHandle { nativeHandle in
+            failOnError {
+                try invokeFnReturningArray {
+                    signal_sender_key_distribution_record_serialize($0, nativeHandle)
+                }
+            }
+        }
+    }
+}
@@ -24,6 +24,20 @@ public class SessionRecord: ClonableHandleOwner {
         self.init(owned: handle!)
     }
 
+    /// Rewrites a serialized record with a version header, in the newest format libsignal reads.
+    ///
+    /// Records with or without a header can always be deserialized directly. `serialize()` still
+    /// writes records without one, so that older versions of libsignal can read them; this is the
+    /// opt-in for apps that no longer need to support those versions. Throws if the record is
+    /// malformed or was written by a newer version of libsignal.
+    public static func migrateToCurrent<Bytes: ContiguousBytes>(_ bytes: Bytes) throws -> [UInt8] {
+        return try bytes.withUnsafeBorrowedBuffer { buffer in
+            try invokeFnReturningArray {
+                signal_session_record_migrate_to_current($0, buffer)
+            }
+        }
+    }
+
     public func serialize() -> [UInt8] {
         return self.withNativeHandle { nativeHandle in
             failOnError {
@@ -67,4 +81,44 @@ public class SessionRecord: ClonableHandleOwner {
         }
         return result
     }
+
+    /// Summarizes the activity on the current session, for security screens and diagnostics.
+    ///
+    /// Throws if there is no current session.
+    public func stats() throws -> SessionStats {
+        return try self.withNativeHandle { nativeHandle in
+            let messagesEncrypted = try invokeFnReturningInteger {
+                signal_session_record_get_messages_encrypted($0, nativeHandle)
+            }
+            let messagesDecrypted = try invokeFnReturningInteger {
+                signal_session_record_get_messages_decrypted($0, nativeHandle)
+            }
+            let lastRatchetStepMillis = try invokeFnReturningInteger {
+                signal_session_record_get_last_ratchet_step_time($0, nativeHandle)
+            }
+            let isPostQuantum = try invokeFnReturningBool {
+                signal_session_record_is_post_quantum($0, nativeHandle)
+            }
+            return SessionStats(
+                messagesEncrypted: messagesEncrypted,
+                messagesDecrypted: messagesDecrypted,
+                lastRatchetStep: lastRatchetStepMillis == 0 ? nil : Date(timeIntervalSince1970: TimeInterval(lastRatchetStepMillis) / 1000),
+                isPostQuantum: isPostQuantum
+            )
+        }
+    }
+}
+
+/// A summary of the activity on a session.
+///
+/// Counts only cover the current session, not any archived ones.
+public struct SessionStats: Equatable, Sendable {
+    /// Messages encrypted on this session.
+    public var messagesEncrypted: UInt32
+    /// Messages successfully decrypted on this session.
+    public var messagesDecrypted: UInt32
+    /// When this device first sent a message using its current ratchet key, if it has sent one.
+    public var lastRatchetStep: Date?
+    /// Whether the session was established with a post-quantum (PQXDH) key agreement.
+    public var isPostQuantum: Bool
 }
@@ -24,6 +24,17 @@ public class SignedPreKeyRecord: ClonableHandleOwner {
         self.init(owned: handle!)
     }
 
+    /// Rewrites a serialized record with a version header, in the newest format libsignal reads.
+    ///
+    /// See ``SessionRecord/migrateToCurrent(_:)``.
+    public static func migrateToCurrent<Bytes: ContiguousBytes>(_ bytes: Bytes) throws -> [UInt8] {
+        return try bytes.withUnsafeBorrowedBuffer { buffer in
+            try invokeFnReturningArray {
+                signal_signed_pre_key_record_migrate_to_current($0, buffer)
+            }
+        }
+    }
+
     public convenience init<Bytes: ContiguousBytes>(
         id: UInt32,
         timestamp: UInt64,
@@ -17,6 +17,19 @@ public class CallLinkSecretParams: ByteArray, @unchecked Sendable {
         }
     }
 
+    /// Derives the room ID the calling server uses for the call link with `rootKey`.
+    ///
+    /// This matches the derivation RingRTC uses for `CallLinkRootKey`.
+    public static func deriveRoomId<RootKey: ContiguousBytes>(_ rootKey: RootKey) -> [UInt8] {
+        return failOnError {
+            try rootKey.withUnsafeBorrowedBuffer { rootKey in
+                try invokeFnReturningFixedLengthArray {
+                    signal_call_link_secret_params_derive_room_id($0, rootKey)
+                }
+            }
+        }
+    }
+
     public required init(contents: [UInt8]) throws {
         try super.init(contents, checkValid: signal_call_link_secret_params_check_valid_contents)
     }
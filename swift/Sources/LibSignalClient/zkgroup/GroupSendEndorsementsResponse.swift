@@ -79,19 +79,23 @@ public class GroupSendEndorsementsResponse: ByteArray, @unchecked Sendable {
     ///
     /// Note that the `receive` operation is provided for both ``ServiceId``s and
     /// ``UuidCiphertext``s. If you already have the ciphertexts for the group members available,
-    /// ``receive(groupMembers:localUser:now:serverParams:)`` should be faster; if you don't, this
+    /// ``receive(groupMembers:localUser:now:serverParams:timeout:)`` should be faster; if you don't, this
     /// method is faster than generating the ciphertexts and throwing them away afterwards.
     ///
     /// `localUser` should be included in `groupMembers`.
     ///
+    /// If `timeout` is provided, the operation is abandoned if it is still running at the next
+    /// opportunity after that much time has passed.
+    ///
     /// - Throws: ``SignalError/verificationFailed(_:)`` if the endorsements are not valid for any
-    ///   reason
+    ///   reason, or ``SignalError/deadlineExceeded(_:)`` if the timeout passes first
     public func receive(
         groupMembers: some Collection<ServiceId>,
         localUser: Aci,
         now: Date = Date(),
         groupParams: GroupSecretParams,
-        serverParams: ServerPublicParams
+        serverParams: ServerPublicParams,
+        timeout: TimeInterval? = nil
     ) throws -> ReceivedEndorsements {
         let rawEndorsements = try withUnsafeBorrowedBuffer { response in
             try ServiceId.concatenatedFixedWidthBinary(groupMembers).withUnsafeBorrowedBuffer { groupMembers in
@@ -99,7 +103,7 @@ public class GroupSendEndorsementsResponse: ByteArray, @unchecked Sendable {
                     try groupParams.withUnsafePointerToSerialized { groupParams in
                         try serverParams.withNativeHandle { serverParams in
                             try invokeFnReturningBytestringArray {
-                                signal_group_send_endorsements_response_receive_and_combine_with_service_ids($0, response, groupMembers, localUser, UInt64(now.timeIntervalSince1970), groupParams, serverParams)
+                                signal_group_send_endorsements_response_receive_and_combine_with_service_ids($0, response, groupMembers, localUser, UInt64(now.timeIntervalSince1970), groupParams, serverParams, deadlineMillis(timeout))
                             }
                         }
                     }
@@ -119,25 +123,28 @@ public class GroupSendEndorsementsResponse: ByteArray, @unchecked Sendable {
     /// Note that the `receive` operation is provided for both ``ServiceId``s and
     /// ``UuidCiphertext``s. If you already have the ciphertexts for the group members available,
     /// this method should be faster; if you don't,
-    /// ``receive(groupMembers:localUser:now:groupParams:serverParams:)`` is faster than generating
+    /// ``receive(groupMembers:localUser:now:groupParams:serverParams:timeout:)`` is faster than generating
     /// the ciphertexts and throwing them away afterwards.
     ///
     /// `localUser` should be included in `groupMembers`.
     ///
+    /// `timeout` behaves as in ``receive(groupMembers:localUser:now:groupParams:serverParams:timeout:)``.
+    ///
     /// - Throws: ``SignalError/verificationFailed(_:)`` if the endorsements are not valid for any
-    ///   reason
+    ///   reason, or ``SignalError/deadlineExceeded(_:)`` if the timeout passes first
     public func receive(
         groupMembers: some Sequence<UuidCiphertext>,
         localUser: UuidCiphertext,
         now: Date = Date(),
-        serverParams: ServerPublicParams
+        serverParams: ServerPublicParams,
+        timeout: TimeInterval? = nil
     ) throws -> ReceivedEndorsements {
         let rawEndorsements = try withUnsafeBorrowedBuffer { response in
             try groupMembers.flatMap { $0.serialize() }.withUnsafeBorrowedBuffer { groupMembers in
                 try localUser.withUnsafeBorrowedBuffer { localUser in
                     try serverParams.withNativeHandle { serverParams in
                         try invokeFnReturningBytestringArray {
-                            signal_group_send_endorsements_response_receive_and_combine_with_ciphertexts($0, response, groupMembers, localUser, UInt64(now.timeIntervalSince1970), serverParams)
+                            signal_group_send_endorsements_response_receive_and_combine_with_ciphertexts($0, response, groupMembers, localUser, UInt64(now.timeIntervalSince1970), serverParams, deadlineMillis(timeout))
                         }
                     }
                 }
@@ -160,6 +160,7 @@ typedef enum {
   SignalErrorCodeInvalidType = 6,
   SignalErrorCodeInvalidUtf8String = 7,
   SignalErrorCodeCancelled = 8,
+  SignalErrorCodeDeadlineExceeded = 9,
   SignalErrorCodeProtobufError = 10,
   SignalErrorCodeLegacyCiphertextVersion = 21,
   SignalErrorCodeUnknownCiphertextVersion = 22,
@@ -205,6 +206,8 @@ typedef enum {
   SignalErrorCodeConnectionFailed = 148,
   SignalErrorCodeChatServiceInactive = 149,
   SignalErrorCodeChatServiceIntentionallyDisconnected = 150,
+  SignalErrorCodeRequestUnauthorized = 151,
+  SignalErrorCodeNotFound = 152,
   SignalErrorCodeSvrDataMissing = 160,
   SignalErrorCodeSvrRestoreFailed = 161,
   SignalErrorCodeSvrRotationMachineTooManySteps = 162,
@@ -224,10 +227,26 @@ typedef struct SignalAes256GcmEncryption SignalAes256GcmEncryption;
 
 typedef struct SignalAes256GcmSiv SignalAes256GcmSiv;
 
+typedef struct SignalAsyncInputStreamCompletion SignalAsyncInputStreamCompletion;
+
+typedef struct SignalBackupPaddingPolicy SignalBackupPaddingPolicy;
+
+typedef struct SignalCdsiConnectionPool SignalCdsiConnectionPool;
+
+/**
+ * The changes found by comparing a lookup response with a client's existing contacts.
+ */
+typedef struct SignalCdsiContactChanges SignalCdsiContactChanges;
+
 typedef struct SignalCdsiLookup SignalCdsiLookup;
 
 typedef struct SignalChatAuthChatService SignalChatAuthChatService;
 
+/**
+ * A single delivery to a [`ChatListener`], handed to a [`ChatListenerExecutor`].
+ */
+typedef struct SignalChatListenerTask SignalChatListenerTask;
+
 typedef struct SignalChatUnauthChatService SignalChatUnauthChatService;
 
 typedef struct SignalCiphertextMessage SignalCiphertextMessage;
@@ -236,6 +255,8 @@ typedef struct SignalConnectionManager SignalConnectionManager;
 
 typedef struct SignalDecryptionErrorMessage SignalDecryptionErrorMessage;
 
+typedef struct SignalDeviceTransferSession SignalDeviceTransferSession;
+
 typedef struct SignalFingerprint SignalFingerprint;
 
 typedef struct SignalHsmEnclaveClient SignalHsmEnclaveClient;
@@ -248,6 +269,8 @@ typedef struct SignalKeyPair SignalKeyPair;
 
 typedef struct SignalKeySecret SignalKeySecret;
 
+typedef struct SignalKeyTransparencySearchCache SignalKeyTransparencySearchCache;
+
 typedef struct SignalKyberPreKeyRecord SignalKyberPreKeyRecord;
 
 typedef struct SignalLookupRequest SignalLookupRequest;
@@ -256,6 +279,18 @@ typedef struct SignalMessageBackupKey SignalMessageBackupKey;
 
 typedef struct SignalMessageBackupValidationOutcome SignalMessageBackupValidationOutcome;
 
+#if defined(SIGNAL_MEDIA_SUPPORTED)
+typedef struct SignalMp4SanitizerOptions SignalMp4SanitizerOptions;
+#endif
+
+/**
+ * A [`signal_crypto::NonceSequence`] that can be shared between threads.
+ *
+ * The lock is held while the app's [`NonceStore`] runs, so the store must not use the same
+ * sequence.
+ */
+typedef struct SignalNonceSequence SignalNonceSequence;
+
 typedef struct SignalPinHash SignalPinHash;
 
 typedef struct SignalPlaintextContent SignalPlaintextContent;
@@ -275,14 +310,20 @@ typedef struct SignalProtocolAddress SignalProtocolAddress;
 
 typedef struct SignalPublicKey SignalPublicKey;
 
+typedef struct SignalReceiptBatch SignalReceiptBatch;
+
 #if defined(SIGNAL_MEDIA_SUPPORTED)
 typedef struct SignalSanitizedMetadata SignalSanitizedMetadata;
 #endif
 
+typedef struct SignalSealedSenderDecryptionResult SignalSealedSenderDecryptionResult;
+
 typedef struct SignalSenderCertificate SignalSenderCertificate;
 
 typedef struct SignalSenderKeyDistributionMessage SignalSenderKeyDistributionMessage;
 
+typedef struct SignalSenderKeyDistributionRecord SignalSenderKeyDistributionRecord;
+
 typedef struct SignalSenderKeyMessage SignalSenderKeyMessage;
 
 typedef struct SignalSenderKeyRecord SignalSenderKeyRecord;
@@ -316,6 +357,13 @@ typedef struct SignalMessage SignalMessage;
 
 typedef struct SignalSignedPreKeyRecord SignalSignedPreKeyRecord;
 
+/**
+ * The inputs to a storage service sync, collected one record at a time.
+ */
+typedef struct SignalStorageSync SignalStorageSync;
+
+typedef struct SignalStorageSyncPlan SignalStorageSyncPlan;
+
 typedef struct SignalTokioAsyncContext SignalTokioAsyncContext;
 
 typedef struct SignalUnidentifiedSenderMessageContent SignalUnidentifiedSenderMessageContent;
@@ -339,6 +387,11 @@ typedef struct {
   size_t length;
 } SignalOwnedBufferOfCStringPtr;
 
+/**
+ * An array of Rust-owned C strings, to be freed with `signal_free_list_of_strings`.
+ */
+typedef SignalOwnedBufferOfCStringPtr SignalStringArray;
+
 typedef struct {
   /**
    * Telephone number, as an unformatted e164.
@@ -359,6 +412,28 @@ typedef struct {
   size_t length;
 } SignalOwnedBufferOfFfiCdsiLookupResponseEntry;
 
+typedef struct {
+  /**
+   * The frame the finding applies to, or -1 if it doesn't apply to a single frame.
+   */
+  int64_t frame_index;
+  const char *field_path;
+  uint8_t kind;
+  uint8_t severity;
+  const char *message;
+} SignalFfiMessageBackupValidationFinding;
+
+/**
+ * A representation of a array allocated on the Rust heap for use in C code.
+ */
+typedef struct {
+  SignalFfiMessageBackupValidationFinding *base;
+  /**
+   * The number of elements in the buffer (not necessarily the number of bytes).
+   */
+  size_t length;
+} SignalOwnedBufferOfFfiMessageBackupValidationFinding;
+
 /**
  * A representation of a array allocated on the Rust heap for use in C code.
  */
@@ -386,8 +461,6 @@ typedef struct {
   SignalOwnedBufferOfusize lengths;
 } SignalBytestringArray;
 
-typedef SignalBytestringArray SignalStringArray;
-
 typedef struct {
   const unsigned char *base;
   size_t length;
@@ -491,6 +564,16 @@ typedef struct {
   SignalMarkKyberPreKeyUsed mark_kyber_pre_key_used;
 } SignalKyberPreKeyStore;
 
+typedef int (*SignalContainsReplay)(void *store_ctx, const SignalProtocolAddress *address, const SignalPublicKey *sender_ratchet_key, uint32_t counter);
+
+typedef int (*SignalInsertReplay)(void *store_ctx, const SignalProtocolAddress *address, const SignalPublicKey *sender_ratchet_key, uint32_t counter);
+
+typedef struct {
+  void *ctx;
+  SignalContainsReplay contains;
+  SignalInsertReplay insert;
+} SignalReplayCache;
+
 typedef struct {
   const SignalProtocolAddress *const *base;
   size_t length;
@@ -505,10 +588,16 @@ typedef int (*SignalLoadSenderKey)(void *store_ctx, SignalSenderKeyRecord**, con
 
 typedef int (*SignalStoreSenderKey)(void *store_ctx, const SignalProtocolAddress*, const uint8_t (*distribution_id)[16], const SignalSenderKeyRecord*);
 
+typedef int (*SignalLoadSenderKeyDistribution)(void *store_ctx, SignalSenderKeyDistributionRecord**, const SignalProtocolAddress*, const uint8_t (*distribution_id)[16]);
+
+typedef int (*SignalStoreSenderKeyDistribution)(void *store_ctx, const SignalProtocolAddress*, const uint8_t (*distribution_id)[16], const SignalSenderKeyDistributionRecord*);
+
 typedef struct {
   void *ctx;
   SignalLoadSenderKey load_sender_key;
   SignalStoreSenderKey store_sender_key;
+  SignalLoadSenderKeyDistribution load_sender_key_distribution;
+  SignalStoreSenderKeyDistribution store_sender_key_distribution;
 } SignalSenderKeyStore;
 
 typedef struct {
@@ -630,6 +719,21 @@ typedef struct {
   SignalCancellationId cancellation_id;
 } SignalCPromiseFfiChatResponse;
 
+/**
+ * A C callback used to report the results of Rust futures.
+ *
+ * cbindgen will produce independent C types like `SignalCPromisei32` and
+ * `SignalCPromiseProtocolAddress`.
+ *
+ * This derives Copy because it behaves like a C type; nevertheless, a promise should still only be
+ * completed once.
+ */
+typedef struct {
+  void (*complete)(SignalFfiError *error, SignalSenderCertificate *const *result, const void *context);
+  const void *context;
+  SignalCancellationId cancellation_id;
+} SignalCPromiseSenderCertificate;
+
 typedef struct {
   SignalFfiChatResponse response;
   SignalFfiChatServiceDebugInfo debug_info;
@@ -650,6 +754,60 @@ typedef struct {
   SignalCancellationId cancellation_id;
 } SignalCPromiseFfiResponseAndDebugInfo;
 
+typedef struct {
+  uint32_t acks_flushed;
+  uint32_t acks_unflushed;
+  uint32_t requests_flushed;
+  uint32_t requests_unflushed;
+} SignalFfiBackgroundFlushReport;
+
+/**
+ * A C callback used to report the results of Rust futures.
+ *
+ * cbindgen will produce independent C types like `SignalCPromisei32` and
+ * `SignalCPromiseProtocolAddress`.
+ *
+ * This derives Copy because it behaves like a C type; nevertheless, a promise should still only be
+ * completed once.
+ */
+typedef struct {
+  void (*complete)(SignalFfiError *error, const SignalFfiBackgroundFlushReport *result, const void *context);
+  const void *context;
+  SignalCancellationId cancellation_id;
+} SignalCPromiseFfiBackgroundFlushReport;
+
+/**
+ * A C callback used to report the results of Rust futures.
+ *
+ * cbindgen will produce independent C types like `SignalCPromisei32` and
+ * `SignalCPromiseProtocolAddress`.
+ *
+ * This derives Copy because it behaves like a C type; nevertheless, a promise should still only be
+ * completed once.
+ */
+typedef struct {
+  void (*complete)(SignalFfiError *error, SignalMessageBackupValidationOutcome *const *result, const void *context);
+  const void *context;
+  SignalCancellationId cancellation_id;
+} SignalCPromiseMessageBackupValidationOutcome;
+
+#if defined(SIGNAL_MEDIA_SUPPORTED)
+/**
+ * A C callback used to report the results of Rust futures.
+ *
+ * cbindgen will produce independent C types like `SignalCPromisei32` and
+ * `SignalCPromiseProtocolAddress`.
+ *
+ * This derives Copy because it behaves like a C type; nevertheless, a promise should still only be
+ * completed once.
+ */
+typedef struct {
+  void (*complete)(SignalFfiError *error, SignalSanitizedMetadata *const *result, const void *context);
+  const void *context;
+  SignalCancellationId cancellation_id;
+} SignalCPromiseSanitizedMetadata;
+#endif
+
 typedef void (*SignalReceivedIncomingMessage)(void *ctx, SignalOwnedBuffer envelope, uint64_t timestamp_millis, SignalServerMessageAck *cleanup);
 
 typedef void (*SignalReceivedQueueEmpty)(void *ctx);
@@ -658,12 +816,19 @@ typedef void (*SignalConnectionInterrupted)(void *ctx, SignalFfiError *error);
 
 typedef void (*SignalDestroyChatListener)(void *ctx);
 
+typedef void (*SignalExecuteChatListenerTask)(void *ctx, SignalChatListenerTask *task);
+
 /**
  * Callbacks for [`ChatListener`].
  *
  * Callbacks will be serialized (i.e. two calls will not come in at the same time), but may not
  * always happen on the same thread. Calls should be responded to promptly to avoid blocking later
  * messages.
+ *
+ * If `execute` is provided, each callback is instead wrapped in a task and passed to `execute`,
+ * which should arrange for it to be run (e.g. on a serial queue) and then destroyed. The next task
+ * won't be submitted until the previous one has run. Destroying a task without running it stops
+ * delivery to this listener.
  */
 typedef struct {
   void *ctx;
@@ -671,6 +836,7 @@ typedef struct {
   SignalReceivedQueueEmpty received_queue_empty;
   SignalConnectionInterrupted connection_interrupted;
   SignalDestroyChatListener destroy;
+  SignalExecuteChatListenerTask execute;
 } SignalFfiChatListenerStruct;
 
 typedef SignalFfiChatListenerStruct SignalFfiMakeChatListenerStruct;
@@ -687,6 +853,34 @@ typedef struct {
 
 typedef SignalInputStream SignalSyncInputStream;
 
+typedef int (*SignalReserveNonces)(void *ctx, uint64_t limit);
+
+typedef struct {
+  void *ctx;
+  SignalReserveNonces reserve_up_to;
+} SignalNonceStore;
+
+typedef void (*SignalAsyncRead)(void *ctx, size_t amount, SignalAsyncInputStreamCompletion *completion);
+
+typedef void (*SignalAsyncSkip)(void *ctx, uint64_t amount, SignalAsyncInputStreamCompletion *completion);
+
+typedef void (*SignalDestroyAsyncInputStream)(void *ctx);
+
+/**
+ * Callbacks for [`AsyncInputStream`].
+ *
+ * `read` and `skip` must return promptly, and report their results later by completing the
+ * given completion (with `signal_async_input_stream_completion_complete` or
+ * `signal_async_input_stream_completion_fail`) and then destroying it. Rust calls `destroy` once
+ * it no longer needs the stream.
+ */
+typedef struct {
+  void *ctx;
+  SignalAsyncRead read;
+  SignalAsyncSkip skip;
+  SignalDestroyAsyncInputStream destroy;
+} SignalAsyncInputStream;
+
 typedef uint8_t SignalRandomnessBytes[SignalRANDOMNESS_LEN];
 
 void signal_print_ptr(const void *p);
@@ -699,6 +893,8 @@ void signal_free_list_of_strings(SignalOwnedBufferOfCStringPtr buffer);
 
 void signal_free_lookup_response_entry_list(SignalOwnedBufferOfFfiCdsiLookupResponseEntry buffer);
 
+void signal_free_message_backup_validation_finding_list(SignalOwnedBufferOfFfiMessageBackupValidationFinding buffer);
+
 void signal_free_bytestring_array(SignalBytestringArray array);
 
 SignalFfiError *signal_error_get_message(const SignalFfiError *err, const char **out);
@@ -711,16 +907,28 @@ uint32_t signal_error_get_type(const SignalFfiError *err);
 
 SignalFfiError *signal_error_get_retry_after_seconds(const SignalFfiError *err, uint32_t *out);
 
+SignalFfiError *signal_error_get_rate_limit_scope(const SignalFfiError *err, uint8_t *out);
+
+SignalFfiError *signal_error_get_rate_limit_reason(const SignalFfiError *err, const char **out);
+
 SignalFfiError *signal_error_get_tries_remaining(const SignalFfiError *err, uint32_t *out);
 
 SignalFfiError *signal_error_get_unknown_fields(const SignalFfiError *err, SignalStringArray *out);
 
+SignalFfiError *signal_error_get_disconnect_close_code(const SignalFfiError *err, uint16_t *out);
+
+SignalFfiError *signal_error_get_disconnect_reason(const SignalFfiError *err, const char **out);
+
+SignalFfiError *signal_error_get_disconnect_alerts(const SignalFfiError *err, SignalStringArray *out);
+
 void signal_error_free(SignalFfiError *err);
 
 SignalFfiError *signal_identitykeypair_deserialize(SignalPrivateKey **private_key, SignalPublicKey **public_key, SignalBorrowedBuffer input);
 
 SignalFfiError *signal_sealed_session_cipher_decrypt(SignalOwnedBuffer *out, const char **sender_e164, const char **sender_uuid, uint32_t *sender_device_id, SignalBorrowedBuffer ctext, const SignalPublicKey *trust_root, uint64_t timestamp, const char *local_e164, const char *local_uuid, unsigned int local_device_id, const SignalSessionStore *session_store, const SignalIdentityKeyStore *identity_store, const SignalPreKeyStore *prekey_store, const SignalSignedPreKeyStore *signed_prekey_store);
 
+SignalFfiError *signal_sealed_session_cipher_decrypt_with_replay_cache(SignalOwnedBuffer *out, const char **sender_e164, const char **sender_uuid, uint32_t *sender_device_id, SignalBorrowedBuffer ctext, const SignalPublicKey *trust_root, uint64_t timestamp, const char *local_e164, const char *local_uuid, unsigned int local_device_id, const SignalSessionStore *session_store, const SignalIdentityKeyStore *identity_store, const SignalPreKeyStore *prekey_store, const SignalSignedPreKeyStore *signed_prekey_store, const SignalReplayCache *replay_cache);
+
 bool signal_init_logger(SignalLogLevel max_level, SignalFfiLogger logger);
 
 SignalFfiError *signal_aes256_gcm_siv_destroy(SignalAes256GcmSiv *p);
@@ -731,16 +939,26 @@ SignalFfiError *signal_aes256_gcm_encryption_destroy(SignalAes256GcmEncryption *
 
 SignalFfiError *signal_aes256_gcm_decryption_destroy(SignalAes256GcmDecryption *p);
 
+SignalFfiError *signal_nonce_sequence_destroy(SignalNonceSequence *p);
+
 SignalFfiError *signal_aes256_ctr32_new(SignalAes256Ctr32 **out, SignalBorrowedBuffer key, SignalBorrowedBuffer nonce, uint32_t initial_ctr);
 
 SignalFfiError *signal_aes256_ctr32_process(SignalAes256Ctr32 *ctr, SignalBorrowedMutableBuffer data, uint32_t offset, uint32_t length);
 
 SignalFfiError *signal_aes256_gcm_encryption_new(SignalAes256GcmEncryption **out, SignalBorrowedBuffer key, SignalBorrowedBuffer nonce, SignalBorrowedBuffer associated_data);
 
+SignalFfiError *signal_aes256_gcm_encryption_new_with_nonce_sequence(SignalAes256GcmEncryption **out, const SignalNonceSequence *nonces, const SignalNonceStore *nonce_store, SignalBorrowedBuffer associated_data);
+
+SignalFfiError *signal_aes256_gcm_encryption_get_nonce(SignalOwnedBuffer *out, const SignalAes256GcmEncryption *gcm);
+
 SignalFfiError *signal_aes256_gcm_encryption_update(SignalAes256GcmEncryption *gcm, SignalBorrowedMutableBuffer data, uint32_t offset, uint32_t length);
 
 SignalFfiError *signal_aes256_gcm_encryption_compute_tag(SignalOwnedBuffer *out, SignalAes256GcmEncryption *gcm);
 
+SignalFfiError *signal_nonce_sequence_new(SignalNonceSequence **out, SignalBorrowedBuffer key, SignalBorrowedBuffer prefix, uint64_t persisted_limit, uint64_t limit);
+
+SignalFfiError *signal_nonce_sequence_remaining(uint64_t *out, const SignalNonceSequence *nonces);
+
 SignalFfiError *signal_aes256_gcm_decryption_new(SignalAes256GcmDecryption **out, SignalBorrowedBuffer key, SignalBorrowedBuffer nonce, SignalBorrowedBuffer associated_data);
 
 SignalFfiError *signal_aes256_gcm_decryption_update(SignalAes256GcmDecryption *gcm, SignalBorrowedMutableBuffer data, uint32_t offset, uint32_t length);
@@ -799,6 +1017,10 @@ SignalFfiError *signal_sender_key_distribution_message_destroy(SignalSenderKeyDi
 
 SignalFfiError *signal_sender_key_distribution_message_clone(SignalSenderKeyDistributionMessage **new_obj, const SignalSenderKeyDistributionMessage *obj);
 
+SignalFfiError *signal_sender_key_distribution_record_destroy(SignalSenderKeyDistributionRecord *p);
+
+SignalFfiError *signal_sender_key_distribution_record_clone(SignalSenderKeyDistributionRecord **new_obj, const SignalSenderKeyDistributionRecord *obj);
+
 SignalFfiError *signal_sender_key_message_destroy(SignalSenderKeyMessage *p);
 
 SignalFfiError *signal_sender_key_message_clone(SignalSenderKeyMessage **new_obj, const SignalSenderKeyMessage *obj);
@@ -829,6 +1051,8 @@ SignalFfiError *signal_kyber_pre_key_record_clone(SignalKyberPreKeyRecord **new_
 
 SignalFfiError *signal_unidentified_sender_message_content_destroy(SignalUnidentifiedSenderMessageContent *p);
 
+SignalFfiError *signal_sealed_sender_decryption_result_destroy(SignalSealedSenderDecryptionResult *p);
+
 SignalFfiError *signal_kyber_key_pair_destroy(SignalKyberKeyPair *p);
 
 SignalFfiError *signal_kyber_key_pair_clone(SignalKyberKeyPair **new_obj, const SignalKyberKeyPair *obj);
@@ -853,6 +1077,16 @@ SignalFfiError *signal_service_id_parse_from_service_id_binary(SignalServiceIdFi
 
 SignalFfiError *signal_service_id_parse_from_service_id_string(SignalServiceIdFixedWidthBinaryBytes *out, const char *input);
 
+SignalFfiError *signal_service_id_parse_from_service_id_binaries(SignalOwnedBuffer *out, SignalBorrowedSliceOfBuffers input);
+
+SignalFfiError *signal_service_id_service_id_binaries(SignalBytestringArray *out, SignalBorrowedBuffer concatenated);
+
+SignalFfiError *signal_service_id_service_id_strings(SignalStringArray *out, SignalBorrowedBuffer concatenated);
+
+SignalFfiError *signal_e164_normalize(const char **out, const char *raw, uint32_t region_calling_code);
+
+SignalFfiError *signal_e164_is_valid(bool *out, const char *e164);
+
 SignalFfiError *signal_address_new(SignalProtocolAddress **out, const char *name, uint32_t device_id);
 
 SignalFfiError *signal_publickey_deserialize(SignalPublicKey **out, SignalBorrowedBuffer data);
@@ -913,6 +1147,10 @@ SignalFfiError *signal_fingerprint_display_string(const char **out, const Signal
 
 SignalFfiError *signal_fingerprint_compare(bool *out, SignalBorrowedBuffer fprint1, SignalBorrowedBuffer fprint2);
 
+SignalFfiError *signal_identity_digest_compute(SignalOwnedBuffer *out, uint32_t iterations, uint32_t version, SignalBorrowedBuffer stable_identifier, const SignalPublicKey *identity_key);
+
+SignalFfiError *signal_identity_digest_display_string(const char **out, uint32_t version, SignalBorrowedBuffer digest);
+
 SignalFfiError *signal_message_deserialize(SignalMessage **out, SignalBorrowedBuffer data);
 
 SignalFfiError *signal_message_get_body(SignalOwnedBuffer *out, const SignalMessage *obj);
@@ -923,12 +1161,16 @@ SignalFfiError *signal_message_get_counter(uint32_t *out, const SignalMessage *o
 
 SignalFfiError *signal_message_get_message_version(uint32_t *out, const SignalMessage *obj);
 
+SignalFfiError *signal_message_get_previous_counter(uint32_t *out, const SignalMessage *obj);
+
 SignalFfiError *signal_message_new(SignalMessage **out, uint8_t message_version, SignalBorrowedBuffer mac_key, const SignalPublicKey *sender_ratchet_key, uint32_t counter, uint32_t previous_counter, SignalBorrowedBuffer ciphertext, const SignalPublicKey *sender_identity_key, const SignalPublicKey *receiver_identity_key);
 
 SignalFfiError *signal_message_verify_mac(bool *out, const SignalMessage *msg, const SignalPublicKey *sender_identity_key, const SignalPublicKey *receiver_identity_key, SignalBorrowedBuffer mac_key);
 
 SignalFfiError *signal_message_get_sender_ratchet_key(SignalPublicKey **out, const SignalMessage *m);
 
+SignalFfiError *signal_message_check_ratchet_continuity(uint8_t *out, const SignalMessage *later, const SignalMessage *earlier);
+
 SignalFfiError *signal_pre_key_signal_message_new(SignalPreKeySignalMessage **out, uint8_t message_version, uint32_t registration_id, uint32_t pre_key_id, uint32_t signed_pre_key_id, const SignalPublicKey *base_key, const SignalPublicKey *identity_key, const SignalMessage *signal_message);
 
 SignalFfiError *signal_pre_key_signal_message_get_base_key(SignalPublicKey **out, const SignalPreKeySignalMessage *m);
@@ -1041,6 +1283,8 @@ SignalFfiError *signal_signed_pre_key_record_get_public_key(SignalPublicKey **ou
 
 SignalFfiError *signal_signed_pre_key_record_get_private_key(SignalPrivateKey **out, const SignalSignedPreKeyRecord *obj);
 
+SignalFfiError *signal_signed_pre_key_record_migrate_to_current(SignalOwnedBuffer *out, SignalBorrowedBuffer data);
+
 SignalFfiError *signal_kyber_pre_key_record_deserialize(SignalKyberPreKeyRecord **out, SignalBorrowedBuffer data);
 
 SignalFfiError *signal_kyber_pre_key_record_get_signature(SignalOwnedBuffer *out, const SignalKyberPreKeyRecord *obj);
@@ -1057,6 +1301,8 @@ SignalFfiError *signal_kyber_pre_key_record_get_secret_key(SignalKyberSecretKey
 
 SignalFfiError *signal_kyber_pre_key_record_get_key_pair(SignalKyberKeyPair **out, const SignalKyberPreKeyRecord *obj);
 
+SignalFfiError *signal_kyber_pre_key_record_migrate_to_current(SignalOwnedBuffer *out, SignalBorrowedBuffer data);
+
 SignalFfiError *signal_signed_pre_key_record_new(SignalSignedPreKeyRecord **out, uint32_t id, uint64_t timestamp, const SignalPublicKey *pub_key, const SignalPrivateKey *priv_key, SignalBorrowedBuffer signature);
 
 SignalFfiError *signal_kyber_pre_key_record_new(SignalKyberPreKeyRecord **out, uint32_t id, uint64_t timestamp, const SignalKyberKeyPair *key_pair, SignalBorrowedBuffer signature);
@@ -1071,12 +1317,20 @@ SignalFfiError *signal_pre_key_record_get_public_key(SignalPublicKey **out, cons
 
 SignalFfiError *signal_pre_key_record_get_private_key(SignalPrivateKey **out, const SignalPreKeyRecord *obj);
 
+SignalFfiError *signal_pre_key_record_migrate_to_current(SignalOwnedBuffer *out, SignalBorrowedBuffer data);
+
 SignalFfiError *signal_pre_key_record_new(SignalPreKeyRecord **out, uint32_t id, const SignalPublicKey *pub_key, const SignalPrivateKey *priv_key);
 
 SignalFfiError *signal_sender_key_record_deserialize(SignalSenderKeyRecord **out, SignalBorrowedBuffer data);
 
 SignalFfiError *signal_sender_key_record_serialize(SignalOwnedBuffer *out, const SignalSenderKeyRecord *obj);
 
+SignalFfiError *signal_sender_key_record_migrate_to_current(SignalOwnedBuffer *out, SignalBorrowedBuffer data);
+
+SignalFfiError *signal_sender_key_distribution_record_deserialize(SignalSenderKeyDistributionRecord **out, SignalBorrowedBuffer data);
+
+SignalFfiError *signal_sender_key_distribution_record_serialize(SignalOwnedBuffer *out, const SignalSenderKeyDistributionRecord *obj);
+
 SignalFfiError *signal_server_certificate_deserialize(SignalServerCertificate **out, SignalBorrowedBuffer data);
 
 SignalFfiError *signal_server_certificate_get_serialized(SignalOwnedBuffer *out, const SignalServerCertificate *obj);
@@ -1111,6 +1365,8 @@ SignalFfiError *signal_sender_certificate_get_key(SignalPublicKey **out, const S
 
 SignalFfiError *signal_sender_certificate_validate(bool *out, const SignalSenderCertificate *cert, const SignalPublicKey *key, uint64_t time);
 
+SignalFfiError *signal_sender_certificate_needs_refresh(bool *out, const SignalSenderCertificate *cert, uint64_t time, uint32_t refresh_margin_seconds);
+
 SignalFfiError *signal_sender_certificate_get_server_certificate(SignalServerCertificate **out, const SignalSenderCertificate *cert);
 
 SignalFfiError *signal_sender_certificate_new(SignalSenderCertificate **out, const char *sender_uuid, const char *sender_e164, uint32_t sender_device_id, const SignalPublicKey *sender_key, uint64_t expiration, const SignalServerCertificate *signer_cert, const SignalPrivateKey *signer_key);
@@ -1131,6 +1387,8 @@ SignalFfiError *signal_unidentified_sender_message_content_get_content_hint(uint
 
 SignalFfiError *signal_unidentified_sender_message_content_new(SignalUnidentifiedSenderMessageContent **out, const SignalCiphertextMessage *message, const SignalSenderCertificate *sender, uint32_t content_hint, SignalBorrowedBuffer group_id);
 
+SignalFfiError *signal_unidentified_sender_message_content_with_content_hint_and_group_id(SignalUnidentifiedSenderMessageContent **out, const SignalUnidentifiedSenderMessageContent *m, uint32_t content_hint, SignalBorrowedBuffer group_id);
+
 SignalFfiError *signal_ciphertext_message_type(uint8_t *out, const SignalCiphertextMessage *msg);
 
 SignalFfiError *signal_ciphertext_message_serialize(SignalOwnedBuffer *out, const SignalCiphertextMessage *obj);
@@ -1143,6 +1401,14 @@ SignalFfiError *signal_session_record_has_usable_sender_chain(bool *out, const S
 
 SignalFfiError *signal_session_record_current_ratchet_key_matches(bool *out, const SignalSessionRecord *s, const SignalPublicKey *key);
 
+SignalFfiError *signal_session_record_get_messages_encrypted(uint32_t *out, const SignalSessionRecord *s);
+
+SignalFfiError *signal_session_record_get_messages_decrypted(uint32_t *out, const SignalSessionRecord *s);
+
+SignalFfiError *signal_session_record_get_last_ratchet_step_time(uint64_t *out, const SignalSessionRecord *s);
+
+SignalFfiError *signal_session_record_is_post_quantum(bool *out, const SignalSessionRecord *s);
+
 SignalFfiError *signal_session_record_deserialize(SignalSessionRecord **out, SignalBorrowedBuffer data);
 
 SignalFfiError *signal_session_record_serialize(SignalOwnedBuffer *out, const SignalSessionRecord *obj);
@@ -1151,6 +1417,20 @@ SignalFfiError *signal_session_record_get_local_registration_id(uint32_t *out, c
 
 SignalFfiError *signal_session_record_get_remote_registration_id(uint32_t *out, const SignalSessionRecord *obj);
 
+SignalFfiError *signal_session_record_migrate_to_current(SignalOwnedBuffer *out, SignalBorrowedBuffer data);
+
+SignalFfiError *signal_sealed_sender_decryption_result_get_sender_uuid(const char **out, const SignalSealedSenderDecryptionResult *obj);
+
+SignalFfiError *signal_sealed_sender_decryption_result_get_sender_e164(const char **out, const SignalSealedSenderDecryptionResult *obj);
+
+SignalFfiError *signal_sealed_sender_decryption_result_get_device_id(uint32_t *out, const SignalSealedSenderDecryptionResult *obj);
+
+SignalFfiError *signal_sealed_sender_decryption_result_message(SignalOwnedBuffer *out, const SignalSealedSenderDecryptionResult *obj);
+
+SignalFfiError *signal_sealed_sender_decryption_result_get_previous_identity_key(SignalPublicKey **out, const SignalSealedSenderDecryptionResult *obj);
+
+SignalFfiError *signal_sealed_sender_decryption_result_get_current_identity_key(SignalPublicKey **out, const SignalSealedSenderDecryptionResult *obj);
+
 SignalFfiError *signal_process_prekey_bundle(const SignalPreKeyBundle *bundle, const SignalProtocolAddress *protocol_address, const SignalSessionStore *session_store, const SignalIdentityKeyStore *identity_key_store, uint64_t now);
 
 SignalFfiError *signal_encrypt_message(SignalCiphertextMessage **out, SignalBorrowedBuffer ptext, const SignalProtocolAddress *protocol_address, const SignalSessionStore *session_store, const SignalIdentityKeyStore *identity_key_store, uint64_t now);
@@ -1159,28 +1439,72 @@ SignalFfiError *signal_decrypt_message(SignalOwnedBuffer *out, const SignalMessa
 
 SignalFfiError *signal_decrypt_pre_key_message(SignalOwnedBuffer *out, const SignalPreKeySignalMessage *message, const SignalProtocolAddress *protocol_address, const SignalSessionStore *session_store, const SignalIdentityKeyStore *identity_key_store, const SignalPreKeyStore *prekey_store, const SignalSignedPreKeyStore *signed_prekey_store, const SignalKyberPreKeyStore *kyber_prekey_store);
 
+SignalFfiError *signal_decrypt_message_with_replay_cache(SignalOwnedBuffer *out, const SignalMessage *message, const SignalProtocolAddress *protocol_address, const SignalSessionStore *session_store, const SignalIdentityKeyStore *identity_key_store, const SignalReplayCache *replay_cache);
+
+SignalFfiError *signal_decrypt_pre_key_message_with_replay_cache(SignalOwnedBuffer *out, const SignalPreKeySignalMessage *message, const SignalProtocolAddress *protocol_address, const SignalSessionStore *session_store, const SignalIdentityKeyStore *identity_key_store, const SignalPreKeyStore *prekey_store, const SignalSignedPreKeyStore *signed_prekey_store, const SignalKyberPreKeyStore *kyber_prekey_store, const SignalReplayCache *replay_cache);
+
 SignalFfiError *signal_sealed_session_cipher_encrypt(SignalOwnedBuffer *out, const SignalProtocolAddress *destination, const SignalUnidentifiedSenderMessageContent *content, const SignalIdentityKeyStore *identity_key_store);
 
 SignalFfiError *signal_sealed_sender_multi_recipient_encrypt(SignalOwnedBuffer *out, SignalBorrowedSliceOfProtocolAddress recipients, SignalBorrowedSliceOfSessionRecord recipient_sessions, SignalBorrowedBuffer excluded_recipients, const SignalUnidentifiedSenderMessageContent *content, const SignalIdentityKeyStore *identity_key_store);
 
 SignalFfiError *signal_sealed_sender_multi_recipient_message_for_single_recipient(SignalOwnedBuffer *out, SignalBorrowedBuffer encoded_multi_recipient_message);
 
+SignalFfiError *signal_sealed_sender_multi_recipient_message_length(uint32_t *out, uint32_t usmc_length, uint32_t recipient_count, uint32_t device_count, uint32_t excluded_recipient_count);
+
+SignalFfiError *signal_sealed_sender_multi_recipient_received_message_length(uint32_t *out, uint32_t usmc_length);
+
 SignalFfiError *signal_sealed_session_cipher_decrypt_to_usmc(SignalUnidentifiedSenderMessageContent **out, SignalBorrowedBuffer ctext, const SignalIdentityKeyStore *identity_store);
 
+SignalFfiError *signal_sealed_sender_decrypt_message_capturing_identity_change(SignalSealedSenderDecryptionResult **out, SignalBorrowedBuffer message, const SignalPublicKey *trust_root, uint64_t timestamp, const char *local_e164, const char *local_uuid, uint32_t local_device_id, const SignalSessionStore *session_store, const SignalIdentityKeyStore *identity_store, const SignalPreKeyStore *prekey_store, const SignalSignedPreKeyStore *signed_prekey_store, const SignalKyberPreKeyStore *kyber_prekey_store);
+
 SignalFfiError *signal_sender_key_distribution_message_create(SignalSenderKeyDistributionMessage **out, const SignalProtocolAddress *sender, const uint8_t (*distribution_id)[16], const SignalSenderKeyStore *store);
 
 SignalFfiError *signal_process_sender_key_distribution_message(const SignalProtocolAddress *sender, const SignalSenderKeyDistributionMessage *sender_key_distribution_message, const SignalSenderKeyStore *store);
 
+SignalFfiError *signal_sender_key_recipients_needing_distribution(SignalOwnedBuffer *out, const SignalProtocolAddress *sender, const uint8_t (*distribution_id)[16], SignalBorrowedSliceOfProtocolAddress recipients, const SignalSenderKeyStore *store);
+
+SignalFfiError *signal_sender_key_mark_distributed(const SignalProtocolAddress *sender, const uint8_t (*distribution_id)[16], SignalBorrowedSliceOfProtocolAddress recipients, const SignalSenderKeyStore *store);
+
+SignalFfiError *signal_sender_key_invalidate_distribution(const SignalProtocolAddress *sender, const uint8_t (*distribution_id)[16], SignalBorrowedSliceOfProtocolAddress recipients, const SignalSenderKeyStore *store);
+
 SignalFfiError *signal_group_encrypt_message(SignalCiphertextMessage **out, const SignalProtocolAddress *sender, const uint8_t (*distribution_id)[16], SignalBorrowedBuffer message, const SignalSenderKeyStore *store);
 
 SignalFfiError *signal_group_decrypt_message(SignalOwnedBuffer *out, const SignalProtocolAddress *sender, SignalBorrowedBuffer message, const SignalSenderKeyStore *store);
 
+SignalFfiError *signal_store_conformance_check_count(uint32_t *out);
+
+SignalFfiError *signal_store_conformance_check(uint32_t check, const SignalPublicKey *identity_key, const SignalPrivateKey *private_key, uint32_t registration_id, const SignalIdentityKeyStore *identity_store, const SignalPreKeyStore *pre_key_store, const SignalSignedPreKeyStore *signed_pre_key_store, const SignalKyberPreKeyStore *kyber_pre_key_store, const SignalSessionStore *session_store, const SignalSenderKeyStore *sender_key_store);
+
 SignalFfiError *signal_device_transfer_generate_private_key(SignalOwnedBuffer *out);
 
 SignalFfiError *signal_device_transfer_generate_private_key_with_format(SignalOwnedBuffer *out, uint8_t key_format);
 
 SignalFfiError *signal_device_transfer_generate_certificate(SignalOwnedBuffer *out, SignalBorrowedBuffer private_key, const char *name, uint32_t days_to_expire);
 
+SignalFfiError *signal_device_transfer_session_destroy(SignalDeviceTransferSession *p);
+
+SignalFfiError *signal_device_transfer_session_for_old_device(SignalDeviceTransferSession **out, SignalBorrowedBuffer local_certificate, uint64_t payload_length);
+
+SignalFfiError *signal_device_transfer_session_for_new_device(SignalDeviceTransferSession **out, SignalBorrowedBuffer local_certificate);
+
+SignalFfiError *signal_device_transfer_session_receive(SignalOwnedBuffer *out, SignalDeviceTransferSession *session, SignalBorrowedBuffer data);
+
+SignalFfiError *signal_device_transfer_session_take_outgoing(SignalOwnedBuffer *out, SignalDeviceTransferSession *session);
+
+SignalFfiError *signal_device_transfer_session_get_state(uint8_t *out, const SignalDeviceTransferSession *session);
+
+SignalFfiError *signal_device_transfer_session_get_verification_code(const char **out, const SignalDeviceTransferSession *session);
+
+SignalFfiError *signal_device_transfer_session_confirm_verification_code(SignalDeviceTransferSession *session);
+
+SignalFfiError *signal_device_transfer_session_send_payload_chunk(SignalDeviceTransferSession *session, SignalBorrowedBuffer chunk);
+
+SignalFfiError *signal_device_transfer_session_finish(SignalDeviceTransferSession *session);
+
+SignalFfiError *signal_device_transfer_session_get_bytes_transferred(uint64_t *out, const SignalDeviceTransferSession *session);
+
+SignalFfiError *signal_device_transfer_session_get_payload_length(uint64_t *out, const SignalDeviceTransferSession *session);
+
 SignalFfiError *signal_cds2_client_state_new(SignalSgxClientState **out, SignalBorrowedBuffer mrenclave, SignalBorrowedBuffer attestation_msg, uint64_t current_timestamp);
 
 SignalFfiError *signal_hsm_enclave_client_destroy(SignalHsmEnclaveClient *p);
@@ -1193,6 +1517,14 @@ SignalFfiError *signal_hsm_enclave_client_established_send(SignalOwnedBuffer *ou
 
 SignalFfiError *signal_hsm_enclave_client_established_recv(SignalOwnedBuffer *out, SignalHsmEnclaveClient *cli, SignalBorrowedBuffer received_ciphertext);
 
+SignalFfiError *signal_hsm_enclave_client_enable_pipelining(SignalHsmEnclaveClient *cli, uint32_t max_in_flight);
+
+SignalFfiError *signal_hsm_enclave_client_in_flight(uint32_t *out, const SignalHsmEnclaveClient *cli);
+
+SignalFfiError *signal_hsm_enclave_client_last_sent_request_id(uint64_t *out, const SignalHsmEnclaveClient *cli);
+
+SignalFfiError *signal_hsm_enclave_client_last_received_request_id(uint64_t *out, const SignalHsmEnclaveClient *cli);
+
 SignalFfiError *signal_hsm_enclave_client_initial_request(SignalOwnedBuffer *out, const SignalHsmEnclaveClient *obj);
 
 SignalFfiError *signal_sgx_client_state_destroy(SignalSgxClientState *p);
@@ -1337,6 +1669,10 @@ SignalFfiError *signal_profile_key_credential_presentation_get_uuid_ciphertext(u
 
 SignalFfiError *signal_profile_key_credential_presentation_get_profile_key_ciphertext(unsigned char (*out)[SignalPROFILE_KEY_CIPHERTEXT_LEN], SignalBorrowedBuffer presentation_bytes);
 
+SignalFfiError *signal_profile_key_credential_presentation_to_compact(SignalOwnedBuffer *out, SignalBorrowedBuffer presentation_bytes);
+
+SignalFfiError *signal_profile_key_credential_presentation_from_compact(SignalOwnedBuffer *out, SignalBorrowedBuffer compact_bytes);
+
 SignalFfiError *signal_receipt_credential_request_context_get_request(unsigned char (*out)[SignalRECEIPT_CREDENTIAL_REQUEST_LEN], const unsigned char (*request_context)[SignalRECEIPT_CREDENTIAL_REQUEST_CONTEXT_LEN]);
 
 SignalFfiError *signal_receipt_credential_get_receipt_expiration_time(uint64_t *out, const unsigned char (*receipt_credential)[SignalRECEIPT_CREDENTIAL_LEN]);
@@ -1361,6 +1697,8 @@ SignalFfiError *signal_call_link_secret_params_check_valid_contents(SignalBorrow
 
 SignalFfiError *signal_call_link_secret_params_derive_from_root_key(SignalOwnedBuffer *out, SignalBorrowedBuffer root_key);
 
+SignalFfiError *signal_call_link_secret_params_derive_room_id(uint8_t (*out)[32], SignalBorrowedBuffer root_key);
+
 SignalFfiError *signal_call_link_secret_params_get_public_params(SignalOwnedBuffer *out, SignalBorrowedBuffer params_bytes);
 
 SignalFfiError *signal_call_link_secret_params_decrypt_user_id(SignalServiceIdFixedWidthBinaryBytes *out, SignalBorrowedBuffer params_bytes, const unsigned char (*user_id)[SignalUUID_CIPHERTEXT_LEN]);
@@ -1443,9 +1781,9 @@ SignalFfiError *signal_group_send_endorsements_response_issue_deterministic(Sign
 
 SignalFfiError *signal_group_send_endorsements_response_get_expiration(uint64_t *out, SignalBorrowedBuffer response_bytes);
 
-SignalFfiError *signal_group_send_endorsements_response_receive_and_combine_with_service_ids(SignalBytestringArray *out, SignalBorrowedBuffer response_bytes, SignalBorrowedBuffer group_members, const SignalServiceIdFixedWidthBinaryBytes *local_user, uint64_t now, const unsigned char (*group_params)[SignalGROUP_SECRET_PARAMS_LEN], const SignalServerPublicParams *server_params);
+SignalFfiError *signal_group_send_endorsements_response_receive_and_combine_with_service_ids(SignalBytestringArray *out, SignalBorrowedBuffer response_bytes, SignalBorrowedBuffer group_members, const SignalServiceIdFixedWidthBinaryBytes *local_user, uint64_t now, const unsigned char (*group_params)[SignalGROUP_SECRET_PARAMS_LEN], const SignalServerPublicParams *server_params, uint64_t deadline);
 
-SignalFfiError *signal_group_send_endorsements_response_receive_and_combine_with_ciphertexts(SignalBytestringArray *out, SignalBorrowedBuffer response_bytes, SignalBorrowedBuffer concatenated_group_member_ciphertexts, SignalBorrowedBuffer local_user_ciphertext, uint64_t now, const SignalServerPublicParams *server_params);
+SignalFfiError *signal_group_send_endorsements_response_receive_and_combine_with_ciphertexts(SignalBytestringArray *out, SignalBorrowedBuffer response_bytes, SignalBorrowedBuffer concatenated_group_member_ciphertexts, SignalBorrowedBuffer local_user_ciphertext, uint64_t now, const SignalServerPublicParams *server_params, uint64_t deadline);
 
 SignalFfiError *signal_group_send_endorsement_check_valid_contents(SignalBorrowedBuffer bytes);
 
@@ -1463,6 +1801,10 @@ SignalFfiError *signal_group_send_full_token_check_valid_contents(SignalBorrowed
 
 SignalFfiError *signal_group_send_full_token_get_expiration(uint64_t *out, SignalBorrowedBuffer token);
 
+SignalFfiError *signal_group_send_full_token_to_compact(SignalOwnedBuffer *out, SignalBorrowedBuffer token);
+
+SignalFfiError *signal_group_send_full_token_from_compact(SignalOwnedBuffer *out, SignalBorrowedBuffer compact_token);
+
 SignalFfiError *signal_group_send_full_token_verify(SignalBorrowedBuffer token, SignalBorrowedBuffer user_ids, uint64_t now, SignalBorrowedBuffer key_pair);
 
 SignalFfiError *signal_connection_manager_destroy(SignalConnectionManager *p);
@@ -1475,14 +1817,38 @@ SignalFfiError *signal_connection_manager_clear_proxy(const SignalConnectionMana
 
 SignalFfiError *signal_connection_manager_set_censorship_circumvention_enabled(const SignalConnectionManager *connection_manager, bool enabled);
 
+SignalFfiError *signal_connection_manager_set_chat_websocket_limits(const SignalConnectionManager *connection_manager, uint32_t max_frame_size, uint32_t max_message_size, uint32_t max_pending_send_bytes);
+
+SignalFfiError *signal_connection_manager_set_retry_policy(const SignalConnectionManager *connection_manager, uint8_t service, uint32_t initial_delay_millis, uint32_t multiplier_percent, uint32_t jitter_percent, uint32_t max_delay_millis, uint32_t max_attempts);
+
+SignalFfiError *signal_connection_manager_set_certificate_pins(const SignalConnectionManager *connection_manager, uint8_t service, SignalBorrowedBuffer pins);
+
+SignalFfiError *signal_connection_manager_set_route_order(const SignalConnectionManager *connection_manager, SignalBorrowedBuffer order);
+
+SignalFfiError *signal_connection_manager_get_route_order(SignalOwnedBuffer *out, const SignalConnectionManager *connection_manager);
+
+SignalFfiError *signal_connection_manager_get_proxy(const char **out, const SignalConnectionManager *connection_manager);
+
+SignalFfiError *signal_connection_manager_export_route_state(SignalOwnedBuffer *out, const SignalConnectionManager *connection_manager, const uint8_t (*key)[32]);
+
+SignalFfiError *signal_connection_manager_import_route_state(const SignalConnectionManager *connection_manager, const uint8_t (*key)[32], SignalBorrowedBuffer state);
+
 SignalFfiError *signal_connection_manager_on_network_change(const SignalConnectionManager *connection_manager);
 
+SignalFfiError *signal_connection_manager_server_time(uint64_t *out, const SignalConnectionManager *connection_manager);
+
 SignalFfiError *signal_create_otp(const char **out, const char *username, SignalBorrowedBuffer secret);
 
 SignalFfiError *signal_create_otp_from_base64(const char **out, const char *username, const char *secret);
 
 SignalFfiError *signal_svr3_backup(SignalCPromiseOwnedBufferOfc_uchar *promise, const SignalTokioAsyncContext *async_runtime, const SignalConnectionManager *connection_manager, SignalBorrowedBuffer secret, const char *password, uint32_t max_tries, const char *username, const char *enclave_password);
 
+SignalFfiError *signal_svr3_backup_with_distribution(SignalCPromiseOwnedBufferOfc_uchar *promise, const SignalTokioAsyncContext *async_runtime, const SignalConnectionManager *connection_manager, SignalBorrowedBuffer secret, const char *password, uint32_t max_tries, SignalBorrowedBuffer server_ids, uint32_t threshold, const char *username, const char *enclave_password);
+
+SignalFfiError *signal_svr3_get_share_set_server_ids(SignalOwnedBuffer *out, SignalBorrowedBuffer share_set);
+
+SignalFfiError *signal_svr3_get_share_set_threshold(uint32_t *out, SignalBorrowedBuffer share_set);
+
 SignalFfiError *signal_svr3_migrate(SignalCPromiseOwnedBufferOfc_uchar *promise, const SignalTokioAsyncContext *async_runtime, const SignalConnectionManager *connection_manager, SignalBorrowedBuffer secret, const char *password, uint32_t max_tries, const char *username, const char *enclave_password);
 
 SignalFfiError *signal_svr3_restore(SignalCPromiseOwnedBufferOfc_uchar *promise, const SignalTokioAsyncContext *async_runtime, const SignalConnectionManager *connection_manager, const char *password, SignalBorrowedBuffer share_set, const char *username, const char *enclave_password);
@@ -1499,6 +1865,8 @@ SignalFfiError *signal_lookup_request_add_e164(const SignalLookupRequest *reques
 
 SignalFfiError *signal_lookup_request_add_previous_e164(const SignalLookupRequest *request, const char *e164);
 
+SignalFfiError *signal_lookup_request_add_raw_e164s(SignalStringArray *out, const SignalLookupRequest *request, SignalBorrowedSliceOfBuffers raw, uint32_t region_calling_code);
+
 SignalFfiError *signal_lookup_request_set_token(const SignalLookupRequest *request, SignalBorrowedBuffer token);
 
 SignalFfiError *signal_lookup_request_add_aci_and_access_key(const SignalLookupRequest *request, const SignalServiceIdFixedWidthBinaryBytes *aci, SignalBorrowedBuffer access_key);
@@ -1509,10 +1877,30 @@ SignalFfiError *signal_cdsi_lookup_destroy(SignalCdsiLookup *p);
 
 SignalFfiError *signal_cdsi_lookup_new(SignalCPromiseCdsiLookup *promise, const SignalTokioAsyncContext *async_runtime, const SignalConnectionManager *connection_manager, const char *username, const char *password, const SignalLookupRequest *request);
 
+SignalFfiError *signal_cdsi_connection_pool_destroy(SignalCdsiConnectionPool *p);
+
+SignalFfiError *signal_cdsi_connection_pool_new(SignalCdsiConnectionPool **out, uint32_t idle_timeout_millis);
+
+SignalFfiError *signal_cdsi_lookup_new_pooled(SignalCPromiseCdsiLookup *promise, const SignalTokioAsyncContext *async_runtime, const SignalConnectionManager *connection_manager, const char *username, const char *password, const SignalLookupRequest *request, const SignalCdsiConnectionPool *pool, bool prepare_next);
+
 SignalFfiError *signal_cdsi_lookup_token(SignalOwnedBuffer *out, const SignalCdsiLookup *lookup);
 
 SignalFfiError *signal_cdsi_lookup_complete(SignalCPromiseFfiCdsiLookupResponse *promise, const SignalTokioAsyncContext *async_runtime, const SignalCdsiLookup *lookup);
 
+SignalFfiError *signal_cdsi_contact_changes_destroy(SignalCdsiContactChanges *p);
+
+SignalFfiError *signal_lookup_response_reconcile(SignalCdsiContactChanges **out, SignalBorrowedBuffer existing, SignalBorrowedBuffer response);
+
+SignalFfiError *signal_cdsi_contact_changes_count(uint32_t *out, const SignalCdsiContactChanges *changes);
+
+SignalFfiError *signal_cdsi_contact_changes_get_kind(uint8_t *out, const SignalCdsiContactChanges *changes, uint32_t index);
+
+SignalFfiError *signal_cdsi_contact_changes_get_e164(const char **out, const SignalCdsiContactChanges *changes, uint32_t index);
+
+SignalFfiError *signal_cdsi_contact_changes_get_aci(const char **out, const SignalCdsiContactChanges *changes, uint32_t index);
+
+SignalFfiError *signal_cdsi_contact_changes_get_pni(const char **out, const SignalCdsiContactChanges *changes, uint32_t index);
+
 SignalFfiError *signal_auth_chat_destroy(SignalAuthChat *p);
 
 SignalFfiError *signal_unauth_chat_destroy(SignalUnauthChat *p);
@@ -1543,16 +1931,84 @@ SignalFfiError *signal_chat_service_unauth_send_and_debug(SignalCPromiseFfiRespo
 
 SignalFfiError *signal_chat_service_auth_send(SignalCPromiseFfiChatResponse *promise, const SignalTokioAsyncContext *async_runtime, const SignalAuthChat *chat, const SignalHttpRequest *http_request, uint32_t timeout_millis);
 
+SignalFfiError *signal_chat_service_auth_fetch_sender_certificate(SignalCPromiseSenderCertificate *promise, const SignalTokioAsyncContext *async_runtime, const SignalAuthChat *chat, bool include_e164, uint32_t timeout_millis);
+
+SignalFfiError *signal_chat_service_auth_fetch_profile_key_credential(SignalCPromiseOwnedBufferOfc_uchar *promise, const SignalTokioAsyncContext *async_runtime, const SignalAuthChat *chat, const SignalServerPublicParams *server_public_params, const uint8_t (*randomness)[SignalRANDOMNESS_LEN], const SignalServiceIdFixedWidthBinaryBytes *aci, const unsigned char (*profile_key)[SignalPROFILE_KEY_LEN], uint64_t current_time_in_seconds, uint32_t timeout_millis);
+
 SignalFfiError *signal_chat_service_auth_send_and_debug(SignalCPromiseFfiResponseAndDebugInfo *promise, const SignalTokioAsyncContext *async_runtime, const SignalAuthChat *chat, const SignalHttpRequest *http_request, uint32_t timeout_millis);
 
+SignalFfiError *signal_chat_service_prepare_for_background_unauth(SignalCPromiseFfiBackgroundFlushReport *promise, const SignalTokioAsyncContext *async_runtime, const SignalUnauthChat *chat, uint32_t budget_millis);
+
+SignalFfiError *signal_chat_service_prepare_for_background_auth(SignalCPromiseFfiBackgroundFlushReport *promise, const SignalTokioAsyncContext *async_runtime, const SignalAuthChat *chat, uint32_t budget_millis);
+
 SignalFfiError *signal_chat_service_set_listener_auth(const SignalTokioAsyncContext *runtime, const SignalAuthChat *chat, const SignalFfiMakeChatListenerStruct *make_listener);
 
 SignalFfiError *signal_chat_service_set_listener_unauth(const SignalTokioAsyncContext *runtime, const SignalUnauthChat *chat, const SignalFfiMakeChatListenerStruct *make_listener);
 
 SignalFfiError *signal_server_message_ack_destroy(SignalServerMessageAck *p);
 
+SignalFfiError *signal_chat_listener_task_destroy(SignalChatListenerTask *p);
+
+SignalFfiError *signal_chat_listener_task_run(const SignalChatListenerTask *task);
+
 SignalFfiError *signal_server_message_ack_send(SignalCPromisebool *promise, const SignalTokioAsyncContext *async_runtime, const SignalServerMessageAck *ack);
 
+SignalFfiError *signal_key_transparency_search_cache_destroy(SignalKeyTransparencySearchCache *p);
+
+SignalFfiError *signal_key_transparency_search_cache_new(SignalKeyTransparencySearchCache **out, uint32_t ttl_seconds, uint32_t max_entries);
+
+SignalFfiError *signal_chat_service_auth_search_key_transparency(SignalCPromiseOwnedBufferOfc_uchar *promise, const SignalTokioAsyncContext *async_runtime, const SignalAuthChat *chat, const SignalKeyTransparencySearchCache *cache, const uint8_t (*signature_key)[32], const uint8_t (*vrf_key)[32], const uint8_t (*auditor_key)[32], SignalBorrowedBuffer search_key, uint32_t timeout_millis);
+
+SignalFfiError *signal_receipt_batch_destroy(SignalReceiptBatch *p);
+
+SignalFfiError *signal_chat_service_auth_queue_receipt(const SignalAuthChat *chat, const SignalServiceIdFixedWidthBinaryBytes *sender, uint8_t kind, uint64_t message_timestamp);
+
+SignalFfiError *signal_chat_service_auth_next_receipt_batch(SignalReceiptBatch **out, const SignalAuthChat *chat);
+
+SignalFfiError *signal_receipt_batch_get_sender(SignalServiceIdFixedWidthBinaryBytes *out, const SignalReceiptBatch *batch);
+
+SignalFfiError *signal_receipt_batch_get_kind(uint8_t *out, const SignalReceiptBatch *batch);
+
+SignalFfiError *signal_receipt_batch_get_timestamps(SignalOwnedBuffer *out, const SignalReceiptBatch *batch);
+
+SignalFfiError *signal_storage_sync_destroy(SignalStorageSync *p);
+
+SignalFfiError *signal_storage_sync_plan_destroy(SignalStorageSyncPlan *p);
+
+SignalFfiError *signal_storage_sync_new(SignalStorageSync **out, SignalBorrowedBuffer previous_manifest, SignalBorrowedBuffer remote_manifest);
+
+SignalFfiError *signal_storage_sync_add_local_record(const SignalStorageSync *sync, SignalBorrowedBuffer id, SignalBorrowedBuffer key, SignalBorrowedBuffer data);
+
+SignalFfiError *signal_storage_sync_add_local_deletion(const SignalStorageSync *sync, SignalBorrowedBuffer id);
+
+SignalFfiError *signal_storage_sync_add_known_unknown(const SignalStorageSync *sync, SignalBorrowedBuffer id);
+
+SignalFfiError *signal_storage_sync_ids_to_fetch(SignalBytestringArray *out, const SignalStorageSync *sync);
+
+SignalFfiError *signal_storage_sync_add_fetched_record(const SignalStorageSync *sync, SignalBorrowedBuffer id, SignalBorrowedBuffer key, SignalBorrowedBuffer data);
+
+SignalFfiError *signal_storage_sync_add_fetched_unknown(const SignalStorageSync *sync, SignalBorrowedBuffer id);
+
+SignalFfiError *signal_storage_sync_plan(SignalStorageSyncPlan **out, const SignalStorageSync *sync);
+
+SignalFfiError *signal_storage_sync_plan_local_upsert_ids(SignalBytestringArray *out, const SignalStorageSyncPlan *plan);
+
+SignalFfiError *signal_storage_sync_plan_local_upsert_data(SignalBytestringArray *out, const SignalStorageSyncPlan *plan);
+
+SignalFfiError *signal_storage_sync_plan_local_deletes(SignalBytestringArray *out, const SignalStorageSyncPlan *plan);
+
+SignalFfiError *signal_storage_sync_plan_unknown(SignalBytestringArray *out, const SignalStorageSyncPlan *plan);
+
+SignalFfiError *signal_storage_sync_plan_has_remote_changes(bool *out, const SignalStorageSyncPlan *plan);
+
+SignalFfiError *signal_storage_sync_plan_remote_manifest_ids(SignalBytestringArray *out, const SignalStorageSyncPlan *plan);
+
+SignalFfiError *signal_storage_sync_plan_remote_insert_ids(SignalBytestringArray *out, const SignalStorageSyncPlan *plan);
+
+SignalFfiError *signal_storage_sync_plan_remote_insert_data(SignalBytestringArray *out, const SignalStorageSyncPlan *plan);
+
+SignalFfiError *signal_storage_sync_plan_remote_deletes(SignalBytestringArray *out, const SignalStorageSyncPlan *plan);
+
 SignalFfiError *signal_tokio_async_context_destroy(SignalTokioAsyncContext *p);
 
 SignalFfiError *signal_tokio_async_context_new(SignalTokioAsyncContext **out);
@@ -1593,6 +2049,16 @@ SignalFfiError *signal_backup_key_derive_media_encryption_key(uint8_t (*out)[Sig
 
 SignalFfiError *signal_backup_key_derive_thumbnail_transit_encryption_key(uint8_t (*out)[SignalMEDIA_ENCRYPTION_KEY_LEN], const uint8_t (*backup_key)[SignalBACKUP_KEY_LEN], const uint8_t (*media_id)[SignalMEDIA_ID_LEN]);
 
+SignalFfiError *signal_parse_limits_set(uint64_t max_message_size, uint64_t max_collection_count, uint64_t max_decompressed_size, uint64_t max_media_metadata_size, uint64_t max_retained_size);
+
+SignalFfiError *signal_bridge_manifest_get_sha256(const char **out);
+
+SignalFfiError *signal_bridge_metrics_set_enabled(bool enabled);
+
+SignalFfiError *signal_bridge_metrics_snapshot(const char **out);
+
+SignalFfiError *signal_bridge_metrics_reset(void);
+
 SignalFfiError *signal_svr2_client_new(SignalSgxClientState **out, SignalBorrowedBuffer mrenclave, SignalBorrowedBuffer attestation_msg, uint64_t current_timestamp);
 
 SignalFfiError *signal_incremental_mac_destroy(SignalIncrementalMac *p);
@@ -1613,8 +2079,16 @@ SignalFfiError *signal_validating_mac_update(int32_t *out, SignalValidatingMac *
 
 SignalFfiError *signal_validating_mac_finalize(int32_t *out, SignalValidatingMac *mac);
 
+SignalFfiError *signal_async_input_stream_completion_destroy(SignalAsyncInputStreamCompletion *p);
+
+SignalFfiError *signal_async_input_stream_completion_complete(const SignalAsyncInputStreamCompletion *completion, SignalBorrowedBuffer data);
+
+SignalFfiError *signal_async_input_stream_completion_fail(const SignalAsyncInputStreamCompletion *completion, const char *message);
+
 SignalFfiError *signal_message_backup_key_destroy(SignalMessageBackupKey *p);
 
+SignalFfiError *signal_backup_padding_policy_destroy(SignalBackupPaddingPolicy *p);
+
 SignalFfiError *signal_message_backup_validation_outcome_destroy(SignalMessageBackupValidationOutcome *p);
 
 SignalFfiError *signal_message_backup_key_from_master_key(SignalMessageBackupKey **out, const uint8_t (*master_key)[32], const SignalServiceIdFixedWidthBinaryBytes *aci);
@@ -1631,7 +2105,23 @@ SignalFfiError *signal_message_backup_validation_outcome_get_error_message(const
 
 SignalFfiError *signal_message_backup_validation_outcome_get_unknown_fields(SignalStringArray *out, const SignalMessageBackupValidationOutcome *outcome);
 
-SignalFfiError *signal_message_backup_validator_validate(SignalMessageBackupValidationOutcome **out, const SignalMessageBackupKey *key, const SignalInputStream *first_stream, const SignalInputStream *second_stream, uint64_t len, uint8_t purpose);
+SignalFfiError *signal_message_backup_validation_outcome_get_findings(SignalOwnedBufferOfFfiMessageBackupValidationFinding *out, const SignalMessageBackupValidationOutcome *outcome);
+
+SignalFfiError *signal_message_backup_validator_validate(SignalMessageBackupValidationOutcome **out, const SignalMessageBackupKey *key, const SignalInputStream *first_stream, const SignalInputStream *second_stream, uint64_t len, uint8_t purpose, uint64_t deadline);
+
+SignalFfiError *signal_message_backup_validator_validate_async(SignalCPromiseMessageBackupValidationOutcome *promise, const SignalTokioAsyncContext *async_runtime, const SignalMessageBackupKey *key, const SignalAsyncInputStream *first_stream, const SignalAsyncInputStream *second_stream, uint64_t len, uint8_t purpose, uint64_t deadline);
+
+SignalFfiError *signal_message_backup_get_padded_size(uint64_t *out, uint64_t compressed_len);
+
+SignalFfiError *signal_message_backup_get_encrypted_size(uint64_t *out, uint64_t compressed_len);
+
+SignalFfiError *signal_backup_padding_policy_default(SignalBackupPaddingPolicy **out);
+
+SignalFfiError *signal_backup_padding_policy_new(SignalBackupPaddingPolicy **out, uint32_t growth_factor_percent, uint64_t min_size);
+
+SignalFfiError *signal_backup_padding_policy_get_padded_size(uint64_t *out, const SignalBackupPaddingPolicy *policy, uint64_t compressed_len);
+
+SignalFfiError *signal_backup_padding_policy_get_encrypted_size(uint64_t *out, const SignalBackupPaddingPolicy *policy, uint64_t compressed_len);
 
 SignalFfiError *signal_username_hash(uint8_t (*out)[32], const char *username);
 
@@ -1647,6 +2137,8 @@ SignalFfiError *signal_username_link_create(SignalOwnedBuffer *out, const char *
 
 SignalFfiError *signal_username_link_decrypt_username(const char **out, SignalBorrowedBuffer entropy, SignalBorrowedBuffer encrypted_username);
 
+SignalFfiError *signal_username_link_parse_url(uint8_t (*out)[48], const char *url);
+
 #if defined(SIGNAL_MEDIA_SUPPORTED)
 SignalFfiError *signal_sanitized_metadata_destroy(SignalSanitizedMetadata *p);
 #endif
@@ -1655,6 +2147,10 @@ SignalFfiError *signal_sanitized_metadata_destroy(SignalSanitizedMetadata *p);
 SignalFfiError *signal_sanitized_metadata_clone(SignalSanitizedMetadata **new_obj, const SignalSanitizedMetadata *obj);
 #endif
 
+#if defined(SIGNAL_MEDIA_SUPPORTED)
+SignalFfiError *signal_mp4_sanitizer_options_destroy(SignalMp4SanitizerOptions *p);
+#endif
+
 #if defined(SIGNAL_MEDIA_SUPPORTED)
 SignalFfiError *signal_signal_media_check_available(void);
 #endif
@@ -1663,6 +2159,34 @@ SignalFfiError *signal_signal_media_check_available(void);
 SignalFfiError *signal_mp4_sanitizer_sanitize(SignalSanitizedMetadata **out, const SignalInputStream *input, uint64_t len);
 #endif
 
+#if defined(SIGNAL_MEDIA_SUPPORTED)
+SignalFfiError *signal_mp4_sanitizer_sanitize_async(SignalCPromiseSanitizedMetadata *promise, const SignalTokioAsyncContext *async_runtime, const SignalAsyncInputStream *input, uint64_t len);
+#endif
+
+#if defined(SIGNAL_MEDIA_SUPPORTED)
+SignalFfiError *signal_mp4_sanitizer_sanitize_with_options(SignalSanitizedMetadata **out, const SignalInputStream *input, uint64_t len, const SignalMp4SanitizerOptions *options);
+#endif
+
+#if defined(SIGNAL_MEDIA_SUPPORTED)
+SignalFfiError *signal_mp4_sanitizer_options_new(SignalMp4SanitizerOptions **out);
+#endif
+
+#if defined(SIGNAL_MEDIA_SUPPORTED)
+SignalFfiError *signal_mp4_sanitizer_options_set_metadata_stripped(const SignalMp4SanitizerOptions *options, bool stripped);
+#endif
+
+#if defined(SIGNAL_MEDIA_SUPPORTED)
+SignalFfiError *signal_mp4_sanitizer_options_set_metadata_box_preserved(const SignalMp4SanitizerOptions *options, const uint8_t (*box_type)[4], bool preserved);
+#endif
+
+#if defined(SIGNAL_MEDIA_SUPPORTED)
+SignalFfiError *signal_mp4_sanitizer_options_set_codec_policy(const SignalMp4SanitizerOptions *options, bool allow_hevc, bool allow_av1, bool allow_opus);
+#endif
+
+#if defined(SIGNAL_MEDIA_SUPPORTED)
+SignalFfiError *signal_mp4_sanitizer_set_max_pooled_buffer_bytes(uint64_t max_bytes);
+#endif
+
 #if defined(SIGNAL_MEDIA_SUPPORTED)
 SignalFfiError *signal_webp_sanitizer_sanitize(const SignalSyncInputStream *input);
 #endif
@@ -102,7 +102,10 @@ typedef struct {
   SignalRawCancellationId cancellation_id;
 } SignalCPromiseRawPointer;
 
-typedef SignalBytestringArray SignalStringArray;
+/**
+ * An array of Rust-owned C strings, to be freed with `signal_free_list_of_strings`.
+ */
+typedef SignalOwnedBufferOfCStringPtr SignalStringArray;
 
 typedef SignalChatAuthChatService SignalAuthChat;
 
@@ -106,11 +106,17 @@ final class ChatServiceTests: TestCaseBase {
         } catch SignalError.deviceDeregistered(_) {}
         do {
             try failWithError("ServiceInactive")
-        } catch SignalError.chatServiceInactive(_) {}
+        } catch SignalError.chatServiceInactive(_, disconnectInfo: nil) {}
+        do {
+            try failWithError("RequestUnauthorized")
+        } catch SignalError.requestUnauthorized(_) {}
+        do {
+            try failWithError("NotFound")
+        } catch SignalError.notFound(_) {}
 
         do {
             try failWithError("WebSocket")
-        } catch SignalError.webSocketError(_) {}
+        } catch SignalError.webSocketError(_, disconnectInfo: nil) {}
         do {
             try failWithError("UnexpectedFrameReceived")
         } catch SignalError.networkProtocolError(_) {}
@@ -135,9 +141,33 @@ final class ChatServiceTests: TestCaseBase {
         } catch SignalError.internalError(_) {}
         do {
             try failWithError("RetryAfter42Seconds")
-        } catch SignalError.rateLimitedError(retryAfter: 42, let message) {
+        } catch SignalError.rateLimitedError(retryAfter: 42, scope: .unspecified, reason: nil, let message) {
             XCTAssertEqual(message, "Rate limited; try again after 42s")
         }
+        do {
+            try failWithError("RetryAfter42SecondsPerAccount")
+        } catch SignalError.rateLimitedError(retryAfter: 42, scope: .perAccount, let reason, _) {
+            XCTAssertEqual(reason, "fake reason")
+        }
+        do {
+            try failWithError("ServiceUnavailable")
+        } catch SignalError.connectionFailed(_, disconnectInfo: nil) {}
+        do {
+            try failWithError("ServiceUnavailableWithDisconnectInfo")
+        } catch SignalError.connectionFailed(_, let disconnectInfo) {
+            XCTAssertEqual(disconnectInfo, DisconnectInfo(closeCode: 4000, reason: "go elsewhere", alerts: ["critical-idle-primary-device"]))
+        }
+        do {
+            try failWithError("ChannelClosedWithDisconnectInfo")
+        } catch SignalError.webSocketError(_, let disconnectInfo) {
+            XCTAssertEqual(disconnectInfo, DisconnectInfo(closeCode: 4000, reason: "go elsewhere", alerts: ["critical-idle-primary-device"]))
+        }
+        do {
+            try failWithError("HttpErrorWithAlerts")
+        } catch SignalError.webSocketError(let message, let disconnectInfo) {
+            XCTAssert(message.contains("503"), message)
+            XCTAssertEqual(disconnectInfo, DisconnectInfo(closeCode: nil, reason: nil, alerts: ["critical-idle-primary-device"]))
+        }
     }
 
     func testConstructRequest() throws {
@@ -164,6 +194,28 @@ final class ChatServiceTests: TestCaseBase {
         }
     }
 
+    func testReceiptBatching() throws {
+        let net = Net(env: .staging, userAgent: Self.userAgent)
+        let chat = net.createAuthenticatedChatService(username: "", password: "", receiveStories: false)
+        let sender = Aci(fromUUID: UUID())
+
+        chat.queueReceipt(sender: sender, kind: .read, messageTimestamp: 30)
+        chat.queueReceipt(sender: sender, kind: .read, messageTimestamp: 10)
+        chat.queueReceipt(sender: sender, kind: .delivery, messageTimestamp: 20)
+        chat.queueReceipt(sender: sender, kind: .read, messageTimestamp: 10)
+
+        let delivery = try XCTUnwrap(chat.nextReceiptBatch())
+        XCTAssertEqual(sender, delivery.sender)
+        XCTAssertEqual(.delivery, delivery.kind)
+        XCTAssertEqual([20], delivery.timestamps)
+
+        let read = try XCTUnwrap(chat.nextReceiptBatch())
+        XCTAssertEqual(.read, read.kind)
+        XCTAssertEqual([10, 30], read.timestamps)
+
+        XCTAssertNil(chat.nextReceiptBatch())
+    }
+
     func testListenerCallbacks() async throws {
         class Listener: ChatListener {
             let queueEmpty: XCTestExpectation
@@ -248,6 +300,53 @@ final class ChatServiceTests: TestCaseBase {
         await self.fulfillment(of: listener.expectations, timeout: 2, enforceOrder: true)
     }
 
+    func testListenerCallbacksOnQueue() async throws {
+        class Listener: ChatListener {
+            static let queueKey = DispatchSpecificKey<Void>()
+
+            let queueEmpty: XCTestExpectation
+            let connectionInterrupted: XCTestExpectation
+
+            init(queueEmpty: XCTestExpectation, connectionInterrupted: XCTestExpectation) {
+                self.queueEmpty = queueEmpty
+                self.connectionInterrupted = connectionInterrupted
+            }
+
+            func chatService(_: AuthenticatedChatService, didReceiveIncomingMessage _: Data, serverDeliveryTimestamp _: UInt64, sendAck _: () async throws -> Void) {
+                XCTFail("unexpected message")
+            }
+
+            func chatServiceDidReceiveQueueEmpty(_: AuthenticatedChatService) {
+                XCTAssertNotNil(DispatchQueue.getSpecific(key: Self.queueKey))
+                self.queueEmpty.fulfill()
+            }
+
+            func connectionWasInterrupted(_: AuthenticatedChatService, error _: Error?) {
+                XCTAssertNotNil(DispatchQueue.getSpecific(key: Self.queueKey))
+                self.connectionInterrupted.fulfill()
+            }
+        }
+
+        let queue = DispatchQueue(label: "chat listener")
+        queue.setSpecific(key: Listener.queueKey, value: ())
+
+        let net = Net(env: .staging, userAgent: Self.userAgent)
+        let chat = net.createAuthenticatedChatService(username: "", password: "", receiveStories: false)
+        let listener = Listener(
+            queueEmpty: expectation(description: "queue empty"),
+            connectionInterrupted: expectation(description: "connection interrupted")
+        )
+        chat.setListener(listener, queue: queue)
+
+        // 1: {"PUT"}
+        // 2: {"/api/v1/queue/empty"}
+        // 4: 99
+        chat.injectServerRequest(base64: "CgNQVVQSEy9hcGkvdjEvcXVldWUvZW1wdHkgYw==")
+        chat.injectConnectionInterrupted()
+
+        await self.fulfillment(of: [listener.queueEmpty, listener.connectionInterrupted], timeout: 2, enforceOrder: true)
+    }
+
 #endif
 
     func testListenerCleanup() async throws {
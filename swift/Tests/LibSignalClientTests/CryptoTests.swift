@@ -132,6 +132,59 @@ class CryptoTests: TestCaseBase {
         XCTAssert(try! gcmDec2.verifyTag(tag))
     }
 
+    func testAesGcmWithNonceSequence() throws {
+        class RecordingStore: NonceStore {
+            var reservations: [UInt64] = []
+            var failNext = false
+
+            func reserve(upTo limit: UInt64) throws {
+                if self.failNext {
+                    self.failNext = false
+                    throw SignalError.ioError("disk full")
+                }
+                self.reservations.append(limit)
+            }
+        }
+
+        let key = self.generateAesKey()
+        let store = RecordingStore()
+        let nonces = try NonceSequence(key: key, prefix: [1, 2, 3, 4], persistedLimit: 0, limit: 2)
+        XCTAssertEqual(nonces.remaining, 2)
+
+        let gcmEnc = try Aes256GcmEncryption(nonces: nonces, store: store, associatedData: [])
+        XCTAssertEqual(gcmEnc.nonce, Data([1, 2, 3, 4, 0, 0, 0, 0, 0, 0, 0, 0]))
+        var ciphertext = Data("hello".utf8)
+        try gcmEnc.encrypt(&ciphertext)
+        let tag = try gcmEnc.computeTag()
+
+        let gcmDec = try Aes256GcmDecryption(key: key, nonce: gcmEnc.nonce, associatedData: [])
+        try gcmDec.decrypt(&ciphertext)
+        XCTAssert(try gcmDec.verifyTag(tag))
+        XCTAssertEqual(ciphertext, Data("hello".utf8))
+
+        // A failed reservation is reported without using up a nonce.
+        store.failNext = true
+        do {
+            _ = try Aes256GcmEncryption(nonces: nonces, store: store, associatedData: [])
+            XCTFail("should have thrown")
+        } catch SignalError.ioError(let message) {
+            XCTAssertEqual(message, "disk full")
+        }
+        XCTAssertEqual(nonces.remaining, 1)
+
+        _ = try Aes256GcmEncryption(nonces: nonces, store: store, associatedData: [])
+        XCTAssertEqual(nonces.remaining, 0)
+        XCTAssertEqual(store.reservations, [2])
+
+        do {
+            _ = try Aes256GcmEncryption(nonces: nonces, store: store, associatedData: [])
+            XCTFail("should have thrown")
+        } catch SignalError.invalidState(_) {}
+
+        let unlimited = try NonceSequence(key: key, prefix: [0, 0, 0, 0], persistedLimit: 0, limit: .max)
+        XCTAssertEqual(unlimited.remaining, NonceSequence.maxLimit)
+    }
+
     func testAesCtr() {
         let plainTextData = Data("Super🔥secret🔥test🔥data🏁🏁".utf8)
         let key = self.generateAesKey()
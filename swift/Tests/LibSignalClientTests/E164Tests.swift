@@ -0,0 +1,34 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+import XCTest
+
+import LibSignalClient
+
+class E164Tests: TestCaseBase {
+    func testNormalize() throws {
+        XCTAssertEqual("+18005551001", try E164.normalize(" +1 (800) 555-1001 ", regionCallingCode: 1))
+        XCTAssertEqual("+18005551001", try E164.normalize("(800) 555-1001", regionCallingCode: 1))
+        XCTAssertEqual("+49301234567", try E164.normalize("030 1234567", regionCallingCode: 49))
+        XCTAssertEqual("+49301234567", try E164.normalize("0049 30 1234567", regionCallingCode: 49))
+
+        XCTAssertNil(try E164.normalize("+1 800 555 1001 ext 2", regionCallingCode: 1))
+        XCTAssertNil(try E164.normalize("+1234", regionCallingCode: 1))
+        XCTAssertNil(try E164.normalize("", regionCallingCode: 1))
+    }
+
+    func testNormalizeInvalidCallingCode() {
+        XCTAssertThrowsError(try E164.normalize("800 555 1001", regionCallingCode: 0))
+        XCTAssertThrowsError(try E164.normalize("800 555 1001", regionCallingCode: 1000))
+    }
+
+    func testIsValid() {
+        XCTAssertTrue(E164.isValid("+18005551001"))
+        XCTAssertFalse(E164.isValid("18005551001"))
+        XCTAssertFalse(E164.isValid("+1 800 555 1001"))
+        XCTAssertFalse(E164.isValid("+1234"))
+    }
+}
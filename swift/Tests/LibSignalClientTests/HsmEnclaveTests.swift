@@ -64,4 +64,20 @@ class HsmEnclaveTests: TestCaseBase {
         let receivedCiphertext: [UInt8] = [0x01, 0x02, 0x03]
         XCTAssertThrowsError(try hsmEnclaveClient.establishedRecv(receivedCiphertext))
     }
+
+    func testPipeliningFailsPriorToEstablishment() {
+        let validKey = IdentityKeyPair.generate().publicKey
+        var hashes = HsmCodeHashList()
+        try! hashes.append([
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ])
+        let hsmEnclaveClient = try! HsmEnclaveClient(publicKey: validKey.keyBytes, codeHashes: hashes)
+        XCTAssertThrowsError(try hsmEnclaveClient.enablePipelining(maxInFlight: 4))
+        XCTAssertThrowsError(try hsmEnclaveClient.inFlight())
+        XCTAssertThrowsError(try hsmEnclaveClient.lastSentRequestId())
+        XCTAssertThrowsError(try hsmEnclaveClient.lastReceivedRequestId())
+        // The client is still usable afterwards.
+        XCTAssertFalse(hsmEnclaveClient.initialRequest().isEmpty)
+    }
 }
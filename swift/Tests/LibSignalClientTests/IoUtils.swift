@@ -55,6 +55,26 @@ public class ThrowsAfterInputStream: SignalInputStream {
     private var readBeforeThrow: UInt64
 }
 
+/// Pauses before every read, to test operations that have a time limit.
+public class SlowInputStream: SignalInputStream {
+    public init(inner: SignalInputStream, delay: TimeInterval) {
+        self.inner = inner
+        self.delay = delay
+    }
+
+    public func read(into buffer: UnsafeMutableRawBufferPointer) throws -> Int {
+        Thread.sleep(forTimeInterval: self.delay)
+        return try self.inner.read(into: buffer)
+    }
+
+    public func skip(by amount: UInt64) throws {
+        try self.inner.skip(by: amount)
+    }
+
+    private var inner: SignalInputStream
+    private var delay: TimeInterval
+}
+
 func readResource(forName name: String) -> Data {
     try! Data(
         contentsOf: URL(fileURLWithPath: #file)
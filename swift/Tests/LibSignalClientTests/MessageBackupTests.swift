@@ -49,6 +49,9 @@ class MessageBackupTests: TestCaseBase {
         XCTAssertThrowsError(try Self.validateBackup(bytes: [])) { error in
             if let error = error as? MessageBackupValidationError {
                 XCTAssertEqual(error.errorMessage, "not enough bytes for an HMAC")
+                XCTAssertEqual(error.findings.map(\.kind), [.malformedFrame])
+                XCTAssertEqual(error.findings.first?.severity, .fatal)
+                XCTAssertNil(error.findings.first?.frameIndex)
             } else {
                 XCTFail("\(error)")
             }
@@ -79,6 +82,84 @@ class MessageBackupTests: TestCaseBase {
         }
     }
 
+    func testSlowInputTimesOut() {
+        let bytes = readResource(forName: "new_account.binproto.encrypted")
+        let makeStream = { SlowInputStream(inner: SignalInputStreamAdapter(bytes), delay: 0.01) }
+        XCTAssertThrowsError(
+            try validateMessageBackup(key: MessageBackupKey.testKey(), purpose: .remoteBackup, length: UInt64(bytes.count), timeout: 0.001, makeStream: makeStream)
+        ) { error in
+            if case SignalError.deadlineExceeded(_) = error {} else { XCTFail("\(error)") }
+        }
+    }
+
+    func testValidInputAsync() async throws {
+        let bytes = readResource(forName: "new_account.binproto.encrypted")
+        let outcome = try await validateMessageBackup(
+            key: MessageBackupKey.testKey(),
+            purpose: .remoteBackup,
+            length: UInt64(bytes.count),
+            makeStream: { AsyncDataInputStream(bytes) }
+        )
+        XCTAssertEqual(outcome.fields, [])
+    }
+
+    func testEmptyInputAsync() async {
+        do {
+            _ = try await validateMessageBackup(
+                key: MessageBackupKey.testKey(),
+                purpose: .remoteBackup,
+                length: 0,
+                makeStream: { AsyncDataInputStream(Data()) }
+            )
+            XCTFail("should have failed")
+        } catch let error as MessageBackupValidationError {
+            XCTAssertEqual(error.errorMessage, "not enough bytes for an HMAC")
+        } catch {
+            XCTFail("\(error)")
+        }
+    }
+
+    func testStalledInputTimesOutAsync() async {
+        do {
+            _ = try await validateMessageBackup(
+                key: MessageBackupKey.testKey(),
+                purpose: .remoteBackup,
+                length: 100,
+                timeout: 0.001,
+                makeStream: { StalledAsyncInputStream() }
+            )
+            XCTFail("should have failed")
+        } catch SignalError.deadlineExceeded(_) {
+            // Okay
+        } catch {
+            XCTFail("\(error)")
+        }
+    }
+
+    func testPaddedAndEncryptedSizes() {
+        XCTAssertEqual(541, messageBackupPaddedSize(compressedLength: 0))
+        XCTAssertEqual(568, messageBackupPaddedSize(compressedLength: 542))
+        // IV + PKCS#7-padded ciphertext + HMAC
+        XCTAssertEqual(16 + 576 + 32, messageBackupEncryptedSize(compressedLength: 542))
+    }
+
+    func testCustomPaddingPolicies() {
+        let standard = BackupPaddingPolicy()
+        XCTAssertEqual(messageBackupPaddedSize(compressedLength: 542), standard.paddedSize(compressedLength: 542))
+        XCTAssertEqual(messageBackupEncryptedSize(compressedLength: 542), standard.encryptedSize(compressedLength: 542))
+
+        let minimumOnly = BackupPaddingPolicy(growthFactor: 1, minSize: 1000)
+        XCTAssertEqual(1000, minimumOnly.paddedSize(compressedLength: 0))
+        XCTAssertEqual(1001, minimumOnly.paddedSize(compressedLength: 1001))
+
+        let unpadded = BackupPaddingPolicy(growthFactor: 1, minSize: 0)
+        XCTAssertEqual(542, unpadded.paddedSize(compressedLength: 542))
+        // IV + PKCS#7-padded ciphertext + HMAC
+        XCTAssertEqual(16 + 544 + 32, unpadded.encryptedSize(compressedLength: 542))
+
+        XCTAssertEqual(1024, BackupPaddingPolicy(growthFactor: 2, minSize: 0).paddedSize(compressedLength: 542))
+    }
+
 #if !os(iOS) || targetEnvironment(simulator)
     func testComparableBackup() throws {
         let bytes = readResource(forName: "canonical-backup.binproto")
@@ -128,3 +209,37 @@ extension MessageBackupKey {
         return try! MessageBackupKey(backupKey: BackupKey(contents: backupKey), backupId: backupId)
     }
 }
+
+/// An async input stream over in-memory data, for testing.
+private actor AsyncDataInputStream: SignalAsyncInputStream {
+    var remaining: Data
+
+    init(_ data: Data) {
+        self.remaining = data
+    }
+
+    func read(maxLength: Int) async throws -> Data {
+        let result = self.remaining.prefix(maxLength)
+        self.remaining = self.remaining.dropFirst(result.count)
+        return Data(result)
+    }
+
+    func skip(by amount: UInt64) async throws {
+        if amount > UInt64(self.remaining.count) {
+            throw SignalInputStreamError.unexpectedEof
+        }
+        self.remaining = self.remaining.dropFirst(Int(amount))
+    }
+}
+
+/// An async input stream whose reads take far longer than any test, for testing timeouts.
+private final class StalledAsyncInputStream: SignalAsyncInputStream {
+    func read(maxLength: Int) async throws -> Data {
+        try await Task.sleep(nanoseconds: 60_000_000_000)
+        return Data()
+    }
+
+    func skip(by amount: UInt64) async throws {
+        try await Task.sleep(nanoseconds: 60_000_000_000)
+    }
+}
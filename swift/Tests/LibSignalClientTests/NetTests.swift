@@ -41,6 +41,29 @@ final class NetTests: XCTestCase {
         XCTAssertEqual(expected, Array(entryList))
     }
 
+    func testCdsiLookupReconcile() async throws {
+        let aci = Aci(fromUUID: UUID(uuidString: "9d0652a3-dcc3-4d11-975f-74d61598733f")!)
+        let pni = Pni(fromUUID: UUID(uuidString: "796abedb-ca4e-4f18-8803-1fde5b921f9f")!)
+        let oldPni = Pni(fromUUID: UUID(uuidString: "00000000-0000-0001-0000-000000000001")!)
+
+        let asyncContext = TokioAsyncContext()
+        let output: SignalFfiCdsiLookupResponse = try await asyncContext.invokeAsyncFunction { promise, asyncContext in
+            signal_testing_cdsi_lookup_response_convert(promise, asyncContext)
+        }
+        let response = CdsiLookupResponse(entries: LookupResponseEntryList(owned: output.entries), debugPermitsUsed: output.debug_permits_used)
+
+        let changes = response.reconcile(existing: [
+            CdsiLookupResponseEntry(e164: 18_005_551_012, aci, oldPni),
+            CdsiLookupResponseEntry(e164: 18_005_551_013, nil, oldPni),
+        ])
+        XCTAssertEqual(changes, [
+            .changedPni(e164: 18_005_551_012, pni: pni),
+            .removed(e164: 18_005_551_013),
+            .newAci(e164: 18_005_551_011, aci: aci),
+            .changedPni(e164: 18_005_551_011, pni: pni),
+        ])
+    }
+
     func testCdsiLookupErrorConversion() async throws {
         let failWithError = {
             try checkError(signal_testing_cdsi_lookup_error_convert($0))
@@ -68,9 +91,14 @@ final class NetTests: XCTestCase {
         }
         do {
             try failWithError("RetryAfter42Seconds")
-        } catch SignalError.rateLimitedError(retryAfter: 42, let message) {
+        } catch SignalError.rateLimitedError(retryAfter: 42, scope: .unspecified, reason: nil, let message) {
             XCTAssertEqual(message, "Rate limited; try again after 42s")
         }
+        do {
+            try failWithError("RetryAfter42SecondsPerNumber")
+        } catch SignalError.rateLimitedError(retryAfter: 42, scope: .perNumber, let reason, _) {
+            XCTAssertEqual(reason, "fake reason")
+        }
         do {
             try failWithError("InvalidToken")
         } catch SignalError.cdsiInvalidToken(let message) {
@@ -93,7 +121,7 @@ final class NetTests: XCTestCase {
         }
         do {
             try failWithError("WebSocketIdleTooLong")
-        } catch SignalError.webSocketError(let message) {
+        } catch SignalError.webSocketError(let message, _) {
             XCTAssertEqual(message, "WebSocket error: channel was idle for too long")
         }
         do {
@@ -128,6 +156,10 @@ final class NetTests: XCTestCase {
             _ = entry.pni
             _ = entry.e164
         }
+
+        let pool = CdsiConnectionPool(idleTimeout: 5)
+        let pooledLookup = try await net.cdsiLookup(auth: auth, request: request, pool: pool, prepareNext: false)
+        _ = try await pooledLookup.complete()
     }
 
     func testNetworkChangeEvent() throws {
@@ -135,6 +167,55 @@ final class NetTests: XCTestCase {
         let net = Net(env: .staging, userAgent: userAgent)
         try net.networkDidChange()
     }
+
+    func testRouteOrder() throws {
+        let net = Net(env: .staging, userAgent: userAgent)
+        XCTAssertEqual(net.routeOrder, [.direct, .proxyF, .proxyG])
+
+        net.routeOrder = [.proxyG, .direct]
+        net.setCensorshipCircumventionEnabled(true)
+        XCTAssertEqual(net.routeOrder, [.proxyG, .direct])
+
+        let key = Data(count: 32)
+        let restored = Net(env: .staging, userAgent: userAgent)
+        try restored.importRouteState(net.exportRouteState(key: key), key: key)
+        XCTAssertEqual(restored.routeOrder, [.proxyG, .direct])
+    }
+
+    func testProxy() throws {
+        let net = Net(env: .staging, userAgent: userAgent)
+        XCTAssertNil(net.proxy)
+        try net.setProxy(host: "proxy.example", port: 443)
+        XCTAssertEqual(net.proxy, "proxy.example:443")
+        net.clearProxy()
+        XCTAssertNil(net.proxy)
+    }
+
+    func testCertificatePins() throws {
+        let net = Net(env: .staging, userAgent: userAgent)
+        try net.setCertificatePins(for: .chat, [Data(repeating: 1, count: 32), Data(repeating: 2, count: 32)])
+        try net.setCertificatePins(for: .chat, [])
+        XCTAssertThrowsError(try net.setCertificatePins(for: .cdsi, [Data(count: 31)])) { error in
+            guard case SignalError.invalidArgument(_) = error else {
+                XCTFail("unexpected error: \(error)")
+                return
+            }
+        }
+    }
+
+    func testRouteStateRoundTrip() throws {
+        let key = Data(repeating: 0x42, count: 32)
+        let exported = try Net(env: .staging, userAgent: userAgent).exportRouteState(key: key)
+
+        let net = Net(env: .staging, userAgent: userAgent)
+        try net.importRouteState(exported, key: key)
+        XCTAssertThrowsError(try net.importRouteState(exported, key: Data(count: 32))) { error in
+            guard case SignalError.ioError(_) = error else {
+                XCTFail("unexpected error: \(error)")
+                return
+            }
+        }
+    }
 }
 
 final class Svr3Tests: TestCaseBase {
@@ -190,6 +271,42 @@ final class Svr3Tests: TestCaseBase {
         XCTAssertEqual(restoredSecret.triesRemaining, tries - 1)
     }
 
+    func testBackupWithDistribution() async throws {
+        let distribution = Svr3ShareDistribution(serverIds: [3, 1], threshold: 2)
+        let shareSet = try await state!.net.svr3.backup(
+            self.storedSecret,
+            password: "password",
+            maxTries: 10,
+            distribution: distribution,
+            auth: self.state!.auth
+        )
+        XCTAssertEqual(try Svr3Client.layout(ofShareSet: shareSet), distribution)
+
+        let restoredSecret = try await state!.net.svr3.restore(
+            password: "password",
+            shareSet: shareSet,
+            auth: self.state!.auth
+        )
+        XCTAssertEqual(restoredSecret.value, self.storedSecret)
+    }
+
+    func testBackupWithUnsupportedThreshold() async throws {
+        do {
+            _ = try await self.state!.net.svr3.backup(
+                self.storedSecret,
+                password: "password",
+                maxTries: 10,
+                distribution: Svr3ShareDistribution(serverIds: [1, 2, 3], threshold: 2),
+                auth: self.state!.auth
+            )
+            XCTFail("Should have thrown")
+        } catch SignalError.invalidArgument(_) {
+            // Success!
+        } catch {
+            XCTFail("Unexpected error: \(error)")
+        }
+    }
+
     func testRestoreAfterRemove() async throws {
         let tries = UInt32(10)
 
@@ -375,7 +492,7 @@ final class Svr3Tests: TestCaseBase {
                 auth: auth
             )
             XCTFail("Should have failed")
-        } catch SignalError.webSocketError(let message) {
+        } catch SignalError.webSocketError(let message, _) {
             XCTAssert(message.contains("401"))
         } catch {
             XCTFail("Unexpected error: \(error)")
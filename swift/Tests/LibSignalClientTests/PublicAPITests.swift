@@ -262,6 +262,29 @@ class PublicAPITests: TestCaseBase {
         XCTAssertThrowsError(try bobFingerprint2.scannable.compare(againstEncoding: aliceFingerprint.scannable.encoding))
         XCTAssertThrowsError(try bobFingerprint.scannable.compare(againstEncoding: aliceFingerprint2.scannable.encoding))
 
+        // testIdentityDigest
+
+        let aliceDigest = try! IdentityDigest(
+            iterations: 5200,
+            version: VERSION_2,
+            stableIdentifier: aliceStableId,
+            identityKey: aliceIdentityKey
+        )
+        let bobDigest = try! IdentityDigest(
+            iterations: 5200,
+            version: VERSION_2,
+            stableIdentifier: bobStableId,
+            identityKey: bobIdentityKey
+        )
+
+        XCTAssertEqual(aliceDigest.digest, Array(ALICE_SCANNABLE_FINGERPRINT_V2[6..<38]))
+        XCTAssertEqual(try! aliceDigest.displayString(), String(DISPLAYABLE_FINGERPRINT_V2.prefix(30)))
+        XCTAssertEqual(try! bobDigest.displayString(), String(DISPLAYABLE_FINGERPRINT_V2.suffix(30)))
+
+        let roundTripped = try! IdentityDigest(version: VERSION_2, digest: aliceDigest.digest)
+        XCTAssertEqual(try! roundTripped.displayString(), try! aliceDigest.displayString())
+        XCTAssertThrowsError(try IdentityDigest(version: VERSION_2, digest: Array(aliceDigest.digest.dropLast())))
+
         // testMismatchingFingerprints
 
         let mitmIdentityKey = PrivateKey.generate().publicKey
@@ -350,6 +373,30 @@ class PublicAPITests: TestCaseBase {
         XCTAssertEqual(b_ptext, [1, 2, 3])
     }
 
+    func testSenderKeyDistributionTracking() throws {
+        let sender = try ProtocolAddress(name: "+14159999111", deviceId: 4)
+        let distributionId = UUID(uuidString: "d1d1d1d1-7000-11eb-b32a-33b8a8a487a6")!
+        let bob = try ProtocolAddress(name: "+14151111111", deviceId: 1)
+        let carol = try ProtocolAddress(name: "+14152222222", deviceId: 1)
+
+        let store = InMemorySignalProtocolStore()
+        func needingDistribution() throws -> [ProtocolAddress] {
+            try senderKeyRecipientsNeedingDistribution(from: sender, distributionId: distributionId, among: [bob, carol], store: store, context: NullContext())
+        }
+
+        XCTAssertEqual(try needingDistribution(), [bob, carol])
+
+        _ = try SenderKeyDistributionMessage(from: sender, distributionId: distributionId, store: store, context: NullContext())
+        try markSenderKeyDistributed(from: sender, distributionId: distributionId, to: [bob], store: store, context: NullContext())
+        XCTAssertEqual(try needingDistribution(), [carol])
+
+        let record = try XCTUnwrap(store.loadSenderKeyDistribution(from: sender, distributionId: distributionId, context: NullContext()))
+        XCTAssertEqual(try SenderKeyDistributionRecord(bytes: record.serialize()).serialize(), record.serialize())
+
+        try invalidateSenderKeyDistribution(from: sender, distributionId: distributionId, for: [bob], store: store, context: NullContext())
+        XCTAssertEqual(try needingDistribution(), [bob, carol])
+    }
+
     func testGroupCipherWithContext() {
         class ContextUsingStore: InMemorySignalProtocolStore {
             var expectedContext: StoreContext & AnyObject
@@ -417,6 +464,11 @@ class PublicAPITests: TestCaseBase {
         XCTAssertEqual(serverCert.keyId, 1)
         XCTAssertEqual(serverCert.publicKey.serialize().count, 33)
         XCTAssertEqual(serverCert.signatureBytes.count, 64)
+
+        XCTAssertFalse(senderCert.needsRefresh(time: 1_605_722_925, refreshMargin: 0))
+        XCTAssertTrue(senderCert.needsRefresh(time: 1_605_722_926, refreshMargin: 0))
+        XCTAssertFalse(senderCert.needsRefresh(time: 1_605_721_925, refreshMargin: 1))
+        XCTAssertTrue(senderCert.needsRefresh(time: 1_605_721_926, refreshMargin: 1))
     }
 
     func testSenderCertificateGetSenderAci() {
@@ -480,6 +532,49 @@ class PublicAPITests: TestCaseBase {
         }
     }
 
+    func testDeviceTransferSession() throws {
+        let payload = [UInt8](repeating: 0x42, count: 3000)
+        let oldDevice = try DeviceTransferSession.forOldDevice(
+            localCertificate: DeviceTransferKey.generate().generateCertificate("old", 1),
+            payloadLength: UInt64(payload.count)
+        )
+        let newDevice = try DeviceTransferSession.forNewDevice(
+            localCertificate: DeviceTransferKey.generate().generateCertificate("new", 1)
+        )
+
+        func exchange(from: DeviceTransferSession, to: DeviceTransferSession) throws -> [UInt8] {
+            return try to.receive(from.takeOutgoing())
+        }
+
+        // Certificates are only revealed after both commitments have been exchanged.
+        _ = try exchange(from: oldDevice, to: newDevice)
+        _ = try exchange(from: newDevice, to: oldDevice)
+        _ = try exchange(from: oldDevice, to: newDevice)
+        XCTAssertEqual(.verifying, oldDevice.state)
+        XCTAssertEqual(.verifying, newDevice.state)
+        XCTAssertEqual(try oldDevice.verificationCode(), try newDevice.verificationCode())
+        XCTAssertEqual(UInt64(payload.count), try newDevice.payloadLength())
+
+        try oldDevice.confirmVerificationCode()
+        try newDevice.confirmVerificationCode()
+        _ = try exchange(from: oldDevice, to: newDevice)
+        _ = try exchange(from: newDevice, to: oldDevice)
+        XCTAssertEqual(.transferring, oldDevice.state)
+
+        try oldDevice.sendPayloadChunk(payload)
+        try oldDevice.finish()
+        XCTAssertEqual(payload, try exchange(from: oldDevice, to: newDevice))
+        XCTAssertEqual(.completed, newDevice.state)
+        XCTAssertEqual(UInt64(payload.count), newDevice.bytesTransferred)
+
+        XCTAssertThrowsError(try newDevice.receive([0xFF, 0, 0, 0, 0]))
+        XCTAssertEqual(.failed, newDevice.state)
+    }
+
+    func testBridgeManifest() throws {
+        try BridgeManifest.verify()
+    }
+
     func testSignAlternateIdentity() {
         let primary = IdentityKeyPair.generate()
         let secondary = IdentityKeyPair.generate()
@@ -85,6 +85,20 @@ class ServiceIdTests: TestCaseBase {
 
     // swiftlint:enable force_cast
 
+    func testBatchConversions() throws {
+        let serviceIds: [ServiceId] = [Aci(fromUUID: Self.TEST_UUID), Pni(fromUUID: Self.TEST_UUID)]
+        let binaries = ServiceId.serviceIdBinaries(of: serviceIds)
+        XCTAssertEqual(binaries, serviceIds.map(\.serviceIdBinary))
+        XCTAssertEqual(ServiceId.serviceIdStrings(of: serviceIds), serviceIds.map(\.serviceIdString))
+        XCTAssertEqual(try ServiceId.parseFrom(serviceIdBinaries: binaries), serviceIds)
+        XCTAssertEqual(ServiceId.serviceIdStrings(of: []), [])
+
+        do {
+            _ = try ServiceId.parseFrom(serviceIdBinaries: [binaries[0], [0xFF]])
+            XCTFail("Should have failed")
+        } catch SignalError.invalidArgument {}
+    }
+
     func testOrdering() {
         let nilUuid = UUID(uuid: (0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0))
         var ids = [
@@ -48,4 +48,26 @@ class SessionRecordTests: TestCaseBase {
             XCTAssertThrowsError(try record.keyPair())
         }
     }
+
+    func testRecordMigration() throws {
+        // Records written before version headers were introduced are bare protobuf messages.
+        // 1: 42
+        let legacy: [UInt8] = [0x08, 0x2A]
+        let current: [UInt8] = [0x00, 0x01, 0x08, 0x2A]
+        XCTAssertEqual(try PreKeyRecord.migrateToCurrent(legacy), current)
+        XCTAssertEqual(try PreKeyRecord.migrateToCurrent(current), current)
+        XCTAssertEqual(try PreKeyRecord(bytes: legacy).id, 42)
+        XCTAssertEqual(try PreKeyRecord(bytes: current).id, 42)
+        // Writing the header is opt-in; serializing still produces the legacy format.
+        XCTAssertEqual(try PreKeyRecord(bytes: current).serialize(), legacy)
+
+        let tooNew: [UInt8] = [0x00, 0x02, 0x08, 0x2A]
+        XCTAssertThrowsError(try PreKeyRecord.migrateToCurrent(tooNew)) { error in
+            guard case SignalError.unrecognizedMessageVersion(_) = error else {
+                XCTFail("unexpected error: \(error)")
+                return
+            }
+        }
+        XCTAssertThrowsError(try PreKeyRecord(bytes: tooNew))
+    }
 }
@@ -78,9 +78,78 @@ class SessionTests: TestCaseBase {
             )
 
             XCTAssertEqual(ptext2_a, ptext2_b)
+
+            // Bob sends another message on the same chain
+            let ctext3_b = try! signalEncrypt(
+                message: ptext2_b,
+                for: alice_address,
+                sessionStore: bob_store,
+                identityStore: bob_store,
+                context: NullContext()
+            )
+            let ctext3_a = try! SignalMessage(bytes: ctext3_b.serialize())
+
+            XCTAssertEqual(ctext3_a.ratchetContinuity(after: ctext2_a), .sameChain)
+            XCTAssertEqual(ctext2_a.ratchetContinuity(after: ctext3_a), .reordered)
+            XCTAssertFalse(ctext2_a.ratchetContinuity(after: ctext3_a).isSuspicious)
         }
     }
 
+    func testReplayCache() throws {
+        let alice_address = try ProtocolAddress(name: "+14151111111", deviceId: 1)
+        let bob_address = try ProtocolAddress(name: "+14151111112", deviceId: 1)
+
+        let alice_store = InMemorySignalProtocolStore()
+        let bob_store = InMemorySignalProtocolStore()
+
+        initializeSessionsV4(alice_store: alice_store, bob_store: bob_store, bob_address: bob_address)
+
+        let ctext = try signalEncrypt(
+            message: [8, 6, 7, 5, 3, 0, 9],
+            for: bob_address,
+            sessionStore: alice_store,
+            identityStore: alice_store,
+            context: NullContext()
+        )
+        let message = try PreKeySignalMessage(bytes: ctext.serialize())
+
+        let replayCache = RecordingReplayCache()
+        replayCache.rejectEverything = true
+        XCTAssertThrowsError(try signalDecryptPreKey(
+            message: message,
+            from: alice_address,
+            sessionStore: bob_store,
+            identityStore: bob_store,
+            preKeyStore: bob_store,
+            signedPreKeyStore: bob_store,
+            kyberPreKeyStore: bob_store,
+            replayCache: replayCache,
+            context: NullContext()
+        )) { error in
+            guard case SignalError.duplicatedMessage = error else {
+                XCTFail("unexpected error: \(error)")
+                return
+            }
+        }
+        // Nothing was saved for the rejected message.
+        XCTAssertNil(try bob_store.loadSession(for: alice_address, context: NullContext()))
+
+        replayCache.rejectEverything = false
+        let ptext = try signalDecryptPreKey(
+            message: message,
+            from: alice_address,
+            sessionStore: bob_store,
+            identityStore: bob_store,
+            preKeyStore: bob_store,
+            signedPreKeyStore: bob_store,
+            kyberPreKeyStore: bob_store,
+            replayCache: replayCache,
+            context: NullContext()
+        )
+        XCTAssertEqual(ptext, [8, 6, 7, 5, 3, 0, 9])
+        XCTAssertEqual(replayCache.seen.count, 1)
+    }
+
     func testSessionCipherWithBadStore() {
         run(initializeSessionsV3)
         run(initializeSessionsV4)
@@ -252,6 +321,47 @@ class SessionTests: TestCaseBase {
         XCTAssertEqual(plaintext.sender, sender_addr)
         XCTAssertEqual(plaintext.sender.senderAci, alice_address.serviceId)
 
+        let secondMessage = try sealedSenderEncrypt(
+            message: message,
+            for: bob_address,
+            from: sender_cert,
+            sessionStore: alice_store,
+            identityStore: alice_store,
+            context: NullContext()
+        )
+        let replayCache = RecordingReplayCache()
+        _ = try sealedSenderDecrypt(
+            message: secondMessage,
+            from: recipient_addr,
+            trustRoot: trust_root.publicKey,
+            timestamp: 31335,
+            sessionStore: bob_store,
+            identityStore: bob_store,
+            preKeyStore: bob_store,
+            signedPreKeyStore: bob_store,
+            replayCache: replayCache,
+            context: NullContext()
+        )
+        XCTAssertEqual(replayCache.seen.count, 1)
+        replayCache.rejectEverything = true
+        XCTAssertThrowsError(try sealedSenderDecrypt(
+            message: secondMessage,
+            from: recipient_addr,
+            trustRoot: trust_root.publicKey,
+            timestamp: 31335,
+            sessionStore: bob_store,
+            identityStore: bob_store,
+            preKeyStore: bob_store,
+            signedPreKeyStore: bob_store,
+            replayCache: replayCache,
+            context: NullContext()
+        )) { error in
+            guard case SignalError.duplicatedMessage = error else {
+                XCTFail("unexpected error: \(error)")
+                return
+            }
+        }
+
         let innerMessage = try signalEncrypt(
             message: [],
             for: bob_address,
@@ -283,6 +393,93 @@ class SessionTests: TestCaseBase {
         }
     }
 
+    func testSealedSenderCapturingIdentityChange() throws {
+        let alice_address = try! ProtocolAddress(name: "9d0652a3-dcc3-4d11-975f-74d61598733f", deviceId: 1)
+        let bob_address = try! ProtocolAddress(name: "6838237D-02F6-4098-B110-698253D15961", deviceId: 1)
+
+        let alice_store = InMemorySignalProtocolStore()
+        let bob_store = InMemorySignalProtocolStore()
+
+        initializeSessionsV4(alice_store: alice_store, bob_store: bob_store, bob_address: bob_address)
+
+        // Bob remembers a different identity for Alice.
+        let previous_identity = IdentityKeyPair.generate().identityKey
+        _ = try bob_store.saveIdentity(previous_identity, for: alice_address, context: NullContext())
+
+        let trust_root = IdentityKeyPair.generate()
+        let server_keys = IdentityKeyPair.generate()
+        let server_cert = try! ServerCertificate(keyId: 1, publicKey: server_keys.publicKey, trustRoot: trust_root.privateKey)
+        let sender_addr = try! SealedSenderAddress(
+            e164: "+14151111111",
+            uuidString: alice_address.name,
+            deviceId: 1
+        )
+        let sender_cert = try! SenderCertificate(
+            sender: sender_addr,
+            publicKey: alice_store.identityKeyPair(context: NullContext()).publicKey,
+            expiration: 31337,
+            signerCertificate: server_cert,
+            signerKey: server_keys.privateKey
+        )
+
+        let message = Array("2020 vision".utf8)
+        let ciphertext = try sealedSenderEncrypt(
+            message: message,
+            for: bob_address,
+            from: sender_cert,
+            sessionStore: alice_store,
+            identityStore: alice_store,
+            context: NullContext()
+        )
+
+        let recipient_addr = try! SealedSenderAddress(e164: nil, uuidString: bob_address.name, deviceId: 1)
+
+        // The regular entry point refuses the new identity...
+        XCTAssertThrowsError(try sealedSenderDecrypt(
+            message: ciphertext,
+            from: recipient_addr,
+            trustRoot: trust_root.publicKey,
+            timestamp: 31335,
+            sessionStore: bob_store,
+            identityStore: bob_store,
+            preKeyStore: bob_store,
+            signedPreKeyStore: bob_store,
+            context: NullContext()
+        )) { error in
+            guard case SignalError.untrustedIdentity = error else {
+                XCTFail("unexpected error: \(error)")
+                return
+            }
+        }
+
+        // ...but this one decrypts the message and reports the change.
+        let plaintext = try sealedSenderDecryptCapturingIdentityChange(
+            message: ciphertext,
+            from: recipient_addr,
+            trustRoot: trust_root.publicKey,
+            timestamp: 31335,
+            sessionStore: bob_store,
+            identityStore: bob_store,
+            preKeyStore: bob_store,
+            signedPreKeyStore: bob_store,
+            kyberPreKeyStore: bob_store,
+            context: NullContext()
+        )
+
+        XCTAssertEqual(plaintext.message, message)
+        XCTAssertEqual(plaintext.sender, sender_addr)
+        XCTAssertEqual(
+            plaintext.identityChange,
+            SealedSenderIdentityChange(
+                previous: previous_identity,
+                current: try alice_store.identityKeyPair(context: NullContext()).identityKey
+            )
+        )
+
+        // The identity store is left for the app to update.
+        XCTAssertEqual(try bob_store.identity(for: alice_address, context: NullContext()), previous_identity)
+    }
+
     func testArchiveSession() throws {
         let bob_address = try! ProtocolAddress(name: "+14151111112", deviceId: 1)
 
@@ -387,6 +584,12 @@ class SessionTests: TestCaseBase {
 
         XCTAssertEqual(b_usmc.groupId, a_usmc.groupId)
 
+        let rebuilt_usmc = try! UnidentifiedSenderMessageContent(bytes: b_usmc.withContentHint(.resendable, groupId: []).serialize())
+        XCTAssertEqual(rebuilt_usmc.contentHint, .resendable)
+        XCTAssertNil(rebuilt_usmc.groupId)
+        XCTAssertEqual(rebuilt_usmc.contents, b_usmc.contents)
+        XCTAssertEqual(rebuilt_usmc.messageType, b_usmc.messageType)
+
         let b_ptext = try! groupDecrypt(
             b_usmc.contents,
             from: alice_address,
@@ -624,6 +827,12 @@ class SessionTests: TestCaseBase {
         let bob_session_with_alice = try XCTUnwrap(bob_store.loadSession(for: alice_address, context: NullContext()))
         XCTAssert(try bob_session_with_alice.currentRatchetKeyMatches(XCTUnwrap(bob_error_message.ratchetKey)))
     }
+
+    func testInMemoryStoreConforms() throws {
+        try checkStoreConformance(context: NullContext()) { identity, registrationId in
+            InMemorySignalProtocolStore(identity: identity, registrationId: registrationId)
+        }
+    }
 }
 
 private func initializeSessionsV3(
@@ -764,3 +973,19 @@ private func initializeSessionsV4(
         context: NullContext()
     )
 }
+
+private class RecordingReplayCache: ReplayCache {
+    var seen: Set<String> = []
+    var rejectEverything = false
+
+    func contains(from address: ProtocolAddress, senderRatchetKey: PublicKey, counter: UInt32, context: StoreContext) throws -> Bool {
+        if self.rejectEverything {
+            return true
+        }
+        return self.seen.contains("\(address.debugDescription)/\(senderRatchetKey.serialize())/\(counter)")
+    }
+
+    func insert(from address: ProtocolAddress, senderRatchetKey: PublicKey, counter: UInt32, context: StoreContext) throws {
+        self.seen.insert("\(address.debugDescription)/\(senderRatchetKey.serialize())/\(counter)")
+    }
+}
@@ -0,0 +1,68 @@
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+import LibSignalClient
+import XCTest
+
+class StorageSyncTests: TestCaseBase {
+    // ManifestRecord.Identifier { raw: aaaa, type: CONTACT }
+    private let idA = [UInt8](fromHexString: "0a02aaaa1001")!
+    // ManifestRecord.Identifier { raw: bbbb, type: CONTACT }
+    private let idB = [UInt8](fromHexString: "0a02bbbb1001")!
+    // ManifestRecord { version: 1, identifiers: [idA] }
+    private let manifestWithA = [UInt8](fromHexString: "080112060a02aaaa1001")!
+
+    func testNewRecordsSyncBothWays() throws {
+        let sync = try StorageSync(previousManifest: [UInt8](), remoteManifest: self.manifestWithA)
+        try sync.addLocalRecord(id: self.idB, key: Array("b".utf8), data: Array("local record".utf8))
+        XCTAssertEqual([self.idA], sync.idsToFetch())
+        try sync.addFetchedRecord(id: self.idA, key: Array("a".utf8), data: Array("remote record".utf8))
+
+        let plan = try sync.plan()
+        XCTAssertEqual([self.idA], plan.localUpsertIds)
+        XCTAssertEqual([Array("remote record".utf8)], plan.localUpsertData)
+        XCTAssertEqual([], plan.localDeletes)
+        XCTAssertEqual([], plan.unknown)
+
+        XCTAssert(plan.hasRemoteChanges)
+        XCTAssertEqual([self.idA, self.idB], plan.remoteManifestIds)
+        XCTAssertEqual([self.idB], plan.remoteInsertIds)
+        XCTAssertEqual([Array("local record".utf8)], plan.remoteInsertData)
+        XCTAssertEqual([], plan.remoteDeletes)
+    }
+
+    func testLocalDeletionIsDeletedRemotely() throws {
+        let sync = try StorageSync(previousManifest: self.manifestWithA, remoteManifest: self.manifestWithA)
+        try sync.addLocalDeletion(id: self.idA)
+        XCTAssertEqual([], sync.idsToFetch())
+
+        let plan = try sync.plan()
+        XCTAssertEqual([], plan.localUpsertIds)
+        XCTAssert(plan.hasRemoteChanges)
+        XCTAssertEqual([], plan.remoteManifestIds)
+        XCTAssertEqual([self.idA], plan.remoteDeletes)
+    }
+
+    func testUnknownRecordsAreKept() throws {
+        let sync = try StorageSync(previousManifest: [UInt8](), remoteManifest: self.manifestWithA)
+        try sync.addFetchedUnknown(id: self.idA)
+
+        let plan = try sync.plan()
+        XCTAssertEqual([self.idA], plan.unknown)
+        XCTAssertFalse(plan.hasRemoteChanges)
+    }
+
+    func testInvalidManifest() {
+        do {
+            _ = try StorageSync(previousManifest: [UInt8](), remoteManifest: [0xFF] as [UInt8])
+            XCTFail("should have failed")
+        } catch SignalError.invalidArgument(_) {
+            // good
+        } catch {
+            XCTFail("unexpected error: \(error)")
+        }
+    }
+}
@@ -196,6 +196,33 @@ class UsernameTests: TestCaseBase {
         XCTAssertEqual(original, newRecreated)
     }
 
+    func testUsernameLinkUrl() throws {
+        let payload = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8AESIzRFVmd4iZqrvM3e7_"
+        for url in ["https://signal.me/#eu/\(payload)", "sgnl://signal.me/#eu/\(payload)"] {
+            let linkUrl = try UsernameLinkUrl(fromUrl: url)
+            XCTAssertEqual([UInt8](0..<32), linkUrl.entropy)
+            XCTAssertEqual(UUID(uuidString: "00112233-4455-6677-8899-aabbccddeeff"), linkUrl.serverId)
+        }
+    }
+
+    func testUsernameLinkUrlFailures() throws {
+        do {
+            _ = try UsernameLinkUrl(fromUrl: "https://signal.org/#eu/AAECAwQF")
+            XCTFail("should have failed")
+        } catch SignalError.usernameLinkInvalid {
+        } catch {
+            XCTFail("unexpected error: \(error)")
+        }
+
+        do {
+            _ = try UsernameLinkUrl(fromUrl: "https://signal.me/#eu/AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8")
+            XCTFail("should have failed")
+        } catch SignalError.usernameLinkInvalidEntropyDataLength {
+        } catch {
+            XCTFail("unexpected error: \(error)")
+        }
+    }
+
     func testUsernameLinkInvalidEntropySize() throws {
         do {
             let randomness = [UInt8](repeating: 0, count: 16)
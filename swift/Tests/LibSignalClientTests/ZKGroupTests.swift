@@ -383,6 +383,14 @@ class ZKGroupTests: TestCaseBase {
         XCTAssertThrowsError(try presentation.verify(roomId: roomId, now: Date(timeIntervalSince1970: TimeInterval(startOfDay + 30 * 60 * 60)), serverParams: serverSecretParams, callLinkParams: clientPublicParams))
     }
 
+    func testCallLinkRoomIdKnownAnswer() {
+        let rootKey: [UInt8] = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F]
+        XCTAssertEqual(
+            CallLinkSecretParams.deriveRoomId(rootKey),
+            [UInt8](fromHexString: "cd415ffc58771108cdbf029b8d86ee857be167bf28dda87e3e592e3f10eb1c9b")!
+        )
+    }
+
     func testCallLinkAuthCredential() throws {
         let userId = Aci(fromUUID: TEST_ARRAY_16)
 
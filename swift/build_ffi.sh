@@ -46,6 +46,7 @@ Options:
 
   --generate-ffi     -- regenerate ffi headers
   --verify-ffi       -- verify that ffi headers are up to date
+  --generate-cpp     -- also write a C++ wrapper for signal_ffi.h to target/include
   --build-std        -- use Cargo's -Zbuild-std to compile for a tier 3 target
   --debug-level-logs -- include log levels below INFO (default for debug builds)
 
@@ -64,11 +65,32 @@ check_cbindgen() {
   fi
 }
 
+# Prints the Swift source recording which bridge manifest signal_ffi.h was generated from.
+bridge_manifest_swift() {
+  local sha256
+  sha256=$(shasum -a 256 rust/bridge/shared/bridge_manifest.json | cut -d ' ' -f 1)
+  cat <<EOF
+//
+// Copyright 2024 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+// WARNING: this file was automatically generated
+
+extension BridgeManifest {
+    /// The hash of the bridge manifest (rust/bridge/shared/bridge_manifest.json) that signal_ffi.h
+    /// was generated from.
+    internal static let expectedSha256 = "${sha256}"
+}
+EOF
+}
+
 
 RELEASE_BUILD=
 VERBOSE=
 SHOULD_CBINDGEN=
 CBINDGEN_VERIFY=
+SHOULD_GENERATE_CPP=
 BUILD_STD=
 DEBUG_LEVEL_LOGS=
 
@@ -90,6 +112,9 @@ while [ "${1:-}" != "" ]; do
       SHOULD_CBINDGEN=1
       CBINDGEN_VERIFY=1
       ;;
+    --generate-cpp )
+      SHOULD_GENERATE_CPP=1
+      ;;
     --build-std)
       BUILD_STD=1
       ;;
@@ -135,6 +160,7 @@ echo_then_run cargo build -p libsignal-ffi ${RELEASE_BUILD:+--release} ${VERBOSE
 
 FFI_HEADER_PATH=swift/Sources/SignalFfi/signal_ffi.h
 FFI_TESTING_HEADER_PATH=swift/Sources/SignalFfi/signal_ffi_testing.h
+BRIDGE_MANIFEST_SWIFT_PATH=swift/Sources/LibSignalClient/BridgeManifestSha256.swift
 
 if [[ -n "${SHOULD_CBINDGEN}" ]]; then
   check_cbindgen
@@ -152,6 +178,12 @@ if [[ -n "${SHOULD_CBINDGEN}" ]]; then
       echo 'error: signal_ffi_testing.h not up to date; run' "$0" '--generate-ffi' >&2
       exit 1
     fi
+    echo diff -u "${BRIDGE_MANIFEST_SWIFT_PATH}" "<(bridge_manifest_swift)"
+    if ! diff -u "${BRIDGE_MANIFEST_SWIFT_PATH}" <(bridge_manifest_swift); then
+      echo
+      echo 'error: BridgeManifestSha256.swift not up to date; run' "$0" '--generate-ffi' >&2
+      exit 1
+    fi
   else
     echo cbindgen ${RELEASE_BUILD:+--profile release} -o "${FFI_HEADER_PATH}" rust/bridge/ffi
     # Use sed to ignore irrelevant cbindgen warnings.
@@ -164,5 +196,14 @@ if [[ -n "${SHOULD_CBINDGEN}" ]]; then
     # shellcheck disable=SC2016
     cbindgen ${RELEASE_BUILD:+--profile release} -o "${FFI_TESTING_HEADER_PATH}" rust/bridge/shared/testing --config rust/bridge/ffi/cbindgen-testing.toml 2>&1 |
       sed '/WARN: Missing `\[defines\]` entry for `feature = "ffi"` in cbindgen config\./ d' >&2
+
+    echo "bridge_manifest_swift > ${BRIDGE_MANIFEST_SWIFT_PATH}"
+    bridge_manifest_swift > "${BRIDGE_MANIFEST_SWIFT_PATH}"
   fi
 fi
+
+if [[ -n "${SHOULD_GENERATE_CPP}" ]]; then
+  CPP_HEADER_DIR="${CARGO_TARGET_DIR:-target}/include"
+  mkdir -p "${CPP_HEADER_DIR}"
+  echo_then_run rust/bridge/ffi/bin/gen_cpp_wrapper.py "${FFI_HEADER_PATH}" -o "${CPP_HEADER_DIR}/signal_ffi.hpp"
+fi